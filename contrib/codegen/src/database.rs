@@ -73,7 +73,7 @@ pub fn database_attr(attr: TokenStream, input: TokenStream) -> Result<TokenStrea
 
     let generated_types = quote_spanned! { span =>
         /// The request guard type.
-        #vis struct #guard_type(#databases::Connection<Self, #conn_type>);
+        #vis struct #guard_type(#databases::SyncConnection<Self, #conn_type>);
     };
 
     Ok(quote! {
@@ -83,14 +83,25 @@ pub fn database_attr(attr: TokenStream, input: TokenStream) -> Result<TokenStrea
             /// Returns a fairing that initializes the associated database
             /// connection pool.
             pub fn fairing() -> impl ::rocket::fairing::Fairing {
-                <#databases::ConnectionPool<Self, #conn_type>>::fairing(#fairing_name, #name)
+                <#databases::SyncConnectionPool<Self, #conn_type>>::fairing(#fairing_name, #name)
             }
 
             /// Retrieves a connection of type `Self` from the `rocket`
             /// instance. Returns `Some` as long as `Self::fairing()` has been
             /// attached.
             pub async fn get_one(cargo: &::rocket::Cargo) -> Option<Self> {
-                <#databases::ConnectionPool<Self, #conn_type>>::get_one(cargo).await.map(Self)
+                <#databases::SyncConnectionPool<Self, #conn_type>>::get_one(cargo).await.map(Self)
+            }
+
+            /// Returns a fairing that runs `migrations` on a connection from
+            /// the associated pool when it is attached, aborting launch if it
+            /// fails. Must be attached after `Self::fairing()`.
+            pub fn migrate<F, E>(migrations: F) -> impl ::rocket::fairing::Fairing
+            where
+                F: FnOnce(&mut #conn_type) -> Result<(), E> + Send + 'static,
+                E: ::std::fmt::Display + Send + 'static,
+            {
+                <#databases::SyncConnectionPool<Self, #conn_type>>::migrate(#name, migrations)
             }
 
             /// Runs the provided closure on a thread from a threadpool. The
@@ -111,7 +122,71 @@ pub fn database_attr(attr: TokenStream, input: TokenStream) -> Result<TokenStrea
             type Error = ();
 
             async fn from_request(request: &'a #request::Request<'r>) -> #request::Outcome<Self, ()> {
-                <#databases::Connection<Self, #conn_type>>::from_request(request).await.map(Self)
+                <#databases::SyncConnection<Self, #conn_type>>::from_request(request).await.map(Self)
+            }
+        }
+    }.into())
+}
+
+const DERIVE_EXAMPLE: &str = "example: `#[database(\"name\")] struct MyDatabase(sqlx::SqlitePool);`";
+const DERIVE_ONLY_ON_STRUCTS: &str = "`Database` can only be derived for structs";
+const DERIVE_ONLY_UNNAMED_FIELDS: &str = "`Database` can only be derived for structs \
+    with exactly one unnamed field";
+const DERIVE_NO_GENERIC_STRUCTS: &str = "`Database` cannot be derived for structs \
+    with generics";
+const DERIVE_MISSING_NAME: &str = "`Database` requires a `#[database(\"name\")]` attribute";
+
+pub fn derive_database(input: TokenStream) -> Result<TokenStream> {
+    let input = crate::syn::parse::<DeriveInput>(input)?;
+    if !input.generics.params.is_empty() {
+        return Err(input.generics.span().error(DERIVE_NO_GENERIC_STRUCTS));
+    }
+
+    let db_name = match input.attrs.iter().find(|attr| attr.path.is_ident("database")) {
+        Some(attr) => attr.parse_args::<LitStr>()?,
+        None => return Err(input.span().error(DERIVE_MISSING_NAME).help(DERIVE_EXAMPLE)),
+    };
+
+    let structure = match input.data {
+        Data::Struct(ref s) => s,
+        _ => return Err(input.span().error(DERIVE_ONLY_ON_STRUCTS))
+    };
+
+    let pool_type = match structure.fields {
+        Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+            &fields.unnamed.first().expect("checked length").ty
+        }
+        _ => return Err(structure.fields.span().error(DERIVE_ONLY_UNNAMED_FIELDS)
+            .help(DERIVE_EXAMPLE))
+    };
+
+    let db_type = &input.ident;
+    let databases = quote_spanned!(pool_type.span() => ::rocket_contrib::databases);
+
+    Ok(quote! {
+        impl #databases::Database for #db_type {
+            type Pool = #pool_type;
+
+            const NAME: &'static str = #db_name;
+        }
+
+        impl ::std::convert::From<#pool_type> for #db_type {
+            fn from(pool: #pool_type) -> Self {
+                Self(pool)
+            }
+        }
+
+        impl ::std::ops::Deref for #db_type {
+            type Target = #pool_type;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl ::std::ops::DerefMut for #db_type {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
             }
         }
     }.into())
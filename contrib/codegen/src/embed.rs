@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+use std::error::Error;
+
+use devise::ext::SpanDiagnosticExt;
+use devise::syn::{self, LitStr};
+use devise::proc_macro2::TokenStream;
+
+/// The extensions of the templates embedded by `embed_templates!`, besides
+/// those passed to it: those of the Tera and Handlebars engines.
+#[cfg(feature = "embed_templates")]
+const TEMPLATE_EXTENSIONS: &[&str] = &["tera", "hbs"];
+
+#[cfg(feature = "embed_templates")]
+pub fn embed_templates(input: proc_macro::TokenStream) -> devise::Result<TokenStream> {
+    use devise::syn::{Token, parse::Parser, punctuated::Punctuated};
+
+    let args = Punctuated::<LitStr, Token![,]>::parse_separated_nonempty.parse(input)?;
+    let mut args = args.into_iter();
+    let dir = args.next().expect("nonempty arguments");
+    let extensions: Vec<String> = TEMPLATE_EXTENSIONS.iter()
+        .map(|ext| ext.to_string())
+        .chain(args.map(|ext| ext.value()))
+        .collect();
+
+    let (root, files) = read_dir(&dir, false)?;
+    let templates = files.iter()
+        .filter(|path| path.extension().map_or(false, |ext| extensions.iter().any(|e| ext == &**e)))
+        .map(|path| {
+            let relative = path.strip_prefix(&root).expect("path in template root")
+                .to_string_lossy()
+                .replace("\\", "/");
+
+            let full_path = path.display().to_string();
+            quote_spanned!(dir.span() => (#relative, include_str!(#full_path)))
+        });
+
+    Ok(quote_spanned!(dir.span() =>
+        ::rocket_contrib::templates::EmbeddedTemplates::new(#dir, &[#(#templates),*])
+    ))
+}
+
+#[cfg(feature = "embed_files")]
+pub fn embed_files(input: proc_macro::TokenStream) -> devise::Result<TokenStream> {
+    use std::hash::{Hash, Hasher};
+    use std::collections::hash_map::DefaultHasher;
+
+    let dir = syn::parse::<LitStr>(input)?;
+    let (root, files) = read_dir(&dir, true)?;
+    let mut embedded = vec![];
+    for path in &files {
+        let relative = path.strip_prefix(&root).expect("path in file root")
+            .to_string_lossy()
+            .replace("\\", "/");
+
+        let contents = std::fs::read(path)
+            .map_err(|e| dir.span().error(format!("failed to read '{}': {}", path.display(), e)))?;
+
+        // The entity tag is computed once, here, rather than on every request.
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+        let etag = format!("\"{:x}-{:x}\"", contents.len(), hasher.finish());
+        embedded.push((relative, etag, path.display().to_string()));
+    }
+
+    // Files are looked up by binary search on their relative path.
+    embedded.sort_by(|a, b| a.0.cmp(&b.0));
+    let files = embedded.iter().map(|(relative, etag, full_path)| {
+        quote_spanned!(dir.span() => (#relative, #etag, include_bytes!(#full_path) as &[u8]))
+    });
+
+    Ok(quote_spanned!(dir.span() =>
+        ::rocket_contrib::serve::EmbeddedFiles::new(&[#(#files),*])
+    ))
+}
+
+/// Returns the path of the directory `dir`, relative to the crate root, and
+/// the sorted paths of the files in it, including hidden ones if `hidden` is
+/// `true`.
+fn read_dir(dir: &LitStr, hidden: bool) -> devise::Result<(PathBuf, Vec<PathBuf>)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("MANIFEST_DIR");
+    let root = Path::new(&manifest_dir).join(dir.value());
+
+    let mut files = vec![];
+    collect_files(&root, hidden, &mut files)
+        .map_err(|e| dir.span().error(format!("failed to read '{}': {}", root.display(), e)))?;
+
+    files.sort();
+    Ok((root, files))
+}
+
+/// Pushes the path of every file in `dir` and its subdirectories to `files`,
+/// skipping hidden ones unless `hidden` is `true`.
+fn collect_files(dir: &Path, hidden: bool, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_hidden = path.file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with('.'));
+
+        if is_hidden && !hidden {
+            continue;
+        } else if path.is_dir() {
+            collect_files(&path, hidden, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
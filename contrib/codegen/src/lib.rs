@@ -17,6 +17,13 @@
 //! This crate implements the following procedural macros:
 //!
 //! * **databases**
+//! * **embed_templates**
+//! * **embed_files**
+//! * **rate_limit**
+//!
+//! It also implements the following custom derive:
+//!
+//! * **Database**
 //!
 //! The syntax for the `databases` macro is:
 //!
@@ -24,6 +31,45 @@
 //! macro := database(DATABASE_NAME)
 //! DATABASE_NAME := (string literal)
 //! </pre>
+//!
+//! The syntax for the `embed_templates` macro is:
+//!
+//! <pre>
+//! macro := embed_templates!(TEMPLATE_DIR (',' EXTENSION)*)
+//! TEMPLATE_DIR := (string literal, relative to the crate root)
+//! EXTENSION := (string literal, an extension to embed besides `tera`, `hbs`)
+//! </pre>
+//!
+//! The syntax for the `embed_files` macro is:
+//!
+//! <pre>
+//! macro := embed_files!(FILE_DIR)
+//! FILE_DIR := (string literal, relative to the crate root)
+//! </pre>
+//!
+//! The syntax for the `rate_limit` attribute is:
+//!
+//! <pre>
+//! #[rate_limit(ARG, ...)]
+//! ROUTE_ATTRIBUTE
+//! fn NAME(...) { ... }
+//!
+//! ARG := limit = (expression of type `u64`)
+//!      | period = (expression of type `u64`, in seconds)
+//!      | algorithm = ("token_bucket" | "sliding_window")
+//!      | key = (expression implementing `rocket_contrib::ratelimit::KeyExtractor`)
+//! </pre>
+//!
+//! The syntax for the `Database` derive is:
+//!
+//! <pre>
+//! #[derive(Database)]
+//! #[database(DATABASE_NAME)]
+//! struct NAME(POOL_TYPE);
+//!
+//! DATABASE_NAME := (string literal)
+//! POOL_TYPE := (type implementing `rocket_contrib::databases::Pool`)
+//! </pre>
 
 #[allow(unused_imports)]
 #[macro_use] extern crate quote;
@@ -34,6 +80,12 @@ use devise::{syn, proc_macro2};
 #[cfg(feature = "database_attribute")]
 mod database;
 
+#[cfg(any(feature = "embed_templates", feature = "embed_files"))]
+mod embed;
+
+#[cfg(feature = "rate_limit_attribute")]
+mod rate_limit;
+
 #[allow(unused_imports)]
 use proc_macro::TokenStream;
 
@@ -44,3 +96,38 @@ pub fn database(attr: TokenStream, input: TokenStream) -> TokenStream {
     crate::database::database_attr(attr, input)
         .unwrap_or_else(|diag| diag.emit_as_item_tokens().into())
 }
+
+/// The custom derive for asynchronous database pools.
+#[cfg(feature = "database_attribute")]
+#[proc_macro_derive(Database, attributes(database))]
+pub fn derive_database(input: TokenStream) -> TokenStream {
+    crate::database::derive_database(input)
+        .unwrap_or_else(|diag| diag.emit_as_item_tokens().into())
+}
+
+/// The procedural macro for embedding a template directory.
+#[cfg(feature = "embed_templates")]
+#[proc_macro]
+pub fn embed_templates(input: TokenStream) -> TokenStream {
+    crate::embed::embed_templates(input)
+        .unwrap_or_else(|diag| diag.emit_as_expr_tokens())
+        .into()
+}
+
+/// The procedural macro for embedding a static file directory.
+#[cfg(feature = "embed_files")]
+#[proc_macro]
+pub fn embed_files(input: TokenStream) -> TokenStream {
+    crate::embed::embed_files(input)
+        .unwrap_or_else(|diag| diag.emit_as_expr_tokens())
+        .into()
+}
+
+/// The procedural macro for the `rate_limit` annotation.
+#[cfg(feature = "rate_limit_attribute")]
+#[proc_macro_attribute]
+pub fn rate_limit(attr: TokenStream, input: TokenStream) -> TokenStream {
+    crate::rate_limit::rate_limit_attr(attr, input)
+        .unwrap_or_else(|diag| diag.emit_as_item_tokens())
+        .into()
+}
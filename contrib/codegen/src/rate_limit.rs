@@ -0,0 +1,92 @@
+use devise::{Spanned, Result, ext::SpanDiagnosticExt};
+use devise::proc_macro2::TokenStream;
+
+use crate::syn::{self, Expr, Ident, Lit, Token};
+use crate::syn::parse::{Parse, ParseStream, Parser};
+use crate::syn::punctuated::Punctuated;
+
+const EXAMPLE: &str = "example: `#[rate_limit(limit = 10, period = 60)]`";
+const ONLY_ON_FUNCTIONS: &str = "`rate_limit` attribute can only be used on functions";
+const ALGORITHMS: &str = "expected \"token_bucket\" or \"sliding_window\"";
+
+/// A `name = value` argument of the attribute.
+struct Arg {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        Ok(Arg { name, value: input.parse()? })
+    }
+}
+
+pub fn rate_limit_attr(
+    attr: proc_macro::TokenStream,
+    input: proc_macro::TokenStream
+) -> Result<TokenStream> {
+    let args = Punctuated::<Arg, Token![,]>::parse_terminated.parse(attr)?;
+    let mut function = syn::parse::<syn::ItemFn>(input)
+        .map_err(|e| e.span().error(ONLY_ON_FUNCTIONS))?;
+
+    let (mut limit, mut period, mut algorithm, mut key) = (None, None, None, None);
+    for arg in args {
+        let value = arg.value;
+        match arg.name.to_string().as_str() {
+            "limit" => limit = Some(value),
+            "period" => period = Some(value),
+            "algorithm" => algorithm = Some(match value {
+                Expr::Lit(ref lit) => match lit.lit {
+                    Lit::Str(ref s) if s.value() == "token_bucket" => quote!(token_bucket),
+                    Lit::Str(ref s) if s.value() == "sliding_window" => quote!(sliding_window),
+                    _ => return Err(value.span().error(ALGORITHMS)),
+                },
+                _ => return Err(value.span().error(ALGORITHMS)),
+            }),
+            "key" => key = Some(value),
+            _ => return Err(arg.name.span().error("unknown argument").help(EXAMPLE)),
+        }
+    }
+
+    let span = function.sig.ident.span();
+    let limit = limit.ok_or_else(|| span.error("missing `limit` argument").help(EXAMPLE))?;
+    let period = period.ok_or_else(|| span.error("missing `period` argument").help(EXAMPLE))?;
+    let algorithm = algorithm.unwrap_or_else(|| quote!(token_bucket));
+    let ratelimit = quote!(::rocket_contrib::ratelimit);
+    let key_fn = key.map(|key| quote! {
+        fn key() -> ::std::boxed::Box<dyn #ratelimit::KeyExtractor> {
+            ::std::boxed::Box::new(#key)
+        }
+    });
+
+    let name = function.sig.ident.to_string();
+    let policy = Ident::new(&format!("__rocket_rate_limit_{}", name), span);
+    let vis = function.vis.clone();
+
+    let guard: syn::FnArg = syn::parse_quote! {
+        __rocket_rate_limit: #ratelimit::RateLimited<#policy>
+    };
+
+    function.sig.inputs.push(guard);
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis struct #policy;
+
+        impl #ratelimit::Policy for #policy {
+            const NAME: &'static str = #name;
+
+            fn quota() -> #ratelimit::Quota {
+                #ratelimit::Quota::new(#limit, ::std::time::Duration::from_secs(#period))
+                    .#algorithm()
+            }
+
+            #key_fn
+        }
+
+        #function
+    })
+}
@@ -0,0 +1,306 @@
+//! Access logging for all requests.
+//!
+//! See the [`AccessLog`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `access_log` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["access_log"]
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use rocket::{Config, Request, Response, Rocket, Data};
+use rocket::config::ConfigError;
+use rocket::fairing::{AdHoc, Fairing, Info, Kind, Order};
+
+use serde_json::json;
+use time::OffsetDateTime;
+
+use crate::received::Received;
+
+/// The format of access log entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The [Common Log Format]:
+    ///
+    /// ```text
+    /// 127.0.0.1 - - [10/Oct/2020:13:55:36 +0000] "GET /index HTTP/1.1" 200 2326
+    /// ```
+    ///
+    /// [Common Log Format]: https://httpd.apache.org/docs/current/logs.html#common
+    Common,
+
+    /// The [Combined Log Format]: the Common Log Format followed by the
+    /// `Referer` and `User-Agent` request headers, then by the time taken to
+    /// respond in milliseconds and the name of the route that handled the
+    /// request, or `-` if there was none:
+    ///
+    /// ```text
+    /// 127.0.0.1 - - [10/Oct/2020:13:55:36 +0000] "GET / HTTP/1.1" 200 6 "-" "curl/7.68" 1.2 "index"
+    /// ```
+    ///
+    /// [Combined Log Format]: https://httpd.apache.org/docs/current/logs.html#combined
+    Combined,
+
+    /// One JSON object per request with the fields `time`, `client`, `method`,
+    /// `uri`, `version`, `status`, `bytes`, `latency_ms`, `route`, `referer`,
+    /// and `user_agent`. Unknown values are `null`.
+    Json,
+}
+
+impl Default for Format {
+    /// Defaults to [`Format::Combined`].
+    fn default() -> Format {
+        Format::Combined
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Format, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "common" => Ok(Format::Common),
+            "combined" => Ok(Format::Combined),
+            "json" => Ok(Format::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The destination of access log entries.
+enum Sink {
+    Log,
+    Custom(Box<dyn Fn(&str) + Send + Sync>),
+}
+
+/// A fairing that writes an access log entry for every request.
+///
+/// Each entry records the client's IP address, the request's method, URI, and
+/// HTTP version, the response's status and body size, the time taken to
+/// respond, and the name of the route that handled the request, formatted
+/// according to a [`Format`]. The client's IP address is determined by
+/// [`Request::client_ip()`], which honors the `X-Real-IP` header set by
+/// proxies. The body size is unknown, and logged as `-` or `null`, for
+/// streamed bodies.
+///
+/// By default, entries are written to the logging subsystem at the `info`
+/// level with the target `access_log`. To write them elsewhere, supply a sink
+/// via [`AccessLog::with_sink()`].
+///
+/// [`Request::client_ip()`]: rocket::Request::client_ip()
+///
+/// # Configuration
+///
+/// [`AccessLog::fairing()`] reads the format from the `format` key of the
+/// `access_log` configuration table: one of `"common"`, `"combined"`, or
+/// `"json"`.
+///
+/// ```toml
+/// [global.access_log]
+/// format = "json"
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::access_log::{AccessLog, Format};
+///
+/// rocket::ignite()
+///     .attach(AccessLog::new(Format::Common))
+/// # ;
+/// ```
+pub struct AccessLog {
+    format: Format,
+    sink: Sink,
+}
+
+impl AccessLog {
+    /// Returns an access log that writes entries in `format` to the logging
+    /// subsystem.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::access_log::{AccessLog, Format};
+    ///
+    /// let log = AccessLog::new(Format::Json);
+    /// ```
+    pub fn new(format: Format) -> AccessLog {
+        AccessLog { format, sink: Sink::Log }
+    }
+
+    /// Returns an access log that passes entries in `format` to `sink`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use std::sync::Mutex;
+    /// use std::fs::File;
+    ///
+    /// use rocket_contrib::access_log::{AccessLog, Format};
+    ///
+    /// # if false {
+    /// let file = Mutex::new(File::create("access.log").unwrap());
+    /// let log = AccessLog::with_sink(Format::Combined, move |entry| {
+    ///     let _ = writeln!(file.lock().unwrap(), "{}", entry);
+    /// });
+    /// # }
+    /// ```
+    pub fn with_sink<F>(format: Format, sink: F) -> AccessLog
+        where F: Fn(&str) + Send + Sync + 'static
+    {
+        AccessLog { format, sink: Sink::Custom(Box::new(sink)) }
+    }
+
+    /// Returns a fairing that reads the format from the `access_log`
+    /// configuration table, defaulting to [`Format::Combined`], and attaches
+    /// an access log that writes to the logging subsystem.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::access_log::AccessLog;
+    ///
+    /// rocket::ignite().attach(AccessLog::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach("Access Log Configuration", |mut rocket: Rocket| async move {
+            let result = configured_format(rocket.config().await);
+            match result {
+                Ok(format) => Ok(rocket.attach(AccessLog::new(format))),
+                Err(e) => {
+                    e.pretty_print();
+                    error_!("Invalid access log configuration.");
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Formats the entry for `req` and `res`, which took `latency`.
+    fn entry(&self, req: &Request<'_>, res: &Response<'_>, latency: Duration) -> String {
+        let client = req.client_ip();
+        let bytes = res.body().and_then(|body| body.known_size());
+        let referer = req.headers().get_one("Referer");
+        let user_agent = req.headers().get_one("User-Agent");
+        let route = req.route().and_then(|route| route.name);
+        let now = OffsetDateTime::now_utc();
+
+        match self.format {
+            Format::Common | Format::Combined => {
+                let mut entry = format!(
+                    "{} - - [{}] \"{} {} {}\" {} {}",
+                    Or(client, "-"),
+                    now.format("%d/%b/%Y:%H:%M:%S %z"),
+                    req.method(),
+                    req.uri(),
+                    req.http_version().unwrap_or("-"),
+                    res.status().code,
+                    Or(bytes, "-"),
+                );
+
+                if self.format == Format::Combined {
+                    entry.push_str(&format!(
+                        " \"{}\" \"{}\" {:.3} \"{}\"",
+                        escape(referer.unwrap_or("-")),
+                        escape(user_agent.unwrap_or("-")),
+                        latency.as_secs_f64() * 1000.0,
+                        escape(route.unwrap_or("-")),
+                    ));
+                }
+
+                entry
+            }
+            Format::Json => json!({
+                "time": now.format("%Y-%m-%dT%H:%M:%SZ"),
+                "client": client.map(|ip| ip.to_string()),
+                "method": req.method().as_str(),
+                "uri": req.uri().to_string(),
+                "version": req.http_version(),
+                "status": res.status().code,
+                "bytes": bytes,
+                "latency_ms": latency.as_secs_f64() * 1000.0,
+                "route": route,
+                "referer": referer,
+                "user_agent": user_agent,
+            }).to_string(),
+        }
+    }
+}
+
+/// Displays the value or, if there is none, the default.
+struct Or<T>(Option<T>, &'static str);
+
+impl<T: fmt::Display> fmt::Display for Or<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(ref value) => value.fmt(f),
+            None => self.1.fmt(f),
+        }
+    }
+}
+
+/// Escapes quotes and backslashes in a quoted log field.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Reads the log format from the `access_log` table in `config`.
+fn configured_format(config: &Config) -> Result<Format, ConfigError> {
+    let table = match config.get_table("access_log") {
+        Ok(table) => table,
+        Err(ConfigError::Missing(_)) => return Ok(Format::default()),
+        Err(e) => return Err(e),
+    };
+
+    match table.get("format") {
+        Some(value) => value.as_str().and_then(|s| s.parse().ok()).ok_or_else(|| {
+            let name = "access_log.format".to_string();
+            let expected = "\"common\", \"combined\", or \"json\"";
+            ConfigError::BadType(name, expected, value.type_str(), None)
+        }),
+        None => Ok(Format::default()),
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Access Log",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Requests are timed from before any other fairing runs.
+    fn order(&self) -> Order {
+        Order::priority(i32::max_value())
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        Received::record(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let entry = self.entry(req, res, Received::elapsed(req));
+        match self.sink {
+            Sink::Log => info!(target: "access_log", "{}", entry),
+            Sink::Custom(ref sink) => sink(&entry),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Compile-time, type-checked templates via Askama.
+//!
+//! See the [`Askama`] type for further details.
+//!
+//! # Overview
+//!
+//! Unlike the [runtime templates](crate::templates) of Tera and Handlebars,
+//! [Askama] templates are compiled into the application: a template's syntax
+//! and the names and types of the values it uses are checked when the
+//! application is built. A type deriving `askama::Template` is returned from a
+//! handler by wrapping it in [`Askama`], which renders the template and infers
+//! the response's Content-Type from the template's extension. Because there is
+//! no template directory to load, no fairing is required to render templates.
+//!
+//! The optional [`AskamaFairing`] controls how rendering errors are reported.
+//!
+//! [Askama]: https://docs.rs/askama/0.10
+//!
+//! # Enabling
+//!
+//! This module is only available when the `askama_templates` feature is
+//! enabled. Because the code generated by Askama's derive refers to the
+//! `askama` crate directly, `askama` must also be a direct dependency:
+//!
+//! ```toml
+//! [dependencies]
+//! askama = "0.10"
+//!
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["askama_templates"]
+//! ```
+
+use rocket::{Request, Rocket};
+use rocket::config::ConfigError;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::response::{self, status, Content, Responder};
+use rocket::http::{ContentType, Status};
+
+/// Responder that renders an Askama template.
+///
+/// Wrapping a value of a type that implements `askama::Template` in `Askama`
+/// makes it a [`Responder`]. The template is rendered when the response is
+/// created. The Content-Type of the response is inferred from the template's
+/// extension: `html` results in `text/html`, `txt` in `text/plain`, and so on.
+/// Templates without an extension, or with an unknown extension, are
+/// responded to as `text/plain`, matching Askama's choice not to escape their
+/// values.
+///
+/// If the template fails to render, the error is logged and an `Err` of
+/// `Status::InternalServerError` is returned, forwarding to the `500`
+/// catcher. If the [`AskamaFairing`] is attached and error details are
+/// enabled, the response is instead a `500` with the error in a plain-text
+/// body.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use askama::Template;
+/// use rocket_contrib::askama::Askama;
+///
+/// #[derive(Template)]
+/// #[template(source = "<h1>Hello, {{ name }}!</h1>", ext = "html")]
+/// struct Hello {
+///     name: String,
+/// }
+///
+/// #[get("/hello/<name>")]
+/// fn hello(name: String) -> Askama<Hello> {
+///     Askama(Hello { name })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Askama<T>(pub T);
+
+impl<T> Askama<T> {
+    /// Consumes the `Askama` wrapper and returns the wrapped template.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket_contrib::askama::Askama;
+    /// let askama = Askama("not a template");
+    /// assert_eq!(askama.into_inner(), "not a template");
+    /// ```
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: askama::Template> Askama<T> {
+    /// Returns the Content-Type of the rendered template, inferred from the
+    /// template's extension.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use askama::Template;
+    /// use rocket::http::ContentType;
+    /// use rocket_contrib::askama::Askama;
+    ///
+    /// #[derive(Template)]
+    /// #[template(source = "{{ n }}", ext = "xml")]
+    /// struct Count { n: usize }
+    ///
+    /// assert_eq!(Askama(Count { n: 3 }).content_type(), ContentType::XML);
+    /// ```
+    pub fn content_type(&self) -> ContentType {
+        self.0.extension()
+            .and_then(ContentType::from_extension)
+            .unwrap_or(ContentType::Plain)
+    }
+}
+
+impl<'r, T: askama::Template> Responder<'r, 'static> for Askama<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let content_type = self.content_type();
+        match self.0.render() {
+            Ok(string) => Content(content_type, string).respond_to(req),
+            Err(e) => {
+                error_!("Askama template failed to render: {}", e);
+                match req.managed_state::<ErrorDetails>() {
+                    Some(&ErrorDetails(true)) => {
+                        let body = format!("Template failed to render: {}", e);
+                        status::Custom(Status::InternalServerError, body).respond_to(req)
+                    }
+                    _ => Err(Status::InternalServerError),
+                }
+            }
+        }
+    }
+}
+
+/// Whether the details of rendering errors are sent in responses.
+struct ErrorDetails(bool);
+
+/// Fairing that configures how Askama rendering errors are reported.
+///
+/// Attaching this fairing is optional. By default, a template that fails to
+/// render results in a `500` handled by the application's catcher. When the
+/// fairing is attached and the `askama_error_details` configuration
+/// parameter is `true`, the error is instead sent in the body of the `500`
+/// response, which is convenient during development. The parameter defaults
+/// to `true` in the development environment and `false` otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_contrib::askama::AskamaFairing;
+///
+/// fn main() {
+///     rocket::ignite()
+///         .attach(AskamaFairing)
+///         // ...
+///     # ;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AskamaFairing;
+
+#[rocket::async_trait]
+impl Fairing for AskamaFairing {
+    fn info(&self) -> Info {
+        Info { name: "Askama", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let config = rocket.config().await;
+        let details = match config.get_bool("askama_error_details") {
+            Ok(details) => details,
+            Err(ConfigError::Missing(_)) => config.environment.is_dev(),
+            Err(e) => {
+                e.pretty_print();
+                return Err(rocket);
+            }
+        };
+
+        Ok(rocket.manage(ErrorDetails(details)))
+    }
+}
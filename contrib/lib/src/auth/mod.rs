@@ -0,0 +1,750 @@
+//! User authentication over private cookies or sessions.
+//!
+//! See the [`Authenticated`] request guard, the [`Auth`] request guard, and
+//! the [`Authentication`] fairing for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `auth` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["auth"]
+//! ```
+//!
+//! Logins are kept in private cookies, so a `secret_key` must be configured
+//! for logins to survive an application restart.
+//!
+//! # Overview
+//!
+//! An application describes its users by implementing [`AuthUser`], which
+//! identifies a user by a string ID and retrieves a user by ID. Once the
+//! [`Authentication`] fairing is attached:
+//!
+//!   * The [`Auth`] request guard logs users in and out, optionally issuing a
+//!     long-lived _remember-me_ token that logs the user back in once their
+//!     login has expired. Tokens are kept in a [`TokenStore`], so logging out
+//!     revokes them. A token is replaced by a new one whenever it is used, so
+//!     a stolen copy stops working once either copy is used.
+//!   * The [`Authenticated<U>`] request guard retrieves the logged-in user,
+//!     failing with `401 Unauthorized` if there is none.
+//!   * If a login URL is configured, requests for HTML pages that fail with
+//!     `401 Unauthorized` are redirected to the login page, with the original
+//!     URI in the `next` query parameter.
+//!
+//! Passwords are hashed and verified with [`hash_password()`] and
+//! [`verify_password()`]. Both are deliberately slow, so async handlers call
+//! them from a blocking task.
+//!
+//! # Configuration
+//!
+//! Authentication is configured in the `auth` table in `Rocket.toml`:
+//!
+//! ```toml
+//! [global.auth]
+//! cookie_name = "app_auth"
+//! login_hours = 8
+//! remember_cookie_name = "app_remember"
+//! remember_days = 14
+//! login_url = "/login"
+//! storage = "session"
+//! secure = true
+//! ```
+//!
+//! Every key is optional. By default, the logged-in user's ID is kept in a
+//! private cookie named `rocket_auth`, logins expire after 24 hours,
+//! remember-me tokens are kept for 30 days in a private cookie named
+//! `rocket_remember`, and there is no login URL. Logins record when they were
+//! issued, and are rejected once older than `login_hours`, whatever the
+//! client does with the cookie's expiry.
+//!
+//! When `secure` is `true`, cookies are only sent over HTTPS. It defaults to
+//! `false` in the development environment and to `true` otherwise.
+//!
+//! When `storage` is `"session"`, the user's ID is instead kept in the
+//! [`Session`](crate::sessions::Session), which is regenerated whenever a user
+//! logs in or out. This requires the `sessions` feature and the
+//! [`Sessions`](crate::sessions::Sessions) fairing.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket::Request;
+//! use rocket::request::Form;
+//! use rocket::response::{Debug, Redirect};
+//! use rocket::tokio::task::spawn_blocking;
+//! use rocket_contrib::auth::{Auth, AuthUser, AuthError, Authenticated, Authentication};
+//! use rocket_contrib::auth::verify_password;
+//!
+//! struct User {
+//!     name: String,
+//!     password_hash: String,
+//! }
+//!
+//! # fn find_user(name: &str) -> Option<User> { None }
+//! #[rocket::async_trait]
+//! impl AuthUser for User {
+//!     fn auth_id(&self) -> String {
+//!         self.name.clone()
+//!     }
+//!
+//!     async fn fetch(id: &str, _: &Request<'_>) -> Option<User> {
+//!         find_user(id)
+//!     }
+//! }
+//!
+//! #[derive(FromForm)]
+//! struct Login {
+//!     name: String,
+//!     password: String,
+//!     remember: bool,
+//! }
+//!
+//! #[post("/login", data = "<login>")]
+//! async fn login(auth: Auth<'_>, login: Form<Login>) -> Result<Redirect, Debug<AuthError>> {
+//!     let user = match find_user(&login.name) {
+//!         Some(user) => user,
+//!         None => return Ok(Redirect::to("/login")),
+//!     };
+//!
+//!     let (hash, password) = (user.password_hash.clone(), login.password.clone());
+//!     let verified = spawn_blocking(move || verify_password(&hash, &password)).await;
+//!     if !verified.unwrap_or(false) {
+//!         return Ok(Redirect::to("/login"));
+//!     }
+//!
+//!     auth.login(&user, login.remember).await?;
+//!     Ok(Redirect::to("/"))
+//! }
+//!
+//! #[get("/")]
+//! fn index(user: Authenticated<User>) -> String {
+//!     format!("Hello, {}!", user.name)
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Authentication::fairing())
+//!         .mount("/", routes![login, index])
+//! #       ;
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use time::OffsetDateTime;
+
+use rocket::{Config, Request, Response, Rocket};
+use rocket::config::{ConfigError, Value};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Cookie, CookieJar, Method, SameSite, Status, uri::Uri};
+use rocket::request::{self, FromRequest};
+
+use crate::clock::unix_now;
+#[cfg(feature = "sessions")]
+use crate::sessions::{Session, SessionError};
+
+mod password;
+
+mod tokens;
+
+pub use self::password::{hash_password, verify_password};
+pub use self::tokens::{TokenStore, MemoryTokenStore};
+
+/// The session key holding the logged-in user's ID.
+#[cfg(feature = "sessions")]
+const SESSION_KEY: &str = "auth_user";
+
+/// Trait implemented by the users of an application.
+///
+/// A user is identified by a string ID, kept in a private cookie or the session
+/// while the user is logged in, and retrieved from its ID by the
+/// [`Authenticated`] request guard.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::Request;
+/// use rocket::State;
+/// use rocket_contrib::auth::AuthUser;
+/// # use std::collections::HashMap;
+/// # type Users = HashMap<u64, String>;
+///
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// #[rocket::async_trait]
+/// impl AuthUser for User {
+///     fn auth_id(&self) -> String {
+///         self.id.to_string()
+///     }
+///
+///     async fn fetch(id: &str, req: &Request<'_>) -> Option<User> {
+///         let id = id.parse().ok()?;
+///         let users = req.guard::<State<'_, Users>>().await.succeeded()?;
+///         users.get(&id).map(|name| User { id, name: name.clone() })
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait AuthUser: Send + Sync + Sized + 'static {
+    /// Returns the ID of this user.
+    fn auth_id(&self) -> String;
+
+    /// Retrieves the user with the ID `id` while handling `req`, or returns
+    /// `None` if there is none, such as when the user has been deleted.
+    async fn fetch(id: &str, req: &Request<'_>) -> Option<Self>;
+}
+
+/// An error returned by the authentication guards or the password utilities.
+#[derive(Debug)]
+pub enum AuthError {
+    /// No user is logged in.
+    Unauthenticated,
+    /// The [`Authentication`] fairing isn't attached.
+    Unmanaged,
+    /// A password couldn't be hashed.
+    Hash(argon2::Error),
+    /// The [`TokenStore`] failed.
+    Store(Box<dyn std::error::Error + Send + Sync>),
+    /// The session couldn't be loaded or saved.
+    #[cfg(feature = "sessions")]
+    Session(SessionError),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Unauthenticated => write!(f, "no user is logged in"),
+            AuthError::Unmanaged => write!(f, "the `Authentication` fairing is not attached"),
+            AuthError::Hash(e) => write!(f, "password hashing error: {}", e),
+            AuthError::Store(e) => write!(f, "token store error: {}", e),
+            #[cfg(feature = "sessions")]
+            AuthError::Session(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AuthError::Hash(e) => Some(e),
+            AuthError::Store(e) => Some(&**e),
+            #[cfg(feature = "sessions")]
+            AuthError::Session(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl AuthError {
+    /// Returns an `AuthError::Store` wrapping `error`, for use by
+    /// [`TokenStore`] implementations.
+    pub fn store<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> AuthError {
+        AuthError::Store(error.into())
+    }
+}
+
+#[cfg(feature = "sessions")]
+impl From<SessionError> for AuthError {
+    fn from(error: SessionError) -> Self {
+        AuthError::Session(error)
+    }
+}
+
+/// Where the logged-in user's ID is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Storage {
+    Cookie,
+    #[cfg(feature = "sessions")]
+    Session,
+}
+
+/// The configuration read from the `auth` table, and the token store.
+#[derive(Clone)]
+struct Settings {
+    cookie_name: String,
+    /// How long logins last, in seconds.
+    login_for: i64,
+    remember_cookie_name: String,
+    /// How long remember-me tokens are kept, in seconds.
+    remember_for: i64,
+    login_url: Option<String>,
+    storage: Storage,
+    secure: bool,
+    tokens: Arc<dyn TokenStore>,
+}
+
+impl Settings {
+    /// Reads the settings from the `auth` table in `config`. Missing keys, or a
+    /// missing table, take their default values.
+    fn from_config(config: &Config, tokens: Arc<dyn TokenStore>) -> Result<Settings, ConfigError> {
+        let mut settings = Settings {
+            cookie_name: "rocket_auth".into(),
+            login_for: 24 * 60 * 60,
+            remember_cookie_name: "rocket_remember".into(),
+            remember_for: 30 * 24 * 60 * 60,
+            login_url: None,
+            storage: Storage::Cookie,
+            secure: !config.environment.is_dev(),
+            tokens,
+        };
+
+        let table = match config.get_table("auth") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(settings),
+            Err(e) => return Err(e),
+        };
+
+        for (key, value) in table {
+            let string = || value.as_str().map(String::from).ok_or_else(|| bad_type(key, value));
+            match key.as_str() {
+                "cookie_name" => settings.cookie_name = string()?,
+                "login_hours" => match value.as_integer() {
+                    Some(hours) if hours > 0 => settings.login_for = hours * 60 * 60,
+                    _ => return Err(bad_type(key, value)),
+                },
+                "remember_cookie_name" => settings.remember_cookie_name = string()?,
+                "remember_days" => match value.as_integer() {
+                    Some(days) if days > 0 => settings.remember_for = days * 24 * 60 * 60,
+                    _ => return Err(bad_type(key, value)),
+                },
+                "login_url" => settings.login_url = Some(string()?),
+                "storage" => settings.storage = match string()?.as_str() {
+                    "cookie" => Storage::Cookie,
+                    #[cfg(feature = "sessions")]
+                    "session" => Storage::Session,
+                    _ => return Err(bad_type(key, value)),
+                },
+                "secure" => match value.as_bool() {
+                    Some(secure) => settings.secure = secure,
+                    None => return Err(bad_type(key, value)),
+                },
+                _ => warn_!("Ignoring unknown auth configuration key '{}'.", key),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Returns a private cookie named `name` with `value`, expiring in
+    /// `max_age` seconds.
+    fn cookie(&self, name: &str, value: String, max_age: i64) -> Cookie<'static> {
+        let max_age = time::Duration::seconds(max_age);
+        let mut cookie = Cookie::build(name.to_string(), value)
+            .path("/")
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(SameSite::Lax)
+            .max_age(max_age)
+            .finish();
+
+        cookie.set_expires(OffsetDateTime::now_utc() + max_age);
+        cookie
+    }
+
+    /// Returns a cookie that removes the cookie named `name`.
+    fn removal_cookie(&self, name: &str) -> Cookie<'static> {
+        let mut cookie = Cookie::named(name.to_string());
+        cookie.set_path("/");
+        cookie
+    }
+}
+
+fn bad_type(key: &str, value: &Value) -> ConfigError {
+    let expected = match key {
+        "login_hours" | "remember_days" => "a positive integer",
+        "secure" => "a boolean",
+        #[cfg(feature = "sessions")]
+        "storage" => "\"cookie\" or \"session\"",
+        #[cfg(not(feature = "sessions"))]
+        "storage" => "\"cookie\" (\"session\" requires the `sessions` feature)",
+        _ => "a string",
+    };
+
+    ConfigError::BadType(format!("auth.{}", key), expected, value.type_str(), None)
+}
+
+/// The fairing that enables authentication.
+///
+/// On attach, the fairing reads the `auth` configuration table, as described
+/// in the [module documentation](crate::auth). If the configuration is invalid,
+/// an error is logged and launch is aborted. Remember-me tokens are kept in a
+/// [`MemoryTokenStore`] unless another store is set with
+/// [`Authentication::with_store()`].
+///
+/// If a `login_url` is configured, the fairing redirects `GET` requests that
+/// fail with `401 Unauthorized` and that prefer an HTML response to the login
+/// URL with a `303 See Other`. The original URI, percent-encoded, is appended
+/// in the `next` query parameter so the login handler can return the user to
+/// it. Other requests, such as those from API clients, receive the `401`.
+pub struct Authentication {
+    tokens: Arc<dyn TokenStore>,
+}
+
+impl Authentication {
+    /// Returns the authentication fairing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::auth::Authentication;
+    ///
+    /// rocket::ignite().attach(Authentication::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> Authentication {
+        Authentication::with_store(MemoryTokenStore::new())
+    }
+
+    /// Returns the authentication fairing, keeping remember-me tokens in
+    /// `store`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::auth::{Authentication, MemoryTokenStore};
+    ///
+    /// rocket::ignite().attach(Authentication::with_store(MemoryTokenStore::new()))
+    /// # ;
+    /// ```
+    pub fn with_store<S: TokenStore>(store: S) -> Authentication {
+        Authentication { tokens: Arc::new(store) }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Authentication {
+    fn info(&self) -> Info {
+        Info { name: "Authentication", kind: Kind::Attach | Kind::Response }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        match Settings::from_config(rocket.config().await, self.tokens.clone()) {
+            Ok(settings) => Ok(rocket.manage(settings)),
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid authentication configuration.");
+                Err(rocket)
+            }
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let login_url = match req.managed_state::<Settings>() {
+            Some(settings) => match &settings.login_url {
+                Some(url) => url,
+                None => return,
+            },
+            None => return,
+        };
+
+        let wants_html = req.accept().map_or(false, |a| a.preferred().media_type().is_html());
+        if res.status() != Status::Unauthorized || req.method() != Method::Get || !wants_html {
+            return;
+        }
+
+        let separator = if login_url.contains('?') { '&' } else { '?' };
+        let next = req.uri().to_string();
+        let location = format!("{}{}next={}", login_url, separator, Uri::percent_encode(&next));
+
+        res.set_status(Status::SeeOther);
+        res.set_raw_header("Location", location);
+        res.remove_header("Content-Type");
+        res.set_sized_body(0, std::io::Cursor::new(""));
+    }
+}
+
+/// A request guard that logs users in and out.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::response::{Debug, Redirect};
+/// use rocket_contrib::auth::{Auth, AuthError};
+///
+/// #[post("/logout")]
+/// async fn logout(auth: Auth<'_>) -> Result<Redirect, Debug<AuthError>> {
+///     auth.logout().await?;
+///     Ok(Redirect::to("/"))
+/// }
+/// ```
+pub struct Auth<'a> {
+    settings: &'a Settings,
+    cookies: &'a CookieJar<'a>,
+    #[cfg(feature = "sessions")]
+    session: Option<Session<'a>>,
+}
+
+impl<'a> Auth<'a> {
+    /// Logs `user` in. If `remember` is `true`, also issues a remember-me
+    /// token that logs the user back in, until it expires or the user logs
+    /// out, once their login has expired. When the session holds the login,
+    /// the session is regenerated first.
+    pub async fn login<U: AuthUser>(&self, user: &U, remember: bool) -> Result<(), AuthError> {
+        let id = user.auth_id();
+        self.establish(&id).await?;
+        if remember {
+            self.remember(&id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Logs the current user out, if any, and revokes their remember-me token.
+    /// When the session holds the login, the session is regenerated as well.
+    pub async fn logout(&self) -> Result<(), AuthError> {
+        let settings = self.settings;
+        if let Some(token) = self.cookies.get_private(&settings.remember_cookie_name) {
+            settings.tokens.remove(token.value()).await?;
+        }
+
+        self.cookies.remove_private(settings.removal_cookie(&settings.remember_cookie_name));
+        match settings.storage {
+            Storage::Cookie => {
+                self.cookies.remove_private(settings.removal_cookie(&settings.cookie_name));
+            }
+            #[cfg(feature = "sessions")]
+            Storage::Session => {
+                let session = self.session.as_ref().ok_or(AuthError::Unmanaged)?;
+                session.remove(SESSION_KEY).await?;
+                session.regenerate().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records `id` as the logged-in user's ID, issued now.
+    async fn establish(&self, id: &str) -> Result<(), AuthError> {
+        let settings = self.settings;
+        let login = format!("{}:{}", unix_now(), id);
+        match settings.storage {
+            Storage::Cookie => {
+                let cookie = settings.cookie(&settings.cookie_name, login, settings.login_for);
+                self.cookies.add_private(cookie);
+            }
+            #[cfg(feature = "sessions")]
+            Storage::Session => {
+                let session = self.session.as_ref().ok_or(AuthError::Unmanaged)?;
+                session.regenerate().await?;
+                session.set(SESSION_KEY, &login).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Issues a remember-me token for the user `id`.
+    async fn remember(&self, id: &str) -> Result<(), AuthError> {
+        let settings = self.settings;
+        let token = tokens::new_token();
+        let ttl = Duration::from_secs(settings.remember_for as u64);
+        settings.tokens.save(&token, id, ttl).await?;
+        let name = &settings.remember_cookie_name;
+        self.cookies.add_private(settings.cookie(name, token, settings.remember_for));
+        Ok(())
+    }
+
+    /// Replaces the remember-me token `token` of the user `id` with a new one,
+    /// so that each token logs the user in at most once.
+    async fn rotate(&self, token: &str, id: &str) -> Result<(), AuthError> {
+        self.settings.tokens.remove(token).await?;
+        self.remember(id).await
+    }
+
+    /// Returns the logged-in user's ID if there is a login and it was issued
+    /// less than `login_hours` ago.
+    fn logged_in(&self) -> Option<String> {
+        let login = match self.settings.storage {
+            Storage::Cookie => {
+                let cookie = self.cookies.get_private(&self.settings.cookie_name)?;
+                cookie.value().to_string()
+            }
+            #[cfg(feature = "sessions")]
+            Storage::Session => self.session.as_ref()?.get::<String>(SESSION_KEY)?,
+        };
+
+        let mut parts = login.splitn(2, ':');
+        let issued: i64 = parts.next()?.parse().ok()?;
+        let id = parts.next()?;
+        let age = unix_now() - issued;
+        match age >= 0 && age < self.settings.login_for {
+            true => Some(id.to_string()),
+            false => None,
+        }
+    }
+
+    /// Returns the remember-me token and its user's ID, if there is a token
+    /// and it hasn't been revoked or expired.
+    async fn remembered(&self) -> Option<(String, String)> {
+        let cookie = self.cookies.get_private(&self.settings.remember_cookie_name)?;
+        match self.settings.tokens.load(cookie.value()).await {
+            Ok(id) => id.map(|id| (cookie.value().to_string(), id)),
+            Err(e) => {
+                warn_!("Failed to load a remember-me token: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Auth<'a> {
+    type Error = AuthError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let settings = match req.managed_state::<Settings>() {
+            Some(settings) => settings,
+            None => {
+                error_!("Missing authentication fairing: attach `Authentication`.");
+                let error = (Status::InternalServerError, AuthError::Unmanaged);
+                return request::Outcome::Failure(error);
+            }
+        };
+
+        #[cfg(feature = "sessions")]
+        let session = match settings.storage {
+            Storage::Session => match req.guard::<Session<'a>>().await {
+                request::Outcome::Success(session) => Some(session),
+                request::Outcome::Failure((status, e)) => {
+                    return request::Outcome::Failure((status, AuthError::Session(e)));
+                }
+                request::Outcome::Forward(()) => return request::Outcome::Forward(()),
+            },
+            Storage::Cookie => None,
+        };
+
+        request::Outcome::Success(Auth {
+            settings,
+            cookies: req.cookies(),
+            #[cfg(feature = "sessions")]
+            session,
+        })
+    }
+}
+
+/// A request guard for the logged-in user.
+///
+/// The guard retrieves the logged-in user with [`AuthUser::fetch()`]. If no
+/// user is logged in, or the login has expired, but the request carries a
+/// valid remember-me token, the token's user is retrieved and logged in anew,
+/// and the token is replaced with a new one. Otherwise, the guard fails with
+/// `401 Unauthorized`, which the [`Authentication`] fairing redirects to the
+/// login page for HTML requests when a login URL is configured. Use
+/// `Option<Authenticated<U>>` for routes that serve anonymous and logged-in
+/// users alike.
+///
+/// An `Authenticated<U>` dereferences to the user. In tests, the guard can be
+/// stubbed with [`Overrides::guard()`](rocket::local::Overrides::guard()).
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// # use rocket::Request;
+/// use rocket_contrib::auth::{AuthUser, Authenticated};
+///
+/// struct Admin(String);
+///
+/// #[rocket::async_trait]
+/// impl AuthUser for Admin {
+///     fn auth_id(&self) -> String {
+///         self.0.clone()
+///     }
+///
+///     async fn fetch(id: &str, _: &Request<'_>) -> Option<Admin> {
+///         Some(Admin(id.to_string())).filter(|_| id == "root")
+///     }
+/// }
+///
+/// #[get("/admin")]
+/// fn admin(admin: Authenticated<Admin>) -> String {
+///     format!("Welcome, {}.", admin.0)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Authenticated<U>(pub U);
+
+impl<U> Authenticated<U> {
+    /// Consumes the guard, returning the user.
+    pub fn into_inner(self) -> U {
+        self.0
+    }
+}
+
+impl<U> Deref for Authenticated<U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, U: AuthUser> FromRequest<'a, 'r> for Authenticated<U> {
+    type Error = AuthError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        if let Some(outcome) = req.stubbed_guard::<Self, AuthError>() {
+            return outcome;
+        }
+
+        let auth = match req.guard::<Auth<'a>>().await {
+            request::Outcome::Success(auth) => auth,
+            request::Outcome::Failure(e) => return request::Outcome::Failure(e),
+            request::Outcome::Forward(()) => return request::Outcome::Forward(()),
+        };
+
+        if let Some(id) = auth.logged_in() {
+            match U::fetch(&id, req).await {
+                Some(user) => return request::Outcome::Success(Authenticated(user)),
+                None => {
+                    if let Err(e) = auth.logout().await {
+                        warn_!("Failed to log out a missing user: {}", e);
+                    }
+                }
+            }
+        }
+
+        if let Some((token, id)) = auth.remembered().await {
+            match U::fetch(&id, req).await {
+                Some(user) => {
+                    if let Err(e) = auth.establish(&id).await {
+                        warn_!("Failed to log in a remembered user: {}", e);
+                    }
+
+                    if let Err(e) = auth.rotate(&token, &id).await {
+                        warn_!("Failed to replace a remember-me token: {}", e);
+                    }
+
+                    return request::Outcome::Success(Authenticated(user));
+                }
+                None => {
+                    if let Err(e) = auth.logout().await {
+                        warn_!("Failed to log out a missing remembered user: {}", e);
+                    }
+                }
+            }
+        }
+
+        request::Outcome::Failure((Status::Unauthorized, AuthError::Unauthenticated))
+    }
+}
@@ -0,0 +1,40 @@
+use argon2::{Config, Variant};
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+use crate::auth::AuthError;
+
+/// Hashes `password` with Argon2id and a random salt, returning the hash in
+/// the PHC string format, which encodes the salt and parameters alongside the
+/// hash.
+///
+/// Hashing is deliberately slow. In an async handler, call this function from
+/// a blocking task, as with `tokio::task::spawn_blocking()`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_contrib::auth::{hash_password, verify_password};
+///
+/// let hash = hash_password("hunter2").unwrap();
+/// assert!(hash.starts_with("$argon2id$"));
+/// assert!(verify_password(&hash, "hunter2"));
+/// assert!(!verify_password(&hash, "hunter3"));
+/// ```
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let config = Config { variant: Variant::Argon2id, ..Config::default() };
+    argon2::hash_encoded(password.as_bytes(), &salt, &config).map_err(AuthError::Hash)
+}
+
+/// Returns `true` if `password` matches `hash`, a hash returned by
+/// [`hash_password()`] or another Argon2 hash in the PHC string format.
+/// Returns `false` if `hash` is malformed.
+///
+/// As with [`hash_password()`], call this function from a blocking task in
+/// async handlers.
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    argon2::verify_encoded(hash, password.as_bytes()).unwrap_or(false)
+}
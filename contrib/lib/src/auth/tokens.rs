@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+use super::AuthError;
+
+/// Trait implemented by stores of remember-me tokens.
+///
+/// A remember-me token is a random string, kept in a private cookie, that
+/// identifies a record in the store holding the ID of the user it logs back
+/// in. Tokens are valid only while their record exists: logging out removes
+/// the record, so a copy of the cookie can't log the user back in. Records
+/// must be deleted once they expire.
+///
+/// [`MemoryTokenStore`] is used unless another store is passed to
+/// [`Authentication::with_store()`](super::Authentication::with_store()).
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket_contrib::auth::{AuthError, TokenStore};
+///
+/// struct Remote { /* .. */ }
+///
+/// #[rocket::async_trait]
+/// impl TokenStore for Remote {
+///     async fn load(&self, token: &str) -> Result<Option<String>, AuthError> {
+///         /* retrieve the user ID of `token`, if it hasn't expired */
+///         # Ok(None)
+///     }
+///
+///     async fn save(&self, token: &str, id: &str, ttl: Duration) -> Result<(), AuthError> {
+///         /* save `id` under `token`, expiring in `ttl` */
+///         # Ok(())
+///     }
+///
+///     async fn remove(&self, token: &str) -> Result<(), AuthError> {
+///         /* delete the record of `token` */
+///         # Ok(())
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait TokenStore: Send + Sync + 'static {
+    /// Returns the ID of the user of the token `token`, or `None` if there is
+    /// no such token or it has expired.
+    async fn load(&self, token: &str) -> Result<Option<String>, AuthError>;
+
+    /// Saves the token `token` of the user `id`, expiring in `ttl`.
+    async fn save(&self, token: &str, id: &str, ttl: Duration) -> Result<(), AuthError>;
+
+    /// Removes the token `token`, if there is one.
+    async fn remove(&self, token: &str) -> Result<(), AuthError>;
+}
+
+/// How often a [`MemoryTokenStore`] sweeps expired tokens.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A store holding remember-me tokens in memory.
+///
+/// Tokens are lost when the application exits, logging remembered users out,
+/// and aren't shared between processes. Expired tokens are swept at most once
+/// a minute, when a token is saved.
+#[derive(Debug)]
+pub struct MemoryTokenStore {
+    tokens: RwLock<HashMap<String, (String, Instant)>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl MemoryTokenStore {
+    /// Returns an empty store.
+    pub fn new() -> MemoryTokenStore {
+        MemoryTokenStore { tokens: RwLock::default(), last_sweep: Mutex::new(Instant::now()) }
+    }
+
+    /// Returns the number of tokens in the store that haven't expired.
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        let tokens = self.tokens.read().expect("token store lock");
+        tokens.values().filter(|(_, expires)| *expires > now).count()
+    }
+
+    /// Returns `true` if every token in the store has expired.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryTokenStore {
+    fn default() -> Self {
+        MemoryTokenStore::new()
+    }
+}
+
+#[rocket::async_trait]
+impl TokenStore for MemoryTokenStore {
+    async fn load(&self, token: &str) -> Result<Option<String>, AuthError> {
+        let tokens = self.tokens.read().expect("token store lock");
+        Ok(tokens.get(token)
+            .filter(|(_, expires)| *expires > Instant::now())
+            .map(|(id, _)| id.clone()))
+    }
+
+    async fn save(&self, token: &str, id: &str, ttl: Duration) -> Result<(), AuthError> {
+        let now = Instant::now();
+        let mut tokens = self.tokens.write().expect("token store lock");
+        tokens.insert(token.to_string(), (id.to_string(), now + ttl));
+
+        let mut last_sweep = self.last_sweep.lock().expect("token sweep lock");
+        if now.duration_since(*last_sweep) >= SWEEP_INTERVAL {
+            tokens.retain(|_, (_, expires)| *expires > now);
+            *last_sweep = now;
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, token: &str) -> Result<(), AuthError> {
+        self.tokens.write().expect("token store lock").remove(token);
+        Ok(())
+    }
+}
+
+/// Returns a new random token.
+pub(super) fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
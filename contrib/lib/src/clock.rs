@@ -0,0 +1,6 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns the current time in seconds since the Unix epoch.
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
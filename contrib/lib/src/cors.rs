@@ -0,0 +1,597 @@
+//! Cross-Origin Resource Sharing (CORS) for Rocket applications.
+//!
+//! The [`Cors`] fairing adds the `Access-Control-*` headers that allow browsers
+//! to make cross-origin requests to an application. It answers preflight
+//! `OPTIONS` requests automatically and sets `Vary` so that caches keep
+//! responses for different origins apart.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `cors` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["cors"]
+//! ```
+//!
+//! # Configuration
+//!
+//! [`Cors::fairing()`] reads its policy from the `cors` table in `Rocket.toml`:
+//!
+//! ```toml
+//! [global.cors]
+//! allowed_origins = ["https://example.com", "https://www.example.com"]
+//! allowed_methods = ["GET", "POST", "DELETE"]
+//! allowed_headers = ["Content-Type", "Authorization"]
+//! expose_headers = ["X-Total-Count"]
+//! allow_credentials = true
+//! max_age = 3600
+//! base = "/api"
+//! ```
+//!
+//! Every key is optional; missing keys take the value of [`Cors::new()`].
+//! `allowed_origins` and `allowed_headers` may also be the string `"*"` to
+//! allow any origin or any header. Credentials may only be allowed with a list
+//! of origins: `allow_credentials = true` with `allowed_origins = "*"` is
+//! rejected.
+//!
+//! A policy can also be built directly, which allows scoping different
+//! policies to different mount points:
+//!
+//! ```rust
+//! # extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket::http::Method;
+//! use rocket_contrib::cors::Cors;
+//!
+//! let public = Cors::new().base("/public");
+//! let api = Cors::new()
+//!     .base("/api")
+//!     .allow_origin("https://example.com")
+//!     .allow_methods(vec![Method::Get, Method::Post])
+//!     .allow_credentials(true);
+//!
+//! rocket::ignite()
+//!     .attach(public)
+//!     .attach(api)
+//! # ;
+//! ```
+
+use std::str::FromStr;
+
+use rocket::{Config, Request, Response, Rocket};
+use rocket::config::{ConfigError, Value};
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::http::uncased::UncasedStr;
+
+/// The methods allowed by [`Cors::new()`].
+const DEFAULT_METHODS: &[Method] = &[
+    Method::Get, Method::Head, Method::Post, Method::Put, Method::Patch, Method::Delete
+];
+
+/// A set of allowed values: either any value or those listed.
+#[derive(Debug, Clone, PartialEq)]
+enum Allowed {
+    Any,
+    Some(Vec<String>),
+}
+
+impl Allowed {
+    fn allows(&self, value: &str) -> bool {
+        match self {
+            Allowed::Any => true,
+            Allowed::Some(values) => values.iter()
+                .any(|v| UncasedStr::new(v) == UncasedStr::new(value))
+        }
+    }
+
+    fn add(&mut self, value: String) {
+        match self {
+            Allowed::Any => *self = Allowed::Some(vec![value]),
+            Allowed::Some(values) => values.push(value),
+        }
+    }
+}
+
+/// A fairing that implements a Cross-Origin Resource Sharing policy.
+///
+/// A `Cors` policy applies to requests whose path lies under its
+/// [`base()`](Cors::base()), `/` by default, and which carry an `Origin`
+/// header naming an allowed origin. For such requests:
+///
+///   * **Preflight** requests, `OPTIONS` requests with an
+///     `Access-Control-Request-Method` header, receive
+///     `Access-Control-Allow-Methods`, `Access-Control-Allow-Headers`, and
+///     `Access-Control-Max-Age` headers if the requested method and headers
+///     are allowed. If no route handles the request, the `404` response is
+///     replaced with an empty `204 No Content` response.
+///   * **Actual** requests receive `Access-Control-Expose-Headers`.
+///
+/// Both receive `Access-Control-Allow-Origin` and, if enabled,
+/// `Access-Control-Allow-Credentials`. When any origin is allowed, the allowed
+/// origin is `*`; otherwise, the request's origin is echoed and `Origin` is
+/// added to the response's `Vary` header, merged with any existing values.
+/// Requests that fall outside of the policy are left untouched.
+///
+/// Credentials may only be allowed for listed origins: a policy allowing them
+/// from any origin would let every website make credentialed requests. Such a
+/// policy fails to attach and, if attached regardless, never allows an origin.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::cors::Cors;
+///
+/// let cors = Cors::new()
+///     .allow_origin("https://example.com")
+///     .allow_headers(vec!["Content-Type", "Authorization"])
+///     .expose_headers(vec!["X-Total-Count"])
+///     .max_age(3600);
+///
+/// rocket::ignite().attach(cors)
+/// # ;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cors {
+    base: String,
+    origins: Allowed,
+    methods: Vec<Method>,
+    headers: Allowed,
+    expose: Vec<String>,
+    credentials: bool,
+    max_age: Option<u64>,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Cors::new()
+    }
+}
+
+impl Cors {
+    /// Returns a permissive policy for all paths: any origin may make `GET`,
+    /// `HEAD`, `POST`, `PUT`, `PATCH`, and `DELETE` requests with any headers,
+    /// without credentials. No headers are exposed, and no max-age is sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new();
+    /// ```
+    pub fn new() -> Cors {
+        Cors {
+            base: "/".into(),
+            origins: Allowed::Any,
+            methods: DEFAULT_METHODS.to_vec(),
+            headers: Allowed::Any,
+            expose: vec![],
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Returns a fairing that reads the policy from the `cors` configuration
+    /// table and attaches it. If the table is missing, the policy of
+    /// [`Cors::new()`] is used. If the table is invalid, an error is logged
+    /// and launch is aborted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// rocket::ignite().attach(Cors::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach("CORS Configuration", |mut rocket: Rocket| async move {
+            let result = Cors::from_config(rocket.config().await);
+            match result {
+                Ok(cors) => Ok(rocket.attach(cors)),
+                Err(e) => {
+                    e.pretty_print();
+                    error_!("Invalid CORS configuration.");
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Reads a policy from the `cors` table in `config`. Keys that are missing
+    /// from the table, or a missing table, take the value of [`Cors::new()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `BadType` error if a value has the wrong type, a method is
+    /// invalid, or credentials are allowed from any origin.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, Value};
+    /// use rocket::http::Method;
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let mut table = rocket::config::Table::new();
+    /// table.insert("allowed_origins".into(), Value::from(vec!["https://a.com"]));
+    /// table.insert("allowed_methods".into(), Value::from(vec!["GET"]));
+    ///
+    /// let config = Config::build(Environment::Development)
+    ///     .extra("cors", table)
+    ///     .unwrap();
+    ///
+    /// let expected = Cors::new()
+    ///     .allow_origin("https://a.com")
+    ///     .allow_methods(vec![Method::Get]);
+    ///
+    /// assert_eq!(Cors::from_config(&config).unwrap(), expected);
+    /// ```
+    pub fn from_config(config: &Config) -> Result<Cors, ConfigError> {
+        let table = match config.get_table("cors") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(Cors::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut cors = Cors::new();
+        for (key, value) in table {
+            match key.as_str() {
+                "allowed_origins" => cors.origins = allowed(key, value)?,
+                "allowed_headers" => cors.headers = allowed(key, value)?,
+                "allowed_methods" => {
+                    cors.methods = strings(key, value)?.iter()
+                        .map(|m| Method::from_str(m).map_err(|_| bad_type(key, value)))
+                        .collect::<Result<_, _>>()?;
+                }
+                "expose_headers" => cors.expose = strings(key, value)?,
+                "allow_credentials" => {
+                    cors.credentials = value.as_bool().ok_or_else(|| bad_type(key, value))?;
+                }
+                "max_age" => match value.as_integer() {
+                    Some(age) if age >= 0 => cors.max_age = Some(age as u64),
+                    _ => return Err(bad_type(key, value)),
+                },
+                "base" => {
+                    let base = value.as_str().ok_or_else(|| bad_type(key, value))?;
+                    cors = cors.base(base);
+                }
+                _ => warn_!("Ignoring unknown CORS configuration key '{}'.", key),
+            }
+        }
+
+        if cors.credentials_from_any_origin() {
+            let key = "cors.allow_credentials".into();
+            let expected = "`false` when `allowed_origins` is \"*\"";
+            return Err(ConfigError::BadType(key, expected, "true", None));
+        }
+
+        Ok(cors)
+    }
+
+    /// Scopes the policy to requests whose path is `base` or lies under
+    /// `base`, typically the mount point of the routes to expose.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// // Applies to `/api` and `/api/users`, but not to `/apiary`.
+    /// let cors = Cors::new().base("/api");
+    /// ```
+    pub fn base<S: Into<String>>(mut self, base: S) -> Self {
+        let base = base.into();
+        self.base = match base.trim_end_matches('/') {
+            "" => "/".into(),
+            trimmed => trimmed.into(),
+        };
+
+        self
+    }
+
+    /// Adds `origin` to the allowed origins. The first call replaces the
+    /// default of allowing any origin.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new()
+    ///     .allow_origin("https://example.com")
+    ///     .allow_origin("https://www.example.com");
+    /// ```
+    pub fn allow_origin<S: Into<String>>(mut self, origin: S) -> Self {
+        self.origins.add(origin.into());
+        self
+    }
+
+    /// Allows requests from any origin. This is the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().allow_any_origin();
+    /// ```
+    pub fn allow_any_origin(mut self) -> Self {
+        self.origins = Allowed::Any;
+        self
+    }
+
+    /// Sets the methods that cross-origin requests may use.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Method;
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().allow_methods(vec![Method::Get, Method::Post]);
+    /// ```
+    pub fn allow_methods<I: IntoIterator<Item = Method>>(mut self, methods: I) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Sets the request headers that cross-origin requests may send. By
+    /// default, any header is allowed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().allow_headers(vec!["Content-Type", "Authorization"]);
+    /// ```
+    pub fn allow_headers<I, S>(mut self, headers: I) -> Self
+        where I: IntoIterator<Item = S>, S: Into<String>
+    {
+        self.headers = Allowed::Some(headers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allows cross-origin requests to send any header. This is the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().allow_any_header();
+    /// ```
+    pub fn allow_any_header(mut self) -> Self {
+        self.headers = Allowed::Any;
+        self
+    }
+
+    /// Sets the response headers that browsers expose to cross-origin
+    /// scripts, in addition to the CORS-safelisted response headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().expose_headers(vec!["X-Total-Count"]);
+    /// ```
+    pub fn expose_headers<I, S>(mut self, headers: I) -> Self
+        where I: IntoIterator<Item = S>, S: Into<String>
+    {
+        self.expose = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether cross-origin requests may include credentials such as
+    /// cookies. Disabled by default. Credentials may only be allowed together
+    /// with a list of origins; a policy that allows them from any origin fails
+    /// to attach.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new()
+    ///     .allow_origin("https://example.com")
+    ///     .allow_credentials(true);
+    /// ```
+    pub fn allow_credentials(mut self, enabled: bool) -> Self {
+        self.credentials = enabled;
+        self
+    }
+
+    /// Sets the number of seconds for which browsers may cache the result of
+    /// a preflight request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::cors::Cors;
+    ///
+    /// let cors = Cors::new().max_age(86400);
+    /// ```
+    pub fn max_age(mut self, seconds: u64) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Returns `true` if `path` lies under `self.base`.
+    fn in_scope(&self, path: &str) -> bool {
+        self.base == "/" || path == self.base
+            || (path.starts_with(&self.base) && path[self.base.len()..].starts_with('/'))
+    }
+
+    /// Returns `true` if the response depends on the request's origin, so that
+    /// `Vary: Origin` must be set.
+    fn echoes_origin(&self) -> bool {
+        self.origins != Allowed::Any
+    }
+
+    /// Returns `true` if the policy allows credentials from any origin, which
+    /// is rejected.
+    fn credentials_from_any_origin(&self) -> bool {
+        self.credentials && self.origins == Allowed::Any
+    }
+
+    /// Sets the preflight headers on `res` if the requested method and headers
+    /// are allowed. Returns `true` if they are.
+    fn preflight(&self, method: &str, req: &Request<'_>, res: &mut Response<'_>) -> bool {
+        let requested_headers: Vec<&str> = req.headers()
+            .get("Access-Control-Request-Headers")
+            .flat_map(|v| v.split(','))
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .collect();
+
+        let method_allowed = Method::from_str(method)
+            .map_or(false, |m| self.methods.contains(&m));
+
+        if !method_allowed || !requested_headers.iter().all(|h| self.headers.allows(h)) {
+            return false;
+        }
+
+        if res.status() == Status::NotFound {
+            res.set_status(Status::NoContent);
+            res.remove_header("Content-Type");
+            let _ = res.take_body();
+        }
+
+        let methods: Vec<_> = self.methods.iter().map(|m| m.as_str()).collect();
+        res.set_header(Header::new("Access-Control-Allow-Methods", methods.join(", ")));
+        if !requested_headers.is_empty() {
+            let headers = match self.headers {
+                Allowed::Any => requested_headers.join(", "),
+                Allowed::Some(ref headers) => headers.join(", "),
+            };
+
+            res.set_header(Header::new("Access-Control-Allow-Headers", headers));
+        }
+
+        if let Some(age) = self.max_age {
+            res.set_header(Header::new("Access-Control-Max-Age", age.to_string()));
+        }
+
+        true
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Attach | Kind::Response,
+        }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        if self.credentials_from_any_origin() {
+            error_!("CORS policy for '{}' allows credentials from any origin.", self.base);
+            info_!("List the allowed origins or disable credentials.");
+            return Err(rocket);
+        }
+
+        Ok(rocket)
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !self.in_scope(req.uri().path()) {
+            return;
+        }
+
+        let preflight_method = match req.method() {
+            Method::Options => req.headers().get_one("Access-Control-Request-Method"),
+            _ => None
+        };
+
+        if self.echoes_origin() {
+            let mut vary = vec!["Origin"];
+            if preflight_method.is_some() {
+                vary.push("Access-Control-Request-Method");
+                vary.push("Access-Control-Request-Headers");
+            }
+
+            merge_vary(res, &vary);
+        }
+
+        let origin = match req.headers().get_one("Origin") {
+            Some(_) if self.credentials_from_any_origin() => return,
+            Some(origin) if self.origins.allows(origin) => origin,
+            _ => return,
+        };
+
+        if let Some(method) = preflight_method {
+            if !self.preflight(method, req, res) {
+                return;
+            }
+        } else if !self.expose.is_empty() {
+            let exposed = self.expose.join(", ");
+            res.set_header(Header::new("Access-Control-Expose-Headers", exposed));
+        }
+
+        let allowed_origin = if self.echoes_origin() { origin } else { "*" };
+
+        res.set_header(Header::new("Access-Control-Allow-Origin", allowed_origin.to_string()));
+        if self.credentials {
+            res.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+    }
+}
+
+/// Adds `names` to the `Vary` header of `res`, keeping existing values and
+/// skipping names that are already present.
+fn merge_vary(res: &mut Response<'_>, names: &[&str]) {
+    let mut vary: Vec<String> = res.headers().get("Vary")
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+        .collect();
+
+    for name in names {
+        if !vary.iter().any(|v| UncasedStr::new(v) == UncasedStr::new(name)) {
+            vary.push(name.to_string());
+        }
+    }
+
+    res.set_header(Header::new("Vary", vary.join(", ")));
+}
+
+fn bad_type(key: &str, value: &Value) -> ConfigError {
+    let expected = match key {
+        "allowed_origins" | "allowed_headers" => "\"*\" or an array of strings",
+        "allowed_methods" => "an array of HTTP methods",
+        "allow_credentials" => "a boolean",
+        "max_age" => "a non-negative integer",
+        "base" => "a string",
+        _ => "an array of strings",
+    };
+
+    ConfigError::BadType(format!("cors.{}", key), expected, value.type_str(), None)
+}
+
+fn strings(key: &str, value: &Value) -> Result<Vec<String>, ConfigError> {
+    value.as_array()
+        .and_then(|array| array.iter().map(|v| v.as_str().map(String::from)).collect())
+        .ok_or_else(|| bad_type(key, value))
+}
+
+fn allowed(key: &str, value: &Value) -> Result<Allowed, ConfigError> {
+    match value.as_str() {
+        Some("*") => Ok(Allowed::Any),
+        Some(_) => Err(bad_type(key, value)),
+        None => strings(key, value).map(Allowed::Some),
+    }
+}
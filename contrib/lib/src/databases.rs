@@ -196,7 +196,7 @@
 //! retrieves a connection from the database pool or fails with a
 //! `Status::ServiceUnavailable` if connecting to the database times out.
 //!
-//! The macro will also generate two inherent methods on the decorated type:
+//! The macro will also generate three inherent methods on the decorated type:
 //!
 //!   * `fn fairing() -> impl Fairing`
 //!
@@ -208,6 +208,13 @@
 //!     Retrieves a connection wrapper from the configured pool. Returns `Some`
 //!     as long as `Self::fairing()` has been attached.
 //!
+//!   * `fn migrate(F) -> impl Fairing`
+//!
+//!     Returns a fairing that runs the migrations function `F` on a
+//!     connection from the pool when it is attached, aborting launch if it
+//!     fails. It must be attached after `Self::fairing()`. See [`Migrate`] for
+//!     details.
+//!
 //! The attribute can only be applied to unit-like structs with one type. The
 //! internal type of the structure must implement [`Poolable`].
 //!
@@ -370,6 +377,160 @@
 //! implementing the [`Poolable`] trait. See the documentation for [`Poolable`]
 //! for more details on how to implement it.
 //!
+//! # Async Pools
+//!
+//! The databases above are accessed through synchronous drivers, and each
+//! connection is used on a thread dedicated to blocking work. Databases with
+//! asynchronous drivers can instead be managed natively with
+//! `#[derive(Database)]`, which implements the [`Database`] trait for a type
+//! wrapping a [`Pool`]:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # #[cfg(feature = "sqlx_sqlite_pool")]
+//! # mod test {
+//! use rocket_contrib::databases::{Connection, Database, sqlx};
+//!
+//! #[derive(Database)]
+//! #[database("sqlite_logs")]
+//! struct Logs(sqlx::SqlitePool);
+//!
+//! #[get("/logs/<id>")]
+//! async fn get_log(mut db: Connection<Logs>, id: i64) -> Option<String> {
+//!     sqlx::query_as::<_, (String,)>("SELECT content FROM logs WHERE id = ?")
+//!         .bind(id)
+//!         .fetch_one(&mut *db)
+//!         .await
+//!         .ok()
+//!         .map(|(content,)| content)
+//! }
+//!
+//! #[launch]
+//! fn rocket() -> rocket::Rocket {
+//!     rocket::ignite()
+//!         .attach(Logs::init())
+//!         .mount("/", routes![get_log])
+//! }
+//! # } fn main() {}
+//! ```
+//!
+//! Async pools are configured exactly like the pools above: the name in the
+//! `#[database]` attribute is the key of the database's configuration in the
+//! `databases` table, whose `url` and `pool_size` are used to initialize the
+//! pool. The [`Connection`] request guard retrieves a connection from the
+//! pool without blocking a worker thread, and the pool itself can be used as
+//! managed state of the derived type.
+//!
+//! Note that the `#[database]` attribute of `#[derive(Database)]` conflicts
+//! with the `#[database]` attribute macro for synchronous databases when the
+//! latter is in scope, for instance via `#[macro_use] extern crate
+//! rocket_contrib`. Import the macros used by a module explicitly to use both
+//! kinds of databases in one module.
+//!
+//! | Kind     | Driver            | Version | `Pool` Type                  | Feature                  |
+//! |----------|-------------------|---------|------------------------------|--------------------------|
+//! | Postgres | [`sqlx`]          | `0.4`   | [`sqlx::PgPool`]             | `sqlx_postgres_pool`     |
+//! | MySQL    | [`sqlx`]          | `0.4`   | [`sqlx::MySqlPool`]          | `sqlx_mysql_pool`        |
+//! | Sqlite   | [`sqlx`]          | `0.4`   | [`sqlx::SqlitePool`]         | `sqlx_sqlite_pool`       |
+//! | Postgres | [`deadpool`]      | `0.5`   | [`deadpool_postgres::Pool`]  | `deadpool_postgres_pool` |
+//! | MongoDB  | [`mongodb`]       | `1.1`   | [`mongodb::Client`]          | `mongodb_pool`           |
+//! | Redis    | [`redis`]         | `0.17`  | [`redis::ConnectionManager`] | `redis_pool`             |
+//!
+//! [`sqlx`]: https://github.com/launchbadge/sqlx
+//! [`sqlx::PgPool`]: https://docs.rs/sqlx/0.4/sqlx/type.PgPool.html
+//! [`sqlx::MySqlPool`]: https://docs.rs/sqlx/0.4/sqlx/type.MySqlPool.html
+//! [`sqlx::SqlitePool`]: https://docs.rs/sqlx/0.4/sqlx/type.SqlitePool.html
+//! [`deadpool`]: https://github.com/bikeshedder/deadpool
+//! [`deadpool_postgres::Pool`]: https://docs.rs/deadpool-postgres/0.5/deadpool_postgres/type.Pool.html
+//! [`mongodb`]: https://github.com/mongodb/mongo-rust-driver
+//! [`mongodb::Client`]: https://docs.rs/mongodb/1.1/mongodb/struct.Client.html
+//! [`redis`]: https://github.com/mitsuhiko/redis-rs
+//! [`redis::ConnectionManager`]: https://docs.rs/redis/0.17/redis/aio/struct.ConnectionManager.html
+//!
+//! Support for other asynchronous databases can be added by implementing
+//! [`Pool`].
+//!
+//! With the `redis_pool` feature, the [`Cache`] request guard caches
+//! serializable values with an optional time-to-live in a Redis database.
+//!
+//! ## Multiple Databases
+//!
+//! Any number of databases, including several using the same driver, can be
+//! used at once. Each is declared with its own type and configuration, and its
+//! pool, fairings, and guards are independent of the others'. For instance, to
+//! send writes to a primary database and reads to a replica:
+//!
+//! ```toml
+//! [global.databases]
+//! primary = { url = "postgres://primary.example.com/app", pool_size = 16 }
+//! read_replica = { url = "postgres://replica.example.com/app", pool_size = 64 }
+//! ```
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # #[cfg(feature = "sqlx_postgres_pool")]
+//! # mod test {
+//! use rocket_contrib::databases::{Connection, Database, sqlx};
+//!
+//! #[derive(Database)]
+//! #[database("primary")]
+//! struct Primary(sqlx::PgPool);
+//!
+//! #[derive(Database)]
+//! #[database("read_replica")]
+//! struct ReadReplica(sqlx::PgPool);
+//!
+//! #[get("/users/<id>")]
+//! async fn get_user(mut db: Connection<ReadReplica>, id: i64) -> Option<String> {
+//!     # let _ = (&mut db, id); None
+//!     /* .. */
+//! }
+//!
+//! #[delete("/users/<id>")]
+//! async fn delete_user(mut db: Connection<Primary>, id: i64) {
+//!     # let _ = (&mut db, id);
+//!     /* .. */
+//! }
+//!
+//! #[launch]
+//! fn rocket() -> rocket::Rocket {
+//!     rocket::ignite()
+//!         .attach(Primary::init())
+//!         .attach(ReadReplica::init())
+//!         .mount("/", routes![get_user, delete_user])
+//! }
+//! # } fn main() {}
+//! ```
+//!
+//! The [`Routed`] request guard combines a primary and a replica database,
+//! retrieving a connection to the replica for reads and to the primary for
+//! writes and when the replica is unavailable.
+//!
+//! ## Transactions
+//!
+//! The [`Tx`] request guard begins a transaction on a database whose pool
+//! implements [`Transactional`], as the `sqlx` pools do. The transaction is
+//! committed when the response status is `2xx` or `3xx` and rolled back
+//! otherwise, once the [`Tx::fairing()`] is attached.
+//!
+//! ## Monitoring
+//!
+//! [`Database::stats()`] returns the statistics of a database's pool: the
+//! number of idle and active connections, and the time spent waiting for a
+//! connection by request guards. The fairing returned by
+//! [`Database::health_check()`] periodically checks that connections to the
+//! database can be made and used. Its reports are passed to user-supplied
+//! callbacks and are available as [`Health`] managed state.
+//!
+//! ## Migrations
+//!
+//! The fairing returned by [`Database::migrate()`] applies embedded
+//! migrations, such as those created by `sqlx::migrate!()` or `refinery`, when
+//! it is attached. Databases declared with `#[database]`, such as `diesel`
+//! connections, apply migrations with their generated `migrate()` method.
+//! Launch is aborted if the migrations can't be applied. See [`Migrate`] for the
+//! supported kinds of migrations and [`Migrations`] for its configuration.
+//!
 //! [`FromRequest`]: rocket::request::FromRequest
 //! [request guards]: rocket::request::FromRequest
 //! [`Poolable`]: crate::databases::Poolable
@@ -409,6 +570,35 @@ use self::r2d2::ManageConnection;
 #[cfg(feature = "memcache_pool")] pub extern crate memcache;
 #[cfg(feature = "memcache_pool")] pub extern crate r2d2_memcache;
 
+#[cfg(any(feature = "sqlx_postgres_pool",
+          feature = "sqlx_mysql_pool",
+          feature = "sqlx_sqlite_pool"))]
+pub extern crate sqlx;
+
+#[cfg(feature = "deadpool_postgres_pool")] pub extern crate deadpool_postgres;
+
+#[cfg(feature = "mongodb_pool")] pub extern crate mongodb;
+
+#[cfg(feature = "refinery_migrations")] pub extern crate refinery;
+
+#[cfg(feature = "redis_pool")] pub extern crate redis;
+
+mod pool;
+mod database;
+mod tx;
+mod migrate;
+mod health;
+mod replica;
+#[cfg(feature = "redis_pool")] mod cache;
+
+pub use self::pool::{Pool, PoolStatus};
+pub use self::database::{Database, Initializer, Connection};
+pub use self::tx::{Transactional, Tx};
+pub use self::migrate::{Migrate, Migrations};
+pub use self::health::{PoolStats, HealthCheck, HealthReport, Health};
+pub use self::replica::{Routed, Role, Policy, ByMethod, ReadOnly, ReadWrite};
+#[cfg(feature = "redis_pool")] pub use self::cache::{Cache, CacheError};
+
 /// A structure representing a particular database configuration.
 ///
 /// For the following configuration:
@@ -785,7 +975,7 @@ impl Poolable for memcache::Client {
 /// This type is implemented here instead of in generated code to ensure all
 /// types are properly checked.
 #[doc(hidden)]
-pub struct ConnectionPool<K, C: Poolable> {
+pub struct SyncConnectionPool<K, C: Poolable> {
     pool: r2d2::Pool<C::Manager>,
     semaphore: Arc<Semaphore>,
     _marker: PhantomData<fn() -> K>,
@@ -796,7 +986,7 @@ pub struct ConnectionPool<K, C: Poolable> {
 /// This type is implemented here instead of in generated code to ensure all
 /// types are properly checked.
 #[doc(hidden)]
-pub struct Connection<K, C: Poolable> {
+pub struct SyncConnection<K, C: Poolable> {
     connection: Arc<Mutex<Option<r2d2::PooledConnection<C::Manager>>>>,
     permit: Option<OwnedSemaphorePermit>,
     _marker: PhantomData<fn() -> K>,
@@ -815,7 +1005,7 @@ async fn run_blocking<F, R>(job: F) -> R
     }
 }
 
-impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
+impl<K: 'static, C: Poolable> SyncConnectionPool<K, C> {
     pub fn fairing(fairing_name: &'static str, config_name: &'static str) -> impl Fairing {
         AdHoc::on_attach(fairing_name, move |mut rocket| async move {
             let config = database_config(config_name, rocket.config().await);
@@ -823,7 +1013,7 @@ impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
 
             match pool {
                 Ok((size, Ok(pool))) => {
-                    let managed = ConnectionPool::<K, C> {
+                    let managed = SyncConnectionPool::<K, C> {
                         pool,
                         semaphore: Arc::new(Semaphore::new(size as usize)),
                         _marker: PhantomData,
@@ -846,7 +1036,41 @@ impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
         })
     }
 
-    async fn get(&self) -> Result<Connection<K, C>, ()> {
+    pub fn migrate<F, E>(config_name: &'static str, migrations: F) -> impl Fairing
+        where F: FnOnce(&mut C) -> Result<(), E> + Send + 'static,
+              E: Display + Send + 'static,
+    {
+        AdHoc::on_attach("Database Migrations", move |mut rocket| async move {
+            match self::migrate::migrations_enabled(config_name, rocket.config().await) {
+                Ok(true) => (),
+                Ok(false) => return Ok(rocket),
+                Err(()) => return Err(rocket),
+            }
+
+            let result = match rocket.state::<Self>().await {
+                Some(pool) => match pool.get().await {
+                    Ok(conn) => conn.run(migrations).await.map_err(|e| e.to_string()),
+                    Err(()) => Err("failed to get a database connection".to_string()),
+                },
+                None => Err(format!("the database fairing for `{}` must be attached first",
+                    std::any::type_name::<K>())),
+            };
+
+            match result {
+                Ok(()) => {
+                    info!("Applied migrations for '{}'.", config_name);
+                    Ok(rocket)
+                }
+                Err(e) => {
+                    error!("Failed to apply migrations for '{}'", config_name);
+                    error_!("{}", e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    async fn get(&self) -> Result<SyncConnection<K, C>, ()> {
         // TODO: Make timeout configurable.
         let permit = match tokio::time::timeout(
             std::time::Duration::from_secs(5),
@@ -862,7 +1086,7 @@ impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
         // TODO: Make timeout configurable.
         let pool = self.pool.clone();
         match run_blocking(move || pool.get_timeout(std::time::Duration::from_secs(5))).await {
-            Ok(c) => Ok(Connection {
+            Ok(c) => Ok(SyncConnection {
                 connection: Arc::new(Mutex::new(Some(c))),
                 permit: Some(permit),
                 _marker: PhantomData,
@@ -875,7 +1099,7 @@ impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
     }
 
     #[inline]
-    pub async fn get_one(cargo: &rocket::Cargo) -> Option<Connection<K, C>> {
+    pub async fn get_one(cargo: &rocket::Cargo) -> Option<SyncConnection<K, C>> {
         match cargo.state::<Self>() {
             Some(pool) => pool.get().await.ok(),
             None => {
@@ -886,7 +1110,7 @@ impl<K: 'static, C: Poolable> ConnectionPool<K, C> {
     }
 }
 
-impl<K: 'static, C: Poolable> Connection<K, C> {
+impl<K: 'static, C: Poolable> SyncConnection<K, C> {
     #[inline]
     pub async fn run<F, R>(&self, f: F) -> R
         where F: FnOnce(&mut C) -> R + Send + 'static,
@@ -901,7 +1125,7 @@ impl<K: 'static, C: Poolable> Connection<K, C> {
     }
 }
 
-impl<K, C: Poolable> Drop for Connection<K, C> {
+impl<K, C: Poolable> Drop for SyncConnection<K, C> {
     fn drop(&mut self) {
         let connection = self.connection.clone();
         let permit = self.permit.take();
@@ -920,12 +1144,12 @@ impl<K, C: Poolable> Drop for Connection<K, C> {
 }
 
 #[rocket::async_trait]
-impl<'a, 'r, K: 'static, C: Poolable> FromRequest<'a, 'r> for Connection<K, C> {
+impl<'a, 'r, K: 'static, C: Poolable> FromRequest<'a, 'r> for SyncConnection<K, C> {
     type Error = ();
 
     #[inline]
     async fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
-        match request.managed_state::<ConnectionPool<K, C>>() {
+        match request.managed_state::<SyncConnectionPool<K, C>>() {
             Some(c) => c.get().await.into_outcome(Status::ServiceUnavailable),
             None => {
                 error_!("Missing database fairing for `{}`", std::any::type_name::<K>());
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use rocket::request::{Request, Outcome, FromRequest};
+
+use crate::databases::{Connection, Database};
+use crate::databases::redis::{self, aio::ConnectionManager};
+
+/// A request guard for caching values in the Redis database `D`.
+///
+/// Values are serialized as JSON and stored with an optional time-to-live.
+/// The guard fails in the same way as a [`Connection`] to `D`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # #[cfg(feature = "redis_pool")]
+/// # mod test {
+/// use std::time::Duration;
+/// use rocket_contrib::databases::{Cache, Database, redis};
+///
+/// #[derive(Database)]
+/// #[database("redis")]
+/// struct Redis(redis::aio::ConnectionManager);
+///
+/// # async fn compute_report(id: u64) -> Vec<u64> { vec![id] }
+/// #[get("/reports/<id>")]
+/// async fn report(cache: Cache<Redis>, id: u64) -> Option<String> {
+///     let key = format!("report:{}", id);
+///     if let Ok(Some(report)) = cache.get::<Vec<u64>>(&key).await {
+///         return Some(format!("{:?}", report));
+///     }
+///
+///     let report = compute_report(id).await;
+///     let _ = cache.set(&key, &report, Some(Duration::from_secs(60))).await;
+///     Some(format!("{:?}", report))
+/// }
+/// # }
+/// ```
+pub struct Cache<D: Database<Pool = ConnectionManager>>(Connection<D>);
+
+/// An error returned by a [`Cache`] operation.
+#[derive(Debug)]
+pub enum CacheError {
+    /// The Redis command failed.
+    Redis(redis::RedisError),
+    /// A value couldn't be serialized or deserialized.
+    Serde(serde_json::Error),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Redis(e) => write!(f, "redis error: {}", e),
+            CacheError::Serde(e) => write!(f, "serialization error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheError::Redis(e) => Some(e),
+            CacheError::Serde(e) => Some(e),
+        }
+    }
+}
+
+impl From<redis::RedisError> for CacheError {
+    fn from(error: redis::RedisError) -> Self {
+        CacheError::Redis(error)
+    }
+}
+
+impl From<serde_json::Error> for CacheError {
+    fn from(error: serde_json::Error) -> Self {
+        CacheError::Serde(error)
+    }
+}
+
+impl<D: Database<Pool = ConnectionManager>> Cache<D> {
+    /// Returns the value cached at `key`, or `None` if there is none.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, CacheError> {
+        let value: Option<String> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut (*self.0).clone())
+            .await?;
+
+        match value {
+            Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Caches `value` at `key`, replacing any existing value. If `ttl` is
+    /// `Some`, the value expires after `ttl`, rounded to milliseconds.
+    pub async fn set<T: Serialize + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>
+    ) -> Result<(), CacheError> {
+        let mut command = redis::cmd("SET");
+        command.arg(key).arg(serde_json::to_string(value)?);
+        if let Some(ttl) = ttl {
+            command.arg("PX").arg(ttl.as_millis().max(1) as u64);
+        }
+
+        Ok(command.query_async(&mut (*self.0).clone()).await?)
+    }
+
+    /// Removes the value cached at `key`. Returns `true` if there was one.
+    pub async fn remove(&self, key: &str) -> Result<bool, CacheError> {
+        let removed: u64 = redis::cmd("DEL")
+            .arg(key)
+            .query_async(&mut (*self.0).clone())
+            .await?;
+
+        Ok(removed > 0)
+    }
+
+    /// Returns the underlying connection for other Redis commands.
+    pub fn connection(&self) -> ConnectionManager {
+        (*self.0).clone()
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, D: Database<Pool = ConnectionManager>> FromRequest<'a, 'r> for Cache<D> {
+    type Error = ();
+
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        Connection::<D>::from_request(request).await.map(Cache)
+    }
+}
@@ -0,0 +1,229 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
+
+use rocket::{Cargo, Rocket};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{Request, Outcome, FromRequest};
+use rocket::http::Status;
+
+use crate::databases::{database_config, Pool, Migrate, Migrations};
+use crate::databases::{HealthCheck, PoolStats, health::Metrics};
+
+/// Trait implemented by types that wrap an asynchronous database [`Pool`].
+///
+/// This trait is usually derived with `#[derive(Database)]`, which names the
+/// database in the configuration the pool is initialized from:
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+/// use rocket_contrib::databases::{Database, sqlx};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_logs")]
+/// struct Logs(sqlx::SqlitePool);
+/// # }
+/// ```
+///
+/// The fairing returned by [`Database::init()`] must be attached for the pool
+/// to be initialized. Connections are then retrieved from the pool with the
+/// [`Connection`] request guard, and the pool itself is available as managed
+/// state of type `Self` or via [`Database::fetch()`].
+pub trait Database: From<Self::Pool> + DerefMut<Target = Self::Pool> + Send + Sync + 'static {
+    /// The type of the wrapped pool.
+    type Pool: Pool;
+
+    /// The name of the database in the `databases` configuration table.
+    const NAME: &'static str;
+
+    /// Returns a fairing that initializes the database's pool from its
+    /// configuration on attach.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+    /// use rocket_contrib::databases::{Database, sqlx};
+    ///
+    /// #[derive(Database)]
+    /// #[database("sqlite_logs")]
+    /// struct Logs(sqlx::SqlitePool);
+    ///
+    /// fn rocket() -> rocket::Rocket {
+    ///     rocket::ignite().attach(Logs::init())
+    /// }
+    /// # }
+    /// ```
+    fn init() -> Initializer<Self> {
+        Initializer(PhantomData)
+    }
+
+    /// Returns a fairing that applies `migrations` to the database on attach.
+    /// The fairing must be attached after the fairing returned by
+    /// [`Database::init()`]. See [`Migrations`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+    /// use rocket_contrib::databases::{Database, sqlx};
+    ///
+    /// #[derive(Database)]
+    /// #[database("sqlite_logs")]
+    /// struct Logs(sqlx::SqlitePool);
+    ///
+    /// async fn rocket() -> rocket::Rocket {
+    ///     let migrator = sqlx::migrate::Migrator::new(std::path::Path::new("./migrations"))
+    ///         .await
+    ///         .expect("migrations directory");
+    ///
+    ///     rocket::ignite()
+    ///         .attach(Logs::init())
+    ///         .attach(Logs::migrate(migrator))
+    /// }
+    /// # }
+    /// ```
+    fn migrate<M: Migrate<Self::Pool>>(migrations: M) -> Migrations<Self, M> {
+        Migrations::new(migrations)
+    }
+
+    /// Returns the database managed by `cargo` if its [`Initializer`] was
+    /// attached, or `None` otherwise.
+    fn fetch(cargo: &Cargo) -> Option<&Self> {
+        let db = cargo.state::<Self>();
+        if db.is_none() {
+            error_!("Missing database fairing for `{}`", std::any::type_name::<Self>());
+        }
+
+        db
+    }
+
+    /// Returns the statistics of the database's pool if its [`Initializer`]
+    /// was attached to `cargo`, or `None` otherwise.
+    fn stats(cargo: &Cargo) -> Option<PoolStats> {
+        let metrics = cargo.state::<Metrics<Self>>()?;
+        Some(metrics.counters().stats(&**Self::fetch(cargo)?))
+    }
+
+    /// Returns a fairing that checks the health of the database every
+    /// `interval` once Rocket launches. The fairing must be attached after the
+    /// fairing returned by [`Database::init()`]. See [`HealthCheck`] for
+    /// details.
+    fn health_check(interval: Duration) -> HealthCheck<Self> where Self::Pool: Clone {
+        HealthCheck::new(interval)
+    }
+}
+
+/// The fairing that initializes the pool of a [`Database`].
+///
+/// An `Initializer` is created with [`Database::init()`].
+pub struct Initializer<D: Database>(PhantomData<fn() -> D>);
+
+#[rocket::async_trait]
+impl<D: Database> Fairing for Initializer<D> {
+    fn info(&self) -> Info {
+        Info { name: std::any::type_name::<D>(), kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let config = match database_config(D::NAME, rocket.config().await) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Database configuration failure: '{}'", D::NAME);
+                error_!("{}", e);
+                return Err(rocket);
+            }
+        };
+
+        match <D::Pool>::init(config).await {
+            Ok(pool) => Ok(rocket.manage(D::from(pool)).manage(Metrics::<D>::new())),
+            Err(e) => {
+                error!("Failed to initialize pool for '{}'", D::NAME);
+                error_!("{}", e);
+                Err(rocket)
+            }
+        }
+    }
+}
+
+/// A request guard that retrieves a connection from the pool of the database
+/// `D`.
+///
+/// The connection is retrieved asynchronously, without blocking a worker
+/// thread. If no connection can be retrieved, the request fails with a status
+/// of `503 Service Unavailable`. If the [`Initializer`] of `D` was not
+/// attached, it fails with `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+/// use rocket_contrib::databases::{Connection, Database, sqlx};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_logs")]
+/// struct Logs(sqlx::SqlitePool);
+///
+/// #[get("/count")]
+/// async fn count(mut db: Connection<Logs>) -> Option<String> {
+///     let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM logs")
+///         .fetch_one(&mut *db)
+///         .await
+///         .ok()?;
+///
+///     Some(count.to_string())
+/// }
+/// # }
+/// ```
+pub struct Connection<D: Database>(<D::Pool as Pool>::Connection);
+
+impl<D: Database> Connection<D> {
+    /// Consumes `self` and returns the inner connection.
+    pub fn into_inner(self) -> <D::Pool as Pool>::Connection {
+        self.0
+    }
+}
+
+impl<D: Database> Deref for Connection<D> {
+    type Target = <D::Pool as Pool>::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<D: Database> DerefMut for Connection<D> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, D: Database> FromRequest<'a, 'r> for Connection<D> {
+    type Error = ();
+
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        let db = match request.managed_state::<D>() {
+            Some(db) => db,
+            None => {
+                error_!("Missing database fairing for `{}`", std::any::type_name::<D>());
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+
+        let start = Instant::now();
+        let conn = db.get().await;
+        if let Some(metrics) = request.managed_state::<Metrics<D>>() {
+            metrics.counters().record(start, conn.is_ok());
+        }
+
+        match conn {
+            Ok(conn) => Outcome::Success(Connection(conn)),
+            Err(e) => {
+                error_!("Failed to get a database connection: {}", e);
+                Outcome::Failure((Status::ServiceUnavailable, ()))
+            }
+        }
+    }
+}
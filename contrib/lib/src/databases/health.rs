@@ -0,0 +1,281 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use rocket::{Cargo, Rocket};
+use rocket::fairing::{Fairing, Info, Kind};
+
+use crate::databases::{Database, Pool};
+
+/// Statistics about the pool of a [`Database`], as returned by
+/// [`Database::stats()`].
+///
+/// The connection counts are reported by the pool and are `None` for pools
+/// that don't track them. The remaining statistics cover the connections
+/// requested by the [`Connection`](crate::databases::Connection) and
+/// [`Tx`](crate::databases::Tx) guards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+    /// The number of open connections, both idle and in use.
+    pub size: Option<u32>,
+    /// The number of open connections that are not in use.
+    pub idle: Option<u32>,
+    /// The number of open connections that are in use.
+    pub active: Option<u32>,
+    /// The number of connections handed out to guards.
+    pub acquired: u64,
+    /// The number of connection requests that failed.
+    pub failed: u64,
+    /// The total time spent waiting for connections.
+    pub total_wait: Duration,
+    /// The longest time spent waiting for a connection.
+    pub max_wait: Duration,
+}
+
+impl PoolStats {
+    /// Returns the mean time spent waiting for a connection, or zero if no
+    /// connection was requested.
+    pub fn mean_wait(&self) -> Duration {
+        match self.acquired + self.failed {
+            0 => Duration::from_secs(0),
+            n => Duration::from_micros((self.total_wait.as_micros() / n as u128) as u64),
+        }
+    }
+}
+
+/// The connection request counters of the database `D`, managed by its
+/// [`Initializer`](crate::databases::Initializer).
+pub(crate) struct Metrics<D>(Arc<Counters>, PhantomData<fn() -> D>);
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    acquired: AtomicU64,
+    failed: AtomicU64,
+    total_wait_us: AtomicU64,
+    max_wait_us: AtomicU64,
+}
+
+impl<D: Database> Metrics<D> {
+    pub(crate) fn new() -> Self {
+        Metrics(Arc::new(Counters::default()), PhantomData)
+    }
+
+    pub(crate) fn counters(&self) -> Arc<Counters> {
+        self.0.clone()
+    }
+}
+
+impl Counters {
+    /// Records a connection request that started at `start`.
+    pub(crate) fn record(&self, start: Instant, succeeded: bool) {
+        let waited = start.elapsed().as_micros() as u64;
+        self.total_wait_us.fetch_add(waited, Ordering::Relaxed);
+        self.max_wait_us.fetch_max(waited, Ordering::Relaxed);
+        if succeeded {
+            self.acquired.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn stats<P: Pool>(&self, pool: &P) -> PoolStats {
+        let status = pool.status();
+        PoolStats {
+            size: status.map(|s| s.size),
+            idle: status.map(|s| s.idle),
+            active: status.map(|s| s.size.saturating_sub(s.idle)),
+            acquired: self.acquired.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            total_wait: Duration::from_micros(self.total_wait_us.load(Ordering::Relaxed)),
+            max_wait: Duration::from_micros(self.max_wait_us.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// The result of a health check of a [`Database`].
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    /// The name of the database, as in its configuration.
+    pub database: &'static str,
+    /// When the check completed.
+    pub checked_at: Instant,
+    /// The time taken to retrieve and ping a connection if the check
+    /// succeeded, or a description of the failure.
+    pub result: Result<Duration, String>,
+    /// The statistics of the database's pool at the time of the check.
+    pub stats: PoolStats,
+}
+
+impl HealthReport {
+    /// Returns `true` if the check succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// The latest [`HealthReport`] of the database `D`, managed by its
+/// [`HealthCheck`] fairing.
+///
+/// It can be retrieved from managed state, for instance with a
+/// `State<Health<D>>` request guard, to report on the database's health.
+pub struct Health<D> {
+    latest: Arc<RwLock<Option<HealthReport>>>,
+    _db: PhantomData<fn() -> D>,
+}
+
+impl<D> Health<D> {
+    /// Returns the report of the latest health check, or `None` if no check
+    /// has completed yet.
+    pub fn latest(&self) -> Option<HealthReport> {
+        self.latest.read().expect("health lock").clone()
+    }
+}
+
+type Callback = Arc<dyn Fn(&HealthReport) + Send + Sync>;
+
+/// A fairing that periodically checks the health of a [`Database`].
+///
+/// A `HealthCheck` is created with [`Database::health_check()`] and must be
+/// attached after the database's
+/// [`Initializer`](crate::databases::Initializer). Once Rocket launches, it
+/// retrieves a connection from the pool and pings it every `interval`, until
+/// Rocket shuts down. A check that doesn't complete within its
+/// [timeout](HealthCheck::timeout()), five seconds by default, fails. The
+/// result of each check is available as [`Health<D>`] managed state and is
+/// passed to the callbacks registered with [`HealthCheck::on_report()`] and
+/// [`HealthCheck::on_failure()`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+/// use std::time::Duration;
+/// use rocket_contrib::databases::{Database, sqlx};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_logs")]
+/// struct Logs(sqlx::SqlitePool);
+///
+/// fn rocket() -> rocket::Rocket {
+///     let health_check = Logs::health_check(Duration::from_secs(30))
+///         .on_failure(|report| eprintln!("{} is unhealthy", report.database));
+///
+///     rocket::ignite()
+///         .attach(Logs::init())
+///         .attach(health_check)
+/// }
+/// # }
+/// ```
+pub struct HealthCheck<D> {
+    interval: Duration,
+    timeout: Duration,
+    latest: Arc<RwLock<Option<HealthReport>>>,
+    on_report: Vec<Callback>,
+    on_failure: Vec<Callback>,
+    _db: PhantomData<fn() -> D>,
+}
+
+impl<D: Database> HealthCheck<D> where D::Pool: Clone {
+    pub(crate) fn new(interval: Duration) -> Self {
+        HealthCheck {
+            interval,
+            timeout: Duration::from_secs(5),
+            latest: Arc::new(RwLock::new(None)),
+            on_report: vec![],
+            on_failure: vec![],
+            _db: PhantomData,
+        }
+    }
+
+    /// Sets the time a check may take to retrieve and ping a connection before
+    /// it fails. Defaults to five seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Registers `callback` to be called with the report of every check.
+    pub fn on_report<F>(mut self, callback: F) -> Self
+        where F: Fn(&HealthReport) + Send + Sync + 'static
+    {
+        self.on_report.push(Arc::new(callback));
+        self
+    }
+
+    /// Registers `callback` to be called with the report of every failed
+    /// check.
+    pub fn on_failure<F>(mut self, callback: F) -> Self
+        where F: Fn(&HealthReport) + Send + Sync + 'static
+    {
+        self.on_failure.push(Arc::new(callback));
+        self
+    }
+}
+
+async fn check<P: Pool>(pool: &P, timeout: Duration) -> Result<Duration, String> {
+    let start = Instant::now();
+    let ping = async {
+        let mut conn = pool.get().await
+            .map_err(|e| format!("failed to get a connection: {}", e))?;
+
+        P::ping(&mut conn).await.map_err(|e| format!("failed to ping the database: {}", e))
+    };
+
+    match rocket::tokio::time::timeout(timeout, ping).await {
+        Ok(result) => result.map(|_| start.elapsed()),
+        Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+    }
+}
+
+#[rocket::async_trait]
+impl<D: Database> Fairing for HealthCheck<D> where D::Pool: Clone {
+    fn info(&self) -> Info {
+        Info { name: "Database Health Check", kind: Kind::Attach | Kind::Launch }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let health = Health::<D> { latest: self.latest.clone(), _db: PhantomData };
+        Ok(rocket.manage(health))
+    }
+
+    fn on_launch(&self, cargo: &Cargo) {
+        let (pool, counters) = match (D::fetch(cargo), cargo.state::<Metrics<D>>()) {
+            (Some(db), Some(metrics)) => ((**db).clone(), metrics.counters()),
+            _ => {
+                error_!("Database health check for '{}' is disabled.", D::NAME);
+                return;
+            }
+        };
+
+        let (interval, timeout) = (self.interval, self.timeout);
+        let latest = self.latest.clone();
+        let on_report = self.on_report.clone();
+        let on_failure = self.on_failure.clone();
+        let shutdown = cargo.shutdown();
+        rocket::tokio::spawn(async move {
+            loop {
+                let result = check(&pool, timeout).await;
+                let report = HealthReport {
+                    database: D::NAME,
+                    checked_at: Instant::now(),
+                    result,
+                    stats: counters.stats(&pool),
+                };
+
+                if let Err(e) = &report.result {
+                    warn!("Database '{}' is unhealthy: {}", D::NAME, e);
+                    on_failure.iter().for_each(|callback| callback(&report));
+                }
+
+                on_report.iter().for_each(|callback| callback(&report));
+                *latest.write().expect("health lock") = Some(report);
+
+                rocket::tokio::select! {
+                    _ = rocket::tokio::time::delay_for(interval) => continue,
+                    _ = shutdown.clone() => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt::Display;
+
+use crate::databases::DatabaseConfig;
+
+/// Trait implemented by asynchronous database connection pools.
+///
+/// A `Pool` is created from a [`DatabaseConfig`] by the
+/// [`Initializer`](crate::databases::Initializer) fairing of a
+/// [`Database`](crate::databases::Database) and hands out connections to the
+/// [`Connection`](crate::databases::Connection) request guard. Retrieving a
+/// connection never blocks a worker thread.
+///
+/// # Provided Implementations
+///
+/// Implementations of `Pool` are provided for the following types, each
+/// behind the feature listed next to it:
+///
+///   * `sqlx::PgPool` (`sqlx_postgres_pool`)
+///   * `sqlx::MySqlPool` (`sqlx_mysql_pool`)
+///   * `sqlx::SqlitePool` (`sqlx_sqlite_pool`)
+///   * `deadpool_postgres::Pool` (`deadpool_postgres_pool`)
+///   * `mongodb::Client` (`mongodb_pool`)
+///   * `redis::aio::ConnectionManager` (`redis_pool`)
+///
+/// Besides `url` and `pool_size`, the `sqlx`, `deadpool_postgres`, and
+/// `mongodb` implementations read the `timeout` configuration parameter: the
+/// number of seconds to wait for a connection before failing, which defaults
+/// to `5`.
+///
+/// # Implementation Guide
+///
+/// Implementing `Pool` for the pool type of another asynchronous database
+/// library allows it to be used with `#[derive(Database)]`. `init` creates the
+/// pool, typically with `config.url` and `config.pool_size`, and `get`
+/// retrieves a connection from it:
+///
+/// ```rust
+/// use rocket_contrib::databases::{DatabaseConfig, Pool};
+/// # mod foo {
+/// #     #[derive(Debug)] pub struct Error;
+/// #     impl std::fmt::Display for Error {
+/// #         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) }
+/// #     }
+/// #     pub struct Pool;
+/// #     pub struct Connection;
+/// #     impl Pool {
+/// #         pub async fn connect(url: &str, size: u32) -> Result<Pool, Error> { Err(Error) }
+/// #         pub async fn get(&self) -> Result<Connection, Error> { Err(Error) }
+/// #     }
+/// # }
+///
+/// #[rocket::async_trait]
+/// impl Pool for foo::Pool {
+///     type Connection = foo::Connection;
+///     type Error = foo::Error;
+///
+///     async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error> {
+///         foo::Pool::connect(config.url, config.pool_size).await
+///     }
+///
+///     async fn get(&self) -> Result<Self::Connection, Self::Error> {
+///         foo::Pool::get(self).await
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait Pool: Sized + Send + Sync + 'static {
+    /// The type of the connections handed out by the pool.
+    type Connection: Send + 'static;
+
+    /// The type of the error returned when the pool can't be initialized or
+    /// a connection can't be retrieved.
+    type Error: Display + Send;
+
+    /// Creates a pool configured by `config`.
+    async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error>;
+
+    /// Retrieves a connection from the pool, waiting asynchronously until one
+    /// is available.
+    async fn get(&self) -> Result<Self::Connection, Self::Error>;
+
+    /// Returns the current number of open and idle connections in the pool, if
+    /// known. The default implementation returns `None`.
+    fn status(&self) -> Option<PoolStatus> {
+        None
+    }
+
+    /// Checks that `conn` is usable, typically by running a trivial query. It
+    /// is used by [`HealthCheck`](crate::databases::HealthCheck). The default
+    /// implementation returns `Ok(())`: retrieving the connection is the check.
+    async fn ping(conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        let _ = conn;
+        Ok(())
+    }
+}
+
+/// The number of connections in a [`Pool`], as returned by [`Pool::status()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// The number of open connections, both idle and in use.
+    pub size: u32,
+    /// The number of open connections that are not in use.
+    pub idle: u32,
+}
+
+/// Returns the connection timeout in `config`: the `timeout` parameter, in
+/// seconds, or 5 seconds.
+#[cfg(any(
+    feature = "sqlx_postgres_pool",
+    feature = "sqlx_mysql_pool",
+    feature = "sqlx_sqlite_pool",
+    feature = "deadpool_postgres_pool",
+    feature = "mongodb_pool"
+))]
+fn timeout(config: &DatabaseConfig<'_>) -> std::time::Duration {
+    let secs = config.extras.get("timeout")
+        .and_then(rocket::config::Value::as_integer)
+        .filter(|&secs| secs >= 0)
+        .unwrap_or(5);
+
+    std::time::Duration::from_secs(secs as u64)
+}
+
+#[cfg(any(
+    feature = "sqlx_postgres_pool",
+    feature = "sqlx_mysql_pool",
+    feature = "sqlx_sqlite_pool"
+))]
+#[rocket::async_trait]
+impl<DB: sqlx::Database> Pool for sqlx::Pool<DB> {
+    type Connection = sqlx::pool::PoolConnection<DB>;
+    type Error = sqlx::Error;
+
+    async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error> {
+        sqlx::pool::PoolOptions::new()
+            .max_connections(config.pool_size)
+            .connect_timeout(timeout(&config))
+            .connect(config.url)
+            .await
+    }
+
+    async fn get(&self) -> Result<Self::Connection, Self::Error> {
+        self.acquire().await
+    }
+
+    fn status(&self) -> Option<PoolStatus> {
+        Some(PoolStatus { size: self.size(), idle: self.num_idle() as u32 })
+    }
+
+    async fn ping(conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        sqlx::Connection::ping(&mut **conn).await
+    }
+}
+
+#[cfg(feature = "deadpool_postgres_pool")]
+#[rocket::async_trait]
+impl Pool for deadpool_postgres::Pool {
+    type Connection = deadpool_postgres::Client;
+    type Error = deadpool_postgres::PoolError;
+
+    async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error> {
+        use deadpool::managed::{PoolConfig, Timeouts};
+        use deadpool_postgres::{tokio_postgres, Manager, PoolError};
+
+        let pg_config = config.url.parse::<tokio_postgres::Config>()
+            .map_err(PoolError::Backend)?;
+
+        let manager = Manager::new(pg_config, tokio_postgres::NoTls);
+        let timeout = Some(timeout(&config));
+        let pool_config = PoolConfig {
+            max_size: config.pool_size as usize,
+            timeouts: Timeouts { wait: timeout, create: timeout, recycle: timeout },
+        };
+
+        Ok(deadpool_postgres::Pool::from_config(manager, pool_config))
+    }
+
+    /// Waits for a connection for at most the configured `timeout`: deadpool's
+    /// `get()` is `timeout_get()` with the timeouts set by `init()`.
+    async fn get(&self) -> Result<Self::Connection, Self::Error> {
+        deadpool_postgres::Pool::get(self).await
+    }
+
+    fn status(&self) -> Option<PoolStatus> {
+        let status = deadpool_postgres::Pool::status(self);
+        Some(PoolStatus { size: status.size as u32, idle: status.available.max(0) as u32 })
+    }
+
+    async fn ping(conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        conn.simple_query("SELECT 1").await
+            .map(|_| ())
+            .map_err(deadpool_postgres::PoolError::Backend)
+    }
+}
+
+#[cfg(feature = "mongodb_pool")]
+#[rocket::async_trait]
+impl Pool for mongodb::Client {
+    type Connection = mongodb::Client;
+    type Error = mongodb::error::Error;
+
+    async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error> {
+        let mut options = mongodb::options::ClientOptions::parse(config.url).await?;
+        options.max_pool_size = Some(config.pool_size);
+        options.connect_timeout = Some(timeout(&config));
+        options.server_selection_timeout = Some(timeout(&config));
+        mongodb::Client::with_options(options)
+    }
+
+    /// A `mongodb::Client` pools connections internally, so the connection
+    /// handed out is a handle to the client itself.
+    async fn get(&self) -> Result<Self::Connection, Self::Error> {
+        Ok(self.clone())
+    }
+
+    async fn ping(conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        let ping = mongodb::bson::doc! { "ping": 1 };
+        conn.database("admin").run_command(ping, None).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "redis_pool")]
+#[rocket::async_trait]
+impl Pool for redis::aio::ConnectionManager {
+    type Connection = redis::aio::ConnectionManager;
+    type Error = redis::RedisError;
+
+    /// A `ConnectionManager` multiplexes commands over a single connection
+    /// that is reestablished as needed, so `pool_size` is ignored.
+    async fn init(config: DatabaseConfig<'_>) -> Result<Self, Self::Error> {
+        redis::Client::open(config.url)?.get_tokio_connection_manager().await
+    }
+
+    async fn get(&self) -> Result<Self::Connection, Self::Error> {
+        Ok(self.clone())
+    }
+
+    async fn ping(conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        redis::cmd("PING").query_async(conn).await
+    }
+}
@@ -0,0 +1,194 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rocket::request::{Request, Outcome, FromRequest};
+use rocket::http::Method;
+
+use crate::databases::{Connection, Database, Health, Pool};
+
+/// The database a [`Routed`] connection was retrieved from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The primary database.
+    Primary,
+    /// The replica database.
+    Replica,
+}
+
+/// Trait implemented by the routing policies of [`Routed`] connections.
+///
+/// A policy chooses the role of the preferred database of a request. The
+/// following policies are provided:
+///
+///   * [`ByMethod`]: the replica for `GET`, `HEAD`, and `OPTIONS` requests
+///     and the primary for all others.
+///   * [`ReadOnly`]: the replica, for handlers that only read, whatever the
+///     request method.
+///   * [`ReadWrite`]: the primary, for handlers that write or must read their
+///     own writes, whatever the request method.
+pub trait Policy: Send + 'static {
+    /// Returns the preferred database of a request with method `method`.
+    fn role(method: Method) -> Role;
+}
+
+/// A [`Policy`] that prefers the replica for `GET`, `HEAD`, and `OPTIONS`
+/// requests and the primary for all others.
+pub enum ByMethod {}
+
+/// A [`Policy`] that always prefers the replica.
+pub enum ReadOnly {}
+
+/// A [`Policy`] that always uses the primary.
+pub enum ReadWrite {}
+
+impl Policy for ByMethod {
+    fn role(method: Method) -> Role {
+        match method {
+            Method::Get | Method::Head | Method::Options => Role::Replica,
+            _ => Role::Primary,
+        }
+    }
+}
+
+impl Policy for ReadOnly {
+    fn role(_: Method) -> Role {
+        Role::Replica
+    }
+}
+
+impl Policy for ReadWrite {
+    fn role(_: Method) -> Role {
+        Role::Primary
+    }
+}
+
+/// A request guard that retrieves a connection from a primary database `P` or
+/// a replica database `R` according to the policy `H`.
+///
+/// Both databases must use the same type of pool. The replica is used when the
+/// policy prefers it and it is available; otherwise, the connection falls back
+/// to the primary. The replica is unavailable if a connection to it can't be
+/// retrieved or if the latest check of its [`HealthCheck`], when one is
+/// attached, failed. The database a connection was retrieved from is
+/// returned by [`Routed::role()`].
+///
+/// The policy defaults to [`ByMethod`]. A handler can override it with an
+/// explicit policy such as [`ReadWrite`], for instance to read its own writes.
+///
+/// [`HealthCheck`]: crate::databases::HealthCheck
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # #[cfg(feature = "sqlx_postgres_pool")]
+/// # mod test {
+/// use rocket_contrib::databases::{Database, Routed, ByMethod, ReadWrite, sqlx};
+///
+/// #[derive(Database)]
+/// #[database("primary")]
+/// struct Primary(sqlx::PgPool);
+///
+/// #[derive(Database)]
+/// #[database("read_replica")]
+/// struct ReadReplica(sqlx::PgPool);
+///
+/// type Db<H = ByMethod> = Routed<Primary, ReadReplica, H>;
+///
+/// // Uses the replica, unless it is unavailable.
+/// #[get("/users/<id>")]
+/// async fn get_user(mut db: Db, id: i64) -> Option<String> {
+///     # let _ = (&mut db, id); None
+///     /* .. */
+/// }
+///
+/// // Uses the primary.
+/// #[post("/users/<id>/name", data = "<name>")]
+/// async fn rename_user(mut db: Db, id: i64, name: String) {
+///     # let _ = (&mut db, id, name);
+///     /* .. */
+/// }
+///
+/// // Uses the primary to read the latest data.
+/// #[get("/users/<id>/fresh")]
+/// async fn get_fresh_user(mut db: Db<ReadWrite>, id: i64) -> Option<String> {
+///     # let _ = (&mut db, id); None
+///     /* .. */
+/// }
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .attach(Primary::init())
+///         .attach(ReadReplica::init())
+///         .mount("/", routes![get_user, rename_user, get_fresh_user])
+/// }
+/// # } fn main() {}
+/// ```
+pub struct Routed<P: Database, R: Database<Pool = P::Pool>, H: Policy = ByMethod> {
+    connection: <P::Pool as Pool>::Connection,
+    role: Role,
+    _policy: PhantomData<fn() -> (R, H)>,
+}
+
+impl<P: Database, R: Database<Pool = P::Pool>, H: Policy> Routed<P, R, H> {
+    /// Returns the role of the database the connection was retrieved from.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Consumes `self` and returns the inner connection.
+    pub fn into_inner(self) -> <P::Pool as Pool>::Connection {
+        self.connection
+    }
+
+    fn new(connection: <P::Pool as Pool>::Connection, role: Role) -> Self {
+        Routed { connection, role, _policy: PhantomData }
+    }
+}
+
+impl<P: Database, R: Database<Pool = P::Pool>, H: Policy> Deref for Routed<P, R, H> {
+    type Target = <P::Pool as Pool>::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.connection
+    }
+}
+
+impl<P: Database, R: Database<Pool = P::Pool>, H: Policy> DerefMut for Routed<P, R, H> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.connection
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, P, R, H> FromRequest<'a, 'r> for Routed<P, R, H>
+    where P: Database, R: Database<Pool = P::Pool>, H: Policy
+{
+    type Error = ();
+
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        if H::role(request.method()) == Role::Replica {
+            let healthy = request.managed_state::<Health<R>>()
+                .and_then(|health| health.latest())
+                .map_or(true, |report| report.is_healthy());
+
+            if healthy {
+                match Connection::<R>::from_request(request).await {
+                    Outcome::Success(conn) => {
+                        return Outcome::Success(Routed::new(conn.into_inner(), Role::Replica));
+                    }
+                    _ => {
+                        warn_!("Replica '{}' is unavailable: using primary '{}'.",
+                            R::NAME, P::NAME);
+                    }
+                }
+            } else {
+                warn_!("Replica '{}' is unhealthy: using primary '{}'.", R::NAME, P::NAME);
+            }
+        }
+
+        Connection::<P>::from_request(request).await
+            .map(|conn| Routed::new(conn.into_inner(), Role::Primary))
+    }
+}
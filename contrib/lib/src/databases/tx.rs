@@ -0,0 +1,254 @@
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
+
+use rocket::{Request, Rocket};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::request::{Outcome, FromRequest};
+use rocket::response::Response;
+use rocket::http::{Status, StatusClass};
+use rocket::tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::databases::{Database, Pool, health::Metrics};
+
+/// Trait implemented by [`Pool`]s whose connections support transactions.
+///
+/// This trait is implemented for the `sqlx` pools. It is required by the
+/// [`Tx`] request guard.
+#[rocket::async_trait]
+pub trait Transactional: Pool {
+    /// The type of a transaction.
+    type Transaction: Send + 'static;
+
+    /// Begins a transaction on a connection retrieved from the pool.
+    async fn begin(&self) -> Result<Self::Transaction, Self::Error>;
+
+    /// Commits the transaction `tx`.
+    async fn commit(tx: Self::Transaction) -> Result<(), Self::Error>;
+
+    /// Rolls back the transaction `tx`.
+    async fn rollback(tx: Self::Transaction) -> Result<(), Self::Error>;
+}
+
+#[cfg(any(
+    feature = "sqlx_postgres_pool",
+    feature = "sqlx_mysql_pool",
+    feature = "sqlx_sqlite_pool"
+))]
+#[rocket::async_trait]
+impl<DB: sqlx::Database> Transactional for sqlx::Pool<DB> {
+    type Transaction = sqlx::Transaction<'static, DB>;
+
+    async fn begin(&self) -> Result<Self::Transaction, Self::Error> {
+        sqlx::Pool::begin(self).await
+    }
+
+    async fn commit(tx: Self::Transaction) -> Result<(), Self::Error> {
+        tx.commit().await
+    }
+
+    async fn rollback(tx: Self::Transaction) -> Result<(), Self::Error> {
+        tx.rollback().await
+    }
+}
+
+type Transaction<D> = <<D as Database>::Pool as Transactional>::Transaction;
+
+/// The transaction begun by a `Tx<D>` guard, stored in the request-local
+/// cache until the response is finalized.
+struct Slot<D: Database>(Arc<Mutex<Option<Transaction<D>>>>)
+    where D::Pool: Transactional;
+
+/// Managed by the fairing returned by [`Tx::fairing()`] to indicate that it
+/// was attached.
+struct Finalizer<D>(PhantomData<fn() -> D>);
+
+/// A request guard that begins a transaction on the database `D` and ends it
+/// when the response is finalized.
+///
+/// The transaction is committed if the response status is `2xx` or `3xx` and
+/// rolled back otherwise, including when the handler fails. If the transaction
+/// fails to commit, or is still in use when the response is finalized, as when
+/// the `Tx` is moved into a streamed body, the response is replaced with a
+/// `500 Internal Server Error`. A `Tx` dereferences to the transaction, which
+/// can be used to run queries in place of a connection.
+///
+/// The fairing returned by [`Tx::fairing()`] ends the transactions and must be
+/// attached in addition to the database's [`Initializer`]. If it is not, the
+/// guard fails with a status of `500 Internal Server Error`. If a transaction
+/// can't be begun, the guard fails with `503 Service Unavailable`.
+///
+/// A request holds at most one transaction at a time: a second `Tx` for the
+/// same route fails without beginning a transaction. If a route forwards, as
+/// when one of its other guards forwards or fails, its transaction is rolled
+/// back before a lower-ranked route's `Tx` begins a new one.
+///
+/// [`Initializer`]: crate::databases::Initializer
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # #[cfg(feature = "sqlx_sqlite_pool")]
+/// # mod test {
+/// use rocket::response::status::Created;
+/// use rocket_contrib::databases::{Database, Tx, sqlx};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_logs")]
+/// struct Logs(sqlx::SqlitePool);
+///
+/// #[post("/logs", data = "<content>")]
+/// async fn create(mut tx: Tx<Logs>, content: String) -> Option<Created<()>> {
+///     // Both queries are committed only if the handler returns `Some`.
+///     sqlx::query("INSERT INTO logs (content) VALUES (?)")
+///         .bind(content)
+///         .execute(&mut *tx)
+///         .await
+///         .ok()?;
+///
+///     sqlx::query("UPDATE stats SET logs = logs + 1")
+///         .execute(&mut *tx)
+///         .await
+///         .ok()?;
+///
+///     Some(Created::new("/logs"))
+/// }
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .attach(Logs::init())
+///         .attach(Tx::<Logs>::fairing())
+///         .mount("/", routes![create])
+/// }
+/// # } fn main() {}
+/// ```
+pub struct Tx<D: Database>(OwnedMutexGuard<Option<Transaction<D>>>)
+    where D::Pool: Transactional;
+
+impl<D: Database> Tx<D> where D::Pool: Transactional {
+    /// Returns a fairing that commits or rolls back the transactions begun by
+    /// `Tx<D>` guards when their responses are finalized.
+    pub fn fairing() -> impl Fairing {
+        TxFairing::<D>(PhantomData)
+    }
+}
+
+impl<D: Database> Deref for Tx<D> where D::Pool: Transactional {
+    type Target = Transaction<D>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("transaction is only taken after the guard is dropped")
+    }
+}
+
+impl<D: Database> DerefMut for Tx<D> where D::Pool: Transactional {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("transaction is only taken after the guard is dropped")
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, D: Database> FromRequest<'a, 'r> for Tx<D> where D::Pool: Transactional {
+    type Error = ();
+
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, ()> {
+        let db = match request.managed_state::<D>() {
+            Some(db) if request.managed_state::<Finalizer<D>>().is_some() => db,
+            _ => {
+                error_!("Missing database or transaction fairing for `{}`",
+                    std::any::type_name::<D>());
+
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+
+        // A transaction that is still locked is held by another `Tx` for this
+        // route: fail without taking a connection from the pool. One that isn't
+        // was left by a route that forwarded and is rolled back to be replaced.
+        let slot = request.local_cache(|| Slot::<D>(Arc::new(Mutex::new(None))));
+        let orphan = match slot.0.try_lock() {
+            Ok(mut tx) => tx.take(),
+            Err(_) => {
+                error_!("A transaction for `{}` is already in use for this request.",
+                    std::any::type_name::<D>());
+
+                return Outcome::Failure((Status::InternalServerError, ()));
+            }
+        };
+
+        if let Some(tx) = orphan {
+            if let Err(e) = <D::Pool as Transactional>::rollback(tx).await {
+                error_!("Failed to roll back database transaction: {}", e);
+            }
+        }
+
+        let start = Instant::now();
+        let tx = Transactional::begin(&**db).await;
+        if let Some(metrics) = request.managed_state::<Metrics<D>>() {
+            metrics.counters().record(start, tx.is_ok());
+        }
+
+        let tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => {
+                error_!("Failed to begin a database transaction: {}", e);
+                return Outcome::Failure((Status::ServiceUnavailable, ()));
+            }
+        };
+
+        let mut guard = slot.0.clone().lock_owned().await;
+        *guard = Some(tx);
+        Outcome::Success(Tx(guard))
+    }
+}
+
+struct TxFairing<D>(PhantomData<fn() -> D>);
+
+#[rocket::async_trait]
+impl<D: Database> Fairing for TxFairing<D> where D::Pool: Transactional {
+    fn info(&self) -> Info {
+        Info { name: "Database Transactions", kind: Kind::Attach | Kind::Response }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        Ok(rocket.manage(Finalizer::<D>(PhantomData)))
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let succeeded = match response.status().class() {
+            StatusClass::Success | StatusClass::Redirection => true,
+            _ => false,
+        };
+
+        let slot = request.local_cache(|| Slot::<D>(Arc::new(Mutex::new(None))));
+        let tx = match slot.0.try_lock() {
+            Ok(mut tx) => match tx.take() {
+                Some(tx) => tx,
+                None => return,
+            },
+            Err(_) => {
+                warn_!("Transaction for `{}` is still in use: it will be rolled back.",
+                    std::any::type_name::<D>());
+
+                // The response can't claim success if its changes won't persist.
+                if succeeded {
+                    *response = Response::build().status(Status::InternalServerError).finalize();
+                }
+
+                return;
+            }
+        };
+
+        if succeeded {
+            if let Err(e) = <D::Pool as Transactional>::commit(tx).await {
+                error_!("Failed to commit database transaction: {}", e);
+                *response = Response::build().status(Status::InternalServerError).finalize();
+            }
+        } else if let Err(e) = <D::Pool as Transactional>::rollback(tx).await {
+            error_!("Failed to roll back database transaction: {}", e);
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Runtime feature flags for handlers and conditional routes.
+//!
+//! See the [`FeatureFlags`] store and the [`Flag`] request guard for further
+//! details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `feature_flags` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["feature_flags"]
+//! ```
+//!
+//! # Overview
+//!
+//! Flags are named booleans kept in a [`FeatureFlags`] store, which is managed
+//! by Rocket once the [`FeatureFlagsFairing`] is attached. Their values come
+//! from, in increasing order of precedence:
+//!
+//!   * **Defaults** set in code with [`FeatureFlagsFairing::flag()`].
+//!   * **Configuration**, in the `flags` table in `Rocket.toml`.
+//!   * **Providers**, implementations of [`FlagProvider`] such as
+//!     [`FileProvider`] or a client of a remote flag service, registered with
+//!     [`FeatureFlagsFairing::provider()`] and polled while Rocket runs. When
+//!     several providers report a flag, the last one registered wins.
+//!
+//! The flags are recomputed from these sources whenever a provider reports,
+//! so a flag a provider stops reporting falls back to its configured or
+//! default value. Flags can also be changed at any time through the store,
+//! for instance from an administrative route, until a provider next reports
+//! them. A flag that isn't set is disabled.
+//!
+//! ```toml
+//! [global.flags]
+//! new_checkout = true
+//! dark_mode = false
+//! ```
+//!
+//! # Using Flags
+//!
+//! A flag is declared by implementing [`FeatureFlag`] for a type naming it.
+//! The [`Flag`] request guard then succeeds if the flag is enabled and
+//! forwards otherwise, so that routes can be enabled conditionally, falling
+//! back to lower-ranked routes when the flag is disabled:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket_contrib::feature_flags::{FeatureFlag, FeatureFlags, Flag};
+//!
+//! struct NewCheckout;
+//!
+//! impl FeatureFlag for NewCheckout {
+//!     const NAME: &'static str = "new_checkout";
+//! }
+//!
+//! #[get("/checkout")]
+//! fn new_checkout(_flag: Flag<NewCheckout>) -> &'static str {
+//!     "The new checkout."
+//! }
+//!
+//! #[get("/checkout", rank = 2)]
+//! fn checkout() -> &'static str {
+//!     "The old checkout."
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .mount("/", routes![new_checkout, checkout])
+//!         .attach(FeatureFlags::fairing())
+//!         // ...
+//!     # ;
+//! }
+//! ```
+//!
+//! Handlers branch on a flag with an `Option<Flag<F>>` guard, or query the
+//! store directly with a `State<FeatureFlags>` guard.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use rocket::{Request, Rocket};
+use rocket::config::{Config, ConfigError, Value};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Status;
+use rocket::request::{self, FromRequest};
+
+/// A flag, named by [`FeatureFlag::NAME`], checked by the [`Flag`] request
+/// guard.
+///
+/// See the [module level documentation](self) for an example.
+pub trait FeatureFlag: Send + Sync + 'static {
+    /// The name of the flag in the [`FeatureFlags`] store.
+    const NAME: &'static str;
+}
+
+/// The store of feature flags.
+///
+/// The store is managed by Rocket once the [`FeatureFlagsFairing`] is
+/// attached, so handlers can retrieve it via [`State`](rocket::State). Stores
+/// are cheap to clone; all clones share the same flags.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::State;
+/// use rocket_contrib::feature_flags::FeatureFlags;
+///
+/// #[post("/admin/flags/<name>/<enabled>")]
+/// fn toggle(name: String, enabled: bool, flags: State<'_, FeatureFlags>) {
+///     flags.set(name, enabled);
+/// }
+///
+/// #[get("/")]
+/// fn index(flags: State<'_, FeatureFlags>) -> &'static str {
+///     if flags.is_enabled("dark_mode") { "dark" } else { "light" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags(Arc<RwLock<Layers>>);
+
+/// The sources of a store's flags, from lowest to highest precedence, and the
+/// flags they add up to.
+#[derive(Debug, Default)]
+struct Layers {
+    /// The defaults, overridden by the configured values.
+    base: HashMap<String, bool>,
+    /// The latest flags reported by each provider, in registration order.
+    reports: Vec<HashMap<String, bool>>,
+    /// Flags set (`Some`) or unset (`None`) through the store since a
+    /// provider last reported them.
+    overrides: HashMap<String, Option<bool>>,
+    /// The effective flags.
+    flags: HashMap<String, bool>,
+}
+
+impl Layers {
+    /// Recomputes the effective flags from their sources.
+    fn rebuild(&mut self) {
+        let mut flags = self.base.clone();
+        for report in &self.reports {
+            flags.extend(report.iter().map(|(name, &enabled)| (name.clone(), enabled)));
+        }
+
+        for (name, value) in &self.overrides {
+            match *value {
+                Some(enabled) => flags.insert(name.clone(), enabled),
+                None => flags.remove(name),
+            };
+        }
+
+        self.flags = flags;
+    }
+}
+
+impl FeatureFlags {
+    /// Returns an empty store.
+    pub fn new() -> Self {
+        FeatureFlags::default()
+    }
+
+    /// Returns a fairing that manages a new store.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::feature_flags::FeatureFlags;
+    ///
+    /// rocket::ignite().attach(FeatureFlags::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> FeatureFlagsFairing {
+        FeatureFlagsFairing {
+            flags: FeatureFlags::new(),
+            defaults: HashMap::new(),
+            sources: vec![],
+        }
+    }
+
+    /// Returns `true` if the flag `name` is enabled. A flag that isn't set is
+    /// disabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name).unwrap_or(false)
+    }
+
+    /// Returns the value of the flag `name`, or `None` if it isn't set.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.0.read().expect("flags lock").flags.get(name).copied()
+    }
+
+    /// Enables or disables the flag `name`. The value is replaced by the next
+    /// value a provider reports for the flag, if any.
+    pub fn set<N: Into<String>>(&self, name: N, enabled: bool) {
+        let name = name.into();
+        let mut layers = self.0.write().expect("flags lock");
+        layers.flags.insert(name.clone(), enabled);
+        layers.overrides.insert(name, Some(enabled));
+    }
+
+    /// Unsets the flag `name`, returning its value, if any. The flag is set
+    /// again by the next value a provider reports for it, if any.
+    pub fn remove(&self, name: &str) -> Option<bool> {
+        let mut layers = self.0.write().expect("flags lock");
+        layers.overrides.insert(name.to_string(), None);
+        layers.flags.remove(name)
+    }
+
+    /// Returns the current value of every flag that is set.
+    pub fn snapshot(&self) -> HashMap<String, bool> {
+        self.0.read().expect("flags lock").flags.clone()
+    }
+
+    /// Replaces the defaults and configured values of the flags with `base`.
+    fn set_base(&self, base: HashMap<String, bool>) {
+        let mut layers = self.0.write().expect("flags lock");
+        layers.base = base;
+        layers.rebuild();
+    }
+
+    /// Replaces the flags last reported by the provider registered at `index`
+    /// with `report`.
+    fn report(&self, index: usize, report: HashMap<String, bool>) {
+        let mut layers = self.0.write().expect("flags lock");
+        if layers.reports.len() <= index {
+            layers.reports.resize_with(index + 1, HashMap::new);
+        }
+
+        for name in report.keys() {
+            layers.overrides.remove(name);
+        }
+
+        layers.reports[index] = report;
+        layers.rebuild();
+    }
+}
+
+/// A request guard that succeeds if the flag `F` is enabled and forwards
+/// otherwise.
+///
+/// As an `Option<Flag<F>>`, the guard always succeeds, with `None` if the flag
+/// is disabled. If the [`FeatureFlagsFairing`] isn't attached, the guard fails
+/// with `500 Internal Server Error`.
+///
+/// See the [module level documentation](self) for an example.
+pub struct Flag<F>(PhantomData<fn() -> F>);
+
+#[rocket::async_trait]
+impl<'a, 'r, F: FeatureFlag> FromRequest<'a, 'r> for Flag<F> {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        if let Some(outcome) = req.stubbed_guard::<Self, ()>() {
+            return outcome;
+        }
+
+        match req.managed_state::<FeatureFlags>() {
+            Some(flags) if flags.is_enabled(F::NAME) => {
+                request::Outcome::Success(Flag(PhantomData))
+            }
+            Some(_) => request::Outcome::Forward(()),
+            None => {
+                error_!("Missing feature flags fairing: attach `FeatureFlags::fairing()`.");
+                request::Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// A source of flag values, such as a file or a remote flag service, polled
+/// by the [`FeatureFlagsFairing`].
+///
+/// # Example
+///
+/// A provider that reads flags from environment variables named `FLAG_*`:
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use rocket_contrib::feature_flags::FlagProvider;
+///
+/// struct EnvFlags;
+///
+/// #[rocket::async_trait]
+/// impl FlagProvider for EnvFlags {
+///     fn name(&self) -> &str {
+///         "env"
+///     }
+///
+///     async fn fetch(&self) -> Result<HashMap<String, bool>, String> {
+///         Ok(std::env::vars()
+///             .filter_map(|(key, value)| {
+///                 let name = key.strip_prefix("FLAG_")?.to_lowercase();
+///                 Some((name, value == "1" || value == "true"))
+///             })
+///             .collect())
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait FlagProvider: Send + Sync + 'static {
+    /// The name of this provider, used in log messages.
+    fn name(&self) -> &str;
+
+    /// Returns the flags known to this provider, or a description of why they
+    /// could not be retrieved. Flags that were previously returned but no
+    /// longer are fall back to their configured or default value. If an error
+    /// is returned, the flags previously returned keep their value.
+    async fn fetch(&self) -> Result<HashMap<String, bool>, String>;
+}
+
+/// A provider that reads flags from a TOML file of `name = true` lines.
+///
+/// The file isn't watched for changes: it is read anew every time the
+/// provider is polled, so changes to it are picked up at the next poll while
+/// Rocket runs.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket_contrib::feature_flags::{FeatureFlags, FileProvider};
+///
+/// let fairing = FeatureFlags::fairing()
+///     .provider(FileProvider::new("flags.toml"), Duration::from_secs(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    path: PathBuf,
+}
+
+impl FileProvider {
+    /// Returns a provider that reads flags from the file at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileProvider {
+        FileProvider { path: path.into() }
+    }
+}
+
+#[rocket::async_trait]
+impl FlagProvider for FileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn fetch(&self) -> Result<HashMap<String, bool>, String> {
+        let path = self.path.display();
+        let contents = rocket::tokio::fs::read_to_string(&self.path).await
+            .map_err(|e| format!("{}: {}", path, e))?;
+
+        let table = match contents.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => return Err(format!("{}: expected a table", path)),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        };
+
+        table.into_iter()
+            .map(|(name, value)| match value.as_bool() {
+                Some(enabled) => Ok((name, enabled)),
+                None => Err(format!("{}: flag '{}' is not a boolean", path, name)),
+            })
+            .collect()
+    }
+}
+
+/// A provider and the interval at which it is polled.
+struct Source {
+    provider: Arc<dyn FlagProvider>,
+    interval: Duration,
+}
+
+/// Fetches the flags of `provider`, registered at `index`, into `flags`,
+/// logging any failure.
+async fn refresh(index: usize, provider: &dyn FlagProvider, flags: &FeatureFlags) {
+    match provider.fetch().await {
+        Ok(fetched) => flags.report(index, fetched),
+        Err(e) => warn_!("Failed to fetch feature flags from '{}': {}", provider.name(), e),
+    }
+}
+
+/// Reads the flags in the `flags` table of `config`.
+fn flags_from_config(config: &Config) -> Result<HashMap<String, bool>, ConfigError> {
+    let table = match config.get_table("flags") {
+        Ok(table) => table,
+        Err(ConfigError::Missing(_)) => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    table.iter()
+        .map(|(name, value)| match value.as_bool() {
+            Some(enabled) => Ok((name.clone(), enabled)),
+            None => {
+                let name = format!("flags.{}", name);
+                Err(ConfigError::BadType(name, "a boolean", value.type_str(), None))
+            }
+        })
+        .collect()
+}
+
+/// A fairing that manages a [`FeatureFlags`] store, created with
+/// [`FeatureFlags::fairing()`].
+///
+/// When attached, the fairing sets the flags to their defaults, then to the
+/// values in the `flags` configuration table, then fetches the flags of every
+/// provider once. Once Rocket launches, each provider is polled at its
+/// interval by a [background task](rocket::Rocket::attach_task()) until
+/// Rocket shuts down. A provider that fails is logged and its flags keep
+/// their values.
+pub struct FeatureFlagsFairing {
+    flags: FeatureFlags,
+    defaults: HashMap<String, bool>,
+    sources: Vec<Source>,
+}
+
+impl FeatureFlagsFairing {
+    /// Sets the default value of the flag `name`, used unless the flag is
+    /// configured or reported by a provider.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::feature_flags::FeatureFlags;
+    ///
+    /// let fairing = FeatureFlags::fairing().flag("new_checkout", true);
+    /// ```
+    pub fn flag<N: Into<String>>(mut self, name: N, enabled: bool) -> Self {
+        self.defaults.insert(name.into(), enabled);
+        self
+    }
+
+    /// Registers `provider`, polled every `interval`. When providers report
+    /// the same flag, the provider registered last wins.
+    pub fn provider<P: FlagProvider>(mut self, provider: P, interval: Duration) -> Self {
+        self.sources.push(Source { provider: Arc::new(provider), interval });
+        self
+    }
+
+    /// Returns the store managed by this fairing, which can be used before
+    /// Rocket launches or outside of handlers.
+    pub fn handle(&self) -> FeatureFlags {
+        self.flags.clone()
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for FeatureFlagsFairing {
+    fn info(&self) -> Info {
+        Info { name: "Feature Flags", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let configured = match flags_from_config(rocket.config().await) {
+            Ok(configured) => configured,
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid feature flags configuration.");
+                return Err(rocket);
+            }
+        };
+
+        let mut base = self.defaults.clone();
+        base.extend(configured);
+        self.flags.set_base(base);
+        for (i, source) in self.sources.iter().enumerate() {
+            refresh(i, &*source.provider, &self.flags).await;
+        }
+
+        let mut rocket = rocket.manage(self.flags.clone());
+        for (i, source) in self.sources.iter().enumerate() {
+            let (provider, interval) = (source.provider.clone(), source.interval);
+            let flags = self.flags.clone();
+            rocket = rocket.attach_task(move |shutdown| async move {
+                loop {
+                    rocket::tokio::select! {
+                        _ = rocket::tokio::time::delay_for(interval) => {},
+                        _ = shutdown.clone() => break,
+                    }
+
+                    refresh(i, &*provider, &flags).await;
+                }
+            });
+        }
+
+        Ok(rocket)
+    }
+}
@@ -0,0 +1,466 @@
+//! GraphQL endpoints backed by [Juniper](https://docs.rs/juniper).
+//!
+//! See the [`GraphQL`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `graphql` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["graphql"]
+//! ```
+//!
+//! # Context
+//!
+//! Each request is executed with a fresh instance of the schema's context
+//! type, which is retrieved as a [request guard](rocket::request::FromRequest).
+//! Any guard, including managed [`State`](rocket::State) and cookies, can thus
+//! be used to build the context, and a context whose guard fails or forwards
+//! fails or forwards the request in turn, before its body is read.
+//!
+//! # Requests
+//!
+//! Queries are accepted over `GET`, with the `query`, `operationName`, and
+//! JSON-encoded `variables` parameters in the query string, and over `POST`,
+//! with either an `application/json` body, which may be a batch of requests,
+//! or an `application/graphql` body containing the query. Bodies are limited
+//! to 1MiB by default; the limit can be increased by setting the
+//! `limits.graphql` configuration parameter.
+//!
+//! Only query operations are executed over `GET`, as links and prefetching
+//! browsers could otherwise trigger mutations: other operations are answered
+//! with `405 Method Not Allowed`. `POST` requests with any other content type
+//! are answered with `415 Unsupported Media Type`, so that cross-site forms,
+//! which can't send JSON without a CORS preflight, can't execute operations.
+//!
+//! Responses are JSON. A request that can't be parsed or executed, as opposed
+//! to one whose fields fail to resolve, is answered with `400 Bad Request`.
+//!
+//! Subscriptions can be declared in the schema but are not yet served, as
+//! Rocket does not yet implement the WebSocket protocol they are carried over.
+//!
+//! # Example
+//!
+//! ```rust
+//! # extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket::request::{self, FromRequest, Request};
+//! use rocket_contrib::graphql::GraphQL;
+//! use rocket_contrib::graphql::juniper::{self, graphql_object, RootNode};
+//! use rocket_contrib::graphql::juniper::{EmptyMutation, EmptySubscription};
+//!
+//! struct Context {
+//!     user: Option<String>,
+//! }
+//!
+//! impl juniper::Context for Context {}
+//!
+//! #[rocket::async_trait]
+//! impl<'a, 'r> FromRequest<'a, 'r> for Context {
+//!     type Error = std::convert::Infallible;
+//!
+//!     async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+//!         let user = req.headers().get_one("X-User").map(String::from);
+//!         request::Outcome::Success(Context { user })
+//!     }
+//! }
+//!
+//! struct Query;
+//!
+//! #[graphql_object(context = Context)]
+//! impl Query {
+//!     fn me(context: &Context) -> Option<&str> {
+//!         context.user.as_deref()
+//!     }
+//! }
+//!
+//! type Schema = RootNode<'static, Query, EmptyMutation<Context>, EmptySubscription<Context>>;
+//!
+//! fn main() {
+//!     let schema = Schema::new(Query, EmptyMutation::new(), EmptySubscription::new());
+//!
+//!     rocket::ignite()
+//!         .mount("/graphql", GraphQL::new(schema).graphiql())
+//!         // ...
+//!     # ;
+//! }
+//! ```
+
+pub extern crate juniper;
+
+use std::sync::Arc;
+
+use rocket::{Request, Data, Route};
+use rocket::data::ByteUnit;
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{Method, Status};
+use rocket::outcome::Outcome::*;
+use rocket::request::{FormItems, FromRequest};
+use rocket::response::{content, status};
+
+use self::juniper::{DefaultScalarValue, GraphQLSubscriptionType, GraphQLTypeAsync, RootNode};
+use self::juniper::http::{GraphQLBatchRequest, GraphQLRequest};
+use self::juniper::http::{graphiql::graphiql_source, playground::playground_source};
+
+/// Default limit for GraphQL request bodies is 1MiB.
+const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
+
+/// A handler that serves a GraphQL schema, and optionally an in-browser IDE,
+/// wherever it's mounted.
+///
+/// `GraphQL` implements [`Into<Vec<Route>>`], so it can be passed directly to
+/// [`Rocket::mount()`](rocket::Rocket::mount()). When mounted at `base`, it
+/// serves:
+///
+///   * `GET base` and `POST base`: the schema, as described in the [module
+///     documentation](crate::graphql).
+///   * `GET base/graphiql`: [GraphiQL], if enabled with
+///     [`GraphQL::graphiql()`].
+///   * `GET base/playground`: [GraphQL Playground], if enabled with
+///     [`GraphQL::playground()`].
+///
+/// The schema's context type, which is shared by its query, mutation, and
+/// subscription types, must implement [`FromRequest`] for every lifetime.
+///
+/// [GraphiQL]: https://github.com/graphql/graphiql
+/// [GraphQL Playground]: https://github.com/graphql/graphql-playground
+///
+/// # Example
+///
+/// Serving `Schema`, whose context is `Context`, at `/api/graphql`, with
+/// GraphQL Playground at `/api/graphql/playground`:
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// # use rocket::request::{self, FromRequest, Request};
+/// # use rocket_contrib::graphql::juniper::{self, graphql_object, RootNode};
+/// # use rocket_contrib::graphql::juniper::{EmptyMutation, EmptySubscription};
+/// # struct Context;
+/// # impl juniper::Context for Context {}
+/// # #[rocket::async_trait]
+/// # impl<'a, 'r> FromRequest<'a, 'r> for Context {
+/// #     type Error = ();
+/// #     async fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
+/// #         request::Outcome::Success(Context)
+/// #     }
+/// # }
+/// # struct Query;
+/// # #[graphql_object(context = Context)]
+/// # impl Query { fn version() -> &'static str { "1" } }
+/// # type Schema = RootNode<'static, Query, EmptyMutation<Context>, EmptySubscription<Context>>;
+/// use rocket_contrib::graphql::GraphQL;
+///
+/// let schema = Schema::new(Query, EmptyMutation::new(), EmptySubscription::new());
+/// rocket::ignite().mount("/api/graphql", GraphQL::new(schema).playground())
+/// # ;
+/// ```
+pub struct GraphQL<Q, M, S>
+    where Q: GraphQLTypeAsync<DefaultScalarValue>,
+          M: GraphQLTypeAsync<DefaultScalarValue, Context = Q::Context>,
+          S: GraphQLSubscriptionType<DefaultScalarValue, Context = Q::Context>
+{
+    schema: Arc<RootNode<'static, Q, M, S>>,
+    ide: Option<Ide>,
+}
+
+/// An in-browser GraphQL IDE.
+#[derive(Debug, Clone, Copy)]
+enum Ide {
+    GraphiQL,
+    Playground,
+}
+
+impl Ide {
+    fn path(self) -> &'static str {
+        match self {
+            Ide::GraphiQL => "/graphiql",
+            Ide::Playground => "/playground",
+        }
+    }
+}
+
+impl<Q, M, S> GraphQL<Q, M, S>
+    where Q: GraphQLTypeAsync<DefaultScalarValue>,
+          M: GraphQLTypeAsync<DefaultScalarValue, Context = Q::Context>,
+          S: GraphQLSubscriptionType<DefaultScalarValue, Context = Q::Context>
+{
+    /// Returns a handler serving `schema`, without an IDE.
+    pub fn new(schema: RootNode<'static, Q, M, S>) -> Self {
+        GraphQL { schema: Arc::new(schema), ide: None }
+    }
+
+    /// Additionally serves GraphiQL at `graphiql`, relative to the mount
+    /// point. Replaces GraphQL Playground, if it was enabled.
+    pub fn graphiql(mut self) -> Self {
+        self.ide = Some(Ide::GraphiQL);
+        self
+    }
+
+    /// Additionally serves GraphQL Playground at `playground`, relative to the
+    /// mount point. Replaces GraphiQL, if it was enabled.
+    pub fn playground(mut self) -> Self {
+        self.ide = Some(Ide::Playground);
+        self
+    }
+}
+
+impl<Q, M, S> Clone for GraphQL<Q, M, S>
+    where Q: GraphQLTypeAsync<DefaultScalarValue>,
+          M: GraphQLTypeAsync<DefaultScalarValue, Context = Q::Context>,
+          S: GraphQLSubscriptionType<DefaultScalarValue, Context = Q::Context>
+{
+    fn clone(&self) -> Self {
+        GraphQL { schema: self.schema.clone(), ide: self.ide }
+    }
+}
+
+impl<Q, M, S> Into<Vec<Route>> for GraphQL<Q, M, S>
+    where Q: GraphQLTypeAsync<DefaultScalarValue> + Send + Sync + 'static,
+          Q::TypeInfo: Send + Sync,
+          Q::Context: for<'a, 'r> FromRequest<'a, 'r> + Send + Sync + 'static,
+          M: GraphQLTypeAsync<DefaultScalarValue, Context = Q::Context> + Send + Sync + 'static,
+          M::TypeInfo: Send + Sync,
+          S: GraphQLSubscriptionType<DefaultScalarValue, Context = Q::Context>,
+          S: Send + Sync + 'static,
+          S::TypeInfo: Send + Sync
+{
+    fn into(self) -> Vec<Route> {
+        let mut routes = vec![
+            Route::new(Method::Get, "/", self.clone()),
+            Route::new(Method::Post, "/", self.clone()),
+        ];
+
+        if let Some(ide) = self.ide {
+            routes.push(Route::new(Method::Get, ide.path(), IdeHandler(ide)));
+        }
+
+        routes
+    }
+}
+
+#[rocket::async_trait]
+impl<Q, M, S> Handler for GraphQL<Q, M, S>
+    where Q: GraphQLTypeAsync<DefaultScalarValue> + Send + Sync + 'static,
+          Q::TypeInfo: Send + Sync,
+          Q::Context: for<'a, 'r> FromRequest<'a, 'r> + Send + Sync + 'static,
+          M: GraphQLTypeAsync<DefaultScalarValue, Context = Q::Context> + Send + Sync + 'static,
+          M::TypeInfo: Send + Sync,
+          S: GraphQLSubscriptionType<DefaultScalarValue, Context = Q::Context>,
+          S: Send + Sync + 'static,
+          S::TypeInfo: Send + Sync
+{
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        let context = match req.guard::<Q::Context>().await {
+            Success(context) => context,
+            Failure((status, e)) => {
+                error_!("GraphQL context guard failed: {:?}", e);
+                return Outcome::failure(status);
+            }
+            Forward(()) => return Outcome::forward(data),
+        };
+
+        let request = match req.method() {
+            Method::Post => parse_body(req, data).await,
+            _ => parse_query(req),
+        };
+
+        let request = match request {
+            Ok(request) => request,
+            Err((status, message)) => {
+                let errors = serde_json::json!({ "errors": [{ "message": message }] });
+                let body = content::Json(errors.to_string());
+                return Outcome::from(req, status::Custom(status, body));
+            }
+        };
+
+        let response = request.execute(&self.schema, &context).await;
+        let status = if response.is_ok() { Status::Ok } else { Status::BadRequest };
+        match serde_json::to_string(&response) {
+            Ok(body) => Outcome::from(req, status::Custom(status, content::Json(body))),
+            Err(e) => {
+                error_!("Failed to serialize GraphQL response: {}", e);
+                Outcome::failure(Status::InternalServerError)
+            }
+        }
+    }
+}
+
+/// Parses a request from the `query`, `operationName`, and `variables`
+/// parameters of the query string. Fails with `405 Method Not Allowed` if the
+/// operation to execute isn't a query.
+fn parse_query(req: &Request<'_>) -> Result<GraphQLBatchRequest, (Status, String)> {
+    let bad_request = |message: String| (Status::BadRequest, message);
+    let (mut query, mut operation_name, mut variables) = (None, None, None);
+    for item in FormItems::from(req.uri().query().unwrap_or("")) {
+        let (key, value) = item.key_value_decoded();
+        match key.as_str() {
+            "query" => query = Some(value),
+            "operationName" => operation_name = Some(value),
+            "variables" => {
+                let value = serde_json::from_str(&value)
+                    .map_err(|e| bad_request(format!("invalid `variables` parameter: {}", e)))?;
+
+                variables = Some(value);
+            }
+            _ => { /* other parameters are ignored */ }
+        }
+    }
+
+    let query = query.ok_or_else(|| bad_request("missing `query` parameter".into()))?;
+    match operation_type(&query, operation_name.as_deref()) {
+        Some("query") => {}
+        Some(kind) => {
+            let message = format!("{} operations must be sent with `POST`", kind);
+            return Err((Status::MethodNotAllowed, message));
+        }
+        None => return Err(bad_request("no operation to execute".into())),
+    }
+
+    let request = GraphQLRequest::new(query, operation_name, variables);
+    Ok(GraphQLBatchRequest::Single(request))
+}
+
+/// Parses a request, or a batch of requests, from an `application/json` or
+/// `application/graphql` body. Fails with `415 Unsupported Media Type` for
+/// bodies of any other type.
+async fn parse_body(
+    req: &Request<'_>,
+    data: Data
+) -> Result<GraphQLBatchRequest, (Status, String)> {
+    let graphql = match req.content_type() {
+        Some(ct) if ct.is_json() => false,
+        Some(ct) if ct.top() == "application" && ct.sub() == "graphql" => true,
+        _ => {
+            let message = "request body must be `application/json` or `application/graphql`";
+            return Err((Status::UnsupportedMediaType, message.into()));
+        }
+    };
+
+    let bad_request = |message: String| (Status::BadRequest, message);
+    let limit = req.limits().get("graphql").unwrap_or(DEFAULT_LIMIT);
+    let bytes = data.open(limit).into_bytes().await
+        .map_err(|e| bad_request(format!("failed to read request body: {}", e)))?;
+
+    let body = std::str::from_utf8(&bytes)
+        .map_err(|e| bad_request(format!("request body is not UTF-8: {}", e)))?;
+
+    match graphql {
+        true => {
+            let request = GraphQLRequest::new(body.to_string(), None, None);
+            Ok(GraphQLBatchRequest::Single(request))
+        }
+        false => serde_json::from_str(body)
+            .map_err(|e| bad_request(format!("invalid GraphQL request: {}", e))),
+    }
+}
+
+/// Returns the type of the operation that executing `document` would run,
+/// `"query"`, `"mutation"`, or `"subscription"`: the operation named `name`
+/// or, if there is no name, the document's only operation. Returns `None` if
+/// there is no such operation.
+///
+/// Only the top level of the document is scanned: strings and comments are
+/// skipped, and selection sets and parenthesized variable definitions and
+/// arguments are skipped by tracking nesting.
+fn operation_type(document: &str, name: Option<&str>) -> Option<&'static str> {
+    let bytes = document.as_bytes();
+    let is_name_start = |b: u8| b == b'_' || b.is_ascii_alphabetic();
+    let (mut i, mut braces, mut parens) = (0, 0usize, 0usize);
+    let mut operations: Vec<(&'static str, Option<&str>)> = vec![];
+    // The definition being read at the top level, if any: its type, its name,
+    // and whether the next name is a directive's.
+    let mut definition: Option<(&'static str, Option<&str>)> = None;
+    let mut directive = false;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => while i < bytes.len() && bytes[i] != b'\n' && bytes[i] != b'\r' {
+                i += 1;
+            },
+            b'"' if bytes[i..].starts_with(b"\"\"\"") => {
+                i += 3;
+                while i < bytes.len() && !bytes[i..].starts_with(b"\"\"\"") {
+                    i += if bytes[i..].starts_with(b"\\\"\"\"") { 4 } else { 1 };
+                }
+
+                i += 2;
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'(' => parens += 1,
+            b')' => parens = parens.saturating_sub(1),
+            b'{' if parens > 0 => {}
+            b'{' => {
+                if braces == 0 {
+                    match definition.take() {
+                        Some(("fragment", _)) => {}
+                        Some(operation) => operations.push(operation),
+                        None => operations.push(("query", None)),
+                    }
+                }
+
+                braces += 1;
+            }
+            b'}' if parens > 0 => {}
+            b'}' => braces = braces.saturating_sub(1),
+            b'@' => directive = true,
+            b if is_name_start(b) => {
+                let start = i;
+                while i < bytes.len() && (is_name_start(bytes[i]) || bytes[i].is_ascii_digit()) {
+                    i += 1;
+                }
+
+                let word = &document[start..i];
+                if braces == 0 && parens == 0 && !std::mem::replace(&mut directive, false) {
+                    match definition {
+                        None => definition = match word {
+                            "query" => Some(("query", None)),
+                            "mutation" => Some(("mutation", None)),
+                            "subscription" => Some(("subscription", None)),
+                            "fragment" => Some(("fragment", None)),
+                            _ => None,
+                        },
+                        Some((kind, None)) => definition = Some((kind, Some(word))),
+                        Some(_) => {}
+                    }
+                }
+
+                continue;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    match name {
+        Some(name) => operations.iter().find(|(_, n)| *n == Some(name)).map(|(kind, _)| *kind),
+        None if operations.len() == 1 => Some(operations[0].0),
+        None => None,
+    }
+}
+
+/// Serves an IDE pointed at the endpoint it's mounted alongside.
+#[derive(Clone)]
+struct IdeHandler(Ide);
+
+#[rocket::async_trait]
+impl Handler for IdeHandler {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        let endpoint = req.route().map(|route| route.base()).unwrap_or("/");
+        let html = match self.0 {
+            Ide::GraphiQL => graphiql_source(endpoint, None),
+            Ide::Playground => playground_source(endpoint, None),
+        };
+
+        Outcome::from(req, content::Html(html))
+    }
+}
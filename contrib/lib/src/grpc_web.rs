@@ -0,0 +1,367 @@
+//! gRPC-web requests served by [tonic](https://docs.rs/tonic) services.
+//!
+//! See the [`GrpcWeb`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `grpc_web` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["grpc_web"]
+//! ```
+//!
+//! # Protocol
+//!
+//! [gRPC-web] carries gRPC calls over plain HTTP/1.1 requests, as browsers
+//! can't make HTTP/2 requests with trailers. Requests are `POST`s to
+//! `/{service}/{method}` whose `Content-Type` is `application/grpc-web`, or
+//! `application/grpc-web-text` for base64-encoded bodies, optionally suffixed
+//! with `+proto`. The request body is the same sequence of length-prefixed
+//! messages a gRPC request carries. The response body is the sequence of
+//! messages of the gRPC response followed by its trailers, encoded as a final
+//! frame, so that clients can read the call's status without HTTP trailers.
+//!
+//! [`GrpcWeb`] translates between the two, so a tonic service can be served
+//! alongside a REST API on the same port. Requests that aren't gRPC-web are
+//! forwarded.
+//!
+//! [gRPC-web]: https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md
+//!
+//! # Limits
+//!
+//! Request bodies are limited to 4MiB by default, the default maximum size of
+//! a gRPC message. The limit can be changed by setting the `limits.grpc`
+//! configuration parameter. Response bodies are streamed as the service
+//! produces them, so server streaming calls are supported.
+//!
+//! # Cross-Origin Requests
+//!
+//! Browser clients are usually served from a different origin than the API.
+//! Such clients require [CORS](crate::cors) to allow their headers and to
+//! expose the `grpc-status` and `grpc-message` headers of trailers-only
+//! responses:
+//!
+//! ```rust
+//! # extern crate rocket_contrib;
+//! use rocket_contrib::cors::Cors;
+//!
+//! let cors = Cors::new()
+//!     .allow_headers(vec!["Content-Type", "X-Grpc-Web", "X-User-Agent", "Grpc-Timeout"])
+//!     .expose_headers(vec!["Grpc-Status", "Grpc-Message"]);
+//! ```
+
+pub extern crate tonic;
+
+use std::fmt::Display;
+use std::io::{self, Cursor, Read};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use rocket::{Request, Response, Data, Route};
+use rocket::data::ByteUnit;
+use rocket::futures::{future::poll_fn, ready};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{Method, Status};
+use rocket::http::hyper::{self, header, Body, Bytes, HeaderMap, HeaderName, HeaderValue};
+use rocket::http::hyper::{HttpBody, Service, Version};
+use rocket::tokio::io::AsyncRead;
+
+use self::tonic::transport::NamedService;
+
+/// Default limit for gRPC-web request bodies is 4MiB.
+const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(4);
+
+/// The flag marking the frame that carries a response's trailers.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// A handler that serves gRPC-web requests with a tonic service.
+///
+/// `GrpcWeb` implements [`Into<Vec<Route>>`], so it can be passed directly to
+/// [`Rocket::mount()`](rocket::Rocket::mount()). It should be mounted at `/`:
+/// it routes `POST` requests to `/{service}/{method}`, where `service` is the
+/// fully qualified name of the wrapped service, as gRPC-web clients expect.
+/// Any number of services can be mounted alongside regular routes.
+///
+/// Each request is translated into a gRPC request and passed to the service.
+/// Its response is translated back into a gRPC-web response, as described in
+/// the [module documentation](crate::grpc_web). Requests that aren't gRPC-web
+/// are forwarded.
+///
+/// # Example
+///
+/// Serving `GreeterServer`, generated by `tonic-build` from the `Greeter`
+/// service of the `helloworld` package, at `/helloworld.Greeter/{method}`:
+///
+/// ```rust,ignore
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::grpc_web::GrpcWeb;
+/// use hello_world::greeter_server::GreeterServer;
+///
+/// #[get("/")]
+/// fn index() -> &'static str {
+///     "Hello, REST!"
+/// }
+///
+/// fn main() {
+///     rocket::ignite()
+///         .mount("/", routes![index])
+///         .mount("/", GrpcWeb::new(GreeterServer::new(MyGreeter::default())))
+///         // ...
+///     # ;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct GrpcWeb<S> {
+    service: S,
+}
+
+impl<S: NamedService> GrpcWeb<S> {
+    /// Returns a handler serving gRPC-web requests with `service`.
+    pub fn new(service: S) -> Self {
+        GrpcWeb { service }
+    }
+}
+
+/// The encoding of a gRPC-web request or response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Binary,
+    Text,
+}
+
+impl Encoding {
+    /// Returns the encoding of `req`'s body, if `req` is a gRPC-web request.
+    fn of(req: &Request<'_>) -> Option<Encoding> {
+        let content_type = req.content_type()?;
+        if content_type.top() != "application" {
+            return None;
+        }
+
+        let sub = content_type.sub();
+        if sub == "grpc-web" || sub == "grpc-web+proto" {
+            Some(Encoding::Binary)
+        } else if sub == "grpc-web-text" || sub == "grpc-web-text+proto" {
+            Some(Encoding::Text)
+        } else {
+            None
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Encoding::Binary => "application/grpc-web+proto",
+            Encoding::Text => "application/grpc-web-text+proto",
+        }
+    }
+}
+
+impl<S, B> Into<Vec<Route>> for GrpcWeb<S>
+    where S: Service<hyper::Request<Body>, Response = hyper::Response<B>> + NamedService,
+          S: Clone + Send + Sync + 'static,
+          S::Future: Send,
+          S::Error: Display,
+          B: HttpBody<Data = Bytes> + Send + 'static,
+          B::Error: Display
+{
+    fn into(self) -> Vec<Route> {
+        vec![Route::new(Method::Post, format!("/{}/<method>", S::NAME), self)]
+    }
+}
+
+#[rocket::async_trait]
+impl<S, B> Handler for GrpcWeb<S>
+    where S: Service<hyper::Request<Body>, Response = hyper::Response<B>> + NamedService,
+          S: Clone + Send + Sync + 'static,
+          S::Future: Send,
+          S::Error: Display,
+          B: HttpBody<Data = Bytes> + Send + 'static,
+          B::Error: Display
+{
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        let encoding = match Encoding::of(req) {
+            Some(encoding) => encoding,
+            None => return Outcome::forward(data),
+        };
+
+        // The service routes by `/{service}/{method}`, whatever the mount point.
+        let path = match req.get_param::<String>(0) {
+            Some(Ok(method)) => format!("/{}/{}", S::NAME, method),
+            _ => return Outcome::forward(data),
+        };
+
+        let limit = req.limits().get("grpc").unwrap_or(DEFAULT_LIMIT);
+        let body = match data.open(limit).into_bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                error_!("Failed to read gRPC-web request body: {}", e);
+                return Outcome::failure(Status::BadRequest);
+            }
+        };
+
+        let body = match encoding {
+            Encoding::Binary => body.to_vec(),
+            Encoding::Text => match base64::decode(&body) {
+                Ok(body) => body,
+                Err(e) => {
+                    error_!("Invalid base64 gRPC-web request body: {}", e);
+                    return Outcome::failure(Status::BadRequest);
+                }
+            }
+        };
+
+        let mut service = self.service.clone();
+        if let Err(e) = poll_fn(|cx| service.poll_ready(cx)).await {
+            error_!("gRPC service '{}' is unavailable: {}", S::NAME, e);
+            return Outcome::failure(Status::ServiceUnavailable);
+        }
+
+        let response = match service.call(grpc_request(req, &path, body)).await {
+            Ok(response) => response,
+            Err(e) => {
+                error_!("gRPC service '{}' failed: {}", S::NAME, e);
+                return Outcome::failure(Status::InternalServerError);
+            }
+        };
+
+        let (parts, body) = response.into_parts();
+        let mut response = Response::build();
+        response.status(Status::from_code(parts.status.as_u16()).unwrap_or(Status::Ok));
+        for (name, value) in parts.headers.iter() {
+            let name = name.as_str();
+            if name == "content-type" || name == "content-length" {
+                continue;
+            }
+
+            if let Ok(value) = value.to_str() {
+                response.raw_header_adjoin(name.to_string(), value.to_string());
+            }
+        }
+
+        response.raw_header("Content-Type", encoding.content_type())
+            .streamed_body(WebBody::new(body, encoding));
+
+        Outcome::Success(response.finalize())
+    }
+}
+
+/// Returns the gRPC request equivalent to the gRPC-web request `req` to the
+/// service path `path` with the decoded body `body`.
+fn grpc_request(req: &Request<'_>, path: &str, body: Vec<u8>) -> hyper::Request<Body> {
+    let mut request = hyper::Request::new(Body::from(body));
+    *request.method_mut() = hyper::Method::POST;
+    *request.version_mut() = Version::HTTP_2;
+    if let Ok(uri) = path.parse() {
+        *request.uri_mut() = uri;
+    }
+
+    let headers = request.headers_mut();
+    for header in req.headers().iter() {
+        let name = match HeaderName::from_bytes(header.name().as_str().as_bytes()) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+
+        if name == header::CONTENT_TYPE || name == header::CONTENT_LENGTH {
+            continue;
+        }
+
+        if let Ok(value) = HeaderValue::from_str(header.value()) {
+            headers.append(name, value);
+        }
+    }
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    headers.insert(header::TE, HeaderValue::from_static("trailers"));
+    request
+}
+
+/// Returns the frame carrying `trailers` in a gRPC-web response.
+fn trailers_frame(trailers: &HeaderMap) -> Vec<u8> {
+    let mut block = vec![];
+    for (name, value) in trailers.iter() {
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.push(b':');
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+
+    let mut frame = Vec::with_capacity(block.len() + 5);
+    frame.push(TRAILERS_FLAG);
+    frame.extend_from_slice(&(block.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&block);
+    frame
+}
+
+/// The state of a [`WebBody`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    Trailers,
+    Done,
+}
+
+/// A gRPC-web response body: the data of a gRPC response body followed by
+/// its trailers frame, each chunk base64-encoded in text mode.
+struct WebBody<B> {
+    body: Pin<Box<B>>,
+    encoding: Encoding,
+    state: State,
+    chunk: Cursor<Vec<u8>>,
+}
+
+impl<B> WebBody<B> {
+    fn new(body: B, encoding: Encoding) -> Self {
+        WebBody { body: Box::pin(body), encoding, state: State::Data, chunk: Cursor::new(vec![]) }
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> AsyncRead for WebBody<B> where B::Error: Display {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        loop {
+            let n = this.chunk.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Poll::Ready(Ok(n));
+            }
+
+            let chunk = match this.state {
+                State::Data => match ready!(this.body.as_mut().poll_data(cx)) {
+                    Some(Ok(data)) => data.to_vec(),
+                    Some(Err(e)) => return Poll::Ready(Err(body_error(e))),
+                    None => {
+                        this.state = State::Trailers;
+                        continue;
+                    }
+                },
+                State::Trailers => {
+                    let trailers = ready!(this.body.as_mut().poll_trailers(cx));
+                    this.state = State::Done;
+                    match trailers {
+                        Ok(Some(trailers)) => trailers_frame(&trailers),
+                        Ok(None) => continue,
+                        Err(e) => return Poll::Ready(Err(body_error(e))),
+                    }
+                }
+                State::Done => return Poll::Ready(Ok(0)),
+            };
+
+            this.chunk = match this.encoding {
+                Encoding::Binary => Cursor::new(chunk),
+                Encoding::Text => Cursor::new(base64::encode(&chunk).into_bytes()),
+            };
+        }
+    }
+}
+
+fn body_error<E: Display>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("gRPC response body failed: {}", e))
+}
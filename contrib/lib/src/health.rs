@@ -0,0 +1,342 @@
+//! Liveness and readiness endpoints aggregating application health checks.
+//!
+//! See the [`HealthChecks`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `health` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["health"]
+//! ```
+
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rocket::{Data, Request, Rocket, Route, Shutdown};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::futures::future::{join_all, BoxFuture, FutureExt};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{Method, Status};
+use rocket::response::{content, status};
+
+use serde_json::{json, Value};
+
+/// The time a check may take, unless configured otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+type CheckFn = Arc<dyn Fn(&Request<'_>) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// A named health check.
+#[derive(Clone)]
+struct Check {
+    name: String,
+    run: CheckFn,
+}
+
+/// A fairing that mounts liveness and readiness endpoints reporting the
+/// outcome of registered health checks.
+///
+/// When attached, `HealthChecks` mounts two routes:
+///
+///   * `GET /healthz`, the liveness endpoint, runs the checks registered with
+///     [`HealthChecks::liveness()`]. A failing liveness check indicates that
+///     the application can't recover and should be restarted.
+///   * `GET /readyz`, the readiness endpoint, runs the checks registered with
+///     [`HealthChecks::readiness()`]. A failing readiness check indicates that
+///     the application shouldn't be sent traffic for now.
+///
+/// The paths can be changed with [`HealthChecks::liveness_path()`] and
+/// [`HealthChecks::readiness_path()`]. A check is an async function returning
+/// `Ok(())` if it passes or an error describing the failure otherwise. The
+/// checks of an endpoint run concurrently, and a check that doesn't complete
+/// within the timeout, five seconds by default, fails.
+///
+/// Endpoints respond with `200 OK` if every check passes and with
+/// `503 Service Unavailable` otherwise. The body is a JSON report with the
+/// status of each check, `"pass"` or `"fail"`, and the time it took:
+///
+/// ```json
+/// {
+///   "status": "fail",
+///   "checks": [
+///     { "name": "cache", "status": "pass", "duration_ms": 2 },
+///     { "name": "disk", "status": "fail", "duration_ms": 0, "error": "disk is full" }
+///   ]
+/// }
+/// ```
+///
+/// # Graceful Shutdown
+///
+/// Once a [shutdown](rocket::Shutdown) is requested, the readiness endpoint
+/// stops running checks and responds with `503 Service Unavailable` and the
+/// status `"draining"`, so that load balancers stop routing traffic to the
+/// application while in-flight requests complete. The liveness endpoint is
+/// unaffected.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use std::time::Duration;
+/// use rocket_contrib::health::HealthChecks;
+///
+/// # async fn free_disk_space() -> u64 { 1 << 30 }
+/// let health = HealthChecks::new()
+///     .timeout(Duration::from_secs(2))
+///     .readiness("disk", || async {
+///         match free_disk_space().await {
+///             0 => Err("disk is full"),
+///             _ => Ok(()),
+///         }
+///     });
+///
+/// rocket::ignite().attach(health)
+/// # ;
+/// ```
+///
+/// The health of a [database](crate::databases) is checked by attaching its
+/// [`HealthCheck`](crate::databases::HealthCheck) fairing and registering it
+/// with [`HealthChecks::database()`].
+#[derive(Clone)]
+pub struct HealthChecks {
+    liveness_path: String,
+    readiness_path: String,
+    timeout: Duration,
+    liveness: Vec<Check>,
+    readiness: Vec<Check>,
+}
+
+impl HealthChecks {
+    /// Returns a fairing without checks, serving its endpoints at `/healthz`
+    /// and `/readyz`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::health::HealthChecks;
+    ///
+    /// let health = HealthChecks::new();
+    /// ```
+    pub fn new() -> Self {
+        HealthChecks {
+            liveness_path: "/healthz".into(),
+            readiness_path: "/readyz".into(),
+            timeout: DEFAULT_TIMEOUT,
+            liveness: vec![],
+            readiness: vec![],
+        }
+    }
+
+    /// Sets the path of the liveness endpoint.
+    pub fn liveness_path<P: Into<String>>(mut self, path: P) -> Self {
+        self.liveness_path = path.into();
+        self
+    }
+
+    /// Sets the path of the readiness endpoint.
+    pub fn readiness_path<P: Into<String>>(mut self, path: P) -> Self {
+        self.readiness_path = path.into();
+        self
+    }
+
+    /// Sets the time each check may take before it fails.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Registers the liveness check `check` named `name`.
+    pub fn liveness<N, F, Fut, E>(mut self, name: N, check: F) -> Self
+        where N: Into<String>,
+              F: Fn() -> Fut + Send + Sync + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+              E: Display
+    {
+        self.liveness.push(Check::new(name, check));
+        self
+    }
+
+    /// Registers the readiness check `check` named `name`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::health::HealthChecks;
+    ///
+    /// # async fn ping_cache() -> Result<(), std::io::Error> { Ok(()) }
+    /// let health = HealthChecks::new().readiness("cache", || ping_cache());
+    /// ```
+    pub fn readiness<N, F, Fut, E>(mut self, name: N, check: F) -> Self
+        where N: Into<String>,
+              F: Fn() -> Fut + Send + Sync + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+              E: Display
+    {
+        self.readiness.push(Check::new(name, check));
+        self
+    }
+
+    /// Registers a readiness check of the database `D`, named after the
+    /// database. The check passes if the latest report of the database's
+    /// [`HealthCheck`](crate::databases::HealthCheck) fairing, which must be
+    /// attached, is healthy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+    /// use std::time::Duration;
+    /// use rocket_contrib::databases::{Database, sqlx};
+    /// use rocket_contrib::health::HealthChecks;
+    ///
+    /// #[derive(Database)]
+    /// #[database("sqlite_logs")]
+    /// struct Logs(sqlx::SqlitePool);
+    ///
+    /// fn rocket() -> rocket::Rocket {
+    ///     rocket::ignite()
+    ///         .attach(Logs::init())
+    ///         .attach(Logs::health_check(Duration::from_secs(10)))
+    ///         .attach(HealthChecks::new().database::<Logs>())
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "databases")]
+    pub fn database<D: crate::databases::Database>(mut self) -> Self {
+        use crate::databases::Health;
+
+        let run: CheckFn = Arc::new(|req: &Request<'_>| {
+            let result = match req.managed_state::<Health<D>>().map(|h| h.latest()) {
+                Some(Some(report)) => report.result.map(|_| ()),
+                Some(None) => Err("the database hasn't been checked yet".into()),
+                None => Err("the database's health check isn't attached".into()),
+            };
+
+            rocket::futures::future::ready(result).boxed()
+        });
+
+        self.readiness.push(Check { name: D::NAME.into(), run });
+        self
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        HealthChecks::new()
+    }
+}
+
+impl Check {
+    fn new<N, F, Fut, E>(name: N, check: F) -> Self
+        where N: Into<String>,
+              F: Fn() -> Fut + Send + Sync + 'static,
+              Fut: Future<Output = Result<(), E>> + Send + 'static,
+              E: Display
+    {
+        let run: CheckFn = Arc::new(move |_: &Request<'_>| {
+            check().map(|result| result.map_err(|e| e.to_string())).boxed()
+        });
+
+        Check { name: name.into(), run }
+    }
+
+    /// Runs the check, failing it if it takes longer than `timeout`.
+    async fn report(&self, req: &Request<'_>, timeout: Duration) -> (bool, Value) {
+        let start = Instant::now();
+        let result = match rocket::tokio::time::timeout(timeout, (self.run)(req)).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+        };
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        match result {
+            Ok(()) => (true, json!({
+                "name": self.name,
+                "status": "pass",
+                "duration_ms": duration_ms,
+            })),
+            Err(e) => (false, json!({
+                "name": self.name,
+                "status": "fail",
+                "duration_ms": duration_ms,
+                "error": e,
+            })),
+        }
+    }
+}
+
+/// Serves an endpoint: runs its checks and reports their outcome.
+#[derive(Clone)]
+struct Endpoint {
+    checks: Arc<Vec<Check>>,
+    timeout: Duration,
+    /// Whether the endpoint reports failure once a shutdown is requested.
+    drains: bool,
+}
+
+impl Endpoint {
+    fn respond<'r>(req: &'r Request<'_>, status: Status, report: Value) -> Outcome<'r> {
+        Outcome::from(req, status::Custom(status, content::Json(report.to_string())))
+    }
+}
+
+#[rocket::async_trait]
+impl Handler for Endpoint {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        if self.drains {
+            let draining = req.guard::<Shutdown>().await
+                .map(|shutdown| shutdown.reason().is_some())
+                .succeeded()
+                .unwrap_or(false);
+
+            if draining {
+                let report = json!({ "status": "draining", "checks": [] });
+                return Endpoint::respond(req, Status::ServiceUnavailable, report);
+            }
+        }
+
+        let reports = join_all(self.checks.iter().map(|c| c.report(req, self.timeout))).await;
+        let passed = reports.iter().all(|(passed, _)| *passed);
+        let checks: Vec<Value> = reports.into_iter().map(|(_, report)| report).collect();
+        if passed {
+            Endpoint::respond(req, Status::Ok, json!({ "status": "pass", "checks": checks }))
+        } else {
+            let report = json!({ "status": "fail", "checks": checks });
+            Endpoint::respond(req, Status::ServiceUnavailable, report)
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for HealthChecks {
+    fn info(&self) -> Info {
+        Info { name: "Health Checks", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let liveness = Endpoint {
+            checks: Arc::new(self.liveness.clone()),
+            timeout: self.timeout,
+            drains: false,
+        };
+
+        let readiness = Endpoint {
+            checks: Arc::new(self.readiness.clone()),
+            timeout: self.timeout,
+            drains: true,
+        };
+
+        Ok(rocket.mount("/", vec![
+            Route::new(Method::Get, &self.liveness_path, liveness),
+            Route::new(Method::Get, &self.readiness_path, readiness),
+        ]))
+    }
+}
@@ -0,0 +1,85 @@
+use rocket::http::uncased::UncasedStr;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+
+use crate::helmet::Policy;
+
+/// The policies a response is exempt from, as recorded by [`Exempt`].
+pub(crate) enum Exemptions {
+    All,
+    Some(Vec<&'static UncasedStr>),
+}
+
+impl Exemptions {
+    pub(crate) fn of<'r>(req: &'r Request<'_>) -> Option<&'r Exemptions> {
+        req.local_cache(|| None::<Exemptions>).as_ref()
+    }
+
+    pub(crate) fn contains(&self, name: &UncasedStr) -> bool {
+        match self {
+            Exemptions::All => true,
+            Exemptions::Some(names) => names.iter().any(|n| *n == name),
+        }
+    }
+}
+
+/// A [`Responder`] that exempts its response from some or all of the policies
+/// of an attached [`SpaceHelmet`](crate::helmet::SpaceHelmet).
+///
+/// Wrap a route's response in `Exempt` to opt that route out of policies that
+/// don't suit it: a page meant to be embedded by other sites, for instance,
+/// should not be sent with [`Frame`](crate::helmet::Frame) or
+/// [`FrameAncestors`](crate::helmet::FrameAncestors).
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::helmet::{Exempt, Frame, FrameAncestors};
+///
+/// #[get("/widget")]
+/// fn widget() -> Exempt<&'static str> {
+///     Exempt::from::<Frame>("<p>embed me</p>").and::<FrameAncestors>()
+/// }
+///
+/// #[get("/raw")]
+/// fn raw() -> Exempt<&'static str> {
+///     Exempt::all("no security headers here")
+/// }
+/// ```
+pub struct Exempt<R> {
+    responder: R,
+    exemptions: Exemptions,
+}
+
+impl<R> Exempt<R> {
+    /// Exempts the response of `responder` from all policies.
+    pub fn all(responder: R) -> Self {
+        Exempt { responder, exemptions: Exemptions::All }
+    }
+
+    /// Exempts the response of `responder` from the policy `P`.
+    pub fn from<P: Policy>(responder: R) -> Self {
+        Exempt { responder, exemptions: Exemptions::Some(vec![P::NAME.into()]) }
+    }
+
+    /// Additionally exempts the response from the policy `P`.
+    pub fn and<P: Policy>(mut self) -> Self {
+        if let Exemptions::Some(ref mut names) = self.exemptions {
+            names.push(P::NAME.into());
+        }
+
+        self
+    }
+}
+
+/// Records the exemptions on the request and responds with the wrapped
+/// responder.
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Exempt<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let exemptions = self.exemptions;
+        req.local_cache(move || Some(exemptions));
+        self.responder.respond_to(req)
+    }
+}
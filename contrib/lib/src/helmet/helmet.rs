@@ -2,10 +2,14 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use rocket::http::uncased::UncasedStr;
-use rocket::fairing::{Fairing, Info, Kind};
-use rocket::{Cargo, Request, Response};
+use rocket::http::{uri::Uri, ext::IntoOwned};
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::config::{ConfigError, Value};
+use rocket::{Cargo, Config, Request, Response, Rocket};
+use time::Duration;
 
 use crate::helmet::*;
+use crate::helmet::exempt::Exemptions;
 
 /// A [`Fairing`](../../rocket/fairing/trait.Fairing.html) that adds HTTP
 /// headers to outgoing responses that control security features on the browser.
@@ -165,11 +169,179 @@ impl SpaceHelmet {
         self.policies.contains_key(UncasedStr::new(P::NAME))
     }
 
+    /// Returns a fairing that configures a `SpaceHelmet` from the `helmet`
+    /// configuration table and attaches it. If the table is missing,
+    /// [`SpaceHelmet::default()`] is attached. If the table is invalid, an
+    /// error is logged and launch is aborted. See [`SpaceHelmet::from_config()`]
+    /// for the recognized keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::helmet::SpaceHelmet;
+    ///
+    /// rocket::ignite().attach(SpaceHelmet::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach("Space Helmet Configuration", |mut rocket: Rocket| async move {
+            let result = SpaceHelmet::from_config(rocket.config().await);
+            match result {
+                Ok(helmet) => Ok(rocket.attach(helmet)),
+                Err(e) => {
+                    e.pretty_print();
+                    error_!("Invalid Space Helmet configuration.");
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Configures a `SpaceHelmet` from the `helmet` table in `config`,
+    /// starting from [`SpaceHelmet::default()`]. Each key enables, replaces,
+    /// or, when set to `false`, disables one policy:
+    ///
+    /// | Key               | Policy             | Value                                         |
+    /// | ----------------- | ------------------ | --------------------------------------------- |
+    /// | `hsts`            | [`Hsts`]           | `true` or the max-age in seconds              |
+    /// | `no_sniff`        | [`NoSniff`]        | `true`                                        |
+    /// | `xss_filter`      | [`XssFilter`]      | `true`                                        |
+    /// | `frame`           | [`Frame`]          | `"deny"` or `"sameorigin"`                    |
+    /// | `frame_ancestors` | [`FrameAncestors`] | an array of origins, `"self"`, or `"*"`       |
+    /// | `referrer`        | [`Referrer`]       | a policy, such as `"no-referrer"`             |
+    /// | `permissions`     | [`Permission`]     | a table of features to arrays of origins      |
+    ///
+    /// An empty array for `frame_ancestors` or for a feature in `permissions`
+    /// denies all origins. For example:
+    ///
+    /// ```toml
+    /// [global.helmet]
+    /// hsts = 31536000
+    /// xss_filter = false
+    /// frame_ancestors = ["self", "https://partner.example.com"]
+    /// referrer = "strict-origin-when-cross-origin"
+    /// permissions = { camera = [], geolocation = ["self"] }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `BadType` error if a value is of the wrong type or is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, Table, Value};
+    /// use rocket_contrib::helmet::{SpaceHelmet, Hsts, XssFilter};
+    ///
+    /// let mut table = Table::new();
+    /// table.insert("hsts".into(), Value::from(true));
+    /// table.insert("xss_filter".into(), Value::from(false));
+    ///
+    /// let config = Config::build(Environment::Production)
+    ///     .extra("helmet", table)
+    ///     .unwrap();
+    ///
+    /// let helmet = SpaceHelmet::from_config(&config).unwrap();
+    /// assert!(helmet.is_enabled::<Hsts>());
+    /// assert!(!helmet.is_enabled::<XssFilter>());
+    /// ```
+    pub fn from_config(config: &Config) -> Result<SpaceHelmet, ConfigError> {
+        let table = match config.get_table("helmet") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(SpaceHelmet::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut helmet = SpaceHelmet::default();
+        for (key, value) in table {
+            let bad_type = |expected| {
+                ConfigError::BadType(format!("helmet.{}", key), expected, value.type_str(), None)
+            };
+
+            if value.as_bool() == Some(false) {
+                helmet = match key.as_str() {
+                    "hsts" => helmet.disable::<Hsts>(),
+                    "no_sniff" => helmet.disable::<NoSniff>(),
+                    "xss_filter" => helmet.disable::<XssFilter>(),
+                    "frame" => helmet.disable::<Frame>(),
+                    "frame_ancestors" => helmet.disable::<FrameAncestors>(),
+                    "referrer" => helmet.disable::<Referrer>(),
+                    "permissions" => helmet.disable::<Permission>(),
+                    _ => {
+                        warn_!("Ignoring unknown Space Helmet configuration key '{}'.", key);
+                        helmet
+                    }
+                };
+
+                continue;
+            }
+
+            helmet = match key.as_str() {
+                "hsts" => match value {
+                    Value::Boolean(true) => helmet.enable(Hsts::default()),
+                    Value::Integer(age) if *age >= 0 => {
+                        helmet.enable(Hsts::Enable(Duration::seconds(*age)))
+                    }
+                    _ => return Err(bad_type("a boolean or a non-negative integer")),
+                },
+                "no_sniff" if value.as_bool().is_some() => helmet.enable(NoSniff::default()),
+                "xss_filter" if value.as_bool().is_some() => helmet.enable(XssFilter::default()),
+                "frame" => match value.as_str().map(|s| s.to_ascii_lowercase()).as_deref() {
+                    Some("deny") => helmet.enable(Frame::Deny),
+                    Some("sameorigin") => helmet.enable(Frame::SameOrigin),
+                    _ => return Err(bad_type("\"deny\", \"sameorigin\", or false")),
+                },
+                "frame_ancestors" => match allowlist(value) {
+                    Some(list) if list.is_empty() => helmet.enable(FrameAncestors::None),
+                    Some(list) => helmet.enable(FrameAncestors::Allow(list)),
+                    None => return Err(bad_type("an array of origins or false")),
+                },
+                "referrer" => match value.as_str().and_then(referrer) {
+                    Some(policy) => helmet.enable(policy),
+                    None => return Err(bad_type("a referrer policy or false")),
+                },
+                "permissions" => {
+                    let features = value.as_table()
+                        .ok_or_else(|| bad_type("a table of features or false"))?;
+
+                    let mut permission = Permission::new();
+                    for (feature, value) in features {
+                        let list = allowlist(value)
+                            .ok_or_else(|| bad_type("a table of arrays of origins"))?;
+
+                        permission = permission.block(feature.clone());
+                        for allow in list {
+                            permission = permission.allow(feature.clone(), allow);
+                        }
+                    }
+
+                    helmet.enable(permission)
+                }
+                "no_sniff" | "xss_filter" => return Err(bad_type("a boolean")),
+                _ => {
+                    warn_!("Ignoring unknown Space Helmet configuration key '{}'.", key);
+                    helmet
+                }
+            };
+        }
+
+        Ok(helmet)
+    }
+
     /// Sets all of the headers in `self.policies` in `response` as long as the
-    /// header is not already in the response.
-    fn apply(&self, response: &mut Response<'_>) {
+    /// header is not already in the response and the response isn't
+    /// [`Exempt`] from the policy.
+    fn apply(&self, request: &Request<'_>, response: &mut Response<'_>) {
+        let exemptions = Exemptions::of(request);
+        let exempt = |name: &UncasedStr| exemptions.map_or(false, |e| e.contains(name));
         for policy in self.policies.values() {
             let name = policy.name();
+            if exempt(name) {
+                continue
+            }
+
             if response.headers().contains(name.as_str()) {
                 warn!("Space Helmet: response contains a '{}' header.", name);
                 warn_!("Refusing to overwrite existing header.");
@@ -180,7 +352,7 @@ impl SpaceHelmet {
             response.set_header(policy.header());
         }
 
-        if self.force_hsts.load(Ordering::Relaxed) {
+        if self.force_hsts.load(Ordering::Relaxed) && !exempt(UncasedStr::new(Hsts::NAME)) {
             if !response.headers().contains(Hsts::NAME) {
                 response.set_header(&Hsts::default());
             }
@@ -197,8 +369,8 @@ impl Fairing for SpaceHelmet {
         }
     }
 
-    async fn on_response<'r>(&self, _: &'r Request<'_>, res: &mut Response<'r>) {
-        self.apply(res);
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        self.apply(req, res);
     }
 
     fn on_launch(&self, cargo: &Cargo) {
@@ -213,3 +385,35 @@ impl Fairing for SpaceHelmet {
         }
     }
 }
+
+/// Parses an allowlist: an array of origins, `"self"`, and `"*"`, or one such
+/// string on its own.
+fn allowlist(value: &Value) -> Option<Vec<Allow>> {
+    let parse = |value: &Value| match value.as_str()? {
+        "*" => Some(Allow::Any),
+        "self" | "'self'" => Some(Allow::This),
+        origin => Uri::parse(origin).ok().map(|uri| Allow::Origin(uri.into_owned())),
+    };
+
+    match value {
+        Value::Array(values) => values.iter().map(parse).collect(),
+        value => parse(value).map(|allow| vec![allow]),
+    }
+}
+
+/// Parses a `Referrer-Policy` value.
+fn referrer(policy: &str) -> Option<Referrer> {
+    let policy = match policy.to_ascii_lowercase().as_str() {
+        "no-referrer" => Referrer::NoReferrer,
+        "no-referrer-when-downgrade" => Referrer::NoReferrerWhenDowngrade,
+        "origin" => Referrer::Origin,
+        "origin-when-cross-origin" => Referrer::OriginWhenCrossOrigin,
+        "same-origin" => Referrer::SameOrigin,
+        "strict-origin" => Referrer::StrictOrigin,
+        "strict-origin-when-cross-origin" => Referrer::StrictOriginWhenCrossOrigin,
+        "unsafe-url" => Referrer::UnsafeUrl,
+        _ => return None,
+    };
+
+    Some(policy)
+}
@@ -23,15 +23,17 @@
 //!
 //! # Supported Headers
 //!
-//! | HTTP Header                 | Description                            | Policy        | Default? |
-//! | --------------------------- | -------------------------------------- | ------------- | -------- |
-//! | [X-XSS-Protection]          | Prevents some reflected XSS attacks.   | [`XssFilter`] | ✔        |
-//! | [X-Content-Type-Options]    | Prevents client sniffing of MIME type. | [`NoSniff`]   | ✔        |
-//! | [X-Frame-Options]           | Prevents [clickjacking].               | [`Frame`]     | ✔        |
-//! | [Strict-Transport-Security] | Enforces strict use of HTTPS.          | [`Hsts`]      | ?        |
-//! | [Expect-CT]                 | Enables certificate transparency.      | [`ExpectCt`]  | ✗        |
-//! | [Referrer-Policy]           | Enables referrer policy.               | [`Referrer`]  | ✗        |
-//! | [X-DNS-Prefetch-Control]    | Controls browser DNS prefetching.      | [`Prefetch`]  | ✗        |
+//! | HTTP Header                 | Description                            | Policy             | Default? |
+//! | --------------------------- | -------------------------------------- | ------------------ | -------- |
+//! | [X-XSS-Protection]          | Prevents some reflected XSS attacks.   | [`XssFilter`]      | ✔        |
+//! | [X-Content-Type-Options]    | Prevents client sniffing of MIME type. | [`NoSniff`]        | ✔        |
+//! | [X-Frame-Options]           | Prevents [clickjacking].               | [`Frame`]          | ✔        |
+//! | [Strict-Transport-Security] | Enforces strict use of HTTPS.          | [`Hsts`]           | ?        |
+//! | [Expect-CT]                 | Enables certificate transparency.      | [`ExpectCt`]       | ✗        |
+//! | [Referrer-Policy]           | Enables referrer policy.               | [`Referrer`]       | ✗        |
+//! | [X-DNS-Prefetch-Control]    | Controls browser DNS prefetching.      | [`Prefetch`]       | ✗        |
+//! | [CSP frame-ancestors]       | Prevents [clickjacking].               | [`FrameAncestors`] | ✗        |
+//! | [Permissions-Policy]        | Restricts use of browser features.     | [`Permission`]     | ✗        |
 //!
 //! <small>? If TLS is enabled when the application is launched, in a
 //! non-development environment (e.g., staging or production), HSTS is
@@ -45,6 +47,8 @@
 //! [Expect-CT]:  https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Expect-CT
 //! [Referrer-Policy]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Referrer-Policy
 //! [X-DNS-Prefetch-Control]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/X-DNS-Prefetch-Control
+//! [CSP frame-ancestors]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Security-Policy/frame-ancestors
+//! [Permissions-Policy]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Feature-Policy
 //! [clickjacking]: https://en.wikipedia.org/wiki/Clickjacking
 //!
 //! [`XssFilter`]: helmet::XssFilter
@@ -54,6 +58,8 @@
 //! [`ExpectCt`]: helmet::ExpectCt
 //! [`Referrer`]: helmet::Referrer
 //! [`Prefetch`]: helmet::Prefetch
+//! [`FrameAncestors`]: helmet::FrameAncestors
+//! [`Permission`]: helmet::Permission
 //!
 //! # Usage
 //!
@@ -90,6 +96,40 @@
 //!     .disable::<NoSniff>();
 //! ```
 //!
+//! # Configuration
+//!
+//! Instead of constructing a `SpaceHelmet` in code, an application can attach
+//! [`SpaceHelmet::fairing()`](helmet::SpaceHelmet::fairing()), which reads the
+//! policies from the `helmet` table in `Rocket.toml`:
+//!
+//! ```toml
+//! [global.helmet]
+//! hsts = 31536000
+//! frame_ancestors = ["self"]
+//! referrer = "strict-origin-when-cross-origin"
+//! permissions = { camera = [], microphone = [] }
+//! ```
+//!
+//! See [`SpaceHelmet::from_config()`](helmet::SpaceHelmet::from_config()) for
+//! the recognized keys.
+//!
+//! # Per-Route Exemptions
+//!
+//! A route whose response shouldn't carry some or all of the headers, such as
+//! a page meant to be embedded by other sites, can wrap its response in
+//! [`Exempt`](helmet::Exempt):
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket_contrib::helmet::{Exempt, Frame, FrameAncestors};
+//!
+//! #[get("/embed")]
+//! fn embed() -> Exempt<&'static str> {
+//!     Exempt::from::<Frame>("embeddable").and::<FrameAncestors>()
+//! }
+//! ```
+//!
 //! # FAQ
 //!
 //! * **Which policies should I choose?**
@@ -108,6 +148,8 @@
 
 mod helmet;
 mod policy;
+mod exempt;
 
 pub use self::helmet::SpaceHelmet;
+pub use self::exempt::Exempt;
 pub use self::policy::*;
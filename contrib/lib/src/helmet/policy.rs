@@ -98,6 +98,8 @@ impl_policy!(Hsts, "Strict-Transport-Security");
 impl_policy!(ExpectCt, "Expect-CT");
 impl_policy!(Referrer, "Referrer-Policy");
 impl_policy!(Prefetch, "X-DNS-Prefetch-Control");
+impl_policy!(FrameAncestors, "Content-Security-Policy");
+impl_policy!(Permission, "Permissions-Policy");
 
 /// The [Referrer-Policy] header: controls the value set by the browser for the
 /// [Referer] header.
@@ -434,3 +436,159 @@ impl Into<Header<'static>> for &Prefetch {
         Header::new(Prefetch::NAME, policy_string)
     }
 }
+
+/// An origin in the allowlist of a [`FrameAncestors`] or [`Permission`]
+/// policy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Allow {
+    /// Any origin.
+    Any,
+
+    /// The origin of the document itself.
+    This,
+
+    /// The origin of `Uri`.
+    Origin(Uri<'static>),
+}
+
+/// The `frame-ancestors` directive of the [Content-Security-Policy] header:
+/// helps prevent [clickjacking] attacks.
+///
+/// Controls which origins may embed the page in a `<frame>`, `<iframe>`,
+/// `<object>`, or `<embed>`. This is the standardized successor of
+/// [`Frame`]: unlike `X-Frame-Options`, it accepts any number of origins.
+/// Browsers that understand both headers ignore `X-Frame-Options`, so the two
+/// can be enabled together to support older browsers.
+///
+/// This policy emits a `Content-Security-Policy` header containing only the
+/// `frame-ancestors` directive. As with all policies, the header is not set if
+/// the response already contains a `Content-Security-Policy` header.
+///
+/// [Content-Security-Policy]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Security-Policy/frame-ancestors
+/// [clickjacking]: https://en.wikipedia.org/wiki/Clickjacking
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameAncestors {
+    /// Page cannot be displayed in a frame.
+    None,
+
+    /// Page can only be displayed in a frame by pages from the listed origins.
+    Allow(Vec<Allow>),
+}
+
+/// Defaults to `FrameAncestors::Allow(vec![Allow::This])`, the equivalent of
+/// [`Frame::SameOrigin`].
+impl Default for FrameAncestors {
+    fn default() -> FrameAncestors {
+        FrameAncestors::Allow(vec![Allow::This])
+    }
+}
+
+impl Into<Header<'static>> for &FrameAncestors {
+    fn into(self) -> Header<'static> {
+        let sources = match self {
+            FrameAncestors::Allow(sources) if !sources.is_empty() => sources,
+            _ => return Header::new(FrameAncestors::NAME, "frame-ancestors 'none'"),
+        };
+
+        let sources: Vec<_> = sources.iter()
+            .map(|source| match source {
+                Allow::Any => "*".into(),
+                Allow::This => "'self'".into(),
+                Allow::Origin(uri) => uri.to_string(),
+            })
+            .collect();
+
+        Header::new(FrameAncestors::NAME, format!("frame-ancestors {}", sources.join(" ")))
+    }
+}
+
+/// The [Permissions-Policy] header: controls which browser features the page
+/// and its frames may use.
+///
+/// Each feature, such as `camera` or `geolocation`, is either blocked
+/// entirely or allowed for a list of origins. Features that aren't mentioned
+/// keep the browser's default policy.
+///
+/// [Permissions-Policy]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Feature-Policy
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::http::uri::Uri;
+/// use rocket_contrib::helmet::{Permission, Allow};
+///
+/// let maps = Uri::parse("https://maps.example.com").unwrap();
+/// let permission = Permission::new()
+///     .block("camera")
+///     .allow("geolocation", Allow::This)
+///     .allow("geolocation", Allow::Origin(maps));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permission(Vec<(Cow<'static, str>, Vec<Allow>)>);
+
+/// Defaults to blocking `camera`, `microphone`, `geolocation`, and
+/// `interest-cohort`.
+impl Default for Permission {
+    fn default() -> Permission {
+        Permission::new()
+            .block("camera")
+            .block("microphone")
+            .block("geolocation")
+            .block("interest-cohort")
+    }
+}
+
+impl Permission {
+    /// Returns a policy that mentions no features.
+    pub fn new() -> Permission {
+        Permission(vec![])
+    }
+
+    /// Blocks `feature` for all origins, replacing any previous allowlist.
+    pub fn block<F: Into<Cow<'static, str>>>(mut self, feature: F) -> Self {
+        *self.allowlist(feature.into()) = vec![];
+        self
+    }
+
+    /// Adds `allow` to the allowlist of `feature`.
+    pub fn allow<F: Into<Cow<'static, str>>>(mut self, feature: F, allow: Allow) -> Self {
+        self.allowlist(feature.into()).push(allow);
+        self
+    }
+
+    fn allowlist(&mut self, feature: Cow<'static, str>) -> &mut Vec<Allow> {
+        match self.0.iter().position(|(f, _)| *f == feature) {
+            Some(i) => &mut self.0[i].1,
+            None => {
+                self.0.push((feature, vec![]));
+                &mut self.0.last_mut().expect("just pushed").1
+            }
+        }
+    }
+}
+
+impl Into<Header<'static>> for &Permission {
+    fn into(self) -> Header<'static> {
+        let directives: Vec<_> = self.0.iter()
+            .map(|(feature, allowlist)| {
+                if allowlist.contains(&Allow::Any) {
+                    return format!("{}=*", feature);
+                }
+
+                let origins: Vec<_> = allowlist.iter()
+                    .map(|allow| match allow {
+                        Allow::This => "self".into(),
+                        Allow::Origin(uri) => format!("\"{}\"", uri),
+                        Allow::Any => unreachable!("handled above"),
+                    })
+                    .collect();
+
+                format!("{}=({})", feature, origins.join(" "))
+            })
+            .collect();
+
+        Header::new(Permission::NAME, directives.join(", "))
+    }
+}
@@ -0,0 +1,646 @@
+//! Localization with Fluent.
+//!
+//! See the [`I18n`] and [`Locales`] types for further details.
+//!
+//! # Overview
+//!
+//! Translations are written in [Fluent] and organized in a directory with one
+//! subdirectory per locale, each containing any number of `.ftl` files:
+//!
+//! ```text
+//! i18n/
+//!   en-US/
+//!     main.ftl
+//!   fr/
+//!     main.ftl
+//! ```
+//!
+//! The [`Locales`] in the directory are loaded by the fairing returned from
+//! [`I18n::fairing()`]. For every request, the locales the client prefers are
+//! negotiated against the available ones: a locale set in the `lang` cookie is
+//! preferred, followed by those listed in the `Accept-Language` header, with
+//! the default locale as the final fallback. Handlers translate messages in
+//! the negotiated locales via the [`I18n`] request guard. When templates are
+//! enabled, the `t` template function translates messages in templates.
+//!
+//! [Fluent]: https://projectfluent.org/
+//!
+//! # Enabling
+//!
+//! This module is only available when the `i18n` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["i18n"]
+//! ```
+//!
+//! # Templates
+//!
+//! To translate messages in templates, load the [`Locales`] up front, attach
+//! them with [`I18n::fairing_with()`], and register the `t` template function
+//! in the template customization callback with [`Locales::register()`]:
+//!
+//! ```rust,no_run
+//! use rocket_contrib::i18n::{I18n, Locales};
+//! use rocket_contrib::templates::Template;
+//!
+//! fn main() {
+//!     let locales = Locales::load("i18n", "en-US").expect("valid translations");
+//!     rocket::ignite()
+//!         .attach(I18n::fairing_with(locales.clone()))
+//!         .attach(Template::custom(move |engines| locales.register(engines)))
+//!         // ...
+//!     # ;
+//! }
+//! ```
+//!
+//! The negotiated locale is set as the global template context value `lang`
+//! for every request. In Tera templates, the message's key is passed as `key`,
+//! the locale as `lang`, and any other arguments are passed to the message:
+//!
+//! ```text
+//! {{ t(key="greeting", lang=lang, name=user.name) }}
+//! ```
+//!
+//! In Handlebars templates, the key is the first parameter. The locale
+//! defaults to the context's `lang` value:
+//!
+//! ```text
+//! {{t "greeting" name=user.name}}
+//! ```
+//!
+//! Translated messages, including their arguments, are escaped like any other
+//! value: by Tera in HTML and XML templates, and by Handlebars' registered
+//! escape function in all Handlebars templates.
+
+use std::fmt;
+use std::io;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use fluent_bundle::{FluentResource, FluentError};
+use fluent_bundle::bundle::FluentBundle;
+use fluent_langneg::{accepted_languages, negotiate_languages, NegotiationStrategy};
+use intl_memoizer::concurrent::IntlLangMemoizer;
+
+use rocket::{Config, Data, Request, Rocket};
+use rocket::config::ConfigError;
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::request::{self, FromRequest};
+use rocket::http::Status;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+pub use unic_langid::LanguageIdentifier;
+
+/// The directory translations are loaded from unless otherwise configured.
+const DEFAULT_DIR: &str = "i18n";
+
+/// The default locale unless otherwise configured.
+const DEFAULT_LOCALE: &str = "en-US";
+
+/// The cookie from which a client's preferred locale is read.
+pub const LOCALE_COOKIE: &str = "lang";
+
+/// The translations for one locale, which can be shared across threads.
+type Bundle = FluentBundle<FluentResource, IntlLangMemoizer>;
+
+/// An error that occurred while loading [`Locales`].
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the translation directory or a translation file failed.
+    Io(io::Error),
+    /// A locale directory or the default locale isn't a valid language
+    /// identifier.
+    InvalidLocale(String),
+    /// The translation file at the path contains invalid Fluent or messages
+    /// that are already defined for the locale.
+    Fluent(PathBuf, Vec<FluentError>),
+    /// There are no translations for the default locale.
+    MissingDefault(LanguageIdentifier),
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::InvalidLocale(locale) => write!(f, "invalid locale '{}'", locale),
+            Error::Fluent(path, errors) => {
+                write!(f, "{} error(s) in '{}'", errors.len(), path.display())?;
+                for error in errors {
+                    write!(f, "; {}", error)?;
+                }
+
+                Ok(())
+            }
+            Error::MissingDefault(locale) => {
+                write!(f, "missing translations for default locale '{}'", locale)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The translations of an application, by locale.
+///
+/// `Locales` are loaded from a directory with one subdirectory, named after
+/// its locale, for every locale. All of the `.ftl` files in a locale's
+/// directory are loaded, in alphabetical order, into one Fluent bundle. A
+/// message may be defined in only one file per locale. The translations for
+/// the default locale are used for messages that are missing in more
+/// preferred locales.
+///
+/// Unicode isolation marks are not inserted around interpolated arguments,
+/// which would otherwise appear in HTML and plain-text responses.
+///
+/// Cloning `Locales` is cheap: clones share the loaded translations.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rocket_contrib::i18n::{Locales, LanguageIdentifier, FluentArgs};
+///
+/// let locales = Locales::load("i18n", "en-US").expect("valid translations");
+/// let fr: LanguageIdentifier = "fr".parse().unwrap();
+///
+/// let mut args = FluentArgs::new();
+/// args.set("name", "Rocket");
+/// let greeting = locales.translate(&locales.negotiate(&[fr]), "greeting", Some(&args));
+/// ```
+#[derive(Clone)]
+pub struct Locales(Arc<Inner>);
+
+struct Inner {
+    bundles: HashMap<LanguageIdentifier, Bundle>,
+    available: Vec<LanguageIdentifier>,
+    default: LanguageIdentifier,
+}
+
+impl Locales {
+    /// Loads the translations in `dir` with `default` as the default locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` can't be read, if the name of a directory in
+    /// `dir` isn't a valid locale, if a translation file can't be read or
+    /// contains an error, or if there are no translations for `default`.
+    pub fn load<P: AsRef<Path>>(dir: P, default: &str) -> Result<Locales, Error> {
+        let default: LanguageIdentifier = default.parse()
+            .map_err(|_| Error::InvalidLocale(default.to_string()))?;
+
+        let mut bundles = HashMap::new();
+        let mut available = vec![];
+        for path in sorted_entries(dir.as_ref())? {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            if !path.is_dir() || name.starts_with('.') {
+                continue;
+            }
+
+            let locale: LanguageIdentifier = name.parse()
+                .map_err(|_| Error::InvalidLocale(name.to_string()))?;
+
+            let mut bundle = Bundle::new_concurrent(vec![locale.clone()]);
+            bundle.set_use_isolating(false);
+            for file in sorted_entries(&path)? {
+                if file.extension().map_or(true, |ext| ext != "ftl") {
+                    continue;
+                }
+
+                let source = std::fs::read_to_string(&file)?;
+                let resource = FluentResource::try_new(source).map_err(|(_, errors)| {
+                    Error::Fluent(file.clone(), errors.into_iter().map(FluentError::from).collect())
+                })?;
+
+                bundle.add_resource(resource).map_err(|errors| Error::Fluent(file, errors))?;
+            }
+
+            available.push(locale.clone());
+            bundles.insert(locale, bundle);
+        }
+
+        if !bundles.contains_key(&default) {
+            return Err(Error::MissingDefault(default));
+        }
+
+        Ok(Locales(Arc::new(Inner { bundles, available, default })))
+    }
+
+    /// Returns the default locale.
+    pub fn default_locale(&self) -> &LanguageIdentifier {
+        &self.0.default
+    }
+
+    /// Returns the locales with translations, in alphabetical order.
+    pub fn available(&self) -> &[LanguageIdentifier] {
+        &self.0.available
+    }
+
+    /// Negotiates the `requested` locales, in order of preference, against the
+    /// available ones. Returns the matching available locales in order of
+    /// preference, always ending with the default locale.
+    pub fn negotiate<L: AsRef<LanguageIdentifier>>(
+        &self,
+        requested: &[L]
+    ) -> Vec<LanguageIdentifier> {
+        let strategy = NegotiationStrategy::Filtering;
+        negotiate_languages(requested, &self.0.available[..], Some(&self.0.default), strategy)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Formats the message `key` with `args` in the first of `locales` that
+    /// has a translation for it. An attribute of a message is translated by
+    /// appending its name to the message's key, as in `login.placeholder`.
+    /// Returns `None` if none of `locales` translates `key`.
+    ///
+    /// Errors while formatting the message, such as missing arguments, are
+    /// logged; the affected parts of the message are replaced by Fluent.
+    pub fn translate(
+        &self,
+        locales: &[LanguageIdentifier],
+        key: &str,
+        args: Option<&FluentArgs<'_>>
+    ) -> Option<String> {
+        let (id, attribute) = match key.find('.') {
+            Some(i) => (&key[..i], Some(&key[(i + 1)..])),
+            None => (key, None),
+        };
+
+        let mut bundles = locales.iter().filter_map(|locale| self.0.bundles.get(locale));
+        bundles.find_map(|bundle| {
+            let message = bundle.get_message(id)?;
+            let pattern = match attribute {
+                Some(attribute) => message.get_attribute(attribute)?.value(),
+                None => message.value()?,
+            };
+
+            let mut errors = vec![];
+            let value = bundle.format_pattern(pattern, args, &mut errors);
+            for error in errors {
+                warn_!("Error formatting message '{}': {}", key, error);
+            }
+
+            Some(value.into_owned())
+        })
+    }
+
+    /// Like [`Locales::translate()`], but returns `key` itself, and logs a
+    /// warning, if none of `locales` translates `key`.
+    fn translate_or_key(
+        &self,
+        locales: &[LanguageIdentifier],
+        key: &str,
+        args: Option<&FluentArgs<'_>>
+    ) -> String {
+        self.translate(locales, key, args).unwrap_or_else(|| {
+            warn_!("Missing translation for message '{}'.", key);
+            key.to_string()
+        })
+    }
+}
+
+/// Returns the paths of the entries in `dir`, sorted.
+fn sorted_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// The locales negotiated for a request.
+struct Negotiated(Vec<LanguageIdentifier>);
+
+/// Returns the locales negotiated for `req`, negotiating them if that hasn't
+/// happened yet.
+fn negotiated<'r>(req: &'r Request<'_>, locales: &Locales) -> &'r [LanguageIdentifier] {
+    &req.local_cache(|| {
+        let mut requested: Vec<LanguageIdentifier> = vec![];
+        if let Some(cookie) = req.cookies().get(LOCALE_COOKIE) {
+            match cookie.value().parse() {
+                Ok(locale) => requested.push(locale),
+                Err(_) => debug_!("Ignoring invalid locale cookie '{}'.", cookie.value()),
+            }
+        }
+
+        if let Some(header) = req.headers().get_one("Accept-Language") {
+            requested.extend(accepted_languages::parse(header));
+        }
+
+        Negotiated(locales.negotiate(&requested))
+    }).0
+}
+
+/// Request guard for translating messages in the locale negotiated for the
+/// request.
+///
+/// The locale preferred in the `lang` cookie ([`LOCALE_COOKIE`]) is tried
+/// first, followed by those in the `Accept-Language` header, in order of
+/// preference, and finally the default locale. To let users choose their
+/// locale, set the cookie. Unknown locales are ignored.
+///
+/// The guard fails with `500 Internal Server Error` if no `Locales` are
+/// managed, which is the case when the fairing returned by
+/// [`I18n::fairing()`] or [`I18n::fairing_with()`] isn't attached.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket_contrib::i18n::{I18n, FluentArgs};
+///
+/// #[get("/hello/<name>")]
+/// fn hello(i18n: I18n<'_>, name: String) -> String {
+///     let mut args = FluentArgs::new();
+///     args.set("name", name);
+///     i18n.t("greeting", Some(&args))
+/// }
+///
+/// fn main() {
+///     rocket::ignite()
+///         .attach(I18n::fairing())
+///         .mount("/", routes![hello])
+///     # ;
+/// }
+/// ```
+pub struct I18n<'r> {
+    locales: &'r Locales,
+    negotiated: &'r [LanguageIdentifier],
+}
+
+impl I18n<'_> {
+    /// Returns a fairing that loads [`Locales`] as configured in the `i18n`
+    /// configuration table and manages them.
+    ///
+    /// The translation directory, relative to the configuration file, is set
+    /// via `dir` and defaults to `i18n`. The default locale is set via
+    /// `default_locale` and defaults to `en-US`:
+    ///
+    /// ```toml
+    /// [global.i18n]
+    /// dir = "translations"
+    /// default_locale = "fr"
+    /// ```
+    ///
+    /// Launch is aborted if the translations fail to load.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::i18n::I18n;
+    ///
+    /// rocket::ignite().attach(I18n::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach("I18n Configuration", |mut rocket: Rocket| async move {
+            let (dir, default) = match configured(rocket.config().await) {
+                Ok(configured) => configured,
+                Err(e) => {
+                    e.pretty_print();
+                    error_!("Invalid i18n configuration.");
+                    return Err(rocket);
+                }
+            };
+
+            match Locales::load(&dir, &default) {
+                Ok(locales) => Ok(rocket.attach(I18nFairing(locales))),
+                Err(e) => {
+                    error_!("Failed to load translations from '{}': {}", dir.display(), e);
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
+    /// Returns a fairing that manages `locales`, ignoring the `i18n`
+    /// configuration table.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rocket_contrib::i18n::{I18n, Locales};
+    ///
+    /// let locales = Locales::load("i18n", "en-US").expect("valid translations");
+    /// rocket::ignite().attach(I18n::fairing_with(locales))
+    /// # ;
+    /// ```
+    pub fn fairing_with(locales: Locales) -> impl Fairing {
+        I18nFairing(locales)
+    }
+}
+
+impl<'r> I18n<'r> {
+    /// Returns the most preferred locale negotiated for the request.
+    pub fn locale(&self) -> &'r LanguageIdentifier {
+        &self.negotiated[0]
+    }
+
+    /// Returns the locales negotiated for the request, in order of
+    /// preference. The last locale is always the default locale.
+    pub fn locales(&self) -> &'r [LanguageIdentifier] {
+        self.negotiated
+    }
+
+    /// Formats the message `key` with `args` in the most preferred locale that
+    /// translates it. If no locale translates `key`, a warning is logged and
+    /// `key` itself is returned. See [`Locales::translate()`] for details.
+    pub fn t(&self, key: &str, args: Option<&FluentArgs<'_>>) -> String {
+        self.locales.translate_or_key(self.negotiated, key, args)
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for I18n<'a> {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match req.managed_state::<Locales>() {
+            Some(locales) => {
+                let negotiated = negotiated(req, locales);
+                request::Outcome::Success(I18n { locales, negotiated })
+            }
+            None => {
+                error_!("Uninitialized translations: missing fairing.");
+                info_!("To use `I18n`, you must attach `I18n::fairing()`.");
+                request::Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+/// Reads the translation directory and default locale from the `i18n` table
+/// in `config`.
+fn configured(config: &Config) -> Result<(PathBuf, String), ConfigError> {
+    let table = match config.get_table("i18n") {
+        Ok(table) => table,
+        Err(ConfigError::Missing(_)) => {
+            return Ok((config.root_relative(DEFAULT_DIR), DEFAULT_LOCALE.to_string()));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let string = |key: &str, default: &str| match table.get(key) {
+        Some(value) => value.as_str().map(String::from).ok_or_else(|| {
+            let name = format!("i18n.{}", key);
+            ConfigError::BadType(name, "a string", value.type_str(), None)
+        }),
+        None => Ok(default.to_string()),
+    };
+
+    let dir = string("dir", DEFAULT_DIR)?;
+    Ok((config.root_relative(dir), string("default_locale", DEFAULT_LOCALE)?))
+}
+
+/// The fairing returned by [`I18n::fairing_with()`], and attached by the one
+/// returned by [`I18n::fairing()`].
+struct I18nFairing(Locales);
+
+#[rocket::async_trait]
+impl Fairing for I18nFairing {
+    fn info(&self) -> Info {
+        Info { name: "I18n", kind: Kind::Attach | Kind::Request }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let available = self.0.available().iter()
+            .map(|locale| locale.to_string())
+            .collect::<Vec<_>>();
+
+        info_!("Translations: {} (default: {})", available.join(", "), self.0.default_locale());
+        Ok(rocket.manage(self.0.clone()))
+    }
+
+    /// Negotiates the request's locales and, when templates are enabled, sets
+    /// the most preferred one as the global template context value `lang`.
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        let locale = &negotiated(req, &self.0)[0];
+        debug_!("Negotiated locale: {}", locale);
+
+        #[cfg(feature = "templates")]
+        crate::templates::Template::set_global(req, "lang", locale.to_string());
+    }
+}
+
+#[cfg(any(feature = "tera_templates", feature = "handlebars_templates"))]
+impl Locales {
+    /// Registers the `t` function, which translates messages with these
+    /// locales, with the template `engines`. Call this method from the
+    /// customization callback passed to [`Template::custom()`]. See the
+    /// [module documentation](crate::i18n#templates) for usage.
+    ///
+    /// [`Template::custom()`]: crate::templates::Template::custom()
+    pub fn register(&self, engines: &mut crate::templates::Engines) {
+        #[cfg(feature = "tera_templates")]
+        engines.tera.register_function("t", templates::Translate(self.clone()));
+
+        #[cfg(feature = "handlebars_templates")]
+        engines.handlebars.register_helper("t", Box::new(templates::Translate(self.clone())));
+    }
+}
+
+#[cfg(any(feature = "tera_templates", feature = "handlebars_templates"))]
+mod templates {
+    use serde_json::Value;
+
+    use super::{Locales, FluentArgs, LanguageIdentifier};
+
+    impl Locales {
+        /// Translates `key` with `args` in the locale `lang`, falling back to
+        /// the default locale. An invalid or missing `lang` is ignored.
+        fn translate_in(&self, lang: Option<&str>, key: &str, args: &FluentArgs<'_>) -> String {
+            let requested: Vec<LanguageIdentifier> = lang.and_then(|lang| lang.parse().ok())
+                .into_iter()
+                .collect();
+
+            self.translate_or_key(&self.negotiate(&requested), key, Some(args))
+        }
+    }
+
+    /// Converts the named template arguments in `values` to Fluent arguments.
+    /// Strings and numbers are passed as-is, booleans as strings. Other values
+    /// are ignored.
+    fn fluent_args<'a, I>(values: I) -> FluentArgs<'a>
+        where I: Iterator<Item = (&'a str, &'a Value)>
+    {
+        let mut args = FluentArgs::new();
+        for (name, value) in values {
+            match value {
+                Value::String(s) => args.set(name, s.as_str()),
+                Value::Bool(b) => args.set(name, b.to_string()),
+                Value::Number(n) => match n.as_f64() {
+                    Some(n) => args.set(name, n),
+                    None => warn_!("Ignoring unrepresentable translation argument '{}'.", name),
+                },
+                _ => warn_!("Ignoring non-scalar translation argument '{}'.", name),
+            }
+        }
+
+        args
+    }
+
+    /// The `t` template function.
+    pub(super) struct Translate(pub Locales);
+
+    #[cfg(feature = "tera_templates")]
+    impl tera::Function for Translate {
+        fn call(&self, args: &std::collections::HashMap<String, Value>) -> tera::Result<Value> {
+            let key = args.get("key").and_then(Value::as_str)
+                .ok_or_else(|| tera::Error::msg("`t` requires a string `key` argument"))?;
+
+            let lang = args.get("lang").and_then(Value::as_str);
+            let args = fluent_args(args.iter()
+                .filter(|(name, _)| *name != "key" && *name != "lang")
+                .map(|(name, value)| (name.as_str(), value)));
+
+            Ok(Value::String(self.0.translate_in(lang, key, &args)))
+        }
+    }
+
+    #[cfg(feature = "handlebars_templates")]
+    impl handlebars::HelperDef for Translate {
+        fn call<'reg: 'rc, 'rc>(
+            &self,
+            h: &handlebars::Helper<'reg, 'rc>,
+            registry: &'reg handlebars::Handlebars<'reg>,
+            ctx: &'rc handlebars::Context,
+            _: &mut handlebars::RenderContext<'reg, 'rc>,
+            out: &mut dyn handlebars::Output,
+        ) -> handlebars::HelperResult {
+            let key = h.param(0).and_then(|key| key.value().as_str()).ok_or_else(|| {
+                handlebars::RenderError::new("`t` requires a string key parameter")
+            })?;
+
+            let lang = h.hash_get("lang").map(|lang| lang.value())
+                .or_else(|| ctx.data().get("lang"))
+                .and_then(Value::as_str);
+
+            let args = fluent_args(h.hash().iter()
+                .filter(|(name, _)| **name != "lang")
+                .map(|(name, value)| (*name, value.value())));
+
+            let message = self.0.translate_in(lang, key, &args);
+            out.write(&registry.get_escape_fn()(&message))?;
+            Ok(())
+        }
+    }
+}
@@ -20,10 +20,12 @@ use std::iter::FromIterator;
 
 use rocket::request::Request;
 use rocket::outcome::Outcome::*;
-use rocket::data::{Data, ByteUnit, Transform::*, Transformed};
+use rocket::data::{Data, ByteUnit, Bytes, Transform::*, Transformed};
 use rocket::data::{FromTransformedData, TransformFuture, FromDataFuture};
-use rocket::http::Status;
+use rocket::http::{MediaType, Status};
 use rocket::response::{self, Responder, content};
+use rocket::openapi::{JsonSchema, OpenApiFromData, OpenApiResponder};
+use rocket::openapi::{RequestBody, Response, Schema, SchemaGenerator};
 
 use serde::{Serialize, Serializer};
 use serde::de::{Deserialize, Deserializer};
@@ -128,14 +130,14 @@ const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
 
 impl<'a, T: Deserialize<'a>> FromTransformedData<'a> for Json<T> {
     type Error = JsonError<'a>;
-    type Owned = String;
-    type Borrowed = str;
+    type Owned = Bytes;
+    type Borrowed = [u8];
 
     fn transform<'r>(r: &'r Request<'_>, d: Data) -> TransformFuture<'r, Self::Owned, Self::Error> {
         Box::pin(async move {
             let size_limit = r.limits().get("json").unwrap_or(DEFAULT_LIMIT);
-            match d.open(size_limit).stream_to_string().await {
-                Ok(s) => Borrowed(Success(s)),
+            match d.open(size_limit).into_bytes().await {
+                Ok(bytes) => Borrowed(Success(bytes)),
                 Err(e) => Borrowed(Failure((Status::BadRequest, JsonError::Io(e))))
             }
         })
@@ -143,8 +145,16 @@ impl<'a, T: Deserialize<'a>> FromTransformedData<'a> for Json<T> {
 
     fn from_data(_: &'a Request<'_>, o: Transformed<'a, Self>) -> FromDataFuture<'a, Self, Self::Error> {
         Box::pin(async move {
-            let string = try_outcome!(o.borrowed());
-            match serde_json::from_str(&string) {
+            let bytes = try_outcome!(o.borrowed());
+            let string = match std::str::from_utf8(bytes) {
+                Ok(string) => string,
+                Err(e) => {
+                    let e = io::Error::new(io::ErrorKind::InvalidData, e);
+                    return Failure((Status::BadRequest, JsonError::Io(e)));
+                }
+            };
+
+            match serde_json::from_str(string) {
                 Ok(v) => Success(Json(v)),
                 Err(e) => {
                     error_!("Couldn't parse JSON body: {:?}", e);
@@ -174,6 +184,31 @@ impl<'r, T: Serialize> Responder<'r, 'static> for Json<T> {
     }
 }
 
+impl<T: JsonSchema> JsonSchema for Json<T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+}
+
+/// Describes the request body as JSON described by `T`.
+impl<T: JsonSchema> OpenApiFromData for Json<T> {
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        let schema = gen.subschema_for::<T>();
+        Some(RequestBody { content_type: Some(MediaType::JSON), schema, required: true })
+    }
+}
+
+/// Describes the response as JSON described by `T`.
+impl<T: JsonSchema> OpenApiResponder for Json<T> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::new(Status::Ok).body(MediaType::JSON, gen.subschema_for::<T>())]
+    }
+}
+
 impl<T> Deref for Json<T> {
     type Target = T;
 
@@ -290,6 +325,19 @@ impl<'r> Responder<'r, 'static> for JsonValue {
     }
 }
 
+impl JsonSchema for JsonValue {
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        Schema::Any
+    }
+}
+
+/// Describes the response as arbitrary JSON.
+impl OpenApiResponder for JsonValue {
+    fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::new(Status::Ok).body(MediaType::JSON, Schema::Any)]
+    }
+}
+
 /// A macro to create ad-hoc JSON serializable values using JSON syntax.
 ///
 /// # Usage
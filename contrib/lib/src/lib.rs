@@ -21,9 +21,30 @@
 //! * [msgpack](msgpack) - MessagePack (de)serialization
 //! * [handlebars_templates](templates) - Handlebars Templating
 //! * [tera_templates](templates) - Tera Templating
+//! * [templates](templates) - Templating with Custom Engines
+//! * [askama_templates](askama) - Askama Compile-Time Templating
+//! * [i18n](i18n) - Localization with Fluent
 //! * [uuid](uuid) - UUID (de)serialization
 //! * [${database}_pool](databases) - Database Configuration and Pooling
 //! * [helmet](helmet) - Fairing for Security and Privacy Headers
+//! * [problem](problem) - RFC 7807 Problem Details Responder
+//! * [cors](cors) - Cross-Origin Resource Sharing Fairing
+//! * [request_id](request_id) - Request ID Generation and Propagation
+//! * [access_log](access_log) - Access Logging Fairing
+//! * [prometheus](prometheus) - Prometheus Metrics Fairing
+//! * [opentelemetry](opentelemetry) - OpenTelemetry Tracing Fairing
+//! * [server_timing](server_timing) - Server-Timing and Response Time Fairing
+//! * [maintenance](maintenance) - Maintenance Mode Fairing
+//! * [sessions](sessions) - Cookie and Server-Side Sessions
+//! * [auth](auth) - User Authentication and Password Hashing
+//! * [oauth](oauth) - OAuth 2.0 and OpenID Connect Sign-In
+//! * [ratelimit](ratelimit) - Rate Limiting Fairing, Guard, and Attribute
+//! * [scheduler](scheduler) - Scheduled Jobs with Cron Expressions
+//! * [graphql](graphql) - GraphQL Endpoints with Juniper
+//! * [grpc_web](grpc_web) - gRPC-web Requests Served by tonic
+//! * [openapi](openapi) - OpenAPI Documents and Swagger UI
+//! * [health](health) - Liveness and Readiness Health Checks
+//! * [feature_flags](feature_flags) - Runtime Feature Flags and Conditional Routes
 //!
 //! The recommend way to include features from this crate via Cargo in your
 //! project is by adding a `[dependencies.rocket_contrib]` section to your
@@ -47,9 +68,34 @@
 #[cfg(feature="serve")] pub mod serve;
 #[cfg(feature="msgpack")] pub mod msgpack;
 #[cfg(feature="templates")] pub mod templates;
+#[cfg(feature="askama_templates")] pub mod askama;
+#[cfg(feature = "i18n")] pub mod i18n;
 #[cfg(feature="uuid")] pub mod uuid;
 #[cfg(feature="databases")] pub mod databases;
 #[cfg(feature = "helmet")] pub mod helmet;
+#[cfg(feature = "problem")] pub mod problem;
+#[cfg(feature = "cors")] pub mod cors;
+#[cfg(feature = "request_id")] pub mod request_id;
+#[cfg(feature = "access_log")] pub mod access_log;
+#[cfg(feature = "prometheus")] pub mod prometheus;
+#[cfg(feature = "opentelemetry")] pub mod opentelemetry;
+#[cfg(feature = "server_timing")] pub mod server_timing;
+#[cfg(feature = "maintenance")] pub mod maintenance;
+#[cfg(feature = "sessions")] pub mod sessions;
+#[cfg(feature = "auth")] pub mod auth;
+#[cfg(feature = "oauth")] pub mod oauth;
+#[cfg(feature = "ratelimit")] pub mod ratelimit;
+#[cfg(feature = "scheduler")] pub mod scheduler;
+#[cfg(feature = "graphql")] pub mod graphql;
+#[cfg(feature = "grpc_web")] pub mod grpc_web;
+#[cfg(feature = "openapi")] pub mod openapi;
+#[cfg(feature = "health")] pub mod health;
+#[cfg(feature = "feature_flags")] pub mod feature_flags;
+
+#[cfg(any(feature = "access_log", feature = "prometheus", feature = "server_timing"))]
+mod received;
+#[cfg(any(feature = "sessions", feature = "auth", feature = "oauth"))]
+mod clock;
 // TODO.async: Migrate compression, reenable this, tests, and add to docs.
 //#[cfg(any(feature="brotli_compression", feature="gzip_compression"))] pub mod compression;
 
@@ -0,0 +1,294 @@
+//! Maintenance mode that can be toggled while the application is running.
+//!
+//! See the [`Maintenance`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `maintenance` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["maintenance"]
+//! ```
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rocket::{Request, Rocket, Data, Route};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::http::uri::Origin;
+use rocket::response::{self, Content, Responder};
+
+#[cfg(feature = "templates")]
+use crate::templates::Template;
+
+/// The path requests are rewritten to during maintenance, and the rank of the
+/// route answering them, which is tried before any application route.
+const UNAVAILABLE_PATH: &str = "/__rocket/maintenance";
+const RANK: isize = isize::min_value();
+
+/// The body sent during maintenance unless another is configured.
+const DEFAULT_PAGE: &str = "The service is down for maintenance. Please try again later.";
+
+/// A handle that turns maintenance mode on and off.
+///
+/// A `MaintenanceMode` is obtained from [`Maintenance::handle()`] and is
+/// managed by Rocket once the fairing is attached, so handlers can retrieve it
+/// via [`State`](rocket::State). Handles are cheap to clone; all clones control
+/// the same fairing.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::State;
+/// use rocket_contrib::maintenance::MaintenanceMode;
+///
+/// #[post("/admin/maintenance/<on>")]
+/// fn toggle(on: bool, mode: State<'_, MaintenanceMode>) -> &'static str {
+///     if on { mode.enable(); "enabled" } else { mode.disable(); "disabled" }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode(Arc<AtomicBool>);
+
+impl MaintenanceMode {
+    /// Turns maintenance mode on.
+    pub fn enable(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Turns maintenance mode off.
+    pub fn disable(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if maintenance mode is on.
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The body of responses sent during maintenance.
+#[derive(Clone)]
+enum Page {
+    Static(ContentType, Cow<'static, str>),
+    #[cfg(feature = "templates")]
+    Template(Cow<'static, str>),
+}
+
+/// A fairing that answers requests with `503 Service Unavailable` while
+/// maintenance mode is on.
+///
+/// While maintenance mode is on, `Maintenance` rewrites every request, except
+/// those to allowed paths, before it is routed to a route mounted by the
+/// fairing. That route answers with a `503 Service Unavailable` response
+/// carrying a `Retry-After` header, so no application handler runs. Requests
+/// to allowed paths, such as health checks or the route that turns
+/// maintenance mode off, and all requests while maintenance mode is off, are
+/// handled as usual.
+///
+/// Maintenance mode is turned on and off via a [`MaintenanceMode`] handle. By
+/// default, the body of the response is a short plain-text message. A custom
+/// page can be set with [`Maintenance::page()`] or, when the `templates`
+/// feature is enabled, [`Maintenance::template()`].
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use std::time::Duration;
+/// use rocket_contrib::maintenance::Maintenance;
+///
+/// let maintenance = Maintenance::new()
+///     .allow("/health")
+///     .allow("/admin")
+///     .retry_after(Duration::from_secs(600));
+///
+/// // Turn maintenance mode on and off from outside of a handler.
+/// let mode = maintenance.handle();
+/// mode.enable();
+///
+/// rocket::ignite().attach(maintenance)
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct Maintenance {
+    mode: MaintenanceMode,
+    allowed: Vec<Cow<'static, str>>,
+    retry_after: Duration,
+    page: Page,
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Maintenance::new()
+    }
+}
+
+impl Maintenance {
+    /// Returns a new `Maintenance` fairing with maintenance mode off, no
+    /// allowed paths, a `Retry-After` of 5 minutes, and the default page.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::maintenance::Maintenance;
+    ///
+    /// let maintenance = Maintenance::new();
+    /// assert!(!maintenance.handle().is_enabled());
+    /// ```
+    pub fn new() -> Maintenance {
+        Maintenance {
+            mode: MaintenanceMode(Arc::new(AtomicBool::new(false))),
+            allowed: vec![],
+            retry_after: Duration::from_secs(300),
+            page: Page::Static(ContentType::Plain, DEFAULT_PAGE.into()),
+        }
+    }
+
+    /// Returns a handle that turns maintenance mode on and off.
+    pub fn handle(&self) -> MaintenanceMode {
+        self.mode.clone()
+    }
+
+    /// Handles requests to `path`, and to paths below it, as usual during
+    /// maintenance. `/health` allows `/health` and `/health/db`, but not
+    /// `/healthz`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::maintenance::Maintenance;
+    ///
+    /// let maintenance = Maintenance::new().allow("/health");
+    /// ```
+    pub fn allow<P: Into<Cow<'static, str>>>(mut self, path: P) -> Self {
+        self.allowed.push(path.into());
+        self
+    }
+
+    /// Sets the time clients are asked to wait before retrying, sent in whole
+    /// seconds in the `Retry-After` header.
+    pub fn retry_after(mut self, duration: Duration) -> Self {
+        self.retry_after = duration;
+        self
+    }
+
+    /// Sets the body of responses sent during maintenance.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::ContentType;
+    /// use rocket_contrib::maintenance::Maintenance;
+    ///
+    /// let maintenance = Maintenance::new()
+    ///     .page(ContentType::HTML, "<h1>Back soon!</h1>");
+    /// ```
+    pub fn page<B: Into<Cow<'static, str>>>(mut self, content_type: ContentType, body: B) -> Self {
+        self.page = Page::Static(content_type, body.into());
+        self
+    }
+
+    /// Renders the template `name` as the body of responses sent during
+    /// maintenance. The template's context has a single field,
+    /// `retry_after`, with the number of seconds in the `Retry-After` header.
+    /// The [`Template::fairing()`] must be attached.
+    ///
+    /// This method is only available when the `templates` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::maintenance::Maintenance;
+    ///
+    /// let maintenance = Maintenance::new().template("maintenance");
+    /// ```
+    #[cfg(feature = "templates")]
+    pub fn template<N: Into<Cow<'static, str>>>(mut self, name: N) -> Self {
+        self.page = Page::Template(name.into());
+        self
+    }
+
+    /// Returns `true` if requests to `path` are handled during maintenance.
+    fn is_allowed(&self, path: &str) -> bool {
+        self.allowed.iter().any(|allowed| {
+            let allowed = allowed.trim_end_matches('/');
+            match path.strip_prefix(allowed) {
+                Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                None => false,
+            }
+        })
+    }
+}
+
+/// The response sent during maintenance.
+struct Unavailable<'a>(&'a Maintenance);
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Unavailable<'o> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let retry_after = self.0.retry_after.as_secs();
+        let mut response = match self.0.page {
+            Page::Static(ref content_type, ref body) => {
+                Content(content_type.clone(), body.as_ref()).respond_to(req)?
+            }
+            #[cfg(feature = "templates")]
+            Page::Template(ref name) => {
+                let context = serde_json::json!({ "retry_after": retry_after });
+                Template::render(name.clone(), context).respond_to(req)?
+            }
+        };
+
+        response.set_status(Status::ServiceUnavailable);
+        response.set_header(Header::new("Retry-After", retry_after.to_string()));
+        Ok(response)
+    }
+}
+
+/// Whether a request was rewritten to the maintenance route by the fairing.
+struct Rewritten(bool);
+
+#[rocket::async_trait]
+impl Handler for Maintenance {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        match req.local_cache(|| Rewritten(false)).0 {
+            true => Outcome::from(req, Unavailable(self)),
+            false => Outcome::forward(data),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Maintenance {
+    fn info(&self) -> Info {
+        Info {
+            name: "Maintenance Mode",
+            kind: Kind::Attach | Kind::Request,
+        }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let unavailable = Route::ranked(RANK, Method::Get, UNAVAILABLE_PATH, self.clone());
+        Ok(rocket.mount("/", vec![unavailable]).manage(self.handle()))
+    }
+
+    /// Rewrites the request to the maintenance route if maintenance mode is
+    /// on and its path isn't allowed.
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        if self.mode.is_enabled() && !self.is_allowed(req.uri().path()) {
+            req.local_cache(|| Rewritten(true));
+            req.set_method(Method::Get);
+            req.set_uri(Origin::parse(UNAVAILABLE_PATH).expect("valid origin"));
+        }
+    }
+}
@@ -16,11 +16,9 @@
 
 use std::ops::{Deref, DerefMut};
 
-use tokio::io::AsyncReadExt;
-
 use rocket::request::Request;
 use rocket::outcome::Outcome::*;
-use rocket::data::{Data, ByteUnit, Transform::*, TransformFuture, Transformed};
+use rocket::data::{Data, ByteUnit, Bytes, Transform::*, TransformFuture, Transformed};
 use rocket::data::{FromTransformedData, FromDataFuture};
 use rocket::response::{self, content, Responder};
 use rocket::http::Status;
@@ -115,16 +113,14 @@ const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
 
 impl<'a, T: Deserialize<'a>> FromTransformedData<'a> for MsgPack<T> {
     type Error = Error;
-    type Owned = Vec<u8>;
+    type Owned = Bytes;
     type Borrowed = [u8];
 
     fn transform<'r>(r: &'r Request<'_>, d: Data) -> TransformFuture<'r, Self::Owned, Self::Error> {
         Box::pin(async move {
             let size_limit = r.limits().get("msgpack").unwrap_or(DEFAULT_LIMIT);
-            let mut buf = Vec::new();
-            let mut reader = d.open(size_limit);
-            match reader.read_to_end(&mut buf).await {
-                Ok(_) => Borrowed(Success(buf)),
+            match d.open(size_limit).into_bytes().await {
+                Ok(buf) => Borrowed(Success(buf)),
                 Err(e) => Borrowed(Failure((Status::BadRequest, Error::InvalidDataRead(e)))),
             }
         })
@@ -0,0 +1,600 @@
+//! OAuth 2.0 and OpenID Connect sign-in.
+//!
+//! See the [`OAuth2`] fairing and the [`OAuthIdentity`] request guard for
+//! further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `oauth` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["oauth"]
+//! ```
+//!
+//! # Flow
+//!
+//! Each attached [`OAuth2`] fairing handles the authorization code flow with
+//! PKCE for one provider, mounting two routes at a base path:
+//!
+//!   * `GET <base>/login` redirects the user to the provider's authorization
+//!     endpoint. It generates a random `state`, a PKCE code verifier, and, for
+//!     OpenID Connect providers, a `nonce`, and keeps them in a short-lived
+//!     private cookie. An optional `next` query parameter, which must be a
+//!     local path such as `/account?tab=security`, sets where the user is
+//!     sent once signed in. Values that browsers may resolve to another host,
+//!     such as `//example.com` or `/\example.com`, are ignored.
+//!   * `GET <base>/callback` is where the provider redirects the user back. It
+//!     checks the `state`, exchanges the authorization code for tokens,
+//!     validates the ID token, if any, retrieves the user's claims from the
+//!     user info endpoint, if configured, and keeps the resulting identity in
+//!     a private cookie. It then redirects to the `next` path or the
+//!     provider's `success_uri`.
+//!
+//! The [`OAuthIdentity`] request guard retrieves the signed-in identity. The
+//! callback responds with `400 Bad Request` if the flow's cookie is missing or
+//! its `state` doesn't match, with `401 Unauthorized` if the user denied the
+//! request, and with `502 Bad Gateway` if the provider's responses are
+//! invalid.
+//!
+//! Identities are kept in private cookies, so a `secret_key` must be
+//! configured for sign-ins to survive an application restart. Cookies are
+//! limited to 4096 bytes: if the claims about a user don't all fit, only the
+//! standard profile claims, such as `sub`, `name`, and `email`, are kept, and
+//! the callback responds with `500 Internal Server Error` if the identity
+//! still doesn't fit. Once the provider's access token expires, the identity
+//! is signed out. Outside the development environment, cookies are only sent
+//! over HTTPS.
+//!
+//! # Configuration
+//!
+//! Each provider is configured in the `oauth.<name>` table in `Rocket.toml`,
+//! where `<name>` is the name passed to [`OAuth2::fairing()`]:
+//!
+//! ```toml
+//! [global.oauth.google]
+//! provider = "google"
+//! client_id = "1234.apps.googleusercontent.com"
+//! client_secret = "s3cr3t"
+//! redirect_uri = "https://example.com/auth/google/callback"
+//! scopes = ["openid", "email", "profile"]
+//!
+//! [global.oauth.corp]
+//! client_id = "rocket-app"
+//! auth_uri = "https://sso.example.com/authorize"
+//! token_uri = "https://sso.example.com/token"
+//! userinfo_uri = "https://sso.example.com/userinfo"
+//! issuer = "https://sso.example.com"
+//! redirect_uri = "https://example.com/auth/corp/callback"
+//! scopes = ["openid"]
+//! success_uri = "/dashboard"
+//! ```
+//!
+//! `client_id` and `redirect_uri` are required. `provider`, one of `"google"`
+//! or `"github"`, sets the well-known `auth_uri`, `token_uri`,
+//! `userinfo_uri`, and `issuer` of the provider; otherwise, `auth_uri` and
+//! `token_uri` are required. `client_secret` is omitted for public clients.
+//! Including the `openid` scope requests an OpenID Connect ID token, whose
+//! audience, expiry, `nonce`, and `issuer`, if configured, are validated.
+//! `success_uri` defaults to `/`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket::http::CookieJar;
+//! use rocket::response::Redirect;
+//! use rocket_contrib::oauth::{OAuth2, OAuthIdentity};
+//!
+//! #[get("/")]
+//! fn index(identity: OAuthIdentity) -> String {
+//!     let email = identity.claim::<String>("email").unwrap_or_default();
+//!     format!("Signed in with {} as {}.", identity.provider(), email)
+//! }
+//!
+//! #[get("/", rank = 2)]
+//! fn anonymous() -> Redirect {
+//!     Redirect::to("/auth/google/login")
+//! }
+//!
+//! #[post("/logout")]
+//! fn logout(cookies: &CookieJar<'_>) -> Redirect {
+//!     OAuthIdentity::remove(cookies);
+//!     Redirect::to("/")
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(OAuth2::fairing("google", "/auth/google"))
+//!         .mount("/", routes![index, anonymous, logout])
+//! #       ;
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use time::OffsetDateTime;
+
+use rocket::{Data, Request, Rocket, Route};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{Cookie, CookieJar, Method, SameSite, Status};
+use rocket::http::uri::Origin;
+use rocket::request::{self, FromRequest};
+use rocket::response::Redirect;
+
+use crate::clock::unix_now;
+
+mod provider;
+
+use self::provider::{Provider, random_token, challenge};
+
+/// The name of the private cookie holding the signed-in identity.
+const IDENTITY_COOKIE: &str = "rocket_oauth";
+
+/// How long a user has to complete a sign-in at the provider, in seconds.
+const FLOW_LIFETIME: i64 = 10 * 60;
+
+/// The size of the largest cookie clients are required to accept, in bytes.
+const MAX_COOKIE_SIZE: usize = 4096;
+
+/// The claims kept in the identity cookie when not all of them fit.
+const PROFILE_CLAIMS: &[&str] = &[
+    "sub", "id", "login", "name", "preferred_username", "email", "email_verified", "picture",
+];
+
+/// An error returned by the [`OAuthIdentity`] request guard or while
+/// completing a sign-in.
+#[derive(Debug)]
+pub enum OAuthError {
+    /// No identity is signed in.
+    Unauthenticated,
+    /// The signed-in identity's access token has expired.
+    Expired,
+    /// A request to the provider failed.
+    Http(reqwest::Error),
+    /// The provider responded with an error or an invalid response.
+    Provider(String),
+    /// The ID token returned by the provider is invalid, for the given reason.
+    InvalidIdToken(&'static str),
+}
+
+impl fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthError::Unauthenticated => write!(f, "no identity is signed in"),
+            OAuthError::Expired => write!(f, "the identity's access token has expired"),
+            OAuthError::Http(e) => write!(f, "request to provider failed: {}", e),
+            OAuthError::Provider(e) => write!(f, "provider error: {}", e),
+            OAuthError::InvalidIdToken(reason) => write!(f, "invalid ID token: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for OAuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OAuthError::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OAuthError {
+    fn from(error: reqwest::Error) -> Self {
+        OAuthError::Http(error)
+    }
+}
+
+/// Returns `true` if `next` is a path on this host. Browsers treat `\` like
+/// `/`, so `/\example.com` is as much a network-path reference as
+/// `//example.com`, and control characters may be stripped before the path is
+/// resolved: both are rejected, as is anything that isn't an origin-form URI.
+fn is_local_path(next: &str) -> bool {
+    if next.contains('\\') || next.chars().any(char::is_control) {
+        return false;
+    }
+
+    match Origin::parse(next) {
+        Ok(origin) => !origin.path().starts_with("//"),
+        Err(_) => false,
+    }
+}
+
+/// Returns the length of a private cookie named `name` with a `value` of
+/// `len` bytes: the value is sealed with a 12-byte nonce and a 16-byte tag,
+/// then base64 encoded.
+fn private_cookie_len(name: &str, len: usize) -> usize {
+    name.len() + 1 + 4 * ((len + 12 + 16 + 2) / 3)
+}
+
+/// The fairing that signs users in with an OAuth 2.0 provider.
+///
+/// On attach, the fairing reads the provider's configuration from the
+/// `oauth.<name>` table, as described in the [module
+/// documentation](crate::oauth), and mounts the sign-in routes at the base
+/// path. If the configuration is missing or invalid, an error is logged and
+/// launch is aborted. Attach one fairing per provider.
+pub struct OAuth2 {
+    name: String,
+    base: String,
+}
+
+impl OAuth2 {
+    /// Returns a fairing for the provider `name`, with its sign-in routes
+    /// mounted at `base`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::oauth::OAuth2;
+    ///
+    /// rocket::ignite()
+    ///     .attach(OAuth2::fairing("github", "/auth/github"))
+    ///     .attach(OAuth2::fairing("google", "/auth/google"))
+    /// # ;
+    /// ```
+    pub fn fairing<N: Into<String>, B: Into<String>>(name: N, base: B) -> OAuth2 {
+        OAuth2 { name: name.into(), base: base.into() }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for OAuth2 {
+    fn info(&self) -> Info {
+        Info { name: "OAuth2", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let config = rocket.config().await;
+        let secure = !config.environment.is_dev();
+        let provider = match Provider::from_config(&self.name, config) {
+            Ok(provider) => Arc::new(provider),
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid configuration for OAuth provider `{}`.", self.name);
+                return Err(rocket);
+            }
+        };
+
+        let base = self.base.trim_end_matches('/');
+        let flow_cookie = format!("{}_{}", IDENTITY_COOKIE, provider.name);
+        let handler = |callback| SignIn {
+            provider: provider.clone(),
+            flow_cookie: flow_cookie.clone(),
+            cookie_path: if base.is_empty() { "/".into() } else { base.to_string() },
+            secure,
+            callback,
+        };
+
+        let routes = vec![
+            Route::new(Method::Get, "/login", handler(false)),
+            Route::new(Method::Get, "/callback", handler(true)),
+        ];
+
+        Ok(rocket.mount(if base.is_empty() { "/" } else { base }, routes))
+    }
+}
+
+/// The handler of a provider's sign-in routes.
+#[derive(Clone)]
+struct SignIn {
+    provider: Arc<Provider>,
+    /// The name of the private cookie holding the state of a sign-in.
+    flow_cookie: String,
+    cookie_path: String,
+    /// Whether cookies are only sent over HTTPS.
+    secure: bool,
+    /// Whether this is the callback route rather than the login route.
+    callback: bool,
+}
+
+impl SignIn {
+    /// Starts a sign-in, redirecting to the provider.
+    fn login<'r>(&self, req: &'r Request<'_>) -> Outcome<'r> {
+        let next = req.get_query_value::<String>("next")
+            .and_then(Result::ok)
+            .filter(|next| is_local_path(next));
+
+        let state = random_token(16);
+        let verifier = random_token(32);
+        let nonce = match self.provider.is_openid() {
+            true => Some(random_token(16)),
+            false => None,
+        };
+
+        let challenge = challenge(&verifier);
+        let url = match self.provider.authorization_url(&state, &challenge, nonce.as_deref()) {
+            Ok(url) => url,
+            Err(e) => {
+                error_!("OAuth provider `{}`: {}", self.provider.name, e);
+                return Outcome::failure(Status::InternalServerError);
+            }
+        };
+
+        let flow = json!({ "state": state, "verifier": verifier, "nonce": nonce, "next": next });
+        let lifetime = time::Duration::seconds(FLOW_LIFETIME);
+        let mut cookie = Cookie::build(self.flow_cookie.clone(), flow.to_string())
+            .path(self.cookie_path.clone())
+            .secure(self.secure)
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .max_age(lifetime)
+            .finish();
+
+        cookie.set_expires(OffsetDateTime::now_utc() + lifetime);
+        req.cookies().add_private(cookie);
+        Outcome::from(req, Redirect::to(url))
+    }
+
+    /// Completes a sign-in, redirecting to the `next` path or the success URI.
+    async fn callback<'r>(&self, req: &'r Request<'_>) -> Outcome<'r> {
+        let cookies = req.cookies();
+        let flow = cookies.get_private(&self.flow_cookie)
+            .and_then(|cookie| serde_json::from_str::<Value>(cookie.value()).ok());
+
+        let mut removal = Cookie::named(self.flow_cookie.clone());
+        removal.set_path(self.cookie_path.clone());
+        cookies.remove_private(removal);
+
+        let query = |name| req.get_query_value::<String>(name).and_then(Result::ok);
+        let flow = match flow {
+            Some(flow) if flow["state"].as_str().is_some()
+                && flow["state"].as_str() == query("state").as_deref() => flow,
+            _ => {
+                warn_!("OAuth callback with a missing or mismatched `state`.");
+                return Outcome::failure(Status::BadRequest);
+            }
+        };
+
+        if let Some(error) = query("error") {
+            info_!("OAuth provider `{}` denied sign-in: {}", self.provider.name, error);
+            return Outcome::failure(Status::Unauthorized);
+        }
+
+        let (code, verifier) = match (query("code"), flow["verifier"].as_str()) {
+            (Some(code), Some(verifier)) => (code, verifier.to_string()),
+            _ => return Outcome::failure(Status::BadRequest),
+        };
+
+        let nonce = flow["nonce"].as_str();
+        let identity = match self.identity(&code, &verifier, nonce).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                error_!("OAuth provider `{}`: {}", self.provider.name, e);
+                return Outcome::failure(Status::BadGateway);
+            }
+        };
+
+        let value = match identity.into_cookie_value() {
+            Some(value) => value,
+            None => {
+                let name = &self.provider.name;
+                error_!("OAuth provider `{}`: identity doesn't fit in a cookie.", name);
+                return Outcome::failure(Status::InternalServerError);
+            }
+        };
+
+        let cookie = Cookie::build(IDENTITY_COOKIE, value)
+            .path("/")
+            .secure(self.secure)
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .finish();
+
+        cookies.add_private(cookie);
+        let next = flow["next"].as_str()
+            .filter(|next| is_local_path(next))
+            .unwrap_or(&self.provider.success_uri);
+
+        Outcome::from(req, Redirect::to(next.to_string()))
+    }
+
+    /// Exchanges `code` for tokens and gathers the user's claims.
+    async fn identity(
+        &self,
+        code: &str,
+        verifier: &str,
+        nonce: Option<&str>
+    ) -> Result<OAuthIdentity, OAuthError> {
+        let provider = &self.provider;
+        let tokens = provider.exchange(code, verifier).await?;
+        let mut claims = match (&tokens.id_token, provider.is_openid()) {
+            (Some(id_token), _) => provider.validate_id_token(id_token, nonce)?,
+            (None, true) => return Err(OAuthError::Provider("missing `id_token`".into())),
+            (None, false) => Map::new(),
+        };
+
+        if let Some(Value::Object(userinfo)) = provider.userinfo(&tokens.access_token).await? {
+            for (key, value) in userinfo {
+                claims.entry(key).or_insert(value);
+            }
+        }
+
+        let subject = match claims.get("sub").or_else(|| claims.get("id")) {
+            Some(Value::String(subject)) => subject.clone(),
+            Some(Value::Number(subject)) => subject.to_string(),
+            _ => return Err(OAuthError::Provider("missing subject claim".into())),
+        };
+
+        Ok(OAuthIdentity {
+            provider: provider.name.clone(),
+            subject,
+            claims,
+            access_token: tokens.access_token,
+            expires_at: tokens.expires_in.map(|secs| unix_now() + secs),
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl Handler for SignIn {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        match self.callback {
+            true => self.callback(req).await,
+            false => self.login(req),
+        }
+    }
+}
+
+/// A request guard for the identity signed in with an [`OAuth2`] provider.
+///
+/// The guard fails with `401 Unauthorized` if no identity is signed in or if
+/// the identity's access token has expired, in which case the identity is
+/// signed out. Use `Option<OAuthIdentity>` for routes that serve anonymous and
+/// signed-in users alike. In tests, the guard can be stubbed with
+/// [`Overrides::guard()`](rocket::local::Overrides::guard()).
+///
+/// The identity's claims are those of the ID token, if the provider returned
+/// one, supplemented by those from the user info endpoint, if configured. Its
+/// subject is the `sub` claim, or the `id` claim for providers, such as
+/// GitHub, that don't implement OpenID Connect.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::oauth::OAuthIdentity;
+///
+/// #[get("/me")]
+/// fn me(identity: OAuthIdentity) -> String {
+///     format!("{} user {}", identity.provider(), identity.subject())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OAuthIdentity {
+    provider: String,
+    subject: String,
+    claims: Map<String, Value>,
+    access_token: String,
+    expires_at: Option<i64>,
+}
+
+impl OAuthIdentity {
+    /// Returns the name of the provider the identity signed in with.
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Returns the identifier of the user at the provider.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the claims about the user.
+    pub fn claims(&self) -> &Map<String, Value> {
+        &self.claims
+    }
+
+    /// Returns the claim `name`, or `None` if there is none or it can't be
+    /// deserialized as a `T`.
+    pub fn claim<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        serde_json::from_value(self.claims.get(name)?.clone()).ok()
+    }
+
+    /// Returns the access token issued by the provider.
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// Returns `true` if the access token has expired. Tokens without a known
+    /// lifetime never expire.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map_or(false, |expires_at| expires_at <= unix_now())
+    }
+
+    /// Signs the current identity, if any, out by removing its cookie from
+    /// `cookies`.
+    pub fn remove(cookies: &CookieJar<'_>) {
+        let mut cookie = Cookie::named(IDENTITY_COOKIE);
+        cookie.set_path("/");
+        cookies.remove_private(cookie);
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "provider": self.provider,
+            "subject": self.subject,
+            "claims": self.claims,
+            "access_token": self.access_token,
+            "expires_at": self.expires_at,
+        })
+    }
+
+    /// Returns the value of the identity's cookie. If it's too large, only
+    /// the [`PROFILE_CLAIMS`] are kept; if it's still too large, returns
+    /// `None`.
+    fn into_cookie_value(mut self) -> Option<String> {
+        let fits = |value: &str| {
+            private_cookie_len(IDENTITY_COOKIE, value.len()) <= MAX_COOKIE_SIZE
+        };
+
+        let value = self.to_json().to_string();
+        if fits(&value) {
+            return Some(value);
+        }
+
+        self.claims.retain(|name, _| PROFILE_CLAIMS.contains(&name.as_str()));
+        let value = self.to_json().to_string();
+        if fits(&value) {
+            warn_!("OAuth identity too large for a cookie: only profile claims were kept.");
+            return Some(value);
+        }
+
+        None
+    }
+
+    fn from_json(value: &str) -> Option<OAuthIdentity> {
+        let mut value = match serde_json::from_str(value).ok()? {
+            Value::Object(value) => value,
+            _ => return None,
+        };
+
+        let claims = match value.remove("claims")? {
+            Value::Object(claims) => claims,
+            _ => return None,
+        };
+
+        Some(OAuthIdentity {
+            provider: value.get("provider")?.as_str()?.to_string(),
+            subject: value.get("subject")?.as_str()?.to_string(),
+            claims,
+            access_token: value.get("access_token")?.as_str()?.to_string(),
+            expires_at: value.get("expires_at").and_then(Value::as_i64),
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for OAuthIdentity {
+    type Error = OAuthError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        if let Some(outcome) = req.stubbed_guard::<Self, OAuthError>() {
+            return outcome;
+        }
+
+        let identity = req.cookies()
+            .get_private(IDENTITY_COOKIE)
+            .and_then(|cookie| OAuthIdentity::from_json(cookie.value()));
+
+        match identity {
+            Some(identity) if identity.is_expired() => {
+                OAuthIdentity::remove(req.cookies());
+                request::Outcome::Failure((Status::Unauthorized, OAuthError::Expired))
+            }
+            Some(identity) => request::Outcome::Success(identity),
+            None => request::Outcome::Failure((Status::Unauthorized, OAuthError::Unauthenticated)),
+        }
+    }
+}
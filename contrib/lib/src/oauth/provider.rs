@@ -0,0 +1,303 @@
+use rand::RngCore;
+use rand::rngs::OsRng;
+use reqwest::Url;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use rocket::Config;
+use rocket::config::{ConfigError, Table, Value as ConfigValue};
+
+use crate::oauth::OAuthError;
+use crate::clock::unix_now;
+
+/// The endpoints of a well-known provider: authorization, token, user info,
+/// and issuer.
+type Preset = (&'static str, &'static str, Option<&'static str>, Option<&'static str>);
+
+const GOOGLE: Preset = (
+    "https://accounts.google.com/o/oauth2/v2/auth",
+    "https://oauth2.googleapis.com/token",
+    Some("https://openidconnect.googleapis.com/v1/userinfo"),
+    Some("https://accounts.google.com"),
+);
+
+const GITHUB: Preset = (
+    "https://github.com/login/oauth/authorize",
+    "https://github.com/login/oauth/access_token",
+    Some("https://api.github.com/user"),
+    None,
+);
+
+/// The keys of a provider's configuration table.
+const KEYS: &[&str] = &[
+    "provider", "client_id", "client_secret", "auth_uri", "token_uri", "userinfo_uri", "issuer",
+    "redirect_uri", "scopes", "success_uri",
+];
+
+/// An OAuth 2.0 provider, as configured in the `oauth.<name>` table.
+#[derive(Debug)]
+pub(crate) struct Provider {
+    pub name: String,
+    client_id: String,
+    client_secret: Option<String>,
+    auth_uri: String,
+    token_uri: String,
+    userinfo_uri: Option<String>,
+    issuer: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    pub success_uri: String,
+    http: reqwest::Client,
+}
+
+/// The tokens returned by a successful token exchange.
+pub(crate) struct Tokens {
+    pub access_token: String,
+    pub expires_in: Option<i64>,
+    pub id_token: Option<String>,
+}
+
+impl Provider {
+    /// Reads the provider `name` from the `oauth.<name>` table in `config`.
+    pub fn from_config(name: &str, config: &Config) -> Result<Provider, ConfigError> {
+        let path = format!("oauth.{}", name);
+        let table = match config.get_table("oauth")?.get(name) {
+            Some(ConfigValue::Table(table)) => table,
+            Some(value) => {
+                return Err(ConfigError::BadType(path, "a table", value.type_str(), None));
+            }
+            None => return Err(ConfigError::Missing(path)),
+        };
+
+        let (mut auth_uri, mut token_uri, mut userinfo_uri, mut issuer) = (None, None, None, None);
+        if let Some(preset) = table.get("provider") {
+            let (auth, token, userinfo, iss) = match preset.as_str() {
+                Some("google") => GOOGLE,
+                Some("github") => GITHUB,
+                _ => return Err(bad_type(&path, "provider", preset)),
+            };
+
+            auth_uri = Some(auth.to_string());
+            token_uri = Some(token.to_string());
+            userinfo_uri = userinfo.map(String::from);
+            issuer = iss.map(String::from);
+        }
+
+        let mut provider = Provider {
+            name: name.to_string(),
+            client_id: required(&path, table, "client_id")?,
+            client_secret: optional(&path, table, "client_secret")?,
+            auth_uri: String::new(),
+            token_uri: String::new(),
+            userinfo_uri: optional(&path, table, "userinfo_uri")?.or(userinfo_uri),
+            issuer: optional(&path, table, "issuer")?.or(issuer),
+            redirect_uri: required(&path, table, "redirect_uri")?,
+            scopes: vec![],
+            success_uri: optional(&path, table, "success_uri")?.unwrap_or_else(|| "/".into()),
+            http: reqwest::Client::new(),
+        };
+
+        provider.auth_uri = match optional(&path, table, "auth_uri")?.or(auth_uri) {
+            Some(uri) => uri,
+            None => return Err(ConfigError::Missing(format!("{}.auth_uri", path))),
+        };
+
+        provider.token_uri = match optional(&path, table, "token_uri")?.or(token_uri) {
+            Some(uri) => uri,
+            None => return Err(ConfigError::Missing(format!("{}.token_uri", path))),
+        };
+
+        if let Some(scopes) = table.get("scopes") {
+            provider.scopes = scopes.as_array()
+                .and_then(|scopes| scopes.iter().map(|s| s.as_str().map(String::from)).collect())
+                .ok_or_else(|| bad_type(&path, "scopes", scopes))?;
+        }
+
+        for key in table.keys() {
+            if !KEYS.contains(&key.as_str()) {
+                warn_!("Ignoring unknown OAuth configuration key '{}.{}'.", path, key);
+            }
+        }
+
+        Ok(provider)
+    }
+
+    /// Returns `true` if the provider is asked for an OpenID Connect ID token.
+    pub fn is_openid(&self) -> bool {
+        self.scopes.iter().any(|scope| scope == "openid")
+    }
+
+    /// Returns the URL of the provider's authorization endpoint for a request
+    /// with `state`, the PKCE `challenge`, and the OpenID Connect `nonce`.
+    pub fn authorization_url(
+        &self,
+        state: &str,
+        challenge: &str,
+        nonce: Option<&str>
+    ) -> Result<String, OAuthError> {
+        let scope = self.scopes.join(" ");
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("state", state),
+            ("code_challenge", challenge),
+            ("code_challenge_method", "S256"),
+        ];
+
+        if !scope.is_empty() {
+            params.push(("scope", scope.as_str()));
+        }
+
+        if let Some(nonce) = nonce {
+            params.push(("nonce", nonce));
+        }
+
+        let url = Url::parse_with_params(&self.auth_uri, &params)
+            .map_err(|e| OAuthError::Provider(format!("invalid `auth_uri`: {}", e)))?;
+
+        Ok(url.as_str().to_string())
+    }
+
+    /// Exchanges the authorization `code` for tokens, proving possession of
+    /// the PKCE `verifier`.
+    pub async fn exchange(&self, code: &str, verifier: &str) -> Result<Tokens, OAuthError> {
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("code_verifier", verifier),
+        ];
+
+        if let Some(secret) = &self.client_secret {
+            params.push(("client_secret", secret.as_str()));
+        }
+
+        let response: Value = self.http.post(&self.token_uri)
+            .header("Accept", "application/json")
+            .form(&params)
+            .send().await?
+            .json().await?;
+
+        if let Some(error) = response.get("error") {
+            let description = response.get("error_description").and_then(Value::as_str);
+            let error = error.as_str().unwrap_or("unknown error");
+            return Err(OAuthError::Provider(match description {
+                Some(description) => format!("{}: {}", error, description),
+                None => error.to_string(),
+            }));
+        }
+
+        let access_token = response.get("access_token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| OAuthError::Provider("missing `access_token`".into()))?;
+
+        Ok(Tokens {
+            access_token: access_token.to_string(),
+            expires_in: response.get("expires_in").and_then(Value::as_i64),
+            id_token: response.get("id_token").and_then(Value::as_str).map(String::from),
+        })
+    }
+
+    /// Retrieves the claims about the user from the user info endpoint, if
+    /// one is configured.
+    pub async fn userinfo(&self, access_token: &str) -> Result<Option<Value>, OAuthError> {
+        let uri = match &self.userinfo_uri {
+            Some(uri) => uri,
+            None => return Ok(None),
+        };
+
+        let response = self.http.get(uri)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .header("User-Agent", "Rocket")
+            .send().await?
+            .error_for_status()?;
+
+        Ok(Some(response.json().await?))
+    }
+
+    /// Decodes the claims of `id_token` and validates its audience, expiry,
+    /// issuer, and `nonce`.
+    ///
+    /// The token's signature isn't verified: the token was received directly
+    /// from the token endpoint over TLS, which authenticates it as permitted
+    /// by OpenID Connect Core 1.0, section 3.1.3.7.
+    pub fn validate_id_token(&self, id_token: &str, nonce: Option<&str>)
+        -> Result<Map<String, Value>, OAuthError>
+    {
+        let invalid = OAuthError::InvalidIdToken;
+        let payload = id_token.split('.').nth(1).ok_or_else(|| invalid("malformed token"))?;
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| invalid("malformed payload"))?;
+
+        let claims = match serde_json::from_slice(&payload) {
+            Ok(Value::Object(claims)) => claims,
+            _ => return Err(invalid("malformed claims")),
+        };
+
+        let audience_matches = match claims.get("aud") {
+            Some(Value::String(aud)) => aud == &self.client_id,
+            Some(Value::Array(auds)) => auds.iter().any(|aud| aud == &*self.client_id),
+            _ => false,
+        };
+
+        if !audience_matches {
+            return Err(invalid("wrong audience"));
+        }
+
+        match claims.get("exp").and_then(Value::as_i64) {
+            Some(exp) if exp > unix_now() => {}
+            _ => return Err(invalid("expired")),
+        }
+
+        if let Some(issuer) = &self.issuer {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err(invalid("wrong issuer"));
+            }
+        }
+
+        if claims.get("nonce").and_then(Value::as_str) != nonce {
+            return Err(invalid("wrong nonce"));
+        }
+
+        Ok(claims)
+    }
+}
+
+/// Returns a random, URL-safe token of `len` random bytes.
+pub(crate) fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+/// Returns the PKCE `S256` code challenge of `verifier`.
+pub(crate) fn challenge(verifier: &str) -> String {
+    base64::encode_config(&Sha256::digest(verifier.as_bytes()), base64::URL_SAFE_NO_PAD)
+}
+
+fn required(path: &str, table: &Table, key: &str) -> Result<String, ConfigError> {
+    optional(path, table, key)?.ok_or_else(|| ConfigError::Missing(format!("{}.{}", path, key)))
+}
+
+fn optional(path: &str, table: &Table, key: &str) -> Result<Option<String>, ConfigError> {
+    match table.get(key) {
+        Some(value) => match value.as_str() {
+            Some(string) => Ok(Some(string.to_string())),
+            None => Err(bad_type(path, key, value)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn bad_type(path: &str, key: &str, value: &ConfigValue) -> ConfigError {
+    let expected = match key {
+        "provider" => "\"google\" or \"github\"",
+        "scopes" => "an array of strings",
+        _ => "a string",
+    };
+
+    ConfigError::BadType(format!("{}.{}", path, key), expected, value.type_str(), None)
+}
@@ -0,0 +1,409 @@
+//! OpenAPI documents describing an application's routes.
+//!
+//! See the [`OpenApi`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `openapi` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["openapi"]
+//! ```
+//!
+//! # Describing Routes
+//!
+//! Routes are described with the [`openapi`](macro@rocket::openapi) attribute,
+//! applied below the route attribute, as detailed in the
+//! [`rocket::openapi`] module. Routes without a description are left out of
+//! the document. [`Json<T>`](crate::json::Json) bodies and responses are
+//! described by `T`'s [`JsonSchema`](rocket::openapi::JsonSchema), which can
+//! be derived with `#[derive(JsonSchema)]`. For example:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket_contrib::json::Json;
+//! use rocket_contrib::openapi::OpenApi;
+//!
+//! /// Lists the names of the items.
+//! #[get("/items")]
+//! #[openapi(tags = "items")]
+//! fn items() -> Json<Vec<String>> {
+//!     /* .. */
+//!     # Json(vec![])
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .mount("/", routes![items])
+//!         .attach(OpenApi::new("Inventory", "1.0.0"))
+//!         // ...
+//!     # ;
+//! }
+//! ```
+
+use std::sync::{Arc, RwLock};
+
+use rocket::{Cargo, Data, Request, Rocket, Route};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{MediaType, Method};
+use rocket::openapi::{Operation, Schema, SchemaGenerator};
+use rocket::response::content;
+
+use serde_json::{json, Map, Value};
+
+/// The version of the OpenAPI specification documents conform to.
+const OPENAPI_VERSION: &str = "3.0.3";
+
+/// A fairing that serves an OpenAPI 3 document describing the application's
+/// routes, and Swagger UI in development.
+///
+/// When attached, `OpenApi` mounts a route at `/openapi.json` serving the
+/// document, and in the development environment, a route at `/swagger-ui`
+/// serving [Swagger UI] pointed at the document. The paths can be changed
+/// with [`OpenApi::path()`] and [`OpenApi::swagger_ui()`]. Swagger UI itself
+/// is loaded from a CDN by the browser.
+///
+/// The document describes every mounted route that was described with the
+/// [`openapi`](macro@rocket::openapi) attribute or
+/// [`Route::openapi()`](rocket::Route::openapi()). It is generated when the
+/// application launches. Local clients don't launch the application, so
+/// documents served to them only describe the routes mounted before the
+/// fairing was attached.
+///
+/// [Swagger UI]: https://swagger.io/tools/swagger-ui/
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::openapi::OpenApi;
+///
+/// let openapi = OpenApi::new("Inventory", "1.0.0")
+///     .description("Keeps track of the items in stock.")
+///     .path("/api/openapi.json");
+///
+/// rocket::ignite().attach(openapi)
+/// # ;
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    path: String,
+    swagger_ui: String,
+    document: Arc<RwLock<String>>,
+}
+
+impl OpenApi {
+    /// Returns a fairing describing the API titled `title` at version
+    /// `version`, served at `/openapi.json`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::openapi::OpenApi;
+    ///
+    /// let openapi = OpenApi::new("Inventory", "1.0.0");
+    /// ```
+    pub fn new<T: Into<String>, V: Into<String>>(title: T, version: V) -> Self {
+        OpenApi {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            path: "/openapi.json".into(),
+            swagger_ui: "/swagger-ui".into(),
+            document: Arc::new(RwLock::new(String::new())),
+        }
+    }
+
+    /// Sets the description of the API, in CommonMark.
+    pub fn description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the path at which the document is served.
+    pub fn path<P: Into<String>>(mut self, path: P) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets the path at which Swagger UI is served in development.
+    pub fn swagger_ui<P: Into<String>>(mut self, path: P) -> Self {
+        self.swagger_ui = path.into();
+        self
+    }
+
+    /// Generates the document describing `routes`.
+    fn generate<'a, I>(&self, routes: I) -> String
+        where I: Iterator<Item = &'a Route>
+    {
+        let mut gen = SchemaGenerator::new();
+        let mut paths = Map::new();
+        for route in routes {
+            let operation = match route.operation(&mut gen) {
+                Some(operation) => operation,
+                None => continue,
+            };
+
+            let method = route.method.as_str().to_lowercase();
+            let item = paths.entry(path_template(route.uri.path()))
+                .or_insert_with(|| json!({}));
+
+            item[method] = operation_json(operation, route.format.as_ref());
+        }
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(ref description) = self.description {
+            info["description"] = json!(description);
+        }
+
+        let schemas: Map<String, Value> = gen.into_components().iter()
+            .map(|(name, schema)| (name.clone(), schema_json(schema)))
+            .collect();
+
+        let document = json!({
+            "openapi": OPENAPI_VERSION,
+            "info": info,
+            "paths": paths,
+            "components": { "schemas": schemas },
+        });
+
+        document.to_string()
+    }
+
+    fn set_document(&self, document: String) {
+        *self.document.write().expect("document lock") = document;
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for OpenApi {
+    fn info(&self) -> Info {
+        Info { name: "OpenAPI", kind: Kind::Attach | Kind::Launch }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let cargo = rocket.inspect().await;
+        self.set_document(self.generate(cargo.routes()));
+
+        let handler = DocumentHandler(self.document.clone());
+        let mut routes = vec![Route::new(Method::Get, &self.path, handler)];
+        if cargo.config().environment.is_dev() {
+            let handler = SwaggerUiHandler(swagger_ui_html(&self.title, &self.path));
+            routes.push(Route::new(Method::Get, &self.swagger_ui, handler));
+        }
+
+        Ok(rocket.mount("/", routes))
+    }
+
+    fn on_launch(&self, cargo: &Cargo) {
+        self.set_document(self.generate(cargo.routes()));
+    }
+}
+
+/// Serves the generated document.
+#[derive(Clone)]
+struct DocumentHandler(Arc<RwLock<String>>);
+
+#[rocket::async_trait]
+impl Handler for DocumentHandler {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        let document = self.0.read().expect("document lock").clone();
+        Outcome::from(req, content::Json(document))
+    }
+}
+
+/// Serves Swagger UI.
+#[derive(Clone)]
+struct SwaggerUiHandler(String);
+
+#[rocket::async_trait]
+impl Handler for SwaggerUiHandler {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        Outcome::from(req, content::Html(self.0.clone()))
+    }
+}
+
+/// Returns the Swagger UI page displaying the document at `path`.
+fn swagger_ui_html(title: &str, path: &str) -> String {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!(r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>{title}</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@3/swagger-ui.css">
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@3/swagger-ui-bundle.js"></script>
+    <script>SwaggerUIBundle({{ url: {url}, dom_id: "#swagger-ui" }});</script>
+  </body>
+</html>
+"#, title = escape(title), url = escape(&json!(path).to_string()))
+}
+
+/// Converts a route path such as `/items/<id>/<rest..>` into an OpenAPI path
+/// template such as `/items/{id}/{rest}`.
+fn path_template(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(name) => format!("{{{}}}", name.trim_end_matches("..")),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the essence of `media_type`, i.e, without its parameters.
+fn essence(media_type: &MediaType) -> String {
+    format!("{}/{}", media_type.top(), media_type.sub())
+}
+
+/// Returns the JSON description of `operation`, whose route has the format
+/// `format`.
+fn operation_json(operation: Operation, format: Option<&MediaType>) -> Value {
+    let mut json = json!({ "operationId": operation.operation_id });
+    if let Some(summary) = operation.summary {
+        json["summary"] = json!(summary);
+    }
+
+    if let Some(description) = operation.description {
+        json["description"] = json!(description);
+    }
+
+    if !operation.tags.is_empty() {
+        json["tags"] = json!(operation.tags);
+    }
+
+    if operation.deprecated {
+        json["deprecated"] = json!(true);
+    }
+
+    if !operation.parameters.is_empty() {
+        let parameters: Vec<Value> = operation.parameters.iter()
+            .map(|parameter| {
+                let mut json = json!({
+                    "name": parameter.name,
+                    "in": parameter.location.as_str(),
+                    "required": parameter.required,
+                    "schema": schema_json(&parameter.schema),
+                });
+
+                if let Some(ref description) = parameter.description {
+                    json["description"] = json!(description);
+                }
+
+                json
+            })
+            .collect();
+
+        json["parameters"] = json!(parameters);
+    }
+
+    if let Some(body) = operation.request_body {
+        let content_type = body.content_type.as_ref().or(format)
+            .map_or_else(|| "*/*".to_string(), essence);
+
+        json["requestBody"] = json!({
+            "required": body.required,
+            "content": { content_type: { "schema": schema_json(&body.schema) } },
+        });
+    }
+
+    let mut responses = Map::new();
+    for response in operation.responses {
+        let status = response.status.map_or_else(|| "default".into(), |s| s.code.to_string());
+        let mut json = json!({ "description": response.description });
+        if let Some(schema) = response.schema {
+            let content_type = response.content_type.as_ref()
+                .map_or_else(|| "*/*".to_string(), essence);
+
+            json["content"] = json!({ content_type: { "schema": schema_json(&schema) } });
+        }
+
+        // When several responses share a status, the first is kept.
+        responses.entry(status).or_insert(json);
+    }
+
+    if responses.is_empty() {
+        responses.insert("default".into(), json!({ "description": "Response" }));
+    }
+
+    json["responses"] = Value::Object(responses);
+    json
+}
+
+/// Returns the JSON form of `schema`.
+fn schema_json(schema: &Schema) -> Value {
+    fn typed(ty: &str, format: &Option<&str>) -> Value {
+        match format {
+            Some(format) => json!({ "type": ty, "format": format }),
+            None => json!({ "type": ty }),
+        }
+    }
+
+    match schema {
+        Schema::Any => json!({}),
+        Schema::Boolean => json!({ "type": "boolean" }),
+        Schema::Integer { format } => typed("integer", format),
+        Schema::Number { format } => typed("number", format),
+        Schema::String { format } => typed("string", format),
+        Schema::Enum(values) => json!({ "type": "string", "enum": values }),
+        Schema::Array(items) => json!({ "type": "array", "items": schema_json(items) }),
+        Schema::Map(values) => {
+            json!({ "type": "object", "additionalProperties": schema_json(values) })
+        }
+        Schema::Object(properties) => {
+            let mut json = json!({ "type": "object" });
+            let mut schemas = Map::new();
+            for property in properties {
+                let schema = match property.description {
+                    Some(ref description) => annotate(schema_json(&property.schema), |json| {
+                        json["description"] = json!(description);
+                    }),
+                    None => schema_json(&property.schema),
+                };
+
+                schemas.insert(property.name.clone(), schema);
+            }
+
+            let required: Vec<_> = properties.iter()
+                .filter(|property| property.required)
+                .map(|property| &property.name)
+                .collect();
+
+            json["properties"] = Value::Object(schemas);
+            if !required.is_empty() {
+                json["required"] = json!(required);
+            }
+
+            json
+        }
+        Schema::Nullable(schema) => annotate(schema_json(schema), |json| {
+            json["nullable"] = json!(true);
+        }),
+        Schema::Reference(name) => json!({ "$ref": format!("#/components/schemas/{}", name) }),
+    }
+}
+
+/// Applies `f` to the schema `json`. References can't have siblings, so a
+/// reference is first wrapped in an `allOf`.
+fn annotate<F: FnOnce(&mut Value)>(json: Value, f: F) -> Value {
+    let mut json = match json.get("$ref") {
+        Some(_) => json!({ "allOf": [json] }),
+        None => json,
+    };
+
+    f(&mut json);
+    json
+}
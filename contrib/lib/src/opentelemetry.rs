@@ -0,0 +1,208 @@
+//! OpenTelemetry tracing for requests.
+//!
+//! See the [`OpenTelemetry`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `opentelemetry` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["opentelemetry"]
+//! ```
+
+pub extern crate opentelemetry as opentelemetry_crate;
+
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime};
+
+use rocket::{Request, Response, Data};
+use rocket::fairing::{Fairing, Info, Kind, Order};
+use rocket::http::Header;
+
+use self::opentelemetry_crate::{global, Context, KeyValue};
+use self::opentelemetry_crate::propagation::TextMapPropagator;
+use self::opentelemetry_crate::sdk::propagation::TraceContextPropagator;
+use self::opentelemetry_crate::trace::{Span, SpanKind, StatusCode, TraceContextExt, Tracer};
+
+/// The name of the tracer used to create spans.
+const TRACER: &str = "rocket";
+
+/// The trace context of a request, containing its server span.
+struct RequestContext(Option<Context>);
+
+/// A fairing that traces every request with [OpenTelemetry].
+///
+/// `OpenTelemetry` opens a server span for every request using the globally
+/// installed tracer provider; install a provider, and with it an exporter, via
+/// [`global::set_tracer_provider()`]. Until one is installed, spans are not
+/// recorded.
+///
+/// The span of a request is named after the route that handled it, falling
+/// back to the request's method, and carries the following attributes from
+/// the OpenTelemetry semantic conventions when the corresponding value is
+/// known: `http.method`, `http.target`, `http.route`, `http.status_code`,
+/// `http.user_agent`, and `net.peer.ip`. Responses with a `5xx` status mark
+/// the span as failed.
+///
+/// If the request carries a W3C [`traceparent`] header, the span continues the
+/// incoming trace. The span's context is sent back to the client in the
+/// `traceparent` and `tracestate` headers of the response.
+///
+/// Each phase of processing the request recorded by Rocket — routing, guards,
+/// handler, and responder — is recorded as a child span of the request's span.
+/// See [`Phase`](rocket::request::Phase) for details. Handlers can create
+/// further child spans via the [`Context`] returned by
+/// [`OpenTelemetry::context()`].
+///
+/// [OpenTelemetry]: https://opentelemetry.io
+/// [`traceparent`]: https://www.w3.org/TR/trace-context/
+/// [`global::set_tracer_provider()`]: opentelemetry_crate::global::set_tracer_provider()
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::opentelemetry::OpenTelemetry;
+///
+/// rocket::ignite().attach(OpenTelemetry::new())
+/// # ;
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OpenTelemetry {
+    _private: (),
+}
+
+impl OpenTelemetry {
+    /// Returns a new `OpenTelemetry` fairing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::opentelemetry::OpenTelemetry;
+    ///
+    /// let fairing = OpenTelemetry::new();
+    /// ```
+    pub fn new() -> OpenTelemetry {
+        OpenTelemetry { _private: () }
+    }
+
+    /// Returns the trace context of `req`, containing its span, if the
+    /// fairing is attached.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket::Request;
+    /// use rocket_contrib::opentelemetry::OpenTelemetry;
+    /// use rocket_contrib::opentelemetry::opentelemetry_crate::global;
+    /// use rocket_contrib::opentelemetry::opentelemetry_crate::trace::{Span, Tracer};
+    ///
+    /// fn lookup(req: &Request<'_>) {
+    ///     let tracer = global::tracer("app");
+    ///     let mut builder = tracer.span_builder("lookup");
+    ///     if let Some(cx) = OpenTelemetry::context(req) {
+    ///         builder = builder.with_parent_context(cx.clone());
+    ///     }
+    ///
+    ///     let span = builder.start(&tracer);
+    ///     /* perform the lookup */
+    ///     span.end();
+    /// }
+    /// ```
+    pub fn context<'r>(req: &'r Request<'_>) -> Option<&'r Context> {
+        req.local_cache(|| RequestContext(None)).0.as_ref()
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for OpenTelemetry {
+    fn info(&self) -> Info {
+        Info {
+            name: "OpenTelemetry",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Spans are opened before any other fairing runs.
+    fn order(&self) -> Order {
+        Order::priority(i32::max_value())
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        let propagator = TraceContextPropagator::new();
+        let carrier: HashMap<String, String> = propagator.fields()
+            .filter_map(|f| req.headers().get_one(f).map(|v| (f.into(), v.into())))
+            .collect();
+
+        let mut attributes = vec![
+            KeyValue::new("http.method", req.method().as_str()),
+            KeyValue::new("http.target", req.uri().to_string()),
+        ];
+
+        if let Some(ip) = req.client_ip() {
+            attributes.push(KeyValue::new("net.peer.ip", ip.to_string()));
+        }
+
+        if let Some(agent) = req.headers().get_one("User-Agent") {
+            attributes.push(KeyValue::new("http.user_agent", agent.to_string()));
+        }
+
+        let tracer = global::tracer(TRACER);
+        let span = tracer.span_builder(&format!("HTTP {}", req.method()))
+            .with_kind(SpanKind::Server)
+            .with_parent_context(propagator.extract(&carrier))
+            .with_attributes(attributes)
+            .start(&tracer);
+
+        let cx = Context::current_with_span(span);
+        req.local_cache(move || RequestContext(Some(cx)));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let cx = match OpenTelemetry::context(req) {
+            Some(cx) => cx,
+            None => return,
+        };
+
+        let span = cx.span();
+        if let Some(route) = req.route() {
+            span.update_name(route.uri.path().to_string());
+            span.set_attribute(KeyValue::new("http.route", route.uri.path().to_string()));
+        }
+
+        let status = res.status();
+        span.set_attribute(KeyValue::new("http.status_code", status.code as i64));
+        if status.code >= 500 {
+            span.set_status(StatusCode::Error, status.reason.to_string());
+        }
+
+        // Phases are timed with `Instant`s; spans need wall-clock times.
+        let (system_now, now) = (SystemTime::now(), Instant::now());
+        let wall_clock = |instant: Instant| system_now - now.saturating_duration_since(instant);
+
+        let tracer = global::tracer(TRACER);
+        for timing in req.phases() {
+            tracer.span_builder(timing.phase.as_str())
+                .with_kind(SpanKind::Internal)
+                .with_parent_context(cx.clone())
+                .with_start_time(wall_clock(timing.start))
+                .start(&tracer)
+                .end_with_timestamp(wall_clock(timing.end));
+        }
+
+        let mut carrier = HashMap::new();
+        TraceContextPropagator::new().inject_context(cx, &mut carrier);
+        for (name, value) in carrier {
+            res.set_header(Header::new(name, value));
+        }
+
+        span.end();
+    }
+}
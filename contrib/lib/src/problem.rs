@@ -0,0 +1,273 @@
+//! RFC 7807 problem details for HTTP APIs.
+//!
+//! See the [`Problem`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `problem` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["problem"]
+//! ```
+
+use std::borrow::Cow;
+
+use rocket::{Request, Catcher};
+use rocket::catcher::ErrorHandlerFuture;
+use rocket::http::{Status, ContentType};
+use rocket::response::{self, Responder, Content, status};
+
+use serde::{Serialize, Serializer};
+use serde::ser::SerializeMap;
+use serde_json::{Map, Value};
+
+/// The default problem type: the problem has no semantics beyond those of its
+/// HTTP status code.
+const ABOUT_BLANK: &str = "about:blank";
+
+/// An RFC 7807 "problem detail" response.
+///
+/// A `Problem` describes an API error in a machine-readable way. It serializes
+/// into an `application/problem+json` document with the standard members
+/// `type`, `title`, `status`, `detail`, and `instance`, along with any
+/// _extension_ members added via [`Problem::extension()`].
+///
+/// # Responder
+///
+/// The `Responder` implementation sets the status of the response to the
+/// problem's status, the `Content-Type` to `application/problem+json`, and the
+/// body to the serialized problem. If an extension fails to serialize, an
+/// `Err` of `Status::InternalServerError` is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::http::Status;
+/// use rocket_contrib::problem::Problem;
+///
+/// #[post("/transfer")]
+/// fn transfer() -> Result<&'static str, Problem> {
+///     Err(Problem::new(Status::Forbidden)
+///         .with_type("https://example.com/probs/out-of-credit")
+///         .title("You do not have enough credit.")
+///         .detail("Your current balance is 30, but that costs 50.")
+///         .instance("/account/12345/msgs/abc")
+///         .extension("balance", 30))
+/// }
+/// ```
+///
+/// # Catchers
+///
+/// To emit problem details for _every_ error, including routing failures and
+/// failing guards, register the catcher returned by [`Problem::catcher()`]:
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::problem::Problem;
+///
+/// let rocket = rocket::ignite().register(vec![Problem::catcher()]);
+/// ```
+///
+/// Problems can also be returned from individual `#[catch]` functions:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::Request;
+/// use rocket::http::Status;
+/// use rocket_contrib::problem::Problem;
+///
+/// #[catch(401)]
+/// fn unauthorized(req: &Request) -> Problem {
+///     Problem::from_request(Status::Unauthorized, req)
+///         .detail("A valid API token is required.")
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    status: Status,
+    type_: Cow<'static, str>,
+    title: Option<Cow<'static, str>>,
+    detail: Option<Cow<'static, str>>,
+    instance: Option<Cow<'static, str>>,
+    extensions: Map<String, Value>,
+}
+
+impl Problem {
+    /// Creates a new `Problem` with status `status`, a `type` of
+    /// `about:blank`, and no title, detail, instance, or extensions. When
+    /// serialized, a missing `title` defaults to the reason phrase of
+    /// `status`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket::http::Status;
+    /// use rocket_contrib::problem::Problem;
+    ///
+    /// let problem = Problem::new(Status::NotFound);
+    /// assert_eq!(problem.status(), Status::NotFound);
+    /// assert_eq!(problem.title(), "Not Found");
+    /// ```
+    pub fn new(status: Status) -> Problem {
+        Problem {
+            status,
+            type_: ABOUT_BLANK.into(),
+            title: None,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Creates a new `Problem` with status `status` whose `instance` is the
+    /// URI of `req`.
+    pub fn from_request(status: Status, req: &Request<'_>) -> Problem {
+        Problem::new(status).instance(req.uri().to_string())
+    }
+
+    /// Returns a catcher that responds to every error with a `Problem` built
+    /// by [`Problem::from_request()`]. The catcher is a _default_ catcher; it
+    /// handles all status codes that don't have a more specific catcher.
+    pub fn catcher() -> Catcher {
+        fn handler<'r>(status: Status, req: &'r Request<'_>) -> ErrorHandlerFuture<'r> {
+            let problem = Problem::from_request(status, req);
+            Box::pin(async move { problem.respond_to(req) })
+        }
+
+        Catcher::new(None, handler)
+    }
+
+    /// Sets the `type` member: a URI reference identifying the problem type.
+    pub fn with_type<T: Into<Cow<'static, str>>>(mut self, type_: T) -> Self {
+        self.type_ = type_.into();
+        self
+    }
+
+    /// Sets the `title` member: a short, human-readable summary of the problem
+    /// type.
+    pub fn title<T: Into<Cow<'static, str>>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the `detail` member: a human-readable explanation specific to this
+    /// occurrence of the problem.
+    pub fn detail<T: Into<Cow<'static, str>>>(mut self, detail: T) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the `instance` member: a URI reference identifying this specific
+    /// occurrence of the problem.
+    pub fn instance<T: Into<Cow<'static, str>>>(mut self, instance: T) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member named `key` with value `value`. Extension
+    /// members named like a standard member are ignored. If `value` fails to
+    /// serialize, it is serialized as `null` and an error is logged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket::http::Status;
+    /// use rocket_contrib::problem::Problem;
+    ///
+    /// let problem = Problem::new(Status::UnprocessableEntity)
+    ///     .extension("invalid_params", vec!["age", "color"]);
+    ///
+    /// let json = problem.to_json_string().unwrap();
+    /// assert!(json.contains(r#""invalid_params":["age","color"]"#));
+    /// ```
+    pub fn extension<K: Into<String>, V: Serialize>(mut self, key: K, value: V) -> Self {
+        let key = key.into();
+        match key.as_str() {
+            "type" | "title" | "status" | "detail" | "instance" => {
+                warn_!("Ignoring problem extension with reserved name '{}'.", key);
+            }
+            _ => {
+                let value = serde_json::to_value(value).unwrap_or_else(|e| {
+                    error_!("Problem extension '{}' failed to serialize: {:?}", key, e);
+                    Value::Null
+                });
+
+                self.extensions.insert(key, value);
+            }
+        }
+
+        self
+    }
+
+    /// Returns the status of this problem.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns the title of this problem, defaulting to the reason phrase of
+    /// its status if none was set.
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(self.status.reason)
+    }
+
+    /// Serializes this problem into an `application/problem+json` string.
+    pub fn to_json_string(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+impl From<Status> for Problem {
+    fn from(status: Status) -> Problem {
+        Problem::new(status)
+    }
+}
+
+impl Serialize for Problem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", &self.type_)?;
+        map.serialize_entry("title", self.title())?;
+        map.serialize_entry("status", &self.status.code)?;
+
+        if let Some(ref detail) = self.detail {
+            map.serialize_entry("detail", detail)?;
+        }
+
+        if let Some(ref instance) = self.instance {
+            map.serialize_entry("instance", instance)?;
+        }
+
+        for (key, value) in &self.extensions {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+/// Serializes the problem and responds with its status and a `Content-Type` of
+/// `application/problem+json`.
+impl<'r> Responder<'r, 'static> for Problem {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let string = self.to_json_string()
+            .map_err(|e| {
+                error_!("Problem failed to serialize: {:?}", e);
+                Status::InternalServerError
+            })?;
+
+        let content_type = ContentType::new("application", "problem+json");
+        status::Custom(self.status, Content(content_type, string)).respond_to(req)
+    }
+}
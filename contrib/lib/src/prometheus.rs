@@ -0,0 +1,197 @@
+//! Prometheus metrics for requests and responses.
+//!
+//! See the [`PrometheusMetrics`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `prometheus` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["prometheus"]
+//! ```
+
+pub extern crate prometheus as prometheus_crate;
+
+use rocket::{Request, Response, Rocket, Data, Route};
+use rocket::fairing::{Fairing, Info, Kind, Order};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{ContentType, Method, Status};
+use rocket::response::Content;
+
+use crate::received::Received;
+
+use self::prometheus_crate::{Encoder, TextEncoder, Registry};
+use self::prometheus_crate::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+
+/// The label used for requests that no route handled.
+const UNMATCHED: &str = "unmatched";
+
+/// A fairing that records request metrics and serves them to Prometheus.
+///
+/// `PrometheusMetrics` records the following metrics for every request, each
+/// labeled by `route`, the URI of the route that handled the request or
+/// `unmatched`, `method`, and `status`:
+///
+/// | Metric                                 | Type      | Description                    |
+/// | -------------------------------------- | --------- | ------------------------------ |
+/// | `rocket_http_requests_total`           | counter   | Number of requests.            |
+/// | `rocket_http_request_duration_seconds` | histogram | Time taken to respond.         |
+/// | `rocket_http_response_size_bytes`      | histogram | Size of sized response bodies. |
+///
+/// When attached, it mounts a route at `/metrics` that serves these metrics,
+/// along with any registered in its [`registry()`](PrometheusMetrics::registry()),
+/// in the Prometheus text exposition format. The path can be changed with
+/// [`PrometheusMetrics::path()`].
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::prometheus::PrometheusMetrics;
+/// use rocket_contrib::prometheus::prometheus_crate::IntCounter;
+///
+/// let metrics = PrometheusMetrics::new();
+///
+/// // Application metrics can be registered alongside the built-in ones.
+/// let logins = IntCounter::new("logins_total", "Number of logins.").unwrap();
+/// metrics.registry().register(Box::new(logins.clone())).unwrap();
+///
+/// rocket::ignite().attach(metrics)
+/// # ;
+/// ```
+#[derive(Clone)]
+pub struct PrometheusMetrics {
+    path: String,
+    registry: Registry,
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    size: HistogramVec,
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        PrometheusMetrics::new()
+    }
+}
+
+impl PrometheusMetrics {
+    /// Returns a new `PrometheusMetrics` with a fresh registry, serving
+    /// metrics at `/metrics`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::prometheus::PrometheusMetrics;
+    ///
+    /// let metrics = PrometheusMetrics::new();
+    /// ```
+    pub fn new() -> PrometheusMetrics {
+        let labels = &["route", "method", "status"];
+        let requests = IntCounterVec::new(
+            Opts::new("rocket_http_requests_total", "Number of HTTP requests."),
+            labels
+        ).expect("valid counter");
+
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "rocket_http_request_duration_seconds",
+                "Time taken to respond to HTTP requests."
+            ),
+            labels
+        ).expect("valid histogram");
+
+        let size = HistogramVec::new(
+            HistogramOpts::new(
+                "rocket_http_response_size_bytes",
+                "Size of HTTP response bodies."
+            ).buckets(prometheus_crate::exponential_buckets(64.0, 4.0, 10).unwrap()),
+            labels
+        ).expect("valid histogram");
+
+        let registry = Registry::new();
+        registry.register(Box::new(requests.clone())).expect("unique metric");
+        registry.register(Box::new(duration.clone())).expect("unique metric");
+        registry.register(Box::new(size.clone())).expect("unique metric");
+
+        PrometheusMetrics { path: "/metrics".into(), registry, requests, duration, size }
+    }
+
+    /// Sets the path at which metrics are served.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::prometheus::PrometheusMetrics;
+    ///
+    /// let metrics = PrometheusMetrics::new().path("/internal/metrics");
+    /// ```
+    pub fn path<P: Into<String>>(mut self, path: P) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Returns the registry from which metrics are served. Register
+    /// application metrics here to serve them with the built-in metrics.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for PrometheusMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Prometheus Metrics",
+            kind: Kind::Attach | Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Requests are timed from before any other fairing runs.
+    fn order(&self) -> Order {
+        Order::priority(i32::max_value())
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let route = Route::new(Method::Get, "/", self.clone());
+        Ok(rocket.mount(&self.path, vec![route]))
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        Received::record(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let route = req.route().map(|r| r.uri.path()).unwrap_or(UNMATCHED);
+        let status = res.status().code.to_string();
+        let labels = &[route, req.method().as_str(), status.as_str()];
+
+        self.requests.with_label_values(labels).inc();
+        let elapsed = Received::elapsed(req).as_secs_f64();
+        self.duration.with_label_values(labels).observe(elapsed);
+        if let Some(size) = res.body().and_then(|b| b.known_size()) {
+            self.size.with_label_values(labels).observe(size as f64);
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Handler for PrometheusMetrics {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, _: Data) -> Outcome<'r> {
+        let encoder = TextEncoder::new();
+        let mut buffer = vec![];
+        if let Err(e) = encoder.encode(&self.registry.gather(), &mut buffer) {
+            error_!("Failed to encode Prometheus metrics: {}", e);
+            return Outcome::failure(Status::InternalServerError);
+        }
+
+        let content_type = ContentType::parse_flexible(encoder.format_type())
+            .unwrap_or(ContentType::Plain);
+
+        Outcome::from(req, Content(content_type, buffer))
+    }
+}
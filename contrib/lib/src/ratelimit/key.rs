@@ -0,0 +1,201 @@
+use std::fmt;
+use std::sync::Arc;
+
+use rocket::Request;
+
+/// Trait implemented by the extractors of the key a request is counted under.
+///
+/// Requests with the same key share a quota. An extractor returns `None` for
+/// requests it can't key, which are counted under the client's IP address
+/// instead.
+///
+/// The following extractors are provided:
+///
+///   * [`ClientIp`]: the client's IP address.
+///   * [`ApiKey`]: the value of a request header, such as `X-Api-Key`.
+///   * `UserId`: the ID of the logged-in user. Requires the `auth` feature.
+///
+/// Functions and closures from `&Request` to `Option<String>` are also
+/// extractors.
+///
+/// # Example
+///
+/// Keying requests by tenant, as named by the first path segment:
+///
+/// ```rust
+/// use std::fmt;
+use std::sync::Arc;
+
+use rocket::Request;
+/// use rocket_contrib::ratelimit::{RateLimit, Quota};
+///
+/// fn tenant(req: &Request<'_>) -> Option<String> {
+///     req.uri().segments().next().map(String::from)
+/// }
+///
+/// let limit = RateLimit::new().global(Quota::per_minute(600)).key(tenant);
+/// ```
+#[rocket::async_trait]
+pub trait KeyExtractor: Send + Sync + 'static {
+    /// Returns the key `req` is counted under, or `None` if `req` isn't rate
+    /// limited.
+    async fn key(&self, req: &Request<'_>) -> Option<String>;
+}
+
+#[rocket::async_trait]
+impl<F> KeyExtractor for F
+    where F: Fn(&Request<'_>) -> Option<String> + Send + Sync + 'static
+{
+    async fn key(&self, req: &Request<'_>) -> Option<String> {
+        self(req)
+    }
+}
+
+/// Keys requests by the client's IP address: that of the remote connection
+/// or, if the [`RateLimit`](super::RateLimit) fairing trusts proxies, the one
+/// returned by [`Request::client_ip()`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClientIp;
+
+#[rocket::async_trait]
+impl KeyExtractor for ClientIp {
+    async fn key(&self, req: &Request<'_>) -> Option<String> {
+        let ip = match req.managed_state::<super::Limiter>() {
+            Some(limiter) => limiter.client_ip(req),
+            None => req.remote().map(|remote| remote.ip()),
+        };
+
+        ip.map(|ip| ip.to_string())
+    }
+}
+
+/// Keys requests by the value of a request header, typically carrying an API
+/// key. Requests without the header, or with a key rejected by the validator
+/// set with [`ApiKey::validate()`], are counted under the client's IP address.
+///
+/// **Warning:** without a validator, every distinct header value has its own
+/// quota, so a client can evade the limit by sending a new value with each
+/// request. Unless keys are validated before the rate limit applies, set a
+/// validator that accepts only issued keys.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_contrib::ratelimit::{RateLimit, Quota, ApiKey};
+///
+/// # fn is_issued(key: &str) -> bool { key == "secret" }
+/// let limit = RateLimit::new()
+///     .global(Quota::per_hour(5000))
+///     .key(ApiKey::header("X-Api-Key").validate(is_issued));
+/// ```
+#[derive(Clone)]
+pub struct ApiKey {
+    header: String,
+    validator: Option<Arc<dyn Fn(&str) -> bool + Send + Sync>>,
+}
+
+impl ApiKey {
+    /// Keys requests by the value of the header `name`.
+    pub fn header<S: Into<String>>(name: S) -> ApiKey {
+        ApiKey { header: name.into(), validator: None }
+    }
+
+    /// Keys requests by the header's value only if `validator` returns `true`
+    /// for it. Requests with other values are counted under the client's IP
+    /// address.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::ratelimit::ApiKey;
+    ///
+    /// let keys = vec!["alpha".to_string(), "beta".to_string()];
+    /// let key = ApiKey::header("X-Api-Key").validate(move |key| keys.iter().any(|k| k == key));
+    /// ```
+    pub fn validate<F>(mut self, validator: F) -> ApiKey
+        where F: Fn(&str) -> bool + Send + Sync + 'static
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("header", &self.header)
+            .field("validated", &self.validator.is_some())
+            .finish()
+    }
+}
+
+#[rocket::async_trait]
+impl KeyExtractor for ApiKey {
+    async fn key(&self, req: &Request<'_>) -> Option<String> {
+        let key = req.headers().get_one(&self.header)?;
+        match &self.validator {
+            Some(validator) if !validator(key) => None,
+            _ => Some(key.to_string()),
+        }
+    }
+}
+
+/// Keys requests by the ID of the logged-in user, as retrieved by the
+/// [`Authenticated`](crate::auth::Authenticated) request guard. Requests
+/// without a logged-in user are counted under the client's IP address.
+///
+/// This type is only available when the `auth` feature is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "auth")] mod test {
+/// # use std::fmt;
+use std::sync::Arc;
+
+use rocket::Request;
+/// use rocket_contrib::auth::AuthUser;
+/// use rocket_contrib::ratelimit::{RateLimit, Quota, UserId};
+///
+/// struct User(String);
+///
+/// #[rocket::async_trait]
+/// impl AuthUser for User {
+///     /* ... */
+/// #   fn auth_id(&self) -> String { self.0.clone() }
+/// #   async fn fetch(id: &str, _: &Request<'_>) -> Option<User> { None }
+/// }
+///
+/// fn limit() -> RateLimit {
+///     RateLimit::new().global(Quota::per_minute(100)).key(UserId::<User>::new())
+/// }
+/// # }
+/// ```
+#[cfg(feature = "auth")]
+pub struct UserId<U>(std::marker::PhantomData<fn() -> U>);
+
+#[cfg(feature = "auth")]
+impl<U: crate::auth::AuthUser> UserId<U> {
+    /// Keys requests by the ID of the logged-in `U`.
+    pub fn new() -> Self {
+        UserId(std::marker::PhantomData)
+    }
+}
+
+#[cfg(feature = "auth")]
+impl<U: crate::auth::AuthUser> Default for UserId<U> {
+    fn default() -> Self {
+        UserId::new()
+    }
+}
+
+#[cfg(feature = "auth")]
+#[rocket::async_trait]
+impl<U: crate::auth::AuthUser> KeyExtractor for UserId<U> {
+    async fn key(&self, req: &Request<'_>) -> Option<String> {
+        use crate::auth::{AuthUser, Authenticated};
+
+        let user = req.guard::<Authenticated<U>>().await.succeeded()?;
+        Some(user.auth_id())
+    }
+}
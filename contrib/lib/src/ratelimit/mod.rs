@@ -0,0 +1,613 @@
+//! Rate limiting by client, user, or API key.
+//!
+//! See the [`RateLimit`] fairing and the [`RateLimited`] request guard for
+//! further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `ratelimit` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["ratelimit"]
+//! ```
+//!
+//! # Overview
+//!
+//! Requests are counted under a key, such as the client's IP address, against
+//! a [`Quota`] of requests per period. Keys are extracted by a
+//! [`KeyExtractor`], quotas are enforced with a token bucket or a sliding
+//! window [`Algorithm`], and counts are kept in a [`RateLimitStore`], in
+//! memory or in Redis.
+//!
+//! Requests for which the extractor returns no key, such as those without an
+//! API key, are counted under the client's IP address instead, and requests
+//! without either share a single quota. The IP address is that of the remote
+//! connection unless `trust_proxy` is set, in which case the `X-Real-IP`
+//! header set by a reverse proxy takes precedence: only set it behind a proxy
+//! that overwrites the header, as clients could otherwise pick their own key.
+//!
+//! Limits are applied in three ways, which may be combined:
+//!
+//!   * **Globally**, by the [`RateLimit`] fairing, to every request before
+//!     it is routed.
+//!   * **Per route**, by the [`RateLimited`] request guard, to the requests
+//!     handled by a route, according to a [`Policy`].
+//!   * **Per route**, by the [`rate_limit`] attribute, which declares a policy
+//!     named after the route's function and adds the guard to the route. The
+//!     attribute must be placed above the route attribute.
+//!
+//! A request over its quota fails with `429 Too Many Requests`. Responses to
+//! limited requests carry `RateLimit-Limit`, `RateLimit-Remaining`,
+//! `RateLimit-Reset`, and `RateLimit-Policy` headers, as in the IETF
+//! `RateLimit` header fields draft, describing the most restrictive quota
+//! counted, and `Retry-After` when the request is refused.
+//!
+//! If the store fails, requests are allowed and a warning is logged.
+//!
+//! # Configuration
+//!
+//! The global quota and per-route policies can be configured in the
+//! `ratelimit` table in `Rocket.toml`, overriding those set in code:
+//!
+//! ```toml
+//! [global.ratelimit]
+//! limit = 600
+//! period = 60
+//! algorithm = "sliding_window"
+//! headers = true
+//! trust_proxy = true
+//!
+//! [global.ratelimit.policies.login]
+//! limit = 5
+//! period = 300
+//! ```
+//!
+//! Every key is optional. `period` is in seconds, `algorithm` is one of
+//! `"token_bucket"`, the default, or `"sliding_window"`, `headers` toggles
+//! the `RateLimit-*` headers, which are sent by default, and `trust_proxy`,
+//! `false` by default, keys requests by the `X-Real-IP` header. Each table in
+//! `policies` replaces the quota of the [`Policy`] with the same name.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket_contrib::ratelimit::{RateLimit, Quota, rate_limit};
+//!
+//! #[rate_limit(limit = 5, period = 60)]
+//! #[post("/login")]
+//! fn login() -> &'static str {
+//!     "welcome"
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(RateLimit::new().global(Quota::per_minute(600)))
+//!         .mount("/", routes![login])
+//! #       ;
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rocket::{Config, Data, Request, Response, Rocket, Route};
+use rocket::config::{ConfigError, Table, Value};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::handler::{Handler, Outcome};
+use rocket::http::{Method, Status, uri::Origin};
+use rocket::request::{self, FromRequest};
+
+mod quota;
+mod key;
+mod store;
+#[cfg(feature = "redis_pool")] mod redis;
+
+pub use self::quota::{Quota, Algorithm, Decision};
+pub use self::key::{KeyExtractor, ClientIp, ApiKey};
+#[cfg(feature = "auth")] pub use self::key::UserId;
+pub use self::store::{RateLimitStore, RateLimitError, MemoryStore};
+#[cfg(feature = "redis_pool")] pub use self::redis::RedisStore;
+
+pub use rocket_contrib_codegen::rate_limit;
+
+/// The path refused requests are rewritten to, and the rank of the route
+/// refusing them, which is tried before any application route.
+const REFUSED_PATH: &str = "/__rocket/ratelimit/refused";
+const RANK: isize = isize::min_value() + 1;
+
+/// The name under which the global quota is counted and configured.
+const GLOBAL: &str = "global";
+
+/// A fairing that enforces a global quota and enables [`RateLimited`] guards.
+///
+/// On attach, the fairing reads the `ratelimit` configuration table, as
+/// described in the [module documentation](crate::ratelimit), and prepares
+/// its store. If the configuration is invalid or the store can't be prepared,
+/// an error is logged and launch is aborted.
+///
+/// If a global quota is set, in code or in configuration, the fairing counts
+/// every request before it is routed. Requests over the quota are rewritten
+/// to a route mounted by the fairing that fails them with
+/// `429 Too Many Requests`, so no application handler runs for them.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::ratelimit::{RateLimit, Quota, ApiKey, MemoryStore};
+///
+/// let limit = RateLimit::new()
+///     .global(Quota::per_second(10))
+///     .key(ApiKey::header("X-Api-Key"))
+///     .store(MemoryStore::new());
+///
+/// rocket::ignite().attach(limit)
+/// # ;
+/// ```
+pub struct RateLimit {
+    global: Option<Quota>,
+    key: Arc<dyn KeyExtractor>,
+    store: Mutex<Option<Box<dyn RateLimitStore>>>,
+    headers: bool,
+    trust_proxy: bool,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit::new()
+    }
+}
+
+impl RateLimit {
+    /// Returns a fairing with no global quota, keying requests by
+    /// [`ClientIp`], counting them in a [`MemoryStore`], sending
+    /// `RateLimit-*` headers, and not trusting proxies.
+    pub fn new() -> RateLimit {
+        RateLimit {
+            global: None,
+            key: Arc::new(ClientIp),
+            store: Mutex::new(Some(Box::new(MemoryStore::new()))),
+            headers: true,
+            trust_proxy: false,
+        }
+    }
+
+    /// Limits every request to `quota`.
+    pub fn global(mut self, quota: Quota) -> Self {
+        self.global = Some(quota);
+        self
+    }
+
+    /// Keys the requests counted against the global quota with `key`.
+    pub fn key<K: KeyExtractor>(mut self, key: K) -> Self {
+        self.key = Arc::new(key);
+        self
+    }
+
+    /// Counts requests in `store`.
+    pub fn store<S: RateLimitStore>(self, store: S) -> Self {
+        *self.store.lock().expect("rate limit store lock") = Some(Box::new(store));
+        self
+    }
+
+    /// Sets whether responses carry `RateLimit-*` headers.
+    pub fn headers(mut self, enabled: bool) -> Self {
+        self.headers = enabled;
+        self
+    }
+
+    /// Sets whether the client's IP address is read from the `X-Real-IP`
+    /// header, when present, rather than from the remote connection. Only
+    /// enable this behind a reverse proxy that sets the header.
+    pub fn trust_proxy(mut self, enabled: bool) -> Self {
+        self.trust_proxy = enabled;
+        self
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RateLimit {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate Limiting",
+            kind: Kind::Attach | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let config = rocket.config().await;
+        let settings = Settings::from_config(config, self.global, self.headers, self.trust_proxy);
+        let settings = match settings {
+            Ok(settings) => settings,
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid rate limiting configuration.");
+                return Err(rocket);
+            }
+        };
+
+        let store = self.store.lock().expect("rate limit store lock").take();
+        let store: Arc<dyn RateLimitStore> = match store {
+            Some(mut store) => match store.init(rocket.inspect().await).await {
+                Ok(()) => Arc::from(store),
+                Err(e) => {
+                    error!("Failed to initialize the rate limit store.");
+                    error_!("{}", e);
+                    return Err(rocket);
+                }
+            },
+            None => {
+                error!("A `RateLimit` fairing can only be attached once.");
+                return Err(rocket);
+            }
+        };
+
+        if settings.global.is_some() {
+            let refuse = Route::ranked(RANK, Method::Get, REFUSED_PATH, Refuse);
+            rocket = rocket.mount("/", vec![refuse]);
+        }
+
+        let limiter = Limiter {
+            store,
+            global: settings.global,
+            policies: settings.policies,
+            headers: settings.headers,
+            trust_proxy: settings.trust_proxy,
+        };
+
+        Ok(rocket.manage(limiter))
+    }
+
+    /// Counts the request against the global quota, if any, rewriting it to
+    /// the refusing route if it is over the quota.
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        let limiter = match req.managed_state::<Limiter>() {
+            Some(limiter) => limiter,
+            None => return,
+        };
+
+        let quota = match limiter.global {
+            Some(quota) => quota,
+            None => return,
+        };
+
+        match limiter.check(req, GLOBAL, quota, &*self.key).await {
+            Some(decision) if !decision.allowed => {
+                req.local_cache(|| Refused(true));
+                req.set_method(Method::Get);
+                req.set_uri(Origin::parse(REFUSED_PATH).expect("valid origin"));
+            }
+            _ => {}
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let headers = match req.managed_state::<Limiter>() {
+            Some(limiter) => limiter.headers,
+            None => return,
+        };
+
+        let decisions = req.local_cache(|| Decisions(Mutex::new(None)));
+        let (decision, quota) = match *decisions.0.lock().expect("rate limit decision lock") {
+            Some(kept) => kept,
+            None => return,
+        };
+
+        if headers {
+            let policy = format!("{};w={}", quota.limit, seconds(quota.period));
+            res.set_raw_header("RateLimit-Limit", decision.limit.to_string());
+            res.set_raw_header("RateLimit-Remaining", decision.remaining.to_string());
+            res.set_raw_header("RateLimit-Reset", seconds(decision.reset).to_string());
+            res.set_raw_header("RateLimit-Policy", policy);
+        }
+
+        if let Some(retry_after) = decision.retry_after {
+            res.set_raw_header("Retry-After", seconds(retry_after).to_string());
+        }
+    }
+}
+
+/// The configuration read from the `ratelimit` table.
+#[derive(Debug)]
+struct Settings {
+    global: Option<Quota>,
+    headers: bool,
+    trust_proxy: bool,
+    policies: HashMap<String, Quota>,
+}
+
+impl Settings {
+    /// Reads the settings from the `ratelimit` table in `config`, starting
+    /// from the `global` quota, `headers`, and `trust_proxy` set in code.
+    fn from_config(
+        config: &Config,
+        global: Option<Quota>,
+        headers: bool,
+        trust_proxy: bool,
+    ) -> Result<Settings, ConfigError> {
+        let policies = HashMap::new();
+        let mut settings = Settings { global, headers, trust_proxy, policies };
+        let table = match config.get_table("ratelimit") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(settings),
+            Err(e) => return Err(e),
+        };
+
+        for (key, value) in table {
+            match key.as_str() {
+                "limit" | "period" | "algorithm" => {}
+                "headers" => {
+                    settings.headers = value.as_bool().ok_or_else(|| bad_type(key, value))?;
+                }
+                "trust_proxy" => {
+                    settings.trust_proxy = value.as_bool().ok_or_else(|| bad_type(key, value))?;
+                }
+                "policies" => {
+                    let policies = value.as_table().ok_or_else(|| bad_type(key, value))?;
+                    for (name, policy) in policies {
+                        let key = format!("policies.{}", name);
+                        let policy = policy.as_table().ok_or_else(|| bad_type(&key, policy))?;
+                        if let Some(quota) = parse_quota(&format!("{}.", key), policy, None)? {
+                            settings.policies.insert(name.clone(), quota);
+                        }
+                    }
+                }
+                _ => warn_!("Ignoring unknown ratelimit configuration key '{}'.", key),
+            }
+        }
+
+        settings.global = parse_quota("", table, settings.global)?;
+        Ok(settings)
+    }
+}
+
+/// Reads a quota from the `limit`, `period`, and `algorithm` keys of `table`,
+/// whose keys are named `ratelimit.{prefix}{key}`. Missing keys take their
+/// values from `default`. Returns `None` if `table` has none of the keys and
+/// there is no `default`.
+fn parse_quota(
+    prefix: &str,
+    table: &Table,
+    default: Option<Quota>
+) -> Result<Option<Quota>, ConfigError> {
+    let positive = |key: &str| match table.get(key) {
+        Some(value) => match value.as_integer() {
+            Some(n) if n > 0 => Ok(Some(n as u64)),
+            _ => Err(bad_type(&format!("{}{}", prefix, key), value)),
+        },
+        None => Ok(None),
+    };
+
+    let limit = positive("limit")?.or(default.map(|q| q.limit));
+    let period = positive("period")?.map(Duration::from_secs).or(default.map(|q| q.period));
+    let algorithm = match table.get("algorithm") {
+        Some(value) => Some(match value.as_str() {
+            Some("token_bucket") => Algorithm::TokenBucket,
+            Some("sliding_window") => Algorithm::SlidingWindow,
+            _ => return Err(bad_type(&format!("{}algorithm", prefix), value)),
+        }),
+        None => default.map(|q| q.algorithm),
+    };
+
+    match (limit, period) {
+        (Some(limit), Some(period)) => Ok(Some(Quota {
+            algorithm: algorithm.unwrap_or(Algorithm::TokenBucket),
+            ..Quota::new(limit, period)
+        })),
+        (None, None) if algorithm.is_none() => Ok(None),
+        (None, _) => Err(ConfigError::Missing(format!("ratelimit.{}limit", prefix))),
+        (_, None) => Err(ConfigError::Missing(format!("ratelimit.{}period", prefix))),
+    }
+}
+
+fn bad_type(key: &str, value: &Value) -> ConfigError {
+    let expected = match key.rsplit('.').next() {
+        Some("headers") | Some("trust_proxy") => "a boolean",
+        Some("limit") | Some("period") => "a positive integer",
+        Some("algorithm") => "\"token_bucket\" or \"sliding_window\"",
+        _ => "a table",
+    };
+
+    ConfigError::BadType(format!("ratelimit.{}", key), expected, value.type_str(), None)
+}
+
+/// Returns `duration` in whole seconds, rounded up.
+fn seconds(duration: Duration) -> u64 {
+    duration.as_secs() + if duration.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+/// The store and settings, managed by the [`RateLimit`] fairing.
+pub(crate) struct Limiter {
+    store: Arc<dyn RateLimitStore>,
+    global: Option<Quota>,
+    policies: HashMap<String, Quota>,
+    headers: bool,
+    trust_proxy: bool,
+}
+
+impl Limiter {
+    /// Returns the IP address of the client of `req`: that of the remote
+    /// connection, or that in the `X-Real-IP` header if proxies are trusted.
+    pub(crate) fn client_ip(&self, req: &Request<'_>) -> Option<IpAddr> {
+        match self.trust_proxy {
+            true => req.client_ip(),
+            false => req.remote().map(|remote| remote.ip()),
+        }
+    }
+
+    /// Counts `req` against the quota named `name`, `quota` unless configured
+    /// otherwise, under the key extracted by `key`, falling back to the
+    /// client's IP address, and then to a key shared by every request with
+    /// neither. Returns `None` if the store failed.
+    async fn check(
+        &self,
+        req: &Request<'_>,
+        name: &str,
+        quota: Quota,
+        key: &dyn KeyExtractor
+    ) -> Option<Decision> {
+        let key = match key.key(req).await {
+            Some(key) => format!("{}:key:{}", name, key),
+            None => match self.client_ip(req) {
+                Some(ip) => format!("{}:ip:{}", name, ip),
+                None => format!("{}:unknown", name),
+            },
+        };
+
+        let quota = self.policies.get(name).copied().unwrap_or(quota);
+        let decision = match self.store.acquire(&key, &quota).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                warn_!("Failed to count a rate limited request: {}", e);
+                return None;
+            }
+        };
+
+        req.local_cache(|| Decisions(Mutex::new(None))).record(decision, quota);
+        Some(decision)
+    }
+}
+
+/// The most restrictive decision made for a request and its quota.
+struct Decisions(Mutex<Option<(Decision, Quota)>>);
+
+impl Decisions {
+    /// Keeps `decision` if it is more restrictive than the one kept so far:
+    /// refusals over allowances, and fewer remaining requests otherwise.
+    fn record(&self, decision: Decision, quota: Quota) {
+        let mut kept = self.0.lock().expect("rate limit decision lock");
+        let replace = match *kept {
+            Some((k, _)) if k.allowed == decision.allowed => decision.remaining < k.remaining,
+            Some((k, _)) => k.allowed,
+            None => true,
+        };
+
+        if replace {
+            *kept = Some((decision, quota));
+        }
+    }
+}
+
+/// Whether the request was refused by the global quota.
+struct Refused(bool);
+
+/// The handler of the route that refuses requests over the global quota.
+#[derive(Clone)]
+struct Refuse;
+
+#[rocket::async_trait]
+impl Handler for Refuse {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        match req.local_cache(|| Refused(false)).0 {
+            true => Outcome::failure(Status::TooManyRequests),
+            false => Outcome::forward(data),
+        }
+    }
+}
+
+/// A named quota applied to routes by the [`RateLimited`] request guard.
+///
+/// Policies are usually declared with the [`rate_limit`] attribute, but can
+/// be implemented by hand. The quota of a policy can be overridden in the
+/// `policies` configuration table under the policy's name.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::ratelimit::{Policy, Quota, KeyExtractor, ApiKey, RateLimited};
+///
+/// struct Search;
+///
+/// impl Policy for Search {
+///     const NAME: &'static str = "search";
+///
+///     fn quota() -> Quota {
+///         Quota::per_minute(30).sliding_window()
+///     }
+///
+///     fn key() -> Box<dyn KeyExtractor> {
+///         Box::new(ApiKey::header("X-Api-Key"))
+///     }
+/// }
+///
+/// #[get("/search?<q>")]
+/// fn search(q: String, _limit: RateLimited<Search>) -> String {
+///     format!("results for {}", q)
+/// }
+/// ```
+pub trait Policy: Send + Sync + 'static {
+    /// The name under which requests are counted and the quota is configured.
+    /// Routes sharing a policy share its quota.
+    const NAME: &'static str;
+
+    /// Returns the quota of the policy.
+    fn quota() -> Quota;
+
+    /// Returns the extractor of the key requests are counted under. The
+    /// default is [`ClientIp`].
+    fn key() -> Box<dyn KeyExtractor> {
+        Box::new(ClientIp)
+    }
+}
+
+/// A request guard that counts requests against the quota of the policy `P`.
+///
+/// The guard succeeds if the request is allowed, or isn't limited because the
+/// store failed. Otherwise it fails with `429 Too Many Requests` and
+/// [`RateLimitError::Exceeded`]. If the [`RateLimit`] fairing isn't attached,
+/// it fails with `500 Internal Server Error`.
+///
+/// See [`Policy`] for an example.
+pub struct RateLimited<P> {
+    decision: Option<Decision>,
+    _policy: PhantomData<fn() -> P>,
+}
+
+impl<P: Policy> RateLimited<P> {
+    /// Returns the decision to allow the request, or `None` if the request
+    /// isn't limited because the store failed.
+    pub fn decision(&self) -> Option<&Decision> {
+        self.decision.as_ref()
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r, P: Policy> FromRequest<'a, 'r> for RateLimited<P> {
+    type Error = RateLimitError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        if let Some(outcome) = req.stubbed_guard::<Self, RateLimitError>() {
+            return outcome;
+        }
+
+        let limiter = match req.managed_state::<Limiter>() {
+            Some(limiter) => limiter,
+            None => {
+                error_!("Missing rate limiting fairing: attach `RateLimit`.");
+                let error = RateLimitError::Unmanaged;
+                return request::Outcome::Failure((Status::InternalServerError, error));
+            }
+        };
+
+        let key = P::key();
+        match limiter.check(req, P::NAME, P::quota(), &*key).await {
+            Some(decision) if !decision.allowed => {
+                let error = RateLimitError::Exceeded(decision);
+                request::Outcome::Failure((Status::TooManyRequests, error))
+            }
+            decision => request::Outcome::Success(RateLimited { decision, _policy: PhantomData }),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+/// The algorithm with which a [`Quota`] is enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// A bucket of `limit` tokens, refilled continuously at `limit` tokens
+    /// per `period`. Each request takes a token. Allows bursts of up to
+    /// `limit` requests after a quiet period.
+    TokenBucket,
+    /// At most `limit` requests in any window of `period`, estimated from the
+    /// counts of the current and previous fixed windows. Allows no bursts
+    /// beyond the limit, at the cost of a less even rate.
+    SlidingWindow,
+}
+
+/// A limit of `limit` requests per `period`, enforced with `algorithm`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket_contrib::ratelimit::{Quota, Algorithm};
+///
+/// let quota = Quota::per_minute(60);
+/// assert_eq!(quota.limit, 60);
+/// assert_eq!(quota.period, Duration::from_secs(60));
+/// assert_eq!(quota.algorithm, Algorithm::TokenBucket);
+///
+/// let quota = Quota::new(1000, Duration::from_secs(3600)).sliding_window();
+/// assert_eq!(quota.algorithm, Algorithm::SlidingWindow);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// The number of requests allowed per `period`.
+    pub limit: u64,
+    /// The period over which `limit` requests are allowed.
+    pub period: Duration,
+    /// The algorithm enforcing the quota.
+    pub algorithm: Algorithm,
+}
+
+impl Quota {
+    /// Returns a token bucket quota of `limit` requests per `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero or `period` is shorter than a millisecond.
+    pub fn new(limit: u64, period: Duration) -> Quota {
+        assert!(limit > 0, "rate limit must be positive");
+        assert!(period.as_millis() > 0, "rate limit period must be at least 1ms");
+        Quota { limit, period, algorithm: Algorithm::TokenBucket }
+    }
+
+    /// Returns a token bucket quota of `limit` requests per second.
+    pub fn per_second(limit: u64) -> Quota {
+        Quota::new(limit, Duration::from_secs(1))
+    }
+
+    /// Returns a token bucket quota of `limit` requests per minute.
+    pub fn per_minute(limit: u64) -> Quota {
+        Quota::new(limit, Duration::from_secs(60))
+    }
+
+    /// Returns a token bucket quota of `limit` requests per hour.
+    pub fn per_hour(limit: u64) -> Quota {
+        Quota::new(limit, Duration::from_secs(60 * 60))
+    }
+
+    /// Enforces this quota with the token bucket algorithm.
+    pub fn token_bucket(mut self) -> Quota {
+        self.algorithm = Algorithm::TokenBucket;
+        self
+    }
+
+    /// Enforces this quota with the sliding window algorithm.
+    pub fn sliding_window(mut self) -> Quota {
+        self.algorithm = Algorithm::SlidingWindow;
+        self
+    }
+
+    /// The period in milliseconds.
+    pub(crate) fn period_ms(&self) -> u64 {
+        self.period.as_millis() as u64
+    }
+}
+
+/// The outcome of counting a request against a [`Quota`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    /// Whether the request is allowed.
+    pub allowed: bool,
+    /// The quota's limit.
+    pub limit: u64,
+    /// The number of further requests allowed right away.
+    pub remaining: u64,
+    /// The time until the quota is fully replenished.
+    pub reset: Duration,
+    /// If the request isn't allowed, the time until one would be.
+    pub retry_after: Option<Duration>,
+}
+
+/// The state of a token bucket: the number of tokens left, and the time, in
+/// milliseconds since the Unix epoch, when it was last counted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Bucket {
+    pub tokens: f64,
+    pub updated: u64,
+}
+
+/// The state of a sliding window: the index of the current window, the count
+/// in it, and the count in the previous window.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Window {
+    pub index: u64,
+    pub current: u64,
+    pub previous: u64,
+}
+
+/// Counts a request at `now`, in milliseconds since the Unix epoch, against
+/// `quota` with the token bucket `state`, updating it.
+pub(crate) fn token_bucket(quota: &Quota, state: Option<Bucket>, now: u64) -> (Decision, Bucket) {
+    let limit = quota.limit as f64;
+    let rate = limit / quota.period_ms() as f64;
+    let state = state.unwrap_or(Bucket { tokens: limit, updated: now });
+    let mut tokens = (state.tokens + now.saturating_sub(state.updated) as f64 * rate).min(limit);
+
+    let retry_after = match tokens >= 1.0 {
+        true => {
+            tokens -= 1.0;
+            None
+        }
+        false => Some(millis((1.0 - tokens) / rate)),
+    };
+
+    let decision = Decision {
+        allowed: retry_after.is_none(),
+        limit: quota.limit,
+        remaining: tokens.floor() as u64,
+        reset: millis((limit - tokens) / rate),
+        retry_after,
+    };
+
+    (decision, Bucket { tokens, updated: now })
+}
+
+/// Counts a request at `now`, in milliseconds since the Unix epoch, against
+/// `quota` with the sliding window `state`, updating it.
+pub(crate) fn sliding_window(quota: &Quota, state: Option<Window>, now: u64) -> (Decision, Window) {
+    let period = quota.period_ms();
+    let (index, elapsed) = (now / period, now % period);
+    let (current, previous) = match state {
+        Some(w) if w.index == index => (w.current, w.previous),
+        Some(w) if w.index + 1 == index => (0, w.current),
+        _ => (0, 0),
+    };
+
+    let weight = (period - elapsed) as f64 / period as f64;
+    let estimate = previous as f64 * weight + current as f64;
+    let reset = Duration::from_millis(period - elapsed);
+    let mut decision = Decision {
+        allowed: estimate + 1.0 <= quota.limit as f64,
+        limit: quota.limit,
+        remaining: 0,
+        reset,
+        retry_after: None,
+    };
+
+    if decision.allowed {
+        decision.remaining = (quota.limit as f64 - estimate - 1.0).max(0.0).floor() as u64;
+        return (decision, Window { index, current: current + 1, previous });
+    }
+
+    // The previous window's weight must decay until the estimate leaves room
+    // for a request; if the current window alone is full, wait for the next.
+    decision.retry_after = Some(match current + 1 > quota.limit || previous == 0 {
+        true => reset,
+        false => {
+            let room = (quota.limit - current - 1) as f64;
+            let at = period as f64 - room * period as f64 / previous as f64;
+            Duration::from_millis((at.ceil() as u64).saturating_sub(elapsed).max(1))
+        }
+    });
+
+    (decision, Window { index, current, previous })
+}
+
+fn millis(ms: f64) -> Duration {
+    Duration::from_millis(ms.max(0.0).ceil() as u64)
+}
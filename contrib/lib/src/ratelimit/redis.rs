@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use rocket::Cargo;
+
+use crate::databases::Database;
+use crate::databases::redis::{self, aio::ConnectionManager};
+use crate::ratelimit::{RateLimitStore, RateLimitError, Quota, Algorithm, Decision};
+
+/// Counts a request against a token bucket. Mirrors `quota::token_bucket()`.
+const TOKEN_BUCKET: &str = r#"
+redis.replicate_commands()
+local limit = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local rate = limit / period
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'updated')
+local tokens = tonumber(state[1]) or limit
+local updated = tonumber(state[2]) or now
+tokens = math.min(limit, tokens + math.max(0, now - updated) * rate)
+local retry = -1
+if tokens >= 1 then
+    tokens = tokens - 1
+else
+    retry = math.ceil((1 - tokens) / rate)
+end
+redis.call('HMSET', KEYS[1], 'tokens', tostring(tokens), 'updated', now)
+redis.call('PEXPIRE', KEYS[1], 2 * period)
+return {math.floor(tokens), math.ceil((limit - tokens) / rate), retry}
+"#;
+
+/// Counts a request against a sliding window. Mirrors `quota::sliding_window()`.
+const SLIDING_WINDOW: &str = r#"
+redis.replicate_commands()
+local limit = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local index = math.floor(now / period)
+local elapsed = now - index * period
+local key = KEYS[1] .. ':' .. index
+local current = tonumber(redis.call('GET', key)) or 0
+local previous = tonumber(redis.call('GET', KEYS[1] .. ':' .. (index - 1))) or 0
+local estimate = previous * (period - elapsed) / period + current
+local reset = period - elapsed
+if estimate + 1 <= limit then
+    redis.call('INCR', key)
+    redis.call('PEXPIRE', key, 2 * period)
+    return {math.floor(math.max(0, limit - estimate - 1)), reset, -1}
+end
+local retry = reset
+if current + 1 <= limit and previous > 0 then
+    local at = period - (limit - current - 1) * period / previous
+    retry = math.max(1, math.ceil(at) - elapsed)
+end
+return {0, reset, retry}
+"#;
+
+/// A store holding rate limiting state in the Redis database `D`.
+///
+/// State is kept at keys made of a prefix, `ratelimit:` by default, and the
+/// request's key, and is counted atomically by Lua scripts, so the store can
+/// be shared by any number of processes. Keys expire once idle. The
+/// [`Initializer`](crate::databases::Initializer) of `D` must be attached
+/// before the [`RateLimit`](crate::ratelimit::RateLimit) fairing.
+///
+/// This type is only available when the `redis_pool` feature is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "redis_pool")] mod test {
+/// use rocket_contrib::databases::{Database, redis};
+/// use rocket_contrib::ratelimit::{RateLimit, RedisStore};
+///
+/// #[derive(Database)]
+/// #[database("redis")]
+/// struct Redis(redis::aio::ConnectionManager);
+///
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .attach(Redis::init())
+///         .attach(RateLimit::new().store(RedisStore::<Redis>::new()))
+/// }
+/// # }
+/// ```
+pub struct RedisStore<D> {
+    connection: Option<ConnectionManager>,
+    prefix: String,
+    _database: PhantomData<fn() -> D>,
+}
+
+impl<D: Database<Pool = ConnectionManager>> RedisStore<D> {
+    /// Returns a store using the default key prefix, `ratelimit:`.
+    pub fn new() -> Self {
+        RedisStore { connection: None, prefix: "ratelimit:".into(), _database: PhantomData }
+    }
+
+    /// Sets the prefix of the keys holding rate limiting state to `prefix`.
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+}
+
+impl<D: Database<Pool = ConnectionManager>> Default for RedisStore<D> {
+    fn default() -> Self {
+        RedisStore::new()
+    }
+}
+
+#[rocket::async_trait]
+impl<D: Database<Pool = ConnectionManager>> RateLimitStore for RedisStore<D> {
+    async fn init(&mut self, cargo: &Cargo) -> Result<(), String> {
+        match D::fetch(cargo) {
+            Some(db) => {
+                self.connection = Some((**db).clone());
+                Ok(())
+            }
+            None => Err(format!("database `{}` is not initialized", D::NAME)),
+        }
+    }
+
+    async fn acquire(&self, key: &str, quota: &Quota) -> Result<Decision, RateLimitError> {
+        let mut connection = self.connection.clone().ok_or(RateLimitError::Unmanaged)?;
+        let script = match quota.algorithm {
+            Algorithm::TokenBucket => TOKEN_BUCKET,
+            Algorithm::SlidingWindow => SLIDING_WINDOW,
+        };
+
+        let (remaining, reset, retry): (u64, u64, i64) = redis::Script::new(script)
+            .key(format!("{}{}", self.prefix, key))
+            .arg(quota.limit)
+            .arg(quota.period_ms())
+            .invoke_async(&mut connection)
+            .await
+            .map_err(RateLimitError::store)?;
+
+        Ok(Decision {
+            allowed: retry < 0,
+            limit: quota.limit,
+            remaining,
+            reset: Duration::from_millis(reset),
+            retry_after: match retry < 0 {
+                true => None,
+                false => Some(Duration::from_millis(retry as u64)),
+            },
+        })
+    }
+}
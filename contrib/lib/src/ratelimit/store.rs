@@ -0,0 +1,207 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocket::Cargo;
+
+use crate::ratelimit::quota::{self, Quota, Algorithm, Decision, Bucket, Window};
+
+/// How often, in milliseconds, a [`MemoryStore`] deletes idle keys.
+const SWEEP_INTERVAL: u64 = 60 * 1000;
+
+/// Trait implemented by stores of rate limiting state.
+///
+/// A store counts requests against quotas, atomically with respect to other
+/// requests counted under the same key, including those counted by other
+/// processes if the store is shared. The following stores are provided:
+///
+///   * [`MemoryStore`]: state held in memory, for applications running as a
+///     single process. This is the default.
+///   * `RedisStore`: state in a Redis database, shared between processes.
+///     Requires the `redis_pool` feature.
+///
+/// Stores are passed to [`RateLimit::store()`](crate::ratelimit::RateLimit::store()).
+#[rocket::async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Prepares the store when the [`RateLimit`](crate::ratelimit::RateLimit)
+    /// fairing is attached, typically by retrieving a database pool from the
+    /// managed state of `cargo`. Returns a description of the problem if the
+    /// store can't be used, which aborts launch. The default implementation
+    /// does nothing.
+    async fn init(&mut self, cargo: &Cargo) -> Result<(), String> {
+        let _ = cargo;
+        Ok(())
+    }
+
+    /// Counts a request under `key` against `quota`, returning whether it is
+    /// allowed. Requests that aren't allowed aren't counted.
+    async fn acquire(&self, key: &str, quota: &Quota) -> Result<Decision, RateLimitError>;
+}
+
+/// An error returned by a [`RateLimitStore`] or the
+/// [`RateLimited`](crate::ratelimit::RateLimited) request guard.
+#[derive(Debug)]
+pub enum RateLimitError {
+    /// The request exceeds its quota.
+    Exceeded(Decision),
+    /// The store failed.
+    Store(Box<dyn std::error::Error + Send + Sync>),
+    /// The [`RateLimit`](crate::ratelimit::RateLimit) fairing isn't attached.
+    Unmanaged,
+}
+
+impl RateLimitError {
+    /// Returns a [`RateLimitError::Store`] wrapping `error`.
+    pub fn store<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> Self {
+        RateLimitError::Store(error.into())
+    }
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::Exceeded(d) => write!(f, "rate limit of {} exceeded", d.limit),
+            RateLimitError::Store(e) => write!(f, "rate limit store error: {}", e),
+            RateLimitError::Unmanaged => write!(f, "the `RateLimit` fairing is not attached"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RateLimitError::Store(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+/// The state of a key in a [`MemoryStore`].
+#[derive(Debug)]
+enum State {
+    Bucket(Bucket),
+    Window(Window),
+}
+
+/// A store holding rate limiting state in memory.
+///
+/// State is lost when the application exits and isn't shared between
+/// processes. Keys that have been idle for long enough to be fully
+/// replenished are deleted periodically.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Each key's state and the time, in milliseconds, after which it's idle.
+    keys: HashMap<String, (State, u64)>,
+    last_sweep: u64,
+}
+
+impl MemoryStore {
+    /// Returns an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Counts a request under `key` at `now`, in milliseconds since the Unix
+    /// epoch.
+    fn acquire_at(&self, key: &str, quota: &Quota, now: u64) -> Decision {
+        let mut inner = self.inner.lock().expect("rate limit store lock");
+        if now.saturating_sub(inner.last_sweep) >= SWEEP_INTERVAL {
+            inner.keys.retain(|_, (_, idle_at)| *idle_at > now);
+            inner.last_sweep = now;
+        }
+
+        let state = inner.keys.remove(key).map(|(state, _)| state);
+        let (decision, state) = match quota.algorithm {
+            Algorithm::TokenBucket => {
+                let bucket = match state { Some(State::Bucket(b)) => Some(b), _ => None };
+                let (decision, bucket) = quota::token_bucket(quota, bucket, now);
+                (decision, State::Bucket(bucket))
+            }
+            Algorithm::SlidingWindow => {
+                let window = match state { Some(State::Window(w)) => Some(w), _ => None };
+                let (decision, window) = quota::sliding_window(quota, window, now);
+                (decision, State::Window(window))
+            }
+        };
+
+        let idle_at = now + 2 * quota.period_ms();
+        inner.keys.insert(key.to_string(), (state, idle_at));
+        decision
+    }
+}
+
+#[rocket::async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn acquire(&self, key: &str, quota: &Quota) -> Result<Decision, RateLimitError> {
+        Ok(self.acquire_at(key, quota, unix_millis()))
+    }
+}
+
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn token_bucket_allows_bursts_and_refills() {
+        let store = MemoryStore::new();
+        let quota = Quota::new(3, Duration::from_secs(3));
+        for remaining in (0..3).rev() {
+            let decision = store.acquire_at("k", &quota, 10_000);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+        }
+
+        let denied = store.acquire_at("k", &quota, 10_000);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Some(Duration::from_secs(1)));
+        assert_eq!(denied.reset, Duration::from_secs(3));
+
+        assert!(!store.acquire_at("k", &quota, 10_500).allowed);
+        assert!(store.acquire_at("k", &quota, 11_000).allowed);
+        assert!(store.acquire_at("other", &quota, 11_000).allowed);
+    }
+
+    #[test]
+    fn sliding_window_weighs_previous_window() {
+        let store = MemoryStore::new();
+        let quota = Quota::new(4, Duration::from_secs(10)).sliding_window();
+        for _ in 0..4 {
+            assert!(store.acquire_at("k", &quota, 5_000).allowed);
+        }
+
+        let denied = store.acquire_at("k", &quota, 9_000);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after, Some(Duration::from_secs(1)));
+
+        // Halfway through the next window, the previous counts for 2.
+        let decision = store.acquire_at("k", &quota, 15_000);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 1);
+        assert!(store.acquire_at("k", &quota, 15_000).allowed);
+        assert!(!store.acquire_at("k", &quota, 15_000).allowed);
+
+        // Two windows later, nothing counts.
+        assert_eq!(store.acquire_at("k", &quota, 30_000).remaining, 3);
+    }
+
+    #[test]
+    fn idle_keys_are_swept() {
+        let store = MemoryStore::new();
+        let quota = Quota::per_second(1);
+        store.acquire_at("a", &quota, 1_000);
+        store.acquire_at("b", &quota, 1_000 + SWEEP_INTERVAL);
+        assert_eq!(store.inner.lock().unwrap().keys.len(), 1);
+    }
+}
@@ -0,0 +1,22 @@
+use std::time::{Duration, Instant};
+
+use rocket::Request;
+
+/// The instant at which a request was received, kept in request-local state.
+///
+/// Fairings that time requests share it, so all of them measure from the
+/// instant recorded by whichever of them saw the request first.
+pub(crate) struct Received(Instant);
+
+impl Received {
+    /// Records the current instant as the time `req` was received, unless a
+    /// time was recorded already.
+    pub(crate) fn record(req: &Request<'_>) {
+        req.local_cache(|| Received(Instant::now()));
+    }
+
+    /// Returns the time elapsed since `req` was received.
+    pub(crate) fn elapsed(req: &Request<'_>) -> Duration {
+        req.local_cache(|| Received(Instant::now())).0.elapsed()
+    }
+}
@@ -0,0 +1,252 @@
+//! Request ID generation and propagation.
+//!
+//! See the [`RequestId`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `request_id` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["request_id"]
+//! ```
+
+use std::fmt;
+use std::borrow::Cow;
+use std::ops::Deref;
+
+use rocket::{Config, Request, Response, Rocket, Data};
+use rocket::config::ConfigError;
+use rocket::fairing::{AdHoc, Fairing, Info, Kind, Order};
+use rocket::request::{self, FromRequest};
+use rocket::http::Header;
+
+use ulid::Ulid;
+
+/// The header from which request IDs are read and to which they are written
+/// unless otherwise configured.
+const DEFAULT_HEADER: &str = "X-Request-Id";
+
+/// The maximum length of an incoming request ID that is honored.
+const MAX_LEN: usize = 128;
+
+/// A unique identifier for a request.
+///
+/// A `RequestId` identifies a request across the services that handle it. If
+/// the incoming request carries an ID in the `X-Request-Id` header, that ID is
+/// used; otherwise, a new [ULID] is generated. Incoming IDs that are empty,
+/// longer than 128 bytes, or contain characters other than visible ASCII are
+/// replaced with a generated ID.
+///
+/// [ULID]: https://github.com/ulid/spec
+///
+/// # Fairing
+///
+/// The fairing returned by [`RequestId::fairing()`] assigns every request an
+/// ID before any other fairing runs, logs it, and echoes it in the response.
+/// The header can be changed via the `header` key of the `request_id`
+/// configuration table:
+///
+/// ```toml
+/// [global.request_id]
+/// header = "X-Correlation-Id"
+/// ```
+///
+/// # Request Guard
+///
+/// `RequestId` is a request guard that never fails. Use it to pass the ID on
+/// to other services or to include it in logs and responses. If the fairing
+/// isn't attached, the guard reads the `X-Request-Id` header or generates an
+/// ID on first use; the same ID is returned for the remainder of the request.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::request_id::RequestId;
+///
+/// #[get("/")]
+/// fn index(id: &RequestId) -> String {
+///     format!("handling request {}", id)
+/// }
+///
+/// fn main() {
+///     rocket::ignite()
+///         .mount("/", routes![index])
+///         .attach(RequestId::fairing())
+///         # ;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Returns a fairing that assigns, logs, and echoes request IDs, using the
+    /// header configured in the `request_id` table or `X-Request-Id`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::request_id::RequestId;
+    ///
+    /// rocket::ignite().attach(RequestId::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> impl Fairing {
+        AdHoc::on_attach("Request ID Configuration", |mut rocket: Rocket| async move {
+            let header = match configured_header(rocket.config().await) {
+                Ok(header) => header,
+                Err(e) => {
+                    e.pretty_print();
+                    error_!("Invalid request ID configuration.");
+                    return Err(rocket);
+                }
+            };
+
+            Ok(rocket.attach(RequestIdFairing { header: header.into() }))
+        })
+    }
+
+    /// Returns a fairing that assigns, logs, and echoes request IDs using the
+    /// header `header`, ignoring any configured header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::request_id::RequestId;
+    ///
+    /// rocket::ignite().attach(RequestId::fairing_with_header("X-Correlation-Id"))
+    /// # ;
+    /// ```
+    pub fn fairing_with_header<H: Into<Cow<'static, str>>>(header: H) -> impl Fairing {
+        RequestIdFairing { header: header.into() }
+    }
+
+    /// Returns a new, randomly generated request ID.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::request_id::RequestId;
+    ///
+    /// let id = RequestId::generate();
+    /// assert_eq!(id.as_str().len(), 26);
+    /// assert_ne!(id, RequestId::generate());
+    /// ```
+    pub fn generate() -> RequestId {
+        RequestId(Ulid::new().to_string())
+    }
+
+    /// Returns the ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the ID of `req`, assigning one from the header `header` or a
+    /// generated one if the request doesn't have one yet.
+    fn of<'r>(req: &'r Request<'_>, header: &str) -> &'r RequestId {
+        req.local_cache(|| {
+            req.headers().get_one(header)
+                .filter(|id| is_valid(id))
+                .map(|id| RequestId(id.to_string()))
+                .unwrap_or_else(RequestId::generate)
+        })
+    }
+}
+
+/// Reads the request ID header from the `request_id` table in `config`.
+fn configured_header(config: &Config) -> Result<String, ConfigError> {
+    let table = match config.get_table("request_id") {
+        Ok(table) => table,
+        Err(ConfigError::Missing(_)) => return Ok(DEFAULT_HEADER.to_string()),
+        Err(e) => return Err(e),
+    };
+
+    match table.get("header") {
+        Some(value) => value.as_str().map(String::from).ok_or_else(|| {
+            let name = "request_id.header".to_string();
+            ConfigError::BadType(name, "a string", value.type_str(), None)
+        }),
+        None => Ok(DEFAULT_HEADER.to_string()),
+    }
+}
+
+/// Returns `true` if `id` is a request ID that can be safely logged and
+/// propagated.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+impl Deref for RequestId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for &'a RequestId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(RequestId::of(req, DEFAULT_HEADER))
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for RequestId {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(RequestId::of(req, DEFAULT_HEADER).clone())
+    }
+}
+
+/// The fairing returned by [`RequestId::fairing()`].
+struct RequestIdFairing {
+    header: Cow<'static, str>,
+}
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Request IDs are assigned before any other fairing runs so that all of
+    /// them can use the ID.
+    fn order(&self) -> Order {
+        Order::priority(i32::max_value())
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        let id = RequestId::of(req, &self.header);
+        info_!("Request ID: {}", id);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !res.headers().contains(&*self.header) {
+            let id = RequestId::of(req, &self.header);
+            res.set_header(Header::new(self.header.clone(), id.to_string()));
+        }
+    }
+}
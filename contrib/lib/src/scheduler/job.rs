@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rocket::{Cargo, Orbit, Shutdown};
+use rocket::futures::future::BoxFuture;
+use rocket::futures::{FutureExt, StreamExt};
+use rocket::futures::stream::FuturesUnordered;
+use rocket::tokio::{self, time::{delay_until, Instant}};
+
+use crate::scheduler::Schedule;
+
+/// What to do when a [`Job`] is due while a previous run is still in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Skip the run. This is the default.
+    Skip,
+    /// Start the run as soon as the previous one finishes. At most one run is
+    /// queued; further runs due in the meantime are skipped.
+    Queue,
+    /// Start the run alongside the previous one.
+    Concurrent,
+}
+
+type Run = Arc<dyn Fn(JobContext) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// A job run on a [`Schedule`] by the [`Scheduler`](crate::scheduler::Scheduler).
+///
+/// A job is a name, a schedule, an [`Overlap`] policy, and a function that is
+/// called with a [`JobContext`] each time the job runs and returns a future
+/// that is spawned on Rocket's runtime. The name identifies the job in logs
+/// and in configuration.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_contrib::scheduler::{Job, Overlap};
+///
+/// let job = Job::cron("report", "0 6 * * mon", |ctx| async move {
+///     println!("Running '{}'.", ctx.name());
+/// }).overlap(Overlap::Queue);
+/// ```
+#[derive(Clone)]
+pub struct Job {
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) schedule: Schedule,
+    pub(crate) overlap: Overlap,
+    run: Run,
+}
+
+impl Job {
+    /// Returns a job named `name` that runs `f` on `schedule`, skipping runs
+    /// that overlap.
+    pub fn new<N, F, Fut>(name: N, schedule: Schedule, f: F) -> Job
+        where N: Into<Cow<'static, str>>,
+              F: Fn(JobContext) -> Fut + Send + Sync + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        Job {
+            name: name.into(),
+            schedule,
+            overlap: Overlap::Skip,
+            run: Arc::new(move |ctx| f(ctx).boxed()),
+        }
+    }
+
+    /// Returns a job named `name` that runs `f` on the cron expression
+    /// `expression`, skipping runs that overlap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `expression` is invalid. Use [`Job::new()`] with
+    /// [`Schedule::cron()`] to handle the error instead.
+    pub fn cron<N, F, Fut>(name: N, expression: &str, f: F) -> Job
+        where N: Into<Cow<'static, str>>,
+              F: Fn(JobContext) -> Fut + Send + Sync + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        match Schedule::cron(expression) {
+            Ok(schedule) => Job::new(name, schedule, f),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Sets what to do when the job is due while a previous run is still in
+    /// progress.
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Runs the job on its schedule until a shutdown begins, then waits for
+    /// the runs in progress to finish.
+    pub(crate) async fn drive(self, orbit: Orbit) {
+        let name: Arc<str> = Arc::from(&*self.name);
+        let shutdown = orbit.shutdown();
+        let mut runs = FuturesUnordered::new();
+        let mut queued = None;
+        let mut next = self.schedule.next_after(SystemTime::now());
+
+        let spawn = |scheduled| {
+            let ctx = JobContext { name: name.clone(), orbit: orbit.clone(), scheduled };
+            tokio::spawn((self.run)(ctx))
+        };
+
+        loop {
+            let due = next.map(|time: SystemTime| {
+                let wait = time.duration_since(SystemTime::now()).unwrap_or_default();
+                delay_until(Instant::now() + wait)
+            });
+
+            tokio::select! {
+                _ = shutdown.clone() => break,
+                Some(result) = runs.next(), if !runs.is_empty() => {
+                    if let Err(e) = result {
+                        error_!("Scheduled job '{}' failed: {}", name, e);
+                    }
+
+                    if let Some(scheduled) = queued.take() {
+                        runs.push(spawn(scheduled));
+                    }
+                }
+                _ = async { due.expect("job is due").await }, if next.is_some() => {
+                    let scheduled = next.expect("job is due");
+                    next = self.schedule.next_after(SystemTime::now().max(scheduled));
+                    match self.overlap {
+                        _ if runs.is_empty() => runs.push(spawn(scheduled)),
+                        Overlap::Concurrent => runs.push(spawn(scheduled)),
+                        Overlap::Queue if queued.is_none() => queued = Some(scheduled),
+                        _ => info_!("Skipping scheduled job '{}': still running.", name),
+                    }
+                }
+            }
+        }
+
+        while let Some(result) = runs.next().await {
+            if let Err(e) = result {
+                error_!("Scheduled job '{}' failed: {}", name, e);
+            }
+        }
+    }
+}
+
+/// The context of a run of a [`Job`].
+///
+/// The context gives access to the application's managed state and
+/// configuration, and to a [`Shutdown`] handle that resolves when a shutdown
+/// begins. Long-running jobs should watch it and finish early, as Rocket waits
+/// for runs in progress before shutting down.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use rocket_contrib::scheduler::JobContext;
+///
+/// struct Visits(AtomicUsize);
+///
+/// async fn reset_visits(ctx: JobContext) {
+///     if let Some(visits) = ctx.state::<Visits>() {
+///         visits.0.store(0, Ordering::Relaxed);
+///     }
+/// }
+/// ```
+pub struct JobContext {
+    name: Arc<str>,
+    orbit: Orbit,
+    scheduled: SystemTime,
+}
+
+impl JobContext {
+    /// Returns the name of the job.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the time the run was scheduled for. The run may start later if
+    /// it was queued.
+    pub fn scheduled_at(&self) -> SystemTime {
+        self.scheduled
+    }
+
+    /// Returns the managed state of type `T`, if any.
+    pub fn state<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.orbit.state()
+    }
+
+    /// Returns the launched application.
+    pub fn cargo(&self) -> &Cargo {
+        &self.orbit
+    }
+
+    /// Returns a handle that resolves when a shutdown begins.
+    pub fn shutdown(&self) -> Shutdown {
+        self.orbit.shutdown()
+    }
+}
@@ -0,0 +1,238 @@
+//! Scheduled jobs run on Rocket's runtime.
+//!
+//! See the [`Scheduler`] fairing and the [`Job`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `scheduler` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["scheduler"]
+//! ```
+//!
+//! # Lifecycle
+//!
+//! Jobs are registered with the [`Scheduler`] fairing when the application is
+//! built. Once the application launches, each job runs on its [`Schedule`] in
+//! a task spawned on Rocket's runtime, with access to managed state via its
+//! [`JobContext`]. When a shutdown begins, no further runs are started, and
+//! Rocket waits for the runs in progress to finish before running shutdown
+//! fairings. Jobs don't run for a [local `Client`](rocket::local).
+//!
+//! If a job is due while a previous run is still in progress, its [`Overlap`]
+//! policy decides whether the run is skipped, queued, or started alongside.
+//!
+//! # Configuration
+//!
+//! Jobs can be rescheduled or disabled in the `scheduler` table in
+//! `Rocket.toml`, by name:
+//!
+//! ```toml
+//! [global.scheduler.cleanup]
+//! schedule = "0 */2 * * *"
+//! overlap = "queue"
+//!
+//! [global.scheduler.report]
+//! enabled = false
+//! ```
+//!
+//! Every key is optional. `schedule` is a cron expression, as described in
+//! [`Schedule`], and `overlap` is one of `"skip"`, `"queue"`, or
+//! `"concurrent"`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use rocket_contrib::scheduler::{Scheduler, Job};
+//!
+//! struct Sessions(AtomicUsize);
+//!
+//! fn main() {
+//!     let cleanup = Job::cron("cleanup", "*/5 * * * *", |ctx| async move {
+//!         let sessions = ctx.state::<Sessions>().expect("managed");
+//!         sessions.0.store(0, Ordering::Relaxed);
+//!     });
+//!
+//!     rocket::ignite()
+//!         .manage(Sessions(AtomicUsize::new(0)))
+//!         .attach(Scheduler::new().job(cleanup))
+//! #       ;
+//! }
+//! ```
+
+use rocket::{Config, Rocket};
+use rocket::config::{ConfigError, Value};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::futures::future::join_all;
+
+mod schedule;
+mod job;
+
+pub use self::schedule::{Schedule, ScheduleError};
+pub use self::job::{Job, JobContext, Overlap};
+
+/// The fairing that runs scheduled [`Job`]s.
+///
+/// On attach, the fairing reads the `scheduler` configuration table, as
+/// described in the [module documentation](crate::scheduler), and registers a
+/// background task, via [`Rocket::attach_orbit_task()`], that runs the enabled
+/// jobs once the application launches. If the configuration is invalid, an
+/// error is logged and launch is aborted.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use std::time::Duration;
+/// use rocket_contrib::scheduler::{Scheduler, Schedule, Job, Overlap};
+///
+/// let heartbeat = Job::new("heartbeat", Schedule::every(Duration::from_secs(30)), |_| async {
+///     println!("Still alive.");
+/// });
+///
+/// let sync = Job::cron("sync", "0 * * * *", |ctx| async move {
+///     rocket::tokio::select! {
+///         _ = rocket::tokio::time::delay_for(Duration::from_secs(600)) => {},
+///         _ = ctx.shutdown() => println!("Sync interrupted by shutdown."),
+///     }
+/// }).overlap(Overlap::Queue);
+///
+/// rocket::ignite().attach(Scheduler::new().job(heartbeat).job(sync))
+/// # ;
+/// ```
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    /// Returns a scheduler with no jobs.
+    pub fn new() -> Scheduler {
+        Scheduler::default()
+    }
+
+    /// Registers `job`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a job with the same name is already registered.
+    pub fn job(mut self, job: Job) -> Self {
+        if self.jobs.iter().any(|j| j.name == job.name) {
+            panic!("a scheduled job named '{}' is already registered", job.name);
+        }
+
+        self.jobs.push(job);
+        self
+    }
+
+    /// Returns the jobs to run, as configured in the `scheduler` table of
+    /// `config`.
+    fn configured_jobs(&self, config: &Config) -> Result<Vec<Job>, ConfigError> {
+        let table = match config.get_table("scheduler") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(self.jobs.clone()),
+            Err(e) => return Err(e),
+        };
+
+        for name in table.keys() {
+            if !self.jobs.iter().any(|job| job.name == name.as_str()) {
+                warn_!("Ignoring configuration of unknown scheduled job '{}'.", name);
+            }
+        }
+
+        let mut jobs = vec![];
+        for mut job in self.jobs.iter().cloned() {
+            let settings = match table.get(&*job.name) {
+                Some(value) => value.as_table().ok_or_else(|| bad_type(&job.name, "", value))?,
+                None => {
+                    jobs.push(job);
+                    continue;
+                }
+            };
+
+            let mut enabled = true;
+            for (key, value) in settings {
+                match key.as_str() {
+                    "enabled" => {
+                        enabled = value.as_bool().ok_or_else(|| bad_type(&job.name, key, value))?;
+                    }
+                    "schedule" => {
+                        let expression = value.as_str()
+                            .ok_or_else(|| bad_type(&job.name, key, value))?;
+
+                        job.schedule = Schedule::cron(expression).map_err(|e| {
+                            error_!("{}", e);
+                            bad_type(&job.name, key, value)
+                        })?;
+                    }
+                    "overlap" => {
+                        job.overlap = match value.as_str() {
+                            Some("skip") => Overlap::Skip,
+                            Some("queue") => Overlap::Queue,
+                            Some("concurrent") => Overlap::Concurrent,
+                            _ => return Err(bad_type(&job.name, key, value)),
+                        };
+                    }
+                    _ => {
+                        warn_!("Ignoring unknown scheduler configuration key '{}.{}'.",
+                            job.name, key);
+                    }
+                }
+            }
+
+            if enabled {
+                jobs.push(job);
+            }
+        }
+
+        Ok(jobs)
+    }
+}
+
+fn bad_type(job: &str, key: &str, value: &Value) -> ConfigError {
+    let (name, expected) = match key {
+        "" => (format!("scheduler.{}", job), "a table"),
+        "enabled" => (format!("scheduler.{}.{}", job, key), "a boolean"),
+        "schedule" => (format!("scheduler.{}.{}", job, key), "a cron expression"),
+        _ => (format!("scheduler.{}.{}", job, key), "\"skip\", \"queue\", or \"concurrent\""),
+    };
+
+    ConfigError::BadType(name, expected, value.type_str(), None)
+}
+
+#[rocket::async_trait]
+impl Fairing for Scheduler {
+    fn info(&self) -> Info {
+        Info {
+            name: "Scheduler",
+            kind: Kind::Attach,
+        }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let jobs = match self.configured_jobs(rocket.config().await) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid scheduler configuration.");
+                return Err(rocket);
+            }
+        };
+
+        for job in &jobs {
+            info_!("Scheduled job '{}': {}", job.name, job.schedule);
+        }
+
+        Ok(rocket.attach_orbit_task(move |orbit| async move {
+            join_all(jobs.into_iter().map(|job| job.drive(orbit.clone()))).await;
+        }))
+    }
+}
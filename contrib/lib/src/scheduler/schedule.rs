@@ -0,0 +1,384 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How far ahead, in years, to search for the next match of a cron
+/// expression before concluding that it never matches, as `0 0 30 2 *` does.
+const SEARCH_YEARS: i64 = 8;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"
+];
+
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// When a [`Job`](crate::scheduler::Job) runs.
+///
+/// A schedule is either a cron expression, matched against the current time
+/// in UTC, or a fixed interval.
+///
+/// # Cron Expressions
+///
+/// A cron expression has five fields, `minute hour day month weekday`, or six,
+/// with a leading `second` field. Each field is `*`, a value, a range `a-b`,
+/// or a comma-separated list of these, each optionally followed by a step
+/// `/n`. Months and weekdays may be given by their three-letter English
+/// names, and Sunday is both `0` and `7`. As in traditional cron, a time
+/// matches if the day of the month _or_ the day of the week matches when both
+/// fields are restricted.
+///
+/// The shorthands `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`,
+/// `@midnight`, and `@hourly` are also accepted.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket_contrib::scheduler::Schedule;
+///
+/// // At 03:30 every weekday.
+/// let nightly = Schedule::cron("30 3 * * mon-fri").unwrap();
+///
+/// // Every 15 seconds.
+/// let frequent: Schedule = "*/15 * * * * *".parse().unwrap();
+///
+/// // Every 10 minutes after the application launches.
+/// let interval = Schedule::every(Duration::from_secs(600));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule(Kind);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Cron(Cron),
+    Every(Duration),
+}
+
+/// The error returned when a cron expression is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleError {
+    expression: String,
+    reason: String,
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid cron expression '{}': {}", self.expression, self.reason)
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// A parsed cron expression: the set of values each field matches, as bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cron {
+    expression: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether both the day and weekday fields are restricted, in which case
+    /// a time matches if either does.
+    either_day: bool,
+}
+
+/// A cron field: its name, bounds, and value names, if any.
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const SECOND: Field = Field { name: "second", min: 0, max: 59, names: &[] };
+const MINUTE: Field = Field { name: "minute", min: 0, max: 59, names: &[] };
+const HOUR: Field = Field { name: "hour", min: 0, max: 23, names: &[] };
+const DAY: Field = Field { name: "day", min: 1, max: 31, names: &[] };
+const MONTH: Field = Field { name: "month", min: 1, max: 12, names: &MONTHS };
+const WEEKDAY: Field = Field { name: "weekday", min: 0, max: 7, names: &WEEKDAYS };
+
+impl Schedule {
+    /// Parses the cron expression `expression`.
+    pub fn cron(expression: &str) -> Result<Schedule, ScheduleError> {
+        let error = |reason: String| ScheduleError { expression: expression.into(), reason };
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (second, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => return Err(error(format!("expected 5 or 6 fields, found {}", n))),
+        };
+
+        let parse = |field: &Field, value: &str| parse_field(field, value).map_err(error);
+        let mut weekdays = parse(&WEEKDAY, rest[4])?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Schedule(Kind::Cron(Cron {
+            expression: expression.trim().into(),
+            seconds: parse(&SECOND, second)?,
+            minutes: parse(&MINUTE, rest[0])?,
+            hours: parse(&HOUR, rest[1])?,
+            days: parse(&DAY, rest[2])?,
+            months: parse(&MONTH, rest[3])?,
+            weekdays,
+            either_day: !rest[2].starts_with('*') && !rest[4].starts_with('*'),
+        })))
+    }
+
+    /// Returns a schedule that runs every `period`, starting `period` after
+    /// launch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn every(period: Duration) -> Schedule {
+        assert!(period > Duration::from_secs(0), "schedule period must be positive");
+        Schedule(Kind::Every(period))
+    }
+
+    /// Returns the first time after `after` at which the schedule runs, or
+    /// `None` if it never does.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use rocket_contrib::scheduler::Schedule;
+    ///
+    /// let schedule = Schedule::cron("0 12 * * *").unwrap();
+    /// let next = schedule.next_after(UNIX_EPOCH).unwrap();
+    /// assert_eq!(next, UNIX_EPOCH + Duration::from_secs(12 * 60 * 60));
+    /// ```
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        match self.0 {
+            Kind::Every(period) => Some(after + period),
+            Kind::Cron(ref cron) => {
+                let secs = match after.duration_since(UNIX_EPOCH) {
+                    Ok(elapsed) => elapsed.as_secs() as i64,
+                    Err(e) => -(e.duration().as_secs() as i64) - 1,
+                };
+
+                let next = cron.next_after(secs)?;
+                Some(match next >= 0 {
+                    true => UNIX_EPOCH + Duration::from_secs(next as u64),
+                    false => UNIX_EPOCH - Duration::from_secs(next.abs() as u64),
+                })
+            }
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(expression: &str) -> Result<Schedule, ScheduleError> {
+        Schedule::cron(expression)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Kind::Every(period) => write!(f, "every {}s", period.as_secs_f64()),
+            Kind::Cron(ref cron) => write!(f, "{}", cron.expression),
+        }
+    }
+}
+
+impl Cron {
+    /// Returns the first second after `after`, in seconds since the Unix
+    /// epoch, that matches.
+    fn next_after(&self, after: i64) -> Option<i64> {
+        let mut t = after + 1;
+        let limit = after + SEARCH_YEARS * 366 * SECONDS_PER_DAY;
+        while t <= limit {
+            let (days, secs) = (t.div_euclid(SECONDS_PER_DAY), t.rem_euclid(SECONDS_PER_DAY));
+            let (year, month, day) = civil_from_days(days);
+            let (hour, minute, second) = (secs / 3600, secs / 60 % 60, secs % 60);
+            let midnight = days * SECONDS_PER_DAY;
+
+            if !contains(self.months, month) {
+                let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                t = days_from_civil(year, month, 1) * SECONDS_PER_DAY;
+            } else if !self.matches_day(day, weekday(days)) {
+                t = midnight + SECONDS_PER_DAY;
+            } else if !contains(self.hours, hour) {
+                t = midnight + (hour + 1) * 3600;
+            } else if !contains(self.minutes, minute) {
+                t = midnight + hour * 3600 + (minute + 1) * 60;
+            } else if !contains(self.seconds, second) {
+                t += 1;
+            } else {
+                return Some(t);
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, day: i64, weekday: i64) -> bool {
+        let (day, weekday) = (contains(self.days, day), contains(self.weekdays, weekday));
+        match self.either_day {
+            true => day || weekday,
+            false => day && weekday,
+        }
+    }
+}
+
+fn contains(set: u64, value: i64) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parses the cron field `value` into the set of values it matches, or a
+/// description of why it is invalid.
+fn parse_field(field: &Field, value: &str) -> Result<u64, String> {
+    let number = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        let n = match field.names.iter().position(|name| *name == lower) {
+            Some(i) => i as u32 + if field.names.len() == 12 { 1 } else { 0 },
+            None => s.parse().map_err(|_| format!("invalid {} '{}'", field.name, s))?,
+        };
+
+        match n >= field.min && n <= field.max {
+            true => Ok(n),
+            false => Err(format!("{} {} is out of range", field.name, n)),
+        }
+    };
+
+    let mut set = 0;
+    for item in value.split(',') {
+        let mut parts = item.splitn(2, '/');
+        let range = parts.next().unwrap_or("");
+        let step = match parts.next() {
+            Some(step) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => step,
+                _ => return Err(format!("invalid {} step '{}'", field.name, step)),
+            },
+            None => 1,
+        };
+
+        let (start, end) = match range {
+            "*" => (field.min, field.max),
+            _ => match range.find('-') {
+                Some(i) => (number(&range[..i])?, number(&range[i + 1..])?),
+                None if step > 1 => (number(range)?, field.max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+
+        if start > end {
+            return Err(format!("{} range '{}' is empty", field.name, range));
+        }
+
+        for n in (start..=end).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+
+    Ok(set)
+}
+
+/// Returns the day of the week, with Sunday as 0, of the day `days` days
+/// after the Unix epoch, a Thursday.
+fn weekday(days: i64) -> i64 {
+    (days + 4).rem_euclid(7)
+}
+
+/// Returns the `(year, month, day)` of the day `days` days after the Unix
+/// epoch in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Returns the number of days after the Unix epoch of `year-month-day`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the next match of `expression` after `after`, both in seconds
+    /// since the Unix epoch.
+    fn next(expression: &str, after: i64) -> Option<i64> {
+        let schedule = Schedule::cron(expression).unwrap();
+        let after = UNIX_EPOCH + Duration::from_secs(after as u64);
+        let next = schedule.next_after(after)?;
+        Some(next.duration_since(UNIX_EPOCH).unwrap().as_secs() as i64)
+    }
+
+    /// 2021-03-01T00:00:00Z, a Monday.
+    const MARCH_1: i64 = 1_614_556_800;
+
+    #[test]
+    fn calendar_round_trips() {
+        for &days in &[-719_468, -1, 0, 11_016, 18_687, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+
+        assert_eq!(civil_from_days(MARCH_1 / SECONDS_PER_DAY), (2021, 3, 1));
+        assert_eq!(weekday(MARCH_1 / SECONDS_PER_DAY), 1);
+    }
+
+    #[test]
+    fn finds_next_match() {
+        assert_eq!(next("* * * * *", MARCH_1), Some(MARCH_1 + 60));
+        assert_eq!(next("*/15 * * * * *", MARCH_1 + 1), Some(MARCH_1 + 15));
+        assert_eq!(next("30 3 * * *", MARCH_1), Some(MARCH_1 + 3 * 3600 + 30 * 60));
+        assert_eq!(next("@hourly", MARCH_1 + 10), Some(MARCH_1 + 3600));
+        assert_eq!(next("0 0 * * sat", MARCH_1), Some(MARCH_1 + 5 * SECONDS_PER_DAY));
+        assert_eq!(next("0 0 * * 7", MARCH_1), Some(MARCH_1 + 6 * SECONDS_PER_DAY));
+        assert_eq!(next("0 0 1 apr *", MARCH_1), Some(MARCH_1 + 31 * SECONDS_PER_DAY));
+        assert_eq!(next("0 0 29 2 *", MARCH_1), Some(1_709_164_800));
+        assert_eq!(next("0 0 30 2 *", MARCH_1), None);
+    }
+
+    #[test]
+    fn restricted_days_match_either() {
+        // The 15th, or any Wednesday: Wednesday the 3rd comes first.
+        let wednesday = MARCH_1 + 2 * SECONDS_PER_DAY;
+        assert_eq!(next("0 0 15 * wed", MARCH_1), Some(wednesday));
+        assert_eq!(next("0 0 * * wed", MARCH_1), Some(wednesday));
+        assert_eq!(next("0 0 15 * *", MARCH_1), Some(MARCH_1 + 14 * SECONDS_PER_DAY));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in &["", "* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *",
+                            "* * * 13 *", "* * * * 8", "*/0 * * * *", "5-1 * * * *",
+                            "* * * foo *", "* * * * * * *"] {
+            assert!(Schedule::cron(expression).is_err(), "{}", expression);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::UNIX_EPOCH;
+
+use rocket::Request;
+use rocket::response::Response;
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::serve::vfs::Metadata;
+
+/// The format of an HTTP-date, as in `Sun, 06 Nov 1994 08:49:37 GMT`.
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// The validators of a served file: its entity tag and modification time.
+pub(crate) struct Validators {
+    etag: Option<String>,
+    last_modified: Option<i64>,
+}
+
+impl Validators {
+    /// Computes the validators of the file with `metadata`. Unless the file
+    /// system provides one, the entity tag is derived from the file's size and
+    /// modification time.
+    pub(crate) fn new(metadata: &Metadata) -> Validators {
+        let modified = metadata.modified
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+
+        let etag = metadata.etag.clone().or_else(|| modified.map(|m| {
+            format!("\"{:x}-{:x}.{:x}\"", metadata.len, m.as_secs(), m.subsec_nanos())
+        }));
+
+        Validators { etag, last_modified: modified.map(|m| m.as_secs() as i64) }
+    }
+
+    /// Returns `true` if the client's cached copy of the file is current
+    /// according to the `If-None-Match` or, in its absence, the
+    /// `If-Modified-Since` header of `req`.
+    pub(crate) fn not_modified(&self, req: &Request<'_>) -> bool {
+        if let Some(header) = req.headers().get_one("If-None-Match") {
+            let etag = match &self.etag {
+                Some(etag) => etag,
+                None => return false,
+            };
+
+            return header.trim() == "*" || header.split(',')
+                .map(|tag| tag.trim())
+                .any(|tag| tag.trim_start_matches("W/") == etag);
+        }
+
+        match (req.headers().get_one("If-Modified-Since"), self.last_modified) {
+            (Some(date), Some(modified)) => parse_http_date(date).map_or(false, |d| modified <= d),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if a `Range` header in `req` should be honored: if there
+    /// is no `If-Range` header or if it matches the current validators.
+    pub(crate) fn range_applies(&self, req: &Request<'_>) -> bool {
+        let header = match req.headers().get_one("If-Range") {
+            Some(header) => header.trim(),
+            None => return true,
+        };
+
+        if header.starts_with('"') {
+            self.etag.as_deref() == Some(header)
+        } else {
+            self.last_modified.is_some() && parse_http_date(header) == self.last_modified
+        }
+    }
+
+    /// Sets the `ETag` and `Last-Modified` headers of `response`.
+    pub(crate) fn set_headers(&self, response: &mut Response<'_>) {
+        if let Some(etag) = &self.etag {
+            response.set_raw_header("ETag", etag.clone());
+        }
+
+        if let Some(modified) = self.last_modified {
+            response.set_raw_header("Last-Modified", format_http_date(modified));
+        }
+    }
+}
+
+/// Formats `secs` since the Unix epoch as an HTTP-date.
+pub(crate) fn format_http_date(secs: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(secs).format(HTTP_DATE)
+}
+
+/// Parses an HTTP-date into seconds since the Unix epoch.
+pub(crate) fn parse_http_date(date: &str) -> Option<i64> {
+    PrimitiveDateTime::parse(date.trim(), HTTP_DATE).ok()
+        .map(|date| date.assume_utc().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_http_date, format_http_date};
+
+    #[test]
+    fn test_http_date() {
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784111777));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("yesterday"), None);
+
+        assert_eq!(format_http_date(784111777), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(format_http_date(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+    }
+}
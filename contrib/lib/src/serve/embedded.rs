@@ -0,0 +1,80 @@
+use std::io::{self, Cursor};
+
+use crate::serve::vfs::{self, Vfs, Metadata, DirEntry, VfsFile, not_found};
+
+/// Static files embedded into the application at build time.
+///
+/// A value of this type is created by the [`embed_files!`] macro, which embeds
+/// every file in a directory, including dotfiles, and is served with
+/// [`StaticFiles::embedded()`]. As a [`Vfs`], it can also be served with
+/// [`StaticFiles::with_vfs()`]. Embedded files are served exactly as the
+/// files in the directory would be by [`StaticFiles::new()`], but the
+/// directory need not exist at run time. Embedded files have no modification
+/// time; their `ETag` is derived from their contents when they're embedded.
+///
+/// [`embed_files!`]: crate::serve::embed_files!
+/// [`StaticFiles::embedded()`]: crate::serve::StaticFiles::embedded()
+/// [`StaticFiles::new()`]: crate::serve::StaticFiles::new()
+/// [`StaticFiles::with_vfs()`]: crate::serve::StaticFiles::with_vfs()
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedFiles {
+    /// The path, entity tag, and contents of each file, sorted by path.
+    files: &'static [(&'static str, &'static str, &'static [u8])],
+}
+
+impl EmbeddedFiles {
+    #[doc(hidden)]
+    pub const fn new(files: &'static [(&'static str, &'static str, &'static [u8])]) -> Self {
+        EmbeddedFiles { files }
+    }
+
+    /// Returns the paths, relative to the embedded directory, of the embedded
+    /// files.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::serve::embed_files;
+    ///
+    /// let files = embed_files!("tests/static");
+    /// assert!(files.paths().any(|path| path == "inner/goodbye"));
+    /// ```
+    pub fn paths(&self) -> impl Iterator<Item = &'static str> {
+        self.files.iter().map(|&(path, ..)| path)
+    }
+
+    /// Returns the metadata and contents of the file at `path`, if one was
+    /// embedded.
+    fn get(&self, path: &str) -> Option<(Metadata, &'static [u8])> {
+        let i = self.files.binary_search_by(|&(p, ..)| p.cmp(path)).ok()?;
+        let (_, etag, contents) = self.files[i];
+        Some((Metadata::file(contents.len() as u64).etag(etag), contents))
+    }
+}
+
+#[rocket::async_trait]
+impl Vfs for EmbeddedFiles {
+    async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+        match self.get(path) {
+            Some((metadata, _)) => Ok(metadata),
+            None if vfs::is_dir(path, self.paths()) => Ok(Metadata::dir()),
+            None => Err(not_found()),
+        }
+    }
+
+    async fn open(&self, path: &str) -> io::Result<VfsFile> {
+        let (metadata, contents) = self.get(path).ok_or_else(not_found)?;
+        Ok(VfsFile::new(Cursor::new(contents), metadata))
+    }
+
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        if !vfs::is_dir(path, self.paths()) {
+            return Err(not_found());
+        }
+
+        let files = self.files.iter()
+            .map(|&(path, _, contents)| (path, Metadata::file(contents.len() as u64)));
+
+        Ok(vfs::list_dir(path, files))
+    }
+}
@@ -0,0 +1,209 @@
+/// A glob pattern matched against the paths of served files, relative to the
+/// root of a [`StaticFiles`](crate::serve::StaticFiles) handler.
+///
+/// The pattern syntax is:
+///
+///   * `?` matches any single character except `/`.
+///   * `*` matches any sequence of characters except `/`.
+///   * `**` matches any sequence of characters, including `/`. `**/` also
+///     matches no directory at all.
+///   * `{a,b}` matches either of the comma-separated alternatives.
+///
+/// Any other character matches itself. Patterns without a `/` are matched
+/// against file names; others are matched against entire paths.
+#[derive(Debug, Clone)]
+pub(crate) struct Glob {
+    alternatives: Vec<Vec<Token>>,
+    name_only: bool,
+}
+
+/// An element of a compiled pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// A character matched literally.
+    Char(char),
+    /// `?`: any character but `/`.
+    Any,
+    /// `*`: any sequence of characters without a `/`.
+    Star,
+    /// `**`: any sequence of characters.
+    Globstar,
+    /// `**/`: nothing, or any sequence of characters ending in a `/`.
+    GlobstarSlash,
+}
+
+impl Token {
+    /// Returns `true` if the token can match no characters at all.
+    fn matches_empty(self) -> bool {
+        match self {
+            Token::Star | Token::Globstar | Token::GlobstarSlash => true,
+            Token::Char(_) | Token::Any => false,
+        }
+    }
+}
+
+impl Glob {
+    pub(crate) fn new(pattern: &str) -> Glob {
+        let name_only = !pattern.contains('/');
+        let alternatives = expand(pattern).iter()
+            .map(|alternative| compile(alternative))
+            .collect();
+
+        Glob { alternatives, name_only }
+    }
+
+    /// Returns `true` if the relative `path`, with components separated by
+    /// `/`, matches the pattern.
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        let path = match self.name_only {
+            true => path.rsplit('/').next().unwrap_or(path),
+            false => path,
+        };
+
+        self.alternatives.iter().any(|tokens| matches(tokens, path))
+    }
+}
+
+/// Expands the first brace group of `pattern`, recursively, returning every
+/// resulting pattern. Unbalanced braces are matched literally.
+fn expand(pattern: &str) -> Vec<String> {
+    let (open, close) = match (pattern.find('{'), pattern.find('}')) {
+        (Some(open), Some(close)) if open < close => (open, close),
+        _ => return vec![pattern.to_string()],
+    };
+
+    let (prefix, suffix) = (&pattern[..open], &pattern[(close + 1)..]);
+    pattern[(open + 1)..close].split(',')
+        .flat_map(|alternative| expand(&format!("{}{}{}", prefix, alternative, suffix)))
+        .collect()
+}
+
+/// Compiles a pattern without brace groups into its tokens.
+fn compile(pattern: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '?' => Token::Any,
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    Token::GlobstarSlash
+                } else {
+                    Token::Globstar
+                }
+            }
+            '*' => Token::Star,
+            c => Token::Char(c),
+        };
+
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+/// Returns `true` if `path` matches the compiled pattern `tokens`.
+///
+/// The pattern is run as an automaton with a state before each token, and
+/// one after the last, tracking every state reachable after each character of
+/// `path`. The time taken is thus proportional to the product of the lengths
+/// of the pattern and the path, however many stars the pattern contains.
+fn matches(tokens: &[Token], path: &str) -> bool {
+    let mut states = vec![false; tokens.len() + 1];
+    let mut next = states.clone();
+    states[0] = true;
+    skip_empty(tokens, &mut states);
+
+    for c in path.chars() {
+        next.iter_mut().for_each(|state| *state = false);
+        let mut alive = false;
+        for (i, &token) in tokens.iter().enumerate().filter(|&(i, _)| states[i]) {
+            let (stay, advance) = match token {
+                Token::Char(expected) => (false, c == expected),
+                Token::Any => (false, c != '/'),
+                Token::Star => (c != '/', false),
+                Token::Globstar => (true, false),
+                Token::GlobstarSlash => (true, c == '/'),
+            };
+
+            next[i] |= stay;
+            next[i + 1] |= advance;
+            alive |= stay || advance;
+        }
+
+        if !alive {
+            return false;
+        }
+
+        skip_empty(tokens, &mut next);
+        std::mem::swap(&mut states, &mut next);
+    }
+
+    states[tokens.len()]
+}
+
+/// Adds to `states` those reachable from them by matching no characters.
+fn skip_empty(tokens: &[Token], states: &mut [bool]) {
+    for (i, token) in tokens.iter().enumerate() {
+        if states[i] && token.matches_empty() {
+            states[i + 1] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Glob;
+
+    #[test]
+    fn test_glob() {
+        let css = Glob::new("*.css");
+        assert!(css.matches("style.css"));
+        assert!(css.matches("assets/style.css"));
+        assert!(!css.matches("style.css.map"));
+
+        let assets = Glob::new("*.{css,js}");
+        assert!(assets.matches("app.js"));
+        assert!(assets.matches("a/b/app.css"));
+        assert!(!assets.matches("index.html"));
+
+        let fingerprinted = Glob::new("assets/**/*.????????.js");
+        assert!(fingerprinted.matches("assets/app.0123abcd.js"));
+        assert!(fingerprinted.matches("assets/vendor/lib.0123abcd.js"));
+        assert!(!fingerprinted.matches("assets/app.js"));
+        assert!(!fingerprinted.matches("other/app.0123abcd.js"));
+
+        let inner = Glob::new("inner/*");
+        assert!(inner.matches("inner/goodbye"));
+        assert!(!inner.matches("inner/a/goodbye"));
+        assert!(!inner.matches("goodbye"));
+
+        let everything = Glob::new("**");
+        assert!(everything.matches("a/b/c"));
+        assert!(everything.matches(""));
+        assert!(Glob::new("{a").matches("{a"));
+
+        let nested = Glob::new("a/**/b/**/c.txt");
+        assert!(nested.matches("a/b/c.txt"));
+        assert!(nested.matches("a/x/y/b/z/c.txt"));
+        assert!(!nested.matches("a/x/c.txt"));
+        assert!(!nested.matches("ab/c.txt"));
+
+        let unicode = Glob::new("d?j?.txt");
+        assert!(unicode.matches("déjà.txt"));
+        assert!(!unicode.matches("d/j.txt"));
+    }
+
+    #[test]
+    fn test_glob_backtracking_is_bounded() {
+        // Exponential for a backtracking matcher.
+        let pattern = format!("{}b", "*a".repeat(16));
+        let path = "a".repeat(64);
+        assert!(!Glob::new(&pattern).matches(&path));
+        let deep = format!("{}c", "a/".repeat(32));
+        assert!(!Glob::new(&format!("{}/b", "**/a".repeat(16))).matches(&deep));
+        assert!(Glob::new(&format!("{}*", "*a".repeat(16))).matches(&path));
+    }
+}
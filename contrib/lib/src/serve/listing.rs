@@ -0,0 +1,198 @@
+use std::fmt::Write;
+use std::io::Cursor;
+use std::time::UNIX_EPOCH;
+
+use rocket::{Request, Response};
+use rocket::http::{ContentType, MediaType, RawStr, uri::Uri};
+use crate::serve::vfs::DirEntry;
+use crate::serve::conditional::format_http_date;
+
+/// A listing of the contents of a directory, rendered by the [`StaticFiles`]
+/// handler when [`Options::DirectoryListing`] is enabled.
+///
+/// [`StaticFiles`]: crate::serve::StaticFiles
+/// [`Options::DirectoryListing`]: crate::serve::Options::DirectoryListing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    /// The request path of the directory, ending with a `/`.
+    pub path: String,
+    /// Whether the directory is the root directory of the handler.
+    pub is_root: bool,
+    /// The entries of the directory: directories first, then files, each
+    /// sorted by name.
+    pub entries: Vec<ListingEntry>,
+}
+
+/// An entry of a [`Listing`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListingEntry {
+    /// The file name of the entry.
+    pub name: String,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+    /// The size of the entry in bytes.
+    pub size: u64,
+    /// The last modification time of the entry, in seconds since the Unix
+    /// epoch, if available.
+    pub modified: Option<u64>,
+}
+
+impl From<DirEntry> for ListingEntry {
+    fn from(entry: DirEntry) -> ListingEntry {
+        ListingEntry {
+            name: entry.name,
+            is_dir: entry.metadata.is_dir,
+            size: entry.metadata.len,
+            modified: entry.metadata.modified
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs()),
+        }
+    }
+}
+
+impl Listing {
+    /// Returns the listing of a directory, requested at `path`, with
+    /// `entries`.
+    pub(crate) fn new(path: &str, is_root: bool, mut entries: Vec<ListingEntry>) -> Listing {
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        let path = match path.ends_with('/') {
+            true => path.to_string(),
+            false => format!("{}/", path),
+        };
+
+        Listing { path, is_root, entries }
+    }
+
+    /// Returns a response with the listing as JSON if `req` prefers it and as
+    /// HTML otherwise.
+    pub(crate) fn respond(&self, req: &Request<'_>) -> Response<'static> {
+        let (content_type, body) = match Listing::prefers_json(req) {
+            true => (ContentType::JSON, self.to_json()),
+            false => (ContentType::HTML, self.to_html()),
+        };
+
+        Response::build()
+            .header(content_type)
+            .raw_header("Vary", "Accept")
+            .sized_body(body.len(), Cursor::new(body))
+            .finalize()
+    }
+
+    /// Returns `true` if `req` prefers a JSON response to an HTML one.
+    pub(crate) fn prefers_json(req: &Request<'_>) -> bool {
+        req.accept().map_or(false, |accept| accept.preferred().media_type() == &MediaType::JSON)
+    }
+
+    /// Returns the listing as the context of a template: an object with a
+    /// `path` string, an `is_root` boolean, and an `entries` array of objects
+    /// with `name`, `is_dir`, `size`, and `modified` fields.
+    #[cfg(feature = "templates")]
+    pub fn to_context(&self) -> serde_json::Value {
+        let entries = self.entries.iter()
+            .map(|entry| serde_json::json!({
+                "name": entry.name,
+                "is_dir": entry.is_dir,
+                "size": entry.size,
+                "modified": entry.modified,
+            }))
+            .collect::<Vec<_>>();
+
+        serde_json::json!({ "path": self.path, "is_root": self.is_root, "entries": entries })
+    }
+
+    /// Renders the listing as JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = format!("{{\"path\":{},\"entries\":[", json_string(&self.path));
+        for (i, entry) in self.entries.iter().enumerate() {
+            let modified = entry.modified.map_or("null".into(), |secs| secs.to_string());
+            let _ = write!(json, "{}{{\"name\":{},\"is_dir\":{},\"size\":{},\"modified\":{}}}",
+                if i == 0 { "" } else { "," }, json_string(&entry.name), entry.is_dir,
+                entry.size, modified);
+        }
+
+        json.push_str("]}");
+        json
+    }
+
+    /// Renders the listing as an HTML page.
+    pub fn to_html(&self) -> String {
+        let title = format!("Index of {}", RawStr::from_str(&self.path).html_escape());
+        let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n<table>\n\
+            <tr><th>Name</th><th>Size</th><th>Last Modified</th></tr>\n", title);
+
+        if !self.is_root {
+            let trimmed = self.path.trim_end_matches('/');
+            let parent = &trimmed[..trimmed.rfind('/').map_or(0, |i| i + 1)];
+            let _ = writeln!(html, "<tr><td><a href=\"{}\">../</a></td><td></td><td></td></tr>",
+                RawStr::from_str(parent).html_escape());
+        }
+
+        for entry in &self.entries {
+            let suffix = if entry.is_dir { "/" } else { "" };
+            let size = match entry.is_dir {
+                true => "-".to_string(),
+                false => entry.size.to_string(),
+            };
+
+            let modified = entry.modified.map_or(String::new(), |secs| {
+                format_http_date(secs as i64)
+            });
+
+            let href = format!("{}{}{}", self.path, Uri::percent_encode(&entry.name), suffix);
+            let _ = writeln!(html,
+                "<tr><td><a href=\"{}\">{}{}</a></td><td>{}</td><td>{}</td></tr>",
+                RawStr::from_str(&href).html_escape(), RawStr::from_str(&entry.name).html_escape(),
+                suffix, size, modified);
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Returns `string` as a JSON string literal.
+fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => { let _ = write!(json, "\\u{:04x}", c as u32); }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_string, Listing, ListingEntry};
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\u000a\"");
+    }
+
+    #[test]
+    fn test_render_html() {
+        let entries = vec![
+            ListingEntry { name: "b&c.txt".into(), is_dir: false, size: 12, modified: Some(0) },
+            ListingEntry { name: "a dir".into(), is_dir: true, size: 0, modified: None },
+        ];
+
+        let html = Listing::new("/files/sub", false, entries).to_html();
+        assert!(html.contains("<title>Index of &#x2F;files&#x2F;sub&#x2F;</title>"));
+        assert!(html.contains("<tr><td><a href=\"&#x2F;files&#x2F;\">../</a></td>\
+            <td></td><td></td></tr>"));
+        assert!(html.contains("<tr><td><a href=\"&#x2F;files&#x2F;sub&#x2F;a%20dir&#x2F;\">\
+            a dir/</a></td><td>-</td><td></td></tr>"));
+        assert!(html.contains("<tr><td><a href=\"&#x2F;files&#x2F;sub&#x2F;b%26c.txt\">\
+            b&amp;c.txt</a></td>\
+            <td>12</td><td>Thu, 01 Jan 1970 00:00:00 GMT</td></tr>"));
+        assert!(html.find("a dir").unwrap() < html.find("b&amp;c.txt").unwrap());
+    }
+}
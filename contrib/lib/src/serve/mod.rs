@@ -0,0 +1,875 @@
+//! Custom handler and options for static file serving.
+//!
+//! See the [`StaticFiles`](crate::serve::StaticFiles) type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `serve` feature is enabled. Enable it
+//! in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["serve"]
+//! ```
+
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+use rocket::{Request, Data, Route};
+use rocket::http::{Method, ContentType, Status, uri::Segments, ext::IntoOwned};
+use rocket::handler::{Handler, Outcome};
+use rocket::response::{ByteRanges, CacheControl, Cached, Redirect, Responder, Response};
+
+pub mod vfs;
+
+mod listing;
+mod conditional;
+mod embedded;
+mod glob;
+
+use self::vfs::{Vfs, VfsFile, Metadata, LocalDir};
+use self::conditional::Validators;
+use self::glob::Glob;
+
+pub use self::listing::{Listing, ListingEntry};
+pub use self::embedded::EmbeddedFiles;
+pub use rocket_contrib_codegen::embed_files;
+
+/// Generates a crate-relative version of `$path`.
+///
+/// This macro is primarily intended for use with [`StaticFiles`] to serve files
+/// from a path relative to the crate root. The macro accepts one parameter,
+/// `$path`, an absolute or relative path. It returns a path (an `&'static str`)
+/// prefixed with the path to the crate root. Use `Path::new()` to retrieve an
+/// `&'static Path`.
+///
+/// See the [relative paths `StaticFiles`
+/// documentation](`StaticFiles`#relative-paths) for an example.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_contrib::serve::{StaticFiles, crate_relative};
+///
+/// let manual = concat!(env!("CARGO_MANIFEST_DIR"), "/static");
+/// let automatic = crate_relative!("static");
+/// assert_eq!(manual, automatic);
+///
+/// use std::path::Path;
+///
+/// let manual = Path::new(env!("CARGO_MANIFEST_DIR")).join("static");
+/// let automatic_1 = Path::new(crate_relative!("static"));
+/// let automatic_2 = Path::new(crate_relative!("/static"));
+/// assert_eq!(manual, automatic_1);
+/// assert_eq!(automatic_1, automatic_2);
+/// ```
+#[macro_export]
+macro_rules! crate_relative {
+    ($path:expr) => {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)
+    };
+}
+
+#[doc(inline)]
+pub use crate_relative;
+
+/// A bitset representing configurable options for the [`StaticFiles`] handler.
+///
+/// The valid options are:
+///
+///   * [`Options::None`] - Return only present, visible files.
+///   * [`Options::DotFiles`] - In addition to visible files, return dotfiles.
+///   * [`Options::Index`] - Render `index.html` pages for directory requests.
+///   * [`Options::NormalizeDirs`] - Redirect directories without a trailing
+///     slash to ones with a trailing slash.
+///   * [`Options::IndexFallback`] - Respond to requests for missing files
+///     with the root `index.html`.
+///   * [`Options::DirectoryListing`] - Render listings of directories without
+///     an index page.
+///   * [`Options::Precompressed`] - Serve precompressed `.br` and `.gz`
+///     variants of files to clients that accept them.
+///   * [`Options::ExternalSymlinks`] - Serve files through symbolic links
+///     that point outside of the root directory.
+///   * [`Options::RequireCanonical`] - Serve files only through their
+///     canonical paths, without any symbolic links.
+///
+/// `Options` structures can be `or`d together to select two or more options.
+/// For instance, to request that both dot files and index pages be returned,
+/// use `Options::DotFiles | Options::Index`.
+///
+/// # Security
+///
+/// By default, requests can only reach files within the root directory: path
+/// segments such as `..` are rejected, and each served path is canonicalized,
+/// resolving symbolic links, and served only if the result remains within the
+/// root directory. Dotfiles, including the targets of symbolic links to
+/// dotfiles, are only served with [`Options::DotFiles`]. These checks can be
+/// relaxed with [`Options::ExternalSymlinks`] and tightened with
+/// [`Options::RequireCanonical`]. Symbolic links are specific to the local
+/// file system: other [`Vfs`]s, such as [embedded](StaticFiles::embedded())
+/// files, only serve dotfiles with [`Options::DotFiles`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options(u16);
+
+#[allow(non_upper_case_globals, non_snake_case)]
+impl Options {
+    /// `Options` representing the empty set. No dotfiles or index pages are
+    /// rendered. This is different than [`Options::default()`](#impl-Default),
+    /// which enables `Index`.
+    pub const None: Options = Options(0b0000);
+
+    /// `Options` enabling responding to requests for a directory with the
+    /// `index.html` file in that directory, if it exists. When this is enabled,
+    /// the [`StaticFiles`] handler will respond to requests for a directory
+    /// `/foo` with the file `${root}/foo/index.html` if it exists. This is
+    /// enabled by default.
+    pub const Index: Options = Options(0b0001);
+
+    /// `Options` enabling returning dot files. When this is enabled, the
+    /// [`StaticFiles`] handler will respond to requests for files or
+    /// directories beginning with `.`. This is _not_ enabled by default.
+    pub const DotFiles: Options = Options(0b0010);
+
+    /// `Options` that normalizes directory requests by redirecting requests to
+    /// directory paths without a trailing slash to ones with a trailing slash.
+    ///
+    /// When enabled, the [`StaticFiles`] handler will respond to requests for a
+    /// directory without a trailing `/` with a permanent redirect (308) to the
+    /// same path with a trailing `/`. This ensures relative URLs within any
+    /// document served for that directory will be interpreted relative to that
+    /// directory, rather than its parent. This is _not_ enabled by default.
+    pub const NormalizeDirs: Options = Options(0b0100);
+
+    /// `Options` enabling responding to requests for files that don't exist
+    /// with the `index.html` file in the root directory, as single-page
+    /// applications that route on the client require.
+    ///
+    /// When enabled, the [`StaticFiles`] handler will respond to requests for a
+    /// path `/foo/bar` that isn't otherwise served with `${root}/index.html`
+    /// and a status of `200`, if the file exists. Requests are thus only
+    /// forwarded if `${root}/index.html` doesn't exist. This is _not_ enabled
+    /// by default.
+    pub const IndexFallback: Options = Options(0b1000);
+
+    /// `Options` enabling responding to requests for a directory with a
+    /// listing of its contents.
+    ///
+    /// When enabled, the [`StaticFiles`] handler will respond to requests for a
+    /// directory that isn't served with an `index.html` file with a [`Listing`]
+    /// of the directory's entries, their sizes, and their modification times.
+    /// The listing is rendered as JSON if the request prefers
+    /// `application/json` and as HTML otherwise, or with the template set by
+    /// [`StaticFiles::listing_template()`]. Dotfiles are only listed if
+    /// [`Options::DotFiles`] is also enabled. This is _not_ enabled by default.
+    pub const DirectoryListing: Options = Options(0b10000);
+
+    /// `Options` enabling serving precompressed variants of files.
+    ///
+    /// When enabled, the [`StaticFiles`] handler will respond to requests for a
+    /// file `${root}/foo.js` from clients that accept the `br` or `gzip`
+    /// encoding with the file `${root}/foo.js.br` or `${root}/foo.js.gz`,
+    /// respectively, if it exists, preferring `br`. The response has the
+    /// `Content-Type` of the original file and a `Content-Encoding` of the
+    /// variant's encoding. Otherwise, the original file is served. Responses
+    /// for files have a `Vary: Accept-Encoding` header either way. This is
+    /// _not_ enabled by default.
+    pub const Precompressed: Options = Options(0b100000);
+
+    /// `Options` enabling serving files through symbolic links that point
+    /// outside of the root directory.
+    ///
+    /// By default, the [`StaticFiles`] handler canonicalizes the path of each
+    /// file or directory it serves and refuses, by forwarding the request, to
+    /// serve paths that resolve to a location outside of the root directory.
+    /// When enabled, this check is skipped, and symbolic links are followed
+    /// wherever they point. Enable this only when every symbolic link in the
+    /// root directory is trusted. This is _not_ enabled by default.
+    pub const ExternalSymlinks: Options = Options(0b1000000);
+
+    /// `Options` requiring that files be served only through their canonical
+    /// paths.
+    ///
+    /// When enabled, the [`StaticFiles`] handler refuses, by forwarding the
+    /// request, to serve a path that differs from its canonicalized form, and
+    /// thus any path that traverses a symbolic link, even one that points
+    /// within the root directory. This takes precedence over
+    /// [`Options::ExternalSymlinks`]. This is _not_ enabled by default.
+    pub const RequireCanonical: Options = Options(0b10000000);
+
+    /// Returns `true` if `self` is a superset of `other`. In other words,
+    /// returns `true` if all of the options in `other` are also in `self`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::serve::Options;
+    ///
+    /// let index_request = Options::Index | Options::DotFiles;
+    /// assert!(index_request.contains(Options::Index));
+    /// assert!(index_request.contains(Options::DotFiles));
+    ///
+    /// let index_only = Options::Index;
+    /// assert!(index_only.contains(Options::Index));
+    /// assert!(!index_only.contains(Options::DotFiles));
+    ///
+    /// let dot_only = Options::DotFiles;
+    /// assert!(dot_only.contains(Options::DotFiles));
+    /// assert!(!dot_only.contains(Options::Index));
+    /// ```
+    #[inline]
+    pub fn contains(self, other: Options) -> bool {
+        (other.0 & self.0) == other.0
+    }
+}
+
+/// The default set of options: `Options::Index`.
+impl Default for Options {
+    fn default() -> Self {
+        Options::Index
+    }
+}
+
+impl std::ops::BitOr for Options {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, rhs: Self) -> Self {
+        Options(self.0 | rhs.0)
+    }
+}
+
+/// Custom handler for serving static files.
+///
+/// This handler makes it simple to serve static files from a directory on the
+/// local file system or from any other [`Vfs`]. To use it, construct a
+/// `StaticFiles` using either [`StaticFiles::from()`] or [`StaticFiles::new()`]
+/// then simply `mount` the handler at a desired path. When mounted, the
+/// handler will generate route(s) that serve the desired static files. If a
+/// requested file is not found, the routes _forward_ the incoming request. The
+/// default rank of the generated routes is `10`. To customize route ranking,
+/// use the [`StaticFiles::rank()`] method.
+///
+/// Responses for files carry `ETag` and `Last-Modified` headers derived from
+/// the file's size and modification time. Requests with a matching
+/// `If-None-Match` or, in its absence, `If-Modified-Since` header are answered
+/// with `304 Not Modified`. `Range` requests are honored as by
+/// [`ByteRanges`](rocket::response::ByteRanges), unless an `If-Range` header
+/// doesn't match the file, in which case the entire file is served.
+///
+/// # Options
+///
+/// The handler's functionality can be customized by passing an [`Options`] to
+/// [`StaticFiles::new()`].
+///
+/// # Example
+///
+/// To serve files from the `/static` directory on the local file system at the
+/// `/public` path, allowing `index.html` files to be used to respond to
+/// requests for a directory (the default), you might write the following:
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::StaticFiles;
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite().mount("/public", StaticFiles::from("/static"))
+/// }
+/// ```
+///
+/// With this, requests for files at `/public/<path..>` will be handled by
+/// returning the contents of `/static/<path..>`. Requests for _directories_ at
+/// `/public/<directory>` will be handled by returning the contents of
+/// `/static/<directory>/index.html`.
+///
+/// ## Relative Paths
+///
+/// In the example above, `/static` is an absolute path. If your static files
+/// are stored relative to your crate and your project is managed by Cargo, use
+/// the [`crate_relative!`] macro to obtain a path that is relative to your
+/// crate's root. For example, to serve files in the `static` subdirectory of
+/// your crate at `/`, you might write:
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::{StaticFiles, crate_relative};
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite().mount("/", StaticFiles::from(crate_relative!("/static")))
+/// }
+/// ```
+///
+/// ## Embedding
+///
+/// Static files can instead be embedded into the application at build time
+/// with [`embed_files!`] and served with [`StaticFiles::embedded()`], for
+/// deployments consisting of a single binary. The directory passed to
+/// `embed_files!` is relative to the crate's root and need not exist at run
+/// time. Changes to embedded files cause the application to be rebuilt, but
+/// adding or removing files doesn't; touch a source file to pick up new files.
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::{StaticFiles, Options, embed_files};
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     let files = StaticFiles::embedded(embed_files!("tests/static"), Options::Index);
+///     rocket::ignite().mount("/", files)
+/// }
+/// ```
+///
+/// ## Virtual File Systems
+///
+/// Files held in memory, generated at run time, or stored remotely can be
+/// served with [`StaticFiles::with_vfs()`] from an implementation of the
+/// [`Vfs`] trait. Requests for them are routed, cached, and honored exactly as
+/// for files on the local file system. See the [`vfs`] module for the provided
+/// implementations.
+#[derive(Clone)]
+pub struct StaticFiles {
+    vfs: Arc<dyn Vfs>,
+    options: Options,
+    rank: isize,
+    cache_rules: Vec<(Glob, CacheControl)>,
+    #[cfg(feature = "templates")]
+    listing_template: Option<String>,
+}
+
+impl StaticFiles {
+    /// The default rank use by `StaticFiles` routes.
+    const DEFAULT_RANK: isize = 10;
+
+    /// Constructs a new `StaticFiles` that serves files from the file system
+    /// `path`. By default, [`Options::Index`] is set, and the generated routes
+    /// have a rank of `10`. To serve static files with other options, use
+    /// [`StaticFiles::new()`]. To choose a different rank for generated routes,
+    /// use [`StaticFiles::rank()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` does not exist or is not a directory.
+    ///
+    /// # Example
+    ///
+    /// Serve the static files in the `/www/public` local directory on path
+    /// `/static`.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::StaticFiles;
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     rocket::ignite().mount("/static", StaticFiles::from("/www/public"))
+    /// }
+    /// ```
+    ///
+    /// Exactly as before, but set the rank for generated routes to `30`.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::StaticFiles;
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     rocket::ignite().mount("/static", StaticFiles::from("/www/public").rank(30))
+    /// }
+    /// ```
+    pub fn from<P: AsRef<Path>>(path: P) -> Self {
+        StaticFiles::new(path, Options::default())
+    }
+
+    /// Constructs a new `StaticFiles` that serves files from the file system
+    /// `path` with `options` enabled. By default, the handler's routes have a
+    /// rank of `10`. To choose a different rank, use [`StaticFiles::rank()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` does not exist or is not a directory.
+    ///
+    /// # Example
+    ///
+    /// Serve the static files in the `/www/public` local directory on path
+    /// `/static` without serving index files or dot files. Additionally, serve
+    /// the same files on `/pub` with a route rank of -1 while also serving
+    /// index files and dot files.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::{StaticFiles, Options};
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     let options = Options::Index | Options::DotFiles;
+    ///     rocket::ignite()
+    ///         .mount("/static", StaticFiles::from("/www/public"))
+    ///         .mount("/pub", StaticFiles::new("/www/public", options).rank(-1))
+    /// }
+    /// ```
+    pub fn new<P: AsRef<Path>>(path: P, options: Options) -> Self {
+        use rocket::yansi::Paint;
+
+        let path = path.as_ref();
+        let dir = match path.is_dir() {
+            true => LocalDir::new(path, options).ok(),
+            false => None,
+        };
+
+        match dir {
+            Some(dir) => StaticFiles::with_vfs(dir, options),
+            None => {
+                error!("`StaticFiles` supplied with invalid path");
+                info_!("'{}' is not a directory", Paint::white(path.display()));
+                panic!("refusing to continue due to invalid static files path");
+            }
+        }
+    }
+
+    /// Constructs a new `StaticFiles` that serves the files embedded with
+    /// [`embed_files!`] with `options` enabled. The files are served exactly
+    /// as the files in the embedded directory would be by
+    /// [`StaticFiles::new()`]. By default, the handler's routes have a rank of
+    /// `10`. To choose a different rank, use [`StaticFiles::rank()`].
+    ///
+    /// # Example
+    ///
+    /// Serve the files in the `tests/static` directory of the crate, embedded
+    /// into the binary at build time, on path `/public`.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::{StaticFiles, Options, embed_files};
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     let files = StaticFiles::embedded(embed_files!("tests/static"), Options::Index);
+    ///     rocket::ignite().mount("/public", files)
+    /// }
+    /// ```
+    pub fn embedded(files: EmbeddedFiles, options: Options) -> Self {
+        StaticFiles::with_vfs(files, options)
+    }
+
+    /// Constructs a new `StaticFiles` that serves files from the virtual file
+    /// system `vfs` with `options` enabled. The files are routed, cached, and
+    /// served exactly as files on the local file system would be by
+    /// [`StaticFiles::new()`]. Policies concerning symbolic links are left to
+    /// `vfs`. By default, the handler's routes have a rank of `10`. To choose
+    /// a different rank, use [`StaticFiles::rank()`].
+    ///
+    /// # Example
+    ///
+    /// Serve files generated at run time on path `/generated`.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::{StaticFiles, Options, vfs::MemoryFs};
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     let files = MemoryFs::new();
+    ///     files.insert("robots.txt", "User-agent: *\nDisallow:\n");
+    ///     rocket::ignite().mount("/generated", StaticFiles::with_vfs(files, Options::None))
+    /// }
+    /// ```
+    pub fn with_vfs<V: Vfs>(vfs: V, options: Options) -> Self {
+        StaticFiles {
+            vfs: Arc::new(vfs),
+            options,
+            rank: Self::DEFAULT_RANK,
+            cache_rules: vec![],
+            #[cfg(feature = "templates")]
+            listing_template: None,
+        }
+    }
+
+    /// Sets the rank for generated routes to `rank`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::{StaticFiles, Options};
+    ///
+    /// // A `StaticFiles` created with `from()` with routes of rank `3`.
+    /// StaticFiles::from("/public").rank(3);
+    ///
+    /// // A `StaticFiles` created with `new()` with routes of rank `-15`.
+    /// StaticFiles::new("/public", Options::Index).rank(-15);
+    /// ```
+    pub fn rank(mut self, rank: isize) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    /// Sets the `Cache-Control` header of responses for files whose paths
+    /// match the glob `pattern` to `cache`, as with the [`Cached`] responder.
+    ///
+    /// Paths are relative to the handler's root. In `pattern`, `?` matches any
+    /// character but `/`, `*` matches any sequence of characters but `/`, `**`
+    /// matches any sequence of characters, and `{a,b}` matches either `a` or
+    /// `b`. Patterns without a `/` are matched against file names only. Rules
+    /// are tried in the order they are added, and the first match applies.
+    /// Responses for files matching no rule have no `Cache-Control` header.
+    ///
+    /// # Example
+    ///
+    /// Cache fingerprinted stylesheets and scripts for a year, but revalidate
+    /// HTML pages on every use.
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket::response::CacheControl;
+    /// use rocket_contrib::serve::StaticFiles;
+    /// use time::Duration;
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     let long_lived = CacheControl::new().public().max_age(Duration::days(365)).immutable();
+    ///     let files = StaticFiles::from("/www/public")
+    ///         .cache("*.{css,js}", long_lived)
+    ///         .cache("*.html", CacheControl::new().no_cache());
+    ///
+    ///     rocket::ignite().mount("/", files)
+    /// }
+    /// ```
+    pub fn cache(mut self, pattern: &str, cache: CacheControl) -> Self {
+        self.cache_rules.push((Glob::new(pattern), cache));
+        self
+    }
+
+    /// Renders HTML directory listings with the template `name` and enables
+    /// [`Options::DirectoryListing`]. The template is rendered with the
+    /// context returned by [`Listing::to_context()`], and the [`Template`]
+    /// fairing must be attached.
+    ///
+    /// This method is only available when the `templates` feature is enabled.
+    ///
+    /// [`Template`]: crate::templates::Template
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::serve::StaticFiles;
+    ///
+    /// // Lists directories with the `listing` template.
+    /// StaticFiles::from("/public").listing_template("listing");
+    /// ```
+    #[cfg(feature = "templates")]
+    pub fn listing_template<S: Into<String>>(mut self, name: S) -> Self {
+        self.options = self.options | Options::DirectoryListing;
+        self.listing_template = Some(name.into());
+        self
+    }
+}
+
+impl Into<Vec<Route>> for StaticFiles {
+    fn into(self) -> Vec<Route> {
+        let non_index = Route::ranked(self.rank, Method::Get, "/<path..>", self.clone());
+        // `Index` requires routing the index for obvious reasons.
+        // `NormalizeDirs` requires routing the index so a `.mount("/foo")` with
+        // a request `/foo`, can be redirected to `/foo/`. `IndexFallback`
+        // requires routing the index so that it can fall back.
+        // `DirectoryListing` requires routing the index to list it.
+        if self.options.contains(Options::Index)
+            || self.options.contains(Options::NormalizeDirs)
+            || self.options.contains(Options::IndexFallback)
+            || self.options.contains(Options::DirectoryListing)
+        {
+            let index = Route::ranked(self.rank, Method::Get, "/", self);
+            vec![index, non_index]
+        } else {
+            vec![non_index]
+        }
+    }
+}
+
+impl StaticFiles {
+    async fn handle_dir<'r>(&self, r: &'r Request<'_>, d: Data, p: &str) -> Outcome<'r> {
+        if self.options.contains(Options::NormalizeDirs) && !r.uri().path().ends_with('/') {
+            let new_path = r.uri().map_path(|p| p.to_owned() + "/")
+                .expect("adding a trailing slash to a known good path results in a valid path")
+                .into_owned();
+
+            return Outcome::from_or_forward(r, d, Redirect::permanent(new_path));
+        }
+
+        if self.options.contains(Options::Index) {
+            if let Some(response) = self.respond_with_path(r, &join(p, "index.html")).await {
+                return Outcome::from(r, response);
+            }
+        }
+
+        if !self.options.contains(Options::DirectoryListing) {
+            return Outcome::forward(d);
+        }
+
+        let entries = match self.vfs.read_dir(p).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn_!("Failed to list directory '{}': {}", p, e);
+                return Outcome::forward(d);
+            }
+        };
+
+        let dotfiles = self.options.contains(Options::DotFiles);
+        let entries = entries.into_iter()
+            .filter(|entry| dotfiles || !entry.name.starts_with('.'))
+            .map(ListingEntry::from)
+            .collect();
+
+        let listing = Listing::new(r.uri().path(), p.is_empty(), entries);
+
+        #[cfg(feature = "templates")]
+        {
+            if let Some(name) = &self.listing_template {
+                if !Listing::prefers_json(r) {
+                    let template = crate::templates::Template::render(name.clone(),
+                        listing.to_context());
+
+                    return Outcome::from(r, template);
+                }
+            }
+        }
+
+        Outcome::from(r, listing.respond(r))
+    }
+
+    /// Returns a response with the file at `path`, whose metadata is
+    /// `metadata`, or one of its precompressed variants if enabled and
+    /// accepted, or `None` if it can't be opened. The response honors
+    /// conditional and `Range` requests; files are only opened if the client's
+    /// cached copy, if any, isn't current.
+    async fn respond_with_file(
+        &self,
+        req: &Request<'_>,
+        path: &str,
+        metadata: Metadata
+    ) -> Option<Response<'static>> {
+        if metadata.is_dir {
+            return None;
+        }
+
+        let mut variant = (path.to_string(), metadata, None);
+        if self.options.contains(Options::Precompressed) {
+            if let Some(precompressed) = self.precompressed(req, path).await {
+                variant = precompressed;
+            }
+        }
+
+        let (source, metadata, encoding) = variant;
+        let content_type = Path::new(path).extension()
+            .and_then(|ext| ContentType::from_extension(&ext.to_string_lossy()));
+
+        let validators = Validators::new(&metadata);
+        let (validators, mut response) = match validators.not_modified(req) {
+            true => (validators, Response::build().status(Status::NotModified).finalize()),
+            false => {
+                // The file may have changed since its metadata was read.
+                let file = self.vfs.open(&source).await.ok()?;
+                let validators = Validators::new(&file.metadata);
+                let ranges = validators.range_applies(req);
+                (validators, respond(req, file, content_type, ranges)?)
+            }
+        };
+
+        if let Some(encoding) = encoding {
+            response.set_raw_header("Content-Encoding", encoding);
+        }
+
+        if let Some((_, cache)) = self.cache_rules.iter().find(|(glob, _)| glob.matches(path)) {
+            response = Cached(cache.clone(), response).respond_to(req).ok()?;
+        }
+
+        if self.options.contains(Options::Precompressed) {
+            response.set_raw_header("Vary", "Accept-Encoding");
+        }
+
+        validators.set_headers(&mut response);
+        Some(response)
+    }
+
+    /// Like [`StaticFiles::respond_with_file()`], reading the metadata of the
+    /// file at `path` first.
+    async fn respond_with_path(&self, req: &Request<'_>, path: &str) -> Option<Response<'static>> {
+        let metadata = self.vfs.metadata(path).await.ok()?;
+        self.respond_with_file(req, path, metadata).await
+    }
+
+    /// Returns the path and metadata of the precompressed variant of the file
+    /// at `path` with the most preferred encoding accepted by `req`, if any
+    /// exists, along with its encoding.
+    async fn precompressed(
+        &self,
+        req: &Request<'_>,
+        path: &str
+    ) -> Option<(String, Metadata, Option<&'static str>)> {
+        for (encoding, ext) in accepted_encodings(req) {
+            let variant = format!("{}.{}", path, ext);
+            match self.vfs.metadata(&variant).await {
+                Ok(metadata) if !metadata.is_dir => {
+                    return Some((variant, metadata, Some(encoding)));
+                }
+                _ => continue,
+            }
+        }
+
+        None
+    }
+
+    async fn serve<'r>(&self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        // If this is not the route with segments, handle it only if the user
+        // requested a handling of index files.
+        let current_route = req.route().expect("route while handling");
+        let is_segments_route = current_route.uri.path().ends_with(">");
+        if !is_segments_route {
+            return self.handle_dir(req, data, "").await;
+        }
+
+        // Otherwise, we're handling segments. Get the segments as a path
+        // relative to the root, only allowing dotfiles if the user allowed it.
+        let allow_dotfiles = self.options.contains(Options::DotFiles);
+        let path = req.get_segments::<Segments<'_>>(0)
+            .and_then(|res| res.ok())
+            .and_then(|segments| segments.into_path_buf(allow_dotfiles).ok())
+            .and_then(|path| normalize(&path));
+
+        let path = match path {
+            Some(path) => path,
+            None => return Outcome::forward(data),
+        };
+
+        let metadata = match self.vfs.metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(_) => return Outcome::forward(data),
+        };
+
+        if metadata.is_dir {
+            return self.handle_dir(req, data, &path).await;
+        }
+
+        Outcome::from_or_forward(req, data, self.respond_with_file(req, &path, metadata).await)
+    }
+}
+
+/// Returns a response with the contents of `file` and `content_type`. If
+/// `ranges` is `true`, `Range` requests are honored.
+fn respond(
+    req: &Request<'_>,
+    file: VfsFile,
+    content_type: Option<ContentType>,
+    ranges: bool
+) -> Option<Response<'static>> {
+    let len = file.metadata.len;
+    if ranges {
+        let mut ranges = ByteRanges::new(file.body, len);
+        if let Some(content_type) = content_type {
+            ranges = ranges.content_type(content_type);
+        }
+
+        return ranges.respond_to(req).ok();
+    }
+
+    let mut builder = Response::build();
+    if let Some(content_type) = content_type {
+        builder.header(content_type);
+    }
+
+    builder.raw_header("Accept-Ranges", "bytes").sized_body(len as usize, file.body);
+    Some(builder.finalize())
+}
+
+/// Returns the relative `path` with its components joined by `/`, as paths
+/// are passed to a [`Vfs`]. Returns `None` if `path` has a component other
+/// than a normal one.
+fn normalize(path: &Path) -> Option<String> {
+    let mut normalized = String::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => {
+                if !normalized.is_empty() {
+                    normalized.push('/');
+                }
+
+                normalized.push_str(part.to_str()?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(normalized)
+}
+
+/// Returns the path of the entry `name` in the directory `dir`.
+fn join(dir: &str, name: &str) -> String {
+    match dir.is_empty() {
+        true => name.to_string(),
+        false => format!("{}/{}", dir, name),
+    }
+}
+
+/// Returns the `(encoding, extension)` pairs of the precompressed variants
+/// accepted by the `Accept-Encoding` header of `req`, most preferred first.
+///
+/// Codings not listed by name take the quality of `*`, if present. Codings
+/// with a quality of zero are never accepted. On ties, `br` is preferred.
+fn accepted_encodings(req: &Request<'_>) -> Vec<(&'static str, &'static str)> {
+    let (mut br, mut gzip, mut any) = (None, None, None);
+    for item in req.headers().get("Accept-Encoding").flat_map(|value| value.split(',')) {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or("").trim();
+        let quality = params.map(|param| param.trim())
+            .find_map(|param| param.strip_prefix("q=").or_else(|| param.strip_prefix("Q=")))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok());
+
+        let quality = match quality {
+            Some(quality) => quality,
+            None => continue,
+        };
+
+        if coding.eq_ignore_ascii_case("br") {
+            br = Some(quality);
+        } else if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+            gzip = Some(quality);
+        } else if coding == "*" {
+            any = Some(quality);
+        }
+    }
+
+    let mut accepted: Vec<_> = vec![
+        (br.or(any).unwrap_or(0.0), ("br", "br")),
+        (gzip.or(any).unwrap_or(0.0), ("gzip", "gz")),
+    ];
+
+    // The sort is stable, so `br` stays ahead of `gzip` on equal quality.
+    accepted.retain(|&(quality, _)| quality > 0.0);
+    accepted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    accepted.into_iter().map(|(_, variant)| variant).collect()
+}
+
+#[rocket::async_trait]
+impl Handler for StaticFiles {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        match self.serve(req, data).await {
+            Outcome::Forward(data) if self.options.contains(Options::IndexFallback) => {
+                let index = self.respond_with_path(req, "index.html").await;
+                Outcome::from_or_forward(req, data, index)
+            }
+            outcome => outcome,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rocket::tokio::fs::{self, File};
+
+use crate::serve::Options;
+use crate::serve::vfs::{Vfs, Metadata, DirEntry, VfsFile, not_found};
+
+/// A directory on the local file system.
+///
+/// This is the file system served by
+/// [`StaticFiles::new()`](crate::serve::StaticFiles::new()). Paths are
+/// canonicalized before they are accessed and are only served if they comply
+/// with the policies of the [`Options`] the directory is created with:
+/// [`Options::DotFiles`], [`Options::ExternalSymlinks`], and
+/// [`Options::RequireCanonical`]. See the [`Options`] documentation for
+/// details.
+#[derive(Debug, Clone)]
+pub struct LocalDir {
+    root: PathBuf,
+    options: Options,
+}
+
+impl LocalDir {
+    /// Returns the directory at `root`, enforcing the policies in `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `root` can't be canonicalized, for instance because
+    /// it doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::serve::{Options, crate_relative, vfs::LocalDir};
+    ///
+    /// let dir = LocalDir::new(crate_relative!("tests/static"), Options::None);
+    /// assert!(dir.is_ok());
+    ///
+    /// let dir = LocalDir::new(crate_relative!("tests/missing"), Options::None);
+    /// assert!(dir.is_err());
+    /// ```
+    pub fn new<P: AsRef<Path>>(root: P, options: Options) -> io::Result<LocalDir> {
+        Ok(LocalDir { root: root.as_ref().canonicalize()?, options })
+    }
+
+    /// Returns the path of the entry at `path` on the local file system if it
+    /// may be served.
+    async fn resolve(&self, path: &str) -> io::Result<PathBuf> {
+        let full = self.root.join(path);
+        let require_canonical = self.options.contains(Options::RequireCanonical);
+        if self.options.contains(Options::ExternalSymlinks) && !require_canonical {
+            return Ok(full);
+        }
+
+        let canonical = fs::canonicalize(&full).await?;
+        if require_canonical && canonical != full {
+            return Err(not_found());
+        }
+
+        let relative = canonical.strip_prefix(&self.root).map_err(|_| not_found())?;
+        let hidden = relative.components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+
+        if hidden && !self.options.contains(Options::DotFiles) {
+            return Err(not_found());
+        }
+
+        Ok(canonical)
+    }
+}
+
+#[rocket::async_trait]
+impl Vfs for LocalDir {
+    async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+        Ok(fs::metadata(self.resolve(path).await?).await?.into())
+    }
+
+    async fn open(&self, path: &str) -> io::Result<VfsFile> {
+        let file = File::open(self.resolve(path).await?).await?;
+        let metadata: Metadata = file.metadata().await?.into();
+        if metadata.is_dir {
+            return Err(not_found());
+        }
+
+        Ok(VfsFile::new(file, metadata))
+    }
+
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        let mut entries = vec![];
+        let mut read_dir = fs::read_dir(self.resolve(path).await?).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            if let Ok(name) = entry.file_name().into_string() {
+                entries.push(DirEntry::new(name, entry.metadata().await?.into()));
+            }
+        }
+
+        Ok(entries)
+    }
+}
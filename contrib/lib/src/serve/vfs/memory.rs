@@ -0,0 +1,106 @@
+use std::io::{self, Cursor};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use std::collections::BTreeMap;
+
+use crate::serve::vfs::{self, Vfs, Metadata, DirEntry, VfsFile, not_found};
+
+/// A file system holding files in memory.
+///
+/// Files can be inserted and removed at any time, including while the file
+/// system is being served: a `MemoryFs` is a handle to shared state, and all
+/// of its clones refer to the same files. Each file's modification time is the
+/// time it was inserted. Directories exist implicitly, as long as they contain
+/// a file.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::{StaticFiles, Options, vfs::MemoryFs};
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     let files = MemoryFs::new();
+///     files.insert("index.html", "<h1>Hello!</h1>");
+///     files.insert("reports/latest.csv", "day,visits\nmonday,10\n");
+///
+///     rocket::ignite()
+///         .manage(files.clone())
+///         .mount("/", StaticFiles::with_vfs(files, Options::Index))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    files: Arc<RwLock<BTreeMap<String, MemoryFile>>>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    contents: Arc<[u8]>,
+    modified: SystemTime,
+}
+
+impl MemoryFs {
+    /// Returns an empty file system.
+    pub fn new() -> MemoryFs {
+        MemoryFs::default()
+    }
+
+    /// Inserts a file at `path` with `contents`, replacing any existing file
+    /// at `path`. Leading and trailing slashes in `path` are ignored.
+    pub fn insert<P: AsRef<str>, C: Into<Vec<u8>>>(&self, path: P, contents: C) {
+        self.insert_modified(path.as_ref(), contents.into(), SystemTime::now());
+    }
+
+    /// Removes the file at `path`. Returns `true` if there was one.
+    pub fn remove<P: AsRef<str>>(&self, path: P) -> bool {
+        let path = path.as_ref().trim_matches('/');
+        self.files.write().expect("memory fs lock").remove(path).is_some()
+    }
+
+    /// Returns the paths of the files in the file system, in order.
+    pub fn paths(&self) -> Vec<String> {
+        self.files.read().expect("memory fs lock").keys().cloned().collect()
+    }
+
+    pub(crate) fn insert_modified(&self, path: &str, contents: Vec<u8>, modified: SystemTime) {
+        let file = MemoryFile { contents: contents.into(), modified };
+        let path = path.trim_matches('/').to_string();
+        self.files.write().expect("memory fs lock").insert(path, file);
+    }
+}
+
+impl MemoryFile {
+    fn metadata(&self) -> Metadata {
+        Metadata::file(self.contents.len() as u64).modified(self.modified)
+    }
+}
+
+#[rocket::async_trait]
+impl Vfs for MemoryFs {
+    async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+        let files = self.files.read().expect("memory fs lock");
+        match files.get(path) {
+            Some(file) => Ok(file.metadata()),
+            None if vfs::is_dir(path, files.keys().map(|k| k.as_str())) => Ok(Metadata::dir()),
+            None => Err(not_found()),
+        }
+    }
+
+    async fn open(&self, path: &str) -> io::Result<VfsFile> {
+        let files = self.files.read().expect("memory fs lock");
+        let file = files.get(path).ok_or_else(not_found)?;
+        Ok(VfsFile::new(Cursor::new(file.contents.clone()), file.metadata()))
+    }
+
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        let files = self.files.read().expect("memory fs lock");
+        if !vfs::is_dir(path, files.keys().map(|k| k.as_str())) {
+            return Err(not_found());
+        }
+
+        Ok(vfs::list_dir(path, files.iter().map(|(path, file)| (path.as_str(), file.metadata()))))
+    }
+}
@@ -0,0 +1,246 @@
+//! Virtual file systems served by [`StaticFiles`](crate::serve::StaticFiles).
+//!
+//! A [`StaticFiles`](crate::serve::StaticFiles) handler reads the files it
+//! serves from a [`Vfs`]. Requests are routed, and responses are cached,
+//! negotiated, and split into ranges, in the same way whatever the `Vfs`. The
+//! following implementations are provided:
+//!
+//!   * [`LocalDir`]: a directory on the local file system, as used by
+//!     [`StaticFiles::new()`](crate::serve::StaticFiles::new()).
+//!   * [`EmbeddedFiles`](crate::serve::EmbeddedFiles): files embedded into
+//!     the application at build time.
+//!   * [`MemoryFs`]: files held in memory, which can be added and removed at
+//!     run time, for generated content.
+//!   * `ZipFs`: the files in a zip archive. Requires the `serve_zip` feature.
+//!   * `S3Fs`: the objects in an Amazon S3 bucket. Requires the `serve_s3`
+//!     feature.
+//!
+//! Other sources of files can be served by implementing [`Vfs`] and passing
+//! the implementation to
+//! [`StaticFiles::with_vfs()`](crate::serve::StaticFiles::with_vfs()).
+
+use std::io;
+use std::time::SystemTime;
+
+use rocket::tokio::io::{AsyncRead, AsyncSeek};
+
+mod local;
+mod memory;
+
+#[cfg(feature = "serve_zip")] mod zip;
+#[cfg(feature = "serve_s3")] mod s3;
+
+pub use self::local::LocalDir;
+pub use self::memory::MemoryFs;
+
+#[cfg(feature = "serve_zip")] pub use self::zip::ZipFs;
+#[cfg(feature = "serve_s3")] pub use self::s3::S3Fs;
+
+/// Trait implemented by virtual file systems served by
+/// [`StaticFiles`](crate::serve::StaticFiles).
+///
+/// Paths passed to the methods of this trait are relative to the root of the
+/// file system, and their components are separated by `/`. They never begin
+/// or end with a `/` and never contain `.` or `..` components. The root is
+/// the empty path. A directory need not exist as such: it is sufficient for a
+/// file system to report that a path is a directory in [`Vfs::metadata()`]
+/// and to list its entries in [`Vfs::read_dir()`].
+///
+/// Methods return an error of kind [`io::ErrorKind::NotFound`] for paths
+/// that don't exist or mustn't be served. Requests for such paths are
+/// forwarded.
+///
+/// Conditional requests are answered from [`Vfs::metadata()`] alone: a file
+/// is only opened when its contents are sent, so the metadata should be
+/// cheap to retrieve and include the file's entity tag if it has one.
+///
+/// # Example
+///
+/// A file system that serves a single generated file, `/now.txt`:
+///
+/// ```rust
+/// use std::io::{self, Cursor};
+/// use std::time::{SystemTime, UNIX_EPOCH};
+///
+/// use rocket_contrib::serve::vfs::{Vfs, Metadata, DirEntry, VfsFile};
+///
+/// struct Clock;
+///
+/// fn now() -> Vec<u8> {
+///     let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+///     now.as_secs().to_string().into_bytes()
+/// }
+///
+/// #[rocket::async_trait]
+/// impl Vfs for Clock {
+///     async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+///         match path {
+///             "" => Ok(Metadata::dir()),
+///             "now.txt" => Ok(Metadata::file(now().len() as u64)),
+///             _ => Err(io::ErrorKind::NotFound.into()),
+///         }
+///     }
+///
+///     async fn open(&self, path: &str) -> io::Result<VfsFile> {
+///         match path {
+///             "now.txt" => {
+///                 let now = now();
+///                 let metadata = Metadata::file(now.len() as u64);
+///                 Ok(VfsFile::new(Cursor::new(now), metadata))
+///             }
+///             _ => Err(io::ErrorKind::NotFound.into()),
+///         }
+///     }
+///
+///     async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+///         match path {
+///             "" => Ok(vec![DirEntry::new("now.txt", self.metadata("now.txt").await?)]),
+///             _ => Err(io::ErrorKind::NotFound.into()),
+///         }
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait Vfs: Send + Sync + 'static {
+    /// Returns the metadata of the file or directory at `path`.
+    async fn metadata(&self, path: &str) -> io::Result<Metadata>;
+
+    /// Opens the file at `path` for reading.
+    async fn open(&self, path: &str) -> io::Result<VfsFile>;
+
+    /// Returns the entries of the directory at `path`, in any order.
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>>;
+}
+
+/// The metadata of a file or directory in a [`Vfs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+    /// The size of the file in bytes, or `0` for directories.
+    pub len: u64,
+    /// The time of the last modification of the entry, if known.
+    pub modified: Option<SystemTime>,
+    /// An entity tag identifying the file's contents, including the quotes,
+    /// if known. When `None`, an entity tag is derived from the file's size
+    /// and modification time.
+    pub etag: Option<String>,
+}
+
+impl Metadata {
+    /// Returns the metadata of a directory with no modification time.
+    pub fn dir() -> Metadata {
+        Metadata { is_dir: true, len: 0, modified: None, etag: None }
+    }
+
+    /// Returns the metadata of a file of `len` bytes with no modification
+    /// time or entity tag.
+    pub fn file(len: u64) -> Metadata {
+        Metadata { is_dir: false, len, modified: None, etag: None }
+    }
+
+    /// Sets the modification time to `modified`.
+    pub fn modified(mut self, modified: SystemTime) -> Metadata {
+        self.modified = Some(modified);
+        self
+    }
+
+    /// Sets the entity tag to `etag`, which must include the quotes.
+    pub fn etag<S: Into<String>>(mut self, etag: S) -> Metadata {
+        self.etag = Some(etag.into());
+        self
+    }
+}
+
+impl From<std::fs::Metadata> for Metadata {
+    fn from(metadata: std::fs::Metadata) -> Metadata {
+        Metadata {
+            is_dir: metadata.is_dir(),
+            len: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata.modified().ok(),
+            etag: None,
+        }
+    }
+}
+
+/// An entry of a directory in a [`Vfs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The name of the entry, without any `/`.
+    pub name: String,
+    /// The metadata of the entry.
+    pub metadata: Metadata,
+}
+
+impl DirEntry {
+    /// Returns an entry named `name` with `metadata`.
+    pub fn new<S: Into<String>>(name: S, metadata: Metadata) -> DirEntry {
+        DirEntry { name: name.into(), metadata }
+    }
+}
+
+/// A readable source of a file's contents.
+pub trait Body: AsyncRead + AsyncSeek + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncSeek + Send + Unpin> Body for T {}
+
+/// A file opened from a [`Vfs`]: its contents and metadata.
+pub struct VfsFile {
+    pub(crate) body: Box<dyn Body>,
+    pub(crate) metadata: Metadata,
+}
+
+impl VfsFile {
+    /// Returns a file reading its contents, which must be exactly
+    /// `metadata.len` bytes long, from `body`.
+    pub fn new<B: Body + 'static>(body: B, metadata: Metadata) -> VfsFile {
+        VfsFile { body: Box::new(body), metadata }
+    }
+
+    /// Returns the metadata of the file.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+/// Returns the entries of the directory `dir` in a file system containing
+/// `files`, each with its path and metadata. Files at paths within a
+/// subdirectory of `dir` give rise to an entry for the subdirectory.
+pub(crate) fn list_dir<'a, I>(dir: &str, files: I) -> Vec<DirEntry>
+    where I: Iterator<Item = (&'a str, Metadata)>
+{
+    let mut entries: Vec<DirEntry> = vec![];
+    for (path, metadata) in files {
+        let child = match dir.is_empty() {
+            true => path,
+            false => match path.strip_prefix(dir).and_then(|rest| rest.strip_prefix('/')) {
+                Some(child) => child,
+                None => continue,
+            },
+        };
+
+        let (name, metadata) = match child.find('/') {
+            Some(i) => (&child[..i], Metadata::dir()),
+            None => (child, metadata),
+        };
+
+        if !entries.iter().any(|entry| entry.name == name) {
+            entries.push(DirEntry::new(name, metadata));
+        }
+    }
+
+    entries
+}
+
+/// Returns `true` if `dir` is the root or a directory containing one of the
+/// files at `paths`.
+pub(crate) fn is_dir<'a, I: Iterator<Item = &'a str>>(dir: &str, mut paths: I) -> bool {
+    dir.is_empty() || paths.any(|path| {
+        path.strip_prefix(dir).map_or(false, |rest| rest.starts_with('/'))
+    })
+}
+
+/// Returns the error for paths that don't exist.
+pub(crate) fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "no such file or directory")
+}
@@ -0,0 +1,231 @@
+use std::io::{self, Cursor};
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rocket::tokio::io::AsyncReadExt;
+use rusoto_core::{Region, RusotoError};
+use rusoto_s3::{S3, S3Client, HeadObjectRequest, HeadObjectError, GetObjectRequest};
+use rusoto_s3::{GetObjectError, ListObjectsV2Request, ListObjectsV2Output};
+use time::PrimitiveDateTime;
+
+use crate::serve::conditional::parse_http_date;
+use crate::serve::vfs::{Vfs, Metadata, DirEntry, VfsFile, not_found};
+
+/// The objects in an Amazon S3 bucket.
+///
+/// Objects are served by their keys, relative to an optional
+/// [prefix](S3Fs::prefix()). Directories are the common prefixes of keys
+/// ending in `/`, as in the S3 console. Each file's entity tag and
+/// modification time are those reported by S3, so conditional requests are
+/// answered without downloading the object.
+///
+/// Objects are downloaded in full for each response, including responses to
+/// `Range` requests, and buffered in memory. Serve large objects directly from
+/// S3 or through a CDN instead.
+///
+/// This type is only available when the `serve_s3` feature is enabled.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::{StaticFiles, Options, vfs::S3Fs};
+/// use rusoto_core::Region;
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     let assets = S3Fs::new(Region::EuWest1, "my-bucket").prefix("assets");
+///     rocket::ignite().mount("/assets", StaticFiles::with_vfs(assets, Options::None))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct S3Fs {
+    client: S3Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3Fs {
+    /// Returns the file system of the objects in `bucket` in `region`,
+    /// accessed with credentials from the environment.
+    pub fn new<B: Into<String>>(region: Region, bucket: B) -> S3Fs {
+        S3Fs::with_client(S3Client::new(region), bucket)
+    }
+
+    /// Returns the file system of the objects in `bucket`, accessed with
+    /// `client`.
+    pub fn with_client<B: Into<String>>(client: S3Client, bucket: B) -> S3Fs {
+        S3Fs { client, bucket: bucket.into(), prefix: String::new() }
+    }
+
+    /// Serves only the objects whose keys begin with the directory `prefix`,
+    /// by the remainder of their keys.
+    pub fn prefix<P: AsRef<str>>(mut self, prefix: P) -> S3Fs {
+        let prefix = prefix.as_ref().trim_matches('/');
+        self.prefix = match prefix.is_empty() {
+            true => String::new(),
+            false => format!("{}/", prefix),
+        };
+
+        self
+    }
+
+    /// Returns the key of the object at `path`.
+    fn key(&self, path: &str) -> String {
+        format!("{}{}", self.prefix, path)
+    }
+
+    /// Returns the prefix of the keys of the objects in the directory `path`.
+    fn dir_prefix(&self, path: &str) -> String {
+        match path.is_empty() {
+            true => self.prefix.clone(),
+            false => format!("{}{}/", self.prefix, path),
+        }
+    }
+
+    /// Lists one page of the objects and common prefixes in the directory
+    /// `path`, continuing from `token`.
+    async fn list(
+        &self,
+        path: &str,
+        token: Option<String>,
+        max_keys: Option<i64>
+    ) -> io::Result<ListObjectsV2Output> {
+        let request = ListObjectsV2Request {
+            bucket: self.bucket.clone(),
+            prefix: Some(self.dir_prefix(path)),
+            delimiter: Some("/".into()),
+            continuation_token: token,
+            max_keys,
+            ..Default::default()
+        };
+
+        self.client.list_objects_v2(request).await.map_err(io_error)
+    }
+}
+
+#[rocket::async_trait]
+impl Vfs for S3Fs {
+    async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+        if path.is_empty() {
+            return Ok(Metadata::dir());
+        }
+
+        let request = HeadObjectRequest {
+            bucket: self.bucket.clone(),
+            key: self.key(path),
+            ..Default::default()
+        };
+
+        match self.client.head_object(request).await {
+            Ok(head) => return Ok(metadata(head.content_length, head.last_modified, head.e_tag)),
+            Err(RusotoError::Service(HeadObjectError::NoSuchKey(_))) => {}
+            Err(e) => match io_error(e) {
+                e if e.kind() == io::ErrorKind::NotFound => {}
+                e => return Err(e),
+            },
+        }
+
+        // There's no object at `path`, but it may be a common prefix.
+        match self.list(path, None, Some(1)).await?.key_count.unwrap_or(0) > 0 {
+            true => Ok(Metadata::dir()),
+            false => Err(not_found()),
+        }
+    }
+
+    async fn open(&self, path: &str) -> io::Result<VfsFile> {
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            key: self.key(path),
+            ..Default::default()
+        };
+
+        let object = self.client.get_object(request).await.map_err(|e| match e {
+            RusotoError::Service(GetObjectError::NoSuchKey(_)) => not_found(),
+            e => io_error(e),
+        })?;
+
+        let mut contents = vec![];
+        if let Some(body) = object.body {
+            body.into_async_read().read_to_end(&mut contents).await?;
+        }
+
+        let metadata = metadata(Some(contents.len() as i64), object.last_modified, object.e_tag);
+        Ok(VfsFile::new(Cursor::new(contents), metadata))
+    }
+
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        let dir_prefix = self.dir_prefix(path);
+        let (mut entries, mut token) = (vec![], None);
+        loop {
+            let page = self.list(path, token, None).await?;
+            for prefix in page.common_prefixes.unwrap_or_default() {
+                let name = prefix.prefix.as_deref()
+                    .and_then(|prefix| prefix.strip_prefix(&dir_prefix))
+                    .map(|name| name.trim_end_matches('/'));
+
+                if let Some(name) = name.filter(|name| !name.is_empty()) {
+                    entries.push(DirEntry::new(name, Metadata::dir()));
+                }
+            }
+
+            for object in page.contents.unwrap_or_default() {
+                let name = object.key.as_deref().and_then(|key| key.strip_prefix(&dir_prefix));
+                if let Some(name) = name.filter(|name| !name.is_empty()) {
+                    let modified = object.last_modified.as_deref().and_then(parse_iso_date);
+                    let mut metadata = Metadata::file(object.size.unwrap_or(0) as u64);
+                    metadata.modified = modified;
+                    metadata.etag = object.e_tag;
+                    entries.push(DirEntry::new(name, metadata));
+                }
+            }
+
+            token = page.next_continuation_token;
+            if !page.is_truncated.unwrap_or(false) || token.is_none() {
+                break;
+            }
+        }
+
+        if entries.is_empty() && !path.is_empty() {
+            return Err(not_found());
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Returns the metadata of an object with the `Content-Length`,
+/// `Last-Modified`, and `ETag` reported by S3.
+fn metadata(len: Option<i64>, modified: Option<String>, etag: Option<String>) -> Metadata {
+    let mut metadata = Metadata::file(len.unwrap_or(0) as u64);
+    metadata.modified = modified.as_deref()
+        .and_then(parse_http_date)
+        .and_then(system_time);
+
+    metadata.etag = etag;
+    metadata
+}
+
+/// Parses the ISO 8601 date of an object in a listing, as in
+/// `2009-10-12T17:50:30.000Z`.
+fn parse_iso_date(date: &str) -> Option<SystemTime> {
+    let date = PrimitiveDateTime::parse(date.get(..19)?, "%Y-%m-%dT%H:%M:%S").ok()?;
+    system_time(date.assume_utc().timestamp())
+}
+
+fn system_time(secs: i64) -> Option<SystemTime> {
+    match secs >= 0 {
+        true => Some(UNIX_EPOCH + Duration::from_secs(secs as u64)),
+        false => None,
+    }
+}
+
+/// Converts an S3 error to an I/O error, of kind `NotFound` if S3 responded
+/// with a `404`.
+fn io_error<E: Error + Send + Sync + 'static>(error: RusotoError<E>) -> io::Error {
+    match error {
+        RusotoError::Unknown(ref response) if response.status.as_u16() == 404 => not_found(),
+        error => io::Error::new(io::ErrorKind::Other, error),
+    }
+}
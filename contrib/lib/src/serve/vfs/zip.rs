@@ -0,0 +1,107 @@
+use std::io::{self, Read, Seek};
+use std::fs::File;
+use std::path::Path;
+use std::time::SystemTime;
+
+use ::zip::{ZipArchive, DateTime};
+use time::Date;
+
+use crate::serve::vfs::{Vfs, Metadata, DirEntry, VfsFile, MemoryFs};
+
+/// The most memory reserved for a file before its contents are read.
+const PREALLOCATE_LIMIT: u64 = 1 << 20;
+
+/// The files in a zip archive.
+///
+/// The archive is read and its files decompressed into memory when the
+/// `ZipFs` is created; requests are then served without touching the archive.
+/// Entries with absolute paths or with `.` or `..` components are ignored.
+/// Each file's modification time is the one recorded in the archive,
+/// interpreted as UTC.
+///
+/// This type is only available when the `serve_zip` feature is enabled.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::serve::{StaticFiles, Options, vfs::ZipFs};
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     let site = ZipFs::open("site.zip").expect("readable site archive");
+///     rocket::ignite().mount("/", StaticFiles::with_vfs(site, Options::Index))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZipFs(MemoryFs);
+
+impl ZipFs {
+    /// Reads the zip archive at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't a valid archive.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<ZipFs> {
+        ZipFs::from_reader(File::open(path)?)
+    }
+
+    /// Reads a zip archive from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `reader` fails or doesn't contain a valid archive.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> io::Result<ZipFs> {
+        let files = MemoryFs::new();
+        let mut archive = ZipArchive::new(reader)?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let name = file.name().trim_matches('/').to_string();
+            let valid = !name.is_empty() && !file.name().starts_with('/')
+                && name.split('/').all(|part| !["", ".", ".."].contains(&part));
+
+            if file.is_dir() || !valid {
+                continue;
+            }
+
+            let modified = system_time(file.last_modified()).unwrap_or_else(SystemTime::now);
+            // The declared size can't be trusted to preallocate memory; it
+            // only bounds how much of the file is read.
+            let size = file.size();
+            let mut contents = Vec::with_capacity(std::cmp::min(size, PREALLOCATE_LIMIT) as usize);
+            file.by_ref().take(size).read_to_end(&mut contents)?;
+            files.insert_modified(&name, contents, modified);
+        }
+
+        Ok(ZipFs(files))
+    }
+
+    /// Returns the paths of the files in the archive, in order.
+    pub fn paths(&self) -> Vec<String> {
+        self.0.paths()
+    }
+}
+
+/// Converts a zip timestamp, interpreted as UTC, to a `SystemTime`.
+fn system_time(date: DateTime) -> Option<SystemTime> {
+    let date = Date::try_from_ymd(date.year() as i32, date.month(), date.day()).ok()?
+        .try_with_hms(date.hour(), date.minute(), date.second()).ok()?;
+
+    Some(date.assume_utc().into())
+}
+
+#[rocket::async_trait]
+impl Vfs for ZipFs {
+    async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+        self.0.metadata(path).await
+    }
+
+    async fn open(&self, path: &str) -> io::Result<VfsFile> {
+        self.0.open(path).await
+    }
+
+    async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        self.0.read_dir(path).await
+    }
+}
@@ -0,0 +1,232 @@
+//! `Server-Timing` and `X-Response-Time` headers for all responses.
+//!
+//! See the [`ServerTiming`] type for further details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `server_timing` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["server_timing"]
+//! ```
+
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::{Request, Response, Data};
+use rocket::fairing::{Fairing, Info, Kind, Order};
+use rocket::request::{self, FromRequest, Phase};
+use rocket::http::Header;
+
+use crate::received::Received;
+
+/// The phases reported in the `Server-Timing` header, in order.
+const PHASES: [Phase; 4] = [Phase::Routing, Phase::Guards, Phase::Handler, Phase::Responder];
+
+/// A fairing that reports how long responding to each request took.
+///
+/// `ServerTiming` adds two headers to every response:
+///
+///   * `Server-Timing`, with a [metric] for each phase of processing the
+///     request recorded by Rocket — `routing`, `guards`, `handler`, and
+///     `responder` — followed by any metrics added by the handler via
+///     [`Timings`]. See [`Phase`] for details on the phases.
+///
+///   * `X-Response-Time`, with the time taken from when the request was
+///     received until its response was ready, in milliseconds: `12.345ms`.
+///
+/// Durations in `Server-Timing` are in milliseconds. When a phase was entered
+/// more than once, as when a request is forwarded, its durations are summed.
+///
+/// Neither header is overwritten if the response already contains it.
+///
+/// [metric]: https://www.w3.org/TR/server-timing/
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::server_timing::ServerTiming;
+///
+/// rocket::ignite().attach(ServerTiming::new())
+/// # ;
+/// ```
+///
+/// For a request handled by a route that added a `db` metric, the headers look
+/// like:
+///
+/// ```text
+/// Server-Timing: routing;dur=0.004, guards;dur=0.011, handler;dur=3.274, responder;dur=0.019, db;desc="user lookup";dur=2.901
+/// X-Response-Time: 3.452ms
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ServerTiming {
+    _private: (),
+}
+
+impl ServerTiming {
+    /// Returns a new `ServerTiming` fairing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::server_timing::ServerTiming;
+    ///
+    /// let fairing = ServerTiming::new();
+    /// ```
+    pub fn new() -> ServerTiming {
+        ServerTiming { _private: () }
+    }
+}
+
+/// A metric added by a handler.
+struct Metric {
+    name: String,
+    description: Option<String>,
+    duration: Duration,
+}
+
+/// The `Server-Timing` metrics added to a response by its handler.
+///
+/// `&Timings` is a request guard that never fails. Metrics added to it are
+/// reported in the `Server-Timing` header of the response, after the metrics
+/// for Rocket's own phases, by an attached [`ServerTiming`] fairing. Metric
+/// names must be HTTP tokens: names containing spaces, quotes, or other
+/// delimiters are not reported.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::server_timing::Timings;
+///
+/// #[get("/user/<id>")]
+/// fn user(id: usize, timings: &Timings) -> String {
+///     let name = timings.time("db", || format!("user {}", id));
+///     timings.add_with_description("cache", "miss", std::time::Duration::from_millis(0));
+///     name
+/// }
+/// ```
+pub struct Timings(Mutex<Vec<Metric>>);
+
+impl Timings {
+    /// Adds a metric named `name` with the duration `duration`.
+    pub fn add(&self, name: &str, duration: Duration) {
+        self.push(Metric { name: name.into(), description: None, duration });
+    }
+
+    /// Adds a metric named `name` with the description `description` and the
+    /// duration `duration`.
+    pub fn add_with_description(&self, name: &str, description: &str, duration: Duration) {
+        let description = Some(description.into());
+        self.push(Metric { name: name.into(), description, duration });
+    }
+
+    /// Runs `f`, adds a metric named `name` with the time it took, and
+    /// returns its result.
+    pub fn time<T, F: FnOnce() -> T>(&self, name: &str, f: F) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.add(name, start.elapsed());
+        value
+    }
+
+    fn push(&self, metric: Metric) {
+        self.0.lock().expect("timings lock").push(metric);
+    }
+
+    fn of<'r>(req: &'r Request<'_>) -> &'r Timings {
+        req.local_cache(|| Timings(Mutex::new(vec![])))
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for &'a Timings {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(Timings::of(req))
+    }
+}
+
+/// Returns `true` if `name` is an HTTP token.
+fn is_token(name: &str) -> bool {
+    const DELIMITERS: &[u8] = b"\"(),/:;<=>?@[\\]{}";
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_graphic() && !DELIMITERS.contains(&b))
+}
+
+/// Returns `duration` in milliseconds, as reported in the headers.
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+/// Returns the value of the `Server-Timing` header for `req`.
+fn server_timing(req: &Request<'_>) -> String {
+    let phases = req.phases();
+    let mut metrics = vec![];
+    for phase in PHASES.iter() {
+        let timings = phases.iter().filter(|t| t.phase == *phase);
+        if timings.clone().next().is_some() {
+            let duration: Duration = timings.map(|t| t.duration()).sum();
+            metrics.push(format!("{};dur={}", phase, millis(duration)));
+        }
+    }
+
+    for metric in Timings::of(req).0.lock().expect("timings lock").iter() {
+        if !is_token(&metric.name) {
+            warn_!("Dropping Server-Timing metric with invalid name {:?}.", metric.name);
+            continue;
+        }
+
+        let mut entry = metric.name.clone();
+        if let Some(ref description) = metric.description {
+            let escaped = description.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = write!(entry, ";desc=\"{}\"", escaped);
+        }
+
+        let _ = write!(entry, ";dur={}", millis(metric.duration));
+        metrics.push(entry);
+    }
+
+    metrics.join(", ")
+}
+
+#[rocket::async_trait]
+impl Fairing for ServerTiming {
+    fn info(&self) -> Info {
+        Info {
+            name: "Server Timing",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    /// Requests are timed from before any other fairing runs.
+    fn order(&self) -> Order {
+        Order::priority(i32::max_value())
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        Received::record(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !res.headers().contains("X-Response-Time") {
+            let elapsed = format!("{}ms", millis(Received::elapsed(req)));
+            res.set_header(Header::new("X-Response-Time", elapsed));
+        }
+
+        if !res.headers().contains("Server-Timing") {
+            let value = server_timing(req);
+            if !value.is_empty() {
+                res.set_header(Header::new("Server-Timing", value));
+            }
+        }
+    }
+}
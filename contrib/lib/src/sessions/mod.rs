@@ -0,0 +1,589 @@
+//! Cookie-backed and server-side sessions.
+//!
+//! See the [`Session`] request guard and the [`Sessions`] fairing for further
+//! details.
+//!
+//! # Enabling
+//!
+//! This module is only available when the `sessions` feature is enabled.
+//! Enable it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket_contrib]
+//! version = "0.5.0-dev"
+//! default-features = false
+//! features = ["sessions"]
+//! ```
+//!
+//! Sessions are kept in private cookies, so a `secret_key` must be configured
+//! for sessions to survive an application restart.
+//!
+//! # Backends
+//!
+//! The [`Sessions::fairing()`] fairing keeps each session's values in the
+//! session cookie itself, encrypted and authenticated with the secret key.
+//! Such sessions need no server-side storage, but are limited by the size of
+//! a cookie, roughly 4KiB, and can't be revoked before they expire.
+//!
+//! The [`Sessions::with_store()`] fairing keeps only a random session ID in
+//! the cookie and the session's values in a [`SessionStore`]:
+//!
+//!   * [`MemoryStore`]: in memory.
+//!   * `RedisStore`: in a Redis database. Requires the `redis_pool` feature.
+//!   * `SqlStore`: in a SQL database table. Requires one of the
+//!     `sqlx_*_pool` features.
+//!
+//! # Configuration
+//!
+//! The session cookie and timeouts are configured in the `sessions` table in
+//! `Rocket.toml`:
+//!
+//! ```toml
+//! [global.sessions]
+//! cookie_name = "app_session"
+//! path = "/"
+//! domain = "example.com"
+//! secure = true
+//! http_only = true
+//! same_site = "strict"
+//! idle_timeout = 900
+//! absolute_timeout = 28800
+//! ```
+//!
+//! Every key is optional. The defaults are a cookie named `rocket_session`
+//! with a path of `/`, no domain, the `HttpOnly` attribute, and a `SameSite`
+//! attribute of `Lax`, an idle timeout of 30 minutes, and an absolute timeout
+//! of 24 hours. Timeouts are in seconds.
+//!
+//! When `secure` is `true`, the cookie is only sent over HTTPS. It defaults to
+//! `false` in the development environment and to `true` otherwise.
+//!
+//! # Expiration
+//!
+//! A session expires once it hasn't been used for the idle timeout or once the
+//! absolute timeout has passed since it was created, whichever comes first.
+//! Each request that retrieves a [`Session`] with values marks it as used,
+//! saving it anew once a tenth of the idle timeout has passed since it was
+//! last saved; sessions are otherwise only saved when their values change. An
+//! expired session is discarded, and the request starts with an empty session.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! # extern crate rocket_contrib;
+//! use rocket::response::Debug;
+//! use rocket_contrib::sessions::{Session, Sessions, SessionError, MemoryStore};
+//!
+//! #[get("/visits")]
+//! async fn visits(session: Session<'_>) -> Result<String, Debug<SessionError>> {
+//!     let visits = session.get::<u64>("visits").unwrap_or(0) + 1;
+//!     session.set("visits", &visits).await?;
+//!     Ok(format!("visit #{}", visits))
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Sessions::with_store(MemoryStore::new()))
+//!         .mount("/", routes![visits])
+//! #       ;
+//! }
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+
+use rocket::{Config, Request, Rocket};
+use rocket::config::{ConfigError, Value as ConfigValue};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::request::{self, FromRequest};
+
+use crate::clock::unix_now;
+
+mod store;
+#[cfg(feature = "redis_pool")] mod redis;
+#[cfg(any(
+    feature = "sqlx_postgres_pool",
+    feature = "sqlx_mysql_pool",
+    feature = "sqlx_sqlite_pool"
+))] mod sql;
+
+pub use self::store::{SessionStore, SessionError, MemoryStore};
+#[cfg(feature = "redis_pool")] pub use self::redis::RedisStore;
+#[cfg(any(
+    feature = "sqlx_postgres_pool",
+    feature = "sqlx_mysql_pool",
+    feature = "sqlx_sqlite_pool"
+))] pub use self::sql::{SqlStore, SqlSessionPool};
+
+/// The largest session cookie, in bytes, that browsers reliably accept.
+const MAX_COOKIE_SIZE: usize = 4096;
+
+/// The fairing that enables [`Session`]s.
+///
+/// On attach, the fairing reads the `sessions` configuration table, as
+/// described in the [module documentation](crate::sessions), and prepares its
+/// store, if any. If the configuration is invalid or the store can't be
+/// prepared, an error is logged and launch is aborted.
+pub struct Sessions {
+    store: Mutex<Option<Box<dyn SessionStore>>>,
+    server_side: bool,
+}
+
+impl Sessions {
+    /// Returns a fairing for sessions kept entirely in the session cookie.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::sessions::Sessions;
+    ///
+    /// rocket::ignite().attach(Sessions::fairing())
+    /// # ;
+    /// ```
+    pub fn fairing() -> Sessions {
+        Sessions { store: Mutex::new(None), server_side: false }
+    }
+
+    /// Returns a fairing for sessions kept in `store`, identified by an ID in
+    /// the session cookie.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// use rocket_contrib::sessions::{Sessions, MemoryStore};
+    ///
+    /// rocket::ignite().attach(Sessions::with_store(MemoryStore::new()))
+    /// # ;
+    /// ```
+    pub fn with_store<S: SessionStore>(store: S) -> Sessions {
+        Sessions { store: Mutex::new(Some(Box::new(store))), server_side: true }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Sessions {
+    fn info(&self) -> Info {
+        Info { name: "Sessions", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
+        let settings = match Settings::from_config(rocket.config().await) {
+            Ok(settings) => settings,
+            Err(e) => {
+                e.pretty_print();
+                error_!("Invalid sessions configuration.");
+                return Err(rocket);
+            }
+        };
+
+        let store = self.store.lock().expect("sessions store lock").take();
+        let store = match store {
+            Some(mut store) => match store.init(rocket.inspect().await).await {
+                Ok(()) => Some(Arc::from(store)),
+                Err(e) => {
+                    error!("Failed to initialize the session store.");
+                    error_!("{}", e);
+                    return Err(rocket);
+                }
+            },
+            None if self.server_side => {
+                error!("A `Sessions` fairing with a store can only be attached once.");
+                return Err(rocket);
+            }
+            None => None,
+        };
+
+        Ok(rocket.manage(Manager { settings, store }))
+    }
+}
+
+/// The configuration and store of sessions, managed by the [`Sessions`]
+/// fairing.
+struct Manager {
+    settings: Settings,
+    store: Option<Arc<dyn SessionStore>>,
+}
+
+/// The configuration read from the `sessions` table.
+#[derive(Debug, Clone)]
+struct Settings {
+    cookie_name: String,
+    path: String,
+    domain: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: SameSite,
+    idle_timeout: i64,
+    absolute_timeout: i64,
+}
+
+impl Settings {
+    /// Reads the settings from the `sessions` table in `config`. Missing keys,
+    /// or a missing table, take their default values.
+    fn from_config(config: &Config) -> Result<Settings, ConfigError> {
+        let mut settings = Settings {
+            cookie_name: "rocket_session".into(),
+            path: "/".into(),
+            domain: None,
+            secure: !config.environment.is_dev(),
+            http_only: true,
+            same_site: SameSite::Lax,
+            idle_timeout: 30 * 60,
+            absolute_timeout: 24 * 60 * 60,
+        };
+
+        let table = match config.get_table("sessions") {
+            Ok(table) => table,
+            Err(ConfigError::Missing(_)) => return Ok(settings),
+            Err(e) => return Err(e),
+        };
+
+        for (key, value) in table {
+            let string = || value.as_str().map(String::from).ok_or_else(|| bad_type(key, value));
+            let boolean = || value.as_bool().ok_or_else(|| bad_type(key, value));
+            let seconds = || match value.as_integer() {
+                Some(secs) if secs > 0 => Ok(secs),
+                _ => Err(bad_type(key, value)),
+            };
+
+            match key.as_str() {
+                "cookie_name" => settings.cookie_name = string()?,
+                "path" => settings.path = string()?,
+                "domain" => settings.domain = Some(string()?),
+                "secure" => settings.secure = boolean()?,
+                "http_only" => settings.http_only = boolean()?,
+                "same_site" => {
+                    settings.same_site = match string()?.to_ascii_lowercase().as_str() {
+                        "strict" => SameSite::Strict,
+                        "lax" => SameSite::Lax,
+                        "none" => SameSite::None,
+                        _ => return Err(bad_type(key, value)),
+                    };
+                }
+                "idle_timeout" => settings.idle_timeout = seconds()?,
+                "absolute_timeout" => settings.absolute_timeout = seconds()?,
+                _ => warn_!("Ignoring unknown sessions configuration key '{}'.", key),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Returns the session cookie with `value`, expiring after `ttl` seconds.
+    fn cookie(&self, value: String, ttl: i64) -> Cookie<'static> {
+        let mut cookie = Cookie::build(self.cookie_name.clone(), value)
+            .path(self.path.clone())
+            .secure(self.secure)
+            .http_only(self.http_only)
+            .same_site(self.same_site)
+            .max_age(time::Duration::seconds(ttl))
+            .finish();
+
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+
+        cookie.set_expires(OffsetDateTime::now_utc() + time::Duration::seconds(ttl));
+        cookie
+    }
+
+    /// Returns a cookie that removes the session cookie.
+    fn removal_cookie(&self) -> Cookie<'static> {
+        let mut cookie = Cookie::named(self.cookie_name.clone());
+        cookie.set_path(self.path.clone());
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+
+        cookie
+    }
+}
+
+fn bad_type(key: &str, value: &ConfigValue) -> ConfigError {
+    let expected = match key {
+        "secure" | "http_only" => "a boolean",
+        "same_site" => "\"strict\", \"lax\", or \"none\"",
+        "idle_timeout" | "absolute_timeout" => "a positive integer",
+        _ => "a string",
+    };
+
+    ConfigError::BadType(format!("sessions.{}", key), expected, value.type_str(), None)
+}
+
+/// Returns a new random session ID.
+fn generate_id() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The contents of a session.
+#[derive(Debug, Default)]
+struct State {
+    /// The ID of the session in the store, if it has been saved to one.
+    id: Option<String>,
+    values: Map<String, Value>,
+    /// When the session was created, in seconds since the Unix epoch.
+    created: i64,
+    /// When the session was last saved, in seconds since the Unix epoch.
+    accessed: i64,
+}
+
+impl State {
+    fn new() -> State {
+        let now = unix_now();
+        State { id: None, values: Map::new(), created: now, accessed: now }
+    }
+
+    /// Parses a record written by [`State::record()`].
+    fn parse(id: Option<String>, record: &str) -> Option<State> {
+        let mut record = match serde_json::from_str(record).ok()? {
+            Value::Object(record) => record,
+            _ => return None,
+        };
+
+        let values = match record.remove("values")? {
+            Value::Object(values) => values,
+            _ => return None,
+        };
+
+        let created = record.get("created")?.as_i64()?;
+        let accessed = record.get("accessed")?.as_i64()?;
+        Some(State { id, values, created, accessed })
+    }
+
+    /// Returns the record of the session.
+    fn record(&self) -> String {
+        let record = serde_json::json!({
+            "values": self.values,
+            "created": self.created,
+            "accessed": self.accessed,
+        });
+
+        record.to_string()
+    }
+}
+
+/// A request guard for the current session.
+///
+/// A `Session` holds string keys mapped to serializable values, and is empty
+/// for a client without a session. Values are read with [`Session::get()`]
+/// and saved immediately, along with the session cookie, by
+/// [`Session::set()`] and [`Session::remove()`].
+///
+/// To protect against session fixation, call [`Session::regenerate()`] when
+/// the client's privileges change, such as when a user logs in or out: the
+/// session keeps its values but is given a new ID. [`Session::destroy()`]
+/// discards the session altogether.
+///
+/// The guard fails with `500 Internal Server Error` if the [`Sessions`]
+/// fairing isn't attached and with `503 Service Unavailable` if the session
+/// can't be loaded from the store.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket::response::Debug;
+/// use rocket_contrib::sessions::{Session, SessionError};
+///
+/// #[post("/login/<user>")]
+/// async fn login(session: Session<'_>, user: String) -> Result<(), Debug<SessionError>> {
+///     // (Authenticate the user here.)
+///     session.regenerate().await?;
+///     Ok(session.set("user", &user).await?)
+/// }
+///
+/// #[get("/me")]
+/// fn me(session: Session<'_>) -> Option<String> {
+///     session.get("user")
+/// }
+///
+/// #[post("/logout")]
+/// async fn logout(session: Session<'_>) -> Result<(), Debug<SessionError>> {
+///     Ok(session.destroy().await?)
+/// }
+/// ```
+pub struct Session<'a> {
+    manager: &'a Manager,
+    cookies: &'a CookieJar<'a>,
+    state: Mutex<State>,
+}
+
+impl<'a> Session<'a> {
+    /// Loads the session of `req`, discarding it if it has expired and marking
+    /// it as used otherwise, if it hasn't been for a tenth of the idle timeout.
+    async fn load(req: &'a Request<'_>) -> Result<Session<'a>, SessionError> {
+        let manager = req.managed_state::<Manager>().ok_or(SessionError::Unmanaged)?;
+        let cookies = req.cookies();
+        let cookie = cookies.get_private(&manager.settings.cookie_name);
+        let loaded = match (&manager.store, cookie) {
+            (_, None) => None,
+            (None, Some(cookie)) => State::parse(None, cookie.value()),
+            (Some(store), Some(cookie)) => match store.load(cookie.value()).await? {
+                Some(record) => State::parse(Some(cookie.value().to_string()), &record),
+                None => None,
+            },
+        };
+
+        let now = unix_now();
+        let settings = &manager.settings;
+        let session = Session { manager, cookies, state: Mutex::new(State::new()) };
+        match loaded {
+            Some(state) => {
+                let idle = now - state.accessed;
+                let expired = idle >= settings.idle_timeout
+                    || now - state.created >= settings.absolute_timeout;
+
+                *session.state() = state;
+                if expired {
+                    session.destroy().await?;
+                } else if idle >= settings.idle_timeout / 10 {
+                    if let Err(e) = session.save().await {
+                        warn_!("Failed to mark session as used: {}", e);
+                    }
+                }
+            }
+            None if cookies.get(&settings.cookie_name).is_some() => {
+                cookies.remove_private(settings.removal_cookie());
+            }
+            None => {}
+        }
+
+        Ok(session)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("session state lock")
+    }
+
+    /// Saves the session to the store or the session cookie, assigning it an
+    /// ID if it has none, and refreshes the session cookie.
+    async fn save(&self) -> Result<(), SessionError> {
+        let settings = &self.manager.settings;
+        let now = unix_now();
+        let (id, record, ttl) = {
+            let mut state = self.state();
+            if self.manager.store.is_some() && state.id.is_none() {
+                state.id = Some(generate_id());
+            }
+
+            let remaining = state.created + settings.absolute_timeout - now;
+            let ttl = settings.idle_timeout.min(remaining).max(1);
+            state.accessed = now;
+            (state.id.clone(), state.record(), ttl)
+        };
+
+        match (&self.manager.store, id) {
+            (Some(store), Some(id)) => {
+                store.save(&id, &record, Duration::from_secs(ttl as u64)).await?;
+                self.cookies.add_private(settings.cookie(id, ttl));
+            }
+            _ => {
+                if record.len() > MAX_COOKIE_SIZE {
+                    warn_!("Session cookie of {} bytes may be rejected by clients.", record.len());
+                }
+
+                self.cookies.add_private(settings.cookie(record, ttl));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the value at `key`, or `None` if there is none or it can't be
+    /// deserialized as a `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.state().values.get(key).cloned()?;
+        serde_json::from_value(value).ok()
+    }
+
+    /// Returns `true` if the session has a value at `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        self.state().values.contains_key(key)
+    }
+
+    /// Returns `true` if the session has no values.
+    pub fn is_empty(&self) -> bool {
+        self.state().values.is_empty()
+    }
+
+    /// Sets the value at `key` to `value` and saves the session if the value
+    /// changed.
+    pub async fn set<T>(&self, key: &str, value: &T) -> Result<(), SessionError>
+        where T: Serialize + ?Sized
+    {
+        let value = serde_json::to_value(value)?;
+        if self.state().values.insert(key.to_string(), value.clone()) == Some(value) {
+            return Ok(());
+        }
+
+        self.save().await
+    }
+
+    /// Removes the value at `key` and saves the session if there was one.
+    pub async fn remove(&self, key: &str) -> Result<(), SessionError> {
+        if self.state().values.remove(key).is_none() {
+            return Ok(());
+        }
+
+        self.save().await
+    }
+
+    /// Gives the session a new ID, keeping its values, and removes the record
+    /// at the old ID from the store. Sessions kept in the cookie are simply
+    /// saved anew. Call this method whenever the client's privileges change.
+    pub async fn regenerate(&self) -> Result<(), SessionError> {
+        let old_id = self.state().id.take();
+        self.save().await?;
+        if let (Some(store), Some(id)) = (&self.manager.store, old_id) {
+            store.remove(&id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Discards the session: removes its values, its record in the store, and
+    /// the session cookie. Values set afterwards start a new session.
+    pub async fn destroy(&self) -> Result<(), SessionError> {
+        let old = std::mem::replace(&mut *self.state(), State::new());
+        self.cookies.remove_private(self.manager.settings.removal_cookie());
+        if let (Some(store), Some(id)) = (&self.manager.store, old.id) {
+            store.remove(&id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Session<'a> {
+    type Error = SessionError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match Session::load(req).await {
+            Ok(session) => request::Outcome::Success(session),
+            Err(SessionError::Unmanaged) => {
+                error_!("Missing sessions fairing: attach `Sessions`.");
+                request::Outcome::Failure((Status::InternalServerError, SessionError::Unmanaged))
+            }
+            Err(e) => {
+                error_!("Failed to load session: {}", e);
+                request::Outcome::Failure((Status::ServiceUnavailable, e))
+            }
+        }
+    }
+}
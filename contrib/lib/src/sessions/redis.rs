@@ -0,0 +1,104 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use rocket::Cargo;
+
+use crate::databases::Database;
+use crate::databases::redis::{self, aio::ConnectionManager};
+use crate::sessions::{SessionStore, SessionError};
+
+/// A store holding session records in the Redis database `D`.
+///
+/// Records are stored at keys made of a prefix, `session:` by default, and
+/// the session ID, and expire with Redis' own key expiration. The
+/// [`Initializer`](crate::databases::Initializer) of `D` must be attached
+/// before the [`Sessions`](crate::sessions::Sessions) fairing.
+///
+/// This type is only available when the `redis_pool` feature is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "redis_pool")] mod test {
+/// use rocket_contrib::databases::{Database, redis};
+/// use rocket_contrib::sessions::{Sessions, RedisStore};
+///
+/// #[derive(Database)]
+/// #[database("redis")]
+/// struct Redis(redis::aio::ConnectionManager);
+///
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .attach(Redis::init())
+///         .attach(Sessions::with_store(RedisStore::<Redis>::new()))
+/// }
+/// # }
+/// ```
+pub struct RedisStore<D> {
+    connection: Option<ConnectionManager>,
+    prefix: String,
+    _database: PhantomData<fn() -> D>,
+}
+
+impl<D: Database<Pool = ConnectionManager>> RedisStore<D> {
+    /// Returns a store using the default key prefix, `session:`.
+    pub fn new() -> Self {
+        RedisStore { connection: None, prefix: "session:".into(), _database: PhantomData }
+    }
+
+    /// Sets the prefix of the keys of session records to `prefix`.
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn connection(&self) -> Result<ConnectionManager, SessionError> {
+        self.connection.clone().ok_or(SessionError::Unmanaged)
+    }
+}
+
+impl<D: Database<Pool = ConnectionManager>> Default for RedisStore<D> {
+    fn default() -> Self {
+        RedisStore::new()
+    }
+}
+
+#[rocket::async_trait]
+impl<D: Database<Pool = ConnectionManager>> SessionStore for RedisStore<D> {
+    async fn init(&mut self, cargo: &Cargo) -> Result<(), String> {
+        match D::fetch(cargo) {
+            Some(db) => {
+                self.connection = Some((**db).clone());
+                Ok(())
+            }
+            None => Err(format!("database `{}` is not initialized", D::NAME)),
+        }
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<String>, SessionError> {
+        redis::cmd("GET")
+            .arg(format!("{}{}", self.prefix, id))
+            .query_async(&mut self.connection()?)
+            .await
+            .map_err(SessionError::store)
+    }
+
+    async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError> {
+        redis::cmd("SET")
+            .arg(format!("{}{}", self.prefix, id))
+            .arg(record)
+            .arg("PX")
+            .arg(ttl.as_millis().max(1) as u64)
+            .query_async(&mut self.connection()?)
+            .await
+            .map_err(SessionError::store)
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), SessionError> {
+        redis::cmd("DEL")
+            .arg(format!("{}{}", self.prefix, id))
+            .query_async(&mut self.connection()?)
+            .await
+            .map_err(SessionError::store)
+    }
+}
@@ -0,0 +1,188 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use rocket::Cargo;
+
+use crate::databases::{Database, sqlx};
+use crate::sessions::{SessionStore, SessionError};
+use crate::clock::unix_now;
+
+/// A store holding session records in a table of the SQL database `D`.
+///
+/// The table, `sessions` by default, must exist before the store is used. It
+/// has a text primary key `id`, a text column `data`, and an integer column
+/// `expires`, the time at which the record expires in seconds since the Unix
+/// epoch. For instance:
+///
+/// ```sql
+/// CREATE TABLE sessions (
+///     id VARCHAR(64) PRIMARY KEY,
+///     data TEXT NOT NULL,
+///     expires BIGINT NOT NULL
+/// );
+/// ```
+///
+/// Expired records are deleted whenever a session is removed. The
+/// [`Initializer`](crate::databases::Initializer) of `D` must be attached
+/// before the [`Sessions`](crate::sessions::Sessions) fairing.
+///
+/// This type is only available when one of the `sqlx_postgres_pool`,
+/// `sqlx_mysql_pool`, or `sqlx_sqlite_pool` features is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "sqlx_sqlite_pool")] mod test {
+/// use rocket_contrib::databases::{Database, sqlx};
+/// use rocket_contrib::sessions::{Sessions, SqlStore};
+///
+/// #[derive(Database)]
+/// #[database("sqlite_app")]
+/// struct App(sqlx::SqlitePool);
+///
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .attach(App::init())
+///         .attach(Sessions::with_store(SqlStore::<App>::new().table("web_sessions")))
+/// }
+/// # }
+/// ```
+pub struct SqlStore<D: Database> {
+    pool: Option<D::Pool>,
+    table: String,
+    _database: PhantomData<fn() -> D>,
+}
+
+impl<D: Database> SqlStore<D> where D::Pool: SqlSessionPool {
+    /// Returns a store using the default table, `sessions`.
+    pub fn new() -> Self {
+        SqlStore { pool: None, table: "sessions".into(), _database: PhantomData }
+    }
+
+    /// Sets the name of the table holding session records to `table`.
+    pub fn table<S: Into<String>>(mut self, table: S) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    fn pool(&self) -> Result<&D::Pool, SessionError> {
+        self.pool.as_ref().ok_or(SessionError::Unmanaged)
+    }
+}
+
+impl<D: Database> Default for SqlStore<D> where D::Pool: SqlSessionPool {
+    fn default() -> Self {
+        SqlStore::new()
+    }
+}
+
+#[rocket::async_trait]
+impl<D: Database> SessionStore for SqlStore<D> where D::Pool: SqlSessionPool {
+    async fn init(&mut self, cargo: &Cargo) -> Result<(), String> {
+        match D::fetch(cargo) {
+            Some(db) => {
+                self.pool = Some((**db).clone());
+                Ok(())
+            }
+            None => Err(format!("database `{}` is not initialized", D::NAME)),
+        }
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<String>, SessionError> {
+        self.pool()?.load_session(&self.table, id, unix_now()).await.map_err(SessionError::store)
+    }
+
+    async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError> {
+        let expires = unix_now() + ttl.as_secs() as i64;
+        self.pool()?.save_session(&self.table, id, record, expires).await
+            .map_err(SessionError::store)
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), SessionError> {
+        self.pool()?.remove_session(&self.table, id, unix_now()).await
+            .map_err(SessionError::store)
+    }
+}
+
+/// Trait implemented by SQL database pools that can hold the records of a
+/// [`SqlStore`].
+///
+/// This trait is implemented for the `sqlx` pools, using the SQL dialect of
+/// each database.
+#[rocket::async_trait]
+pub trait SqlSessionPool: Clone + Send + Sync + 'static {
+    /// Returns the data of the record `id` in `table` if it expires after
+    /// `now`.
+    async fn load_session(&self, table: &str, id: &str, now: i64)
+        -> Result<Option<String>, sqlx::Error>;
+
+    /// Inserts or replaces the record `id` in `table`.
+    async fn save_session(&self, table: &str, id: &str, data: &str, expires: i64)
+        -> Result<(), sqlx::Error>;
+
+    /// Deletes the record `id` and every record that expired by `now` from
+    /// `table`.
+    async fn remove_session(&self, table: &str, id: &str, now: i64)
+        -> Result<(), sqlx::Error>;
+}
+
+macro_rules! impl_sql_session_pool {
+    ($feature:literal, $pool:ty, $load:literal, $save:literal, $remove:literal) => {
+        #[cfg(feature = $feature)]
+        #[rocket::async_trait]
+        impl SqlSessionPool for $pool {
+            async fn load_session(&self, table: &str, id: &str, now: i64)
+                -> Result<Option<String>, sqlx::Error>
+            {
+                let row: Option<(String,)> = sqlx::query_as(&$load.replace("{table}", table))
+                    .bind(id)
+                    .bind(now)
+                    .fetch_optional(self)
+                    .await?;
+
+                Ok(row.map(|(data,)| data))
+            }
+
+            async fn save_session(&self, table: &str, id: &str, data: &str, expires: i64)
+                -> Result<(), sqlx::Error>
+            {
+                sqlx::query(&$save.replace("{table}", table))
+                    .bind(id)
+                    .bind(data)
+                    .bind(expires)
+                    .execute(self)
+                    .await
+                    .map(|_| ())
+            }
+
+            async fn remove_session(&self, table: &str, id: &str, now: i64)
+                -> Result<(), sqlx::Error>
+            {
+                sqlx::query(&$remove.replace("{table}", table))
+                    .bind(id)
+                    .bind(now)
+                    .execute(self)
+                    .await
+                    .map(|_| ())
+            }
+        }
+    };
+}
+
+impl_sql_session_pool!("sqlx_postgres_pool", sqlx::PgPool,
+    "SELECT data FROM {table} WHERE id = $1 AND expires > $2",
+    "INSERT INTO {table} (id, data, expires) VALUES ($1, $2, $3) \
+        ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data, expires = EXCLUDED.expires",
+    "DELETE FROM {table} WHERE id = $1 OR expires <= $2");
+
+impl_sql_session_pool!("sqlx_mysql_pool", sqlx::MySqlPool,
+    "SELECT data FROM {table} WHERE id = ? AND expires > ?",
+    "INSERT INTO {table} (id, data, expires) VALUES (?, ?, ?) \
+        ON DUPLICATE KEY UPDATE data = VALUES(data), expires = VALUES(expires)",
+    "DELETE FROM {table} WHERE id = ? OR expires <= ?");
+
+impl_sql_session_pool!("sqlx_sqlite_pool", sqlx::SqlitePool,
+    "SELECT data FROM {table} WHERE id = ? AND expires > ?",
+    "INSERT INTO {table} (id, data, expires) VALUES (?, ?, ?) \
+        ON CONFLICT (id) DO UPDATE SET data = excluded.data, expires = excluded.expires",
+    "DELETE FROM {table} WHERE id = ? OR expires <= ?");
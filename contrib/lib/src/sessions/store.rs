@@ -0,0 +1,202 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+use std::sync::{Mutex, RwLock};
+use std::collections::HashMap;
+
+use rocket::Cargo;
+
+/// Trait implemented by server-side stores of session records.
+///
+/// A store maps session IDs to records, strings produced by [`Session`] that
+/// hold a session's values and timestamps. Records expire after the
+/// time-to-live passed to [`SessionStore::save()`]; a store must not return a
+/// record after it expires, but needn't delete it immediately.
+///
+/// Stores are passed to [`Sessions::with_store()`]. The following stores are
+/// provided:
+///
+///   * [`MemoryStore`]: records held in memory, for development and for
+///     applications running as a single process.
+///   * `RedisStore`: records in a Redis database. Requires the `redis_pool`
+///     feature.
+///   * `SqlStore`: records in a table of a SQL database. Requires one of the
+///     `sqlx_*_pool` features.
+///
+/// [`Session`]: crate::sessions::Session
+/// [`Sessions::with_store()`]: crate::sessions::Sessions::with_store()
+///
+/// # Example
+///
+/// A store that delegates to another, logging every operation:
+///
+/// ```rust
+/// use std::time::Duration;
+/// use rocket_contrib::sessions::{SessionStore, SessionError, MemoryStore};
+///
+/// struct Logged(MemoryStore);
+///
+/// #[rocket::async_trait]
+/// impl SessionStore for Logged {
+///     async fn load(&self, id: &str) -> Result<Option<String>, SessionError> {
+///         println!("loading session {}", id);
+///         self.0.load(id).await
+///     }
+///
+///     async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError> {
+///         println!("saving session {} for {:?}", id, ttl);
+///         self.0.save(id, record, ttl).await
+///     }
+///
+///     async fn remove(&self, id: &str) -> Result<(), SessionError> {
+///         println!("removing session {}", id);
+///         self.0.remove(id).await
+///     }
+/// }
+/// ```
+#[rocket::async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// Prepares the store when the [`Sessions`](crate::sessions::Sessions)
+    /// fairing is attached, typically by retrieving a database pool from the
+    /// managed state of `cargo`. Returns a description of the problem if the
+    /// store can't be used, which aborts launch. The default implementation
+    /// does nothing.
+    async fn init(&mut self, cargo: &Cargo) -> Result<(), String> {
+        let _ = cargo;
+        Ok(())
+    }
+
+    /// Returns the record of the session `id`, or `None` if there is none or
+    /// it has expired.
+    async fn load(&self, id: &str) -> Result<Option<String>, SessionError>;
+
+    /// Stores `record` as the record of the session `id`, replacing any
+    /// existing record, for `ttl`.
+    async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError>;
+
+    /// Removes the record of the session `id`, if there is one.
+    async fn remove(&self, id: &str) -> Result<(), SessionError>;
+}
+
+/// An error returned by a [`Session`](crate::sessions::Session) or a
+/// [`SessionStore`].
+#[derive(Debug)]
+pub enum SessionError {
+    /// The store failed.
+    Store(Box<dyn std::error::Error + Send + Sync>),
+    /// A value couldn't be serialized or deserialized.
+    Serde(serde_json::Error),
+    /// The [`Sessions`](crate::sessions::Sessions) fairing isn't attached.
+    Unmanaged,
+}
+
+impl SessionError {
+    /// Returns a [`SessionError::Store`] wrapping `error`.
+    pub fn store<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> SessionError {
+        SessionError::Store(error.into())
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Store(e) => write!(f, "session store error: {}", e),
+            SessionError::Serde(e) => write!(f, "serialization error: {}", e),
+            SessionError::Unmanaged => write!(f, "the `Sessions` fairing is not attached"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SessionError::Store(e) => Some(&**e),
+            SessionError::Serde(e) => Some(e),
+            SessionError::Unmanaged => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(error: serde_json::Error) -> Self {
+        SessionError::Serde(error)
+    }
+}
+
+/// How often a [`MemoryStore`] sweeps expired records.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A store holding session records in memory.
+///
+/// Records are lost when the application exits and aren't shared between
+/// processes. Expired records are swept at most once a minute, when a session
+/// is saved.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate rocket;
+/// # extern crate rocket_contrib;
+/// use rocket_contrib::sessions::{Sessions, MemoryStore};
+///
+/// rocket::ignite().attach(Sessions::with_store(MemoryStore::new()))
+/// # ;
+/// ```
+#[derive(Debug)]
+pub struct MemoryStore {
+    records: RwLock<HashMap<String, (String, Instant)>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl MemoryStore {
+    /// Returns an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore { records: RwLock::default(), last_sweep: Mutex::new(Instant::now()) }
+    }
+
+    /// Returns the number of records in the store that haven't expired.
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        let records = self.records.read().expect("session store lock");
+        records.values().filter(|(_, expires)| *expires > now).count()
+    }
+
+    /// Returns `true` if every record in the store has expired.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+#[rocket::async_trait]
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &str) -> Result<Option<String>, SessionError> {
+        let records = self.records.read().expect("session store lock");
+        Ok(records.get(id)
+            .filter(|(_, expires)| *expires > Instant::now())
+            .map(|(record, _)| record.clone()))
+    }
+
+    async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError> {
+        let now = Instant::now();
+        let mut records = self.records.write().expect("session store lock");
+        records.insert(id.to_string(), (record.to_string(), now + ttl));
+
+        let mut last_sweep = self.last_sweep.lock().expect("session sweep lock");
+        if now.duration_since(*last_sweep) >= SWEEP_INTERVAL {
+            records.retain(|_, (_, expires)| *expires > now);
+            *last_sweep = now;
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), SessionError> {
+        self.records.write().expect("session store lock").remove(id);
+        Ok(())
+    }
+}
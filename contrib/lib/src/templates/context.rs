@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 
-use crate::templates::{Engines, TemplateInfo};
+use crate::templates::{EmbeddedTemplates, Engines, Registration, TemplateInfo};
 
 use rocket::http::ContentType;
 
@@ -17,41 +17,79 @@ pub(crate) struct Context {
 impl Context {
     /// Load all of the templates at `root`, initialize them using the relevant
     /// template engine, and store all of the initialized state in a `Context`
-    /// structure, which is returned if all goes well.
-    pub fn initialize(root: PathBuf) -> Option<Context> {
+    /// structure, which is returned if all goes well. Templates for the
+    /// `registered` engines are loaded alongside those of the enabled ones.
+    pub fn initialize(root: PathBuf, registered: &[Registration]) -> Option<Context> {
         let mut templates: HashMap<String, TemplateInfo> = HashMap::new();
-        for ext in Engines::ENABLED_EXTENSIONS {
+        for ext in extensions(registered) {
             let mut glob_path = root.join("**").join("*");
             glob_path.set_extension(ext);
             let glob_path = glob_path.to_str().expect("valid glob path string");
 
             for path in glob::glob(glob_path).unwrap().filter_map(Result::ok) {
-                let (name, data_type_str) = split_path(&root, &path);
-                if let Some(info) = templates.get(&*name) {
-                    warn_!("Template name '{}' does not have a unique path.", name);
-                    info_!("Existing path: {:?}", info.path);
-                    info_!("Additional path: {:?}", path);
-                    warn_!("Using existing path for template '{}'.", name);
-                    continue;
-                }
+                add_template(&mut templates, &root, &path, ext, None);
+            }
+        }
 
-                let data_type = data_type_str.as_ref()
-                    .and_then(|ext| ContentType::from_extension(ext))
-                    .unwrap_or(ContentType::HTML);
+        Engines::init(&templates, registered)
+            .map(|engines| Context { root, templates, engines } )
+    }
 
-                templates.insert(name, TemplateInfo {
-                    path: path.to_path_buf(),
-                    extension: ext.to_string(),
-                    data_type,
-                });
+    /// Like [`Context::initialize()`], but loads the `embedded` templates
+    /// instead of those in a directory.
+    pub fn embedded(embedded: &EmbeddedTemplates, registered: &[Registration]) -> Option<Context> {
+        let mut templates: HashMap<String, TemplateInfo> = HashMap::new();
+        for ext in extensions(registered) {
+            for &(path, source) in embedded.templates {
+                let path = Path::new(path);
+                if path.extension().map_or(false, |e| e == ext) {
+                    add_template(&mut templates, Path::new(""), path, ext, Some(source));
+                }
             }
         }
 
-        Engines::init(&templates)
+        let root = PathBuf::from(embedded.root);
+        Engines::init(&templates, registered)
             .map(|engines| Context { root, templates, engines } )
     }
 }
 
+/// The extensions of the enabled and `registered` engines.
+fn extensions(registered: &[Registration]) -> impl Iterator<Item = &'static str> + '_ {
+    Engines::ENABLED_EXTENSIONS.iter().copied()
+        .chain(registered.iter().map(|r| r.ext))
+}
+
+/// Adds the template at `path` in `root` for the engine with extension `ext`
+/// to `templates` unless a template with the same name was already added.
+fn add_template(
+    templates: &mut HashMap<String, TemplateInfo>,
+    root: &Path,
+    path: &Path,
+    ext: &str,
+    source: Option<&'static str>,
+) {
+    let (name, data_type_str) = split_path(root, path);
+    if let Some(info) = templates.get(&*name) {
+        warn_!("Template name '{}' does not have a unique path.", name);
+        info_!("Existing path: {:?}", info.path);
+        info_!("Additional path: {:?}", path);
+        warn_!("Using existing path for template '{}'.", name);
+        return;
+    }
+
+    let data_type = data_type_str.as_ref()
+        .and_then(|ext| ContentType::from_extension(ext))
+        .unwrap_or(ContentType::HTML);
+
+    templates.insert(name, TemplateInfo {
+        path: path.to_path_buf(),
+        extension: ext.to_string(),
+        data_type,
+        source,
+    });
+}
+
 /// Removes the file path's extension or does nothing if there is none.
 fn remove_extension<P: AsRef<Path>>(path: P) -> PathBuf {
     let path = path.as_ref();
@@ -0,0 +1,52 @@
+/// Templates embedded into the application at build time.
+///
+/// A value of this type is created by the [`embed_templates!`] macro and is
+/// registered with [`TemplateFairing::embed()`]. The macro embeds the files in
+/// a template directory with a `tera` or `hbs` extension, as well as those
+/// with any extension passed after the directory, such as that of a custom
+/// engine: `embed_templates!("templates", "shout")`. Templates are named by
+/// their path relative to the template directory, as they are when loaded
+/// from it.
+///
+/// In release builds, embedded templates are used in place of the template
+/// directory, which then need not exist at run time. In debug builds,
+/// templates continue to be loaded from the template directory, and reloaded
+/// when they change, unless the directory doesn't exist.
+///
+/// [`embed_templates!`]: crate::templates::embed_templates!
+/// [`TemplateFairing::embed()`]: crate::templates::TemplateFairing::embed()
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedTemplates {
+    /// The template directory, relative to the crate root.
+    pub(crate) root: &'static str,
+    pub(crate) templates: &'static [(&'static str, &'static str)],
+}
+
+impl EmbeddedTemplates {
+    #[doc(hidden)]
+    pub const fn new(
+        root: &'static str,
+        templates: &'static [(&'static str, &'static str)]
+    ) -> EmbeddedTemplates {
+        EmbeddedTemplates { root, templates }
+    }
+
+    /// Returns the paths, relative to the template directory, of the embedded
+    /// files.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::templates::embed_templates;
+    ///
+    /// let templates = embed_templates!("tests/templates");
+    /// assert!(templates.paths().any(|path| path == "tera/base.txt.tera"));
+    /// assert!(templates.paths().all(|path| !path.ends_with(".shout")));
+    ///
+    /// let templates = embed_templates!("tests/templates", "shout");
+    /// assert!(templates.paths().any(|path| path == "shout/hello.txt.shout"));
+    /// ```
+    pub fn paths(&self) -> impl Iterator<Item = &'static str> {
+        self.templates.iter().map(|&(path, _)| path)
+    }
+}
@@ -1,17 +1,193 @@
+use std::any::Any;
+use std::io::Write;
+use std::path::Path;
 use std::collections::HashMap;
 
 use serde::Serialize;
+use serde_json::Value;
 
 use crate::templates::TemplateInfo;
 
 #[cfg(feature = "tera_templates")] use crate::templates::tera::Tera;
 #[cfg(feature = "handlebars_templates")] use crate::templates::handlebars::Handlebars;
 
-pub(crate) trait Engine: Send + Sync + 'static {
+/// A template engine that renders templates discovered in the template
+/// directory.
+///
+/// Tera and Handlebars implement `Engine` when their respective features are
+/// enabled. Other engines, such as [MiniJinja] or [Liquid], or an
+/// application's own, can be used with the same [`Template::render()`] API by
+/// implementing `Engine` and registering the implementation with
+/// [`TemplateFairing::engine()`]. Templates ending in the engine's
+/// [`EXT`](Engine::EXT) are discovered, named, reloaded in debug mode, and
+/// rendered exactly like Tera and Handlebars templates. To use only custom
+/// engines, enable the `templates` feature instead of `tera_templates` or
+/// `handlebars_templates`.
+///
+/// [MiniJinja]: https://docs.rs/minijinja
+/// [Liquid]: https://docs.rs/liquid
+/// [`Template::render()`]: crate::templates::Template::render()
+/// [`TemplateFairing::engine()`]: crate::templates::TemplateFairing::engine()
+///
+/// # Example
+///
+/// An engine that renders templates verbatim, ignoring the context:
+///
+/// ```rust
+/// use std::path::Path;
+/// use std::collections::HashMap;
+///
+/// use serde::Serialize;
+/// use rocket_contrib::templates::{Engine, Template};
+///
+/// struct Verbatim(HashMap<String, String>);
+///
+/// impl Engine for Verbatim {
+///     const EXT: &'static str = "txt";
+///
+///     fn init(templates: &[(&str, &Path)]) -> Option<Self> {
+///         let mut map = HashMap::new();
+///         for &(name, path) in templates {
+///             map.insert(name.to_string(), std::fs::read_to_string(path).ok()?);
+///         }
+///
+///         Some(Verbatim(map))
+///     }
+///
+///     fn render<C: Serialize>(&self, name: &str, _: C) -> Option<String> {
+///         self.0.get(name).cloned()
+///     }
+/// }
+///
+/// fn main() {
+///     rocket::ignite()
+///         .attach(Template::fairing().engine::<Verbatim>())
+///         // ...
+///     # ;
+/// }
+/// ```
+pub trait Engine: Send + Sync + 'static {
+    /// The extension, without the leading `.`, of the templates rendered by
+    /// this engine. For example, `"tera"`.
     const EXT: &'static str;
 
-    fn init(templates: &[(&str, &TemplateInfo)]) -> Option<Self> where Self: Sized;
+    /// Initializes the engine with `templates`, the name and path of every
+    /// discovered template ending in [`EXT`](Engine::EXT). Returns `None`,
+    /// after logging the reason, if the templates cannot be loaded. This
+    /// method is called again with the current templates whenever templates
+    /// are reloaded.
+    fn init(templates: &[(&str, &Path)]) -> Option<Self> where Self: Sized;
+
+    /// Initializes the engine with [embedded](crate::templates::embed_templates!)
+    /// `templates`: the name, path relative to the template directory, and
+    /// source of every embedded template ending in [`EXT`](Engine::EXT).
+    /// Engines that can load templates from strings should override this
+    /// method; the default logs an error and returns `None`.
+    fn init_embedded(templates: &[(&str, &Path, &str)]) -> Option<Self> where Self: Sized {
+        if let Some(&(name, ..)) = templates.first() {
+            error!("Template '{}' can't be loaded: the '{}' engine doesn't support \
+                embedded templates.", name, Self::EXT);
+            return None;
+        }
+
+        Self::init(&[])
+    }
+
+    /// Renders the template named `name` with the context `context`. Returns
+    /// `None`, after logging the reason, if rendering fails.
     fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String>;
+
+    /// Renders the template named `name` into `writer`. Engines that can
+    /// render incrementally should override this method; the default renders
+    /// into a `String` and then writes it out in its entirety.
+    fn render_to<C: Serialize>(&self, name: &str, context: C, writer: &mut dyn Write) -> Option<()> {
+        let string = Engine::render(self, name, context)?;
+        writer.write_all(string.as_bytes())
+            .map_err(|e| debug_!("Failed to write rendered template '{}': {}", name, e))
+            .ok()
+    }
+
+    /// Renders only the block named `block` of the template named `name`.
+    /// Engines that support blocks, or a similar notion of named fragments,
+    /// should override this method; the default logs an error and returns
+    /// `None`.
+    fn render_block<C: Serialize>(&self, name: &str, block: &str, _context: C) -> Option<String> {
+        error_!("Block '{}' of template '{}' can't be rendered: the template's engine \
+            doesn't support rendering blocks.", block, name);
+        None
+    }
+}
+
+/// An object-safe `Engine`, used to store registered engines.
+trait DynEngine: Send + Sync + 'static {
+    fn render(&self, name: &str, context: &Value) -> Option<String>;
+    fn render_to(&self, name: &str, context: &Value, writer: &mut dyn Write) -> Option<()>;
+    fn render_block(&self, name: &str, block: &str, context: &Value) -> Option<String>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<E: Engine> DynEngine for E {
+    fn render(&self, name: &str, context: &Value) -> Option<String> {
+        <E as Engine>::render(self, name, context)
+    }
+
+    fn render_to(&self, name: &str, context: &Value, writer: &mut dyn Write) -> Option<()> {
+        <E as Engine>::render_to(self, name, context, writer)
+    }
+
+    fn render_block(&self, name: &str, block: &str, context: &Value) -> Option<String> {
+        <E as Engine>::render_block(self, name, block, context)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Initializes `E` with its templates in `templates`, which are either all
+/// embedded or all loaded from disk.
+fn init<E: Engine>(templates: &HashMap<String, TemplateInfo>) -> Option<E> {
+    let templates = templates.iter()
+        .filter(|&(_, i)| i.extension == E::EXT)
+        .collect::<Vec<_>>();
+
+    if templates.iter().any(|(_, i)| i.source.is_some()) {
+        let embedded = templates.iter()
+            .map(|(k, i)| (k.as_str(), i.path.as_path(), i.source.unwrap_or_default()))
+            .collect::<Vec<_>>();
+
+        E::init_embedded(&embedded)
+    } else {
+        let named = templates.iter()
+            .map(|(k, i)| (k.as_str(), i.path.as_path()))
+            .collect::<Vec<_>>();
+
+        E::init(&named)
+    }
+}
+
+/// An engine registered via `TemplateFairing::engine()`.
+#[derive(Clone, Copy)]
+pub(crate) struct Registration {
+    pub ext: &'static str,
+    init: fn(&HashMap<String, TemplateInfo>) -> Option<Box<dyn DynEngine>>,
+}
+
+impl Registration {
+    pub fn of<E: Engine>() -> Registration {
+        fn init_dyn<E: Engine>(
+            templates: &HashMap<String, TemplateInfo>
+        ) -> Option<Box<dyn DynEngine>> {
+            init::<E>(templates).map(|engine| Box::new(engine) as Box<dyn DynEngine>)
+        }
+
+        Registration { ext: E::EXT, init: init_dyn::<E> }
+    }
 }
 
 /// A structure exposing access to templating engines.
@@ -48,6 +224,8 @@ pub(crate) trait Engine: Send + Sync + 'static {
 /// # }
 /// ```
 ///
+/// Registered engines are accessed via [`Engines::get_mut()`].
+///
 /// [`tera::Value`]: crate::templates::tera::Value
 /// [`tera::Result`]: crate::templates::tera::Result
 pub struct Engines {
@@ -63,6 +241,8 @@ pub struct Engines {
     /// `rocket_contrib::templates::handlebars` to avoid version mismatches.
     #[cfg(feature = "handlebars_templates")]
     pub handlebars: Handlebars<'static>,
+    /// Engines registered via `TemplateFairing::engine()`, by extension.
+    registered: Vec<(&'static str, Box<dyn DynEngine>)>,
 }
 
 impl Engines {
@@ -71,35 +251,92 @@ impl Engines {
         #[cfg(feature = "handlebars_templates")] Handlebars::EXT,
     ];
 
-    pub(crate) fn init(templates: &HashMap<String, TemplateInfo>) -> Option<Engines> {
-        fn inner<E: Engine>(templates: &HashMap<String, TemplateInfo>) -> Option<E> {
-            let named_templates = templates.iter()
-                .filter(|&(_, i)| i.extension == E::EXT)
-                .map(|(k, i)| (k.as_str(), i))
-                .collect::<Vec<_>>();
-
-            E::init(&*named_templates)
+    pub(crate) fn init(
+        templates: &HashMap<String, TemplateInfo>,
+        registered: &[Registration],
+    ) -> Option<Engines> {
+        let mut engines = Vec::with_capacity(registered.len());
+        for registration in registered {
+            let engine = (registration.init)(templates)?;
+            engines.push((registration.ext, engine));
         }
 
         Some(Engines {
             #[cfg(feature = "tera_templates")]
-            tera: match inner::<Tera>(templates) {
-                Some(tera) => tera,
-                None => return None
-            },
+            tera: init::<Tera>(templates)?,
             #[cfg(feature = "handlebars_templates")]
-            handlebars: match inner::<Handlebars<'static>>(templates) {
-                Some(hb) => hb,
-                None => return None
-            },
+            handlebars: init::<Handlebars<'static>>(templates)?,
+            registered: engines,
         })
     }
 
-    pub(crate) fn render<C: Serialize>(
+    /// Returns a reference to the engine of type `E`, if it is enabled or
+    /// registered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "tera_templates")] {
+    /// use rocket_contrib::templates::{Template, Engines};
+    /// use rocket_contrib::templates::tera::Tera;
+    ///
+    /// Template::custom(|engines: &mut Engines| {
+    ///     assert!(engines.get::<Tera>().is_some());
+    /// });
+    /// # }
+    /// ```
+    pub fn get<E: Engine>(&self) -> Option<&E> {
+        #[cfg(feature = "tera_templates")]
+        {
+            if let Some(engine) = (&self.tera as &dyn Any).downcast_ref::<E>() {
+                return Some(engine);
+            }
+        }
+
+        #[cfg(feature = "handlebars_templates")]
+        {
+            if let Some(engine) = (&self.handlebars as &dyn Any).downcast_ref::<E>() {
+                return Some(engine);
+            }
+        }
+
+        self.registered.iter().find_map(|(_, engine)| engine.as_any().downcast_ref::<E>())
+    }
+
+    /// Returns a mutable reference to the engine of type `E`, if it is
+    /// enabled or registered. This allows registered engines to be customized
+    /// in the callback passed to [`Template::custom()`].
+    ///
+    /// [`Template::custom()`]: crate::templates::Template::custom()
+    pub fn get_mut<E: Engine>(&mut self) -> Option<&mut E> {
+        #[cfg(feature = "tera_templates")]
+        {
+            if let Some(engine) = (&mut self.tera as &mut dyn Any).downcast_mut::<E>() {
+                return Some(engine);
+            }
+        }
+
+        #[cfg(feature = "handlebars_templates")]
+        {
+            if let Some(engine) = (&mut self.handlebars as &mut dyn Any).downcast_mut::<E>() {
+                return Some(engine);
+            }
+        }
+
+        self.registered.iter_mut().find_map(|(_, engine)| engine.as_any_mut().downcast_mut::<E>())
+    }
+
+    fn registered_for(&self, info: &TemplateInfo) -> Option<&dyn DynEngine> {
+        self.registered.iter()
+            .find(|(ext, _)| info.extension == *ext)
+            .map(|(_, engine)| &**engine)
+    }
+
+    pub(crate) fn render(
         &self,
         name: &str,
         info: &TemplateInfo,
-        context: C
+        context: &Value
     ) -> Option<String> {
         #[cfg(feature = "tera_templates")]
         {
@@ -115,6 +352,54 @@ impl Engines {
             }
         }
 
-        None
+        self.registered_for(info)?.render(name, context)
+    }
+
+    pub(crate) fn render_to(
+        &self,
+        name: &str,
+        info: &TemplateInfo,
+        context: &Value,
+        writer: &mut dyn Write,
+    ) -> Option<()> {
+        #[cfg(feature = "tera_templates")]
+        {
+            if info.extension == Tera::EXT {
+                return Engine::render_to(&self.tera, name, context, writer);
+            }
+        }
+
+        #[cfg(feature = "handlebars_templates")]
+        {
+            if info.extension == Handlebars::EXT {
+                return Engine::render_to(&self.handlebars, name, context, writer);
+            }
+        }
+
+        self.registered_for(info)?.render_to(name, context, writer)
+    }
+
+    pub(crate) fn render_block(
+        &self,
+        name: &str,
+        block: &str,
+        info: &TemplateInfo,
+        context: &Value,
+    ) -> Option<String> {
+        #[cfg(feature = "tera_templates")]
+        {
+            if info.extension == Tera::EXT {
+                return Engine::render_block(&self.tera, name, block, context);
+            }
+        }
+
+        #[cfg(feature = "handlebars_templates")]
+        {
+            if info.extension == Handlebars::EXT {
+                return Engine::render_block(&self.handlebars, name, block, context);
+            }
+        }
+
+        self.registered_for(info)?.render_block(name, block, context)
     }
 }
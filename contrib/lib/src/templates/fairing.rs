@@ -1,27 +1,41 @@
-use crate::templates::{DEFAULT_TEMPLATE_DIR, Context, Engines};
+use crate::templates::{DEFAULT_TEMPLATE_DIR, Context, Engine, Engines, Registration};
+use crate::templates::{EmbeddedTemplates, Minify};
+use crate::templates::globals::{Globals, Provider};
 
-use rocket::Rocket;
-use rocket::config::ConfigError;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glob::Pattern;
+use serde::Serialize;
+
+use rocket::{Cargo, Request, Rocket};
+use rocket::config::{Config, ConfigError, ConfigUpdated};
 use rocket::fairing::{Fairing, Info, Kind};
 
 pub(crate) use self::context::ContextManager;
 
 #[cfg(not(debug_assertions))]
 mod context {
-    use std::ops::Deref;
+    use std::sync::{Arc, RwLock};
     use crate::templates::Context;
 
     /// Wraps a Context. With `cfg(debug_assertions)` active, this structure
     /// additionally provides a method to reload the context at runtime.
-    pub(crate) struct ContextManager(Context);
+    pub(crate) struct ContextManager(RwLock<Arc<Context>>);
 
     impl ContextManager {
         pub fn new(ctxt: Context) -> ContextManager {
-            ContextManager(ctxt)
+            ContextManager(RwLock::new(Arc::new(ctxt)))
+        }
+
+        pub fn context(&self) -> Arc<Context> {
+            self.0.read().unwrap().clone()
         }
 
-        pub fn context<'a>(&'a self) -> impl Deref<Target=Context> + 'a {
-            &self.0
+        /// Replaces the current template context with `ctxt`.
+        pub fn replace(&self, ctxt: Context) {
+            *self.0.write().unwrap() = Arc::new(ctxt);
         }
 
         pub fn is_reloading(&self) -> bool {
@@ -32,19 +46,18 @@ mod context {
 
 #[cfg(debug_assertions)]
 mod context {
-    use std::ops::{Deref, DerefMut};
-    use std::sync::{RwLock, Mutex};
+    use std::sync::{Arc, RwLock, Mutex};
     use std::sync::mpsc::{channel, Receiver};
 
     use notify::{raw_watcher, RawEvent, RecommendedWatcher, RecursiveMode, Watcher};
 
-    use crate::templates::{Context, Engines};
+    use crate::templates::{Context, Engines, Registration};
 
     /// Wraps a Context. With `cfg(debug_assertions)` active, this structure
     /// additionally provides a method to reload the context at runtime.
     pub(crate) struct ContextManager {
         /// The current template context, inside an RwLock so it can be updated.
-        context: RwLock<Context>,
+        context: RwLock<Arc<Context>>,
         /// A filesystem watcher and the receive queue for its events.
         watcher: Option<Mutex<(RecommendedWatcher, Receiver<RawEvent>)>>,
     }
@@ -69,27 +82,46 @@ mod context {
 
             ContextManager {
                 watcher,
-                context: RwLock::new(ctxt),
+                context: RwLock::new(Arc::new(ctxt)),
             }
         }
 
-        pub fn context(&self) -> impl Deref<Target=Context> + '_ {
-            self.context.read().unwrap()
+        pub fn context(&self) -> Arc<Context> {
+            self.context.read().unwrap().clone()
         }
 
         pub fn is_reloading(&self) -> bool {
             self.watcher.is_some()
         }
 
-        fn context_mut(&self) -> impl DerefMut<Target=Context> + '_ {
-            self.context.write().unwrap()
+        /// Replaces the current template context with `ctxt`, watching the
+        /// root directory of `ctxt` for changes from now on.
+        pub fn replace(&self, ctxt: Context) {
+            let mut current = self.context.write().unwrap();
+            if let Some(ref watcher) = self.watcher {
+                let watcher = &mut watcher.lock().expect("receive queue lock").0;
+                let _ = current.root.canonicalize().map(|root| watcher.unwatch(root));
+                let rewatched = ctxt.root.canonicalize()
+                    .map_err(notify::Error::from)
+                    .and_then(|root| watcher.watch(root, RecursiveMode::Recursive));
+
+                if let Err(e) = rewatched {
+                    warn_!("Failed to watch new template directory: {}", e);
+                }
+            }
+
+            *current = Arc::new(ctxt);
         }
 
         /// Checks whether any template files have changed on disk. If there
         /// have been changes since the last reload, all templates are
-        /// reinitialized from disk and the user's customization callback is run
-        /// again.
-        pub fn reload_if_needed<F: Fn(&mut Engines)>(&self, custom_callback: F) {
+        /// reinitialized from disk, along with the `registered` engines, and the
+        /// user's customization callback is run again.
+        pub fn reload_if_needed<F: Fn(&mut Engines)>(
+            &self,
+            registered: &[Registration],
+            custom_callback: F
+        ) {
             self.watcher.as_ref().map(|w| {
                 let rx_lock = w.lock().expect("receive queue lock");
                 let mut changed = false;
@@ -99,10 +131,10 @@ mod context {
 
                 if changed {
                     info_!("Change detected: reloading templates.");
-                    let mut ctxt = self.context_mut();
-                    if let Some(mut new_ctxt) = Context::initialize(ctxt.root.clone()) {
+                    let mut ctxt = self.context.write().unwrap();
+                    if let Some(mut new_ctxt) = Context::initialize(ctxt.root.clone(), registered) {
                         custom_callback(&mut new_ctxt.engines);
-                        *ctxt = new_ctxt;
+                        *ctxt = Arc::new(new_ctxt);
                     } else {
                         warn_!("An error occurred while reloading templates.");
                         warn_!("The previous templates will remain active.");
@@ -113,15 +145,235 @@ mod context {
     }
 }
 
-/// The TemplateFairing initializes the template system on attach, running
-/// custom_callback after templates have been loaded. In debug mode, the fairing
-/// checks for modifications to templates before every request and reloads them
-/// if necessary.
+/// Returns the directory templates are loaded from according to `config`: the
+/// `template_dir` config variable or the default ([DEFAULT_TEMPLATE_DIR]).
+fn template_root(config: &Config) -> PathBuf {
+    let mut template_root = config.root_relative(DEFAULT_TEMPLATE_DIR);
+    match config.get_str("template_dir") {
+        Ok(dir) => template_root = config.root_relative(dir),
+        Err(ConfigError::Missing(_)) => { /* ignore missing */ }
+        Err(e) => {
+            e.pretty_print();
+            warn_!("Using default templates directory '{:?}'", template_root);
+        }
+    };
+
+    template_root
+}
+
+/// The fairing that initializes and maintains templating state.
+///
+/// A `TemplateFairing` is created via [`Template::fairing()`] or
+/// [`Template::custom()`]. It initializes the template system on attach,
+/// running the customization callback after templates have been loaded. In
+/// debug mode, the fairing checks for modifications to templates before every
+/// request and reloads them if necessary. When the `template_dir` config
+/// variable is changed by a configuration reload, templates are reloaded from
+/// the new directory.
+///
+/// Template engines beyond those enabled via features are registered with
+/// [`TemplateFairing::engine()`]. Values merged into the context of every
+/// rendered template are registered with [`TemplateFairing::global()`]. The
+/// HTML output of templates is minified in release builds when enabled with
+/// [`TemplateFairing::minify()`].
+///
+/// [`Template::fairing()`]: crate::templates::Template::fairing()
+/// [`Template::custom()`]: crate::templates::Template::custom()
 pub struct TemplateFairing {
     /// The user-provided customization callback, allowing the use of
     /// functionality specific to individual template engines. In debug mode,
     /// this callback might be run multiple times as templates are reloaded.
-    pub custom_callback: Box<dyn Fn(&mut Engines) + Send + Sync + 'static>,
+    pub(crate) custom_callback: Box<dyn Fn(&mut Engines) + Send + Sync + 'static>,
+    /// The engines registered in addition to the enabled ones.
+    pub(crate) registered: Vec<Registration>,
+    /// The global context providers, by key.
+    pub(crate) globals: Vec<(Cow<'static, str>, Arc<Provider>)>,
+    /// The templates to use instead of the template directory in release.
+    pub(crate) embedded: Option<EmbeddedTemplates>,
+    /// The patterns of the templates whose output is minified.
+    pub(crate) minify: Vec<Pattern>,
+    /// Whether minification is active, if set via `minify_enabled()`.
+    /// Otherwise, it is active in release builds.
+    pub(crate) minify_enabled: Option<bool>,
+}
+
+impl TemplateFairing {
+    /// Registers the template engine `E`. Templates ending in `E::EXT` are
+    /// discovered, reloaded, and rendered by `E` like those of the built-in
+    /// engines. See [`Engine`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an engine for the extension `E::EXT` is already enabled or
+    /// registered.
+    pub fn engine<E: Engine>(mut self) -> Self {
+        let taken = Engines::ENABLED_EXTENSIONS.contains(&E::EXT)
+            || self.registered.iter().any(|r| r.ext == E::EXT);
+
+        if taken {
+            panic!("A template engine for the extension '{}' is already registered.", E::EXT);
+        }
+
+        self.registered.push(Registration::of::<E>());
+        self
+    }
+
+    /// Registers `provider` as the source of the global context value `key`.
+    ///
+    /// When a [`Template`] is returned as a response, `provider` is called
+    /// with the request and the value it returns is set as `key` in the
+    /// template's context unless the context already has a value for `key`.
+    /// This makes values that nearly every page needs, such as the current
+    /// user, a CSRF token, flash messages, or an asset manifest, available to
+    /// all templates without adding them to every context by hand. Values
+    /// set during the request with [`Template::set_global()`], for instance
+    /// by another fairing or a request guard, take precedence over those of
+    /// providers. Global values are only merged into contexts that serialize
+    /// to an object or to `null`.
+    ///
+    /// Global values are not merged by [`Template::show()`], which is not
+    /// called with a request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// let fairing = Template::fairing()
+    ///     .global("site_name", |_| "My Site")
+    ///     .global("path", |req| req.uri().path().to_string());
+    ///
+    /// # let _ = rocket::ignite().attach(fairing);
+    /// ```
+    ///
+    /// [`Template`]: crate::templates::Template
+    /// [`Template::set_global()`]: crate::templates::Template::set_global()
+    /// [`Template::show()`]: crate::templates::Template::show()
+    pub fn global<K, F, T>(mut self, key: K, provider: F) -> Self
+        where K: Into<Cow<'static, str>>,
+              F: Fn(&Request<'_>) -> T + Send + Sync + 'static,
+              T: Serialize
+    {
+        let key = key.into();
+        let provider = Globals::provider(&key, provider);
+        self.globals.push((key, provider));
+        self
+    }
+
+    /// Uses the embedded `templates` in release builds.
+    ///
+    /// In release builds, templates are loaded from `templates`, typically
+    /// created with [`embed_templates!`], instead of from the template
+    /// directory, so the application can be deployed without its templates.
+    /// The `template_dir` configuration parameter is then ignored. In debug
+    /// builds, templates are still loaded from the template directory and
+    /// reloaded when they change, so changes are visible without rebuilding,
+    /// unless the directory doesn't exist: the embedded templates are then
+    /// used as well.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use rocket_contrib::templates::{Template, embed_templates};
+    ///
+    /// fn main() {
+    ///     rocket::ignite()
+    ///         .attach(Template::fairing().embed(embed_templates!("templates")))
+    ///         // ...
+    ///     # ;
+    /// }
+    /// ```
+    ///
+    /// [`embed_templates!`]: crate::templates::embed_templates!
+    pub fn embed(mut self, templates: EmbeddedTemplates) -> Self {
+        self.embedded = Some(templates);
+        self
+    }
+
+    /// Minifies the output of the HTML templates whose path matches `glob`
+    /// when minification is active: by default, in release builds.
+    ///
+    /// `glob` is matched against the path of a template relative to the
+    /// template directory, including its extensions, so patterns can select
+    /// templates by directory, by name, or by engine. `*` does not match `/`;
+    /// use `**` to match any number of directories. This method can be called
+    /// multiple times; the output of a template is minified if any of the
+    /// patterns matches. Only templates with an `html` data type are minified.
+    ///
+    /// Minification removes comments, except for conditional comments, and
+    /// collapses every run of whitespace in text into a single space. Tags and
+    /// the contents of `pre`, `textarea`, `script`, and `style` elements are
+    /// left untouched. By default, templates are not minified in debug builds,
+    /// so their output is easy to inspect during development; use
+    /// [`TemplateFairing::minify_enabled()`] to change when minification is
+    /// active. Templates are never minified when they are
+    /// [`streamed`](crate::templates::Template::streamed()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `glob` is not a valid glob pattern.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// // Minify all Tera templates and the Handlebars templates in `pages`.
+    /// let fairing = Template::fairing()
+    ///     .minify("**/*.tera")
+    ///     .minify("pages/**/*.hbs");
+    ///
+    /// # let _ = rocket::ignite().attach(fairing);
+    /// ```
+    pub fn minify(mut self, glob: &str) -> Self {
+        match Pattern::new(glob) {
+            Ok(pattern) => self.minify.push(pattern),
+            Err(e) => panic!("Invalid template minification pattern '{}': {}", glob, e),
+        }
+
+        self
+    }
+
+    /// Sets whether the output of the templates selected via
+    /// [`TemplateFairing::minify()`] is minified, regardless of the build
+    /// profile. By default, it is minified in release builds only.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// // Minify HTML templates in debug builds too.
+    /// let fairing = Template::fairing()
+    ///     .minify("**/*.html.*")
+    ///     .minify_enabled(true);
+    ///
+    /// # let _ = rocket::ignite().attach(fairing);
+    /// ```
+    pub fn minify_enabled(mut self, enabled: bool) -> Self {
+        self.minify_enabled = Some(enabled);
+        self
+    }
+
+    /// Returns the embedded templates if they are used in place of the
+    /// template directory `root`: when templates were embedded, in release
+    /// builds or if `root` doesn't exist.
+    fn embedded(&self, root: &Path) -> Option<&EmbeddedTemplates> {
+        self.embedded.as_ref().filter(|_| !cfg!(debug_assertions) || !root.is_dir())
+    }
+
+    /// Loads the templates, embedded or from the template directory per
+    /// `config`, and runs the customization callback.
+    fn load(&self, config: &Config) -> Option<Context> {
+        let root = template_root(config);
+        let mut ctxt = match self.embedded(&root) {
+            Some(embedded) => Context::embedded(embedded, &self.registered)?,
+            None => Context::initialize(root, &self.registered)?,
+        };
+
+        (self.custom_callback)(&mut ctxt.engines);
+        Some(ctxt)
+    }
 }
 
 #[rocket::async_trait]
@@ -132,7 +384,7 @@ impl Fairing for TemplateFairing {
         #[cfg(debug_assertions)]
         let info = Info {
             name: "Templates",
-            kind: Kind::Attach | Kind::Request,
+            kind: Kind::Attach | Kind::Request | Kind::ConfigUpdate,
         };
 
         // FIXME: We declare two `info` variables here, instead of just one with
@@ -140,35 +392,52 @@ impl Fairing for TemplateFairing {
         #[cfg(not(debug_assertions))]
         let info = Info {
             name: "Templates",
-            kind: Kind::Attach,
+            kind: Kind::Attach | Kind::ConfigUpdate,
         };
 
         info
     }
 
     /// Initializes the template context. Templates will be searched for in the
-    /// `template_dir` config variable or the default ([DEFAULT_TEMPLATE_DIR]).
-    /// The user's callback, if any was supplied, is called to customize the
-    /// template engines. In debug mode, the `ContextManager::new` method
-    /// initializes a directory watcher for auto-reloading of templates.
+    /// `template_dir` config variable or the default ([DEFAULT_TEMPLATE_DIR])
+    /// unless embedded templates are used. The user's callback, if any was
+    /// supplied, is called to customize the template engines. In debug mode,
+    /// the `ContextManager::new` method initializes a directory watcher for
+    /// auto-reloading of templates.
     async fn on_attach(&self, mut rocket: Rocket) -> Result<Rocket, Rocket> {
-        let config = rocket.config().await;
-        let mut template_root = config.root_relative(DEFAULT_TEMPLATE_DIR);
-        match config.get_str("template_dir") {
-            Ok(dir) => template_root = config.root_relative(dir),
-            Err(ConfigError::Missing(_)) => { /* ignore missing */ }
-            Err(e) => {
-                e.pretty_print();
-                warn_!("Using default templates directory '{:?}'", template_root);
+        let ctxt = self.load(rocket.config().await);
+        match ctxt {
+            Some(ctxt) => {
+                Ok(rocket.manage(ContextManager::new(ctxt))
+                    .manage(Globals(self.globals.clone()))
+                    .manage(Minify {
+                        patterns: self.minify.clone(),
+                        enabled: self.minify_enabled.unwrap_or(!cfg!(debug_assertions)),
+                    }))
             }
-        };
+            None => Err(rocket),
+        }
+    }
 
-        match Context::initialize(template_root) {
-            Some(mut ctxt) => {
-                (self.custom_callback)(&mut ctxt.engines);
-                Ok(rocket.manage(ContextManager::new(ctxt)))
+    /// Reloads all templates from the new template directory if a
+    /// configuration reload changed `template_dir`. If the templates fail to
+    /// load, the previous templates remain active.
+    async fn on_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {
+        let embedded_only = self.embedded.is_some() && !cfg!(debug_assertions);
+        if !update.extra_changed("template_dir") || embedded_only {
+            return;
+        }
+
+        let cm = cargo.state::<ContextManager>()
+            .expect("Template ContextManager registered in on_attach");
+
+        info_!("Template directory changed: reloading templates.");
+        match self.load(&update.current) {
+            Some(ctxt) => cm.replace(ctxt),
+            None => {
+                warn_!("An error occurred while reloading templates.");
+                warn_!("The previous templates will remain active.");
             }
-            None => Err(rocket),
         }
     }
 
@@ -177,6 +446,6 @@ impl Fairing for TemplateFairing {
         let cm = req.guard::<rocket::State<'_, ContextManager>>().await
             .expect("Template ContextManager registered in on_attach");
 
-        cm.reload_if_needed(&*self.custom_callback);
+        cm.reload_if_needed(&self.registered, &*self.custom_callback);
     }
 }
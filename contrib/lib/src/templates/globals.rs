@@ -0,0 +1,75 @@
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::{Map, Value, to_value};
+
+use rocket::Request;
+
+/// A provider of a global context value, registered via
+/// `TemplateFairing::global()`.
+pub(crate) type Provider = dyn Fn(&Request<'_>) -> Option<Value> + Send + Sync;
+
+/// The global context providers of the template fairing, by key.
+pub(crate) struct Globals(pub Vec<(Cow<'static, str>, Arc<Provider>)>);
+
+/// Global context values set during a request via `Template::set_global()`.
+#[derive(Default)]
+struct RequestGlobals(Mutex<Map<String, Value>>);
+
+impl Globals {
+    /// Wraps `provider` in a `Provider` that serializes its values.
+    pub fn provider<F, T>(key: &str, provider: F) -> Arc<Provider>
+        where F: Fn(&Request<'_>) -> T + Send + Sync + 'static, T: Serialize
+    {
+        let key = key.to_string();
+        Arc::new(move |req: &Request<'_>| match to_value(provider(req)) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn_!("Global template context value '{}' failed to serialize: {}", key, e);
+                None
+            }
+        })
+    }
+}
+
+/// Sets the global context value `key` to `value` for the remainder of the
+/// request `req`.
+pub(crate) fn set<T: Serialize>(req: &Request<'_>, key: String, value: T) {
+    match to_value(value) {
+        Ok(value) => {
+            let globals = req.local_cache(RequestGlobals::default);
+            globals.0.lock().expect("request globals lock").insert(key, value);
+        }
+        Err(e) => warn_!("Global template context value '{}' failed to serialize: {}", key, e),
+    }
+}
+
+/// Merges the global context values for `req` into `value`. Values set in
+/// `value` take precedence over those set via `set()`, which take precedence
+/// over those of the providers in `globals`. Contexts that aren't objects, or
+/// `null`, are returned unchanged.
+pub(crate) fn merge(req: &Request<'_>, globals: Option<&Globals>, value: Value) -> Value {
+    let mut map = match value {
+        Value::Object(map) => map,
+        Value::Null => Map::new(),
+        value => return value,
+    };
+
+    let request_globals = req.local_cache(RequestGlobals::default);
+    for (key, value) in request_globals.0.lock().expect("request globals lock").iter() {
+        if !map.contains_key(key) {
+            map.insert(key.clone(), value.clone());
+        }
+    }
+
+    for (key, provider) in globals.iter().flat_map(|globals| globals.0.iter()) {
+        if !map.contains_key(&**key) {
+            if let Some(value) = provider(req) {
+                map.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    Value::Object(map)
+}
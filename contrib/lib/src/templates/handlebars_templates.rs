@@ -1,16 +1,18 @@
+use std::io::Write;
+use std::path::Path;
+
 use serde::Serialize;
 
-use crate::templates::{Engine, TemplateInfo};
+use crate::templates::Engine;
 
 pub use crate::templates::handlebars::Handlebars;
 
 impl Engine for Handlebars<'static> {
     const EXT: &'static str = "hbs";
 
-    fn init(templates: &[(&str, &TemplateInfo)]) -> Option<Handlebars<'static>> {
+    fn init(templates: &[(&str, &Path)]) -> Option<Handlebars<'static>> {
         let mut hb = Handlebars::new();
-        for &(name, info) in templates {
-            let path = &info.path;
+        for &(name, path) in templates {
             if let Err(e) = hb.register_template_file(name, path) {
                 error!("Error in Handlebars template '{}'.", name);
                 info_!("{}", e);
@@ -22,6 +24,20 @@ impl Engine for Handlebars<'static> {
         Some(hb)
     }
 
+    fn init_embedded(templates: &[(&str, &Path, &str)]) -> Option<Handlebars<'static>> {
+        let mut hb = Handlebars::new();
+        for &(name, path, source) in templates {
+            if let Err(e) = hb.register_template_string(name, source) {
+                error!("Error in Handlebars template '{}'.", name);
+                info_!("{}", e);
+                info_!("Template path: '{}'.", path.to_string_lossy());
+                return None;
+            }
+        }
+
+        Some(hb)
+    }
+
     fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String> {
         if self.get_template(name).is_none() {
             error_!("Handlebars template '{}' does not exist.", name);
@@ -36,4 +52,19 @@ impl Engine for Handlebars<'static> {
             }
         }
     }
+
+    fn render_to<C: Serialize>(&self, name: &str, context: C, writer: &mut dyn Write) -> Option<()> {
+        if self.get_template(name).is_none() {
+            error_!("Handlebars template '{}' does not exist.", name);
+            return None;
+        }
+
+        match Handlebars::render_to_write(self, name, &context, writer) {
+            Ok(()) => Some(()),
+            Err(e) => {
+                error_!("Error rendering Handlebars template '{}': {}", name, e);
+                None
+            }
+        }
+    }
 }
@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern};
+
+use rocket::http::ContentType;
+
+use crate::templates::TemplateInfo;
+
+/// Elements whose content is copied verbatim.
+const RAW_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+/// The patterns of the templates whose output is minified, registered via
+/// `TemplateFairing::minify()`, and whether minification is active.
+pub(crate) struct Minify {
+    pub patterns: Vec<Pattern>,
+    pub enabled: bool,
+}
+
+impl Minify {
+    /// Returns `true` if the output of the template `info` in the template
+    /// directory `root` is minified: when minification is active, for HTML
+    /// templates whose path relative to `root` matches one of the patterns.
+    pub fn applies(&self, root: &Path, info: &TemplateInfo) -> bool {
+        if !self.enabled || info.data_type != ContentType::HTML {
+            return false;
+        }
+
+        let options = MatchOptions { require_literal_separator: true, ..MatchOptions::new() };
+        let path = info.path.strip_prefix(root).unwrap_or(&info.path);
+        self.patterns.iter().any(|pattern| pattern.matches_path_with(path, options))
+    }
+}
+
+/// Minifies `html` by removing comments and collapsing runs of whitespace in
+/// text into a single space. Tags, conditional comments, and the contents of
+/// `pre`, `textarea`, `script`, and `style` elements are left untouched.
+pub(crate) fn html(html: &str) -> String {
+    // ASCII lowercasing preserves byte offsets, so closing tags of raw
+    // elements are found in `lower` at the same offsets as in `html`.
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut i = 0;
+    while i < html.len() {
+        let rest = &html[i..];
+        if rest.starts_with("<!--") && !rest.starts_with("<!--[if") {
+            i += rest.find("-->").map_or(rest.len(), |end| end + 3);
+        } else if rest.starts_with('<') {
+            let tag = &rest[..tag_end(rest)];
+            out.push_str(tag);
+            i += tag.len();
+
+            if let Some(name) = raw_element(tag) {
+                let close = lower[i..].find(&format!("</{}", name))
+                    .map_or(html.len(), |close| i + close);
+
+                out.push_str(&html[i..close]);
+                i = close;
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            collapse_whitespace(&rest[..end], &mut out);
+            i += end;
+        }
+    }
+
+    out.trim().to_string()
+}
+
+/// Returns the index just past the end of the tag at the start of `html`,
+/// ignoring `>` in quoted attribute values.
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (i, b) in html.bytes().enumerate() {
+        match (quote, b) {
+            (None, b'>') => return i + 1,
+            (None, b'"') | (None, b'\'') => quote = Some(b),
+            (Some(q), b) if q == b => quote = None,
+            _ => {}
+        }
+    }
+
+    html.len()
+}
+
+/// Returns the name of the element opened by `tag` if its contents are copied
+/// verbatim.
+fn raw_element(tag: &str) -> Option<&'static str> {
+    if tag.ends_with("/>") {
+        return None;
+    }
+
+    let name = tag[1..].split(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("");
+
+    RAW_ELEMENTS.iter().copied().find(|raw| raw.eq_ignore_ascii_case(name))
+}
+
+/// Pushes `text` to `out`, replacing every run of ASCII whitespace with a
+/// single space. Other whitespace, such as non-breaking spaces, is preserved.
+/// Whitespace following a space in `out`, left by text before a removed
+/// comment, is dropped.
+fn collapse_whitespace(text: &str, out: &mut String) {
+    let mut in_whitespace = out.ends_with(' ');
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !in_whitespace {
+                out.push(' ');
+            }
+
+            in_whitespace = true;
+        } else {
+            out.push(c);
+            in_whitespace = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::html;
+
+    #[test]
+    fn collapses_whitespace_and_removes_comments() {
+        let input = concat!(
+            "<html>\n  <body>\n    <!-- a comment -->\n",
+            "    <p>Hello,\n\t world!</p>\n  </body>\n</html>\n"
+        );
+
+        assert_eq!(html(input), "<html> <body> <p>Hello, world!</p> </body> </html>");
+    }
+
+    #[test]
+    fn preserves_tags_and_raw_elements() {
+        let input = "<a title=\"a  > b\">x</a> <pre>\n  keep\n</pre> <SCRIPT>a  <b</SCRIPT>";
+        assert_eq!(html(input), input);
+
+        let input = "<!--[if IE]><p>IE</p><![endif]--> <textarea>\n\n</textarea>";
+        assert_eq!(html(input), input);
+    }
+
+    #[test]
+    fn preserves_non_ascii_whitespace() {
+        assert_eq!(html("<p>a\u{a0}\u{a0}b  c</p>"), "<p>a\u{a0}\u{a0}b c</p>");
+    }
+}
@@ -1,4 +1,4 @@
-//! Dynamic template engine support for handlebars and tera.
+//! Dynamic template engine support for handlebars, tera, and custom engines.
 //!
 //! # Overview
 //!
@@ -71,6 +71,10 @@
 //! [Tera]: https://docs.rs/crate/tera/1
 //! [Handlebars]: https://docs.rs/crate/handlebars/2
 //!
+//! Other engines can be used by implementing [`Engine`] and registering the
+//! implementation with [`TemplateFairing::engine()`]; see [Custom
+//! Engines](#custom-engines).
+//!
 //! Any file that ends with one of these extension will be discovered and
 //! rendered with the corresponding templating engine. The _name_ of the
 //! template will be the path to the template file relative to `template_dir`
@@ -109,7 +113,101 @@
 //! the templates directory since the previous request. In release builds,
 //! template reloading is disabled to improve performance and cannot be enabled.
 //!
+//! ## Embedding
+//!
+//! Templates can be embedded into the application at build time with
+//! [`embed_templates!`] and registered with [`TemplateFairing::embed()`]. In
+//! release builds, the embedded templates are then used in place of the
+//! template directory, which need not be deployed with the application. In
+//! debug builds, templates are still loaded from the template directory and
+//! reloaded when they change, unless the directory doesn't exist.
+//!
+//! ```rust,ignore
+//! use rocket_contrib::templates::{Template, embed_templates};
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Template::fairing().embed(embed_templates!("templates")))
+//!         // ...
+//!     # ;
+//! }
+//! ```
+//!
+//! The template directory passed to `embed_templates!` is relative to the
+//! crate's root. Only files with a `tera` or `hbs` extension are embedded,
+//! along with those with an extension passed after the directory, such as
+//! that of a [custom engine](crate::templates::TemplateFairing::engine()). Changes
+//! to embedded templates cause the application to be rebuilt, but adding or
+//! removing templates doesn't; touch a source file to pick up new templates.
+//!
+//! ## Minification
+//!
+//! The HTML output of templates can be minified in release builds, or
+//! whenever enabled with [`TemplateFairing::minify_enabled()`], by registering
+//! glob patterns that select templates with [`TemplateFairing::minify()`].
+//! Minification removes comments and collapses whitespace in text, leaving tags
+//! and preformatted content untouched:
+//!
+//! ```rust
+//! use rocket_contrib::templates::Template;
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Template::fairing().minify("**/*.html.*"))
+//!         // ...
+//!     # ;
+//! }
+//! ```
+//!
+//! ## Custom Engines
+//!
+//! Any template engine can be used with the same [`Template::render()`] API,
+//! fairing, and reloading behavior as Tera and Handlebars by implementing the
+//! [`Engine`] trait for it and registering it with the template fairing:
+//!
+//! ```rust
+//! # use std::path::Path;
+//! # use serde::Serialize;
+//! use rocket_contrib::templates::{Engine, Template};
+//!
+//! struct Liquid { /* .. */ }
+//!
+//! impl Engine for Liquid {
+//!     const EXT: &'static str = "liquid";
+//!
+//!     fn init(templates: &[(&str, &Path)]) -> Option<Self> {
+//!         /* parse the templates */
+//!         # Some(Liquid { })
+//!     }
+//!
+//!     fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String> {
+//!         /* render the template named `name` */
+//!         # None
+//!     }
+//! }
+//!
+//! fn main() {
+//!     rocket::ignite()
+//!         .attach(Template::fairing().engine::<Liquid>())
+//!         // ...
+//!     # ;
+//! }
+//! ```
+//!
+//! Templates ending in `.liquid` are then discovered and named as described
+//! above and rendered by the registered engine. Registered engines are
+//! available in the customization callback via [`Engines::get_mut()`]. To use
+//! custom engines exclusively, enable the `templates` feature instead of
+//! `handlebars_templates` or `tera_templates`.
+//!
 //! [`Serialize`]: serde::Serialize
+//! [`Engine`]: crate::templates::Engine
+//! [`Engines::get_mut()`]: crate::templates::Engines::get_mut()
+//! [`TemplateFairing::engine()`]: crate::templates::TemplateFairing::engine()
+//! [`TemplateFairing::embed()`]: crate::templates::TemplateFairing::embed()
+//! [`TemplateFairing::minify()`]: crate::templates::TemplateFairing::minify()
+//! [`TemplateFairing::minify_enabled()`]: crate::templates::TemplateFairing::minify_enabled()
+//! [`embed_templates!`]: crate::templates::embed_templates!
 //! [`Template`]: crate::templates::Template
 //! [`Template::fairing()`]: crate::templates::Template::fairing()
 //! [`Template::custom()`]: crate::templates::Template::custom()
@@ -125,25 +223,31 @@ mod engine;
 mod fairing;
 mod context;
 mod metadata;
+mod stream;
+mod globals;
+mod embedded;
+mod minify;
 
-pub use self::engine::Engines;
+pub use self::engine::{Engine, Engines};
+pub use self::fairing::TemplateFairing;
 pub use self::metadata::Metadata;
+pub use self::embedded::EmbeddedTemplates;
+pub use rocket_contrib_codegen::embed_templates;
 pub(crate) use self::context::Context;
 pub(crate) use self::fairing::ContextManager;
-
-use self::engine::Engine;
-use self::fairing::TemplateFairing;
+pub(crate) use self::engine::Registration;
+pub(crate) use self::minify::Minify;
 
 use serde::Serialize;
 use serde_json::{Value, to_value};
 
 use std::borrow::Cow;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use rocket::Cargo;
 use rocket::request::Request;
-use rocket::fairing::Fairing;
-use rocket::response::{self, Content, Responder};
+use rocket::response::{self, Content, Response, Responder};
 use rocket::http::{ContentType, Status};
 
 const DEFAULT_TEMPLATE_DIR: &str = "templates";
@@ -163,8 +267,8 @@ const DEFAULT_TEMPLATE_DIR: &str = "templates";
 /// features = ["handlebars_templates", "tera_templates"]
 /// ```
 ///
-/// Then, ensure that the template [`Fairing`] is attached to your Rocket
-/// application:
+/// Then, ensure that the template [`Fairing`](rocket::fairing::Fairing) is
+/// attached to your Rocket application:
 ///
 /// ```rust
 /// # extern crate rocket;
@@ -200,20 +304,48 @@ const DEFAULT_TEMPLATE_DIR: &str = "templates";
 /// You may use the [`Template::custom()`] method to construct a fairing with
 /// customized templating engines. Among other things, this method allows you to
 /// register template helpers and register templates from strings.
+///
+/// # Global Context
+///
+/// Values that most templates need, such as the current user or a CSRF token,
+/// can be merged into the context of every template returned as a response
+/// instead of being added by every handler. See [`TemplateFairing::global()`]
+/// and [`Template::set_global()`].
+///
+/// # Streaming
+///
+/// By default, a template is rendered into a `String` in its entirety before
+/// the response is sent. For large pages, calling [`Template::streamed()`]
+/// instead renders the template on a blocking thread directly into the
+/// response body, so the client begins receiving output before rendering has
+/// finished and the full page is never held in memory. Both Tera and
+/// Handlebars support incremental rendering.
+///
+/// Because the status and headers of a streamed response are sent before
+/// rendering completes, a template that fails to render _after_ it has begun
+/// streaming results in a truncated response instead of a `500` error. Errors
+/// detected before rendering begins, such as a missing template or a context
+/// that fails to serialize, still result in a `500`. Streamed templates are
+/// not minified.
 #[derive(Debug)]
 pub struct Template {
     name: Cow<'static, str>,
-    value: Option<Value>
+    value: Option<Value>,
+    block: Option<Cow<'static, str>>,
+    streamed: bool,
 }
 
 #[derive(Debug)]
 pub(crate) struct TemplateInfo {
-    /// The complete path, including `template_dir`, to this template.
+    /// The complete path, including `template_dir`, to this template, or the
+    /// path relative to the template directory if the template is embedded.
     path: PathBuf,
     /// The extension for the engine of this template.
     extension: String,
     /// The extension before the engine extension in the template, if any.
-    data_type: ContentType
+    data_type: ContentType,
+    /// The source of this template if it is embedded.
+    source: Option<&'static str>,
 }
 
 impl Template {
@@ -247,7 +379,7 @@ impl Template {
     ///     # ;
     /// }
     /// ```
-    pub fn fairing() -> impl Fairing {
+    pub fn fairing() -> TemplateFairing {
         Template::custom(|_| {})
     }
 
@@ -275,10 +407,17 @@ impl Template {
     ///     # ;
     /// }
     /// ```
-    pub fn custom<F>(f: F) -> impl Fairing
+    pub fn custom<F>(f: F) -> TemplateFairing
         where F: Fn(&mut Engines) + Send + Sync + 'static
     {
-        TemplateFairing { custom_callback: Box::new(f) }
+        TemplateFairing {
+            custom_callback: Box::new(f),
+            registered: vec![],
+            globals: vec![],
+            embedded: None,
+            minify: vec![],
+            minify_enabled: None,
+        }
     }
 
     /// Render the template named `name` with the context `context`. The
@@ -302,7 +441,107 @@ impl Template {
     pub fn render<S, C>(name: S, context: C) -> Template
         where S: Into<Cow<'static, str>>, C: Serialize
     {
-        Template { name: name.into(), value: to_value(context).ok() }
+        Template { name: name.into(), value: to_value(context).ok(), block: None, streamed: false }
+    }
+
+    /// Render only the block named `block` of the template named `name` with
+    /// the context `context`. The block is rendered as it would be in the
+    /// full template, but nothing outside of it is. This allows a handler to
+    /// respond with a fragment of a page, for instance to update part of a
+    /// page in place, without maintaining a separate template for the
+    /// fragment.
+    ///
+    /// Rendering blocks is engine-permitting. Tera supports rendering any
+    /// `{% block %}` whose contents don't call `super()` of the templates
+    /// Rocket loads, from the template directory or
+    /// [embedded](crate::templates::embed_templates!). Tera has no public
+    /// interface for rendering a single block, so Rocket prepares one
+    /// template per block when it loads templates; templates added directly
+    /// to [`Engines::tera`] in a [`Template::custom()`] callback have no
+    /// renderable blocks. Handlebars doesn't support rendering blocks; custom
+    /// engines may, by implementing [`Engine::render_block()`]. Rendering a
+    /// block that isn't supported results in a `500`. Fragments are never
+    /// [streamed](Template::streamed()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// # use std::collections::HashMap;
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// #[get("/rows/<id>")]
+    /// fn row(id: usize) -> Template {
+    ///     # let context: HashMap<&str, usize> = HashMap::new();
+    ///     Template::render_block("table", "row", &context)
+    /// }
+    /// ```
+    #[inline]
+    pub fn render_block<S, B, C>(name: S, block: B, context: C) -> Template
+        where S: Into<Cow<'static, str>>, B: Into<Cow<'static, str>>, C: Serialize
+    {
+        Template { block: Some(block.into()), ..Template::render(name, context) }
+    }
+
+    /// Sets the global context value `key` to `value` for the remainder of the
+    /// request `req`. When a [`Template`] is returned in response to `req`,
+    /// `value` is set as `key` in its context unless the context already has
+    /// a value for `key`. This allows fairings and request guards to make
+    /// values available to every template; see
+    /// [`TemplateFairing::global()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::{Request, Data};
+    /// use rocket::fairing::{Fairing, Info, Kind};
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// struct Locale;
+    ///
+    /// #[rocket::async_trait]
+    /// impl Fairing for Locale {
+    ///     fn info(&self) -> Info {
+    ///         Info { name: "Locale", kind: Kind::Request }
+    ///     }
+    ///
+    ///     async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+    ///         let locale = req.headers().get_one("Accept-Language")
+    ///             .unwrap_or("en")
+    ///             .to_string();
+    ///
+    ///         Template::set_global(req, "locale", locale);
+    ///     }
+    /// }
+    /// ```
+    pub fn set_global<K, T>(req: &Request<'_>, key: K, value: T)
+        where K: Into<String>, T: Serialize
+    {
+        globals::set(req, key.into(), value);
+    }
+
+    /// Renders `self` incrementally into the response body instead of into a
+    /// `String`. See [Streaming](#streaming) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate rocket_contrib;
+    /// # use std::collections::HashMap;
+    /// use rocket_contrib::templates::Template;
+    ///
+    /// #[get("/report")]
+    /// fn report() -> Template {
+    ///     # let rows: HashMap<&str, &str> = HashMap::new();
+    ///     Template::render("report", &rows).streamed()
+    /// }
+    /// ```
+    #[inline]
+    pub fn streamed(mut self) -> Template {
+        self.streamed = true;
+        self
     }
 
     /// Render the template named `name` with the context `context` into a
@@ -351,15 +590,74 @@ impl Template {
             None
         })?;
 
-        Template::render(name, context).finalize(&ctxt).ok().map(|v| v.0)
+        let minify = cargo.state::<Minify>();
+        Template::render(name, context).finalize(&ctxt, minify).ok().map(|v| v.0)
     }
 
-    /// Actually render this template given a template context. This method is
-    /// called by the `Template` `Responder` implementation as well as
-    /// `Template::show()`.
+    /// Actually render this template given a template context, minifying the
+    /// output if `minify` applies to it. This method is called by the
+    /// `Template` `Responder` implementation as well as `Template::show()`.
     #[inline(always)]
-    fn finalize(self, ctxt: &Context) -> Result<(String, ContentType), Status> {
+    fn finalize(
+        self,
+        ctxt: &Context,
+        minify: Option<&Minify>
+    ) -> Result<(String, ContentType), Status> {
         let name = &*self.name;
+        let (info, value) = Template::prepare(name, self.value, ctxt)?;
+        let string = match self.block {
+            Some(ref block) => ctxt.engines.render_block(name, block, &info, &value),
+            None => ctxt.engines.render(name, &info, &value),
+        };
+
+        let mut string = string.ok_or_else(|| {
+            error_!("Template '{}' failed to render.", name);
+            Status::InternalServerError
+        })?;
+
+        if minify.map_or(false, |minify| minify.applies(&ctxt.root, info)) {
+            string = minify::html(&string);
+        }
+
+        Ok((string, info.data_type.clone()))
+    }
+
+    /// Renders this template into the body of a streamed response on a
+    /// blocking thread. Called by the `Template` `Responder` implementation
+    /// when `streamed` is set.
+    fn finalize_streamed(self, ctxt: Arc<Context>) -> response::Result<'static> {
+        let name = self.name;
+        let (content_type, value) = {
+            let (info, value) = Template::prepare(&name, self.value, &ctxt)?;
+            (info.data_type.clone(), value)
+        };
+
+        let (mut writer, reader) = stream::channel();
+        tokio::task::spawn_blocking(move || {
+            use std::io::Write;
+
+            let info = &ctxt.templates[&*name];
+            let rendered = ctxt.engines.render_to(&name, info, &value, &mut writer)
+                .and_then(|_| writer.flush().ok());
+
+            if rendered.is_none() {
+                error_!("Template '{}' failed to render. The response is truncated.", name);
+            }
+        });
+
+        Response::build()
+            .header(content_type)
+            .streamed_body(reader)
+            .ok()
+    }
+
+    /// Looks up the template `name` in `ctxt` and checks that its context was
+    /// successfully serialized.
+    fn prepare<'c, V>(
+        name: &str,
+        value: Option<V>,
+        ctxt: &'c Context
+    ) -> Result<(&'c TemplateInfo, V), Status> {
         let info = ctxt.templates.get(name).ok_or_else(|| {
             let ts: Vec<_> = ctxt.templates.keys().map(|s| s.as_str()).collect();
             error_!("Template '{}' does not exist.", name);
@@ -368,36 +666,42 @@ impl Template {
             Status::InternalServerError
         })?;
 
-        let value = self.value.ok_or_else(|| {
+        let value = value.ok_or_else(|| {
             error_!("The provided template context failed to serialize.");
             Status::InternalServerError
         })?;
 
-        let string = ctxt.engines.render(name, &info, value).ok_or_else(|| {
-            error_!("Template '{}' failed to render.", name);
-            Status::InternalServerError
-        })?;
-
-        Ok((string, info.data_type.clone()))
+        Ok((info, value))
     }
 }
 
 /// Returns a response with the Content-Type derived from the template's
 /// extension and a fixed-size body containing the rendered template. If
 /// rendering fails, an `Err` of `Status::InternalServerError` is returned.
+///
+/// Global context values, registered via [`TemplateFairing::global()`] or set
+/// via [`Template::set_global()`], are merged into the template's context
+/// before it is rendered.
+///
+/// If the template is [`streamed`](Template::streamed()), the body is instead
+/// a streamed body that is written to as the template renders.
 impl<'r> Responder<'r, 'static> for Template {
-    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
-        let (render, content_type) = {
-            let ctxt = req.managed_state::<ContextManager>().ok_or_else(|| {
-                error_!("Uninitialized template context: missing fairing.");
-                info_!("To use templates, you must attach `Template::fairing()`.");
-                info_!("See the `Template` documentation for more information.");
-                Status::InternalServerError
-            })?.context();
-
-            self.finalize(&ctxt)?
-        };
+    fn respond_to(mut self, req: &'r Request<'_>) -> response::Result<'static> {
+        let ctxt = req.managed_state::<ContextManager>().ok_or_else(|| {
+            error_!("Uninitialized template context: missing fairing.");
+            info_!("To use templates, you must attach `Template::fairing()`.");
+            info_!("See the `Template` documentation for more information.");
+            Status::InternalServerError
+        })?.context();
+
+        let globals = req.managed_state::<globals::Globals>();
+        self.value = self.value.map(|value| globals::merge(req, globals, value));
+        if self.streamed && self.block.is_none() {
+            return self.finalize_streamed(ctxt);
+        }
 
+        let minify = req.managed_state::<Minify>();
+        let (render, content_type) = self.finalize(&ctxt, minify)?;
         Content(content_type, render).respond_to(req)
     }
 }
@@ -0,0 +1,77 @@
+use std::io::{self, Read, Write, Cursor};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use rocket::futures::{ready, executor::block_on};
+use rocket::response::DEFAULT_CHUNK_SIZE;
+use tokio::io::AsyncRead;
+use tokio::sync::mpsc;
+
+/// The number of rendered chunks that may be buffered before rendering blocks
+/// waiting for the client to catch up.
+const MAX_PENDING_CHUNKS: usize = 4;
+
+/// Returns a connected writer/reader pair. Bytes written to the writer on a
+/// blocking thread are made available to the reader in chunks of at most
+/// `DEFAULT_CHUNK_SIZE` bytes.
+pub(crate) fn channel() -> (ChunkWriter, ChunkReader) {
+    let (tx, rx) = mpsc::channel(MAX_PENDING_CHUNKS);
+    let writer = ChunkWriter { tx, buffer: Vec::with_capacity(DEFAULT_CHUNK_SIZE) };
+    let reader = ChunkReader { rx, chunk: Cursor::new(vec![]) };
+    (writer, reader)
+}
+
+/// The rendering side of a template stream. Must only be used from a blocking
+/// context: writes block while the reader is `MAX_PENDING_CHUNKS` behind.
+pub(crate) struct ChunkWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= DEFAULT_CHUNK_SIZE {
+            self.flush()?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(DEFAULT_CHUNK_SIZE));
+        block_on(self.tx.send(chunk)).map_err(|_| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected during render")
+        })
+    }
+}
+
+/// The response side of a template stream.
+pub(crate) struct ChunkReader {
+    rx: mpsc::Receiver<Vec<u8>>,
+    chunk: Cursor<Vec<u8>>,
+}
+
+impl AsyncRead for ChunkReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let n = self.chunk.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Poll::Ready(Ok(n));
+            }
+
+            match ready!(self.rx.poll_recv(cx)) {
+                Some(chunk) => self.chunk = Cursor::new(chunk),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+    }
+}
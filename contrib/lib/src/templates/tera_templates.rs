@@ -1,70 +1,171 @@
 use serde::Serialize;
 use std::error::Error;
+use std::io::Write;
+use std::path::Path;
 
-use crate::templates::{Engine, TemplateInfo};
+use crate::templates::Engine;
 
 pub use crate::templates::tera::{Context, Tera};
+use crate::templates::tera::{Template, ast::{Node, WS}};
 
 impl Engine for Tera {
     const EXT: &'static str = "tera";
 
-    fn init(templates: &[(&str, &TemplateInfo)]) -> Option<Tera> {
+    fn init(templates: &[(&str, &Path)]) -> Option<Tera> {
         // Create the Tera instance.
-        let mut tera = Tera::default();
-        let ext = [".html.tera", ".htm.tera", ".xml.tera", ".html", ".htm", ".xml"];
-        tera.autoescape_on(ext.to_vec());
+        let mut tera = new_tera();
 
         // Collect into a tuple of (name, path) for Tera.
         let tera_templates = templates.iter()
-            .map(|&(name, info)| (&info.path, Some(name)))
+            .map(|&(name, path)| (path, Some(name)))
             .collect::<Vec<_>>();
 
         // Finally try to tell Tera about all of the templates.
-        if let Err(e) = tera.add_template_files(tera_templates) {
-            error!("Failed to initialize Tera templating.");
+        let result = tera.add_template_files(tera_templates);
+        finish(tera, result)
+    }
 
-            let mut error = Some(&e as &dyn Error);
-            while let Some(err) = error {
-                info_!("{}", err);
-                error = err.source();
+    fn init_embedded(templates: &[(&str, &Path, &str)]) -> Option<Tera> {
+        let mut tera = new_tera();
+        let sources = templates.iter().map(|&(name, _, source)| (name, source));
+        let result = tera.add_raw_templates(sources).map(|_| {
+            // Tera decides whether to autoescape by a template's path.
+            for &(name, path, _) in templates {
+                if let Some(template) = tera.templates.get_mut(name) {
+                    template.path = Some(path.to_string_lossy().into_owned());
+                }
             }
+        });
 
-            None
-        } else {
-            Some(tera)
-        }
+        finish(tera, result)
     }
 
     fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String> {
-        if self.get_template(name).is_err() {
-            error_!("Tera template '{}' does not exist.", name);
-            return None;
-        };
-
-        let tera_ctx = match Context::from_serialize(context) {
-            Ok(ctx) => ctx,
-            Err(_) => {
-                error_!(
-                    "Error generating context when rendering Tera template '{}'.",
-                    name
-                );
-                return None;
-            }
-        };
-
+        let tera_ctx = tera_context(self, name, context)?;
         match Tera::render(self, name, &tera_ctx) {
             Ok(string) => Some(string),
             Err(e) => {
-                error_!("Error rendering Tera template '{}'.", name);
+                log_render_error(name, &e);
+                None
+            }
+        }
+    }
 
-                let mut error = Some(&e as &dyn Error);
-                while let Some(err) = error {
-                    error_!("{}", err);
-                    error = err.source();
-                }
+    fn render_to<C: Serialize>(&self, name: &str, context: C, writer: &mut dyn Write) -> Option<()> {
+        let tera_ctx = tera_context(self, name, context)?;
+        match Tera::render_to(self, name, &tera_ctx, writer) {
+            Ok(()) => Some(()),
+            Err(e) => {
+                log_render_error(name, &e);
+                None
+            }
+        }
+    }
 
+    fn render_block<C: Serialize>(&self, name: &str, block: &str, context: C) -> Option<String> {
+        let tera_ctx = tera_context(self, name, context)?;
+        let block_name = block_template_name(name, block);
+        if self.get_template(&block_name).is_err() {
+            error_!("Tera template '{}' has no block '{}'.", name, block);
+            return None;
+        }
+
+        match Tera::render(self, &block_name, &tera_ctx) {
+            Ok(string) => Some(string),
+            Err(e) => {
+                log_render_error(name, &e);
                 None
             }
         }
     }
 }
+
+/// Returns a `Tera` instance that autoescapes HTML and XML templates.
+fn new_tera() -> Tera {
+    let mut tera = Tera::default();
+    let ext = [".html.tera", ".htm.tera", ".xml.tera", ".html", ".htm", ".xml"];
+    tera.autoescape_on(ext.to_vec());
+    tera
+}
+
+/// Returns `tera` if its templates were added successfully, as indicated by
+/// `result`, and logs the error otherwise.
+fn finish(mut tera: Tera, result: crate::templates::tera::Result<()>) -> Option<Tera> {
+    if let Err(e) = result {
+        error!("Failed to initialize Tera templating.");
+
+        let mut error = Some(&e as &dyn Error);
+        while let Some(err) = error {
+            info_!("{}", err);
+            error = err.source();
+        }
+
+        None
+    } else {
+        add_block_templates(&mut tera);
+        Some(tera)
+    }
+}
+
+/// Returns the name of the template that renders only the block `block` of
+/// the template `name`.
+fn block_template_name(name: &str, block: &str) -> String {
+    format!("{}#{}", name, block)
+}
+
+/// Adds a template for every block of every template in `tera` that renders
+/// only that block, as it would be rendered in the full template: with the
+/// definitions of the most derived template, including nested blocks. Tera
+/// renders a template from the root of its inheritance chain, so these
+/// templates have no parents, and blocks that call `super()` can't be rendered
+/// on their own.
+///
+/// Tera has no public interface for rendering a block, so this relies on the
+/// undocumented `templates` field and `Template` type. Only the templates in
+/// `tera` when this is called have block templates: those Rocket loads, but
+/// not those added by a `Template::custom()` callback afterwards.
+fn add_block_templates(tera: &mut Tera) {
+    let block_templates = tera.templates.values()
+        .filter(|template| !template.name.contains('#'))
+        .flat_map(|template| template.blocks_definitions.iter().map(move |(block, defs)| {
+            let mut block_template = template.clone();
+            block_template.name = block_template_name(&template.name, block);
+            block_template.ast = vec![Node::Block(WS::default(), defs[0].1.clone(), WS::default())];
+            block_template.parent = None;
+            block_template.parents = vec![];
+            block_template
+        }))
+        .collect::<Vec<Template>>();
+
+    for template in block_templates {
+        tera.templates.insert(template.name.clone(), template);
+    }
+}
+
+fn tera_context<C: Serialize>(tera: &Tera, name: &str, context: C) -> Option<Context> {
+    if tera.get_template(name).is_err() {
+        error_!("Tera template '{}' does not exist.", name);
+        return None;
+    };
+
+    match Context::from_serialize(context) {
+        Ok(ctx) => Some(ctx),
+        Err(_) => {
+            error_!(
+                "Error generating context when rendering Tera template '{}'.",
+                name
+            );
+            None
+        }
+    }
+}
+
+fn log_render_error(name: &str, e: &crate::templates::tera::Error) {
+    error_!("Error rendering Tera template '{}'.", name);
+
+    let mut error = Some(e as &dyn Error);
+    while let Some(err) = error {
+        error_!("{}", err);
+        error = err.source();
+    }
+}
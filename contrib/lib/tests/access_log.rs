@@ -0,0 +1,117 @@
+#[macro_use]
+#[cfg(feature = "access_log")]
+extern crate rocket;
+
+#[cfg(feature = "access_log")]
+mod access_log_tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rocket::config::{Config, Environment};
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+    use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use rocket::tokio::net::TcpStream;
+    use rocket::tokio::time::timeout;
+
+    use rocket_contrib::access_log::{AccessLog, Format};
+
+    #[get("/hello")]
+    fn hello() -> &'static str {
+        "Hello!"
+    }
+
+    fn entries(format: Format, requests: impl FnOnce(&Client)) -> Vec<String> {
+        let entries = Arc::new(Mutex::new(vec![]));
+        let sink = entries.clone();
+        let log = AccessLog::with_sink(format, move |entry| {
+            sink.lock().unwrap().push(entry.to_string());
+        });
+
+        let rocket = rocket::ignite().mount("/", routes![hello]).attach(log);
+        let client = Client::new(rocket).unwrap();
+        requests(&client);
+
+        let entries = entries.lock().unwrap();
+        entries.clone()
+    }
+
+    #[test]
+    fn common_format() {
+        let entries = entries(Format::Common, |client| {
+            client.get("/hello").header(Header::new("X-Real-IP", "8.8.8.8")).dispatch();
+            client.get("/missing").dispatch();
+        });
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].starts_with("8.8.8.8 - - ["));
+        // Local requests don't arrive over any HTTP version.
+        assert!(entries[0].ends_with("\"GET /hello -\" 200 6"));
+        assert!(entries[1].contains("\"GET /missing -\" 404 "));
+    }
+
+    #[test]
+    fn combined_format() {
+        let entries = entries(Format::Combined, |client| {
+            client.get("/hello")
+                .header(Header::new("Referer", "https://example.com"))
+                .header(Header::new("User-Agent", "test \"agent\""))
+                .dispatch();
+        });
+
+        assert!(entries[0].contains(r#"200 6 "https://example.com" "test \"agent\"" "#));
+        assert!(entries[0].ends_with(r#" "hello""#));
+
+        let latency = entries[0].rsplit(' ').nth(1).unwrap();
+        assert!(latency.parse::<f64>().unwrap() >= 0.0);
+    }
+
+    #[rocket::async_test]
+    async fn served_requests_log_their_http_version() {
+        let entries = Arc::new(Mutex::new(vec![]));
+        let sink = entries.clone();
+        let log = AccessLog::with_sink(Format::Common, move |entry| {
+            sink.lock().unwrap().push(entry.to_string());
+        });
+
+        let config = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(0)
+            .unwrap();
+
+        let rocket = rocket::custom(config)
+            .mount("/", routes![hello])
+            .attach(log)
+            .attach_orbit_task(|orbit| async move {
+                let port = orbit.config().port;
+                let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+                stream.write_all(b"GET /hello HTTP/1.0\r\n\r\n").await.unwrap();
+                let mut response = vec![];
+                let _ = stream.read_to_end(&mut response).await;
+                orbit.shutdown().shutdown();
+            });
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("server shut down").is_ok());
+
+        let entries = entries.lock().unwrap();
+        assert!(entries[0].ends_with("\"GET /hello HTTP/1.0\" 200 6"));
+    }
+
+    #[test]
+    fn json_format() {
+        let entries = entries(Format::Json, |client| {
+            client.get("/hello?x=1").dispatch();
+        });
+
+        let entry: serde_json::Value = serde_json::from_str(&entries[0]).unwrap();
+        assert_eq!(entry["method"], "GET");
+        assert_eq!(entry["uri"], "/hello?x=1");
+        assert!(entry["version"].is_null());
+        assert_eq!(entry["status"], 200);
+        assert_eq!(entry["bytes"], 6);
+        assert_eq!(entry["route"], "hello");
+        assert!(entry["latency_ms"].as_f64().unwrap() >= 0.0);
+        assert!(entry["referer"].is_null());
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(feature = "askama_templates")]
+#[macro_use] extern crate rocket;
+
+#[cfg(feature = "askama_templates")]
+mod askama_tests {
+    use std::fmt;
+
+    use askama::Template;
+    use rocket::Rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket_contrib::askama::{Askama, AskamaFairing};
+
+    #[derive(Template)]
+    #[template(source = "<p>Hello, {{ name }}!</p>", ext = "html")]
+    struct Hello {
+        name: String,
+    }
+
+    #[derive(Template)]
+    #[template(source = "{{ name }}", ext = "txt")]
+    struct Raw {
+        name: String,
+    }
+
+    struct Unprintable;
+
+    impl fmt::Display for Unprintable {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[derive(Template)]
+    #[template(source = "{{ value }}", ext = "txt")]
+    struct Failing {
+        value: Unprintable,
+    }
+
+    #[get("/hello/<name>")]
+    fn hello(name: String) -> Askama<Hello> {
+        Askama(Hello { name })
+    }
+
+    #[get("/raw/<name>")]
+    fn raw(name: String) -> Askama<Raw> {
+        Askama(Raw { name })
+    }
+
+    #[get("/fail")]
+    fn fail() -> Askama<Failing> {
+        Askama(Failing { value: Unprintable })
+    }
+
+    fn rocket(env: Environment) -> Rocket {
+        let config = Config::build(env).finalize().unwrap();
+        rocket::custom(config).mount("/", routes![hello, raw, fail])
+    }
+
+    #[test]
+    fn renders_with_inferred_content_type() {
+        let client = Client::new(rocket(Environment::Development)).unwrap();
+        let response = client.get("/hello/%3Cb%3E").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().unwrap(), "<p>Hello, &lt;b&gt;!</p>");
+
+        let response = client.get("/raw/%3Cb%3E").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        assert_eq!(response.into_string().unwrap(), "<b>");
+    }
+
+    #[test]
+    fn render_errors_are_internal_server_errors() {
+        let client = Client::new(rocket(Environment::Development)).unwrap();
+        let response = client.get("/fail").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert!(!response.into_string().unwrap().contains("Template failed to render"));
+    }
+
+    #[test]
+    fn fairing_sends_error_details_in_development() {
+        let rocket = rocket(Environment::Development).attach(AskamaFairing);
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/fail").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        assert!(response.into_string().unwrap().starts_with("Template failed to render"));
+
+        let rocket = rocket(Environment::Production).attach(AskamaFairing);
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/fail").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert!(!response.into_string().unwrap().contains("Template failed to render"));
+    }
+}
@@ -0,0 +1,270 @@
+#[macro_use]
+#[cfg(feature = "auth")]
+extern crate rocket;
+
+#[cfg(feature = "auth")]
+mod auth_tests {
+    use rocket::{Config, Request};
+    use rocket::config::{Environment, Table, Value};
+    use rocket::http::{Accept, Cookie, Status};
+    use rocket::local::blocking::Client;
+    use rocket::response::Debug;
+
+    use rocket_contrib::auth::{Auth, AuthUser, AuthError, Authenticated, Authentication};
+    use rocket_contrib::auth::{hash_password, verify_password};
+
+    struct User(String);
+
+    #[rocket::async_trait]
+    impl AuthUser for User {
+        fn auth_id(&self) -> String {
+            self.0.clone()
+        }
+
+        async fn fetch(id: &str, _: &Request<'_>) -> Option<User> {
+            match id {
+                "alice" | "bob" => Some(User(id.to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    #[post("/login/<name>?<remember>")]
+    async fn login(auth: Auth<'_>, name: String, remember: bool) -> Result<(), Debug<AuthError>> {
+        Ok(auth.login(&User(name), remember).await?)
+    }
+
+    #[post("/logout")]
+    async fn logout(auth: Auth<'_>) -> Result<(), Debug<AuthError>> {
+        Ok(auth.logout().await?)
+    }
+
+    #[get("/private")]
+    fn private(user: Authenticated<User>) -> String {
+        user.0.clone()
+    }
+
+    fn client_with(config: Config) -> Client {
+        let rocket = rocket::custom(config)
+            .mount("/", routes![login, logout, private])
+            .attach(Authentication::fairing());
+
+        Client::new(rocket).unwrap()
+    }
+
+    fn client() -> Client {
+        client_with(Config::development())
+    }
+
+    fn config(table: Table) -> Config {
+        Config::build(Environment::Development)
+            .extra("auth", table)
+            .unwrap()
+    }
+
+    #[test]
+    fn login_and_logout() {
+        let client = client();
+        assert_eq!(client.get("/private").dispatch().status(), Status::Unauthorized);
+
+        assert_eq!(client.post("/login/alice?remember=false").dispatch().status(), Status::Ok);
+        let response = client.get("/private").dispatch();
+        assert_eq!(response.into_string().unwrap(), "alice");
+
+        assert_eq!(client.post("/logout").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/private").dispatch().status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn unknown_users_are_unauthorized() {
+        let client = client();
+        client.post("/login/mallory?remember=true").dispatch();
+        assert_eq!(client.get("/private").dispatch().status(), Status::Unauthorized);
+        assert!(client.cookies().get("rocket_auth").is_none());
+        assert!(client.cookies().get("rocket_remember").is_none());
+    }
+
+    #[test]
+    fn logins_expire() {
+        let client = client();
+        let response = client.get("/private")
+            .private_cookie(Cookie::new("rocket_auth", "0:alice"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.get("/private")
+            .private_cookie(Cookie::new("rocket_auth", "alice"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let mut table = Table::new();
+        table.insert("login_hours".into(), Value::from(1));
+        let client = client_with(config(table));
+        client.post("/login/alice?remember=false").dispatch();
+        let login = client.cookies().get("rocket_auth").unwrap();
+        assert_eq!(login.max_age(), Some(time::Duration::hours(1)));
+        assert_eq!(client.get("/private").dispatch().into_string().unwrap(), "alice");
+    }
+
+    #[test]
+    fn cookies_are_secure_outside_development() {
+        let client = client();
+        client.post("/login/alice?remember=true").dispatch();
+        assert_ne!(client.cookies().get("rocket_auth").unwrap().secure(), Some(true));
+
+        let client = client_with(Config::production());
+        let response = client.post("/login/alice?remember=true").dispatch();
+        for name in &["rocket_auth", "rocket_remember"] {
+            assert_eq!(response.cookies().get(name).unwrap().secure(), Some(true));
+        }
+
+        let mut table = Table::new();
+        table.insert("secure".into(), Value::from(false));
+        let config = Config::build(Environment::Production).extra("auth", table).unwrap();
+        let response = client_with(config).post("/login/alice?remember=false").dispatch();
+        assert_ne!(response.cookies().get("rocket_auth").unwrap().secure(), Some(true));
+    }
+
+    #[test]
+    fn logout_revokes_remember_me_tokens() {
+        let client = client();
+        client.post("/login/alice?remember=true").dispatch();
+        let token = client.cookies().get_private("rocket_remember").unwrap();
+
+        client.post("/logout").dispatch();
+        let response = client.get("/private").private_cookie(token).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn remember_me_restores_login() {
+        let client = client();
+        client.post("/login/bob?remember=true").dispatch();
+        assert!(client.cookies().get("rocket_remember").is_some());
+
+        // Replace the login with an invalid one: the remember-me token applies.
+        let response = client.get("/private")
+            .cookie(Cookie::new("rocket_auth", "forged"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "bob");
+        assert!(client.cookies().get("rocket_auth").is_some());
+
+        client.post("/logout").dispatch();
+        assert!(client.cookies().get("rocket_remember").is_none());
+        let response = client.get("/private")
+            .cookie(Cookie::new("rocket_auth", "forged"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn remember_me_tokens_are_rotated_on_use() {
+        let client = client();
+        client.post("/login/bob?remember=true").dispatch();
+        let stolen = client.cookies().get_private("rocket_remember").unwrap();
+
+        let response = client.get("/private")
+            .cookie(Cookie::new("rocket_auth", "forged"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "bob");
+        let rotated = client.cookies().get_private("rocket_remember").unwrap();
+        assert_ne!(rotated.value(), stolen.value());
+
+        // The used token no longer logs anyone in; its replacement does.
+        let response = client.get("/private")
+            .cookie(Cookie::new("rocket_auth", "forged"))
+            .private_cookie(stolen)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.get("/private")
+            .cookie(Cookie::new("rocket_auth", "forged"))
+            .private_cookie(rotated)
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "bob");
+    }
+
+    #[test]
+    fn html_requests_redirect_to_login() {
+        let mut table = Table::new();
+        table.insert("login_url".into(), Value::from("/login"));
+        let client = client_with(config(table));
+
+        let response = client.get("/private").header(Accept::HTML).dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+        let location = response.headers().get_one("Location").unwrap();
+        assert!(location.starts_with("/login?next="));
+
+        let response = client.get("/private").header(Accept::JSON).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = client.post("/logout").header(Accept::HTML).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn authenticated_guard_can_be_stubbed() {
+        use rocket::request::Outcome;
+
+        let rocket = rocket::ignite()
+            .mount("/", routes![private])
+            .attach(Authentication::fairing());
+
+        let client = Client::tracked_with(rocket, |overrides| overrides
+            .guard(|_| Outcome::<_, AuthError>::Success(Authenticated(User("stub".into())))))
+            .unwrap();
+
+        assert_eq!(client.get("/private").dispatch().into_string().unwrap(), "stub");
+    }
+
+    #[test]
+    fn passwords_are_hashed_and_verified() {
+        let hash = hash_password("correct horse").unwrap();
+        assert_ne!(hash, hash_password("correct horse").unwrap());
+        assert!(verify_password(&hash, "correct horse"));
+        assert!(!verify_password(&hash, "battery staple"));
+        assert!(!verify_password("not a hash", "correct horse"));
+    }
+
+    #[test]
+    fn invalid_config_aborts_launch() {
+        let mut table = Table::new();
+        table.insert("remember_days".into(), Value::from("forever"));
+
+        let rocket = rocket::custom(config(table)).attach(Authentication::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
+
+    #[cfg(feature = "sessions")]
+    #[test]
+    fn logins_can_be_kept_in_sessions() {
+        use rocket_contrib::sessions::{Sessions, MemoryStore};
+
+        let mut table = Table::new();
+        table.insert("storage".into(), Value::from("session"));
+
+        let rocket = rocket::custom(config(table))
+            .mount("/", routes![login, logout, private])
+            .attach(Sessions::with_store(MemoryStore::new()))
+            .attach(Authentication::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        client.post("/login/alice?remember=false").dispatch();
+        assert!(client.cookies().get("rocket_auth").is_none());
+        let session = client.cookies().get("rocket_session").map(|c| c.value().to_string());
+        assert!(session.is_some());
+        assert_eq!(client.get("/private").dispatch().into_string().unwrap(), "alice");
+
+        client.post("/logout").dispatch();
+        assert_eq!(client.get("/private").dispatch().status(), Status::Unauthorized);
+        let new_session = client.cookies().get("rocket_session").map(|c| c.value().to_string());
+        assert_ne!(session, new_session);
+    }
+}
@@ -0,0 +1,200 @@
+#[macro_use]
+#[cfg(feature = "cors")]
+extern crate rocket;
+
+#[cfg(feature = "cors")]
+mod cors_tests {
+    use rocket::config::{Config, Environment, Table, Value};
+    use rocket::http::{Header, Method, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::cors::Cors;
+
+    #[get("/users")]
+    fn users() -> &'static str {
+        "users"
+    }
+
+    #[get("/other")]
+    fn other() -> &'static str {
+        "other"
+    }
+
+    fn client(cors: Cors) -> Client {
+        let rocket = rocket::ignite()
+            .mount("/api", routes![users])
+            .mount("/", routes![other])
+            .attach(cors);
+
+        Client::new(rocket).unwrap()
+    }
+
+    fn origin(origin: &'static str) -> Header<'static> {
+        Header::new("Origin", origin)
+    }
+
+    #[test]
+    fn any_origin_without_credentials_is_wildcard() {
+        let client = client(Cors::new());
+        let response = client.get("/api/users").header(origin("https://a.com")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("*"));
+        assert!(response.headers().get_one("Vary").is_none());
+    }
+
+    #[test]
+    fn listed_origins_are_echoed() {
+        let client = client(Cors::new().allow_origin("https://a.com").expose_headers(vec!["X-A"]));
+
+        let response = client.get("/api/users").header(origin("https://a.com")).dispatch();
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Access-Control-Allow-Origin"), Some("https://a.com"));
+        assert_eq!(headers.get_one("Access-Control-Expose-Headers"), Some("X-A"));
+        assert_eq!(headers.get_one("Vary"), Some("Origin"));
+
+        let response = client.get("/api/users").header(origin("https://b.com")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+
+        let response = client.get("/api/users").dispatch();
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+        assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn preflight_is_answered() {
+        let cors = Cors::new()
+            .allow_origin("https://a.com")
+            .allow_methods(vec![Method::Get, Method::Delete])
+            .allow_headers(vec!["Content-Type"])
+            .allow_credentials(true)
+            .max_age(600);
+
+        let client = client(cors);
+        let response = client.options("/api/users")
+            .header(origin("https://a.com"))
+            .header(Header::new("Access-Control-Request-Method", "DELETE"))
+            .header(Header::new("Access-Control-Request-Headers", "content-type"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NoContent);
+        let headers = response.headers();
+        assert!(headers.get_one("Content-Type").is_none());
+        assert_eq!(headers.get_one("Access-Control-Allow-Origin"), Some("https://a.com"));
+        assert_eq!(headers.get_one("Access-Control-Allow-Methods"), Some("GET, DELETE"));
+        assert_eq!(headers.get_one("Access-Control-Allow-Headers"), Some("Content-Type"));
+        assert_eq!(headers.get_one("Access-Control-Allow-Credentials"), Some("true"));
+        assert_eq!(headers.get_one("Access-Control-Max-Age"), Some("600"));
+        assert_eq!(headers.get_one("Vary"),
+            Some("Origin, Access-Control-Request-Method, Access-Control-Request-Headers"));
+        assert!(response.into_string().is_none());
+    }
+
+    #[test]
+    fn disallowed_preflight_is_rejected() {
+        let cors = Cors::new()
+            .allow_methods(vec![Method::Get])
+            .allow_headers(vec!["Content-Type"]);
+
+        let client = client(cors);
+        let response = client.options("/api/users")
+            .header(origin("https://a.com"))
+            .header(Header::new("Access-Control-Request-Method", "PUT"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+
+        let response = client.options("/api/users")
+            .header(origin("https://a.com"))
+            .header(Header::new("Access-Control-Request-Method", "GET"))
+            .header(Header::new("Access-Control-Request-Headers", "X-Secret"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.headers().get_one("Access-Control-Allow-Methods").is_none());
+    }
+
+    #[test]
+    fn policy_is_scoped_to_base() {
+        let client = client(Cors::new().base("/api/"));
+        let response = client.get("/api/users").header(origin("https://a.com")).dispatch();
+        assert_eq!(response.headers().get_one("Access-Control-Allow-Origin"), Some("*"));
+
+        let response = client.get("/other").header(origin("https://a.com")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+    }
+
+    #[test]
+    fn policy_is_read_from_config() {
+        let mut table = Table::new();
+        table.insert("allowed_origins".into(), Value::from(vec!["https://a.com"]));
+        table.insert("allow_credentials".into(), Value::from(true));
+        table.insert("base".into(), Value::from("/api"));
+
+        let config = Config::build(Environment::Development)
+            .extra("cors", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config)
+            .mount("/api", routes![users])
+            .attach(Cors::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/api/users").header(origin("https://a.com")).dispatch();
+        let headers = response.headers();
+        assert_eq!(headers.get_one("Access-Control-Allow-Origin"), Some("https://a.com"));
+        assert_eq!(headers.get_one("Access-Control-Allow-Credentials"), Some("true"));
+    }
+
+    #[test]
+    fn credentials_from_any_origin_are_rejected() {
+        let rocket = rocket::ignite()
+            .mount("/api", routes![users])
+            .attach(Cors::new().allow_credentials(true));
+
+        assert!(Client::new(rocket).is_err());
+
+        let mut table = Table::new();
+        table.insert("allowed_origins".into(), Value::from("*"));
+        table.insert("allow_credentials".into(), Value::from(true));
+        let config = Config::build(Environment::Development)
+            .extra("cors", table)
+            .unwrap();
+
+        assert!(Cors::from_config(&config).is_err());
+        let rocket = rocket::custom(config).attach(Cors::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
+
+    #[rocket::async_test]
+    async fn credentials_from_any_origin_are_never_reflected() {
+        use rocket::Response;
+        use rocket::local::FairingHarness;
+
+        // The harness doesn't attach the fairing, so its check is bypassed.
+        let harness = FairingHarness::new(Cors::new().allow_credentials(true));
+        let mut request = harness.request(Method::Get, "/api/users");
+        request.add_header(origin("https://evil.com"));
+
+        let mut response = Response::new();
+        harness.on_response(&request, &mut response).await;
+        assert!(response.headers().get_one("Access-Control-Allow-Origin").is_none());
+        assert!(response.headers().get_one("Access-Control-Allow-Credentials").is_none());
+    }
+
+    #[test]
+    fn invalid_config_aborts_launch() {
+        let mut table = Table::new();
+        table.insert("max_age".into(), Value::from("forever"));
+
+        let config = Config::build(Environment::Development)
+            .extra("cors", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config).attach(Cors::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
+}
@@ -48,4 +48,599 @@ mod rusqlite_integration_test {
             tx.commit().expect("committed transaction");
         }).await;
     }
+
+    fn migrated_rocket() -> rocket::Rocket {
+        let mut test_db: Map<String, Value> = Map::new();
+        let mut test_db_opts: Map<String, Value> = Map::new();
+        test_db_opts.insert("url".into(), Value::String(":memory:".into()));
+        // A single connection keeps the in-memory database alive.
+        test_db_opts.insert("pool_size".into(), Value::Integer(1));
+        test_db.insert("test_db".into(), Value::Table(test_db_opts));
+        let config = Config::build(Environment::Development)
+            .extra("databases", Value::Table(test_db))
+            .finalize()
+            .unwrap();
+
+        rocket::custom(config).attach(SqliteDb::fairing())
+    }
+
+    #[rocket::async_test]
+    async fn test_migrations() {
+        let create = |conn: &mut rusqlite::Connection| {
+            conn.execute_batch("CREATE TABLE items (id INTEGER)")
+        };
+
+        let mut rocket = migrated_rocket().attach(SqliteDb::migrate(create));
+
+        let conn = SqliteDb::get_one(rocket.inspect().await).await.expect("connection");
+        let count: i64 = conn.run(|conn| {
+            conn.query_row("SELECT COUNT(*) FROM items", &[] as &[&dyn ToSql], |row| row.get(0))
+        }).await.expect("items table");
+
+        assert_eq!(count, 0);
+    }
+
+    #[rocket::async_test]
+    async fn test_failed_migrations_abort_launch() {
+        let rocket = migrated_rocket()
+            .attach(SqliteDb::migrate(|conn| conn.execute_batch("CREATE TABLE")));
+
+        assert!(rocket::local::asynchronous::Client::new(rocket).await.is_err());
+    }
+}
+
+#[cfg(feature = "sqlx_sqlite_pool")]
+mod sqlx_integration_test {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rocket::{Orbit, Rocket};
+    use rocket::config::{Config, Environment, Value, Map};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::local::asynchronous::Client as AsyncClient;
+    use rocket::tokio::time::{delay_for, timeout};
+    use rocket_contrib::databases::{Connection, Database, Health, HealthReport, Tx, sqlx};
+    use rocket_contrib::databases::{Routed, ReadOnly, ReadWrite};
+
+    #[derive(Database)]
+    #[database("test_db")]
+    struct SqliteDb(sqlx::SqlitePool);
+
+    #[derive(Database)]
+    #[database("test_db_2")]
+    struct SqliteDb2(sqlx::SqlitePool);
+
+    #[rocket::get("/")]
+    async fn select_one(mut db: Connection<SqliteDb>) -> Option<String> {
+        let (one,): (i32,) = sqlx::query_as("SELECT 1")
+            .fetch_one(&mut *db)
+            .await
+            .ok()?;
+
+        Some(one.to_string())
+    }
+
+    #[rocket::post("/insert/<status>")]
+    async fn insert(mut tx: Tx<SqliteDb>, status: u16) -> Status {
+        sqlx::query("CREATE TABLE IF NOT EXISTS items (id INTEGER)")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        sqlx::query("INSERT INTO items VALUES (1)").execute(&mut *tx).await.unwrap();
+        Status::from_code(status).unwrap()
+    }
+
+    #[rocket::post("/twice")]
+    fn twice(_tx: Tx<SqliteDb>, _again: Tx<SqliteDb>) { }
+
+    /// A guard that always forwards.
+    struct Forward;
+
+    #[rocket::async_trait]
+    impl<'a, 'r> rocket::request::FromRequest<'a, 'r> for Forward {
+        type Error = ();
+
+        async fn from_request(_: &'a rocket::Request<'r>) -> rocket::request::Outcome<Self, ()> {
+            rocket::request::Outcome::Forward(())
+        }
+    }
+
+    #[rocket::post("/insert/200")]
+    fn forwarding(_tx: Tx<SqliteDb>, _forward: Forward) { }
+
+    #[rocket::get("/count")]
+    async fn count(mut db: Connection<SqliteDb>) -> String {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM items")
+            .fetch_one(&mut *db)
+            .await
+            .unwrap_or((-1,));
+
+        count.to_string()
+    }
+
+    type Db<H = rocket_contrib::databases::ByMethod> = Routed<SqliteDb, SqliteDb2, H>;
+
+    #[rocket::get("/role")]
+    fn get_role(db: Db) -> String {
+        format!("{:?}", db.role())
+    }
+
+    #[rocket::post("/role")]
+    fn post_role(db: Db) -> String {
+        format!("{:?}", db.role())
+    }
+
+    #[rocket::get("/role/rw")]
+    fn get_rw_role(db: Db<ReadWrite>) -> String {
+        format!("{:?}", db.role())
+    }
+
+    #[rocket::post("/role/ro")]
+    fn post_ro_role(db: Db<ReadOnly>) -> String {
+        format!("{:?}", db.role())
+    }
+
+    fn rocket(url: &str) -> Rocket {
+        rocket_with(url, Map::new())
+    }
+
+    fn rocket_with(url: &str, mut test_db_opts: Map<String, Value>) -> Rocket {
+        let mut test_db: Map<String, Value> = Map::new();
+        test_db_opts.insert("url".into(), Value::String(url.into()));
+        // A single connection keeps the in-memory database alive across requests.
+        test_db_opts.insert("pool_size".into(), Value::Integer(1));
+        test_db.insert("test_db".into(), Value::Table(test_db_opts));
+        let config = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(0)
+            .extra("databases", Value::Table(test_db))
+            .finalize()
+            .unwrap();
+
+        let routes = rocket::routes![select_one, insert, twice, count];
+        rocket::custom(config).mount("/", routes)
+    }
+
+    #[test]
+    fn test_connection_guard() {
+        let client = Client::new(rocket("sqlite::memory:").attach(SqliteDb::init())).unwrap();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.into_string().unwrap(), "1");
+    }
+
+    #[test]
+    fn test_pool_stats() {
+        let client = Client::new(rocket("sqlite::memory:").attach(SqliteDb::init())).unwrap();
+        let stats = SqliteDb::stats(client.cargo()).expect("pool stats");
+        assert_eq!((stats.acquired, stats.failed), (0, 0));
+
+        client.get("/").dispatch();
+        client.get("/").dispatch();
+        let stats = SqliteDb::stats(client.cargo()).expect("pool stats");
+        assert_eq!((stats.acquired, stats.failed), (2, 0));
+        assert_eq!(stats.size, Some(1));
+        assert!(stats.max_wait >= stats.mean_wait());
+
+        let client = Client::new(rocket("sqlite::memory:")).unwrap();
+        assert!(SqliteDb::stats(client.cargo()).is_none());
+    }
+
+    #[test]
+    fn test_missing_fairing() {
+        let client = Client::new(rocket("sqlite::memory:")).unwrap();
+        assert_eq!(client.get("/").dispatch().status(), Status::InternalServerError);
+    }
+
+    #[test]
+    fn test_transactions() {
+        let rocket = rocket("sqlite::memory:")
+            .attach(SqliteDb::init())
+            .attach(Tx::<SqliteDb>::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.post("/insert/200").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "1");
+
+        assert_eq!(client.post("/insert/204").dispatch().status(), Status::NoContent);
+        assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "2");
+
+        let response = client.post("/insert/500").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "2");
+
+        assert_eq!(client.post("/insert/404").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "2");
+    }
+
+    #[test]
+    fn test_second_transaction_fails_without_a_connection() {
+        let rocket = rocket("sqlite::memory:")
+            .attach(SqliteDb::init())
+            .attach(Tx::<SqliteDb>::fairing());
+
+        // With a single connection, beginning the second transaction would
+        // wait for the first one's connection until the pool timed out.
+        let client = Client::new(rocket).unwrap();
+        let response = client.post("/twice").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(client.post("/insert/200").dispatch().status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_transaction_after_forward() {
+        let rocket = rocket("sqlite::memory:")
+            .mount("/", rocket::routes![forwarding])
+            .attach(SqliteDb::init())
+            .attach(Tx::<SqliteDb>::fairing());
+
+        // The static forwarding route is tried first. Its transaction holds
+        // the only connection and must be rolled back for the fallback's.
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.post("/insert/200").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "1");
+    }
+
+    #[test]
+    fn test_transaction_requires_fairing() {
+        let client = Client::new(rocket("sqlite::memory:").attach(SqliteDb::init())).unwrap();
+        let response = client.post("/insert/200").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+
+    #[rocket::async_test]
+    async fn test_multiple_pools() {
+        let mut test_db: Map<String, Value> = Map::new();
+        let mut test_db_opts: Map<String, Value> = Map::new();
+        test_db_opts.insert("url".into(), Value::String("sqlite::memory:".into()));
+        test_db_opts.insert("pool_size".into(), Value::Integer(1));
+        test_db.insert("test_db".into(), Value::Table(test_db_opts.clone()));
+        test_db.insert("test_db_2".into(), Value::Table(test_db_opts));
+        let config = Config::build(Environment::Development)
+            .extra("databases", Value::Table(test_db))
+            .finalize()
+            .unwrap();
+
+        let mut rocket = rocket::custom(config)
+            .attach(SqliteDb::init())
+            .attach(SqliteDb2::init());
+
+        let cargo = rocket.inspect().await;
+        let db = SqliteDb::fetch(cargo).expect("first pool");
+        let db2 = SqliteDb2::fetch(cargo).expect("second pool");
+        sqlx::query("CREATE TABLE items (id INTEGER)").execute(&**db).await.unwrap();
+
+        let query = "SELECT COUNT(*) FROM items";
+        assert!(sqlx::query(query).fetch_one(&**db).await.is_ok());
+        assert!(sqlx::query(query).fetch_one(&**db2).await.is_err());
+    }
+
+    fn replicated_rocket() -> Rocket {
+        let mut test_db: Map<String, Value> = Map::new();
+        let mut test_db_opts: Map<String, Value> = Map::new();
+        test_db_opts.insert("url".into(), Value::String("sqlite::memory:".into()));
+        test_db.insert("test_db".into(), Value::Table(test_db_opts.clone()));
+        test_db.insert("test_db_2".into(), Value::Table(test_db_opts));
+        let config = Config::build(Environment::Development)
+            .extra("databases", Value::Table(test_db))
+            .finalize()
+            .unwrap();
+
+        let routes = rocket::routes![get_role, post_role, get_rw_role, post_ro_role];
+        rocket::custom(config).mount("/", routes).attach(SqliteDb::init())
+    }
+
+    #[test]
+    fn test_replica_routing() {
+        let client = Client::new(replicated_rocket().attach(SqliteDb2::init())).unwrap();
+        assert_eq!(client.get("/role").dispatch().into_string().unwrap(), "Replica");
+        assert_eq!(client.post("/role").dispatch().into_string().unwrap(), "Primary");
+        assert_eq!(client.get("/role/rw").dispatch().into_string().unwrap(), "Primary");
+        assert_eq!(client.post("/role/ro").dispatch().into_string().unwrap(), "Replica");
+    }
+
+    #[test]
+    fn test_replica_fallback() {
+        let client = Client::new(replicated_rocket()).unwrap();
+        assert_eq!(client.get("/role").dispatch().into_string().unwrap(), "Primary");
+        assert_eq!(client.post("/role/ro").dispatch().into_string().unwrap(), "Primary");
+    }
+
+    async fn migrator() -> sqlx::migrate::Migrator {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("migrations");
+        sqlx::migrate::Migrator::new(path.as_path()).await.expect("migrations")
+    }
+
+    #[rocket::async_test]
+    async fn test_migrations() {
+        let rocket = rocket("sqlite::memory:")
+            .attach(SqliteDb::init())
+            .attach(SqliteDb::migrate(migrator().await));
+
+        let client = AsyncClient::new(rocket).await.unwrap();
+        let response = client.get("/count").dispatch().await;
+        assert_eq!(response.into_string().await.unwrap(), "0");
+    }
+
+    #[rocket::async_test]
+    async fn test_disabled_migrations() {
+        let mut opts = Map::new();
+        opts.insert("migrate".into(), Value::Boolean(false));
+        let rocket = rocket_with("sqlite::memory:", opts)
+            .attach(SqliteDb::init())
+            .attach(SqliteDb::migrate(migrator().await));
+
+        let client = AsyncClient::new(rocket).await.unwrap();
+        let response = client.get("/count").dispatch().await;
+        assert_eq!(response.into_string().await.unwrap(), "-1");
+    }
+
+    #[rocket::async_test]
+    async fn test_migrations_require_pool() {
+        let rocket = rocket("sqlite::memory:").attach(SqliteDb::migrate(migrator().await));
+        assert!(AsyncClient::new(rocket).await.is_err());
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        let rocket = rocket("postgres://localhost/test").attach(SqliteDb::init());
+        assert!(Client::new(rocket).is_err());
+    }
+
+    /// Waits for a health report of `SqliteDb` in `orbit` that is healthy if
+    /// `healthy` and unhealthy otherwise.
+    async fn await_report(orbit: &Orbit, healthy: bool) -> Option<HealthReport> {
+        let health = orbit.state::<Health<SqliteDb>>().expect("health state");
+        let waiting = async {
+            loop {
+                match health.latest() {
+                    Some(report) if report.is_healthy() == healthy => return report,
+                    _ => delay_for(Duration::from_millis(10)).await,
+                }
+            }
+        };
+
+        timeout(Duration::from_secs(2), waiting).await.ok()
+    }
+
+    #[rocket::async_test]
+    async fn test_health_check_of_healthy_database() {
+        let (report, reported) = (Arc::new(Mutex::new(None)), Arc::new(Mutex::new(0)));
+        let (latest, count) = (report.clone(), reported.clone());
+        let health_check = SqliteDb::health_check(Duration::from_millis(10))
+            .on_report(move |_| *count.lock().unwrap() += 1);
+
+        let rocket = rocket("sqlite::memory:")
+            .attach(SqliteDb::init())
+            .attach(health_check)
+            .attach_orbit_task(move |orbit| async move {
+                *latest.lock().unwrap() = await_report(&orbit, true).await;
+                orbit.shutdown().shutdown();
+            });
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("server shut down").is_ok());
+
+        let report = report.lock().unwrap().take().expect("health report");
+        assert!(report.is_healthy());
+        assert_eq!(report.database, "test_db");
+        assert!(*reported.lock().unwrap() >= 1);
+    }
+
+    #[rocket::async_test]
+    async fn test_health_check_of_unavailable_database() {
+        let (report, failures) = (Arc::new(Mutex::new(None)), Arc::new(Mutex::new(0)));
+        let (latest, count) = (report.clone(), failures.clone());
+        let health_check = SqliteDb::health_check(Duration::from_millis(10))
+            .timeout(Duration::from_millis(50))
+            .on_failure(move |_| *count.lock().unwrap() += 1);
+
+        let rocket = rocket("sqlite::memory:")
+            .attach(SqliteDb::init())
+            .attach(health_check)
+            .attach_orbit_task(move |orbit| async move {
+                // Holding the pool's only connection stalls every check.
+                let db = SqliteDb::fetch(&orbit).expect("database");
+                let _conn = db.acquire().await.expect("connection");
+                *latest.lock().unwrap() = await_report(&orbit, false).await;
+                orbit.shutdown().shutdown();
+            });
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("server shut down").is_ok());
+
+        let report = report.lock().unwrap().take().expect("health report");
+        assert_eq!(report.result, Err("timed out after 50ms".to_string()));
+        assert!(*failures.lock().unwrap() >= 1);
+    }
+}
+
+#[cfg(feature = "redis_pool")]
+mod redis_cache_tests {
+    use std::time::Duration;
+
+    use rocket::config::{Config, Environment, Value, Map};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket_contrib::databases::{Cache, Database, redis};
+
+    #[derive(Database)]
+    #[database("redis")]
+    struct Redis(redis::aio::ConnectionManager);
+
+    #[rocket::get("/<key>")]
+    async fn cached(cache: Cache<Redis>, key: String) -> Option<String> {
+        if let Some(value) = cache.get::<String>(&key).await.ok()? {
+            return Some(value);
+        }
+
+        cache.set(&key, "computed", Some(Duration::from_secs(1))).await.ok()?;
+        cache.remove("stale").await.ok()?;
+        Some("computed".into())
+    }
+
+    #[rocket::get("/get/<key>")]
+    async fn get(cache: Cache<Redis>, key: String) -> Result<Option<String>, String> {
+        cache.get::<String>(&key).await.map_err(|e| e.to_string())
+    }
+
+    #[rocket::get("/get/<key>/number")]
+    async fn get_number(cache: Cache<Redis>, key: String) -> Result<Option<String>, String> {
+        let number = cache.get::<u64>(&key).await.map_err(|e| e.to_string())?;
+        Ok(number.map(|n| n.to_string()))
+    }
+
+    #[rocket::put("/set/<key>/<value>?<ttl_ms>")]
+    async fn set(
+        cache: Cache<Redis>,
+        key: String,
+        value: String,
+        ttl_ms: Option<u64>
+    ) -> Option<()> {
+        cache.set(&key, &value, ttl_ms.map(Duration::from_millis)).await.ok()
+    }
+
+    #[rocket::delete("/remove/<key>")]
+    async fn remove(cache: Cache<Redis>, key: String) -> Option<String> {
+        cache.remove(&key).await.ok().map(|removed| removed.to_string())
+    }
+
+    #[test]
+    fn test_cache_routes_compile() {
+        let _ = rocket::ignite().attach(Redis::init()).mount("/", rocket::routes![cached]);
+    }
+
+    /// Returns a client for an application using the Redis server at the URL
+    /// in the `ROCKET_TEST_REDIS_URL` environment variable, or `None` if it
+    /// isn't set.
+    fn client() -> Option<Client> {
+        let url = std::env::var("ROCKET_TEST_REDIS_URL").ok()?;
+        let mut databases: Map<String, Value> = Map::new();
+        let mut redis_opts: Map<String, Value> = Map::new();
+        redis_opts.insert("url".into(), Value::String(url));
+        databases.insert("redis".into(), Value::Table(redis_opts));
+        let config = Config::build(Environment::Development)
+            .extra("databases", Value::Table(databases))
+            .finalize()
+            .unwrap();
+
+        let routes = rocket::routes![get, get_number, set, remove];
+        let rocket = rocket::custom(config).attach(Redis::init()).mount("/", routes);
+        Some(Client::new(rocket).expect("valid rocket"))
+    }
+
+    fn body(client: &Client, path: &str) -> Option<String> {
+        let response = client.get(path).dispatch();
+        match response.status() {
+            Status::NotFound => None,
+            _ => Some(response.into_string().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_cache_get_set_remove() {
+        let client = match client() {
+            Some(client) => client,
+            None => return,
+        };
+
+        let key = format!("rocket-cache-test-{}", std::process::id());
+        assert_eq!(client.put(format!("/set/{}/hello", key)).dispatch().status(), Status::Ok);
+        assert_eq!(body(&client, &format!("/get/{}", key)), Some("hello".into()));
+
+        // The value is JSON, so it can't be read back as a number.
+        let response = client.get(format!("/get/{}/number", key)).dispatch();
+        assert!(response.into_string().unwrap().contains("serialization error"));
+
+        let response = client.delete(format!("/remove/{}", key)).dispatch();
+        assert_eq!(response.into_string().unwrap(), "true");
+        assert_eq!(body(&client, &format!("/get/{}", key)), None);
+
+        let response = client.delete(format!("/remove/{}", key)).dispatch();
+        assert_eq!(response.into_string().unwrap(), "false");
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        let client = match client() {
+            Some(client) => client,
+            None => return,
+        };
+
+        let key = format!("rocket-cache-ttl-test-{}", std::process::id());
+        let response = client.put(format!("/set/{}/soon?ttl_ms=100", key)).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(body(&client, &format!("/get/{}", key)), Some("soon".into()));
+
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(body(&client, &format!("/get/{}", key)), None);
+    }
+}
+
+#[cfg(feature = "refinery_migrations")]
+mod refinery_migrations_test {
+    use rocket::config::{Config, Environment, Value, Map};
+    use rocket::local::asynchronous::Client;
+    use rocket_contrib::databases::{Database, deadpool_postgres, refinery};
+
+    use refinery::{Migration, Runner};
+
+    #[derive(Database)]
+    #[database("test_db")]
+    struct PgDb(deadpool_postgres::Pool);
+
+    fn runner(sql: &str) -> Runner {
+        let migration = Migration::unapplied("V1__create_rocket_items", sql).expect("migration");
+        Runner::new(&[migration])
+    }
+
+    /// Returns a Rocket configured to use the Postgres database at the URL in
+    /// the `ROCKET_TEST_POSTGRES_URL` environment variable, or `None` if it
+    /// isn't set.
+    fn rocket() -> Option<rocket::Rocket> {
+        let url = std::env::var("ROCKET_TEST_POSTGRES_URL").ok()?;
+        let mut test_db: Map<String, Value> = Map::new();
+        let mut test_db_opts: Map<String, Value> = Map::new();
+        test_db_opts.insert("url".into(), Value::String(url));
+        test_db.insert("test_db".into(), Value::Table(test_db_opts));
+        let config = Config::build(Environment::Development)
+            .extra("databases", Value::Table(test_db))
+            .finalize()
+            .unwrap();
+
+        Some(rocket::custom(config).attach(PgDb::init()))
+    }
+
+    #[rocket::async_test]
+    async fn test_refinery_migrations() {
+        let rocket = match rocket() {
+            Some(rocket) => rocket,
+            None => return,
+        };
+
+        let sql = "CREATE TABLE IF NOT EXISTS rocket_items (id INTEGER)";
+        let client = Client::new(rocket.attach(PgDb::migrate(runner(sql)))).await.unwrap();
+        let db = PgDb::fetch(client.cargo()).expect("database");
+        let conn = db.get().await.expect("connection");
+        assert!(conn.simple_query("SELECT COUNT(*) FROM rocket_items").await.is_ok());
+
+        let applied = conn.query("SELECT version FROM refinery_schema_history", &[]).await;
+        assert_eq!(applied.expect("migration history").len(), 1);
+        conn.batch_execute("DROP TABLE rocket_items; DROP TABLE refinery_schema_history")
+            .await
+            .unwrap();
+    }
+
+    #[rocket::async_test]
+    async fn test_failed_refinery_migrations_abort_launch() {
+        let rocket = match rocket() {
+            Some(rocket) => rocket,
+            None => return,
+        };
+
+        let rocket = rocket.attach(PgDb::migrate(runner("CREATE TABLE")));
+        assert!(Client::new(rocket).await.is_err());
+    }
 }
@@ -0,0 +1,222 @@
+#[macro_use]
+#[cfg(feature = "feature_flags")]
+extern crate rocket;
+
+#[cfg(feature = "feature_flags")]
+mod feature_flags_tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rocket::{Config, Rocket, State};
+    use rocket::config::{Environment, Table, Value};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use rocket::tokio::time::{delay_for, timeout};
+
+    use rocket_contrib::feature_flags::*;
+
+    struct NewCheckout;
+
+    impl FeatureFlag for NewCheckout {
+        const NAME: &'static str = "new_checkout";
+    }
+
+    struct DarkMode;
+
+    impl FeatureFlag for DarkMode {
+        const NAME: &'static str = "dark_mode";
+    }
+
+    #[get("/checkout")]
+    fn new_checkout(_flag: Flag<NewCheckout>) -> &'static str {
+        "new"
+    }
+
+    #[get("/checkout", rank = 2)]
+    fn checkout() -> &'static str {
+        "old"
+    }
+
+    #[get("/theme")]
+    fn theme(dark: Option<Flag<DarkMode>>) -> &'static str {
+        if dark.is_some() { "dark" } else { "light" }
+    }
+
+    #[post("/flags/<name>/<enabled>")]
+    fn toggle(name: String, enabled: bool, flags: State<'_, FeatureFlags>) {
+        flags.set(name, enabled);
+    }
+
+    struct Remote(HashMap<String, bool>);
+
+    #[rocket::async_trait]
+    impl FlagProvider for Remote {
+        fn name(&self) -> &str {
+            "remote"
+        }
+
+        async fn fetch(&self) -> Result<HashMap<String, bool>, String> {
+            Ok(self.0.clone())
+        }
+    }
+
+    struct Broken;
+
+    #[rocket::async_trait]
+    impl FlagProvider for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        async fn fetch(&self) -> Result<HashMap<String, bool>, String> {
+            Err("unreachable".into())
+        }
+    }
+
+    /// A provider reporting whatever flags are in the shared map.
+    struct Shared(Arc<Mutex<HashMap<String, bool>>>);
+
+    #[rocket::async_trait]
+    impl FlagProvider for Shared {
+        fn name(&self) -> &str {
+            "shared"
+        }
+
+        async fn fetch(&self) -> Result<HashMap<String, bool>, String> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn rocket(config: Config) -> Rocket {
+        rocket::custom(config).mount("/", routes![new_checkout, checkout, theme, toggle])
+    }
+
+    fn body(client: &Client, path: &str) -> String {
+        client.get(path).dispatch().into_string().unwrap()
+    }
+
+    #[test]
+    fn flags_select_routes_and_branches() {
+        let fairing = FeatureFlags::fairing();
+        let flags = fairing.handle();
+        let client = Client::new(rocket(Config::development()).attach(fairing)).unwrap();
+        assert_eq!(body(&client, "/checkout"), "old");
+        assert_eq!(body(&client, "/theme"), "light");
+
+        flags.set("new_checkout", true);
+        assert_eq!(body(&client, "/checkout"), "new");
+        assert_eq!(body(&client, "/theme"), "light");
+
+        assert_eq!(client.post("/flags/dark_mode/true").dispatch().status(), Status::Ok);
+        assert_eq!(body(&client, "/theme"), "dark");
+        assert!(flags.is_enabled("dark_mode"));
+
+        assert_eq!(flags.remove("new_checkout"), Some(true));
+        assert_eq!(body(&client, "/checkout"), "old");
+    }
+
+    #[test]
+    fn sources_take_precedence_in_order() {
+        let mut table = Table::new();
+        table.insert("new_checkout".into(), Value::Boolean(true));
+        table.insert("dark_mode".into(), Value::Boolean(true));
+        let config = Config::build(Environment::Development).extra("flags", table).unwrap();
+
+        let mut remote = HashMap::new();
+        remote.insert("dark_mode".to_string(), false);
+        let fairing = FeatureFlags::fairing()
+            .flag("new_checkout", false)
+            .flag("beta", true)
+            .provider(Broken, Duration::from_secs(60))
+            .provider(Remote(remote), Duration::from_secs(60));
+
+        let flags = fairing.handle();
+        let client = Client::new(rocket(config).attach(fairing)).unwrap();
+        assert_eq!(body(&client, "/checkout"), "new");
+        assert_eq!(body(&client, "/theme"), "light");
+
+        let mut expected = HashMap::new();
+        expected.insert("new_checkout".to_string(), true);
+        expected.insert("dark_mode".to_string(), false);
+        expected.insert("beta".to_string(), true);
+        assert_eq!(flags.snapshot(), expected);
+    }
+
+    #[rocket::async_test]
+    async fn providers_are_polled_while_running() {
+        let reported = Arc::new(Mutex::new(HashMap::new()));
+        reported.lock().unwrap().insert("beta".to_string(), true);
+        let fairing = FeatureFlags::fairing()
+            .flag("beta", false)
+            .provider(Shared(reported.clone()), Duration::from_millis(10));
+
+        let flags = fairing.handle();
+        let (watched, initially) = (flags.clone(), Arc::new(Mutex::new(None)));
+        let initial = initially.clone();
+        let config = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(0)
+            .unwrap();
+
+        let rocket = rocket(config)
+            .attach(fairing)
+            .attach_task(move |shutdown| async move {
+                *initial.lock().unwrap() = watched.get("beta");
+
+                // The provider stops reporting `beta` and starts reporting
+                // `dark_mode`; the next poll should pick both changes up.
+                *reported.lock().unwrap() = vec![("dark_mode".to_string(), true)]
+                    .into_iter()
+                    .collect();
+
+                let _ = timeout(Duration::from_secs(2), async {
+                    while watched.get("dark_mode").is_none() {
+                        delay_for(Duration::from_millis(10)).await;
+                    }
+                }).await;
+
+                shutdown.shutdown();
+            });
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("server shut down").is_ok());
+        assert_eq!(*initially.lock().unwrap(), Some(true));
+        assert_eq!(flags.get("dark_mode"), Some(true));
+        assert_eq!(flags.get("beta"), Some(false));
+    }
+
+    #[test]
+    fn invalid_configuration_aborts_attach() {
+        let mut table = Table::new();
+        table.insert("new_checkout".into(), Value::String("yes".into()));
+        let config = Config::build(Environment::Development).extra("flags", table).unwrap();
+        assert!(Client::new(rocket(config).attach(FeatureFlags::fairing())).is_err());
+    }
+
+    #[rocket::async_test]
+    async fn file_provider_reads_toml() {
+        let path = std::env::temp_dir()
+            .join(format!("rocket-feature-flags-{}.toml", std::process::id()));
+
+        std::fs::write(&path, "new_checkout = true\ndark_mode = false\n").unwrap();
+        let provider = FileProvider::new(&path);
+        let flags = provider.fetch().await.unwrap();
+        assert_eq!(flags.get("new_checkout"), Some(&true));
+        assert_eq!(flags.get("dark_mode"), Some(&false));
+
+        std::fs::write(&path, "new_checkout = 1\n").unwrap();
+        let error = provider.fetch().await.unwrap_err();
+        assert!(error.contains("flag 'new_checkout' is not a boolean"));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(provider.fetch().await.is_err());
+    }
+
+    #[test]
+    fn guard_fails_without_fairing() {
+        let client = Client::new(rocket(Config::development())).unwrap();
+        let response = client.get("/checkout").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+}
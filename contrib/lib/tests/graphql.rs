@@ -0,0 +1,249 @@
+#[cfg(feature = "graphql")]
+mod graphql_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use rocket::State;
+    use rocket::http::{ContentType, Header, Status, uri::Uri};
+    use rocket::local::blocking::Client;
+    use rocket::request::{self, FromRequest, Request};
+
+    use rocket_contrib::graphql::GraphQL;
+    use rocket_contrib::graphql::juniper::{self, graphql_object, RootNode};
+    use rocket_contrib::graphql::juniper::EmptySubscription;
+
+    struct Context {
+        user: Option<String>,
+        counter: Arc<AtomicI32>,
+    }
+
+    impl juniper::Context for Context {}
+
+    #[rocket::async_trait]
+    impl<'a, 'r> FromRequest<'a, 'r> for Context {
+        type Error = ();
+
+        async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+            if req.headers().contains("X-Banned") {
+                return request::Outcome::Failure((Status::Forbidden, ()));
+            }
+
+            let counter = rocket::try_outcome!(req.guard::<State<'_, Arc<AtomicI32>>>().await);
+            request::Outcome::Success(Context {
+                user: req.headers().get_one("X-User").map(String::from),
+                counter: counter.inner().clone(),
+            })
+        }
+    }
+
+    struct Query;
+
+    #[graphql_object(context = Context)]
+    impl Query {
+        fn me(context: &Context) -> Option<String> {
+            context.user.clone()
+        }
+
+        fn add(a: i32, b: i32) -> i32 {
+            a + b
+        }
+
+        fn counter(context: &Context) -> i32 {
+            context.counter.load(Ordering::SeqCst)
+        }
+    }
+
+    struct Mutation;
+
+    #[graphql_object(context = Context)]
+    impl Mutation {
+        fn increment(context: &Context, by: i32) -> i32 {
+            context.counter.fetch_add(by, Ordering::SeqCst) + by
+        }
+    }
+
+    type Schema = RootNode<'static, Query, Mutation, EmptySubscription<Context>>;
+
+    fn graphql() -> GraphQL<Query, Mutation, EmptySubscription<Context>> {
+        GraphQL::new(Schema::new(Query, Mutation, EmptySubscription::new()))
+    }
+
+    fn client(graphql: GraphQL<Query, Mutation, EmptySubscription<Context>>) -> Client {
+        let rocket = rocket::ignite()
+            .manage(Arc::new(AtomicI32::new(0)))
+            .mount("/graphql", graphql);
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn queries_over_post() {
+        let client = client(graphql());
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body(r#"{"query": "{ add(a: 1, b: 2) }"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"add":3}}"#);
+
+        let response = client.post("/graphql")
+            .header(ContentType::new("application", "graphql"))
+            .body("{ add(a: 2, b: 2) }")
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"add":4}}"#);
+    }
+
+    #[test]
+    fn queries_over_get() {
+        let client = client(graphql());
+        let query = "query%20Add(%24b%3A%20Int!)%20%7B%20add(a%3A%201%2C%20b%3A%20%24b)%20%7D";
+        let variables = "%7B%22b%22%3A%2041%7D";
+        let uri = format!("/graphql?query={}&operationName=Add&variables={}", query, variables);
+
+        let response = client.get(uri).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"add":42}}"#);
+
+        let response = client.get("/graphql").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().contains("missing `query` parameter"));
+    }
+
+    #[test]
+    fn batches_are_executed() {
+        let client = client(graphql());
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body(r#"[{"query": "{ add(a: 1, b: 1) }"}, {"query": "{ add(a: 2, b: 2) }"}]"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().unwrap();
+        assert_eq!(body, r#"[{"data":{"add":2}},{"data":{"add":4}}]"#);
+    }
+
+    #[test]
+    fn context_is_built_from_guards() {
+        let client = client(graphql());
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .header(Header::new("X-User", "bob"))
+            .body(r#"{"query": "{ me }"}"#)
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"me":"bob"}}"#);
+
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body(r#"{"query": "mutation { increment(by: 5) }"}"#)
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"increment":5}}"#);
+
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .header(Header::new("X-Banned", "true"))
+            .body(r#"{"query": "{ counter }"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn invalid_requests_are_rejected() {
+        let client = client(graphql());
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body(r#"{"query": "{ unknown }"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().contains("errors"));
+
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body("not json")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().contains("invalid GraphQL request"));
+    }
+
+    #[test]
+    fn only_queries_are_executed_over_get() {
+        let client = client(graphql());
+        let get = |params: &str, query: &str| {
+            let uri = format!("/graphql?{}query={}", params, Uri::percent_encode(query));
+            let response = client.get(uri).dispatch();
+            (response.status(), response.into_string().unwrap())
+        };
+
+        let (status, body) = get("", "mutation { increment(by: 1) }");
+        assert_eq!(status, Status::MethodNotAllowed);
+        assert!(body.contains("mutation operations must be sent with `POST`"));
+
+        let (status, _) = get("", "# { counter }\nmutation Bump($by: Int!) { increment(by: $by) }");
+        assert_eq!(status, Status::MethodNotAllowed);
+
+        let document = "query Counter { counter } mutation Bump { increment(by: 1) }";
+        let (status, body) = get("", document);
+        assert_eq!(status, Status::BadRequest);
+        assert!(body.contains("no operation to execute"));
+
+        assert_eq!(get("operationName=Bump&", document).0, Status::MethodNotAllowed);
+        let (status, body) = get("operationName=Counter&", document);
+        assert_eq!((status, body.as_str()), (Status::Ok, r#"{"data":{"counter":0}}"#));
+
+        let (status, body) = get("", "fragment F on Query { counter } query Q { ...F }");
+        assert_eq!((status, body.as_str()), (Status::Ok, r#"{"data":{"counter":0}}"#));
+
+        let (status, body) = get("", r#"{ me(x: "}") }"#);
+        assert_eq!(status, Status::BadRequest);
+        assert!(!body.contains("no operation to execute"));
+    }
+
+    #[test]
+    fn post_bodies_must_be_json_or_graphql() {
+        let client = client(graphql());
+        let response = client.post("/graphql")
+            .header(ContentType::Form)
+            .body(r#"{"query": "mutation { increment(by: 1) }"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnsupportedMediaType);
+
+        let response = client.post("/graphql")
+            .body(r#"{"query": "mutation { increment(by: 1) }"}"#)
+            .dispatch();
+
+        assert_eq!(response.status(), Status::UnsupportedMediaType);
+
+        let response = client.post("/graphql")
+            .header(ContentType::JSON)
+            .body(r#"{"query": "{ counter }"}"#)
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), r#"{"data":{"counter":0}}"#);
+    }
+
+    #[test]
+    fn ides_are_served_when_enabled() {
+        let plain = client(graphql());
+        assert_eq!(plain.get("/graphql/graphiql").dispatch().status(), Status::NotFound);
+
+        let graphiql = client(graphql().graphiql());
+        let response = graphiql.get("/graphql/graphiql").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        let body = response.into_string().unwrap();
+        assert!(body.contains("graphiql"));
+        assert!(body.contains("/graphql"));
+
+        let playground = client(graphql().playground());
+        assert_eq!(playground.get("/graphql/graphiql").dispatch().status(), Status::NotFound);
+        let response = playground.get("/graphql/playground").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+    }
+}
@@ -0,0 +1,217 @@
+#[macro_use]
+#[cfg(feature = "grpc_web")]
+extern crate rocket;
+
+#[cfg(feature = "grpc_web")]
+mod grpc_web_tests {
+    use std::convert::Infallible;
+    use std::path::PathBuf;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use rocket::futures::future::BoxFuture;
+    use rocket::http::{ContentType, Status};
+    use rocket::http::hyper::{self, Body, Bytes, HeaderMap, HttpBody, Service};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::grpc_web::GrpcWeb;
+    use rocket_contrib::grpc_web::tonic::transport::NamedService;
+
+    /// A gRPC response body with a single message followed by trailers.
+    struct Reply {
+        message: Option<Bytes>,
+        trailers: Option<HeaderMap>,
+    }
+
+    impl HttpBody for Reply {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_data(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>
+        ) -> Poll<Option<Result<Bytes, Infallible>>> {
+            Poll::Ready(self.message.take().map(Ok))
+        }
+
+        fn poll_trailers(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>
+        ) -> Poll<Result<Option<HeaderMap>, Infallible>> {
+            Poll::Ready(Ok(self.trailers.take()))
+        }
+    }
+
+    /// A gRPC service echoing the messages sent to `Say`, failing `Fail`.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl NamedService for Echo {
+        const NAME: &'static str = "test.Echo";
+    }
+
+    impl Service<hyper::Request<Body>> for Echo {
+        type Response = hyper::Response<Reply>;
+        type Error = Infallible;
+        type Future = BoxFuture<'static, Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: hyper::Request<Body>) -> Self::Future {
+            Box::pin(async move {
+                if req.uri().path() == "/test.Echo/Fail" {
+                    let response = hyper::Response::builder()
+                        .header("content-type", "application/grpc")
+                        .header("grpc-status", "5")
+                        .header("grpc-message", "missing")
+                        .body(Reply { message: None, trailers: None });
+
+                    return Ok(response.unwrap());
+                }
+
+                let content_type = req.headers()["content-type"].clone();
+                let message = hyper::to_bytes(req.into_body()).await.unwrap();
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", "0".parse().unwrap());
+
+                let response = hyper::Response::builder()
+                    .header("content-type", "application/grpc")
+                    .header("x-received-content-type", content_type)
+                    .body(Reply { message: Some(message), trailers: Some(trailers) });
+
+                Ok(response.unwrap())
+            })
+        }
+    }
+
+    #[get("/")]
+    fn index() -> &'static str {
+        "REST"
+    }
+
+    #[post("/<path..>", rank = 20)]
+    fn fallback(path: PathBuf) -> String {
+        format!("REST: {}", path.display())
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/", routes![index, fallback])
+            .mount("/", GrpcWeb::new(Echo));
+
+        Client::new(rocket).unwrap()
+    }
+
+    /// A message frame carrying `message`.
+    fn frame(message: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0];
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(message);
+        frame
+    }
+
+    /// The trailers frame of a successful call.
+    fn ok_trailers() -> Vec<u8> {
+        let mut frame = vec![0x80, 0, 0, 0, 15];
+        frame.extend_from_slice(b"grpc-status:0\r\n");
+        frame
+    }
+
+    #[test]
+    fn binary_requests_are_bridged() {
+        let client = client();
+        let response = client.post("/test.Echo/Say")
+            .header(ContentType::new("application", "grpc-web+proto"))
+            .body(frame(b"hello"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let content_type = ContentType::new("application", "grpc-web+proto");
+        assert_eq!(response.content_type(), Some(content_type));
+        assert!(response.headers().get_one("grpc-status").is_none());
+
+        let mut expected = frame(b"hello");
+        expected.extend(ok_trailers());
+        assert_eq!(response.into_bytes().unwrap(), expected);
+
+        let index = client.get("/").dispatch();
+        assert_eq!(index.into_string().unwrap(), "REST");
+    }
+
+    #[test]
+    fn text_requests_are_bridged() {
+        let client = client();
+        let response = client.post("/test.Echo/Say")
+            .header(ContentType::new("application", "grpc-web-text"))
+            .body(base64::encode(frame(b"hello")))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let content_type = ContentType::new("application", "grpc-web-text+proto");
+        assert_eq!(response.content_type(), Some(content_type));
+
+        let body = response.into_string().unwrap();
+        let expected = base64::encode(frame(b"hello")) + &base64::encode(ok_trailers());
+        assert_eq!(body, expected);
+
+        let response = client.post("/test.Echo/Say")
+            .header(ContentType::new("application", "grpc-web-text"))
+            .body("not base64!")
+            .dispatch();
+
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn services_receive_grpc_requests() {
+        let client = client();
+        let response = client.post("/test.Echo/Say")
+            .header(ContentType::new("application", "grpc-web"))
+            .body(frame(b""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get("Content-Type").count(), 1);
+        let received = response.headers().get_one("x-received-content-type");
+        assert_eq!(received, Some("application/grpc"));
+    }
+
+    #[test]
+    fn trailers_only_responses_keep_their_status() {
+        let client = client();
+        let response = client.post("/test.Echo/Fail")
+            .header(ContentType::new("application", "grpc-web+proto"))
+            .body(frame(b""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("grpc-status"), Some("5"));
+        assert_eq!(response.headers().get_one("grpc-message"), Some("missing"));
+        assert!(response.into_bytes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn services_see_their_own_path_under_any_mount_point() {
+        let rocket = rocket::ignite().mount("/api", GrpcWeb::new(Echo));
+        let client = Client::new(rocket).unwrap();
+        let response = client.post("/api/test.Echo/Fail")
+            .header(ContentType::new("application", "grpc-web"))
+            .body(frame(b""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("grpc-status"), Some("5"));
+    }
+
+    #[test]
+    fn other_requests_are_forwarded() {
+        let client = client();
+        let response = client.post("/test.Echo/Say").header(ContentType::JSON).dispatch();
+        assert_eq!(response.into_string().unwrap(), "REST: test.Echo/Say");
+
+        let response = client.post("/test.Echo/Say").dispatch();
+        assert_eq!(response.into_string().unwrap(), "REST: test.Echo/Say");
+    }
+}
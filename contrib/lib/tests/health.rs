@@ -0,0 +1,120 @@
+#[cfg(feature = "health")]
+extern crate rocket;
+
+#[cfg(feature = "health")]
+mod health_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket_contrib::health::HealthChecks;
+
+    use serde_json::{json, Value};
+
+    fn report(client: &Client, path: &str) -> (Status, Value) {
+        let response = client.get(path).dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let status = response.status();
+        (status, serde_json::from_str(&response.into_string().unwrap()).unwrap())
+    }
+
+    fn without_durations(mut report: Value) -> Value {
+        for check in report["checks"].as_array_mut().unwrap() {
+            assert!(check["duration_ms"].is_u64());
+            check.as_object_mut().unwrap().remove("duration_ms");
+        }
+
+        report
+    }
+
+    #[test]
+    fn endpoints_without_checks_pass() {
+        let client = Client::new(rocket::ignite().attach(HealthChecks::new())).unwrap();
+        let pass = json!({ "status": "pass", "checks": [] });
+        assert_eq!(report(&client, "/healthz"), (Status::Ok, pass.clone()));
+        assert_eq!(report(&client, "/readyz"), (Status::Ok, pass));
+    }
+
+    #[test]
+    fn checks_are_aggregated() {
+        let cache_up = Arc::new(AtomicBool::new(true));
+        let up = cache_up.clone();
+        let health = HealthChecks::new()
+            .liveness("loop", || async { Ok::<_, String>(()) })
+            .readiness("cache", move || {
+                let up = up.load(Ordering::SeqCst);
+                async move { if up { Ok(()) } else { Err("cache is down") } }
+            })
+            .readiness("disk", || async { Ok::<_, String>(()) });
+
+        let client = Client::new(rocket::ignite().attach(health)).unwrap();
+        let (status, live) = report(&client, "/healthz");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(without_durations(live), json!({
+            "status": "pass",
+            "checks": [{ "name": "loop", "status": "pass" }]
+        }));
+
+        let (status, ready) = report(&client, "/readyz");
+        assert_eq!(status, Status::Ok);
+        assert_eq!(ready["checks"].as_array().unwrap().len(), 2);
+
+        cache_up.store(false, Ordering::SeqCst);
+        let (status, ready) = report(&client, "/readyz");
+        assert_eq!(status, Status::ServiceUnavailable);
+        assert_eq!(without_durations(ready), json!({
+            "status": "fail",
+            "checks": [
+                { "name": "cache", "status": "fail", "error": "cache is down" },
+                { "name": "disk", "status": "pass" }
+            ]
+        }));
+
+        let (status, _) = report(&client, "/healthz");
+        assert_eq!(status, Status::Ok);
+    }
+
+    #[test]
+    fn slow_checks_time_out() {
+        let health = HealthChecks::new()
+            .timeout(Duration::from_millis(50))
+            .readiness("slow", || async {
+                rocket::tokio::time::delay_for(Duration::from_secs(10)).await;
+                Ok::<_, String>(())
+            });
+
+        let client = Client::new(rocket::ignite().attach(health)).unwrap();
+        let (status, ready) = report(&client, "/readyz");
+        assert_eq!(status, Status::ServiceUnavailable);
+        assert_eq!(ready["checks"][0]["error"], "timed out after 50ms");
+    }
+
+    #[test]
+    fn paths_are_configurable() {
+        let health = HealthChecks::new().liveness_path("/live").readiness_path("/ready");
+        let client = Client::new(rocket::ignite().attach(health)).unwrap();
+        assert_eq!(report(&client, "/live").0, Status::Ok);
+        assert_eq!(report(&client, "/ready").0, Status::Ok);
+        assert_eq!(client.get("/healthz").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn not_ready_while_draining() {
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = ran.clone();
+        let health = HealthChecks::new().readiness("db", move || {
+            flag.store(true, Ordering::SeqCst);
+            async { Ok::<_, String>(()) }
+        });
+
+        let client = Client::new(rocket::ignite().attach(health)).unwrap();
+        client.cargo().shutdown().shutdown();
+
+        let draining = json!({ "status": "draining", "checks": [] });
+        assert_eq!(report(&client, "/readyz"), (Status::ServiceUnavailable, draining));
+        assert_eq!(report(&client, "/healthz").0, Status::Ok);
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+}
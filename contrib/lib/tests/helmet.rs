@@ -7,11 +7,22 @@ mod helmet_tests {
     use rocket::http::{Status, uri::Uri};
     use rocket::local::blocking::{Client, LocalResponse};
 
+    use rocket::config::{Config, Environment, Table, Value};
     use rocket_contrib::helmet::*;
     use time::Duration;
 
     #[get("/")] fn hello() { }
 
+    #[get("/embed")]
+    fn embed() -> Exempt<&'static str> {
+        Exempt::from::<Frame>("embed").and::<FrameAncestors>()
+    }
+
+    #[get("/raw")]
+    fn raw() -> Exempt<&'static str> {
+        Exempt::all("raw")
+    }
+
     macro_rules! assert_header {
         ($response:ident, $name:expr, $value:expr) => {
             match $response.headers().get_one($name) {
@@ -146,4 +157,100 @@ mod helmet_tests {
             assert_header!(response, "X-DNS-Prefetch-Control", "on");
         });
     }
+
+    #[test]
+    fn frame_ancestors_and_permissions_test() {
+        let partner = Uri::parse("https://partner.example.com").unwrap();
+        let helmet = SpaceHelmet::default()
+            .enable(FrameAncestors::Allow(vec![Allow::This, Allow::Origin(partner)]))
+            .enable(Permission::default().allow("fullscreen", Allow::Any));
+
+        dispatch!(helmet, |response: LocalResponse<'_>| {
+            assert_header!(response, "Content-Security-Policy",
+                "frame-ancestors 'self' https://partner.example.com");
+
+            assert_header!(response, "Permissions-Policy",
+                "camera=(), microphone=(), geolocation=(), interest-cohort=(), fullscreen=*");
+        });
+
+        let maps = Uri::parse("https://maps.example.com").unwrap();
+        let helmet = SpaceHelmet::new()
+            .enable(FrameAncestors::None)
+            .enable(Permission::new()
+                .allow("geolocation", Allow::This)
+                .allow("geolocation", Allow::Origin(maps)));
+
+        dispatch!(helmet, |response: LocalResponse<'_>| {
+            assert_header!(response, "Content-Security-Policy", "frame-ancestors 'none'");
+            assert_header!(response, "Permissions-Policy",
+                "geolocation=(self \"https://maps.example.com\")");
+        });
+    }
+
+    #[test]
+    fn exempt_routes_test() {
+        let helmet = SpaceHelmet::default().enable(FrameAncestors::default());
+        let rocket = rocket::ignite().mount("/", routes![hello, embed, raw]).attach(helmet);
+        let client = Client::new(rocket).unwrap();
+
+        let response = client.get("/").dispatch();
+        assert_header!(response, "X-Frame-Options", "SAMEORIGIN");
+        assert_header!(response, "Content-Security-Policy", "frame-ancestors 'self'");
+
+        let response = client.get("/embed").dispatch();
+        assert_header!(response, "X-Content-Type-Options", "nosniff");
+        assert_no_header!(response, "X-Frame-Options");
+        assert_no_header!(response, "Content-Security-Policy");
+
+        let response = client.get("/raw").dispatch();
+        assert_no_header!(response, "X-Content-Type-Options");
+        assert_no_header!(response, "X-XSS-Protection");
+        assert_no_header!(response, "X-Frame-Options");
+    }
+
+    #[test]
+    fn config_test() {
+        let mut permissions = Table::new();
+        permissions.insert("camera".into(), Value::Array(vec![]));
+        permissions.insert("geolocation".into(), Value::from(vec!["self"]));
+
+        let mut table = Table::new();
+        table.insert("hsts".into(), Value::from(3600));
+        table.insert("xss_filter".into(), Value::from(false));
+        table.insert("frame".into(), Value::from("deny"));
+        table.insert("frame_ancestors".into(), Value::Array(vec![]));
+        table.insert("referrer".into(), Value::from("same-origin"));
+        table.insert("permissions".into(), Value::Table(permissions));
+
+        let config = Config::build(Environment::Development)
+            .extra("helmet", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config)
+            .mount("/", routes![hello])
+            .attach(SpaceHelmet::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/").dispatch();
+        assert_header!(response, "Strict-Transport-Security", "max-age=3600");
+        assert_header!(response, "X-Frame-Options", "DENY");
+        assert_header!(response, "Content-Security-Policy", "frame-ancestors 'none'");
+        assert_header!(response, "Referrer-Policy", "same-origin");
+        assert_header!(response, "Permissions-Policy", "camera=(), geolocation=(self)");
+        assert_header!(response, "X-Content-Type-Options", "nosniff");
+        assert_no_header!(response, "X-XSS-Protection");
+    }
+
+    #[test]
+    fn invalid_config_test() {
+        let mut table = Table::new();
+        table.insert("frame".into(), Value::from("sometimes"));
+
+        let config = Config::build(Environment::Development)
+            .extra("helmet", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config).attach(SpaceHelmet::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
 }
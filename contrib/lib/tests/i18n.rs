@@ -0,0 +1,219 @@
+#[macro_use]
+#[cfg(feature = "i18n")]
+extern crate rocket;
+
+#[cfg(feature = "i18n")]
+mod i18n_tests {
+    use std::path::{Path, PathBuf};
+
+    use rocket::Rocket;
+    use rocket::config::{Config, Environment, Table, Value};
+    use rocket::http::{Cookie, Header, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::i18n::{I18n, Locales, Error, FluentArgs};
+
+    fn i18n_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("i18n")
+    }
+
+    fn locales() -> Locales {
+        Locales::load(i18n_dir().join("locales"), "en-US").expect("valid translations")
+    }
+
+    #[get("/t/<key>?<name>")]
+    fn translate(i18n: I18n<'_>, key: String, name: Option<String>) -> String {
+        let mut args = FluentArgs::new();
+        if let Some(name) = name {
+            args.set("name", name);
+        }
+
+        i18n.t(&key, Some(&args))
+    }
+
+    #[get("/locale")]
+    fn locale(i18n: I18n<'_>) -> String {
+        i18n.locale().to_string()
+    }
+
+    fn rocket() -> Rocket {
+        rocket::ignite()
+            .mount("/", routes![translate, locale])
+            .attach(I18n::fairing_with(locales()))
+    }
+
+    #[test]
+    fn test_locales_load() {
+        let locales = locales();
+        let available: Vec<_> = locales.available().iter().map(|l| l.to_string()).collect();
+        assert_eq!(available, vec!["en-US", "fr"]);
+        assert_eq!(locales.default_locale().to_string(), "en-US");
+
+        match Locales::load(i18n_dir().join("locales"), "de") {
+            Err(Error::MissingDefault(locale)) => assert_eq!(locale.to_string(), "de"),
+            _ => panic!("expected a missing default locale error"),
+        }
+
+        assert!(Locales::load(i18n_dir().join("missing"), "en-US").is_err());
+    }
+
+    #[test]
+    fn test_default_locale() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/t/greeting?name=Rocket").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, Rocket!");
+        assert_eq!(client.get("/locale").dispatch().into_string().unwrap(), "en-US");
+
+        let response = client.get("/locale")
+            .header(Header::new("Accept-Language", "de-DE,de;q=0.8"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "en-US");
+    }
+
+    #[test]
+    fn test_accept_language_negotiation() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/t/greeting?name=Rocket")
+            .header(Header::new("Accept-Language", "fr-CA,fr;q=0.9,en;q=0.5"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "Bonjour, Rocket !");
+
+        let response = client.get("/t/login.placeholder")
+            .header(Header::new("Accept-Language", "fr"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "Votre e-mail");
+    }
+
+    #[test]
+    fn test_cookie_takes_precedence() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/locale")
+            .header(Header::new("Accept-Language", "fr"))
+            .cookie(Cookie::new("lang", "en-US"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "en-US");
+
+        let response = client.get("/locale")
+            .cookie(Cookie::new("lang", "fr"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "fr");
+    }
+
+    #[test]
+    fn test_missing_translations() {
+        let client = Client::new(rocket()).unwrap();
+        let response = client.get("/t/farewell")
+            .header(Header::new("Accept-Language", "fr"))
+            .dispatch();
+
+        assert_eq!(response.into_string().unwrap(), "Goodbye!");
+
+        let response = client.get("/t/unknown").dispatch();
+        assert_eq!(response.into_string().unwrap(), "unknown");
+    }
+
+    #[test]
+    fn test_configured_fairing() {
+        let mut table = Table::new();
+        let dir = i18n_dir().join("locales");
+        table.insert("dir".into(), Value::from(dir.to_str().expect("i18n directory")));
+        table.insert("default_locale".into(), Value::from("fr"));
+
+        let config = Config::build(Environment::Development)
+            .extra("i18n", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config)
+            .mount("/", routes![locale])
+            .attach(I18n::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.get("/locale").dispatch().into_string().unwrap(), "fr");
+    }
+
+    #[test]
+    fn test_guard_requires_fairing() {
+        let rocket = rocket::ignite().mount("/", routes![locale]);
+        let client = Client::new(rocket).unwrap();
+        assert_eq!(client.get("/locale").dispatch().status(), Status::InternalServerError);
+    }
+
+    #[cfg(any(feature = "tera_templates", feature = "handlebars_templates"))]
+    mod templates_tests {
+        use super::*;
+
+        use rocket_contrib::templates::Template;
+
+        #[get("/<name>/<user>")]
+        fn render(name: String, user: String) -> Template {
+            let mut map = std::collections::HashMap::new();
+            map.insert("name", user);
+            Template::render(name, &map)
+        }
+
+        fn client() -> Client {
+            let config = Config::build(Environment::Development)
+                .extra("template_dir", i18n_dir().join("templates").to_str().expect("dir"))
+                .expect("valid configuration");
+
+            let locales = locales();
+            let rocket = rocket::custom(config)
+                .mount("/", routes![render])
+                .attach(I18n::fairing_with(locales.clone()))
+                .attach(Template::custom(move |engines| locales.register(engines)));
+
+            Client::new(rocket).unwrap()
+        }
+
+        #[test]
+        #[cfg(feature = "tera_templates")]
+        fn test_tera_translate_function() {
+            let client = client();
+            let response = client.get("/tera/Rocket").dispatch();
+            assert_eq!(response.into_string().unwrap().trim(), "Hello, Rocket! Goodbye!");
+
+            let response = client.get("/tera/Rocket")
+                .header(Header::new("Accept-Language", "fr"))
+                .dispatch();
+
+            assert_eq!(response.into_string().unwrap().trim(), "Bonjour, Rocket ! Goodbye!");
+        }
+
+        #[test]
+        #[cfg(feature = "tera_templates")]
+        fn test_tera_translations_are_escaped() {
+            let client = client();
+            let response = client.get("/escape_tera/%3Cscript%3E").dispatch();
+            let html = response.into_string().unwrap();
+            assert_eq!(html.trim(), "<p>Hello, &lt;script&gt;!</p>");
+        }
+
+        #[test]
+        #[cfg(feature = "handlebars_templates")]
+        fn test_handlebars_translate_helper() {
+            let client = client();
+            let response = client.get("/hbs/Rocket").dispatch();
+            assert_eq!(response.into_string().unwrap().trim(), "Hello, Rocket! Your email");
+
+            let response = client.get("/hbs/Rocket")
+                .cookie(Cookie::new("lang", "fr"))
+                .dispatch();
+
+            assert_eq!(response.into_string().unwrap().trim(), "Bonjour, Rocket ! Votre e-mail");
+        }
+
+        #[test]
+        #[cfg(feature = "handlebars_templates")]
+        fn test_handlebars_translations_are_escaped() {
+            let client = client();
+            let response = client.get("/escape_hbs/%3Cscript%3E").dispatch();
+            let html = response.into_string().unwrap();
+            assert_eq!(html.trim(), "<p>Hello, &lt;script&gt;!</p>");
+        }
+    }
+}
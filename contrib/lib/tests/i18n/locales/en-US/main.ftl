@@ -0,0 +1,4 @@
+greeting = Hello, { $name }!
+farewell = Goodbye!
+login = Log in
+    .placeholder = Your email
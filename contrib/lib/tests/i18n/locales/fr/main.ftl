@@ -0,0 +1,3 @@
+greeting = Bonjour, { $name } !
+login = Connexion
+    .placeholder = Votre e-mail
@@ -0,0 +1,104 @@
+#[macro_use]
+#[cfg(feature = "maintenance")]
+extern crate rocket;
+
+#[cfg(feature = "maintenance")]
+mod maintenance_tests {
+    use std::time::Duration;
+
+    use rocket::State;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::maintenance::{Maintenance, MaintenanceMode};
+
+    #[get("/")]
+    fn index() -> &'static str {
+        "index"
+    }
+
+    #[post("/data", data = "<body>")]
+    fn data(body: String) -> String {
+        body
+    }
+
+    #[get("/health")]
+    fn health() -> &'static str {
+        "ok"
+    }
+
+    #[post("/admin/maintenance/<on>")]
+    fn toggle(on: bool, mode: State<'_, MaintenanceMode>) -> &'static str {
+        if on { mode.enable(); "on" } else { mode.disable(); "off" }
+    }
+
+    fn client(maintenance: Maintenance) -> Client {
+        let rocket = rocket::ignite()
+            .mount("/", routes![index, data, health, toggle])
+            .attach(maintenance);
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn requests_are_handled_when_disabled() {
+        let client = client(Maintenance::new());
+        assert_eq!(client.get("/").dispatch().into_string().unwrap(), "index");
+        let response = client.post("/data").body("hello").dispatch();
+        assert_eq!(response.into_string().unwrap(), "hello");
+        assert_eq!(client.get("/missing").dispatch().status(), Status::NotFound);
+        let response = client.get("/__rocket/maintenance").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn requests_are_rejected_when_enabled() {
+        let maintenance = Maintenance::new().retry_after(Duration::from_secs(60));
+        maintenance.handle().enable();
+
+        let client = client(maintenance);
+        for response in vec![
+            client.get("/").dispatch(),
+            client.post("/data").body("hello").dispatch(),
+            client.get("/missing").dispatch(),
+            client.put("/data").body("hello").dispatch(),
+        ] {
+            assert_eq!(response.status(), Status::ServiceUnavailable);
+            assert_eq!(response.headers().get_one("Retry-After"), Some("60"));
+            assert_eq!(response.content_type(), Some(ContentType::Plain));
+        }
+    }
+
+    #[test]
+    fn allowed_paths_are_handled_when_enabled() {
+        let maintenance = Maintenance::new().allow("/health").allow("/admin/");
+        let client = client(maintenance);
+
+        let response = client.post("/admin/maintenance/true").dispatch();
+        assert_eq!(response.into_string().unwrap(), "on");
+        assert_eq!(client.get("/").dispatch().status(), Status::ServiceUnavailable);
+        assert_eq!(client.get("/health").dispatch().into_string().unwrap(), "ok");
+        assert_eq!(client.get("/healthz").dispatch().status(), Status::ServiceUnavailable);
+
+        let response = client.post("/admin/maintenance/false").dispatch();
+        assert_eq!(response.into_string().unwrap(), "off");
+        assert_eq!(client.get("/").dispatch().into_string().unwrap(), "index");
+    }
+
+    #[test]
+    fn custom_page() {
+        let maintenance = Maintenance::new().page(ContentType::HTML, "<h1>Back soon</h1>");
+        let mode = maintenance.handle();
+        let client = client(maintenance);
+
+        mode.enable();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.headers().get_one("Retry-After"), Some("300"));
+        assert_eq!(response.into_string().unwrap(), "<h1>Back soon</h1>");
+
+        mode.disable();
+        assert_eq!(client.get("/").dispatch().status(), Status::Ok);
+    }
+}
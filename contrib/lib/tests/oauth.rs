@@ -0,0 +1,202 @@
+#[macro_use]
+#[cfg(feature = "oauth")]
+extern crate rocket;
+
+#[cfg(feature = "oauth")]
+mod oauth_tests {
+    use rocket::Config;
+    use rocket::config::{Environment, Table, Value};
+    use rocket::http::{Cookie, Status};
+    use rocket::local::blocking::Client;
+    use rocket::request::Outcome;
+
+    use rocket_contrib::oauth::{OAuth2, OAuthIdentity, OAuthError};
+
+    #[get("/me")]
+    fn me(identity: OAuthIdentity) -> String {
+        identity.subject().to_string()
+    }
+
+    fn provider(scopes: &[&str]) -> Table {
+        let mut table = Table::new();
+        table.insert("client_id".into(), Value::from("rocket"));
+        table.insert("auth_uri".into(), Value::from("https://sso.example.com/authorize"));
+        table.insert("token_uri".into(), Value::from("https://sso.example.com/token"));
+        table.insert("redirect_uri".into(), Value::from("http://localhost/auth/test/callback"));
+        table.insert("scopes".into(), Value::from(scopes.to_vec()));
+        table
+    }
+
+    fn config_in(environment: Environment, provider: Table) -> Config {
+        let mut oauth = Table::new();
+        oauth.insert("test".into(), Value::from(provider));
+        Config::build(environment)
+            .extra("oauth", oauth)
+            .unwrap()
+    }
+
+    fn config(provider: Table) -> Config {
+        config_in(Environment::Development, provider)
+    }
+
+    fn client_with(config: Config) -> Client {
+        let rocket = rocket::custom(config)
+            .mount("/", routes![me])
+            .attach(OAuth2::fairing("test", "/auth/test"));
+
+        Client::new(rocket).unwrap()
+    }
+
+    fn client(provider: Table) -> Client {
+        client_with(config(provider))
+    }
+
+    /// Returns the value of the query parameter `name` in `url`.
+    fn param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+        url.split(|c: char| c == '?' || c == '&')
+            .map(|pair| pair.splitn(2, '='))
+            .find_map(|mut pair| match pair.next() == Some(name) {
+                true => pair.next(),
+                false => None,
+            })
+    }
+
+    #[test]
+    fn login_redirects_to_provider() {
+        let client = client(provider(&["openid", "email"]));
+        let response = client.get("/auth/test/login").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+
+        let location = response.headers().get_one("Location").unwrap();
+        assert!(location.starts_with("https://sso.example.com/authorize?"));
+        assert_eq!(param(location, "response_type"), Some("code"));
+        assert_eq!(param(location, "client_id"), Some("rocket"));
+        assert_eq!(param(location, "code_challenge_method"), Some("S256"));
+        assert_eq!(param(location, "scope"), Some("openid+email"));
+        assert!(param(location, "state").is_some());
+        assert!(param(location, "nonce").is_some());
+        assert_eq!(param(location, "code_challenge").map(str::len), Some(43));
+
+        let response = client.get("/auth/test/login").dispatch();
+        let other = response.headers().get_one("Location").unwrap();
+        assert_ne!(param(location, "state"), param(other, "state"));
+    }
+
+    #[test]
+    fn nonce_is_only_sent_to_openid_providers() {
+        let client = client(provider(&["repo"]));
+        let response = client.get("/auth/test/login").dispatch();
+        let location = response.headers().get_one("Location").unwrap();
+        assert!(param(location, "nonce").is_none());
+    }
+
+    #[test]
+    fn callback_checks_state() {
+        let client = client(provider(&[]));
+        let response = client.get("/auth/test/callback?code=abc&state=xyz").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        client.get("/auth/test/login").dispatch();
+        let response = client.get("/auth/test/callback?code=abc&state=xyz").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        let response = client.get("/auth/test/login").dispatch();
+        let location = response.headers().get_one("Location").unwrap().to_string();
+        let state = param(&location, "state").unwrap();
+        let uri = format!("/auth/test/callback?error=access_denied&state={}", state);
+        assert_eq!(client.get(uri.clone()).dispatch().status(), Status::Unauthorized);
+
+        // The flow's cookie is removed by the first callback: states can't be replayed.
+        assert_eq!(client.get(uri).dispatch().status(), Status::BadRequest);
+    }
+
+    /// Returns the `next` path kept in the flow's cookie by a login request
+    /// to `uri`.
+    fn flow_next(uri: &str) -> Option<String> {
+        let client = client(provider(&[]));
+        let response = client.get(uri).dispatch();
+        let flow = response.cookies().get_private("rocket_oauth_test").unwrap();
+        let flow: serde_json::Value = serde_json::from_str(flow.value()).unwrap();
+        flow["next"].as_str().map(String::from)
+    }
+
+    #[test]
+    fn cookies_are_secure_outside_development() {
+        let response = client(provider(&[])).get("/auth/test/login").dispatch();
+        let flow = response.cookies().get_private("rocket_oauth_test").unwrap();
+        assert_ne!(flow.secure(), Some(true));
+
+        let client = client_with(config_in(Environment::Production, provider(&[])));
+        let response = client.get("/auth/test/login").dispatch();
+        let flow = response.cookies().get_private("rocket_oauth_test").unwrap();
+        assert_eq!(flow.secure(), Some(true));
+    }
+
+    #[test]
+    fn login_only_keeps_local_next_paths() {
+        let next = flow_next("/auth/test/login?next=/account%3Ftab%3Dsecurity");
+        assert_eq!(next.as_deref(), Some("/account?tab=security"));
+
+        assert_eq!(flow_next("/auth/test/login?next=https://evil.com"), None);
+        assert_eq!(flow_next("/auth/test/login?next=//evil.com"), None);
+        assert_eq!(flow_next("/auth/test/login?next=/%5Cevil.com"), None);
+        assert_eq!(flow_next("/auth/test/login?next=/%5C%5Cevil.com"), None);
+        assert_eq!(flow_next("/auth/test/login?next=/%09/evil.com"), None);
+        assert_eq!(flow_next("/auth/test/login?next=evil.com"), None);
+    }
+
+    #[test]
+    fn identity_guard_requires_sign_in() {
+        let client = client(provider(&[]));
+        assert_eq!(client.get("/me").dispatch().status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn expired_identities_are_signed_out() {
+        let identity = |expires_at: i64| Cookie::new("rocket_oauth", serde_json::json!({
+            "provider": "test",
+            "subject": "alice",
+            "claims": {},
+            "access_token": "token",
+            "expires_at": expires_at,
+        }).to_string());
+
+        let client = client(provider(&[]));
+        let response = client.get("/me").private_cookie(identity(i64::max_value())).dispatch();
+        assert_eq!(response.into_string().unwrap(), "alice");
+
+        let response = client.get("/me").private_cookie(identity(0)).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let removal = response.cookies().get("rocket_oauth").unwrap();
+        assert_eq!(removal.value(), "");
+    }
+
+    #[test]
+    fn identity_guard_can_be_stubbed() {
+        let rocket = rocket::custom(config(provider(&[])))
+            .mount("/", routes![me])
+            .attach(OAuth2::fairing("test", "/auth/test"));
+
+        let client = Client::tracked_with(rocket, |overrides| overrides.guard(|_| {
+            Outcome::<OAuthIdentity, _>::Failure((Status::Forbidden, OAuthError::Unauthenticated))
+        })).unwrap();
+
+        assert_eq!(client.get("/me").dispatch().status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn missing_or_invalid_config_aborts_launch() {
+        let rocket = rocket::ignite().attach(OAuth2::fairing("test", "/auth/test"));
+        assert!(Client::new(rocket).is_err());
+
+        let mut table = provider(&[]);
+        table.remove("token_uri");
+        let rocket = rocket::custom(config(table)).attach(OAuth2::fairing("test", "/auth/test"));
+        assert!(Client::new(rocket).is_err());
+
+        let mut table = provider(&[]);
+        table.insert("provider".into(), Value::from("myspace"));
+        let rocket = rocket::custom(config(table)).attach(OAuth2::fairing("test", "/auth/test"));
+        assert!(Client::new(rocket).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(feature = "openapi")]
+#[macro_use] extern crate rocket;
+
+#[cfg(feature = "openapi")]
+mod openapi_tests {
+    use rocket::Rocket;
+    use rocket::config::{Config, Environment};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::request::Form;
+    use rocket::response::status;
+    use rocket_contrib::json::{Json, JsonValue};
+    use rocket_contrib::openapi::OpenApi;
+
+    use serde_json::{json, Value};
+
+    #[derive(FromForm, JsonSchema)]
+    struct Filter {
+        /// Only items with this tag.
+        tag: Option<String>,
+    }
+
+    /// Lists the items.
+    ///
+    /// Items are listed by name.
+    #[get("/items?<limit>")]
+    #[openapi(tags = "items")]
+    fn items(limit: Option<usize>) -> Json<Vec<String>> {
+        Json(vec!["apple".into(); limit.unwrap_or(1)])
+    }
+
+    #[post("/items/<id>", data = "<names>")]
+    #[openapi(operation_id = "addItems")]
+    fn add(id: u32, names: Json<Vec<String>>) -> status::Created<JsonValue> {
+        let _ = (id, names);
+        status::Created::new("/items/1")
+    }
+
+    #[post("/search", data = "<filter>")]
+    #[openapi]
+    fn search(filter: Form<Filter>) -> Option<String> {
+        filter.into_inner().tag
+    }
+
+    #[get("/hidden")]
+    fn hidden() { }
+
+    fn rocket(env: Environment) -> Rocket {
+        let config = Config::build(env).finalize().unwrap();
+        rocket::custom(config).mount("/api", routes![items, add, search, hidden])
+    }
+
+    fn document(client: &Client, path: &str) -> Value {
+        let response = client.get(path).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        serde_json::from_str(&response.into_string().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn document_describes_routes() {
+        let openapi = OpenApi::new("Inventory", "1.0.0").description("Stock.");
+        let client = Client::new(rocket(Environment::Production).attach(openapi)).unwrap();
+        let doc = document(&client, "/openapi.json");
+
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["info"], json!({
+            "title": "Inventory",
+            "version": "1.0.0",
+            "description": "Stock."
+        }));
+
+        let paths = doc["paths"].as_object().unwrap();
+        let mut names: Vec<_> = paths.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["/api/items", "/api/items/{id}", "/api/search"]);
+
+        let list = &paths["/api/items"]["get"];
+        assert_eq!(list["operationId"], "items");
+        assert_eq!(list["summary"], "Lists the items.");
+        assert_eq!(list["description"], "Items are listed by name.");
+        assert_eq!(list["tags"], json!(["items"]));
+        assert_eq!(list["parameters"], json!([{
+            "name": "limit",
+            "in": "query",
+            "required": false,
+            "schema": { "type": "integer", "format": "int64", "nullable": true }
+        }]));
+        assert_eq!(list["responses"]["200"]["content"]["application/json"]["schema"], json!({
+            "type": "array",
+            "items": { "type": "string" }
+        }));
+
+        let add = &paths["/api/items/{id}"]["post"];
+        assert_eq!(add["operationId"], "addItems");
+        assert_eq!(add["parameters"][0]["in"], "path");
+        assert_eq!(add["parameters"][0]["required"], true);
+        assert_eq!(add["requestBody"]["required"], true);
+        assert_eq!(add["requestBody"]["content"]["application/json"]["schema"]["type"], "array");
+        assert!(add["responses"]["201"]["content"]["application/json"].is_object());
+    }
+
+    #[test]
+    fn derived_schemas_are_components() {
+        let client = Client::new(rocket(Environment::Production).attach(OpenApi::new("A", "1")))
+            .unwrap();
+
+        let doc = document(&client, "/openapi.json");
+        let search = &doc["paths"]["/api/search"]["post"];
+        let content = &search["requestBody"]["content"];
+        assert_eq!(content["application/x-www-form-urlencoded"]["schema"], json!({
+            "$ref": "#/components/schemas/Filter"
+        }));
+
+        assert!(search["responses"]["200"].is_object());
+        assert!(search["responses"]["404"].is_object());
+        assert_eq!(doc["components"]["schemas"]["Filter"], json!({
+            "type": "object",
+            "properties": {
+                "tag": {
+                    "type": "string",
+                    "nullable": true,
+                    "description": "Only items with this tag."
+                }
+            }
+        }));
+    }
+
+    #[test]
+    fn swagger_ui_is_served_in_development() {
+        let openapi = OpenApi::new("A", "1").path("/docs/api.json").swagger_ui("/docs");
+        let client = Client::new(rocket(Environment::Development).attach(openapi)).unwrap();
+        assert!(document(&client, "/docs/api.json")["paths"]["/api/items"].is_object());
+        assert_eq!(client.get("/openapi.json").dispatch().status(), Status::NotFound);
+
+        let response = client.get("/docs").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert!(response.into_string().unwrap().contains("url: \"/docs/api.json\""));
+
+        let client = Client::new(rocket(Environment::Production).attach(OpenApi::new("A", "1")))
+            .unwrap();
+
+        assert_eq!(client.get("/swagger-ui").dispatch().status(), Status::NotFound);
+    }
+}
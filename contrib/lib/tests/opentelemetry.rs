@@ -0,0 +1,75 @@
+#[macro_use]
+#[cfg(feature = "opentelemetry")]
+extern crate rocket;
+
+#[cfg(feature = "opentelemetry")]
+mod opentelemetry_tests {
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::opentelemetry::OpenTelemetry;
+    use rocket_contrib::opentelemetry::opentelemetry_crate::{global, sdk};
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[get("/hello/<name>")]
+    fn hello(name: String) -> String {
+        format!("Hello, {}!", name)
+    }
+
+    fn client() -> Client {
+        global::set_tracer_provider(sdk::trace::TracerProvider::builder().build());
+        let rocket = rocket::ignite()
+            .mount("/", routes![hello])
+            .attach(OpenTelemetry::new());
+
+        Client::new(rocket).unwrap()
+    }
+
+    /// Returns the trace ID and span ID of a `traceparent` header value.
+    fn ids(traceparent: &str) -> (&str, &str) {
+        let parts: Vec<_> = traceparent.split('-').collect();
+        assert_eq!(parts.len(), 4, "malformed traceparent: {}", traceparent);
+        (parts[1], parts[2])
+    }
+
+    #[test]
+    fn incoming_trace_is_continued() {
+        let client = client();
+        let response = client.get("/hello/Bob")
+            .header(Header::new("traceparent", PARENT))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        let traceparent = response.headers().get_one("traceparent").unwrap().to_string();
+        let (trace_id, span_id) = ids(&traceparent);
+        assert_eq!(trace_id, TRACE_ID);
+        assert_ne!(span_id, "00f067aa0ba902b7");
+        assert_eq!(response.into_string().unwrap(), "Hello, Bob!");
+    }
+
+    #[test]
+    fn new_trace_is_started() {
+        let client = client();
+        let first = client.get("/hello/Bob").dispatch();
+        let first = first.headers().get_one("traceparent").unwrap().to_string();
+        let second = client.get("/missing").dispatch();
+        assert_eq!(second.status(), Status::NotFound);
+        let second = second.headers().get_one("traceparent").unwrap().to_string();
+
+        assert_ne!(ids(&first).0, TRACE_ID);
+        assert_ne!(ids(&first).0, ids(&second).0);
+    }
+
+    #[test]
+    fn malformed_traceparent_is_ignored() {
+        let client = client();
+        let response = client.get("/hello/Bob")
+            .header(Header::new("traceparent", "not-a-trace-parent"))
+            .dispatch();
+
+        let traceparent = response.headers().get_one("traceparent").unwrap().to_string();
+        assert_eq!(ids(&traceparent).0.len(), 32);
+    }
+}
@@ -0,0 +1,54 @@
+#[macro_use]
+#[cfg(feature = "problem")]
+extern crate rocket;
+
+#[cfg(feature = "problem")]
+mod problem_tests {
+    use rocket::http::{Status, ContentType, Accept};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::problem::Problem;
+
+    #[get("/credit")]
+    fn credit() -> Result<&'static str, Problem> {
+        Err(Problem::new(Status::Forbidden)
+            .with_type("https://example.com/probs/out-of-credit")
+            .title("You do not have enough credit.")
+            .extension("balance", 30))
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/", routes![credit])
+            .register(vec![Problem::catcher()]);
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn problem_responder() {
+        let client = client();
+        let response = client.get("/credit").dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+        assert_eq!(response.content_type(), Some(ContentType::new("application", "problem+json")));
+
+        let body = response.into_string().unwrap();
+        assert!(body.contains(r#""type":"https://example.com/probs/out-of-credit""#));
+        assert!(body.contains(r#""title":"You do not have enough credit.""#));
+        assert!(body.contains(r#""status":403"#));
+        assert!(body.contains(r#""balance":30"#));
+    }
+
+    #[test]
+    fn problem_catcher() {
+        let client = client();
+        let response = client.get("/missing").header(Accept::HTML).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.content_type(), Some(ContentType::new("application", "problem+json")));
+
+        let body = response.into_string().unwrap();
+        assert!(body.contains(r#""type":"about:blank""#));
+        assert!(body.contains(r#""title":"Not Found""#));
+        assert!(body.contains(r#""instance":"/missing""#));
+    }
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+#[cfg(feature = "prometheus")]
+extern crate rocket;
+
+#[cfg(feature = "prometheus")]
+mod prometheus_tests {
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::prometheus::PrometheusMetrics;
+
+    #[get("/hello/<name>")]
+    fn hello(name: String) -> String {
+        format!("Hello, {}!", name)
+    }
+
+    fn client(metrics: PrometheusMetrics) -> Client {
+        let rocket = rocket::ignite().mount("/", routes![hello]).attach(metrics);
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn requests_are_recorded_and_exposed() {
+        let client = client(PrometheusMetrics::new());
+        client.get("/hello/Bob").dispatch();
+        client.get("/hello/Alice").dispatch();
+        client.get("/missing").dispatch();
+
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type().map(|ct| ct.media_type().clone()),
+            Some(ContentType::Plain.media_type().clone()));
+
+        let body = response.into_string().unwrap();
+        assert!(body.contains(
+            r#"rocket_http_requests_total{method="GET",route="/hello/<name>",status="200"} 2"#
+        ));
+
+        assert!(body.contains(
+            r#"rocket_http_requests_total{method="GET",route="unmatched",status="404"} 1"#
+        ));
+
+        assert!(body.contains("rocket_http_request_duration_seconds_bucket"));
+        assert!(body.contains("rocket_http_response_size_bytes_bucket"));
+    }
+
+    #[test]
+    fn custom_path_and_registry() {
+        let metrics = PrometheusMetrics::new().path("/internal/metrics");
+        let counter = rocket_contrib::prometheus::prometheus_crate::IntCounter::new(
+            "custom_total", "A custom counter."
+        ).unwrap();
+
+        metrics.registry().register(Box::new(counter.clone())).unwrap();
+        counter.inc();
+
+        let client = client(metrics);
+        assert_eq!(client.get("/metrics").dispatch().status(), Status::NotFound);
+
+        let response = client.get("/internal/metrics").dispatch();
+        assert!(response.into_string().unwrap().contains("custom_total 1"));
+    }
+}
@@ -0,0 +1,272 @@
+#[macro_use]
+#[cfg(feature = "ratelimit")]
+extern crate rocket;
+
+#[cfg(feature = "ratelimit")]
+mod ratelimit_tests {
+    use std::net::SocketAddr;
+
+    use rocket::{Config, Rocket};
+    use rocket::config::{Environment, Table, Value};
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::{Client, LocalResponse};
+
+    use rocket_contrib::ratelimit::{RateLimit, RateLimited, Policy, Quota, ApiKey, rate_limit};
+
+    struct Search;
+
+    impl Policy for Search {
+        const NAME: &'static str = "search";
+
+        fn quota() -> Quota {
+            Quota::per_minute(2)
+        }
+    }
+
+    #[get("/")]
+    fn index() -> &'static str {
+        "index"
+    }
+
+    #[get("/search")]
+    fn search(_limit: RateLimited<Search>) -> &'static str {
+        "results"
+    }
+
+    #[rate_limit(limit = 1, period = 60, algorithm = "sliding_window")]
+    #[post("/login")]
+    fn login() -> &'static str {
+        "welcome"
+    }
+
+    #[rate_limit(limit = 1, period = 60, key = ApiKey::header("X-Api-Key"))]
+    #[get("/keyed")]
+    fn keyed() -> &'static str {
+        "keyed"
+    }
+
+    fn is_issued(key: &str) -> bool {
+        key == "alpha"
+    }
+
+    #[rate_limit(limit = 1, period = 60, key = ApiKey::header("X-Api-Key").validate(is_issued))]
+    #[get("/validated")]
+    fn validated() -> &'static str {
+        "validated"
+    }
+
+    fn rocket(config: Config, limit: RateLimit) -> Rocket {
+        rocket::custom(config)
+            .mount("/", routes![index, search, login, keyed, validated])
+            .attach(limit)
+    }
+
+    fn config(table: Table) -> Config {
+        Config::build(Environment::Development)
+            .extra("ratelimit", table)
+            .unwrap()
+    }
+
+    fn client(limit: RateLimit) -> Client {
+        Client::new(rocket(Config::development(), limit)).unwrap()
+    }
+
+    fn remote(ip: &str) -> SocketAddr {
+        format!("{}:8000", ip).parse().unwrap()
+    }
+
+    fn header<'a>(response: &'a LocalResponse<'_>, name: &str) -> Option<&'a str> {
+        response.headers().get_one(name)
+    }
+
+    #[test]
+    fn global_quota_limits_every_route() {
+        let client = client(RateLimit::new().global(Quota::per_minute(2)));
+        for remaining in &["1", "0"] {
+            let response = client.get("/").remote(remote("10.0.0.1")).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(header(&response, "RateLimit-Limit"), Some("2"));
+            assert_eq!(header(&response, "RateLimit-Remaining"), Some(*remaining));
+            assert_eq!(header(&response, "RateLimit-Policy"), Some("2;w=60"));
+            assert!(header(&response, "Retry-After").is_none());
+        }
+
+        let response = client.get("/search").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(header(&response, "RateLimit-Remaining"), Some("0"));
+        assert_eq!(header(&response, "Retry-After"), Some("30"));
+
+        let response = client.get("/").remote(remote("10.0.0.2")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn requests_without_an_address_share_a_quota() {
+        let client = client(RateLimit::new().global(Quota::per_minute(1)));
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(header(&response, "RateLimit-Limit"), Some("1"));
+
+        assert_eq!(client.get("/").dispatch().status(), Status::TooManyRequests);
+        assert_eq!(client.get("/search").dispatch().status(), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn proxy_headers_are_only_trusted_when_configured() {
+        let client = client(RateLimit::new().global(Quota::per_minute(1)));
+        let get = |ip: &'static str| client.get("/")
+            .remote(remote("10.0.0.1"))
+            .header(Header::new("X-Real-IP", ip))
+            .dispatch()
+            .status();
+
+        assert_eq!(get("192.168.0.1"), Status::Ok);
+        assert_eq!(get("192.168.0.2"), Status::TooManyRequests);
+
+        let limit = RateLimit::new().global(Quota::per_minute(1)).trust_proxy(true);
+        let client = client(limit);
+        let get = |ip: &'static str| client.get("/")
+            .remote(remote("10.0.0.1"))
+            .header(Header::new("X-Real-IP", ip))
+            .dispatch()
+            .status();
+
+        assert_eq!(get("192.168.0.1"), Status::Ok);
+        assert_eq!(get("192.168.0.2"), Status::Ok);
+        assert_eq!(get("192.168.0.1"), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn refused_requests_never_reach_routes() {
+        let client = client(RateLimit::new().global(Quota::per_minute(1)));
+        let response = client.post("/login").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.into_string().unwrap(), "welcome");
+
+        let response = client.post("/login").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+
+        // The route refusing requests forwards those that weren't refused.
+        let response = client.get("/__rocket/ratelimit/refused").remote(remote("10.0.0.2"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn guard_applies_policy() {
+        let client = client(RateLimit::new());
+        for _ in 0..2 {
+            let response = client.get("/search").remote(remote("10.0.0.1")).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+        }
+
+        let response = client.get("/search").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert!(header(&response, "Retry-After").is_some());
+
+        let response = client.get("/").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(header(&response, "RateLimit-Limit").is_none());
+    }
+
+    #[test]
+    fn attribute_declares_policy() {
+        let client = client(RateLimit::new());
+        let response = client.post("/login").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "welcome");
+
+        let response = client.post("/login").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn api_keys_have_separate_quotas() {
+        let client = client(RateLimit::new());
+        let get = |key: &'static str| client.get("/keyed")
+            .header(Header::new("X-Api-Key", key))
+            .dispatch()
+            .status();
+
+        assert_eq!(get("alpha"), Status::Ok);
+        assert_eq!(get("alpha"), Status::TooManyRequests);
+        assert_eq!(get("beta"), Status::Ok);
+
+        // Requests without a key are counted under the client's address.
+        let anonymous = |ip| client.get("/keyed").remote(remote(ip)).dispatch().status();
+        assert_eq!(anonymous("10.0.0.1"), Status::Ok);
+        assert_eq!(anonymous("10.0.0.1"), Status::TooManyRequests);
+        assert_eq!(anonymous("10.0.0.2"), Status::Ok);
+    }
+
+    #[test]
+    fn unknown_api_keys_are_counted_by_address() {
+        let client = client(RateLimit::new());
+        let get = |key: &'static str, ip| client.get("/validated")
+            .header(Header::new("X-Api-Key", key))
+            .remote(remote(ip))
+            .dispatch()
+            .status();
+
+        assert_eq!(get("alpha", "10.0.0.1"), Status::Ok);
+        assert_eq!(get("alpha", "10.0.0.2"), Status::TooManyRequests);
+
+        // Made-up keys don't earn fresh quotas.
+        assert_eq!(get("made-up-1", "10.0.0.3"), Status::Ok);
+        assert_eq!(get("made-up-2", "10.0.0.3"), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn headers_can_be_disabled() {
+        let client = client(RateLimit::new().global(Quota::per_minute(1)).headers(false));
+        let response = client.get("/").remote(remote("10.0.0.1")).dispatch();
+        assert!(header(&response, "RateLimit-Limit").is_none());
+
+        let response = client.get("/").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert!(header(&response, "RateLimit-Limit").is_none());
+        assert!(header(&response, "Retry-After").is_some());
+    }
+
+    #[test]
+    fn config_overrides_quotas() {
+        let mut policy = Table::new();
+        policy.insert("limit".into(), Value::from(1));
+        policy.insert("period".into(), Value::from(10));
+        let mut policies = Table::new();
+        policies.insert("search".into(), Value::from(policy));
+
+        let mut table = Table::new();
+        table.insert("limit".into(), Value::from(5));
+        table.insert("policies".into(), Value::from(policies));
+
+        let limit = RateLimit::new().global(Quota::per_minute(100));
+        let client = Client::new(rocket(config(table), limit)).unwrap();
+        let response = client.get("/search").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(header(&response, "RateLimit-Policy"), Some("1;w=10"));
+
+        let response = client.get("/search").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+
+        let response = client.get("/").remote(remote("10.0.0.1")).dispatch();
+        assert_eq!(header(&response, "RateLimit-Policy"), Some("5;w=60"));
+    }
+
+    #[test]
+    fn invalid_config_aborts_launch() {
+        let mut table = Table::new();
+        table.insert("limit".into(), Value::from(0));
+        assert!(Client::new(rocket(config(table), RateLimit::new())).is_err());
+
+        let mut table = Table::new();
+        table.insert("limit".into(), Value::from(10));
+        assert!(Client::new(rocket(config(table), RateLimit::new())).is_err());
+
+        let mut table = Table::new();
+        table.insert("limit".into(), Value::from(10));
+        table.insert("period".into(), Value::from(60));
+        table.insert("algorithm".into(), Value::from("leaky_bucket"));
+        assert!(Client::new(rocket(config(table), RateLimit::new())).is_err());
+    }
+}
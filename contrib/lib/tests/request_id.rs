@@ -0,0 +1,95 @@
+#[macro_use]
+#[cfg(feature = "request_id")]
+extern crate rocket;
+
+#[cfg(feature = "request_id")]
+mod request_id_tests {
+    use rocket::config::{Config, Environment, Table, Value};
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::request_id::RequestId;
+
+    #[get("/")]
+    fn index(id: &RequestId) -> String {
+        id.to_string()
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/", routes![index])
+            .attach(RequestId::fairing());
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn generated_id_is_echoed() {
+        let client = client();
+        let response = client.get("/").dispatch();
+        let echoed = response.headers().get_one("X-Request-Id").unwrap().to_string();
+        assert_eq!(echoed.len(), 26);
+        assert_eq!(response.into_string().unwrap(), echoed);
+
+        let response = client.get("/").dispatch();
+        assert_ne!(response.headers().get_one("X-Request-Id").unwrap(), echoed);
+    }
+
+    #[test]
+    fn incoming_id_is_honored() {
+        let client = client();
+        let response = client.get("/")
+            .header(Header::new("X-Request-Id", "abc-123"))
+            .dispatch();
+
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some("abc-123"));
+        assert_eq!(response.into_string().unwrap(), "abc-123");
+    }
+
+    #[test]
+    fn invalid_incoming_id_is_replaced() {
+        let client = client();
+        let response = client.get("/")
+            .header(Header::new("X-Request-Id", "has spaces"))
+            .dispatch();
+
+        let id = response.headers().get_one("X-Request-Id").unwrap();
+        assert_ne!(id, "has spaces");
+        assert_eq!(id.len(), 26);
+    }
+
+    #[test]
+    fn configured_header_is_used() {
+        let mut table = Table::new();
+        table.insert("header".into(), Value::from("X-Correlation-Id"));
+
+        let config = Config::build(Environment::Development)
+            .extra("request_id", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config)
+            .mount("/", routes![index])
+            .attach(RequestId::fairing());
+
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/")
+            .header(Header::new("X-Correlation-Id", "xyz"))
+            .dispatch();
+
+        assert_eq!(response.headers().get_one("X-Correlation-Id"), Some("xyz"));
+        assert!(response.headers().get_one("X-Request-Id").is_none());
+        assert_eq!(response.into_string().unwrap(), "xyz");
+    }
+
+    #[test]
+    fn guard_works_without_fairing() {
+        let rocket = rocket::ignite().mount("/", routes![index]);
+        let client = Client::new(rocket).unwrap();
+        let response = client.get("/")
+            .header(Header::new("X-Request-Id", "abc"))
+            .dispatch();
+
+        assert!(response.headers().get_one("X-Request-Id").is_none());
+        assert_eq!(response.into_string().unwrap(), "abc");
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(feature = "scheduler")]
+mod scheduler_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use rocket::{Config, Rocket};
+    use rocket::config::{Environment, Table, Value};
+    use rocket::local::blocking::Client;
+    use rocket::tokio::time::{delay_for, timeout};
+
+    use rocket_contrib::scheduler::{Scheduler, Schedule, Job, Overlap};
+
+    #[derive(Default)]
+    struct Gauge {
+        runs: AtomicUsize,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    fn config(scheduler: Option<Table>) -> Config {
+        let mut builder = Config::build(Environment::Development)
+            .address("127.0.0.1")
+            .port(0);
+
+        if let Some(table) = scheduler {
+            builder = builder.extra("scheduler", table);
+        }
+
+        builder.unwrap()
+    }
+
+    fn every(millis: u64) -> Schedule {
+        Schedule::every(Duration::from_millis(millis))
+    }
+
+    fn slow_job(overlap: Overlap) -> Job {
+        Job::new("slow", every(10), |ctx| async move {
+            let gauge = ctx.state::<Arc<Gauge>>().unwrap();
+            gauge.runs.fetch_add(1, Ordering::SeqCst);
+            let running = gauge.running.fetch_add(1, Ordering::SeqCst) + 1;
+            gauge.max_running.fetch_max(running, Ordering::SeqCst);
+            delay_for(Duration::from_millis(100)).await;
+            gauge.running.fetch_sub(1, Ordering::SeqCst);
+        }).overlap(overlap)
+    }
+
+    /// Launches `rocket` with a managed `Gauge`, shutting down after `millis`.
+    async fn run_for(rocket: Rocket, millis: u64) -> Arc<Gauge> {
+        let gauge = Arc::new(Gauge::default());
+        let rocket = rocket.manage(gauge.clone())
+            .attach_task(move |shutdown| async move {
+                delay_for(Duration::from_millis(millis)).await;
+                shutdown.shutdown();
+            });
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("server shut down").is_ok());
+        gauge
+    }
+
+    #[rocket::async_test]
+    async fn jobs_run_with_managed_state() {
+        let job = Job::new("count", every(20), |ctx| async move {
+            let gauge = ctx.state::<Arc<Gauge>>().unwrap();
+            if gauge.runs.fetch_add(1, Ordering::SeqCst) + 1 == 3 {
+                ctx.shutdown().shutdown();
+            }
+        });
+
+        let gauge = Arc::new(Gauge::default());
+        let rocket = rocket::custom(config(None))
+            .manage(gauge.clone())
+            .attach(Scheduler::new().job(job));
+
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("job shut down the server").is_ok());
+        assert!(gauge.runs.load(Ordering::SeqCst) >= 3);
+    }
+
+    #[rocket::async_test]
+    async fn runs_in_progress_finish_before_shutdown() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        let job = Job::new("finish", every(10), move |ctx| {
+            let flag = flag.clone();
+            async move {
+                ctx.shutdown().shutdown();
+                delay_for(Duration::from_millis(100)).await;
+                flag.store(true, Ordering::SeqCst);
+            }
+        });
+
+        let rocket = rocket::custom(config(None)).attach(Scheduler::new().job(job));
+        let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+        assert!(launched.expect("job shut down the server").is_ok());
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[rocket::async_test]
+    async fn overlapping_runs_are_skipped_by_default() {
+        let rocket = rocket::custom(config(None))
+            .attach(Scheduler::new().job(slow_job(Overlap::Skip)));
+
+        let gauge = run_for(rocket, 250).await;
+        assert!(gauge.runs.load(Ordering::SeqCst) >= 1);
+        assert_eq!(gauge.max_running.load(Ordering::SeqCst), 1);
+    }
+
+    #[rocket::async_test]
+    async fn overlapping_runs_can_be_concurrent() {
+        let rocket = rocket::custom(config(None))
+            .attach(Scheduler::new().job(slow_job(Overlap::Concurrent)));
+
+        let gauge = run_for(rocket, 250).await;
+        assert!(gauge.max_running.load(Ordering::SeqCst) > 1);
+    }
+
+    #[rocket::async_test]
+    async fn jobs_can_be_disabled_in_config() {
+        let mut slow = Table::new();
+        slow.insert("enabled".into(), Value::from(false));
+        let mut table = Table::new();
+        table.insert("slow".into(), Value::Table(slow));
+
+        let rocket = rocket::custom(config(Some(table)))
+            .attach(Scheduler::new().job(slow_job(Overlap::Skip)));
+
+        let gauge = run_for(rocket, 100).await;
+        assert_eq!(gauge.runs.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn invalid_config_aborts_launch() {
+        for &(key, value) in &[("schedule", "every minute"), ("overlap", "sometimes")] {
+            let mut slow = Table::new();
+            slow.insert(key.into(), Value::from(value));
+            let mut table = Table::new();
+            table.insert("slow".into(), Value::Table(slow));
+
+            let rocket = rocket::custom(config(Some(table)))
+                .attach(Scheduler::new().job(slow_job(Overlap::Skip)));
+
+            assert!(Client::new(rocket).is_err());
+        }
+    }
+
+    #[test]
+    fn schedules_display_their_expression() {
+        assert_eq!(Schedule::cron("*/5 * * * *").unwrap().to_string(), "*/5 * * * *");
+        assert_eq!(every(30_000).to_string(), "every 30s");
+        assert!(Schedule::cron("61 * * * *").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered")]
+    fn duplicate_job_names_panic() {
+        Scheduler::new().job(slow_job(Overlap::Skip)).job(slow_job(Overlap::Queue));
+    }
+}
@@ -0,0 +1,91 @@
+#[macro_use]
+#[cfg(feature = "server_timing")]
+extern crate rocket;
+
+#[cfg(feature = "server_timing")]
+mod server_timing_tests {
+    use std::time::Duration;
+
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    use rocket_contrib::server_timing::{ServerTiming, Timings};
+
+    #[get("/")]
+    fn index() -> &'static str {
+        "Hello, world!"
+    }
+
+    #[get("/timed")]
+    fn timed(timings: &Timings) -> &'static str {
+        timings.add("db", Duration::from_millis(2));
+        timings.add_with_description("cache", "a \"warm\" hit", Duration::from_micros(500));
+        timings.add("not valid", Duration::from_millis(1));
+        timings.time("compute", || "timed")
+    }
+
+    #[derive(Responder)]
+    #[response(content_type = "plain")]
+    struct Preset(&'static str, Header<'static>);
+
+    #[get("/preset")]
+    fn preset() -> Preset {
+        Preset("preset", Header::new("Server-Timing", "custom;dur=1"))
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::ignite()
+            .mount("/", routes![index, timed, preset])
+            .attach(ServerTiming::new());
+
+        Client::new(rocket).unwrap()
+    }
+
+    #[test]
+    fn phases_are_reported() {
+        let client = client();
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let timing = response.headers().get_one("Server-Timing").unwrap();
+        let names: Vec<_> = timing.split(", ")
+            .map(|metric| metric.split(';').next().unwrap())
+            .collect();
+
+        assert_eq!(names, vec!["routing", "guards", "handler", "responder"]);
+        assert!(timing.split(", ").all(|metric| metric.contains(";dur=")));
+
+        let elapsed = response.headers().get_one("X-Response-Time").unwrap();
+        assert!(elapsed.ends_with("ms"));
+        assert!(elapsed.trim_end_matches("ms").parse::<f64>().is_ok());
+    }
+
+    #[test]
+    fn handler_metrics_are_reported() {
+        let client = client();
+        let response = client.get("/timed").dispatch();
+        let timing = response.headers().get_one("Server-Timing").unwrap();
+        assert!(timing.contains(", db;dur=2.000"));
+        assert!(timing.contains(r#", cache;desc="a \"warm\" hit";dur=0.500"#));
+        assert!(timing.contains(", compute;dur="));
+        assert!(!timing.contains("not valid"));
+    }
+
+    #[test]
+    fn unmatched_requests_report_routing() {
+        let client = client();
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        let timing = response.headers().get_one("Server-Timing").unwrap();
+        assert!(timing.starts_with("routing;dur="));
+        assert!(response.headers().get_one("X-Response-Time").is_some());
+    }
+
+    #[test]
+    fn existing_headers_are_kept() {
+        let client = client();
+        let response = client.get("/preset").dispatch();
+        let values: Vec<_> = response.headers().get("Server-Timing").collect();
+        assert_eq!(values, vec!["custom;dur=1"]);
+    }
+}
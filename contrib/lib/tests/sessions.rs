@@ -0,0 +1,337 @@
+#[macro_use]
+#[cfg(feature = "sessions")]
+extern crate rocket;
+
+#[cfg(feature = "sessions")]
+mod sessions_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use rocket::config::{Config, Environment, Table, Value};
+    use rocket::http::{Cookie, Status};
+    use rocket::local::blocking::Client;
+    use rocket::response::Debug;
+
+    use rocket_contrib::sessions::{Session, Sessions, SessionError, SessionStore, MemoryStore};
+
+    #[get("/")]
+    fn get(session: Session<'_>) -> Option<String> {
+        session.get("user")
+    }
+
+    #[post("/<user>")]
+    async fn set(session: Session<'_>, user: String) -> Result<(), Debug<SessionError>> {
+        Ok(session.set("user", &user).await?)
+    }
+
+    #[delete("/")]
+    async fn remove(session: Session<'_>) -> Result<(), Debug<SessionError>> {
+        Ok(session.remove("user").await?)
+    }
+
+    #[post("/regenerate")]
+    async fn regenerate(session: Session<'_>) -> Result<(), Debug<SessionError>> {
+        Ok(session.regenerate().await?)
+    }
+
+    #[post("/destroy")]
+    async fn destroy(session: Session<'_>) -> Result<(), Debug<SessionError>> {
+        Ok(session.destroy().await?)
+    }
+
+    fn client_with(config: Config, sessions: Sessions) -> Client {
+        let rocket = rocket::custom(config)
+            .mount("/", routes![get, set, remove, regenerate, destroy])
+            .attach(sessions);
+
+        Client::new(rocket).unwrap()
+    }
+
+    fn client(sessions: Sessions) -> Client {
+        client_with(Config::development(), sessions)
+    }
+
+    fn user(client: &Client) -> Option<String> {
+        client.get("/").dispatch().into_string()
+    }
+
+    fn session_cookie(client: &Client) -> Option<String> {
+        client.cookies().get("rocket_session").map(|c| c.value().to_string())
+    }
+
+    fn check_values(client: Client) {
+        assert_eq!(client.get("/").dispatch().status(), Status::NotFound);
+
+        assert_eq!(client.post("/alice").dispatch().status(), Status::Ok);
+        assert_eq!(user(&client).as_deref(), Some("alice"));
+
+        assert_eq!(client.post("/bob").dispatch().status(), Status::Ok);
+        assert_eq!(user(&client).as_deref(), Some("bob"));
+
+        assert_eq!(client.delete("/").dispatch().status(), Status::Ok);
+        assert_eq!(user(&client), None);
+    }
+
+    #[test]
+    fn cookie_sessions_hold_values() {
+        check_values(client(Sessions::fairing()));
+    }
+
+    #[test]
+    fn store_sessions_hold_values() {
+        check_values(client(Sessions::with_store(MemoryStore::new())));
+    }
+
+    #[test]
+    fn destroy_discards_session() {
+        for sessions in vec![Sessions::fairing(), Sessions::with_store(MemoryStore::new())] {
+            let client = client(sessions);
+            client.post("/alice").dispatch();
+            assert!(session_cookie(&client).is_some());
+
+            assert_eq!(client.post("/destroy").dispatch().status(), Status::Ok);
+            assert!(session_cookie(&client).is_none());
+            assert_eq!(user(&client), None);
+        }
+    }
+
+    #[test]
+    fn regenerate_invalidates_old_id() {
+        let client = client(Sessions::with_store(MemoryStore::new()));
+        client.post("/alice").dispatch();
+        let old = session_cookie(&client).unwrap();
+
+        assert_eq!(client.post("/regenerate").dispatch().status(), Status::Ok);
+        let new = session_cookie(&client).unwrap();
+        assert_ne!(old, new);
+        assert_eq!(user(&client).as_deref(), Some("alice"));
+
+        let response = client.get("/").cookie(Cookie::new("rocket_session", old)).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn idle_sessions_expire() {
+        let mut table = Table::new();
+        table.insert("idle_timeout".into(), Value::from(1));
+
+        let config = Config::build(Environment::Development)
+            .extra("sessions", table)
+            .unwrap();
+
+        let client = client_with(config, Sessions::with_store(MemoryStore::new()));
+        client.post("/alice").dispatch();
+        assert_eq!(user(&client).as_deref(), Some("alice"));
+
+        std::thread::sleep(Duration::from_secs(2));
+        assert_eq!(user(&client), None);
+    }
+
+    #[test]
+    fn sessions_expire_after_absolute_timeout() {
+        let mut table = Table::new();
+        table.insert("absolute_timeout".into(), Value::from(1));
+
+        let config = Config::build(Environment::Development)
+            .extra("sessions", table)
+            .unwrap();
+
+        for sessions in vec![Sessions::fairing(), Sessions::with_store(MemoryStore::new())] {
+            let client = client_with(config.clone(), sessions);
+            client.post("/alice").dispatch();
+            assert_eq!(user(&client).as_deref(), Some("alice"));
+
+            // The idle timeout is far off; only the absolute timeout passes.
+            std::thread::sleep(Duration::from_secs(2));
+            assert_eq!(user(&client), None);
+        }
+    }
+
+    /// A `MemoryStore` that counts the records it saves.
+    struct Counting(MemoryStore, Arc<AtomicUsize>);
+
+    #[rocket::async_trait]
+    impl SessionStore for Counting {
+        async fn load(&self, id: &str) -> Result<Option<String>, SessionError> {
+            self.0.load(id).await
+        }
+
+        async fn save(&self, id: &str, record: &str, ttl: Duration) -> Result<(), SessionError> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            self.0.save(id, record, ttl).await
+        }
+
+        async fn remove(&self, id: &str) -> Result<(), SessionError> {
+            self.0.remove(id).await
+        }
+    }
+
+    #[test]
+    fn sessions_are_saved_only_when_changed() {
+        let saves = Arc::new(AtomicUsize::new(0));
+        let store = Counting(MemoryStore::new(), saves.clone());
+        let client = client(Sessions::with_store(store));
+
+        client.post("/alice").dispatch();
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+
+        // Reading the session, or setting a value it already has, doesn't
+        // save it again until a tenth of the idle timeout has passed.
+        assert_eq!(user(&client).as_deref(), Some("alice"));
+        client.post("/alice").dispatch();
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+
+        client.post("/bob").dispatch();
+        assert_eq!(saves.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn cookie_attributes_are_read_from_config() {
+        let mut table = Table::new();
+        table.insert("cookie_name".into(), Value::from("sid"));
+        table.insert("path".into(), Value::from("/app"));
+        table.insert("secure".into(), Value::from(true));
+        table.insert("same_site".into(), Value::from("strict"));
+
+        let config = Config::build(Environment::Development)
+            .extra("sessions", table)
+            .unwrap();
+
+        let client = client_with(config, Sessions::fairing());
+        let response = client.post("/alice").dispatch();
+        let cookie = response.headers().get_one("Set-Cookie").unwrap();
+        assert!(cookie.starts_with("sid="));
+        assert!(cookie.contains("Path=/app"));
+        assert!(cookie.contains("Secure"));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.contains("SameSite=Strict"));
+    }
+
+    #[test]
+    fn cookie_is_secure_outside_development() {
+        let client = client(Sessions::fairing());
+        let response = client.post("/alice").dispatch();
+        assert!(!response.headers().get_one("Set-Cookie").unwrap().contains("Secure"));
+
+        let client = client_with(Config::production(), Sessions::fairing());
+        let response = client.post("/alice").dispatch();
+        assert!(response.headers().get_one("Set-Cookie").unwrap().contains("Secure"));
+
+        let mut table = Table::new();
+        table.insert("secure".into(), Value::from(false));
+        let config = Config::build(Environment::Production).extra("sessions", table).unwrap();
+        let response = client_with(config, Sessions::fairing()).post("/alice").dispatch();
+        assert!(!response.headers().get_one("Set-Cookie").unwrap().contains("Secure"));
+    }
+
+    #[test]
+    fn invalid_config_aborts_launch() {
+        let mut table = Table::new();
+        table.insert("same_site".into(), Value::from("sometimes"));
+
+        let config = Config::build(Environment::Development)
+            .extra("sessions", table)
+            .unwrap();
+
+        let rocket = rocket::custom(config).attach(Sessions::fairing());
+        assert!(Client::new(rocket).is_err());
+    }
+
+    #[cfg(feature = "sqlx_sqlite_pool")]
+    mod sql_store {
+        use rocket::config::{Config, Environment, Map, Value};
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket_contrib::databases::{Database, sqlx};
+        use rocket_contrib::sessions::{Sessions, SqlStore};
+
+        #[derive(Database)]
+        #[database("sessions_db")]
+        struct Db(sqlx::SqlitePool);
+
+        async fn count(db: &sqlx::SqlitePool) -> i64 {
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sessions")
+                .fetch_one(db)
+                .await
+                .unwrap();
+
+            count
+        }
+
+        #[rocket::async_test]
+        async fn sql_store_holds_records() {
+            let mut opts: Map<String, Value> = Map::new();
+            opts.insert("url".into(), Value::String("sqlite::memory:".into()));
+            // A single connection keeps the in-memory database alive.
+            opts.insert("pool_size".into(), Value::Integer(1));
+            let mut databases: Map<String, Value> = Map::new();
+            databases.insert("sessions_db".into(), Value::Table(opts));
+            let config = Config::build(Environment::Development)
+                .extra("databases", Value::Table(databases))
+                .unwrap();
+
+            let mut rocket = rocket::custom(config)
+                .mount("/", routes![super::get, super::set, super::destroy])
+                .attach(Db::init())
+                .attach(Sessions::with_store(SqlStore::<Db>::new()));
+
+            let db = (**Db::fetch(rocket.inspect().await).expect("pool")).clone();
+            let schema = "CREATE TABLE sessions \
+                (id VARCHAR(64) PRIMARY KEY, data TEXT NOT NULL, expires BIGINT NOT NULL)";
+            sqlx::query(schema).execute(&db).await.unwrap();
+
+            let client = Client::new(rocket).await.unwrap();
+            assert_eq!(client.post("/alice").dispatch().await.status(), Status::Ok);
+            let user = client.get("/").dispatch().await.into_string().await;
+            assert_eq!(user.as_deref(), Some("alice"));
+            assert_eq!(count(&db).await, 1);
+
+            assert_eq!(client.post("/destroy").dispatch().await.status(), Status::Ok);
+            assert_eq!(client.get("/").dispatch().await.status(), Status::NotFound);
+            assert_eq!(count(&db).await, 0);
+        }
+    }
+
+    #[cfg(feature = "redis_pool")]
+    mod redis_store {
+        use rocket::config::{Config, Environment, Map, Value};
+        use rocket::local::blocking::Client;
+        use rocket_contrib::databases::{Database, redis};
+        use rocket_contrib::sessions::{Sessions, RedisStore};
+
+        #[derive(Database)]
+        #[database("redis")]
+        struct Redis(redis::aio::ConnectionManager);
+
+        /// Returns a client for an application keeping sessions in the Redis
+        /// server at the URL in the `ROCKET_TEST_REDIS_URL` environment
+        /// variable, or `None` if it isn't set.
+        fn client() -> Option<Client> {
+            let url = std::env::var("ROCKET_TEST_REDIS_URL").ok()?;
+            let mut opts: Map<String, Value> = Map::new();
+            opts.insert("url".into(), Value::String(url));
+            let mut databases: Map<String, Value> = Map::new();
+            databases.insert("redis".into(), Value::Table(opts));
+            let config = Config::build(Environment::Development)
+                .extra("databases", Value::Table(databases))
+                .unwrap();
+
+            let prefix = format!("rocket-session-test-{}:", std::process::id());
+            let store = RedisStore::<Redis>::new().prefix(prefix);
+            let rocket = rocket::custom(config)
+                .mount("/", routes![super::get, super::set, super::remove])
+                .attach(Redis::init())
+                .attach(Sessions::with_store(store));
+
+            Some(Client::new(rocket).expect("valid rocket"))
+        }
+
+        #[test]
+        fn redis_store_holds_values() {
+            if let Some(client) = client() {
+                super::check_values(client);
+            }
+        }
+    }
+}
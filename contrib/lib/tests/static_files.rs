@@ -4,9 +4,11 @@ mod static_tests {
     use std::path::Path;
 
     use rocket::{self, Rocket, Route};
-    use rocket_contrib::serve::{StaticFiles, Options, crate_relative};
-    use rocket::http::Status;
+    use rocket_contrib::serve::{StaticFiles, Options, crate_relative, embed_files};
+    use rocket::http::{Status, ContentType, Accept, Header};
     use rocket::local::blocking::Client;
+    use rocket::response::CacheControl;
+    use time::Duration;
 
     fn static_root() -> &'static Path {
         Path::new(crate_relative!("/tests/static"))
@@ -22,6 +24,17 @@ mod static_tests {
             .mount("/both", StaticFiles::new(&root, Options::DotFiles | Options::Index))
             .mount("/redir", StaticFiles::new(&root, Options::NormalizeDirs))
             .mount("/redir_index", StaticFiles::new(&root, Options::NormalizeDirs | Options::Index))
+            .mount("/spa", StaticFiles::new(&root, Options::IndexFallback))
+            .mount("/list", StaticFiles::new(&root, Options::DirectoryListing))
+            .mount("/list_index", StaticFiles::new(&root, Options::Index | Options::DirectoryListing))
+            .mount("/compressed", StaticFiles::new(&root, Options::Precompressed))
+            .mount("/embedded", StaticFiles::embedded(embed_files!("tests/static"), Options::Index))
+            .mount("/embedded_all", StaticFiles::embedded(embed_files!("tests/static"),
+                Options::DotFiles | Options::DirectoryListing | Options::Precompressed))
+            .mount("/cached", StaticFiles::new(&root, Options::Index | Options::Precompressed)
+                .cache("*.{css,js}", CacheControl::new().public().max_age(Duration::days(365)).immutable())
+                .cache("*.html", CacheControl::new().no_cache())
+                .cache("inner/**", CacheControl::new().private()))
     }
 
     static REGULAR_FILES: &[&str] = &[
@@ -189,4 +202,523 @@ mod static_tests {
         assert_eq!(response.status(), Status::PermanentRedirect);
         assert_eq!(response.headers().get("Location").next(), Some("/redir_index/"));
     }
+
+    #[test]
+    fn test_index_fallback() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        assert_all(&client, "spa", REGULAR_FILES, true);
+
+        let index = std::fs::read_to_string(static_root().join("index.html")).unwrap();
+        for path in &["/spa", "/spa/", "/spa/app/route", "/spa/inner/", "/spa/.hidden"] {
+            let response = client.get(*path).dispatch();
+            assert_eq!(response.status(), Status::Ok, "{}", path);
+            assert_eq!(response.into_string().as_ref(), Some(&index), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_directory_listing() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        assert_all(&client, "list", REGULAR_FILES, true);
+        assert_all(&client, "list", HIDDEN_FILES, false);
+
+        let response = client.get("/list/inner/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept"));
+        let html = response.into_string().unwrap();
+        assert!(html.contains("<a href=\"&#x2F;list&#x2F;inner&#x2F;goodbye\">goodbye</a>"));
+        assert!(html.contains("<a href=\"&#x2F;list&#x2F;inner&#x2F;index.html\">index.html</a>"));
+        assert!(html.contains("<a href=\"&#x2F;list&#x2F;\">../</a>"));
+        assert!(!html.contains(".hideme"));
+
+        let html = client.get("/list").dispatch().into_string().unwrap();
+        assert!(html.contains("<a href=\"&#x2F;list&#x2F;inner&#x2F;\">inner/</a>"));
+        assert!(html.contains("<a href=\"&#x2F;list&#x2F;other&#x2F;\">other/</a>"));
+        assert!(!html.contains("../"));
+        assert!(!html.contains(".hidden"));
+        assert!(html.find("other/").unwrap() < html.find("index.html").unwrap());
+
+        let response = client.get("/list/other").header(Accept::JSON).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let json = response.into_string().unwrap();
+        assert!(json.starts_with("{\"path\":\"/list/other/\",\"entries\":[{\"name\":\"hello.txt\""));
+        assert!(json.contains("\"is_dir\":false,\"size\":4,"));
+
+        // Directories with an index page are served the index if enabled.
+        assert_file(&client, "list_index", "inner/", true);
+        let html = client.get("/list_index/other/").dispatch().into_string().unwrap();
+        assert!(html.contains("hello.txt"));
+    }
+
+    #[test]
+    fn test_precompressed() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        assert_all(&client, "compressed", REGULAR_FILES, true);
+
+        let read = |path: &str| std::fs::read_to_string(static_root().join(path)).unwrap();
+        let requests = &[
+            ("style.css", "br, gzip", Some("br"), "compressed/style.css.br"),
+            ("style.css", "gzip;q=1.0, br;q=0", Some("gzip"), "compressed/style.css.gz"),
+            ("style.css", "identity", None, "compressed/style.css"),
+            ("script.js", "gzip, deflate, br", Some("gzip"), "compressed/script.js.gz"),
+            ("script.js", "br", None, "compressed/script.js"),
+            ("style.css", "*", Some("br"), "compressed/style.css.br"),
+            ("style.css", "br;q=0.5, gzip", Some("gzip"), "compressed/style.css.gz"),
+            ("style.css", "gzip;q=0.2, *;q=0.8", Some("br"), "compressed/style.css.br"),
+            ("style.css", "*;q=0.1, br;q=0", Some("gzip"), "compressed/style.css.gz"),
+            ("style.css", "*;q=0", None, "compressed/style.css"),
+            ("script.js", "br, *;q=0.5", Some("gzip"), "compressed/script.js.gz"),
+        ];
+
+        for &(file, accept, encoding, expected) in requests {
+            let response = client.get(format!("/compressed/compressed/{}", file))
+                .header(Header::new("Accept-Encoding", accept))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.headers().get_one("Content-Encoding"), encoding);
+            assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+            let content_type = if file.ends_with(".css") { ContentType::CSS } else { ContentType::JavaScript };
+            assert_eq!(response.content_type(), Some(content_type));
+            assert_eq!(response.into_string(), Some(read(expected)));
+        }
+
+        // Without the option, the original is always served.
+        let response = client.get("/default/compressed/style.css")
+            .header(Header::new("Accept-Encoding", "br, gzip"))
+            .dispatch();
+
+        assert!(response.headers().get_one("Content-Encoding").is_none());
+        assert_eq!(response.into_string(), Some(read("compressed/style.css")));
+    }
+
+    #[test]
+    fn test_conditional_requests() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        let response = client.get("/default/other/hello.txt").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let etag = response.headers().get_one("ETag").expect("etag").to_string();
+        let modified = response.headers().get_one("Last-Modified").expect("date").to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert!(modified.ends_with(" GMT"));
+
+        let validators = &[
+            ("If-None-Match", etag.clone()),
+            ("If-None-Match", format!("\"other\", W/{}", etag)),
+            ("If-None-Match", "*".to_string()),
+            ("If-Modified-Since", modified.clone()),
+        ];
+
+        for (name, value) in validators.iter() {
+            let response = client.get("/default/other/hello.txt")
+                .header(Header::new(*name, value.clone()))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::NotModified, "{}: {}", name, value);
+            assert_eq!(response.headers().get_one("ETag"), Some(&*etag));
+            assert!(response.into_string().unwrap_or_default().is_empty());
+        }
+
+        let stale = &[
+            ("If-None-Match", "\"other\"".to_string()),
+            ("If-Modified-Since", "Thu, 01 Jan 1970 00:00:00 GMT".to_string()),
+        ];
+
+        for (name, value) in stale.iter() {
+            let response = client.get("/default/other/hello.txt")
+                .header(Header::new(*name, value.clone()))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok, "{}: {}", name, value);
+        }
+
+        // `If-None-Match` takes precedence over `If-Modified-Since`.
+        let response = client.get("/default/other/hello.txt")
+            .header(Header::new("If-None-Match", "\"other\""))
+            .header(Header::new("If-Modified-Since", modified))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn test_range_requests() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        let contents = std::fs::read_to_string(static_root().join("inner/goodbye")).unwrap();
+        let response = client.get("/default/inner/goodbye").dispatch();
+        assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+        let etag = response.headers().get_one("ETag").unwrap().to_string();
+
+        let response = client.get("/default/inner/goodbye")
+            .header(Header::new("Range", "bytes=0-3"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PartialContent);
+        let range = format!("bytes 0-3/{}", contents.len());
+        assert_eq!(response.headers().get_one("Content-Range"), Some(&*range));
+        assert_eq!(response.into_string(), Some(contents[..4].to_string()));
+
+        let response = client.get("/default/inner/goodbye")
+            .header(Header::new("Range", "bytes=1000-"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::RangeNotSatisfiable);
+
+        // A matching `If-Range` honors the range; a stale one doesn't.
+        let response = client.get("/default/inner/goodbye")
+            .header(Header::new("Range", "bytes=0-3"))
+            .header(Header::new("If-Range", etag))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PartialContent);
+
+        let response = client.get("/default/inner/goodbye")
+            .header(Header::new("Range", "bytes=0-3"))
+            .header(Header::new("If-Range", "\"stale\""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string(), Some(contents));
+    }
+
+    #[test]
+    fn test_embedded_paths() {
+        let files = embed_files!("tests/static");
+        for path in REGULAR_FILES.iter().chain(HIDDEN_FILES) {
+            assert!(files.paths().any(|p| p == *path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_embedded() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        assert_all(&client, "embedded", REGULAR_FILES, true);
+        assert_all(&client, "embedded", HIDDEN_FILES, false);
+        assert_all(&client, "embedded", INDEXED_DIRECTORIES, true);
+        assert_all(&client, "embedded_all", REGULAR_FILES, true);
+        assert_all(&client, "embedded_all", HIDDEN_FILES, true);
+
+        for path in &["/embedded/other/", "/embedded/missing", "/embedded/inner/missing"] {
+            let response = client.get(*path).dispatch();
+            assert_eq!(response.status(), Status::NotFound, "{}", path);
+        }
+
+        let response = client.get("/embedded/other/hello.txt").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        assert!(response.headers().get_one("Last-Modified").is_none());
+        let etag = response.headers().get_one("ETag").expect("etag").to_string();
+        let response = client.get("/embedded/other/hello.txt")
+            .header(Header::new("If-None-Match", etag))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotModified);
+
+        let response = client.get("/embedded/inner/goodbye")
+            .header(Header::new("Range", "bytes=0-3"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PartialContent);
+
+        let response = client.get("/embedded_all/compressed/style.css")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_eq!(response.content_type(), Some(ContentType::CSS));
+
+        let html = client.get("/embedded_all/inner/").dispatch().into_string().unwrap();
+        assert!(html.contains(
+            "<a href=\"&#x2F;embedded_all&#x2F;inner&#x2F;.hideme\">.hideme</a>"));
+        assert!(html.contains(
+            "<a href=\"&#x2F;embedded_all&#x2F;inner&#x2F;goodbye\">goodbye</a>"));
+
+        let html = client.get("/embedded_all/").dispatch().into_string().unwrap();
+        assert!(html.contains("<a href=\"&#x2F;embedded_all&#x2F;inner&#x2F;\">inner/</a>"));
+        assert_eq!(html.matches("inner&#x2F;\"").count(), 1);
+    }
+
+    #[test]
+    fn test_cache_rules() {
+        let client = Client::new(rocket()).expect("valid rocket");
+        assert_all(&client, "cached", REGULAR_FILES, true);
+
+        let rules = &[
+            ("/cached/compressed/style.css", Some("public, max-age=31536000, immutable")),
+            ("/cached/compressed/script.js", Some("public, max-age=31536000, immutable")),
+            ("/cached/index.html", Some("no-cache")),
+            ("/cached/", Some("no-cache")),
+            ("/cached/inner/index.html", Some("no-cache")),
+            ("/cached/inner/goodbye", Some("private")),
+            ("/cached/other/hello.txt", None),
+        ];
+
+        for &(path, cache_control) in rules {
+            let response = client.get(path).header(Header::new("Accept-Encoding", "gzip")).dispatch();
+            assert_eq!(response.headers().get_one("Cache-Control"), cache_control, "{}", path);
+        }
+
+        let response = client.get("/cached/compressed/style.css")
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+
+        assert!(response.headers().get_one("Expires").is_some());
+        assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policies() {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join(format!("rocket-static-links-{}", std::process::id()));
+        let root = dir.join("root");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.join(".env"), "hidden").unwrap();
+        std::fs::write(root.join("sub/file.txt"), "file").unwrap();
+        symlink(dir.join("secret.txt"), root.join("escape.txt")).unwrap();
+        symlink(root.join("sub/file.txt"), root.join("inside.txt")).unwrap();
+        symlink(root.join(".env"), root.join("env.txt")).unwrap();
+        symlink(root.join("sub"), root.join("linked")).unwrap();
+
+        let rocket = rocket::ignite()
+            .mount("/default", StaticFiles::new(&root, Options::None))
+            .mount("/dots", StaticFiles::new(&root, Options::DotFiles))
+            .mount("/external", StaticFiles::new(&root, Options::ExternalSymlinks))
+            .mount("/canonical", StaticFiles::new(&root, Options::RequireCanonical));
+
+        let client = Client::new(rocket).expect("valid rocket");
+        let cases = &[
+            ("/default/sub/file.txt", Some("file")),
+            ("/default/inside.txt", Some("file")),
+            ("/default/linked/file.txt", Some("file")),
+            ("/default/escape.txt", None),
+            ("/default/env.txt", None),
+            ("/dots/env.txt", Some("hidden")),
+            ("/dots/escape.txt", None),
+            ("/external/escape.txt", Some("secret")),
+            ("/external/inside.txt", Some("file")),
+            ("/canonical/sub/file.txt", Some("file")),
+            ("/canonical/inside.txt", None),
+            ("/canonical/linked/file.txt", None),
+            ("/canonical/escape.txt", None),
+        ];
+
+        for &(path, expected) in cases {
+            let response = client.get(path).dispatch();
+            match expected {
+                Some(body) => assert_eq!(response.into_string().as_deref(), Some(body), "{}", path),
+                None => assert_eq!(response.status(), Status::NotFound, "{}", path),
+            }
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory_vfs() {
+        use rocket_contrib::serve::vfs::MemoryFs;
+
+        let files = MemoryFs::new();
+        files.insert("index.html", "<h1>Generated</h1>");
+        files.insert("/data/report.csv", "day,visits\nmonday,10\n");
+        files.insert("data/.secret", "hidden");
+
+        let options = Options::Index | Options::DirectoryListing;
+        let rocket = rocket::ignite()
+            .mount("/mem", StaticFiles::with_vfs(files.clone(), options))
+            .mount("/mem_dots", StaticFiles::with_vfs(files.clone(), Options::DotFiles));
+
+        let client = Client::new(rocket).expect("valid rocket");
+        let response = client.get("/mem/").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().as_deref(), Some("<h1>Generated</h1>"));
+
+        let response = client.get("/mem/data/report.csv").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::CSV));
+        assert!(response.headers().get_one("Last-Modified").is_some());
+        let etag = response.headers().get_one("ETag").expect("etag").to_string();
+        let response = client.get("/mem/data/report.csv")
+            .header(Header::new("If-None-Match", etag))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotModified);
+
+        let response = client.get("/mem/data/report.csv")
+            .header(Header::new("Range", "bytes=0-2"))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::PartialContent);
+        assert_eq!(response.into_string().as_deref(), Some("day"));
+
+        let html = client.get("/mem/data/").dispatch().into_string().unwrap();
+        assert!(html.contains("<a href=\"&#x2F;mem&#x2F;data&#x2F;report.csv\">report.csv</a>"));
+        assert!(!html.contains(".secret"));
+
+        assert_eq!(client.get("/mem/data/.secret").dispatch().status(), Status::NotFound);
+        let response = client.get("/mem_dots/data/.secret").dispatch();
+        assert_eq!(response.into_string().as_deref(), Some("hidden"));
+
+        files.insert("data/new.txt", "new");
+        let response = client.get("/mem/data/new.txt").dispatch();
+        assert_eq!(response.into_string().as_deref(), Some("new"));
+
+        assert!(files.remove("data/report.csv"));
+        assert!(!files.remove("data/report.csv"));
+        assert_eq!(client.get("/mem/data/report.csv").dispatch().status(), Status::NotFound);
+        assert_eq!(files.paths(), vec!["data/.secret", "data/new.txt", "index.html"]);
+    }
+
+    #[cfg(feature = "serve_zip")]
+    #[test]
+    fn test_zip_vfs() {
+        use std::io::{Cursor, Write};
+        use rocket_contrib::serve::vfs::ZipFs;
+        use zip::{ZipWriter, write::FileOptions, CompressionMethod};
+
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.add_directory("inner/", options).unwrap();
+        writer.start_file("inner/goodbye", options).unwrap();
+        writer.write_all(b"Thanks for coming!").unwrap();
+        writer.start_file("index.html", options).unwrap();
+        writer.write_all(b"<h1>Zipped</h1>").unwrap();
+        writer.start_file("../escape.txt", options).unwrap();
+        writer.write_all(b"escaped").unwrap();
+        let archive = writer.finish().unwrap();
+
+        let files = ZipFs::from_reader(Cursor::new(archive.into_inner())).expect("valid archive");
+        assert_eq!(files.paths(), vec!["index.html", "inner/goodbye"]);
+
+        let rocket = rocket::ignite().mount("/zip", StaticFiles::with_vfs(files, Options::Index));
+        let client = Client::new(rocket).expect("valid rocket");
+        let response = client.get("/zip/").dispatch();
+        assert_eq!(response.into_string().as_deref(), Some("<h1>Zipped</h1>"));
+
+        let response = client.get("/zip/inner/goodbye").dispatch();
+        assert!(response.headers().get_one("Last-Modified").is_some());
+        assert_eq!(response.into_string().as_deref(), Some("Thanks for coming!"));
+        assert_eq!(client.get("/zip/inner/").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn test_not_modified_files_are_not_opened() {
+        use std::io;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use rocket_contrib::serve::vfs::{Vfs, Metadata, DirEntry, VfsFile, MemoryFs};
+
+        struct Counted(MemoryFs, Arc<AtomicUsize>);
+
+        #[rocket::async_trait]
+        impl Vfs for Counted {
+            async fn metadata(&self, path: &str) -> io::Result<Metadata> {
+                self.0.metadata(path).await
+            }
+
+            async fn open(&self, path: &str) -> io::Result<VfsFile> {
+                self.1.fetch_add(1, Ordering::SeqCst);
+                self.0.open(path).await
+            }
+
+            async fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+                self.0.read_dir(path).await
+            }
+        }
+
+        let files = MemoryFs::new();
+        files.insert("app.js", "console.log(1)");
+        let opened = Arc::new(AtomicUsize::new(0));
+        let vfs = Counted(files, opened.clone());
+        let rocket = rocket::ignite().mount("/", StaticFiles::with_vfs(vfs, Options::None));
+        let client = Client::new(rocket).expect("valid rocket");
+
+        let response = client.get("/app.js").dispatch();
+        let etag = response.headers().get_one("ETag").expect("etag").to_string();
+        assert_eq!(response.into_string().as_deref(), Some("console.log(1)"));
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+
+        let response = client.get("/app.js").header(Header::new("If-None-Match", etag)).dispatch();
+        assert_eq!(response.status(), Status::NotModified);
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "serve_s3")]
+    #[test]
+    fn test_s3_vfs() {
+        use std::sync::{Arc, Mutex};
+        use rocket_contrib::serve::vfs::S3Fs;
+        use rusoto_core::Region;
+        use rusoto_mock::{MockCredentialsProvider, MockRequestDispatcher};
+        use rusoto_mock::MultipleMockRequestDispatcher;
+        use rusoto_s3::S3Client;
+
+        const MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+        const LISTING: &str = "<ListBucketResult><Name>bucket</Name>\
+            <Prefix>assets/</Prefix><KeyCount>2</KeyCount><MaxKeys>1000</MaxKeys>\
+            <Delimiter>/</Delimiter><IsTruncated>false</IsTruncated>\
+            <Contents><Key>assets/app.js</Key><LastModified>2015-10-21T07:28:00.000Z\
+            </LastModified><ETag>&quot;abc&quot;</ETag><Size>14</Size></Contents>\
+            <CommonPrefixes><Prefix>assets/img/</Prefix></CommonPrefixes></ListBucketResult>";
+        const EMPTY_LISTING: &str = "<ListBucketResult><Name>bucket</Name>\
+            <Prefix>assets/missing.js/</Prefix><KeyCount>0</KeyCount><MaxKeys>1</MaxKeys>\
+            <Delimiter>/</Delimiter><IsTruncated>false</IsTruncated></ListBucketResult>";
+
+        // Each request to S3 is answered by the next response, in order.
+        let requests = Arc::new(Mutex::new(vec![]));
+        let respond = |status: u16| {
+            let requests = requests.clone();
+            MockRequestDispatcher::with_status(status).with_request_checker(move |req| {
+                let request = format!("{} {}", req.method(), req.path());
+                requests.lock().unwrap().push(request);
+            })
+        };
+
+        let object = |status| respond(status)
+            .with_header("Content-Length", "14")
+            .with_header("ETag", "\"abc\"")
+            .with_header("Last-Modified", MODIFIED);
+
+        let dispatcher = MultipleMockRequestDispatcher::new(vec![
+            respond(200).with_body(LISTING),
+            object(200),
+            object(200).with_body("console.log(1)"),
+            object(200),
+            respond(404),
+            respond(200).with_body(EMPTY_LISTING),
+        ]);
+
+        let client = S3Client::new_with(dispatcher, MockCredentialsProvider, Region::UsEast1);
+        let vfs = S3Fs::with_client(client, "bucket").prefix("/assets/");
+        let rocket = rocket::ignite()
+            .mount("/s3", StaticFiles::with_vfs(vfs, Options::DirectoryListing));
+
+        let client = Client::new(rocket).expect("valid rocket");
+        let listing = client.get("/s3/").dispatch().into_string().unwrap();
+        assert!(listing.contains("app.js") && listing.contains("img"), "{}", listing);
+
+        let response = client.get("/s3/app.js").dispatch();
+        assert_eq!(response.headers().get_one("ETag"), Some("\"abc\""));
+        assert_eq!(response.headers().get_one("Last-Modified"), Some(MODIFIED));
+        assert_eq!(response.content_type(), Some(ContentType::JavaScript));
+        assert_eq!(response.into_string().as_deref(), Some("console.log(1)"));
+
+        // Conditional requests are answered from the object's metadata.
+        let response = client.get("/s3/app.js")
+            .header(Header::new("If-None-Match", "\"abc\""))
+            .dispatch();
+
+        assert_eq!(response.status(), Status::NotModified);
+        assert_eq!(client.get("/s3/missing.js").dispatch().status(), Status::NotFound);
+
+        let requests = requests.lock().unwrap();
+        let methods: Vec<_> = requests.iter().map(|r| r.split(' ').next().unwrap()).collect();
+        assert_eq!(methods, vec!["GET", "HEAD", "GET", "HEAD", "HEAD", "GET"]);
+        assert!(requests[1].ends_with("/bucket/assets/app.js"), "{}", requests[1]);
+        assert!(requests[4].ends_with("/bucket/assets/missing.js"), "{}", requests[4]);
+    }
 }
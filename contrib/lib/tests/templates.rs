@@ -7,7 +7,7 @@ mod templates_tests {
 
     use rocket::{Rocket, http::RawStr};
     use rocket::config::{Config, Environment};
-    use rocket_contrib::templates::{Template, Metadata};
+    use rocket_contrib::templates::{Template, TemplateFairing, Metadata};
 
     #[get("/<engine>/<name>")]
     fn template_check(md: Metadata<'_>, engine: &RawStr, name: &RawStr) -> Option<()> {
@@ -26,13 +26,179 @@ mod templates_tests {
         Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("templates")
     }
 
+    #[get("/streamed/<name>")]
+    fn streamed(name: String) -> Template {
+        let mut map = std::collections::HashMap::new();
+        map.insert("title", "_test_");
+        map.insert("content", "<script />");
+        Template::render(name, &map).streamed()
+    }
+
+    #[get("/block/<name>/<block>")]
+    fn block(name: String, block: String) -> Template {
+        let mut map = std::collections::HashMap::new();
+        map.insert("title", "_test_");
+        map.insert("content", "<script />");
+        Template::render_block(name, block, &map)
+    }
+
     fn rocket() -> Rocket {
+        rocket_with(Template::fairing())
+    }
+
+    fn rocket_with(fairing: TemplateFairing) -> Rocket {
         let config = Config::build(Environment::Development)
             .extra("template_dir", template_root().to_str().expect("template directory"))
             .expect("valid configuration");
 
-        rocket::custom(config).attach(Template::fairing())
-            .mount("/", routes![template_check, is_reloading])
+        rocket::custom(config).attach(fairing)
+            .mount("/", routes![template_check, is_reloading, streamed, block])
+    }
+
+    mod embedded_tests {
+        use super::*;
+        use rocket::http::Status;
+        use rocket::local::blocking::Client;
+        use rocket_contrib::templates::embed_templates;
+
+        #[test]
+        fn test_embedded_template_paths() {
+            let templates = embed_templates!("tests/templates");
+            let paths: Vec<_> = templates.paths().collect();
+            assert!(paths.contains(&"hbs/common/header.html.hbs"));
+            assert!(paths.contains(&"tera/base.txt.tera"));
+            assert!(!paths.contains(&"shout/hello.txt.shout"));
+
+            let templates = embed_templates!("tests/templates", "shout");
+            assert!(templates.paths().any(|path| path == "shout/hello.txt.shout"));
+        }
+
+        /// Returns a client for an application with embedded templates whose
+        /// template directory is `dir`.
+        fn embedded_client(dir: &str) -> Client {
+            let config = Config::build(Environment::Development)
+                .extra("template_dir", dir)
+                .expect("valid configuration");
+
+            let fairing = Template::fairing().embed(embed_templates!("tests/templates"));
+            let routes = routes![template_check, is_reloading];
+            Client::new(rocket::custom(config).attach(fairing).mount("/", routes)).unwrap()
+        }
+
+        #[test]
+        #[cfg(all(feature = "tera_templates", feature = "handlebars_templates"))]
+        fn test_embedded_templates() {
+            // Without a template directory, the embedded templates are used
+            // in debug and release builds alike.
+            let client = embedded_client("does/not/exist");
+            for template in &["hbs/test", "tera/html_test", "tera/txt_test"] {
+                let response = client.get(format!("/{}", template)).dispatch();
+                assert_eq!(response.status(), Status::Ok);
+            }
+
+            let response = client.get("/shout/hello").dispatch();
+            assert_eq!(response.status(), Status::NotFound);
+            assert_eq!(client.get("/is_reloading").dispatch().status(), Status::NotFound);
+
+            let mut map = std::collections::HashMap::new();
+            map.insert("title", "_test_");
+            map.insert("content", "<script />");
+            let template = Template::show(client.cargo(), "tera/html_test", &map).unwrap();
+            assert!(template.contains("&lt;script &#x2F;&gt;"));
+
+            let template = Template::show(client.cargo(), "tera/txt_test", &map).unwrap();
+            assert!(template.contains("<script />"));
+
+            let template = Template::show(client.cargo(), "hbs/test", &map).unwrap();
+            assert!(template.contains("&lt;script /&gt;"));
+        }
+
+        #[test]
+        #[cfg(feature = "tera_templates")]
+        fn test_embedded_templates_replace_template_dir_in_release() {
+            // In debug builds, an existing template directory is used, and
+            // reloaded, instead of the embedded templates.
+            let root = template_root();
+            let client = embedded_client(root.to_str().expect("template directory"));
+            let status = client.get("/is_reloading").dispatch().status();
+            assert_eq!(status == Status::Ok, cfg!(debug_assertions));
+            assert_eq!(client.get("/tera/txt_test").dispatch().status(), Status::Ok);
+        }
+    }
+
+    mod custom_engine_tests {
+        use super::*;
+        use std::collections::HashMap;
+
+        use serde::Serialize;
+        use rocket::http::Status;
+        use rocket::local::blocking::Client;
+        use rocket_contrib::templates::Engine;
+
+        /// Upper-cases templates after replacing `{name}` with the context's
+        /// `name`, then appends a configurable suffix.
+        struct Shout {
+            templates: HashMap<String, String>,
+            suffix: String,
+        }
+
+        impl Engine for Shout {
+            const EXT: &'static str = "shout";
+
+            fn init(templates: &[(&str, &Path)]) -> Option<Shout> {
+                let mut map = HashMap::new();
+                for &(name, path) in templates {
+                    map.insert(name.to_string(), std::fs::read_to_string(path).ok()?);
+                }
+
+                Some(Shout { templates: map, suffix: String::new() })
+            }
+
+            fn render<C: Serialize>(&self, name: &str, context: C) -> Option<String> {
+                let template = self.templates.get(name)?;
+                let context = serde_json::to_value(context).ok()?;
+                let value = context.get("name")?.as_str()?;
+                let rendered = template.replace("{name}", value).to_uppercase();
+                Some(format!("{}{}", rendered, self.suffix))
+            }
+        }
+
+        fn shouting_rocket() -> Rocket {
+            let fairing = Template::custom(|engines| {
+                engines.get_mut::<Shout>().expect("registered engine").suffix = "!".into();
+            });
+
+            rocket_with(fairing.engine::<Shout>())
+        }
+
+        #[test]
+        fn test_custom_engine_templates() {
+            let client = Client::new(shouting_rocket()).unwrap();
+            let response = client.get("/shout/hello").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+
+            let mut map = HashMap::new();
+            map.insert("name", "bob");
+            let template = Template::show(client.cargo(), "shout/hello", &map);
+            assert_eq!(template, Some("HELLO, BOB!".into()));
+
+            // Without a `name`, the engine fails to render the template.
+            let template = Template::show(client.cargo(), "shout/hello", ());
+            assert_eq!(template, None);
+        }
+
+        #[test]
+        fn test_unregistered_engine_templates_are_ignored() {
+            let client = Client::new(rocket()).unwrap();
+            let response = client.get("/shout/hello").dispatch();
+            assert_eq!(response.status(), Status::NotFound);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_duplicate_engines_panic() {
+            Template::fairing().engine::<Shout>().engine::<Shout>();
+        }
     }
 
     #[cfg(feature = "tera_templates")]
@@ -64,6 +230,31 @@ mod templates_tests {
             assert_eq!(template, Some(ESCAPED_EXPECTED.into()));
         }
 
+        #[rocket::async_test]
+        async fn test_tera_template_minification() {
+            let fairing = Template::fairing().minify("tera/*.html.tera").minify_enabled(true);
+            let mut rocket = rocket_with(fairing);
+            let cargo = rocket.inspect().await;
+            let mut map = HashMap::new();
+            map.insert("title", "_test_");
+            map.insert("content", "<script />");
+
+            // Text templates are never minified, even when minification is on.
+            let template = Template::show(cargo, "tera/txt_test", &map);
+            assert_eq!(template, Some(UNESCAPED_EXPECTED.into()));
+
+            let template = Template::show(cargo, "tera/html_test", &map);
+            let expected = "h_start title: _test_ h_end &lt;script &#x2F;&gt; foot";
+            assert_eq!(template, Some(expected.into()));
+
+            // Nothing is minified when minification is off.
+            let fairing = Template::fairing().minify("tera/*.html.tera").minify_enabled(false);
+            let mut rocket = rocket_with(fairing);
+            let cargo = rocket.inspect().await;
+            let template = Template::show(cargo, "tera/html_test", &map);
+            assert_eq!(template, Some(ESCAPED_EXPECTED.into()));
+        }
+
         #[test]
         fn test_template_metadata_with_tera() {
             let client = Client::new(rocket()).unwrap();
@@ -80,6 +271,89 @@ mod templates_tests {
             let response = client.get("/hbs/txt_test").dispatch();
             assert_eq!(response.status(), Status::NotFound);
         }
+
+        #[test]
+        fn test_streamed_tera_templates() {
+            let client = Client::new(rocket()).unwrap();
+
+            let response = client.get("/streamed/tera%2Fhtml_test").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.into_string(), Some(ESCAPED_EXPECTED.into()));
+
+            let response = client.get("/streamed/tera%2Fnot_existing").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+        }
+
+        #[test]
+        fn test_tera_template_blocks() {
+            let client = Client::new(rocket()).unwrap();
+
+            let response = client.get("/block/tera%2Fhtml_test/content").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.into_string().unwrap(), "\n&lt;script &#x2F;&gt;\n");
+
+            // Nested blocks use the definitions of the derived template.
+            let response = client.get("/block/tera%2Ftxt_test/head").dispatch();
+            assert_eq!(response.into_string().unwrap(), "\nh_start\ntitle: _test_\nh_end\n");
+
+            // Blocks only defined by the parent can be rendered, too.
+            let response = client.get("/block/tera%2Ftxt_test/footer").dispatch();
+            assert_eq!(response.into_string().unwrap(), "foot");
+
+            let response = client.get("/block/tera%2Ftxt_test/sidebar").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+
+            let response = client.get("/block/tera%2Fnot_existing/content").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+        }
+
+        #[test]
+        #[cfg(feature = "serve")]
+        fn test_tera_listing_template() {
+            use rocket::http::{Accept, ContentType};
+            use rocket_contrib::serve::{StaticFiles, crate_relative};
+
+            let files = StaticFiles::from(crate_relative!("/tests/static"))
+                .listing_template("tera/listing");
+
+            // Mounted alone so that no other route can collide with listings.
+            let config = Config::build(Environment::Development)
+                .extra("template_dir", template_root().to_str().expect("template directory"))
+                .expect("valid configuration");
+
+            let rocket = rocket::custom(config).attach(Template::fairing()).mount("/files", files);
+            let client = Client::new(rocket).unwrap();
+            let response = client.get("/files/other/").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.content_type(), Some(ContentType::HTML));
+            assert_eq!(response.into_string().unwrap(), "<ul>\n<li>hello.txt (4)</li>\n</ul>\n");
+
+            let html = client.get("/files/").dispatch().into_string().unwrap();
+            assert!(html.contains("<li>inner/ ("), "{}", html);
+            assert!(html.find("<li>other/").unwrap() < html.find("<li>index.html").unwrap());
+
+            // Clients preferring JSON still get the built-in JSON listing.
+            let response = client.get("/files/other/").header(Accept::JSON).dispatch();
+            assert_eq!(response.content_type(), Some(ContentType::JSON));
+        }
+
+        #[test]
+        fn test_custom_tera_templates_have_no_blocks() {
+            let fairing = Template::custom(|engines| {
+                engines.tera.add_raw_template("custom", "{% block content %}hi{% endblock %}")
+                    .expect("valid template");
+            });
+
+            // Only the templates Rocket loads have renderable blocks.
+            let client = Client::new(rocket_with(fairing)).unwrap();
+            let context: HashMap<&str, &str> = HashMap::new();
+            assert_eq!(Template::show(client.cargo(), "custom", &context), Some("hi".into()));
+            let response = client.get("/block/custom/content").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+
+            let response = client.get("/block/tera%2Fhtml_test/content").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+        }
     }
 
     #[cfg(feature = "handlebars_templates")]
@@ -119,6 +393,74 @@ mod templates_tests {
             assert_eq!(response.status(), Status::NotFound);
         }
 
+        #[test]
+        fn test_streamed_handlebars_templates() {
+            let client = Client::new(rocket()).unwrap();
+
+            let response = client.get("/streamed/hbs%2Ftest").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            let expected = "Hello _test_!\n\n<main> &lt;script /&gt; </main>\nDone.\n\n";
+            assert_eq!(response.into_string(), Some(expected.into()));
+
+            let response = client.get("/streamed/hbs%2Fnot_existing").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+        }
+
+        #[test]
+        fn test_handlebars_template_blocks_are_unsupported() {
+            let client = Client::new(rocket()).unwrap();
+            let response = client.get("/block/hbs%2Ftest/content").dispatch();
+            assert_eq!(response.status(), Status::InternalServerError);
+        }
+
+        #[get("/globals?<content>")]
+        fn globals(content: Option<String>) -> Template {
+            let mut map = HashMap::new();
+            if let Some(content) = content {
+                map.insert("content", content);
+            }
+
+            Template::render("hbs/test", &map)
+        }
+
+        #[test]
+        fn test_global_context_values() {
+            use rocket::fairing::AdHoc;
+            use rocket::http::Header;
+
+            let fairing = Template::fairing()
+                .global("title", |_| "_global_")
+                .global("content", |_| "provider");
+
+            let rocket = rocket_with(fairing)
+                .mount("/", routes![globals])
+                .attach(AdHoc::on_request("Content", |req, _| Box::pin(async move {
+                    if req.headers().contains("X-Content") {
+                        Template::set_global(req, "content", "fairing");
+                    }
+                })));
+
+            let client = Client::new(rocket).unwrap();
+            let expected = |content: &str| {
+                format!("Hello _global_!\n\n<main> {} </main>\nDone.\n\n", content)
+            };
+
+            let response = client.get("/globals").dispatch();
+            assert_eq!(response.into_string(), Some(expected("provider")));
+
+            let response = client.get("/globals")
+                .header(Header::new("X-Content", ""))
+                .dispatch();
+
+            assert_eq!(response.into_string(), Some(expected("fairing")));
+
+            let response = client.get("/globals?content=handler")
+                .header(Header::new("X-Content", ""))
+                .dispatch();
+
+            assert_eq!(response.into_string(), Some(expected("handler")));
+        }
+
         #[test]
         #[cfg(debug_assertions)]
         fn test_template_reload() {
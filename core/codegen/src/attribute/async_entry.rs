@@ -82,7 +82,7 @@ impl EntryAttr for Launch {
             #[allow(dead_code)] #f
 
             #vis #sig {
-                ::rocket::async_main(async move { let _ = #rocket.launch().await; })
+                let _ = ::rocket::async_launch(#rocket);
             }
         ))
     }
@@ -0,0 +1,103 @@
+use devise::{syn, Spanned, Result};
+use devise::ext::SpanDiagnosticExt;
+
+use crate::proc_macro2::{TokenStream, Span};
+use crate::syn::{Attribute, Meta, NestedMeta, Lit};
+
+/// The parsed `#[cache(ttl = "60s", vary = "Accept", tags = "items")]`
+/// attribute on a route's function.
+#[derive(Debug)]
+pub struct Cache {
+    /// The time to live of a cached response, in seconds.
+    pub ttl: u64,
+    /// The request headers a cached response varies on.
+    pub vary: Vec<String>,
+    /// The tags cached responses are stored with.
+    pub tags: Vec<String>,
+}
+
+/// Parses a duration such as `"30"`, `"60s"`, `"5m"`, or `"1h"` into seconds.
+fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let split = duration.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration.len());
+    let (number, unit) = (duration[..split].parse::<u64>().ok()?, duration[split..].trim());
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+
+    number.checked_mul(multiplier)
+}
+
+/// Splits a comma-separated list such as `"Accept, Accept-Language"`.
+fn parse_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse(attr: &Attribute) -> Result<Cache> {
+    let help = "cache must be of the form: \
+        `#[cache(ttl = \"60s\", vary = \"Header, ...\", tags = \"tag, ...\")]`";
+
+    let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        meta => return Err(meta.span().error("invalid cache attribute").help(help)),
+    };
+
+    let (mut ttl, mut vary, mut tags) = (None, vec![], vec![]);
+    for nested in &list.nested {
+        let (name, value) = match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) => match (nv.path.get_ident(), &nv.lit) {
+                (Some(name), Lit::Str(value)) => (name.to_string(), value),
+                _ => return Err(nested.span().error("invalid cache parameter").help(help)),
+            },
+            _ => return Err(nested.span().error("invalid cache parameter").help(help)),
+        };
+
+        match name.as_str() {
+            "ttl" => ttl = Some(parse_duration(&value.value()).ok_or_else(|| {
+                value.span().error("invalid duration")
+                    .help("expected a duration such as \"30\", \"60s\", \"5m\", or \"1h\"")
+            })?),
+            "vary" => vary = parse_list(&value.value()),
+            "tags" => tags = parse_list(&value.value()),
+            _ => return Err(nested.span().error("unknown cache parameter").help(help)),
+        }
+    }
+
+    let ttl = ttl.ok_or_else(|| list.span().error("missing `ttl` parameter").help(help))?;
+    Ok(Cache { ttl, vary, tags })
+}
+
+/// Removes the `#[cache]` attribute, if any, from the route's `function`,
+/// returning the parsed attribute.
+pub fn split(function: &mut syn::ItemFn) -> Result<Option<Cache>> {
+    let mut cache = None;
+    for attr in std::mem::replace(&mut function.attrs, vec![]) {
+        if !attr.path.is_ident("cache") {
+            function.attrs.push(attr);
+            continue;
+        }
+
+        if cache.is_some() {
+            return Err(attr.span().error("duplicate `#[cache]` attribute"));
+        }
+
+        cache = Some(parse(&attr)?);
+    }
+
+    Ok(cache)
+}
+
+pub fn cache_attribute(_: proc_macro::TokenStream, _: proc_macro::TokenStream) -> TokenStream {
+    Span::call_site()
+        .error("`#[cache]` must be applied below a route attribute")
+        .help("place `#[cache(...)]` after the route attribute: `#[get(...)] #[cache(...)]`")
+        .emit_as_item_tokens()
+}
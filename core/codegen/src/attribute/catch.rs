@@ -1,7 +1,7 @@
-use devise::ext::SpanDiagnosticExt;
-use devise::{syn, MetaItem, Spanned, Result, FromMeta, Diagnostic};
+use devise::ext::{SpanDiagnosticExt, TypeExt};
+use devise::{syn, MetaItem, Spanned, SpanWrapped, Result, FromMeta, Diagnostic};
 
-use crate::http_codegen::{self, Optional};
+use crate::http_codegen::{self, Optional, DataSegment};
 use crate::proc_macro2::{TokenStream, Span};
 use crate::syn_ext::{IdentExt, ReturnTypeExt, TokenStreamExt};
 use self::syn::{Attribute, parse::Parser};
@@ -11,7 +11,8 @@ use crate::{CATCH_FN_PREFIX, CATCH_STRUCT_PREFIX};
 #[derive(Debug, FromMeta)]
 struct CatchAttribute {
     #[meta(naked)]
-    status: CatcherCode
+    status: CatcherCode,
+    error: Option<SpanWrapped<DataSegment>>,
 }
 
 /// `Some` if there's a code, `None` if it's `default`.
@@ -40,6 +41,8 @@ impl FromMeta for CatcherCode {
 struct CatchParams {
     /// The status associated with the code in the `#[catch(code)]` attribute.
     status: Option<http_codegen::Status>,
+    /// The typed error parameter in `#[catch(code, error = "<param>")]`, if any.
+    error: Option<SpanWrapped<DataSegment>>,
     /// The function that was decorated with the `catch` attribute.
     function: syn::ItemFn,
 }
@@ -62,7 +65,7 @@ fn parse_params(
         None => return Err(Span::call_site().error("internal error: bad attribute"))
     };
 
-    Ok(CatchParams { status: attribute.status.0, function })
+    Ok(CatchParams { status: attribute.status.0, error: attribute.error, function })
 }
 
 pub fn _catch(
@@ -82,10 +85,34 @@ pub fn _catch(
 
     // Variables names we'll use and reuse.
     define_vars_and_mods!(catch.function.span().into() =>
-        req, status, _Box, Request, Response, ErrorHandlerFuture, Status);
+        req, status, catch_mod, _Box, _Some, _None, _Err, Request, Response,
+        ErrorHandlerFuture, Status);
+
+    // Find the typed error argument, if one was declared.
+    let error_arg = match catch.error {
+        Some(ref error) => {
+            let name = &error.value.0.name;
+            let position = catch.function.sig.inputs.iter().position(|arg| match arg {
+                syn::FnArg::Typed(arg) => match *arg.pat {
+                    syn::Pat::Ident(ref pat) => pat.ident == name,
+                    _ => false
+                },
+                _ => false
+            });
+
+            match position {
+                Some(i) => Some(i),
+                None => return Err(error.span.error("unused error parameter")
+                    .span_note(catch.function.sig.paren_token.span,
+                        format!("expected argument named `{}` here", name)))
+            }
+        }
+        None => None
+    };
 
     // Determine the number of parameters that will be passed in.
-    if catch.function.sig.inputs.len() > 2 {
+    let positional_count = catch.function.sig.inputs.len() - error_arg.map_or(0, |_| 1);
+    if positional_count > 2 {
         return Err(catch.function.sig.paren_token.span
             .error("invalid number of arguments: must be zero, one, or two")
             .help("catchers optionally take `&Request` or `Status, &Request`"));
@@ -99,12 +126,40 @@ pub fn _catch(
     // Set the `req` and `status` spans to that of their respective function
     // arguments for a more correct `wrong type` error span. `rev` to be cute.
     let codegen_args = &[&req, &status];
-    let inputs = catch.function.sig.inputs.iter().rev()
+    let mut positional: Vec<_> = catch.function.sig.inputs.iter().enumerate()
+        .filter(|(i, _)| Some(*i) != error_arg)
+        .map(|(_, fn_arg)| fn_arg)
+        .rev()
         .zip(codegen_args.into_iter())
         .map(|(fn_arg, codegen_arg)| match fn_arg {
             syn::FnArg::Receiver(_) => codegen_arg.respanned(fn_arg.span()),
             syn::FnArg::Typed(a) => codegen_arg.respanned(a.ty.span())
-        }).rev();
+        })
+        .collect();
+
+    // Interleave the typed error argument, if any, with the positional ones.
+    let error_ident = quote!(__catch_error);
+    let inputs = (0..catch.function.sig.inputs.len())
+        .map(|i| match Some(i) == error_arg {
+            true => error_ident.clone(),
+            false => positional.pop().expect("positional argument"),
+        })
+        .collect::<Vec<_>>();
+
+    // Retrieve the typed error, declining to handle the error if it's missing.
+    let error_guard = error_arg.map(|i| {
+        let ty = match catch.function.sig.inputs[i] {
+            syn::FnArg::Typed(ref arg) => arg.ty.with_stripped_lifetimes(),
+            _ => unreachable!("typed error argument is a typed argument"),
+        };
+
+        quote_spanned!(ty.span().into() =>
+            let #error_ident: #ty = match <#ty as #catch_mod::FromTypedError>::from_request(#req) {
+                #_Some(__e) => __e,
+                #_None => return #catch_mod::decline(#req, #status),
+            };
+        )
+    });
 
     // We append `.await` to the function call if this is `async`.
     let dot_await = catch.function.sig.asyncness
@@ -126,6 +181,7 @@ pub fn _catch(
             #req: &'_b #Request
         ) -> #ErrorHandlerFuture<'_b> {
             #_Box::pin(async move {
+                #error_guard
                 let __response = #catcher_response;
                 #Response::build()
                     .status(#status)
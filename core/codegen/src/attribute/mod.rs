@@ -1,4 +1,6 @@
 pub mod async_entry;
+pub mod cache;
 pub mod catch;
+pub mod openapi;
 pub mod route;
 pub mod segments;
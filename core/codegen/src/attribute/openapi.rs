@@ -0,0 +1,117 @@
+use devise::{syn, Spanned, Result};
+use devise::ext::SpanDiagnosticExt;
+
+use crate::proc_macro2::{TokenStream, Span};
+use crate::syn::{Attribute, Meta, NestedMeta, Lit};
+
+/// The parsed `#[openapi(tags = "items", operation_id = "get_item")]`
+/// attribute on a route's function.
+#[derive(Debug, Default)]
+pub struct OpenApi {
+    /// The tags the route's operation is grouped by.
+    pub tags: Vec<String>,
+    /// The operation's identifier, if it isn't the function's name.
+    pub operation_id: Option<String>,
+    /// Whether the operation is deprecated.
+    pub deprecated: bool,
+}
+
+/// Splits a comma-separated list such as `"items, admin"`.
+fn parse_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse(attr: &Attribute) -> Result<OpenApi> {
+    let help = "openapi must be of the form: `#[openapi]` or \
+        `#[openapi(tags = \"tag, ...\", operation_id = \"id\", deprecated)]`";
+
+    let list = match attr.parse_meta()? {
+        Meta::Path(_) => return Ok(OpenApi::default()),
+        Meta::List(list) => list,
+        meta => return Err(meta.span().error("invalid openapi attribute").help(help)),
+    };
+
+    let mut openapi = OpenApi::default();
+    for nested in &list.nested {
+        match nested {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("deprecated") => {
+                openapi.deprecated = true;
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) => match (nv.path.get_ident(), &nv.lit) {
+                (Some(name), Lit::Str(value)) if name == "tags" => {
+                    openapi.tags = parse_list(&value.value());
+                }
+                (Some(name), Lit::Str(value)) if name == "operation_id" => {
+                    openapi.operation_id = Some(value.value());
+                }
+                _ => return Err(nested.span().error("unknown openapi parameter").help(help)),
+            },
+            _ => return Err(nested.span().error("invalid openapi parameter").help(help)),
+        }
+    }
+
+    Ok(openapi)
+}
+
+/// Removes the `#[openapi]` attribute, if any, from the route's `function`,
+/// returning the parsed attribute.
+pub fn split(function: &mut syn::ItemFn) -> Result<Option<OpenApi>> {
+    let mut openapi = None;
+    for attr in std::mem::replace(&mut function.attrs, vec![]) {
+        if !attr.path.is_ident("openapi") {
+            function.attrs.push(attr);
+            continue;
+        }
+
+        if openapi.is_some() {
+            return Err(attr.span().error("duplicate `#[openapi]` attribute"));
+        }
+
+        openapi = Some(parse(&attr)?);
+    }
+
+    Ok(openapi)
+}
+
+/// Returns the text of the doc comments among `attrs`, with the leading space
+/// of each line removed, or `None` if there are none.
+pub fn doc_text(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(ref doc) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(String::from).unwrap_or(line))
+        .collect();
+
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() { None } else { Some(text) }
+}
+
+/// Splits the text of a route's doc comments into a summary, its first
+/// paragraph joined into a single line, and a description, the remaining
+/// paragraphs.
+pub fn summary_and_description(text: &str) -> (String, Option<String>) {
+    let (first, rest) = match text.find("\n\n") {
+        Some(i) => (&text[..i], Some(text[i..].trim())),
+        None => (text, None),
+    };
+
+    let summary = first.lines().map(|line| line.trim()).collect::<Vec<_>>().join(" ");
+    (summary, rest.filter(|rest| !rest.is_empty()).map(String::from))
+}
+
+pub fn openapi_attribute(_: proc_macro::TokenStream, _: proc_macro::TokenStream) -> TokenStream {
+    Span::call_site()
+        .error("`#[openapi]` must be applied below a route attribute")
+        .help("place `#[openapi]` after the route attribute: `#[get(...)] #[openapi]`")
+        .emit_as_item_tokens()
+}
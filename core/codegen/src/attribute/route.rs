@@ -11,9 +11,13 @@ use crate::syn_ext::IdentExt;
 use crate::proc_macro2::{TokenStream, Span};
 use crate::http_codegen::{Method, MediaType, RoutePath, DataSegment, Optional};
 use crate::attribute::segments::{Source, Kind, Segment};
+use crate::attribute::cache::{self, Cache};
+use crate::attribute::openapi::{self, OpenApi};
 use crate::syn::{Attribute, parse::Parser};
+use crate::proc_macro2::TokenTree;
 
 use crate::{ROUTE_FN_PREFIX, ROUTE_STRUCT_PREFIX, URI_MACRO_PREFIX, ROCKET_PARAM_PREFIX};
+use crate::ROUTE_OPENAPI_FN_PREFIX;
 
 /// The raw, parsed `#[route]` attribute.
 #[derive(Debug, FromMeta)]
@@ -24,6 +28,7 @@ struct RouteAttribute {
     data: Option<SpanWrapped<DataSegment>>,
     format: Option<MediaType>,
     rank: Option<isize>,
+    max_concurrency: Option<SpanWrapped<usize>>,
 }
 
 /// The raw, parsed `#[method]` (e.g, `get`, `put`, `post`, etc.) attribute.
@@ -34,6 +39,15 @@ struct MethodRouteAttribute {
     data: Option<SpanWrapped<DataSegment>>,
     format: Option<MediaType>,
     rank: Option<isize>,
+    max_concurrency: Option<SpanWrapped<usize>>,
+}
+
+/// A data limit override in a `limits(name = "size", ...)` route parameter:
+/// the name of the limit and the size in bytes.
+#[derive(Debug)]
+struct Limit {
+    name: String,
+    bytes: u64,
 }
 
 /// This structure represents the parsed `route` attribute and associated items.
@@ -41,6 +55,12 @@ struct MethodRouteAttribute {
 struct Route {
     /// The attribute: `#[get(path, ...)]`.
     attribute: RouteAttribute,
+    /// The data limit overrides: `limits(file = "1GiB")`.
+    limits: Vec<Limit>,
+    /// The response caching policy: `#[cache(ttl = "60s")]`.
+    cache: Option<Cache>,
+    /// The OpenAPI description options: `#[openapi(tags = "items")]`.
+    openapi: Option<OpenApi>,
     /// The function the attribute decorated, i.e, the handler.
     function: syn::ItemFn,
     /// The non-static parameters declared in the route segments.
@@ -51,6 +71,72 @@ struct Route {
     inputs: Vec<(syn::Ident, syn::Ident, syn::Type)>,
 }
 
+/// Parses a data size such as `"512"`, `"64 KiB"`, or `"1GB"` into bytes.
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (number, unit) = (size[..split].parse::<u64>().ok()?, size[split..].trim());
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "kib" => 1 << 10,
+        "mb" => 1_000_000,
+        "mib" => 1 << 20,
+        "gb" => 1_000_000_000,
+        "gib" => 1 << 30,
+        "tb" => 1_000_000_000_000,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+
+    number.checked_mul(multiplier)
+}
+
+/// Removes the `limits(...)` parameter, if any, from the route attribute's
+/// arguments `args`, returning the remaining arguments and the parsed limits.
+fn split_limits(args: TokenStream) -> Result<(TokenStream, Vec<Limit>)> {
+    use crate::syn::{punctuated::Punctuated, Meta, NestedMeta, Lit, Token};
+
+    // Leave the arguments untouched, and any errors to the attribute's
+    // parser, unless there's a `limits` parameter.
+    let has_limits = args.clone().into_iter()
+        .any(|tt| matches!(tt, TokenTree::Ident(ref ident) if ident == "limits"));
+
+    let parser = Punctuated::<NestedMeta, Token![,]>::parse_terminated;
+    let args = match parser.parse2(args.clone()) {
+        Ok(parsed) if has_limits => parsed,
+        _ => return Ok((args, vec![])),
+    };
+
+    let (mut rest, mut limits) = (vec![], vec![]);
+    for arg in args {
+        let list = match arg {
+            NestedMeta::Meta(Meta::List(ref list)) if list.path.is_ident("limits") => list,
+            _ => { rest.push(arg); continue; }
+        };
+
+        for nested in &list.nested {
+            let help = "limits must be of the form: `limits(name = \"size\", ...)`";
+            let (name, size) = match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) => match (nv.path.get_ident(), &nv.lit) {
+                    (Some(name), Lit::Str(size)) => (name.to_string(), size),
+                    _ => return Err(nested.span().error("invalid limit").help(help)),
+                },
+                _ => return Err(nested.span().error("invalid limit").help(help)),
+            };
+
+            let bytes = parse_size(&size.value()).ok_or_else(|| {
+                size.span().error("invalid data size")
+                    .help("expected a size such as \"512\", \"64KiB\", or \"1GB\"")
+            })?;
+
+            limits.push(Limit { name, bytes });
+        }
+    }
+
+    Ok((quote!(#(#rest),*), limits))
+}
+
 fn parse_route(attr: RouteAttribute, function: syn::ItemFn) -> Result<Route> {
     // Gather diagnostics as we proceed.
     let mut diags = Diagnostics::new();
@@ -66,6 +152,13 @@ fn parse_route(attr: RouteAttribute, function: syn::ItemFn) -> Result<Route> {
         }
     }
 
+    // A route without execution slots could never be called.
+    if let Some(ref limit) = attr.max_concurrency {
+        if limit.value == 0 {
+            diags.push(limit.span.error("`max_concurrency` must be at least 1"));
+        }
+    }
+
     // Collect all of the dynamic segments in an `IndexSet`, checking for dups.
     let mut segments: IndexSet<Segment> = IndexSet::new();
     fn dup_check<I>(set: &mut IndexSet<Segment>, iter: I, diags: &mut Diagnostics)
@@ -121,7 +214,9 @@ fn parse_route(attr: RouteAttribute, function: syn::ItemFn) -> Result<Route> {
             .span_note(span, format!("expected argument named `{}` here", missing.name)))
     }
 
-    diags.head_err_or(Route { attribute: attr, function, inputs, segments })
+    diags.head_err_or(Route {
+        attribute: attr, limits: vec![], cache: None, openapi: None, function, inputs, segments
+    })
 }
 
 fn param_expr(seg: &Segment, ident: &syn::Ident, ty: &syn::Type) -> TokenStream {
@@ -174,6 +269,7 @@ fn param_expr(seg: &Segment, ident: &syn::Ident, ty: &syn::Type) -> TokenStream
 
 fn data_expr(ident: &syn::Ident, ty: &syn::Type) -> TokenStream {
     define_vars_and_mods!(req, data, FromTransformedData, Outcome, Transform);
+    let capture_error = capture_error_expr();
     let span = ident.span().join(ty.span()).unwrap_or_else(|| ty.span());
     quote_spanned! { span =>
         let __transform = <#ty as #FromTransformedData>::transform(#req, #data).await;
@@ -196,7 +292,10 @@ fn data_expr(ident: &syn::Ident, ty: &syn::Type) -> TokenStream {
         let #ident: #ty = match <#ty as #FromTransformedData>::from_data(#req, __outcome).await {
             #Outcome::Success(__d) => __d,
             #Outcome::Forward(__d) => return #Outcome::Forward(__d),
-            #Outcome::Failure((__c, _)) => return #Outcome::Failure(__c),
+            #Outcome::Failure((__c, __e)) => {
+                #capture_error
+                return #Outcome::Failure(__c);
+            }
         };
     }
 }
@@ -306,15 +405,29 @@ fn query_exprs(route: &Route) -> Option<TokenStream> {
     })
 }
 
+/// Records the guard error `__e` on the request if its type is a `TypedError`.
+fn capture_error_expr() -> TokenStream {
+    define_vars_and_mods!(req, catch_mod);
+    quote! {
+        #[allow(unused_imports)]
+        use #catch_mod::{CaptureTyped as _, CaptureUntyped as _};
+        (&&#catch_mod::Capture::new(__e)).capture(#req);
+    }
+}
+
 fn request_guard_expr(ident: &syn::Ident, ty: &syn::Type) -> TokenStream {
     define_vars_and_mods!(req, data, request, Outcome);
+    let capture_error = capture_error_expr();
     let span = ident.span().join(ty.span()).unwrap_or_else(|| ty.span());
     quote_spanned! { span =>
         #[allow(non_snake_case, unreachable_patterns, unreachable_code)]
         let #ident: #ty = match <#ty as #request::FromRequest>::from_request(#req).await {
             #Outcome::Success(__v) => __v,
             #Outcome::Forward(_) => return #Outcome::Forward(#data),
-            #Outcome::Failure((__c, _)) => return #Outcome::Failure(__c),
+            #Outcome::Failure((__c, __e)) => {
+                #capture_error
+                return #Outcome::Failure(__c);
+            }
         };
     }
 }
@@ -363,7 +476,7 @@ fn generate_respond_expr(route: &Route) -> TokenStream {
         syn::ReturnType::Type(_, ref ty) => ty.span().into()
     };
 
-    define_vars_and_mods!(req);
+    define_vars_and_mods!(req, Phase);
     define_vars_and_mods!(ret_span => handler);
     let user_handler_fn_name = &route.function.sig.ident;
     let parameter_names = route.inputs.iter()
@@ -371,13 +484,127 @@ fn generate_respond_expr(route: &Route) -> TokenStream {
 
     let _await = route.function.sig.asyncness.map(|a| quote_spanned!(a.span().into() => .await));
     let responder_stmt = quote_spanned! { ret_span =>
+        #req._enter_phase(#Phase::Handler);
         let ___responder = #user_handler_fn_name(#(#parameter_names),*) #_await;
+        #req._enter_phase(#Phase::Responder);
     };
 
     quote_spanned! { ret_span =>
         #responder_stmt
-        #handler::Outcome::from(#req, ___responder)
+        let ___outcome = #handler::Outcome::from(#req, ___responder);
+        #req._exit_phase();
+        ___outcome
+    }
+}
+
+/// Whether `tokens` contain an `impl Trait` type, which can't be named.
+fn contains_impl_trait(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ref ident) => ident == "impl",
+        TokenTree::Group(ref group) => contains_impl_trait(group.stream()),
+        _ => false,
+    })
+}
+
+fn generate_openapi_fn(route: &Route, name: &syn::Ident) -> Option<TokenStream> {
+    define_vars_and_mods!(openapi_mod, _Some, _None);
+    let OpenApi { ref tags, ref operation_id, deprecated } = *route.openapi.as_ref()?;
+    let probe = |ty: &syn::Type| quote_spanned! { ty.span().into() =>
+        (&&#openapi_mod::Describe::<#ty>::new())
+    };
+
+    let mut describe_stmts = vec![];
+    for (ident, _, ty) in &route.inputs {
+        let fn_segment: Segment = ident.into();
+        let describe = probe(ty);
+        let stmt = match route.segments.get(&fn_segment) {
+            Some(seg) if seg.source == Source::Path => {
+                let name = &seg.name;
+                quote! {
+                    let (__schema, _) = #describe.schema(__gen);
+                    let __location = #openapi_mod::Location::Path;
+                    __op.parameters.push(#openapi_mod::Parameter::new(#name, __location, __schema));
+                }
+            }
+            Some(seg) if seg.source == Source::Query && seg.kind == Kind::Multi => {
+                let name = &seg.name;
+                quote! {
+                    let (__schema, _) = #describe.schema(__gen);
+                    __op.parameters.extend(#openapi_mod::query_parameters(__gen, #name, __schema));
+                }
+            }
+            Some(seg) if seg.source == Source::Query => {
+                let name = &seg.name;
+                quote! {
+                    let (__schema, __required) = #describe.schema(__gen);
+                    let __location = #openapi_mod::Location::Query;
+                    let __param = #openapi_mod::Parameter::new(#name, __location, __schema);
+                    __op.parameters.push(__param.required(__required));
+                }
+            }
+            Some(_) => quote!(__op.request_body = #describe.request_body(__gen);),
+            None => quote!(#describe.guard(__gen, &mut __op);),
+        };
+
+        describe_stmts.push(stmt);
     }
+
+    let responses = match route.function.sig.output {
+        syn::ReturnType::Default => {
+            let probe = probe(&syn::parse_quote!(()));
+            quote!(#probe.responses(__gen))
+        }
+        syn::ReturnType::Type(_, ref ty) if !contains_impl_trait(quote!(#ty)) => {
+            let probe = probe(&ty.with_stripped_lifetimes());
+            quote!(#probe.responses(__gen))
+        }
+        syn::ReturnType::Type(..) => {
+            quote!(::std::vec![#openapi_mod::Response::default_response()])
+        }
+    };
+
+    let user_handler_fn_name = &route.function.sig.ident;
+    let operation_id = operation_id.clone().unwrap_or_else(|| user_handler_fn_name.to_string());
+    let (summary, description) = match openapi::doc_text(&route.function.attrs) {
+        Some(text) => openapi::summary_and_description(&text),
+        None => (String::new(), None),
+    };
+
+    let summary = match summary.is_empty() {
+        true => quote!(#_None),
+        false => quote!(#_Some(#summary.to_string())),
+    };
+
+    let description = match description {
+        Some(description) => quote!(#_Some(#description.to_string())),
+        None => quote!(#_None),
+    };
+
+    let vis = &route.function.vis;
+    Some(quote! {
+        /// Rocket code generated OpenAPI operation.
+        #[doc(hidden)]
+        #vis fn #name(__gen: &mut #openapi_mod::SchemaGenerator) -> #openapi_mod::Operation {
+            #[allow(unused_imports)]
+            use #openapi_mod::{DescribeSchema as _, DescribeAnySchema as _};
+            #[allow(unused_imports)]
+            use #openapi_mod::{DescribeGuard as _, DescribeNoGuard as _};
+            #[allow(unused_imports)]
+            use #openapi_mod::{DescribeData as _, DescribeNoData as _};
+            #[allow(unused_imports)]
+            use #openapi_mod::{DescribeResponder as _, DescribeNoResponder as _};
+
+            let mut __op = #openapi_mod::Operation::default();
+            __op.operation_id = #_Some(#operation_id.to_string());
+            __op.summary = #summary;
+            __op.description = #description;
+            __op.tags = ::std::vec![#(#tags.to_string()),*];
+            __op.deprecated = #deprecated;
+            #(#describe_stmts)*
+            __op.responses = #responses;
+            __op
+        }
+    })
 }
 
 fn codegen_route(route: Route) -> Result<TokenStream> {
@@ -408,18 +635,41 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
     }
 
     // Gather everything we need.
-    define_vars_and_mods!(req, data, _Box, Request, Data, StaticRouteInfo, HandlerFuture);
+    define_vars_and_mods!(req, data, _Box, Request, Data, StaticRouteInfo, HandlerFuture, Phase);
+    define_vars_and_mods!(StaticCacheInfo, Outcome, _Some, _None);
     let (vis, user_handler_fn) = (&route.function.vis, &route.function);
     let user_handler_fn_name = &user_handler_fn.sig.ident;
     let generated_fn_name = user_handler_fn_name.prepend(ROUTE_FN_PREFIX);
     let generated_struct_name = user_handler_fn_name.prepend(ROUTE_STRUCT_PREFIX);
     let generated_internal_uri_macro = generate_internal_uri_macro(&route);
     let generated_respond_expr = generate_respond_expr(&route);
+    let generated_openapi_fn_name = user_handler_fn_name.prepend(ROUTE_OPENAPI_FN_PREFIX);
+    let generated_openapi_fn = generate_openapi_fn(&route, &generated_openapi_fn_name);
+    let openapi = match generated_openapi_fn {
+        Some(_) => quote!(#_Some(#generated_openapi_fn_name)),
+        None => quote!(#_None),
+    };
 
     let method = route.attribute.method;
     let path = route.attribute.path.origin.0.to_string();
     let rank = Optional(route.attribute.rank);
     let format = Optional(route.attribute.format);
+    let max_concurrency = Optional(route.attribute.max_concurrency.as_ref().map(|l| l.value));
+    let limits = route.limits.iter().map(|Limit { name, bytes }| quote!((#name, #bytes)));
+    // Cached responses are served only once the guards have succeeded.
+    let cache_lookup = quote! {
+        if let #_Some(__response) = #req._cached_response().await {
+            #req._exit_phase();
+            return #Outcome::Success(__response);
+        }
+    };
+
+    let cache = match route.cache {
+        Some(Cache { ttl, ref vary, ref tags }) => quote! {
+            #_Some(#StaticCacheInfo { ttl: #ttl, vary: &[#(#vary),*], tags: &[#(#tags),*] })
+        },
+        None => quote!(#_None),
+    };
 
     Ok(quote! {
         #user_handler_fn
@@ -431,8 +681,10 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
             #data: #Data
         ) -> #HandlerFuture<'_b> {
             #_Box::pin(async move {
+                #req._enter_phase(#Phase::Guards);
                 #(#req_guard_definitions)*
                 #(#parameter_definitions)*
+                #cache_lookup
                 #data_stmt
 
                 #generated_respond_expr
@@ -442,6 +694,8 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
         /// Rocket code generated wrapping URI macro.
         #generated_internal_uri_macro
 
+        #generated_openapi_fn
+
         /// Rocket code generated static route info.
         #[doc(hidden)]
         #[allow(non_upper_case_globals)]
@@ -453,15 +707,22 @@ fn codegen_route(route: Route) -> Result<TokenStream> {
                 handler: #generated_fn_name,
                 format: #format,
                 rank: #rank,
+                limits: &[#(#limits),*],
+                cache: #cache,
+                max_concurrency: #max_concurrency,
+                openapi: #openapi,
             };
     }.into())
 }
 
 fn complete_route(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
-    let function: syn::ItemFn = syn::parse2(input)
+    let mut function: syn::ItemFn = syn::parse2(input)
         .map_err(|e| Diagnostic::from(e))
         .map_err(|diag| diag.help("`#[route]` can only be used on functions"))?;
 
+    let (args, limits) = split_limits(args)?;
+    let cache = cache::split(&mut function)?;
+    let openapi = openapi::split(&mut function)?;
     let full_attr = quote!(#[route(#args)]);
     let attrs = Attribute::parse_outer.parse2(full_attr)?;
     let attribute = match RouteAttribute::from_attrs("route", &attrs) {
@@ -469,7 +730,7 @@ fn complete_route(args: TokenStream, input: TokenStream) -> Result<TokenStream>
         None => return Err(Span::call_site().error("internal error: bad attribute"))
     };
 
-    codegen_route(parse_route(attribute, function)?)
+    codegen_route(Route { limits, cache, openapi, ..parse_route(attribute, function)? })
 }
 
 fn incomplete_route(
@@ -484,10 +745,13 @@ fn incomplete_route(
 
     let method_ident = syn::Ident::new(&method_str, method_span.into());
 
-    let function: syn::ItemFn = syn::parse2(input)
+    let mut function: syn::ItemFn = syn::parse2(input)
         .map_err(|e| Diagnostic::from(e))
         .map_err(|d| d.help(format!("#[{}] can only be used on functions", method_str)))?;
 
+    let (args, limits) = split_limits(args)?;
+    let cache = cache::split(&mut function)?;
+    let openapi = openapi::split(&mut function)?;
     let full_attr = quote!(#[#method_ident(#args)]);
     let attrs = Attribute::parse_outer.parse2(full_attr)?;
     let method_attribute = match MethodRouteAttribute::from_attrs(&method_str, &attrs) {
@@ -503,9 +767,10 @@ fn incomplete_route(
         data: method_attribute.data,
         format: method_attribute.format,
         rank: method_attribute.rank,
+        max_concurrency: method_attribute.max_concurrency,
     };
 
-    codegen_route(parse_route(attribute, function)?)
+    codegen_route(Route { limits, cache, openapi, ..parse_route(attribute, function)? })
 }
 
 pub fn route_attribute<M: Into<Option<crate::http::Method>>>(
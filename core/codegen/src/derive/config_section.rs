@@ -0,0 +1,107 @@
+use devise::{*, ext::SpanDiagnosticExt};
+use devise::syn;
+
+use crate::proc_macro2::TokenStream;
+
+/// The `#[config]` attributes of a field.
+#[derive(Default)]
+struct FieldConfig {
+    default: Option<syn::Lit>,
+    env: Option<syn::LitStr>,
+}
+
+impl FieldConfig {
+    /// Parses the `#[config]` attributes in `attrs`.
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<FieldConfig> {
+        let mut config = FieldConfig::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("config")) {
+            let list = match attr.parse_meta()? {
+                syn::Meta::List(list) => list,
+                meta => return Err(meta.span().error("expected `#[config(...)]`")),
+            };
+
+            for nested in list.nested {
+                let pair = match nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) => pair,
+                    nested => {
+                        return Err(nested.span().error("expected `default = ..` or `env = ..`"));
+                    }
+                };
+
+                let name = pair.path.get_ident().map(|ident| ident.to_string());
+                match (name.as_deref(), pair.lit) {
+                    (Some("default"), lit) => config.default = Some(lit),
+                    (Some("env"), syn::Lit::Str(var)) => config.env = Some(var),
+                    (Some("env"), lit) => {
+                        return Err(lit.span().error("expected a string literal"));
+                    }
+                    _ => {
+                        return Err(pair.path.span().error("unknown config attribute")
+                            .help("`ConfigSection` supports `default` and `env`"));
+                    }
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Returns the name serde deserializes the field `ident` with `attrs` from:
+/// the value of its `#[serde(rename = "..")]` attribute, if any, or its name.
+fn serde_name(ident: &syn::Ident, attrs: &[syn::Attribute]) -> String {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("serde")) {
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            for nested in list.nested {
+                if let syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) = nested {
+                    if let (true, syn::Lit::Str(name)) = (pair.path.is_ident("rename"), pair.lit) {
+                        return name.value();
+                    }
+                }
+            }
+        }
+    }
+
+    ident.to_string().trim_start_matches("r#").to_string()
+}
+
+pub fn derive_config_section(input: proc_macro::TokenStream) -> TokenStream {
+    DeriveGenerator::build_for(input, quote!(impl ::rocket::config::ConfigSection))
+        .generic_support(GenericSupport::None)
+        .data_support(DataSupport::NamedStruct)
+        .function(|_, inner| inner)
+        .try_map_fields(|_, fields| {
+            define_vars_and_mods!(_Vec);
+            let (mut defaults, mut overrides) = (vec![], vec![]);
+            for field in fields.iter() {
+                let config = FieldConfig::from_attrs(&field.attrs)?;
+                let ident = field.ident.as_ref().expect("named field");
+                let name = serde_name(ident, &field.attrs);
+                if let Some(default) = config.default {
+                    defaults.push(quote_spanned! { default.span() =>
+                        let __value = ::rocket::config::Value::try_from(#default)
+                            .expect("serializable default");
+
+                        __table.insert(#name.to_string(), __value);
+                    });
+                }
+
+                if let Some(var) = config.env {
+                    overrides.push(quote!((#name, #var)));
+                }
+            }
+
+            Ok(quote! {
+                fn defaults() -> ::rocket::config::Table {
+                    let mut __table = ::rocket::config::Table::new();
+                    #(#defaults)*
+                    __table
+                }
+
+                fn env_overrides() -> #_Vec<(&'static str, &'static str)> {
+                    ::std::vec![#(#overrides),*]
+                }
+            })
+        })
+        .to_tokens2()
+}
@@ -0,0 +1,245 @@
+use devise::{*, ext::SpanDiagnosticExt};
+use devise::syn;
+
+use crate::proc_macro2::TokenStream;
+use crate::attribute::openapi::doc_text;
+
+const NO_FIELDS: &str = "variants cannot have fields";
+const NO_EMPTY_ENUMS: &str = "enum must have at least one variant";
+const ONLY_ONE_UNNAMED: &str = "tuple structs must have exactly one field";
+
+/// Serde attributes that don't change the shape of a type's JSON.
+const SERDE_IGNORED: &[&str] = &[
+    "alias", "bound", "crate", "deny_unknown_fields", "expecting",
+];
+
+/// The serde attributes of a type, field, or variant that change its JSON.
+#[derive(Default)]
+struct Serde {
+    rename: Option<String>,
+    rename_all: Option<SpanWrapped<String>>,
+    skip: bool,
+    /// Whether the field may be missing, via `default` or `skip_serializing_if`.
+    optional: bool,
+}
+
+impl Serde {
+    /// Parses the `#[serde]` attributes in `attrs`. Attributes the schema
+    /// can't describe, such as `flatten`, are rejected so that the schema
+    /// never silently differs from the JSON.
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<Serde> {
+        let mut serde = Serde::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("serde")) {
+            let list = match attr.parse_meta()? {
+                syn::Meta::List(list) => list,
+                meta => return Err(meta.span().error("expected `#[serde(...)]`")),
+            };
+
+            for nested in list.nested {
+                let meta = match nested {
+                    syn::NestedMeta::Meta(meta) => meta,
+                    syn::NestedMeta::Lit(lit) => {
+                        return Err(lit.span().error("unexpected literal in `#[serde]`"));
+                    }
+                };
+
+                let name = meta.path().get_ident().map(|ident| ident.to_string());
+                match (name.as_deref(), &meta) {
+                    (Some("rename"), syn::Meta::NameValue(pair)) => {
+                        serde.rename = Some(lit_str(&pair.lit)?.value);
+                    }
+                    (Some("rename_all"), syn::Meta::NameValue(pair)) => {
+                        let rename_all = lit_str(&pair.lit)?;
+                        if !RENAME_RULES.contains(&rename_all.value.as_str()) {
+                            let help = format!("expected one of: {}", RENAME_RULES.join(", "));
+                            return Err(rename_all.span.error("unknown rename rule").help(help));
+                        }
+
+                        serde.rename_all = Some(rename_all);
+                    }
+                    (Some("skip"), syn::Meta::Path(_)) => serde.skip = true,
+                    (Some("default"), _) | (Some("skip_serializing_if"), _) => {
+                        serde.optional = true;
+                    }
+                    (Some(name), _) if SERDE_IGNORED.contains(&name) => continue,
+                    _ => {
+                        return Err(meta.span().error("unsupported serde attribute")
+                            .help("`JsonSchema` supports `rename`, `rename_all`, `skip`, `default`, \
+                                and `skip_serializing_if`"));
+                    }
+                }
+            }
+        }
+
+        Ok(serde)
+    }
+
+    /// The `rename_all` rule, if any.
+    fn rule(&self) -> Option<&str> {
+        self.rename_all.as_ref().map(|rule| rule.value.as_str())
+    }
+
+    /// Returns the JSON name of the field named `name`, in snake case, as
+    /// serde renames it per `rename_all`.
+    fn rename_field(&self, name: &str) -> String {
+        let pascal = || name.split('_').map(capitalize).collect::<String>();
+        match self.rule() {
+            None | Some("lowercase") | Some("snake_case") => name.to_string(),
+            Some("UPPERCASE") | Some("SCREAMING_SNAKE_CASE") => name.to_ascii_uppercase(),
+            Some("PascalCase") => pascal(),
+            Some("camelCase") => uncapitalize(&pascal()),
+            Some("kebab-case") => name.replace('_', "-"),
+            Some(_) => name.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+
+    /// Returns the JSON name of the variant named `name`, in Pascal case, as
+    /// serde renames it per `rename_all`.
+    fn rename_variant(&self, name: &str) -> String {
+        let snake = || {
+            let mut snake = String::new();
+            for (i, c) in name.char_indices() {
+                if i > 0 && c.is_uppercase() {
+                    snake.push('_');
+                }
+
+                snake.push(c.to_ascii_lowercase());
+            }
+
+            snake
+        };
+
+        match self.rule() {
+            None | Some("PascalCase") => name.to_string(),
+            Some("lowercase") => name.to_ascii_lowercase(),
+            Some("UPPERCASE") => name.to_ascii_uppercase(),
+            Some("camelCase") => uncapitalize(name),
+            Some("snake_case") => snake(),
+            Some("SCREAMING_SNAKE_CASE") => snake().to_ascii_uppercase(),
+            Some("kebab-case") => snake().replace('_', "-"),
+            Some(_) => snake().replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |c| c.to_uppercase().chain(chars).collect())
+}
+
+fn uncapitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |c| c.to_lowercase().chain(chars).collect())
+}
+
+/// The rules `rename_all` accepts.
+const RENAME_RULES: &[&str] = &[
+    "lowercase", "UPPERCASE", "PascalCase", "camelCase", "snake_case",
+    "SCREAMING_SNAKE_CASE", "kebab-case", "SCREAMING-KEBAB-CASE",
+];
+
+/// Returns the string in `lit`, which must be a string literal.
+fn lit_str(lit: &syn::Lit) -> Result<SpanWrapped<String>> {
+    match lit {
+        syn::Lit::Str(s) => {
+            Ok(SpanWrapped { full_span: s.span(), span: s.span(), value: s.value() })
+        }
+        _ => Err(lit.span().error("expected a string literal")),
+    }
+}
+
+pub fn derive_json_schema(input: proc_macro::TokenStream) -> TokenStream {
+    DeriveGenerator::build_for(input, quote!(impl ::rocket::openapi::JsonSchema))
+        .generic_support(GenericSupport::None)
+        .data_support(DataSupport::Struct | DataSupport::Enum)
+        .validate_enum(|_, data| {
+            if data.variants().count() == 0 {
+                return Err(data.brace_token.span.error(NO_EMPTY_ENUMS));
+            }
+
+            for variant in data.variants() {
+                if !variant.fields().is_empty() {
+                    return Err(variant.fields().span().error(NO_FIELDS));
+                }
+            }
+
+            Ok(())
+        })
+        .validate_struct(|_, data| match data.fields() {
+            fields if fields.are_unnamed() && fields.count() > 1 => {
+                Err(fields.span.error(ONLY_ONE_UNNAMED))
+            }
+            _ => Ok(())
+        })
+        .function(|gen, inner| {
+            define_vars_and_mods!(openapi_mod, _Option, _Some);
+            let name = gen.input.ident.to_string();
+            quote! {
+                fn schema_name() -> #_Option<::std::string::String> {
+                    #_Some(#name.to_string())
+                }
+
+                fn json_schema(
+                    __gen: &mut #openapi_mod::SchemaGenerator
+                ) -> #openapi_mod::Schema {
+                    #inner
+                }
+            }
+        })
+        .try_map_enum(|_, data| {
+            define_vars_and_mods!(openapi_mod);
+            let container = Serde::from_attrs(&data.derive_input.attrs)?;
+            let mut names = vec![];
+            for variant in data.variants() {
+                let serde = Serde::from_attrs(&variant.attrs)?;
+                if !serde.skip {
+                    let name = variant.ident.to_string();
+                    names.push(serde.rename.unwrap_or_else(|| container.rename_variant(&name)));
+                }
+            }
+
+            Ok(quote!(#openapi_mod::Schema::Enum(::std::vec![#(#names.to_string()),*])))
+        })
+        .try_map_fields(|_, fields| {
+            define_vars_and_mods!(openapi_mod, _Some, _None);
+            if fields.are_unnamed() {
+                let ty = &fields.iter().next().expect("have exactly one field").ty;
+                return Ok(quote!(__gen.subschema_for::<#ty>()));
+            }
+
+            let container = Serde::from_attrs(fields.parent.attrs())?;
+            let mut properties = vec![];
+            for field in fields.iter() {
+                let serde = Serde::from_attrs(&field.attrs)?;
+                if serde.skip {
+                    continue;
+                }
+
+                let ident = field.ident.as_ref().expect("named field").to_string();
+                let ident = ident.trim_start_matches("r#");
+                let name = serde.rename.unwrap_or_else(|| container.rename_field(ident));
+                let ty = &field.ty;
+                let description = match doc_text(&field.attrs) {
+                    Some(text) => quote!(#_Some(#text.to_string())),
+                    None => quote!(#_None),
+                };
+
+                let required = match serde.optional || container.optional {
+                    true => quote!(false),
+                    false => quote!(<#ty as #openapi_mod::JsonSchema>::is_required()),
+                };
+
+                properties.push(quote_spanned! { field.span().into() =>
+                    #openapi_mod::Property {
+                        name: #name.to_string(),
+                        schema: __gen.subschema_for::<#ty>(),
+                        required: #required,
+                        description: #description,
+                    }
+                });
+            }
+
+            Ok(quote!(#openapi_mod::Schema::Object(::std::vec![#(#properties),*])))
+        })
+        .to_tokens2()
+}
@@ -1,4 +1,6 @@
+pub mod config_section;
 pub mod from_form;
 pub mod from_form_value;
+pub mod json_schema;
 pub mod responder;
 pub mod uri_display;
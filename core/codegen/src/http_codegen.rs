@@ -45,8 +45,8 @@ pub struct RoutePath {
 impl FromMeta for Status {
     fn from_meta(meta: MetaItem<'_>) -> Result<Self> {
         let num = usize::from_meta(meta)?;
-        if num < 100 || num >= 600 {
-            return Err(meta.value_span().error("status must be in range [100, 599]"));
+        if num < 100 || num > 999 {
+            return Err(meta.value_span().error("status must be in range [100, 999]"));
         }
 
         Ok(Status(http::Status::raw(num as u16)))
@@ -82,6 +82,8 @@ vars_and_mods! {
     request => rocket::request,
     response => rocket::response,
     handler => rocket::handler,
+    catch_mod => rocket::catcher,
+    openapi_mod => rocket::openapi,
     log => rocket::logger,
     Outcome => rocket::outcome::Outcome,
     FromTransformedData => rocket::data::FromTransformedData,
@@ -89,9 +91,11 @@ vars_and_mods! {
     Query => rocket::request::Query,
     FromFormValue => rocket::request::FromFormValue,
     Request => rocket::request::Request,
+    Phase => rocket::request::Phase,
     Response => rocket::response::Response,
     Data => rocket::data::Data,
     StaticRouteInfo => rocket::StaticRouteInfo,
+    StaticCacheInfo => rocket::StaticCacheInfo,
     SmallVec => rocket::http::private::SmallVec,
     Status => rocket::http::Status,
     HandlerFuture => rocket::handler::HandlerFuture,
@@ -127,6 +131,7 @@ static ROUTE_STRUCT_PREFIX: &str = "static_rocket_route_info_for_";
 static CATCH_STRUCT_PREFIX: &str = "static_rocket_catch_info_for_";
 static CATCH_FN_PREFIX: &str = "rocket_catch_fn_";
 static ROUTE_FN_PREFIX: &str = "rocket_route_fn_";
+static ROUTE_OPENAPI_FN_PREFIX: &str = "rocket_route_openapi_fn_";
 static URI_MACRO_PREFIX: &str = "rocket_uri_macro_";
 static ROCKET_PARAM_PREFIX: &str = "__rocket_param_";
 
@@ -213,6 +218,10 @@ macro_rules! route_attribute {
         /// parameter := 'rank' '=' INTEGER
         ///            | 'format' '=' '"' MEDIA_TYPE '"'
         ///            | 'data' '=' '"' SINGLE_PARAM '"'
+        ///            | 'limits' '(' limit (',' limit)* ')'
+        ///            | 'max_concurrency' '=' INTEGER
+        ///
+        /// limit := IDENT '=' '"' SIZE '"'
         ///
         /// SINGLE_PARAM := '<' IDENT '>'
         /// MULTI_PARAM := '<' IDENT '..>'
@@ -220,10 +229,45 @@ macro_rules! route_attribute {
         /// URI_SEG := valid, non-percent-encoded HTTP URI segment
         /// MEDIA_TYPE := valid HTTP media type or known shorthand
         ///
+        /// SIZE := unsigned integer followed by an optional unit: B, kB, KiB,
+        ///         MB, MiB, GB, GiB, TB, or TiB
+        ///
         /// INTEGER := unsigned integer, as defined by Rust
         /// IDENT := valid identifier, as defined by Rust, except `_`
         /// ```
         ///
+        /// The `limits` parameter overrides the configured data limits for
+        /// requests handled by the route, so that, for instance, a single
+        /// upload route can accept large files without raising the limit for
+        /// the entire application:
+        ///
+        /// ```rust
+        /// # #[macro_use] extern crate rocket;
+        /// # use rocket::Data;
+        /// #[post("/upload", data = "<file>", limits(file = "1GiB"))]
+        /// async fn upload(file: Data) { /* .. */ }
+        /// ```
+        ///
+        /// See [`Request::limits()`](../rocket/struct.Request.html#method.limits).
+        ///
+        /// The `max_concurrency` parameter limits the number of requests the
+        /// route handles at once, so that an expensive route can't starve the
+        /// rest of the application. Requests beyond the limit wait briefly for
+        /// a request to finish, then fail with `503 Service Unavailable`:
+        ///
+        /// ```rust
+        /// # #[macro_use] extern crate rocket;
+        /// #[post("/report", max_concurrency = 4)]
+        /// async fn report() { /* .. */ }
+        /// ```
+        ///
+        /// See [`Route::max_concurrency()`](../rocket/struct.Route.html#method.max_concurrency).
+        ///
+        /// The responses of a route can be cached by applying the
+        /// [`cache`](attr.cache.html) attribute below the route attribute.
+        /// Similarly, a route is described for OpenAPI documents by applying the
+        /// [`openapi`](attr.openapi.html) attribute below the route attribute.
+        ///
         /// The generic route attribute is defined as:
         ///
         /// ```text
@@ -344,6 +388,87 @@ route_attribute!(head => Method::Head);
 route_attribute!(patch => Method::Patch);
 route_attribute!(options => Method::Options);
 
+/// Attribute to cache the responses of a route.
+///
+/// This attribute must be applied to a route's function _below_ the route
+/// attribute, which consumes it:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// #[get("/items")]
+/// #[cache(ttl = "60s", vary = "Accept", tags = "items")]
+/// fn items() -> &'static str { "[]" }
+/// ```
+///
+/// # Grammar
+///
+/// ```text
+/// cache := 'ttl' '=' DURATION (',' param)*
+///
+/// param := 'vary' '=' '"' HEADER (',' HEADER)* '"'
+///        | 'tags' '=' '"' TAG (',' TAG)* '"'
+///
+/// DURATION := '"' INTEGER ('s' | 'm' | 'h' | 'd')? '"'
+/// HEADER := a header name
+/// TAG := any string without a comma
+/// ```
+///
+/// Responses are cached only when a [`Cache`] fairing is attached. See the
+/// [`cache`] module for the caching semantics.
+///
+/// [`Cache`]: ../rocket/cache/struct.Cache.html
+/// [`cache`]: ../rocket/cache/index.html
+#[proc_macro_attribute]
+pub fn cache(args: TokenStream, input: TokenStream) -> TokenStream {
+    emit!(attribute::cache::cache_attribute(args, input))
+}
+
+/// Attribute to describe a route in OpenAPI documents.
+///
+/// This attribute must be applied to a route's function _below_ the route
+/// attribute, which consumes it:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// /// Lists the items.
+/// ///
+/// /// Items are listed in the order they were added.
+/// #[get("/items?<page>")]
+/// #[openapi(tags = "items", operation_id = "listItems")]
+/// fn items(page: Option<usize>) -> &'static str { "[]" }
+/// ```
+///
+/// # Grammar
+///
+/// ```text
+/// openapi := (param (',' param)*)?
+///
+/// param := 'tags' '=' '"' TAG (',' TAG)* '"'
+///        | 'operation_id' '=' STRING_LIT
+///        | 'deprecated'
+///
+/// TAG := any string without a comma
+/// STRING_LIT := any valid string literal, as defined by Rust
+/// ```
+///
+/// # Semantics
+///
+/// The route attribute additionally generates a function returning the
+/// route's [`Operation`], which is recorded in the generated [`Route`]. The
+/// operation's summary is the first paragraph of the function's doc comments
+/// and its description the remaining paragraphs. Its identifier is the
+/// function's name unless `operation_id` is set. The function's arguments and
+/// return type are described according to their role, as detailed in the
+/// [`openapi`] module.
+///
+/// [`Operation`]: ../rocket/openapi/struct.Operation.html
+/// [`Route`]: ../rocket/struct.Route.html
+/// [`openapi`]: ../rocket/openapi/index.html
+#[proc_macro_attribute]
+pub fn openapi(args: TokenStream, input: TokenStream) -> TokenStream {
+    emit!(attribute::openapi::openapi_attribute(args, input))
+}
+
 /// Attribute to generate a [`Catcher`] and associated metadata.
 ///
 /// This attribute can only be applied to free functions:
@@ -370,9 +495,12 @@ route_attribute!(options => Method::Options);
 /// The grammar for the `#[catch]` attributes is defined as:
 ///
 /// ```text
-/// catch := STATUS | 'default'
+/// catch := (STATUS | 'default') (',' error)?
 ///
-/// STATUS := valid HTTP status code (integer in [200, 599])
+/// error := 'error' '=' '"<' IDENT '>"'
+///
+/// STATUS := valid HTTP status code (integer in [100, 999])
+/// IDENT := a valid identifier, as defined by Rust
 /// ```
 ///
 /// # Typing Requirements
@@ -384,6 +512,24 @@ route_attribute!(options => Method::Options);
 ///   * `fn(`[`&Request`]`) -> R`
 ///   * `fn(`[`Status`]`, `[`&Request`]`) -> R`
 ///
+/// If an `error` parameter is declared, the function must additionally take
+/// an argument of that name, in any position, of type `&E` or `Option<&E>`
+/// where `E: `[`TypedError`]. The argument is the error produced by the guard
+/// that failed the request. A catcher taking `&E` declines to handle errors
+/// that did not produce an `E`, in which case the default catcher is used:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # #[derive(Debug)] struct MyError;
+/// # impl rocket::catcher::TypedError for MyError { }
+/// use rocket::Request;
+///
+/// #[catch(422, error = "<e>")]
+/// fn unprocessable(e: &MyError, req: &Request) -> String {
+///     format!("Invalid data for {}: {:?}", req.uri(), e)
+/// }
+/// ```
+///
 /// # Semantics
 ///
 /// The attribute generates two items:
@@ -403,6 +549,7 @@ route_attribute!(options => Method::Options);
 ///
 /// [`&Request`]: ../rocket/struct.Request.html
 /// [`Status`]: ../rocket/http/struct.Status.html
+/// [`TypedError`]: ../rocket/catcher/trait.TypedError.html
 /// [`ErrorHandler`]: ../rocket/type.ErrorHandler.html
 /// [`catchers!`]: macro.catchers.html
 /// [`Catcher`]: ../rocket/struct.Catcher.html
@@ -627,7 +774,7 @@ pub fn derive_from_form(input: TokenStream) -> TokenStream {
 /// parameter := 'status' '=' STATUS
 ///            | 'content_type' '=' CONTENT_TYPE
 ///
-/// STATUS := unsigned integer >= 100 and < 600
+/// STATUS := unsigned integer >= 100 and <= 999
 /// CONTENT_TYPE := string literal, as defined by Rust, identifying a valid
 ///                 Content-Type, as defined by Rocket
 /// ```
@@ -780,6 +927,110 @@ pub fn derive_uri_display_path(input: TokenStream) -> TokenStream {
     emit!(derive::uri_display::derive_uri_display_path(input))
 }
 
+/// Derive for the [`ConfigSection`] trait.
+///
+/// The [`ConfigSection`] derive can be applied to structs with named fields
+/// that also implement `Deserialize` and [`Validate`]:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::config::Validate;
+///
+/// #[derive(Deserialize, ConfigSection)]
+/// struct Pool {
+///     #[config(default = 4, env = "POOL_SIZE")]
+///     size: u32,
+///     #[config(default = "main")]
+///     name: String,
+///     url: String,
+/// }
+///
+/// impl Validate for Pool { }
+/// ```
+///
+/// Each field accepts a `#[config]` attribute with the following optional
+/// parameters:
+///
+///   * `default = LITERAL`
+///
+///     The value of the field when the configuration section doesn't set
+///     it. `LITERAL` is any literal that serializes to the field's type.
+///
+///   * `env = "VAR"`
+///
+///     The name of an environment variable that, when set, overrides the
+///     field's value. Its value is parsed as other environment variables
+///     overriding configuration parameters are.
+///
+/// Fields are named as serde deserializes them, taking `#[serde(rename)]`
+/// into account.
+///
+/// [`ConfigSection`]: ../rocket/config/trait.ConfigSection.html
+/// [`Validate`]: ../rocket/config/trait.Validate.html
+#[proc_macro_derive(ConfigSection, attributes(config))]
+pub fn derive_config_section(input: TokenStream) -> TokenStream {
+    emit!(derive::config_section::derive_config_section(input))
+}
+
+/// Derive for the [`JsonSchema`] trait.
+///
+/// The [`JsonSchema`] derive can be applied to structs and to enums whose
+/// variants have no fields:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// #[derive(JsonSchema)]
+/// enum Status {
+///     Active,
+///     Archived,
+/// }
+///
+/// #[derive(JsonSchema)]
+/// struct Id(u64);
+///
+/// #[derive(JsonSchema)]
+/// struct Item {
+///     /// The item's identifier.
+///     id: Id,
+///     status: Status,
+///     note: Option<String>,
+/// }
+/// ```
+///
+/// The type of every field is required to implement [`JsonSchema`]. The
+/// generated schema is named after the type. A struct with named fields is
+/// described by an object with a property of the same name for each field,
+/// whose description is the field's doc comments, and which is required
+/// unless the field's type isn't, as is the case for `Option<T>`. A tuple
+/// struct, which must have exactly one field, is described by the schema of
+/// its field. An enum is described by the names of its variants.
+///
+/// So that the schema matches the type's JSON, serde's `rename`, `rename_all`,
+/// and `skip` attributes rename and omit properties and variants as they do
+/// on the wire, and fields with `default` or `skip_serializing_if` aren't
+/// required. Serde attributes that the schema can't describe, such as
+/// `flatten` or `tag`, are rejected at compile time:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Serialize;
+/// #[derive(Serialize, JsonSchema)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Item {
+///     /// Documented as the `itemId` property.
+///     item_id: u64,
+///     #[serde(skip)]
+///     cached: bool,
+/// }
+/// ```
+///
+/// [`JsonSchema`]: ../rocket/openapi/trait.JsonSchema.html
+#[proc_macro_derive(JsonSchema)]
+pub fn derive_json_schema(input: TokenStream) -> TokenStream {
+    emit!(derive::json_schema::derive_json_schema(input))
+}
+
 /// Generates a [`Vec`] of [`Route`]s from a set of route paths.
 ///
 /// The `routes!` macro expands a list of route paths into a [`Vec`] of their
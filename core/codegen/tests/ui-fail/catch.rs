@@ -20,7 +20,7 @@ fn e3(_request: &Request) { }
 #[catch(99)]
 fn e4(_request: &Request) { }
 
-#[catch(600)]
+#[catch(1000)]
 fn e5(_request: &Request) { }
 
 #[catch(400, message = "foo")]
@@ -107,4 +107,9 @@ fn f3() {}
 #[route(120, "/")]
 fn f4() {}
 
+// Check that concurrency limits are validated.
+
+#[get("/", max_concurrency = 0)]
+fn g0() {}
+
 fn main() {}
@@ -6,9 +6,13 @@
 
 #[doc(hidden)] pub use hyper::{Body, Request, Response, Server};
 #[doc(hidden)] pub use hyper::body::{Bytes, HttpBody, Sender as BodySender};
+#[doc(hidden)] pub use hyper::body::to_bytes;
+#[doc(hidden)] pub use hyper::client::conn as client_conn;
+#[doc(hidden)] pub use hyper::server::conn as server_conn;
 #[doc(hidden)] pub use hyper::error::Error;
 #[doc(hidden)] pub use hyper::rt::Executor;
 #[doc(hidden)] pub use hyper::service::{make_service_fn, service_fn, Service};
+#[doc(hidden)] pub use hyper::upgrade::{OnUpgrade, Upgraded};
 
 #[doc(hidden)] pub use http::header::HeaderMap;
 #[doc(hidden)] pub use http::header::HeaderName as HeaderName;
@@ -18,6 +22,7 @@
 #[doc(hidden)] pub use http::response::Builder as ResponseBuilder;
 #[doc(hidden)] pub use http::status::StatusCode;
 #[doc(hidden)] pub use http::uri::{Uri, Parts as UriParts};
+#[doc(hidden)] pub use http::version::Version;
 
 /// Reexported http header types.
 pub mod header {
@@ -40,7 +45,7 @@ pub mod header {
         EXPIRES, FORWARDED, FROM, HOST, IF_MATCH, IF_MODIFIED_SINCE,
         IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, LAST_MODIFIED, LINK,
         LOCATION, ORIGIN, PRAGMA, RANGE, REFERER, REFERRER_POLICY, REFRESH,
-        STRICT_TRANSPORT_SECURITY, TE, TRANSFER_ENCODING, UPGRADE, USER_AGENT,
-        VARY
+        RETRY_AFTER, STRICT_TRANSPORT_SECURITY, TE, TRANSFER_ENCODING, UPGRADE,
+        USER_AGENT, VARY
     }
 }
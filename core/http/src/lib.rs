@@ -22,6 +22,10 @@ pub mod ext;
 #[cfg(feature = "tls")]
 pub mod tls;
 
+#[doc(hidden)]
+#[cfg(feature = "native-tls")]
+pub mod native_tls;
+
 #[doc(hidden)]
 pub mod route;
 
@@ -39,6 +43,7 @@ mod accept;
 mod raw_str;
 mod parse;
 mod listener;
+mod proxy;
 
 /// Case-preserving, ASCII case-insensitive string types.
 ///
@@ -64,7 +69,11 @@ pub mod private {
     }
 
     // These as well.
-    pub use crate::listener::{Incoming, Listener, Connection, bind_tcp};
+    pub use crate::listener::{Incoming, Listener, Connection, TlsInfo, bind_tcp};
+    pub use crate::listener::{AcceptCounts, HandshakeError};
+    pub use crate::listener::{TimedListener, TimedConnection, Timeouts};
+    pub use crate::proxy::{ProxyListener, ProxyConnection, PROXY_HEADER_TIMEOUT};
+    #[cfg(unix)] pub use crate::listener::bind_unix;
 }
 
 pub use crate::method::Method;
@@ -75,3 +84,4 @@ pub use crate::header::{Header, HeaderMap};
 pub use crate::raw_str::RawStr;
 pub use crate::media_type::MediaType;
 pub use crate::cookies::{Cookie, CookieJar, CookieCrumb, SameSite};
+pub use crate::proxy::{ProxyHeader, ProxyTls};
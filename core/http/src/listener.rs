@@ -3,6 +3,8 @@ use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::task::{Context, Poll};
 use std::time::Duration;
 
@@ -11,8 +13,11 @@ use hyper::server::accept::Accept;
 use log::{debug, error};
 
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::time::Delay;
+use tokio::time::{Delay, Instant};
 use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)] use tokio::net::{UnixListener, UnixStream};
+
+use crate::proxy::ProxyHeader;
 
 // TODO.async: 'Listener' and 'Connection' provide common enough functionality
 // that they could be introduced in upstream libraries.
@@ -30,6 +35,79 @@ pub trait Listener {
 /// A 'Connection' represents an open connection to a client
 pub trait Connection: AsyncRead + AsyncWrite {
     fn remote_addr(&self) -> Option<SocketAddr>;
+
+    /// The local address the connection was accepted on, if it has one.
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    /// The details of the TLS session the connection is secured with, if any.
+    fn tls_info(&self) -> Option<TlsInfo> {
+        None
+    }
+
+    /// The DER-encoded certificate chain the client authenticated with, if
+    /// any, starting with the client's own certificate.
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>> {
+        None
+    }
+
+    /// The PROXY protocol header the connection started with, if it was
+    /// accepted by a `ProxyListener`.
+    fn proxy_header(&self) -> Option<ProxyHeader> {
+        None
+    }
+}
+
+/// The details of the TLS session a connection is secured with. Fields are
+/// `None` when the TLS library doesn't report them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsInfo {
+    /// The TLS version, such as `TLSv1.3`.
+    pub version: Option<String>,
+    /// The cipher suite, such as `TLS13_AES_256_GCM_SHA384`.
+    pub cipher: Option<String>,
+    /// The application protocol negotiated via ALPN, such as `h2`.
+    pub alpn: Option<String>,
+    /// The host name the client requested via SNI.
+    pub sni: Option<String>,
+}
+
+/// The error a listener returns when the handshake of a connection it
+/// accepted, such as a TLS handshake, fails. Only that connection is affected.
+#[derive(Debug)]
+pub struct HandshakeError(pub io::Error);
+
+impl HandshakeError {
+    /// Wraps `error` in an `io::Error` of the same kind.
+    pub fn wrap(error: io::Error) -> io::Error {
+        io::Error::new(error.kind(), HandshakeError(error))
+    }
+
+    fn is(error: &io::Error) -> bool {
+        error.get_ref().map_or(false, |inner| inner.is::<HandshakeError>())
+    }
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "handshake failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Counts of the connections an [`Incoming`] accepted.
+#[derive(Debug, Default)]
+pub struct AcceptCounts {
+    /// Connections accepted, after any handshake succeeded.
+    pub accepted: AtomicU64,
+    /// Connections closed because their handshake failed.
+    pub handshake_failures: AtomicU64,
 }
 
 /// This is a genericized version of hyper's AddrIncoming that is intended to be
@@ -41,6 +119,7 @@ pub struct Incoming<L> {
     listener: L,
     sleep_on_errors: Option<Duration>,
     pending_error_delay: Option<Delay>,
+    counts: Option<Arc<AcceptCounts>>,
 }
 
 impl<L: Listener> Incoming<L> {
@@ -50,6 +129,18 @@ impl<L: Listener> Incoming<L> {
             listener,
             sleep_on_errors: Some(Duration::from_secs(1)),
             pending_error_delay: None,
+            counts: None,
+        }
+    }
+
+    /// Count accepted connections and failed handshakes in `counts`.
+    pub fn set_counts(&mut self, counts: Arc<AcceptCounts>) {
+        self.counts = Some(counts);
+    }
+
+    fn count(&self, counter: impl Fn(&AcceptCounts) -> &AtomicU64) {
+        if let Some(ref counts) = self.counts {
+            counter(counts).fetch_add(1, Ordering::Relaxed);
         }
     }
 
@@ -85,9 +176,15 @@ impl<L: Listener> Incoming<L> {
         loop {
             match self.listener.poll_accept(cx) {
                 Poll::Ready(Ok(stream)) => {
+                    self.count(|counts| &counts.accepted);
                     return Poll::Ready(Ok(stream));
                 },
                 Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) if HandshakeError::is(&e) => {
+                    debug!("closing connection: {}", e);
+                    self.count(|counts| &counts.handshake_failures);
+                    continue;
+                }
                 Poll::Ready(Err(e)) => {
                     // Connection errors can be ignored directly, continue by
                     // accepting the next request.
@@ -157,6 +254,275 @@ impl<L: fmt::Debug> fmt::Debug for Incoming<L> {
     }
 }
 
+/// The timeouts enforced on connections by a [`TimedListener`]. A timeout of
+/// `None` is not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// How long a client may take to send the headers of a request, measured
+    /// from the first byte of the request or the opening of the connection.
+    pub read_header: Option<Duration>,
+    /// How long an open connection may be idle between requests, or before
+    /// its first request.
+    pub idle: Option<Duration>,
+    /// How long a read of a request's body may stall, measured from the end
+    /// of its headers or the last read, until the response begins.
+    pub read: Option<Duration>,
+    /// How long a write to a client may stall before the connection is closed.
+    pub write: Option<Duration>,
+}
+
+/// A `Listener` that enforces [`Timeouts`] on the connections it accepts,
+/// protecting against clients that open connections and then send or receive
+/// data very slowly, or not at all.
+#[derive(Debug)]
+pub struct TimedListener<L> {
+    listener: L,
+    timeouts: Timeouts,
+}
+
+impl<L: Listener> TimedListener<L> {
+    /// Wraps `listener` so that its connections are subject to `timeouts`.
+    pub fn new(listener: L, timeouts: Timeouts) -> Self {
+        TimedListener { listener, timeouts }
+    }
+}
+
+impl<L: Listener> Listener for TimedListener<L> where L::Connection: Unpin {
+    type Connection = TimedConnection<L::Connection>;
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Connection>> {
+        let timeouts = self.timeouts;
+        self.listener.poll_accept(cx).map_ok(|conn| TimedConnection::new(conn, timeouts))
+    }
+}
+
+/// Where a connection is in the request/response cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Waiting for the next request after a response.
+    Idle,
+    /// Reading request headers. The value is how many bytes of the terminating
+    /// `\r\n\r\n` have been seen.
+    Header(usize),
+    /// Reading the request body or writing the response.
+    Body,
+}
+
+/// A connection accepted by a [`TimedListener`].
+pub struct TimedConnection<C> {
+    conn: C,
+    timeouts: Timeouts,
+    phase: Phase,
+    read_deadline: Option<Delay>,
+    write_deadline: Option<Delay>,
+}
+
+impl<C> TimedConnection<C> {
+    fn new(conn: C, timeouts: Timeouts) -> Self {
+        // A new connection is idle until it starts its first request, which
+        // it must do within both the idle and the header timeouts.
+        let first = match (timeouts.idle, timeouts.read_header) {
+            (Some(idle), Some(header)) => Some(idle.min(header)),
+            (idle, header) => idle.or(header),
+        };
+
+        let read_deadline = first.map(tokio::time::delay_for);
+        let (phase, write_deadline) = (Phase::Idle, None);
+        TimedConnection { conn, timeouts, phase, read_deadline, write_deadline }
+    }
+
+    fn enter(&mut self, phase: Phase) {
+        self.read_deadline = match phase {
+            Phase::Idle => self.timeouts.idle.map(tokio::time::delay_for),
+            Phase::Header(_) => self.timeouts.read_header.map(tokio::time::delay_for),
+            Phase::Body => self.timeouts.read.map(tokio::time::delay_for),
+        };
+
+        self.phase = phase;
+    }
+
+    /// Advances the phase of the connection after `bytes` have been read.
+    fn advance_read(&mut self, bytes: &[u8]) {
+        const END: &[u8] = b"\r\n\r\n";
+
+        if bytes.is_empty() {
+            return;
+        }
+
+        if self.phase == Phase::Idle {
+            self.enter(Phase::Header(0));
+        }
+
+        if self.phase == Phase::Body {
+            // The body timeout counts from the last read.
+            if let (Some(delay), Some(read)) = (&mut self.read_deadline, self.timeouts.read) {
+                delay.reset(Instant::now() + read);
+            }
+
+            return;
+        }
+
+        if let Phase::Header(mut seen) = self.phase {
+            for &byte in bytes {
+                seen = match byte {
+                    _ if byte == END[seen] => seen + 1,
+                    b'\r' => 1,
+                    _ => 0,
+                };
+
+                if seen == END.len() {
+                    self.enter(Phase::Body);
+                    return;
+                }
+            }
+
+            self.phase = Phase::Header(seen);
+        }
+    }
+
+    /// Advances the phase of the connection after a successful write.
+    fn advance_write(&mut self) {
+        match self.phase {
+            Phase::Body => self.enter(Phase::Idle),
+            Phase::Idle => {
+                // The idle timeout counts from the last write of a response.
+                if let (Some(delay), Some(idle)) = (&mut self.read_deadline, self.timeouts.idle) {
+                    delay.reset(Instant::now() + idle);
+                }
+            }
+            Phase::Header(_) => { /* a response before the request; keep waiting */ }
+        }
+    }
+
+    fn poll_write_deadline(&mut self, cx: &mut Context<'_>) -> Poll<io::Error> {
+        let timeout = match self.timeouts.write {
+            Some(timeout) => timeout,
+            None => return Poll::Pending,
+        };
+
+        let deadline = self.write_deadline.get_or_insert_with(|| tokio::time::delay_for(timeout));
+        Pin::new(deadline).poll(cx).map(|_| {
+            debug!("connection write timed out after {:?}", timeout);
+            io::Error::new(io::ErrorKind::TimedOut, "write timed out")
+        })
+    }
+}
+
+impl<C: AsyncRead + Unpin> AsyncRead for TimedConnection<C> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.conn).poll_read(cx, buf) {
+            Poll::Ready(Ok(n)) => {
+                this.advance_read(&buf[..n]);
+                Poll::Ready(Ok(n))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => {
+                let deadline = match this.read_deadline.as_mut() {
+                    Some(deadline) => deadline,
+                    None => return Poll::Pending,
+                };
+
+                match Pin::new(deadline).poll(cx) {
+                    Poll::Pending => Poll::Pending,
+                    // An idle connection is closed as if by the client.
+                    Poll::Ready(()) if this.phase == Phase::Idle => {
+                        debug!("closing idle connection");
+                        Poll::Ready(Ok(0))
+                    }
+                    Poll::Ready(()) if this.phase == Phase::Body => {
+                        debug!("connection timed out while reading a request body");
+                        let msg = "timed out reading request body";
+                        Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, msg)))
+                    }
+                    Poll::Ready(()) => {
+                        debug!("connection timed out while reading request headers");
+                        let msg = "timed out reading request headers";
+                        Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, msg)))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<C: AsyncWrite + Unpin> AsyncWrite for TimedConnection<C> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8]
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.conn).poll_write(cx, buf) {
+            Poll::Ready(result) => {
+                this.write_deadline = None;
+                if let Ok(n) = result {
+                    if n > 0 {
+                        this.advance_write();
+                    }
+                }
+
+                Poll::Ready(result)
+            }
+            Poll::Pending => this.poll_write_deadline(cx).map(Err),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.conn).poll_flush(cx) {
+            Poll::Ready(result) => {
+                this.write_deadline = None;
+                Poll::Ready(result)
+            }
+            Poll::Pending => this.poll_write_deadline(cx).map(Err),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.conn).poll_shutdown(cx)
+    }
+}
+
+impl<C: Connection + Unpin> Connection for TimedConnection<C> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.conn.local_addr()
+    }
+
+    fn tls_info(&self) -> Option<TlsInfo> {
+        self.conn.tls_info()
+    }
+
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>> {
+        self.conn.peer_certificates()
+    }
+
+    fn proxy_header(&self) -> Option<ProxyHeader> {
+        self.conn.proxy_header()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for TimedConnection<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedConnection")
+            .field("conn", &self.conn)
+            .field("timeouts", &self.timeouts)
+            .finish()
+    }
+}
+
 pub async fn bind_tcp(address: SocketAddr) -> io::Result<TcpListener> {
     Ok(TcpListener::bind(address).await?)
 }
@@ -177,4 +543,34 @@ impl Connection for TcpStream {
     fn remote_addr(&self) -> Option<SocketAddr> {
         self.peer_addr().ok()
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        TcpStream::local_addr(self).ok()
+    }
+}
+
+#[cfg(unix)]
+pub fn bind_unix(path: &std::path::Path) -> io::Result<UnixListener> {
+    UnixListener::bind(path)
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Connection = UnixStream;
+
+    /// Unix domain sockets have no `SocketAddr`: always returns `None`.
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Connection>> {
+        self.poll_accept(cx).map_ok(|(stream, _addr)| stream)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
 }
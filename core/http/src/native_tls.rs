@@ -0,0 +1,124 @@
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::net::TcpListener;
+
+use tokio_tls::{TlsAcceptor, TlsStream};
+
+pub use native_tls_crate::{Error, Identity};
+
+use crate::listener::{Connection, HandshakeError, Listener, TlsInfo};
+use crate::proxy::ProxyHeader;
+
+/// Parses a PEM-encoded certificate chain and PKCS#8 private key.
+pub fn load_identity(cert_chain: &[u8], key: &[u8]) -> Result<Identity, Error> {
+    Identity::from_pkcs8(cert_chain, key)
+}
+
+pub struct NativeTlsListener<L: Listener = TcpListener> {
+    listener: L,
+    acceptor: TlsAcceptor,
+    state: NativeTlsListenerState<L::Connection>,
+}
+
+enum NativeTlsListenerState<C> {
+    Listening,
+    Accepting(Pin<Box<dyn Future<Output=Result<TlsStream<C>, io::Error>> + Send>>),
+}
+
+impl<L: Listener> Listener for NativeTlsListener<L>
+    where L::Connection: Unpin + Send + 'static
+{
+    type Connection = TlsStream<L::Connection>;
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Result<Self::Connection, io::Error>> {
+        loop {
+            match &mut self.state {
+                NativeTlsListenerState::Listening => {
+                    match self.listener.poll_accept(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Ok(stream)) => {
+                            let acceptor = self.acceptor.clone();
+                            let fut = async move {
+                                acceptor.accept(stream).await
+                                    .map_err(|e| io::Error::new(io::ErrorKind::ConnectionAborted, e))
+                                    .map_err(HandshakeError::wrap)
+                            };
+
+                            self.state = NativeTlsListenerState::Accepting(Box::pin(fut));
+                        }
+                        Poll::Ready(Err(e)) => {
+                            return Poll::Ready(Err(e));
+                        }
+                    }
+                }
+                NativeTlsListenerState::Accepting(fut) => {
+                    match fut.as_mut().poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(result) => {
+                            self.state = NativeTlsListenerState::Listening;
+                            return Poll::Ready(result);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<L: Listener> NativeTlsListener<L> {
+    /// Serves TLS on the connections of `f(listener)` instead, such as to
+    /// read a PROXY protocol header before the TLS handshake. Must be called
+    /// before any connection is accepted.
+    pub fn map<M: Listener, F: FnOnce(L) -> M>(self, f: F) -> NativeTlsListener<M> {
+        let state = NativeTlsListenerState::Listening;
+        NativeTlsListener { listener: f(self.listener), acceptor: self.acceptor, state }
+    }
+}
+
+pub async fn bind_native_tls(
+    address: SocketAddr,
+    identity: Identity,
+) -> io::Result<NativeTlsListener> {
+    let listener = TcpListener::bind(address).await?;
+    native_tls_listener(listener, identity)
+}
+
+/// Serves TLS via the platform's TLS library on the already-bound `listener`.
+pub fn native_tls_listener(
+    listener: TcpListener,
+    identity: Identity,
+) -> io::Result<NativeTlsListener> {
+    let acceptor = native_tls_crate::TlsAcceptor::new(identity)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    let acceptor = TlsAcceptor::from(acceptor);
+    let state = NativeTlsListenerState::Listening;
+    Ok(NativeTlsListener { listener, acceptor, state })
+}
+
+impl<C: Connection + Unpin> Connection for TlsStream<C> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.get_ref().remote_addr()
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Connection::local_addr(self.get_ref())
+    }
+
+    fn proxy_header(&self) -> Option<ProxyHeader> {
+        self.get_ref().proxy_header()
+    }
+
+    /// The platform's TLS library doesn't report the session's details.
+    fn tls_info(&self) -> Option<TlsInfo> {
+        Some(TlsInfo::default())
+    }
+}
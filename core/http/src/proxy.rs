@@ -0,0 +1,494 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::str;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio::sync::mpsc;
+
+use crate::listener::{Connection, HandshakeError, Listener, TlsInfo};
+
+/// The signature that starts every version 2 header.
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The prefix of every version 1 header.
+const V1_PREFIX: &[u8] = b"PROXY ";
+
+/// The maximum length of a version 1 header, including its `\r\n`.
+const V1_MAX_LEN: usize = 107;
+
+/// The time connections have to send their header when no other timeout is
+/// configured.
+pub const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The version 2 TLV types Rocket reads.
+const PP2_TYPE_ALPN: u8 = 0x01;
+const PP2_TYPE_AUTHORITY: u8 = 0x02;
+const PP2_TYPE_SSL: u8 = 0x20;
+const PP2_SUBTYPE_SSL_VERSION: u8 = 0x21;
+const PP2_SUBTYPE_SSL_CN: u8 = 0x22;
+const PP2_SUBTYPE_SSL_CIPHER: u8 = 0x23;
+
+/// The bits of the `client` field of a `PP2_TYPE_SSL` TLV.
+const PP2_CLIENT_SSL: u8 = 0x01;
+const PP2_CLIENT_CERT_CONN: u8 = 0x02;
+const PP2_CLIENT_CERT_SESS: u8 = 0x04;
+
+/// The connection details a proxy sent in a PROXY protocol header.
+///
+/// Addresses are `None` when the proxy didn't relay them, as is the case for
+/// health checks the proxy makes on its own behalf. The remaining fields are
+/// only sent in version 2 headers, and only by proxies configured to do so.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyHeader {
+    /// The address of the client that connected to the proxy.
+    pub source: Option<SocketAddr>,
+    /// The address the client connected to.
+    pub destination: Option<SocketAddr>,
+    /// The host name the client requested, such as its TLS SNI.
+    pub authority: Option<String>,
+    /// The application protocol negotiated with the client via ALPN.
+    pub alpn: Option<String>,
+    /// The TLS details of the client's connection if it connected to the
+    /// proxy over TLS.
+    pub tls: Option<ProxyTls>,
+}
+
+/// The TLS details of a client's connection to a proxy, from a PROXY protocol
+/// version 2 header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyTls {
+    /// The TLS version, such as `TLSv1.3`.
+    pub version: Option<String>,
+    /// The cipher suite, such as `ECDHE-RSA-AES128-GCM-SHA256`.
+    pub cipher: Option<String>,
+    /// The common name of the client certificate's subject, if any.
+    pub common_name: Option<String>,
+    /// Whether the client presented a certificate.
+    pub client_cert: bool,
+    /// Whether the client presented a certificate the proxy verified.
+    pub verified: bool,
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn string(value: &[u8]) -> io::Result<String> {
+    str::from_utf8(value).map(String::from).map_err(|_| invalid("PROXY header has invalid UTF-8"))
+}
+
+/// Parses the PROXY protocol header at the start of `buf`. Returns the header
+/// and its length, or `None` if `buf` holds only the start of a header.
+fn parse(buf: &[u8]) -> io::Result<Option<(ProxyHeader, usize)>> {
+    if buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else if buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if V2_SIGNATURE.starts_with(buf) || V1_PREFIX.starts_with(buf) {
+        Ok(None)
+    } else {
+        Err(invalid("connection did not start with a PROXY header"))
+    }
+}
+
+/// Parses a version 1 header: `PROXY TCP4 {src} {dst} {src_port} {dst_port}\r\n`.
+fn parse_v1(buf: &[u8]) -> io::Result<Option<(ProxyHeader, usize)>> {
+    let end = match buf.windows(2).position(|w| w == b"\r\n") {
+        Some(end) if end + 2 <= V1_MAX_LEN => end,
+        None if buf.len() < V1_MAX_LEN => return Ok(None),
+        _ => return Err(invalid("PROXY header is too long")),
+    };
+
+    let line = str::from_utf8(&buf[V1_PREFIX.len()..end])
+        .map_err(|_| invalid("PROXY header has invalid UTF-8"))?;
+
+    let fields: Vec<&str> = line.split(' ').collect();
+    let header = match fields.as_slice() {
+        ["UNKNOWN", ..] => ProxyHeader::default(),
+        [proto, src, dst, src_port, dst_port] if *proto == "TCP4" || *proto == "TCP6" => {
+            let addr = |ip: &str, port: &str| -> io::Result<SocketAddr> {
+                let ip: IpAddr = ip.parse().map_err(|_| invalid("PROXY header has a bad IP"))?;
+                let port = port.parse().map_err(|_| invalid("PROXY header has a bad port"))?;
+                Ok(SocketAddr::new(ip, port))
+            };
+
+            ProxyHeader {
+                source: Some(addr(src, src_port)?),
+                destination: Some(addr(dst, dst_port)?),
+                ..ProxyHeader::default()
+            }
+        }
+        _ => return Err(invalid("malformed PROXY header")),
+    };
+
+    Ok(Some((header, end + 2)))
+}
+
+/// Parses a version 2 header: the signature, version and command, address
+/// family, length, addresses, and TLVs.
+fn parse_v2(buf: &[u8]) -> io::Result<Option<(ProxyHeader, usize)>> {
+    if buf.len() < 16 {
+        return Ok(None);
+    }
+
+    let (version_command, family) = (buf[12], buf[13]);
+    let len = 16 + u16::from_be_bytes([buf[14], buf[15]]) as usize;
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+
+    if buf.len() < len {
+        return Ok(None);
+    }
+
+    // A `LOCAL` command is a connection the proxy made on its own behalf.
+    let payload = &buf[16..len];
+    match version_command & 0x0F {
+        0x0 => return Ok(Some((ProxyHeader::default(), len))),
+        0x1 => {},
+        _ => return Err(invalid("unsupported PROXY command")),
+    }
+
+    // TCP or UDP over IPv4 or IPv6, Unix sockets, or unspecified.
+    let addresses_len = match family >> 4 {
+        0x0 => 0,
+        0x1 => 12,
+        0x2 => 36,
+        0x3 => 216,
+        _ => return Err(invalid("unsupported PROXY address family")),
+    };
+
+    if payload.len() < addresses_len {
+        return Err(invalid("PROXY header is truncated"));
+    }
+
+    let port = |at: usize| u16::from_be_bytes([payload[at], payload[at + 1]]);
+    let mut header = ProxyHeader::default();
+    match family >> 4 {
+        0x1 => {
+            let ip = |at: usize| {
+                let mut octets = [0; 4];
+                octets.copy_from_slice(&payload[at..at + 4]);
+                IpAddr::V4(Ipv4Addr::from(octets))
+            };
+
+            header.source = Some(SocketAddr::new(ip(0), port(8)));
+            header.destination = Some(SocketAddr::new(ip(4), port(10)));
+        }
+        0x2 => {
+            let ip = |at: usize| {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(&payload[at..at + 16]);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            };
+
+            header.source = Some(SocketAddr::new(ip(0), port(32)));
+            header.destination = Some(SocketAddr::new(ip(16), port(34)));
+        }
+        _ => {}
+    }
+
+    for (kind, value) in tlvs(&payload[addresses_len..])? {
+        match kind {
+            PP2_TYPE_ALPN => header.alpn = Some(string(value)?),
+            PP2_TYPE_AUTHORITY => header.authority = Some(string(value)?),
+            PP2_TYPE_SSL => header.tls = parse_tls(value)?,
+            _ => {}
+        }
+    }
+
+    Ok(Some((header, len)))
+}
+
+/// Parses the value of a `PP2_TYPE_SSL` TLV: the client flags, the
+/// verification result, and sub-TLVs.
+fn parse_tls(value: &[u8]) -> io::Result<Option<ProxyTls>> {
+    if value.len() < 5 {
+        return Err(invalid("PROXY header has a truncated SSL TLV"));
+    }
+
+    let client = value[0];
+    if client & PP2_CLIENT_SSL == 0 {
+        return Ok(None);
+    }
+
+    let client_cert = client & (PP2_CLIENT_CERT_CONN | PP2_CLIENT_CERT_SESS) != 0;
+    let verify = u32::from_be_bytes([value[1], value[2], value[3], value[4]]);
+    let verified = client_cert && verify == 0;
+    let mut tls = ProxyTls { client_cert, verified, ..ProxyTls::default() };
+    for (kind, value) in tlvs(&value[5..])? {
+        match kind {
+            PP2_SUBTYPE_SSL_VERSION => tls.version = Some(string(value)?),
+            PP2_SUBTYPE_SSL_CN => tls.common_name = Some(string(value)?),
+            PP2_SUBTYPE_SSL_CIPHER => tls.cipher = Some(string(value)?),
+            _ => {}
+        }
+    }
+
+    Ok(Some(tls))
+}
+
+/// Splits `data` into its type-length-value entries.
+fn tlvs(mut data: &[u8]) -> io::Result<Vec<(u8, &[u8])>> {
+    let mut tlvs = vec![];
+    while !data.is_empty() {
+        if data.len() < 3 {
+            return Err(invalid("PROXY header has a truncated TLV"));
+        }
+
+        let end = 3 + u16::from_be_bytes([data[1], data[2]]) as usize;
+        let value = data.get(3..end).ok_or_else(|| invalid("PROXY header has a truncated TLV"))?;
+        tlvs.push((data[0], value));
+        data = &data[end..];
+    }
+
+    Ok(tlvs)
+}
+
+/// A listener that reads the PROXY protocol header every accepted connection
+/// must start with. Connections without a valid header are closed.
+pub struct ProxyListener<L: Listener> {
+    listener: L,
+    timeout: Duration,
+    admit: Box<dyn FnMut() -> Option<Box<dyn Send>> + Send>,
+    tx: mpsc::UnboundedSender<io::Result<ProxyConnection<L::Connection>>>,
+    rx: mpsc::UnboundedReceiver<io::Result<ProxyConnection<L::Connection>>>,
+}
+
+impl<L: Listener> ProxyListener<L> {
+    /// Reads headers from the connections `listener` accepts, closing those
+    /// that don't send one within `timeout`.
+    pub fn new(listener: L, timeout: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let admit = Box::new(|| Some(Box::new(()) as Box<dyn Send>));
+        ProxyListener { listener, timeout, admit, tx, rx }
+    }
+
+    /// Calls `admit` for every accepted connection before reading its header.
+    /// Connections for which it returns `None` are closed immediately;
+    /// otherwise, the returned guard is held until the header has been read.
+    /// This bounds the number of headers read at once.
+    pub fn admit<F>(mut self, admit: F) -> Self
+        where F: FnMut() -> Option<Box<dyn Send>> + Send + 'static
+    {
+        self.admit = Box::new(admit);
+        self
+    }
+}
+
+impl<L: Listener> Listener for ProxyListener<L>
+    where L::Connection: Unpin + Send + 'static
+{
+    type Connection = ProxyConnection<L::Connection>;
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Connection>> {
+        // Headers are read concurrently so that a slow client can't hold up
+        // the connections accepted after it.
+        loop {
+            match self.listener.poll_accept(cx) {
+                Poll::Ready(Ok(conn)) => {
+                    let guard = match (self.admit)() {
+                        Some(guard) => guard,
+                        None => continue,
+                    };
+
+                    let (tx, timeout) = (self.tx.clone(), self.timeout);
+                    tokio::spawn(async move {
+                        let result = ProxyConnection::handshake(conn, timeout).await
+                            .map_err(HandshakeError::wrap);
+
+                        drop(guard);
+                        let _ = tx.send(result);
+                    });
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => break,
+            }
+        }
+
+        match self.rx.poll_recv(cx) {
+            Poll::Ready(Some(result)) => Poll::Ready(result),
+            // We hold a sender, so the channel is never closed.
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A connection accepted by a [`ProxyListener`], whose remote address is the
+/// one relayed by the proxy, if any.
+pub struct ProxyConnection<C> {
+    conn: C,
+    header: ProxyHeader,
+    /// Bytes read past the header, returned before reading from `conn`.
+    buffered: Vec<u8>,
+    pos: usize,
+}
+
+impl<C: AsyncRead + Unpin> ProxyConnection<C> {
+    /// Reads the header `conn` starts with, failing if it takes longer than
+    /// `timeout`.
+    async fn handshake(mut conn: C, timeout: Duration) -> io::Result<Self> {
+        let (header, buffered) = tokio::time::timeout(timeout, read_header(&mut conn)).await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "PROXY header timed out"))??;
+
+        Ok(ProxyConnection { conn, header, buffered, pos: 0 })
+    }
+}
+
+/// Reads the header `conn` starts with, returning it and any bytes read past
+/// it.
+async fn read_header<C: AsyncRead + Unpin>(conn: &mut C) -> io::Result<(ProxyHeader, Vec<u8>)> {
+    let mut buf = vec![];
+    loop {
+        if let Some((header, len)) = parse(&buf)? {
+            buf.drain(..len);
+            return Ok((header, buf));
+        }
+
+        let mut chunk = [0; 512];
+        match conn.read(&mut chunk).await? {
+            0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            n => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+impl<C: AsyncRead + Unpin> AsyncRead for ProxyConnection<C> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        if this.pos < this.buffered.len() {
+            let n = std::cmp::min(buf.len(), this.buffered.len() - this.pos);
+            buf[..n].copy_from_slice(&this.buffered[this.pos..this.pos + n]);
+            this.pos += n;
+            return Poll::Ready(Ok(n));
+        }
+
+        Pin::new(&mut this.conn).poll_read(cx, buf)
+    }
+}
+
+impl<C: AsyncWrite + Unpin> AsyncWrite for ProxyConnection<C> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8]
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.conn).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.conn).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.conn).poll_shutdown(cx)
+    }
+}
+
+impl<C: Connection + Unpin> Connection for ProxyConnection<C> {
+    /// The client address relayed by the proxy or, if there is none, the
+    /// address of the proxy.
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.header.source.or_else(|| self.conn.remote_addr())
+    }
+
+    /// The address the client connected to as relayed by the proxy or, if
+    /// there is none, the address the proxy connected to.
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.header.destination.or_else(|| self.conn.local_addr())
+    }
+
+    fn tls_info(&self) -> Option<TlsInfo> {
+        self.conn.tls_info()
+    }
+
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>> {
+        self.conn.peer_certificates()
+    }
+
+    fn proxy_header(&self) -> Option<ProxyHeader> {
+        Some(self.header.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_v1_headers() {
+        let buf = b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\nGET / HTTP/1.1\r\n";
+        let (header, len) = parse(buf).unwrap().unwrap();
+        assert_eq!(&buf[len..], b"GET / HTTP/1.1\r\n");
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(header.destination, Some("198.51.100.2:443".parse().unwrap()));
+
+        let (header, _) = parse(b"PROXY TCP6 ::1 ::2 1 2\r\n").unwrap().unwrap();
+        assert_eq!(header.source, Some("[::1]:1".parse().unwrap()));
+
+        let (header, len) = parse(b"PROXY UNKNOWN\r\n").unwrap().unwrap();
+        assert_eq!((header, len), (ProxyHeader::default(), 15));
+
+        assert!(parse(b"PROXY TCP4 192.0.2.1").unwrap().is_none());
+        assert!(parse(b"PRO").unwrap().is_none());
+        assert!(parse(b"").unwrap().is_none());
+        assert!(parse(b"GET / HTTP/1.1\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.0.2.1 198.51.100.2 56324\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 99999\r\n").is_err());
+        assert!(parse(&[V1_PREFIX, &[b'x'; 200]].concat()).is_err());
+    }
+
+    #[test]
+    fn parses_v2_headers() {
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x21, 0x11]);
+        let mut payload = vec![192, 0, 2, 1, 198, 51, 100, 2, 0xDC, 0x04, 0x01, 0xBB];
+        payload.extend_from_slice(&[PP2_TYPE_AUTHORITY, 0, 11]);
+        payload.extend_from_slice(b"example.com");
+
+        let mut ssl = vec![PP2_CLIENT_SSL | PP2_CLIENT_CERT_CONN, 0, 0, 0, 0];
+        ssl.extend_from_slice(&[PP2_SUBTYPE_SSL_VERSION, 0, 7]);
+        ssl.extend_from_slice(b"TLSv1.3");
+        ssl.extend_from_slice(&[PP2_SUBTYPE_SSL_CN, 0, 5]);
+        ssl.extend_from_slice(b"alice");
+        payload.extend_from_slice(&[PP2_TYPE_SSL, 0, ssl.len() as u8]);
+        payload.extend_from_slice(&ssl);
+
+        buf.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&payload);
+        let header_len = buf.len();
+        buf.extend_from_slice(b"GET /");
+
+        assert!(parse(&buf[..20]).unwrap().is_none());
+        let (header, len) = parse(&buf).unwrap().unwrap();
+        assert_eq!(len, header_len);
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(header.destination, Some("198.51.100.2:443".parse().unwrap()));
+        assert_eq!(header.authority.as_deref(), Some("example.com"));
+
+        let tls = header.tls.unwrap();
+        assert_eq!(tls.version.as_deref(), Some("TLSv1.3"));
+        assert_eq!(tls.common_name.as_deref(), Some("alice"));
+        assert!(tls.client_cert && tls.verified);
+
+        // A `LOCAL` command relays no addresses.
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(parse(&local).unwrap().unwrap(), (ProxyHeader::default(), 16));
+
+        let mut bad_version = V2_SIGNATURE.to_vec();
+        bad_version.extend_from_slice(&[0x11, 0x11, 0, 0]);
+        assert!(parse(&bad_version).is_err());
+    }
+}
@@ -84,6 +84,29 @@ impl StatusClass {
 /// assert_eq!(not_found.reason, "Not Found");
 /// assert_eq!(not_found.to_string(), "404 Not Found".to_string());
 /// ```
+///
+/// ## Unregistered Codes
+///
+/// Any three-digit code, registered or not, can be used as a status. Use
+/// [`Status::new()`] to construct one:
+///
+/// ```rust
+/// # extern crate rocket;
+/// use rocket::http::Status;
+///
+/// let unregistered = Status::new(499, "Client Closed Request");
+/// assert!(unregistered.is_valid());
+/// assert!(!Status::new(1000, "Too Big").is_valid());
+/// ```
+///
+/// A response with an invalid code, one for which [`Status::is_valid()`]
+/// returns `false`, whether returned by a route or a catcher or set by a
+/// fairing, is replaced with a `500 Internal Server Error`.
+///
+/// The reason phrase of a `Status` is only informational: it is not written
+/// to the network. The HTTP/1.1 status line contains the _canonical_ reason
+/// phrase for registered codes and no meaningful phrase for unregistered ones,
+/// and HTTP/2 does not transmit reason phrases at all.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Status {
     /// The HTTP status code associated with this status.
@@ -158,6 +181,25 @@ impl Status {
         Status { code, reason }
     }
 
+    /// Returns `true` if the code of `self` is a valid HTTP status code: a
+    /// three-digit integer in the range `[100, 999]`. The code need not be
+    /// registered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate rocket;
+    /// use rocket::http::Status;
+    ///
+    /// assert!(Status::Ok.is_valid());
+    /// assert!(Status::new(799, "Custom").is_valid());
+    /// assert!(!Status::new(42, "Answer").is_valid());
+    /// ```
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.code >= 100 && self.code <= 999
+    }
+
     /// Returns the class of a given status.
     ///
     /// # Example
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs;
 use std::future::Future;
 use std::io::{self, BufReader};
@@ -7,15 +8,19 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use tokio::net::{TcpListener, TcpStream};
+use arc_swap::ArcSwap;
+use tokio::net::TcpListener;
 
 use tokio_rustls::{TlsAcceptor, server::TlsStream};
-use tokio_rustls::rustls;
+use tokio_rustls::{rustls, webpki};
+use rustls::{ClientCertVerified, ClientCertVerifier, DistinguishedNames, Session, TLSError};
 
 pub use rustls::internal::pemfile;
-pub use rustls::{Certificate, PrivateKey, ServerConfig};
+pub use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+pub use rustls::{sign, ClientHello, ResolvesServerCert};
 
-use crate::listener::{Connection, Listener};
+use crate::listener::{Connection, HandshakeError, Listener, TlsInfo};
+use crate::proxy::ProxyHeader;
 
 #[derive(Debug)]
 pub enum Error {
@@ -23,6 +28,9 @@ pub enum Error {
     BadCerts,
     BadKeyCount,
     BadKey,
+    BadCrl,
+    UntrustedCrl,
+    StaleCrl,
 }
 
 // TODO.async: consider using async fs operations
@@ -67,22 +75,301 @@ pub fn load_private_key<P: AsRef<Path>>(path: P) -> Result<rustls::PrivateKey, E
     }
 }
 
-pub struct TlsListener {
-    listener: TcpListener,
-    acceptor: TlsAcceptor,
-    state: TlsListenerState,
+/// Loads the trust anchors clients' certificates must chain to.
+pub fn load_ca_certs<P: AsRef<Path>>(path: P) -> Result<RootCertStore, Error> {
+    let file = fs::File::open(path.as_ref()).map_err(Error::Io)?;
+    let mut roots = RootCertStore::empty();
+    match roots.add_pem_file(&mut BufReader::new(file)) {
+        Ok((valid, 0)) if valid > 0 => Ok(roots),
+        _ => Err(Error::BadCerts),
+    }
+}
+
+/// The certificates revoked by a set of certificate revocation lists.
+#[derive(Debug, Default)]
+pub struct Revocations {
+    /// The issuer and serial number of each revoked certificate.
+    revoked: HashSet<(Vec<u8>, Vec<u8>)>,
+    /// The earliest time, in seconds since the Unix epoch, by which one of the
+    /// lists is to be replaced, if any.
+    next_update: Option<i64>,
+}
+
+impl Revocations {
+    /// Returns `true` if the certificate `cert` has been revoked.
+    fn is_revoked(&self, cert: &x509_parser::certificate::X509Certificate<'_>) -> bool {
+        let key = (cert.issuer().as_raw().to_vec(), cert.raw_serial().to_vec());
+        self.revoked.contains(&key)
+    }
+
+    /// Returns `true` if a list is past its next update, so that it may no
+    /// longer list every revoked certificate.
+    fn is_stale(&self) -> bool {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+
+        self.next_update.map_or(false, |next_update| now > next_update)
+    }
+}
+
+/// Loads the certificates revoked by the PEM or DER encoded certificate
+/// revocation lists in the file at `path`. Every list must be signed by one of
+/// the certificate authorities `ca_certs` and must not be past its next update.
+pub fn load_crl<P: AsRef<Path>>(path: P, ca_certs: &[Certificate]) -> Result<Revocations, Error> {
+    use x509_parser::pem::Pem;
+
+    let bytes = fs::read(path.as_ref()).map_err(Error::Io)?;
+    let mut ders = vec![];
+    for pem in Pem::iter_from_buffer(&bytes) {
+        match pem {
+            Ok(pem) if pem.label == "X509 CRL" => ders.push(pem.contents),
+            _ => break,
+        }
+    }
+
+    if ders.is_empty() {
+        ders.push(bytes);
+    }
+
+    let mut cas = vec![];
+    for ca in ca_certs {
+        let (_, ca) = x509_parser::parse_x509_certificate(&ca.0).map_err(|_| Error::BadCerts)?;
+        cas.push(ca);
+    }
+
+    let mut revocations = Revocations::default();
+    for der in &ders {
+        let (_, crl) = x509_parser::parse_x509_crl(der).map_err(|_| Error::BadCrl)?;
+        let issuer = crl.issuer().as_raw();
+        let verified = cas.iter()
+            .filter(|ca| ca.subject().as_raw() == issuer)
+            .any(|ca| verify_crl_signature(der, &crl, ca));
+
+        if !verified {
+            return Err(Error::UntrustedCrl);
+        }
+
+        if let Some(next_update) = crl.next_update() {
+            let next_update = next_update.timestamp();
+            revocations.next_update = match revocations.next_update {
+                Some(earliest) => Some(earliest.min(next_update)),
+                None => Some(next_update),
+            };
+        }
+
+        for cert in crl.iter_revoked_certificates() {
+            revocations.revoked.insert((issuer.to_vec(), cert.raw_serial().to_vec()));
+        }
+    }
+
+    if revocations.is_stale() {
+        return Err(Error::StaleCrl);
+    }
+
+    Ok(revocations)
+}
+
+/// Returns `true` if the DER encoded list `der`, parsed as `crl`, was signed
+/// with the key of the certificate `ca`.
+fn verify_crl_signature(
+    der: &[u8],
+    crl: &x509_parser::revocation_list::CertificateRevocationList<'_>,
+    ca: &x509_parser::certificate::X509Certificate<'_>
+) -> bool {
+    use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+
+    // A `CertificateList` is a sequence whose first element is the signed
+    // `TBSCertList`.
+    let tbs = match der_element(der).and_then(|(_, list, _)| der_element(list)) {
+        Some((tbs, _, _)) => tbs,
+        None => return false,
+    };
+
+    let algorithms: Vec<&'static dyn VerificationAlgorithm> =
+        match &*crl.signature_algorithm.algorithm.to_id_string() {
+            "1.2.840.113549.1.1.11" => vec![&signature::RSA_PKCS1_2048_8192_SHA256],
+            "1.2.840.113549.1.1.12" => vec![&signature::RSA_PKCS1_2048_8192_SHA384],
+            "1.2.840.113549.1.1.13" => vec![&signature::RSA_PKCS1_2048_8192_SHA512],
+            "1.2.840.10045.4.3.2" => {
+                vec![&signature::ECDSA_P256_SHA256_ASN1, &signature::ECDSA_P384_SHA256_ASN1]
+            }
+            "1.2.840.10045.4.3.3" => {
+                vec![&signature::ECDSA_P256_SHA384_ASN1, &signature::ECDSA_P384_SHA384_ASN1]
+            }
+            "1.3.101.112" => vec![&signature::ED25519],
+            _ => vec![],
+        };
+
+    let key = ca.public_key().subject_public_key.data;
+    let signature = crl.signature_value.data;
+    algorithms.into_iter()
+        .any(|alg| UnparsedPublicKey::new(alg, key).verify(tbs, signature).is_ok())
+}
+
+/// Splits the DER element with a single-byte tag at the start of `input`,
+/// returning the element, its contents, and the remainder of `input`.
+fn der_element(input: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let (header, len) = match *input.get(1)? {
+        len if len < 0x80 => (2, len as usize),
+        len => {
+            let count = (len & 0x7F) as usize;
+            if count == 0 || count > 4 {
+                return None;
+            }
+
+            let bytes = input.get(2..2 + count)?;
+            (2 + count, bytes.iter().fold(0, |len, &byte| (len << 8) | byte as usize))
+        }
+    };
+
+    let end = header.checked_add(len)?;
+    let element = input.get(..end)?;
+    Some((element, &element[header..], &input[end..]))
+}
+
+/// Returns the time, in seconds since the Unix epoch, after which `cert` is no
+/// longer valid, or `None` if `cert` is malformed.
+pub fn not_after(cert: &Certificate) -> Option<i64> {
+    let (_, cert) = x509_parser::parse_x509_certificate(&cert.0).ok()?;
+    Some(cert.validity().not_after.timestamp())
+}
+
+/// Returns the subject public key of `cert`, such as the uncompressed point of
+/// an ECDSA key, or `None` if `cert` is malformed.
+pub fn public_key(cert: &Certificate) -> Option<Vec<u8>> {
+    let (_, cert) = x509_parser::parse_x509_certificate(&cert.0).ok()?;
+    Some(cert.public_key().subject_public_key.data.to_vec())
+}
+
+/// How a TLS listener authenticates clients via certificates.
+#[derive(Clone)]
+pub struct ClientAuth {
+    /// The trust anchors client certificates must chain to.
+    pub roots: RootCertStore,
+    /// Whether clients must present a certificate.
+    pub mandatory: bool,
+    /// The revoked client certificates.
+    pub revoked: Arc<Revocations>,
+}
+
+/// Verifies client certificates against `roots`, then rejects revoked ones.
+struct ClientAuthVerifier {
+    inner: Arc<dyn ClientCertVerifier>,
+    revoked: Arc<Revocations>,
+}
+
+impl ClientAuthVerifier {
+    fn new(auth: ClientAuth) -> Arc<dyn ClientCertVerifier> {
+        let inner = if auth.mandatory {
+            rustls::AllowAnyAuthenticatedClient::new(auth.roots)
+        } else {
+            rustls::AllowAnyAnonymousOrAuthenticatedClient::new(auth.roots)
+        };
+
+        Arc::new(ClientAuthVerifier { inner, revoked: auth.revoked })
+    }
+}
+
+impl ClientCertVerifier for ClientAuthVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.inner.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self, sni: Option<&webpki::DNSName>) -> Option<bool> {
+        self.inner.client_auth_mandatory(sni)
+    }
+
+    fn client_auth_root_subjects(&self, sni: Option<&webpki::DNSName>) -> Option<DistinguishedNames> {
+        self.inner.client_auth_root_subjects(sni)
+    }
+
+    fn verify_client_cert(
+        &self,
+        presented_certs: &[Certificate],
+        sni: Option<&webpki::DNSName>
+    ) -> Result<ClientCertVerified, TLSError> {
+        let verified = self.inner.verify_client_cert(presented_certs, sni)?;
+        if let Some(cert) = presented_certs.first() {
+            let (_, cert) = x509_parser::parse_x509_certificate(&cert.0)
+                .map_err(|_| TLSError::General("malformed client certificate".into()))?;
+
+            if self.revoked.is_stale() {
+                let error = "certificate revocation list is past its next update";
+                return Err(TLSError::General(error.into()));
+            }
+
+            if self.revoked.is_revoked(&cert) {
+                return Err(TLSError::General("client certificate has been revoked".into()));
+            }
+        }
+
+        Ok(verified)
+    }
+}
+
+pub struct TlsListener<L: Listener = TcpListener> {
+    listener: L,
+    handle: TlsHandle,
+    state: TlsListenerState<L::Connection>,
+}
+
+/// A handle to replace the certificate chain, private key, and client
+/// authentication of a running `TlsListener`. Connections accepted before the
+/// replacement keep using the previous certificates.
+#[derive(Clone)]
+pub struct TlsHandle {
+    config: Arc<ArcSwap<ServerConfig>>,
+    alpn: Arc<Vec<Vec<u8>>>,
 }
 
-enum TlsListenerState {
+impl TlsHandle {
+    /// Prepares the configuration for serving `cert_chain` with `key` and
+    /// `client_auth`, to be installed via [`TlsHandle::replace()`]. Fails if
+    /// `key` can't be used with `cert_chain`.
+    pub fn prepare(
+        &self,
+        cert_chain: Vec<Certificate>,
+        key: PrivateKey,
+        client_auth: Option<ClientAuth>,
+    ) -> Result<Arc<ServerConfig>, Error> {
+        server_config_for(cert_chain, key, client_auth, &self.alpn)
+    }
+
+    /// Serves new connections with `config`, prepared via
+    /// [`TlsHandle::prepare()`].
+    pub fn replace(&self, config: Arc<ServerConfig>) {
+        self.config.store(config);
+    }
+
+    /// Prepares and serves the configuration for `cert_chain`, `key`, and
+    /// `client_auth`.
+    pub fn reload(
+        &self,
+        cert_chain: Vec<Certificate>,
+        key: PrivateKey,
+        client_auth: Option<ClientAuth>,
+    ) -> Result<(), Error> {
+        self.replace(self.prepare(cert_chain, key, client_auth)?);
+        Ok(())
+    }
+
+    fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.config.load_full())
+    }
+}
+
+enum TlsListenerState<C> {
     Listening,
-    Accepting(Pin<Box<dyn Future<Output=Result<TlsStream<TcpStream>, io::Error>> + Send>>),
+    Accepting(Pin<Box<dyn Future<Output=Result<TlsStream<C>, io::Error>> + Send>>),
 }
 
-impl Listener for TlsListener {
-    type Connection = TlsStream<TcpStream>;
+impl<L: Listener> Listener for TlsListener<L>
+    where L::Connection: Unpin + Send + 'static
+{
+    type Connection = TlsStream<L::Connection>;
 
     fn local_addr(&self) -> Option<SocketAddr> {
-        self.listener.local_addr().ok()
+        self.listener.local_addr()
     }
 
     fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<Result<Self::Connection, io::Error>> {
@@ -91,8 +378,9 @@ impl Listener for TlsListener {
                 TlsListenerState::Listening => {
                     match self.listener.poll_accept(cx) {
                         Poll::Pending => return Poll::Pending,
-                        Poll::Ready(Ok((stream, _addr))) => {
-                            self.state = TlsListenerState::Accepting(Box::pin(self.acceptor.accept(stream)));
+                        Poll::Ready(Ok(stream)) => {
+                            let accept = self.handle.acceptor().accept(stream);
+                            self.state = TlsListenerState::Accepting(Box::pin(accept));
                         }
                         Poll::Ready(Err(e)) => {
                             return Poll::Ready(Err(e));
@@ -104,7 +392,7 @@ impl Listener for TlsListener {
                         Poll::Pending => return Poll::Pending,
                         Poll::Ready(result) => {
                             self.state = TlsListenerState::Listening;
-                            return Poll::Ready(result);
+                            return Poll::Ready(result.map_err(HandshakeError::wrap));
                         }
                     }
                 }
@@ -116,25 +404,136 @@ impl Listener for TlsListener {
 pub async fn bind_tls(
     address: SocketAddr,
     cert_chain: Vec<Certificate>,
-    key: PrivateKey
+    key: PrivateKey,
+    client_auth: Option<ClientAuth>,
+    alpn: Vec<Vec<u8>>,
 ) -> io::Result<TlsListener> {
     let listener = TcpListener::bind(address).await?;
+    Ok(tls_listener(listener, cert_chain, key, client_auth, alpn))
+}
+
+/// Serves TLS on the already-bound `listener`, authenticating clients by
+/// certificate if `client_auth` is `Some` and offering the ALPN protocol IDs
+/// `alpn` in order of preference.
+pub fn tls_listener(
+    listener: TcpListener,
+    cert_chain: Vec<Certificate>,
+    key: PrivateKey,
+    client_auth: Option<ClientAuth>,
+    alpn: Vec<Vec<u8>>,
+) -> TlsListener {
+    let config = server_config_for(cert_chain, key, client_auth, &alpn).expect("invalid key");
+    let handle = TlsHandle { config: Arc::new(ArcSwap::new(config)), alpn: Arc::new(alpn) };
+    let state = TlsListenerState::Listening;
 
-    let client_auth = rustls::NoClientAuth::new();
+    TlsListener { listener, handle, state }
+}
+
+/// Serves TLS on the already-bound `listener` with the certificates chosen by
+/// `resolver` for each connection, offering the ALPN protocol IDs `alpn` in
+/// order of preference. Reloading via the listener's handle replaces the
+/// resolver with the reloaded certificates.
+pub fn resolver_listener(
+    listener: TcpListener,
+    resolver: Arc<dyn ResolvesServerCert>,
+    alpn: Vec<Vec<u8>>,
+) -> TlsListener {
+    let mut tls_config = server_config(rustls::NoClientAuth::new(), &alpn);
+    tls_config.cert_resolver = resolver;
+
+    let config = Arc::new(ArcSwap::from_pointee(tls_config));
+    let handle = TlsHandle { config, alpn: Arc::new(alpn) };
+    let state = TlsListenerState::Listening;
+
+    TlsListener { listener, handle, state }
+}
+
+impl<L: Listener> TlsListener<L> {
+    /// Returns a handle to replace the certificates of this listener.
+    pub fn handle(&self) -> TlsHandle {
+        self.handle.clone()
+    }
+
+    /// Serves TLS on the connections of `f(listener)` instead, such as to
+    /// read a PROXY protocol header before the TLS handshake. Must be called
+    /// before any connection is accepted.
+    pub fn map<M: Listener, F: FnOnce(L) -> M>(self, f: F) -> TlsListener<M> {
+        let state = TlsListenerState::Listening;
+        TlsListener { listener: f(self.listener), handle: self.handle, state }
+    }
+}
+
+fn server_config_for(
+    cert_chain: Vec<Certificate>,
+    key: PrivateKey,
+    client_auth: Option<ClientAuth>,
+    alpn: &[Vec<u8>],
+) -> Result<Arc<ServerConfig>, Error> {
+    let client_auth = match client_auth {
+        Some(auth) => ClientAuthVerifier::new(auth),
+        None => rustls::NoClientAuth::new(),
+    };
+
+    let mut tls_config = server_config(client_auth, alpn);
+    tls_config.set_single_cert(cert_chain, key).map_err(|_| Error::BadKey)?;
+    Ok(Arc::new(tls_config))
+}
+
+fn server_config(client_auth: Arc<dyn ClientCertVerifier>, alpn: &[Vec<u8>]) -> ServerConfig {
     let mut tls_config = ServerConfig::new(client_auth);
     let cache = rustls::ServerSessionMemoryCache::new(1024);
     tls_config.set_persistence(cache);
     tls_config.ticketer = rustls::Ticketer::new();
-    tls_config.set_single_cert(cert_chain, key).expect("invalid key");
+    tls_config.set_protocols(alpn);
+    tls_config
+}
 
-    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
-    let state = TlsListenerState::Listening;
+/// Connects to the TLS server `host` over `stream`, verifying its certificate
+/// against Mozilla's root certificates.
+#[cfg(feature = "tls-acme")]
+pub async fn connect_tls(
+    host: &str,
+    stream: tokio::net::TcpStream
+) -> io::Result<tokio_rustls::client::TlsStream<tokio::net::TcpStream>> {
+    let name = webpki::DNSNameRef::try_from_ascii_str(host)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid DNS name"))?;
 
-    Ok(TlsListener { listener, acceptor, state })
+    let mut config = rustls::ClientConfig::new();
+    config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+    tokio_rustls::TlsConnector::from(Arc::new(config)).connect(name, stream).await
 }
 
-impl Connection for TlsStream<TcpStream> {
+impl<C: Connection + Unpin> Connection for TlsStream<C> {
     fn remote_addr(&self) -> Option<SocketAddr> {
         self.get_ref().0.remote_addr()
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Connection::local_addr(self.get_ref().0)
+    }
+
+    fn proxy_header(&self) -> Option<ProxyHeader> {
+        self.get_ref().0.proxy_header()
+    }
+
+    fn tls_info(&self) -> Option<TlsInfo> {
+        let session = self.get_ref().1;
+        let version = session.get_protocol_version().map(|version| match version {
+            rustls::ProtocolVersion::TLSv1_2 => "TLSv1.2".into(),
+            rustls::ProtocolVersion::TLSv1_3 => "TLSv1.3".into(),
+            other => format!("{:?}", other),
+        });
+
+        Some(TlsInfo {
+            version,
+            cipher: session.get_negotiated_ciphersuite().map(|s| format!("{:?}", s.suite)),
+            alpn: session.get_alpn_protocol().map(|p| String::from_utf8_lossy(p).into_owned()),
+            sni: session.get_sni_hostname().map(String::from),
+        })
+    }
+
+    fn peer_certificates(&self) -> Option<Vec<Vec<u8>>> {
+        let certs = self.get_ref().1.get_peer_certificates()?;
+        Some(certs.into_iter().map(|cert| cert.0).collect())
+    }
 }
@@ -0,0 +1,284 @@
+use std::io;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+use crate::http::hyper::{self, header};
+use crate::http::tls::connect_tls;
+
+use super::{other, Acme};
+use super::jws::{base64url, AccountKey};
+
+/// How often, and how many times, a pending order or authorization is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_ATTEMPTS: usize = 30;
+
+/// The problem type of a request with an expired nonce, which is retried.
+const BAD_NONCE: &str = "urn:ietf:params:acme:error:badNonce";
+
+/// A response from an ACME server.
+struct Response {
+    status: u16,
+    location: Option<String>,
+    nonce: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(&self) -> io::Result<Value> {
+        serde_json::from_slice(&self.body).map_err(other)
+    }
+
+    /// The problem document in the body of an error response.
+    fn problem(&self) -> Option<Value> {
+        if self.status < 400 {
+            return None;
+        }
+
+        Some(self.json().unwrap_or(Value::Null))
+    }
+
+    /// Returns `self` if it isn't an error response.
+    fn checked(self) -> io::Result<Response> {
+        match self.problem() {
+            Some(problem) => {
+                let detail = problem["detail"].as_str().unwrap_or("no details");
+                Err(other(format!("ACME server responded {}: {}", self.status, detail)))
+            }
+            None => Ok(self)
+        }
+    }
+}
+
+/// Sends a `method` request to `url` with the JWS `body`, if any.
+async fn request(method: hyper::Method, url: &str, body: Option<Value>) -> io::Result<Response> {
+    let uri: hyper::Uri = url.parse().map_err(other)?;
+    let host = uri.host().ok_or_else(|| other(format!("`{}` has no host", url)))?.to_string();
+    let https = uri.scheme_str() != Some("http");
+    let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+    let authority = uri.authority().map_or(host.as_str(), |a| a.as_str());
+    let path = uri.path_and_query().map_or("/", |p| p.as_str());
+
+    let mut builder = hyper::Request::builder()
+        .method(method)
+        .uri(path)
+        .header(header::HOST, authority)
+        .header(header::USER_AGENT, concat!("Rocket/", env!("CARGO_PKG_VERSION")));
+
+    if body.is_some() {
+        builder = builder.header(header::CONTENT_TYPE, "application/jose+json");
+    }
+
+    let body = body.map_or_else(hyper::Body::empty, |b| b.to_string().into());
+    let request = builder.body(body).map_err(other)?;
+    let stream = TcpStream::connect((host.as_str(), port)).await?;
+    match https {
+        true => send(connect_tls(&host, stream).await?, request).await,
+        false => send(stream, request).await,
+    }
+}
+
+async fn send<S>(stream: S, request: hyper::Request<hyper::Body>) -> io::Result<Response>
+    where S: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    let (mut sender, connection) = hyper::client_conn::handshake(stream).await.map_err(other)?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            debug_!("ACME connection error: {}", e);
+        }
+    });
+
+    let response = sender.send_request(request).await.map_err(other)?;
+    let (status, location, nonce) = {
+        let get = |name: &str| response.headers().get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        (response.status().as_u16(), get("location"), get("replay-nonce"))
+    };
+
+    let body = hyper::to_bytes(response.into_body()).await.map_err(other)?;
+    Ok(Response { status, location, nonce, body: body.to_vec() })
+}
+
+/// The string values of the JSON array `value`.
+fn strings(value: &Value) -> Vec<String> {
+    value.as_array()
+        .map(|array| array.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// An RFC 8555 client for the account with the key `key`.
+pub(super) struct Client {
+    key: AccountKey,
+    new_nonce: String,
+    new_account: String,
+    new_order: String,
+    /// The nonce for the next request, if the last response included one.
+    nonce: Option<String>,
+    /// The account URL, once registered.
+    kid: Option<String>,
+}
+
+impl Client {
+    /// Reads the ACME directory at `directory`.
+    pub(super) async fn new(directory: &str, key: AccountKey) -> io::Result<Client> {
+        let directory = request(hyper::Method::GET, directory, None).await?.checked()?.json()?;
+        let url = |name: &str| directory[name].as_str()
+            .map(String::from)
+            .ok_or_else(|| other(format!("ACME directory has no `{}`", name)));
+
+        Ok(Client {
+            key,
+            new_nonce: url("newNonce")?,
+            new_account: url("newAccount")?,
+            new_order: url("newOrder")?,
+            nonce: None,
+            kid: None,
+        })
+    }
+
+    /// Sends `payload`, or, if it is `None`, a POST-as-GET request, to `url`.
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> io::Result<Response> {
+        let payload = payload.map_or(String::new(), |p| p.to_string());
+        let mut retries = 2;
+        loop {
+            let nonce = match self.nonce.take() {
+                Some(nonce) => nonce,
+                None => request(hyper::Method::HEAD, &self.new_nonce, None).await?
+                    .nonce
+                    .ok_or_else(|| other("ACME server sent no nonce"))?,
+            };
+
+            let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+            match self.kid {
+                Some(ref kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = self.key.jwk(),
+            }
+
+            let body = self.key.sign(&protected, &payload)?;
+            let response = request(hyper::Method::POST, url, Some(body)).await?;
+            self.nonce = response.nonce.clone();
+
+            let bad_nonce = response.problem().map_or(false, |p| p["type"] == BAD_NONCE);
+            if bad_nonce && retries > 0 {
+                retries -= 1;
+                continue;
+            }
+
+            return response.checked();
+        }
+    }
+
+    /// POST-as-GETs `url` until its status is no longer one of `pending`.
+    async fn poll(&mut self, url: &str, pending: &[&str]) -> io::Result<Value> {
+        for _ in 0..POLL_ATTEMPTS {
+            let value = self.post(url, None).await?.json()?;
+            match value["status"].as_str() {
+                Some(status) if pending.contains(&status) => {
+                    tokio::time::delay_for(POLL_INTERVAL).await;
+                }
+                _ => return Ok(value),
+            }
+        }
+
+        Err(other(format!("timed out waiting for `{}`", url)))
+    }
+
+    /// Registers the account, agreeing to the terms of service, or looks up
+    /// the account if it already exists.
+    pub(super) async fn register(&mut self, contact: &[String]) -> io::Result<()> {
+        let payload = json!({ "termsOfServiceAgreed": true, "contact": contact });
+        let url = self.new_account.clone();
+        let response = self.post(&url, Some(&payload)).await?;
+        self.kid = Some(response.location.ok_or_else(|| other("ACME account has no URL"))?);
+        Ok(())
+    }
+
+    /// Orders a certificate for the domains configured in `acme`, answering
+    /// its challenges via `acme`. Returns the PEM encoded certificate chain and
+    /// the PKCS#8 DER encoded private key.
+    pub(super) async fn order(&mut self, acme: &Acme) -> io::Result<(String, Vec<u8>)> {
+        let domains = &acme.config.domains;
+        let identifiers: Vec<_> = domains.iter()
+            .map(|domain| json!({ "type": "dns", "value": domain }))
+            .collect();
+
+        let url = self.new_order.clone();
+        let response = self.post(&url, Some(&json!({ "identifiers": identifiers }))).await?;
+        let order_url = response.location.clone()
+            .ok_or_else(|| other("ACME order has no URL"))?;
+        let order = response.json()?;
+        for authorization in strings(&order["authorizations"]) {
+            self.authorize(acme, &authorization).await?;
+        }
+
+        // Request the certificate for a new key. The CSR may only name the
+        // ordered domains.
+        let mut params = rcgen::CertificateParams::new(domains.clone());
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        let cert = rcgen::Certificate::from_params(params).map_err(other)?;
+        let csr = cert.serialize_request_der().map_err(other)?;
+        let finalize = order["finalize"].as_str()
+            .ok_or_else(|| other("ACME order has no `finalize`"))?;
+        self.post(finalize, Some(&json!({ "csr": base64url(csr) }))).await?;
+
+        let order = self.poll(&order_url, &["pending", "ready", "processing"]).await?;
+        let chain_url = match (order["status"].as_str(), order["certificate"].as_str()) {
+            (Some("valid"), Some(url)) => url.to_string(),
+            _ => return Err(other(format!("ACME order failed: {}", order["error"]))),
+        };
+
+        let chain = self.post(&chain_url, None).await?;
+        let chain = String::from_utf8(chain.body).map_err(other)?;
+        Ok((chain, cert.serialize_private_key_der()))
+    }
+
+    /// Completes the authorization at `url` via the challenge configured in
+    /// `acme`, unless it is already valid.
+    async fn authorize(&mut self, acme: &Acme, url: &str) -> io::Result<()> {
+        let authorization = self.post(url, None).await?.json()?;
+        if authorization["status"] == "valid" {
+            return Ok(());
+        }
+
+        let kind = acme.config.challenge.as_str();
+        let domain = authorization["identifier"]["value"].as_str().unwrap_or_default();
+        let domain = domain.to_string();
+        let challenge = authorization["challenges"].as_array()
+            .and_then(|challenges| challenges.iter().find(|c| c["type"] == kind))
+            .ok_or_else(|| other(format!("no {} challenge is offered for {}", kind, domain)))?;
+
+        let (token, challenge_url) = (challenge["token"].as_str(), challenge["url"].as_str());
+        let (token, challenge_url) = match (token, challenge_url) {
+            (Some(token), Some(challenge_url)) => (token.to_string(), challenge_url.to_string()),
+            _ => return Err(other(format!("malformed {} challenge for {}", kind, domain))),
+        };
+
+        let key_authorization = format!("{}.{}", token, self.key.thumbprint());
+        acme.present(&domain, &token, &key_authorization)?;
+        let result = self.validate(&challenge_url, url).await;
+        acme.clear(&domain, &token);
+
+        let authorization = result?;
+        match authorization["status"].as_str() {
+            Some("valid") => Ok(()),
+            _ => {
+                let detail = authorization["challenges"].as_array()
+                    .and_then(|cs| cs.iter().find_map(|c| c["error"]["detail"].as_str()))
+                    .unwrap_or("no details");
+
+                Err(other(format!("validation of {} failed: {}", domain, detail)))
+            }
+        }
+    }
+
+    /// Asks the server to verify the challenge at `challenge_url`, returning
+    /// the authorization at `url` once verified.
+    async fn validate(&mut self, challenge_url: &str, url: &str) -> io::Result<Value> {
+        self.post(challenge_url, Some(&json!({}))).await?;
+        self.poll(url, &["pending", "processing"]).await
+    }
+}
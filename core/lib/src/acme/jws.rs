@@ -0,0 +1,76 @@
+use std::io;
+
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+
+use super::other;
+
+/// Encodes `data` as unpadded URL-safe base64, as JOSE requires.
+pub(super) fn base64url<T: AsRef<[u8]>>(data: T) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+/// The P-256 key that identifies and authenticates an ACME account.
+pub(super) struct AccountKey {
+    pair: EcdsaKeyPair,
+    pkcs8: Vec<u8>,
+}
+
+impl AccountKey {
+    /// Generates a new key.
+    pub(super) fn generate() -> io::Result<AccountKey> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map_err(|_| other("failed to generate an account key"))?;
+
+        AccountKey::from_pkcs8(pkcs8.as_ref().to_vec())
+    }
+
+    /// Reads the PKCS#8 DER encoded key `pkcs8`.
+    pub(super) fn from_pkcs8(pkcs8: Vec<u8>) -> io::Result<AccountKey> {
+        let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &pkcs8)
+            .map_err(|_| other("malformed account key"))?;
+
+        Ok(AccountKey { pair, pkcs8 })
+    }
+
+    /// The key, PKCS#8 DER encoded.
+    pub(super) fn pkcs8(&self) -> &[u8] {
+        &self.pkcs8
+    }
+
+    /// The public key as a JSON Web Key.
+    pub(super) fn jwk(&self) -> Value {
+        let (x, y) = self.coordinates();
+        json!({ "crv": "P-256", "kty": "EC", "x": x, "y": y })
+    }
+
+    /// The RFC 7638 thumbprint of the public key.
+    pub(super) fn thumbprint(&self) -> String {
+        // The members must be in lexicographic order, without whitespace.
+        let (x, y) = self.coordinates();
+        let jwk = format!(r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#, x, y);
+        base64url(digest(&SHA256, jwk.as_bytes()))
+    }
+
+    /// Signs `payload` with the `protected` header, returning the flattened
+    /// JWS serialization.
+    pub(super) fn sign(&self, protected: &Value, payload: &str) -> io::Result<Value> {
+        let protected = base64url(protected.to_string());
+        let payload = base64url(payload);
+        let message = format!("{}.{}", protected, payload);
+        let signature = self.pair.sign(&SystemRandom::new(), message.as_bytes())
+            .map_err(|_| other("failed to sign an ACME request"))?;
+
+        Ok(json!({ "protected": protected, "payload": payload, "signature": base64url(signature) }))
+    }
+
+    /// The base64url encoded `x` and `y` coordinates of the public key.
+    fn coordinates(&self) -> (String, String) {
+        // An uncompressed point: `0x04`, then `x` and `y`, 32 bytes each.
+        let point = self.pair.public_key().as_ref();
+        (base64url(&point[1..33]), base64url(&point[33..65]))
+    }
+}
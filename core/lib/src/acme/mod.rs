@@ -0,0 +1,327 @@
+//! Automatic certificates via ACME (RFC 8555). See
+//! [`AcmeConfig`](crate::config::AcmeConfig) for the user-facing details.
+
+mod jws;
+mod client;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ring::digest::{digest, SHA256};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use tokio::net::TcpListener;
+
+use crate::{Data, Request, Route};
+use crate::config::{AcmeChallenge, AcmeConfig, Config, Protocol};
+use crate::handler::{Handler, Outcome};
+use crate::http::Method;
+use crate::http::tls::{self, sign::CertifiedKey, Certificate, PrivateKey, ClientHello};
+use crate::http::tls::{ResolvesServerCert, TlsListener};
+
+use self::client::Client;
+use self::jws::AccountKey;
+
+/// The ALPN protocol ID of `tls-alpn-01` challenge handshakes.
+const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+
+/// How long before it expires a certificate is renewed.
+const RENEW_BEFORE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How often certificates are checked for renewal, and how long to wait
+/// before retrying after a failure.
+const CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn other<E: fmt::Display>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error.to_string())
+}
+
+/// Obtains, renews, and serves the certificates of the default listener.
+pub(crate) struct Acme {
+    config: AcmeConfig,
+    /// The root-relative path of the cache directory.
+    cache: PathBuf,
+    resolver: Arc<Resolver>,
+    /// The key authorizations of pending `http-01` challenges, by token.
+    tokens: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl Acme {
+    /// Returns the ACME state of the application configured by `config`, if
+    /// ACME is configured.
+    pub(crate) fn new(config: &Config) -> Option<Arc<Acme>> {
+        let acme = config.acme.clone()?;
+        let cache = config.root_relative(&acme.cache);
+        let (resolver, tokens) = (Arc::default(), Arc::default());
+        Some(Arc::new(Acme { config: acme, cache, resolver, tokens }))
+    }
+
+    /// The route that answers `http-01` challenges, to be mounted at
+    /// `/.well-known/acme-challenge`, if that is the configured challenge.
+    pub(crate) fn routes(&self) -> Vec<Route> {
+        match self.config.challenge {
+            AcmeChallenge::Http01 => {
+                let handler = Http01 { tokens: self.tokens.clone() };
+                vec![Route::ranked(Http01::RANK, Method::Get, "/<token>", handler)]
+            }
+            AcmeChallenge::TlsAlpn01 => vec![],
+        }
+    }
+
+    /// Serves TLS on `listener` with the obtained certificates, offering
+    /// `protocols` and answering `tls-alpn-01` challenges.
+    pub(crate) fn tls_listener(
+        &self,
+        listener: TcpListener,
+        protocols: &[Protocol]
+    ) -> TlsListener {
+        let mut alpn = Protocol::alpn(protocols);
+        alpn.push(ACME_TLS_ALPN.to_vec());
+        tls::resolver_listener(listener, self.resolver.clone(), alpn)
+    }
+
+    /// Serves the cached certificate, if any, then obtains a certificate
+    /// whenever the served one is missing or about to expire. Never returns.
+    pub(crate) async fn run(self: Arc<Acme>) {
+        let mut expires = match self.load_cached() {
+            Ok(expires) => expires,
+            Err(e) => {
+                warn_!("Failed to load the cached ACME certificate: {}", e);
+                None
+            }
+        };
+
+        loop {
+            let due = expires.map_or(true, |expires| SystemTime::now() + RENEW_BEFORE >= expires);
+            let delay = if !due {
+                CHECK_INTERVAL
+            } else {
+                match self.obtain().await {
+                    Ok(new_expiry) => {
+                        let domains = self.config.domains.join(", ");
+                        info_!("Obtained a certificate for {} via ACME.", domains);
+                        expires = Some(new_expiry);
+                        CHECK_INTERVAL
+                    }
+                    Err(e) => {
+                        error_!("Failed to obtain a certificate via ACME: {}", e);
+                        RETRY_INTERVAL
+                    }
+                }
+            };
+
+            tokio::time::delay_for(delay).await;
+        }
+    }
+
+    /// The path of the cache file `{prefix}-{id}.{ext}`, where `id` identifies
+    /// the directory and, for certificates and keys, the domains.
+    fn cache_path(&self, prefix: &str, ext: &str) -> PathBuf {
+        let mut id = self.config.directory.clone();
+        if prefix != "account" {
+            id.push('\n');
+            id.push_str(&self.config.domains.join(","));
+        }
+
+        let hash = digest(&SHA256, id.as_bytes());
+        let hex: String = hash.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        self.cache.join(format!("{}-{}.{}", prefix, hex, ext))
+    }
+
+    /// Serves the cached certificate, if there is one, returning when it
+    /// expires.
+    fn load_cached(&self) -> io::Result<Option<SystemTime>> {
+        let chain = match fs::read(self.cache_path("cert", "pem")) {
+            Ok(chain) => chain,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let key = fs::read(self.cache_path("key", "der"))?;
+        self.serve(&chain, key).map(Some)
+    }
+
+    /// Serves the PEM encoded certificate chain `chain` with the PKCS#8 DER
+    /// encoded private key `key`, returning when the certificate expires.
+    /// Fails if `key` isn't the private key of the chain's certificate.
+    fn serve(&self, chain: &[u8], key: Vec<u8>) -> io::Result<SystemTime> {
+        let certs = tls::pemfile::certs(&mut &chain[..])
+            .map_err(|_| other("malformed certificate chain"))?;
+
+        let cert = certs.first().ok_or_else(|| other("empty certificate chain"))?;
+        let not_after = tls::not_after(cert).ok_or_else(|| other("malformed certificate"))?;
+        let public_key = tls::public_key(cert).ok_or_else(|| other("malformed certificate"))?;
+        let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &key)
+            .map_err(|_| other("malformed private key"))?;
+
+        if pair.public_key().as_ref() != public_key.as_slice() {
+            return Err(other("the private key does not match the certificate"));
+        }
+
+        let key = tls::sign::any_ecdsa_type(&PrivateKey(key))
+            .map_err(|_| other("malformed private key"))?;
+
+        let certified = CertifiedKey::new(certs, Arc::new(key));
+        *self.resolver.certified.write().unwrap_or_else(|e| e.into_inner()) = Some(certified);
+        Ok(UNIX_EPOCH + Duration::from_secs(not_after.max(0) as u64))
+    }
+
+    /// Obtains a new certificate, serves it, and caches it, returning when it
+    /// expires.
+    async fn obtain(&self) -> io::Result<SystemTime> {
+        // TODO.async: consider using async fs operations
+        fs::create_dir_all(&self.cache)?;
+        let account_path = self.cache_path("account", "der");
+        let key = match fs::read(&account_path) {
+            Ok(pkcs8) => AccountKey::from_pkcs8(pkcs8)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let key = AccountKey::generate()?;
+                write_atomically(&[(account_path.as_path(), key.pkcs8())])?;
+                key
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut client = Client::new(&self.config.directory, key).await?;
+        client.register(&self.config.contact).await?;
+        let (chain, key) = client.order(self).await?;
+        let expires = self.serve(chain.as_bytes(), key.clone())?;
+
+        let (cert_path, key_path) = (self.cache_path("cert", "pem"), self.cache_path("key", "der"));
+        let files: [(&Path, &[u8]); 2] = [(&key_path, &key), (&cert_path, chain.as_bytes())];
+        write_atomically(&files)?;
+        Ok(expires)
+    }
+
+    /// Answers the challenge for `domain` with the token `token`.
+    fn present(&self, domain: &str, token: &str, key_authorization: &str) -> io::Result<()> {
+        match self.config.challenge {
+            AcmeChallenge::Http01 => {
+                let mut tokens = self.tokens.write().unwrap_or_else(|e| e.into_inner());
+                tokens.insert(token.into(), key_authorization.into());
+            }
+            AcmeChallenge::TlsAlpn01 => {
+                // A self-signed certificate for `domain` that carries the
+                // digest of the key authorization.
+                let hash = digest(&SHA256, key_authorization.as_bytes());
+                let extension = rcgen::CustomExtension::new_acme_identifier(hash.as_ref());
+                let mut params = rcgen::CertificateParams::new(vec![domain.to_string()]);
+                params.custom_extensions = vec![extension];
+
+                let cert = rcgen::Certificate::from_params(params).map_err(other)?;
+                let der = cert.serialize_der().map_err(other)?;
+                let key = PrivateKey(cert.serialize_private_key_der());
+                let key = tls::sign::any_ecdsa_type(&key)
+                    .map_err(|_| other("unusable challenge key"))?;
+
+                let certified = CertifiedKey::new(vec![Certificate(der)], Arc::new(key));
+                let mut challenges = self.resolver.challenges.write()
+                    .unwrap_or_else(|e| e.into_inner());
+
+                challenges.insert(domain.into(), certified);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops answering the challenge for `domain` with the token `token`.
+    fn clear(&self, domain: &str, token: &str) {
+        match self.config.challenge {
+            AcmeChallenge::Http01 => {
+                self.tokens.write().unwrap_or_else(|e| e.into_inner()).remove(token);
+            }
+            AcmeChallenge::TlsAlpn01 => {
+                let challenges = &self.resolver.challenges;
+                challenges.write().unwrap_or_else(|e| e.into_inner()).remove(domain);
+            }
+        }
+    }
+}
+
+/// Writes each file's data to its path, readable only by the owner on Unix.
+/// Every file is written to a temporary file first, and only once all have
+/// been written are they renamed into place, so that an interrupted write
+/// never leaves a truncated file behind. A crash between renames can leave a
+/// certificate and key that don't match, which `serve()` detects.
+fn write_atomically(files: &[(&Path, &[u8])]) -> io::Result<()> {
+    use std::io::Write;
+
+    let temporary = |path: &Path| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        path.with_file_name(name)
+    };
+
+    for (path, data) in files {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)] {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(temporary(path))?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    for (path, _) in files {
+        fs::rename(temporary(path), path)?;
+    }
+
+    Ok(())
+}
+
+/// Chooses the certificate of each TLS handshake: the challenge certificate
+/// for `tls-alpn-01` handshakes, the obtained certificate otherwise.
+#[derive(Default)]
+struct Resolver {
+    certified: RwLock<Option<CertifiedKey>>,
+    /// The certificates of pending `tls-alpn-01` challenges, by domain.
+    challenges: RwLock<HashMap<String, CertifiedKey>>,
+}
+
+impl ResolvesServerCert for Resolver {
+    fn resolve(&self, hello: ClientHello<'_>) -> Option<CertifiedKey> {
+        let alpn = hello.alpn().unwrap_or_default();
+        let is_challenge = alpn.len() == 1 && alpn[0] == ACME_TLS_ALPN;
+        if is_challenge {
+            let domain: &str = hello.server_name()?.into();
+            let challenges = self.challenges.read().unwrap_or_else(|e| e.into_inner());
+            return challenges.get(domain).cloned();
+        }
+
+        self.certified.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Responds to `http-01` challenges with the key authorization of the token.
+#[derive(Clone)]
+struct Http01 {
+    tokens: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl Http01 {
+    /// Before any application route.
+    const RANK: isize = isize::MIN;
+}
+
+#[crate::async_trait]
+impl Handler for Http01 {
+    async fn handle<'r, 's: 'r>(&'s self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        let key_authorization = req.get_param::<String>(0)
+            .and_then(|token| token.ok())
+            .and_then(|token| {
+                let tokens = self.tokens.read().unwrap_or_else(|e| e.into_inner());
+                tokens.get(&token).cloned()
+            });
+
+        Outcome::from_or_forward(req, data, key_authorization)
+    }
+}
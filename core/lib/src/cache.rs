@@ -0,0 +1,603 @@
+//! Caching of route responses.
+//!
+//! Routes opt in to caching with the [`cache`](macro@crate::cache) attribute,
+//! applied below the route attribute, or with [`Route::cache()`]. Their
+//! responses are cached once a [`Cache`] fairing is attached:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::cache::Cache;
+//!
+//! #[get("/items")]
+//! #[cache(ttl = "60s", vary = "Accept", tags = "items")]
+//! fn items() -> &'static str {
+//!     "[]"
+//! }
+//!
+//! #[launch]
+//! fn rocket() -> rocket::Rocket {
+//!     rocket::ignite()
+//!         .mount("/", routes![items])
+//!         .attach(Cache::memory())
+//! }
+//! ```
+//!
+//! # Semantics
+//!
+//! Only `GET` requests, including `HEAD` requests handled by a `GET` route,
+//! are cached. A response is stored under the request's `Host` header and URI,
+//! once for every combination of values of the request headers the route
+//! varies on, and is served until its time to live elapses. Cached responses are only served
+//! once the route's request guards succeed, so guards such as authentication
+//! guards apply to cached responses as well.
+//!
+//! Requests carrying credentials, in an `Authorization` or `Cookie` header,
+//! are neither served from nor stored in the cache, unless the route varies on
+//! that header, in which case every caller gets their own response.
+//!
+//! A response is stored only if:
+//!
+//!   * its status is a success (`2xx`) status,
+//!   * its body is sized, i.e, not streamed,
+//!   * it sets no cookies, and
+//!   * its `Cache-Control` header contains neither `no-store`, `no-cache`,
+//!     nor `private`.
+//!
+//! A `max-age` in the response's `Cache-Control` header shortens the time to
+//! live when it is lower. A request whose `Cache-Control` header contains
+//! `no-cache` or `max-age=0` is always handled by the route, and one that
+//! contains `no-store` additionally isn't stored. Cached responses are served
+//! with an `Age` header.
+//!
+//! # Invalidation
+//!
+//! [`Cache`] is also a request guard that invalidates cached responses by
+//! [key](Cache::invalidate()), the request's host and URI, or by tag:
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::cache::Cache;
+//!
+//! #[post("/items")]
+//! async fn add_item(cache: Cache) {
+//!     /* .. */
+//!     cache.invalidate_tag("items").await;
+//! }
+//! ```
+//!
+//! # Stores
+//!
+//! Responses are kept in a [`CacheStore`]. [`Cache::memory()`] keeps them in
+//! a bounded [`MemoryStore`] in process memory, while [`Cache::new()`] uses any
+//! other store, such as one shared between instances.
+//!
+//! [`Route::cache()`]: crate::Route::cache()
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::{Request, Response, Rocket};
+use crate::codegen::StaticCacheInfo;
+use crate::data::Bytes;
+use crate::fairing::{Fairing, Info, Kind};
+use crate::http::{Header, Method, Status};
+use crate::request::{FromRequest, Outcome};
+
+/// The policy for caching a route's responses.
+///
+/// See [`Route::cache()`](crate::Route::cache()).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachePolicy {
+    ttl: Duration,
+    vary: Vec<String>,
+    tags: Vec<String>,
+}
+
+impl CachePolicy {
+    /// Returns a policy that caches responses for `ttl`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new(Duration::from_secs(60));
+    /// assert_eq!(policy.ttl(), Duration::from_secs(60));
+    /// ```
+    pub fn new(ttl: Duration) -> CachePolicy {
+        CachePolicy { ttl, vary: vec![], tags: vec![] }
+    }
+
+    /// Caches a response separately for every value of the request header
+    /// `header`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new(Duration::from_secs(60))
+    ///     .vary("Accept")
+    ///     .vary("Accept-Language");
+    ///
+    /// assert_eq!(policy.varies_on().collect::<Vec<_>>(), ["Accept", "Accept-Language"]);
+    /// ```
+    pub fn vary<S: Into<String>>(mut self, header: S) -> Self {
+        self.vary.push(header.into());
+        self
+    }
+
+    /// Stores responses with `tag`, so that they can be invalidated with
+    /// [`Cache::invalidate_tag()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rocket::cache::CachePolicy;
+    ///
+    /// let policy = CachePolicy::new(Duration::from_secs(60)).tag("items");
+    /// assert_eq!(policy.tags().collect::<Vec<_>>(), ["items"]);
+    /// ```
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Returns the time to live of cached responses.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the names of the request headers responses vary on.
+    pub fn varies_on(&self) -> impl Iterator<Item = &str> {
+        self.vary.iter().map(|s| s.as_str())
+    }
+
+    /// Returns the tags responses are stored with.
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(|s| s.as_str())
+    }
+}
+
+#[doc(hidden)]
+impl From<&StaticCacheInfo> for CachePolicy {
+    fn from(info: &StaticCacheInfo) -> CachePolicy {
+        CachePolicy {
+            ttl: Duration::from_secs(info.ttl),
+            vary: info.vary.iter().map(|&s| s.into()).collect(),
+            tags: info.tags.iter().map(|&s| s.into()).collect(),
+        }
+    }
+}
+
+/// A response as kept by a [`CacheStore`].
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// The response's status.
+    pub status: Status,
+    /// The response's headers.
+    pub headers: Vec<Header<'static>>,
+    /// The response's body.
+    pub body: Bytes,
+    /// The names and values of the request headers the response varies on.
+    pub vary: Vec<(String, Option<String>)>,
+    /// The tags the response was stored with.
+    pub tags: Vec<String>,
+    /// When the response was stored.
+    pub stored: SystemTime,
+    /// How long the response is fresh for after it was stored.
+    pub ttl: Duration,
+}
+
+impl CachedResponse {
+    /// Returns the time elapsed since the response was stored.
+    pub fn age(&self) -> Duration {
+        self.stored.elapsed().unwrap_or_default()
+    }
+
+    /// Returns `true` if the response's time to live hasn't elapsed.
+    pub fn is_fresh(&self) -> bool {
+        self.age() < self.ttl
+    }
+
+    fn respond<'r>(self) -> Response<'r> {
+        let mut response = Response::new();
+        let age = self.age().as_secs();
+        response.set_status(self.status);
+        for header in self.headers {
+            response.adjoin_header(header);
+        }
+
+        response.set_header(Header::new("Age", age.to_string()));
+        response.set_sized_body(self.body.len(), Cursor::new(self.body));
+        response
+    }
+}
+
+/// Trait implemented by stores of cached responses.
+///
+/// A store keeps responses by key, the host and URI of the request they
+/// respond to.
+/// Each key may have several responses, one for every combination of values
+/// of the request headers it [varies](CachedResponse::vary) on.
+///
+/// # Example
+///
+/// A store that keeps no responses:
+///
+/// ```rust
+/// use rocket::cache::{CacheStore, CachedResponse};
+///
+/// struct NoStore;
+///
+/// #[rocket::async_trait]
+/// impl CacheStore for NoStore {
+///     async fn get(
+///         &self,
+///         key: &str,
+///         vary: &[(String, Option<String>)]
+///     ) -> Option<CachedResponse> {
+///         None
+///     }
+///
+///     async fn put(&self, key: &str, response: CachedResponse) { }
+///
+///     async fn remove(&self, key: &str) { }
+///
+///     async fn remove_tagged(&self, tag: &str) { }
+/// }
+/// ```
+#[crate::async_trait]
+pub trait CacheStore: Send + Sync + 'static {
+    /// Returns the [fresh](CachedResponse::is_fresh()) response stored for
+    /// `key` whose varying request header values are `vary`, if any.
+    async fn get(&self, key: &str, vary: &[(String, Option<String>)]) -> Option<CachedResponse>;
+
+    /// Stores `response` for `key`, replacing the response, if any, stored
+    /// for `key` with the same varying request header values.
+    async fn put(&self, key: &str, response: CachedResponse);
+
+    /// Removes every response stored for `key`.
+    async fn remove(&self, key: &str);
+
+    /// Removes every response stored with `tag`.
+    async fn remove_tagged(&self, tag: &str);
+}
+
+/// A [`CacheStore`] that keeps responses in process memory.
+///
+/// The store holds at most [`MemoryStore::DEFAULT_CAPACITY`] responses unless
+/// created with [`MemoryStore::with_capacity()`]. When full, the least
+/// recently used response is evicted. Responses whose time to live has
+/// elapsed are evicted when they are next looked up or when a response is
+/// stored for the same key.
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    lru: Mutex<Lru>,
+}
+
+/// The responses of a [`MemoryStore`], ordered by last use.
+#[derive(Debug, Default)]
+struct Lru {
+    /// The responses stored for each key, with the tick of their last use.
+    entries: HashMap<String, Vec<(u64, CachedResponse)>>,
+    /// The key of every response, by the tick of its last use.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Removes the responses of `key` for which `remove` returns `true`.
+    fn remove_where<F>(&mut self, key: &str, mut remove: F)
+        where F: FnMut(&CachedResponse) -> bool
+    {
+        let order = &mut self.order;
+        if let Some(responses) = self.entries.get_mut(key) {
+            responses.retain(|(tick, response)| {
+                let keep = !remove(response);
+                if !keep {
+                    order.remove(tick);
+                }
+
+                keep
+            });
+
+            if responses.is_empty() {
+                self.entries.remove(key);
+            }
+        }
+    }
+
+    fn get(&mut self, key: &str, vary: &[(String, Option<String>)]) -> Option<CachedResponse> {
+        let tick = self.next_tick();
+        let responses = self.entries.get_mut(key)?;
+        let entry = responses.iter_mut().find(|(_, response)| response.vary == vary)?;
+        if !entry.1.is_fresh() {
+            self.remove_where(key, |response| response.vary == vary);
+            return None;
+        }
+
+        let last_used = std::mem::replace(&mut entry.0, tick);
+        let response = entry.1.clone();
+        self.order.remove(&last_used);
+        self.order.insert(tick, key.into());
+        Some(response)
+    }
+
+    fn put(&mut self, key: &str, response: CachedResponse, capacity: usize) {
+        self.remove_where(key, |r| r.vary == response.vary || !r.is_fresh());
+        if capacity == 0 {
+            return;
+        }
+
+        while self.order.len() >= capacity {
+            let (&oldest, _) = self.order.iter().next().expect("non-empty order");
+            let oldest_key = self.order.remove(&oldest).expect("oldest key");
+            let responses = self.entries.get_mut(&oldest_key).expect("oldest responses");
+            responses.retain(|(tick, _)| *tick != oldest);
+            if responses.is_empty() {
+                self.entries.remove(&oldest_key);
+            }
+        }
+
+        let tick = self.next_tick();
+        self.entries.entry(key.into()).or_default().push((tick, response));
+        self.order.insert(tick, key.into());
+    }
+}
+
+impl MemoryStore {
+    /// The number of responses a store holds unless configured otherwise.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Returns a new, empty store holding at most
+    /// [`MemoryStore::DEFAULT_CAPACITY`] responses.
+    pub fn new() -> MemoryStore {
+        MemoryStore::with_capacity(MemoryStore::DEFAULT_CAPACITY)
+    }
+
+    /// Returns a new, empty store holding at most `capacity` responses.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::cache::{Cache, MemoryStore};
+    ///
+    /// rocket::ignite().attach(Cache::new(MemoryStore::with_capacity(64)))
+    /// # ;
+    /// ```
+    pub fn with_capacity(capacity: usize) -> MemoryStore {
+        MemoryStore { capacity, lru: Mutex::new(Lru::default()) }
+    }
+
+    /// Returns the number of responses in the store, including any whose
+    /// time to live has elapsed but that haven't been evicted yet.
+    pub fn len(&self) -> usize {
+        self.lru().order.len()
+    }
+
+    /// Returns `true` if the store holds no responses.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lru(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.lru.lock().expect("cache store lock")
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+#[crate::async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &str, vary: &[(String, Option<String>)]) -> Option<CachedResponse> {
+        self.lru().get(key, vary)
+    }
+
+    async fn put(&self, key: &str, response: CachedResponse) {
+        self.lru().put(key, response, self.capacity);
+    }
+
+    async fn remove(&self, key: &str) {
+        self.lru().remove_where(key, |_| true);
+    }
+
+    async fn remove_tagged(&self, tag: &str) {
+        let mut lru = self.lru();
+        let keys: Vec<String> = lru.entries.keys().cloned().collect();
+        for key in keys {
+            lru.remove_where(&key, |r| r.tags.iter().any(|t| t == tag));
+        }
+    }
+}
+
+/// A fairing that enables caching of route responses, and a request guard
+/// that invalidates them.
+///
+/// See the [module level documentation](crate::cache) for details.
+#[derive(Clone)]
+pub struct Cache {
+    store: Arc<dyn CacheStore>,
+}
+
+impl Cache {
+    /// Returns a `Cache` that keeps responses in `store`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::cache::{Cache, MemoryStore};
+    ///
+    /// rocket::ignite().attach(Cache::new(MemoryStore::new()))
+    /// # ;
+    /// ```
+    pub fn new<S: CacheStore>(store: S) -> Cache {
+        Cache { store: Arc::new(store) }
+    }
+
+    /// Returns a `Cache` that keeps responses in a [`MemoryStore`].
+    pub fn memory() -> Cache {
+        Cache::new(MemoryStore::new())
+    }
+
+    /// Removes every response cached for `key`: the lowercased `Host` header
+    /// of a request followed by its URI, such as `example.com/items?page=2`,
+    /// or the URI alone, such as `/items?page=2`, for requests without a
+    /// `Host` header.
+    pub async fn invalidate(&self, key: &str) {
+        self.store.remove(key).await;
+    }
+
+    /// Removes every cached response stored with `tag`.
+    pub async fn invalidate_tag(&self, tag: &str) {
+        self.store.remove_tagged(tag).await;
+    }
+
+    /// Returns the response cached for `req` under `policy`, if any.
+    pub(crate) async fn lookup<'r>(
+        &self,
+        req: &Request<'_>,
+        policy: &CachePolicy
+    ) -> Option<Response<'r>> {
+        if req.method() != Method::Get
+            || carries_credentials(req, policy)
+            || directives(req.headers().get("Cache-Control"))
+                .any(|d| d == "no-cache" || d == "no-store" || d == "max-age=0")
+        {
+            return None;
+        }
+
+        let response = self.store.get(&key(req), &vary(req, policy)).await?;
+        Some(response.respond())
+    }
+
+    /// Stores `response` to `req` under `policy` if it's cacheable, returning
+    /// it with its body intact.
+    pub(crate) async fn store<'r>(
+        &self,
+        req: &Request<'_>,
+        policy: &CachePolicy,
+        mut response: Response<'r>
+    ) -> Response<'r> {
+        if req.method() != Method::Get
+            || carries_credentials(req, policy)
+            || !response.status().class().is_success()
+            || response.body().map_or(false, |body| body.is_chunked())
+            || response.headers().contains("Set-Cookie")
+            || directives(req.headers().get("Cache-Control")).any(|d| d == "no-store")
+            || directives(response.headers().get("Cache-Control"))
+                .any(|d| d == "no-store" || d == "no-cache" || d == "private")
+        {
+            return response;
+        }
+
+        let max_age = directives(response.headers().get("Cache-Control"))
+            .filter_map(|d| d.strip_prefix("max-age=").and_then(|secs| secs.parse().ok()))
+            .map(Duration::from_secs)
+            .min();
+
+        let ttl = max_age.map_or(policy.ttl, |max_age| std::cmp::min(max_age, policy.ttl));
+        if ttl == Duration::from_secs(0) {
+            return response;
+        }
+
+        if !policy.vary.is_empty() && !response.headers().contains("Vary") {
+            response.set_raw_header("Vary", policy.vary.join(", "));
+        }
+
+        let body = match response.body_bytes().await {
+            Some(bytes) => Bytes::from(bytes),
+            None if response.body().is_none() => Bytes::new(),
+            None => return response,
+        };
+
+        response.set_sized_body(body.len(), Cursor::new(body.clone()));
+        let cached = CachedResponse {
+            status: response.status(),
+            headers: response.headers().iter()
+                .map(|h| Header::new(h.name().to_string(), h.value().to_string()))
+                .collect(),
+            body,
+            vary: vary(req, policy),
+            tags: policy.tags.clone(),
+            stored: SystemTime::now(),
+            ttl,
+        };
+
+        self.store.put(&key(req), cached).await;
+        response
+    }
+}
+
+/// Returns the key of `req`: its lowercased `Host` header, if any, followed by
+/// its URI. Responses to the same path on different hosts may differ.
+fn key(req: &Request<'_>) -> String {
+    match req.headers().get_one("Host") {
+        Some(host) => format!("{}{}", host.to_ascii_lowercase(), req.uri()),
+        None => req.uri().to_string(),
+    }
+}
+
+/// Returns the lowercased directives in the `Cache-Control` header `values`.
+fn directives<'a>(values: impl Iterator<Item = &'a str>) -> impl Iterator<Item = String> {
+    values.flat_map(|value| value.split(','))
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .filter(|directive| !directive.is_empty())
+}
+
+/// Returns `true` if `req` carries credentials, an `Authorization` or `Cookie`
+/// header, that `policy` doesn't vary on. Responses to such requests may be
+/// specific to the caller.
+fn carries_credentials(req: &Request<'_>, policy: &CachePolicy) -> bool {
+    ["Authorization", "Cookie"].iter()
+        .filter(|name| req.headers().contains(name))
+        .any(|name| !policy.vary.iter().any(|v| v.eq_ignore_ascii_case(name)))
+}
+
+/// Returns the values of the request headers `policy` varies on.
+fn vary(req: &Request<'_>, policy: &CachePolicy) -> Vec<(String, Option<String>)> {
+    policy.vary.iter()
+        .map(|name| (name.clone(), req.headers().get_one(name).map(String::from)))
+        .collect()
+}
+
+#[crate::async_trait]
+impl Fairing for Cache {
+    fn info(&self) -> Info {
+        Info { name: "Response Cache", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        Ok(rocket.manage(self.clone()))
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Cache {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        match req.state.managed.try_get::<Cache>() {
+            Some(cache) => Outcome::Success(cache.clone()),
+            None => {
+                error_!("Attempted to retrieve the response cache without a `Cache` fairing.");
+                Outcome::Failure((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
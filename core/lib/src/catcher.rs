@@ -2,10 +2,16 @@
 //! values.
 
 use std::fmt;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::backtrace::Backtrace;
 
-use crate::response::Response;
+use crate::http::Status;
+use crate::response::{Response, Responder, content, status};
 use crate::codegen::StaticCatcherInfo;
 use crate::request::Request;
+use crate::http::uri::{self, Origin};
 
 use futures::future::BoxFuture;
 use yansi::Paint;
@@ -38,7 +44,54 @@ pub type ErrorHandlerFuture<'r> = BoxFuture<'r, Result<'r>>;
 ///
 /// If no catcher for a given status code exists, the _default_ catcher is
 /// called. A _default_ catcher is a `Catcher` with a `code` of `None`. There is
-/// at-most one default catcher.
+/// at-most one default catcher per scope.
+///
+/// ## Scoping
+///
+/// Every catcher has a _base_ path, `/` by default. A catcher only handles
+/// errors for requests whose path begins with the catcher's base, where
+/// prefixes are compared segment by segment: a base of `/api` applies to
+/// `/api` and `/api/users` but not to `/apis`. Catchers are registered under a
+/// base with [`Rocket::register_at()`](crate::Rocket::register_at()).
+///
+/// Catchers can also be registered on an individual [`Route`] via
+/// [`Route::register()`](crate::Route::register()). Such catchers only handle
+/// errors that arise while that route is processing a request: from its
+/// guards failing or its responder failing.
+///
+/// When an error arises, Rocket selects the catcher from the closest scope:
+///
+///   1. Catchers registered on the route that produced the error, if any.
+///   2. Catchers whose base is a prefix of the request path, longest first.
+///
+/// Within a scope, a catcher for the error's status code is preferred over a
+/// default catcher. If a catcher declines to handle an error, as a [typed
+/// catcher](#typed-errors) does when the error it expects is missing, the next
+/// catcher in this order is tried. If no catcher applies, Rocket's built-in
+/// default catcher is used.
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// # use rocket::Request;
+/// #[catch(404)]
+/// fn html_not_found() -> rocket::response::content::Html<&'static str> {
+///     rocket::response::content::Html("<h1>Not Found</h1>")
+/// }
+///
+/// #[catch(default)]
+/// fn json_error(status: rocket::http::Status) -> rocket::response::content::Json<String> {
+///     rocket::response::content::Json(format!("{{\"status\":{}}}", status.code))
+/// }
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     rocket::ignite()
+///         .register(catchers![html_not_found])
+///         .register_at("/api", catchers![json_error])
+/// }
+/// ```
+///
+/// [`Route`]: crate::Route
 ///
 /// ## Error Handler Restrictions
 ///
@@ -49,8 +102,10 @@ pub type ErrorHandlerFuture<'r> = BoxFuture<'r, Result<'r>>;
 /// # Built-In Default Catcher
 ///
 /// Rocket's built-in default catcher can handle all errors. It produces HTML or
-/// JSON, depending on the value of the `Accept` header. As such, catchers only
-/// need to be registered if an error needs to be handled in a custom fashion.
+/// JSON, depending on the value of the `Accept` and `X-Requested-With` headers.
+/// As such, catchers only need to be registered if an error needs to be
+/// handled in a custom fashion. To change the pages it produces without
+/// writing a catcher, register a [`DefaultCatcher`].
 ///
 /// # Code Generation
 ///
@@ -91,8 +146,60 @@ pub type ErrorHandlerFuture<'r> = BoxFuture<'r, Result<'r>>;
 ///   * `fn(`[`&Request`]`) -> R`
 ///   * `fn(`[`Status`]`, `[`&Request`]`) -> R`
 ///
+/// Additionally, a catcher may take the error produced by a failing guard as
+/// described in [Typed Errors](#typed-errors).
+///
 /// See the [`catch`] documentation for full details.
 ///
+/// # Typed Errors
+///
+/// When a request or data guard fails, the error it returns is recorded on the
+/// request if its type implements [`TypedError`]. A catcher can receive that
+/// error by naming an argument in the `error` parameter of the `catch`
+/// attribute. The argument must be of type `&E` or `Option<&E>`, where `E` is
+/// the guard's error type:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Request;
+/// use rocket::catcher::TypedError;
+/// use rocket::request::{self, FromRequest};
+/// use rocket::http::Status;
+///
+/// #[derive(Debug)]
+/// struct ApiKeyError { reason: &'static str }
+///
+/// impl TypedError for ApiKeyError { }
+///
+/// struct ApiKey(String);
+///
+/// #[rocket::async_trait]
+/// impl<'a, 'r> FromRequest<'a, 'r> for ApiKey {
+///     type Error = ApiKeyError;
+///
+///     async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+///         match req.headers().get_one("x-api-key") {
+///             Some(key) => request::Outcome::Success(ApiKey(key.to_string())),
+///             None => {
+///                 let error = ApiKeyError { reason: "missing `x-api-key` header" };
+///                 request::Outcome::Failure((Status::Unauthorized, error))
+///             }
+///         }
+///     }
+/// }
+///
+/// #[catch(401, error = "<e>")]
+/// fn unauthorized(e: &ApiKeyError) -> String {
+///     format!("Unauthorized: {}.", e.reason)
+/// }
+/// ```
+///
+/// A catcher taking `&E` only handles errors that carry an `E`. If the request
+/// failed for any other reason, the catcher _declines_ to handle the error, as
+/// if by calling [`decline()`], and the next catcher that applies is invoked
+/// instead. A catcher taking `Option<&E>` handles every error, receiving
+/// `None` when no `E` is available.
+///
 /// [`catch`]: rocket_codegen::catch
 /// [`Responder`]: crate::response::Responder
 /// [`&Request`]: crate::request::Request
@@ -102,6 +209,10 @@ pub struct Catcher {
     /// The HTTP status code to match against if this route is not `default`.
     pub code: Option<u16>,
 
+    /// The base path of this catcher: the catcher only handles errors for
+    /// requests whose path is prefixed by `base`.
+    pub base: Origin<'static>,
+
     /// The catcher's associated error handler.
     pub handler: Box<dyn ErrorHandler>,
 }
@@ -141,7 +252,56 @@ impl Catcher {
     pub fn new<C, H>(code: C, handler: H) -> Catcher
         where C: Into<Option<u16>>, H: ErrorHandler
     {
-        Catcher { code: code.into(), handler: Box::new(handler) }
+        Catcher { code: code.into(), base: Origin::dummy(), handler: Box::new(handler) }
+    }
+
+    /// Maps the `base` of this catcher using `mapper`, returning a new
+    /// `Catcher` with the returned base.
+    ///
+    /// `mapper` is called with the current base. The returned `String` is used
+    /// as the new base if it is a valid URI. If the returned base URI contains
+    /// a query, it is ignored. Returns an error if the base produced by
+    /// `mapper` is not a valid origin URI.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Catcher;
+    /// # use rocket::Request;
+    /// # use rocket::http::Status;
+    /// # use rocket::catcher::ErrorHandlerFuture;
+    /// # fn handler<'r>(s: Status, _: &'r Request<'_>) -> ErrorHandlerFuture<'r> {
+    /// #     Box::pin(async move { Err(s) })
+    /// # }
+    ///
+    /// let catcher = Catcher::new(404, handler);
+    /// assert_eq!(catcher.base.path(), "/");
+    ///
+    /// let catcher = catcher.map_base(|base| format!("{}{}", "/api", base)).unwrap();
+    /// assert_eq!(catcher.base.path(), "/api");
+    /// ```
+    pub fn map_base<F>(mut self, mapper: F) -> Result<Self, uri::Error<'static>>
+        where F: FnOnce(Origin<'static>) -> String
+    {
+        self.base = Origin::parse_owned(mapper(self.base))?.into_normalized();
+        self.base.clear_query();
+        Ok(self)
+    }
+
+    /// Returns the number of segments in the base of `self` if it is a prefix
+    /// of the path of `req`, and `None` otherwise.
+    pub(crate) fn base_depth(&self, req: &Request<'_>) -> Option<usize> {
+        let mut depth = 0;
+        let mut path = req.uri().segments();
+        for segment in self.base.segments() {
+            if path.next() != Some(segment) {
+                return None;
+            }
+
+            depth += 1;
+        }
+
+        Some(depth)
     }
 }
 
@@ -151,7 +311,7 @@ impl Default for Catcher {
             Box::pin(async move { default(status, request) })
         }
 
-        Catcher { code: None, handler: Box::new(async_default) }
+        Catcher { code: None, base: Origin::dummy(), handler: Box::new(async_default) }
     }
 }
 
@@ -258,6 +418,233 @@ impl<F: Clone + Sync + Send + 'static> ErrorHandler for F
     }
 }
 
+/// Declines to handle the error with status `status` of the request `req`, so
+/// that the next catcher that applies to it, if any, is tried instead.
+///
+/// A catcher that fails any other way, such as because its responder failed,
+/// hasn't declined: the error it fails with is then handled by the catchers
+/// for its status.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::Request;
+/// use rocket::catcher::{self, ErrorHandlerFuture};
+/// use rocket::http::Status;
+///
+/// fn api_only<'r>(status: Status, req: &'r Request<'_>) -> ErrorHandlerFuture<'r> {
+///     Box::pin(async move {
+///         if !req.uri().path().starts_with("/api") {
+///             return catcher::decline(req, status);
+///         }
+///
+///         Ok(rocket::response::Response::build().status(status).finalize())
+///     })
+/// }
+/// ```
+pub fn decline<'r>(req: &Request<'_>, status: Status) -> Result<'r> {
+    req.state.declined.store(true, std::sync::atomic::Ordering::Release);
+    Err(status)
+}
+
+/// Marker trait for guard error types that can be received by catchers.
+///
+/// When a request or data guard fails with an error whose type implements
+/// `TypedError`, the error is recorded on the request. It can then be
+/// retrieved with [`Request::error()`] or, more conveniently, received directly
+/// by a [typed catcher](Catcher#typed-errors). Errors of all other types are
+/// discarded once they have been logged. Handlers record errors the same way,
+/// via [`Request::set_error()`], before failing.
+///
+/// Because errors must outlive the guard that produced them, `TypedError` can
+/// only be implemented for owned, `'static` types.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::catcher::TypedError;
+///
+/// #[derive(Debug)]
+/// enum ValidationError {
+///     TooShort(usize),
+///     InvalidCharacter(char),
+/// }
+///
+/// impl TypedError for ValidationError { }
+/// ```
+pub trait TypedError: Send + Sync + 'static { }
+
+impl TypedError for String { }
+impl TypedError for &'static str { }
+impl TypedError for std::io::Error { }
+impl TypedError for std::num::ParseIntError { }
+impl TypedError for std::num::ParseFloatError { }
+impl TypedError for std::str::ParseBoolError { }
+impl TypedError for std::str::Utf8Error { }
+impl TypedError for std::string::FromUtf8Error { }
+
+/// The error recorded when a route handler panics.
+///
+/// When a handler, or one of its guards or responders, panics, Rocket catches
+/// the panic, records a `Panic` as the request's error, and invokes the `500`
+/// catcher. The worker and connection remain usable. A catcher can receive the
+/// `Panic` as a [typed error](Catcher#typed-errors):
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::catcher::Panic;
+///
+/// #[catch(500, error = "<panic>")]
+/// fn internal_error(panic: Option<&Panic>) -> String {
+///     match panic {
+///         Some(panic) if cfg!(debug_assertions) => format!("Handler panicked: {}", panic),
+///         _ => "Internal server error.".into()
+///     }
+/// }
+/// ```
+///
+/// The panic's message and location are always logged. A backtrace of the
+/// panic is captured, and available via [`Panic::backtrace()`], when enabled
+/// by the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables, as
+/// described in [`Backtrace`]. Because the message and backtrace may contain
+/// sensitive information, they should generally only be exposed to clients
+/// when debugging, as above.
+#[derive(Debug, Clone)]
+pub struct Panic {
+    message: String,
+    location: Option<String>,
+    backtrace: Arc<Backtrace>,
+}
+
+thread_local! {
+    /// The location and backtrace of the last panic on this thread, recorded
+    /// by the hook installed via `Panic::install_hook()`.
+    static LAST_PANIC: RefCell<Option<(Option<String>, Backtrace)>> = Default::default();
+}
+
+impl Panic {
+    /// Returns the panic's message, if the payload was a string, or
+    /// `"<unknown>"` otherwise.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the source location of the panic as `file:line:column`, if it
+    /// is known.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// Returns the backtrace captured when the handler panicked. Its
+    /// [`status()`](Backtrace::status()) is `Disabled` unless backtraces were
+    /// enabled via the environment.
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+
+    /// Installs, exactly once, a panic hook that records the location and
+    /// backtrace of panics before delegating to the previously installed hook.
+    pub(crate) fn install_hook() {
+        static INSTALL: std::sync::Once = std::sync::Once::new();
+        INSTALL.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                let location = info.location().map(|l| l.to_string());
+                let last = (location, Backtrace::capture());
+                let _ = LAST_PANIC.try_with(|p| *p.borrow_mut() = Some(last));
+                previous(info);
+            }));
+        });
+    }
+
+    /// Creates a `Panic` from the payload of a panic caught on this thread.
+    pub(crate) fn from_payload(payload: Box<dyn std::any::Any + Send>) -> Panic {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "<unknown>".into(),
+            }
+        };
+
+        let (location, backtrace) = LAST_PANIC.try_with(|p| p.borrow_mut().take())
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| (None, Backtrace::disabled()));
+
+        Panic { message, location, backtrace: Arc::new(backtrace) }
+    }
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some(ref location) => write!(f, "'{}' at {}", self.message, location),
+            None => write!(f, "'{}'", self.message),
+        }
+    }
+}
+
+impl TypedError for Panic { }
+
+/// Wrapper used by generated guard code to record a guard's error on the
+/// request if, and only if, its type implements `TypedError`. Resolution
+/// between [`CaptureTyped`] and [`CaptureUntyped`] relies on auto-deref: the
+/// former is preferred when it applies to `&&Capture<E>`.
+#[doc(hidden)]
+pub struct Capture<E>(std::cell::Cell<Option<E>>);
+
+impl<E> Capture<E> {
+    #[inline(always)]
+    pub fn new(error: E) -> Self {
+        Capture(std::cell::Cell::new(Some(error)))
+    }
+}
+
+#[doc(hidden)]
+pub trait CaptureTyped {
+    fn capture(&self, req: &Request<'_>);
+}
+
+impl<E: TypedError> CaptureTyped for &Capture<E> {
+    #[inline(always)]
+    fn capture(&self, req: &Request<'_>) {
+        if let Some(error) = self.0.take() {
+            req.set_error(error);
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait CaptureUntyped {
+    fn capture(&self, req: &Request<'_>);
+}
+
+impl<E> CaptureUntyped for Capture<E> {
+    #[inline(always)]
+    fn capture(&self, _: &Request<'_>) { }
+}
+
+/// Trait used by generated catcher code to retrieve a typed error argument.
+#[doc(hidden)]
+pub trait FromTypedError<'r>: Sized {
+    fn from_request(req: &'r Request<'_>) -> Option<Self>;
+}
+
+impl<'r, E: TypedError> FromTypedError<'r> for &'r E {
+    #[inline(always)]
+    fn from_request(req: &'r Request<'_>) -> Option<Self> {
+        req.error::<E>()
+    }
+}
+
+impl<'r, E: TypedError> FromTypedError<'r> for Option<&'r E> {
+    #[inline(always)]
+    fn from_request(req: &'r Request<'_>) -> Option<Self> {
+        Some(req.error::<E>())
+    }
+}
+
 #[doc(hidden)]
 impl<'a> From<&'a StaticCatcherInfo> for Catcher {
     fn from(info: &'a StaticCatcherInfo) -> Catcher {
@@ -267,6 +654,10 @@ impl<'a> From<&'a StaticCatcherInfo> for Catcher {
 
 impl fmt::Display for Catcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.base.path() != "/" {
+            write!(f, "{} ", Paint::blue(&self.base).underline())?;
+        }
+
         match self.code {
             Some(code) => write!(f, "{}", Paint::blue(code)),
             None => write!(f, "{}", Paint::blue("default"))
@@ -278,23 +669,24 @@ impl fmt::Debug for Catcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Catcher")
             .field("code", &self.code)
+            .field("base", &self.base)
             .finish()
     }
 }
 
-macro_rules! html_error_template {
-    ($code:expr, $reason:expr, $description:expr) => (
-        concat!(r#"
+/// The built-in HTML error page. See [`DefaultCatcher::html()`].
+const DEFAULT_HTML_TEMPLATE: &str = r#"
             <!DOCTYPE html>
             <html lang="en">
             <head>
                 <meta charset="utf-8">
-                <title>"#, $code, " ", $reason, r#"</title>
+                <title>{code} {reason}</title>
             </head>
             <body align="center">
                 <div role="main" align="center">
-                    <h1>"#, $code, ": ", $reason, r#"</h1>
-                    <p>"#, $description, r#"</p>
+                    <h1>{code}: {reason}</h1>
+                    <p>{description}</p>
+                    {near_misses}
                     <hr />
                 </div>
                 <div role="contentinfo" align="center">
@@ -302,69 +694,225 @@ macro_rules! html_error_template {
                 </div>
             </body>
             </html>
-        "#
-        )
-    )
+        "#;
+
+/// A configurable version of Rocket's built-in default catcher.
+///
+/// Rocket's built-in default catcher is used whenever no registered catcher
+/// handles an error. It negotiates the format of its response with the client:
+/// a JSON document is emitted if the client prefers JSON according to its
+/// `Accept` header or if the request was made via `XMLHttpRequest`, as
+/// indicated by an `X-Requested-With: XMLHttpRequest` header, and does not
+/// explicitly prefer HTML. Otherwise, an HTML page is emitted.
+///
+/// A `DefaultCatcher` emits the same responses but allows the HTML page and
+/// JSON document to be replaced. Templates are plain strings in which the
+/// following placeholders are replaced:
+///
+///   * `{code}` - the status code, e.g, `404`
+///   * `{reason}` - the standard reason phrase, e.g, `Not Found`
+///   * `{description}` - a sentence describing the error
+///   * `{near_misses}` - in HTML responses to a routing failure in the
+///     development environment, a list of the routes that nearly matched the
+///     request and why they didn't; empty otherwise
+///
+/// Register a `DefaultCatcher` as a default catcher to use it in place of the
+/// built-in one. Like any other catcher, it can be scoped to a base path.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// use rocket::catcher::DefaultCatcher;
+///
+/// #[launch]
+/// fn rocket() -> rocket::Rocket {
+///     let pages = DefaultCatcher::new()
+///         .html("<h1>Oops! {code}</h1><p>{description}</p>")
+///         .json(r#"{ "status": {code}, "message": "{description}" }"#);
+///
+///     rocket::ignite().register(vec![pages.into()])
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultCatcher {
+    html: Cow<'static, str>,
+    /// The JSON template, or `None` for the built-in JSON document.
+    json: Option<Cow<'static, str>>,
 }
 
-macro_rules! json_error_template {
-    ($code:expr, $reason:expr, $description:expr) => (
-        concat!(
-r#"{
-  "error": {
-    "code": "#, $code, r#",
-    "reason": ""#, $reason, r#"",
-    "description": ""#, $description, r#""
-  }
-}"#
-        )
-    )
+impl DefaultCatcher {
+    /// Returns a `DefaultCatcher` that emits the same responses as Rocket's
+    /// built-in default catcher.
+    pub fn new() -> DefaultCatcher {
+        DefaultCatcher {
+            html: DEFAULT_HTML_TEMPLATE.into(),
+            json: None,
+        }
+    }
+
+    /// Sets the template for HTML responses to `template`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::catcher::DefaultCatcher;
+    ///
+    /// let catcher = DefaultCatcher::new().html("<h1>{code} {reason}</h1>");
+    /// ```
+    pub fn html<T: Into<Cow<'static, str>>>(mut self, template: T) -> Self {
+        self.html = template.into();
+        self
+    }
+
+    /// Sets the template for JSON responses to `template`. The values
+    /// substituted for `{reason}` and `{description}` are escaped for use
+    /// within JSON strings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::catcher::DefaultCatcher;
+    ///
+    /// let catcher = DefaultCatcher::new().json(r#"{ "error": {code} }"#);
+    /// ```
+    pub fn json<T: Into<Cow<'static, str>>>(mut self, template: T) -> Self {
+        self.json = Some(template.into());
+        self
+    }
+
+    /// Returns `true` if the error response for `req` should be JSON.
+    fn prefers_json(req: &Request<'_>) -> bool {
+        match req.accept().map(|a| a.preferred().media_type()) {
+            Some(media) if media.is_json() => true,
+            Some(media) if media.is_html() => false,
+            _ => req.headers().get_one("X-Requested-With")
+                .map_or(false, |v| v.eq_ignore_ascii_case("XMLHttpRequest"))
+        }
+    }
+
+    /// Substitutes the placeholders in `template` for the error `status`.
+    /// `near_misses` is the already rendered value for `{near_misses}`.
+    fn render(template: &str, status: Status, near_misses: &str) -> String {
+        let (reason, description) = DefaultCatcher::describe(status);
+        template.replace("{code}", &status.code.to_string())
+            .replace("{reason}", reason)
+            .replace("{description}", description)
+            .replace("{near_misses}", near_misses)
+    }
+
+    /// Renders the JSON document for the error `status`: the built-in one,
+    /// built with `serde_json`, or the configured template with its values
+    /// escaped as the contents of JSON strings.
+    fn render_json(&self, status: Status) -> String {
+        let (reason, description) = DefaultCatcher::describe(status);
+        let template = match &self.json {
+            Some(template) => template,
+            None => {
+                let document = serde_json::json!({
+                    "error": { "code": status.code, "reason": reason, "description": description }
+                });
+
+                return serde_json::to_string_pretty(&document).expect("JSON values serialize");
+            }
+        };
+
+        let escape = |string: &str| {
+            let quoted = serde_json::Value::from(string).to_string();
+            quoted[1..quoted.len() - 1].to_string()
+        };
+
+        template.replace("{code}", &status.code.to_string())
+            .replace("{reason}", &escape(reason))
+            .replace("{description}", &escape(description))
+            .replace("{near_misses}", "")
+    }
+
+    /// Returns the reason phrase and description of the error `status`.
+    fn describe(status: Status) -> (&'static str, &'static str) {
+        describe(status.code).unwrap_or(("Unknown Error", "An unknown error has occurred."))
+    }
+
+    /// Renders the near misses recorded on `req` as an HTML list.
+    fn near_misses_html(req: &Request<'_>) -> String {
+        let near_misses = req.near_misses();
+        if near_misses.is_empty() {
+            return String::new();
+        }
+
+        let mut html = String::from("<p>Routes that nearly matched:</p><ul>");
+        for near_miss in near_misses {
+            html.push_str("<li><code>");
+            for c in near_miss.chars() {
+                match c {
+                    '<' => html.push_str("&lt;"),
+                    '>' => html.push_str("&gt;"),
+                    '&' => html.push_str("&amp;"),
+                    '"' => html.push_str("&quot;"),
+                    '\'' => html.push_str("&#x27;"),
+                    c => html.push(c),
+                }
+            }
+
+            html.push_str("</code></li>");
+        }
+
+        html.push_str("</ul>");
+        html
+    }
+
+    /// Responds to the error `status` for `req`.
+    fn respond<'r>(&self, status: Status, req: &'r Request<'_>) -> Result<'r> {
+        if DefaultCatcher::prefers_json(req) {
+            let json = self.render_json(status);
+            status::Custom(status, content::Json(json)).respond_to(req)
+        } else {
+            let near_misses = DefaultCatcher::near_misses_html(req);
+            let html = DefaultCatcher::render(&self.html, status, &near_misses);
+            status::Custom(status, content::Html(html)).respond_to(req)
+        }
+    }
 }
 
-// This is unfortunate, but the `{`, `}` above make it unusable for `format!`.
-macro_rules! json_error_fmt_template {
-    ($code:expr, $reason:expr, $description:expr) => (
-        concat!(
-r#"{{
-  "error": {{
-    "code": "#, $code, r#",
-    "reason": ""#, $reason, r#"",
-    "description": ""#, $description, r#""
-  }}
-}}"#
-        )
-    )
+impl Default for DefaultCatcher {
+    fn default() -> Self {
+        DefaultCatcher::new()
+    }
+}
+
+#[crate::async_trait]
+impl ErrorHandler for DefaultCatcher {
+    async fn handle<'r, 's: 'r>(&'s self, status: Status, req: &'r Request<'_>) -> Result<'r> {
+        self.respond(status, req)
+    }
+}
+
+/// Creates a default catcher, with a `base` of `/`, from `catcher`.
+impl From<DefaultCatcher> for Catcher {
+    fn from(catcher: DefaultCatcher) -> Catcher {
+        Catcher::new(None, catcher)
+    }
+}
+
+/// Rocket's built-in default catcher.
+pub(crate) fn default<'r>(status: Status, req: &'r Request<'_>) -> Result<'r> {
+    DefaultCatcher::new().respond(status, req)
 }
 
-macro_rules! default_catcher_fn {
+macro_rules! describe_fn {
     ($($code:expr, $reason:expr, $description:expr),+) => (
-        use std::borrow::Cow;
-        use crate::http::Status;
-        use crate::response::{content, status, Responder};
-
-        pub(crate) fn default<'r>(status: Status, req: &'r Request<'_>) -> Result<'r> {
-            if req.accept().map(|a| a.preferred().is_json()).unwrap_or(false) {
-                let json: Cow<'_, str> = match status.code {
-                    $($code => json_error_template!($code, $reason, $description).into(),)*
-                    code => format!(json_error_fmt_template!("{}", "Unknown Error",
-                            "An unknown error has occurred."), code).into()
-                };
-
-                status::Custom(status, content::Json(json)).respond_to(req)
-            } else {
-                let html: Cow<'_, str> = match status.code {
-                    $($code => html_error_template!($code, $reason, $description).into(),)*
-                    code => format!(html_error_template!("{}", "Unknown Error",
-                            "An unknown error has occurred."), code, code).into(),
-                };
-
-                status::Custom(status, content::Html(html)).respond_to(req)
+        /// Returns the standard reason phrase and a description of the error
+        /// status `code`, if it is known.
+        fn describe(code: u16) -> Option<(&'static str, &'static str)> {
+            match code {
+                $($code => Some(($reason, $description)),)*
+                _ => None
             }
         }
     )
 }
 
-default_catcher_fn! {
+describe_fn! {
     400, "Bad Request", "The request could not be understood by the server due \
         to malformed syntax.",
     401, "Unauthorized", "The request requires user authentication.",
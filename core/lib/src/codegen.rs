@@ -2,6 +2,7 @@ use crate::{Request, Data};
 use crate::handler::HandlerFuture;
 use crate::catcher::ErrorHandlerFuture;
 use crate::http::{Status, Method, MediaType};
+use crate::openapi::{Operation, SchemaGenerator};
 
 /// Type of a route handler, generated from a `fn` annotated with `#[route]`.
 pub type StaticHandler = for<'r> fn(&'r Request<'_>, Data) -> HandlerFuture<'r>;
@@ -23,6 +24,24 @@ pub struct StaticRouteInfo {
     pub handler: StaticHandler,
     /// The route's rank, if any.
     pub rank: Option<isize>,
+    /// The route's data limit overrides as `(name, bytes)` pairs.
+    pub limits: &'static [(&'static str, u64)],
+    /// The route's response caching policy, if any.
+    pub cache: Option<StaticCacheInfo>,
+    /// The route's concurrency limit, if any.
+    pub max_concurrency: Option<usize>,
+    /// The function describing the route, if it's described.
+    pub openapi: Option<fn(&mut SchemaGenerator) -> Operation>,
+}
+
+/// Information generated by the `cache` attribute during codegen.
+pub struct StaticCacheInfo {
+    /// The time to live of a cached response, in seconds.
+    pub ttl: u64,
+    /// The request headers a cached response varies on.
+    pub vary: &'static [&'static str],
+    /// The tags cached responses are stored with.
+    pub tags: &'static [&'static str],
 }
 
 /// Information generated by the `catch` attribute during codegen.
@@ -0,0 +1,163 @@
+use crate::config::{ConfigError, Environment, Result, Value};
+use crate::config::toml_ext::parse_simple_toml_value;
+
+/// Configuration overrides from command-line arguments.
+///
+/// `Args` lets an application be configured from its command line, which is
+/// often more convenient than `ROCKET_{PARAM}` environment variables when
+/// deploying with containers or process managers. Arguments are applied via
+/// [`Rocket::ignite_with_args()`] and take precedence over both `Rocket.toml`
+/// and environment variables:
+///
+/// ```sh
+/// ./app --profile staging --port 8080 --limits.json 5MiB --log=critical
+/// ```
+///
+/// The following arguments are recognized:
+///
+///   * `--profile {env}` or `--env {env}` selects the active environment,
+///     overriding `ROCKET_ENV`.
+///   * `--{param} {value}` or `--{param}={value}` sets the configuration
+///     parameter `{param}`. Values are parsed exactly like those of
+///     environment variables. A parameter with no value is set to `true`.
+///   * `--{param}.{key} {value}` sets the field `{key}` of the table
+///     parameter `{param}` without replacing the rest of the table, such as
+///     `--limits.json 5MiB` or `--server.write_timeout 60`.
+///   * `--` ends the arguments for Rocket; the arguments that follow it are
+///     ignored.
+///
+/// Parameter names may use `-` in place of `_`: `--keep-alive 0` sets
+/// `keep_alive`. Any other argument is an error.
+///
+/// [`Rocket::ignite_with_args()`]: crate::Rocket::ignite_with_args()
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Args, Environment};
+///
+/// let args = Args::parse(&["--profile", "staging", "--port=8080"]).unwrap();
+/// assert_eq!(args.profile(), Some(Environment::Staging));
+///
+/// assert!(Args::parse(&["positional"]).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    profile: Option<Environment>,
+    overrides: Vec<(String, Value)>,
+}
+
+impl Args {
+    /// Returns an empty set of arguments that overrides nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Args;
+    ///
+    /// let args = Args::new();
+    /// assert_eq!(args.profile(), None);
+    /// ```
+    pub fn new() -> Args {
+        Args::default()
+    }
+
+    /// Parses the arguments of the current process, skipping the name of the
+    /// program.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `BadArg` error if any argument is not recognized or its value
+    /// cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # if false {
+    /// let args = rocket::config::Args::from_env().unwrap();
+    /// # }
+    /// ```
+    pub fn from_env() -> Result<Args> {
+        Args::parse(std::env::args().skip(1))
+    }
+
+    /// Parses `args`, which should not include the name of the program.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `BadArg` error if any argument is not recognized or its value
+    /// cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Args;
+    ///
+    /// assert!(Args::parse(&["--workers", "4", "--limits.forms=64KiB"]).is_ok());
+    /// assert!(Args::parse(&["--profile", "nowhere"]).is_err());
+    /// ```
+    pub fn parse<I, S>(args: I) -> Result<Args>
+        where I: IntoIterator<Item = S>, S: AsRef<str>
+    {
+        let mut parsed = Args::new();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            if arg == "--" {
+                break;
+            }
+
+            let bad_arg = |e: &str| ConfigError::BadArg(arg.into(), e.into());
+            let flag = match arg.strip_prefix("--") {
+                Some(flag) if !flag.is_empty() => flag,
+                _ => return Err(bad_arg("expected an argument of the form `--{param}`")),
+            };
+
+            let (name, value) = match flag.find('=') {
+                Some(i) => (&flag[..i], flag[(i + 1)..].to_string()),
+                None => match args.peek() {
+                    Some(next) if !next.as_ref().starts_with("--") => {
+                        (flag, args.next().unwrap().as_ref().to_string())
+                    }
+                    _ => (flag, "true".to_string()),
+                }
+            };
+
+            let name = name.replace('-', "_").to_lowercase();
+            if name == "profile" || name == "env" {
+                let env = value.parse().map_err(|_| bad_arg("unknown environment"))?;
+                parsed.profile = Some(env);
+                continue;
+            }
+
+            // Secret references, escaped or not, needn't be quoted.
+            let value = match value.starts_with("${") || value.starts_with("$${") {
+                true => Value::String(value),
+                false => parse_simple_toml_value(&value).map_err(|e| bad_arg(&e))?,
+            };
+
+            parsed.overrides.push((name, value));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the environment selected with `--profile` or `--env`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Args, Environment};
+    ///
+    /// let args = Args::parse(&["--env", "prod"]).unwrap();
+    /// assert_eq!(args.profile(), Some(Environment::Production));
+    /// ```
+    pub fn profile(&self) -> Option<Environment> {
+        self.profile
+    }
+
+    /// The parameters to override: dot-separated paths and their values.
+    pub(crate) fn overrides(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.overrides.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::config::{Result, Config, Value, Environment, LoggingLevel};
+use crate::config::{Result, Config, Value, Environment, LoggingLevel, ServerConfig, UnixConfig};
+use crate::config::CompressionConfig;
+use crate::config::{MutualTls, AcmeConfig, Protocol};
 use crate::data::Limits;
 
 /// Structure following the builder pattern for building `Config` structures.
@@ -15,6 +17,9 @@ pub struct ConfigBuilder {
     pub port: u16,
     /// The number of workers to run in parallel.
     pub workers: u16,
+    /// The maximum number of threads, beyond `workers`, that run blocking
+    /// tasks.
+    pub max_blocking_threads: u16,
     /// Keep-alive timeout in seconds or disabled if 0.
     pub keep_alive: u32,
     /// How much information to log.
@@ -23,8 +28,25 @@ pub struct ConfigBuilder {
     pub secret_key: Option<String>,
     /// TLS configuration (path to certificates file, path to private key file).
     pub tls: Option<(String, String)>,
+    /// Client certificate authentication for TLS.
+    pub mtls: Option<MutualTls>,
+    /// Automatic certificate configuration.
+    pub acme: Option<AcmeConfig>,
     /// Size limits.
     pub limits: Limits,
+    /// Connection timeouts and limits.
+    pub server: ServerConfig,
+    /// Unix domain socket settings.
+    pub unix: UnixConfig,
+    /// Response compression settings.
+    pub compression: CompressionConfig,
+    /// The HTTP protocols to serve, in order of preference.
+    pub protocols: Vec<Protocol>,
+    /// Whether connections to `address` and `port` start with a PROXY
+    /// protocol header.
+    pub proxy_protocol: bool,
+    /// Additional plaintext listeners (name, address, port).
+    pub listeners: Vec<(String, String, u16)>,
     /// Any extra parameters that aren't part of Rocket's config.
     pub extras: HashMap<String, Value>,
     /// The root directory of this config, if any.
@@ -57,11 +79,20 @@ impl ConfigBuilder {
             address: config.address,
             port: config.port,
             workers: config.workers,
+            max_blocking_threads: config.max_blocking_threads,
             keep_alive: config.keep_alive.unwrap_or(0),
             log_level: config.log_level,
             secret_key: None,
             tls: None,
+            mtls: None,
+            acme: None,
             limits: config.limits,
+            server: config.server,
+            unix: config.unix,
+            compression: config.compression,
+            protocols: config.protocols,
+            proxy_protocol: config.proxy_protocol,
+            listeners: vec![],
             extras: config.extras,
             root: None,
         }
@@ -123,6 +154,25 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets `max_blocking_threads` in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .max_blocking_threads(32)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.max_blocking_threads, 32);
+    /// ```
+    #[inline]
+    pub fn max_blocking_threads(mut self, max: u16) -> Self {
+        self.max_blocking_threads = max;
+        self
+    }
+
     /// Sets the keep-alive timeout to `timeout` seconds. If `timeout` is `0`,
     /// keep-alive is disabled.
     ///
@@ -202,6 +252,123 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the connection timeouts and limits in the configuration being
+    /// built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, ServerConfig};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .server(ServerConfig { read_header_timeout: Some(5), ..Default::default() })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.server.read_header_timeout, Some(5));
+    /// ```
+    pub fn server(mut self, server: ServerConfig) -> Self {
+        self.server = server;
+        self
+    }
+
+    /// Sets the Unix domain socket settings in the configuration being built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, UnixConfig};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .unix(UnixConfig { mode: Some(0o660), ..Default::default() })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.unix.mode, Some(0o660));
+    /// ```
+    pub fn unix(mut self, unix: UnixConfig) -> Self {
+        self.unix = unix;
+        self
+    }
+
+    /// Sets the response compression settings in the configuration being
+    /// built.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, CompressionConfig};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .compression(CompressionConfig { enabled: true, ..Default::default() })
+    ///     .unwrap();
+    ///
+    /// assert!(config.compression.enabled);
+    /// ```
+    pub fn compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the HTTP protocols to serve, in order of preference, in the
+    /// configuration being built. See [`Protocol`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, Protocol};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .protocols(vec![Protocol::Http1])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.protocols, [Protocol::Http1]);
+    /// ```
+    pub fn protocols(mut self, protocols: Vec<Protocol>) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Sets whether connections to `address` and `port` start with a PROXY
+    /// protocol header in the configuration being built. See
+    /// [`Config::set_proxy_protocol()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .proxy_protocol(true)
+    ///     .unwrap();
+    ///
+    /// assert!(config.proxy_protocol);
+    /// ```
+    pub fn proxy_protocol(mut self, proxy_protocol: bool) -> Self {
+        self.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// Adds a listener named `name` that serves plaintext HTTP on `address`
+    /// and `port` to the configuration being built. See
+    /// [`Config::add_listener()`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .listener("admin", "127.0.0.1", 9000)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.listeners[0].name, "admin");
+    /// ```
+    pub fn listener<N, A>(mut self, name: N, address: A, port: u16) -> Self
+        where N: Into<String>, A: Into<String>
+    {
+        self.listeners.push((name.into(), address.into(), port));
+        self
+    }
+
     /// Sets the TLS configuration in the configuration being built.
     ///
     /// Certificates are read from `certs_path`. The certificate chain must be
@@ -226,6 +393,46 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets the client certificate authentication of the TLS configuration in
+    /// the configuration being built. See [`MutualTls`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, MutualTls};
+    ///
+    /// let mut config = Config::build(Environment::Staging)
+    ///     .tls("/path/to/certs.pem", "/path/to/key.pem")
+    ///     .mtls(MutualTls::optional("/path/to/ca.pem"))
+    /// # ; /*
+    ///     .unwrap();
+    /// # */
+    /// ```
+    pub fn mtls(mut self, mtls: MutualTls) -> Self {
+        self.mtls = Some(mtls);
+        self
+    }
+
+    /// Sets the automatic certificate configuration in the configuration being
+    /// built. See [`AcmeConfig`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment, AcmeConfig};
+    ///
+    /// let acme = AcmeConfig::new(vec!["example.com"]).accept_terms(true);
+    /// let config = Config::build(Environment::Staging)
+    ///     .acme(acme)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.acme.unwrap().domains, ["example.com"]);
+    /// ```
+    pub fn acme(mut self, acme: AcmeConfig) -> Self {
+        self.acme = Some(acme);
+        self
+    }
+
     /// Sets the `environment` in the configuration being built.
     ///
     /// # Example
@@ -292,7 +499,8 @@ impl ConfigBuilder {
     ///
     /// # Errors
     ///
-    /// If the address or secret key fail to parse, returns a `BadType` error.
+    /// If the address or secret key fail to parse, or if `workers` or
+    /// `max_blocking_threads` is `0`, returns a `BadType` error.
     ///
     /// # Example
     ///
@@ -316,13 +524,31 @@ impl ConfigBuilder {
     /// ```
     pub fn finalize(self) -> Result<Config> {
         let mut config = Config::new(self.environment);
+        if self.workers == 0 {
+            return Err(config.bad_type("workers", "0", "a positive 16-bit integer"));
+        }
+
+        if self.max_blocking_threads == 0 {
+            let expected = "a positive 16-bit integer";
+            return Err(config.bad_type("max_blocking_threads", "0", expected));
+        }
+
         config.set_address(self.address)?;
         config.set_port(self.port);
         config.set_workers(self.workers);
+        config.set_max_blocking_threads(self.max_blocking_threads);
         config.set_keep_alive(self.keep_alive);
         config.set_log_level(self.log_level);
         config.set_extras(self.extras);
         config.set_limits(self.limits);
+        config.set_server(self.server);
+        config.set_unix(self.unix);
+        config.set_compression(self.compression);
+        config.set_protocols(self.protocols);
+        config.set_proxy_protocol(self.proxy_protocol);
+        for (name, address, port) in &self.listeners {
+            config.add_listener(name, address, *port)?;
+        }
 
         if let Some(root) = self.root {
             config.set_root(root);
@@ -332,6 +558,14 @@ impl ConfigBuilder {
             config.set_tls(&certs_path, &key_path)?;
         }
 
+        if let Some(mtls) = self.mtls {
+            config.set_mtls(&mtls)?;
+        }
+
+        if let Some(acme) = self.acme {
+            config.set_acme(acme)?;
+        }
+
         if let Some(key) = self.secret_key {
             config.set_secret_key(key)?;
         }
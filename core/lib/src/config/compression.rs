@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::data::{ByteUnit, ToByteUnit};
+use crate::http::MediaType;
+
+/// Settings for compressing responses: the `[compression]` table.
+///
+/// When enabled, Rocket compresses the body of a response with the encoding
+/// the client prefers among those it supports, as stated by the request's
+/// `Accept-Encoding` header: Brotli (`br`) or gzip. The body is compressed as
+/// it is written, so streamed bodies are compressed without being buffered. A
+/// compressed body's size isn't known in advance, so it is sent without a
+/// `Content-Length`, and a strong `ETag` is made weak since the compressed
+/// bytes differ from the original ones. Compression requires the
+/// `compression` feature; Brotli additionally requires the `brotli` feature,
+/// and enabling `brotli` without it is a configuration error.
+///
+/// | name     | default     | description                                          |
+/// |----------|-------------|------------------------------------------------------|
+/// | enabled  | `false`     | whether responses are compressed                     |
+/// | brotli   | _see below_ | whether Brotli is offered in addition to gzip        |
+/// | min_size | `1KiB`      | bodies of a known size smaller than this are sent as is |
+/// | exclude  | _see below_ | media types, such as `"image/*"`, sent as is         |
+///
+/// `brotli` defaults to `true` if the `brotli` feature is enabled and `false`
+/// otherwise.
+///
+/// By default, media types that are already compressed are excluded:
+/// `image/*`, `video/*`, `audio/*`, `font/woff2`, `application/gzip`,
+/// `application/zip`, `application/wasm`, and `application/octet-stream`.
+/// Setting `exclude` replaces these. Responses without a body, partial
+/// responses, responses that already have a `Content-Encoding`, and responses
+/// with `Cache-Control: no-transform` are never compressed. Compressible
+/// responses carry `Vary: Accept-Encoding` whether or not they're compressed.
+///
+/// Compressing responses to requests over TLS that mix secrets with
+/// attacker-controlled data can expose the secrets to attacks such as BREACH.
+/// Consider this before enabling compression.
+///
+/// ```toml
+/// [global.compression]
+/// enabled = true
+/// min_size = "4KiB"
+/// exclude = ["image/*", "video/*", "application/x-xz"]
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment, CompressionConfig};
+///
+/// let compression = CompressionConfig { enabled: true, ..CompressionConfig::default() };
+/// let config = Config::build(Environment::Staging)
+///     .compression(compression)
+///     .unwrap();
+///
+/// assert!(config.compression.enabled);
+/// assert_eq!(config.compression.min_size, 1024);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionConfig {
+    /// Whether responses are compressed.
+    pub enabled: bool,
+    /// Whether Brotli is offered. Requires the `brotli` feature.
+    pub brotli: bool,
+    /// The size below which bodies of a known size aren't compressed.
+    pub min_size: ByteUnit,
+    /// Media types whose bodies aren't compressed. A subtype of `*` matches
+    /// every subtype.
+    pub exclude: Vec<MediaType>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        let exclude = [
+            "image/*", "video/*", "audio/*", "font/woff2", "application/gzip",
+            "application/zip", "application/wasm", "application/octet-stream",
+        ];
+
+        CompressionConfig {
+            enabled: false,
+            brotli: cfg!(feature = "brotli"),
+            min_size: 1.kibibytes(),
+            exclude: exclude.iter().filter_map(|m| MediaType::parse_flexible(m)).collect(),
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Whether bodies of the media type `media_type` are excluded.
+    pub(crate) fn excludes(&self, media_type: &MediaType) -> bool {
+        self.exclude.iter().any(|excluded| {
+            excluded.top() == media_type.top()
+                && (excluded.sub() == "*" || excluded.sub() == media_type.sub())
+        })
+    }
+}
+
+impl fmt::Display for CompressionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.enabled {
+            return write!(f, "disabled");
+        }
+
+        let encodings = if self.brotli { "br, gzip" } else { "gzip" };
+        write!(f, "encodings = [{}], min_size = {}, exclude = [", encodings, self.min_size)?;
+        for (i, media_type) in self.exclude.iter().enumerate() {
+            if i != 0 { write!(f, ", ")?; }
+            write!(f, "{}", media_type)?;
+        }
+
+        write!(f, "]")
+    }
+}
@@ -4,13 +4,21 @@ use std::path::{Path, PathBuf};
 use std::convert::AsRef;
 use std::fmt;
 
+use serde::de::DeserializeOwned;
+
 use crate::http::private::cookie::Key;
 use crate::config::Environment::*;
 use crate::config::{Result, ConfigBuilder, Environment, ConfigError, LoggingLevel};
 use crate::config::{FullConfig, Table, Value, Array, Datetime};
+use crate::config::{ConfigSection, ValidationError, Provenance};
+use crate::config::{ServerConfig, UnixConfig, ListenerConfig};
+use crate::config::CompressionConfig;
+use crate::config::Protocol;
+use crate::config::{MutualTls, AcmeConfig};
 use crate::data::Limits;
 
 use super::custom_values::*;
+use super::toml_ext::parse_simple_toml_value;
 
 /// Structure for Rocket application configuration.
 ///
@@ -44,6 +52,9 @@ pub struct Config {
     pub port: u16,
     /// The number of workers to run concurrently.
     pub workers: u16,
+    /// The maximum number of threads, beyond `workers`, that run blocking
+    /// tasks.
+    pub max_blocking_threads: u16,
     /// Keep-alive timeout in seconds or None if disabled.
     pub keep_alive: Option<u32>,
     /// How much information to log.
@@ -52,14 +63,31 @@ pub struct Config {
     pub(crate) secret_key: SecretKey,
     /// TLS configuration.
     pub(crate) tls: Option<TlsConfig>,
+    /// Automatic certificate configuration.
+    pub acme: Option<AcmeConfig>,
     /// Streaming data limits.
     pub limits: Limits,
+    /// Connection timeouts and limits.
+    pub server: ServerConfig,
+    /// Unix domain socket settings.
+    pub unix: UnixConfig,
+    /// Response compression settings.
+    pub compression: CompressionConfig,
+    /// The HTTP protocols to serve, in order of preference.
+    pub protocols: Vec<Protocol>,
+    /// Whether connections to `address` and `port` start with a PROXY
+    /// protocol header.
+    pub proxy_protocol: bool,
+    /// Additional addresses to serve on.
+    pub listeners: Vec<ListenerConfig>,
     /// Extra parameters that aren't part of Rocket's core config.
     pub extras: HashMap<String, Value>,
     /// The path to the configuration file this config was loaded from, if any.
     pub(crate) config_file_path: Option<PathBuf>,
     /// The path root-relative files will be rooted from.
     pub(crate) root_path: Option<PathBuf>,
+    /// Where each parameter not set to its default came from.
+    pub(crate) provenance: HashMap<String, Provenance>,
 }
 
 macro_rules! config_from_raw {
@@ -275,14 +303,23 @@ impl Config {
                     address: "localhost".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_blocking_threads: 512,
                     keep_alive: Some(5),
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    acme: None,
                     limits: Limits::default(),
+                    server: ServerConfig::default(),
+                    unix: UnixConfig::default(),
+                    compression: CompressionConfig::default(),
+                    protocols: Protocol::DEFAULT.to_vec(),
+                    proxy_protocol: false,
+                    listeners: vec![],
                     extras: HashMap::new(),
                     config_file_path: None,
                     root_path: None,
+                    provenance: HashMap::new(),
                 }
             }
             Staging => {
@@ -291,14 +328,23 @@ impl Config {
                     address: "0.0.0.0".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_blocking_threads: 512,
                     keep_alive: Some(5),
                     log_level: LoggingLevel::Normal,
                     secret_key: key,
                     tls: None,
+                    acme: None,
                     limits: Limits::default(),
+                    server: ServerConfig::default(),
+                    unix: UnixConfig::default(),
+                    compression: CompressionConfig::default(),
+                    protocols: Protocol::DEFAULT.to_vec(),
+                    proxy_protocol: false,
+                    listeners: vec![],
                     extras: HashMap::new(),
                     config_file_path: None,
                     root_path: None,
+                    provenance: HashMap::new(),
                 }
             }
             Production => {
@@ -307,14 +353,23 @@ impl Config {
                     address: "0.0.0.0".to_string(),
                     port: 8000,
                     workers: default_workers,
+                    max_blocking_threads: 512,
                     keep_alive: Some(5),
                     log_level: LoggingLevel::Critical,
                     secret_key: key,
                     tls: None,
+                    acme: None,
                     limits: Limits::default(),
+                    server: ServerConfig::default(),
+                    unix: UnixConfig::default(),
+                    compression: CompressionConfig::default(),
+                    protocols: Protocol::DEFAULT.to_vec(),
+                    proxy_protocol: false,
+                    listeners: vec![],
                     extras: HashMap::new(),
                     config_file_path: None,
                     root_path: None,
+                    provenance: HashMap::new(),
                 }
             }
         })
@@ -342,22 +397,47 @@ impl Config {
     ///
     ///   * **address**: String
     ///   * **port**: Integer (16-bit unsigned)
-    ///   * **workers**: Integer (16-bit unsigned)
+    ///   * **workers**: Integer (16-bit unsigned, greater than 0)
+    ///   * **max_blocking_threads**: Integer (16-bit unsigned, greater than 0)
     ///   * **keep_alive**: Integer
     ///   * **log**: String
     ///   * **secret_key**: String (256-bit base64 or base16)
-    ///   * **tls**: Table (`certs` (path as String), `key` (path as String))
+    ///   * **tls**: Table (`certs` (path as String), `key` (path as String),
+    ///     optionally `client_auth` (String), `ca_certs` and `crl` (paths as
+    ///     String))
+    ///   * **acme**: Table (`domains` and `contact` (Arrays of Strings),
+    ///     `directory`, `challenge`, and `cache` (String), `accept_terms`
+    ///     (Boolean))
+    ///   * **limits**: Table (data type as String => limit as Integer)
+    ///   * **server**: Table (`read_header_timeout`, `write_timeout`,
+    ///     `max_requests`, `max_connections`, `max_in_flight`, `retry_after`,
+    ///     `grace`, and `mercy`, all Integer)
+    ///   * **unix**: Table (`mode` as Integer or String, `cleanup` as Boolean)
+    ///   * **compression**: Table (`enabled` and `brotli` as Boolean,
+    ///     `min_size` as Integer or String, `exclude` as Array of Strings)
+    ///   * **protocols**: Array of Strings (`"h2"` or `"http/1.1"`)
+    ///   * **proxy_protocol**: Boolean
+    ///   * **listeners**: Array of Tables (`name`, `address`, `port`, and
+    ///     optionally `tls` and `proxy_protocol`)
     pub(crate) fn set_raw(&mut self, name: &str, val: &Value) -> Result<()> {
         let (id, ok) = (|val| val, |_| Ok(()));
         config_from_raw!(self, name, val,
             address => (str, set_address, id),
             port => (u16, set_port, ok),
-            workers => (u16, set_workers, ok),
+            workers => (positive_u16, set_workers, ok),
+            max_blocking_threads => (positive_u16, set_max_blocking_threads, ok),
             keep_alive => (u32, set_keep_alive, ok),
             log => (log_level, set_log_level, ok),
             secret_key => (str, set_secret_key, id),
             tls => (tls_config, set_raw_tls, id),
+            acme => (acme_config, set_acme, id),
             limits => (limits, set_limits, ok),
+            server => (server_config, set_server, ok),
+            unix => (unix_config, set_unix, ok),
+            compression => (compression_config, set_compression, ok),
+            protocols => (protocols, set_protocols, ok),
+            proxy_protocol => (bool, set_proxy_protocol, ok),
+            listeners => (listeners, set_raw_listeners, id),
             | _ => {
                 self.extras.insert(name.into(), val.clone());
                 Ok(())
@@ -385,10 +465,13 @@ impl Config {
 
     /// Sets the address of `self` to `address`.
     ///
+    /// An address of the form `unix:{path}` listens on a Unix domain socket at
+    /// `path` instead of a TCP port. See [`UnixConfig`] for details.
+    ///
     /// # Errors
     ///
-    /// If `address` is not a valid IP address or hostname, returns a `BadType`
-    /// error.
+    /// If `address` is not a valid IP address, hostname, or Unix domain socket
+    /// address, returns a `BadType` error.
     ///
     /// # Example
     ///
@@ -398,12 +481,14 @@ impl Config {
     /// let mut config = Config::new(Environment::Staging);
     /// assert!(config.set_address("localhost").is_ok());
     /// assert!(config.set_address("::").is_ok());
+    /// assert!(config.set_address("unix:/run/my_app.sock").is_ok());
     /// assert!(config.set_address("?").is_err());
+    /// assert!(config.set_address("unix:").is_err());
     /// ```
     pub fn set_address<A: Into<String>>(&mut self, address: A) -> Result<()> {
         let address = address.into();
-        if (&*address, 0u16).to_socket_addrs().is_err() {
-            return Err(self.bad_type("address", "string", "a valid hostname or IP"));
+        if !valid_address(&address) {
+            return Err(self.bad_type("address", "string", "a valid hostname, IP, or unix:{path}"));
         }
 
         self.address = address;
@@ -442,6 +527,62 @@ impl Config {
         self.workers = workers;
     }
 
+    /// Sets the maximum number of threads, beyond `workers`, that run blocking
+    /// tasks to `max`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let mut config = Config::new(Environment::Staging);
+    /// config.set_max_blocking_threads(64);
+    /// assert_eq!(config.max_blocking_threads, 64);
+    /// ```
+    #[inline]
+    pub fn set_max_blocking_threads(&mut self, max: u16) {
+        self.max_blocking_threads = max;
+    }
+
+    /// Returns a builder for a multi-threaded tokio runtime sized by this
+    /// configuration: `workers` worker threads, at most `max_blocking_threads`
+    /// more threads for blocking tasks, and I/O and time drivers enabled. This
+    /// is the runtime `#[launch]` creates from the configuration of the
+    /// `Rocket` instance it launches.
+    ///
+    /// Applications that run Rocket alongside other asynchronous components
+    /// can further tune the builder, for instance with thread names or start
+    /// and stop callbacks, then launch Rocket on the runtime it builds. Any
+    /// tokio runtime with I/O and time drivers enabled can launch Rocket.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::new(Environment::Production);
+    /// let mut runtime = config.runtime()
+    ///     .thread_name("app-worker")
+    ///     .on_thread_start(|| println!("worker started"))
+    ///     .build()
+    ///     .expect("runtime");
+    ///
+    /// let result = runtime.block_on(rocket::custom(config).launch());
+    /// result.expect("server failed unexpectedly");
+    /// ```
+    pub fn runtime(&self) -> tokio::runtime::Builder {
+        let workers = std::cmp::max(self.workers, 1) as usize;
+        let blocking = std::cmp::max(self.max_blocking_threads, 1) as usize;
+        let mut builder = tokio::runtime::Builder::new();
+        builder.threaded_scheduler()
+            .enable_all()
+            .thread_name("rocket-worker")
+            .core_threads(workers)
+            .max_threads(workers + blocking);
+
+        builder
+    }
+
     /// Sets the keep-alive timeout to `timeout` seconds. If `timeout` is `0`,
     /// keep-alive is disabled.
     ///
@@ -540,11 +681,98 @@ impl Config {
         self.limits = limits;
     }
 
+    /// Sets the connection timeouts and limits in `self` to `server`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ServerConfig;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_server(ServerConfig { write_timeout: Some(60), ..ServerConfig::default() });
+    /// assert_eq!(config.server.write_timeout, Some(60));
+    /// ```
+    #[inline]
+    pub fn set_server(&mut self, server: ServerConfig) {
+        self.server = server;
+    }
+
+    /// Sets the Unix domain socket settings in `self` to `unix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::UnixConfig;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_unix(UnixConfig { mode: Some(0o600), cleanup: false });
+    /// assert_eq!(config.unix.mode, Some(0o600));
+    /// ```
+    #[inline]
+    pub fn set_unix(&mut self, unix: UnixConfig) {
+        self.unix = unix;
+    }
+
+    /// Sets the response compression settings in `self` to `compression`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::CompressionConfig;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_compression(CompressionConfig { enabled: true, ..Default::default() });
+    /// assert!(config.compression.enabled);
+    /// ```
+    #[inline]
+    pub fn set_compression(&mut self, compression: CompressionConfig) {
+        self.compression = compression;
+    }
+
+    /// Sets the HTTP protocols to serve, in order of preference, to
+    /// `protocols`. See [`Protocol`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::Protocol;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_protocols(vec![Protocol::Http1]);
+    /// assert_eq!(config.protocols, [Protocol::Http1]);
+    /// ```
+    #[inline]
+    pub fn set_protocols(&mut self, protocols: Vec<Protocol>) {
+        self.protocols = protocols;
+    }
+
+    /// Sets whether connections to `address` and `port` must start with a
+    /// PROXY protocol header, as sent by proxies such as HAProxy, to
+    /// `proxy_protocol`. The client address the proxy relays is then the
+    /// request's [`remote()`](crate::Request::remote()) address. See
+    /// [PROXY protocol](crate::config#proxy-protocol) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let mut config = Config::new(Environment::Staging);
+    /// config.set_proxy_protocol(true);
+    /// assert!(config.proxy_protocol);
+    /// ```
+    #[inline]
+    pub fn set_proxy_protocol(&mut self, proxy_protocol: bool) {
+        self.proxy_protocol = proxy_protocol;
+    }
+
     /// Sets the TLS configuration in `self`.
     ///
     /// Certificates are read from `certs_path`. The certificate chain must be
     /// in X.509 PEM format. The private key is read from `key_path`. The
     /// private key must be an RSA key in either PKCS#1 or PKCS#8 PEM format.
+    /// With the `native-tls` feature, the private key must be in PKCS#8 PEM
+    /// format and may be of any type supported by the platform's TLS library.
     ///
     /// # Errors
     ///
@@ -563,8 +791,164 @@ impl Config {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(feature = "tls")]
+    #[cfg(any(feature = "tls", feature = "native-tls"))]
     pub fn set_tls(&mut self, certs_path: &str, key_path: &str) -> Result<()> {
+        self.tls = Some(self.load_tls("tls", certs_path, key_path)?);
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
+        self.tls = Some(TlsConfig);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn set_raw_tls(&mut self, _raw: RawTls<'_>) -> Result<()> {
+        #[cfg(not(test))]
+        {
+            self.set_tls(_raw.certs, _raw.key)?;
+            match _raw.mtls {
+                Some(ref mtls) => self.set_mtls(mtls),
+                None => Ok(())
+            }
+        }
+
+        // During unit testing, we don't want to actually read certs/keys.
+        #[cfg(test)]
+        { Ok(()) }
+    }
+
+    /// Authenticates TLS clients by certificate as configured by `mtls`. See
+    /// [`MutualTls`] for details.
+    ///
+    /// # Errors
+    ///
+    /// If TLS has not been configured via [`Config::set_tls()`], or if the
+    /// `native-tls` feature is enabled, returns a `BadType` error. If reading
+    /// the certificate authorities or revocation list fails, an error of
+    /// variant `Io` is returned. If either file is malformed, an error of
+    /// variant `BadType` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// use rocket::config::MutualTls;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_tls("/etc/ssl/my_certs.pem", "/etc/ssl/priv.key")?;
+    /// config.set_mtls(&MutualTls::required("/etc/ssl/ca.pem"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_mtls(&mut self, mtls: &MutualTls) -> Result<()> {
+        match self.tls.clone() {
+            Some(tls) => {
+                self.tls = Some(self.with_mtls("tls", tls, mtls)?);
+                Ok(())
+            }
+            None => Err(self.bad_type("tls", "mutual TLS without TLS",
+                                      "a `tls` table with `certs` and `key` entries")),
+        }
+    }
+
+    /// Obtains the certificates of the default listener automatically as
+    /// configured by `acme`. See [`AcmeConfig`] for details.
+    ///
+    /// # Errors
+    ///
+    /// If `acme` has no domains, or if its terms of service haven't been
+    /// accepted, returns a `BadType` error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// use rocket::config::AcmeConfig;
+    ///
+    /// let mut config = rocket::Config::development();
+    /// config.set_acme(AcmeConfig::new(vec!["example.com"]).accept_terms(true))?;
+    /// assert!(config.set_acme(AcmeConfig::new(vec!["example.com"])).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_acme(&mut self, acme: AcmeConfig) -> Result<()> {
+        if acme.domains.is_empty() {
+            return Err(self.bad_type("acme.domains", "an empty array",
+                                     "a non-empty array of domains"));
+        }
+
+        if !acme.accept_terms {
+            return Err(self.bad_type("acme.accept_terms", "false",
+                                     "`true` to accept the CA's terms of service"));
+        }
+
+        self.acme = Some(acme);
+        Ok(())
+    }
+
+    /// Adds the client authentication configured by `mtls` to `tls`, the
+    /// TLS configuration of the parameter `name`.
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    fn with_mtls(&self, name: &str, tls: TlsConfig, mtls: &MutualTls) -> Result<TlsConfig> {
+        use crate::http::tls::{load_ca_certs, load_certs, load_crl, ClientAuth, Error};
+
+        let pem_err = "malformed PEM file";
+        let ca_certs_err = |e: Error| match e {
+            Error::Io(e) => ConfigError::Io(e, "tls.ca_certs"),
+            _ => self.bad_type(name, pem_err, "a valid CA certificates file")
+        };
+
+        let ca_certs = self.root_relative(&mtls.ca_certs);
+        let roots = load_ca_certs(&ca_certs).map_err(ca_certs_err)?;
+        let revoked = match mtls.crl {
+            Some(ref crl) => {
+                let ca_certs = load_certs(&ca_certs).map_err(ca_certs_err)?;
+                load_crl(self.root_relative(crl), &ca_certs).map_err(|e| match e {
+                    Error::Io(e) => ConfigError::Io(e, "tls.crl"),
+                    Error::UntrustedCrl => self.bad_type(name, "CRL not signed by a CA",
+                        "a certificate revocation list signed by one of `ca_certs`"),
+                    Error::StaleCrl => self.bad_type(name, "CRL past its next update",
+                        "a current certificate revocation list"),
+                    _ => self.bad_type(name, "malformed CRL", "a valid certificate revocation list")
+                })?
+            }
+            None => Default::default(),
+        };
+
+        let client_auth = ClientAuth { roots, mandatory: mtls.mandatory, revoked: revoked.into() };
+        Ok(TlsConfig { client_auth: Some(client_auth), mtls: Some(mtls.clone()), ..tls })
+    }
+
+    /// Rereads the files `tls` was loaded from.
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    pub(crate) fn reload_tls(&self, name: &str, tls: &TlsConfig) -> Result<TlsConfig> {
+        let reloaded = self.load_tls(name, &tls.certs_path, &tls.key_path)?;
+        match tls.mtls {
+            Some(ref mtls) => self.with_mtls(name, reloaded, mtls),
+            None => Ok(reloaded),
+        }
+    }
+
+    #[cfg(feature = "native-tls")]
+    fn with_mtls(&self, name: &str, _: TlsConfig, _: &MutualTls) -> Result<TlsConfig> {
+        Err(self.bad_type(name, "mutual TLS with the native-tls backend",
+                          "the tls backend for `client_auth`"))
+    }
+
+    #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+    fn with_mtls(&self, _: &str, tls: TlsConfig, _: &MutualTls) -> Result<TlsConfig> {
+        Ok(tls)
+    }
+
+    /// Loads the certificates at `certs_path` and the private key at
+    /// `key_path` for the TLS configuration of the parameter `name`.
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    fn load_tls(&self, name: &str, certs_path: &str, key_path: &str) -> Result<TlsConfig> {
         use crate::http::tls::{load_certs, load_private_key, Error};
 
         let pem_err = "malformed PEM file";
@@ -573,35 +957,160 @@ impl Config {
         let certs = load_certs(self.root_relative(certs_path))
             .map_err(|e| match e {
                 Error::Io(e) => ConfigError::Io(e, "tls.certs"),
-                _ => self.bad_type("tls", pem_err, "a valid certificates file")
+                _ => self.bad_type(name, pem_err, "a valid certificates file")
             })?;
 
         // And now the private key.
         let key = load_private_key(self.root_relative(key_path))
             .map_err(|e| match e {
                 Error::Io(e) => ConfigError::Io(e, "tls.key"),
-                _ => self.bad_type("tls", pem_err, "a valid private key file")
+                _ => self.bad_type(name, pem_err, "a valid private key file")
             })?;
 
-        self.tls = Some(TlsConfig { certs, key });
-        Ok(())
+        let (certs_path, key_path) = (certs_path.to_string(), key_path.to_string());
+        Ok(TlsConfig { certs, key, client_auth: None, certs_path, key_path, mtls: None })
     }
 
-    #[doc(hidden)]
-    #[cfg(not(feature = "tls"))]
-    pub fn set_tls(&mut self, _: &str, _: &str) -> Result<()> {
-        self.tls = Some(TlsConfig);
+    #[cfg(feature = "native-tls")]
+    fn load_tls(&self, name: &str, certs_path: &str, key_path: &str) -> Result<TlsConfig> {
+        let certs = std::fs::read(self.root_relative(certs_path))
+            .map_err(|e| ConfigError::Io(e, "tls.certs"))?;
+
+        let key = std::fs::read(self.root_relative(key_path))
+            .map_err(|e| ConfigError::Io(e, "tls.key"))?;
+
+        let identity = crate::http::native_tls::load_identity(&certs, &key)
+            .map_err(|_| self.bad_type(name, "malformed PEM file",
+                    "a valid certificates file and PKCS#8 private key file"))?;
+
+        Ok(TlsConfig { identity })
+    }
+
+    #[cfg(not(any(feature = "tls", feature = "native-tls")))]
+    fn load_tls(&self, _: &str, _: &str, _: &str) -> Result<TlsConfig> {
+        Ok(TlsConfig)
+    }
+
+    /// Adds a listener named `name` that serves plaintext HTTP on `address`
+    /// and `port` in addition to the `address` and `port` of `self`.
+    ///
+    /// # Errors
+    ///
+    /// If `address` is not a valid IP address or hostname, or if `name` is
+    /// `default` or the name of another listener, returns a `BadType` error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let mut config = Config::new(Environment::Staging);
+    /// assert!(config.add_listener("admin", "127.0.0.1", 9000).is_ok());
+    /// assert!(config.add_listener("admin", "127.0.0.1", 9001).is_err());
+    /// assert!(config.add_listener("default", "127.0.0.1", 9002).is_err());
+    ///
+    /// assert_eq!(config.listeners[0].name, "admin");
+    /// assert_eq!(config.listeners[0].port, 9000);
+    /// ```
+    pub fn add_listener(&mut self, name: &str, address: &str, port: u16) -> Result<()> {
+        self.push_listener(name, address, port, None)
+    }
+
+    /// Adds a listener named `name` that serves HTTPS on `address` and `port`
+    /// in addition to the `address` and `port` of `self`. Certificates and the
+    /// private key are read as in [`Config::set_tls()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Config::add_listener()`] and
+    /// [`Config::set_tls()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::config::ConfigError;
+    /// # fn config_test() -> Result<(), ConfigError> {
+    /// let mut config = rocket::Config::development();
+    /// config.add_tls_listener("https", "0.0.0.0", 443, "/etc/ssl/certs.pem", "/etc/ssl/key.pem")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_tls_listener(
+        &mut self,
+        name: &str,
+        address: &str,
+        port: u16,
+        certs_path: &str,
+        key_path: &str
+    ) -> Result<()> {
+        let tls = self.load_tls("listeners", certs_path, key_path)?;
+        self.push_listener(name, address, port, Some(tls))
+    }
+
+    fn push_listener(
+        &mut self,
+        name: &str,
+        address: &str,
+        port: u16,
+        tls: Option<TlsConfig>
+    ) -> Result<()> {
+        if !valid_address(address) {
+            return Err(self.bad_type("listeners", "string", "a valid hostname, IP, or unix:{path}"));
+        }
+
+        let taken = name == ListenerConfig::DEFAULT || self.listeners.iter().any(|l| l.name == name);
+        if taken {
+            return Err(self.bad_type("listeners", "a duplicate name", "a unique listener name"));
+        }
+
+        let (name, address) = (name.to_string(), address.to_string());
+        self.listeners.push(ListenerConfig {
+            name, address, port, tls, acme: false, proxy_protocol: false
+        });
         Ok(())
     }
 
-    #[inline(always)]
-    fn set_raw_tls(&mut self, _paths: (&str, &str)) -> Result<()> {
-        #[cfg(not(test))]
-        { self.set_tls(_paths.0, _paths.1) }
+    /// Returns the listener for `address` and `port`, named `default`,
+    /// followed by the `listeners`.
+    pub(crate) fn listener_configs(&self) -> Vec<ListenerConfig> {
+        let default = ListenerConfig {
+            name: ListenerConfig::DEFAULT.into(),
+            address: self.address.clone(),
+            port: self.port,
+            tls: self.tls.clone(),
+            acme: self.acme_enabled(),
+            proxy_protocol: self.proxy_protocol,
+        };
 
-        // During unit testing, we don't want to actually read certs/keys.
-        #[cfg(test)]
-        { Ok(()) }
+        let mut listeners = vec![default];
+        listeners.extend(self.listeners.iter().cloned());
+        listeners
+    }
+
+    fn set_raw_listeners(&mut self, listeners: Vec<RawListener<'_>>) -> Result<()> {
+        self.listeners.clear();
+        for listener in listeners {
+            let (name, address, port) = (listener.name, listener.address, listener.port);
+            match listener.tls {
+                // During unit testing, we don't want to actually read certs/keys.
+                Some(_) if cfg!(test) => self.push_listener(name, address, port, None)?,
+                Some(tls) => {
+                    let mut tls_config = self.load_tls("listeners", tls.certs, tls.key)?;
+                    if let Some(ref mtls) = tls.mtls {
+                        tls_config = self.with_mtls("listeners", tls_config, mtls)?;
+                    }
+
+                    self.push_listener(name, address, port, Some(tls_config))?
+                }
+                None => self.add_listener(name, address, port)?,
+            }
+
+            if let Some(pushed) = self.listeners.last_mut() {
+                pushed.proxy_protocol = listener.proxy_protocol;
+            }
+        }
+
+        Ok(())
     }
 
     /// Sets the extras for `self` to be the key/value pairs in `extras`.
@@ -652,15 +1161,19 @@ impl Config {
         self.extras.iter().map(|(k, v)| (k.as_str(), v))
     }
 
-    /// Returns `true` if TLS is enabled.
+    /// Returns `true` if TLS is enabled, either via `tls` or via `acme`.
     ///
-    /// Always returns `false` if the `tls` compilation feature is not enabled.
+    /// Always returns `false` if neither the `tls` nor the `native-tls`
+    /// compilation feature is enabled.
     pub fn tls_enabled(&self) -> bool {
-        if cfg!(feature = "tls") {
-            self.tls.is_some()
-        } else {
-            false
-        }
+        let tls = cfg!(any(feature = "tls", feature = "native-tls")) && self.tls.is_some();
+        tls || self.acme_enabled()
+    }
+
+    /// Returns `true` if the default listener serves certificates obtained
+    /// via ACME.
+    fn acme_enabled(&self) -> bool {
+        cfg!(all(feature = "tls-acme", not(feature = "native-tls"))) && self.acme.is_some()
     }
 
     /// Retrieves the secret key from `self`.
@@ -889,6 +1402,203 @@ impl Config {
             .ok_or_else(|| self.bad_type(name, val.type_str(), "a datetime"))
     }
 
+    /// Deserializes the extra named `name` into a `T`. If there is no extra
+    /// named `name`, an empty table is deserialized instead, so a `T` whose
+    /// fields all have defaults, via `#[serde(default)]`, can be extracted
+    /// from a configuration that omits the section entirely.
+    ///
+    /// When `ROCKET_NESTED_ENV` is set to `true` or `1`, fields of a section
+    /// can be overridden individually with environment variables that separate
+    /// the path to the field with double underscores:
+    /// `ROCKET_DATABASE__POOL_SIZE=16` sets `pool_size` in the `database`
+    /// section. Use [`Config::extract_validated()`] to declare per-field
+    /// defaults and overrides instead.
+    ///
+    /// # Errors
+    ///
+    /// If the extra cannot be deserialized into a `T`, returns an `Invalid`
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # extern crate serde;
+    /// use serde::Deserialize;
+    /// use rocket::config::{Config, Environment, Value};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Database {
+    ///     url: String,
+    ///     #[serde(default)]
+    ///     pool_size: u32,
+    /// }
+    ///
+    /// let mut table = rocket::config::Table::new();
+    /// table.insert("url".into(), Value::from("postgres://localhost/app"));
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("database", table)
+    ///     .unwrap();
+    ///
+    /// let database: Database = config.extract("database").unwrap();
+    /// assert_eq!(database.url, "postgres://localhost/app");
+    /// assert_eq!(database.pool_size, 0);
+    /// ```
+    pub fn extract<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let value = match self.extras.get(name) {
+            Some(value) => value.clone(),
+            None => Value::Table(Table::new()),
+        };
+
+        self.deserialize_extra(name, value)
+    }
+
+    /// Deserializes `value`, the extra named `name`, into a `T`.
+    fn deserialize_extra<T: DeserializeOwned>(&self, name: &str, value: Value) -> Result<T> {
+        value.try_into().map_err(|e| {
+            let error = ValidationError::new(name, e.to_string());
+            ConfigError::Invalid(name.into(), vec![error], self.config_file_path.clone())
+        })
+    }
+
+    /// Deserializes the extra named `name` into a `T` as in
+    /// [`Config::extract()`], then validates it via [`Validate::validate()`].
+    ///
+    /// Fields missing from the extra take their values from
+    /// [`ConfigSection::defaults()`]. Fields named in
+    /// [`ConfigSection::env_overrides()`] whose environment variable is set
+    /// take their value from the variable, which is parsed as it is for
+    /// `ROCKET_` variables.
+    ///
+    /// # Errors
+    ///
+    /// If an override can't be parsed, if the extra cannot be deserialized
+    /// into a `T`, or if the `T` is invalid, returns an `Invalid` error. The
+    /// path of each [`ValidationError`] is prefixed with `name`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate serde;
+    /// use serde::Deserialize;
+    /// use rocket::config::{Config, ConfigError, Environment, Value};
+    /// use rocket::config::{Validate, ValidationError};
+    ///
+    /// #[derive(Deserialize, ConfigSection)]
+    /// struct Pool {
+    ///     #[config(default = 4)]
+    ///     size: u32,
+    /// }
+    ///
+    /// impl Validate for Pool {
+    ///     fn validate(&self) -> Result<(), Vec<ValidationError>> {
+    ///         match self.size {
+    ///             0 => Err(vec![ValidationError::new("size", "must be at least 1")]),
+    ///             _ => Ok(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut table = rocket::config::Table::new();
+    /// table.insert("size".into(), Value::Integer(0));
+    /// let config = Config::build(Environment::Staging)
+    ///     .extra("pool", table)
+    ///     .unwrap();
+    ///
+    /// match config.extract_validated::<Pool>("pool") {
+    ///     Err(ConfigError::Invalid(_, errors, _)) => assert_eq!(errors[0].path, "pool.size"),
+    ///     _ => unreachable!("pool size is invalid"),
+    /// }
+    ///
+    /// let config = Config::build(Environment::Staging).unwrap();
+    /// let pool: Pool = config.extract_validated("pool").unwrap();
+    /// assert_eq!(pool.size, 4);
+    /// ```
+    ///
+    /// [`Validate::validate()`]: crate::config::Validate::validate()
+    pub fn extract_validated<T: ConfigSection>(&self, name: &str) -> Result<T> {
+        let mut table = T::defaults();
+        match self.extras.get(name) {
+            Some(Value::Table(extra)) => table.extend(extra.clone()),
+            // Deserialize a non-table as-is so the error names its type.
+            Some(value) => return self.deserialize_extra(name, value.clone()),
+            None => {}
+        }
+
+        for (field, var) in T::env_overrides() {
+            if let Ok(raw) = std::env::var(var) {
+                let value = parse_simple_toml_value(&raw).map_err(|e| {
+                    let msg = format!("invalid value in `{}`: {}", var, e);
+                    let error = ValidationError::new(field, msg).nested(name);
+                    ConfigError::Invalid(name.into(), vec![error], self.config_file_path.clone())
+                })?;
+
+                table.insert(field.to_string(), value);
+            }
+        }
+
+        let value: T = self.deserialize_extra(name, Value::Table(table))?;
+        value.validate().map_err(|errors| {
+            let errors = errors.into_iter().map(|e| e.nested(name)).collect();
+            ConfigError::Invalid(name.into(), errors, self.config_file_path.clone())
+        })?;
+
+        Ok(value)
+    }
+
+    /// Sets the configuration `val` for the parameter at the dot-separated
+    /// `path`. A path with one component is set as by [`Config::set_raw()`].
+    /// Otherwise, a single field of the `limits` or `server` table, or a
+    /// nested value within the extras, is set, leaving the rest of the table
+    /// intact.
+    pub(crate) fn set_raw_path(&mut self, path: &[&str], val: &Value) -> Result<()> {
+        match path {
+            [name] => self.set_raw(name, val),
+            ["limits", name] => {
+                let limit = byte_unit(self, &format!("limits.{}", name), val)?;
+                self.limits = self.limits.clone().limit(*name, limit);
+                Ok(())
+            }
+            [table_name @ "server", name]
+            | [table_name @ "unix", name]
+            | [table_name @ "compression", name] => {
+                let mut table = Table::new();
+                table.insert(name.to_string(), val.clone());
+                self.set_raw(table_name, &Value::Table(table))
+            }
+            _ => self.set_nested_extra(path, val.clone()),
+        }
+    }
+
+    /// Returns where the parameter `name` came from.
+    pub(crate) fn provenance(&self, name: &str) -> Provenance {
+        self.provenance.get(name).cloned().unwrap_or(Provenance::Default)
+    }
+
+    /// Sets the value at the `path` of nested tables within the extras to
+    /// `val`, creating tables as needed.
+    pub(crate) fn set_nested_extra(&mut self, path: &[&str], val: Value) -> Result<()> {
+        let (first, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        let empty = || Value::Table(Table::new());
+        let mut slot = self.extras.entry(first.to_string()).or_insert_with(empty);
+        for (i, key) in rest.iter().enumerate() {
+            slot = match slot {
+                Value::Table(table) => table.entry(key.to_string()).or_insert_with(empty),
+                other => {
+                    let name = path[..=i].join(".");
+                    return Err(ConfigError::BadType(name, "a table", other.type_str(), None));
+                }
+            };
+        }
+
+        *slot = val;
+        Ok(())
+    }
+
     /// Returns the root path of the configuration, if one is known.
     ///
     /// For configurations loaded from a `Rocket.toml` file, this will be the
@@ -963,6 +1673,15 @@ impl Config {
     }
 }
 
+/// Returns `true` if `address` is a valid hostname or IP address or has the
+/// form `unix:{path}`.
+fn valid_address(address: &str) -> bool {
+    match address.strip_prefix("unix:") {
+        Some(path) => !path.is_empty(),
+        None => (address, 0u16).to_socket_addrs().is_ok(),
+    }
+}
+
 impl fmt::Debug for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut s = f.debug_struct("Config");
@@ -970,7 +1689,15 @@ impl fmt::Debug for Config {
         s.field("address", &self.address);
         s.field("port", &self.port);
         s.field("workers", &self.workers);
+        s.field("max_blocking_threads", &self.max_blocking_threads);
         s.field("keep_alive", &self.keep_alive);
+        s.field("server", &self.server);
+        s.field("unix", &self.unix);
+        s.field("compression", &self.compression);
+        s.field("protocols", &self.protocols);
+        s.field("proxy_protocol", &self.proxy_protocol);
+        s.field("acme", &self.acme);
+        s.field("listeners", &self.listeners);
         s.field("log_level", &self.log_level);
 
         for (key, value) in self.extras() {
@@ -987,8 +1714,16 @@ impl PartialEq for Config {
         self.address == other.address
             && self.port == other.port
             && self.workers == other.workers
+            && self.max_blocking_threads == other.max_blocking_threads
             && self.log_level == other.log_level
             && self.keep_alive == other.keep_alive
+            && self.server == other.server
+            && self.unix == other.unix
+            && self.compression == other.compression
+            && self.protocols == other.protocols
+            && self.proxy_protocol == other.proxy_protocol
+            && self.acme == other.acme
+            && self.listeners == other.listeners
             && self.environment == other.environment
             && self.extras == other.extras
     }
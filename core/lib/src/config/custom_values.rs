@@ -1,10 +1,16 @@
 use std::fmt;
 
-#[cfg(feature = "tls")] use crate::http::tls::{Certificate, PrivateKey};
+#[cfg(all(feature = "tls", not(feature = "native-tls")))]
+use crate::http::tls::{Certificate, PrivateKey, ClientAuth};
+
+#[cfg(feature = "native-tls")] use crate::http::native_tls::Identity;
 
 use crate::http::private::cookie::Key;
-use crate::config::{Result, Config, Value, ConfigError, LoggingLevel};
-use crate::data::Limits;
+use crate::config::{Result, Config, Value, ConfigError, LoggingLevel, ServerConfig, UnixConfig};
+use crate::config::{MutualTls, AcmeConfig, AcmeChallenge, Protocol, CompressionConfig};
+use crate::config::ValidationError;
+use crate::http::MediaType;
+use crate::data::{ByteUnit, Limits};
 
 #[derive(Clone)]
 pub enum SecretKey {
@@ -45,14 +51,26 @@ impl fmt::Display for SecretKey {
     }
 }
 
-#[cfg(feature = "tls")]
+#[cfg(all(feature = "tls", not(feature = "native-tls")))]
 #[derive(Clone)]
 pub struct TlsConfig {
     pub certs: Vec<Certificate>,
-    pub key: PrivateKey
+    pub key: PrivateKey,
+    pub client_auth: Option<ClientAuth>,
+    /// The paths and client authentication the above were loaded from, to
+    /// reload them from.
+    pub certs_path: String,
+    pub key_path: String,
+    pub mtls: Option<MutualTls>,
+}
+
+#[cfg(feature = "native-tls")]
+#[derive(Clone)]
+pub struct TlsConfig {
+    pub identity: Identity
 }
 
-#[cfg(not(feature = "tls"))]
+#[cfg(not(any(feature = "tls", feature = "native-tls")))]
 #[derive(Clone)]
 pub struct TlsConfig;
 
@@ -74,6 +92,13 @@ pub fn u16(conf: &Config, name: &str, value: &Value) -> Result<u16> {
     }
 }
 
+pub fn positive_u16(conf: &Config, name: &str, value: &Value) -> Result<u16> {
+    match value.as_integer() {
+        Some(x) if x > 0 && x <= (u16::max_value() as i64) => Ok(x as u16),
+        _ => Err(conf.bad_type(name, value.type_str(), "a positive 16-bit integer"))
+    }
+}
+
 pub fn u32(conf: &Config, name: &str, value: &Value) -> Result<u32> {
     match value.as_integer() {
         Some(x) if x >= 0 && x <= (u32::max_value() as i64) => Ok(x as u32),
@@ -81,6 +106,18 @@ pub fn u32(conf: &Config, name: &str, value: &Value) -> Result<u32> {
     }
 }
 
+pub fn bool(conf: &Config, name: &str, value: &Value) -> Result<bool> {
+    value.as_bool().ok_or_else(|| conf.bad_type(name, value.type_str(), "a boolean"))
+}
+
+pub fn byte_unit(conf: &Config, name: &str, value: &Value) -> Result<ByteUnit> {
+    match value {
+        Value::String(s) => s.parse()
+            .map_err(|_| conf.bad_type(name, value.type_str(), "a size like \"5MiB\"")),
+        _ => u64(conf, name, value).map(ByteUnit::from),
+    }
+}
+
 pub fn log_level(conf: &Config,
                           name: &str,
                           value: &Value
@@ -89,11 +126,19 @@ pub fn log_level(conf: &Config,
         .and_then(|s| s.parse().map_err(|e| conf.bad_type(name, value.type_str(), e)))
 }
 
+/// A `tls` table, before its files are loaded.
+pub struct RawTls<'v> {
+    pub certs: &'v str,
+    pub key: &'v str,
+    pub mtls: Option<MutualTls>,
+}
+
 pub fn tls_config<'v>(conf: &Config,
                                name: &str,
                                value: &'v Value,
-                               ) -> Result<(&'v str, &'v str)> {
+                               ) -> Result<RawTls<'v>> {
     let (mut certs_path, mut key_path) = (None, None);
+    let (mut client_auth, mut ca_certs, mut crl) = (None, None, None);
     let table = value.as_table()
         .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
 
@@ -102,27 +147,262 @@ pub fn tls_config<'v>(conf: &Config,
         match key.as_str() {
             "certs" => certs_path = Some(str(conf, "tls.certs", value)?),
             "key" => key_path = Some(str(conf, "tls.key", value)?),
+            "client_auth" => client_auth = match str(conf, "tls.client_auth", value)? {
+                "required" => Some(true),
+                "optional" => Some(false),
+                _ => return Err(conf.bad_type("tls.client_auth", "an unknown mode",
+                                              "\"required\" or \"optional\"")),
+            },
+            "ca_certs" => ca_certs = Some(str(conf, "tls.ca_certs", value)?),
+            "crl" => crl = Some(str(conf, "tls.crl", value)?),
             _ => return Err(ConfigError::UnknownKey(format!("{}.tls.{}", env, key)))
         }
     }
 
+    let mtls = match (client_auth, ca_certs) {
+        (Some(mandatory), Some(ca_certs)) => Some(MutualTls {
+            mandatory,
+            ca_certs: ca_certs.into(),
+            crl: crl.map(|crl| crl.into()),
+        }),
+        (None, None) if crl.is_none() => None,
+        _ => return Err(conf.bad_type(name, "a table with missing entries",
+                                      "`client_auth` and `ca_certs` entries together")),
+    };
+
     if let (Some(certs), Some(key)) = (certs_path, key_path) {
-        Ok((certs, key))
+        Ok(RawTls { certs, key, mtls })
     } else {
         Err(conf.bad_type(name, "a table with missing entries",
                             "a table with `certs` and `key` entries"))
     }
 }
 
+pub fn acme_config(conf: &Config, name: &str, value: &Value) -> Result<AcmeConfig> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let strings = |name: &str, value: &Value| -> Result<Vec<String>> {
+        value.as_array()
+            .and_then(|array| array.iter().map(|v| v.as_str().map(String::from)).collect())
+            .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of strings"))
+    };
+
+    let mut acme = AcmeConfig::new(Vec::<String>::new());
+    for (key, val) in table {
+        match key.as_str() {
+            "domains" => acme.domains = strings("acme.domains", val)?,
+            "contact" => acme.contact = strings("acme.contact", val)?,
+            "directory" => acme.directory = str(conf, "acme.directory", val)?.into(),
+            "cache" => acme.cache = str(conf, "acme.cache", val)?.into(),
+            "challenge" => {
+                acme.challenge = str(conf, "acme.challenge", val)?.parse::<AcmeChallenge>()
+                    .map_err(|_| conf.bad_type("acme.challenge", "an unknown challenge",
+                                               "\"tls-alpn-01\" or \"http-01\""))?;
+            }
+            "accept_terms" => {
+                let expected = "a boolean";
+                acme.accept_terms = val.as_bool()
+                    .ok_or_else(|| conf.bad_type("acme.accept_terms", val.type_str(), expected))?;
+            }
+            _ => {
+                let env = conf.environment;
+                return Err(ConfigError::UnknownKey(format!("{}.acme.{}", env, key)));
+            }
+        }
+    }
+
+    Ok(acme)
+}
+
 pub fn limits(conf: &Config, name: &str, value: &Value) -> Result<Limits> {
     let table = value.as_table()
         .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
 
     let mut limits = Limits::default();
     for (key, val) in table {
-        let val = u64(conf, &format!("limits.{}", key), val)?;
-        limits = limits.limit(key.as_str(), val.into());
+        let val = byte_unit(conf, &format!("limits.{}", key), val)?;
+        limits = limits.limit(key.as_str(), val);
     }
 
     Ok(limits)
 }
+
+pub fn server_config(conf: &Config, name: &str, value: &Value) -> Result<ServerConfig> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut server = conf.server;
+    for (key, val) in table {
+        let val = match u32(conf, &format!("server.{}", key), val)? {
+            0 => None,
+            n => Some(n),
+        };
+
+        match key.as_str() {
+            "read_header_timeout" => server.read_header_timeout = val,
+            "read_timeout" => server.read_timeout = val,
+            "write_timeout" => server.write_timeout = val,
+            "max_requests" => server.max_requests = val,
+            "max_connections" => server.max_connections = val,
+            "max_in_flight" => server.max_in_flight = val,
+            "retry_after" => server.retry_after = val,
+            "grace" => server.grace = val,
+            "mercy" => server.mercy = val,
+            _ => {
+                let env = conf.environment;
+                return Err(ConfigError::UnknownKey(format!("{}.server.{}", env, key)));
+            }
+        }
+    }
+
+    Ok(server)
+}
+
+pub fn unix_config(conf: &Config, name: &str, value: &Value) -> Result<UnixConfig> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut unix = conf.unix;
+    for (key, val) in table {
+        match key.as_str() {
+            "mode" => {
+                let mode = match val {
+                    Value::Integer(i) if *i >= 0 && *i <= 0o7777 => Some(*i as u32),
+                    Value::String(s) => u32::from_str_radix(s.trim_start_matches("0o"), 8).ok()
+                        .filter(|mode| *mode <= 0o7777),
+                    _ => None,
+                };
+
+                let expected = "file permissions, such as `0o660` or \"660\"";
+                let mode = mode.ok_or_else(|| conf.bad_type("unix.mode", val.type_str(), expected))?;
+                unix.mode = Some(mode);
+            }
+            "cleanup" => {
+                let cleanup = val.as_bool()
+                    .ok_or_else(|| conf.bad_type("unix.cleanup", val.type_str(), "a boolean"))?;
+
+                unix.cleanup = cleanup;
+            }
+            _ => {
+                let env = conf.environment;
+                return Err(ConfigError::UnknownKey(format!("{}.unix.{}", env, key)));
+            }
+        }
+    }
+
+    Ok(unix)
+}
+
+pub fn compression_config(conf: &Config, name: &str, value: &Value) -> Result<CompressionConfig> {
+    let table = value.as_table()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "a table"))?;
+
+    let mut compression = conf.compression.clone();
+    for (key, val) in table {
+        match key.as_str() {
+            "enabled" => {
+                let enabled = val.as_bool();
+                let bad_type = || conf.bad_type("compression.enabled", val.type_str(), "a boolean");
+                compression.enabled = enabled.ok_or_else(bad_type)?;
+            }
+            "brotli" => {
+                let brotli = val.as_bool();
+                let bad_type = || conf.bad_type("compression.brotli", val.type_str(), "a boolean");
+                compression.brotli = brotli.ok_or_else(bad_type)?;
+                if compression.brotli && !cfg!(feature = "brotli") {
+                    let error = ValidationError::new("brotli", "requires the `brotli` feature");
+                    let path = conf.config_file_path.clone();
+                    return Err(ConfigError::Invalid(name.into(), vec![error], path));
+                }
+            }
+            "min_size" => compression.min_size = byte_unit(conf, "compression.min_size", val)?,
+            "exclude" => {
+                let expected = "an array of media types, such as \"image/*\"";
+                let bad_type = || conf.bad_type("compression.exclude", val.type_str(), expected);
+                compression.exclude = val.as_array()
+                    .ok_or_else(bad_type)?
+                    .iter()
+                    .map(|v| v.as_str().and_then(MediaType::parse_flexible).ok_or_else(bad_type))
+                    .collect::<Result<_>>()?;
+            }
+            _ => {
+                let env = conf.environment;
+                return Err(ConfigError::UnknownKey(format!("{}.compression.{}", env, key)));
+            }
+        }
+    }
+
+    Ok(compression)
+}
+
+pub fn protocols(conf: &Config, name: &str, value: &Value) -> Result<Vec<Protocol>> {
+    let expected = "a non-empty array of \"h2\" or \"http/1.1\"";
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), expected))?;
+
+    let mut protocols = vec![];
+    for value in array {
+        let protocol = value.as_str()
+            .and_then(|s| s.parse::<Protocol>().ok())
+            .ok_or_else(|| conf.bad_type(name, "an unknown protocol", expected))?;
+
+        if !protocols.contains(&protocol) {
+            protocols.push(protocol);
+        }
+    }
+
+    if protocols.is_empty() {
+        return Err(conf.bad_type(name, "an empty array", expected));
+    }
+
+    Ok(protocols)
+}
+
+/// An entry of the `listeners` array, before its TLS files are loaded.
+pub struct RawListener<'v> {
+    pub name: &'v str,
+    pub address: &'v str,
+    pub port: u16,
+    pub tls: Option<RawTls<'v>>,
+    pub proxy_protocol: bool,
+}
+
+pub fn listeners<'v>(conf: &Config, name: &str, value: &'v Value) -> Result<Vec<RawListener<'v>>> {
+    let array = value.as_array()
+        .ok_or_else(|| conf.bad_type(name, value.type_str(), "an array of tables"))?;
+
+    let mut listeners = vec![];
+    for (i, value) in array.iter().enumerate() {
+        let entry = format!("listeners[{}]", i);
+        let table = value.as_table()
+            .ok_or_else(|| conf.bad_type(&entry, value.type_str(), "a table"))?;
+
+        let (mut name, mut address, mut port, mut tls) = (None, None, None, None);
+        let mut proxy_protocol = false;
+        for (key, val) in table {
+            let key_name = format!("{}.{}", entry, key);
+            match key.as_str() {
+                "name" => name = Some(str(conf, &key_name, val)?),
+                "address" => address = Some(str(conf, &key_name, val)?),
+                "port" => port = Some(u16(conf, &key_name, val)?),
+                "tls" => tls = Some(tls_config(conf, &key_name, val)?),
+                "proxy_protocol" => proxy_protocol = bool(conf, &key_name, val)?,
+                _ => {
+                    let env = conf.environment;
+                    return Err(ConfigError::UnknownKey(format!("{}.{}", env, key_name)));
+                }
+            }
+        }
+
+        match (name, address, port) {
+            (Some(name), Some(address), Some(port)) => {
+                listeners.push(RawListener { name, address, port, tls, proxy_protocol });
+            }
+            _ => return Err(conf.bad_type(&entry, "a table with missing entries",
+                                          "a table with `name`, `address`, and `port` entries")),
+        }
+    }
+
+    Ok(listeners)
+}
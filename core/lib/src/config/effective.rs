@@ -0,0 +1,166 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use yansi::Paint;
+
+use crate::config::{Config, Environment, LoggedValue, Secrets};
+
+/// Where the value of a configuration parameter came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provenance {
+    /// Rocket's default, or a value set in code via [`Config`] or
+    /// [`ConfigBuilder`](crate::config::ConfigBuilder).
+    Default,
+    /// The configuration file at the given path.
+    File(PathBuf),
+    /// The given `ROCKET_{PARAM}` environment variable.
+    Env(String),
+    /// The given command-line argument.
+    Arg(String),
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provenance::Default => write!(f, "default"),
+            Provenance::File(path) => write!(f, "{}", path.display()),
+            Provenance::Env(var) => write!(f, "env {}", var),
+            Provenance::Arg(arg) => write!(f, "arg {}", arg),
+        }
+    }
+}
+
+/// A configuration parameter as reported by [`EffectiveConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveParam {
+    /// The name of the parameter.
+    pub name: String,
+    /// The value of the parameter, or `[secret]` if the value is a secret.
+    pub value: String,
+    /// Where the value came from.
+    pub provenance: Provenance,
+    /// Whether the parameter is an extra.
+    pub extra: bool,
+}
+
+/// The fully merged configuration of an application, with secrets redacted and
+/// the source of every parameter recorded.
+///
+/// An `EffectiveConfig` lets operators verify which configuration an
+/// application actually loaded, and from where: `Rocket.toml`, a
+/// `ROCKET_{PARAM}` environment variable, a command-line argument, or the
+/// defaults. It is retrieved via [`Cargo::effective_config()`] and printed at
+/// launch. Its `Display` implementation renders one `name = value (source)`
+/// line per parameter.
+///
+/// The secret key is only ever reported as `provided` or `generated`, and
+/// extras resolved from a [`SecretProvider`](crate::config::SecretProvider)
+/// are reported as `[secret]`.
+///
+/// [`Cargo::effective_config()`]: crate::Cargo::effective_config()
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment, Provenance};
+///
+/// # rocket::async_test(async {
+/// let config = Config::build(Environment::Staging).port(9000).unwrap();
+/// let mut rocket = rocket::custom(config);
+/// let effective = rocket.effective_config().await;
+///
+/// let port = effective.get("port").unwrap();
+/// assert_eq!(port.value, "9000");
+/// assert_eq!(port.provenance, Provenance::Default);
+/// println!("{}", effective);
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveConfig {
+    /// The active environment.
+    pub environment: Environment,
+    /// The parameters: first Rocket's, then the extras, sorted by name.
+    pub params: Vec<EffectiveParam>,
+}
+
+impl EffectiveConfig {
+    pub(crate) fn new(config: &Config, secrets: &Secrets) -> EffectiveConfig {
+        let mut params = vec![];
+        let mut push = |name: &str, value: String, extra: bool| {
+            let provenance = config.provenance(name);
+            params.push(EffectiveParam { name: name.into(), value, provenance, extra });
+        };
+
+        let secs = |s: Option<u32>| s.map_or("disabled".into(), |s| format!("{}s", s));
+        push("address", config.address.clone(), false);
+        push("port", config.port.to_string(), false);
+        push("log", config.log_level.to_string(), false);
+        push("workers", config.workers.to_string(), false);
+        push("max_blocking_threads", config.max_blocking_threads.to_string(), false);
+        push("secret_key", config.secret_key.to_string(), false);
+        push("limits", config.limits.to_string(), false);
+        push("keep_alive", secs(config.keep_alive), false);
+        push("server", config.server.to_string(), false);
+        push("unix", config.unix.to_string(), false);
+        push("compression", config.compression.to_string(), false);
+        let protocols: Vec<_> = config.protocols.iter().map(|p| p.as_str()).collect();
+        push("protocols", protocols.join(", "), false);
+        push("proxy_protocol", config.proxy_protocol.to_string(), false);
+        let tls = if config.tls_enabled() { "enabled" } else { "disabled" };
+        push("tls", tls.into(), false);
+        push("acme", config.acme.as_ref().map_or("disabled".into(), |a| a.to_string()), false);
+        let listeners: Vec<_> = config.listeners.iter().map(|l| l.to_string()).collect();
+        match listeners.is_empty() {
+            true => push("listeners", "none".into(), false),
+            false => push("listeners", listeners.join(", "), false),
+        }
+
+        let mut extras: Vec<_> = config.extras().collect();
+        extras.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in extras {
+            let value = match secrets.conceals(value) {
+                true => "[secret]".into(),
+                false => LoggedValue(value).to_string(),
+            };
+
+            push(name, value, true);
+        }
+
+        EffectiveConfig { environment: config.environment, params }
+    }
+
+    /// Returns the parameter named `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&EffectiveParam> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Logs the configuration with Rocket's launch formatting, noting the
+    /// source of every parameter that isn't a default.
+    pub(crate) fn pretty_print(&self) {
+        launch_info!("{}Configured for {}.", Paint::emoji("🔧 "), self.environment);
+        for param in &self.params {
+            let value = Paint::default(&param.value).bold();
+            let source = match param.provenance {
+                Provenance::Default => String::new(),
+                ref other => format!(" ({})", other),
+            };
+
+            match param.extra {
+                true => launch_info_!("{} {}: {}{}", Paint::yellow("[extra]"), param.name,
+                                      value, Paint::new(source).dimmed()),
+                false => launch_info_!("{}: {}{}", param.name, value, Paint::new(source).dimmed()),
+            }
+        }
+    }
+}
+
+impl fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[{}]", self.environment)?;
+        for param in &self.params {
+            writeln!(f, "{} = {} ({})", param.name, param.value, param.provenance)?;
+        }
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use std::error::Error;
 
 use yansi::Paint;
 
-use super::Environment;
+use super::{Environment, ValidationError};
 use self::ConfigError::*;
 
 /// The type of a configuration error.
@@ -52,6 +52,19 @@ pub enum ConfigError {
     ///
     /// Parameters: (key)
     Missing(String),
+    /// A custom configuration section could not be extracted or failed
+    /// validation.
+    ///
+    /// Parameters: (section_name, errors, filename)
+    Invalid(String, Vec<ValidationError>, Option<PathBuf>),
+    /// A secret reference could not be resolved.
+    ///
+    /// Parameters: (reference, error)
+    BadSecret(String, String),
+    /// A command-line argument could not be parsed.
+    ///
+    /// Parameters: (argument, reason)
+    BadArg(String, String),
 }
 
 impl ConfigError {
@@ -112,6 +125,25 @@ impl ConfigError {
             Missing(ref key) => {
                 error!("missing configuration key: {}", Paint::default(key).bold());
             }
+            Invalid(ref name, ref errors, ref filename) => {
+                error!("configuration section {} is invalid", Paint::default(name).bold());
+                if let Some(filename) = filename {
+                    info_!("in {}", Paint::default(filename.display()).bold());
+                }
+
+                for e in errors {
+                    info_!("{}: {}", Paint::default(&e.path).bold(), e.message);
+                }
+            }
+            BadSecret(ref reference, ref error) => {
+                error!("secret {} could not be resolved", Paint::default(reference).bold());
+                info_!("{}", error);
+            }
+            BadArg(ref arg, ref error) => {
+                error!("command-line argument {} could not be parsed",
+                       Paint::default(arg).bold());
+                info_!("{}", error);
+            }
         }
     }
 
@@ -146,6 +178,16 @@ impl fmt::Display for ConfigError {
             ParseError(..) => write!(f, "the config file contains invalid TOML"),
             UnknownKey(ref k) => write!(f, "'{}' is an unknown key", k),
             Missing(ref k) => write!(f, "missing key: '{}'", k),
+            Invalid(ref n, ref errors, _) => {
+                write!(f, "invalid section '{}'", n)?;
+                for (i, e) in errors.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { ":" } else { ";" }, e)?;
+                }
+
+                Ok(())
+            }
+            BadSecret(ref r, _) => write!(f, "secret '{}' could not be resolved", r),
+            BadArg(ref a, ref e) => write!(f, "argument '{}' could not be parsed: {}", a, e),
             BadEntry(ref e, _) => {
                 write!(f, "{:?} is not a valid `[environment]` entry", e)
             }
@@ -174,6 +216,9 @@ impl Error for ConfigError {
             BadEnvVal(..) => "an environment variable could not be parsed",
             UnknownKey(..) => "an unknown key was used in a disallowed position",
             Missing(..) => "an expected key was not found",
+            Invalid(..) => "a configuration section is invalid",
+            BadSecret(..) => "a secret reference could not be resolved",
+            BadArg(..) => "a command-line argument could not be parsed",
         }
     }
 }
@@ -197,10 +242,14 @@ impl PartialEq for ConfigError {
                 k1 == k2 && v1 == v2
             }
             (&Missing(ref k1), &Missing(ref k2)) => k1 == k2,
+            (&Invalid(ref n1, ref e1, _), &Invalid(ref n2, ref e2, _)) => n1 == n2 && e1 == e2,
+            (&BadSecret(ref r1, _), &BadSecret(ref r2, _)) => r1 == r2,
+            (&BadArg(ref a1, _), &BadArg(ref a2, _)) => a1 == a2,
             (&NotFound, _) | (&IoError, _) | (&RandFailure, _) | (&Io(..), _)
                 | (&BadFilePath(..), _) | (&BadEnv(..), _) | (&ParseError(..), _)
                 | (&UnknownKey(..), _) | (&BadEntry(..), _) | (&BadType(..), _)
-                | (&BadEnvVal(..), _) | (&Missing(..), _) => false
+                | (&BadEnvVal(..), _) | (&Missing(..), _) | (&Invalid(..), _)
+                | (&BadSecret(..), _) | (&BadArg(..), _) => false
         }
     }
 }
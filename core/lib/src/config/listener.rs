@@ -0,0 +1,156 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::Request;
+use crate::config::custom_values::TlsConfig;
+use crate::request::{self, FromRequest};
+
+/// An address served by an application in addition to `address` and `port`:
+/// an entry of the `listeners` array.
+///
+/// Every listener is served by the same application, with the same routes,
+/// state, and fairings, and may enable TLS independently of the others.
+/// Listeners are configured as an array of tables, each with a unique `name`,
+/// an `address`, a `port`, an optional `tls` table, and an optional
+/// `proxy_protocol` boolean. An address of the form `unix:{path}` listens on a
+/// Unix domain socket; see [`UnixConfig`](crate::config::UnixConfig). With
+/// `proxy_protocol`, connections must start with a PROXY protocol header; see
+/// [PROXY protocol](crate::config#proxy-protocol).
+///
+/// ```toml
+/// [[global.listeners]]
+/// name = "admin"
+/// address = "127.0.0.1"
+/// port = 9000
+///
+/// [[global.listeners]]
+/// name = "https"
+/// address = "0.0.0.0"
+/// port = 443
+/// tls = { certs = "private/cert.pem", key = "private/key.pem" }
+///
+/// [[global.listeners]]
+/// name = "haproxy"
+/// address = "unix:/run/my_app/haproxy.sock"
+/// port = 0
+/// proxy_protocol = true
+/// ```
+///
+/// The listener for `address` and `port` is named `default`. Handlers retrieve
+/// the listener that accepted their request via the `&ListenerConfig` request
+/// guard or [`Request::listener()`](crate::Request::listener()). Requests
+/// dispatched via a [local `Client`](crate::local) weren't accepted by any
+/// listener; the guard forwards them.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::config::ListenerConfig;
+///
+/// #[get("/")]
+/// fn index(listener: &ListenerConfig) -> String {
+///     format!("Served by the {} listener.", listener.name)
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ListenerConfig {
+    /// The name of the listener.
+    pub name: String,
+    /// The address to serve on.
+    pub address: String,
+    /// The port to serve on.
+    pub port: u16,
+    /// TLS configuration.
+    pub(crate) tls: Option<TlsConfig>,
+    /// Whether TLS is served with certificates obtained via ACME.
+    pub(crate) acme: bool,
+    /// Whether connections start with a PROXY protocol header.
+    pub proxy_protocol: bool,
+}
+
+impl ListenerConfig {
+    /// The name of the listener for the `address` and `port` parameters.
+    pub const DEFAULT: &'static str = "default";
+
+    /// Returns `true` if TLS is enabled on this listener.
+    ///
+    /// Always returns `false` if neither the `tls` nor the `native-tls`
+    /// compilation feature is enabled.
+    pub fn tls_enabled(&self) -> bool {
+        (cfg!(any(feature = "tls", feature = "native-tls")) && self.tls.is_some()) || self.acme
+    }
+
+    /// Returns the path of the Unix domain socket this listener listens on if
+    /// its address has the form `unix:{path}`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// let config = Config::build(Environment::Staging)
+    ///     .listener("proxy", "unix:/run/my_app.sock", 0)
+    ///     .listener("admin", "127.0.0.1", 9000)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(config.listeners[0].unix_path(), Some(Path::new("/run/my_app.sock")));
+    /// assert_eq!(config.listeners[1].unix_path(), None);
+    /// ```
+    pub fn unix_path(&self) -> Option<&Path> {
+        self.address.strip_prefix("unix:").map(Path::new)
+    }
+
+    /// The URL clients reach this listener at, for display.
+    pub(crate) fn url(&self) -> String {
+        let proto = if self.tls.is_some() || self.acme { "https://" } else { "http://" };
+        match self.unix_path() {
+            Some(path) => format!("{}unix:{}", proto, path.display()),
+            None => format!("{}{}:{}", proto, self.address, self.port),
+        }
+    }
+}
+
+impl fmt::Debug for ListenerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListenerConfig")
+            .field("name", &self.name)
+            .field("address", &self.address)
+            .field("port", &self.port)
+            .field("tls", &self.tls.is_some())
+            .field("acme", &self.acme)
+            .field("proxy_protocol", &self.proxy_protocol)
+            .finish()
+    }
+}
+
+/// Doesn't consider the TLS certificates or key.
+impl PartialEq for ListenerConfig {
+    fn eq(&self, other: &ListenerConfig) -> bool {
+        self.name == other.name
+            && self.address == other.address
+            && self.port == other.port
+            && self.tls.is_some() == other.tls.is_some()
+            && self.acme == other.acme
+            && self.proxy_protocol == other.proxy_protocol
+    }
+}
+
+impl fmt::Display for ListenerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.url())
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for &'a ListenerConfig {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match req.listener() {
+            Some(listener) => request::Outcome::Success(listener),
+            None => request::Outcome::Forward(()),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+use std::path::Path;
+
+use crate::{Rocket, Request};
+use crate::config::{Args, Config, ConfigError, FullConfig, Result, Secrets};
+use crate::request::{self, FromRequest};
+use crate::data::Limits;
+use crate::logger;
+
+/// How often the configuration file is checked for changes when reloading is
+/// enabled.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A versioned snapshot of the configuration of a running application.
+///
+/// The configuration parameters `log`, `limits`, and all extras can be
+/// reloaded while an application is running: see [`Cargo::reload_config()`].
+/// Each reload produces a new snapshot with a higher [`version`]. Snapshots
+/// never change, so code that reads several parameters from one snapshot sees
+/// a consistent configuration even if a reload happens concurrently.
+///
+/// [`Cargo::reload_config()`]: crate::Cargo::reload_config()
+/// [`version`]: ConfigSnapshot::version()
+///
+/// # Request Guard
+///
+/// `ConfigSnapshot` is a request guard that never fails. Every request is
+/// processed with the snapshot that was current when the request was received,
+/// and all guards of a request retrieve that same snapshot. The limits of a
+/// request, [`Request::limits()`], are read from it as well.
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::config::ConfigSnapshot;
+///
+/// #[get("/motd")]
+/// fn motd(config: ConfigSnapshot) -> String {
+///     let message = config.get_str("motd").unwrap_or("Hello!");
+///     format!("{} (configuration v{})", message, config.version())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigSnapshot {
+    version: u64,
+    config: Arc<Config>,
+}
+
+impl ConfigSnapshot {
+    fn new(version: u64, config: Config) -> ConfigSnapshot {
+        ConfigSnapshot { version, config: Arc::new(config) }
+    }
+
+    /// Returns the version of this snapshot. The configuration an application
+    /// launched with is version `0`; each reload increments the version.
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the configuration in this snapshot.
+    #[inline(always)]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl Deref for ConfigSnapshot {
+    type Target = Config;
+
+    #[inline(always)]
+    fn deref(&self) -> &Config {
+        &self.config
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for ConfigSnapshot {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(req.state.snapshot.clone())
+    }
+}
+
+/// The configuration of a running application before and after a reload.
+///
+/// A `ConfigUpdated` is passed to the
+/// [`on_config_update`](crate::fairing::Fairing::on_config_update()) callback
+/// of fairings and returned by [`Cargo::reload_config()`].
+///
+/// [`Cargo::reload_config()`]: crate::Cargo::reload_config()
+#[derive(Debug, Clone)]
+pub struct ConfigUpdated {
+    /// The configuration before the reload.
+    pub previous: ConfigSnapshot,
+    /// The configuration after the reload.
+    pub current: ConfigSnapshot,
+}
+
+impl ConfigUpdated {
+    /// Returns `true` if the extra configuration parameter `name` was added,
+    /// removed, or changed by the reload.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ConfigUpdated;
+    ///
+    /// fn on_update(update: &ConfigUpdated) {
+    ///     if update.extra_changed("feature_flags") {
+    ///         /* refresh feature flags */
+    ///     }
+    /// }
+    /// ```
+    pub fn extra_changed(&self, name: &str) -> bool {
+        self.previous.get_extra(name).ok() != self.current.get_extra(name).ok()
+    }
+}
+
+/// The current configuration of a running application.
+pub(crate) struct LiveConfig {
+    current: RwLock<ConfigSnapshot>,
+    /// Every distinct set of limits handed out by [`LiveConfig::intern()`].
+    limits: Mutex<Vec<&'static Limits>>,
+}
+
+impl LiveConfig {
+    pub(crate) fn new(config: Config) -> LiveConfig {
+        LiveConfig {
+            current: RwLock::new(ConfigSnapshot::new(0, config)),
+            limits: Mutex::new(vec![]),
+        }
+    }
+
+    /// Returns a reference to limits equal to `limits` that lives as long as
+    /// the application. Only the limits that differ from those the
+    /// application launched with, after a reload or when a route overrides
+    /// them, are interned. There is one such set per route with overrides per
+    /// reload, so the few that are leaked are bounded.
+    pub(crate) fn intern(&self, limits: Limits) -> &'static Limits {
+        let mut interned = self.limits.lock().expect("interned limits lock");
+        if let Some(existing) = interned.iter().copied().find(|existing| **existing == limits) {
+            return existing;
+        }
+
+        let leaked: &'static Limits = Box::leak(Box::new(limits));
+        interned.push(leaked);
+        leaked
+    }
+
+    pub(crate) fn current(&self) -> ConfigSnapshot {
+        self.current.read().expect("live config lock").clone()
+    }
+
+    /// Applies the reloadable parameters of `config`, returning the previous
+    /// and new configurations. Changes to other parameters are ignored with a
+    /// warning.
+    pub(crate) fn update(&self, config: Config) -> ConfigUpdated {
+        let mut current = self.current.write().expect("live config lock");
+        let previous = current.clone();
+
+        let ignored = [
+            ("address", previous.address != config.address),
+            ("workers", previous.workers != config.workers),
+            ("max_blocking_threads", previous.max_blocking_threads != config.max_blocking_threads),
+            ("keep_alive", previous.keep_alive != config.keep_alive),
+            ("server", previous.server != config.server),
+            ("unix", previous.unix != config.unix),
+            ("compression", previous.compression != config.compression),
+            ("protocols", previous.protocols != config.protocols),
+            ("proxy_protocol", previous.proxy_protocol != config.proxy_protocol),
+            ("listeners", previous.listeners != config.listeners),
+            ("tls", previous.tls_enabled() != config.tls_enabled()),
+            ("acme", previous.acme != config.acme),
+        ];
+
+        for &(name, _) in ignored.iter().filter(|(_, changed)| *changed) {
+            warn_!("Changes to '{}' take effect after a restart.", name);
+        }
+
+        let mut next = (*previous.config).clone();
+        next.log_level = config.log_level;
+        next.limits = config.limits;
+        next.extras = config.extras;
+
+        // Only the reloaded parameters take their provenance from `config`.
+        let extras = &next.extras;
+        let reloadable = |name: &str| ["log", "limits"].contains(&name) || extras.contains_key(name);
+        next.provenance.retain(|name, _| !reloadable(name));
+        for (name, source) in config.provenance {
+            if reloadable(&name) {
+                next.provenance.insert(name, source);
+            }
+        }
+
+        if next.log_level != previous.log_level {
+            logger::set_level(next.log_level);
+        }
+
+        *current = ConfigSnapshot::new(previous.version + 1, next);
+        ConfigUpdated { previous, current: current.clone() }
+    }
+}
+
+/// Reads the configuration for the environment of `config` from the file
+/// `config` was read from, resolving secret references with `secrets` and
+/// reapplying the command-line overrides in `args`.
+pub(crate) fn reread(config: &Config, secrets: &Secrets, args: &Args) -> Result<Config> {
+    let path = config.config_file_path.as_ref().ok_or(ConfigError::NotFound)?;
+    let mut full = FullConfig::read_from_with(path, secrets)?;
+    full.override_from_args(args, secrets)?;
+    secrets.warn_unknown();
+    full.active_env = config.environment;
+    Ok(full.take_active())
+}
+
+fn modified(path: Option<&Path>) -> Option<SystemTime> {
+    path?.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// Reloads the configuration of `rocket` whenever the process receives
+/// `SIGHUP` or the configuration file changes, until `rocket` is dropped.
+pub(crate) async fn watch(rocket: Weak<Rocket>) {
+    let path = match rocket.upgrade() {
+        Some(rocket) => rocket.config.config_file_path.clone(),
+        None => return,
+    };
+
+    let mut last_modified = modified(path.as_deref());
+    let mut hangup = hangup_signal();
+    loop {
+        let signaled = tokio::select! {
+            _ = tokio::time::delay_for(WATCH_INTERVAL) => false,
+            _ = next_hangup(&mut hangup) => true,
+        };
+
+        let now_modified = modified(path.as_deref());
+        if !signaled && now_modified == last_modified {
+            if rocket.strong_count() == 0 {
+                return;
+            }
+
+            continue;
+        }
+
+        last_modified = now_modified;
+        let rocket = match rocket.upgrade() {
+            Some(rocket) => rocket,
+            None => return,
+        };
+
+        info!("Reloading configuration.");
+        match rocket.cargo().reload_config().await {
+            Ok(update) => info_!("Configuration is now at version {}.", update.current.version()),
+            Err(e) => {
+                e.pretty_print();
+                warn_!("Failed to reload configuration.");
+                warn_!("The previous configuration remains active.");
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::hangup()) {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            warn!("Failed to enable reloading configuration on `SIGHUP`.");
+            info_!("Error: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup { }
+
+#[cfg(unix)]
+async fn next_hangup(hangup: &mut Hangup) {
+    if let Some(signal) = hangup {
+        if signal.recv().await.is_some() {
+            return;
+        }
+
+        *hangup = None;
+    }
+
+    futures::future::pending().await
+}
+
+#[cfg(not(unix))]
+async fn next_hangup(_: &mut Hangup) {
+    futures::future::pending().await
+}
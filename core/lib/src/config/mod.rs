@@ -33,16 +33,27 @@
 //!
 //! | name       | type           | description                                                 | examples                   |
 //! |------------|----------------|-------------------------------------------------------------|----------------------------|
-//! | address    | string         | ip address or host to listen on                             | `"localhost"`, `"1.2.3.4"` |
+//! | address    | string         | ip address, host, or `unix:{path}` to listen on             | `"localhost"`, `"1.2.3.4"` |
 //! | port       | integer        | port number to listen on                                    | `8000`, `80`               |
 //! | keep_alive | integer        | keep-alive timeout in seconds                               | `0` (disable), `10`        |
 //! | workers    | integer        | number of concurrent thread workers                         | `36`, `512`                |
+//! | max_blocking_threads | integer | maximum threads for blocking tasks, beyond `workers`  | `64`, `512`                |
 //! | log        | string         | max log level: `"off"`, `"normal"`, `"debug"`, `"critical"` | `"off"`, `"normal"`        |
 //! | secret_key | 256-bit base64 | secret key for private cookies                              | `"8Xui8SI..."` (44 chars)  |
 //! | tls        | table          | tls config table with two keys (`certs`, `key`)             | _see below_                |
 //! | tls.certs  | string         | path to certificate chain in PEM format                     | `"private/cert.pem"`       |
 //! | tls.key    | string         | path to private key for `tls.certs` in PEM format           | `"private/key.pem"`        |
-//! | limits     | table          | map from data type (string) to data limit (integer: bytes)  | `{ forms = 65536 }`        |
+//! | tls.client_auth | string    | client certificates: `"required"` or `"optional"`           | `"required"`               |
+//! | tls.ca_certs | string       | path to CA certificates for `tls.client_auth` in PEM format | `"private/ca.pem"`         |
+//! | tls.crl    | string         | path to a certificate revocation list in PEM or DER format  | `"private/crl.pem"`        |
+//! | acme       | table          | automatic certificates for the default listener via ACME    | _see below_                |
+//! | limits     | table          | map from data type (string) to data limit (bytes or size)   | `{ forms = "64KiB" }`      |
+//! | server     | table          | connection timeouts and limits                              | _see below_                |
+//! | unix       | table          | unix domain socket permissions and cleanup                  | _see below_                |
+//! | compression | table         | negotiated gzip and brotli compression of responses         | _see below_                |
+//! | protocols  | array          | HTTP protocols to serve: `"h2"`, `"http/1.1"`               | `["h2", "http/1.1"]`       |
+//! | proxy_protocol | boolean    | whether connections start with a PROXY protocol header      | `false`, `true`            |
+//! | listeners  | array          | additional addresses to listen on                           | _see below_                |
 //!
 //! ### Rocket.toml
 //!
@@ -67,6 +78,7 @@
 //! log = "normal"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
+//! server = { read_header_timeout = 0, write_timeout = 0, max_requests = 0 }
 //!
 //! [staging]
 //! address = "0.0.0.0"
@@ -76,6 +88,7 @@
 //! log = "normal"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
+//! server = { read_header_timeout = 0, write_timeout = 0, max_requests = 0 }
 //!
 //! [production]
 //! address = "0.0.0.0"
@@ -85,6 +98,7 @@
 //! log = "critical"
 //! secret_key = [randomly generated at launch]
 //! limits = { forms = 32768 }
+//! server = { read_header_timeout = 0, write_timeout = 0, max_requests = 0 }
 //! ```
 //!
 //! The `workers` and `secret_key` default parameters are computed by Rocket
@@ -124,6 +138,175 @@
 //! key = "/path/to/key.pem"
 //! ```
 //!
+//! By default, TLS is implemented by [rustls]. Compiling Rocket with the
+//! `native-tls` feature instead uses the platform's TLS library: OpenSSL on
+//! Linux, SChannel on Windows, and Security.framework on macOS. This allows
+//! deployments to use a FIPS-validated OpenSSL build or the operating system's
+//! trust store. With `native-tls`, the private key must be in PKCS#8 PEM format.
+//! The features are additive: if both are enabled, as when different crates in
+//! a dependency graph enable one each, `native-tls` takes precedence and the
+//! `tls` backend, including ACME, is unused.
+//!
+//! With the `tls` feature, clients can be required to authenticate by
+//! certificate via the `tls.client_auth`, `tls.ca_certs`, and `tls.crl`
+//! parameters. This is described in full in [`MutualTls`].
+//!
+//! ```toml
+//! [global.tls]
+//! certs = "/path/to/certs.pem"
+//! key = "/path/to/key.pem"
+//! client_auth = "required"
+//! ca_certs = "/path/to/ca.pem"
+//! ```
+//!
+//! Certificates are read at launch. To deploy renewed certificates without a
+//! restart, reread them via a [`TlsReloader`](crate::TlsReloader).
+//!
+//! Alternatively, with the `tls-acme` feature, the default listener obtains
+//! and renews its certificates automatically from an ACME certificate
+//! authority such as Let's Encrypt. This is described in full in
+//! [`AcmeConfig`].
+//!
+//! ```toml
+//! [global]
+//! port = 443
+//!
+//! [global.acme]
+//! domains = ["example.com"]
+//! contact = ["mailto:admin@example.com"]
+//! accept_terms = true
+//! ```
+//!
+//! [rustls]: https://github.com/ctz/rustls
+//!
+//! ### Runtime
+//!
+//! `#[launch]` runs the application on a multi-threaded tokio runtime with
+//! `workers` worker threads and up to `max_blocking_threads` additional threads
+//! for blocking tasks, as configured for the `Rocket` instance it launches,
+//! including one built with [`rocket::custom()`]. Both values must be greater
+//! than `0`. `#[rocket::main]` runs before any configuration is known and uses
+//! tokio's defaults instead. To control the runtime further, build one with
+//! [`Config::runtime()`] or by hand and run [`Rocket::launch()`] on it.
+//!
+//! [`rocket::custom()`]: crate::custom()
+//! [`Rocket::launch()`]: crate::Rocket::launch()
+//!
+//! ### Server Configuration
+//!
+//! The `server` table limits how long clients may hold connections open and
+//! how much concurrent work the server accepts before shedding load with `503
+//! Service Unavailable`. It also sets how long a shutdown waits for requests
+//! and connections to finish. It is described in full in [`ServerConfig`].
+//! The idle timeout of kept-alive connections is the `keep_alive` parameter.
+//!
+//! ```toml
+//! [global.server]
+//! read_header_timeout = 10  # seconds to send request headers; 0 disables
+//! read_timeout = 30         # seconds a request body read may stall; 0 disables
+//! write_timeout = 30        # seconds a response write may stall; 0 disables
+//! max_requests = 1000       # requests per connection; 0 is unlimited
+//! max_connections = 10000   # open connections; 0 is unlimited
+//! max_in_flight = 512       # requests handled at once; 0 is unlimited
+//! retry_after = 5           # `Retry-After` seconds of shed requests; 0 omits
+//! grace = 2                 # seconds to finish requests at shutdown; 0 waits
+//! mercy = 3                 # seconds more to close connections; 0 waits
+//! ```
+//!
+//! By default, `grace` and `mercy` are `0`: a shutdown waits for every request
+//! and connection to finish, however long that takes.
+//!
+//! ### Unix Domain Sockets
+//!
+//! An `address` of the form `unix:{path}` listens on a Unix domain socket
+//! instead of a TCP port, which lets an application sit behind a reverse proxy
+//! without opening a port. The `unix` table sets the socket's permissions and
+//! whether stale sockets are removed; it is described in full in
+//! [`UnixConfig`].
+//!
+//! ```toml
+//! [global]
+//! address = "unix:/run/my_app/app.sock"
+//! unix = { mode = 0o660, cleanup = true }
+//! ```
+//!
+//! ### Compression
+//!
+//! With the `compression` feature, Rocket can compress responses with gzip,
+//! and with the `brotli` feature also with Brotli, as negotiated with the
+//! client via `Accept-Encoding`. Compression is disabled by default; the
+//! `compression` table enables it and sets which responses are compressed. It
+//! is described in full in [`CompressionConfig`].
+//!
+//! ```toml
+//! [global.compression]
+//! enabled = true
+//! min_size = "1KiB"
+//! exclude = ["image/*", "video/*"]
+//! ```
+//!
+//! ### Protocols
+//!
+//! By default, Rocket serves HTTP/1.1 only. The `protocols` array sets the
+//! protocols served and their order of preference. Listing `h2` serves HTTP/2
+//! to TLS clients that negotiate it via ALPN; HTTP/2 isn't served over
+//! plaintext. The array is described in full in [`Protocol`]. With the
+//! `native-tls` feature, ALPN isn't offered.
+//!
+//! ```toml
+//! [global]
+//! protocols = ["h2", "http/1.1"]
+//! ```
+//!
+//! ### Listeners
+//!
+//! An application always listens on `address` and `port`. The `listeners`
+//! array adds further addresses, each served by the same application with its
+//! own name and, optionally, its own TLS configuration. Handlers can tell the
+//! listeners apart via the [`ListenerConfig`] request guard.
+//!
+//! ```toml
+//! [global]
+//! port = 80
+//!
+//! [[global.listeners]]
+//! name = "https"
+//! address = "0.0.0.0"
+//! port = 443
+//! tls = { certs = "/path/to/certs.pem", key = "/path/to/key.pem" }
+//!
+//! [[global.listeners]]
+//! name = "admin"
+//! address = "127.0.0.1"
+//! port = 9000
+//! ```
+//!
+//! ### PROXY Protocol
+//!
+//! Behind a TCP load balancer such as HAProxy, connections arrive from the
+//! balancer rather than from clients. With `proxy_protocol` enabled, for the
+//! default listener at the top level or for another listener in its
+//! `listeners` entry, every connection must start with a [PROXY protocol]
+//! header, version 1 or 2, in which the balancer relays the client's address.
+//! That address becomes the request's [`remote()`](crate::Request::remote())
+//! address and so its [`client_ip()`](crate::Request::client_ip()). Version 2
+//! headers may also relay the TLS details of connections the balancer
+//! terminated; all relayed details are available via the
+//! [`ProxyHeader`](crate::http::ProxyHeader) request guard.
+//!
+//! Connections that don't start with a valid header within the
+//! `server.read_header_timeout`, or within 10 seconds if it is disabled, are
+//! closed. Connections count against `server.max_connections` while their
+//! header is read, and those beyond it are closed without reading one. On
+//! listeners that serve TLS themselves, the header precedes the TLS handshake.
+//!
+//! ```toml
+//! [global]
+//! proxy_protocol = true
+//! ```
+//!
+//! [PROXY protocol]: https://www.haproxy.org/download/2.2/doc/proxy-protocol.txt
+//!
 //! ### Environment Variables
 //!
 //! All configuration parameters, including extras, can be overridden through
@@ -151,6 +334,63 @@
 //! ROCKET_DICT={key="abc",val=123}
 //! ```
 //!
+//! ### Command-Line Arguments
+//!
+//! Applications launched via
+//! [`Rocket::ignite_with_args()`](crate::Rocket::ignite_with_args()) can also
+//! be configured from the command line. Arguments take precedence over
+//! environment variables and select the environment with `--profile`:
+//!
+//! ```sh
+//! ./your_application --profile staging --port 8080 --limits.json 5MiB
+//! ```
+//!
+//! See [`Args`] for the full syntax.
+//!
+//! ### Reloading
+//!
+//! The `log` and `limits` parameters and all extras can be changed while an
+//! application is running. When the extra parameter `reload` is `true`, Rocket
+//! reloads them from the configuration file whenever the file changes and, on
+//! Unix, whenever the process receives `SIGHUP`:
+//!
+//! ```toml
+//! [global]
+//! reload = true
+//! ```
+//!
+//! Reloads can also be triggered programmatically via
+//! [`Cargo::reload_config()`](crate::Cargo::reload_config()) and
+//! [`Cargo::update_config()`](crate::Cargo::update_config()). Changes to other
+//! parameters take effect only after a restart. Handlers retrieve a consistent
+//! view of the current configuration via the [`ConfigSnapshot`] request guard,
+//! and fairings are notified of reloads via
+//! [`Fairing::on_config_update()`](crate::fairing::Fairing::on_config_update()).
+//!
+//! Reloads never change the `Config` an application launched with: the
+//! `Config` returned by [`Cargo::config()`](crate::Cargo::config()) and any
+//! copy of it made at launch keep their launch values. Code that should see
+//! reloaded values must read them from a fresh snapshot via
+//! [`Cargo::config_snapshot()`](crate::Cargo::config_snapshot()) or the
+//! `ConfigSnapshot` guard, or refresh its copy in `on_config_update`.
+//!
+//! ### Secrets
+//!
+//! Instead of a plaintext value, any string parameter, including
+//! `secret_key`, may reference a secret held by a [`SecretProvider`] as
+//! `${provider:key}`: `"${env:DATABASE_URL}"` or
+//! `"${file:/run/secrets/secret_key}"`. References are resolved when the
+//! configuration is read. Custom providers are registered via
+//! [`Rocket::ignite_with_secrets()`](crate::Rocket::ignite_with_secrets()).
+//!
+//! ### Effective Configuration
+//!
+//! At launch, Rocket logs every configuration parameter along with where its
+//! value came from when it isn't a default: `Rocket.toml`, an environment
+//! variable, or a command-line argument. The same information, with secrets
+//! redacted, is available to applications as an [`EffectiveConfig`] via
+//! [`Cargo::effective_config()`](crate::Cargo::effective_config()).
+//!
 //! ## Retrieving Configuration Parameters
 //!
 //! Configuration parameters for the currently active configuration environment
@@ -182,6 +422,24 @@
 //! # ;
 //! }
 //! ```
+//!
+//! ### Custom Sections
+//!
+//! Tables of related parameters, such as a `[global.database]` section, can
+//! be deserialized into a custom type via [`Config::extract()`]. Types that
+//! also implement [`Validate`] can be checked as they are extracted via
+//! [`Config::extract_validated()`], and [`AdHoc::config()`] manages a validated
+//! section, aborting launch with the path of every invalid field if it fails.
+//! Fields of a section that derives [`ConfigSection`] can declare a default
+//! value and an environment variable that overrides them.
+//!
+//! When `ROCKET_NESTED_ENV` is set to `true` or `1`, individual fields of any
+//! section can also be overridden with environment variables that separate
+//! the path to the field with double underscores, such as
+//! `ROCKET_DATABASE__POOL_SIZE=16`. Otherwise, such variables set a parameter
+//! named `database__pool_size`, as any other variable does.
+//!
+//! [`AdHoc::config()`]: crate::fairing::AdHoc::config()
 
 mod error;
 mod environment;
@@ -189,6 +447,15 @@ mod config;
 mod builder;
 mod toml_ext;
 mod custom_values;
+mod live;
+mod validate;
+mod secrets;
+mod server;
+mod compression;
+mod listener;
+mod tls;
+mod args;
+mod effective;
 
 use std::env;
 use std::fs::File;
@@ -203,6 +470,16 @@ pub use self::error::ConfigError;
 pub use self::environment::Environment;
 pub use self::config::Config;
 pub use self::builder::ConfigBuilder;
+pub use self::live::{ConfigSnapshot, ConfigUpdated};
+pub use self::validate::{ConfigSection, Validate, ValidationError};
+pub use self::secrets::{SecretProvider, Secrets, EnvVar, SecretFile, EnvFile};
+pub use self::server::{ServerConfig, UnixConfig, Protocol};
+pub use self::compression::CompressionConfig;
+pub use self::listener::ListenerConfig;
+pub use self::tls::{MutualTls, AcmeConfig, AcmeChallenge};
+pub use self::args::Args;
+pub use self::effective::{EffectiveConfig, EffectiveParam, Provenance};
+pub(crate) use self::live::{LiveConfig, reread, watch};
 pub use crate::logger::LoggingLevel;
 pub(crate) use self::toml_ext::LoggedValue;
 
@@ -218,7 +495,9 @@ const ENV_VAR_PREFIX: &str = "ROCKET_";
 
 const CODEGEN_DEBUG_ENV: &str = "ROCKET_CODEGEN_DEBUG";
 const CONFIG_FILE_ENV: &str = "ROCKET_CONFIG_FILE";
-const PREHANDLED_VARS: [&str; 4] = [CODEGEN_DEBUG_ENV, CONFIG_FILE_ENV, CONFIG_ENV, COLORS_ENV];
+const NESTED_ENV: &str = "ROCKET_NESTED_ENV";
+const PREHANDLED_VARS: [&str; 5] =
+    [CODEGEN_DEBUG_ENV, CONFIG_FILE_ENV, CONFIG_ENV, COLORS_ENV, NESTED_ENV];
 
 /// Wraps `std::result` with the error type of [`ConfigError`].
 pub type Result<T> = std::result::Result<T, ConfigError>;
@@ -234,6 +513,12 @@ impl FullConfig {
     /// Read the configuration from the `Rocket.toml` file. The file is searched
     /// for recursively up the tree, starting from the CWD.
     pub fn read_from(path: &Path) -> Result<FullConfig> {
+        FullConfig::read_from_with(path, &Secrets::new())
+    }
+
+    /// Like [`FullConfig::read_from()`] but resolves secret references with
+    /// the providers in `secrets`.
+    pub fn read_from_with(path: &Path, secrets: &Secrets) -> Result<FullConfig> {
         // Try to open the config file for reading.
         let mut handle = File::open(path).map_err(|_| ConfigError::IoError)?;
 
@@ -242,10 +527,10 @@ impl FullConfig {
         handle.read_to_string(&mut contents).map_err(|_| ConfigError::IoError)?;
 
         // Parse the config and return the result.
-        let mut config = FullConfig::parse(contents, path)?;
+        let mut config = FullConfig::parse_with(contents, path, secrets)?;
 
         // Override any config values with those from the environment.
-        config.override_from_env()?;
+        config.override_from_env(secrets)?;
 
         Ok(config)
     }
@@ -253,10 +538,11 @@ impl FullConfig {
     /// Return the default configuration for all environments and marks the
     /// active environment (from `CONFIG_ENV`) as active. Overrides the defaults
     /// with values from the `ROCKET_{PARAM}` environment variables. Doesn't
-    /// read any other sources.
-    pub fn env_default() -> Result<FullConfig> {
+    /// read any other sources. Resolves secret references with the providers in
+    /// `secrets`.
+    pub fn env_default_with(secrets: &Secrets) -> Result<FullConfig> {
         let mut config = Self::active_default_with_path(None)?;
-        config.override_from_env()?;
+        config.override_from_env(secrets)?;
         Ok(config)
     }
 
@@ -291,7 +577,7 @@ impl FullConfig {
     /// If the variable isn't set, Iteratively search for `CONFIG_FILENAME`
     /// starting at the current working directory and working up through its
     /// parents. Returns the path to the discovered file.
-    fn find_config_path() -> Result<PathBuf> {
+    pub(crate) fn find_config_path() -> Result<PathBuf> {
         if let Some(path) = env::var_os(CONFIG_FILE_ENV) {
             let config = Path::new(&path);
             if config.metadata().map_or(false, |m| m.is_file()) {
@@ -333,8 +619,12 @@ impl FullConfig {
     /// `self`, otherwise this function panics. Any existing values are
     /// overridden by those in `kvs`.
     fn set_from_table(&mut self, env: Environment, kvs: &Table) -> Result<()> {
+        let config = self.get_mut(env);
         for (key, value) in kvs {
-            self.get_mut(env).set_raw(key, value)?;
+            config.set_raw(key, value)?;
+            if let Some(ref path) = config.config_file_path {
+                config.provenance.insert(key.clone(), Provenance::File(path.clone()));
+            }
         }
 
         Ok(())
@@ -361,7 +651,8 @@ impl FullConfig {
     }
 
     // Override all environments with values from env variables if present.
-    fn override_from_env(&mut self) -> Result<()> {
+    fn override_from_env(&mut self, secrets: &Secrets) -> Result<()> {
+        let nested = env::var_os(NESTED_ENV).map_or(false, |v| v == "1" || v == "true");
         for (key, val) in env::vars() {
             if key.len() < ENV_VAR_PREFIX.len() {
                 continue
@@ -375,12 +666,37 @@ impl FullConfig {
             }
 
             // Parse the key and value and try to set the variable for all envs.
+            let source = Provenance::Env(key.clone());
             let key = key[ENV_VAR_PREFIX.len()..].to_lowercase();
-            let toml_val = match parse_simple_toml_value(&val) {
+            // Secret references, escaped or not, needn't be quoted.
+            let parsed = match val.starts_with("${") || val.starts_with("$${") {
+                true => Ok(Value::String(val.clone())),
+                false => parse_simple_toml_value(&val),
+            };
+
+            let mut toml_val = match parsed {
                 Ok(val) => val,
                 Err(e) => return Err(ConfigError::BadEnvVal(key, val, e))
             };
 
+            secrets.resolve(&mut toml_val)?;
+
+            // When opted into, keys like `section__field` override a field of
+            // an extra table. Otherwise, they're set as they are.
+            if nested && key.contains("__") {
+                let path: Vec<_> = key.split("__").collect();
+                for env in &Environment::ALL {
+                    let config = self.get_mut(*env);
+                    if let Err(e) = config.set_nested_extra(&path, toml_val.clone()) {
+                        return Err(ConfigError::BadEnvVal(key, val, e.to_string()));
+                    }
+
+                    config.provenance.insert(path[0].to_string(), source.clone());
+                }
+
+                continue;
+            }
+
             for env in &Environment::ALL {
                 match self.get_mut(*env).set_raw(&key, &toml_val) {
                     Err(ConfigError::BadType(_, exp, actual, _)) => {
@@ -388,7 +704,41 @@ impl FullConfig {
                         return Err(ConfigError::BadEnvVal(key, val, e))
                     }
                     Err(e) => return Err(e),
-                    Ok(_) => { /* move along */ }
+                    Ok(_) => {
+                        let config = self.get_mut(*env);
+                        config.provenance.insert(key.clone(), source.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Overrides all environments with the parameters in `args` and, if `args`
+    /// selects one, makes its environment the active one.
+    pub(crate) fn override_from_args(&mut self, args: &Args, secrets: &Secrets) -> Result<()> {
+        if let Some(env) = args.profile() {
+            self.active_env = env;
+        }
+
+        for (name, value) in args.overrides() {
+            let mut value = value.clone();
+            secrets.resolve(&mut value)?;
+
+            let path: Vec<_> = name.split('.').collect();
+            for env in &Environment::ALL {
+                match self.get_mut(*env).set_raw_path(&path, &value) {
+                    Err(ConfigError::BadType(_, exp, actual, _)) => {
+                        let arg = format!("--{}", name);
+                        let e = format!("expected {}, but found {}", exp, actual);
+                        return Err(ConfigError::BadArg(arg, e));
+                    }
+                    Err(e) => return Err(e),
+                    Ok(_) => {
+                        let source = Provenance::Arg(format!("--{}", name));
+                        self.get_mut(*env).provenance.insert(path[0].to_string(), source);
+                    }
                 }
             }
         }
@@ -398,15 +748,22 @@ impl FullConfig {
 
     /// Parses the configuration from the Rocket.toml file. Also overrides any
     /// values there with values from the environment.
+    #[cfg(test)]
     fn parse<S, P>(src: S, filename: P) -> Result<FullConfig>
         where S: Into<String>, P: AsRef<Path>
+    {
+        FullConfig::parse_with(src, filename, &Secrets::new())
+    }
+
+    fn parse_with<S, P>(src: S, filename: P, secrets: &Secrets) -> Result<FullConfig>
+        where S: Into<String>, P: AsRef<Path>
     {
         use self::ConfigError::ParseError;
 
         // Parse the source as TOML, if possible.
         let src = src.into();
         let path = filename.as_ref().to_path_buf();
-        let table = match src.parse::<toml::Value>() {
+        let mut table = match src.parse::<toml::Value>() {
             Ok(toml::Value::Table(table)) => table,
             Ok(value) => {
                 let err = format!("expected a table, found {}", value.type_str());
@@ -415,6 +772,9 @@ impl FullConfig {
             Err(e) => return Err(ParseError(src, path, e.to_string(), e.line_col()))
         };
 
+        // Replace secret references with the secrets they reference.
+        secrets.resolve_table(&mut table)?;
+
         // Create a config with the defaults; set the env to the active one.
         let mut config = FullConfig::active_default_with_path(Some(filename.as_ref()))?;
 
@@ -462,7 +822,7 @@ mod test {
     use std::sync::Mutex;
 
     use super::{Config, FullConfig, ConfigError, ConfigBuilder};
-    use super::{Environment, GLOBAL_ENV_NAME};
+    use super::{Environment, Secrets, ServerConfig, MutualTls, Value, GLOBAL_ENV_NAME};
     use super::environment::CONFIG_ENV;
     use super::Environment::*;
     use super::Result;
@@ -495,7 +855,7 @@ mod test {
     }
 
     fn env_default() -> Result<FullConfig>  {
-        FullConfig::env_default()
+        FullConfig::env_default_with(&Secrets::new())
     }
 
     fn default_config(env: Environment) -> ConfigBuilder {
@@ -732,6 +1092,102 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_mtls_config() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        assert!(FullConfig::parse(r#"
+            [development.tls]
+            certs = "some/path.pem"
+            key = "some/key.pem"
+            client_auth = "required"
+            ca_certs = "some/ca.pem"
+            crl = "some/crl.pem"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
+
+        assert!(FullConfig::parse(r#"
+            [development]
+            tls = { certs = "a.pem", key = "b.pem", client_auth = "optional", ca_certs = "c.pem" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_ok());
+
+        assert!(FullConfig::parse(r#"
+            [development]
+            tls = { certs = "a.pem", key = "b.pem", client_auth = "sometimes", ca_certs = "c.pem" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [development]
+            tls = { certs = "a.pem", key = "b.pem", client_auth = "required" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [development]
+            tls = { certs = "a.pem", key = "b.pem", ca_certs = "c.pem" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [development]
+            tls = { certs = "a.pem", key = "b.pem", crl = "d.pem" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        let mtls = MutualTls::required("ca.pem").crl("crl.pem");
+        assert_eq!(mtls.to_string(), "client_auth = required, ca_certs = ca.pem, crl = crl.pem");
+
+        let mut config = Config::development();
+        assert!(config.set_mtls(&MutualTls::optional("ca.pem")).is_err());
+    }
+
+    #[test]
+    fn test_acme_config() {
+        use super::{AcmeConfig, AcmeChallenge};
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage.acme]
+                          domains = ["example.com", "www.example.com"]
+                          contact = ["mailto:admin@example.com"]
+                          challenge = "http-01"
+                          cache = "/var/cache/acme"
+                          accept_terms = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).acme(
+                              AcmeConfig::new(vec!["example.com", "www.example.com"])
+                                  .contact("mailto:admin@example.com")
+                                  .challenge(AcmeChallenge::Http01)
+                                  .cache("/var/cache/acme")
+                                  .accept_terms(true)
+                          )
+                      });
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            acme = { domains = ["example.com"] }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            acme = { domains = [], accept_terms = true }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            acme = { domains = ["example.com"], accept_terms = true, challenge = "dns-01" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            acme = { domains = "example.com", accept_terms = true }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        let acme = AcmeConfig::new(vec!["example.com"]).directory("https://ca.test/dir");
+        assert_eq!(acme.to_string(), "example.com via https://ca.test/dir (tls-alpn-01)");
+    }
+
     #[test]
     fn test_good_port_values() {
         // Take the lock so changing the environment doesn't cause races.
@@ -852,6 +1308,43 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_max_blocking_threads() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          max_blocking_threads = 16
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).max_blocking_threads(16)
+                      });
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            max_blocking_threads = -1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            max_blocking_threads = "many"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            max_blocking_threads = 0
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            workers = 0
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(Config::build(Staging).max_blocking_threads(0).finalize().is_err());
+        assert!(Config::build(Staging).workers(0).finalize().is_err());
+    }
+
     #[test]
     fn test_good_keep_alives() {
         // Take the lock so changing the environment doesn't cause races.
@@ -914,6 +1407,415 @@ mod test {
         "#.to_string(), TEST_CONFIG_FILENAME).is_err());
     }
 
+    #[test]
+    fn test_server_config() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        let server = ServerConfig {
+            read_header_timeout: Some(3),
+            read_timeout: Some(20),
+            write_timeout: None,
+            max_requests: Some(100),
+            max_connections: Some(1000),
+            max_in_flight: Some(64),
+            retry_after: None,
+            grace: Some(5),
+            mercy: None,
+        };
+
+        check_config!(FullConfig::parse(r#"
+                          [stage.server]
+                          read_header_timeout = 3
+                          read_timeout = 20
+                          write_timeout = 0
+                          max_requests = 100
+                          max_connections = 1000
+                          max_in_flight = 64
+                          retry_after = 0
+                          grace = 5
+                          mercy = 0
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).server(server)
+                      });
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            server = { read_header_timeout = -1 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            server = { idle_timeout = 1 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            server = 10
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_unix_config() {
+        use super::UnixConfig;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          address = "unix:/run/app.sock"
+                          unix = { mode = 0o660 }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .address("unix:/run/app.sock")
+                              .unix(UnixConfig { mode: Some(0o660), cleanup: true })
+                      });
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          unix = { mode = "600", cleanup = false }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .unix(UnixConfig { mode: Some(0o600), cleanup: false })
+                      });
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            address = "unix:"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            unix = { mode = "rw-rw----" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            unix = { mode = 0o10000 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            unix = { owner = "www-data" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_compression_config() {
+        use super::CompressionConfig;
+        use crate::http::MediaType;
+        use crate::data::ToByteUnit;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          compression = { enabled = true }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .compression(CompressionConfig {
+                                  enabled: true,
+                                  ..CompressionConfig::default()
+                              })
+                      });
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          compression = { min_size = "4KiB", exclude = ["image/*"] }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .compression(CompressionConfig {
+                                  enabled: false,
+                                  min_size: 4.kibibytes(),
+                                  exclude: vec![MediaType::new("image", "*")],
+                                  ..CompressionConfig::default()
+                              })
+                      });
+
+        let brotli = FullConfig::parse(r#"
+            [stage]
+            compression = { brotli = true }
+        "#.to_string(), TEST_CONFIG_FILENAME);
+
+        assert_eq!(brotli.is_ok(), cfg!(feature = "brotli"));
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          compression = { brotli = false }
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .compression(CompressionConfig {
+                                  brotli: false,
+                                  ..CompressionConfig::default()
+                              })
+                      });
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            compression = { enabled = "yes" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            compression = { exclude = ["not a media type"] }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            compression = { level = 9 }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_protocols() {
+        use super::Protocol;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          protocols = ["http/1.1", "h2", "http/1.1"]
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .protocols(vec![Protocol::Http1, Protocol::H2])
+                      });
+
+        assert_eq!(default_config(Staging).unwrap().protocols, [Protocol::Http1]);
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            protocols = []
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            protocols = ["spdy/3"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            protocols = ["h2c"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            protocols = ["http/1.0"]
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            protocols = "h2"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_proxy_protocol() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [stage]
+                          proxy_protocol = true
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging).proxy_protocol(true)
+                      });
+
+        let config = FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "haproxy"
+            address = "127.0.0.1"
+            port = 9000
+            proxy_protocol = true
+
+            [[stage.listeners]]
+            name = "direct"
+            address = "127.0.0.1"
+            port = 9001
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap();
+
+        let listeners = config.active().listener_configs();
+        assert!(!listeners[0].proxy_protocol);
+        assert!(listeners[1].proxy_protocol);
+        assert!(!listeners[2].proxy_protocol);
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            proxy_protocol = 1
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_listeners() {
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::set_var(CONFIG_ENV, "stage");
+
+        check_config!(FullConfig::parse(r#"
+                          [[stage.listeners]]
+                          name = "admin"
+                          address = "127.0.0.1"
+                          port = 9000
+
+                          [[stage.listeners]]
+                          name = "public"
+                          address = "0.0.0.0"
+                          port = 8080
+                      "#.to_string(), TEST_CONFIG_FILENAME), {
+                          default_config(Staging)
+                              .listener("admin", "127.0.0.1", 9000)
+                              .listener("public", "0.0.0.0", 8080)
+                      });
+
+        let config = FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "https"
+            address = "0.0.0.0"
+            port = 8443
+            tls = { certs = "certs.pem", key = "key.pem" }
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap();
+
+        let listeners = config.active().listener_configs();
+        assert_eq!(listeners.len(), 2);
+        assert_eq!(listeners[0].name, "default");
+        assert_eq!(listeners[1].name, "https");
+        assert_eq!(listeners[1].port, 8443);
+
+        assert!(FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "admin"
+            address = "127.0.0.1"
+            port = 9000
+
+            [[stage.listeners]]
+            name = "admin"
+            address = "127.0.0.1"
+            port = 9001
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "default"
+            address = "127.0.0.1"
+            port = 9000
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "admin"
+            port = 9000
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "admin"
+            address = "127.0.0.1"
+            port = 9000
+            proxy_protocol = "yes"
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [[stage.listeners]]
+            name = "admin"
+            address = "127.0.0.1"
+            port = 9000
+            backlog = 10
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+
+        assert!(FullConfig::parse(r#"
+            [stage]
+            listeners = { name = "admin" }
+        "#.to_string(), TEST_CONFIG_FILENAME).is_err());
+    }
+
+    #[test]
+    fn test_command_line_args() {
+        use crate::data::ToByteUnit;
+        use super::Args;
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+
+        let mut config = FullConfig::parse(r#"
+            [global]
+            port = 7000
+            limits = { forms = "1KiB", json = 1024 }
+        "#.to_string(), TEST_CONFIG_FILENAME).unwrap();
+
+        let args = Args::parse(&[
+            "--profile", "prod", "--port=9000", "--limits.json", "5MiB",
+            "--server.max-requests", "10", "--db.pool.size", "4", "--reload",
+            "--", "--ignored"
+        ]).unwrap();
+
+        config.override_from_args(&args, &Secrets::new()).unwrap();
+        assert_eq!(config.active_env, Production);
+
+        let active = config.active();
+        assert_eq!(active.port, 9000);
+        assert_eq!(active.limits.get("forms"), Some(1.kibibytes()));
+        assert_eq!(active.limits.get("json"), Some(5.mebibytes()));
+        assert_eq!(active.server.max_requests, Some(10));
+        assert_eq!(active.get_bool("reload"), Ok(true));
+        assert_eq!(active.get_table("db").unwrap()["pool"]["size"], Value::Integer(4));
+        assert!(active.get_extra("ignored").is_err());
+
+        for bad in &[&["stray"][..], &["--profile", "nowhere"], &["--port", "[1"]] {
+            assert!(Args::parse(*bad).is_err());
+        }
+
+        let args = Args::parse(&["--port", "seventy"]).unwrap();
+        assert!(config.override_from_args(&args, &Secrets::new()).is_err());
+    }
+
+    #[test]
+    fn test_effective_config() {
+        use std::path::PathBuf;
+        use super::{Args, EffectiveConfig, Provenance};
+
+        // Take the lock so changing the environment doesn't cause races.
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        env::remove_var(CONFIG_ENV);
+        env::set_var("ROCKET_WORKERS", "3");
+        env::set_var("ROCKET_TOKEN", "${env:EFFECTIVE_CONFIG_TEST_TOKEN}");
+        env::set_var("EFFECTIVE_CONFIG_TEST_TOKEN", "hunter2");
+
+        let secrets = Secrets::new();
+        let mut config = FullConfig::parse_with(r#"
+            [development]
+            port = 7000
+            name = "app"
+        "#, TEST_CONFIG_FILENAME, &secrets).unwrap();
+
+        config.override_from_env(&secrets).unwrap();
+        let args = Args::parse(&["--address", "127.0.0.1"]).unwrap();
+        config.override_from_args(&args, &secrets).unwrap();
+
+        env::remove_var("ROCKET_WORKERS");
+        env::remove_var("ROCKET_TOKEN");
+        env::remove_var("EFFECTIVE_CONFIG_TEST_TOKEN");
+
+        let effective = EffectiveConfig::new(config.active(), &secrets);
+        let source = |name| effective.get(name).unwrap().provenance.clone();
+        assert_eq!(source("port"), Provenance::File(PathBuf::from(TEST_CONFIG_FILENAME)));
+        assert_eq!(source("name"), Provenance::File(PathBuf::from(TEST_CONFIG_FILENAME)));
+        assert_eq!(source("workers"), Provenance::Env("ROCKET_WORKERS".into()));
+        assert_eq!(source("address"), Provenance::Arg("--address".into()));
+        assert_eq!(source("log"), Provenance::Default);
+
+        assert_eq!(effective.get("workers").unwrap().value, "3");
+        assert_eq!(effective.get("token").unwrap().value, "[secret]");
+        assert!(effective.get("token").unwrap().extra);
+        assert!(!effective.to_string().contains("hunter2"));
+    }
+
     #[test]
     fn test_good_log_levels() {
         // Take the lock so changing the environment doesn't cause races.
@@ -1142,7 +2044,7 @@ mod test {
             env::set_var(format!("ROCKET_{}", key), val);
 
             let mut r = FullConfig::parse(toml, TEST_CONFIG_FILENAME).unwrap();
-            r.override_from_env().unwrap();
+            r.override_from_env(&Secrets::new()).unwrap();
             check_value(&*key.to_lowercase(), val, r.active());
 
             // And non-active configs.
@@ -1156,4 +2058,34 @@ mod test {
             env::remove_var(format!("ROCKET_{}", key))
         }
     }
+
+    #[test]
+    fn test_secret_references() {
+        let _env_lock = ENV_LOCK.lock().unwrap();
+        let secrets = Secrets::new();
+        env::set_var("ROCKET_TEST_SECRET_REFERENCE", "hunter2");
+
+        let config = FullConfig::parse_with(r#"
+            [development]
+            password = "${env:ROCKET_TEST_SECRET_REFERENCE}"
+            nested = { passwords = ["${env:ROCKET_TEST_SECRET_REFERENCE}", "plain"] }
+        "#, TEST_CONFIG_FILENAME, &secrets).unwrap();
+
+        let dev = config.get(Development);
+        assert_eq!(dev.get_str("password"), Ok("hunter2"));
+        let nested = dev.get_table("nested").unwrap()["passwords"].as_array().unwrap();
+        assert_eq!(nested[0].as_str(), Some("hunter2"));
+        assert_eq!(nested[1].as_str(), Some("plain"));
+        assert!(secrets.conceals(dev.get_extra("password").unwrap()));
+        assert!(!secrets.conceals(&Value::String("plain".into())));
+
+        let missing = FullConfig::parse_with(r#"
+            [development]
+            password = "${vault:rocket/password}"
+        "#, TEST_CONFIG_FILENAME, &secrets);
+
+        let reference = "${vault:rocket/password}".to_string();
+        assert_eq!(missing.unwrap_err(), ConfigError::BadSecret(reference, String::new()));
+        env::remove_var("ROCKET_TEST_SECRET_REFERENCE");
+    }
 }
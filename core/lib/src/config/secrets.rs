@@ -0,0 +1,302 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::config::{ConfigError, Result, Table, Value};
+
+/// A source of secret configuration values.
+///
+/// Instead of storing secrets such as the `secret_key` or database passwords
+/// in plaintext in `Rocket.toml`, a configuration value can _reference_ a
+/// secret held by a provider. A reference is a string of the form
+/// `${provider:key}`:
+///
+/// ```toml
+/// [production]
+/// secret_key = "${file:/run/secrets/rocket_secret_key}"
+/// database_url = "${env:DATABASE_URL}"
+/// ```
+///
+/// References are resolved when the configuration is read, before any
+/// parameter is parsed, by asking the provider named `provider` for the secret
+/// named `key`. References may appear anywhere a string may, including in
+/// nested tables and arrays and in `ROCKET_{PARAM}` environment variables. A
+/// reference to a known provider that cannot be resolved aborts launch. A
+/// string naming an unknown provider is left as it is, and a warning is logged
+/// at launch.
+///
+/// To use a string of the form `${provider:key}` literally, escape it with an
+/// extra `$`: `"$${env:HOME}"` is the string `"${env:HOME}"`.
+///
+/// The providers [`EnvVar`] and [`SecretFile`] are always available. Further
+/// providers, such as [`EnvFile`] or clients for secret stores like HashiCorp
+/// Vault or AWS Secrets Manager, are registered via [`Secrets::provider()`] and
+/// [`Rocket::ignite_with_secrets()`].
+///
+/// [`Rocket::ignite_with_secrets()`]: crate::Rocket::ignite_with_secrets()
+///
+/// # Example
+///
+/// A provider that serves secrets from an in-memory map:
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use rocket::config::{SecretProvider, Secrets};
+///
+/// struct Static(HashMap<&'static str, &'static str>);
+///
+/// impl SecretProvider for Static {
+///     fn name(&self) -> &str {
+///         "static"
+///     }
+///
+///     fn resolve(&self, key: &str) -> Result<String, String> {
+///         self.0.get(key)
+///             .map(|value| value.to_string())
+///             .ok_or_else(|| format!("no secret named '{}'", key))
+///     }
+/// }
+///
+/// let mut secrets = HashMap::new();
+/// secrets.insert("db_password", "hunter2");
+///
+/// # if false {
+/// rocket::Rocket::ignite_with_secrets(Secrets::new().provider(Static(secrets)))
+/// # ;
+/// # }
+/// ```
+pub trait SecretProvider: Send + Sync + 'static {
+    /// The name of this provider, used as `provider` in `${provider:key}`
+    /// references.
+    fn name(&self) -> &str;
+
+    /// Returns the secret named `key`, or a description of why it could not be
+    /// retrieved.
+    fn resolve(&self, key: &str) -> std::result::Result<String, String>;
+}
+
+/// A provider named `env` that resolves secrets from environment variables:
+/// `${env:DATABASE_URL}` is the value of the `DATABASE_URL` variable.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EnvVar;
+
+impl SecretProvider for EnvVar {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn resolve(&self, key: &str) -> std::result::Result<String, String> {
+        std::env::var(key).map_err(|e| format!("{}: {}", key, e))
+    }
+}
+
+/// A provider named `file` that resolves secrets from the contents of files,
+/// such as those mounted by Docker or Kubernetes: `${file:/run/secrets/key}` is
+/// the contents of `/run/secrets/key` without a trailing newline.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SecretFile;
+
+impl SecretProvider for SecretFile {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn resolve(&self, key: &str) -> std::result::Result<String, String> {
+        let contents = fs::read_to_string(key).map_err(|e| format!("{}: {}", key, e))?;
+        Ok(contents.trim_end_matches(|c| c == '\n' || c == '\r').to_string())
+    }
+}
+
+/// A provider named `env_file` that resolves secrets from a `.env` file of
+/// `KEY=VALUE` lines: `${env_file:DATABASE_URL}` is the value of the
+/// `DATABASE_URL` line. Blank lines, lines starting with `#`, and a leading
+/// `export` are ignored; values may be quoted.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{EnvFile, Secrets};
+///
+/// let secrets = Secrets::new().provider(EnvFile::new(".env"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EnvFile {
+    path: PathBuf,
+}
+
+impl EnvFile {
+    /// Returns a provider that reads secrets from the file at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> EnvFile {
+        EnvFile { path: path.into() }
+    }
+}
+
+impl SecretProvider for EnvFile {
+    fn name(&self) -> &str {
+        "env_file"
+    }
+
+    fn resolve(&self, key: &str) -> std::result::Result<String, String> {
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let mut parts = line.splitn(2, '=');
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                if name.trim() == key {
+                    let value = value.trim();
+                    let unquoted = value.strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+                        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                        .unwrap_or(value);
+
+                    return Ok(unquoted.to_string());
+                }
+            }
+        }
+
+        Err(format!("'{}' is not set in {}", key, self.path.display()))
+    }
+}
+
+/// The set of [`SecretProvider`]s used to resolve secret references in the
+/// configuration.
+///
+/// A new `Secrets` contains the [`EnvVar`] and [`SecretFile`] providers. When
+/// two providers have the same name, the one added last is used.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{EnvFile, Secrets};
+///
+/// # if false {
+/// let secrets = Secrets::new().provider(EnvFile::new("/etc/my_app/.env"));
+/// rocket::Rocket::ignite_with_secrets(secrets)
+/// # ;
+/// # }
+/// ```
+pub struct Secrets {
+    providers: Vec<Box<dyn SecretProvider>>,
+    /// The secrets resolved so far, so they can be kept out of logs.
+    resolved: Mutex<Vec<String>>,
+    /// The references to unknown providers found so far, warned about at
+    /// launch.
+    unknown: Mutex<Vec<String>>,
+}
+
+impl Default for Secrets {
+    fn default() -> Self {
+        Secrets::new()
+    }
+}
+
+impl Secrets {
+    /// Returns a set containing the [`EnvVar`] and [`SecretFile`] providers.
+    pub fn new() -> Secrets {
+        Secrets {
+            providers: vec![Box::new(EnvVar), Box::new(SecretFile)],
+            resolved: Mutex::new(vec![]),
+            unknown: Mutex::new(vec![]),
+        }
+    }
+
+    /// Adds `provider` to the set.
+    pub fn provider<P: SecretProvider>(mut self, provider: P) -> Secrets {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Returns the secret referenced by `reference` if it is a reference of
+    /// the form `${provider:key}` to a known provider, the unescaped string if
+    /// it is escaped as `$${...}`, and `None` otherwise.
+    fn resolve_reference(&self, reference: &str) -> Option<Result<String>> {
+        if reference.starts_with("$${") {
+            return Some(Ok(reference[1..].to_string()));
+        }
+
+        let inner = reference.strip_prefix("${")?.strip_suffix('}')?;
+        let mut parts = inner.splitn(2, ':');
+        let (name, key) = (parts.next()?, parts.next()?);
+        let provider = match self.providers.iter().rev().find(|p| p.name() == name) {
+            Some(provider) => provider,
+            None => {
+                self.unknown.lock().expect("unknown secrets lock").push(reference.into());
+                return None;
+            }
+        };
+
+        let result = provider.resolve(key)
+            .map_err(|e| ConfigError::BadSecret(reference.into(), e))
+            .map(|secret| {
+                self.resolved.lock().expect("resolved secrets lock").push(secret.clone());
+                secret
+            });
+
+        Some(result)
+    }
+
+    /// Logs a warning for every string that looked like a reference to an
+    /// unknown provider and was left as it is.
+    pub(crate) fn warn_unknown(&self) {
+        for reference in self.unknown.lock().expect("unknown secrets lock").drain(..) {
+            warn!("'{}' names an unknown secret provider and is used as-is.", reference);
+            info_!("escape it as '${}' to silence this warning", reference);
+        }
+    }
+
+    /// Replaces every secret reference in `value` with the secret it
+    /// references.
+    pub(crate) fn resolve(&self, value: &mut Value) -> Result<()> {
+        match value {
+            Value::String(string) => {
+                if let Some(secret) = self.resolve_reference(string) {
+                    *string = secret?;
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter_mut() {
+                    self.resolve(value)?;
+                }
+            }
+            Value::Table(table) => self.resolve_table(table)?,
+            _ => { /* no references in other values */ }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if `value` contains a secret resolved by `self`.
+    pub(crate) fn conceals(&self, value: &Value) -> bool {
+        match value {
+            Value::String(string) => {
+                self.resolved.lock().expect("resolved secrets lock").contains(string)
+            }
+            Value::Array(array) => array.iter().any(|v| self.conceals(v)),
+            Value::Table(table) => table.iter().any(|(_, v)| self.conceals(v)),
+            _ => false,
+        }
+    }
+
+    /// Replaces every secret reference in `table` with the secret it
+    /// references.
+    pub(crate) fn resolve_table(&self, table: &mut Table) -> Result<()> {
+        for (_, value) in table.iter_mut() {
+            self.resolve(value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.providers.iter().map(|p| p.name()).collect();
+        f.debug_struct("Secrets").field("providers", &names).finish()
+    }
+}
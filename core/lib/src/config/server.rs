@@ -0,0 +1,324 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::Request;
+use crate::http::private::Timeouts;
+use crate::request::{self, FromRequest};
+
+/// Connection-level settings of the HTTP server: the `[server]` table.
+///
+/// Each timeout is a number of seconds; a timeout of `None` (set as `0` in
+/// configuration sources) is not enforced. Together with the `keep_alive`
+/// parameter, which closes connections that are idle between requests, these
+/// settings protect an application from clients that hold connections open
+/// by sending or receiving data very slowly.
+///
+/// | name                | default | description                                        |
+/// |---------------------|---------|----------------------------------------------------|
+/// | read_header_timeout | `0`     | time allowed to send the headers of a request      |
+/// | read_timeout        | `0`     | time a read of a request body may stall            |
+/// | write_timeout       | `0`     | time a write to the client may stall               |
+/// | max_requests        | `0`     | requests served per connection (`0` is unlimited)  |
+/// | max_connections     | `0`     | concurrently open connections (`0` is unlimited)   |
+/// | max_in_flight       | `0`     | requests handled concurrently (`0` is unlimited)   |
+/// | retry_after         | `5`     | `Retry-After` of shed requests (`0` omits it)      |
+/// | grace               | `0`     | time to finish requests (`0` waits indefinitely)   |
+/// | mercy               | `0`     | then to close connections (`0` waits indefinitely) |
+///
+/// The timeouts are disabled by default. The `keep_alive` timeout also
+/// applies to a new connection until it starts its first request.
+///
+/// Requests beyond `max_connections` or `max_in_flight` are shed rather than
+/// queued: they are answered immediately with `503 Service Unavailable`. Both
+/// limits apply across all listeners. The current counts are reported by
+/// [`Load`](crate::Load).
+///
+/// Once a [shutdown](crate::Shutdown) begins, Rocket stops accepting
+/// connections and asks clients to close the ones that are open. By default,
+/// it then waits for every request and connection to finish. With `grace`
+/// set, requests in flight have `grace` seconds to produce a response; those
+/// that don't are answered with `503 Service Unavailable` and their handlers
+/// are cancelled. With `mercy` set, connections still open `mercy` seconds
+/// after that, such as those streaming a response, are closed forcibly.
+///
+/// ```toml
+/// [global.server]
+/// read_header_timeout = 5
+/// read_timeout = 30
+/// write_timeout = 60
+/// max_requests = 1000
+/// max_connections = 10000
+/// max_in_flight = 512
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment, ServerConfig};
+///
+/// let server = ServerConfig { max_requests: Some(100), ..ServerConfig::default() };
+/// let config = Config::build(Environment::Staging)
+///     .server(server)
+///     .unwrap();
+///
+/// assert_eq!(config.server.read_header_timeout, None);
+/// assert_eq!(config.server.max_requests, Some(100));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// Seconds a client may take to send the headers of a request, measured
+    /// from the first byte of the request, or from the opening of the
+    /// connection for its first request.
+    pub read_header_timeout: Option<u32>,
+    /// Seconds a read of a request body may stall, measured from the end of
+    /// the request's headers or the last read until the response begins, before
+    /// the connection is closed. Handlers that take longer than this to respond
+    /// without reading the body may see their connection closed.
+    pub read_timeout: Option<u32>,
+    /// Seconds a write of a response may stall before the connection is
+    /// closed.
+    pub write_timeout: Option<u32>,
+    /// The maximum number of requests served on one connection before it is
+    /// closed.
+    pub max_requests: Option<u32>,
+    /// The maximum number of connections open at once. Every request on a
+    /// connection opened beyond the limit is shed, and the connection is
+    /// closed.
+    pub max_connections: Option<u32>,
+    /// The maximum number of requests handled at once. Requests beyond the
+    /// limit are shed.
+    pub max_in_flight: Option<u32>,
+    /// Seconds after which clients are asked to retry shed requests.
+    pub retry_after: Option<u32>,
+    /// Seconds requests in flight have to produce a response once a shutdown
+    /// begins.
+    pub grace: Option<u32>,
+    /// Seconds connections have to close after `grace` before they are closed
+    /// forcibly.
+    pub mercy: Option<u32>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            read_header_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            max_requests: None,
+            max_connections: None,
+            max_in_flight: None,
+            retry_after: Some(5),
+            grace: None,
+            mercy: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The timeouts to enforce on connections given the idle `keep_alive`
+    /// timeout.
+    pub(crate) fn timeouts(&self, keep_alive: Option<u32>) -> Timeouts {
+        let secs = |s: Option<u32>| s.map(|s| Duration::from_secs(s as u64));
+        Timeouts {
+            read_header: secs(self.read_header_timeout),
+            idle: secs(keep_alive),
+            read: secs(self.read_timeout),
+            write: secs(self.write_timeout),
+        }
+    }
+}
+
+impl fmt::Display for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn secs(f: &mut fmt::Formatter<'_>, name: &str, value: Option<u32>) -> fmt::Result {
+            match value {
+                Some(v) => write!(f, "{} = {}s", name, v),
+                None => write!(f, "{} = disabled", name),
+            }
+        }
+
+        fn max(f: &mut fmt::Formatter<'_>, name: &str, value: Option<u32>) -> fmt::Result {
+            match value {
+                Some(v) => write!(f, "{} = {}", name, v),
+                None => write!(f, "{} = unlimited", name),
+            }
+        }
+
+        secs(f, "read_header_timeout", self.read_header_timeout)?;
+        secs(f, ", read_timeout", self.read_timeout)?;
+        secs(f, ", write_timeout", self.write_timeout)?;
+        max(f, ", max_requests", self.max_requests)?;
+        max(f, ", max_connections", self.max_connections)?;
+        max(f, ", max_in_flight", self.max_in_flight)?;
+        secs(f, ", retry_after", self.retry_after)?;
+        secs(f, ", grace", self.grace)?;
+        secs(f, ", mercy", self.mercy)
+    }
+}
+
+/// Settings for Unix domain sockets: the `[unix]` table.
+///
+/// Rocket listens on a Unix domain socket instead of a TCP port when `address`,
+/// or the address of a listener, has the form `unix:{path}`. This lets an
+/// application sit behind a reverse proxy such as nginx or HAProxy without
+/// opening a TCP port. The `port` of such a listener is ignored. These settings
+/// apply to every socket Rocket creates.
+///
+/// | name    | default | description                                                   |
+/// |---------|---------|---------------------------------------------------------------|
+/// | mode    | none    | permissions of the socket file, such as `0o660`               |
+/// | cleanup | `true`  | remove a stale socket file at launch and the socket at exit   |
+///
+/// Without a `mode`, the socket file's permissions are determined by the
+/// process's umask. `mode` may also be given as an octal string such as
+/// `"660"`. With a `mode`, the socket is bound in a private directory next to
+/// the socket file and only appears at its path once its mode is set. With
+/// `cleanup`, only files that are sockets are ever removed, and a socket that
+/// another server is still accepting connections on fails the launch instead.
+///
+/// ```toml
+/// [global]
+/// address = "unix:/run/my_app/app.sock"
+/// unix = { mode = 0o660, cleanup = true }
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Config, Environment, UnixConfig};
+///
+/// let config = Config::build(Environment::Staging)
+///     .address("unix:/run/my_app/app.sock")
+///     .unix(UnixConfig { mode: Some(0o660), ..UnixConfig::default() })
+///     .unwrap();
+///
+/// assert_eq!(config.unix.mode, Some(0o660));
+/// assert!(config.unix.cleanup);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnixConfig {
+    /// The permissions to set on socket files, if any.
+    pub mode: Option<u32>,
+    /// Whether to remove stale socket files before binding and socket files
+    /// after shutting down.
+    pub cleanup: bool,
+}
+
+impl Default for UnixConfig {
+    fn default() -> Self {
+        UnixConfig { mode: None, cleanup: true }
+    }
+}
+
+impl fmt::Display for UnixConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            Some(mode) => write!(f, "mode = {:#o}", mode)?,
+            None => write!(f, "mode = umask")?,
+        }
+
+        write!(f, ", cleanup = {}", self.cleanup)
+    }
+}
+
+/// An HTTP protocol an application may serve: an entry of the `protocols`
+/// array.
+///
+/// `protocols` lists the protocols an application serves in order of
+/// preference. TLS listeners offer the listed protocols to clients via ALPN in
+/// that order. HTTP/2 is only served over TLS: plaintext listeners serve
+/// HTTP/1.1 regardless of `protocols`. By default, only HTTP/1.1 is served;
+/// list `h2` to serve HTTP/2 as well.
+///
+/// ```toml
+/// [global]
+/// # Also serve HTTP/2 to TLS clients that prefer it.
+/// protocols = ["h2", "http/1.1"]
+/// ```
+///
+/// Handlers retrieve the protocol a request arrived over via the `Protocol`
+/// request guard or [`Request::protocol()`](crate::Request::protocol()).
+/// Requests made with HTTP/1.0 arrive over [`Protocol::Http10`], which is
+/// served whenever `http/1.1` is and can't be configured on its own. Requests
+/// dispatched via a [local `Client`](crate::local) didn't arrive over any
+/// protocol; the guard forwards them.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::config::{Config, Environment, Protocol};
+///
+/// #[get("/")]
+/// fn index(protocol: Protocol) -> String {
+///     format!("Served over {}.", protocol)
+/// }
+///
+/// let config = Config::build(Environment::Staging)
+///     .protocols(vec![Protocol::H2, Protocol::Http1])
+///     .unwrap();
+///
+/// assert_eq!(config.protocols, [Protocol::H2, Protocol::Http1]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// HTTP/1.1: `http/1.1`.
+    Http1,
+    /// HTTP/1.0: `http/1.0`. Only reported for requests; served whenever
+    /// `http/1.1` is.
+    Http10,
+    /// HTTP/2 over TLS: `h2`.
+    H2,
+}
+
+impl Protocol {
+    /// The protocols served by default: HTTP/1.1 only.
+    pub(crate) const DEFAULT: &'static [Protocol] = &[Protocol::Http1];
+
+    /// The ALPN protocol IDs to offer TLS clients given the configured
+    /// `protocols`, in order of preference.
+    pub(crate) fn alpn(protocols: &[Protocol]) -> Vec<Vec<u8>> {
+        protocols.iter()
+            .map(|p| p.as_str().as_bytes().to_vec())
+            .collect()
+    }
+
+    /// The name of the protocol, as used in configuration and ALPN.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Http1 => "http/1.1",
+            Protocol::Http10 => "http/1.0",
+            Protocol::H2 => "h2",
+        }
+    }
+}
+
+impl std::str::FromStr for Protocol {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Protocol, ()> {
+        match s {
+            "http/1.1" => Ok(Protocol::Http1),
+            "h2" => Ok(Protocol::H2),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Protocol {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match req.protocol() {
+            Some(protocol) => request::Outcome::Success(protocol),
+            None => request::Outcome::Forward(()),
+        }
+    }
+}
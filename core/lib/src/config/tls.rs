@@ -0,0 +1,253 @@
+use std::fmt;
+
+/// Client certificate authentication, or mutual TLS: the `client_auth`,
+/// `ca_certs`, and `crl` keys of a `tls` table.
+///
+/// When enabled, the TLS listener asks clients for a certificate during the
+/// handshake and verifies that it chains to one of the certificates in
+/// `ca_certs`. With `client_auth = "required"`, clients that don't present a
+/// valid certificate are refused; with `client_auth = "optional"`, they are
+/// served without one. Certificates revoked by the certificate revocation
+/// list at `crl`, if any, are refused either way. The CRL must be signed by one
+/// of the authorities in `ca_certs`, and is refused at launch, or on reload, if
+/// it's past its next update. Once a running server's CRL is past its next
+/// update, every client certificate is refused until the CRL is reloaded.
+///
+/// Handlers read the certificates a client authenticated with via the
+/// [`PeerCertificates`](crate::request::PeerCertificates) request guard.
+///
+/// ```toml
+/// [global.tls]
+/// certs = "private/cert.pem"
+/// key = "private/key.pem"
+/// client_auth = "required"
+/// ca_certs = "private/ca.pem"
+/// crl = "private/crl.pem"
+/// ```
+///
+/// Mutual TLS is only supported by the `tls` backend. Configuring it with the
+/// `native-tls` feature enabled is an error.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rocket::config::{Config, Environment, MutualTls};
+///
+/// let mtls = MutualTls::required("/etc/ssl/ca.pem").crl("/etc/ssl/crl.pem");
+/// let config = Config::build(Environment::Staging)
+///     .tls("/etc/ssl/cert.pem", "/etc/ssl/key.pem")
+///     .mtls(mtls)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutualTls {
+    /// Whether clients must present a certificate.
+    pub mandatory: bool,
+    /// Path to the PEM file of certificate authorities client certificates
+    /// must chain to.
+    pub ca_certs: String,
+    /// Path to a PEM or DER encoded certificate revocation list, if any.
+    pub crl: Option<String>,
+}
+
+impl MutualTls {
+    /// Requires clients to present a certificate signed by one of the
+    /// authorities in `ca_certs`.
+    pub fn required<P: Into<String>>(ca_certs: P) -> MutualTls {
+        MutualTls { mandatory: true, ca_certs: ca_certs.into(), crl: None }
+    }
+
+    /// Verifies the certificates clients present against the authorities in
+    /// `ca_certs`, but also serves clients that present none.
+    pub fn optional<P: Into<String>>(ca_certs: P) -> MutualTls {
+        MutualTls { mandatory: false, ca_certs: ca_certs.into(), crl: None }
+    }
+
+    /// Refuses the certificates revoked by the list at `crl`.
+    pub fn crl<P: Into<String>>(mut self, crl: P) -> MutualTls {
+        self.crl = Some(crl.into());
+        self
+    }
+}
+
+impl fmt::Display for MutualTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.mandatory { "required" } else { "optional" };
+        write!(f, "client_auth = {}, ca_certs = {}", mode, self.ca_certs)?;
+        if let Some(ref crl) = self.crl {
+            write!(f, ", crl = {}", crl)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Automatic certificates via ACME: the `acme` table.
+///
+/// With the `tls-acme` feature enabled, the default listener serves TLS with
+/// certificates for `domains` obtained from the ACME certificate authority at
+/// `directory`, Let's Encrypt by default, instead of those in a `tls` table.
+/// The account key, certificates, and private keys are stored in the `cache`
+/// directory, relative to the configuration file, so that restarts reuse
+/// them. Files in the cache are replaced atomically and readable only by their
+/// owner; a cached certificate whose key doesn't match is discarded.
+/// Certificates are requested at launch if none is cached and renewed in the
+/// background 30 days before they expire. Until the first certificate is
+/// obtained, TLS handshakes fail.
+///
+/// The authority verifies control of each domain via the `challenge`:
+///
+///   * **`"tls-alpn-01"`** (default): answered by the default listener itself,
+///     which must be reachable on port 443 at every domain.
+///   * **`"http-01"`**: answered at `/.well-known/acme-challenge/` by every
+///     listener. A plaintext listener must be reachable on port 80 at every
+///     domain.
+///
+/// Certificates are only requested if `accept_terms` is `true`, which agrees
+/// to the authority's terms of service.
+///
+/// ```toml
+/// [global.acme]
+/// domains = ["example.com", "www.example.com"]
+/// contact = ["mailto:admin@example.com"]
+/// accept_terms = true
+/// cache = "acme"
+/// ```
+///
+/// ACME is only supported by the `tls` backend and requires the `tls-acme`
+/// feature. Configuring it with the `native-tls` feature enabled, or without
+/// the `tls-acme` feature, logs an error at launch. Configuring both `acme`
+/// and `tls` is an error at launch.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{AcmeConfig, AcmeChallenge, Config, Environment};
+///
+/// let acme = AcmeConfig::new(vec!["example.com"])
+///     .contact("mailto:admin@example.com")
+///     .directory(AcmeConfig::LETS_ENCRYPT_STAGING)
+///     .challenge(AcmeChallenge::Http01)
+///     .accept_terms(true);
+///
+/// let config = Config::build(Environment::Staging)
+///     .port(443)
+///     .acme(acme)
+///     .unwrap();
+///
+/// assert!(config.acme.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcmeConfig {
+    /// The domains to obtain a certificate for.
+    pub domains: Vec<String>,
+    /// Contact URLs, such as `mailto:` addresses, for the account.
+    pub contact: Vec<String>,
+    /// The URL of the certificate authority's ACME directory.
+    pub directory: String,
+    /// How the authority verifies control of the domains.
+    pub challenge: AcmeChallenge,
+    /// Path to the directory certificates and keys are stored in.
+    pub cache: String,
+    /// Whether the authority's terms of service are agreed to.
+    pub accept_terms: bool,
+}
+
+impl AcmeConfig {
+    /// The directory of Let's Encrypt's production environment.
+    pub const LETS_ENCRYPT: &'static str = "https://acme-v02.api.letsencrypt.org/directory";
+
+    /// The directory of Let's Encrypt's staging environment, which issues
+    /// untrusted certificates with generous rate limits.
+    pub const LETS_ENCRYPT_STAGING: &'static str =
+        "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+    /// Obtains certificates for `domains` from Let's Encrypt via the
+    /// `tls-alpn-01` challenge, caching them in `acme`. The terms of service
+    /// are not yet accepted.
+    pub fn new<D: Into<String>>(domains: Vec<D>) -> AcmeConfig {
+        AcmeConfig {
+            domains: domains.into_iter().map(|d| d.into()).collect(),
+            contact: vec![],
+            directory: AcmeConfig::LETS_ENCRYPT.into(),
+            challenge: AcmeChallenge::TlsAlpn01,
+            cache: "acme".into(),
+            accept_terms: false,
+        }
+    }
+
+    /// Adds the contact URL `contact`, such as `mailto:admin@example.com`.
+    pub fn contact<C: Into<String>>(mut self, contact: C) -> AcmeConfig {
+        self.contact.push(contact.into());
+        self
+    }
+
+    /// Uses the ACME directory at `directory`.
+    pub fn directory<D: Into<String>>(mut self, directory: D) -> AcmeConfig {
+        self.directory = directory.into();
+        self
+    }
+
+    /// Verifies control of the domains via `challenge`.
+    pub fn challenge(mut self, challenge: AcmeChallenge) -> AcmeConfig {
+        self.challenge = challenge;
+        self
+    }
+
+    /// Stores certificates and keys in the directory `cache`.
+    pub fn cache<P: Into<String>>(mut self, cache: P) -> AcmeConfig {
+        self.cache = cache.into();
+        self
+    }
+
+    /// Sets whether the authority's terms of service are agreed to.
+    pub fn accept_terms(mut self, accept: bool) -> AcmeConfig {
+        self.accept_terms = accept;
+        self
+    }
+}
+
+impl fmt::Display for AcmeConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} via {} ({})", self.domains.join(", "), self.directory, self.challenge)
+    }
+}
+
+/// How an ACME certificate authority verifies control of a domain.
+///
+/// See [`AcmeConfig`] for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcmeChallenge {
+    /// `tls-alpn-01`: a TLS handshake with a special certificate.
+    TlsAlpn01,
+    /// `http-01`: a plaintext HTTP request to `/.well-known/acme-challenge/`.
+    Http01,
+}
+
+impl AcmeChallenge {
+    /// Returns the name of this challenge in ACME, such as `"http-01"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AcmeChallenge::TlsAlpn01 => "tls-alpn-01",
+            AcmeChallenge::Http01 => "http-01",
+        }
+    }
+}
+
+impl std::str::FromStr for AcmeChallenge {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<AcmeChallenge, ()> {
+        match s {
+            "tls-alpn-01" => Ok(AcmeChallenge::TlsAlpn01),
+            "http-01" => Ok(AcmeChallenge::Http01),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for AcmeChallenge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use crate::config::Table;
+
+/// A check on a custom configuration section, run when the section is
+/// extracted via [`Config::extract_validated()`].
+///
+/// Implement `Validate` for a type that is extracted from the configuration to
+/// reject values that deserialize successfully but are nonetheless invalid,
+/// such as a pool size of `0` or a URL with the wrong scheme. Each problem is
+/// reported as a [`ValidationError`] naming the offending field. The default
+/// implementation accepts every value.
+///
+/// [`Config::extract_validated()`]: crate::config::Config::extract_validated()
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::config::{Validate, ValidationError};
+///
+/// struct DbConfig {
+///     url: String,
+///     pool_size: u32,
+/// }
+///
+/// impl Validate for DbConfig {
+///     fn validate(&self) -> Result<(), Vec<ValidationError>> {
+///         let mut errors = vec![];
+///         if !self.url.starts_with("postgres://") {
+///             errors.push(ValidationError::new("url", "must be a postgres:// URL"));
+///         }
+///
+///         if self.pool_size == 0 {
+///             errors.push(ValidationError::new("pool_size", "must be at least 1"));
+///         }
+///
+///         if errors.is_empty() { Ok(()) } else { Err(errors) }
+///     }
+/// }
+/// ```
+pub trait Validate {
+    /// Checks `self`, returning an error for each invalid field.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method returns `Ok(())`.
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        Ok(())
+    }
+}
+
+/// A custom configuration section, extracted via
+/// [`Config::extract_validated()`].
+///
+/// A `ConfigSection` provides the default values of the section's fields and
+/// the environment variables that override them. It is typically derived:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use serde::Deserialize;
+/// use rocket::config::Validate;
+///
+/// #[derive(Deserialize, ConfigSection)]
+/// struct Pool {
+///     #[config(default = 4, env = "POOL_SIZE")]
+///     size: u32,
+///     url: String,
+/// }
+///
+/// impl Validate for Pool { }
+/// ```
+///
+/// See the [`ConfigSection`](../derive.ConfigSection.html) derive for the
+/// attributes it accepts. Both methods have default implementations, so a
+/// section without defaults or overrides can implement the trait with an
+/// empty `impl` block.
+///
+/// [`Config::extract_validated()`]: crate::config::Config::extract_validated()
+pub trait ConfigSection: DeserializeOwned + Validate {
+    /// Returns the default value of each field that has one, by name. Values
+    /// in the configuration take precedence.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method returns an empty table.
+    fn defaults() -> Table {
+        Table::new()
+    }
+
+    /// Returns the name of each field that can be overridden by an
+    /// environment variable paired with the name of the variable. Variables
+    /// that are set take precedence over the configuration and defaults.
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method returns an empty vector.
+    fn env_overrides() -> Vec<(&'static str, &'static str)> {
+        vec![]
+    }
+}
+
+/// An invalid field in a custom configuration section.
+///
+/// The `path` of an error is the dot-separated path to the field within the
+/// section, such as `pool.size`. When the section is extracted, the section's
+/// name is prepended, so errors are reported with the full path of the field:
+/// `databases.pool.size`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The dot-separated path to the invalid field.
+    pub path: String,
+    /// A description of why the field is invalid.
+    pub message: String,
+}
+
+impl ValidationError {
+    /// Returns an error for the field at `path` with the description
+    /// `message`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ValidationError;
+    ///
+    /// let error = ValidationError::new("pool.size", "must be at least 1");
+    /// assert_eq!(error.to_string(), "pool.size: must be at least 1");
+    /// ```
+    pub fn new<P, M>(path: P, message: M) -> ValidationError
+        where P: Into<String>, M: Into<String>
+    {
+        ValidationError { path: path.into(), message: message.into() }
+    }
+
+    /// Returns `self` with `prefix` prepended to its path. Use this method to
+    /// report the errors of a nested section with their full paths.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::ValidationError;
+    ///
+    /// let error = ValidationError::new("size", "must be at least 1").nested("pool");
+    /// assert_eq!(error.path, "pool.size");
+    /// ```
+    pub fn nested(mut self, prefix: &str) -> ValidationError {
+        self.path = match self.path.is_empty() {
+            true => prefix.into(),
+            false => format!("{}.{}", prefix, self.path),
+        };
+
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
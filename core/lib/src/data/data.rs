@@ -3,8 +3,8 @@ use std::io::Cursor;
 use crate::http::hyper;
 use crate::ext::AsyncReadBody;
 use crate::tokio::io::AsyncReadExt;
-use crate::data::data_stream::DataStream;
-use crate::data::ByteUnit;
+use crate::data::data_stream::{DataStream, ByteStream, ByteStreamState};
+use crate::data::{ByteUnit, Bytes};
 
 /// The number of bytes to read into the "peek" buffer.
 pub const PEEK_BYTES: usize = 512;
@@ -93,6 +93,49 @@ impl Data {
         DataStream { buffer, stream }
     }
 
+    /// Returns the body as a stream of [`Bytes`] chunks, limited to `limit`
+    /// bytes.
+    ///
+    /// Like [`open()`](Data::open()), the stream contains all of the data in
+    /// the body of the request, including that in the `peek` buffer, and the
+    /// method consumes the `Data` instance. Chunks are yielded as they are
+    /// received, without copying, making the stream suitable for proxying or
+    /// processing large bodies piecewise. A body exceeding `limit` is reported
+    /// with an error after the chunks up to the limit. Dropping the stream
+    /// cancels reading the body. See [`ByteStream`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io;
+    /// use rocket::data::{Data, ToByteUnit};
+    /// use rocket::futures::stream::TryStreamExt;
+    ///
+    /// async fn checksum(data: Data) -> io::Result<u32> {
+    ///     let mut stream = data.open_stream(64.mebibytes());
+    ///     let mut sum = 0u32;
+    ///     while let Some(chunk) = stream.try_next().await? {
+    ///         sum = chunk.iter().fold(sum, |sum, &b| sum.wrapping_add(b as u32));
+    ///     }
+    ///
+    ///     Ok(sum)
+    /// }
+    /// ```
+    pub fn open_stream(self, limit: ByteUnit) -> ByteStream {
+        let buffer = match self.buffer.is_empty() {
+            true => None,
+            false => Some(Bytes::from(self.buffer)),
+        };
+
+        ByteStream {
+            buffer,
+            stream: self.stream,
+            limit,
+            remaining: limit.into(),
+            state: ByteStreamState::Reading,
+        }
+    }
+
     /// Retrieve at most `num` bytes from the `peek` buffer without consuming
     /// `self`.
     ///
@@ -125,7 +168,12 @@ impl Data {
     /// }
     /// ```
     pub async fn peek(&mut self, num: usize) -> &[u8] {
-        let num = std::cmp::min(PEEK_BYTES, num);
+        self.buffer_up_to(std::cmp::min(PEEK_BYTES, num)).await
+    }
+
+    /// Like `peek()` but without the `PEEK_BYTES` cap: buffers and returns at
+    /// most `num` bytes of the body.
+    pub(crate) async fn buffer_up_to(&mut self, num: usize) -> &[u8] {
         let mut len = self.buffer.len();
         if len >= num {
             return &self.buffer[..num];
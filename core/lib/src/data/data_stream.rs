@@ -3,9 +3,12 @@ use std::task::{Context, Poll};
 use std::path::Path;
 use std::io::{self, Cursor};
 
+use futures::stream::Stream;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncReadExt, Take};
 
 use crate::ext::AsyncReadBody;
+use crate::http::hyper::Bytes;
+use crate::data::ByteUnit;
 
 /// Raw data stream of a request body.
 ///
@@ -107,6 +110,56 @@ impl DataStream {
         self.read_to_end(&mut vec).await?;
         Ok(vec)
     }
+
+    /// A helper method to read the body of the request into [`Bytes`].
+    ///
+    /// Unlike [`DataStream::stream_to_vec()`], this method doesn't copy the
+    /// body when it was received in a single chunk, as is typical of small
+    /// bodies; the returned `Bytes` then shares the buffer it was received in.
+    /// A body received in several chunks is copied once, into one buffer.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io;
+    /// use rocket::data::{Bytes, Data, ToByteUnit};
+    ///
+    /// async fn handler(data: Data) -> io::Result<Bytes> {
+    ///     data.open(4.kibibytes()).into_bytes().await
+    /// }
+    /// ```
+    pub async fn into_bytes(self) -> io::Result<Bytes> {
+        let mut chunks = vec![];
+
+        // Whatever was peeked, and not yet read, comes first.
+        let limit = self.buffer.limit();
+        let cursor = self.buffer.into_inner();
+        let start = cursor.position() as usize;
+        let buffer = Bytes::from(cursor.into_inner());
+        let end = std::cmp::min(buffer.len() as u64, start as u64 + limit) as usize;
+        if start < end {
+            chunks.push(buffer.slice(start..end));
+        }
+
+        let mut remaining = self.stream.limit();
+        let mut stream = self.stream.into_inner();
+        while remaining > 0 {
+            let mut chunk = match stream.chunk().await? {
+                Some(chunk) => chunk,
+                None => break,
+            };
+
+            chunk.truncate(std::cmp::min(chunk.len() as u64, remaining) as usize);
+            remaining -= chunk.len() as u64;
+            chunks.push(chunk);
+        }
+
+        match chunks.len() {
+            0 => Ok(Bytes::new()),
+            1 => Ok(chunks.remove(0)),
+            _ => Ok(Bytes::from(chunks.concat())),
+        }
+    }
 }
 
 // TODO.async: Consider implementing `AsyncBufRead`.
@@ -130,3 +183,131 @@ impl AsyncRead for DataStream {
         Pin::new(&mut self.stream).poll_read(cx, buf)
     }
 }
+
+/// A stream of the chunks of a request body, as received.
+///
+/// This stream can only be obtained by calling
+/// [`Data::open_stream()`](crate::data::Data::open_stream()). Chunks are
+/// yielded without copying, and the body is read from the connection only as
+/// the stream is polled, so a slow consumer slows the client down instead of
+/// the body being buffered in memory.
+///
+/// # Limits
+///
+/// Unlike [`DataStream`], which ends silently at its limit, a `ByteStream`
+/// reports a body that exceeds its limit: it yields the chunks up to the limit
+/// followed by an error of kind [`io::ErrorKind::InvalidData`], and then ends.
+///
+/// # Cancellation
+///
+/// Dropping the stream, or calling [`ByteStream::cancel()`], stops reading the
+/// body. The rest of the body is discarded, and the connection may be closed
+/// after the response rather than reused.
+pub struct ByteStream {
+    pub(crate) buffer: Option<Bytes>,
+    pub(crate) stream: AsyncReadBody,
+    pub(crate) limit: ByteUnit,
+    pub(crate) remaining: u64,
+    pub(crate) state: ByteStreamState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ByteStreamState {
+    Reading,
+    Exceeded,
+    Done,
+}
+
+impl ByteStream {
+    /// Returns the number of bytes that may still be yielded before the limit
+    /// is reached.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::data::{Data, ToByteUnit};
+    ///
+    /// fn handler(data: Data) {
+    ///     let stream = data.open_stream(2.mebibytes());
+    ///     assert_eq!(stream.remaining(), 2 << 20);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Stops reading the body. The stream yields no further items.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::data::{Data, ToByteUnit};
+    /// use rocket::futures::stream::StreamExt;
+    ///
+    /// async fn starts_with_magic(data: Data) -> bool {
+    ///     let mut stream = data.open_stream(1.mebibytes());
+    ///     let magic = match stream.next().await {
+    ///         Some(Ok(chunk)) => chunk.starts_with(b"\x89PNG"),
+    ///         _ => false,
+    ///     };
+    ///
+    ///     stream.cancel();
+    ///     magic
+    /// }
+    /// ```
+    pub fn cancel(&mut self) {
+        self.buffer = None;
+        self.stream = AsyncReadBody::empty();
+        self.state = ByteStreamState::Done;
+    }
+
+    /// Yields as much of `chunk` as the limit allows.
+    fn limited(&mut self, mut chunk: Bytes) -> Bytes {
+        if chunk.len() as u64 > self.remaining {
+            chunk.truncate(self.remaining as usize);
+            self.state = ByteStreamState::Exceeded;
+        }
+
+        self.remaining -= chunk.len() as u64;
+        chunk
+    }
+}
+
+impl Stream for ByteStream {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.state {
+                ByteStreamState::Done => return Poll::Ready(None),
+                ByteStreamState::Exceeded => {
+                    self.cancel();
+                    let msg = format!("body exceeds the limit of {}", self.limit);
+                    return Poll::Ready(Some(Err(io::Error::new(io::ErrorKind::InvalidData, msg))));
+                }
+                ByteStreamState::Reading => {}
+            }
+
+            let chunk = match self.buffer.take() {
+                Some(buffer) => buffer,
+                None => match futures::ready!(self.stream.poll_chunk(cx)) {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => {
+                        self.state = ByteStreamState::Done;
+                        return Poll::Ready(None);
+                    }
+                    Err(e) => {
+                        self.cancel();
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            };
+
+            let chunk = self.limited(chunk);
+            if !chunk.is_empty() {
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+        }
+    }
+}
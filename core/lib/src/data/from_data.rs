@@ -7,7 +7,7 @@ use crate::outcome::{self, IntoOutcome};
 use crate::outcome::Outcome::*;
 use crate::http::Status;
 use crate::request::Request;
-use crate::data::{Data, ByteUnit};
+use crate::data::{Data, ByteUnit, Bytes, ToByteUnit};
 
 /// Type alias for the `Outcome` of a `FromTransformedData` conversion.
 pub type Outcome<S, E> = outcome::Outcome<S, (Status, E), Data>;
@@ -302,6 +302,15 @@ pub type FromDataFuture<'fut, T, E> = BoxFuture<'fut, Outcome<T, E>>;
 ///     `Ok`. If the derivation is a `Failure`, the error value is returned in
 ///     `Err`. If the derivation is a `Forward`, the request is forwarded.
 ///
+///   * **Bytes**
+///
+///     Reads the request body, up to the `bytes` limit (1MiB by default), into
+///     [`Bytes`] via [`DataStream::into_bytes()`], sharing rather than copying
+///     the buffer a small body was received in. If reading fails, returns a
+///     `Failure` with the corresponding `io::Error`.
+///
+///     [`DataStream::into_bytes()`]: crate::data::DataStream::into_bytes()
+///
 ///   * **String**
 ///
 ///     **Note:** _An implementation of `FromTransformedData` for `String` is only available
@@ -601,6 +610,20 @@ impl<'a, T: FromTransformedData<'a> + 'a> FromTransformedData<'a> for Option<T>
     }
 }
 
+#[crate::async_trait]
+impl FromData for Bytes {
+    type Error = std::io::Error;
+
+    #[inline(always)]
+    async fn from_data(req: &Request<'_>, data: Data) -> Outcome<Self, Self::Error> {
+        let limit = req.limits().get("bytes").unwrap_or(1.mebibytes());
+        match data.open(limit).into_bytes().await {
+            Ok(bytes) => Success(bytes),
+            Err(e) => Failure((Status::BadRequest, e)),
+        }
+    }
+}
+
 #[cfg(debug_assertions)]
 #[crate::async_trait]
 impl FromData for String {
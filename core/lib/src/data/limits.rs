@@ -28,7 +28,7 @@ use crate::data::{ByteUnit, ToByteUnit};
 ///     .limit("forms", 64.kibibytes())
 ///     .limit("json", 3.mebibytes());
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Limits {
     // We cache this internally but don't share that fact in the API.
     pub(crate) forms: ByteUnit,
@@ -6,8 +6,9 @@ mod from_data;
 mod limits;
 
 pub use self::data::Data;
-pub use self::data_stream::DataStream;
+pub use self::data_stream::{DataStream, ByteStream};
 pub use self::from_data::{FromData, Outcome, FromTransformedData, FromDataFuture};
 pub use self::from_data::{Transform, Transformed, TransformFuture};
 pub use self::limits::Limits;
 pub use ubyte::{ByteUnit, ToByteUnit};
+pub use crate::http::hyper::Bytes;
@@ -265,3 +265,15 @@ impl fmt::Display for RouteUriError {
         }
     }
 }
+
+/// Error returned by [`Route::max_concurrency()`] when the limit is `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroConcurrencyLimit;
+
+impl fmt::Display for ZeroConcurrencyLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A route's concurrency limit must be at least 1.")
+    }
+}
+
+impl std::error::Error for ZeroConcurrencyLimit { }
@@ -60,6 +60,43 @@ impl AsyncReadBody {
     pub fn empty() -> Self {
         Self { inner: hyper::Body::empty(), state: State::Done }
     }
+
+    /// Returns the next unread chunk of the body as received from hyper,
+    /// without copying it, or `None` once the body is exhausted.
+    pub async fn chunk(&mut self) -> io::Result<Option<Bytes>> {
+        futures::future::poll_fn(|cx| self.poll_chunk(cx)).await
+    }
+
+    /// Polls for the next unread chunk of the body. See `chunk()`.
+    pub fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Bytes>>> {
+        loop {
+            match std::mem::replace(&mut self.state, State::Pending) {
+                State::Partial(cursor) => {
+                    let position = cursor.position() as usize;
+                    let bytes = cursor.into_inner();
+                    if position < bytes.len() {
+                        return Poll::Ready(Ok(Some(bytes.slice(position..))));
+                    }
+                }
+                State::Done => {
+                    self.state = State::Done;
+                    return Poll::Ready(Ok(None));
+                }
+                State::Pending => match ready!(Pin::new(&mut self.inner).poll_data(cx)) {
+                    Some(Ok(bytes)) if bytes.is_empty() => continue,
+                    Some(Ok(bytes)) => return Poll::Ready(Ok(Some(bytes))),
+                    Some(Err(e)) => {
+                        let error = io::Error::new(io::ErrorKind::Other, e);
+                        return Poll::Ready(Err(error));
+                    }
+                    None => {
+                        self.state = State::Done;
+                        return Poll::Ready(Ok(None));
+                    }
+                },
+            }
+        }
+    }
 }
 
 impl From<hyper::Body> for AsyncReadBody {
@@ -1,9 +1,15 @@
+use std::io::{self, Cursor};
+use std::pin::Pin;
 use std::sync::Mutex;
+use std::task::{Context, Poll};
 
 use futures::future::{Future, BoxFuture};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::{Cargo, Rocket, Request, Response, Data};
-use crate::fairing::{Fairing, Kind, Info};
+use crate::config::{ConfigSection, ConfigUpdated};
+use crate::fairing::{Fairing, Kind, Info, Order};
+use crate::response::ResponseBody;
 
 /// A ad-hoc fairing that can be created from a function or closure.
 ///
@@ -13,8 +19,11 @@ use crate::fairing::{Fairing, Kind, Info};
 /// # Usage
 ///
 /// Use the [`on_attach`](#method.on_attach), [`on_launch`](#method.on_launch),
-/// [`on_request`](#method.on_request), or [`on_response`](#method.on_response)
-/// constructors to create an `AdHoc` structure from a function or closure.
+/// [`on_request`](#method.on_request), [`on_response`](#method.on_response),
+/// [`on_response_body`](#method.on_response_body),
+/// [`on_shutdown`](#method.on_shutdown), or
+/// [`on_config_update`](#method.on_config_update) constructors to create an `AdHoc`
+/// structure from a function or closure.
 /// Then, simply attach the structure to the `Rocket` instance.
 ///
 /// # Example
@@ -42,6 +51,7 @@ use crate::fairing::{Fairing, Kind, Info};
 pub struct AdHoc {
     name: &'static str,
     kind: AdHocKind,
+    order: Order,
 }
 
 // macro_rules! Async {
@@ -77,6 +87,20 @@ enum AdHocKind {
     /// sent to a client.
     Response(Box<dyn for<'a> Fn(&'a Request<'_>, &'a mut Response<'_>)
         -> BoxFuture<'a, ()> + Send + Sync + 'static>),
+
+    /// An ad-hoc **response body** fairing. Called with the fully read body
+    /// of a response after all response fairings have run.
+    ResponseBody(Box<dyn Fn(&Request<'_>, &mut Response<'_>, Vec<u8>) -> Vec<u8>
+        + Send + Sync + 'static>),
+
+    /// An ad-hoc **shutdown** fairing. Called after the server shuts down.
+    Shutdown(Mutex<Option<Box<dyn for<'a> FnOnce(&'a Cargo)
+        -> BoxFuture<'a, ()> + Send + 'static>>>),
+
+    /// An ad-hoc **config update** fairing. Called after the configuration is
+    /// reloaded.
+    ConfigUpdate(Box<dyn for<'a> Fn(&'a Cargo, &'a ConfigUpdated)
+        -> BoxFuture<'a, ()> + Send + Sync + 'static>),
 }
 
 impl AdHoc {
@@ -98,10 +122,62 @@ impl AdHoc {
     {
         AdHoc {
             name,
-            kind: AdHocKind::Attach(Mutex::new(Some(Box::new(|rocket| Box::pin(f(rocket))))))
+            kind: AdHocKind::Attach(Mutex::new(Some(Box::new(|rocket| Box::pin(f(rocket)))))),
+            order: Order::default(),
         }
     }
 
+    /// Constructs an `AdHoc` attach fairing that extracts the configuration
+    /// section `section` into a `T`, validates it, and manages it. If the
+    /// section cannot be extracted or is invalid, every error is logged with
+    /// the path of its field and launch is aborted. See
+    /// [`Config::extract_validated()`] for details.
+    ///
+    /// [`Config::extract_validated()`]: crate::config::Config::extract_validated()
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// # extern crate serde;
+    /// use serde::Deserialize;
+    /// use rocket::State;
+    /// use rocket::config::Validate;
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// #[derive(Deserialize, ConfigSection)]
+    /// struct Motd {
+    ///     #[config(default = "Hello!", env = "MOTD")]
+    ///     message: String,
+    /// }
+    ///
+    /// impl Validate for Motd { }
+    ///
+    /// #[get("/")]
+    /// fn index(motd: State<'_, Motd>) -> String {
+    ///     motd.message.clone()
+    /// }
+    ///
+    /// rocket::ignite()
+    ///     .attach(AdHoc::config::<Motd>("motd"))
+    ///     .mount("/", routes![index])
+    /// # ;
+    /// ```
+    pub fn config<T>(section: &'static str) -> AdHoc
+        where T: ConfigSection + Send + Sync + 'static
+    {
+        AdHoc::on_attach(std::any::type_name::<T>(), move |mut rocket| async move {
+            let result = rocket.config().await.extract_validated::<T>(section);
+            match result {
+                Ok(value) => Ok(rocket.manage(value)),
+                Err(e) => {
+                    e.pretty_print();
+                    Err(rocket)
+                }
+            }
+        })
+    }
+
     /// Constructs an `AdHoc` launch fairing named `name`. The function `f` will
     /// be called by Rocket just prior to launching.
     ///
@@ -118,7 +194,8 @@ impl AdHoc {
     pub fn on_launch<F: Send + 'static>(name: &'static str, f: F) -> AdHoc
         where F: FnOnce(&Cargo)
     {
-        AdHoc { name, kind: AdHocKind::Launch(Mutex::new(Some(Box::new(f)))) }
+        let kind = AdHocKind::Launch(Mutex::new(Some(Box::new(f))));
+        AdHoc { name, kind, order: Order::default() }
     }
 
     /// Constructs an `AdHoc` request fairing named `name`. The function `f`
@@ -141,7 +218,7 @@ impl AdHoc {
     pub fn on_request<F: Send + Sync + 'static>(name: &'static str, f: F) -> AdHoc
         where F: for<'a> Fn(&'a mut Request<'_>, &'a Data) -> BoxFuture<'a, ()>
     {
-        AdHoc { name, kind: AdHocKind::Request(Box::new(f)) }
+        AdHoc { name, kind: AdHocKind::Request(Box::new(f)), order: Order::default() }
     }
     // // FIXME: Can the generated future hold references to the request with this?
     // pub fn on_request<F, Fut>(name: &'static str, f: F) -> AdHoc
@@ -175,7 +252,107 @@ impl AdHoc {
     pub fn on_response<F: Send + Sync + 'static>(name: &'static str, f: F) -> AdHoc
         where F: for<'a> Fn(&'a Request<'_>, &'a mut Response<'_>) -> BoxFuture<'a, ()>
     {
-        AdHoc { name, kind: AdHocKind::Response(Box::new(f)) }
+        AdHoc { name, kind: AdHocKind::Response(Box::new(f)), order: Order::default() }
+    }
+
+    /// Constructs an `AdHoc` response body fairing named `name`. The body of
+    /// every response that has one is read into memory and passed to `f`,
+    /// along with the request and the response, after all response fairings
+    /// have run. The bytes returned by `f` become the new body.
+    ///
+    /// Because the body is buffered, this is best suited to small, textual
+    /// responses. To transform bodies as they stream, implement
+    /// [`Fairing::on_response_body()`] directly.
+    ///
+    /// If reading the body fails, the error is logged and `f` is not called.
+    /// The bytes read before the error are sent, followed by the error, which
+    /// aborts the response as it would have without this fairing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    /// use rocket::http::ContentType;
+    ///
+    /// // A fairing that injects a script into every HTML page.
+    /// let fairing = AdHoc::on_response_body("Script Injector", |_, res, body| {
+    ///     if res.content_type() != Some(ContentType::HTML) {
+    ///         return body;
+    ///     }
+    ///
+    ///     let html = String::from_utf8_lossy(&body);
+    ///     html.replace("</body>", "<script src=\"/reload.js\"></script></body>").into()
+    /// });
+    /// ```
+    pub fn on_response_body<F: Send + Sync + 'static>(name: &'static str, f: F) -> AdHoc
+        where F: Fn(&Request<'_>, &mut Response<'_>, Vec<u8>) -> Vec<u8>
+    {
+        AdHoc { name, kind: AdHocKind::ResponseBody(Box::new(f)), order: Order::default() }
+    }
+
+    /// Constructs an `AdHoc` shutdown fairing named `name`. The function `f`
+    /// will be called and the returned `Future` will be `await`ed by Rocket
+    /// once the server has shut down gracefully.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// // A fairing that prints a message after shutting down.
+    /// let fairing = AdHoc::on_shutdown("Goodbye", |cargo| {
+    ///     Box::pin(async move {
+    ///         println!("Shut down after serving {} routes.", cargo.routes().count());
+    ///     })
+    /// });
+    /// ```
+    pub fn on_shutdown<F: Send + 'static>(name: &'static str, f: F) -> AdHoc
+        where F: for<'a> FnOnce(&'a Cargo) -> BoxFuture<'a, ()>
+    {
+        let kind = AdHocKind::Shutdown(Mutex::new(Some(Box::new(f))));
+        AdHoc { name, kind, order: Order::default() }
+    }
+
+    /// Constructs an `AdHoc` config update fairing named `name`. The function
+    /// `f` will be called and the returned `Future` will be `await`ed by Rocket
+    /// each time the configuration of the running application is reloaded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// // A fairing that prints the version of each new configuration.
+    /// let fairing = AdHoc::on_config_update("Reload Printer", |_, update| {
+    ///     Box::pin(async move {
+    ///         println!("Configuration is now v{}.", update.current.version());
+    ///     })
+    /// });
+    /// ```
+    pub fn on_config_update<F: Send + Sync + 'static>(name: &'static str, f: F) -> AdHoc
+        where F: for<'a> Fn(&'a Cargo, &'a ConfigUpdated) -> BoxFuture<'a, ()>
+    {
+        AdHoc { name, kind: AdHocKind::ConfigUpdate(Box::new(f)), order: Order::default() }
+    }
+
+    /// Sets the ordering constraints of this fairing to `order`. See
+    /// [`Order`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::{AdHoc, Order};
+    ///
+    /// // A response fairing that runs after all others with a default order.
+    /// let fairing = AdHoc::on_response("Last", |req, resp| {
+    ///     Box::pin(async move {
+    /// #       let (_, _) = (req, resp);
+    ///     })
+    /// }).with_order(Order::priority(-100));
+    /// ```
+    pub fn with_order(mut self, order: Order) -> AdHoc {
+        self.order = order;
+        self
     }
 }
 
@@ -187,11 +364,18 @@ impl Fairing for AdHoc {
             AdHocKind::Launch(_) => Kind::Launch,
             AdHocKind::Request(_) => Kind::Request,
             AdHocKind::Response(_) => Kind::Response,
+            AdHocKind::ResponseBody(_) => Kind::ResponseBody,
+            AdHocKind::Shutdown(_) => Kind::Shutdown,
+            AdHocKind::ConfigUpdate(_) => Kind::ConfigUpdate,
         };
 
         Info { name: self.name, kind }
     }
 
+    fn order(&self) -> Order {
+        self.order.clone()
+    }
+
     async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
         if let AdHocKind::Attach(ref mutex) = self.kind {
             let f = mutex.lock()
@@ -223,4 +407,57 @@ impl Fairing for AdHoc {
             callback(req, res).await;
         }
     }
+
+    async fn on_response_body<'r>(
+        &self,
+        req: &'r Request<'_>,
+        res: &mut Response<'r>,
+        mut body: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        if let AdHocKind::ResponseBody(ref callback) = self.kind {
+            let mut bytes = vec![];
+            if let Err(e) = body.as_reader().read_to_end(&mut bytes).await {
+                error_!("Failed to read the response body in '{}': {}", self.name, e);
+                return ResponseBody::streamed(Cursor::new(bytes).chain(Failed(Some(e))));
+            }
+
+            let bytes = callback(req, res, bytes);
+            let len = bytes.len();
+            return ResponseBody::sized(Cursor::new(bytes), len);
+        }
+
+        body
+    }
+
+    async fn on_shutdown(&self, cargo: &Cargo) {
+        if let AdHocKind::Shutdown(ref mutex) = self.kind {
+            let f = mutex.lock()
+                .expect("AdHoc::Shutdown lock")
+                .take()
+                .expect("internal error: `on_shutdown` one-call invariant broken");
+            f(cargo).await
+        }
+    }
+
+    async fn on_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {
+        if let AdHocKind::ConfigUpdate(ref callback) = self.kind {
+            callback(cargo, update).await;
+        }
+    }
+}
+
+/// A reader that fails with the error it holds, then reports EOF.
+struct Failed(Option<io::Error>);
+
+impl AsyncRead for Failed {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        match self.0.take() {
+            Some(e) => Poll::Ready(Err(e)),
+            None => Poll::Ready(Ok(0)),
+        }
+    }
 }
@@ -1,4 +1,10 @@
 use crate::{Cargo, Rocket, Request, Response, Data};
+use crate::config::ConfigUpdated;
+use crate::response::ResponseBody;
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
 use crate::fairing::{Fairing, Kind};
 use crate::logger::PaintExt;
 
@@ -8,10 +14,17 @@ use yansi::Paint;
 pub struct Fairings {
     all_fairings: Vec<Box<dyn Fairing>>,
     attach_failures: Vec<&'static str>,
+    // The types of the singleton fairings that have been attached.
+    singletons: HashSet<TypeId>,
     // The vectors below hold indices into `all_fairings`.
     launch: Vec<usize>,
     request: Vec<usize>,
     response: Vec<usize>,
+    response_body: Vec<usize>,
+    shutdown: Vec<usize>,
+    config_update: Vec<usize>,
+    // Whether the fairings' dependencies are cyclic and thus ignored.
+    cyclic: bool,
 }
 
 impl Fairings {
@@ -20,10 +33,20 @@ impl Fairings {
         Fairings::default()
     }
 
-    pub async fn attach(&mut self, fairing: Box<dyn Fairing>, mut rocket: Rocket) -> Rocket {
-        // Run the `on_attach` callback if this is an 'attach' fairing.
+    pub async fn attach(
+        &mut self,
+        type_id: TypeId,
+        fairing: Box<dyn Fairing>,
+        mut rocket: Rocket
+    ) -> Rocket {
         let kind = fairing.info().kind;
         let name = fairing.info().name;
+        if kind.is(Kind::Singleton) && !self.singletons.insert(type_id) {
+            info_!("Singleton fairing '{}' is already attached; ignoring.", name);
+            return rocket;
+        }
+
+        // Run the `on_attach` callback if this is an 'attach' fairing.
         if kind.is(Kind::Attach) {
             rocket = fairing.on_attach(rocket).await
                 .unwrap_or_else(|r| { self.attach_failures.push(name); r })
@@ -35,17 +58,88 @@ impl Fairings {
 
     fn add(&mut self, fairing: Box<dyn Fairing>) {
         let kind = fairing.info().kind;
-        if !kind.is_exactly(Kind::Attach) {
-            let index = self.all_fairings.len();
+        let attach_only = Kind::Attach | Kind::Singleton;
+        if !kind.is_exactly(Kind::Attach) && !kind.is_exactly(attach_only) {
             self.all_fairings.push(fairing);
+            self.reorder();
+        }
+    }
 
+    /// Recomputes the order in which the callbacks of each kind are run
+    /// according to each fairing's `Order`.
+    fn reorder(&mut self) {
+        self.launch.clear();
+        self.request.clear();
+        self.response.clear();
+        self.response_body.clear();
+        self.shutdown.clear();
+        self.config_update.clear();
+
+        let (order, cyclic) = self.order();
+        self.cyclic = cyclic;
+        for index in order {
+            let kind = self.all_fairings[index].info().kind;
             if kind.is(Kind::Launch) { self.launch.push(index); }
             if kind.is(Kind::Request) { self.request.push(index); }
             if kind.is(Kind::Response) { self.response.push(index); }
+            if kind.is(Kind::ResponseBody) { self.response_body.push(index); }
+            if kind.is(Kind::Shutdown) { self.shutdown.push(index); }
+            if kind.is(Kind::ConfigUpdate) { self.config_update.push(index); }
+        }
+    }
+
+    /// Returns the indices of all fairings in the order in which they should
+    /// run: a topological sort of their dependencies that prefers higher
+    /// priorities and then earlier attachment. If the dependencies are cyclic,
+    /// they are ignored, and the returned flag is `true`.
+    fn order(&self) -> (Vec<usize>, bool) {
+        let n = self.all_fairings.len();
+        let orders: Vec<_> = self.all_fairings.iter().map(|f| f.order()).collect();
+        let names: Vec<_> = self.all_fairings.iter().map(|f| f.info().name).collect();
+        let key = |i: usize| (Reverse(orders[i].get_priority()), i);
+
+        // `successors[i]` must run after `i`; `pending[i]` counts predecessors.
+        let mut successors = vec![vec![]; n];
+        let mut pending = vec![0usize; n];
+        for i in 0..n {
+            for j in (0..n).filter(|&j| j != i) {
+                if orders[i].get_after().contains(&names[j]) {
+                    successors[j].push(i);
+                    pending[i] += 1;
+                }
+
+                if orders[i].get_before().contains(&names[j]) {
+                    successors[i].push(j);
+                    pending[j] += 1;
+                }
+            }
+        }
+
+        let mut sorted = Vec::with_capacity(n);
+        let mut done = vec![false; n];
+        while sorted.len() < n {
+            let next = (0..n).filter(|&i| !done[i] && pending[i] == 0).min_by_key(|&i| key(i));
+            match next {
+                Some(i) => {
+                    done[i] = true;
+                    sorted.push(i);
+                    for &j in &successors[i] {
+                        pending[j] -= 1;
+                    }
+                }
+                None => {
+                    let mut all: Vec<usize> = (0..n).collect();
+                    all.sort_by_key(|&i| key(i));
+                    return (all, true);
+                }
+            }
         }
+
+        (sorted, false)
     }
 
     pub fn append(&mut self, others: Fairings) {
+        self.singletons.extend(others.singletons);
         for fairing in others.all_fairings {
             self.add(fairing);
         }
@@ -70,6 +164,31 @@ impl Fairings {
         for &i in &self.response {
             self.all_fairings[i].on_response(request, response).await;
         }
+
+        if self.response_body.is_empty() {
+            return;
+        }
+
+        if let Some(mut body) = response.take_body() {
+            for &i in &self.response_body {
+                body = self.all_fairings[i].on_response_body(request, response, body).await;
+            }
+
+            response.set_body(body);
+        }
+    }
+
+    /// Runs the shutdown callbacks in the reverse of the launch order.
+    pub async fn handle_shutdown(&self, cargo: &Cargo) {
+        for &i in self.shutdown.iter().rev() {
+            self.all_fairings[i].on_shutdown(cargo).await;
+        }
+    }
+
+    pub async fn handle_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {
+        for &i in &self.config_update {
+            self.all_fairings[i].on_config_update(cargo, update).await;
+        }
     }
 
     pub fn failures(&self) -> Option<&[&'static str]> {
@@ -95,9 +214,16 @@ impl Fairings {
     pub fn pretty_print_counts(&self) {
         if !self.all_fairings.is_empty() {
             info!("{}{}:", Paint::emoji("📡 "), Paint::magenta("Fairings"));
+            if self.cyclic {
+                warn_!("Fairing dependencies are cyclic and will be ignored.");
+            }
+
             self.info_for("launch", &self.launch);
             self.info_for("request", &self.request);
             self.info_for("response", &self.response);
+            self.info_for("response body", &self.response_body);
+            self.info_for("shutdown", &self.shutdown);
+            self.info_for("config update", &self.config_update);
         }
     }
 }
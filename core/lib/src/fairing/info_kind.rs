@@ -40,6 +40,11 @@ pub struct Info {
 ///   * Launch
 ///   * Request
 ///   * Response
+///   * ResponseBody
+///   * Shutdown
+///   * ConfigUpdate
+///
+/// A fairing can additionally mark itself as a [singleton](Kind::Singleton).
 ///
 /// Two `Kind` structures can be `or`d together to represent a combination. For
 /// instance, to represent a fairing that is both a launch and request fairing,
@@ -58,6 +63,24 @@ impl Kind {
     pub const Request: Kind = Kind(0b0100);
     /// `Kind` flag representing a request for a 'response' callback.
     pub const Response: Kind = Kind(0b1000);
+    /// `Kind` flag representing a request for a 'response body' callback.
+    pub const ResponseBody: Kind = Kind(0b10000);
+    /// `Kind` flag representing a request for a 'shutdown' callback.
+    pub const Shutdown: Kind = Kind(0b100000);
+    /// `Kind` flag marking the fairing as a singleton. Once a singleton
+    /// fairing is attached, attaching another fairing of the same type has no
+    /// effect: its attach callback is not run, and its other callbacks are
+    /// never called. Use this flag for fairings that may be attached by more
+    /// than one library but must only process each request once.
+    ///
+    /// Fairings are compared by their Rust type, so fairings that are
+    /// distinguished only by their values, such as [`AdHoc`] fairings, should
+    /// not be singletons.
+    ///
+    /// [`AdHoc`]: crate::fairing::AdHoc
+    pub const Singleton: Kind = Kind(0b1000000);
+    /// `Kind` flag representing a request for a 'config update' callback.
+    pub const ConfigUpdate: Kind = Kind(0b10000000);
 
     /// Returns `true` if `self` is a superset of `other`. In other words,
     /// returns `true` if all of the kinds in `other` are also in `self`.
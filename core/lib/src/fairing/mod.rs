@@ -1,4 +1,5 @@
-//! Fairings: callbacks at attach, launch, request, and response time.
+//! Fairings: callbacks at attach, launch, request, response, shutdown, and
+//! configuration update time.
 //!
 //! Fairings allow for structured interposition at various points in the
 //! application lifetime. Fairings can be seen as a restricted form of
@@ -37,25 +38,36 @@
 //!
 //! ## Ordering
 //!
-//! `Fairing`s are executed in the order in which they are attached: the first
-//! attached fairing has its callbacks executed before all others. Because
-//! fairing callbacks may not be commutative, the order in which fairings are
-//! attached may be significant. Because of this, it is important to communicate
-//! to the user every consequence of a fairing.
+//! By default, `Fairing`s are executed in the order in which they are attached:
+//! the first attached fairing has its callbacks executed before all others.
+//! Because fairing callbacks may not be commutative, the order in which
+//! fairings are attached may be significant. Because of this, it is important
+//! to communicate to the user every consequence of a fairing.
+//!
+//! A fairing whose correctness depends on its position relative to others can
+//! declare a priority or dependencies on other fairings by name via
+//! [`Fairing::order()`]; see [`Order`] for details. Declared orders take
+//! precedence over attach order.
 //!
 //! Furthermore, a `Fairing` should take care to act locally so that the actions
 //! of other `Fairings` are not jeopardized. For instance, unless it is made
 //! abundantly clear, a fairing should not rewrite every request.
 
 use crate::{Cargo, Rocket, Request, Response, Data};
+use crate::response::ResponseBody;
+use crate::config::ConfigUpdated;
 
 mod fairings;
 mod ad_hoc;
 mod info_kind;
+mod order;
+mod when;
 
 pub(crate) use self::fairings::Fairings;
 pub use self::ad_hoc::AdHoc;
 pub use self::info_kind::{Info, Kind};
+pub use self::order::Order;
+pub use self::when::When;
 
 // We might imagine that a request fairing returns an `Outcome`. If it returns
 // `Success`, we don't do any routing and use that response directly. Same if it
@@ -94,12 +106,13 @@ pub use self::info_kind::{Info, Kind};
 ///
 /// ## Fairing Callbacks
 ///
-/// There are four kinds of fairing callbacks: attach, launch, request, and
-/// response. A fairing can request any combination of these callbacks through
-/// the `kind` field of the `Info` structure returned from the `info` method.
-/// Rocket will only invoke the callbacks set in the `kind` field.
+/// There are seven kinds of fairing callbacks: attach, launch, request,
+/// response, response body, shutdown, and config update. A fairing can request any
+/// combination of these callbacks through the `kind` field of the `Info`
+/// structure returned from the `info` method. Rocket will only invoke the
+/// callbacks set in the `kind` field.
 ///
-/// The four callback kinds are as follows:
+/// The seven callback kinds are as follows:
 ///
 ///   * **Attach (`on_attach`)**
 ///
@@ -152,13 +165,45 @@ pub use self::info_kind::{Info, Kind};
 ///     request. Additionally, Rocket will automatically strip the body for
 ///     `HEAD` requests _after_ response fairings have run.
 ///
+///   * **Response Body (`on_response_body`)**
+///
+///     A response body callback, represented by the
+///     [`Fairing::on_response_body()`] method, is called after _all_ response
+///     callbacks have run, and only if the response has a body. The callback
+///     receives the body, moved out of the response, and returns the body to
+///     send in its place. It can thus replace the body entirely, as when
+///     injecting into or minifying HTML or wrapping the body in an envelope,
+///     or wrap it in a streaming transformation without buffering it. The
+///     response itself remains available so that the callback can adjust the
+///     status and headers, such as `Content-Type`, to agree with the new body.
+///     Response body callbacks run in attach order, each receiving the body
+///     returned by the previous one.
+///
+///   * **Shutdown (`on_shutdown`)**
+///
+///     A shutdown callback, represented by the [`Fairing::on_shutdown()`]
+///     method, is called once the server has stopped accepting connections and
+///     all pending requests have completed. A shutdown callback can release
+///     resources held by the fairing, such as flushing buffered metrics or
+///     closing connection pools, before `launch()` returns.
+///
+///   * **Config Update (`on_config_update`)**
+///
+///     A config update callback, represented by the
+///     [`Fairing::on_config_update()`] method, is called after the
+///     configuration of a running application has been reloaded. A config
+///     update callback can apply the new configuration to state the fairing
+///     manages, such as reloading templates from a new directory.
+///
 /// # Implementing
 ///
 /// A `Fairing` implementation has one required method: [`info`]. A `Fairing`
 /// can also implement any of the available callbacks: `on_attach`, `on_launch`,
-/// `on_request`, and `on_response`. A `Fairing` _must_ set the appropriate
-/// callback kind in the `kind` field of the returned `Info` structure from
-/// [`info`] for a callback to actually be called by Rocket.
+/// `on_request`, `on_response`, `on_response_body`, `on_shutdown`, and
+/// `on_config_update`. A
+/// `Fairing` _must_ set the appropriate callback kind in the `kind` field of
+/// the returned `Info` structure from [`info`] for a callback to actually be
+/// called by Rocket.
 ///
 /// ## Fairing `Info`
 ///
@@ -403,6 +448,39 @@ pub trait Fairing: Send + Sync + 'static {
     /// ```
     fn info(&self) -> Info;
 
+    /// Returns the ordering constraints of this fairing's callbacks relative
+    /// to other fairings. See [`Order`] for details.
+    ///
+    /// This method is called each time a fairing is attached.
+    ///
+    /// ## Example
+    ///
+    /// A fairing that runs after a fairing named "CORS":
+    ///
+    /// ```rust
+    /// use rocket::fairing::{Fairing, Info, Kind, Order};
+    ///
+    /// struct Auth;
+    ///
+    /// impl Fairing for Auth {
+    ///     fn info(&self) -> Info {
+    ///         Info { name: "Auth", kind: Kind::Request }
+    ///     }
+    ///
+    ///     fn order(&self) -> Order {
+    ///         Order::default().after("CORS")
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation returns `Order::default()`: a priority of
+    /// `0` and no dependencies.
+    fn order(&self) -> Order {
+        Order::default()
+    }
+
     /// The attach callback. Returns `Ok` if launch should proceed and `Err` if
     /// launch should be aborted.
     ///
@@ -454,6 +532,154 @@ pub trait Fairing: Send + Sync + 'static {
     /// The default implementation of this method does nothing.
     #[allow(unused_variables)]
     async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {}
+
+    /// The response body callback.
+    ///
+    /// This method is called after all response callbacks have run if
+    /// `Kind::ResponseBody` is in the `kind` field of the `Info` structure for
+    /// this fairing and the response has a body. The `&Request` parameter is
+    /// the request that was routed, the `&mut Response` parameter is the
+    /// resulting response _without_ its body, and `body` is the body. The
+    /// returned body becomes the body of the response.
+    ///
+    /// The size of the returned body need not match that of `body`; Rocket
+    /// sets the `Content-Length` of the final response itself.
+    ///
+    /// ## Example
+    ///
+    /// A fairing that wraps JSON responses in an envelope:
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    /// use rocket::{Request, Response};
+    /// use rocket::fairing::{Fairing, Info, Kind};
+    /// use rocket::response::ResponseBody;
+    /// use rocket::http::ContentType;
+    ///
+    /// struct Envelope;
+    ///
+    /// #[rocket::async_trait]
+    /// impl Fairing for Envelope {
+    ///     fn info(&self) -> Info {
+    ///         Info { name: "JSON Envelope", kind: Kind::ResponseBody }
+    ///     }
+    ///
+    ///     async fn on_response_body<'r>(
+    ///         &self,
+    ///         _: &'r Request<'_>,
+    ///         res: &mut Response<'r>,
+    ///         body: ResponseBody<'r>
+    ///     ) -> ResponseBody<'r> {
+    ///         if res.content_type() != Some(ContentType::JSON) {
+    ///             return body;
+    ///         }
+    ///
+    ///         let data = body.into_string().await.unwrap_or_default();
+    ///         let wrapped = format!(r#"{{"status":{},"data":{}}}"#, res.status().code, data);
+    ///         ResponseBody::sized(Cursor::new(wrapped.clone()), wrapped.len())
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method returns `body` unchanged.
+    #[allow(unused_variables)]
+    async fn on_response_body<'r>(
+        &self,
+        req: &'r Request<'_>,
+        res: &mut Response<'r>,
+        body: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        body
+    }
+
+    /// The shutdown callback.
+    ///
+    /// This method is called once the server has shut down gracefully if
+    /// `Kind::Shutdown` is in the `kind` field of the `Info` structure for this
+    /// fairing: after shutdown was requested via [`Shutdown`] or `Ctrl+C` and
+    /// all pending requests have completed, or after the server stopped due to
+    /// an error. The `Cargo` parameter corresponds to the application that was
+    /// running. Use this callback to release resources deterministically:
+    /// flushing metrics, closing connection pools, or syncing files.
+    ///
+    /// Shutdown callbacks run in the reverse of the order in which launch
+    /// callbacks run, so a fairing is shut down before those it depends on.
+    /// They are not called for applications that were never launched, such as
+    /// those driven by a local [`Client`](crate::local::asynchronous::Client).
+    ///
+    /// [`Shutdown`]: crate::Shutdown
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method does nothing.
+    #[allow(unused_variables)]
+    async fn on_shutdown(&self, cargo: &Cargo) {}
+
+    /// The config update callback.
+    ///
+    /// This method is called after the configuration of the running
+    /// application has been reloaded, via [`Cargo::reload_config()`] or
+    /// [`Cargo::update_config()`], if `Kind::ConfigUpdate` is in the `kind`
+    /// field of the `Info` structure for this fairing. The `update` parameter
+    /// holds the configuration before and after the reload. Requests received
+    /// before the reload may still be processed with the previous
+    /// configuration while this callback runs.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use rocket::Cargo;
+    /// use rocket::config::ConfigUpdated;
+    /// use rocket::fairing::{Fairing, Info, Kind};
+    ///
+    /// struct Motd;
+    ///
+    /// #[rocket::async_trait]
+    /// impl Fairing for Motd {
+    ///     fn info(&self) -> Info {
+    ///         Info { name: "Message of the Day", kind: Kind::ConfigUpdate }
+    ///     }
+    ///
+    ///     async fn on_config_update(&self, _: &Cargo, update: &ConfigUpdated) {
+    ///         if update.extra_changed("motd") {
+    ///             println!("New message: {:?}", update.current.get_str("motd"));
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// ## Default Implementation
+    ///
+    /// The default implementation of this method does nothing.
+    #[allow(unused_variables)]
+    async fn on_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {}
+
+    /// Returns a fairing whose request, response, and response body callbacks
+    /// only run for requests for which `predicate` returns `true`. See
+    /// [`When`] for details.
+    ///
+    /// # Example
+    ///
+    /// Run a fairing only for requests to paths beginning with `/api`:
+    ///
+    /// ```rust
+    /// use rocket::fairing::{AdHoc, Fairing};
+    ///
+    /// let fairing = AdHoc::on_request("API", |_, _| Box::pin(async move {
+    ///     /* process API requests */
+    /// }));
+    ///
+    /// rocket::ignite()
+    ///     .attach(fairing.when(|req| req.uri().path().starts_with("/api")))
+    /// # ;
+    /// ```
+    fn when<P>(self, predicate: P) -> When<Self, P>
+        where Self: Sized, P: Fn(&Request<'_>) -> bool + Send + Sync + 'static
+    {
+        When::new(self, predicate)
+    }
 }
 
 #[crate::async_trait]
@@ -463,6 +689,11 @@ impl<T: Fairing> Fairing for std::sync::Arc<T> {
         (self as &T).info()
     }
 
+    #[inline]
+    fn order(&self) -> Order {
+        (self as &T).order()
+    }
+
     #[inline]
     async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
         (self as &T).on_attach(rocket).await
@@ -482,4 +713,24 @@ impl<T: Fairing> Fairing for std::sync::Arc<T> {
     async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
         (self as &T).on_response(req, res).await;
     }
+
+    #[inline]
+    async fn on_response_body<'r>(
+        &self,
+        req: &'r Request<'_>,
+        res: &mut Response<'r>,
+        body: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        (self as &T).on_response_body(req, res, body).await
+    }
+
+    #[inline]
+    async fn on_shutdown(&self, cargo: &Cargo) {
+        (self as &T).on_shutdown(cargo).await;
+    }
+
+    #[inline]
+    async fn on_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {
+        (self as &T).on_config_update(cargo, update).await;
+    }
 }
@@ -0,0 +1,102 @@
+/// The ordering constraints of a [`Fairing`](crate::fairing::Fairing).
+///
+/// By default, fairing callbacks run in the order in which the fairings were
+/// attached. A fairing can override this by returning an `Order` from
+/// [`Fairing::order()`](crate::fairing::Fairing::order()):
+///
+///   * A **priority**: fairings with a higher priority run before those with a
+///     lower priority. The default priority is `0`.
+///   * **Dependencies**: a fairing can require that it run [`after`] or
+///     [`before`] other fairings, identified by the `name` in their [`Info`].
+///     Dependencies take precedence over priorities. Dependencies on fairings
+///     that aren't attached are ignored.
+///
+/// Fairings with equal priorities and no dependencies between them run in
+/// attach order. If the dependencies are cyclic, a warning is logged and the
+/// dependencies are ignored; fairings then run in order of priority.
+///
+/// Orders apply to launch, request, response, and response body callbacks.
+/// Attach callbacks always run when the fairing is attached.
+///
+/// [`after`]: Order::after()
+/// [`before`]: Order::before()
+/// [`Info`]: crate::fairing::Info
+///
+/// # Example
+///
+/// A fairing that must see every request before a fairing named "Auth" and
+/// after all fairings with a default priority:
+///
+/// ```rust
+/// use rocket::fairing::Order;
+///
+/// let order = Order::priority(-10).before("Auth");
+/// assert_eq!(order.get_priority(), -10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Order {
+    priority: i32,
+    after: Vec<&'static str>,
+    before: Vec<&'static str>,
+}
+
+impl Order {
+    /// Returns an `Order` with priority `priority` and no dependencies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::Order;
+    ///
+    /// let order = Order::priority(100);
+    /// assert_eq!(order.get_priority(), 100);
+    /// ```
+    pub fn priority(priority: i32) -> Order {
+        Order { priority, ..Order::default() }
+    }
+
+    /// Requires the fairing to run after the fairing named `name`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::Order;
+    ///
+    /// let order = Order::default().after("CORS").after("Request ID");
+    /// assert_eq!(order.get_after(), &["CORS", "Request ID"]);
+    /// ```
+    pub fn after(mut self, name: &'static str) -> Self {
+        self.after.push(name);
+        self
+    }
+
+    /// Requires the fairing to run before the fairing named `name`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::Order;
+    ///
+    /// let order = Order::default().before("Auth");
+    /// assert_eq!(order.get_before(), &["Auth"]);
+    /// ```
+    pub fn before(mut self, name: &'static str) -> Self {
+        self.before.push(name);
+        self
+    }
+
+    /// Returns the priority.
+    pub fn get_priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Returns the names of the fairings that must run first.
+    pub fn get_after(&self) -> &[&'static str] {
+        &self.after
+    }
+
+    /// Returns the names of the fairings that must run later.
+    pub fn get_before(&self) -> &[&'static str] {
+        &self.before
+    }
+}
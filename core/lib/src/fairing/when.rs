@@ -0,0 +1,99 @@
+use crate::{Cargo, Rocket, Request, Response, Data};
+use crate::config::ConfigUpdated;
+use crate::fairing::{Fairing, Info, Order};
+use crate::response::ResponseBody;
+
+/// A fairing whose request and response callbacks only run for requests that
+/// match a predicate.
+///
+/// A `When` is created by [`Fairing::when()`]. It has the same [`Info`] and
+/// [`Order`] as the fairing it wraps. Its attach, launch, shutdown, and config
+/// update callbacks always run. Its request, response, and response body callbacks
+/// run only if the predicate returns `true` for the request. The predicate is
+/// evaluated anew for each callback: a request fairing that rewrites the
+/// request's URI may change whether the response callbacks run.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::fairing::{AdHoc, Fairing};
+/// use rocket::http::Header;
+///
+/// let no_store = AdHoc::on_response("No Store", |_, res| Box::pin(async move {
+///     res.set_header(Header::new("Cache-Control", "no-store"));
+/// }));
+///
+/// rocket::ignite()
+///     .attach(no_store.when(|req| req.uri().path().starts_with("/api")))
+/// # ;
+/// ```
+pub struct When<F, P> {
+    fairing: F,
+    predicate: P,
+}
+
+impl<F, P> When<F, P> {
+    pub(crate) fn new(fairing: F, predicate: P) -> Self {
+        When { fairing, predicate }
+    }
+}
+
+#[crate::async_trait]
+impl<F, P> Fairing for When<F, P>
+    where F: Fairing, P: Fn(&Request<'_>) -> bool + Send + Sync + 'static
+{
+    #[inline]
+    fn info(&self) -> Info {
+        self.fairing.info()
+    }
+
+    #[inline]
+    fn order(&self) -> Order {
+        self.fairing.order()
+    }
+
+    #[inline]
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        self.fairing.on_attach(rocket).await
+    }
+
+    #[inline]
+    fn on_launch(&self, cargo: &Cargo) {
+        self.fairing.on_launch(cargo)
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, data: &Data) {
+        if (self.predicate)(req) {
+            self.fairing.on_request(req, data).await;
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if (self.predicate)(req) {
+            self.fairing.on_response(req, res).await;
+        }
+    }
+
+    async fn on_response_body<'r>(
+        &self,
+        req: &'r Request<'_>,
+        res: &mut Response<'r>,
+        body: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        if (self.predicate)(req) {
+            self.fairing.on_response_body(req, res, body).await
+        } else {
+            body
+        }
+    }
+
+    #[inline]
+    async fn on_shutdown(&self, cargo: &Cargo) {
+        self.fairing.on_shutdown(cargo).await
+    }
+
+    #[inline]
+    async fn on_config_update(&self, cargo: &Cargo, update: &ConfigUpdated) {
+        self.fairing.on_config_update(cargo, update).await
+    }
+}
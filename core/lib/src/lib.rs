@@ -74,6 +74,21 @@
 //!
 //! [private cookies]: crate::http::CookieJar#private-cookies
 //!
+//! The `tower` feature, disabled by default, enables the [`tower`] module for
+//! interoperability with `tower` middleware.
+//!
+//! The `tls-acme` feature, disabled by default, obtains and renews TLS
+//! certificates automatically via ACME, as configured by
+//! [`AcmeConfig`](crate::config::AcmeConfig).
+//!
+//! The `compression` feature, disabled by default, compresses responses with
+//! gzip as negotiated with clients, when enabled by
+//! [`CompressionConfig`](crate::config::CompressionConfig). The `brotli`
+//! feature additionally compresses responses with Brotli.
+//!
+//! The `json` feature, disabled by default, enables `assert_json()` on local
+//! responses, as on [`LocalResponse`](crate::local::blocking::LocalResponse).
+//!
 //! ## Configuration
 //!
 //! Rocket and Rocket libraries are configured via the `Rocket.toml` file and/or
@@ -88,7 +103,8 @@
 //! The [`local`] module contains structures that facilitate unit and
 //! integration testing of a Rocket application. The top-level [`local`] module
 //! documentation and the [testing chapter of the guide] include detailed
-//! examples.
+//! examples. The [`record`] module records requests to files and replays them
+//! in tests.
 //!
 //! [testing chapter of the guide]: https://rocket.rs/v0.5/guide/testing/#testing
 
@@ -114,6 +130,13 @@ pub mod handler;
 pub mod fairing;
 pub mod error;
 pub mod catcher;
+pub mod cache;
+pub mod openapi;
+pub mod record;
+
+#[cfg(feature = "tower")]
+#[cfg_attr(nightly, doc(cfg(feature = "tower")))]
+pub mod tower;
 
 // Reexport of HTTP everything.
 pub mod http {
@@ -127,20 +150,29 @@ pub mod http {
 }
 
 mod shutdown;
+mod socket;
+mod load;
+mod tls;
+#[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+mod acme;
 mod router;
 mod rocket;
 mod codegen;
 mod ext;
 
 #[doc(inline)] pub use crate::response::Response;
-#[doc(hidden)] pub use crate::codegen::{StaticRouteInfo, StaticCatcherInfo};
+#[doc(hidden)] pub use crate::codegen::{StaticRouteInfo, StaticCacheInfo, StaticCatcherInfo};
 #[doc(inline)] pub use crate::data::Data;
 #[doc(inline)] pub use crate::config::Config;
 #[doc(inline)] pub use crate::catcher::Catcher;
 pub use crate::router::Route;
 pub use crate::request::{Request, State};
-pub use crate::rocket::{Cargo, Rocket};
-pub use crate::shutdown::Shutdown;
+pub use crate::rocket::{Cargo, Orbit, Rocket};
+pub use crate::shutdown::{Shutdown, ShutdownReason};
+pub use crate::socket::BoundSocket;
+pub use crate::load::{Load, ServerStats};
+pub use crate::tls::TlsReloader;
+pub use crate::logger::Logger;
 
 /// Alias to [`Rocket::ignite()`] Creates a new instance of `Rocket`.
 pub fn ignite() -> Rocket {
@@ -175,3 +207,13 @@ pub fn async_main<R>(fut: impl std::future::Future<Output = R> + Send) -> R {
         .expect("create tokio runtime")
         .block_on(fut)
 }
+
+/// WARNING: This is unstable! Do not use this method outside of Rocket!
+#[doc(hidden)]
+pub fn async_launch(rocket: Rocket) -> Result<(), error::Error> {
+    // Size the runtime by the configuration the application will launch with.
+    rocket.config.runtime()
+        .build()
+        .expect("create tokio runtime")
+        .block_on(rocket.launch())
+}
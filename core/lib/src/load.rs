@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::http::hyper::{self, header};
+use crate::http::private::AcceptCounts;
+use crate::request::{self, FromRequest, Request};
+
+/// The current load of a running server: open connections, requests in flight,
+/// and requests shed.
+///
+/// When the `max_connections` or `max_in_flight` limit of the
+/// [`server` table](crate::config::ServerConfig) is reached, Rocket sheds load
+/// instead of queueing it: requests beyond the limit are answered immediately
+/// with `503 Service Unavailable` and, if `retry_after` is set, a
+/// `Retry-After` header. Requests on a connection opened beyond
+/// `max_connections` are all shed, and the connection is closed after the
+/// response. `Load` reports the counts behind these decisions, for instance to
+/// export them as metrics.
+///
+/// A `Load` is retrieved via [`Cargo::load()`] or as a request guard. It is
+/// cheap to clone; clones observe the same counts.
+///
+/// [`Cargo::load()`]: crate::Cargo::load()
+///
+/// For the full set of server counters, including accepted and refused
+/// connections, see [`ServerStats`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Load;
+///
+/// #[get("/metrics")]
+/// fn metrics(load: Load) -> String {
+///     format!("connections {}\nin_flight {}\nshed_total {}\n",
+///         load.connections(), load.in_flight(), load.shed())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Load(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    connections: AtomicUsize,
+    in_flight: AtomicUsize,
+    shed: AtomicU64,
+    refused: AtomicU64,
+    accepts: Arc<AcceptCounts>,
+}
+
+impl Load {
+    /// The number of connections currently open, including those whose
+    /// requests are being shed.
+    #[inline]
+    pub fn connections(&self) -> usize {
+        self.0.connections.load(Ordering::Relaxed)
+    }
+
+    /// The number of requests currently being handled, from the time their
+    /// headers are received until their response is written.
+    #[inline]
+    pub fn in_flight(&self) -> usize {
+        self.0.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// The total number of requests answered with `503 Service Unavailable`
+    /// because a limit was reached, including routes'
+    /// [concurrency limits](crate::Route::max_concurrency()).
+    #[inline]
+    pub fn shed(&self) -> u64 {
+        self.0.shed.load(Ordering::Relaxed)
+    }
+
+    /// Counts a newly opened connection until the returned ticket is dropped.
+    /// Also returns whether the connection exceeds the limit `max`.
+    pub(crate) fn connection(&self, max: Option<u32>) -> (Ticket, bool) {
+        let open = self.0.connections.fetch_add(1, Ordering::Relaxed) + 1;
+        let ticket = Ticket { counters: self.0.clone(), in_flight: false };
+        let refused = max.map_or(false, |max| open > max as usize);
+        if refused {
+            self.0.refused.fetch_add(1, Ordering::Relaxed);
+        }
+
+        (ticket, refused)
+    }
+
+    /// Counts a request in flight until the returned ticket is dropped, or, if
+    /// that would exceed the limit `max`, counts the request as shed and
+    /// returns `None`.
+    pub(crate) fn request(&self, max: Option<u32>) -> Option<Ticket> {
+        let in_flight = self.0.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        let ticket = Ticket { counters: self.0.clone(), in_flight: true };
+        if max.map_or(false, |max| in_flight > max as usize) {
+            drop(ticket);
+            self.shed_request();
+            return None;
+        }
+
+        Some(ticket)
+    }
+
+    /// Counts a request as shed.
+    pub(crate) fn shed_request(&self) {
+        self.0.shed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The counts the listener records accepted connections and failed
+    /// handshakes in.
+    pub(crate) fn accept_counts(&self) -> Arc<AcceptCounts> {
+        self.0.accepts.clone()
+    }
+}
+
+/// Counters and gauges of a running server, available as managed state.
+///
+/// Rocket manages a `ServerStats` for every application, so it can be
+/// retrieved with [`State`](crate::State) in request handlers or with
+/// [`Cargo::state()`](crate::Cargo::state()) in fairings. Unlike counts
+/// inferred from request fairing callbacks, the stats include connections that
+/// never carry a complete request: those refused by `max_connections` and
+/// those whose TLS or PROXY protocol handshake failed. Counts are cumulative
+/// since launch; gauges reflect the current instant. Stats are not recorded
+/// for local requests.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::{ServerStats, State};
+///
+/// #[get("/metrics")]
+/// fn metrics(stats: State<'_, ServerStats>) -> String {
+///     format!("open_connections {}\naccepted_total {}\nhandshake_failures_total {}\n",
+///         stats.open_connections(), stats.accepted(), stats.handshake_failures())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServerStats(Load);
+
+impl ServerStats {
+    pub(crate) fn new(load: Load) -> ServerStats {
+        ServerStats(load)
+    }
+
+    /// Gauge: the number of connections currently open.
+    #[inline]
+    pub fn open_connections(&self) -> usize {
+        self.0.connections()
+    }
+
+    /// Gauge: the number of requests currently being handled.
+    #[inline]
+    pub fn active_requests(&self) -> usize {
+        self.0.in_flight()
+    }
+
+    /// Counter: the number of connections accepted, after any TLS or PROXY
+    /// protocol handshake succeeded.
+    #[inline]
+    pub fn accepted(&self) -> u64 {
+        (self.0).0.accepts.accepted.load(Ordering::Relaxed)
+    }
+
+    /// Counter: the number of accepted connections refused because they
+    /// exceeded `max_connections`.
+    #[inline]
+    pub fn refused(&self) -> u64 {
+        (self.0).0.refused.load(Ordering::Relaxed)
+    }
+
+    /// Counter: the number of connections closed because their TLS or PROXY
+    /// protocol handshake failed.
+    #[inline]
+    pub fn handshake_failures(&self) -> u64 {
+        (self.0).0.accepts.handshake_failures.load(Ordering::Relaxed)
+    }
+
+    /// Counter: the number of requests answered with `503 Service
+    /// Unavailable` because a limit was reached. See [`Load::shed()`].
+    #[inline]
+    pub fn shed(&self) -> u64 {
+        self.0.shed()
+    }
+}
+
+/// Keeps a connection or request counted by a [`Load`] until dropped.
+pub(crate) struct Ticket {
+    counters: Arc<Counters>,
+    in_flight: bool,
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        match self.in_flight {
+            true => self.counters.in_flight.fetch_sub(1, Ordering::Relaxed),
+            false => self.counters.connections.fetch_sub(1, Ordering::Relaxed),
+        };
+    }
+}
+
+/// The response to a shed request: `503 Service Unavailable`, asking the
+/// client to retry after `retry_after` seconds, if set, and, if `close`, to
+/// close the connection.
+pub(crate) fn unavailable(retry_after: Option<u32>, close: bool) -> hyper::Response<hyper::Body> {
+    let mut response = hyper::Response::new(hyper::Body::empty());
+    *response.status_mut() = hyper::StatusCode::SERVICE_UNAVAILABLE;
+
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_LENGTH, hyper::HeaderValue::from_static("0"));
+    if let Some(secs) = retry_after {
+        headers.insert(header::RETRY_AFTER, secs.into());
+    }
+
+    if close {
+        headers.insert(header::CONNECTION, hyper::HeaderValue::from_static("close"));
+    }
+
+    response
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Load {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(req.state.load.clone())
+    }
+}
@@ -0,0 +1,156 @@
+//! Checks backing the `assert_*` methods of `LocalResponse`, panicking with a
+//! description of the mismatch when a check fails.
+
+use crate::http::{HeaderMap, Status};
+
+/// The most bytes of a body to show in a failure message.
+const MAX_BODY: usize = 2048;
+
+fn show(bytes: &[u8]) -> String {
+    if bytes.len() <= MAX_BODY {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+
+    let shown = String::from_utf8_lossy(&bytes[..MAX_BODY]);
+    format!("{}... ({} more bytes)", shown, bytes.len() - MAX_BODY)
+}
+
+#[track_caller]
+pub(crate) fn status(actual: Status, expected: Status) {
+    if actual != expected {
+        panic!("response status mismatch\n  expected: {}\n     found: {}", expected, actual);
+    }
+}
+
+#[track_caller]
+pub(crate) fn header(headers: &HeaderMap<'_>, name: &str, expected: &str) {
+    let values: Vec<_> = headers.get(name).collect();
+    if values.iter().any(|value| *value == expected) {
+        return;
+    }
+
+    let mut message = format!("response header mismatch\n  expected: {}: {}\n", name, expected);
+    if values.is_empty() {
+        message.push_str(&format!("     found: no `{}` header; the response has:\n", name));
+        for header in headers.iter() {
+            message.push_str(&format!("            {}: {}\n", header.name(), header.value()));
+        }
+    } else {
+        for (i, value) in values.iter().enumerate() {
+            let label = if i == 0 { "     found: " } else { "            " };
+            message.push_str(&format!("{}{}: {}\n", label, name, value));
+        }
+    }
+
+    panic!("{}", message.trim_end());
+}
+
+#[track_caller]
+pub(crate) fn body_contains(body: Option<&[u8]>, needle: &str) {
+    let body = match body {
+        Some(body) => body,
+        None => panic!("response body mismatch\n  expected a body containing: {:?}\n  \
+            found: no body", needle),
+    };
+
+    let needle = needle.as_bytes();
+    if needle.is_empty() || body.windows(needle.len()).any(|window| window == needle) {
+        return;
+    }
+
+    // Find the longest prefix of `needle` in the body to point out where the
+    // body diverges from what was expected.
+    let (mut matched, mut at) = (0, 0);
+    for start in 0..body.len() {
+        let len = body[start..].iter().zip(needle).take_while(|(a, b)| a == b).count();
+        if len > matched {
+            matched = len;
+            at = start;
+        }
+    }
+
+    let mut message = format!("response body mismatch\n  expected a body containing: {:?}\n",
+        String::from_utf8_lossy(needle));
+
+    if matched > 0 {
+        let diverges = at + matched;
+        let found = &body[diverges..std::cmp::min(body.len(), diverges + needle.len() - matched)];
+        message.push_str(&format!("  closest match at byte {}: {:?} then {:?}, not {:?}\n",
+            at, String::from_utf8_lossy(&needle[..matched]), String::from_utf8_lossy(found),
+            String::from_utf8_lossy(&needle[matched..])));
+    }
+
+    message.push_str(&format!("  body:\n{}", show(body)));
+    panic!("{}", message);
+}
+
+#[cfg(feature = "json")]
+#[track_caller]
+pub(crate) fn json<T, F>(body: Option<&[u8]>, predicate: F)
+    where T: serde::de::DeserializeOwned, F: FnOnce(&T) -> bool
+{
+    let body = match body {
+        Some(body) => body,
+        None => panic!("response body mismatch\n  expected: a JSON body\n     found: no body"),
+    };
+
+    let value: T = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => panic!("response body mismatch\n  expected: JSON of type `{}`\n     \
+            found: {}\n  body:\n{}", std::any::type_name::<T>(), e, show(body)),
+    };
+
+    if !predicate(&value) {
+        let pretty = serde_json::from_slice::<serde_json::Value>(body).ok()
+            .and_then(|value| serde_json::to_string_pretty(&value).ok())
+            .unwrap_or_else(|| show(body));
+
+        panic!("response JSON body doesn't satisfy the predicate\n  body:\n{}", pretty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{Header, HeaderMap, Status};
+
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+        payload.downcast_ref::<String>().cloned().expect("formatted panic message")
+    }
+
+    #[test]
+    fn test_status() {
+        super::status(Status::Ok, Status::Ok);
+        let message = panic_message(|| super::status(Status::NotFound, Status::Ok));
+        assert!(message.contains("expected: 200 OK"));
+        assert!(message.contains("found: 404 Not Found"));
+    }
+
+    #[test]
+    fn test_header() {
+        let mut headers = HeaderMap::new();
+        headers.add(Header::new("X-Id", "1"));
+        headers.add(Header::new("X-Id", "2"));
+        super::header(&headers, "x-id", "2");
+
+        let message = panic_message(|| super::header(&headers, "X-Id", "3"));
+        assert!(message.contains("expected: X-Id: 3"));
+        assert!(message.contains("found: X-Id: 1"));
+        assert!(message.contains("X-Id: 2"));
+
+        let message = panic_message(|| super::header(&headers, "X-Other", "3"));
+        assert!(message.contains("no `X-Other` header"));
+    }
+
+    #[test]
+    fn test_body_contains() {
+        super::body_contains(Some(b"Hello, world!"), "world");
+
+        let message = panic_message(|| super::body_contains(Some(b"Hello, world!"), "word"));
+        assert!(message.contains("closest match at byte 7: \"wor\" then \"l\", not \"d\""));
+        assert!(message.ends_with("Hello, world!"));
+
+        let message = panic_message(|| super::body_contains(None, "world"));
+        assert!(message.contains("no body"));
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use tokio::time::{delay_for, timeout, Elapsed};
+
+use crate::local::asynchronous::{LocalRequest, LocalResponse};
+
+/// A batch of local requests dispatched concurrently.
+///
+/// A `Batch` is created by [`Client::batch()`](super::Client::batch()).
+/// Requests are added with [`add()`](Batch::add()), to start as soon as the
+/// batch is dispatched, or [`add_after()`](Batch::add_after()), to start after
+/// a delay. Dispatching the batch with [`dispatch()`](Batch::dispatch())
+/// dispatches every request concurrently and resolves once every request has
+/// been answered or has timed out.
+///
+/// ## Ordering
+///
+/// Requests that start at the same time start in the order they were added:
+/// each is dispatched up to its first suspension point, such as a request
+/// guard waiting on a lock, before the next is. Responses are returned in the
+/// order their requests were added, regardless of the order they complete in.
+///
+/// ## Example
+///
+/// Dispatch two requests at once, then a third 50ms later, failing any that
+/// takes longer than a second:
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use rocket::local::asynchronous::Client;
+///
+/// # rocket::async_test(async {
+/// let client = Client::new(rocket::ignite()).await.expect("valid rocket");
+/// let responses = client.batch()
+///     .add(client.get("/a"))
+///     .add(client.get("/b"))
+///     .add_after(Duration::from_millis(50), client.get("/c"))
+///     .timeout(Duration::from_secs(1))
+///     .dispatch()
+///     .await;
+///
+/// assert_eq!(responses.len(), 3);
+/// assert!(responses.iter().all(|response| response.is_ok()));
+/// # });
+/// ```
+pub struct Batch<'c> {
+    requests: Vec<(Duration, LocalRequest<'c>)>,
+    timeout: Option<Duration>,
+}
+
+impl<'c> Batch<'c> {
+    pub(crate) fn new() -> Batch<'c> {
+        Batch { requests: vec![], timeout: None }
+    }
+
+    /// Adds `request` to the batch, to be started as soon as the batch is
+    /// dispatched.
+    pub fn add(self, request: LocalRequest<'c>) -> Self {
+        self.add_after(Duration::from_secs(0), request)
+    }
+
+    /// Adds `request` to the batch, to be started `delay` after the batch is
+    /// dispatched.
+    pub fn add_after(mut self, delay: Duration, request: LocalRequest<'c>) -> Self {
+        self.requests.push((delay, request));
+        self
+    }
+
+    /// Sets the time each request in the batch has, from when it is started,
+    /// to be answered. A request that isn't answered in time is canceled, and
+    /// its result is an `Elapsed` error. By default, there is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Dispatches every request in the batch concurrently, returning their
+    /// responses, or timeout errors, in the order the requests were added.
+    pub async fn dispatch(self) -> Vec<Result<LocalResponse<'c>, Elapsed>> {
+        let limit = self.timeout;
+        join_all(self.requests.into_iter().map(|(delay, request)| async move {
+            if delay > Duration::from_secs(0) {
+                delay_for(delay).await;
+            }
+
+            match limit {
+                Some(limit) => timeout(limit, request.dispatch()).await,
+                None => Ok(request.dispatch().await),
+            }
+        })).await
+    }
+}
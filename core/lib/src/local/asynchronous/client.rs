@@ -1,8 +1,14 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
-use crate::local::asynchronous::{LocalRequest, LocalResponse};
-use crate::rocket::{Rocket, Cargo};
-use crate::http::{private::cookie, Method};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::local::Overrides;
+use crate::local::asynchronous::{Batch, LocalRequest, LocalResponse, Session};
+use crate::rocket::{Rocket, Cargo, hyper_service_fn};
+use crate::request::{GuardStubs, Upgraded};
+use crate::http::{hyper, private::cookie, Method};
 use crate::error::LaunchError;
 
 /// An `async` client to construct and dispatch local requests.
@@ -43,20 +49,26 @@ use crate::error::LaunchError;
 /// # });
 /// ```
 pub struct Client {
-    cargo: Cargo,
+    rocket: Arc<Rocket>,
     pub(in super) tracked: bool,
-    pub(in super) cookies: cookie::CookieJar,
+    pub(in super) cookies: Arc<cookie::CookieJar>,
+    sessions: Mutex<HashMap<String, Arc<cookie::CookieJar>>>,
+    pub(in super) stubs: Option<Arc<GuardStubs>>,
 }
 
 impl Client {
     pub(crate) async fn _new(
         mut rocket: Rocket,
-        tracked: bool
+        tracked: bool,
+        overrides: Option<Overrides>
     ) -> Result<Client, LaunchError> {
+        let stubs = overrides.and_then(|overrides| overrides.apply(&mut rocket));
         rocket.prelaunch_check().await?;
-        let cargo = rocket.into_cargo().await;
+        let rocket = Arc::new(rocket);
 
-        Ok(Client { cargo, tracked, cookies: cookie::CookieJar::new() })
+        let cookies = Arc::new(cookie::CookieJar::new());
+        let sessions = Mutex::new(HashMap::new());
+        Ok(Client { rocket, tracked, cookies, sessions, stubs })
     }
 
     // WARNING: This is unstable! Do not use this method outside of Rocket!
@@ -75,7 +87,7 @@ impl Client {
 
     #[inline(always)]
     pub(crate) fn _cargo(&self) -> &Cargo {
-        &self.cargo
+        self.rocket.cargo()
     }
 
     #[inline(always)]
@@ -83,11 +95,75 @@ impl Client {
         &self.cookies
     }
 
+    pub(crate) fn _session_jar(&self, name: &str) -> Arc<cookie::CookieJar> {
+        let mut sessions = self.sessions.lock().expect("sessions lock");
+        sessions.entry(name.into())
+            .or_insert_with(|| Arc::new(cookie::CookieJar::new()))
+            .clone()
+    }
+
+    pub(crate) async fn _dispatch_raw(&self, bytes: &[u8]) -> Vec<u8> {
+        let (client, server) = Upgraded::local_pair();
+        let rocket = self.rocket.clone();
+        let remote = ([0, 0, 0, 0], 0).into();
+        let service = hyper::service_fn(move |req| hyper_service_fn(rocket.clone(), remote, req));
+
+        // Serve the connection as a launched Rocket would serve HTTP/1,
+        // responding to requests even after the client has finished writing.
+        let connection = hyper::server_conn::Http::new()
+            .http1_only(true)
+            .http1_half_close(true)
+            .serve_connection(server, service)
+            .with_upgrades();
+
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                info_!("Raw local connection closed with error: {}.", e);
+            }
+        });
+
+        // The connection is bounded: read the response while writing so that
+        // neither end waits on the other.
+        let mut response = vec![];
+        let (mut reader, mut writer) = tokio::io::split(client);
+        let write = async {
+            writer.write_all(bytes).await?;
+            writer.shutdown().await
+        };
+
+        let (written, read) = futures::join!(write, reader.read_to_end(&mut response));
+        if let Err(e) = written.and(read) {
+            error_!("Failed to dispatch raw local request: {}.", e);
+        }
+
+        response
+    }
+
     #[inline(always)]
     fn _req<'c, 'u: 'c, U>(&'c self, method: Method, uri: U) -> LocalRequest<'c>
         where U: Into<Cow<'u, str>>
     {
-        LocalRequest::new(self, method, uri.into())
+        LocalRequest::new(self, self.cookies.clone(), method, uri.into())
+    }
+
+    /// Returns an empty [`Batch`] of requests to dispatch concurrently.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::Client;
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite()).await.expect("valid rocket");
+    /// let responses = client.batch()
+    ///     .add(client.get("/"))
+    ///     .add(client.post("/"))
+    ///     .dispatch()
+    ///     .await;
+    /// # });
+    /// ```
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new()
     }
 
     // Generates the public API methods, which call the private methods above.
@@ -6,10 +6,16 @@
 //!
 //! See the [top-level documentation](super) for more usage details.
 
+mod batch;
 mod client;
 mod request;
 mod response;
+mod session;
+mod websocket;
 
+pub use batch::*;
 pub use client::*;
 pub use request::*;
 pub use response::*;
+pub use session::*;
+pub use websocket::*;
@@ -1,7 +1,10 @@
 use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{Request, Data};
-use crate::http::{Status, Method, uri::Origin, ext::IntoOwned};
+use crate::request::{PendingUpgrade, Upgraded};
+use crate::http::{Status, Method, uri::Origin, ext::IntoOwned, private::cookie};
 
 use super::{Client, LocalResponse};
 
@@ -32,6 +35,7 @@ use super::{Client, LocalResponse};
 pub struct LocalRequest<'c> {
     pub(in super) client: &'c Client,
     pub(in super) request: Request<'c>,
+    jar: Arc<cookie::CookieJar>,
     data: Vec<u8>,
     uri: Cow<'c, str>,
 }
@@ -39,6 +43,7 @@ pub struct LocalRequest<'c> {
 impl<'c> LocalRequest<'c> {
     pub(crate) fn new(
         client: &'c Client,
+        jar: Arc<cookie::CookieJar>,
         method: Method,
         uri: Cow<'c, str>
     ) -> LocalRequest<'c> {
@@ -49,12 +54,17 @@ impl<'c> LocalRequest<'c> {
 
         // Add any cookies we know about.
         if client.tracked {
-            for crumb in client.cookies.iter() {
+            for crumb in jar.iter() {
                 request.cookies().add_original(crumb.into_cookie());
             }
         }
 
-        LocalRequest { client, request, uri, data: vec![] }
+        // Let guards that opt in find the client's stubs.
+        if let Some(stubs) = &client.stubs {
+            request.set_guard_stubs(stubs.clone());
+        }
+
+        LocalRequest { client, request, jar, uri, data: vec![] }
     }
 
     pub(crate) fn _request(&self) -> &Request<'c> {
@@ -82,17 +92,36 @@ impl<'c> LocalRequest<'c> {
             }).await
         }
 
+        // Like a connection, a request without a body asking for an upgrade
+        // can be upgraded, here to an in-memory connection.
+        let mut upgrade = None;
+        if self.request.headers().contains("Upgrade") && self.data.is_empty() {
+            let (server, client) = Upgraded::local_pair();
+            let pending = Arc::new(PendingUpgrade::local(server));
+            self.request.state.upgrade = Some(pending.clone());
+            upgrade = Some((pending, client));
+        }
+
         // Actually dispatch the request.
         let mut data = Data::local(self.data);
         let token = rocket.preprocess_request(&mut self.request, &mut data).await;
-        let response = LocalResponse::new(self.request, move |req| {
+        let mut response = LocalResponse::new(self.request, move |req| {
             rocket.dispatch(token, req, data)
         }).await;
 
-        // If the client is tracking cookies, updates the internal cookie jar
-        // with the changes reflected by `response`.
+        // Hand the server's end of an upgraded connection to the handler that
+        // accepted it and keep the client's end in the response.
+        if let Some((pending, client)) = upgrade {
+            if response.status() == Status::SwitchingProtocols {
+                tokio::spawn(async move { pending.finish().await });
+                response.upgraded = Some(client);
+            }
+        }
+
+        // If the client is tracking cookies, updates the cookie jar of the
+        // request's session with the changes reflected by `response`.
         if self.client.tracked {
-            let jar = &self.client.cookies;
+            let jar = &self.jar;
             let current_time = time::OffsetDateTime::now_utc();
             for cookie in response.cookies().iter() {
                 if let Some(expires) = cookie.expires() {
@@ -109,6 +138,33 @@ impl<'c> LocalRequest<'c> {
         response
     }
 
+    /// Dispatches the request, returning the response if it is ready within
+    /// `timeout` and an `Elapsed` error otherwise. A request that times out
+    /// is canceled: its handler, if running, is dropped at its current
+    /// suspension point.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use rocket::local::asynchronous::Client;
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite()).await.expect("valid rocket");
+    /// let response = client.get("/")
+    ///     .dispatch_timeout(Duration::from_secs(1))
+    ///     .await
+    ///     .expect("response within a second");
+    /// # });
+    /// ```
+    pub async fn dispatch_timeout(
+        self,
+        timeout: Duration
+    ) -> Result<LocalResponse<'c>, tokio::time::Elapsed> {
+        tokio::time::timeout(timeout, self.dispatch()).await
+    }
+
     pub_request_impl!("# use rocket::local::asynchronous::Client;
         use rocket::local::asynchronous::LocalRequest;" async await);
 }
@@ -118,6 +174,7 @@ impl<'c> Clone for LocalRequest<'c> {
         LocalRequest {
             client: self.client,
             request: self.request.clone(),
+            jar: self.jar.clone(),
             data: self.data.clone(),
             uri: self.uri.clone(),
         }
@@ -2,11 +2,16 @@ use std::io;
 use std::future::Future;
 use std::{pin::Pin, task::{Context, Poll}};
 
-use tokio::io::AsyncRead;
+use futures::stream::{self, LocalBoxStream, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
-use crate::http::CookieJar;
+use crate::http::{CookieJar, Status};
+use crate::local::{assert, websocket, Event, EventParser};
+use crate::request::Upgraded;
 use crate::{Request, Response};
 
+use super::WebSocket;
+
 /// An `async` response from a dispatched [`LocalRequest`](super::LocalRequest).
 ///
 /// This `LocalResponse` implements [`tokio::io::AsyncRead`]. As such, if
@@ -57,6 +62,7 @@ pub struct LocalResponse<'c> {
     _request: Box<Request<'c>>,
     response: Response<'c>,
     cookies: CookieJar<'c>,
+    pub(in super) upgraded: Option<Upgraded>,
 }
 
 impl<'c> LocalResponse<'c> {
@@ -94,7 +100,7 @@ impl<'c> LocalResponse<'c> {
                 cookies.add(cookie.into_owned());
             }
 
-            LocalResponse { cookies, _request: boxed_req, response, }
+            LocalResponse { cookies, _request: boxed_req, response, upgraded: None }
         }
     }
 }
@@ -116,6 +122,221 @@ impl LocalResponse<'_> {
         self.response.body_bytes().await
     }
 
+    /// Reads the body into memory, leaving an identical body in its place.
+    pub(crate) async fn _buffered_body(&mut self) -> Option<Vec<u8>> {
+        let bytes = self.response.body_bytes().await?;
+        self.response.set_sized_body(bytes.len(), io::Cursor::new(bytes.clone()));
+        Some(bytes)
+    }
+
+    /// Asserts that the body of the response contains `needle`, returning
+    /// `self` for further assertions. The body is read into memory; it can
+    /// still be read afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, showing the body and where it diverges from the closest match,
+    /// if the body doesn't contain `needle` or there is no body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::http::Status;
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite()).await.expect("valid rocket");
+    /// let mut response = client.get("/missing").dispatch().await;
+    /// response.assert_status(Status::NotFound)
+    ///     .assert_body_contains("could not be found").await;
+    /// # });
+    /// ```
+    pub async fn assert_body_contains(&mut self, needle: &str) -> &mut Self {
+        let body = self._buffered_body().await;
+        assert::body_contains(body.as_deref(), needle);
+        self
+    }
+
+    /// Asserts that the body of the response is JSON deserializing to a `T`
+    /// that satisfies `predicate`, returning `self` for further assertions.
+    /// The body is read into memory; it can still be read afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, showing the body, if it isn't JSON, doesn't deserialize to a
+    /// `T`, or the `T` doesn't satisfy `predicate`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::response::content::Json;
+    ///
+    /// #[get("/user")]
+    /// fn user() -> Json<&'static str> {
+    ///     Json(r#"{ "name": "Bob", "roles": ["admin"] }"#)
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct User { name: String, roles: Vec<String> }
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite().mount("/", routes![user])).await.unwrap();
+    /// let mut response = client.get("/user").dispatch().await;
+    /// response.assert_json(|user: &User| user.name == "Bob" && user.roles.len() == 1).await;
+    /// # });
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(nightly, doc(cfg(feature = "json")))]
+    pub async fn assert_json<T, F>(&mut self, predicate: F) -> &mut Self
+        where T: serde::de::DeserializeOwned, F: FnOnce(&T) -> bool
+    {
+        let body = self._buffered_body().await;
+        assert::json(body.as_deref(), predicate);
+        self
+    }
+
+    /// Returns a stream of the server-sent events in the body of the response,
+    /// reading the body as events are requested.
+    ///
+    /// The stream ends when the body does. An event that the end of the body
+    /// interrupts is discarded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::futures::stream::StreamExt;
+    /// use rocket::http::ContentType;
+    /// use rocket::response::content::Content;
+    ///
+    /// #[get("/updates")]
+    /// fn updates() -> Content<&'static str> {
+    ///     let event_stream = ContentType::new("text", "event-stream");
+    ///     Content(event_stream, "event: tick\ndata: 1\n\nevent: tick\ndata: 2\n\n")
+    /// }
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite().mount("/", routes![updates])).await.unwrap();
+    /// let mut response = client.get("/updates").dispatch().await;
+    /// let mut events = response.events();
+    ///
+    /// let first = events.next().await.unwrap().unwrap();
+    /// assert_eq!(first.event(), Some("tick"));
+    /// assert_eq!(first.data(), "1");
+    /// # });
+    /// ```
+    pub fn events(&mut self) -> LocalBoxStream<'_, io::Result<Event>> {
+        let state = (self, EventParser::default(), false);
+        stream::unfold(state, |(response, mut parser, done)| async move {
+            if done {
+                return None;
+            }
+
+            let mut buf = [0; 1024];
+            loop {
+                if let Some(event) = parser.next_event() {
+                    return Some((Ok(event), (response, parser, false)));
+                }
+
+                match response.read(&mut buf).await {
+                    Ok(0) => return None,
+                    Ok(n) => parser.push(&buf[..n]),
+                    Err(e) => return Some((Err(e), (response, parser, true))),
+                }
+            }
+        }).boxed_local()
+    }
+
+    /// Returns the client's end of the connection the request was upgraded
+    /// on, if the response is a `101 Switching Protocols` response to a
+    /// request accepted via [`Request::upgrade()`]. The handler registered by
+    /// the request runs in the background, on the server's end.
+    ///
+    /// The connection is returned only once; subsequent calls return `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::Request;
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::request::Upgrade;
+    /// use rocket::http::{Header, Status};
+    /// use rocket::tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+    ///
+    /// #[get("/echo")]
+    /// fn echo(req: &Request<'_>) -> Option<Upgrade> {
+    ///     req.upgrade("echo", |io| async move {
+    ///         let (mut reader, mut writer) = io::split(io);
+    ///         let _ = io::copy(&mut reader, &mut writer).await;
+    ///     })
+    /// }
+    ///
+    /// # rocket::async_test(async {
+    /// let client = Client::new(rocket::ignite().mount("/", routes![echo])).await.unwrap();
+    /// let mut response = client.get("/echo")
+    ///     .header(Header::new("Connection", "upgrade"))
+    ///     .header(Header::new("Upgrade", "echo"))
+    ///     .dispatch()
+    ///     .await;
+    ///
+    /// assert_eq!(response.status(), Status::SwitchingProtocols);
+    /// let mut io = response.upgraded().unwrap();
+    /// io.write_all(b"ping").await.unwrap();
+    ///
+    /// let mut buf = [0; 4];
+    /// io.read_exact(&mut buf).await.unwrap();
+    /// assert_eq!(&buf, b"ping");
+    /// # });
+    /// ```
+    ///
+    /// [`Request::upgrade()`]: crate::Request::upgrade()
+    pub fn upgraded(&mut self) -> Option<Upgraded> {
+        self.upgraded.take()
+    }
+
+    /// Returns the client's end of the WebSocket connection the request was
+    /// upgraded to, if the request was sent with
+    /// [`LocalRequest::websocket()`](super::LocalRequest::websocket()) and the
+    /// response accepts its handshake: it is a `101 Switching Protocols`
+    /// response with the `Sec-WebSocket-Accept` header the request's key
+    /// calls for.
+    ///
+    /// Like [`upgraded()`](Self::upgraded()), the connection is returned only
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rocket::local::asynchronous::Client;
+    /// use rocket::local::Message;
+    ///
+    /// # async fn f(client: Client) -> std::io::Result<()> {
+    /// let mut response = client.get("/chat").websocket().dispatch().await;
+    /// let mut socket = response.websocket().expect("accepted handshake");
+    /// socket.send(Message::Text("hello".into())).await?;
+    /// if let Some(Message::Text(reply)) = socket.receive().await? {
+    ///     println!("server said: {}", reply);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn websocket(&mut self) -> Option<WebSocket> {
+        let key = self._request.headers().get_one("Sec-WebSocket-Key")?;
+        let accept = self.response.headers().get_one("Sec-WebSocket-Accept")?;
+        let accepted = self.response.status() == Status::SwitchingProtocols
+            && accept == websocket::accept_key(key);
+
+        if !accepted {
+            return None;
+        }
+
+        self.upgraded().map(WebSocket::new)
+    }
+
     // Generates the public API methods, which call the private methods above.
     pub_response_impl!("# use rocket::local::asynchronous::Client;
         use rocket::local::asynchronous::LocalResponse;" async await);
@@ -0,0 +1,48 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::local::asynchronous::{Client, LocalRequest};
+use crate::http::{private::cookie, Method};
+
+/// A named session of an `async` [`Client`], with its own cookies.
+///
+/// A `Session` is returned by [`Client::session()`]. Requests created through
+/// a session carry the cookies of that session, and, when the client tracks
+/// cookies, the changes their responses make to cookies are reflected only in
+/// that session. This allows a single client to act as several users at once.
+/// Sessions with the same name share cookies; the client's own cookies, those
+/// of requests created directly through the client, are separate from those
+/// of every session.
+///
+/// For the `blocking` version, see
+/// [`blocking::Session`](crate::local::blocking::Session).
+///
+/// ## Example
+///
+/// ```rust
+/// use rocket::local::asynchronous::Client;
+///
+/// # rocket::async_test(async {
+/// let client = Client::new(rocket::ignite()).await.expect("valid rocket");
+/// let alice = client.session("alice");
+/// let bob = client.session("bob");
+///
+/// let response = alice.post("/login").body("username=alice").dispatch().await;
+/// let response = bob.get("/messages").dispatch().await;
+/// # });
+/// ```
+pub struct Session<'c> {
+    client: &'c Client,
+    name: String,
+    jar: Arc<cookie::CookieJar>,
+}
+
+impl<'c> Session<'c> {
+    pub(crate) fn new(client: &'c Client, name: &str) -> Session<'c> {
+        let jar = client._session_jar(name);
+        Session { client, name: name.into(), jar }
+    }
+
+    // Generates the public API methods.
+    pub_session_impl!("use rocket::local::asynchronous::Client;");
+}
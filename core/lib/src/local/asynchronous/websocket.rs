@@ -0,0 +1,60 @@
+use std::io;
+
+use crate::local::websocket::{self, Message, Reader};
+use crate::request::Upgraded;
+
+/// The client's end of a local WebSocket connection.
+///
+/// A `WebSocket` is returned by
+/// [`LocalResponse::websocket()`](super::LocalResponse::websocket()) once the
+/// server has accepted the handshake of a request sent with
+/// [`LocalRequest::websocket()`](super::LocalRequest::websocket()). Messages
+/// are framed and masked as RFC 6455 requires of clients, and fragmented
+/// messages are reassembled when received.
+///
+/// A close frame received from the server is answered with a close frame of
+/// the same status code, completing the closing handshake; pings are returned
+/// like any other message.
+pub struct WebSocket {
+    io: Upgraded,
+    reader: Reader,
+    closed: bool,
+}
+
+impl WebSocket {
+    pub(crate) fn new(io: Upgraded) -> WebSocket {
+        WebSocket { io, reader: Reader::default(), closed: false }
+    }
+
+    /// Sends `message` to the server.
+    ///
+    /// Fails if the connection has closed or if the message is a control
+    /// message whose payload exceeds 125 bytes.
+    pub async fn send(&mut self, message: Message) -> io::Result<()> {
+        if let Message::Close(..) = message {
+            self.closed = true;
+        }
+
+        websocket::write(&mut self.io, message).await
+    }
+
+    /// Receives the next message from the server, returning `None` once the
+    /// server has closed the connection.
+    pub async fn receive(&mut self) -> io::Result<Option<Message>> {
+        let message = self.reader.read(&mut self.io).await?;
+        if let Some(Message::Close(code, _)) = message {
+            if !self.closed {
+                self.closed = true;
+                websocket::write(&mut self.io, Message::Close(code, String::new())).await?;
+            }
+        }
+
+        Ok(message)
+    }
+}
+
+impl std::fmt::Debug for WebSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocket").field("closed", &self.closed).finish()
+    }
+}
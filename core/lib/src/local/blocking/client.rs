@@ -2,7 +2,7 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 
 use crate::error::LaunchError;
-use crate::local::{asynchronous, blocking::{LocalRequest, LocalResponse}};
+use crate::local::{Overrides, asynchronous, blocking::{LocalRequest, LocalResponse, Session}};
 use crate::rocket::{Rocket, Cargo};
 use crate::http::Method;
 
@@ -31,7 +31,11 @@ pub struct Client {
 }
 
 impl Client {
-    fn _new(rocket: Rocket, tracked: bool) -> Result<Client, LaunchError> {
+    fn _new(
+        rocket: Rocket,
+        tracked: bool,
+        overrides: Option<Overrides>
+    ) -> Result<Client, LaunchError> {
         let mut runtime = tokio::runtime::Builder::new()
             .basic_scheduler()
             .enable_all()
@@ -39,7 +43,7 @@ impl Client {
             .expect("create tokio runtime");
 
         // Initialize the Rocket instance
-        let inner = runtime.block_on(asynchronous::Client::_new(rocket, tracked))?;
+        let inner = runtime.block_on(asynchronous::Client::_new(rocket, tracked, overrides))?;
         Ok(Self { inner, runtime: RefCell::new(runtime) })
     }
 
@@ -72,6 +76,11 @@ impl Client {
         self.inner._cookies()
     }
 
+    #[inline(always)]
+    fn _dispatch_raw(&self, bytes: &[u8]) -> Vec<u8> {
+        self.block_on(self.inner._dispatch_raw(bytes))
+    }
+
     #[inline(always)]
     pub(crate) fn _req<'c, 'u: 'c, U>(
         &'c self,
@@ -80,7 +89,7 @@ impl Client {
     ) -> LocalRequest<'c>
         where U: Into<Cow<'u, str>>
     {
-        LocalRequest::new(self, method, uri.into())
+        LocalRequest::new(self, self.inner.cookies.clone(), method, uri.into())
     }
 
     // Generates the public API methods, which call the private methods above.
@@ -10,7 +10,13 @@
 mod client;
 mod request;
 mod response;
+mod session;
+mod upgraded;
+mod websocket;
 
 pub use self::client::*;
 pub use self::request::*;
 pub use self::response::*;
+pub use self::session::*;
+pub use self::upgraded::*;
+pub use self::websocket::*;
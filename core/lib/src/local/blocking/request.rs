@@ -1,6 +1,7 @@
 use std::borrow::Cow;
+use std::sync::Arc;
 
-use crate::{Request, http::Method, local::asynchronous};
+use crate::{Request, http::{Method, private::cookie}, local::asynchronous};
 
 use super::{Client, LocalResponse};
 
@@ -36,10 +37,11 @@ impl<'c> LocalRequest<'c> {
     #[inline]
     pub(crate) fn new(
         client: &'c Client,
+        jar: Arc<cookie::CookieJar>,
         method: Method,
         uri: Cow<'c, str>
     ) -> LocalRequest<'c> {
-        let inner = asynchronous::LocalRequest::new(&client.inner, method, uri);
+        let inner = asynchronous::LocalRequest::new(&client.inner, jar, method, uri);
         Self { inner, client }
     }
 
@@ -2,8 +2,9 @@ use std::io;
 use tokio::io::AsyncReadExt;
 
 use crate::{Response, local::asynchronous, http::CookieJar};
+use crate::local::{assert, Event, EventParser};
 
-use super::Client;
+use super::{Client, Upgraded, WebSocket};
 
 /// A `blocking` response from a dispatched [`LocalRequest`](super::LocalRequest).
 ///
@@ -72,6 +73,205 @@ impl LocalResponse<'_> {
         self.client.block_on(self.inner._into_bytes())
     }
 
+    /// Asserts that the body of the response contains `needle`, returning
+    /// `self` for further assertions. The body is read into memory; it can
+    /// still be read afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, showing the body and where it diverges from the closest match,
+    /// if the body doesn't contain `needle` or there is no body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::blocking::Client;
+    /// use rocket::http::Status;
+    ///
+    /// let client = Client::new(rocket::ignite()).expect("valid rocket");
+    /// client.get("/missing").dispatch()
+    ///     .assert_status(Status::NotFound)
+    ///     .assert_header("Content-Type", "text/html; charset=utf-8")
+    ///     .assert_body_contains("could not be found");
+    /// ```
+    #[track_caller]
+    pub fn assert_body_contains(&mut self, needle: &str) -> &mut Self {
+        let body = self.client.block_on(self.inner._buffered_body());
+        assert::body_contains(body.as_deref(), needle);
+        self
+    }
+
+    /// Asserts that the body of the response is JSON deserializing to a `T`
+    /// that satisfies `predicate`, returning `self` for further assertions.
+    /// The body is read into memory; it can still be read afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics, showing the body, if it isn't JSON, doesn't deserialize to a
+    /// `T`, or the `T` doesn't satisfy `predicate`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::local::blocking::Client;
+    /// use rocket::response::content::Json;
+    ///
+    /// #[get("/user")]
+    /// fn user() -> Json<&'static str> {
+    ///     Json(r#"{ "name": "Bob", "roles": ["admin"] }"#)
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct User { name: String, roles: Vec<String> }
+    ///
+    /// let client = Client::new(rocket::ignite().mount("/", routes![user])).unwrap();
+    /// client.get("/user").dispatch()
+    ///     .assert_json(|user: &User| user.name == "Bob" && user.roles.len() == 1)
+    ///     .assert_body_contains("admin");
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(nightly, doc(cfg(feature = "json")))]
+    #[track_caller]
+    pub fn assert_json<T, F>(&mut self, predicate: F) -> &mut Self
+        where T: serde::de::DeserializeOwned, F: FnOnce(&T) -> bool
+    {
+        let body = self.client.block_on(self.inner._buffered_body());
+        assert::json(body.as_deref(), predicate);
+        self
+    }
+
+    /// Returns an iterator over the server-sent events in the body of the
+    /// response, reading the body as events are requested.
+    ///
+    /// The iterator ends when the body does. An event that the end of the body
+    /// interrupts is discarded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::local::blocking::Client;
+    /// use rocket::http::ContentType;
+    /// use rocket::response::content::Content;
+    ///
+    /// #[get("/updates")]
+    /// fn updates() -> Content<&'static str> {
+    ///     let event_stream = ContentType::new("text", "event-stream");
+    ///     Content(event_stream, "event: tick\ndata: 1\n\nevent: tick\ndata: 2\n\n")
+    /// }
+    ///
+    /// let client = Client::new(rocket::ignite().mount("/", routes![updates])).unwrap();
+    /// let mut response = client.get("/updates").dispatch();
+    /// let data: Vec<_> = response.events()
+    ///     .map(|event| event.unwrap().data().to_string())
+    ///     .collect();
+    ///
+    /// assert_eq!(data, vec!["1", "2"]);
+    /// ```
+    pub fn events(&mut self) -> Box<dyn Iterator<Item = io::Result<Event>> + '_> {
+        let mut parser = EventParser::default();
+        let mut done = false;
+        Box::new(std::iter::from_fn(move || {
+            let mut buf = [0; 1024];
+            while !done {
+                if let Some(event) = parser.next_event() {
+                    return Some(Ok(event));
+                }
+
+                match io::Read::read(self, &mut buf) {
+                    Ok(0) => done = true,
+                    Ok(n) => parser.push(&buf[..n]),
+                    Err(e) => {
+                        done = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+
+            None
+        }))
+    }
+
+    /// Returns the client's end of the connection the request was upgraded
+    /// on, if the response is a `101 Switching Protocols` response to a
+    /// request accepted via [`Request::upgrade()`]. The handler registered by
+    /// the request runs on the server's end while the connection is used.
+    ///
+    /// The connection is returned only once; subsequent calls return `None`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use std::io::{Read, Write};
+    ///
+    /// use rocket::Request;
+    /// use rocket::local::blocking::Client;
+    /// use rocket::request::Upgrade;
+    /// use rocket::http::{Header, Status};
+    /// use rocket::tokio::io;
+    ///
+    /// #[get("/echo")]
+    /// fn echo(req: &Request<'_>) -> Option<Upgrade> {
+    ///     req.upgrade("echo", |io| async move {
+    ///         let (mut reader, mut writer) = io::split(io);
+    ///         let _ = io::copy(&mut reader, &mut writer).await;
+    ///     })
+    /// }
+    ///
+    /// let client = Client::new(rocket::ignite().mount("/", routes![echo])).unwrap();
+    /// let mut response = client.get("/echo")
+    ///     .header(Header::new("Connection", "upgrade"))
+    ///     .header(Header::new("Upgrade", "echo"))
+    ///     .dispatch();
+    ///
+    /// assert_eq!(response.status(), Status::SwitchingProtocols);
+    /// let mut io = response.upgraded().unwrap();
+    /// io.write_all(b"ping").unwrap();
+    ///
+    /// let mut buf = [0; 4];
+    /// io.read_exact(&mut buf).unwrap();
+    /// assert_eq!(&buf, b"ping");
+    /// ```
+    ///
+    /// [`Request::upgrade()`]: crate::Request::upgrade()
+    pub fn upgraded(&mut self) -> Option<Upgraded<'_>> {
+        let io = self.inner.upgraded()?;
+        Some(Upgraded { io, client: self.client })
+    }
+
+    /// Returns the client's end of the WebSocket connection the request was
+    /// upgraded to, if the request was sent with
+    /// [`LocalRequest::websocket()`](super::LocalRequest::websocket()) and the
+    /// response accepts its handshake: it is a `101 Switching Protocols`
+    /// response with the `Sec-WebSocket-Accept` header the request's key
+    /// calls for.
+    ///
+    /// Like [`upgraded()`](Self::upgraded()), the connection is returned only
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rocket::local::blocking::Client;
+    /// use rocket::local::Message;
+    ///
+    /// # fn f(client: Client) -> std::io::Result<()> {
+    /// let mut response = client.get("/chat").websocket().dispatch();
+    /// let mut socket = response.websocket().expect("accepted handshake");
+    /// socket.send(Message::Text("hello".into()))?;
+    /// if let Some(Message::Text(reply)) = socket.receive()? {
+    ///     println!("server said: {}", reply);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn websocket(&mut self) -> Option<WebSocket<'_>> {
+        let inner = self.inner.websocket()?;
+        Some(WebSocket { inner, client: self.client })
+    }
+
     // Generates the public API methods, which call the private methods above.
     pub_response_impl!("# use rocket::local::blocking::Client;
         use rocket::local::blocking::LocalResponse;");
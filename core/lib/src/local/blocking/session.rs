@@ -0,0 +1,41 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use crate::local::blocking::{Client, LocalRequest};
+use crate::http::{private::cookie, Method};
+
+/// A named session of a `blocking` [`Client`], with its own cookies.
+///
+/// A `Session` is returned by [`Client::session()`]. Requests created through
+/// a session carry the cookies of that session, and, when the client tracks
+/// cookies, the changes their responses make to cookies are reflected only in
+/// that session. For details, see
+/// [`asynchronous::Session`](crate::local::asynchronous::Session).
+///
+/// ## Example
+///
+/// ```rust
+/// use rocket::local::blocking::Client;
+///
+/// let client = Client::new(rocket::ignite()).expect("valid rocket");
+/// let alice = client.session("alice");
+/// let bob = client.session("bob");
+///
+/// let response = alice.post("/login").body("username=alice").dispatch();
+/// let response = bob.get("/messages").dispatch();
+/// ```
+pub struct Session<'c> {
+    client: &'c Client,
+    name: String,
+    jar: Arc<cookie::CookieJar>,
+}
+
+impl<'c> Session<'c> {
+    pub(crate) fn new(client: &'c Client, name: &str) -> Session<'c> {
+        let jar = client.inner._session_jar(name);
+        Session { client, name: name.into(), jar }
+    }
+
+    // Generates the public API methods.
+    pub_session_impl!("use rocket::local::blocking::Client;");
+}
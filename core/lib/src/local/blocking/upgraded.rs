@@ -0,0 +1,41 @@
+use std::io;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::request;
+
+use super::Client;
+
+/// The client's end of an upgraded local connection.
+///
+/// An `Upgraded` is returned by
+/// [`LocalResponse::upgraded()`](super::LocalResponse::upgraded()). It
+/// implements [`io::Read`] and [`io::Write`], blocking on the client's runtime,
+/// which also runs the server's handler of the connection, while reading and
+/// writing.
+pub struct Upgraded<'c> {
+    pub(in super) io: request::Upgraded,
+    pub(in super) client: &'c Client,
+}
+
+impl io::Read for Upgraded<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.client.block_on(self.io.read(buf))
+    }
+}
+
+impl io::Write for Upgraded<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.client.block_on(self.io.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.client.block_on(self.io.flush())
+    }
+}
+
+impl std::fmt::Debug for Upgraded<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.io.fmt(f)
+    }
+}
@@ -0,0 +1,36 @@
+use std::io;
+
+use crate::local::{asynchronous, websocket::Message};
+
+use super::Client;
+
+/// The client's end of a local WebSocket connection.
+///
+/// A `WebSocket` is returned by
+/// [`LocalResponse::websocket()`](super::LocalResponse::websocket()). It blocks
+/// on the client's runtime, which also runs the server's handler of the
+/// connection, while sending and receiving. See the
+/// [`async` variant](crate::local::asynchronous::WebSocket) for details.
+pub struct WebSocket<'c> {
+    pub(in super) inner: asynchronous::WebSocket,
+    pub(in super) client: &'c Client,
+}
+
+impl WebSocket<'_> {
+    /// Sends `message` to the server.
+    pub fn send(&mut self, message: Message) -> io::Result<()> {
+        self.client.block_on(self.inner.send(message))
+    }
+
+    /// Receives the next message from the server, returning `None` once the
+    /// server has closed the connection.
+    pub fn receive(&mut self) -> io::Result<Option<Message>> {
+        self.client.block_on(self.inner.receive())
+    }
+}
+
+impl std::fmt::Debug for WebSocket<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -71,7 +71,7 @@ macro_rules! pub_client_impl {
     /// ```
     #[inline(always)]
     pub $($prefix)? fn new(rocket: Rocket) -> Result<Self, LaunchError> {
-        Self::_new(rocket, true) $(.$suffix)?
+        Self::_new(rocket, true, None) $(.$suffix)?
     }
 
     /// Construct a new `Client` from an instance of `Rocket` _without_
@@ -94,7 +94,63 @@ macro_rules! pub_client_impl {
     /// let client = Client::untracked(rocket);
     /// ```
     pub $($prefix)? fn untracked(rocket: Rocket) -> Result<Self, LaunchError> {
-        Self::_new(rocket, true) $(.$suffix)?
+        Self::_new(rocket, true, None) $(.$suffix)?
+    }
+
+    /// Construct a new `Client` from an instance of `Rocket` with cookie
+    /// tracking, applying the [`Overrides`](crate::local::Overrides) built by
+    /// `f` to `rocket` first.
+    ///
+    /// # Errors
+    ///
+    /// If launching the `Rocket` instance would fail, excepting network errors,
+    /// the `LaunchError` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if state is overridden twice for the same type.
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    ///
+    /// struct Db(&'static str);
+    ///
+    /// let rocket = rocket::ignite().manage(Db("postgres://production"));
+    /// let client = Client::tracked_with(rocket, |o| o.state(Db("mock")));
+    /// ```
+    pub $($prefix)? fn tracked_with<F>(rocket: Rocket, f: F) -> Result<Self, LaunchError>
+        where F: FnOnce(crate::local::Overrides) -> crate::local::Overrides
+    {
+        let overrides = f(crate::local::Overrides::default());
+        Self::_new(rocket, true, Some(overrides)) $(.$suffix)?
+    }
+
+    /// Construct a new `Client` from an instance of `Rocket` _without_
+    /// cookie tracking, applying the [`Overrides`](crate::local::Overrides)
+    /// built by `f` to `rocket` first.
+    ///
+    /// # Errors
+    ///
+    /// If launching the `Rocket` instance would fail, excepting network
+    /// errors, the `LaunchError` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if state is overridden twice for the same type.
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    ///
+    /// struct Db(&'static str);
+    ///
+    /// let rocket = rocket::ignite().manage(Db("postgres://production"));
+    /// let client = Client::untracked_with(rocket, |o| o.state(Db("mock")));
+    /// ```
+    pub $($prefix)? fn untracked_with<F>(rocket: Rocket, f: F) -> Result<Self, LaunchError>
+        where F: FnOnce(crate::local::Overrides) -> crate::local::Overrides
+    {
+        let overrides = f(crate::local::Overrides::default());
+        Self::_new(rocket, false, Some(overrides)) $(.$suffix)?
     }
 
     /// Returns a reference to the `Rocket` this client is creating requests
@@ -156,6 +212,34 @@ macro_rules! pub_client_impl {
         crate::http::CookieJar::from(self._cookies().clone(), key)
     }
 
+    /// Returns the session of this client named `name`, creating it if it
+    /// doesn't yet exist.
+    ///
+    /// Each session has its own cookies, separate from those of other
+    /// sessions and from the client's own [`cookies()`](Client::cookies()).
+    /// Requests created through the session carry its cookies and, if the
+    /// client tracks cookies, update only its cookies. Sessions with the same
+    /// name share cookies.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// let alice = client.session("alice");
+    /// let bob = client.session("bob");
+    ///
+    /// let alice_req = alice.get("/inbox");
+    /// let bob_req = bob.get("/inbox");
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn session(&self, name: &str) -> Session<'_> {
+        Session::new(self, name)
+    }
+
     req_method!($import, "GET", get, Method::Get);
     req_method!($import, "PUT", put, Method::Put);
     req_method!($import, "POST", post, Method::Post);
@@ -193,6 +277,59 @@ macro_rules! pub_client_impl {
         self._req(method, uri)
     }
 
+    /// Create a local request replaying `recording`: a request with the
+    /// recorded method, URI, headers, and body.
+    ///
+    /// See [`record`](crate::record) for details on recording requests.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    /// use rocket::record::Recording;
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// let recording = Recording::load("requests/1603000000000-00000000.http")
+    ///     .expect("recording");
+    ///
+    /// let request = client.replay(&recording);
+    /// # });
+    /// ```
+    pub fn replay(&self, recording: &crate::record::Recording) -> LocalRequest<'_> {
+        let mut request = self._req(recording.method(), recording.uri().to_string());
+        for header in recording.headers() {
+            request.add_header(header.clone());
+        }
+
+        request.set_body(recording.body());
+        request
+    }
+
+    /// Writes `bytes` to a new connection to the instance of Rocket within
+    /// `self` and returns the bytes written back until the connection closes.
+    ///
+    /// Unlike requests created with [`req()`](Client::req()), `bytes` pass
+    /// through the same HTTP/1 parser as requests to a launched application,
+    /// so this method can test the handling of malformed requests, unusual
+    /// header encodings, or several pipelined requests. The connection is
+    /// closed for writing once `bytes` are written; every complete request in
+    /// `bytes` is responded to, in order. Responses are returned unparsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// let response = client.dispatch_raw("GET /\r\nHost: rocket.rs\r\n\r\n");
+    /// # });
+    /// ```
+    pub $($prefix)? fn dispatch_raw<B: AsRef<[u8]>>(&self, bytes: B) -> Vec<u8> {
+        self._dispatch_raw(bytes.as_ref()) $(.$suffix)?
+    }
+
     #[cfg(test)]
     #[allow(dead_code)]
     fn _ensure_impls_exist() {
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+/// A server-sent event read from the body of a local response.
+///
+/// Events are returned by `LocalResponse::events()`, both the
+/// [`blocking`](crate::local::blocking::LocalResponse::events()) and
+/// [`async`](crate::local::asynchronous::LocalResponse::events()) variants,
+/// which parse the body as a `text/event-stream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    event: Option<String>,
+    data: String,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// The type of the event, set by its `event` field, if any.
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// The data of the event: the values of its `data` fields, joined by
+    /// newlines.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// The last event ID set by an `id` field of this or a previous event,
+    /// if any.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// The reconnection time set by the event's `retry` field, if any.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+}
+
+/// An incremental parser of a `text/event-stream`.
+#[derive(Debug, Default)]
+pub(crate) struct EventParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl EventParser {
+    /// Buffers `bytes` read from the stream.
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Parses buffered lines until an event is complete, returning it, or
+    /// until no complete lines remain, returning `None`.
+    pub(crate) fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let end = self.buffer.iter().position(|&b| b == b'\n' || b == b'\r')?;
+
+            // A '\r' may be followed by a '\n' that hasn't been read yet.
+            let len = match self.buffer.get(end + 1) {
+                Some(b'\n') if self.buffer[end] == b'\r' => 2,
+                None if self.buffer[end] == b'\r' => return None,
+                _ => 1,
+            };
+
+            let line: Vec<u8> = self.buffer.drain(..end + len).take(end).collect();
+            if let Some(event) = self.line(&String::from_utf8_lossy(&line)) {
+                return Some(event);
+            }
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            let event = self.event.take();
+            return self.data.take().map(|data| Event {
+                event, data, id: self.id.clone(), retry: self.retry.take()
+            });
+        }
+
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.find(':') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match self.data {
+                Some(ref mut data) => { data.push('\n'); data.push_str(value); }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" => if let Ok(ms) = value.parse() {
+                self.retry = Some(Duration::from_millis(ms));
+            },
+            _ => { /* unknown fields are ignored */ }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::EventParser;
+
+    #[test]
+    fn test_event_parser() {
+        let mut parser = EventParser::default();
+        parser.push(b": comment\nevent: greeting\ndata: hello\ndata:world\nid: 7\r");
+        assert!(parser.next_event().is_none());
+
+        parser.push(b"\n\r\nretry: 2500\ndata\n\nevent: unfinished\ndata: ...");
+        let event = parser.next_event().unwrap();
+        assert_eq!(event.event(), Some("greeting"));
+        assert_eq!(event.data(), "hello\nworld");
+        assert_eq!(event.id(), Some("7"));
+        assert_eq!(event.retry(), None);
+
+        let event = parser.next_event().unwrap();
+        assert_eq!(event.event(), None);
+        assert_eq!(event.data(), "");
+        assert_eq!(event.id(), Some("7"));
+        assert_eq!(event.retry(), Some(Duration::from_millis(2500)));
+
+        assert!(parser.next_event().is_none());
+    }
+}
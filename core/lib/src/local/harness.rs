@@ -0,0 +1,136 @@
+use crate::{Data, Request, Response, Rocket};
+use crate::config::Config;
+use crate::fairing::{Fairing, Kind};
+use crate::http::{Method, uri::Origin};
+
+/// A harness to test a single fairing's request and response callbacks.
+///
+/// A `FairingHarness` runs the [`on_request`](Fairing::on_request()) and
+/// [`on_response`](Fairing::on_response()) callbacks of one fairing against
+/// requests and responses built by the test, without mounting routes,
+/// attaching the fairing to an application, or dispatching requests. This
+/// makes it practical to unit test a fairing, such as one implementing
+/// middleware, in isolation from the application that uses it.
+///
+/// As when the fairing is attached, its callbacks are run only if the `Kind`
+/// in its [`Info`](crate::fairing::Info) includes them. Requests are created
+/// via [`request()`](FairingHarness::request()) and see the configuration and
+/// state of the harness.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::{Request, Data, Response};
+/// use rocket::fairing::{Fairing, Info, Kind};
+/// use rocket::http::{Header, Method};
+/// use rocket::local::FairingHarness;
+///
+/// struct Tagger;
+///
+/// #[rocket::async_trait]
+/// impl Fairing for Tagger {
+///     fn info(&self) -> Info {
+///         Info { name: "Tagger", kind: Kind::Request | Kind::Response }
+///     }
+///
+///     async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+///         req.add_header(Header::new("X-Seen", "yes"));
+///     }
+///
+///     async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+///         res.set_raw_header("X-Path", req.uri().path().to_string());
+///     }
+/// }
+///
+/// # rocket::async_test(async {
+/// let harness = FairingHarness::new(Tagger);
+/// let mut request = harness.request(Method::Get, "/hello");
+/// harness.on_request(&mut request, "").await;
+/// assert_eq!(request.headers().get_one("X-Seen"), Some("yes"));
+///
+/// let mut response = Response::new();
+/// harness.on_response(&request, &mut response).await;
+/// assert_eq!(response.headers().get_one("X-Path"), Some("/hello"));
+/// # })
+/// ```
+pub struct FairingHarness<F> {
+    rocket: Rocket,
+    fairing: F,
+}
+
+impl<F: Fairing> FairingHarness<F> {
+    /// Returns a harness for `fairing` with the default development
+    /// configuration.
+    pub fn new(fairing: F) -> FairingHarness<F> {
+        FairingHarness::custom(Config::development(), fairing)
+    }
+
+    /// Returns a harness for `fairing` with the configuration `config`.
+    pub fn custom(config: Config, fairing: F) -> FairingHarness<F> {
+        FairingHarness { rocket: Rocket::custom(config), fairing }
+    }
+
+    /// Manages `state`, making it available to the fairing via the requests of
+    /// this harness, as with [`Rocket::manage()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if state of type `T` is already being managed.
+    pub fn manage<T: Send + Sync + 'static>(self, state: T) -> Self {
+        if !self.rocket.managed_state.set::<T>(state) {
+            panic!("State for type '{}' is already being managed.", std::any::type_name::<T>());
+        }
+
+        self
+    }
+
+    /// Returns the fairing under test.
+    pub fn fairing(&self) -> &F {
+        &self.fairing
+    }
+
+    /// Returns a new request with the method `method` and URI `uri`. Headers,
+    /// cookies, and the remote address can be set on the request before
+    /// running the fairing's callbacks with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `uri` is not a valid origin URI.
+    pub fn request(&self, method: Method, uri: &str) -> Request<'_> {
+        let uri = Origin::parse_owned(uri.to_string()).expect("valid origin URI");
+        Request::new(&self.rocket, method, uri)
+    }
+
+    /// Runs the fairing's `on_request` callback, if it has one, with `req`
+    /// and the request body `body`. The body is fully buffered, as it is for
+    /// requests dispatched by a local [`Client`](crate::local::asynchronous::Client).
+    pub async fn on_request<B: AsRef<[u8]>>(&self, req: &mut Request<'_>, body: B) {
+        if self.fairing.info().kind.is(Kind::Request) {
+            self.fairing.on_request(req, &Data::local(body.as_ref().into())).await;
+        }
+    }
+
+    /// Runs the fairing's `on_response` and then `on_response_body`
+    /// callbacks, those it has, with `req` and `res`.
+    pub async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let kind = self.fairing.info().kind;
+        if kind.is(Kind::Response) {
+            self.fairing.on_response(req, res).await;
+        }
+
+        if kind.is(Kind::ResponseBody) {
+            if let Some(body) = res.take_body() {
+                let body = self.fairing.on_response_body(req, res, body).await;
+                res.set_body(body);
+            }
+        }
+    }
+}
+
+impl<F: Fairing> std::fmt::Debug for FairingHarness<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FairingHarness")
+            .field("fairing", &self.fairing.info().name)
+            .finish()
+    }
+}
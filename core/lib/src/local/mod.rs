@@ -112,6 +112,20 @@
 //! `untracked()` constructor to create a `Client` that _will not_ track
 //! cookies.
 //!
+//! **Overrides**
+//!
+//! A `Client` constructed using `tracked_with()` or `untracked_with()` applies
+//! [`Overrides`] to the `Rocket` instance: managed state, such as a database
+//! pool, can be replaced by a stand-in, and request guards that opt in can be
+//! stubbed, so that handlers can be tested in isolation.
+//!
+//! **Sessions**
+//!
+//! A single `Client` can act as several users at once via [`session()`]
+//! ([`async` `session()`]), which returns a named session with its own cookies.
+//! Requests created through a session carry, and update, only that session's
+//! cookies, so interactions between users can be tested with one client.
+//!
 //! [`Client`]: blocking::Client
 //! [`async` `Client`]: asynchronous::Client
 //! [`LocalRequest`]: blocking::LocalRequest
@@ -121,6 +135,8 @@
 //! [`untracked()`]: blocking::Client::untracked()
 //! [`async` `new()`]: asynchronous::Client::new()
 //! [`async` `untracked()`]: asynchronous::Client::untracked()
+//! [`session()`]: blocking::Client::session()
+//! [`async` `session()`]: asynchronous::Client::session()
 //!
 //! [`get()`]: blocking::Client::get()
 //! [`put()`]: blocking::Client::put()
@@ -129,6 +145,25 @@
 //! [`async` `put()`]: asynchronous::Client::put()
 //! [`async` `post()`]: asynchronous::Client::post()
 //!
+//! **Concurrency**
+//!
+//! The [`async` `Client`] can dispatch several requests at once, as a
+//! [`Batch`](asynchronous::Batch), with control over when each starts and how
+//! long each may take, to test behavior such as rate limiting or concurrency
+//! limits. A single request can be dispatched with a timeout via
+//! [`dispatch_timeout()`](asynchronous::LocalRequest::dispatch_timeout()).
+//!
+//! **Raw Requests**
+//!
+//! Requests can also be written as raw bytes via [`dispatch_raw()`]
+//! ([`async` `dispatch_raw()`]), which passes them through the same HTTP/1
+//! parser as requests to a launched application and returns the raw bytes of
+//! the response. This tests the handling of requests the structured
+//! `LocalRequest` API can't express, such as malformed or pipelined requests.
+//!
+//! [`dispatch_raw()`]: blocking::Client::dispatch_raw()
+//! [`async` `dispatch_raw()`]: asynchronous::Client::dispatch_raw()
+//!
 //! **Example**
 //!
 //! For a usage example, see [`Client`] or [`async` `Client`].
@@ -138,7 +173,8 @@
 //! A [`LocalRequest`] ([`async` `LocalRequest`]) is constructed via a `Client`.
 //! Once obtained, headers, cookies, including private cookies, the remote IP
 //! address, and the request body can all be set via methods on the
-//! `LocalRequest` structure.
+//! `LocalRequest` structure. Private cookies can also be sealed with a known
+//! secret key, independently of any `Client`, via [`PrivateCookies`].
 //!
 //! **Dispatching**
 //!
@@ -146,6 +182,9 @@
 //! `dispatch()`]). The `LocalRequest` is consumed and a [`LocalResponse`]
 //! ([`async` `LocalResponse`]) is returned.
 //!
+//! A `multipart/form-data` body, such as that of a file upload, can be built
+//! with [`Multipart`] and set via `multipart()`.
+//!
 //! Note that `LocalRequest` implements [`Clone`]. As such, if the same request
 //! needs to be dispatched multiple times, the request can first be cloned and
 //! then dispatched: `request.clone().dispatch()`.
@@ -172,16 +211,53 @@
 //! the `blocking` `LocalResponse` implements `Read` while the `async`
 //! `LocalResponse` implements `AsyncRead`.
 //!
+//! The body of a `text/event-stream` response can instead be read as it is
+//! produced, one server-sent [`Event`] at a time, via `events()`. If the
+//! response upgraded the connection, the client's end of the upgraded
+//! connection is returned by `upgraded()`. A request sent with `websocket()`
+//! asks for a WebSocket handshake; once the server accepts it, the response's
+//! `websocket()` returns a client exchanging [`Message`]s with the server.
+//!
+//! Responses can also be checked with `assert_status()`, `assert_header()`,
+//! `assert_body_contains()`, and, with the `json` feature, `assert_json()`,
+//! which panic with a description of the mismatch when a check fails and
+//! otherwise return the response for further checks.
+//!
 //! For a usage example, see [`LocalResponse`] or [`async` `LocalResponse`].
 //!
 //! [`into_string()`]: blocking::LocalResponse::into_string()
 //! [`async` `into_string()`]: asynchronous::LocalResponse::into_string()
 //! [`into_bytes()`]: blocking::LocalResponse::into_bytes()
 //! [`async` `into_bytes()`]: asynchronous::LocalResponse::into_bytes()
+//!
+//! # Fairings
+//!
+//! The request and response callbacks of a single fairing can be tested on
+//! their own, against requests and responses built by the test, with a
+//! [`FairingHarness`]. No application is built and no request is dispatched.
 
 #[macro_use] mod client;
 #[macro_use] mod request;
 #[macro_use] mod response;
+#[macro_use] mod session;
+mod multipart;
+mod event;
+mod assert;
+mod overrides;
+mod harness;
+pub(crate) mod websocket;
+#[cfg(feature = "secrets")] mod private;
 
 pub mod asynchronous;
 pub mod blocking;
+
+pub use self::multipart::Multipart;
+pub use self::event::Event;
+pub use self::websocket::Message;
+pub(crate) use self::event::EventParser;
+pub use self::overrides::Overrides;
+pub use self::harness::FairingHarness;
+
+#[cfg(feature = "secrets")]
+#[cfg_attr(nightly, doc(cfg(feature = "secrets")))]
+pub use self::private::PrivateCookies;
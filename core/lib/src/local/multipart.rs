@@ -0,0 +1,182 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::http::ContentType;
+
+/// A `multipart/form-data` body for a local request.
+///
+/// A `Multipart` is built from text fields and file parts, then set as the
+/// body of a [`LocalRequest`](super::blocking::LocalRequest) via
+/// `multipart()`, which also sets the request's `Content-Type`, including the
+/// boundary. Parts are encoded in the order they're added. The boundary is
+/// derived from the parts so that it never appears in them.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::local::Multipart;
+/// use rocket::local::blocking::Client;
+/// use rocket::http::ContentType;
+///
+/// let client = Client::new(rocket::ignite()).expect("valid rocket");
+/// let form = Multipart::new()
+///     .text("name", "Rocketeer")
+///     .file("avatar", "avatar.png", ContentType::PNG, &[0x89, b'P', b'N', b'G']);
+///
+/// let response = client.post("/profile").multipart(form).dispatch();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Multipart {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Hash)]
+struct Part {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Multipart {
+    /// Returns an empty `multipart/form-data` body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Multipart;
+    ///
+    /// let form = Multipart::new();
+    /// ```
+    pub fn new() -> Multipart {
+        Multipart::default()
+    }
+
+    /// Adds a text field named `name` with the value `value`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Multipart;
+    ///
+    /// let form = Multipart::new()
+    ///     .text("username", "rocket")
+    ///     .text("remember", "on");
+    /// ```
+    pub fn text<N, V>(mut self, name: N, value: V) -> Self
+        where N: Into<String>, V: Into<String>
+    {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            data: value.into().into_bytes(),
+        });
+
+        self
+    }
+
+    /// Adds a file part named `name` with the file name `filename`, the
+    /// content type `content_type`, and the contents `data`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::Multipart;
+    /// use rocket::http::ContentType;
+    ///
+    /// let form = Multipart::new()
+    ///     .file("notes", "notes.txt", ContentType::Plain, "Remember the milk.")
+    ///     .file("data", "data.bin", ContentType::Binary, vec![0, 1, 2, 3]);
+    /// ```
+    pub fn file<N, F, D>(mut self, name: N, filename: F, content_type: ContentType, data: D) -> Self
+        where N: Into<String>, F: Into<String>, D: AsRef<[u8]>
+    {
+        self.parts.push(Part {
+            name: name.into(),
+            filename: Some(filename.into()),
+            content_type: Some(content_type.to_string()),
+            data: data.as_ref().to_vec(),
+        });
+
+        self
+    }
+
+    /// Encodes the parts, returning the `Content-Type` of the body, which
+    /// names the boundary, and the body itself.
+    pub(crate) fn encode(&self) -> (ContentType, Vec<u8>) {
+        let mut hasher = DefaultHasher::new();
+        self.parts.hash(&mut hasher);
+        let mut boundary = format!("rocket-multipart-{:016x}", hasher.finish());
+        while self.parts.iter().any(|part| contains(&part.data, boundary.as_bytes())) {
+            boundary.push('-');
+        }
+
+        let mut body = vec![];
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"",
+                escape(&part.name));
+
+            if let Some(ref filename) = part.filename {
+                disposition.push_str(&format!("; filename=\"{}\"", escape(filename)));
+            }
+
+            body.extend_from_slice(disposition.as_bytes());
+            body.extend_from_slice(b"\r\n");
+            if let Some(ref content_type) = part.content_type {
+                body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+            }
+
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.data);
+            body.extend_from_slice(b"\r\n");
+        }
+
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+        let params = ("boundary", boundary);
+        (ContentType::with_params("multipart", "form-data", params), body)
+    }
+}
+
+/// Escapes a field name or file name for a quoted `Content-Disposition`
+/// parameter as browsers do.
+fn escape(value: &str) -> String {
+    value.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Multipart;
+    use crate::http::ContentType;
+
+    #[test]
+    fn test_encode() {
+        let form = Multipart::new()
+            .text("a \"quoted\" name", "value")
+            .file("file", "a.txt", ContentType::Plain, "contents");
+
+        let (content_type, body) = form.encode();
+        let boundary = content_type.params()
+            .find(|&(key, _)| key == "boundary")
+            .map(|(_, value)| value.to_string())
+            .unwrap();
+
+        let expected = format!("--{b}\r\n\
+            Content-Disposition: form-data; name=\"a %22quoted%22 name\"\r\n\r\n\
+            value\r\n\
+            --{b}\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\r\n\
+            contents\r\n\
+            --{b}--\r\n", b = boundary);
+
+        assert_eq!(content_type.top(), "multipart");
+        assert_eq!(content_type.sub(), "form-data");
+        assert_eq!(String::from_utf8(body).unwrap(), expected);
+    }
+}
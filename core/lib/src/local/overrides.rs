@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use crate::Rocket;
+use crate::request::{GuardStubs, Outcome, Request};
+
+/// Replacements for managed state and request guards of a local `Client`.
+///
+/// `Overrides` are applied by a `Client` constructed with `tracked_with()` or
+/// `untracked_with()`, as on the [`blocking`](super::blocking::Client) or
+/// [`async`](super::asynchronous::Client) `Client`, so that handlers can be
+/// tested against stand-ins, such as a mock database, without changing how
+/// the `Rocket` instance is built.
+///
+/// # Managed State
+///
+/// State registered via [`state()`](Overrides::state()) is managed in place of
+/// any state of the same type managed by the application, which is ignored,
+/// even if it is managed later, as by a fairing.
+///
+/// # Request Guards
+///
+/// A stub registered via [`guard()`](Overrides::guard()) computes the outcome
+/// of a request guard in place of the guard itself. Because a guard's
+/// `FromRequest` implementation can't be replaced from outside, stubs apply
+/// only to guards that opt in by returning the outcome of
+/// [`Request::stubbed_guard()`] when there is one. Stubs are set in the
+/// request-local state of each request the `Client` builds; requests written
+/// via `dispatch_raw()` aren't stubbed. A guard opts in as follows:
+///
+/// ```rust
+/// use rocket::request::{self, Request, FromRequest};
+/// use rocket::outcome::IntoOutcome;
+///
+/// struct User(String);
+///
+/// #[rocket::async_trait]
+/// impl<'a, 'r> FromRequest<'a, 'r> for User {
+///     type Error = ();
+///
+///     async fn from_request(req: &'a Request<'r>) -> request::Outcome<User, ()> {
+///         if let Some(outcome) = req.stubbed_guard::<User, ()>() {
+///             return outcome;
+///         }
+///
+///         req.cookies()
+///             .get_private("user")
+///             .map(|cookie| User(cookie.value().to_string()))
+///             .or_forward(())
+///     }
+/// }
+/// ```
+///
+/// [`Request::stubbed_guard()`]: crate::Request::stubbed_guard()
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// # use rocket::request::{self, Request, FromRequest};
+/// # struct User(String);
+/// # #[rocket::async_trait]
+/// # impl<'a, 'r> FromRequest<'a, 'r> for User {
+/// #     type Error = ();
+/// #     async fn from_request(req: &'a Request<'r>) -> request::Outcome<User, ()> {
+/// #         req.stubbed_guard::<User, ()>().unwrap_or(request::Outcome::Forward(()))
+/// #     }
+/// # }
+/// use rocket::State;
+/// use rocket::request::Outcome;
+/// use rocket::local::blocking::Client;
+///
+/// struct Db(Vec<String>);
+///
+/// #[get("/posts")]
+/// fn posts(user: User, db: State<'_, Db>) -> String {
+///     format!("{}: {}", user.0, db.0.join(", "))
+/// }
+///
+/// let rocket = rocket::ignite()
+///     .mount("/", routes![posts])
+///     .manage(Db(vec![]));
+///
+/// let client = Client::tracked_with(rocket, |overrides| overrides
+///     .state(Db(vec!["first".into(), "second".into()]))
+///     .guard(|_| Outcome::<User, ()>::Success(User("bob".into()))))
+///     .expect("valid rocket");
+///
+/// let response = client.get("/posts").dispatch();
+/// assert_eq!(response.into_string().unwrap(), "bob: first, second");
+/// ```
+#[derive(Default)]
+pub struct Overrides {
+    states: Vec<Box<dyn FnOnce(&mut Rocket) + Send>>,
+    stubs: GuardStubs,
+}
+
+impl Overrides {
+    /// Manages `state` in place of any managed state of type `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if state of type `T` is overridden more than once.
+    pub fn state<T: Send + Sync + 'static>(mut self, state: T) -> Self {
+        self.states.push(Box::new(move |rocket| rocket.override_state(state)));
+        self
+    }
+
+    /// Stubs the request guard of type `T`, with the error type `E`, with
+    /// `stub`, which computes the guard's outcome for a request. Replaces any
+    /// previously registered stub for `T`.
+    ///
+    /// The stub applies only to guards that opt in, as described in the
+    /// [type-level documentation](Overrides#request-guards).
+    pub fn guard<T, E, F>(mut self, stub: F) -> Self
+        where T: 'static, E: 'static,
+              F: Fn(&Request<'_>) -> Outcome<T, E> + Send + Sync + 'static
+    {
+        self.stubs.insert(stub);
+        self
+    }
+
+    /// Overrides the managed state of `rocket` and returns the guard stubs to
+    /// set on each local request, if any.
+    pub(crate) fn apply(self, rocket: &mut Rocket) -> Option<Arc<GuardStubs>> {
+        for set_state in self.states {
+            set_state(rocket);
+        }
+
+        match self.stubs.len() {
+            0 => None,
+            _ => Some(Arc::new(self.stubs))
+        }
+    }
+}
+
+impl std::fmt::Debug for Overrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Overrides")
+            .field("states", &self.states.len())
+            .field("guards", &self.stubs.len())
+            .finish()
+    }
+}
@@ -0,0 +1,106 @@
+use crate::config::{Config, ConfigError};
+use crate::http::{Cookie, CookieJar};
+
+/// Seals and opens private cookies with a known secret key.
+///
+/// A [private cookie] is encrypted and authenticated with the application's
+/// `secret_key`, so a test can't simply set its value. A request created by a
+/// local `Client` can carry a private cookie sealed with the key of the
+/// client's application via `private_cookie()`. `PrivateCookies` instead seals
+/// cookies with a given key, such as the `secret_key` configured for a test or
+/// staging environment, so that tests can simulate a returning client, one
+/// that already holds private cookies such as a session, without scripting a
+/// login flow. The sealed values can be sent by any means: via a local
+/// `Client`, in a [raw request](super::blocking::Client::dispatch_raw()), or
+/// to a remote server. Conversely, private cookies set by a server can be
+/// opened to check their values.
+///
+/// This type is only available when the `secrets` feature is enabled.
+///
+/// [private cookie]: crate::http::CookieJar::add_private()
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::http::Cookie;
+/// use rocket::local::PrivateCookies;
+///
+/// let key = "8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg=";
+/// let cookies = PrivateCookies::new(key).expect("valid secret key");
+///
+/// let sealed = cookies.seal(Cookie::new("user_id", "42"));
+/// assert_eq!(sealed.name(), "user_id");
+/// assert_ne!(sealed.value(), "42");
+///
+/// let opened = cookies.open(&sealed).expect("authentic cookie");
+/// assert_eq!(opened.value(), "42");
+/// ```
+#[derive(Clone)]
+pub struct PrivateCookies {
+    config: Config,
+}
+
+impl PrivateCookies {
+    /// Returns a `PrivateCookies` with the secret key `key`, a 256-bit base64
+    /// or base16 (hex) encoded string, as the `secret_key` configuration
+    /// parameter.
+    ///
+    /// # Errors
+    ///
+    /// If `key` is not a valid 256-bit encoded string, returns a `BadType`
+    /// error.
+    pub fn new(key: &str) -> Result<PrivateCookies, ConfigError> {
+        let mut config = Config::development();
+        config.set_secret_key(key)?;
+        Ok(PrivateCookies { config })
+    }
+
+    /// Returns a `PrivateCookies` with the secret key of `config`, whether it
+    /// was provided or generated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::local::PrivateCookies;
+    ///
+    /// let rocket = rocket::ignite();
+    /// let cookies = PrivateCookies::from_config(rocket.config());
+    /// ```
+    pub fn from_config(config: &Config) -> PrivateCookies {
+        PrivateCookies { config: config.clone() }
+    }
+
+    /// Returns a cookie named as `cookie` whose value is `cookie`'s value
+    /// encrypted and authenticated, along with its name, with the secret key,
+    /// as a server would set it via `add_private()`. The attributes of
+    /// `cookie`, such as its path, are not carried over: only a name and value
+    /// are sent by a client.
+    pub fn seal(&self, cookie: Cookie<'static>) -> Cookie<'static> {
+        let jar = CookieJar::new(self.config.secret_key());
+        let name = cookie.name().to_string();
+        jar.add_original_private(cookie);
+
+        let value = jar.iter()
+            .find(|crumb| crumb.name() == name)
+            .map(|crumb| crumb.value().to_string())
+            .expect("sealed cookie in jar");
+
+        Cookie::new(name, value)
+    }
+
+    /// Returns the cookie sealed in `cookie` if its value was sealed with the
+    /// secret key and hasn't been tampered with, and `None` otherwise.
+    pub fn open(&self, cookie: &Cookie<'_>) -> Option<Cookie<'static>> {
+        let jar = CookieJar::new(self.config.secret_key());
+        let cookie = Cookie::new(cookie.name().to_string(), cookie.value().to_string());
+        let name = cookie.name().to_string();
+        jar.add_original(cookie);
+        jar.get_private(&name)
+    }
+}
+
+impl std::fmt::Debug for PrivateCookies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateCookies").finish()
+    }
+}
@@ -215,6 +215,63 @@ macro_rules! pub_request_impl {
         *self._body_mut() = body.as_ref().into();
     }
 
+    /// Set the body of the request to the `multipart/form-data` body `form`;
+    /// also sets the Content-Type, replacing any previously set.
+    ///
+    /// # Examples
+    ///
+    /// Upload a file along with a text field:
+    ///
+    /// ```rust
+    #[doc = $import]
+    /// use rocket::http::ContentType;
+    /// use rocket::local::Multipart;
+    ///
+    /// # Client::_test(|_, request, _| {
+    /// let request: LocalRequest = request;
+    /// let form = Multipart::new()
+    ///     .text("title", "Quarterly Report")
+    ///     .file("report", "report.csv", ContentType::CSV, "q,revenue\n1,100\n");
+    ///
+    /// let req = request.multipart(form);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn multipart(mut self, form: crate::local::Multipart) -> Self {
+        let (content_type, body) = form.encode();
+        self._request_mut().replace_header(content_type);
+        *self._body_mut() = body;
+        self
+    }
+
+    /// Asks for the connection to be upgraded to a WebSocket, adding the
+    /// headers of a WebSocket handshake, with a new random key, to the
+    /// request. Once the server accepts the handshake, the connection is
+    /// returned by the response's `websocket()` method.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    #[doc = $import]
+    ///
+    /// # Client::_test(|_, request, _| {
+    /// let request: LocalRequest = request;
+    /// let req = request.websocket();
+    /// assert!(req.inner().headers().contains("Sec-WebSocket-Key"));
+    /// # });
+    /// ```
+    pub fn websocket(mut self) -> Self {
+        use crate::http::Header;
+
+        let request = self._request_mut();
+        request.replace_header(Header::new("Connection", "Upgrade"));
+        request.replace_header(Header::new("Upgrade", "websocket"));
+        request.replace_header(Header::new("Sec-WebSocket-Version", "13"));
+        let key = crate::local::websocket::handshake_key();
+        request.replace_header(Header::new("Sec-WebSocket-Key", key));
+        self
+    }
+
     /// Dispatches the request, returning the response.
     ///
     /// This method consumes `self` and is the preferred mechanism for
@@ -94,6 +94,57 @@ macro_rules! pub_response_impl {
         self._into_bytes() $(.$suffix)?
     }
 
+    /// Asserts that the status of the response is `status`, returning `self`
+    /// for further assertions.
+    ///
+    /// # Panics
+    ///
+    /// Panics, naming the expected and actual status, if the status differs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    #[doc = $doc_prelude]
+    /// use rocket::http::Status;
+    ///
+    /// # Client::_test(|_, _, response| {
+    /// let mut response: LocalResponse = response;
+    /// response.assert_status(Status::NotFound);
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn assert_status(&mut self, status: crate::http::Status) -> &mut Self {
+        crate::local::assert::status(self.status(), status);
+        self
+    }
+
+    /// Asserts that the response has a header named `name`, ignoring case,
+    /// with the value `value`, returning `self` for further assertions. If
+    /// the response has several headers named `name`, any of them may match.
+    ///
+    /// # Panics
+    ///
+    /// Panics, listing the headers the response does have, if no header
+    /// matches.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    #[doc = $doc_prelude]
+    /// use rocket::http::Status;
+    ///
+    /// # Client::_test(|_, _, response| {
+    /// let mut response: LocalResponse = response;
+    /// response.assert_status(Status::NotFound)
+    ///     .assert_header("Content-Type", "text/html; charset=utf-8");
+    /// # });
+    /// ```
+    #[track_caller]
+    pub fn assert_header(&mut self, name: &str, value: &str) -> &mut Self {
+        crate::local::assert::header(self.headers(), name, value);
+        self
+    }
+
     #[cfg(test)]
     #[allow(dead_code)]
     fn _ensure_impls_exist() {
@@ -0,0 +1,114 @@
+macro_rules! session_req_method {
+    ($import:literal, $NAME:literal, $f:ident, $method:expr) => (
+        session_req_method!(@
+            $import,
+            $NAME,
+            concat!("let req = alice.", stringify!($f), r#"("/hello");"#),
+            $f,
+            $method
+        );
+    );
+
+    (@$import:literal, $NAME:literal, $use_it:expr, $f:ident, $method:expr) => (
+        /// Create a local `
+        #[doc = $NAME]
+        /// ` request to the URI `uri` in this session.
+        ///
+        /// The request carries, and once dispatched updates, the cookies of
+        /// this session. The request is not dispatched automatically. To
+        /// actually dispatch the request, call [`LocalRequest::dispatch()`] on
+        /// the returned request.
+        ///
+        /// # Example
+        ///
+        /// ```rust,no_run
+        #[doc = $import]
+        ///
+        /// # Client::_test(|client, _, _| {
+        /// let client: &Client = client;
+        /// let alice = client.session("alice");
+        #[doc = $use_it]
+        /// # });
+        /// ```
+        #[inline(always)]
+        pub fn $f<'u: 'c, U>(&self, uri: U) -> LocalRequest<'c>
+            where U: Into<Cow<'u, str>>
+        {
+            self.req($method, uri)
+        }
+    )
+}
+
+macro_rules! pub_session_impl {
+    ($import:literal) =>
+{
+    /// Returns the name of this session.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    #[doc = $import]
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// let alice = client.session("alice");
+    /// assert_eq!(alice.name(), "alice");
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a cookie jar containing all of the cookies this session is
+    /// currently tracking.
+    ///
+    /// If cookie tracking is disabled, the returned jar will always be empty.
+    /// Otherwise, it will contain all of the cookies collected from responses
+    /// to requests dispatched in this session that have not expired.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    #[doc = $import]
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// let cookies = client.session("alice").cookies();
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn cookies(&self) -> crate::http::CookieJar<'c> {
+        let key = self.client.rocket().config.secret_key();
+        crate::http::CookieJar::from((*self.jar).clone(), key)
+    }
+
+    session_req_method!($import, "GET", get, Method::Get);
+    session_req_method!($import, "PUT", put, Method::Put);
+    session_req_method!($import, "POST", post, Method::Post);
+    session_req_method!($import, "DELETE", delete, Method::Delete);
+    session_req_method!($import, "OPTIONS", options, Method::Options);
+    session_req_method!($import, "HEAD", head, Method::Head);
+    session_req_method!($import, "PATCH", patch, Method::Patch);
+
+    /// Create a local request with the method `method` to the URI `uri` in
+    /// this session.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    #[doc = $import]
+    /// use rocket::http::Method;
+    ///
+    /// # Client::_test(|client, _, _| {
+    /// let client: &Client = client;
+    /// client.session("alice").req(Method::Get, "/hello");
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn req<'u: 'c, U>(&self, method: Method, uri: U) -> LocalRequest<'c>
+        where U: Into<Cow<'u, str>>
+    {
+        LocalRequest::new(self.client, self.jar.clone(), method, uri.into())
+    }
+}}
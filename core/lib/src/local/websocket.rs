@@ -0,0 +1,268 @@
+use std::io;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The GUID a server appends to the key of a handshake to accept it.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The largest message a local WebSocket reads before failing.
+const MAX_MESSAGE_SIZE: u64 = 16 << 20;
+
+/// A message sent or received over a local WebSocket connection.
+///
+/// Messages are exchanged via `WebSocket`, both the
+/// [`blocking`](crate::local::blocking::WebSocket) and
+/// [`async`](crate::local::asynchronous::WebSocket) variants, which frame them
+/// as RFC 6455 requires of clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Vec<u8>),
+    /// A ping with its application data.
+    Ping(Vec<u8>),
+    /// A pong with its application data.
+    Pong(Vec<u8>),
+    /// A close frame with its status code, if any, and reason. The reason is
+    /// only sent along with a status code.
+    Close(Option<u16>, String),
+}
+
+/// Returns a random value, different on every call.
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Returns a new, random `Sec-WebSocket-Key` for a handshake.
+pub(crate) fn handshake_key() -> String {
+    let mut nonce = [0; 16];
+    nonce[..8].copy_from_slice(&random().to_be_bytes());
+    nonce[8..].copy_from_slice(&random().to_be_bytes());
+    base64(&nonce)
+}
+
+/// Returns the `Sec-WebSocket-Accept` a server must respond with to accept a
+/// handshake with the key `key`.
+pub(crate) fn accept_key(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+/// Writes `message` to `io` as a single, masked frame.
+pub(crate) async fn write<W>(io: &mut W, message: Message) -> io::Result<()>
+    where W: AsyncWrite + Unpin
+{
+    let (opcode, payload) = match message {
+        Message::Text(text) => (0x1, text.into_bytes()),
+        Message::Binary(data) => (0x2, data),
+        Message::Close(code, reason) => {
+            let mut payload = vec![];
+            if let Some(code) = code {
+                payload.extend_from_slice(&code.to_be_bytes());
+                payload.extend_from_slice(reason.as_bytes());
+            }
+
+            (0x8, payload)
+        }
+        Message::Ping(data) => (0x9, data),
+        Message::Pong(data) => (0xA, data),
+    };
+
+    if opcode >= 0x8 && payload.len() > 125 {
+        let error = "control frame payloads are limited to 125 bytes";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, error));
+    }
+
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= u16::max_value() as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+
+    let mask = (random() as u32).to_be_bytes();
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+    io.write_all(&frame).await?;
+    io.flush().await
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Reads messages from a WebSocket connection, reassembling fragmented ones.
+#[derive(Debug, Default)]
+pub(crate) struct Reader {
+    /// The opcode and data of the fragmented message being read, if any.
+    fragments: Option<(u8, Vec<u8>)>,
+}
+
+impl Reader {
+    /// Reads the next message from `io`, returning `None` if the connection
+    /// was closed in between messages.
+    pub(crate) async fn read<R>(&mut self, io: &mut R) -> io::Result<Option<Message>>
+        where R: AsyncRead + Unpin
+    {
+        loop {
+            let mut head = [0; 2];
+            if io.read(&mut head[..1]).await? == 0 {
+                return match self.fragments {
+                    Some(_) => Err(io::ErrorKind::UnexpectedEof.into()),
+                    None => Ok(None),
+                };
+            }
+
+            io.read_exact(&mut head[1..]).await?;
+            if head[1] & 0x80 != 0 {
+                return Err(invalid("frames sent by a server must not be masked"));
+            }
+
+            let len = match head[1] & 0x7F {
+                126 => io.read_u16().await? as u64,
+                127 => io.read_u64().await?,
+                len => len as u64,
+            };
+
+            let buffered = self.fragments.as_ref().map_or(0, |(_, data)| data.len() as u64);
+            if len.saturating_add(buffered) > MAX_MESSAGE_SIZE {
+                return Err(invalid("message is too large"));
+            }
+
+            let mut payload = vec![0; len as usize];
+            io.read_exact(&mut payload).await?;
+
+            let (fin, opcode) = (head[0] & 0x80 != 0, head[0] & 0x0F);
+            match (opcode, self.fragments.as_mut()) {
+                (0x0, Some((_, data))) => data.extend_from_slice(&payload),
+                (0x1, None) | (0x2, None) => self.fragments = Some((opcode, payload)),
+                (0x8, _) | (0x9, _) | (0xA, _) if fin => return control(opcode, payload),
+                _ => return Err(invalid("unexpected frame")),
+            }
+
+            if fin {
+                let (opcode, data) = self.fragments.take().expect("fragments");
+                return match opcode {
+                    0x1 => String::from_utf8(data)
+                        .map(|text| Some(Message::Text(text)))
+                        .map_err(|_| invalid("text message is not UTF-8")),
+                    _ => Ok(Some(Message::Binary(data))),
+                };
+            }
+        }
+    }
+}
+
+/// Returns the control message with opcode `opcode` and payload `payload`.
+fn control(opcode: u8, payload: Vec<u8>) -> io::Result<Option<Message>> {
+    let message = match opcode {
+        0x9 => Message::Ping(payload),
+        0xA => Message::Pong(payload),
+        _ if payload.is_empty() => Message::Close(None, String::new()),
+        _ if payload.len() == 1 => return Err(invalid("truncated close frame")),
+        _ => {
+            let code = u16::from_be_bytes([payload[0], payload[1]]);
+            let reason = String::from_utf8(payload[2..].to_vec())
+                .map_err(|_| invalid("close reason is not UTF-8"))?;
+
+            Message::Close(Some(code), reason)
+        }
+    };
+
+    Ok(Some(message))
+}
+
+/// Encodes `bytes` as padded, standard base64.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let byte = |i: usize| chunk.get(i).map_or(0, |&b| b as u32);
+        let bits = (byte(0) << 16) | (byte(1) << 8) | byte(2);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((bits >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Returns the SHA-1 digest of `data`, as the handshake's accept key requires.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+
+            let t = a.rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+
+        for (s, v) in state.iter_mut().zip(&[a, b, c, d, e]) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(&state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn accept_key_matches_rfc_6455() {
+        // The example handshake of RFC 6455, section 1.3.
+        let accept = super::accept_key("dGhlIHNhbXBsZSBub25jZQ==");
+        assert_eq!(accept, "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
@@ -6,9 +6,15 @@ use std::str::FromStr;
 use log;
 use yansi::Paint;
 
+use crate::{Request, Data, Route};
+use crate::data::ToByteUnit;
+use crate::handler::{self, HandlerFuture};
+use crate::http::{Method, Status};
+use crate::request::{self, FromRequest};
+
 pub(crate) const COLORS_ENV: &str = "ROCKET_CLI_COLORS";
 
-struct RocketLogger(LoggingLevel);
+struct RocketLogger;
 
 /// Defines the different levels for log messages.
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -33,6 +39,27 @@ impl LoggingLevel {
             LoggingLevel::Off => log::LevelFilter::Off
         }
     }
+
+    #[inline(always)]
+    fn from_usize(num: usize) -> LoggingLevel {
+        match num {
+            0 => LoggingLevel::Off,
+            1 => LoggingLevel::Critical,
+            2 => LoggingLevel::Normal,
+            3 => LoggingLevel::Debug,
+            _ => unreachable!("max num is 3 in LoggingLevel::from_usize")
+        }
+    }
+
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        match self {
+            LoggingLevel::Off => 0,
+            LoggingLevel::Critical => 1,
+            LoggingLevel::Normal => 2,
+            LoggingLevel::Debug => 3,
+        }
+    }
 }
 
 impl FromStr for LoggingLevel {
@@ -84,7 +111,7 @@ macro_rules! warn_ { ($($args:expr),+) => { log_!(warn: $($args),+); }; }
 impl log::Log for RocketLogger {
     #[inline(always)]
     fn enabled(&self, record: &log::Metadata<'_>) -> bool {
-        match self.0.to_level_filter().to_level() {
+        match level().to_level_filter().to_level() {
             Some(max) => record.level() <= max || record.target().starts_with("launch"),
             None => false
         }
@@ -97,7 +124,7 @@ impl log::Log for RocketLogger {
         }
 
         // Don't print Hyper or Rustls messages unless debug is enabled.
-        let configged_level = self.0;
+        let configged_level = level();
         let from_hyper = record.module_path().map_or(false, |m| m.starts_with("hyper::"));
         let from_rustls = record.module_path().map_or(false, |m| m.starts_with("rustls::"));
         if configged_level != LoggingLevel::Debug && (from_hyper || from_rustls) {
@@ -158,7 +185,7 @@ pub(crate) fn try_init(level: LoggingLevel, verbose: bool) -> bool {
     }
 
     push_max_level(level);
-    if let Err(e) = log::set_boxed_logger(Box::new(RocketLogger(level))) {
+    if let Err(e) = log::set_boxed_logger(Box::new(RocketLogger)) {
         if verbose {
             eprintln!("Logger failed to initialize: {}", e);
         }
@@ -167,11 +194,33 @@ pub(crate) fn try_init(level: LoggingLevel, verbose: bool) -> bool {
         return false;
     }
 
+    LEVEL.store(level.to_usize(), Ordering::Release);
+    INSTALLED.store(true, Ordering::Release);
     true
 }
 
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering};
 
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+/// The level at which Rocket's logger logs.
+fn level() -> LoggingLevel {
+    LoggingLevel::from_usize(LEVEL.load(Ordering::Acquire))
+}
+
+/// Changes the level at which Rocket's logger logs, if it is installed, to
+/// `level`. Returns `false` if Rocket's logger isn't installed.
+pub(crate) fn set_level(level: LoggingLevel) -> bool {
+    if !INSTALLED.load(Ordering::Acquire) {
+        return false;
+    }
+
+    LEVEL.store(level.to_usize(), Ordering::Release);
+    log::set_max_level(level.to_level_filter());
+    true
+}
+
 static PUSHED: AtomicBool = AtomicBool::new(false);
 static LAST_LOG_FILTER: AtomicUsize = AtomicUsize::new(0);
 
@@ -242,3 +291,196 @@ external_log_function!(error: error);
 external_log_function!(error_: error_);
 external_log_function!(warn: warn);
 external_log_function!(warn_: warn_);
+
+/// A handle to change Rocket's log level while an application is running.
+///
+/// Rocket logs at the level set by the `log` configuration parameter. A
+/// `Logger` changes that level on the fly, for instance to temporarily collect
+/// debug logs from a production server without restarting it. A `Logger` is
+/// available as a request guard and via [`Cargo::logger()`]. A change lasts
+/// until the level is changed again, including by a configuration reload that
+/// changes `log`.
+///
+/// [`Cargo::logger()`]: crate::Cargo::logger()
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Logger;
+/// use rocket::config::LoggingLevel;
+///
+/// #[post("/debug")]
+/// fn debug(logger: Logger) -> &'static str {
+///     logger.set_level(LoggingLevel::Debug);
+///     "Debug logging enabled."
+/// }
+/// ```
+///
+/// # Admin Endpoint
+///
+/// [`Logger::routes()`] returns routes that read (`GET`) and change (`PUT`)
+/// the log level over HTTP. They aren't authenticated, so the level can only
+/// be changed via `PUT` in the `development` environment; elsewhere, `PUT`
+/// fails with `403 Forbidden`:
+///
+/// ```rust
+/// let rocket = rocket::ignite().mount("/admin/log", rocket::Logger::routes());
+/// ```
+///
+/// ```sh
+/// curl -X PUT -d debug http://localhost:8000/admin/log
+/// ```
+///
+/// To change the level in any environment, use [`Logger::guarded_routes()`]
+/// with a request guard that authorizes the request:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::request::{self, Request, FromRequest};
+///
+/// struct Admin;
+///
+/// #[rocket::async_trait]
+/// impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+///     type Error = ();
+///
+///     async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+///         /* authenticate the administrator */
+///         # request::Outcome::Forward(())
+///     }
+/// }
+///
+/// let routes = rocket::Logger::guarded_routes::<Admin>();
+/// let rocket = rocket::ignite().mount("/admin/log", routes);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Logger {
+    _private: (),
+}
+
+impl Logger {
+    pub(crate) const fn new() -> Logger {
+        Logger { _private: () }
+    }
+
+    /// Returns the level at which Rocket currently logs.
+    pub fn level(&self) -> LoggingLevel {
+        level()
+    }
+
+    /// Changes the level at which Rocket logs to `level`. Returns `false`
+    /// if Rocket's logger is not installed, for instance because another
+    /// logger was installed first, in which case nothing is changed.
+    pub fn set_level(&self, level: LoggingLevel) -> bool {
+        let changed = set_level(level);
+        if changed {
+            warn!("Log level changed to {}.", Paint::default(level).bold());
+        }
+
+        changed
+    }
+
+    /// Returns routes that report the current log level on `GET /` and change
+    /// it to the level in the request body, such as `debug`, on `PUT /`. The
+    /// level is only changed in the `development` environment: elsewhere,
+    /// `PUT` fails with `403 Forbidden`. See [`Logger::guarded_routes()`] to
+    /// change the level in any environment.
+    pub fn routes() -> Vec<Route> {
+        vec![
+            Route::new(Method::Get, "/", get_level),
+            Route::new(Method::Put, "/", put_level_in_dev),
+        ]
+    }
+
+    /// Like [`Logger::routes()`], but the routes run, in any environment, only
+    /// if the request guard `G` succeeds. If `G` fails or forwards, so do the
+    /// routes.
+    pub fn guarded_routes<G>() -> Vec<Route>
+        where G: for<'a, 'r> FromRequest<'a, 'r> + 'static
+    {
+        vec![
+            Route::new(Method::Get, "/", guarded_get_level::<G>),
+            Route::new(Method::Put, "/", guarded_put_level::<G>),
+        ]
+    }
+}
+
+fn guarded_get_level<'r, G>(req: &'r Request<'_>, data: Data) -> HandlerFuture<'r>
+    where G: for<'a, 'x> FromRequest<'a, 'x> + 'static
+{
+    guarded::<G>(req, data, get_level)
+}
+
+fn guarded_put_level<'r, G>(req: &'r Request<'_>, data: Data) -> HandlerFuture<'r>
+    where G: for<'a, 'x> FromRequest<'a, 'x> + 'static
+{
+    guarded::<G>(req, data, put_level)
+}
+
+/// Runs `handler` if the request guard `G` succeeds, and otherwise fails or
+/// forwards as `G` does.
+fn guarded<'r, G>(
+    req: &'r Request<'_>,
+    data: Data,
+    handler: fn(&'r Request<'_>, Data) -> HandlerFuture<'r>
+) -> HandlerFuture<'r>
+    where G: for<'a, 'x> FromRequest<'a, 'x> + 'static
+{
+    Box::pin(async move {
+        let failed = match req.guard::<G>().await {
+            request::Outcome::Success(_) => None,
+            request::Outcome::Failure((status, _)) => Some(Some(status)),
+            request::Outcome::Forward(_) => Some(None),
+        };
+
+        match failed {
+            None => handler(req, data).await,
+            Some(Some(status)) => handler::Outcome::failure(status),
+            Some(None) => handler::Outcome::forward(data),
+        }
+    })
+}
+
+fn get_level<'r>(req: &'r Request<'_>, _: Data) -> HandlerFuture<'r> {
+    handler::Outcome::from(req, level().to_string()).pin()
+}
+
+fn put_level_in_dev<'r>(req: &'r Request<'_>, data: Data) -> HandlerFuture<'r> {
+    if !req.state.config.environment.is_dev() {
+        warn_!("The log level can only be changed via HTTP in development.");
+        return Box::pin(async { handler::Outcome::failure(Status::Forbidden) });
+    }
+
+    put_level(req, data)
+}
+
+fn put_level<'r>(req: &'r Request<'_>, data: Data) -> HandlerFuture<'r> {
+    Box::pin(async move {
+        let level = match data.open(32.bytes()).stream_to_string().await {
+            Ok(body) => body.trim().parse::<LoggingLevel>(),
+            Err(_) => return handler::Outcome::failure(Status::BadRequest),
+        };
+
+        let level = match level {
+            Ok(level) => level,
+            Err(_) => return handler::Outcome::failure(Status::BadRequest),
+        };
+
+        if !Logger::new().set_level(level) {
+            return handler::Outcome::failure(Status::ServiceUnavailable);
+        }
+
+        handler::Outcome::from(req, level.to_string())
+    })
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Logger {
+    type Error = std::convert::Infallible;
+
+    #[inline]
+    async fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(Logger::new())
+    }
+}
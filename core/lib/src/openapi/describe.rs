@@ -0,0 +1,408 @@
+use std::marker::PhantomData;
+
+use crate::data::Data;
+use crate::http::{MediaType, Status};
+use crate::request::{Form, LenientForm};
+use crate::response::{content, status, Flash, Redirect};
+
+use super::{Operation, Parameter, Location, RequestBody, Response};
+use super::schema::{Schema, JsonSchema, SchemaGenerator};
+
+/// Trait implemented by request guards that add to the description of the
+/// routes they guard.
+///
+/// # Example
+///
+/// A guard reading an API key from the `X-Api-Key` header declares it:
+///
+/// ```rust
+/// use rocket::openapi::{OpenApiFromRequest, Operation, Parameter, Location};
+/// use rocket::openapi::{Schema, SchemaGenerator};
+///
+/// struct ApiKey(String);
+///
+/// impl OpenApiFromRequest for ApiKey {
+///     fn describe(_: &mut SchemaGenerator, operation: &mut Operation) {
+///         let header = Parameter::new("X-Api-Key", Location::Header, Schema::string());
+///         operation.parameters.push(header.required(true));
+///     }
+/// }
+/// ```
+///
+/// The parameters added by a guard of type `Option<T>` or `Result<T, E>` are
+/// not required.
+pub trait OpenApiFromRequest {
+    /// Adds what the guard reads from a request to `operation`.
+    fn describe(gen: &mut SchemaGenerator, operation: &mut Operation);
+}
+
+/// Trait implemented by data guards that describe the request body they
+/// accept.
+pub trait OpenApiFromData {
+    /// Returns the description of the request body, or `None` if no body is
+    /// read.
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody>;
+}
+
+/// Trait implemented by responders that describe the responses they produce.
+pub trait OpenApiResponder {
+    /// Returns the descriptions of the possible responses.
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response>;
+}
+
+/// Marks the parameters added from `start` on as not required.
+fn optional_from(operation: &mut Operation, start: usize) {
+    for parameter in &mut operation.parameters[start..] {
+        parameter.required = false;
+    }
+}
+
+impl<T: OpenApiFromRequest> OpenApiFromRequest for Option<T> {
+    fn describe(gen: &mut SchemaGenerator, operation: &mut Operation) {
+        let start = operation.parameters.len();
+        T::describe(gen, operation);
+        optional_from(operation, start);
+    }
+}
+
+impl<T: OpenApiFromRequest, E> OpenApiFromRequest for Result<T, E> {
+    fn describe(gen: &mut SchemaGenerator, operation: &mut Operation) {
+        let start = operation.parameters.len();
+        T::describe(gen, operation);
+        optional_from(operation, start);
+    }
+}
+
+fn body(content_type: Option<MediaType>, schema: Schema) -> Option<RequestBody> {
+    Some(RequestBody { content_type, schema, required: true })
+}
+
+impl OpenApiFromData for Data {
+    fn request_body(_: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(None, Schema::binary())
+    }
+}
+
+impl OpenApiFromData for String {
+    fn request_body(_: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(Some(MediaType::Plain), Schema::string())
+    }
+}
+
+impl OpenApiFromData for Vec<u8> {
+    fn request_body(_: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(Some(MediaType::Binary), Schema::binary())
+    }
+}
+
+impl<T: JsonSchema> OpenApiFromData for Form<T> {
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(Some(MediaType::Form), gen.subschema_for::<T>())
+    }
+}
+
+impl<T: JsonSchema> OpenApiFromData for LenientForm<T> {
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(Some(MediaType::Form), gen.subschema_for::<T>())
+    }
+}
+
+impl<T: OpenApiFromData> OpenApiFromData for Option<T> {
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        T::request_body(gen).map(|body| RequestBody { required: false, ..body })
+    }
+}
+
+impl<T: OpenApiFromData, E> OpenApiFromData for Result<T, E> {
+    fn request_body(gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        T::request_body(gen)
+    }
+}
+
+/// A query form's parameters are the fields of the form.
+impl<T: JsonSchema> JsonSchema for Form<T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+}
+
+/// A query form's parameters are the fields of the form.
+impl<T: JsonSchema> JsonSchema for LenientForm<T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+}
+
+macro_rules! impl_body_responder {
+    ($($T:ty => $content_type:expr, $schema:expr;)*) => ($(
+        impl OpenApiResponder for $T {
+            fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+                vec![Response::new(Status::Ok).body($content_type, $schema)]
+            }
+        }
+    )*)
+}
+
+impl_body_responder! {
+    String => MediaType::Plain, Schema::string();
+    &str => MediaType::Plain, Schema::string();
+    Vec<u8> => MediaType::Binary, Schema::binary();
+    &[u8] => MediaType::Binary, Schema::binary();
+}
+
+impl OpenApiResponder for () {
+    fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::new(Status::Ok)]
+    }
+}
+
+impl OpenApiResponder for Status {
+    fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::default_response()]
+    }
+}
+
+impl OpenApiResponder for Redirect {
+    fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+        let redirect = Response::default_response();
+        vec![Response { description: "Redirection".to_string(), ..redirect }]
+    }
+}
+
+impl<R: OpenApiResponder> OpenApiResponder for Option<R> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        let mut responses = R::responses(gen);
+        responses.push(Response::new(Status::NotFound));
+        responses
+    }
+}
+
+impl<R: OpenApiResponder, E: OpenApiResponder> OpenApiResponder for Result<R, E> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        let mut responses = R::responses(gen);
+        responses.extend(E::responses(gen));
+        responses
+    }
+}
+
+impl<R: OpenApiResponder> OpenApiResponder for Flash<R> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        R::responses(gen)
+    }
+}
+
+impl<R: OpenApiResponder> OpenApiResponder for content::Content<R> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        R::responses(gen)
+    }
+}
+
+/// Sets the media type of the responses with a body to `content_type`. When
+/// `opaque`, the schema of the bodies is replaced with an unconstrained one
+/// since it describes the encoded, not the decoded, body.
+fn with_content_type(
+    responses: Vec<Response>,
+    content_type: MediaType,
+    opaque: bool
+) -> Vec<Response> {
+    responses.into_iter()
+        .map(|mut response| match response.schema.take() {
+            Some(schema) => {
+                let schema = if opaque { Schema::Any } else { schema };
+                response.body(content_type.clone(), schema)
+            }
+            None => response,
+        })
+        .collect()
+}
+
+macro_rules! impl_content_responder {
+    ($($name:ident: $ct:ident, $opaque:expr),*) => ($(
+        impl<R: OpenApiResponder> OpenApiResponder for content::$name<R> {
+            fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+                with_content_type(R::responses(gen), MediaType::$ct, $opaque)
+            }
+        }
+    )*)
+}
+
+impl_content_responder! {
+    Json: JSON, true,
+    Xml: XML, true,
+    MsgPack: MsgPack, true,
+    Html: HTML, false,
+    Plain: Plain, false,
+    Css: CSS, false,
+    JavaScript: JavaScript, false
+}
+
+/// Gives the successful responses of `responses` the status `status`, or the
+/// default status when `status` is `None`.
+fn with_status(responses: Vec<Response>, status: Option<Status>) -> Vec<Response> {
+    responses.into_iter()
+        .map(|response| match response.status {
+            Some(s) if s.code >= 300 => response,
+            _ => Response {
+                status,
+                description: status.map_or("Response", |s| s.reason).to_string(),
+                ..response
+            }
+        })
+        .collect()
+}
+
+macro_rules! impl_status_responder {
+    ($($name:ident: $status:ident),*) => ($(
+        impl<R: OpenApiResponder> OpenApiResponder for status::$name<R> {
+            fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+                with_status(R::responses(gen), Some(Status::$status))
+            }
+        }
+    )*)
+}
+
+impl_status_responder! {
+    Created: Created,
+    Accepted: Accepted,
+    BadRequest: BadRequest,
+    Unauthorized: Unauthorized,
+    Forbidden: Forbidden,
+    NotFound: NotFound,
+    Conflict: Conflict
+}
+
+impl OpenApiResponder for status::NoContent {
+    fn responses(_: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::new(Status::NoContent)]
+    }
+}
+
+impl<R: OpenApiResponder> OpenApiResponder for status::Custom<R> {
+    fn responses(gen: &mut SchemaGenerator) -> Vec<Response> {
+        with_status(R::responses(gen), None)
+    }
+}
+
+/// Returns the parameters of a query parameter named `name` collecting the
+/// remaining query items, described by `schema`: the properties of `schema`,
+/// if it's an object, or a single parameter otherwise.
+pub fn query_parameters(gen: &SchemaGenerator, name: &str, schema: Schema) -> Vec<Parameter> {
+    match gen.resolve(&schema) {
+        Schema::Object(properties) => properties.iter()
+            .map(|property| Parameter {
+                name: property.name.clone(),
+                location: Location::Query,
+                required: property.required,
+                description: property.description.clone(),
+                schema: property.schema.clone(),
+            })
+            .collect(),
+        _ => vec![Parameter::new(name, Location::Query, schema)],
+    }
+}
+
+/// A probe for the role traits a type implements, used by the code generated
+/// for described routes. Each trait pair below has a method implemented for
+/// `&Describe<T>` when `T` implements the role trait and for `Describe<T>`
+/// otherwise, so that calling the method on `&&Describe<T>` prefers the
+/// former.
+pub struct Describe<T>(PhantomData<fn() -> T>);
+
+impl<T> Describe<T> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Describe(PhantomData)
+    }
+}
+
+impl<T> Default for Describe<T> {
+    fn default() -> Self {
+        Describe::new()
+    }
+}
+
+pub trait DescribeSchema {
+    fn schema(&self, gen: &mut SchemaGenerator) -> (Schema, bool);
+}
+
+impl<T: JsonSchema> DescribeSchema for &Describe<T> {
+    fn schema(&self, gen: &mut SchemaGenerator) -> (Schema, bool) {
+        (gen.subschema_for::<T>(), T::is_required())
+    }
+}
+
+pub trait DescribeAnySchema {
+    fn schema(&self, gen: &mut SchemaGenerator) -> (Schema, bool);
+}
+
+impl<T> DescribeAnySchema for Describe<T> {
+    fn schema(&self, _: &mut SchemaGenerator) -> (Schema, bool) {
+        (Schema::Any, true)
+    }
+}
+
+pub trait DescribeGuard {
+    fn guard(&self, gen: &mut SchemaGenerator, operation: &mut Operation);
+}
+
+impl<T: OpenApiFromRequest> DescribeGuard for &Describe<T> {
+    fn guard(&self, gen: &mut SchemaGenerator, operation: &mut Operation) {
+        T::describe(gen, operation)
+    }
+}
+
+pub trait DescribeNoGuard {
+    fn guard(&self, gen: &mut SchemaGenerator, operation: &mut Operation);
+}
+
+impl<T> DescribeNoGuard for Describe<T> {
+    fn guard(&self, _: &mut SchemaGenerator, _: &mut Operation) { }
+}
+
+pub trait DescribeData {
+    fn request_body(&self, gen: &mut SchemaGenerator) -> Option<RequestBody>;
+}
+
+impl<T: OpenApiFromData> DescribeData for &Describe<T> {
+    fn request_body(&self, gen: &mut SchemaGenerator) -> Option<RequestBody> {
+        T::request_body(gen)
+    }
+}
+
+pub trait DescribeNoData {
+    fn request_body(&self, gen: &mut SchemaGenerator) -> Option<RequestBody>;
+}
+
+impl<T> DescribeNoData for Describe<T> {
+    fn request_body(&self, _: &mut SchemaGenerator) -> Option<RequestBody> {
+        body(None, Schema::Any)
+    }
+}
+
+pub trait DescribeResponder {
+    fn responses(&self, gen: &mut SchemaGenerator) -> Vec<Response>;
+}
+
+impl<T: OpenApiResponder> DescribeResponder for &Describe<T> {
+    fn responses(&self, gen: &mut SchemaGenerator) -> Vec<Response> {
+        T::responses(gen)
+    }
+}
+
+pub trait DescribeNoResponder {
+    fn responses(&self, gen: &mut SchemaGenerator) -> Vec<Response>;
+}
+
+impl<T> DescribeNoResponder for Describe<T> {
+    fn responses(&self, _: &mut SchemaGenerator) -> Vec<Response> {
+        vec![Response::default_response()]
+    }
+}
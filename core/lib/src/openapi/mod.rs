@@ -0,0 +1,229 @@
+//! Descriptions of routes for OpenAPI documents.
+//!
+//! Routes opt in to being described with the [`openapi`](macro@crate::openapi)
+//! attribute, applied below the route attribute, or with
+//! [`Route::openapi()`]. The route attributes then record an [`Operation`]
+//! describing the route: its summary and description, taken from the
+//! function's doc comments, its path and query parameters, the parameters
+//! added by its request guards, its request body, and its responses.
+//!
+//! ```rust
+//! # #[macro_use] extern crate rocket;
+//! use rocket::openapi::JsonSchema;
+//!
+//! #[derive(JsonSchema)]
+//! struct Item {
+//!     /// The item's name.
+//!     name: String,
+//!     quantity: Option<u32>,
+//! }
+//!
+//! /// Looks up an item.
+//! ///
+//! /// Items that don't exist are answered with `404 Not Found`.
+//! #[get("/items/<id>?<verbose>")]
+//! #[openapi(tags = "items")]
+//! fn item(id: usize, verbose: Option<bool>) -> Option<String> {
+//!     /* .. */
+//!     # None
+//! }
+//! ```
+//!
+//! The `rocket_contrib::openapi` fairing gathers the operations of all
+//! mounted routes into an OpenAPI 3 document.
+//!
+//! # Types
+//!
+//! Each argument and the return type of a described route contributes to its
+//! operation according to its role:
+//!
+//!   * The type of a path or query parameter is described by its
+//!     [`JsonSchema`] implementation.
+//!   * A request guard adds to the operation via its [`OpenApiFromRequest`]
+//!     implementation, for instance to declare the header it reads.
+//!   * The data guard describes the request body via its [`OpenApiFromData`]
+//!     implementation.
+//!   * The responder describes the possible responses via its
+//!     [`OpenApiResponder`] implementation.
+//!
+//! A type that doesn't implement the trait for its role is still accepted: a
+//! parameter or body is then described with an unconstrained schema, a guard
+//! adds nothing, and the responses are described by a single default
+//! response. [`JsonSchema`] can be derived for structs and unit-only enums.
+//!
+//! [`Route::openapi()`]: crate::Route::openapi()
+
+mod schema;
+mod describe;
+
+pub use self::schema::{Schema, Property, JsonSchema, SchemaGenerator};
+pub use self::describe::{OpenApiFromRequest, OpenApiFromData, OpenApiResponder};
+
+#[doc(hidden)]
+pub use self::describe::{Describe, DescribeSchema, DescribeAnySchema};
+#[doc(hidden)]
+pub use self::describe::{DescribeGuard, DescribeNoGuard, DescribeData, DescribeNoData};
+#[doc(hidden)]
+pub use self::describe::{DescribeResponder, DescribeNoResponder, query_parameters};
+
+use crate::http::{MediaType, Status};
+
+/// The description of a route.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Operation {
+    /// The operation's unique identifier, by default the name of the route.
+    pub operation_id: Option<String>,
+    /// A short summary of what the operation does.
+    pub summary: Option<String>,
+    /// A longer description of the operation, in CommonMark.
+    pub description: Option<String>,
+    /// The tags the operation is grouped by.
+    pub tags: Vec<String>,
+    /// Whether the operation is deprecated.
+    pub deprecated: bool,
+    /// The operation's parameters.
+    pub parameters: Vec<Parameter>,
+    /// The operation's request body, if it accepts one.
+    pub request_body: Option<RequestBody>,
+    /// The operation's possible responses.
+    pub responses: Vec<Response>,
+}
+
+/// Where a [`Parameter`] is found in a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// A dynamic segment of the path.
+    Path,
+    /// A query string parameter.
+    Query,
+    /// A request header.
+    Header,
+    /// A cookie.
+    Cookie,
+}
+
+impl Location {
+    /// Returns the name of the location in an OpenAPI document.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Location::Path => "path",
+            Location::Query => "query",
+            Location::Header => "header",
+            Location::Cookie => "cookie",
+        }
+    }
+}
+
+/// A parameter of an [`Operation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    /// The name of the parameter.
+    pub name: String,
+    /// Where the parameter is found.
+    pub location: Location,
+    /// Whether the parameter must be present.
+    pub required: bool,
+    /// A description of the parameter, if any.
+    pub description: Option<String>,
+    /// The schema of the parameter's value.
+    pub schema: Schema,
+}
+
+impl Parameter {
+    /// Returns a parameter named `name` found at `location` whose value is
+    /// described by `schema`. Path parameters are required, while others are
+    /// not until set with [`Parameter::required()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::openapi::{Parameter, Location, Schema};
+    ///
+    /// let api_key = Parameter::new("X-Api-Key", Location::Header, Schema::string())
+    ///     .required(true)
+    ///     .description("The caller's API key.");
+    /// ```
+    pub fn new<N: Into<String>>(name: N, location: Location, schema: Schema) -> Self {
+        let required = location == Location::Path;
+        Parameter { name: name.into(), location, required, description: None, schema }
+    }
+
+    /// Sets whether the parameter must be present.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the description of the parameter.
+    pub fn description<D: Into<String>>(mut self, description: D) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// The request body of an [`Operation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestBody {
+    /// The media type of the body. When `None`, the format of the route, if
+    /// any, applies.
+    pub content_type: Option<MediaType>,
+    /// The schema of the body.
+    pub schema: Schema,
+    /// Whether the body must be present.
+    pub required: bool,
+}
+
+/// A possible response of an [`Operation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// The status of the response, or `None` for the default response, which
+    /// describes responses with any other status.
+    pub status: Option<Status>,
+    /// A description of the response.
+    pub description: String,
+    /// The media type of the response's body, if it has one.
+    pub content_type: Option<MediaType>,
+    /// The schema of the response's body, if it has one.
+    pub schema: Option<Schema>,
+}
+
+impl Response {
+    /// Returns a response with status `status` and no body, described by the
+    /// status' reason phrase.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::{MediaType, Status};
+    /// use rocket::openapi::{Response, Schema};
+    ///
+    /// let ok = Response::new(Status::Ok).body(MediaType::Plain, Schema::string());
+    /// assert_eq!(ok.description, "OK");
+    /// ```
+    pub fn new(status: Status) -> Self {
+        Response {
+            status: Some(status),
+            description: status.reason.to_string(),
+            content_type: None,
+            schema: None,
+        }
+    }
+
+    /// Returns the default response, which describes responses with any
+    /// status not otherwise described.
+    pub fn default_response() -> Self {
+        Response {
+            status: None,
+            description: "Response".to_string(),
+            content_type: None,
+            schema: None,
+        }
+    }
+
+    /// Sets the media type and schema of the response's body.
+    pub fn body(mut self, content_type: MediaType, schema: Schema) -> Self {
+        self.content_type = Some(content_type);
+        self.schema = Some(schema);
+        self
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::http::RawStr;
+
+/// A JSON schema, as used by OpenAPI documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Any value.
+    Any,
+    /// A boolean.
+    Boolean,
+    /// An integer, with an optional format such as `"int32"`.
+    Integer { format: Option<&'static str> },
+    /// A number, with an optional format such as `"double"`.
+    Number { format: Option<&'static str> },
+    /// A string, with an optional format such as `"binary"`.
+    String { format: Option<&'static str> },
+    /// One of the given strings.
+    Enum(Vec<String>),
+    /// An array whose items are described by the schema.
+    Array(Box<Schema>),
+    /// An object with the given properties.
+    Object(Vec<Property>),
+    /// An object with arbitrary keys whose values are described by the schema.
+    Map(Box<Schema>),
+    /// The schema, or `null`.
+    Nullable(Box<Schema>),
+    /// A reference to the named schema in the document's components.
+    Reference(String),
+}
+
+impl Schema {
+    /// Returns the schema of any string.
+    pub fn string() -> Schema {
+        Schema::String { format: None }
+    }
+
+    /// Returns the schema of binary data.
+    pub fn binary() -> Schema {
+        Schema::String { format: Some("binary") }
+    }
+}
+
+/// A property of a [`Schema::Object`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// The name of the property.
+    pub name: String,
+    /// The schema of the property's value.
+    pub schema: Schema,
+    /// Whether the property must be present.
+    pub required: bool,
+    /// A description of the property, if any.
+    pub description: Option<String>,
+}
+
+/// Trait implemented by types that can be described by a JSON [`Schema`].
+///
+/// The trait is implemented for Rust's primitive types, strings, and
+/// collections, and can be derived for structs and enums whose variants have
+/// no fields:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::openapi::JsonSchema;
+///
+/// #[derive(JsonSchema)]
+/// enum Kind {
+///     Book,
+///     Record,
+/// }
+///
+/// #[derive(JsonSchema)]
+/// struct Item {
+///     /// The item's name.
+///     name: String,
+///     kind: Kind,
+///     tags: Vec<String>,
+///     price: Option<f64>,
+/// }
+/// ```
+///
+/// A derived schema is named after its type and placed in the document's
+/// components, where it's referred to from every use. Field doc comments
+/// become property descriptions, and fields of type `Option<T>` are not
+/// required.
+pub trait JsonSchema {
+    /// The name of the schema in the document's components, if it's placed
+    /// there. Anonymous schemas, the default, are inlined at every use.
+    fn schema_name() -> Option<String> {
+        None
+    }
+
+    /// Returns the schema describing `Self`. Schemas of the types `Self` is
+    /// composed of should be retrieved with
+    /// [`SchemaGenerator::subschema_for()`].
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema;
+
+    /// Whether a property or parameter of this type must be present. Returns
+    /// `true` by default, and `false` for `Option<T>`.
+    fn is_required() -> bool {
+        true
+    }
+}
+
+/// Generates schemas, collecting the named ones into a document's components.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaGenerator {
+    components: BTreeMap<String, Schema>,
+}
+
+impl SchemaGenerator {
+    /// Returns a generator without any components.
+    pub fn new() -> Self {
+        SchemaGenerator::default()
+    }
+
+    /// Returns the schema of `T`. If `T`'s schema is named, it's added to the
+    /// components, if it isn't there yet, and a reference to it is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::openapi::{Schema, SchemaGenerator};
+    ///
+    /// let mut gen = SchemaGenerator::new();
+    /// let schema = gen.subschema_for::<Vec<u8>>();
+    /// let byte = Schema::Integer { format: Some("int32") };
+    /// assert_eq!(schema, Schema::Array(Box::new(byte)));
+    /// ```
+    pub fn subschema_for<T: JsonSchema + ?Sized>(&mut self) -> Schema {
+        let name = match T::schema_name() {
+            Some(name) => name,
+            None => return T::json_schema(self),
+        };
+
+        if !self.components.contains_key(&name) {
+            // Insert a placeholder first so that recursive types terminate.
+            self.components.insert(name.clone(), Schema::Any);
+            let schema = T::json_schema(self);
+            self.components.insert(name.clone(), schema);
+        }
+
+        Schema::Reference(name)
+    }
+
+    /// Returns the schema `schema` refers to, following references into the
+    /// components, or `schema` itself if it isn't a reference.
+    pub fn resolve<'a>(&'a self, mut schema: &'a Schema) -> &'a Schema {
+        while let Schema::Reference(name) = schema {
+            match self.components.get(name) {
+                Some(component) if component != schema => schema = component,
+                _ => break,
+            }
+        }
+
+        schema
+    }
+
+    /// Returns the named schemas generated so far.
+    pub fn components(&self) -> &BTreeMap<String, Schema> {
+        &self.components
+    }
+
+    /// Consumes the generator, returning the named schemas it generated.
+    pub fn into_components(self) -> BTreeMap<String, Schema> {
+        self.components
+    }
+}
+
+macro_rules! impl_schema {
+    ($($T:ty => $schema:expr),* $(,)?) => ($(
+        impl JsonSchema for $T {
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                $schema
+            }
+        }
+    )*)
+}
+
+impl_schema! {
+    bool => Schema::Boolean,
+    i8 => Schema::Integer { format: Some("int32") },
+    i16 => Schema::Integer { format: Some("int32") },
+    i32 => Schema::Integer { format: Some("int32") },
+    i64 => Schema::Integer { format: Some("int64") },
+    i128 => Schema::Integer { format: None },
+    isize => Schema::Integer { format: Some("int64") },
+    u8 => Schema::Integer { format: Some("int32") },
+    u16 => Schema::Integer { format: Some("int32") },
+    u32 => Schema::Integer { format: Some("int64") },
+    u64 => Schema::Integer { format: Some("int64") },
+    u128 => Schema::Integer { format: None },
+    usize => Schema::Integer { format: Some("int64") },
+    f32 => Schema::Number { format: Some("float") },
+    f64 => Schema::Number { format: Some("double") },
+    char => Schema::string(),
+    str => Schema::string(),
+    String => Schema::string(),
+    RawStr => Schema::string(),
+    PathBuf => Schema::string(),
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for &T {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+
+    fn is_required() -> bool {
+        T::is_required()
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Box<T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+
+    fn is_required() -> bool {
+        T::is_required()
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Arc<T> {
+    fn schema_name() -> Option<String> {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+
+    fn is_required() -> bool {
+        T::is_required()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        Schema::Nullable(Box::new(gen.subschema_for::<T>()))
+    }
+
+    fn is_required() -> bool {
+        false
+    }
+}
+
+macro_rules! impl_array_schema {
+    ($($T:ty),*) => ($(
+        impl<T: JsonSchema> JsonSchema for $T {
+            fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+                Schema::Array(Box::new(gen.subschema_for::<T>()))
+            }
+        }
+    )*)
+}
+
+impl_array_schema!([T], Vec<T>, VecDeque<T>, HashSet<T>, BTreeSet<T>);
+
+macro_rules! impl_map_schema {
+    ($($T:ty),*) => ($(
+        impl<K, V: JsonSchema> JsonSchema for $T {
+            fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+                Schema::Map(Box::new(gen.subschema_for::<V>()))
+            }
+        }
+    )*)
+}
+
+impl_map_schema!(HashMap<K, V>, BTreeMap<K, V>);
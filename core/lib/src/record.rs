@@ -0,0 +1,415 @@
+//! Recording of requests and their replay in tests.
+//!
+//! A [`Recorder`] fairing writes incoming requests, their method, URI,
+//! headers, and body, to a directory, one file per request. A [`Recording`]
+//! reads such a file back, and [`Client::replay()`] dispatches it against an
+//! application in a test. Together, they build regression suites from samples
+//! of production traffic.
+//!
+//! ```rust
+//! use rocket::record::Recorder;
+//!
+//! # let dir = std::env::temp_dir();
+//! rocket::ignite().attach(Recorder::new(dir).sample(100))
+//! # ;
+//! ```
+//!
+//! Then, in a test:
+//!
+//! ```rust,no_run
+//! use rocket::local::blocking::Client;
+//! use rocket::record::Recording;
+//! use rocket::http::Status;
+//!
+//! # let dir = std::env::temp_dir();
+//! let client = Client::new(rocket::ignite()).expect("valid rocket");
+//! for (path, recording) in Recording::load_dir(dir).expect("recordings") {
+//!     let response = client.replay(&recording).dispatch();
+//!     assert_ne!(response.status(), Status::InternalServerError, "{:?}", path);
+//! }
+//! ```
+//!
+//! # Format
+//!
+//! Every request is stored as an HTTP/1.1 request message: a request line,
+//! the headers, an empty line, and the body. The files are named
+//! `{millis}-{n}.http`, where `millis` is the time the request was received in
+//! milliseconds since the Unix epoch and `n` counts the requests seen by the
+//! recorder, so that the files sort in the order the requests were received.
+//! Recordings can thus be inspected, edited, and written by hand.
+//!
+//! Requests are recorded as they are received, before request fairings run
+//! and before the method is rewritten by a `_method` form field. Bodies longer
+//! than the recorder's [body limit](Recorder::body_limit()) are truncated. The
+//! values of sensitive headers, `Authorization`, `Proxy-Authorization`, and
+//! `Cookie` by default, are [redacted](Recorder::redact()), as are the parts
+//! of bodies selected by [body redaction hooks](Recorder::redact_body()). The
+//! `Content-Length` and `Transfer-Encoding` headers are replaced by a
+//! `Content-Length` that matches the recorded body.
+//!
+//! Recordings are written by a background thread, so recording never delays
+//! the handling of a request; if the thread falls behind by more than 1024
+//! recordings, new ones are dropped with a warning. On Unix, the files are
+//! only readable and writable by their owner (mode `0600`).
+//!
+//! [`Client::replay()`]: crate::local::blocking::Client::replay()
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::time::SystemTime;
+
+use crate::{Request, Rocket};
+use crate::data::{ByteUnit, Data, ToByteUnit};
+use crate::fairing::{Fairing, Info, Kind};
+use crate::http::{Header, Method, uncased::Uncased};
+
+/// The value recorded in place of a redacted header's value.
+const REDACTED: &str = "[redacted]";
+
+/// The headers describing the framing of a request's body, which are replaced
+/// by a `Content-Length` matching the recorded body.
+const FRAMING: &[&str] = &["Content-Length", "Transfer-Encoding"];
+
+/// The number of recordings that can be waiting to be written.
+const QUEUE_SIZE: usize = 1024;
+
+type BodyRedactor = Arc<dyn Fn(&Request<'_>, &mut Vec<u8>) + Send + Sync>;
+
+/// A fairing that records incoming requests to files.
+///
+/// See the [module level documentation](crate::record) for details.
+#[derive(Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    body_limit: ByteUnit,
+    every: u64,
+    redact: Vec<Uncased<'static>>,
+    redact_body: Vec<BodyRedactor>,
+    seen: Arc<AtomicU64>,
+    /// The queue of the writer thread, started when the recorder is attached.
+    writer: Option<SyncSender<(PathBuf, Vec<u8>)>>,
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("dir", &self.dir)
+            .field("body_limit", &self.body_limit)
+            .field("every", &self.every)
+            .field("redact", &self.redact)
+            .field("redact_body", &self.redact_body.len())
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// Returns a `Recorder` that records every request to files in `dir`,
+    /// which is created if it doesn't exist. Bodies are recorded up to 64KiB.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recorder;
+    ///
+    /// rocket::ignite().attach(Recorder::new("/var/lib/app/requests"))
+    /// # ;
+    /// ```
+    pub fn new<P: AsRef<Path>>(dir: P) -> Recorder {
+        let redact = ["Authorization", "Proxy-Authorization", "Cookie"];
+        Recorder {
+            dir: dir.as_ref().to_path_buf(),
+            body_limit: 64.kibibytes(),
+            every: 1,
+            redact: redact.iter().map(|&name| Uncased::from(name)).collect(),
+            redact_body: vec![],
+            seen: Arc::new(AtomicU64::new(0)),
+            writer: None,
+        }
+    }
+
+    /// Records at most `limit` bytes of request bodies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recorder;
+    /// use rocket::data::ToByteUnit;
+    ///
+    /// let recorder = Recorder::new("requests").body_limit(1.mebibytes());
+    /// ```
+    pub fn body_limit(mut self, limit: ByteUnit) -> Self {
+        self.body_limit = limit;
+        self
+    }
+
+    /// Records one in every `n` requests. A value of `0` is treated as `1`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recorder;
+    ///
+    /// // Record 1% of all requests.
+    /// let recorder = Recorder::new("requests").sample(100);
+    /// ```
+    pub fn sample(mut self, n: u64) -> Self {
+        self.every = std::cmp::max(n, 1);
+        self
+    }
+
+    /// Records the value of the header named `name` as `[redacted]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recorder;
+    ///
+    /// let recorder = Recorder::new("requests").redact("X-Api-Key");
+    /// ```
+    pub fn redact<S: Into<String>>(mut self, name: S) -> Self {
+        self.redact.push(Uncased::from(name.into()));
+        self
+    }
+
+    /// Calls `redact` with every recorded request and its body, truncated to
+    /// the [body limit](Recorder::body_limit()), before the body is written.
+    /// `redact` can replace sensitive values in the body, or the whole body.
+    /// This method can be called multiple times; the hooks run in the order
+    /// they were registered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recorder;
+    ///
+    /// // Don't record the bodies of requests to `/login`.
+    /// let recorder = Recorder::new("requests").redact_body(|req, body| {
+    ///     if req.uri().path() == "/login" {
+    ///         *body = b"[redacted]".to_vec();
+    ///     }
+    /// });
+    /// ```
+    pub fn redact_body<F>(mut self, redact: F) -> Self
+        where F: Fn(&Request<'_>, &mut Vec<u8>) + Send + Sync + 'static
+    {
+        self.redact_body.push(Arc::new(redact));
+        self
+    }
+
+    /// Records `req`, with the start of the body in `data`, if it is sampled.
+    pub(crate) async fn record(&self, req: &Request<'_>, data: &mut Data) {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed);
+        if n % self.every != 0 {
+            return;
+        }
+
+        let limit = usize::try_from(self.body_limit.as_u64()).unwrap_or(usize::MAX);
+        let body = data.buffer_up_to(limit.saturating_add(1)).await;
+        if body.len() > limit {
+            warn_!("Recording only the first {} of the body of {}.", self.body_limit, req);
+        }
+
+        let mut body = body[..std::cmp::min(body.len(), limit)].to_vec();
+        self.redact_body.iter().for_each(|redact| redact(req, &mut body));
+
+        let mut message = format!("{} {} HTTP/1.1\r\n", req.method(), req.uri());
+        for header in req.headers().iter() {
+            // The recorded body is dechunked, truncated, and redacted: its
+            // framing is described by the `Content-Length` written below.
+            if FRAMING.iter().any(|&name| header.name() == name) {
+                continue;
+            }
+
+            let redacted = self.redact.iter().any(|n| n.as_uncased_str() == header.name());
+            let value = match redacted {
+                true => REDACTED,
+                false => header.value(),
+            };
+
+            message.push_str(&format!("{}: {}\r\n", header.name(), value));
+        }
+
+        if !body.is_empty() {
+            message.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+
+        message.push_str("\r\n");
+        let mut bytes = message.into_bytes();
+        bytes.extend_from_slice(&body);
+
+        let millis = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+
+        let path = self.dir.join(format!("{}-{:08}.http", millis, n));
+        let writer = self.writer.as_ref().expect("writer started on attach");
+        match writer.try_send((path, bytes)) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => warn_!("Dropping recording of {}: queue is full.", req),
+            Err(TrySendError::Disconnected(_)) => warn_!("Recorder writer stopped."),
+        }
+    }
+}
+
+/// Writes the recordings received from `queue` until every sender is dropped.
+fn write_recordings(queue: Receiver<(PathBuf, Vec<u8>)>) {
+    for (path, bytes) in queue {
+        if let Err(e) = write_private(&path, &bytes) {
+            warn_!("Failed to record request to {:?}: {}.", path, e);
+        }
+    }
+}
+
+/// Writes `bytes` to a new file at `path` that, on Unix, only its owner can
+/// read and write.
+fn write_private(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)] {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(bytes)
+}
+
+#[crate::async_trait]
+impl Fairing for Recorder {
+    fn info(&self) -> Info {
+        Info { name: "Request Recorder", kind: Kind::Attach }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        if let Err(e) = tokio::fs::create_dir_all(&self.dir).await {
+            error_!("Failed to create recording directory {:?}: {}.", self.dir, e);
+            return Err(rocket);
+        }
+
+        let (writer, queue) = sync_channel(QUEUE_SIZE);
+        let spawned = std::thread::Builder::new()
+            .name("rocket-recorder".into())
+            .spawn(move || write_recordings(queue));
+
+        if let Err(e) = spawned {
+            error_!("Failed to start the request recorder: {}.", e);
+            return Err(rocket);
+        }
+
+        Ok(rocket.manage(Recorder { writer: Some(writer), ..self.clone() }))
+    }
+}
+
+/// A recorded request.
+///
+/// See the [module level documentation](crate::record) for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    method: Method,
+    uri: String,
+    headers: Vec<Header<'static>>,
+    body: Vec<u8>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Recording {
+    /// Parses a recording from `bytes`, an HTTP/1.1 request message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::record::Recording;
+    /// use rocket::http::Method;
+    ///
+    /// let bytes = b"POST /items HTTP/1.1\r\nContent-Type: text/plain\r\n\r\nhi";
+    /// let recording = Recording::parse(bytes).unwrap();
+    /// assert_eq!(recording.method(), Method::Post);
+    /// assert_eq!(recording.uri(), "/items");
+    /// assert_eq!(recording.headers()[0].value(), "text/plain");
+    /// assert_eq!(recording.body(), b"hi");
+    /// ```
+    pub fn parse(bytes: &[u8]) -> io::Result<Recording> {
+        let end = bytes.windows(4).position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| invalid("missing end of request head"))?;
+
+        let head = std::str::from_utf8(&bytes[..end])
+            .map_err(|_| invalid("request head is not UTF-8"))?;
+
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split(' ');
+        let method = request_line.next()
+            .and_then(|method| method.parse().ok())
+            .ok_or_else(|| invalid("invalid request method"))?;
+
+        let uri = request_line.next()
+            .filter(|uri| !uri.is_empty())
+            .ok_or_else(|| invalid("missing request URI"))?;
+
+        let mut headers = vec![];
+        for line in lines {
+            let colon = line.find(':').ok_or_else(|| invalid("invalid header"))?;
+            let (name, value) = (line[..colon].trim(), line[colon + 1..].trim());
+            headers.push(Header::new(name.to_string(), value.to_string()));
+        }
+
+        Ok(Recording {
+            method,
+            uri: uri.to_string(),
+            headers,
+            body: bytes[end + 4..].to_vec(),
+        })
+    }
+
+    /// Reads and parses the recording in the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Recording> {
+        Recording::parse(&fs::read(path)?)
+    }
+
+    /// Reads and parses every recording, every `.http` file, in `dir`, in the
+    /// order they were recorded, along with the path to its file.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<(PathBuf, Recording)>> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "http") {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        paths.into_iter()
+            .map(|path| Recording::load(&path).map(|recording| (path, recording)))
+            .collect()
+    }
+
+    /// Returns the recorded request's method.
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Returns the recorded request's URI.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the recorded request's headers, in the order they were
+    /// received. Redacted headers have the value `[redacted]`.
+    pub fn headers(&self) -> &[Header<'static>] {
+        &self.headers
+    }
+
+    /// Returns the recorded request's body, truncated to the recorder's body
+    /// limit.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+}
@@ -0,0 +1,93 @@
+use std::net::SocketAddr;
+
+use crate::http::private::TlsInfo;
+use crate::request::{self, FromRequest, Request};
+
+/// The local address of the connection a request arrived on, as recorded in
+/// the request's extensions.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LocalAddr(pub SocketAddr);
+
+/// Request guard for the details of the connection a request arrived on.
+///
+/// The guard always succeeds. Details the connection doesn't have, such as TLS
+/// details of a plaintext connection or addresses of a Unix domain socket
+/// connection, are `None`, as are all details of local requests. On listeners
+/// with [`proxy_protocol`](crate::config#proxy-protocol) enabled, the
+/// addresses are those relayed by the proxy, when it relays them.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::request::ConnectionInfo;
+///
+/// #[get("/whereami")]
+/// fn whereami(conn: ConnectionInfo<'_>) -> String {
+///     match conn.tls_version() {
+///         Some(version) => format!("{:?} over {}", conn.local_addr(), version),
+///         None => format!("{:?} in plaintext", conn.local_addr()),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionInfo<'r> {
+    local: Option<SocketAddr>,
+    peer: Option<SocketAddr>,
+    tls: Option<&'r TlsInfo>,
+}
+
+impl<'r> ConnectionInfo<'r> {
+    pub(crate) fn new(req: &'r Request<'_>) -> ConnectionInfo<'r> {
+        ConnectionInfo {
+            local: req.state.local_addr,
+            peer: req.remote(),
+            tls: req.state.tls.as_deref(),
+        }
+    }
+
+    /// The address of the socket the connection was accepted on.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local
+    }
+
+    /// The address of the client. This is the request's
+    /// [`remote()`](Request::remote()) address.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    /// Whether the connection is secured with TLS.
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    /// The application protocol negotiated via ALPN, such as `h2`.
+    pub fn alpn_protocol(&self) -> Option<&'r str> {
+        self.tls?.alpn.as_deref()
+    }
+
+    /// The TLS version, such as `TLSv1.3`.
+    pub fn tls_version(&self) -> Option<&'r str> {
+        self.tls?.version.as_deref()
+    }
+
+    /// The TLS cipher suite, such as `TLS13_AES_256_GCM_SHA384`.
+    pub fn cipher_suite(&self) -> Option<&'r str> {
+        self.tls?.cipher.as_deref()
+    }
+
+    /// The host name the client requested via TLS SNI.
+    pub fn sni_hostname(&self) -> Option<&'r str> {
+        self.tls?.sni.as_deref()
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for ConnectionInfo<'a> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(req.connection())
+    }
+}
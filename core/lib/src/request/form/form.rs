@@ -3,6 +3,7 @@ use std::ops::Deref;
 use crate::outcome::Outcome::*;
 use crate::request::{Request, form::{FromForm, FormItems, FormDataError}};
 use crate::data::{Outcome, Transform, Transformed, Data, FromTransformedData, TransformFuture, FromDataFuture};
+use crate::data::Bytes;
 use crate::http::{Status, uri::{Query, FromUriParam}};
 
 /// A data guard for parsing [`FromForm`] types strictly.
@@ -146,11 +147,19 @@ impl<T> Deref for Form<T> {
 
 impl<'f, T: FromForm<'f>> Form<T> {
     pub(crate) fn from_data(
-        form_str: &'f str,
+        form: &'f [u8],
         strict: bool
     ) -> Outcome<T, FormDataError<'f, T::Error>> {
         use self::FormDataError::*;
 
+        let form_str = match std::str::from_utf8(form) {
+            Ok(form_str) => form_str,
+            Err(e) => {
+                let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+                return Failure((Status::InternalServerError, Io(e)));
+            }
+        };
+
         let mut items = FormItems::from(form_str);
         let result = T::from_form(&mut items, strict);
         if !items.exhaust() {
@@ -183,8 +192,8 @@ impl<'f, T: FromForm<'f>> Form<T> {
 /// logging format.
 impl<'f, T: FromForm<'f> + Send + 'f> FromTransformedData<'f> for Form<T> {
     type Error = FormDataError<'f, T::Error>;
-    type Owned = String;
-    type Borrowed = str;
+    type Owned = Bytes;
+    type Borrowed = [u8];
 
     fn transform<'r>(
         request: &'r Request<'_>,
@@ -196,8 +205,8 @@ impl<'f, T: FromForm<'f> + Send + 'f> FromTransformedData<'f> for Form<T> {
                 return Transform::Borrowed(Forward(data));
             }
 
-            match data.open(request.limits().forms).stream_to_string().await {
-                Ok(form_string) => Transform::Borrowed(Success(form_string)),
+            match data.open(request.limits().forms).into_bytes().await {
+                Ok(form) => Transform::Borrowed(Success(form)),
                 Err(e) => {
                     let err = (Status::InternalServerError, FormDataError::Io(e));
                     Transform::Borrowed(Failure(err))
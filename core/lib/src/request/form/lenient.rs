@@ -1,7 +1,7 @@
 use std::ops::Deref;
 
 use crate::request::{Request, form::{Form, FormDataError, FromForm}};
-use crate::data::{Data, Transformed, FromTransformedData, TransformFuture, FromDataFuture};
+use crate::data::{Data, Bytes, Transformed, FromTransformedData, TransformFuture, FromDataFuture};
 use crate::http::uri::{Query, FromUriParam};
 
 /// A data guard for parsing [`FromForm`] types leniently.
@@ -95,8 +95,8 @@ impl<T> Deref for LenientForm<T> {
 
 impl<'f, T: FromForm<'f> + Send + 'f> FromTransformedData<'f> for LenientForm<T> {
     type Error = FormDataError<'f, T::Error>;
-    type Owned = String;
-    type Borrowed = str;
+    type Owned = Bytes;
+    type Borrowed = [u8];
 
     fn transform<'r>(r: &'r Request<'_>, d: Data) -> TransformFuture<'r, Self::Owned, Self::Error> {
         <Form<T>>::transform(r, d)
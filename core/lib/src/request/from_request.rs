@@ -8,7 +8,7 @@ use crate::request::Request;
 use crate::outcome::{self, IntoOutcome};
 use crate::outcome::Outcome::*;
 
-use crate::http::{Status, ContentType, Accept, Method, CookieJar, uri::Origin};
+use crate::http::{Status, ContentType, Accept, Method, CookieJar, ProxyHeader, uri::Origin};
 
 /// Type alias for the `Outcome` of a `FromRequest` conversion.
 pub type Outcome<S, E> = outcome::Outcome<S, (Status, E), ()>;
@@ -160,6 +160,14 @@ impl<S, E> IntoOutcome<S, (Status, E), ()> for Result<S, E> {
 ///
 ///     _This implementation always returns successfully._
 ///
+///   * **&ProxyHeader**
+///
+///     Extracts the [`ProxyHeader`] the request's connection started with. If
+///     the listener that accepted the connection doesn't have
+///     `proxy_protocol` enabled, the request is forwarded.
+///
+///     _This implementation always returns successfully._
+///
 ///   * **Option&lt;T>** _where_ **T: FromRequest**
 ///
 ///     The type `T` is derived from the incoming request using `T`'s
@@ -454,6 +462,18 @@ impl<'a, 'r> FromRequest<'a, 'r> for SocketAddr {
     }
 }
 
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for &'a ProxyHeader {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
+        match request.proxy_header() {
+            Some(header) => Success(header),
+            None => Forward(())
+        }
+    }
+}
+
 impl<'a, 'r, T: FromRequest<'a, 'r> + 'a> FromRequest<'a, 'r> for Result<T, T::Error> {
     type Error = std::convert::Infallible;
 
@@ -6,6 +6,10 @@ mod form;
 mod from_request;
 mod state;
 mod query;
+mod phase;
+mod mtls;
+mod connection;
+mod upgrade;
 
 #[cfg(test)]
 mod tests;
@@ -13,6 +17,7 @@ mod tests;
 #[doc(hidden)] pub use rocket_codegen::{FromForm, FromFormValue};
 
 pub use self::request::Request;
+pub(crate) use self::request::GuardStubs;
 pub use self::from_request::{FromRequest, Outcome};
 pub use self::param::{FromParam, FromSegments};
 pub use self::form::{FromForm, FromFormValue};
@@ -20,6 +25,12 @@ pub use self::form::{Form, LenientForm, FormItems, FormItem};
 pub use self::form::{FormError, FormParseError, FormDataError};
 pub use self::state::State;
 pub use self::query::{Query, FromQuery};
+pub use self::phase::{Phase, PhaseTiming};
+pub use self::mtls::PeerCertificates;
+pub use self::connection::ConnectionInfo;
+pub use self::upgrade::{Upgrade, Upgraded};
+pub(crate) use self::upgrade::PendingUpgrade;
+pub(crate) use self::connection::LocalAddr;
 
 #[doc(inline)]
 pub use crate::response::flash::FlashMessage;
@@ -0,0 +1,64 @@
+use crate::request::{self, FromRequest, Request};
+
+/// Request guard for the certificates a client authenticated with via mutual
+/// TLS.
+///
+/// The guard succeeds when the connection the request arrived on was
+/// authenticated by client certificate, which requires a listener configured
+/// with [`MutualTls`](crate::config::MutualTls) and a client that presented a
+/// certificate the listener accepted. Otherwise, the guard forwards. Rocket
+/// verifies the certificates before serving any request; the guard exposes
+/// them, DER-encoded, so that applications can map them to their users.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::request::PeerCertificates;
+///
+/// #[get("/whoami")]
+/// fn whoami(certs: &PeerCertificates) -> String {
+///     format!("certificate of {} bytes", certs.end_entity().len())
+/// }
+///
+/// #[get("/whoami", rank = 2)]
+/// fn anonymous() -> &'static str {
+///     "no certificate presented"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificates(Vec<Vec<u8>>);
+
+impl PeerCertificates {
+    /// Returns `None` if `chain` is empty.
+    pub(crate) fn new(chain: Vec<Vec<u8>>) -> Option<PeerCertificates> {
+        if chain.is_empty() {
+            return None;
+        }
+
+        Some(PeerCertificates(chain))
+    }
+
+    /// The DER-encoded certificate of the client.
+    pub fn end_entity(&self) -> &[u8] {
+        &self.0[0]
+    }
+
+    /// The DER-encoded certificate chain the client presented, starting with
+    /// its own certificate.
+    pub fn chain(&self) -> &[Vec<u8>] {
+        &self.0
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for &'a PeerCertificates {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match req.peer_certificates() {
+            Some(certs) => request::Outcome::Success(certs),
+            None => request::Outcome::Forward(()),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A phase of request processing.
+///
+/// Rocket records when each phase of processing a request begins and ends.
+/// The recorded [`PhaseTiming`]s are available via
+/// [`Request::phases()`](crate::Request::phases()), typically from a response
+/// fairing, for use in metrics and tracing.
+///
+/// Guards, handler, and responder phases are recorded for routes generated by
+/// the route attributes. For other handlers, the entire handler is recorded as
+/// a [`Phase::Handler`]. When a request is forwarded, phases are recorded for
+/// each route that was tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Matching the request against the mounted routes.
+    Routing,
+    /// Running the request, parameter, and data guards of a route.
+    Guards,
+    /// Running a route's handler.
+    Handler,
+    /// Converting the value returned by a handler into a response via its
+    /// [`Responder`](crate::response::Responder) implementation.
+    Responder,
+}
+
+impl Phase {
+    /// Returns the name of the phase in lowercase: `routing`, `guards`,
+    /// `handler`, or `responder`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::request::Phase;
+    ///
+    /// assert_eq!(Phase::Guards.as_str(), "guards");
+    /// ```
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Routing => "routing",
+            Phase::Guards => "guards",
+            Phase::Handler => "handler",
+            Phase::Responder => "responder",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The time spent in one [`Phase`] of processing a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// The phase.
+    pub phase: Phase,
+    /// When the phase began.
+    pub start: Instant,
+    /// When the phase ended.
+    pub end: Instant,
+}
+
+impl PhaseTiming {
+    /// Returns the time spent in the phase.
+    pub fn duration(&self) -> std::time::Duration {
+        self.end - self.start
+    }
+}
+
+/// The phases recorded for a request: those that have completed, and the one
+/// in progress, if any.
+#[derive(Default)]
+pub(crate) struct Phases(Mutex<(Vec<PhaseTiming>, Option<(Phase, Instant)>)>);
+
+impl Phases {
+    /// Ends the phase in progress, if any, and begins `phase`.
+    pub(crate) fn enter(&self, phase: Phase) {
+        let now = Instant::now();
+        let mut phases = self.0.lock().expect("phases lock");
+        if let Some((phase, start)) = phases.1.take() {
+            phases.0.push(PhaseTiming { phase, start, end: now });
+        }
+
+        phases.1 = Some((phase, now));
+    }
+
+    /// Ends the phase in progress, if any.
+    pub(crate) fn exit(&self) {
+        let now = Instant::now();
+        let mut phases = self.0.lock().expect("phases lock");
+        if let Some((phase, start)) = phases.1.take() {
+            phases.0.push(PhaseTiming { phase, start, end: now });
+        }
+    }
+
+    /// The number of completed phases.
+    pub(crate) fn count(&self) -> usize {
+        self.0.lock().expect("phases lock").0.len()
+    }
+
+    /// Ends the phase in progress, if any. If no phases completed since `count`
+    /// phases had, records a `phase` that began at `start`.
+    pub(crate) fn exit_or_record(&self, count: usize, phase: Phase, start: Instant) {
+        self.exit();
+        let mut phases = self.0.lock().expect("phases lock");
+        if phases.0.len() == count {
+            phases.0.push(PhaseTiming { phase, start, end: Instant::now() });
+        }
+    }
+
+    /// The completed phases, in the order they began.
+    pub(crate) fn completed(&self) -> Vec<PhaseTiming> {
+        self.0.lock().expect("phases lock").0.clone()
+    }
+}
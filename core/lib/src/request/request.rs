@@ -1,3 +1,5 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::net::{IpAddr, SocketAddr};
 use std::future::Future;
@@ -6,19 +8,51 @@ use std::str;
 
 use yansi::Paint;
 use state::{Container, Storage};
-use futures::future::BoxFuture;
+use futures::future::{BoxFuture, FutureExt};
 use atomic::{Atomic, Ordering};
 
 use crate::request::{FromParam, FromSegments, FromRequest, Outcome};
-use crate::request::{FromFormValue, FormItems, FormItem};
+use crate::request::{FromFormValue, FormItems, FormItem, PeerCertificates, ConnectionInfo};
+use crate::request::phase::{Phase, PhaseTiming, Phases};
+use crate::request::upgrade::{self, PendingUpgrade, Upgrade, Upgraded};
 
-use crate::{Rocket, Config, Shutdown, Route};
+use crate::{Rocket, Config, Shutdown, Route, TlsReloader, Load};
+use crate::config::{ConfigSnapshot, ListenerConfig, LiveConfig, Protocol};
+use crate::catcher::TypedError;
 use crate::http::{hyper, uri::{Origin, Segments}};
-use crate::http::{Method, Header, HeaderMap};
+use crate::http::{Method, Header, HeaderMap, ProxyHeader};
 use crate::http::{RawStr, ContentType, Accept, MediaType, CookieJar, Cookie};
-use crate::http::private::{Indexed, SmallVec};
+use crate::http::private::{Indexed, SmallVec, TlsInfo};
 use crate::data::Limits;
 
+/// Marks, in request-local state, a request served from the response cache.
+struct ServedFromCache;
+
+type GuardStub<T, E> = Box<dyn Fn(&Request<'_>) -> Outcome<T, E> + Send + Sync>;
+
+/// Request guard stubs, keyed by the type of the guard, as set in
+/// request-local state by a local client. Each is a `GuardStub<T, E>`.
+#[derive(Default)]
+pub(crate) struct GuardStubs(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl GuardStubs {
+    pub(crate) fn insert<T, E, F>(&mut self, stub: F)
+        where T: 'static, E: 'static,
+              F: Fn(&Request<'_>) -> Outcome<T, E> + Send + Sync + 'static
+    {
+        let stub: GuardStub<T, E> = Box::new(stub);
+        self.0.insert(TypeId::of::<T>(), Box::new(stub));
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get<T: 'static, E: 'static>(&self) -> Option<&GuardStub<T, E>> {
+        self.0.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+}
+
 /// The type of an incoming web request.
 ///
 /// This should be used sparingly in Rocket applications. In particular, it
@@ -35,8 +69,20 @@ pub struct Request<'r> {
 
 pub(crate) struct RequestState<'r> {
     pub config: &'r Config,
+    pub snapshot: ConfigSnapshot,
+    pub live: &'r LiveConfig,
+    pub listener: Option<Arc<ListenerConfig>>,
+    pub peer_certificates: Option<Arc<PeerCertificates>>,
+    pub proxy_header: Option<Arc<ProxyHeader>>,
+    pub local_addr: Option<SocketAddr>,
+    pub tls: Option<Arc<TlsInfo>>,
+    pub upgrade: Option<Arc<PendingUpgrade>>,
+    pub protocol: Option<Protocol>,
+    pub http_version: Option<&'static str>,
     pub managed: &'r Container,
     pub shutdown: &'r Shutdown,
+    pub tls_reloader: &'r TlsReloader,
+    pub load: &'r Load,
     pub path_segments: SmallVec<[Indices; 12]>,
     pub query_items: Option<SmallVec<[IndexedFormItem; 6]>>,
     pub route: Atomic<Option<&'r Route>>,
@@ -44,8 +90,14 @@ pub(crate) struct RequestState<'r> {
     pub accept: Storage<Option<Accept>>,
     pub content_type: Storage<Option<ContentType>>,
     pub cache: Arc<Container>,
+    pub near_misses: Storage<Vec<String>>,
+    pub declined: Atomic<bool>,
+    pub phases: Phases,
 }
 
+/// A [`TypedError`] recorded in the request-local state of a request.
+struct Failure<E>(E);
+
 impl Request<'_> {
     pub(crate) fn clone(&self) -> Self {
         Request {
@@ -62,8 +114,20 @@ impl RequestState<'_> {
     fn clone(&self) -> Self {
         RequestState {
             config: self.config,
+            snapshot: self.snapshot.clone(),
+            live: self.live,
+            listener: self.listener.clone(),
+            peer_certificates: self.peer_certificates.clone(),
+            proxy_header: self.proxy_header.clone(),
+            local_addr: self.local_addr,
+            tls: self.tls.clone(),
+            upgrade: self.upgrade.clone(),
+            protocol: self.protocol,
+            http_version: self.http_version,
             managed: self.managed,
             shutdown: self.shutdown,
+            tls_reloader: self.tls_reloader,
+            load: self.load,
             path_segments: self.path_segments.clone(),
             query_items: self.query_items.clone(),
             route: Atomic::new(self.route.load(Ordering::Acquire)),
@@ -71,6 +135,9 @@ impl RequestState<'_> {
             accept: self.accept.clone(),
             content_type: self.content_type.clone(),
             cache: self.cache.clone(),
+            near_misses: Storage::new(),
+            declined: Atomic::new(false),
+            phases: Phases::default(),
         }
     }
 }
@@ -92,13 +159,28 @@ impl<'r> Request<'r> {
                 path_segments: SmallVec::new(),
                 query_items: None,
                 config: &rocket.config,
+                snapshot: rocket.live.current(),
+                live: &rocket.live,
+                listener: None,
+                peer_certificates: None,
+                proxy_header: None,
+                local_addr: None,
+                tls: None,
+                upgrade: None,
+                protocol: None,
+                http_version: None,
                 managed: &rocket.managed_state,
                 shutdown: &rocket.shutdown_handle,
+                tls_reloader: &rocket.tls_reloader,
+                load: &rocket.load,
                 route: Atomic::new(None),
                 cookies: CookieJar::new(rocket.config.secret_key()),
                 accept: Storage::new(),
                 content_type: Storage::new(),
                 cache: Arc::new(Container::new()),
+                near_misses: Storage::new(),
+                declined: Atomic::new(false),
+                phases: Phases::default(),
             }
         };
 
@@ -232,6 +314,167 @@ impl<'r> Request<'r> {
         self.remote = Some(address);
     }
 
+    /// Returns the listener that accepted the connection this request arrived
+    /// on, or `None` if the request wasn't received by a launched server, as
+    /// is the case for requests dispatched via a local `Client`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.listener().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn listener(&self) -> Option<&ListenerConfig> {
+        self.state.listener.as_deref()
+    }
+
+    /// Returns the certificates the client authenticated with via mutual TLS,
+    /// or `None` if it didn't authenticate by certificate. See
+    /// [`PeerCertificates`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.peer_certificates().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn peer_certificates(&self) -> Option<&PeerCertificates> {
+        self.state.peer_certificates.as_deref()
+    }
+
+    /// Returns the PROXY protocol header the request's connection started
+    /// with, or `None` if the listener that accepted the connection doesn't
+    /// have `proxy_protocol` enabled. The client address in the header is the
+    /// request's [`remote()`](Request::remote()) address. See
+    /// [PROXY protocol](crate::config#proxy-protocol) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.proxy_header().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn proxy_header(&self) -> Option<&ProxyHeader> {
+        self.state.proxy_header.as_deref()
+    }
+
+    /// Returns the details of the connection the request arrived on, such as
+    /// its local address and TLS session. See [`ConnectionInfo`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// let connection = request.connection();
+    /// assert!(connection.local_addr().is_none());
+    /// assert!(!connection.is_tls());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn connection(&self) -> ConnectionInfo<'_> {
+        ConnectionInfo::new(self)
+    }
+
+    /// Accepts the client's request to upgrade the connection to `protocol`,
+    /// registering `handler` to take over the connection.
+    ///
+    /// If the request asks to upgrade to `protocol`, via the `Connection:
+    /// upgrade` and `Upgrade` headers, returns an [`Upgrade`] responder. When
+    /// a `101 Switching Protocols` response, such as the `Upgrade`, is sent,
+    /// `handler` is called with the raw [`Upgraded`] IO of the connection,
+    /// which Rocket no longer serves. If another response is sent instead,
+    /// `handler` is dropped and the connection is served as usual. Calling
+    /// this method again replaces the previously registered handler.
+    ///
+    /// Returns `None` if the request doesn't ask to upgrade to `protocol` or if
+    /// its connection can't be upgraded: HTTP/2 connections and connections of
+    /// requests with a body can't be upgraded. Local requests are upgraded to
+    /// an in-memory connection whose other end is returned by
+    /// [`LocalResponse::upgraded()`](crate::local::asynchronous::LocalResponse::upgraded()).
+    ///
+    /// Upgraded connections aren't tracked by Rocket: they aren't counted as
+    /// open connections and aren't closed on shutdown. `handler` should
+    /// watch for [`Shutdown`](crate::Shutdown) itself if needed.
+    ///
+    /// # Example
+    ///
+    /// A route upgrading to an echo protocol:
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::Request;
+    /// use rocket::request::Upgrade;
+    /// use rocket::http::Status;
+    /// use rocket::tokio::io::{self, AsyncWriteExt};
+    ///
+    /// #[get("/echo")]
+    /// fn echo(req: &Request<'_>) -> Result<Upgrade, Status> {
+    ///     let upgrade = req.upgrade("echo", |io| async move {
+    ///         let (mut reader, mut writer) = io::split(io);
+    ///         let _ = io::copy(&mut reader, &mut writer).await;
+    ///         let _ = writer.shutdown().await;
+    ///     });
+    ///
+    ///     upgrade.ok_or(Status::UpgradeRequired)
+    /// }
+    /// ```
+    pub fn upgrade<F, Fut>(&self, protocol: &str, handler: F) -> Option<Upgrade>
+        where F: FnOnce(Upgraded) -> Fut + Send + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        upgrade::accept(self, protocol, Box::new(move |io| handler(io).boxed()))
+    }
+
+    /// Returns the HTTP protocol this request arrived over, or `None` if the
+    /// request wasn't received by a launched server, as is the case for
+    /// requests dispatched via a local `Client`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.protocol().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn protocol(&self) -> Option<Protocol> {
+        self.state.protocol
+    }
+
+    /// Returns the HTTP version of this request as it appears in a request
+    /// line, such as `HTTP/1.0`, `HTTP/1.1`, or `HTTP/2.0`, or `None` if the
+    /// request wasn't received by a launched server.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// assert!(request.http_version().is_none());
+    /// # });
+    /// ```
+    #[inline(always)]
+    pub fn http_version(&self) -> Option<&'static str> {
+        self.state.http_version
+    }
+
     /// Returns the IP address in the "X-Real-IP" header of the request if such
     /// a header exists and contains a valid IP address.
     ///
@@ -476,7 +719,10 @@ impl<'r> Request<'r> {
         }
     }
 
-    /// Returns the configured application data limits.
+    /// Returns the configured application data limits. The limits are those
+    /// of the [`ConfigSnapshot`](crate::config::ConfigSnapshot) that was
+    /// current when the request was received, with any overrides set by the
+    /// route currently handling the request via [`Route::limit()`] applied.
     ///
     /// # Example
     ///
@@ -488,7 +734,14 @@ impl<'r> Request<'r> {
     /// # });
     /// ```
     pub fn limits(&self) -> &'r Limits {
-        &self.state.config.limits
+        let limits = &self.state.snapshot.limits;
+        match self.route() {
+            Some(route) if !route.limits.is_empty() => {
+                self.state.live.intern(route.limits(limits))
+            }
+            _ if self.state.snapshot.version() == 0 => &self.state.config.limits,
+            _ => self.state.live.intern(limits.clone()),
+        }
     }
 
     /// Get the presently matched route, if any.
@@ -543,6 +796,27 @@ impl<'r> Request<'r> {
         T::from_request(self)
     }
 
+    /// Returns the outcome of the stub registered for the request guard `T`,
+    /// with the error type `E`, via [`Overrides::guard()`], if there is one.
+    ///
+    /// Stubs are registered only by local test clients, which set them in the
+    /// request-local state of the requests they build. A guard implementation
+    /// that returns this outcome when there is one can be stubbed in tests.
+    /// See [`Overrides`] for an example.
+    ///
+    /// [`Overrides`]: crate::local::Overrides
+    /// [`Overrides::guard()`]: crate::local::Overrides::guard()
+    pub fn stubbed_guard<T: 'static, E: 'static>(&self) -> Option<Outcome<T, E>> {
+        let stub = self.state.cache.try_get::<Arc<GuardStubs>>()?.get::<T, E>()?;
+        Some(stub(self))
+    }
+
+    /// Sets the request guard stubs consulted by [`Request::stubbed_guard()`]
+    /// for this request.
+    pub(crate) fn set_guard_stubs(&self, stubs: Arc<GuardStubs>) {
+        self.state.cache.set(stubs);
+    }
+
     #[inline(always)]
     pub fn managed_state<T>(&self) -> Option<&'r T>
         where T: Send + Sync + 'static
@@ -550,6 +824,62 @@ impl<'r> Request<'r> {
         self.state.managed.try_get::<T>()
     }
 
+    /// Returns the error of type `E` that failed this request, if any. Returns
+    /// `None` if no error was recorded, or if the recorded error is not an
+    /// `E`.
+    ///
+    /// Errors are recorded in the request-local state when a guard whose error
+    /// type implements [`TypedError`] fails, or when a handler calls
+    /// [`Request::set_error()`]. This method is primarily useful in catchers
+    /// and response fairings; see [typed catchers](crate::catcher#typed-errors)
+    /// for a more convenient alternative.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use rocket::Request;
+    /// # use rocket::http::Method;
+    /// # Request::example(Method::Get, "/uri", |request| {
+    /// let error = request.error::<std::num::ParseIntError>();
+    /// assert!(error.is_none());
+    /// # });
+    /// ```
+    pub fn error<E: TypedError>(&self) -> Option<&E> {
+        self.state.cache.try_get::<Failure<E>>().map(|failure| &failure.0)
+    }
+
+    /// Records `error` as the error that failed this request, making it
+    /// available to catchers and response fairings via [`Request::error()`].
+    /// Only the first recorded error of each type is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use std::num::ParseIntError;
+    /// use rocket::{Request, Data};
+    /// use rocket::handler::Outcome;
+    /// use rocket::http::Status;
+    ///
+    /// fn parse_route<'r>(req: &'r Request, _: Data) -> Outcome<'r> {
+    ///     match req.uri().path()[1..].parse::<u8>() {
+    ///         Ok(n) => Outcome::from(req, n.to_string()),
+    ///         Err(e) => {
+    ///             req.set_error(e);
+    ///             Outcome::failure(Status::BadRequest)
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[catch(400, error = "<e>")]
+    /// fn bad_request(e: &ParseIntError) -> String {
+    ///     format!("Bad request: {}.", e)
+    /// }
+    /// ```
+    pub fn set_error<E: TypedError>(&self, error: E) {
+        self.local_cache(|| Failure(error));
+    }
+
     /// Retrieves the cached value for type `T` from the request-local cached
     /// state of `self`. If no such value has previously been cached for this
     /// request, `f` is called to produce the value which is subsequently
@@ -0,0 +1,304 @@
+use std::io;
+use std::pin::Pin;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::request::Request;
+use crate::response::{self, Responder, Response};
+use crate::http::{hyper, Header, Status};
+
+pub(crate) type Handler = Box<dyn FnOnce(Upgraded) -> BoxFuture<'static, ()> + Send>;
+
+/// Where the IO of an upgraded connection comes from.
+enum Source {
+    /// A connection served by hyper, available once the response is sent.
+    Hyper(hyper::OnUpgrade),
+    /// The server's end of a local connection.
+    Local(Upgraded),
+}
+
+/// The pending upgrade of the connection a request arrived on, and the handler
+/// registered to take over the connection once it is upgraded.
+pub(crate) struct PendingUpgrade {
+    source: Mutex<Option<Source>>,
+    handler: Mutex<Option<Handler>>,
+}
+
+impl PendingUpgrade {
+    pub(crate) fn new(on_upgrade: hyper::OnUpgrade) -> PendingUpgrade {
+        PendingUpgrade {
+            source: Mutex::new(Some(Source::Hyper(on_upgrade))),
+            handler: Mutex::new(None),
+        }
+    }
+
+    /// A pending upgrade of a local connection whose server end is `io`.
+    pub(crate) fn local(io: Upgraded) -> PendingUpgrade {
+        PendingUpgrade {
+            source: Mutex::new(Some(Source::Local(io))),
+            handler: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn set_handler(&self, handler: Handler) {
+        *self.handler.lock().expect("upgrade handler lock") = Some(handler);
+    }
+
+    /// Waits for the connection to be upgraded, then runs the registered
+    /// handler, if any, on the upgraded connection.
+    pub(crate) async fn finish(&self) {
+        let handler = self.handler.lock().expect("upgrade handler lock").take();
+        let source = self.source.lock().expect("upgrade lock").take();
+        match (handler, source) {
+            (Some(handler), Some(Source::Hyper(on_upgrade))) => match on_upgrade.await {
+                Ok(io) => handler(Upgraded(Inner::Hyper(io))).await,
+                Err(e) => error_!("Failed to upgrade connection: {}.", e),
+            },
+            (Some(handler), Some(Source::Local(io))) => handler(io).await,
+            _ => { /* nothing to upgrade or no one to hand it to */ }
+        }
+    }
+}
+
+/// The raw IO of an upgraded connection.
+///
+/// An `Upgraded` is passed to the handler registered with
+/// [`Request::upgrade()`] once the `101 Switching Protocols` response has been
+/// sent. It implements [`AsyncRead`] and [`AsyncWrite`], reading any bytes the
+/// client sent after the request before reading from the connection itself.
+///
+/// The client's end of an upgraded local connection, as returned by
+/// [`LocalResponse::upgraded()`](crate::local::asynchronous::LocalResponse::upgraded()),
+/// is also an `Upgraded`.
+pub struct Upgraded(Inner);
+
+enum Inner {
+    Hyper(hyper::Upgraded),
+    Local(Pipe),
+}
+
+impl Upgraded {
+    /// Returns the two ends of a new, in-memory connection.
+    pub(crate) fn local_pair() -> (Upgraded, Upgraded) {
+        let (a, b) = (Arc::new(Mutex::new(Buffer::default())), Arc::default());
+        let first = Pipe { read: a.clone(), write: b.clone() };
+        let second = Pipe { read: b, write: a };
+        (Upgraded(Inner::Local(first)), Upgraded(Inner::Local(second)))
+    }
+}
+
+impl AsyncRead for Upgraded {
+    #[inline(always)]
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        match self.0 {
+            Inner::Hyper(ref mut io) => Pin::new(io).poll_read(cx, buf),
+            Inner::Local(ref mut io) => io.poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    #[inline(always)]
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8]
+    ) -> Poll<io::Result<usize>> {
+        match self.0 {
+            Inner::Hyper(ref mut io) => Pin::new(io).poll_write(cx, buf),
+            Inner::Local(ref mut io) => io.poll_write(cx, buf),
+        }
+    }
+
+    #[inline(always)]
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.0 {
+            Inner::Hyper(ref mut io) => Pin::new(io).poll_flush(cx),
+            Inner::Local(_) => Poll::Ready(Ok(())),
+        }
+    }
+
+    #[inline(always)]
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.0 {
+            Inner::Hyper(ref mut io) => Pin::new(io).poll_shutdown(cx),
+            Inner::Local(ref mut io) => Poll::Ready(Ok(io.shutdown())),
+        }
+    }
+}
+
+/// The number of bytes written to one end of a local connection that may be
+/// buffered before writes wait for the other end to read.
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// The bytes written to one end of a local connection, not yet read from the
+/// other.
+#[derive(Default)]
+struct Buffer {
+    data: VecDeque<u8>,
+    closed: bool,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+}
+
+impl Buffer {
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// One end of a bounded, in-memory connection.
+struct Pipe {
+    read: Arc<Mutex<Buffer>>,
+    write: Arc<Mutex<Buffer>>,
+}
+
+impl Pipe {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buffer = self.read.lock().expect("pipe lock");
+        if buffer.data.is_empty() && !buffer.closed {
+            buffer.reader = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let n = std::cmp::min(buf.len(), buffer.data.len());
+        for (dst, src) in buf.iter_mut().zip(buffer.data.drain(..n)) {
+            *dst = src;
+        }
+
+        if let Some(waker) = buffer.writer.take() {
+            waker.wake();
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let mut buffer = self.write.lock().expect("pipe lock");
+        if buffer.closed {
+            let error = io::Error::new(io::ErrorKind::BrokenPipe, "connection closed");
+            return Poll::Ready(Err(error));
+        }
+
+        let available = PIPE_CAPACITY - buffer.data.len();
+        if available == 0 && !buf.is_empty() {
+            buffer.writer = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let n = std::cmp::min(buf.len(), available);
+        buffer.data.extend(&buf[..n]);
+        if let Some(waker) = buffer.reader.take() {
+            waker.wake();
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn shutdown(&mut self) {
+        self.write.lock().expect("pipe lock").close();
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        self.write.lock().expect("pipe lock").close();
+        self.read.lock().expect("pipe lock").close();
+    }
+}
+
+impl std::fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Upgraded").finish()
+    }
+}
+
+/// A `101 Switching Protocols` response accepting an upgrade.
+///
+/// An `Upgrade` is returned by [`Request::upgrade()`]. It responds with the
+/// `Connection: upgrade` and `Upgrade` headers naming the accepted protocol,
+/// along with any headers added via [`Upgrade::header()`], such as those a
+/// protocol's handshake requires.
+#[derive(Debug, Clone)]
+pub struct Upgrade {
+    protocol: String,
+    headers: Vec<Header<'static>>,
+}
+
+impl Upgrade {
+    /// Adds `header` to the response.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::Request;
+    /// use rocket::http::Header;
+    /// use rocket::request::Upgrade;
+    ///
+    /// fn accept(req: &Request<'_>, key: &str) -> Option<Upgrade> {
+    ///     let upgrade = req.upgrade("websocket", |_io| async move { /* .. */ })?;
+    ///     Some(upgrade.header(Header::new("Sec-WebSocket-Accept", key.to_string())))
+    /// }
+    /// ```
+    pub fn header<H: Into<Header<'static>>>(mut self, header: H) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for Upgrade {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = Response::build()
+            .status(Status::SwitchingProtocols)
+            .raw_header("Connection", "upgrade")
+            .raw_header("Upgrade", self.protocol)
+            .finalize();
+
+        for header in self.headers {
+            response.adjoin_header(header);
+        }
+
+        Ok(response)
+    }
+}
+
+/// Returns `true` if the comma-separated header values `values` contain a
+/// token matching `token`, ignoring case and any protocol version.
+fn has_token<'a>(values: impl Iterator<Item = &'a str>, token: &str) -> bool {
+    values.flat_map(|value| value.split(','))
+        .map(|item| item.trim())
+        .any(|item| {
+            item.eq_ignore_ascii_case(token)
+                || item.split('/').next().map_or(false, |name| name.eq_ignore_ascii_case(token))
+        })
+}
+
+/// Registers `handler` to take over the connection `req` arrived on if the
+/// request asks to upgrade to `protocol` and its connection can be upgraded.
+pub(crate) fn accept(req: &Request<'_>, protocol: &str, handler: Handler) -> Option<Upgrade> {
+    let pending = req.state.upgrade.as_ref()?;
+    if !has_token(req.headers().get("Connection"), "upgrade")
+        || !has_token(req.headers().get("Upgrade"), protocol)
+    {
+        return None;
+    }
+
+    pending.set_handler(handler);
+    Some(Upgrade { protocol: protocol.to_string(), headers: vec![] })
+}
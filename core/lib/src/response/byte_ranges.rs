@@ -0,0 +1,360 @@
+use std::{io, fmt};
+use std::pin::Pin;
+use std::path::Path;
+use std::io::{Cursor, SeekFrom};
+use std::task::{Poll, Context};
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use futures::ready;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncSeek};
+
+use crate::request::Request;
+use crate::response::{self, Response, Responder, NamedFile, DEFAULT_CHUNK_SIZE};
+use crate::http::{Status, ContentType, Header};
+
+/// The maximum number of ranges honored in a single request. Requests for more
+/// ranges are answered with the full representation.
+const MAX_RANGES: usize = 32;
+
+/// Responds to `Range` requests from a seekable source of known length.
+///
+/// `ByteRanges` inspects the `Range` header of the incoming request and
+/// responds as follows:
+///
+///   * **No `Range` header, an invalid header, or a non-`bytes` unit:**
+///     `200 OK` with the entire source as a sized body.
+///   * **A single satisfiable range:** `206 Partial Content` with a
+///     `Content-Range` header and only the requested bytes as the body.
+///   * **Multiple satisfiable ranges:** `206 Partial Content` with a
+///     `multipart/byteranges` body. Each part carries its own `Content-Type`
+///     and `Content-Range` headers. Overlapping and adjacent ranges are
+///     coalesced before the response is generated.
+///   * **No satisfiable ranges:** `416 Range Not Satisfiable` with a
+///     `Content-Range: bytes */{len}` header.
+///
+/// In every case, an `Accept-Ranges: bytes` header is set. Requests for more
+/// than 32 ranges are answered with the full representation.
+///
+/// Partial responses are streamed directly from the source: only the requested
+/// ranges are ever read.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use std::io;
+///
+/// use rocket::response::ByteRanges;
+/// use tokio::fs::File;
+///
+/// #[get("/video")]
+/// async fn video() -> io::Result<ByteRanges<File>> {
+///     ByteRanges::open("static/video.mp4").await
+/// }
+/// ```
+pub struct ByteRanges<B> {
+    source: B,
+    len: u64,
+    content_type: Option<ContentType>,
+}
+
+impl<B: AsyncRead + AsyncSeek + Send + Unpin> ByteRanges<B> {
+    /// Creates a new `ByteRanges` responder serving ranges of `source`, which
+    /// must be exactly `len` bytes long. The source is read starting at
+    /// position `0`, irrespective of its current position.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    /// use rocket::response::ByteRanges;
+    ///
+    /// let data = b"Hello, world!";
+    /// # #[allow(unused_variables)]
+    /// let ranges = ByteRanges::new(Cursor::new(data), data.len() as u64);
+    /// ```
+    pub fn new(source: B, len: u64) -> Self {
+        ByteRanges { source, len, content_type: None }
+    }
+
+    /// Sets the `Content-Type` of the full representation to `content_type`.
+    /// For multipart responses, this is the `Content-Type` of each part.
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+}
+
+impl ByteRanges<File> {
+    /// Opens the file at `path` for serving ranges. The `Content-Type` is set
+    /// according to the file's extension, as with [`NamedFile`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or its metadata cannot be
+    /// read.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        ByteRanges::from_file(NamedFile::open(path).await?).await
+    }
+
+    /// Serves ranges of the already opened `file`. The `Content-Type` is set
+    /// according to the file's extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file's metadata cannot be read.
+    pub async fn from_file(file: NamedFile) -> io::Result<Self> {
+        let content_type = file.path().extension()
+            .and_then(|ext| ContentType::from_extension(&ext.to_string_lossy()));
+
+        let len = file.metadata().await?.len();
+        Ok(ByteRanges { source: file.take_file(), len, content_type })
+    }
+}
+
+impl<B> fmt::Debug for ByteRanges<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteRanges")
+            .field("len", &self.len)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+/// The outcome of interpreting a `Range` header against a representation.
+#[derive(Debug, PartialEq)]
+enum Ranges {
+    /// The header should be ignored: respond with the full representation.
+    Full,
+    /// No range in the header is satisfiable.
+    Unsatisfiable,
+    /// The satisfiable ranges, as inclusive `(start, end)` byte positions.
+    Satisfiable(Vec<(u64, u64)>),
+}
+
+/// Interprets the value of a `Range` header for a representation of `len`
+/// bytes per RFC 7233.
+fn parse_ranges(header: &str, len: u64) -> Ranges {
+    let specs = match header.trim().strip_prefix("bytes=") {
+        Some(specs) => specs,
+        None => return Ranges::Full,
+    };
+
+    let mut ranges = vec![];
+    for spec in specs.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let (first, last) = match spec.find('-') {
+            Some(i) => (spec[..i].trim(), spec[(i + 1)..].trim()),
+            None => return Ranges::Full,
+        };
+
+        let range = match (first.parse::<u64>().ok(), last.parse::<u64>().ok()) {
+            (Some(start), _) if start >= len => None,
+            (Some(start), None) if last.is_empty() => Some((start, len - 1)),
+            (Some(start), Some(end)) if start <= end => Some((start, std::cmp::min(end, len - 1))),
+            (None, Some(suffix)) if first.is_empty() => match suffix {
+                0 => None,
+                n => Some((len - std::cmp::min(n, len), len - 1)),
+            },
+            _ => return Ranges::Full,
+        };
+
+        ranges.extend(range);
+    }
+
+    if ranges.len() > MAX_RANGES {
+        return Ranges::Full;
+    } else if ranges.is_empty() {
+        return Ranges::Unsatisfiable;
+    }
+
+    // Coalesce overlapping and adjacent ranges.
+    ranges.sort();
+    let mut coalesced: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match coalesced.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = std::cmp::max(last.1, end),
+            _ => coalesced.push((start, end)),
+        }
+    }
+
+    Ranges::Satisfiable(coalesced)
+}
+
+/// One part of a partial response: a (possibly empty) preamble followed by the
+/// bytes `start..(start + len)` of the source.
+struct Part {
+    preamble: Cursor<Vec<u8>>,
+    start: u64,
+    len: u64,
+}
+
+enum ReadState {
+    Preamble,
+    Seek,
+    Seeking,
+    Body(u64),
+}
+
+/// An `AsyncRead` that emits a sequence of `Part`s from `source` followed by
+/// `trailer`.
+struct PartsReader<B> {
+    source: B,
+    parts: VecDeque<Part>,
+    trailer: Cursor<Vec<u8>>,
+    state: ReadState,
+}
+
+impl<B: AsyncRead + AsyncSeek + Unpin> AsyncRead for PartsReader<B> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        loop {
+            let part = match this.parts.front_mut() {
+                Some(part) => part,
+                None => return Pin::new(&mut this.trailer).poll_read(cx, buf),
+            };
+
+            match this.state {
+                ReadState::Preamble => {
+                    match ready!(Pin::new(&mut part.preamble).poll_read(cx, buf))? {
+                        0 => this.state = ReadState::Seek,
+                        n => return Poll::Ready(Ok(n)),
+                    }
+                }
+                ReadState::Seek => {
+                    let start = SeekFrom::Start(part.start);
+                    ready!(Pin::new(&mut this.source).start_seek(cx, start))?;
+                    this.state = ReadState::Seeking;
+                }
+                ReadState::Seeking => {
+                    ready!(Pin::new(&mut this.source).poll_complete(cx))?;
+                    this.state = ReadState::Body(part.len);
+                }
+                ReadState::Body(0) => {
+                    this.parts.pop_front();
+                    this.state = ReadState::Preamble;
+                }
+                ReadState::Body(remaining) => {
+                    let max = std::cmp::min(remaining, buf.len() as u64) as usize;
+                    let n = ready!(Pin::new(&mut this.source).poll_read(cx, &mut buf[..max]))?;
+                    if n == 0 {
+                        let msg = "range source ended before its declared length";
+                        return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg)));
+                    }
+
+                    this.state = ReadState::Body(remaining - n as u64);
+                    return Poll::Ready(Ok(n));
+                }
+            }
+        }
+    }
+}
+
+impl<'r, 'o: 'r, B> Responder<'r, 'o> for ByteRanges<B>
+    where B: AsyncRead + AsyncSeek + Send + Unpin + 'o
+{
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let ByteRanges { source, len, content_type } = self;
+        let mut response = Response::build();
+        response.raw_header("Accept-Ranges", "bytes");
+
+        let ranges = match req.headers().get_one("Range") {
+            Some(header) if len > 0 => parse_ranges(header, len),
+            Some(_) => Ranges::Unsatisfiable,
+            None => Ranges::Full,
+        };
+
+        match ranges {
+            Ranges::Full => {
+                if let Some(ct) = content_type {
+                    response.header(ct);
+                }
+
+                response.sized_body(len as usize, source);
+            }
+            Ranges::Unsatisfiable => {
+                response.status(Status::RangeNotSatisfiable)
+                    .raw_header("Content-Range", format!("bytes */{}", len));
+            }
+            Ranges::Satisfiable(ranges) if ranges.len() == 1 => {
+                let (start, end) = ranges[0];
+                if let Some(ct) = content_type {
+                    response.header(ct);
+                }
+
+                let part = Part { preamble: Cursor::new(vec![]), start, len: end - start + 1 };
+                let reader = PartsReader {
+                    source,
+                    parts: vec![part].into(),
+                    trailer: Cursor::new(vec![]),
+                    state: ReadState::Preamble,
+                };
+
+                response.status(Status::PartialContent)
+                    .raw_header("Content-Range", format!("bytes {}-{}/{}", start, end, len))
+                    .chunked_body(reader, DEFAULT_CHUNK_SIZE);
+            }
+            Ranges::Satisfiable(ranges) => {
+                let mut hasher = DefaultHasher::default();
+                std::time::SystemTime::now().hash(&mut hasher);
+                (len, &ranges).hash(&mut hasher);
+                let boundary = format!("rocket-byteranges-{:016x}", hasher.finish());
+
+                let parts = ranges.iter().enumerate().map(|(i, &(start, end))| {
+                    let mut preamble = String::new();
+                    if i != 0 { preamble.push_str("\r\n"); }
+                    preamble.push_str(&format!("--{}\r\n", boundary));
+                    if let Some(ref ct) = content_type {
+                        preamble.push_str(&format!("Content-Type: {}\r\n", ct));
+                    }
+
+                    preamble.push_str(&format!("Content-Range: bytes {}-{}/{}\r\n\r\n", start, end, len));
+                    Part { preamble: Cursor::new(preamble.into_bytes()), start, len: end - start + 1 }
+                });
+
+                let reader = PartsReader {
+                    source,
+                    parts: parts.collect(),
+                    trailer: Cursor::new(format!("\r\n--{}--\r\n", boundary).into_bytes()),
+                    state: ReadState::Preamble,
+                };
+
+                let ct = format!("multipart/byteranges; boundary={}", boundary);
+                response.status(Status::PartialContent)
+                    .header(Header::new("Content-Type", ct))
+                    .chunked_body(reader, DEFAULT_CHUNK_SIZE);
+            }
+        }
+
+        response.ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ranges, Ranges::*};
+
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(parse_ranges("bytes=0-4", 10), Satisfiable(vec![(0, 4)]));
+        assert_eq!(parse_ranges("bytes=5-", 10), Satisfiable(vec![(5, 9)]));
+        assert_eq!(parse_ranges("bytes=-3", 10), Satisfiable(vec![(7, 9)]));
+        assert_eq!(parse_ranges("bytes=-30", 10), Satisfiable(vec![(0, 9)]));
+        assert_eq!(parse_ranges("bytes=0-100", 10), Satisfiable(vec![(0, 9)]));
+        assert_eq!(parse_ranges("bytes=0-1, 6-7", 10), Satisfiable(vec![(0, 1), (6, 7)]));
+        assert_eq!(parse_ranges("bytes=6-7,0-1", 10), Satisfiable(vec![(0, 1), (6, 7)]));
+        assert_eq!(parse_ranges("bytes=0-3,2-5,6-7", 10), Satisfiable(vec![(0, 7)]));
+        assert_eq!(parse_ranges("bytes=20-30", 10), Unsatisfiable);
+        assert_eq!(parse_ranges("bytes=-0", 10), Unsatisfiable);
+        assert_eq!(parse_ranges("bytes=20-30, 1-2", 10), Satisfiable(vec![(1, 2)]));
+        assert_eq!(parse_ranges("items=0-4", 10), Full);
+        assert_eq!(parse_ranges("bytes=4-2", 10), Full);
+        assert_eq!(parse_ranges("bytes=abc", 10), Full);
+    }
+}
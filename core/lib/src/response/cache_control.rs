@@ -0,0 +1,356 @@
+use std::fmt;
+use std::borrow::Cow;
+
+use time::{Duration, OffsetDateTime};
+
+use crate::request::Request;
+use crate::response::{self, Responder};
+use crate::http::Header;
+use crate::http::uncased::UncasedStr;
+
+/// The format of an HTTP-date as specified in RFC 7231 §7.1.1.1.
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// The value of the `Expires` header for responses that must not be cached.
+const EXPIRED_DATE: &str = "Thu, 01 Jan 1970 00:00:00 GMT";
+
+/// Who may store a response: the `public` or `private` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visibility {
+    Public,
+    Private,
+}
+
+/// A typed builder for the `Cache-Control` header.
+///
+/// A `CacheControl` is a set of caching directives. It is typically applied to
+/// a response via the [`Cached`] responder, which additionally sets `Expires`
+/// and `Vary` so that all three headers agree with one another. It can also be
+/// converted directly into a [`Header`] for use with [`Response::set_header()`].
+///
+/// Builder methods are chainable and can be called in any order. Directives
+/// that contradict each other are resolved as follows:
+///
+///   * `public` and `private` are mutually exclusive; the last one set wins.
+///   * `no-store` suppresses every freshness directive (`max-age`, `s-maxage`,
+///     `immutable`, `stale-while-revalidate`, and `stale-if-error`).
+///
+/// [`Response::set_header()`]: crate::response::Response::set_header()
+///
+/// # Example
+///
+/// Cache a fingerprinted asset in any cache for a year:
+///
+/// ```rust
+/// use rocket::response::CacheControl;
+/// use rocket::http::Header;
+/// use time::Duration;
+///
+/// let cache = CacheControl::new()
+///     .public()
+///     .max_age(Duration::days(365))
+///     .immutable();
+///
+/// let header: Header<'static> = cache.into();
+/// assert_eq!(header.value(), "public, max-age=31536000, immutable");
+/// ```
+///
+/// Forbid caching entirely:
+///
+/// ```rust
+/// use rocket::response::CacheControl;
+/// use time::Duration;
+///
+/// let cache = CacheControl::new().max_age(Duration::hours(1)).no_store();
+/// assert_eq!(cache.to_string(), "no-store");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheControl {
+    visibility: Option<Visibility>,
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    must_revalidate: bool,
+    proxy_revalidate: bool,
+    immutable: bool,
+    max_age: Option<Duration>,
+    s_max_age: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+    vary: Vec<Cow<'static, str>>,
+}
+
+impl CacheControl {
+    /// Returns a new `CacheControl` with no directives set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::CacheControl;
+    ///
+    /// let cache = CacheControl::new();
+    /// assert_eq!(cache.to_string(), "");
+    /// ```
+    pub fn new() -> CacheControl {
+        CacheControl::default()
+    }
+
+    /// Sets the `public` directive: the response may be stored by any cache.
+    /// Overrides a previous call to [`CacheControl::private()`].
+    pub fn public(mut self) -> Self {
+        self.visibility = Some(Visibility::Public);
+        self
+    }
+
+    /// Sets the `private` directive: the response may only be stored by the
+    /// client's own cache. Overrides a previous call to
+    /// [`CacheControl::public()`].
+    pub fn private(mut self) -> Self {
+        self.visibility = Some(Visibility::Private);
+        self
+    }
+
+    /// Sets the `no-cache` directive: caches must revalidate the response with
+    /// the server before each reuse.
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Sets the `no-store` directive: the response must not be stored at all.
+    /// Freshness directives are omitted when this directive is set.
+    pub fn no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
+    /// Sets the `no-transform` directive: intermediaries must not modify the
+    /// response body.
+    pub fn no_transform(mut self) -> Self {
+        self.no_transform = true;
+        self
+    }
+
+    /// Sets the `must-revalidate` directive: stale responses must not be used
+    /// without successful revalidation.
+    pub fn must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    /// Sets the `proxy-revalidate` directive: like `must-revalidate`, but only
+    /// for shared caches.
+    pub fn proxy_revalidate(mut self) -> Self {
+        self.proxy_revalidate = true;
+        self
+    }
+
+    /// Sets the `immutable` directive: the response will not change while it
+    /// is fresh, so clients need not revalidate it on reload.
+    pub fn immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+
+    /// Sets the `max-age` directive to `age`. Negative durations are treated
+    /// as zero. This also determines the value of the `Expires` header set by
+    /// [`Cached`].
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Sets the `s-maxage` directive, the maximum age for shared caches, to
+    /// `age`. Negative durations are treated as zero.
+    pub fn s_max_age(mut self, age: Duration) -> Self {
+        self.s_max_age = Some(age);
+        self
+    }
+
+    /// Sets the `stale-while-revalidate` directive to `duration`.
+    pub fn stale_while_revalidate(mut self, duration: Duration) -> Self {
+        self.stale_while_revalidate = Some(duration);
+        self
+    }
+
+    /// Sets the `stale-if-error` directive to `duration`.
+    pub fn stale_if_error(mut self, duration: Duration) -> Self {
+        self.stale_if_error = Some(duration);
+        self
+    }
+
+    /// Adds `header` to the list of request headers that select between
+    /// representations of the response. [`Cached`] emits these in the `Vary`
+    /// header, merged with any `Vary` values set by the wrapped responder.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::CacheControl;
+    ///
+    /// let cache = CacheControl::new()
+    ///     .private()
+    ///     .vary("Accept-Encoding")
+    ///     .vary("Cookie");
+    /// ```
+    pub fn vary<H: Into<Cow<'static, str>>>(mut self, header: H) -> Self {
+        let header = header.into();
+        if !self.vary.iter().any(|h| UncasedStr::new(h) == UncasedStr::new(&header)) {
+            self.vary.push(header);
+        }
+
+        self
+    }
+
+    /// Returns `true` if the directives forbid reuse of the response without
+    /// contacting the server: `no-store` or `no-cache` is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::CacheControl;
+    ///
+    /// assert!(CacheControl::new().no_cache().is_uncacheable());
+    /// assert!(!CacheControl::new().public().is_uncacheable());
+    /// ```
+    pub fn is_uncacheable(&self) -> bool {
+        self.no_store || self.no_cache
+    }
+
+    /// Returns the value for the `Expires` header corresponding to these
+    /// directives, computed relative to `now`, if there is one.
+    fn expires(&self, now: OffsetDateTime) -> Option<Cow<'static, str>> {
+        if self.is_uncacheable() {
+            return Some(EXPIRED_DATE.into());
+        }
+
+        self.max_age.map(|age| {
+            let age = if age.is_negative() { Duration::zero() } else { age };
+            (now + age).format(HTTP_DATE_FORMAT).into()
+        })
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn seconds(d: Duration) -> i64 {
+            std::cmp::max(d.whole_seconds(), 0)
+        }
+
+        let mut directives: Vec<Cow<'static, str>> = vec![];
+        match self.visibility {
+            Some(Visibility::Public) => directives.push("public".into()),
+            Some(Visibility::Private) => directives.push("private".into()),
+            None => { /* no visibility directive */ }
+        }
+
+        if self.no_cache { directives.push("no-cache".into()); }
+        if self.no_store { directives.push("no-store".into()); }
+        if self.no_transform { directives.push("no-transform".into()); }
+        if self.must_revalidate { directives.push("must-revalidate".into()); }
+        if self.proxy_revalidate { directives.push("proxy-revalidate".into()); }
+
+        if !self.no_store {
+            if let Some(age) = self.max_age {
+                directives.push(format!("max-age={}", seconds(age)).into());
+            }
+
+            if let Some(age) = self.s_max_age {
+                directives.push(format!("s-maxage={}", seconds(age)).into());
+            }
+
+            if let Some(d) = self.stale_while_revalidate {
+                directives.push(format!("stale-while-revalidate={}", seconds(d)).into());
+            }
+
+            if let Some(d) = self.stale_if_error {
+                directives.push(format!("stale-if-error={}", seconds(d)).into());
+            }
+
+            if self.immutable { directives.push("immutable".into()); }
+        }
+
+        write!(f, "{}", directives.join(", "))
+    }
+}
+
+impl From<&CacheControl> for Header<'static> {
+    fn from(cache: &CacheControl) -> Self {
+        Header::new("Cache-Control", cache.to_string())
+    }
+}
+
+impl From<CacheControl> for Header<'static> {
+    fn from(cache: CacheControl) -> Self {
+        Header::from(&cache)
+    }
+}
+
+/// Sets the `Cache-Control`, `Expires`, and `Vary` headers of a response.
+///
+/// The wrapped responder `R` generates the response. `Cached` then overrides
+/// `Cache-Control` with the [`CacheControl`] directives and sets `Expires` to
+/// agree with them:
+///
+///   * If `no-store` or `no-cache` is set, `Expires` is set to a date in the
+///     past so that HTTP/1.0 caches do not store the response either.
+///   * Otherwise, if `max-age` is set, `Expires` is set to the current time
+///     plus the `max-age`.
+///   * Otherwise, any `Expires` header set by `R` is left untouched.
+///
+/// Headers named via [`CacheControl::vary()`] are merged with any `Vary`
+/// values already present in the response; duplicates are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::response::{Cached, CacheControl};
+/// use rocket::response::content::Css;
+/// use time::Duration;
+///
+/// #[get("/style.css")]
+/// fn style() -> Cached<Css<&'static str>> {
+///     let cache = CacheControl::new().public().max_age(Duration::days(1));
+///     Cached(cache, Css("body { color: red; }"))
+/// }
+///
+/// # use rocket::local::blocking::Client;
+/// # let client = Client::new(rocket::ignite().mount("/", routes![style])).unwrap();
+/// let response = client.get("/style.css").dispatch();
+/// assert_eq!(response.headers().get_one("Cache-Control"), Some("public, max-age=86400"));
+/// assert!(response.headers().get_one("Expires").is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached<R>(pub CacheControl, pub R);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Cached<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let Cached(cache, responder) = self;
+        let mut response = responder.respond_to(req)?;
+        response.set_header(&cache);
+
+        if let Some(expires) = cache.expires(OffsetDateTime::now_utc()) {
+            response.set_header(Header::new("Expires", expires));
+        }
+
+        if !cache.vary.is_empty() {
+            let mut vary: Vec<String> = response.headers().get("Vary")
+                .flat_map(|v| v.split(','))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+                .collect();
+
+            for name in &cache.vary {
+                if !vary.iter().any(|v| UncasedStr::new(v) == UncasedStr::new(name)) {
+                    vary.push(name.to_string());
+                }
+            }
+
+            response.set_header(Header::new("Vary", vary.join(", ")));
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,180 @@
+use std::pin::Pin;
+
+use async_compression::tokio_02::bufread::GzipEncoder;
+#[cfg(feature = "brotli")]
+use async_compression::{Level, tokio_02::bufread::BrotliEncoder};
+use tokio::io::{AsyncRead, BufReader};
+
+use crate::Request;
+use crate::config::CompressionConfig;
+use crate::response::{Body, Response, DEFAULT_CHUNK_SIZE};
+
+/// The content encodings Rocket compresses responses with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    /// Wraps `reader` in an encoder that compresses it as it is read.
+    fn encode<'r, R>(self, reader: R) -> Pin<Box<dyn AsyncRead + Send + 'r>>
+        where R: AsyncRead + Send + 'r
+    {
+        let reader = BufReader::new(reader);
+        match self {
+            // The default quality of 11 is far too slow to compress on the fly.
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Box::pin(BrotliEncoder::with_quality(reader, Level::Precise(4))),
+            #[cfg(not(feature = "brotli"))]
+            Encoding::Brotli => unreachable!("brotli is only negotiated with the brotli feature"),
+            Encoding::Gzip => Box::pin(GzipEncoder::new(reader)),
+        }
+    }
+}
+
+/// Returns the encoding the client prefers according to the `Accept-Encoding`
+/// header values `values`, or `None` if it accepts neither encoding. Brotli,
+/// if `brotli` allows it, is preferred over gzip when the client has no
+/// preference.
+fn negotiate<'a>(values: impl Iterator<Item = &'a str>, brotli: bool) -> Option<Encoding> {
+    let (mut br, mut gzip, mut any) = (None, None, None);
+    for item in values.flat_map(|value| value.split(',')) {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or("").trim();
+        let quality = params.map(|param| param.trim())
+            .find_map(|param| param.strip_prefix("q=").or_else(|| param.strip_prefix("Q=")))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok());
+
+        let quality = match quality {
+            Some(quality) => quality,
+            None => continue,
+        };
+
+        if coding.eq_ignore_ascii_case("br") {
+            br = Some(quality);
+        } else if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+            gzip = Some(quality);
+        } else if coding == "*" {
+            any = Some(quality);
+        }
+    }
+
+    let br = if brotli { br.or(any).unwrap_or(0.0) } else { 0.0 };
+    let gzip = gzip.or(any).unwrap_or(0.0);
+    if br > 0.0 && br >= gzip {
+        Some(Encoding::Brotli)
+    } else if gzip > 0.0 {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Whether `response` may be compressed according to `config`.
+async fn compressible(config: &CompressionConfig, response: &mut Response<'_>) -> bool {
+    let code = response.status().code;
+    if code < 200 || code == 204 || code == 206 || code == 304 {
+        return false;
+    }
+
+    let headers = response.headers();
+    let no_transform = headers.get("Cache-Control")
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"));
+
+    if no_transform || headers.contains("Content-Encoding") || headers.contains("Content-Range") {
+        return false;
+    }
+
+    if response.content_type().map_or(false, |ct| config.excludes(ct.media_type())) {
+        return false;
+    }
+
+    match response.body_mut() {
+        Some(body) => body.size().await.map_or(true, |size| config.min_size <= size),
+        None => false,
+    }
+}
+
+/// Compresses the body of `response` with the encoding negotiated with the
+/// client of `request`, if compression is enabled and the response is
+/// compressible. The compressed body is streamed, without a `Content-Length`.
+pub(crate) async fn compress<'r>(
+    config: &CompressionConfig,
+    request: &'r Request<'_>,
+    response: &mut Response<'r>
+) {
+    if !config.enabled || !compressible(config, response).await {
+        return;
+    }
+
+    // The response differs by `Accept-Encoding` whether it's compressed or not.
+    let varies = response.headers().get("Vary")
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim())
+        .any(|name| name == "*" || name.eq_ignore_ascii_case("Accept-Encoding"));
+
+    if !varies {
+        response.adjoin_raw_header("Vary", "Accept-Encoding");
+    }
+
+    let brotli = config.brotli && cfg!(feature = "brotli");
+    let encoding = match negotiate(request.headers().get("Accept-Encoding"), brotli) {
+        Some(encoding) => encoding,
+        None => return,
+    };
+
+    let (encoded, chunk_size) = match response.take_body() {
+        Some(Body::Sized(reader, _)) => (encoding.encode(reader), DEFAULT_CHUNK_SIZE),
+        Some(Body::Chunked(reader, chunk_size)) => (encoding.encode(reader), chunk_size),
+        None => return,
+    };
+
+    response.set_chunked_body(encoded, chunk_size);
+    response.set_raw_header("Content-Encoding", encoding.as_str());
+    response.remove_header("Content-Length");
+    response.remove_header("Accept-Ranges");
+
+    // The compressed bytes differ from the original ones, so a strong entity
+    // tag no longer matches them byte for byte. Weak comparison, as used by
+    // `If-None-Match`, still matches the weakened tag.
+    let etag = response.headers().get_one("ETag").map(|tag| tag.to_string());
+    if let Some(etag) = etag.filter(|tag| tag.starts_with('"')) {
+        response.set_raw_header("ETag", format!("W/{}", etag));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{negotiate, Encoding};
+
+    fn negotiated(accept: &str) -> Option<Encoding> {
+        negotiate(std::iter::once(accept), true)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiated("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiated("gzip, deflate"), Some(Encoding::Gzip));
+        assert_eq!(negotiated("br;q=0.5, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiated("br;q=0, gzip;q=0"), None);
+        assert_eq!(negotiated("*"), Some(Encoding::Brotli));
+        assert_eq!(negotiated("*;q=0.1, br;q=0"), Some(Encoding::Gzip));
+        assert_eq!(negotiated("identity"), None);
+        assert_eq!(negotiated(""), None);
+        assert_eq!(negotiated("GZIP;Q=1"), Some(Encoding::Gzip));
+
+        let without_brotli = |accept: &str| negotiate(std::iter::once(accept), false);
+        assert_eq!(without_brotli("br, gzip;q=0.5"), Some(Encoding::Gzip));
+        assert_eq!(without_brotli("*"), Some(Encoding::Gzip));
+        assert_eq!(without_brotli("br"), None);
+    }
+}
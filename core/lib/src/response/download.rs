@@ -0,0 +1,162 @@
+use std::fmt::Write;
+use std::borrow::Cow;
+
+use crate::request::Request;
+use crate::response::{self, Responder};
+use crate::http::{Header, ContentType};
+
+/// Sets the `Content-Disposition` of a response so that it is downloaded as a
+/// file with a given name.
+///
+/// By default, the disposition is `attachment`, prompting the client to save
+/// the response instead of displaying it. Use [`Download::inline()`] to ask the
+/// client to display the response while still suggesting a file name for when
+/// it is saved.
+///
+/// # File Names
+///
+/// File names are emitted in two forms so that all clients receive the best
+/// name they can understand:
+///
+///   * A quoted `filename` parameter containing an ASCII-only approximation of
+///     the name: non-ASCII characters, control characters, `"` and `\` are
+///     replaced with `_`.
+///   * If the name contains any non-ASCII characters, an RFC 5987 encoded
+///     `filename*` parameter containing the exact UTF-8 name.
+///
+/// In both forms, any path components are stripped from the name: only the
+/// portion following the final `/` or `\` is used.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::response::Download;
+///
+/// #[get("/report")]
+/// fn report() -> Download<&'static str> {
+///     Download::new("résumé 2020.txt", "Hello, world!")
+/// }
+///
+/// # use rocket::local::blocking::Client;
+/// # let client = Client::new(rocket::ignite().mount("/", routes![report])).unwrap();
+/// let response = client.get("/report").dispatch();
+/// let disposition = response.headers().get_one("Content-Disposition");
+/// assert_eq!(disposition, Some("attachment; filename=\"r_sum_ 2020.txt\"; \
+///     filename*=UTF-8''r%C3%A9sum%C3%A9%202020.txt"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Download<R> {
+    name: Cow<'static, str>,
+    inline: bool,
+    content_type: Option<ContentType>,
+    responder: R,
+}
+
+impl<R> Download<R> {
+    /// Creates a new `Download` that responds with `responder` as an
+    /// `attachment` named `name`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Download;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let download = Download::new("hello.txt", "Hello, world!");
+    /// ```
+    pub fn new<N: Into<Cow<'static, str>>>(name: N, responder: R) -> Self {
+        Download { name: name.into(), inline: false, content_type: None, responder }
+    }
+
+    /// Sets the disposition to `inline`: the client should display the
+    /// response but use the file name if the user chooses to save it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Download;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let download = Download::new("chart.svg", "<svg></svg>").inline();
+    /// ```
+    pub fn inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    /// Overrides the `Content-Type` set by the wrapped responder with
+    /// `content_type`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Download;
+    /// use rocket::http::ContentType;
+    ///
+    /// # #[allow(unused_variables)]
+    /// let download = Download::new("data.csv", "a,b,c")
+    ///     .content_type(ContentType::CSV);
+    /// ```
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Returns the value of the `Content-Disposition` header for `self`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::response::Download;
+    ///
+    /// let download = Download::new("../../etc/say \"hi\".txt", ());
+    /// assert_eq!(download.disposition(), r#"attachment; filename="say _hi_.txt""#);
+    /// ```
+    pub fn disposition(&self) -> String {
+        let kind = if self.inline { "inline" } else { "attachment" };
+        let name = self.name.rsplit(|c| c == '/' || c == '\\').next().unwrap_or("");
+        if name.is_empty() {
+            return kind.into();
+        }
+
+        let fallback: String = name.chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if !c.is_ascii() || c.is_ascii_control() => '_',
+                c => c
+            })
+            .collect();
+
+        let mut value = format!("{}; filename=\"{}\"", kind, fallback);
+        if !name.is_ascii() {
+            value.push_str("; filename*=UTF-8''");
+            for byte in name.bytes() {
+                match byte {
+                    b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'
+                        | b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.'
+                        | b'^' | b'_' | b'`' | b'|' | b'~' => value.push(byte as char),
+                    _ => { let _ = write!(value, "%{:02X}", byte); }
+                }
+            }
+        }
+
+        value
+    }
+}
+
+/// Sets the `Content-Disposition` header, and the `Content-Type` if one was
+/// provided, then delegates the remainder of the response to the wrapped
+/// responder.
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Download<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let disposition = self.disposition();
+        let mut response = self.responder.respond_to(req)?;
+        response.set_header(Header::new("Content-Disposition", disposition));
+        if let Some(content_type) = self.content_type {
+            response.set_header(content_type);
+        }
+
+        Ok(response)
+    }
+}
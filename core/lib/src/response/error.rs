@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::request::Request;
+use crate::response::{self, Responder};
+use crate::catcher::TypedError;
+use crate::http::Status;
+
+use yansi::Paint;
+
+/// An application error that can be returned directly from handlers.
+///
+/// Implementing `ResponseError` for a type makes the type a [`Responder`], so
+/// that handlers anywhere in an application can return `Result<T, E>` without
+/// per-type `Responder` implementations. The error determines only the status
+/// of the response, via [`ResponseError::status()`]; the response itself is
+/// produced by the catcher registered for that status.
+///
+/// Because `ResponseError` requires [`TypedError`], the error is recorded on
+/// the request before the catcher runs. Catchers can thus render all of an
+/// application's errors uniformly by receiving them as [typed
+/// errors](crate::Catcher#typed-errors). Registering a single default catcher
+/// that receives the error is enough to map every error to a response.
+///
+/// # Responder
+///
+/// The `Responder` implementation logs the error via its `Display`
+/// implementation, records it on the request, and fails with the status
+/// returned by [`ResponseError::status()`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use std::fmt;
+///
+/// use rocket::catcher::TypedError;
+/// use rocket::response::ResponseError;
+/// use rocket::http::Status;
+///
+/// #[derive(Debug)]
+/// enum AppError {
+///     NotFound(String),
+///     Database,
+/// }
+///
+/// impl fmt::Display for AppError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         match self {
+///             AppError::NotFound(what) => write!(f, "{} not found", what),
+///             AppError::Database => write!(f, "database error"),
+///         }
+///     }
+/// }
+///
+/// impl TypedError for AppError { }
+///
+/// impl ResponseError for AppError {
+///     fn status(&self) -> Status {
+///         match self {
+///             AppError::NotFound(_) => Status::NotFound,
+///             AppError::Database => Status::InternalServerError,
+///         }
+///     }
+/// }
+///
+/// #[get("/user/<name>")]
+/// fn user(name: String) -> Result<String, AppError> {
+///     Err(AppError::NotFound(name))
+/// }
+///
+/// #[catch(default, error = "<e>")]
+/// fn app_error(status: Status, e: Option<&AppError>) -> String {
+///     match e {
+///         Some(e) => format!("{}: {}", status.code, e),
+///         None => format!("{}", status.code),
+///     }
+/// }
+///
+/// # use rocket::local::blocking::Client;
+/// let rocket = rocket::ignite()
+///     .mount("/", routes![user])
+///     .register(catchers![app_error]);
+///
+/// # let client = Client::new(rocket).unwrap();
+/// # let response = client.get("/user/bob").dispatch();
+/// # assert_eq!(response.status(), Status::NotFound);
+/// # assert_eq!(response.into_string().unwrap(), "404: bob not found");
+/// ```
+pub trait ResponseError: TypedError + fmt::Display {
+    /// Returns the status of the response to this error. The default
+    /// implementation returns `500 Internal Server Error`.
+    fn status(&self) -> Status {
+        Status::InternalServerError
+    }
+}
+
+/// Logs the error, records it on the request, and fails with the error's
+/// status.
+impl<'r, E: ResponseError> Responder<'r, 'static> for E {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = self.status();
+        warn_!("Handler failed with {}: {}", Paint::red(status), self);
+        req.set_error(self);
+        Err(status)
+    }
+}
@@ -29,6 +29,12 @@ mod named_file;
 mod stream;
 mod response;
 mod debug;
+mod cache_control;
+mod download;
+mod byte_ranges;
+mod error;
+#[cfg(feature = "compression")]
+mod compression;
 
 pub(crate) mod flash;
 
@@ -38,6 +44,8 @@ pub mod status;
 #[doc(hidden)] pub use rocket_codegen::Responder;
 
 pub use self::response::DEFAULT_CHUNK_SIZE;
+#[cfg(feature = "compression")]
+pub(crate) use self::compression::compress;
 pub use self::response::{Response, ResponseBody, ResponseBuilder, Body};
 pub use self::responder::Responder;
 pub use self::redirect::Redirect;
@@ -45,6 +53,10 @@ pub use self::flash::Flash;
 pub use self::named_file::NamedFile;
 pub use self::stream::Stream;
 pub use self::debug::Debug;
+pub use self::cache_control::{CacheControl, Cached};
+pub use self::download::Download;
+pub use self::byte_ranges::ByteRanges;
+pub use self::error::ResponseError;
 #[doc(inline)] pub use self::content::Content;
 
 /// Type alias for the `Result` of a [`Responder::respond_to()`] call.
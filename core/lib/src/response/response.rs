@@ -592,6 +592,46 @@ pub type ResponseBody<'r> = Body<
     Pin<Box<dyn AsyncRead + Send + 'r>>
 >;
 
+impl<'r> ResponseBody<'r> {
+    /// Returns a new fixed-size `ResponseBody` reading from `body` with size
+    /// `size`, which may be `None`. Useful when replacing a body in a
+    /// [response body fairing](crate::fairing::Fairing::on_response_body()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::io::Cursor;
+    /// use rocket::response::ResponseBody;
+    ///
+    /// let body = ResponseBody::sized(Cursor::new("Hello!"), 6);
+    /// assert_eq!(body.known_size(), Some(6));
+    /// ```
+    pub fn sized<B, S>(body: B, size: S) -> ResponseBody<'r>
+        where B: AsyncRead + AsyncSeek + Send + 'r, S: Into<Option<usize>>
+    {
+        Body::Sized(Box::pin(body), size.into())
+    }
+
+    /// Returns a new `ResponseBody` streaming from `body` in chunks of
+    /// [`DEFAULT_CHUNK_SIZE`]. Useful when wrapping a body in a [response body
+    /// fairing](crate::fairing::Fairing::on_response_body()).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tokio::io::{repeat, AsyncReadExt};
+    /// use rocket::response::ResponseBody;
+    ///
+    /// let body = ResponseBody::streamed(repeat(97).take(5));
+    /// assert!(body.is_chunked());
+    /// ```
+    pub fn streamed<B>(body: B) -> ResponseBody<'r>
+        where B: AsyncRead + Send + 'r
+    {
+        Body::Chunked(Box::pin(body), DEFAULT_CHUNK_SIZE)
+    }
+}
+
 /// A response, as returned by types implementing [`Responder`].
 #[derive(Default)]
 pub struct Response<'r> {
@@ -1040,6 +1080,13 @@ impl<'r> Response<'r> {
         self.body.take()
     }
 
+    /// Sets the body of `self` to `body`, as previously returned by
+    /// [`Response::take_body()`].
+    #[inline(always)]
+    pub(crate) fn set_body(&mut self, body: ResponseBody<'r>) {
+        self.body = Some(body);
+    }
+
     // Makes the `AsyncRead`er in the body empty but leaves the size of the body if
     // it exists. Only meant to be used to handle HEAD requests automatically.
     #[inline(always)]
@@ -1,33 +1,46 @@
 use std::{io, mem};
-use std::sync::Arc;
-use std::collections::HashMap;
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
 
 #[allow(unused_imports)]
-use futures::future::FutureExt;
+use futures::future::{FutureExt, TryFutureExt};
 use futures::stream::StreamExt;
-use futures::future::{Future, BoxFuture};
-use tokio::sync::{mpsc, oneshot};
+use futures::future::{Future, BoxFuture, Either, Shared};
+use tokio::sync::oneshot;
 use ref_cast::RefCast;
 
 use yansi::Paint;
 use state::Container;
 
 use crate::{logger, handler};
-use crate::config::{Config, FullConfig, ConfigError, LoggedValue};
-use crate::request::{Request, FormItems};
+use crate::config::{Config, FullConfig, ConfigError, EffectiveConfig, ListenerConfig, UnixConfig};
+use crate::config::Protocol;
+use crate::config::{ConfigSnapshot, ConfigUpdated, LiveConfig, Secrets, Args};
+use crate::request::{Request, FormItems, Phase, PeerCertificates, LocalAddr, PendingUpgrade};
 use crate::data::Data;
-use crate::catcher::Catcher;
+use crate::catcher::{Catcher, Panic};
+use crate::cache::Cache;
+use crate::record::Recorder;
 use crate::response::{Body, Response};
 use crate::router::{Router, Route};
 use crate::outcome::Outcome;
 use crate::error::{LaunchError, LaunchErrorKind};
 use crate::fairing::{Fairing, Fairings};
-use crate::logger::PaintExt;
+use crate::logger::{PaintExt, Logger};
 use crate::ext::AsyncReadExt;
-use crate::shutdown::Shutdown;
-
-use crate::http::{Method, Status, Header};
-use crate::http::private::{Listener, Connection, Incoming};
+use crate::shutdown::{self, Shutdown, ShutdownReason};
+use crate::socket::BoundSocket;
+use crate::tls::TlsReloader;
+use crate::load::{self, Load, ServerStats};
+
+use crate::http::{Method, Status, Header, ProxyHeader};
+use crate::http::private::{Listener, Connection, Incoming, TimedListener, TimedConnection};
+use crate::http::private::TlsInfo;
+use crate::http::private::{ProxyListener, PROXY_HEADER_TIMEOUT};
 use crate::http::hyper::{self, header};
 use crate::http::uri::Origin;
 
@@ -35,22 +48,30 @@ use crate::http::uri::Origin;
 /// application.
 pub struct Rocket {
     pub(crate) config: Config,
+    pub(crate) live: LiveConfig,
+    pub(crate) secrets: Arc<Secrets>,
+    pub(crate) args: Args,
     pub(crate) managed_state: Container,
+    overridden_state: HashSet<TypeId>,
     manifest: Vec<PreLaunchOp>,
     router: Router,
-    default_catcher: Option<Catcher>,
-    catchers: HashMap<u16, Catcher>,
-    fairings: Fairings,
-    shutdown_receiver: Option<mpsc::Receiver<()>>,
+    catchers: Vec<Catcher>,
+    pub(crate) fairings: Fairings,
+    shutdown_receiver: Option<shutdown::Receiver>,
     pub(crate) shutdown_handle: Shutdown,
+    tasks: Mutex<Vec<shutdown::Task>>,
+    pub(crate) tls_reloader: TlsReloader,
+    pub(crate) load: Load,
+    #[cfg(feature = "tower")]
+    pub(crate) layers: Vec<crate::tower::BoxLayer>,
 }
 
 /// An operation that occurs prior to launching a Rocket instance.
 enum PreLaunchOp {
     Mount(Origin<'static>, Vec<Route>),
-    Register(Vec<Catcher>),
-    Manage(&'static str, Box<dyn FnOnce(&mut Container) + Send + Sync + 'static>),
-    Attach(Box<dyn Fairing>),
+    Register(Origin<'static>, Vec<Catcher>),
+    Manage(&'static str, TypeId, Box<dyn FnOnce(&mut Container) + Send + Sync + 'static>),
+    Attach(TypeId, Box<dyn Fairing>),
 }
 
 /// A frozen view into the contents of an instance of `Rocket`.
@@ -60,6 +81,14 @@ enum PreLaunchOp {
 #[repr(transparent)]
 pub struct Cargo(Rocket);
 
+/// A shared handle to a launched instance of `Rocket`.
+///
+/// Given to background tasks attached via [`Rocket::attach_orbit_task()`]. An
+/// `Orbit` dereferences to [`Cargo`], so managed state and configuration can
+/// be accessed for as long as the handle is held. Handles are cheap to clone.
+#[derive(Clone)]
+pub struct Orbit(Arc<Rocket>);
+
 // A token returned to force the execution of one method before another.
 pub(crate) struct Token;
 
@@ -87,28 +116,37 @@ impl Rocket {
     }
 
     #[inline]
-    fn _register(&mut self, catchers: Vec<Catcher>) {
+    fn _register(&mut self, base: Origin<'static>, catchers: Vec<Catcher>) {
         info!("{}{}", Paint::emoji("👾 "), Paint::magenta("Catchers:"));
 
         for catcher in catchers {
-            info_!("{}", catcher);
+            let old_catcher = catcher.clone();
+            let catcher = catcher.map_base(|old| format!("{}{}", base, old))
+                .unwrap_or_else(|e| {
+                    error_!("Catcher `{}` has a malformed base URI.", old_catcher);
+                    error_!("{}", e);
+                    panic!("Invalid catcher base URI.");
+                });
 
-            let existing = match catcher.code {
-                Some(code) => self.catchers.insert(code, catcher),
-                None => self.default_catcher.replace(catcher)
-            };
+            info_!("{}", catcher);
+            let existing = self.catchers.iter()
+                .position(|c| c.code == catcher.code && c.base == catcher.base);
 
-            if let Some(existing) = existing {
-                warn_!("Replacing existing '{}' catcher.", existing);
+            match existing {
+                Some(i) => {
+                    warn_!("Replacing existing '{}' catcher.", self.catchers[i]);
+                    self.catchers[i] = catcher;
+                }
+                None => self.catchers.push(catcher),
             }
         }
     }
 
     #[inline]
-    async fn _attach(mut self, fairing: Box<dyn Fairing>) -> Self {
+    async fn _attach(mut self, type_id: TypeId, fairing: Box<dyn Fairing>) -> Self {
         // Attach (and run attach-) fairings, which requires us to move `self`.
         let mut fairings = mem::replace(&mut self.fairings, Fairings::new());
-        self = fairings.attach(fairing, self).await;
+        self = fairings.attach(type_id, fairing, self).await;
 
         // Note that `self.fairings` may now be non-empty! Move them to the end.
         fairings.append(self.fairings);
@@ -121,13 +159,21 @@ impl Rocket {
         Rocket {
             manifest: vec![],
             config: Config::development(),
+            live: LiveConfig::new(Config::development()),
+            secrets: Arc::new(Secrets::new()),
+            args: Args::new(),
             router: Router::new(),
-            default_catcher: None,
-            catchers: HashMap::new(),
+            catchers: vec![],
             managed_state: Container::new(),
+            overridden_state: HashSet::new(),
             fairings: Fairings::new(),
-            shutdown_handle: Shutdown(mpsc::channel(1).0),
+            shutdown_handle: Shutdown::new().0,
             shutdown_receiver: None,
+            tasks: Mutex::new(vec![]),
+            tls_reloader: TlsReloader::default(),
+            load: Load::default(),
+            #[cfg(feature = "tower")]
+            layers: vec![],
         }
     }
 
@@ -147,12 +193,16 @@ impl Rocket {
         while !manifest.is_empty() {
             trace_!("[MANIEST PROGRESS]: {:?}", manifest);
             match manifest.remove(0) {
-                PreLaunchOp::Manage(_, callback) => callback(&mut self.managed_state),
+                PreLaunchOp::Manage(_, type_id, callback) => {
+                    if !self.overridden_state.contains(&type_id) {
+                        callback(&mut self.managed_state);
+                    }
+                }
                 PreLaunchOp::Mount(base, routes) => self._mount(base, routes),
-                PreLaunchOp::Register(catchers) => self._register(catchers),
-                PreLaunchOp::Attach(fairing) => {
+                PreLaunchOp::Register(base, catchers) => self._register(base, catchers),
+                PreLaunchOp::Attach(type_id, fairing) => {
                     let rocket = mem::replace(self, Rocket::dummy());
-                    *self = rocket._attach(fairing).await;
+                    *self = rocket._attach(type_id, fairing).await;
                     self.manifest.append(&mut manifest);
                     manifest = mem::replace(&mut self.manifest, vec![]);
                 }
@@ -160,12 +210,19 @@ impl Rocket {
         }
     }
 
-    pub(crate) async fn into_cargo(mut self) -> Cargo {
-        self.actualize_manifest().await;
-        Cargo(self)
+    /// Manages `state` in place of any state of the same type managed via
+    /// `manage()`, before or after this call, which is ignored.
+    pub(crate) fn override_state<T: Send + Sync + 'static>(&mut self, state: T) {
+        if !self.overridden_state.insert(TypeId::of::<T>()) {
+            panic!("State for type '{}' is already overridden.", std::any::type_name::<T>());
+        }
+
+        if !self.managed_state.set::<T>(state) {
+            panic!("State for type '{}' is already being managed.", std::any::type_name::<T>());
+        }
     }
 
-    fn cargo(&self) -> &Cargo {
+    pub(crate) fn cargo(&self) -> &Cargo {
         if !self.manifest.is_empty() {
             panic!("internal error: immutable launch state with manifest");
         }
@@ -178,7 +235,7 @@ impl Rocket {
 // converts Hyper types into Rocket types, then calls the `dispatch` function,
 // which knows nothing about Hyper. Because responding depends on the
 // `HyperResponse` type, this function does the actual response processing.
-async fn hyper_service_fn(
+pub(crate) async fn hyper_service_fn(
     rocket: Arc<Rocket>,
     h_addr: std::net::SocketAddr,
     hyp_req: hyper::Request<hyper::Body>,
@@ -188,10 +245,16 @@ async fn hyper_service_fn(
     // we do the body writing in another future that will send us
     // the response metadata (and a body channel) beforehand.
     let (tx, rx) = oneshot::channel();
+    let grace = hyp_req.extensions().get::<Grace>().cloned();
 
-    tokio::spawn(async move {
+    let (handler, abort) = futures::future::abortable(async move {
         // Get all of the information from Hyper.
-        let (h_parts, h_body) = hyp_req.into_parts();
+        let (h_parts, mut h_body) = hyp_req.into_parts();
+        let listener = h_parts.extensions.get::<Arc<ListenerConfig>>().cloned();
+        let peer_certificates = h_parts.extensions.get::<Arc<PeerCertificates>>().cloned();
+        let proxy_header = h_parts.extensions.get::<Arc<ProxyHeader>>().cloned();
+        let local_addr = h_parts.extensions.get::<LocalAddr>().map(|addr| addr.0);
+        let tls = h_parts.extensions.get::<Arc<TlsInfo>>().cloned();
 
         // Convert the Hyper request into a Rocket request.
         let req_res = Request::from_hyp(&rocket, h_parts.method, h_parts.headers, &h_parts.uri, h_addr);
@@ -209,18 +272,80 @@ async fn hyper_service_fn(
             }
         };
 
+        // Record the protocol and HTTP version the request arrived over, the
+        // listener that accepted the connection, the certificates the client
+        // authenticated with, the PROXY protocol header the connection started
+        // with, and the connection's local address and TLS session, if any.
+        req.state.protocol = match h_parts.version {
+            hyper::Version::HTTP_2 => Some(Protocol::H2),
+            hyper::Version::HTTP_09 | hyper::Version::HTTP_10 => Some(Protocol::Http10),
+            _ => Some(Protocol::Http1),
+        };
+
+        req.state.http_version = Some(match h_parts.version {
+            hyper::Version::HTTP_09 => "HTTP/0.9",
+            hyper::Version::HTTP_10 => "HTTP/1.0",
+            hyper::Version::HTTP_2 => "HTTP/2.0",
+            _ => "HTTP/1.1",
+        });
+
+        req.state.listener = listener;
+        req.state.peer_certificates = peer_certificates;
+        req.state.proxy_header = proxy_header;
+        req.state.local_addr = local_addr;
+        req.state.tls = tls;
+
+        // Keep hold of the connection's upgrade if the request, which has no
+        // body, asks for one. Only HTTP/1.1 connections can be upgraded.
+        let has_body = h_parts.headers.contains_key(header::TRANSFER_ENCODING)
+            || h_parts.headers.get(header::CONTENT_LENGTH).map_or(false, |len| len != "0");
+
+        if h_parts.version == hyper::Version::HTTP_11
+            && h_parts.headers.contains_key(header::UPGRADE)
+            && !has_body
+        {
+            let on_upgrade = std::mem::replace(&mut h_body, hyper::Body::empty()).on_upgrade();
+            req.state.upgrade = Some(Arc::new(PendingUpgrade::new(on_upgrade)));
+        }
+
         // Retrieve the data from the hyper body.
         let mut data = Data::from_hyp(h_body).await;
 
         // Dispatch the request to get a response, then write that response out.
         let token = rocket.preprocess_request(&mut req, &mut data).await;
         let r = rocket.dispatch(token, &mut req, data).await;
+        let switching = r.status() == Status::SwitchingProtocols;
         rocket.issue_response(r, tx).await;
+
+        // Hand the upgraded connection to the handler that accepted it.
+        if let (true, Some(upgrade)) = (switching, req.state.upgrade.take()) {
+            upgrade.finish().await;
+        }
     });
 
-    rx.await.map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    tokio::spawn(handler);
+
+    // Give up on a response still pending when the grace period of a shutdown
+    // ends, cancelling the handler that was producing it.
+    let response = match grace {
+        Some(Grace(grace)) => match futures::future::select(rx, grace).await {
+            Either::Left((response, _)) => response,
+            Either::Right(_) => {
+                abort.abort();
+                return Ok(load::unavailable(None, true));
+            }
+        },
+        None => rx.await,
+    };
+
+    response.map_err(|e| io::Error::new(io::ErrorKind::Other, e))
 }
 
+/// The end of the grace period of a shutdown, in the extensions of requests
+/// accepted by a listener.
+#[derive(Clone)]
+struct Grace(Signal);
+
 impl Rocket {
     #[inline]
     async fn issue_response(
@@ -245,8 +370,15 @@ impl Rocket {
         mut response: Response<'_>,
         tx: oneshot::Sender<hyper::Response<hyper::Body>>,
     ) -> io::Result<()> {
-        let mut hyp_res = hyper::Response::builder()
-            .status(response.status().code);
+        // A response fairing may have set an invalid status after routing.
+        let status = response.status();
+        let code = hyper::StatusCode::from_u16(status.code).unwrap_or_else(|_| {
+            error_!("Response has invalid status code {}.", status.code);
+            warn_!("Responding with 500 Internal Server Error.");
+            hyper::StatusCode::INTERNAL_SERVER_ERROR
+        });
+
+        let mut hyp_res = hyper::Response::builder().status(code);
 
         for header in response.headers().iter() {
             let name = header.name.as_str();
@@ -265,6 +397,9 @@ impl Rocket {
         };
 
         match response.body_mut() {
+            None if code == hyper::StatusCode::SWITCHING_PROTOCOLS => {
+                send_response(hyp_res, hyper::Body::empty())?;
+            }
             None => {
                 hyp_res = hyp_res.header(header::CONTENT_LENGTH, "0");
                 send_response(hyp_res, hyper::Body::empty())?;
@@ -295,6 +430,7 @@ impl Rocket {
 
     /// Preprocess the request for Rocket things. Currently, this means:
     ///
+    ///   * Recording the request if a `Recorder` is attached.
     ///   * Rewriting the method in the request if _method form field exists.
     ///   * Run the request fairings.
     ///
@@ -304,6 +440,11 @@ impl Rocket {
         req: &mut Request<'_>,
         data: &mut Data
     ) -> Token {
+        // Record the request as it was received, if a `Recorder` is attached.
+        if let Some(recorder) = self.managed_state.try_get::<Recorder>() {
+            recorder.record(req, data).await;
+        }
+
         // Check if this is a form and if the form contains the special _method
         // field which we use to reinterpret the request's method.
         let (min_len, max_len) = ("_method=get".len(), "_method=delete".len());
@@ -337,6 +478,11 @@ impl Rocket {
     ) -> impl Future<Output = Response<'r>> + Send + 's {
         async move {
             let mut response = match self.route(request, data).await {
+                Outcome::Success(response) if !response.status().is_valid() => {
+                    error_!("Response has invalid status code {}.", response.status().code);
+                    info_!("Status codes must be three-digit integers in [100, 999].");
+                    self.handle_error(Status::InternalServerError, request).await
+                }
                 Outcome::Success(response) => response,
                 Outcome::Forward(data) => {
                     // There was no matching route. Autohandle `HEAD` requests.
@@ -351,10 +497,16 @@ impl Rocket {
                         return try_next.await;
                     } else {
                         // No match was found and it can't be autohandled. 404.
+                        if self.config.environment.is_dev() {
+                            self.suggest_routes(request);
+                        }
+
                         self.handle_error(Status::NotFound, request).await
                     }
                 }
-                Outcome::Failure(status) => self.handle_error(status, request).await,
+                Outcome::Failure(status) => {
+                    self.dispatch_error(status, request.route(), request).await
+                }
             };
 
             // Set the cookies. Note that error responses will only include
@@ -386,14 +538,69 @@ impl Rocket {
     ) -> impl Future<Output = handler::Outcome<'r>> + 's {
         async move {
             // Go through the list of matching routes until we fail or succeed.
+            let phases = &request.state.phases;
+            phases.enter(Phase::Routing);
             let matches = self.router.route(request);
             for route in matches {
                 // Retrieve and set the requests parameters.
                 info_!("Matched: {}", route);
                 request.set_route(route);
 
-                // Dispatch the request to the handler.
-                let outcome = route.handler.handle(request, data).await;
+                // Dispatch the request to the handler, catching any panics.
+                // Generated handlers record their own phases; record others
+                // as a single handler phase.
+                phases.exit();
+
+                // Serve the response from the cache, if it's cached, unless the
+                // handler does so itself once the route's guards succeed.
+                let cache = route.cache.as_ref()
+                    .and_then(|policy| Some((self.managed_state.try_get::<Cache>()?, policy)));
+
+                if let (Some((cache, policy)), false) = (cache, route.guarded_cache) {
+                    if let Some(response) = cache.lookup(request, policy).await {
+                        info_!("{} {}", Paint::default("Outcome:").bold(), "Success (cached)");
+                        return Outcome::Success(response);
+                    }
+                }
+
+                // Wait briefly for an execution slot if the route's
+                // concurrency is limited, shedding the request otherwise.
+                let _permit = match route.concurrency {
+                    Some(ref concurrency) => match concurrency.acquire().await {
+                        Some(permit) => Some(permit),
+                        None => {
+                            warn_!("Route is at its concurrency limit.");
+                            request.state.load.shed_request();
+                            return Outcome::Failure(Status::ServiceUnavailable);
+                        }
+                    },
+                    None => None,
+                };
+
+                let (handler_start, recorded) = (Instant::now(), phases.count());
+                let handler = AssertUnwindSafe(route.handler.handle(request, data));
+                let outcome = match handler.catch_unwind().await {
+                    Ok(outcome) => outcome,
+                    Err(payload) => {
+                        let panic = Panic::from_payload(payload);
+                        error_!("Handler for {} panicked: {}.", route, panic);
+                        warn_!("Responding with 500 Internal Server Error.");
+                        request.set_error(panic);
+                        Outcome::Failure(Status::InternalServerError)
+                    }
+                };
+
+                phases.exit_or_record(recorded, Phase::Handler, handler_start);
+
+                let outcome = match (outcome, cache) {
+                    (Outcome::Success(response), _) if request.served_from_cache() => {
+                        Outcome::Success(response)
+                    }
+                    (Outcome::Success(response), Some((cache, policy))) => {
+                        Outcome::Success(cache.store(request, policy, response).await)
+                    }
+                    (outcome, _) => outcome,
+                };
 
                 // Check if the request processing completed (Some) or if the
                 // request needs to be forwarded. If it does, continue the loop
@@ -403,13 +610,35 @@ impl Rocket {
                     o@Outcome::Success(_) | o@Outcome::Failure(_) => return o,
                     Outcome::Forward(unused_data) => data = unused_data,
                 }
+
+                phases.enter(Phase::Routing);
             }
 
+            phases.exit();
             error_!("No matching routes for {}.", request);
             Outcome::Forward(data)
         }
     }
 
+    /// Logs the routes that nearly matched `req`, which failed to route, and
+    /// records them on `req` for use by the default catcher.
+    fn suggest_routes(&self, req: &Request<'_>) {
+        let near_misses = self.router.near_misses(req);
+        if near_misses.is_empty() {
+            return;
+        }
+
+        warn_!("Routes that nearly matched:");
+        let hints: Vec<String> = near_misses.into_iter()
+            .map(|(route, why)| {
+                info_!("{}: {}", route, why);
+                format!("{} {}: {}", route.method, route.uri, why)
+            })
+            .collect();
+
+        req.set_near_misses(hints);
+    }
+
     #[inline]
     pub(crate) async fn dispatch<'s, 'r: 's>(
         &'s self,
@@ -433,6 +662,16 @@ impl Rocket {
 
         // Run the response fairings.
         self.fairings.handle_response(request, &mut response).await;
+        if !response.status().is_valid() {
+            error_!("A response fairing set invalid status code {}.", response.status().code);
+            warn_!("Using Rocket's default 500 error catcher.");
+            response = crate::catcher::default(Status::InternalServerError, request)
+                .expect("Rocket has default 500 response");
+        }
+
+        // Compress the body as negotiated with the client, if enabled.
+        #[cfg(feature = "compression")]
+        crate::response::compress(&self.config.compression, request, &mut response).await;
 
         // Strip the body if this is a `HEAD` request.
         if was_head_request {
@@ -443,14 +682,88 @@ impl Rocket {
     }
 
     // Finds the error catcher for the status `status` and executes it for the
-    // given request `req`. If a user has registered a catcher for `status`, the
-    // catcher is called. If the catcher fails to return a good response, the
-    // 500 catcher is executed. If there is no registered catcher for `status`,
-    // the default catcher is used.
+    // given request `req`. Equivalent to `dispatch_error` without a route.
     pub(crate) fn handle_error<'s, 'r: 's>(
         &'s self,
         status: Status,
         req: &'r Request<'s>
+    ) -> impl Future<Output = Response<'r>> + 's {
+        self.dispatch_error(status, None, req)
+    }
+
+    // Returns the catchers that apply to an error with status `status` for the
+    // request `req`, optionally produced by `route`, closest scope first.
+    fn catchers_for<'a>(
+        &'a self,
+        status: Status,
+        route: Option<&'a Route>,
+        req: &Request<'_>
+    ) -> Vec<&'a Catcher> {
+        // Within a scope, a catcher for `status` precedes a default catcher.
+        let precedence = |c: &Catcher| c.code.is_none();
+        let applies = |c: &&Catcher| c.code.map_or(true, |code| code == status.code);
+
+        let mut route_catchers: Vec<_> = route.into_iter()
+            .flat_map(|r| r.catchers.iter())
+            .filter(applies)
+            .collect();
+
+        route_catchers.sort_by_key(|c| precedence(c));
+
+        let mut scoped: Vec<_> = self.catchers.iter()
+            .filter(applies)
+            .filter_map(|c| c.base_depth(req).map(|depth| (depth, c)))
+            .collect();
+
+        scoped.sort_by_key(|&(depth, c)| (std::cmp::Reverse(depth), precedence(c)));
+        route_catchers.extend(scoped.into_iter().map(|(_, c)| c));
+        route_catchers
+    }
+
+    // Runs the catchers that apply to an error with status `status` for the
+    // request `req`, which failed while being handled by `route`, if any.
+    // Catchers are tried in scope order: those registered on `route`, then
+    // those whose base prefixes the request's path, longest first, with
+    // catchers for `status` preceding default catchers within a scope. A
+    // catcher that declines via `catcher::decline()`, as a typed catcher does
+    // when the request's error isn't of its type, passes the error on to the
+    // next one. Returns `None` if every catcher declined.
+    async fn run_catchers<'s, 'r: 's>(
+        &'s self,
+        status: Status,
+        route: Option<&'s Route>,
+        req: &'r Request<'s>
+    ) -> Option<crate::catcher::Result<'r>> {
+        for catcher in self.catchers_for(status, route, req) {
+            info_!("Using {} catcher.", catcher);
+            req.state.declined.store(false, Ordering::Release);
+            let handler = AssertUnwindSafe(catcher.handler.handle(status, req));
+            let response = handler.catch_unwind().await.unwrap_or_else(|payload| {
+                error_!("Catcher {} panicked: {}.", catcher, Panic::from_payload(payload));
+                Err(Status::InternalServerError)
+            });
+
+            if !req.state.declined.swap(false, Ordering::AcqRel) {
+                return Some(response);
+            }
+
+            warn_!("The {} catcher declined to handle the error.", catcher);
+        }
+
+        None
+    }
+
+    // Finds the error catcher for the status `status` and executes it for the
+    // given request `req`, which failed while being handled by `route`, if
+    // any, as described in `run_catchers`. If every catcher declines, Rocket's
+    // default catcher is used. If a catcher fails with another status, as when
+    // its responder fails, that error is handled by the catchers for its
+    // status in turn. If that fails too, Rocket's default 500 catcher is used.
+    fn dispatch_error<'s, 'r: 's>(
+        &'s self,
+        status: Status,
+        route: Option<&'s Route>,
+        req: &'r Request<'s>
     ) -> impl Future<Output = Response<'r>> + 's {
         async move {
             warn_!("Responding with {} catcher.", Paint::red(&status));
@@ -460,20 +773,32 @@ impl Rocket {
             // response. We may wish to relax this in the future.
             req.cookies().reset_delta();
 
-            // Try to get the active catcher but fallback to user's 500 catcher.
-            let code = Paint::red(status.code);
-            let response = if let Some(catcher) = self.catchers.get(&status.code) {
-                catcher.handler.handle(status, req).await
-            } else if let Some(ref default) =  self.default_catcher {
-                warn_!("No {} catcher found. Using default catcher.", code);
-                default.handler.handle(status, req).await
-            } else {
-                warn_!("No {} or default catcher found. Using Rocket default catcher.", code);
+            let default = |status: Status| {
+                let code = Paint::red(status.code);
+                warn_!("No {} catcher found. Using Rocket default catcher.", code);
                 crate::catcher::default(status, req)
             };
 
+            let response = match self.run_catchers(status, route, req).await {
+                None => default(status),
+                Some(Err(failed)) if failed != status && failed.is_valid() => {
+                    warn_!("The catcher failed with {}.", Paint::red(&failed));
+                    match self.run_catchers(failed, route, req).await {
+                        None => default(failed),
+                        Some(response) => response,
+                    }
+                }
+                Some(response) => response,
+            };
+
             // Dispatch to the catcher. If it fails, use the Rocket default 500.
             match response {
+                Ok(r) if !r.status().is_valid() => {
+                    error_!("Catcher responded with invalid status code {}.", r.status().code);
+                    warn_!("Using Rocket's default 500 error catcher.");
+                    let default = crate::catcher::default(Status::InternalServerError, req);
+                    default.expect("Rocket has default 500 response")
+                }
                 Ok(r) => r,
                 Err(err_status) => {
                     error_!("Catcher unexpectedly failed with {}.", err_status);
@@ -486,14 +811,41 @@ impl Rocket {
     }
 
     // TODO.async: Solidify the Listener APIs and make this function public
-    async fn listen_on<L>(mut self, listener: L) -> Result<(), crate::error::Error>
-        where L: Listener + Send + Unpin + 'static,
-              <L as Listener>::Connection: Send + Unpin + 'static,
-    {
-        // Determine the address and port we actually binded to.
-        self.config.port = listener.local_addr().map(|a| a.port()).unwrap_or(0);
-        let proto = self.config.tls.as_ref().map_or("http://", |_| "https://");
-        let full_addr = format!("{}:{}", self.config.address, self.config.port);
+    async fn listen_on(mut self, bound: Vec<(ListenerConfig, Bound)>) -> Result<(), crate::error::Error> {
+        // Determine the ports we actually binded to. The first listener is
+        // the default listener; the rest are `config.listeners`, in order.
+        let mut listeners = vec![];
+        for (i, (mut config, listener)) in bound.into_iter().enumerate() {
+            if let Some(addr) = listener.local_addr() {
+                config.port = addr.port();
+                match i {
+                    0 => self.config.port = config.port,
+                    i => self.config.listeners[i - 1].port = config.port,
+                }
+            }
+
+            listeners.push((Arc::new(config), listener));
+        }
+
+        // Note the Unix domain sockets to remove after shutting down.
+        #[cfg(unix)]
+        let sockets: Vec<_> = listeners.iter()
+            .filter_map(|(_, listener)| match listener {
+                Bound::Unix(_, cleanup) => cleanup.clone(),
+                _ => None,
+            })
+            .collect();
+
+        self.live = LiveConfig::new(self.config.clone());
+
+        // Allow the certificates of the TLS listeners to be reloaded.
+        #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+        self.tls_reloader.serve(self.config.clone(), listeners.iter()
+            .filter_map(|(config, listener)| match listener {
+                Bound::Tls(listener) => Some((config.clone(), listener.handle())),
+                _ => None,
+            })
+            .collect());
 
         // Freeze managed state for synchronization-free accesses later.
         self.managed_state.freeze();
@@ -502,55 +854,468 @@ impl Rocket {
         self.fairings.pretty_print_counts();
         self.fairings.handle_launch(self.cargo());
 
-        launch_info!("{}{} {}{}",
+        // The background tasks are spawned once `self` is shared.
+        let tasks = mem::replace(self.tasks.get_mut().expect("tasks lock"), vec![]);
+
+        launch_info!("{}{} {}",
                      Paint::emoji("🚀 "),
                      Paint::default("Rocket has launched from").bold(),
-                     Paint::default(proto).bold().underline(),
-                     Paint::default(&full_addr).bold().underline());
+                     Paint::default(listeners[0].0.url()).bold().underline());
+
+        for (config, _) in listeners.iter().skip(1) {
+            launch_info_!("{} listener: {}", Paint::default(&config.name).bold(),
+                          Paint::default(config.url()).bold().underline());
+        }
 
         // Restore the log level back to what it originally was.
         logger::pop_max_level();
 
-        // Set the keep-alive.
-        // TODO.async: implement keep-alive in Listener
-        // let timeout = self.config.keep_alive.map(|s| Duration::from_secs(s as u64));
-        // listener.set_keepalive(timeout);
-
         // We need to get this before moving `self` into an `Arc`.
-        let mut shutdown_receiver = self.shutdown_receiver
+        let shutdown::Receiver { mut requests, trigger } = self.shutdown_receiver
             .take().expect("shutdown receiver has already been used");
 
         let rocket = Arc::new(self);
-        let service = hyper::make_service_fn(move |connection: &<L as Listener>::Connection| {
+        let cargo = rocket.clone();
+        let tasks = shutdown::spawn_tasks(tasks, Orbit(rocket.clone()));
+
+        // Reload the configuration on `SIGHUP` and file changes, if enabled.
+        if rocket.config.get_bool("reload") == Ok(true) {
+            tokio::spawn(crate::config::watch(Arc::downgrade(&rocket)));
+        }
+
+        // All listeners clone one layered service and shut down together.
+        #[cfg(feature = "tower")]
+        let layered = crate::tower::layered(&rocket);
+
+        // Once a shutdown is requested, notify every `Shutdown` handle. From
+        // then on, requests have `grace` seconds to respond and connections a
+        // further `mercy` seconds to close.
+        let shutdown = async move {
+            requests.recv().await;
+            let _ = trigger.send(());
+        }.boxed().shared();
+
+        let grace = deadline(shutdown.clone(), rocket.config.server.grace);
+        let mercy = deadline(grace.clone(), rocket.config.server.mercy);
+
+        let timeouts = rocket.config.server.timeouts(rocket.config.keep_alive);
+
+        // PROXY protocol headers are always read with a timeout, as long as
+        // the one for request headers if there is one.
+        let header_timeout = timeouts.read_header.unwrap_or(PROXY_HEADER_TIMEOUT);
+        let (load, max_connections) = (rocket.load.clone(), rocket.config.server.max_connections);
+        let servers = listeners.into_iter().map(|(config, listener)| {
+            macro_rules! serve {
+                ($listener:expr) => {{
+                    let listener = TimedListener::new($listener, timeouts);
+                    #[cfg(feature = "tower")]
+                    let server = Rocket::serve(rocket.clone(), config, listener,
+                                               layered.clone(), shutdown.clone(),
+                                               grace.clone(), mercy.clone());
+                    #[cfg(not(feature = "tower"))]
+                    let server = Rocket::serve(rocket.clone(), config, listener,
+                                               shutdown.clone(), grace.clone(), mercy.clone());
+                    server.boxed()
+                }};
+            }
+
+            // Read the PROXY protocol header of connections before serving
+            // them, and before the TLS handshake of TLS listeners.
+            match listener {
+                Bound::Tcp(listener) if config.proxy_protocol => {
+                    serve!(proxy(listener, &load, max_connections, header_timeout))
+                }
+                Bound::Tcp(listener) => serve!(listener),
+                #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+                Bound::Tls(listener) if config.proxy_protocol => {
+                    serve!(listener.map(|l| proxy(l, &load, max_connections, header_timeout)))
+                }
+                #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+                Bound::Tls(listener) => serve!(listener),
+                #[cfg(feature = "native-tls")]
+                Bound::NativeTls(listener) if config.proxy_protocol => {
+                    serve!(listener.map(|l| proxy(l, &load, max_connections, header_timeout)))
+                }
+                #[cfg(feature = "native-tls")]
+                Bound::NativeTls(listener) => serve!(listener),
+                #[cfg(unix)]
+                Bound::Unix(listener, _) if config.proxy_protocol => {
+                    serve!(proxy(listener, &load, max_connections, header_timeout))
+                }
+                #[cfg(unix)]
+                Bound::Unix(listener, _) => serve!(listener),
+            }
+        });
+
+        let result = futures::future::try_join_all(servers).await.map(|_| ());
+        #[cfg(unix)]
+        for socket in sockets {
+            if let Err(e) = std::fs::remove_file(&socket) {
+                warn_!("Failed to remove socket {}: {}", socket.display(), e);
+            }
+        }
+
+        // Stop the background tasks, even if serving failed, giving them until
+        // the end of the `mercy` period to finish.
+        if result.is_err() && !tasks.is_empty() {
+            let reason = ShutdownReason::Fatal("the server failed".into());
+            cargo.shutdown_handle.clone().shutdown_with(reason);
+        }
+
+        shutdown::join_tasks(tasks, mercy).await;
+
+        // Run the shutdown fairings now that all requests and tasks have completed.
+        cargo.fairings.handle_shutdown(cargo.cargo()).await;
+        result
+    }
+
+    // Serves requests accepted by `listener`, described by `config`, until
+    // `shutdown` resolves, then drains connections: requests still in flight
+    // at `grace` are answered with a `503`, and connections open at `mercy`
+    // are dropped.
+    async fn serve<L>(
+        rocket: Arc<Rocket>,
+        config: Arc<ListenerConfig>,
+        listener: TimedListener<L>,
+        #[cfg(feature = "tower")] layered: Option<crate::tower::BoxService>,
+        shutdown: Signal,
+        grace: Signal,
+        mercy: Signal,
+    ) -> Result<(), crate::error::Error>
+        where L: Listener + Send + Unpin + 'static,
+              <L as Listener>::Connection: Send + Unpin + 'static,
+    {
+        let keep_alive = rocket.config.keep_alive;
+        let server = rocket.config.server;
+        let load = rocket.load.clone();
+        let accept_counts = rocket.load.accept_counts();
+        let draining = shutdown.clone();
+
+        // Serve the configured protocols this listener supports, or HTTP/1.1
+        // if it supports none of them. HTTP/2 is only served over TLS.
+        let http2 = config.tls_enabled() && rocket.config.protocols.contains(&Protocol::H2);
+        let http1 = rocket.config.protocols.contains(&Protocol::Http1) || !http2;
+
+        let service = hyper::make_service_fn(move |connection: &TimedConnection<L::Connection>| {
             let rocket = rocket.clone();
+            let config = config.clone();
+            #[cfg(feature = "tower")]
+            let layered = layered.clone();
             let remote_addr = connection.remote_addr().unwrap_or_else(|| ([0, 0, 0, 0], 0).into());
+            let peer_certificates = connection.peer_certificates()
+                .and_then(PeerCertificates::new)
+                .map(Arc::new);
+
+            let proxy_header = connection.proxy_header().map(Arc::new);
+            let local_addr = connection.local_addr().map(LocalAddr);
+            let tls = connection.tls_info().map(Arc::new);
+
+            let served = Arc::new(AtomicU32::new(0));
+            let load = load.clone();
+            let draining = draining.clone();
+            let grace = grace.clone();
+            let (connection_ticket, shed_connection) = load.connection(server.max_connections);
             async move {
-                Ok::<_, std::convert::Infallible>(hyper::service_fn(move |req| {
-                    hyper_service_fn(rocket.clone(), remote_addr, req)
+                Ok::<_, std::convert::Infallible>(hyper::service_fn(move |mut req| {
+                    // The connection is counted for as long as it is served.
+                    let _connection = &connection_ticket;
+
+                    // Shed requests beyond the limits instead of queueing them.
+                    if shed_connection {
+                        load.shed_request();
+                        let response = load::unavailable(server.retry_after, true);
+                        return futures::future::ok::<_, io::Error>(response).boxed();
+                    }
+
+                    // The request is counted until its response is written.
+                    let ticket = match load.request(server.max_in_flight) {
+                        Some(ticket) => ticket,
+                        None => {
+                            let response = load::unavailable(server.retry_after, false);
+                            return futures::future::ok(response).boxed();
+                        }
+                    };
+
+                    let http1_request = req.version() < hyper::Version::HTTP_2;
+                    req.extensions_mut().insert(ticket);
+                    req.extensions_mut().insert(config.clone());
+                    if let Some(ref certs) = peer_certificates {
+                        req.extensions_mut().insert(certs.clone());
+                    }
+
+                    if let Some(ref header) = proxy_header {
+                        req.extensions_mut().insert(header.clone());
+                    }
+
+                    if let Some(addr) = local_addr {
+                        req.extensions_mut().insert(addr);
+                    }
+
+                    if let Some(ref tls) = tls {
+                        req.extensions_mut().insert(tls.clone());
+                    }
+
+                    req.extensions_mut().insert(Grace(grace.clone()));
+
+                    #[cfg(feature = "tower")]
+                    let response = match layered {
+                        Some(ref layered) => {
+                            crate::tower::call_layered(layered, remote_addr, req)
+                        }
+                        None => hyper_service_fn(rocket.clone(), remote_addr, req).boxed(),
+                    };
+
+                    #[cfg(not(feature = "tower"))]
+                    let response = hyper_service_fn(rocket.clone(), remote_addr, req).boxed();
+
+                    // Ask the client to close the connection after its last
+                    // allowed request or once a shutdown has begun.
+                    let count = served.fetch_add(1, Ordering::Relaxed) + 1;
+                    let last = server.max_requests.map_or(false, |max| count >= max);
+                    let draining = draining.clone();
+                    response.map_ok(move |mut response| {
+                        let upgrading = response.status() == hyper::StatusCode::SWITCHING_PROTOCOLS;
+                        if http1_request && !upgrading && (last || draining.peek().is_some()) {
+                            let close = hyper::HeaderValue::from_static("close");
+                            response.headers_mut().insert(header::CONNECTION, close);
+                        }
+
+                        response
+                    }).boxed()
                 }))
             }
         });
 
-        #[derive(Clone)]
-        struct TokioExecutor;
+        let mut incoming = Incoming::from_listener(listener);
+        incoming.set_counts(accept_counts);
 
-        impl<Fut> hyper::Executor<Fut> for TokioExecutor
-            where Fut: Future + Send + 'static, Fut::Output: Send
-        {
-            fn execute(&self, fut: Fut) {
-                tokio::spawn(fut);
-            }
-        }
-
-        hyper::Server::builder(Incoming::from_listener(listener))
-            .executor(TokioExecutor)
+        hyper::Server::builder(incoming)
+            .executor(TokioExecutor(mercy))
+            .http1_keepalive(keep_alive.is_some())
+            .http1_only(!http2)
+            .http2_only(!http1)
             .serve(service)
-            .with_graceful_shutdown(async move { shutdown_receiver.recv().await; })
+            .with_graceful_shutdown(shutdown)
             .await
             .map_err(|e| crate::error::Error::Run(Box::new(e)))
     }
 }
 
+/// A listener, bound at launch, that Rocket has not yet begun serving.
+enum Bound {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    Tls(crate::http::tls::TlsListener),
+    #[cfg(feature = "native-tls")]
+    NativeTls(crate::http::native_tls::NativeTlsListener),
+    /// A Unix domain socket and, if it should be removed after shutting
+    /// down, its path.
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, Option<std::path::PathBuf>),
+}
+
+impl Bound {
+    /// Binds the listener `config` with the settings of the application's
+    /// configuration `app`.
+    async fn bind(config: &ListenerConfig, app: &Config) -> Result<Bound, LaunchError> {
+        use std::net::ToSocketAddrs;
+
+        let bind_error = |e| LaunchError::new(LaunchErrorKind::Bind(e));
+        if let Some(path) = config.unix_path() {
+            return Bound::bind_unix(path, config, &app.unix).map_err(bind_error);
+        }
+
+        let addr = match (config.address.as_str(), config.port).to_socket_addrs() {
+            Ok(mut addrs) => addrs.next().expect(">= 1 socket addr"),
+            Err(e) => return Err(e.into()),
+        };
+
+        match config.tls {
+            #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+            Some(ref tls) => {
+                let (certs, key, auth) = (tls.certs.clone(), tls.key.clone(), tls.client_auth.clone());
+                let alpn = Protocol::alpn(&app.protocols);
+                crate::http::tls::bind_tls(addr, certs, key, auth, alpn).await
+                    .map(Bound::Tls)
+                    .map_err(bind_error)
+            }
+            #[cfg(feature = "native-tls")]
+            Some(ref tls) => {
+                crate::http::native_tls::bind_native_tls(addr, tls.identity.clone()).await
+                    .map(Bound::NativeTls)
+                    .map_err(bind_error)
+            }
+            _ => crate::http::private::bind_tcp(addr).await.map(Bound::Tcp).map_err(bind_error),
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(
+        path: &std::path::Path,
+        config: &ListenerConfig,
+        unix: &UnixConfig
+    ) -> io::Result<Bound> {
+        use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+        if config.tls.is_some() {
+            let msg = "TLS is not supported on Unix domain sockets";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        // Remove the socket left behind by a previous run, but never a file
+        // that isn't a socket or a socket a live server is accepting on.
+        if unix.cleanup {
+            match std::fs::symlink_metadata(path) {
+                Ok(meta) if meta.file_type().is_socket() => {
+                    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                        let msg = "another server is listening on the socket";
+                        return Err(io::Error::new(io::ErrorKind::AddrInUse, msg));
+                    }
+
+                    std::fs::remove_file(path)?;
+                }
+                _ => { /* nothing to clean up, or not ours to remove */ }
+            }
+        }
+
+        // Bind in a private directory and link the socket into place only once
+        // its mode is set so that no client can connect in between.
+        let listener = match unix.mode {
+            Some(mode) => {
+                let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let private = dir.unwrap_or_else(|| std::path::Path::new("."))
+                    .join(format!(".{}.{}", name, std::process::id()));
+
+                std::fs::DirBuilder::new().mode(0o700).create(&private)?;
+                let staged = private.join("socket");
+                let bound = crate::http::private::bind_unix(&staged).and_then(|listener| {
+                    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(mode))?;
+                    std::fs::hard_link(&staged, path)?;
+                    Ok(listener)
+                });
+
+                let _ = std::fs::remove_file(&staged);
+                let _ = std::fs::remove_dir(&private);
+                bound?
+            }
+            None => crate::http::private::bind_unix(path)?,
+        };
+
+        let cleanup = if unix.cleanup { Some(path.to_path_buf()) } else { None };
+        Ok(Bound::Unix(listener, cleanup))
+    }
+
+    #[cfg(not(unix))]
+    fn bind_unix(_: &std::path::Path, _: &ListenerConfig, _: &UnixConfig) -> io::Result<Bound> {
+        let msg = "Unix domain sockets are not supported on this platform";
+        Err(io::Error::new(io::ErrorKind::InvalidInput, msg))
+    }
+
+    /// Serves the pre-bound `socket` as the listener `config`, updating the
+    /// address and port of `config` to those of the socket. Pre-bound Unix
+    /// domain sockets are never removed.
+    fn adopt(socket: BoundSocket, config: &mut ListenerConfig, _app: &Config) -> io::Result<Bound> {
+        match socket {
+            BoundSocket::Tcp(listener) => {
+                listener.set_nonblocking(true)?;
+                let addr = listener.local_addr()?;
+                config.address = addr.ip().to_string();
+                config.port = addr.port();
+
+                let listener = tokio::net::TcpListener::from_std(listener)?;
+                match config.tls {
+                    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+                    Some(ref tls) => {
+                        let (certs, key, auth) = (tls.certs.clone(), tls.key.clone(), tls.client_auth.clone());
+                        let alpn = Protocol::alpn(&_app.protocols);
+                        Ok(Bound::Tls(crate::http::tls::tls_listener(listener, certs, key, auth, alpn)))
+                    }
+                    #[cfg(feature = "native-tls")]
+                    Some(ref tls) => {
+                        let identity = tls.identity.clone();
+                        crate::http::native_tls::native_tls_listener(listener, identity)
+                            .map(Bound::NativeTls)
+                    }
+                    _ => Ok(Bound::Tcp(listener)),
+                }
+            }
+            #[cfg(unix)]
+            BoundSocket::Unix(listener) => {
+                if config.tls.is_some() {
+                    let msg = "TLS is not supported on Unix domain sockets";
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+                }
+
+                listener.set_nonblocking(true)?;
+                if let Some(path) = listener.local_addr()?.as_pathname() {
+                    config.address = format!("unix:{}", path.display());
+                }
+
+                Ok(Bound::Unix(tokio::net::UnixListener::from_std(listener)?, None))
+            }
+        }
+    }
+
+    fn local_addr(&self) -> Option<std::net::SocketAddr> {
+        match self {
+            Bound::Tcp(listener) => Listener::local_addr(listener),
+            #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+            Bound::Tls(listener) => Listener::local_addr(listener),
+            #[cfg(feature = "native-tls")]
+            Bound::NativeTls(listener) => Listener::local_addr(listener),
+            #[cfg(unix)]
+            Bound::Unix(listener, _) => Listener::local_addr(listener),
+        }
+    }
+}
+
+/// Reads the PROXY protocol header of the connections `listener` accepts,
+/// closing those that don't send one within `timeout`. Connections count
+/// against `max_connections` from the moment they're accepted, and those
+/// beyond it are closed before their header is read.
+fn proxy<L: Listener>(
+    listener: L,
+    load: &Load,
+    max_connections: Option<u32>,
+    timeout: Duration,
+) -> ProxyListener<L> {
+    let load = load.clone();
+    ProxyListener::new(listener, timeout).admit(move || match load.connection(max_connections) {
+        (ticket, false) => Some(Box::new(ticket) as Box<dyn Send>),
+        (_, true) => None,
+    })
+}
+
+/// A future, shared among listeners and connections, that resolves once.
+type Signal = Shared<BoxFuture<'static, ()>>;
+
+/// A signal that resolves `secs` seconds after `start` does, or never if `secs`
+/// is `None`.
+fn deadline(start: Signal, secs: Option<u32>) -> Signal {
+    async move {
+        start.await;
+        match secs {
+            Some(secs) => tokio::time::delay_for(Duration::from_secs(secs.into())).await,
+            None => futures::future::pending().await,
+        }
+    }.boxed().shared()
+}
+
+/// Spawns connections, and the streams within them, onto the tokio runtime,
+/// dropping those still running when the contained deadline passes.
+#[derive(Clone)]
+struct TokioExecutor(Signal);
+
+impl<Fut> hyper::Executor<Fut> for TokioExecutor
+    where Fut: Future + Send + 'static, Fut::Output: Send
+{
+    fn execute(&self, fut: Fut) {
+        let deadline = self.0.clone();
+        tokio::spawn(futures::future::select(Box::pin(fut), deadline));
+    }
+}
+
 impl Rocket {
     /// Create a new `Rocket` application using the configuration information in
     /// `Rocket.toml`. If the file does not exist or if there is an I/O error
@@ -574,16 +1339,107 @@ impl Rocket {
     /// # };
     /// ```
     pub fn ignite() -> Rocket {
-        Config::read()
+        Rocket::ignite_with(Secrets::new(), Args::new())
+    }
+
+    /// Like [`Rocket::ignite()`], but resolves secret references in the
+    /// configuration with the providers in `secrets`. See [`SecretProvider`]
+    /// for details on secret references.
+    ///
+    /// [`SecretProvider`]: crate::config::SecretProvider
+    ///
+    /// # Panics
+    ///
+    /// If there is an error reading configuration sources or a secret
+    /// reference cannot be resolved, this function prints a nice error message
+    /// and then exits the process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::Rocket;
+    /// use rocket::config::{EnvFile, Secrets};
+    ///
+    /// # if false {
+    /// let secrets = Secrets::new().provider(EnvFile::new(".env"));
+    /// Rocket::ignite_with_secrets(secrets)
+    /// # ;
+    /// # }
+    /// ```
+    pub fn ignite_with_secrets(secrets: Secrets) -> Rocket {
+        Rocket::ignite_with(secrets, Args::new())
+    }
+
+    /// Like [`Rocket::ignite()`], but overrides the configuration with the
+    /// command-line arguments of the process. See [`Args`] for the syntax of
+    /// the arguments.
+    ///
+    /// [`Args`]: crate::config::Args
+    ///
+    /// # Panics
+    ///
+    /// If there is an error reading configuration sources or the arguments
+    /// cannot be parsed, this function prints a nice error message and then
+    /// exits the process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # if false {
+    /// // ./app --profile staging --port 8080
+    /// rocket::Rocket::ignite_with_args()
+    /// # ;
+    /// # }
+    /// ```
+    pub fn ignite_with_args() -> Rocket {
+        match Args::from_env() {
+            Ok(args) => Rocket::ignite_with(Secrets::new(), args),
+            Err(e) => {
+                logger::init(logger::LoggingLevel::Debug);
+                e.pretty_print();
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// Like [`Rocket::ignite()`], but resolves secret references with the
+    /// providers in `secrets` and overrides the configuration with `args`.
+    ///
+    /// # Panics
+    ///
+    /// If there is an error reading configuration sources or a secret
+    /// reference cannot be resolved, this function prints a nice error message
+    /// and then exits the process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rocket::Rocket;
+    /// use rocket::config::{Args, EnvFile, Secrets};
+    ///
+    /// # if false {
+    /// let secrets = Secrets::new().provider(EnvFile::new(".env"));
+    /// let args = Args::parse(&["--port", "8080"]).unwrap();
+    /// Rocket::ignite_with(secrets, args)
+    /// # ;
+    /// # }
+    /// ```
+    pub fn ignite_with(secrets: Secrets, args: Args) -> Rocket {
+        FullConfig::find_config_path()
+            .and_then(|path| FullConfig::read_from_with(&path, &secrets))
             .or_else(|e| match e {
                 ConfigError::IoError => {
                     warn!("Failed to read 'Rocket.toml'. Using defaults.");
-                    Ok(FullConfig::env_default()?.take_active())
+                    FullConfig::env_default_with(&secrets)
                 }
-                ConfigError::NotFound => Ok(FullConfig::env_default()?.take_active()),
+                ConfigError::NotFound => FullConfig::env_default_with(&secrets),
                 _ => Err(e)
             })
-            .map(Rocket::configured)
+            .and_then(|mut config| {
+                config.override_from_args(&args, &secrets)?;
+                Ok(config.take_active())
+            })
+            .map(|config| Rocket::configured_with(config, Arc::new(secrets), args))
             .unwrap_or_else(|e: ConfigError| {
                 logger::init(logger::LoggingLevel::Debug);
                 e.pretty_print();
@@ -622,56 +1478,61 @@ impl Rocket {
 
     #[inline]
     fn configured(config: Config) -> Rocket {
+        Rocket::configured_with(config, Arc::new(Secrets::new()), Args::new())
+    }
+
+    fn configured_with(config: Config, secrets: Arc<Secrets>, args: Args) -> Rocket {
+        Panic::install_hook();
         if logger::try_init(config.log_level, false) {
             // Temporary weaken log level for launch info.
             logger::push_max_level(logger::LoggingLevel::Normal);
         }
 
-        launch_info!("{}Configured for {}.", Paint::emoji("🔧 "), config.environment);
-        launch_info_!("address: {}", Paint::default(&config.address).bold());
-        launch_info_!("port: {}", Paint::default(&config.port).bold());
-        launch_info_!("log: {}", Paint::default(config.log_level).bold());
-        launch_info_!("workers: {}", Paint::default(config.workers).bold());
-        launch_info_!("secret key: {}", Paint::default(&config.secret_key).bold());
-        launch_info_!("limits: {}", Paint::default(&config.limits).bold());
-
-        match config.keep_alive {
-            Some(v) => launch_info_!("keep-alive: {}", Paint::default(format!("{}s", v)).bold()),
-            None => launch_info_!("keep-alive: {}", Paint::default("disabled").bold()),
+        EffectiveConfig::new(&config, &secrets).pretty_print();
+        secrets.warn_unknown();
+        let tls_configured = config.tls.is_some() || config.listeners.iter().any(|l| l.tls.is_some());
+        if tls_configured && !cfg!(any(feature = "tls", feature = "native-tls")) {
+            error_!("tls is configured, but neither the tls nor the native-tls feature is enabled");
         }
 
-        let tls_configured = config.tls.is_some();
-        if tls_configured && cfg!(feature = "tls") {
-            launch_info_!("tls: {}", Paint::default("enabled").bold());
-        } else if tls_configured {
-            error_!("tls: {}", Paint::default("disabled").bold());
-            error_!("tls is configured, but the tls feature is disabled");
-        } else {
-            launch_info_!("tls: {}", Paint::default("disabled").bold());
+        if config.acme.is_some() && !cfg!(all(feature = "tls-acme", not(feature = "native-tls"))) {
+            error_!("acme is configured, but the tls-acme feature is disabled or overridden \
+                    by the native-tls feature");
         }
 
-        if config.secret_key.is_generated() && config.environment.is_prod() {
-            warn!("environment is 'production' but no `secret_key` is configured");
+        if config.compression.enabled && !cfg!(feature = "compression") {
+            error_!("compression is enabled, but the compression feature is disabled");
         }
 
-        for (name, value) in config.extras() {
-            launch_info_!("{} {}: {}",
-                          Paint::yellow("[extra]"), name,
-                          Paint::default(LoggedValue(value)).bold());
+        if config.compression.brotli && !cfg!(feature = "brotli") {
+            error_!("brotli compression is enabled, but the brotli feature is disabled");
         }
 
+        if config.secret_key.is_generated() && config.environment.is_prod() {
+            warn!("environment is 'production' but no `secret_key` is configured");
+        }
+
+        let load = Load::default();
         let managed_state = Container::new();
-        let (shutdown_sender, shutdown_receiver) = mpsc::channel(1);
+        managed_state.set(ServerStats::new(load.clone()));
+        let (shutdown_handle, shutdown_receiver) = Shutdown::new();
 
         Rocket {
+            live: LiveConfig::new(config.clone()),
+            secrets, args,
             config, managed_state,
-            shutdown_handle: Shutdown(shutdown_sender),
+            overridden_state: HashSet::new(),
+            shutdown_handle,
             manifest: vec![],
             router: Router::new(),
-            default_catcher: None,
-            catchers: HashMap::new(),
+            catchers: vec![],
             fairings: Fairings::new(),
             shutdown_receiver: Some(shutdown_receiver),
+            tasks: Mutex::new(vec![]),
+            tls_reloader: TlsReloader::default(),
+            load,
+            #[cfg(feature = "tower")]
+            layers: vec![],
         }
     }
 
@@ -768,7 +1629,58 @@ impl Rocket {
     /// ```
     #[inline]
     pub fn register(mut self, catchers: Vec<Catcher>) -> Self {
-        self.manifest.push(PreLaunchOp::Register(catchers));
+        self.manifest.push(PreLaunchOp::Register(Origin::dummy(), catchers));
+        self
+    }
+
+    /// Registers all of the catchers in the supplied vector scoped to `base`.
+    /// The catchers only handle errors for requests whose path begins with
+    /// `base` and take precedence over catchers registered with a shorter
+    /// base. See [catcher scoping](crate::Catcher#scoping) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base` is not a valid static path: a valid origin URI without
+    /// dynamic parameters or a query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::Request;
+    /// use rocket::response::content::Json;
+    ///
+    /// #[catch(404)]
+    /// fn not_found(req: &Request) -> String {
+    ///     format!("I couldn't find '{}'. Try something else?", req.uri())
+    /// }
+    ///
+    /// #[catch(404)]
+    /// fn api_not_found() -> Json<&'static str> {
+    ///     Json(r#"{ "error": "not found" }"#)
+    /// }
+    ///
+    /// #[launch]
+    /// fn rocket() -> rocket::Rocket {
+    ///     rocket::ignite()
+    ///         .register(catchers![not_found])
+    ///         .register_at("/api", catchers![api_not_found])
+    /// }
+    /// ```
+    #[inline]
+    pub fn register_at(mut self, base: &str, catchers: Vec<Catcher>) -> Self {
+        let base_uri = Origin::parse_owned(base.to_string())
+            .unwrap_or_else(|e| {
+                error!("Invalid catcher base URI: {}.", Paint::white(base));
+                panic!("Error: {}", e);
+            });
+
+        if base_uri.query().is_some() {
+            error!("Catcher base '{}' contains query string.", base);
+            panic!("Invalid catcher base.");
+        }
+
+        self.manifest.push(PreLaunchOp::Register(base_uri, catchers));
         self
     }
 
@@ -809,7 +1721,8 @@ impl Rocket {
     #[inline]
     pub fn manage<T: Send + Sync + 'static>(mut self, state: T) -> Self {
         let type_name = std::any::type_name::<T>();
-        self.manifest.push(PreLaunchOp::Manage(type_name, Box::new(move |managed| {
+        let type_id = TypeId::of::<T>();
+        self.manifest.push(PreLaunchOp::Manage(type_name, type_id, Box::new(move |managed| {
             if !managed.set::<T>(state) {
                 error!("State for type '{}' is already being managed!", type_name);
                 panic!("Aborting due to duplicately managed state.");
@@ -823,6 +1736,10 @@ impl Rocket {
     /// _attach_ fairing, it is run immediately. All other kinds of fairings
     /// will be executed at their appropriate time.
     ///
+    /// If the fairing is a [singleton](crate::fairing::Kind::Singleton) and a
+    /// fairing of the same type is already attached, this method has no
+    /// effect.
+    ///
     /// # Example
     ///
     /// ```rust,no_run
@@ -840,7 +1757,72 @@ impl Rocket {
     /// ```
     #[inline]
     pub fn attach<F: Fairing>(mut self, fairing: F) -> Self {
-        self.manifest.push(PreLaunchOp::Attach(Box::new(fairing)));
+        self.manifest.push(PreLaunchOp::Attach(TypeId::of::<F>(), Box::new(fairing)));
+        self
+    }
+
+    /// Attaches a background task, spawned when `self` is launched and
+    /// awaited when it shuts down.
+    ///
+    /// `task` is called with a [`Shutdown`] handle, which resolves when a
+    /// shutdown begins, once launch fairings have run, and the future it
+    /// returns is spawned. A task should return soon after the handle
+    /// resolves. During a shutdown, Rocket waits for every task to finish
+    /// before running shutdown fairings; tasks still running at the end of the
+    /// `mercy` period of the [`server` table](crate::config::ServerConfig) are
+    /// abandoned. If the server fails, a shutdown is requested so that tasks
+    /// stop. A task may also call [`Shutdown::shutdown()`] to shut the server
+    /// down itself.
+    ///
+    /// Tasks run only when `self` is launched; they are not spawned for a
+    /// [local `Client`](crate::local).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::tokio::time::{self, Duration};
+    ///
+    /// let rocket = rocket::ignite()
+    ///     .attach_task(|shutdown| async move {
+    ///         let mut interval = time::interval(Duration::from_secs(60));
+    ///         rocket::tokio::select! {
+    ///             _ = async { loop { interval.tick().await; /* ... */ } } => {}
+    ///             _ = shutdown => println!("Stopping the background worker."),
+    ///         }
+    ///     });
+    /// ```
+    pub fn attach_task<F, Fut>(self, task: F) -> Self
+        where F: FnOnce(Shutdown) -> Fut + Send + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        self.attach_orbit_task(move |orbit| task(orbit.shutdown()))
+    }
+
+    /// Like [`Rocket::attach_task()`], but `task` is called with an [`Orbit`]
+    /// handle to the launched application, giving it access to managed state.
+    /// The task's [`Shutdown`] handle is available via the `Orbit`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Hits(AtomicUsize);
+    ///
+    /// let rocket = rocket::ignite()
+    ///     .manage(Hits(AtomicUsize::new(0)))
+    ///     .attach_orbit_task(|orbit| async move {
+    ///         orbit.shutdown().await;
+    ///         let hits = orbit.state::<Hits>().unwrap();
+    ///         println!("Served {} hits.", hits.0.load(Ordering::Relaxed));
+    ///     });
+    /// ```
+    pub fn attach_orbit_task<F, Fut>(mut self, task: F) -> Self
+        where F: FnOnce(Orbit) -> Fut + Send + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        let task: shutdown::Task = Box::new(move |orbit| task(orbit).boxed());
+        self.tasks.get_mut().expect("tasks lock").push(task);
         self
     }
 
@@ -905,6 +1887,24 @@ impl Rocket {
         self.inspect().await.config()
     }
 
+    /// Returns the effective configuration: every parameter with its value,
+    /// secrets redacted, and where the value came from.
+    ///
+    /// This function is equivalent to `.inspect().await.effective_config()`
+    /// and is provided as a convenience.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # rocket::async_test(async {
+    /// let mut rocket = rocket::ignite();
+    /// println!("{}", rocket.effective_config().await);
+    /// # });
+    /// ```
+    pub async fn effective_config(&mut self) -> EffectiveConfig {
+        self.inspect().await.effective_config()
+    }
+
     /// Returns a handle which can be used to gracefully terminate this instance
     /// of Rocket. In routes, use the [`Shutdown`] request guard.
     ///
@@ -963,6 +1963,13 @@ impl Rocket {
     ///
     /// [`Error`]: crate::error::Error
     ///
+    /// # Socket Activation
+    ///
+    /// If the process was passed a socket by its service manager, as indicated
+    /// by `LISTEN_FDS`, the socket is served as if by [`Rocket::launch_on()`]
+    /// in place of binding to `address` and `port`. See
+    /// [`BoundSocket::from_env()`] for details.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -974,18 +1981,98 @@ impl Rocket {
     /// # }
     /// }
     /// ```
-    pub async fn launch(mut self) -> Result<(), crate::error::Error> {
-        use std::net::ToSocketAddrs;
+    pub async fn launch(self) -> Result<(), crate::error::Error> {
+        match BoundSocket::from_env() {
+            Some(Ok(socket)) => self.launch_with(Some(socket)).await,
+            Some(Err(e)) => {
+                let kind = LaunchErrorKind::Bind(e);
+                Err(crate::error::Error::Launch(LaunchError::new(kind)))
+            }
+            None => self.launch_with(None).await,
+        }
+    }
+
+    /// Like [`Rocket::launch()`], but serves the already-bound `socket` instead
+    /// of binding to the configured `address` and `port`. Any additional
+    /// `listeners` are bound as usual. See [`BoundSocket`] for details.
+    ///
+    /// If TLS is configured, it is served on a TCP `socket`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[rocket::main]
+    /// async fn main() {
+    /// # if false {
+    ///     // Bound while privileged, served after privileges are dropped.
+    ///     let socket = std::net::TcpListener::bind("0.0.0.0:80").unwrap();
+    ///     let result = rocket::ignite().launch_on(socket).await;
+    ///     assert!(result.is_ok());
+    /// # }
+    /// }
+    /// ```
+    pub async fn launch_on<S: Into<BoundSocket>>(self, socket: S) -> Result<(), crate::error::Error> {
+        self.launch_with(Some(socket.into())).await
+    }
+
+    async fn launch_with(mut self, mut socket: Option<BoundSocket>) -> Result<(), crate::error::Error> {
         use futures::future::Either;
         use crate::error::Error::Launch;
 
+        let bind_error = |e| Launch(LaunchError::new(LaunchErrorKind::Bind(e)));
+        if self.config.acme.is_some() && self.config.tls.is_some() {
+            let msg = "`acme` and `tls` can't both configure the default listener";
+            return Err(bind_error(io::Error::new(io::ErrorKind::InvalidInput, msg)));
+        }
+
+        // Answer ACME `http-01` challenges before any application route.
+        #[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+        let acme = crate::acme::Acme::new(&self.config);
+        #[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+        if let Some(routes) = acme.as_ref().map(|acme| acme.routes()).filter(|r| !r.is_empty()) {
+            self = self.mount("/.well-known/acme-challenge", routes);
+        }
+
         self.prelaunch_check().await.map_err(crate::error::Error::Launch)?;
 
-        let full_addr = format!("{}:{}", self.config.address, self.config.port);
-        let addr = match full_addr.to_socket_addrs() {
-            Ok(mut addrs) => addrs.next().expect(">= 1 socket addr"),
-            Err(e) => return Err(Launch(e.into())),
-        };
+        // Bind every listener before serving any of them. The pre-bound
+        // `socket`, if any, is served as the default listener.
+        let mut bound = vec![];
+        for mut config in self.config.listener_configs() {
+            let listener = match socket.take() {
+                Some(socket) => {
+                    let listener = Bound::adopt(socket, &mut config, &self.config)
+                        .map_err(bind_error)?;
+
+                    self.config.address = config.address.clone();
+                    listener
+                }
+                None => Bound::bind(&config, &self.config).await.map_err(Launch)?,
+            };
+
+            // Serve the certificates obtained via ACME on the default listener.
+            #[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+            let listener = match (listener, &acme) {
+                (Bound::Tcp(listener), Some(acme)) if config.acme => {
+                    Bound::Tls(acme.tls_listener(listener, &self.config.protocols))
+                }
+                (_, Some(_)) if config.acme => {
+                    let msg = "ACME is not supported on Unix domain sockets";
+                    return Err(bind_error(io::Error::new(io::ErrorKind::InvalidInput, msg)));
+                }
+                (listener, _) => listener,
+            };
+
+            bound.push((config, listener));
+        }
+
+        // Obtain and renew certificates in the background until shutdown.
+        #[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+        let acme = acme.map(|acme| {
+            let (renewal, handle) = futures::future::abortable(acme.run());
+            tokio::spawn(renewal);
+            handle
+        });
 
         // FIXME: Make `ctrlc` a known `Rocket` config option.
         // If `ctrl-c` shutdown is enabled, we `select` on `the ctrl-c` signal
@@ -996,33 +2083,11 @@ impl Rocket {
             _ => tokio::signal::ctrl_c().boxed(),
         };
 
-        let server = {
-            macro_rules! listen_on {
-                ($expr:expr) => {{
-                    let listener = match $expr {
-                        Ok(ok) => ok,
-                        Err(err) => return Err(Launch(LaunchError::new(LaunchErrorKind::Bind(err))))
-                    };
-                    self.listen_on(listener)
-                }};
-            }
-
-            #[cfg(feature = "tls")] {
-                if let Some(tls) = self.config.tls.clone() {
-                    listen_on!(crate::http::tls::bind_tls(addr, tls.certs, tls.key).await).boxed()
-                } else {
-                    listen_on!(crate::http::private::bind_tcp(addr).await).boxed()
-                }
-            }
-            #[cfg(not(feature = "tls"))] {
-                listen_on!(crate::http::private::bind_tcp(addr).await).boxed()
-            }
-        };
-
-        match futures::future::select(shutdown_signal, server).await {
+        let server = self.listen_on(bound).boxed();
+        let result = match futures::future::select(shutdown_signal, server).await {
             Either::Left((Ok(()), server)) => {
                 // Ctrl-was pressed. Signal shutdown, wait for the server.
-                shutdown_handle.shutdown();
+                shutdown_handle.shutdown_with(ShutdownReason::Signal);
                 server.await
             }
             Either::Left((Err(err), server)) => {
@@ -1033,7 +2098,14 @@ impl Rocket {
             }
             // Server shut down before Ctrl-C; return the result.
             Either::Right((result, _)) => result,
+        };
+
+        #[cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+        if let Some(renewal) = acme {
+            renewal.abort();
         }
+
+        result
     }
 }
 
@@ -1045,13 +2117,14 @@ impl std::fmt::Debug for PreLaunchOp {
                 .field(&origin)
                 .field(&routes)
                 .finish(),
-            Register(catchers) => f.debug_tuple("PreLaunchOp::Register")
+            Register(base, catchers) => f.debug_tuple("PreLaunchOp::Register")
+                .field(&base)
                 .field(&catchers)
                 .finish(),
-            Manage(name, _) => f.debug_tuple("PreLaunchOp::Manage")
+            Manage(name, ..) => f.debug_tuple("PreLaunchOp::Manage")
                 .field(&name)
                 .finish(),
-            Attach(fairing) => f.debug_tuple("PreLaunchOp::Attach")
+            Attach(_, fairing) => f.debug_tuple("PreLaunchOp::Attach")
                 .field(&fairing.info())
                 .finish()
         }
@@ -1066,6 +2139,14 @@ impl std::ops::Deref for Cargo {
     }
 }
 
+impl std::ops::Deref for Orbit {
+    type Target = Cargo;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.cargo()
+    }
+}
+
 impl Cargo {
     /// Returns an iterator over all of the routes mounted on this instance of
     /// Rocket.
@@ -1126,7 +2207,13 @@ impl Cargo {
         self.0.managed_state.try_get()
     }
 
-    /// Returns the active configuration.
+    /// Returns the configuration the application launched with.
+    ///
+    /// The returned `Config` is not updated when the configuration is
+    /// reloaded, so it goes stale after a reload. To read reloadable
+    /// parameters, `log`, `limits`, and extras, use
+    /// [`Cargo::config_snapshot()`] instead, retrieving a new snapshot each
+    /// time the current values are needed.
     ///
     /// # Example
     ///
@@ -1147,4 +2234,177 @@ impl Cargo {
     pub fn config(&self) -> &Config {
         &self.0.config
     }
+
+    /// Returns the current snapshot of the configuration. Unlike
+    /// [`Cargo::config()`], which always returns the configuration the
+    /// application launched with, the snapshot reflects the latest reload.
+    /// The snapshot itself never changes: a later reload is only visible in a
+    /// snapshot retrieved after it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # rocket::async_test(async {
+    /// let mut rocket = rocket::ignite();
+    /// let snapshot = rocket.inspect().await.config_snapshot();
+    /// assert_eq!(snapshot.version(), 0);
+    /// # });
+    /// ```
+    pub fn config_snapshot(&self) -> ConfigSnapshot {
+        self.0.live.current()
+    }
+
+    /// Returns the effective configuration of the running application: every
+    /// parameter with its value, secrets redacted, and where the value came
+    /// from. See [`EffectiveConfig`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// rocket::ignite()
+    ///     .attach(AdHoc::on_launch("Config Dump", |cargo| {
+    ///         println!("{}", cargo.effective_config());
+    ///     }))
+    /// # ;
+    /// ```
+    pub fn effective_config(&self) -> EffectiveConfig {
+        EffectiveConfig::new(&self.config_snapshot(), &self.0.secrets)
+    }
+
+    /// Returns a handle to Rocket's logger, which can change the log level
+    /// while the application is running.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::LoggingLevel;
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// rocket::ignite()
+    ///     .attach(AdHoc::on_launch("Quiet", |cargo| {
+    ///         cargo.logger().set_level(LoggingLevel::Critical);
+    ///     }))
+    /// # ;
+    /// ```
+    #[inline(always)]
+    pub fn logger(&self) -> Logger {
+        Logger::new()
+    }
+
+    /// Returns a handle to reload the TLS certificates of the running
+    /// application. See [`TlsReloader`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// rocket::ignite()
+    ///     .attach(AdHoc::on_launch("TLS Reloader", |cargo| {
+    ///         let tls = cargo.tls_reloader();
+    ///         rocket::tokio::spawn(async move {
+    ///             loop {
+    ///                 // Pick up renewed certificates once a day.
+    ///                 rocket::tokio::time::delay_for(std::time::Duration::from_secs(86400)).await;
+    ///                 if let Err(e) = tls.reload().await {
+    ///                     eprintln!("TLS reload failed: {}", e);
+    ///                 }
+    ///             }
+    ///         });
+    ///     }))
+    /// # ;
+    /// ```
+    #[inline(always)]
+    pub fn tls_reloader(&self) -> TlsReloader {
+        self.0.tls_reloader.clone()
+    }
+
+    /// Returns the current load of the running application: its open
+    /// connections, requests in flight, and shed requests. See [`Load`] for
+    /// details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    ///
+    /// rocket::ignite()
+    ///     .attach(AdHoc::on_launch("Load Reporter", |cargo| {
+    ///         let load = cargo.load();
+    ///         rocket::tokio::spawn(async move {
+    ///             loop {
+    ///                 rocket::tokio::time::delay_for(std::time::Duration::from_secs(60)).await;
+    ///                 println!("{} requests in flight", load.in_flight());
+    ///             }
+    ///         });
+    ///     }))
+    /// # ;
+    /// ```
+    #[inline(always)]
+    pub fn load(&self) -> Load {
+        self.0.load.clone()
+    }
+
+    /// Rereads the configuration file and applies its reloadable parameters,
+    /// `log`, `limits`, and all extras, to the running application. Changes to
+    /// other parameters take effect after a restart. Returns the previous and
+    /// new configurations.
+    ///
+    /// The configuration is reloaded automatically when the extra `reload` is
+    /// `true`; see [reloading](crate::config#reloading).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration was not read from a file or if the
+    /// file cannot be read or parsed. The current configuration then remains
+    /// active.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # rocket::async_test(async {
+    /// let mut rocket = rocket::ignite();
+    /// match rocket.inspect().await.reload_config().await {
+    ///     Ok(update) => println!("configuration v{}", update.current.version()),
+    ///     Err(e) => println!("reload failed: {}", e),
+    /// }
+    /// # });
+    /// ```
+    pub async fn reload_config(&self) -> Result<ConfigUpdated, ConfigError> {
+        let config = crate::config::reread(&self.0.config, &self.0.secrets, &self.0.args)?;
+        Ok(self.update_config(config).await)
+    }
+
+    /// Applies the reloadable parameters of `config`, `log`, `limits`, and all
+    /// extras, to the running application and runs the
+    /// [`on_config_update`](crate::fairing::Fairing::on_config_update())
+    /// callbacks of all fairings. Changes to other parameters take effect after
+    /// a restart. Returns the previous and new configurations.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::config::{Config, Environment};
+    ///
+    /// # rocket::async_test(async {
+    /// let mut rocket = rocket::ignite();
+    /// let cargo = rocket.inspect().await;
+    ///
+    /// let config = Config::build(Environment::Development)
+    ///     .extra("motd", "Back soon!")
+    ///     .unwrap();
+    ///
+    /// let update = cargo.update_config(config).await;
+    /// assert_eq!(update.current.version(), 1);
+    /// assert_eq!(update.current.get_str("motd"), Ok("Back soon!"));
+    /// assert!(update.extra_changed("motd"));
+    /// # });
+    /// ```
+    pub async fn update_config(&self, config: Config) -> ConfigUpdated {
+        let update = self.0.live.update(config);
+        self.0.fairings.handle_config_update(self, &update).await;
+        update
+    }
 }
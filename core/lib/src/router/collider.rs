@@ -1,6 +1,8 @@
+use std::fmt;
+
 use super::Route;
 
-use crate::http::MediaType;
+use crate::http::{Method, MediaType};
 use crate::http::route::Kind;
 use crate::request::Request;
 
@@ -46,6 +48,106 @@ impl Route {
     }
 }
 
+/// The single reason a route failed to match a request it otherwise would
+/// have, or that it matched but forwarded. See [`Route::near_miss()`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Mismatch {
+    /// The route's method, `expected`, differs from the request's, `found`.
+    Method { expected: Method, found: Method },
+    /// The route's format doesn't match the request's, if any.
+    Format { expected: MediaType, found: Option<MediaType> },
+    /// The static path segment at `index` differs.
+    Segment { index: usize, expected: String, found: String },
+    /// A static query segment is missing from the request.
+    Query,
+    /// The route matched but forwarded the request.
+    Forwarded { rank: isize },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Method { expected, found } => {
+                write!(f, "method is {}, not {}", expected, found)
+            }
+            Mismatch::Format { expected, found: Some(found) } => {
+                write!(f, "format is {}, not {}", expected, found)
+            }
+            Mismatch::Format { expected, found: None } => {
+                write!(f, "format is {}, but the request has none", expected)
+            }
+            Mismatch::Segment { index, expected, found } => {
+                write!(f, "segment {} is '{}', not '{}'", index + 1, expected, found)
+            }
+            Mismatch::Query => write!(f, "a static query segment is missing"),
+            Mismatch::Forwarded { rank } => write!(f, "matched but forwarded (rank {})", rank),
+        }
+    }
+}
+
+impl Route {
+    /// Determines if this route _nearly_ matches the given request: every
+    /// criterion in [`Route::matches()`] holds except for exactly one. Returns
+    /// the reason the route doesn't match, or `Mismatch::Forwarded` if it does.
+    /// Returns `None` if the route is not a near miss.
+    ///
+    /// The method is considered to be the only mismatch if the path and query
+    /// match, regardless of the format, since format matching depends on the
+    /// method.
+    pub(crate) fn near_miss(&self, req: &Request<'_>) -> Option<Mismatch> {
+        let method = self.method == req.method();
+        let path = paths_match(self, req);
+        let query = queries_match(self, req);
+        let format = formats_match(self, req);
+
+        match (method, path, query, format) {
+            (true, true, true, true) => Some(Mismatch::Forwarded { rank: self.rank }),
+            (false, true, true, _) => Some(Mismatch::Method {
+                expected: self.method,
+                found: req.method(),
+            }),
+            (true, true, true, false) => Some(Mismatch::Format {
+                expected: self.format.clone()?,
+                found: req.format().cloned(),
+            }),
+            (true, true, false, true) => Some(Mismatch::Query),
+            (true, false, true, true) => path_near_miss(self, req),
+            _ => None
+        }
+    }
+}
+
+/// Returns a `Mismatch::Segment` if the route's path and the request's path
+/// have the same number of segments and differ in exactly one static segment.
+fn path_near_miss(route: &Route, request: &Request<'_>) -> Option<Mismatch> {
+    let route_segments = &route.metadata.path_segments;
+    if route_segments.len() != request.state.path_segments.len() {
+        return None;
+    }
+
+    let mut mismatch = None;
+    let request_segments = request.raw_path_segments();
+    for (i, (route_seg, req_seg)) in route_segments.iter().zip(request_segments).enumerate() {
+        match route_seg.kind {
+            Kind::Multi => return None,
+            Kind::Static if &*route_seg.string != req_seg.as_str() => {
+                if mismatch.is_some() {
+                    return None;
+                }
+
+                mismatch = Some(Mismatch::Segment {
+                    index: i,
+                    expected: route_seg.string.to_string(),
+                    found: req_seg.to_string(),
+                });
+            }
+            _ => continue,
+        }
+    }
+
+    mismatch
+}
+
 fn paths_collide(route: &Route, other: &Route) -> bool {
     let a_segments = &route.metadata.path_segments;
     let b_segments = &other.metadata.path_segments;
@@ -8,6 +8,10 @@ use crate::http::Method;
 use crate::handler::dummy;
 
 pub use self::route::Route;
+pub(crate) use self::collider::Mismatch;
+
+/// The maximum number of near misses returned by [`Router::near_misses()`].
+const MAX_NEAR_MISSES: usize = 5;
 
 // type Selector = (Method, usize);
 type Selector = Method;
@@ -44,6 +48,23 @@ impl Router {
         matches
     }
 
+    /// Returns up to `MAX_NEAR_MISSES` routes that nearly match `req`, along
+    /// with the reason each doesn't. Routes that matched but forwarded come
+    /// first, followed by the others in order of rank.
+    pub(crate) fn near_misses<'b>(&'b self, req: &Request<'_>) -> Vec<(&'b Route, Mismatch)> {
+        let mut near_misses: Vec<_> = self.routes()
+            .filter_map(|route| route.near_miss(req).map(|why| (route, why)))
+            .collect();
+
+        near_misses.sort_by_key(|(route, why)| match why {
+            Mismatch::Forwarded { .. } => (0, route.rank),
+            _ => (1, route.rank)
+        });
+
+        near_misses.truncate(MAX_NEAR_MISSES);
+        near_misses
+    }
+
     pub(crate) fn collisions(&mut self) -> Result<(), Vec<(Route, Route)>> {
         let mut collisions = vec![];
         for routes in self.routes.values_mut() {
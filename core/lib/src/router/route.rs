@@ -1,15 +1,23 @@
 use std::fmt::{self, Display};
 use std::convert::From;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
 
 use yansi::Paint;
 
 use crate::codegen::StaticRouteInfo;
+use crate::catcher::Catcher;
 use crate::handler::Handler;
 use crate::http::{Method, MediaType};
 use crate::http::route::{RouteSegment, Kind};
-use crate::error::RouteUriError;
+use crate::error::{RouteUriError, ZeroConcurrencyLimit};
 use crate::http::ext::IntoOwned;
 use crate::http::uri::{Origin, Path, Query};
+use crate::data::{ByteUnit, Limits, ToByteUnit};
+use crate::cache::CachePolicy;
+use crate::openapi::{Operation, SchemaGenerator};
 
 /// A route: a method, its handler, path, rank, and format/media type.
 #[derive(Clone)]
@@ -33,6 +41,37 @@ pub struct Route {
     pub format: Option<MediaType>,
     /// Cached metadata that aids in routing later.
     pub(crate) metadata: Metadata,
+    /// Catchers scoped to this route.
+    pub(crate) catchers: Vec<Catcher>,
+    /// Data limits that override the configured limits for this route.
+    pub(crate) limits: Vec<(String, ByteUnit)>,
+    /// The policy for caching this route's responses, if any.
+    pub(crate) cache: Option<CachePolicy>,
+    /// Whether the handler looks up cached responses itself, once the route's
+    /// request guards succeed, as handlers generated by the route attributes
+    /// do.
+    pub(crate) guarded_cache: bool,
+    /// The limit on concurrent executions of this route's handler, if any.
+    pub(crate) concurrency: Option<ConcurrencyLimit>,
+    /// The function describing this route, if it's described.
+    pub(crate) openapi: Option<Arc<dyn Fn(&mut SchemaGenerator) -> Operation + Send + Sync>>,
+}
+
+/// A limit on the number of concurrent executions of a route's handler. Clones
+/// share the limit.
+#[derive(Debug, Clone)]
+pub(crate) struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+    queue: Duration,
+}
+
+impl ConcurrencyLimit {
+    /// Waits up to the queueing time for an execution slot. Returns `None` if
+    /// none was freed up in time.
+    pub async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        tokio::time::timeout(self.queue, self.semaphore.acquire()).await.ok()
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -183,6 +222,12 @@ impl Route {
             base: Origin::dummy(),
             handler: Box::new(handler),
             metadata: Metadata::default(),
+            catchers: vec![],
+            limits: vec![],
+            cache: None,
+            guarded_cache: false,
+            concurrency: None,
+            openapi: None,
             method, rank,
         };
 
@@ -274,6 +319,214 @@ impl Route {
         self.update_metadata()?;
         Ok(self)
     }
+
+    /// Registers `catchers` on this route. The catchers only handle errors
+    /// that arise while this route processes a request, from a failing guard or
+    /// responder, and take precedence over all other catchers. The `base` of
+    /// each catcher is ignored. See [catcher scoping](crate::Catcher#scoping)
+    /// for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// #[get("/upload")]
+    /// fn upload() { /* .. */ }
+    ///
+    /// #[catch(413)]
+    /// fn too_large() -> &'static str {
+    ///     "Uploads are limited to 1MiB."
+    /// }
+    ///
+    /// let route = routes![upload].remove(0).register(catchers![too_large]);
+    ///
+    /// let rocket = rocket::ignite().mount("/", vec![route]);
+    /// ```
+    pub fn register(mut self, catchers: Vec<Catcher>) -> Self {
+        self.catchers.extend(catchers);
+        self
+    }
+
+    /// Returns the catchers registered on this route.
+    #[inline]
+    pub fn catchers(&self) -> &[Catcher] {
+        &self.catchers
+    }
+
+    /// Sets the data limit `name` to `limit` for requests handled by this
+    /// route, overriding the configured limit. This allows a single route,
+    /// such as an upload endpoint, to accept more data than the rest of the
+    /// application. Routes generated by the route attributes set their
+    /// overrides via the `limits` parameter:
+    /// `#[post("/upload", data = "<file>", limits(file = "1GiB"))]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Route;
+    /// use rocket::http::Method;
+    /// use rocket::data::ToByteUnit;
+    /// # use rocket::handler::dummy as handler;
+    ///
+    /// let upload = Route::new(Method::Post, "/upload", handler)
+    ///     .limit("file", 1.gibibytes());
+    ///
+    /// let limits = upload.limits(&Default::default());
+    /// assert_eq!(limits.get("file"), Some(1.gibibytes()));
+    /// ```
+    pub fn limit<S: Into<String>>(mut self, name: S, limit: ByteUnit) -> Self {
+        self.limits.push((name.into(), limit));
+        self
+    }
+
+    /// Returns `limits` with this route's overrides applied.
+    pub fn limits(&self, limits: &Limits) -> Limits {
+        self.limits.iter()
+            .fold(limits.clone(), |limits, (name, limit)| limits.limit(name.as_str(), *limit))
+    }
+
+    /// Caches the responses of this route according to `policy` when a
+    /// [`Cache`](crate::cache::Cache) is attached. Routes generated by the
+    /// route attributes set their policy via the `#[cache]` attribute:
+    /// `#[get("/items")] #[cache(ttl = "60s")]`.
+    ///
+    /// Routes generated by the route attributes serve cached responses only
+    /// once their request guards succeed. Other handlers are bypassed entirely
+    /// when a cached response is served, so they must not be used for
+    /// responses that depend on who is asking.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use rocket::Route;
+    /// use rocket::cache::CachePolicy;
+    /// use rocket::http::Method;
+    /// # use rocket::handler::dummy as handler;
+    ///
+    /// let policy = CachePolicy::new(Duration::from_secs(60)).vary("Accept");
+    /// let route = Route::new(Method::Get, "/items", handler).cache(policy);
+    /// ```
+    pub fn cache(mut self, policy: CachePolicy) -> Self {
+        self.cache = Some(policy);
+        self
+    }
+
+    /// The time a request waits for an execution slot of a route with a
+    /// concurrency limit by default: one second.
+    pub const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Limits the number of requests this route's handler executes at once to
+    /// `limit`. A request beyond the limit waits for an execution to finish
+    /// for up to [`Route::DEFAULT_QUEUE_TIMEOUT`], or the time set with
+    /// [`Route::queue_timeout()`], then fails with `503 Service Unavailable`.
+    /// The limit is shared by all clones of this route, including those
+    /// mounted at several bases. Routes generated by the route attributes set
+    /// their limit via the `max_concurrency` parameter:
+    /// `#[post("/report", max_concurrency = 4)]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Route;
+    /// use rocket::http::Method;
+    /// # use rocket::handler::dummy as handler;
+    ///
+    /// let report = Route::new(Method::Post, "/report", handler)
+    ///     .max_concurrency(4)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(report.concurrency_limit(), Some(4));
+    ///
+    /// let never = Route::new(Method::Post, "/report", handler).max_concurrency(0);
+    /// assert!(never.is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `limit` is `0`: such a route could never be called.
+    pub fn max_concurrency(mut self, limit: usize) -> Result<Self, ZeroConcurrencyLimit> {
+        if limit == 0 {
+            return Err(ZeroConcurrencyLimit);
+        }
+
+        let queue = self.concurrency.as_ref()
+            .map_or(Route::DEFAULT_QUEUE_TIMEOUT, |concurrency| concurrency.queue);
+
+        let semaphore = Arc::new(Semaphore::new(limit));
+        self.concurrency = Some(ConcurrencyLimit { semaphore, limit, queue });
+        Ok(self)
+    }
+
+    /// Sets the time a request waits for an execution slot of a route with a
+    /// [concurrency limit](Route::max_concurrency()) before it fails. Has no
+    /// effect on routes without a limit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use rocket::Route;
+    /// use rocket::http::Method;
+    /// # use rocket::handler::dummy as handler;
+    ///
+    /// let report = Route::new(Method::Post, "/report", handler)
+    ///     .max_concurrency(4)
+    ///     .unwrap()
+    ///     .queue_timeout(Duration::from_millis(100));
+    /// ```
+    pub fn queue_timeout(mut self, timeout: Duration) -> Self {
+        if let Some(ref mut concurrency) = self.concurrency {
+            concurrency.queue = timeout;
+        }
+
+        self
+    }
+
+    /// Returns the limit on concurrent executions of this route's handler, if
+    /// any.
+    pub fn concurrency_limit(&self) -> Option<usize> {
+        self.concurrency.as_ref().map(|concurrency| concurrency.limit)
+    }
+
+    /// Describes this route with the [`Operation`] returned by `describe`,
+    /// which is called whenever the route's description is requested with
+    /// [`Route::operation()`]. Routes generated by the route attributes are
+    /// described by applying the [`openapi`](macro@crate::openapi) attribute
+    /// below the route attribute.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::Route;
+    /// use rocket::http::{Method, Status};
+    /// use rocket::openapi::{Operation, Response, SchemaGenerator};
+    /// # use rocket::handler::dummy as handler;
+    ///
+    /// let route = Route::new(Method::Get, "/health", handler)
+    ///     .openapi(|_: &mut SchemaGenerator| Operation {
+    ///         summary: Some("Checks the application's health.".into()),
+    ///         responses: vec![Response::new(Status::Ok)],
+    ///         ..Default::default()
+    ///     });
+    ///
+    /// let operation = route.operation(&mut SchemaGenerator::new()).unwrap();
+    /// assert_eq!(operation.responses[0].status, Some(Status::Ok));
+    /// ```
+    pub fn openapi<F>(mut self, describe: F) -> Self
+        where F: Fn(&mut SchemaGenerator) -> Operation + Send + Sync + 'static
+    {
+        self.openapi = Some(Arc::new(describe));
+        self
+    }
+
+    /// Returns the description of this route, generating the schemas it
+    /// refers to with `gen`, or `None` if the route isn't described.
+    pub fn operation(&self, gen: &mut SchemaGenerator) -> Option<Operation> {
+        self.openapi.as_ref().map(|describe| describe(gen))
+    }
 }
 
 impl fmt::Display for Route {
@@ -312,6 +565,10 @@ impl fmt::Debug for Route {
             .field("rank", &self.rank)
             .field("format", &self.format)
             .field("metadata", &self.metadata)
+            .field("catchers", &self.catchers)
+            .field("cache", &self.cache)
+            .field("concurrency", &self.concurrency)
+            .field("openapi", &self.openapi.is_some())
             .finish()
     }
 }
@@ -322,11 +579,29 @@ impl From<&StaticRouteInfo> for Route {
         // This should never panic since `info.path` is statically checked.
         let mut route = Route::new(info.method, info.path, info.handler);
         route.format = info.format.clone();
+        route.guarded_cache = true;
         route.name = Some(info.name);
         if let Some(rank) = info.rank {
             route.rank = rank;
         }
 
+        for &(name, bytes) in info.limits {
+            route = route.limit(name, bytes.bytes());
+        }
+
+        if let Some(ref cache) = info.cache {
+            route = route.cache(cache.into());
+        }
+
+        // Codegen rejects a limit of `0`, so this should never panic.
+        if let Some(limit) = info.max_concurrency {
+            route = route.max_concurrency(limit).expect("positive concurrency limit");
+        }
+
+        if let Some(describe) = info.openapi {
+            route = route.openapi(describe);
+        }
+
         route
     }
 }
@@ -1,5 +1,14 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::future::{self, BoxFuture, Either, FutureExt, Shared};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::Orbit;
 use crate::request::{FromRequest, Outcome, Request};
-use tokio::sync::mpsc;
 
 /// A request guard to gracefully shutdown a Rocket server.
 ///
@@ -9,6 +18,47 @@ use tokio::sync::mpsc;
 ///
 /// [`Rocket::launch()`]: crate::Rocket::launch()
 ///
+/// # Draining
+///
+/// Once a shutdown begins, Rocket stops accepting connections, answers
+/// requests on open connections with `Connection: close`, and sends HTTP/2
+/// clients a `GOAWAY`. By default, it then waits for requests in flight to
+/// complete and for connections to close. If the `grace` period of the
+/// [`server` table](crate::config::ServerConfig) is set, requests still in
+/// flight at its end are answered with `503 Service Unavailable` and their
+/// handlers are cancelled; if the `mercy` period is set, connections still
+/// open a further `mercy` seconds later are closed forcibly.
+///
+/// A `Shutdown` is also a future that resolves when a shutdown begins. Long
+/// running handlers and streaming responses, such as server-sent events, can
+/// await it to finish early instead of being cut off:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::Shutdown;
+/// use rocket::tokio::time::{delay_for, Duration};
+///
+/// #[get("/wait")]
+/// async fn wait(shutdown: Shutdown) -> &'static str {
+///     rocket::tokio::select! {
+///         _ = delay_for(Duration::from_secs(60)) => "Done waiting.",
+///         _ = shutdown => "Shutting down; stopped waiting.",
+///     }
+/// }
+/// ```
+///
+/// # Reasons and Restarts
+///
+/// A shutdown may be given a [`ShutdownReason`] via
+/// [`Shutdown::shutdown_with()`]; a shutdown triggered by `Ctrl-C` has the
+/// reason [`ShutdownReason::Signal`]. The reason of the first shutdown request
+/// is available from any `Shutdown` handle via [`Shutdown::reason()`], both
+/// during the shutdown and after `launch()` returns. Since every listener is
+/// closed by the time `launch()` returns, an application can restart in
+/// process by igniting a new `Rocket`, which reads its configuration anew, when
+/// the reason is [`ShutdownReason::Restart`]. See [`ShutdownReason`] for an
+/// example.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -34,19 +84,135 @@ use tokio::sync::mpsc;
 /// }
 /// ```
 #[derive(Debug, Clone)]
-pub struct Shutdown(pub(crate) mpsc::Sender<()>);
+pub struct Shutdown {
+    sender: mpsc::Sender<()>,
+    signal: Shared<BoxFuture<'static, ()>>,
+    reason: Arc<Mutex<Option<ShutdownReason>>>,
+}
+
+/// Why a server was shut down, as retrieved by [`Shutdown::reason()`].
+///
+/// # Example
+///
+/// An application that restarts when `/restart` is visited, picking up any
+/// changes to `Rocket.toml`, and exits otherwise:
+///
+/// ```rust,no_run
+/// # #[macro_use] extern crate rocket;
+/// use rocket::{Shutdown, ShutdownReason};
+///
+/// #[post("/restart")]
+/// fn restart(shutdown: Shutdown) -> &'static str {
+///     shutdown.shutdown_with(ShutdownReason::Restart);
+///     "Restarting..."
+/// }
+///
+/// #[rocket::main]
+/// async fn main() {
+///     loop {
+///         let rocket = rocket::ignite().mount("/", routes![restart]);
+///         let shutdown = rocket.shutdown();
+///         rocket.launch().await.expect("server failed unexpectedly");
+///
+///         match shutdown.reason() {
+///             Some(ShutdownReason::Restart) => continue,
+///             _ => break,
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// A signal, such as `Ctrl-C`, was received.
+    Signal,
+    /// Shutdown was requested via [`Shutdown::shutdown()`].
+    Requested,
+    /// The application asked to be restarted with a new `Rocket` instance.
+    Restart,
+    /// The application encountered an error it can't recover from.
+    Fatal(String),
+}
+
+/// The receiving ends of a [`Shutdown`]: shutdown requests, and the trigger
+/// that notifies every `Shutdown` that a shutdown has begun.
+pub(crate) struct Receiver {
+    pub requests: mpsc::Receiver<()>,
+    pub trigger: oneshot::Sender<()>,
+}
 
 impl Shutdown {
+    /// Creates a new `Shutdown` handle and its receiving ends. If the trigger
+    /// is dropped without being fired, the shutdown is considered begun.
+    pub(crate) fn new() -> (Shutdown, Receiver) {
+        let (sender, requests) = mpsc::channel(1);
+        let (trigger, signal) = oneshot::channel();
+        let signal = signal.map(|_| ()).boxed().shared();
+        let reason = Arc::new(Mutex::new(None));
+        (Shutdown { sender, signal, reason }, Receiver { requests, trigger })
+    }
+
     /// Notify Rocket to shut down gracefully. This function returns
-    /// immediately; pending requests will continue to run until completion
-    /// before the actual shutdown occurs.
+    /// immediately. Pending requests continue to run until completion before
+    /// the actual shutdown occurs unless the `grace` and `mercy` periods of
+    /// the [`server` table](crate::config::ServerConfig) cut them short; see
+    /// [draining](#draining).
+    ///
+    /// The shutdown's reason is [`ShutdownReason::Requested`].
     #[inline]
-    pub fn shutdown(mut self) {
+    pub fn shutdown(self) {
+        self.shutdown_with(ShutdownReason::Requested);
+    }
+
+    /// Notify Rocket to shut down gracefully for `reason`, like
+    /// [`Shutdown::shutdown()`]. If a shutdown was already requested, its
+    /// reason is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[macro_use] extern crate rocket;
+    /// use rocket::{Shutdown, ShutdownReason};
+    ///
+    /// #[post("/fail")]
+    /// fn fail(shutdown: Shutdown) {
+    ///     shutdown.shutdown_with(ShutdownReason::Fatal("database is gone".into()));
+    /// }
+    /// ```
+    pub fn shutdown_with(mut self, reason: ShutdownReason) {
+        self.reason.lock().expect("shutdown reason lock").get_or_insert(reason);
+
         // Intentionally ignore any error, as the only scenarios this can happen
         // is sending too many shutdown requests or we're already shut down.
-        let _ = self.0.try_send(());
+        let _ = self.sender.try_send(());
         info!("Server shutdown requested, waiting for all pending requests to finish.");
     }
+
+    /// Returns the reason of the first shutdown request, or `None` if a
+    /// shutdown hasn't been requested.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::ShutdownReason;
+    ///
+    /// let shutdown = rocket::ignite().shutdown();
+    /// assert_eq!(shutdown.reason(), None);
+    ///
+    /// shutdown.clone().shutdown_with(ShutdownReason::Restart);
+    /// assert_eq!(shutdown.reason(), Some(ShutdownReason::Restart));
+    /// ```
+    pub fn reason(&self) -> Option<ShutdownReason> {
+        self.reason.lock().expect("shutdown reason lock").clone()
+    }
+}
+
+impl Future for Shutdown {
+    type Output = ();
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.signal).poll(cx)
+    }
 }
 
 #[crate::async_trait]
@@ -58,3 +224,36 @@ impl<'a, 'r> FromRequest<'a, 'r> for Shutdown {
         Outcome::Success(request.state.shutdown.clone())
     }
 }
+
+/// A background task attached via [`Rocket::attach_orbit_task()`],
+/// type-erased.
+///
+/// [`Rocket::attach_orbit_task()`]: crate::Rocket::attach_orbit_task()
+pub(crate) type Task = Box<dyn FnOnce(Orbit) -> BoxFuture<'static, ()> + Send>;
+
+/// Spawns `tasks`, giving each a clone of `orbit`.
+pub(crate) fn spawn_tasks(tasks: Vec<Task>, orbit: Orbit) -> Vec<JoinHandle<()>> {
+    tasks.into_iter()
+        .map(|task| tokio::spawn(task(orbit.clone())))
+        .collect()
+}
+
+/// Waits for the spawned `tasks` to finish, abandoning any still running when
+/// `deadline` resolves.
+pub(crate) async fn join_tasks<F>(tasks: Vec<JoinHandle<()>>, deadline: F)
+    where F: Future<Output = ()> + Send
+{
+    if tasks.is_empty() {
+        return;
+    }
+
+    info!("Waiting for {} background task(s) to finish.", tasks.len());
+    match future::select(future::join_all(tasks), deadline.boxed()).await {
+        Either::Left((results, _)) => {
+            for error in results.into_iter().filter_map(Result::err) {
+                error_!("A background task failed: {}", error);
+            }
+        }
+        Either::Right(_) => warn_!("Abandoning background tasks that are still running."),
+    }
+}
@@ -0,0 +1,122 @@
+use std::io;
+use std::net::TcpListener;
+
+#[cfg(unix)] use std::os::unix::net::UnixListener;
+
+/// A socket that was bound before launch, to be served via
+/// [`Rocket::launch_on()`](crate::Rocket::launch_on()).
+///
+/// Serving a pre-bound socket lets an application listen on a privileged port
+/// without running as root and be restarted without refusing connections: a
+/// supervisor such as systemd binds the socket once and hands it to every new
+/// instance of the application. A `BoundSocket` is created from a standard
+/// library listener via `From` or from the sockets passed by the service
+/// manager via [`BoundSocket::from_env()`], which [`Rocket::launch()`] serves
+/// automatically.
+///
+/// [`Rocket::launch()`]: crate::Rocket::launch()
+///
+/// # Example
+///
+/// ```rust
+/// # rocket::async_test(async {
+/// # if false {
+/// // Bound while privileged, served after privileges are dropped.
+/// let socket = std::net::TcpListener::bind("0.0.0.0:80").unwrap();
+/// let result = rocket::ignite().launch_on(socket).await;
+/// # }
+/// # });
+/// ```
+#[derive(Debug)]
+pub enum BoundSocket {
+    /// A TCP socket.
+    Tcp(TcpListener),
+    /// A Unix domain socket.
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl BoundSocket {
+    /// Returns the first socket passed to this process by its service manager
+    /// using the systemd socket activation protocol, or `None` if no socket was
+    /// passed.
+    ///
+    /// The protocol passes sockets as the file descriptors starting at `3`,
+    /// with their number in the `LISTEN_FDS` environment variable and the ID of
+    /// the process they are intended for in `LISTEN_PID`. Sockets intended for
+    /// another process are ignored. The variables are removed so that child
+    /// processes don't inherit them. If more than one socket was passed, only
+    /// the first is returned; a warning is logged for the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variables are malformed or if the passed file
+    /// descriptor is not a listening TCP or Unix domain socket.
+    ///
+    /// Always returns `None` on platforms other than Unix.
+    pub fn from_env() -> Option<io::Result<BoundSocket>> {
+        #[cfg(unix)] {
+            let (pid, fds) = (std::env::var("LISTEN_PID"), std::env::var("LISTEN_FDS"));
+            std::env::remove_var("LISTEN_PID");
+            std::env::remove_var("LISTEN_FDS");
+            std::env::remove_var("LISTEN_FDNAMES");
+
+            let (pid, fds) = (pid.ok()?, fds.ok()?);
+            if pid.parse::<u32>().ok() != Some(std::process::id()) {
+                return None;
+            }
+
+            match fds.parse::<u32>() {
+                Ok(0) => None,
+                Ok(n) => {
+                    if n > 1 {
+                        warn!("Passed {} sockets via LISTEN_FDS; serving only the first.", n);
+                    }
+
+                    Some(unsafe { BoundSocket::from_fd(3) })
+                }
+                Err(_) => {
+                    let msg = format!("LISTEN_FDS is not a number: {:?}", fds);
+                    Some(Err(io::Error::new(io::ErrorKind::InvalidInput, msg)))
+                }
+            }
+        }
+
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Takes ownership of the listening socket `fd`.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor owned by no other value.
+    #[cfg(unix)]
+    unsafe fn from_fd(fd: std::os::unix::io::RawFd) -> io::Result<BoundSocket> {
+        use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+        // A Unix domain socket has no IP address; tell the two apart by that.
+        let tcp = TcpListener::from_raw_fd(fd);
+        match tcp.local_addr() {
+            Ok(_) => Ok(BoundSocket::Tcp(tcp)),
+            Err(_) => {
+                let unix = UnixListener::from_raw_fd(tcp.into_raw_fd());
+                unix.local_addr()?;
+                Ok(BoundSocket::Unix(unix))
+            }
+        }
+    }
+}
+
+impl From<TcpListener> for BoundSocket {
+    fn from(listener: TcpListener) -> Self {
+        BoundSocket::Tcp(listener)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for BoundSocket {
+    fn from(listener: UnixListener) -> Self {
+        BoundSocket::Unix(listener)
+    }
+}
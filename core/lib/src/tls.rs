@@ -0,0 +1,127 @@
+use std::io;
+
+use crate::config::ConfigError;
+use crate::request::{self, FromRequest, Request};
+
+#[cfg(all(feature = "tls", not(feature = "native-tls")))]
+use {
+    std::sync::{Arc, Mutex},
+    crate::config::{Config, ListenerConfig},
+    crate::http::tls::TlsHandle,
+};
+
+/// A handle to reload the TLS certificates and keys of a running server.
+///
+/// [`TlsReloader::reload()`] rereads the certificate chain, private key, and,
+/// if configured, the client certificate authorities and revocation list of
+/// every TLS listener from the files they were configured with, then serves
+/// new connections with them. Connections that are already open are
+/// unaffected. This allows certificates renewed by, for instance, Let's
+/// Encrypt to be deployed without restarting the application.
+///
+/// A `TlsReloader` is retrieved via [`Cargo::tls_reloader()`] or as a request
+/// guard. It is cheap to clone. Before launch, and if no listener serves
+/// TLS, reloading does nothing. Only the `tls` backend supports reloading;
+/// with the `native-tls` feature enabled, reloading fails.
+///
+/// [`Cargo::tls_reloader()`]: crate::Cargo::tls_reloader()
+///
+/// # Example
+///
+/// Reload the certificates when an administrator requests it, for instance
+/// from a certificate renewal hook:
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::TlsReloader;
+///
+/// #[post("/admin/reload-tls")]
+/// async fn reload_tls(tls: TlsReloader) -> String {
+///     match tls.reload().await {
+///         Ok(()) => "Reloaded.".into(),
+///         Err(e) => format!("Reload failed: {}", e),
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct TlsReloader {
+    /// The configuration and TLS listeners being served, once launched.
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    served: Arc<Mutex<Option<(Config, Vec<(Arc<ListenerConfig>, TlsHandle)>)>>>,
+}
+
+impl TlsReloader {
+    /// Records the TLS listeners `listeners`, configured by `config`, that
+    /// are being served.
+    #[cfg(all(feature = "tls", not(feature = "native-tls")))]
+    pub(crate) fn serve(&self, config: Config, listeners: Vec<(Arc<ListenerConfig>, TlsHandle)>) {
+        *self.served.lock().unwrap_or_else(|e| e.into_inner()) = Some((config, listeners));
+    }
+
+    /// Rereads the TLS files of every TLS listener and serves new connections
+    /// with them. The files are read on a blocking thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Config::set_tls()`] and [`Config::set_mtls()`]
+    /// if reading any file fails. Returns a `BadType` error if a private key
+    /// is unusable. The previous certificates of every listener then remain
+    /// in use. With the `native-tls` feature enabled, always returns an `Io`
+    /// error, as reloading is unsupported.
+    ///
+    /// [`Config::set_tls()`]: crate::Config::set_tls()
+    /// [`Config::set_mtls()`]: crate::Config::set_mtls()
+    pub async fn reload(&self) -> Result<(), ConfigError> {
+        if cfg!(feature = "native-tls") {
+            let error = "reloading TLS certificates is not supported by the native-tls backend";
+            return Err(ConfigError::Io(io::Error::new(io::ErrorKind::Other, error), "tls"));
+        }
+
+        #[cfg(all(feature = "tls", not(feature = "native-tls")))] {
+            let served = self.served.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            let (config, listeners) = match served {
+                Some(served) => served,
+                None => return Ok(()),
+            };
+
+            // Prepare every listener's certificates before replacing any.
+            let prepared = tokio::task::spawn_blocking(move || {
+                let mut prepared = vec![];
+                for (listener, handle) in listeners {
+                    let tls = match listener.tls {
+                        Some(ref tls) => config.reload_tls("tls", tls)?,
+                        None => continue,
+                    };
+
+                    let server_config = handle.prepare(tls.certs, tls.key, tls.client_auth)
+                        .map_err(|_| config.bad_type("tls", "a private key for other certificates",
+                                                     "the private key for `tls.certs`"))?;
+
+                    prepared.push((listener, handle, server_config));
+                }
+
+                Ok::<_, ConfigError>(prepared)
+            });
+
+            let prepared = prepared.await.map_err(|e| {
+                ConfigError::Io(io::Error::new(io::ErrorKind::Other, e.to_string()), "tls")
+            })??;
+
+            for (listener, handle, server_config) in prepared {
+                handle.replace(server_config);
+                info_!("Reloaded the TLS certificates of the {} listener.", listener.name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[crate::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for TlsReloader {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(req.state.tls_reloader.clone())
+    }
+}
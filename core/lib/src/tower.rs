@@ -0,0 +1,311 @@
+//! Interoperability with the `tower` middleware ecosystem.
+//!
+//! This module provides two adapters:
+//!
+//!   * [`Rocket::layer()`] attaches a `tower` [`Layer`], such as a timeout,
+//!     load-shedding, or tracing layer, to a Rocket application. The layer
+//!     wraps all of Rocket's request handling when the application is
+//!     launched.
+//!   * [`RocketService`] exposes Rocket's request handling as a `tower`
+//!     [`Service`], so that a Rocket application can be embedded in other
+//!     `tower`-based servers and stacks.
+//!
+//! Both adapters operate on `hyper` requests and responses, re-exported in
+//! [`rocket::http::hyper`](crate::http::hyper).
+//!
+//! # Enabling
+//!
+//! This module is only available when the `tower` feature is enabled. Enable
+//! it in `Cargo.toml` as follows:
+//!
+//! ```toml
+//! [dependencies.rocket]
+//! version = "0.5.0-dev"
+//! features = ["tower"]
+//! ```
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::{self, BoxFuture};
+
+use crate::rocket::{Rocket, hyper_service_fn};
+use crate::error::LaunchError;
+use crate::http::hyper;
+
+pub use tower_layer::Layer;
+pub use tower_service::Service;
+
+/// The type of requests handled by Rocket's `tower` services.
+pub type HttpRequest = hyper::Request<hyper::Body>;
+
+/// The type of responses returned by Rocket's `tower` services.
+pub type HttpResponse = hyper::Response<hyper::Body>;
+
+/// A type-erased error returned by layered services.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The remote address of the client that issued a request.
+///
+/// When Rocket serves a request through its layers, it inserts a `RemoteAddr`
+/// into the request's extensions. [`RocketService`] reads it back to determine
+/// the [`Request::remote()`](crate::Request::remote()) address; embedders
+/// should insert it into requests they pass to a `RocketService`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// Rocket's request handling as a `tower` [`Service`].
+///
+/// A `RocketService` routes requests to the mounted routes and catchers, runs
+/// fairings, and returns the response, just as a launched Rocket application
+/// does. It is cheap to clone; clones share the same application.
+///
+/// # Example
+///
+/// ```rust
+/// # #[macro_use] extern crate rocket;
+/// use rocket::tower::{RocketService, Service};
+/// use rocket::http::hyper;
+///
+/// #[get("/")]
+/// fn hello() -> &'static str {
+///     "Hello, world!"
+/// }
+///
+/// # rocket::async_test(async {
+/// let rocket = rocket::ignite().mount("/", routes![hello]);
+/// let mut service = RocketService::new(rocket).await.expect("valid application");
+///
+/// let request = hyper::Request::get("/").body(hyper::Body::empty()).unwrap();
+/// let response = service.call(request).await.unwrap();
+/// assert_eq!(response.status(), 200);
+/// # })
+/// ```
+#[derive(Clone)]
+pub struct RocketService {
+    rocket: Arc<Rocket>,
+}
+
+impl RocketService {
+    /// Prepares `rocket` to handle requests and returns a service that
+    /// dispatches to it. This performs the same checks as
+    /// [`Rocket::launch()`] and runs launch fairings, but does not bind to a
+    /// socket.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checks fail: if routes collide or if an attach
+    /// fairing fails.
+    pub async fn new(mut rocket: Rocket) -> Result<RocketService, LaunchError> {
+        rocket.prelaunch_check().await?;
+        rocket.managed_state.freeze();
+        rocket.fairings.handle_launch(rocket.cargo());
+        Ok(RocketService { rocket: Arc::new(rocket) })
+    }
+}
+
+impl Service<HttpRequest> for RocketService {
+    type Response = HttpResponse;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, io::Result<HttpResponse>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let remote = request.extensions().get::<RemoteAddr>()
+            .map_or_else(|| ([0, 0, 0, 0], 0).into(), |addr| addr.0);
+
+        Box::pin(hyper_service_fn(self.rocket.clone(), remote, request))
+    }
+}
+
+/// The type-erased service that [`Rocket::layer()`] layers wrap.
+///
+/// A `BoxService` is cloned, as the service it erases is, for every request.
+pub struct BoxService {
+    inner: Box<dyn CloneService>,
+}
+
+/// A type-erased service that can be cloned.
+trait CloneService: Service<
+    HttpRequest,
+    Response = HttpResponse,
+    Error = BoxError,
+    Future = BoxFuture<'static, Result<HttpResponse, BoxError>>
+> + Send {
+    fn clone_box(&self) -> Box<dyn CloneService>;
+}
+
+/// Adapts a service to `BoxService`'s error and future types.
+struct Boxed<S>(S);
+
+impl<S> Service<HttpRequest> for Boxed<S>
+    where S: Service<HttpRequest, Response = HttpResponse>,
+          S::Error: Into<BoxError>,
+          S::Future: Send + 'static
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.0.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let future = self.0.call(request);
+        Box::pin(async move { future.await.map_err(Into::into) })
+    }
+}
+
+impl<S> CloneService for Boxed<S>
+    where S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + 'static,
+          S::Error: Into<BoxError>,
+          S::Future: Send + 'static
+{
+    fn clone_box(&self) -> Box<dyn CloneService> {
+        Box::new(Boxed(self.0.clone()))
+    }
+}
+
+impl BoxService {
+    fn new<S>(service: S) -> BoxService
+        where S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + 'static,
+              S::Error: Into<BoxError>,
+              S::Future: Send + 'static
+    {
+        BoxService { inner: Box::new(Boxed(service)) }
+    }
+}
+
+impl Clone for BoxService {
+    fn clone(&self) -> BoxService {
+        BoxService { inner: self.inner.clone_box() }
+    }
+}
+
+impl Service<HttpRequest> for BoxService {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        self.inner.call(request)
+    }
+}
+
+/// A layer, type-erased, as stored by `Rocket`.
+pub(crate) type BoxLayer = Box<dyn Fn(BoxService) -> BoxService + Send + Sync>;
+
+impl Rocket {
+    /// Attaches the `tower` [`Layer`] `layer`, which wraps all of Rocket's
+    /// request handling when `self` is launched.
+    ///
+    /// Layers are applied in the order they are attached: the first attached
+    /// layer is the outermost and sees requests first. The layered service is
+    /// built once, at launch, and cloned for each request, so layered services
+    /// must implement `Clone`. As is conventional for `tower` services, clones
+    /// share state that should apply across the entire server, such as the
+    /// permits of a concurrency limit; requests aren't serialized through a
+    /// single instance.
+    ///
+    /// If a layered service fails, either when becoming ready or when handling
+    /// a request, the error is logged and a `503 Service Unavailable` response
+    /// is returned. This is typically the result of middleware such as a
+    /// timeout or load shedding rejecting a request.
+    ///
+    /// Layers apply only to requests served by a launched application; they do
+    /// not apply to requests dispatched via a [local
+    /// `Client`](crate::local) or to a [`RocketService`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::task::{Context, Poll};
+    /// use rocket::tower::{Layer, Service, HttpRequest};
+    ///
+    /// /// A layer that adds a header to every request.
+    /// struct Tag;
+    ///
+    /// #[derive(Clone)]
+    /// struct Tagged<S>(S);
+    ///
+    /// impl<S> Layer<S> for Tag {
+    ///     type Service = Tagged<S>;
+    ///
+    ///     fn layer(&self, inner: S) -> Tagged<S> {
+    ///         Tagged(inner)
+    ///     }
+    /// }
+    ///
+    /// impl<S: Service<HttpRequest>> Service<HttpRequest> for Tagged<S> {
+    ///     type Response = S::Response;
+    ///     type Error = S::Error;
+    ///     type Future = S::Future;
+    ///
+    ///     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+    ///         self.0.poll_ready(cx)
+    ///     }
+    ///
+    ///     fn call(&mut self, mut req: HttpRequest) -> S::Future {
+    ///         req.headers_mut().insert("x-tagged", "yes".parse().unwrap());
+    ///         self.0.call(req)
+    ///     }
+    /// }
+    ///
+    /// let rocket = rocket::ignite().layer(Tag);
+    /// ```
+    pub fn layer<L>(mut self, layer: L) -> Self
+        where L: Layer<BoxService> + Send + Sync + 'static,
+              L::Service: Service<HttpRequest, Response = HttpResponse> + Clone + Send + 'static,
+              <L::Service as Service<HttpRequest>>::Error: Into<BoxError>,
+              <L::Service as Service<HttpRequest>>::Future: Send + 'static
+    {
+        self.layers.push(Box::new(move |service| BoxService::new(layer.layer(service))));
+        self
+    }
+}
+
+/// Wraps `rocket`'s request handling in its layers, if it has any.
+pub(crate) fn layered(rocket: &Arc<Rocket>) -> Option<BoxService> {
+    if rocket.layers.is_empty() {
+        return None;
+    }
+
+    let base = BoxService::new(RocketService { rocket: rocket.clone() });
+    Some(rocket.layers.iter().rev().fold(base, |service, layer| layer(service)))
+}
+
+/// Handles `request`, from `remote`, with a clone of the layered `service`.
+pub(crate) fn call_layered(
+    service: &BoxService,
+    remote: SocketAddr,
+    mut request: HttpRequest
+) -> BoxFuture<'static, io::Result<HttpResponse>> {
+    let mut service = service.clone();
+    request.extensions_mut().insert(RemoteAddr(remote));
+    Box::pin(async move {
+        if let Err(e) = future::poll_fn(|cx| service.poll_ready(cx)).await {
+            return Ok(unavailable(e));
+        }
+
+        Ok(service.call(request).await.unwrap_or_else(unavailable))
+    })
+}
+
+/// Logs `error` and returns a `503 Service Unavailable` response.
+fn unavailable(error: BoxError) -> HttpResponse {
+    error_!("Layered service failed: {}", error);
+    warn_!("Responding with 503 Service Unavailable.");
+    let mut response = hyper::Response::new(hyper::Body::empty());
+    *response.status_mut() = hyper::StatusCode::SERVICE_UNAVAILABLE;
+    response
+}
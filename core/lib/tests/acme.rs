@@ -0,0 +1,265 @@
+#![cfg(all(feature = "tls-acme", not(feature = "native-tls")))]
+
+#[macro_use] extern crate rocket;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::time::Duration;
+
+use rocket::{Shutdown, State};
+use rocket::config::{AcmeChallenge, AcmeConfig, Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::http::{Header, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::{content, status::Created};
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+use serde_json::{json, Value};
+use tokio_rustls::{rustls, webpki, TlsConnector};
+
+/// The token of the mock authority's only `http-01` challenge.
+const TOKEN: &str = "mock-token";
+
+/// A mock ACME certificate authority that issues certificates signed by `ca`.
+struct Authority {
+    ca: rcgen::Certificate,
+    ca_der: Vec<u8>,
+    /// The port of the application's plaintext listener, once launched.
+    app_port: AtomicU16,
+    validated: AtomicBool,
+    chain: Mutex<Option<String>>,
+}
+
+impl Authority {
+    fn new() -> Authority {
+        let mut params = rcgen::CertificateParams::new(vec![]);
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = rcgen::Certificate::from_params(params).unwrap();
+        let ca_der = ca.serialize_der().unwrap();
+        let (app_port, validated) = (AtomicU16::new(0), AtomicBool::new(false));
+        Authority { ca, ca_der, app_port, validated, chain: Mutex::new(None) }
+    }
+}
+
+/// The base URL of the mock authority, from the request's `Host`.
+struct Base(String);
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Base {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match req.headers().get_one("Host") {
+            Some(host) => request::Outcome::Success(Base(format!("http://{}", host))),
+            None => request::Outcome::Failure((Status::BadRequest, ())),
+        }
+    }
+}
+
+/// The decoded payload of the JWS `jws`.
+fn payload(jws: &str) -> Value {
+    let jws: Value = serde_json::from_str(jws).unwrap();
+    let payload = jws["payload"].as_str().unwrap();
+    let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).unwrap();
+    serde_json::from_slice(&payload).unwrap()
+}
+
+fn json(value: Value) -> content::Json<String> {
+    content::Json(value.to_string())
+}
+
+#[get("/directory")]
+fn directory(base: Base) -> content::Json<String> {
+    json(json!({
+        "newNonce": format!("{}/nonce", base.0),
+        "newAccount": format!("{}/account", base.0),
+        "newOrder": format!("{}/order", base.0),
+    }))
+}
+
+#[get("/nonce")]
+fn nonce() { }
+
+#[post("/account")]
+fn account(base: Base) -> Created<content::Json<String>> {
+    Created::new(format!("{}/account/1", base.0)).body(json(json!({ "status": "valid" })))
+}
+
+#[post("/order")]
+fn new_order(base: Base) -> Created<content::Json<String>> {
+    Created::new(format!("{}/order/1", base.0)).body(json(json!({
+        "status": "pending",
+        "authorizations": [format!("{}/authz/1", base.0)],
+        "finalize": format!("{}/finalize/1", base.0),
+    })))
+}
+
+#[post("/authz/1")]
+fn authorization(base: Base, ca: State<'_, Arc<Authority>>) -> content::Json<String> {
+    let status = if ca.validated.load(Ordering::SeqCst) { "valid" } else { "pending" };
+    json(json!({
+        "status": status,
+        "identifier": { "type": "dns", "value": "localhost" },
+        "challenges": [{
+            "type": "http-01",
+            "url": format!("{}/challenge/1", base.0),
+            "token": TOKEN,
+        }]
+    }))
+}
+
+/// Validates the challenge by requesting the key authorization from the
+/// application's plaintext listener.
+#[post("/challenge/1")]
+async fn challenge(ca: State<'_, Arc<Authority>>) -> content::Json<String> {
+    let mut port = ca.app_port.load(Ordering::SeqCst);
+    while port == 0 {
+        rocket::tokio::time::delay_for(Duration::from_millis(10)).await;
+        port = ca.app_port.load(Ordering::SeqCst);
+    }
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("GET /.well-known/acme-challenge/{} HTTP/1.1\r\n\
+        Host: localhost\r\nConnection: close\r\n\r\n", TOKEN);
+
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let body = response.rsplit("\r\n\r\n").next().unwrap();
+    if response.starts_with("HTTP/1.1 200") && body.starts_with(&format!("{}.", TOKEN)) {
+        ca.validated.store(true, Ordering::SeqCst);
+    }
+
+    json(json!({ "status": "processing" }))
+}
+
+/// Issues the certificate requested by the CSR in the JWS `jws`.
+#[post("/finalize/1", data = "<jws>")]
+fn finalize(jws: String, ca: State<'_, Arc<Authority>>) -> content::Json<String> {
+    let csr = payload(&jws)["csr"].as_str().unwrap().to_string();
+    let csr = base64::decode_config(&csr, base64::URL_SAFE_NO_PAD).unwrap();
+    let mut csr = rcgen::CertificateSigningRequest::from_der(&csr).unwrap();
+    csr.params.subject_alt_names = vec![rcgen::SanType::DnsName("localhost".into())];
+
+    let cert = csr.serialize_pem_with_signer(&ca.ca).unwrap();
+    *ca.chain.lock().unwrap() = Some(cert);
+    json(json!({ "status": "processing" }))
+}
+
+#[post("/order/1")]
+fn order(base: Base, ca: State<'_, Arc<Authority>>) -> content::Json<String> {
+    if ca.chain.lock().unwrap().is_none() {
+        return json(json!({ "status": "pending" }));
+    }
+
+    json(json!({ "status": "valid", "certificate": format!("{}/cert/1", base.0) }))
+}
+
+#[post("/cert/1")]
+fn certificate(ca: State<'_, Arc<Authority>>) -> Option<String> {
+    ca.chain.lock().unwrap().clone()
+}
+
+/// Launches `rocket`, returning its configuration and a handle to shut it down.
+async fn launch(rocket: rocket::Rocket) -> (Config, Shutdown) {
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket.attach(AdHoc::on_launch("Config", move |cargo| {
+        let tx = tx.lock().unwrap().take().unwrap();
+        let _ = tx.send((cargo.config().clone(), cargo.shutdown()));
+    }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+/// Launches the mock authority, returning its shared state, the URL of its
+/// directory, and a handle to shut it down.
+async fn launch_authority() -> (Arc<Authority>, String, Shutdown) {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    let authority = Arc::new(Authority::new());
+    let rocket = rocket::custom(config)
+        .manage(authority.clone())
+        .mount("/", routes![
+            directory, nonce, account, new_order, authorization, challenge,
+            finalize, order, certificate
+        ])
+        .attach(AdHoc::on_response("Nonce", |_, res| Box::pin(async move {
+            res.set_header(Header::new("Replay-Nonce", "nonce"));
+        })));
+
+    let (config, shutdown) = launch(rocket).await;
+    (authority, format!("http://127.0.0.1:{}/directory", config.port), shutdown)
+}
+
+/// Performs a TLS handshake with the server at `port`, trusting only `ca`.
+async fn handshake(port: u16, ca: &[u8]) -> std::io::Result<()> {
+    let mut config = rustls::ClientConfig::new();
+    config.root_store.add(&rustls::Certificate(ca.to_vec())).unwrap();
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    TlsConnector::from(Arc::new(config)).connect(name, stream).await.map(|_| ())
+}
+
+#[rocket::async_test]
+async fn certificates_are_obtained_and_cached() {
+    let (authority, directory, authority_shutdown) = launch_authority().await;
+
+    let cache = std::env::temp_dir().join(format!("rocket-acme-{}", std::process::id()));
+    let acme = AcmeConfig::new(vec!["localhost"])
+        .directory(directory)
+        .challenge(AcmeChallenge::Http01)
+        .cache(cache.to_str().unwrap())
+        .accept_terms(true);
+
+    let mut config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .acme(acme)
+        .unwrap();
+
+    config.add_listener("http", "127.0.0.1", 0).unwrap();
+    let (config, shutdown) = launch(rocket::custom(config)).await;
+    authority.app_port.store(config.listeners[0].port, Ordering::SeqCst);
+
+    // Handshakes fail until the certificate is obtained.
+    let mut attempts = 0;
+    while handshake(config.port, &authority.ca_der).await.is_err() {
+        attempts += 1;
+        assert!(attempts < 300, "no certificate was obtained");
+        rocket::tokio::time::delay_for(Duration::from_millis(100)).await;
+    }
+
+    assert!(authority.validated.load(Ordering::SeqCst));
+    shutdown.shutdown();
+    authority_shutdown.shutdown();
+
+    // The account key, certificate, and key are cached, with no temporary
+    // files left behind, and keys are only readable by their owner.
+    let files: Vec<PathBuf> = std::fs::read_dir(&cache).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+
+    assert_eq!(files.len(), 3);
+    assert!(files.iter().all(|path| path.extension().unwrap() != "tmp"));
+
+    #[cfg(unix)] {
+        use std::os::unix::fs::PermissionsExt;
+
+        for key in files.iter().filter(|path| path.extension().unwrap() == "der") {
+            let mode = std::fs::metadata(key).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    std::fs::remove_dir_all(&cache).unwrap();
+}
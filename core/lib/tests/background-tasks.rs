@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rocket::ShutdownReason;
+use rocket::config::{Config, Environment, ServerConfig};
+use rocket::fairing::AdHoc;
+use rocket::tokio::time::{delay_for, timeout};
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+fn config(server: ServerConfig) -> Config {
+    Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .server(server)
+        .unwrap()
+}
+
+fn push(log: &Log, entry: &'static str) {
+    log.lock().unwrap().push(entry);
+}
+
+#[rocket::async_test]
+async fn tasks_are_awaited_before_shutdown_fairings() {
+    let log: Log = Arc::new(Mutex::new(vec![]));
+    let (task_log, launch_log, shutdown_log) = (log.clone(), log.clone(), log.clone());
+    let rocket = rocket::custom(config(ServerConfig::default()))
+        .attach_task(move |shutdown| async move {
+            push(&task_log, "started");
+            shutdown.await;
+            delay_for(Duration::from_millis(100)).await;
+            push(&task_log, "stopped");
+        })
+        .attach(AdHoc::on_launch("Launch", move |cargo| {
+            push(&launch_log, "launch");
+            cargo.shutdown().shutdown();
+        }))
+        .attach(AdHoc::on_shutdown("Shutdown", move |_| Box::pin(async move {
+            push(&shutdown_log, "shutdown");
+        })));
+
+    assert!(rocket.launch().await.is_ok());
+    assert_eq!(*log.lock().unwrap(), vec!["launch", "started", "stopped", "shutdown"]);
+}
+
+#[rocket::async_test]
+async fn tasks_can_shut_down_the_server() {
+    let rocket = rocket::custom(config(ServerConfig::default()))
+        .attach_task(|shutdown| async move {
+            shutdown.shutdown_with(ShutdownReason::Fatal("worker failed".into()));
+        });
+
+    let shutdown = rocket.shutdown();
+    let launched = timeout(Duration::from_secs(5), rocket.launch()).await;
+    assert!(launched.expect("server shut down").is_ok());
+    assert_eq!(shutdown.reason(), Some(ShutdownReason::Fatal("worker failed".into())));
+}
+
+#[rocket::async_test]
+async fn stuck_tasks_are_abandoned_after_mercy() {
+    let server = ServerConfig { grace: Some(0), mercy: Some(1), ..ServerConfig::default() };
+    let rocket = rocket::custom(config(server))
+        .attach_task(|_| async move {
+            delay_for(Duration::from_secs(60)).await;
+        })
+        .attach(AdHoc::on_launch("Stop", |cargo| cargo.shutdown().shutdown()));
+
+    let launched = timeout(Duration::from_secs(10), rocket.launch()).await;
+    assert!(launched.expect("stuck task was abandoned").is_ok());
+}
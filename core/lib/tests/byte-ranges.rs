@@ -0,0 +1,61 @@
+#[macro_use] extern crate rocket;
+
+use std::io::Cursor;
+
+use rocket::response::ByteRanges;
+use rocket::local::blocking::Client;
+use rocket::http::{Status, ContentType, Header};
+
+const DATA: &[u8] = b"0123456789";
+
+#[get("/")]
+fn data() -> ByteRanges<Cursor<&'static [u8]>> {
+    ByteRanges::new(Cursor::new(DATA), DATA.len() as u64).content_type(ContentType::Plain)
+}
+
+fn client() -> Client {
+    Client::new(rocket::ignite().mount("/", routes![data])).unwrap()
+}
+
+#[test]
+fn full_representation_without_range() {
+    let client = client();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.headers().get_one("Accept-Ranges"), Some("bytes"));
+    assert_eq!(response.into_bytes().unwrap(), DATA);
+}
+
+#[test]
+fn single_range() {
+    let client = client();
+    let response = client.get("/").header(Header::new("Range", "bytes=2-4")).dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes 2-4/10"));
+    assert_eq!(response.content_type(), Some(ContentType::Plain));
+    assert_eq!(response.into_string().unwrap(), "234");
+}
+
+#[test]
+fn multiple_ranges() {
+    let client = client();
+    let response = client.get("/").header(Header::new("Range", "bytes=0-1, -2")).dispatch();
+    assert_eq!(response.status(), Status::PartialContent);
+
+    let content_type = response.headers().get_one("Content-Type").unwrap().to_string();
+    let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+    let expected = format!("--{b}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+        Content-Range: bytes 0-1/10\r\n\r\n01\r\n--{b}\r\n\
+        Content-Type: text/plain; charset=utf-8\r\nContent-Range: bytes 8-9/10\r\n\r\n89\
+        \r\n--{b}--\r\n", b = boundary);
+
+    assert_eq!(response.into_string().unwrap(), expected);
+}
+
+#[test]
+fn unsatisfiable_range() {
+    let client = client();
+    let response = client.get("/").header(Header::new("Range", "bytes=20-30")).dispatch();
+    assert_eq!(response.status(), Status::RangeNotSatisfiable);
+    assert_eq!(response.headers().get_one("Content-Range"), Some("bytes */10"));
+}
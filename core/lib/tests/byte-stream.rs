@@ -0,0 +1,62 @@
+#[macro_use] extern crate rocket;
+
+use rocket::data::{Data, ToByteUnit};
+use rocket::futures::stream::StreamExt;
+
+#[post("/<limit>", data = "<data>")]
+async fn chunks(limit: u64, mut data: Data) -> String {
+    assert_eq!(data.peek(5).await, b"Hello");
+
+    let mut stream = data.open_stream(limit.bytes());
+    let mut body = String::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => body.push_str(std::str::from_utf8(&chunk).unwrap()),
+            Err(e) => return format!("{} [{}]", body, e),
+        }
+    }
+
+    body
+}
+
+#[post("/cancel", data = "<data>")]
+async fn cancel(data: Data) -> String {
+    let mut stream = data.open_stream(1.kibibytes());
+    let first = stream.next().await.unwrap().unwrap();
+    stream.cancel();
+    assert!(stream.next().await.is_none());
+    String::from_utf8(first.to_vec()).unwrap()
+}
+
+mod byte_stream_tests {
+    use super::*;
+    use rocket::local::blocking::Client;
+
+    fn client() -> Client {
+        Client::new(rocket::ignite().mount("/", routes![chunks, cancel])).unwrap()
+    }
+
+    #[test]
+    fn streams_whole_body() {
+        let client = client();
+        let response = client.post("/1024").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, world!");
+
+        let response = client.post("/13").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn reports_exceeded_limit() {
+        let client = client();
+        let response = client.post("/5").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello [body exceeds the limit of 5B]");
+    }
+
+    #[test]
+    fn stream_can_be_cancelled() {
+        let client = client();
+        let response = client.post("/cancel").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, world!");
+    }
+}
@@ -0,0 +1,45 @@
+#[macro_use] extern crate rocket;
+
+use rocket::data::{Bytes, Data, Limits, ToByteUnit};
+
+#[post("/", data = "<bytes>")]
+fn echo(bytes: Bytes) -> Vec<u8> {
+    bytes.to_vec()
+}
+
+#[post("/peeked", data = "<data>")]
+async fn peeked(mut data: Data) -> Vec<u8> {
+    assert_eq!(data.peek(5).await, b"Hello");
+    data.open(64.bytes()).into_bytes().await.unwrap().to_vec()
+}
+
+mod bytes_data_tests {
+    use super::*;
+    use rocket::config::{Config, Environment};
+    use rocket::local::blocking::Client;
+
+    fn client(limit: u64) -> Client {
+        let config = Config::build(Environment::Development)
+            .limits(Limits::default().limit("bytes", limit.into()))
+            .unwrap();
+
+        Client::new(rocket::custom(config).mount("/", routes![echo, peeked])).unwrap()
+    }
+
+    #[test]
+    fn reads_body_as_bytes() {
+        let client = client(1024);
+        let response = client.post("/").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, world!");
+
+        let response = client.post("/peeked").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello, world!");
+    }
+
+    #[test]
+    fn bytes_limit_is_respected() {
+        let client = client(5);
+        let response = client.post("/").body("Hello, world!").dispatch();
+        assert_eq!(response.into_string().unwrap(), "Hello");
+    }
+}
@@ -0,0 +1,160 @@
+#[macro_use] extern crate rocket;
+
+use serde::Deserialize;
+
+use rocket::State;
+use rocket::config::{Config, ConfigError, Environment, Table, Value};
+use rocket::config::{Validate, ValidationError};
+use rocket::fairing::AdHoc;
+use rocket::local::blocking::Client;
+
+#[derive(Debug, PartialEq, Deserialize, ConfigSection)]
+struct Pool {
+    #[config(default = 4)]
+    size: u32,
+    #[config(default = "main")]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize, ConfigSection)]
+struct Cache {
+    #[config(default = 64, env = "EXTRACT_CACHE_CAPACITY")]
+    capacity: u32,
+    #[serde(rename = "ttl")]
+    #[config(default = 30, env = "EXTRACT_CACHE_TTL")]
+    ttl_secs: u32,
+}
+
+impl Validate for Cache { }
+
+impl Validate for Pool {
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        if self.size == 0 {
+            errors.push(ValidationError::new("size", "must be at least 1"));
+        }
+
+        if self.name.contains(' ') {
+            errors.push(ValidationError::new("name", "must not contain spaces"));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+fn config_with_pool(size: i64, name: &str) -> Config {
+    let mut table = Table::new();
+    table.insert("size".into(), Value::Integer(size));
+    table.insert("name".into(), Value::from(name));
+    Config::build(Environment::Development).extra("pool", table).unwrap()
+}
+
+#[get("/")]
+fn index(pool: State<'_, Pool>) -> String {
+    format!("{}:{}", pool.name, pool.size)
+}
+
+#[test]
+fn missing_section_uses_defaults() {
+    let config = Config::development();
+    let pool: Pool = config.extract_validated("pool").unwrap();
+    assert_eq!(pool, Pool { size: 4, name: "main".into() });
+}
+
+#[test]
+fn valid_section_is_extracted() {
+    let pool: Pool = config_with_pool(8, "main").extract_validated("pool").unwrap();
+    assert_eq!(pool, Pool { size: 8, name: "main".into() });
+}
+
+#[test]
+fn invalid_fields_are_reported_with_paths() {
+    let config = config_with_pool(0, "the main pool");
+    match config.extract_validated::<Pool>("pool") {
+        Err(ConfigError::Invalid(name, errors, _)) => {
+            assert_eq!(name, "pool");
+            let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+            assert_eq!(paths, vec!["pool.size", "pool.name"]);
+        }
+        other => panic!("expected invalid section, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn mistyped_section_is_invalid() {
+    let config = Config::build(Environment::Development)
+        .extra("pool", "not a table")
+        .unwrap();
+
+    let result = config.extract::<Pool>("pool");
+    assert!(matches!(result, Err(ConfigError::Invalid(..))));
+}
+
+#[test]
+fn ad_hoc_config_manages_section() {
+    let rocket = rocket::custom(config_with_pool(2, "main"))
+        .attach(AdHoc::config::<Pool>("pool"))
+        .mount("/", routes![index]);
+
+    let client = Client::new(rocket).unwrap();
+    assert_eq!(client.get("/").dispatch().into_string().unwrap(), "main:2");
+}
+
+#[test]
+fn ad_hoc_config_aborts_launch_when_invalid() {
+    let rocket = rocket::custom(config_with_pool(0, "main"))
+        .attach(AdHoc::config::<Pool>("pool"));
+
+    assert!(Client::new(rocket).is_err());
+}
+
+#[test]
+fn defaults_fill_in_missing_fields() {
+    let mut table = Table::new();
+    table.insert("size".into(), Value::Integer(8));
+    let config = Config::build(Environment::Development).extra("pool", table).unwrap();
+    let pool: Pool = config.extract_validated("pool").unwrap();
+    assert_eq!(pool, Pool { size: 8, name: "main".into() });
+}
+
+#[test]
+fn env_vars_override_declared_fields() {
+    let mut table = Table::new();
+    table.insert("capacity".into(), Value::Integer(128));
+    table.insert("ttl".into(), Value::Integer(10));
+    let config = Config::build(Environment::Development).extra("cache", table).unwrap();
+
+    std::env::set_var("EXTRACT_CACHE_TTL", "60");
+    let cache = config.extract_validated::<Cache>("cache");
+    std::env::set_var("EXTRACT_CACHE_TTL", "sixty");
+    let invalid = config.extract_validated::<Cache>("cache");
+    std::env::remove_var("EXTRACT_CACHE_TTL");
+
+    assert_eq!(cache.unwrap(), Cache { capacity: 128, ttl_secs: 60 });
+    match invalid {
+        Err(ConfigError::Invalid(_, errors, _)) => assert_eq!(errors[0].path, "cache.ttl"),
+        other => panic!("expected invalid override, got {:?}", other.map(|_| ())),
+    }
+}
+
+fn env_config() -> Config {
+    let mut rocket = rocket::ignite();
+    rocket::async_test(async move { rocket.config().await.clone() })
+}
+
+#[test]
+fn nested_env_vars_are_opt_in() {
+    std::env::set_var("ROCKET_EXTRACT_POOL__SIZE", "16");
+    let flat = env_config();
+    std::env::set_var("ROCKET_NESTED_ENV", "true");
+    let nested = env_config();
+    std::env::remove_var("ROCKET_NESTED_ENV");
+    std::env::remove_var("ROCKET_EXTRACT_POOL__SIZE");
+
+    assert_eq!(flat.get_int("extract_pool__size").unwrap(), 16);
+    let pool: Pool = flat.extract_validated("extract_pool").unwrap();
+    assert_eq!(pool.size, 4);
+
+    let pool: Pool = nested.extract_validated("extract_pool").unwrap();
+    assert_eq!(pool.size, 16);
+}
@@ -0,0 +1,90 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rocket::Request;
+use rocket::config::{Config, ConfigSnapshot, Environment};
+use rocket::data::{Limits, ToByteUnit};
+use rocket::fairing::AdHoc;
+use rocket::local::asynchronous::Client;
+
+#[get("/motd")]
+fn motd(config: ConfigSnapshot) -> String {
+    let message = config.get_str("motd").unwrap_or("none");
+    format!("{} v{}", message, config.version())
+}
+
+#[get("/limit")]
+fn limit(req: &Request<'_>) -> String {
+    req.limits().get("forms").map(|l| l.to_string()).unwrap_or_default()
+}
+
+fn config_with_motd(motd: &str) -> Config {
+    Config::build(Environment::Development)
+        .extra("motd", motd)
+        .limits(Limits::new().limit("forms", 1.kibibytes()))
+        .unwrap()
+}
+
+#[rocket::async_test]
+async fn snapshot_reflects_updates() {
+    let rocket = rocket::custom(Config::development()).mount("/", routes![motd, limit]);
+    let client = Client::new(rocket).await.unwrap();
+
+    let response = client.get("/motd").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), "none v0");
+    assert_eq!(client.cargo().config_snapshot().version(), 0);
+
+    let update = client.cargo().update_config(config_with_motd("hello")).await;
+    assert_eq!(update.previous.version(), 0);
+    assert_eq!(update.current.version(), 1);
+    assert!(update.extra_changed("motd"));
+    assert!(!update.extra_changed("template_dir"));
+
+    let response = client.get("/motd").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), "hello v1");
+
+    let response = client.get("/limit").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), 1.kibibytes().to_string());
+}
+
+#[rocket::async_test]
+async fn non_reloadable_parameters_are_kept() {
+    let config = Config::build(Environment::Development).port(8123).unwrap();
+    let client = Client::new(rocket::custom(config)).await.unwrap();
+
+    let new_config = Config::build(Environment::Development).port(9999).unwrap();
+    let update = client.cargo().update_config(new_config).await;
+    assert_eq!(update.current.port, 8123);
+    assert_eq!(client.cargo().config_snapshot().port, 8123);
+}
+
+#[rocket::async_test]
+async fn config_update_fairings_run() {
+    let seen = Arc::new(AtomicU64::new(0));
+    let fairing_seen = seen.clone();
+    let rocket = rocket::custom(Config::development())
+        .attach(AdHoc::on_config_update("Recorder", move |_, update| {
+            let seen = fairing_seen.clone();
+            Box::pin(async move {
+                seen.store(update.current.version(), Ordering::SeqCst);
+            })
+        }));
+
+    let client = Client::new(rocket).await.unwrap();
+    assert_eq!(seen.load(Ordering::SeqCst), 0);
+
+    client.cargo().update_config(config_with_motd("one")).await;
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+
+    client.cargo().update_config(config_with_motd("two")).await;
+    assert_eq!(seen.load(Ordering::SeqCst), 2);
+}
+
+#[rocket::async_test]
+async fn reload_without_config_file_fails() {
+    let client = Client::new(rocket::custom(Config::development())).await.unwrap();
+    assert!(client.cargo().reload_config().await.is_err());
+    assert_eq!(client.cargo().config_snapshot().version(), 0);
+}
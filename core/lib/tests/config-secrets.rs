@@ -0,0 +1,71 @@
+use std::env;
+
+use rocket::config::{EnvFile, SecretProvider, Secrets};
+
+struct Static;
+
+impl SecretProvider for Static {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    fn resolve(&self, key: &str) -> Result<String, String> {
+        match key {
+            "token" => Ok("s3cr3t".into()),
+            _ => Err(format!("no secret named '{}'", key)),
+        }
+    }
+}
+
+fn extra(secrets: Secrets, name: &str) -> Option<String> {
+    let mut rocket = rocket::Rocket::ignite_with_secrets(secrets);
+    rocket::async_test(async move {
+        rocket.config().await.get_string(name).ok()
+    })
+}
+
+#[test]
+fn secret_references_are_resolved() {
+    let dir = env::temp_dir().join(format!("rocket-secrets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let secret_file = dir.join("api_key");
+    std::fs::write(&secret_file, "from-file\n").unwrap();
+    let env_file = dir.join(".env");
+    std::fs::write(&env_file, "# comment\nexport PASSWORD=\"from env file\"\n").unwrap();
+
+    env::set_var("SECRETS_TEST_SOURCE", "from-env");
+    env::set_var("ROCKET_SECRETS_TEST_ENV", "${env:SECRETS_TEST_SOURCE}");
+    env::set_var("ROCKET_SECRETS_TEST_FILE", format!("${{file:{}}}", secret_file.display()));
+    env::set_var("ROCKET_SECRETS_TEST_DOTENV", "${env_file:PASSWORD}");
+    env::set_var("ROCKET_SECRETS_TEST_STATIC", "${static:token}");
+
+    let secrets = || Secrets::new().provider(Static).provider(EnvFile::new(&env_file));
+    assert_eq!(extra(secrets(), "secrets_test_env").unwrap(), "from-env");
+    assert_eq!(extra(secrets(), "secrets_test_file").unwrap(), "from-file");
+    assert_eq!(extra(secrets(), "secrets_test_dotenv").unwrap(), "from env file");
+    assert_eq!(extra(secrets(), "secrets_test_static").unwrap(), "s3cr3t");
+
+    for var in &["ENV", "FILE", "DOTENV", "STATIC"] {
+        env::remove_var(format!("ROCKET_SECRETS_TEST_{}", var));
+    }
+
+    env::remove_var("SECRETS_TEST_SOURCE");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn escaped_and_unknown_references_are_kept() {
+    env::set_var("SECRETS_TEST_ESCAPED_SOURCE", "from-env");
+    env::set_var("ROCKET_SECRETS_TEST_ESCAPED", "$${env:SECRETS_TEST_ESCAPED_SOURCE}");
+    env::set_var("ROCKET_SECRETS_TEST_UNKNOWN", "${vault:db/password}");
+
+    let escaped = extra(Secrets::new(), "secrets_test_escaped");
+    let unknown = extra(Secrets::new(), "secrets_test_unknown");
+    env::remove_var("ROCKET_SECRETS_TEST_ESCAPED");
+    env::remove_var("ROCKET_SECRETS_TEST_UNKNOWN");
+    env::remove_var("SECRETS_TEST_ESCAPED_SOURCE");
+
+    assert_eq!(escaped.unwrap(), "${env:SECRETS_TEST_ESCAPED_SOURCE}");
+    assert_eq!(unknown.unwrap(), "${vault:db/password}");
+}
@@ -0,0 +1,61 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::request::ConnectionInfo;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/")]
+fn index(conn: ConnectionInfo<'_>) -> String {
+    let peer = conn.peer_addr().map(|addr| addr.ip().to_string());
+    format!("{:?} {:?} {} {:?}", conn.local_addr(), peer, conn.is_tls(), conn.tls_version())
+}
+
+#[rocket::async_test]
+async fn connection_details_are_exposed() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(request).await.unwrap();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+
+    let response = String::from_utf8_lossy(&buf);
+    let expected = format!("Some(127.0.0.1:{}) Some(\"127.0.0.1\") false None", port);
+    assert!(response.ends_with(&expected), "{}", response);
+
+    shutdown.shutdown();
+}
+
+#[test]
+fn local_requests_have_no_connection() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![index])).unwrap();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.into_string().unwrap(), "None None false None");
+}
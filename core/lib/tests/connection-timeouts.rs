@@ -0,0 +1,119 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::Shutdown;
+use rocket::config::{Config, Environment, ServerConfig};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, world!"
+}
+
+#[post("/", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+/// Launches a server with `server` settings, returning its port and a handle
+/// to shut it down.
+async fn launch(server: ServerConfig) -> (u16, Shutdown) {
+    launch_with(server, 5).await
+}
+
+/// Launches a server with `server` settings and a `keep_alive` timeout,
+/// returning its port and a handle to shut it down.
+async fn launch_with(server: ServerConfig, keep_alive: u32) -> (u16, Shutdown) {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .keep_alive(keep_alive)
+        .server(server)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, echo])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+#[rocket::async_test]
+async fn silent_clients_are_disconnected() {
+    let server = ServerConfig { read_header_timeout: Some(1), ..ServerConfig::default() };
+    let (port, shutdown) = launch(server).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(b"GET / HTTP/1.1\r\nHost: local").await.unwrap();
+
+    // The server closes the connection after a second; we'd wait forever.
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    assert!(!String::from_utf8_lossy(&buf).contains("Hello, world!"));
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn connections_that_never_send_are_disconnected() {
+    let (port, shutdown) = launch_with(ServerConfig::default(), 1).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    assert!(buf.is_empty());
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn stalled_bodies_are_disconnected() {
+    let server = ServerConfig { read_timeout: Some(1), ..ServerConfig::default() };
+    let (port, shutdown) = launch(server).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let head = "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 10\r\n\r\nhi";
+    stream.write_all(head.as_bytes()).await.unwrap();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    assert!(!String::from_utf8_lossy(&buf).contains("200 OK"));
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn connections_close_after_max_requests() {
+    let server = ServerConfig { max_requests: Some(2), ..ServerConfig::default() };
+    let (port, shutdown) = launch(server).await;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+    stream.write_all(request).await.unwrap();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+
+    let response = String::from_utf8_lossy(&buf).to_lowercase();
+    assert_eq!(response.matches("hello, world!").count(), 2);
+    assert_eq!(response.matches("connection: close").count(), 1);
+
+    shutdown.shutdown();
+}
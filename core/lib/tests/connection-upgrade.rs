@@ -0,0 +1,198 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::Request;
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::http::{Header, Status};
+use rocket::request::Upgrade;
+use rocket::tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/echo")]
+fn echo(req: &Request<'_>) -> Result<Upgrade, Status> {
+    let upgrade = req.upgrade("echo", |io| async move {
+        let (mut reader, mut writer) = io::split(io);
+        let _ = io::copy(&mut reader, &mut writer).await;
+    });
+
+    upgrade.map(|u| u.header(Header::new("X-Echo", "yes"))).ok_or(Status::UpgradeRequired)
+}
+
+#[rocket::async_test]
+async fn upgraded_connection_is_handed_to_handler() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![echo])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+
+    let request = b"GET /echo HTTP/1.1\r\nHost: localhost\r\n\
+        Connection: Upgrade\r\nUpgrade: echo\r\n\r\n";
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(request).await.unwrap();
+
+    let mut head = vec![];
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0];
+        let read = timeout(Duration::from_secs(5), stream.read_exact(&mut byte)).await;
+        read.expect("response head").unwrap();
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8(head).unwrap().to_lowercase();
+    assert!(head.starts_with("http/1.1 101"), "{}", head);
+    assert!(head.contains("upgrade: echo\r\n"), "{}", head);
+    assert!(head.contains("x-echo: yes\r\n"), "{}", head);
+    assert!(!head.contains("content-length"), "{}", head);
+
+    stream.write_all(b"ping").await.unwrap();
+    let mut pong = [0; 4];
+    timeout(Duration::from_secs(5), stream.read_exact(&mut pong)).await.unwrap().unwrap();
+    assert_eq!(&pong, b"ping");
+
+    shutdown.shutdown();
+}
+
+#[test]
+fn local_requests_are_upgraded() {
+    use std::io::{Read, Write};
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![echo])).unwrap();
+    let mut response = client.get("/echo")
+        .header(Header::new("Connection", "upgrade"))
+        .header(Header::new("Upgrade", "echo"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert_eq!(response.headers().get_one("X-Echo"), Some("yes"));
+
+    let mut io = response.upgraded().expect("upgraded connection");
+    io.write_all(b"ping").unwrap();
+    let mut pong = [0; 4];
+    io.read_exact(&mut pong).unwrap();
+    assert_eq!(&pong, b"ping");
+}
+
+#[rocket::async_test]
+async fn local_upgrades_apply_backpressure() {
+    use rocket::local::asynchronous::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![echo])).await.unwrap();
+    let mut response = client.get("/echo")
+        .header(Header::new("Connection", "upgrade"))
+        .header(Header::new("Upgrade", "echo"))
+        .dispatch()
+        .await;
+
+    // Far more than the connection buffers: writes must wait for reads.
+    let data: Vec<u8> = (0..4 << 20).map(|i| i as u8).collect();
+    let io = response.upgraded().expect("upgraded connection");
+    let (mut reader, mut writer) = io::split(io);
+    let write = async {
+        writer.write_all(&data).await.unwrap();
+        writer.shutdown().await.unwrap();
+    };
+
+    let mut echoed = vec![];
+    let read = reader.read_to_end(&mut echoed);
+    let (_, read) = timeout(Duration::from_secs(10), async { rocket::futures::join!(write, read) })
+        .await
+        .expect("echo completes");
+
+    read.unwrap();
+    assert!(echoed == data);
+}
+
+#[get("/ws")]
+fn websocket(req: &Request<'_>) -> Option<Upgrade> {
+    use rocket::futures::{SinkExt, StreamExt};
+    use sha1::{Digest, Sha1};
+    use tokio_tungstenite::{WebSocketStream, tungstenite::protocol::Role};
+
+    let key = req.headers().get_one("Sec-WebSocket-Key")?;
+    let digest = Sha1::digest(format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key).as_bytes());
+    let upgrade = req.upgrade("websocket", |io| async move {
+        let mut socket = WebSocketStream::from_raw_socket(io, Role::Server, None).await;
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_text() || message.is_binary() {
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    })?;
+
+    Some(upgrade.header(Header::new("Sec-WebSocket-Accept", base64::encode(digest))))
+}
+
+#[test]
+fn local_websockets_exchange_messages() {
+    use rocket::local::Message;
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![echo, websocket])).unwrap();
+    let mut response = client.get("/ws").websocket().dispatch();
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    let mut socket = response.websocket().expect("accepted handshake");
+
+    socket.send(Message::Text("hello".into())).unwrap();
+    assert_eq!(socket.receive().unwrap(), Some(Message::Text("hello".into())));
+
+    // Larger than the connection buffers and framed with a 64-bit length.
+    let data: Vec<u8> = (0..300 * 1024).map(|i| i as u8).collect();
+    socket.send(Message::Binary(data.clone())).unwrap();
+    assert!(socket.receive().unwrap() == Some(Message::Binary(data)));
+
+    socket.send(Message::Ping(b"are you there?".to_vec())).unwrap();
+    socket.send(Message::Text("still here".into())).unwrap();
+    assert_eq!(socket.receive().unwrap(), Some(Message::Pong(b"are you there?".to_vec())));
+    assert_eq!(socket.receive().unwrap(), Some(Message::Text("still here".into())));
+
+    socket.send(Message::Close(Some(1000), "bye".into())).unwrap();
+    match socket.receive().unwrap() {
+        Some(Message::Close(code, _)) => assert_eq!(code, Some(1000)),
+        message => panic!("expected a close frame, got {:?}", message),
+    }
+
+    assert_eq!(socket.receive().unwrap(), None);
+    assert!(response.websocket().is_none());
+}
+
+#[test]
+fn websocket_handshakes_are_verified() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![echo, websocket])).unwrap();
+
+    // Upgraded, but not to a WebSocket.
+    let mut response = client.get("/echo")
+        .header(Header::new("Connection", "upgrade"))
+        .header(Header::new("Upgrade", "echo"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert!(response.websocket().is_none());
+
+    // A handshake the server declines.
+    let mut response = client.get("/ws").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response.websocket().is_none());
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+
+#[test]
+fn launches_on_caller_provided_runtime() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .workers(2)
+        .max_blocking_threads(4)
+        .unwrap();
+
+    let started = Arc::new(AtomicUsize::new(0));
+    let counter = started.clone();
+    let mut runtime = config.runtime()
+        .thread_name("custom-worker")
+        .on_thread_start(move || { counter.fetch_add(1, Ordering::SeqCst); })
+        .build()
+        .expect("runtime");
+
+    let rocket = rocket::custom(config)
+        .attach(AdHoc::on_launch("Stop", |cargo| {
+            let name = std::thread::current().name().map(String::from);
+            assert_eq!(name.as_deref(), Some("custom-worker"));
+            cargo.shutdown().shutdown();
+        }));
+
+    assert!(runtime.block_on(rocket::tokio::spawn(rocket.launch())).unwrap().is_ok());
+    assert!(started.load(Ordering::SeqCst) >= 1);
+}
+
+#[test]
+fn launch_sizes_runtime_by_custom_config() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .workers(1)
+        .max_blocking_threads(1)
+        .unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let rocket = rocket::custom(config)
+        .attach(AdHoc::on_launch("Stop", move |cargo| {
+            let shutdown = cargo.shutdown();
+            rocket::tokio::spawn(async move {
+                tx.send(std::thread::current().name().map(String::from)).unwrap();
+                shutdown.shutdown();
+            });
+        }));
+
+    assert!(rocket::async_launch(rocket).is_ok());
+    assert_eq!(rx.recv().unwrap().as_deref(), Some("rocket-worker"));
+}
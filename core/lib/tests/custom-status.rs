@@ -0,0 +1,71 @@
+#[macro_use] extern crate rocket;
+
+use rocket::Request;
+use rocket::fairing::AdHoc;
+use rocket::response::status;
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[get("/unregistered")]
+fn unregistered() -> status::Custom<&'static str> {
+    status::Custom(Status::new(799, "Custom"), "unregistered")
+}
+
+#[get("/invalid")]
+fn invalid() -> status::Custom<&'static str> {
+    status::Custom(Status::new(1000, "Invalid"), "invalid")
+}
+
+#[get("/teapot")]
+fn teapot() -> Status {
+    Status::ImATeapot
+}
+
+#[catch(404)]
+fn not_found(_: &Request<'_>) -> status::Custom<&'static str> {
+    status::Custom(Status::new(42, "Not Found"), "not found")
+}
+
+fn client() -> Client {
+    Client::new(rocket::ignite().mount("/", routes![unregistered, invalid])).unwrap()
+}
+
+#[test]
+fn unregistered_status_codes_are_preserved() {
+    let client = client();
+    let response = client.get("/unregistered").dispatch();
+    assert_eq!(response.status(), Status::new(799, "Custom"));
+    assert_eq!(response.into_string().unwrap(), "unregistered");
+}
+
+#[test]
+fn invalid_status_codes_are_errors() {
+    let client = client();
+    let response = client.get("/invalid").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+}
+
+#[test]
+fn invalid_catcher_statuses_are_errors() {
+    let rocket = rocket::ignite().register(catchers![not_found]);
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/missing").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+}
+
+#[test]
+fn invalid_fairing_statuses_are_errors() {
+    let rocket = rocket::ignite()
+        .mount("/", routes![teapot])
+        .attach(AdHoc::on_response("Invalid Status", |req, res| {
+            Box::pin(async move {
+                if req.uri().path() == "/teapot" {
+                    res.set_status(Status::new(1000, "Invalid"));
+                }
+            })
+        }));
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/teapot").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+}
@@ -0,0 +1,55 @@
+use rocket::catcher::DefaultCatcher;
+use rocket::local::blocking::Client;
+use rocket::http::{Accept, ContentType, Header, Status};
+
+#[test]
+fn built_in_default_catcher_negotiates_format() {
+    let client = Client::new(rocket::ignite()).unwrap();
+
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+
+    let response = client.get("/").header(Accept::JSON).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+
+    let xhr = Header::new("X-Requested-With", "XMLHttpRequest");
+    let response = client.get("/").header(xhr.clone()).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+
+    let response = client.get("/").header(xhr).header(Accept::HTML).dispatch();
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+}
+
+#[test]
+fn default_catcher_templates_can_be_replaced() {
+    let catcher = DefaultCatcher::new()
+        .html("<p>{code} {reason}: {description}</p>")
+        .json(r#"{"code":{code}}"#);
+
+    let client = Client::new(rocket::ignite().register(vec![catcher.into()])).unwrap();
+
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_string().unwrap(),
+        "<p>404 Not Found: The requested resource could not be found.</p>");
+
+    let response = client.get("/").header(Accept::JSON).dispatch();
+    assert_eq!(response.into_string().unwrap(), r#"{"code":404}"#);
+}
+
+#[test]
+fn built_in_json_errors_are_valid_json() {
+    let client = Client::new(rocket::ignite()).unwrap();
+    let body = client.get("/").header(Accept::JSON).dispatch().into_string().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body).expect("valid JSON");
+    assert_eq!(json["error"]["code"], 404);
+    assert_eq!(json["error"]["reason"], "Not Found");
+    assert_eq!(json["error"]["description"], "The requested resource could not be found.");
+
+    let catcher = DefaultCatcher::new().json(r#"{"reason":"{reason}","about":"{description}"}"#);
+    let client = Client::new(rocket::ignite().register(vec![catcher.into()])).unwrap();
+    let body = client.get("/").header(Accept::JSON).dispatch().into_string().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body).expect("valid JSON");
+    assert_eq!(json["reason"], "Not Found");
+}
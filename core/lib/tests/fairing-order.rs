@@ -0,0 +1,61 @@
+#[macro_use] extern crate rocket;
+
+use rocket::fairing::{AdHoc, Order};
+use rocket::local::blocking::Client;
+use rocket::http::Header;
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+fn tag(name: &'static str) -> AdHoc {
+    AdHoc::on_response(name, move |_, res| {
+        Box::pin(async move {
+            res.adjoin_header(Header::new("X-Order", name));
+        })
+    })
+}
+
+fn order_of(rocket: rocket::Rocket) -> Vec<String> {
+    let client = Client::new(rocket.mount("/", routes![index])).unwrap();
+    let response = client.get("/").dispatch();
+    response.headers().get("X-Order").map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn attach_order_is_the_default() {
+    let rocket = rocket::ignite().attach(tag("A")).attach(tag("B")).attach(tag("C"));
+    assert_eq!(order_of(rocket), vec!["A", "B", "C"]);
+}
+
+#[test]
+fn higher_priorities_run_first() {
+    let rocket = rocket::ignite()
+        .attach(tag("A"))
+        .attach(tag("B").with_order(Order::priority(10)))
+        .attach(tag("C").with_order(Order::priority(-10)))
+        .attach(tag("D"));
+
+    assert_eq!(order_of(rocket), vec!["B", "A", "D", "C"]);
+}
+
+#[test]
+fn dependencies_override_priorities() {
+    let rocket = rocket::ignite()
+        .attach(tag("Auth").with_order(Order::priority(10).after("CORS")))
+        .attach(tag("Log").with_order(Order::priority(-10).before("Auth")))
+        .attach(tag("CORS"))
+        .attach(tag("Other").with_order(Order::default().after("Missing")));
+
+    assert_eq!(order_of(rocket), vec!["CORS", "Other", "Log", "Auth"]);
+}
+
+#[test]
+fn cyclic_dependencies_are_ignored() {
+    let rocket = rocket::ignite()
+        .attach(tag("A").with_order(Order::default().after("B")))
+        .attach(tag("B").with_order(Order::priority(1).after("A")));
+
+    assert_eq!(order_of(rocket), vec!["B", "A"]);
+}
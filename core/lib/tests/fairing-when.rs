@@ -0,0 +1,75 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::fairing::{AdHoc, Fairing, Order};
+use rocket::local::blocking::Client;
+use rocket::http::Header;
+
+#[get("/api/hello")]
+fn api() -> &'static str {
+    "api"
+}
+
+#[get("/hello")]
+fn hello() -> &'static str {
+    "hello"
+}
+
+fn tag(name: &'static str) -> AdHoc {
+    AdHoc::on_response(name, move |_, res| {
+        Box::pin(async move {
+            res.adjoin_header(Header::new("X-Tag", name));
+        })
+    })
+}
+
+fn client(rocket: rocket::Rocket) -> Client {
+    Client::new(rocket.mount("/", routes![api, hello])).unwrap()
+}
+
+fn tags(client: &Client, uri: &str) -> Vec<String> {
+    let response = client.get(uri.to_string()).dispatch();
+    response.headers().get("X-Tag").map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn callbacks_run_only_for_matching_requests() {
+    let rocket = rocket::ignite()
+        .attach(tag("API").when(|req| req.uri().path().starts_with("/api")))
+        .attach(tag("All"));
+
+    let client = client(rocket);
+    assert_eq!(tags(&client, "/api/hello"), vec!["API", "All"]);
+    assert_eq!(tags(&client, "/hello"), vec!["All"]);
+}
+
+#[test]
+fn request_callbacks_are_conditional() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    let fairing = AdHoc::on_request("Count", move |_, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { })
+    });
+
+    let client = client(rocket::ignite().attach(fairing.when(|req| req.uri().query().is_some())));
+    client.get("/hello").dispatch();
+    client.get("/hello?a=b").dispatch();
+    client.get("/api/hello").dispatch();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn info_and_order_are_preserved() {
+    let fairing = tag("Tag").with_order(Order::priority(10)).when(|_| true);
+    assert_eq!(fairing.info().name, "Tag");
+    assert_eq!(fairing.order(), Order::priority(10));
+
+    let rocket = rocket::ignite()
+        .attach(tag("A"))
+        .attach(fairing);
+
+    assert_eq!(tags(&client(rocket), "/hello"), vec!["Tag", "A"]);
+}
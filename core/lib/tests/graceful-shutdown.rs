@@ -0,0 +1,135 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use rocket::{Shutdown, ShutdownReason};
+use rocket::config::{Config, Environment, ServerConfig};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::task::JoinHandle;
+use rocket::tokio::time::{delay_for, timeout};
+
+#[get("/wait")]
+async fn wait(shutdown: Shutdown) -> &'static str {
+    shutdown.await;
+    "notified"
+}
+
+/// Set once the handler of `/stuck` is dropped.
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+struct Cancelled;
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        CANCELLED.store(true, Ordering::SeqCst);
+    }
+}
+
+#[get("/stuck")]
+async fn stuck() -> &'static str {
+    let _cancelled = Cancelled;
+    delay_for(Duration::from_secs(60)).await;
+    "unreachable"
+}
+
+type Launched = JoinHandle<Result<(), rocket::error::Error>>;
+
+/// Launches a server with `server` settings, returning its port, a handle to
+/// shut it down, and the task running `launch()`.
+async fn launch(server: ServerConfig) -> (u16, Shutdown, Launched) {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .server(server)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![wait, stuck])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+    (port, shutdown, server)
+}
+
+/// Sends a keep-alive `GET` request for `path`, shuts the server down once the
+/// request is in flight, and returns the lowercased response.
+async fn get_during_shutdown(port: u16, path: &str, shutdown: Shutdown) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    delay_for(Duration::from_millis(250)).await;
+    shutdown.shutdown();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(10), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    String::from_utf8_lossy(&buf).to_lowercase()
+}
+
+#[rocket::async_test]
+async fn handlers_are_notified_of_shutdown() {
+    let (port, shutdown, server) = launch(ServerConfig::default()).await;
+
+    let response = get_during_shutdown(port, "/wait", shutdown).await;
+    assert!(response.starts_with("http/1.1 200"));
+    assert!(response.contains("connection: close"));
+    assert!(response.ends_with("notified"));
+
+    let result = timeout(Duration::from_secs(5), server).await;
+    assert!(result.expect("server did not shut down").unwrap().is_ok());
+}
+
+#[rocket::async_test]
+async fn grace_period_is_enforced() {
+    let server = ServerConfig { grace: Some(1), mercy: Some(1), ..ServerConfig::default() };
+    let (port, shutdown, server) = launch(server).await;
+
+    let response = get_during_shutdown(port, "/stuck", shutdown).await;
+    assert!(response.starts_with("http/1.1 503"));
+    assert!(response.contains("connection: close"));
+    delay_for(Duration::from_millis(100)).await;
+    assert!(CANCELLED.load(Ordering::SeqCst), "handler was not cancelled");
+
+    let result = timeout(Duration::from_secs(5), server).await;
+    assert!(result.expect("server did not shut down").unwrap().is_ok());
+}
+
+#[rocket::async_test]
+async fn reason_is_kept_across_restarts() {
+    let (port, shutdown, server) = launch(ServerConfig::default()).await;
+    shutdown.clone().shutdown_with(ShutdownReason::Restart);
+    shutdown.clone().shutdown();
+    assert!(server.await.unwrap().is_ok());
+    assert_eq!(shutdown.reason(), Some(ShutdownReason::Restart));
+
+    // The port was released, so a new instance can take it over.
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(port)
+        .unwrap();
+
+    let rocket = rocket::custom(config)
+        .attach(AdHoc::on_launch("Stop", |cargo| cargo.shutdown().shutdown()));
+
+    let shutdown = rocket.shutdown();
+    assert!(rocket.launch().await.is_ok());
+    assert_eq!(shutdown.reason(), Some(ShutdownReason::Requested));
+}
+
+#[test]
+fn shutdown_waits_by_default() {
+    let server = ServerConfig::default();
+    assert_eq!((server.grace, server.mercy), (None, None));
+}
@@ -0,0 +1,87 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+
+use rocket::{BoundSocket, Rocket, Shutdown};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, pre-bound world!"
+}
+
+/// Returns `rocket` with a fairing that sends its port and a handle to shut it
+/// down once launched.
+fn rocket() -> (Rocket, oneshot::Receiver<(u16, Shutdown)>) {
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::ignite()
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    (rocket, rx)
+}
+
+#[rocket::async_test]
+async fn serves_pre_bound_tcp_listener() {
+    let socket = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let bound_port = socket.local_addr().unwrap().port();
+
+    let (rocket, rx) = rocket();
+    let server = rocket::tokio::spawn(rocket.launch_on(socket));
+    let (port, shutdown) = rx.await.unwrap();
+    assert_eq!(port, bound_port);
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("Hello, pre-bound world!"));
+
+    shutdown.shutdown();
+    server.await.unwrap().unwrap();
+}
+
+#[cfg(unix)]
+#[rocket::async_test]
+async fn never_removes_pre_bound_unix_socket() {
+    let path = std::env::temp_dir().join(format!("rocket-launch-on-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let socket = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+    let (rocket, rx) = rocket();
+    let server = rocket::tokio::spawn(rocket.launch_on(socket));
+    let (_, shutdown) = rx.await.unwrap();
+
+    let mut stream = rocket::tokio::net::UnixStream::connect(&path).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.ends_with("Hello, pre-bound world!"));
+
+    shutdown.shutdown();
+    server.await.unwrap().unwrap();
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn ignores_sockets_for_other_processes() {
+    std::env::set_var("LISTEN_PID", (std::process::id() + 1).to_string());
+    std::env::set_var("LISTEN_FDS", "1");
+
+    assert!(BoundSocket::from_env().is_none());
+    assert!(std::env::var("LISTEN_PID").is_err());
+    assert!(std::env::var("LISTEN_FDS").is_err());
+}
@@ -0,0 +1,121 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::{Load, Shutdown};
+use rocket::config::{Config, Environment, ServerConfig};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::delay_for;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, world!"
+}
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+    delay_for(Duration::from_millis(1000)).await;
+    "Finally."
+}
+
+#[get("/load")]
+fn load(load: Load) -> String {
+    format!("shed={}", load.shed())
+}
+
+/// Launches a server with `server` settings, returning its port and a handle
+/// to shut it down.
+async fn launch(server: ServerConfig) -> (u16, Shutdown) {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .server(server)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, slow, load])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+/// Sends a `GET` request for `path` on `stream`, returning the lowercased
+/// response.
+async fn get(stream: &mut TcpStream, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut buf = vec![];
+    stream.read_to_end(&mut buf).await.unwrap();
+    String::from_utf8_lossy(&buf).to_lowercase()
+}
+
+#[rocket::async_test]
+async fn requests_beyond_max_in_flight_are_shed() {
+    let server = ServerConfig { max_in_flight: Some(1), ..ServerConfig::default() };
+    let (port, shutdown) = launch(server).await;
+
+    let slow = rocket::tokio::spawn(async move {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        get(&mut stream, "/slow").await
+    });
+
+    delay_for(Duration::from_millis(250)).await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let response = get(&mut stream, "/").await;
+    assert!(response.starts_with("http/1.1 503"));
+    assert!(response.contains("retry-after: 5"));
+    assert!(!response.contains("hello, world!"));
+
+    let response = slow.await.unwrap();
+    assert!(response.starts_with("http/1.1 200"));
+    assert!(response.contains("finally."));
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    assert!(get(&mut stream, "/load").await.contains("shed=1"));
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn connections_beyond_max_connections_are_shed() {
+    let server = ServerConfig {
+        max_connections: Some(1),
+        retry_after: None,
+        ..ServerConfig::default()
+    };
+
+    let (port, shutdown) = launch(server).await;
+    let idle = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+
+    delay_for(Duration::from_millis(250)).await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let response = get(&mut stream, "/").await;
+    assert!(response.starts_with("http/1.1 503"));
+    assert!(!response.contains("retry-after"));
+
+    // Once the idle connection closes, connections are served again.
+    drop(idle);
+    let mut served = false;
+    for _ in 0..20 {
+        delay_for(Duration::from_millis(100)).await;
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        if get(&mut stream, "/").await.contains("hello, world!") {
+            served = true;
+            break;
+        }
+    }
+
+    assert!(served, "connections were not served after the idle one closed");
+    shutdown.shutdown();
+}
@@ -0,0 +1,85 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use rocket::response::content::Json;
+
+#[get("/user")]
+fn user() -> Json<&'static str> {
+    Json(r#"{ "name": "Bob", "roles": ["admin", "editor"] }"#)
+}
+
+#[derive(Responder)]
+struct Tagged {
+    body: &'static str,
+    tag: Header<'static>,
+}
+
+#[get("/tagged")]
+fn tagged() -> Tagged {
+    Tagged { body: "tagged", tag: Header::new("X-Tag", "blue") }
+}
+
+fn client() -> Client {
+    Client::new(rocket::ignite().mount("/", routes![user, tagged])).unwrap()
+}
+
+#[test]
+fn passing_assertions_chain() {
+    let client = client();
+    let mut response = client.get("/user").dispatch();
+    response.assert_status(Status::Ok)
+        .assert_header("content-type", "application/json")
+        .assert_body_contains("\"Bob\"")
+        .assert_body_contains("editor");
+
+    // The body is still there after the assertions read it.
+    assert!(response.into_string().unwrap().contains("admin"));
+}
+
+#[test]
+#[should_panic(expected = "found: 404 Not Found")]
+fn status_mismatch_panics() {
+    client().get("/nope").dispatch().assert_status(Status::Ok);
+}
+
+#[test]
+#[should_panic(expected = "found: X-Tag: blue")]
+fn header_mismatch_panics() {
+    client().get("/tagged").dispatch().assert_header("X-Tag", "red");
+}
+
+#[test]
+#[should_panic(expected = "closest match at byte 0: \"tag\" then \"g\", not \"s\"")]
+fn body_mismatch_panics() {
+    client().get("/tagged").dispatch().assert_body_contains("tags");
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::client;
+
+    #[derive(serde::Deserialize)]
+    struct User {
+        name: String,
+        roles: Vec<String>,
+    }
+
+    #[test]
+    fn json_assertions_pass() {
+        client().get("/user").dispatch()
+            .assert_json(|user: &User| user.name == "Bob" && user.roles.len() == 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't satisfy the predicate")]
+    fn json_predicate_failure_panics() {
+        client().get("/user").dispatch().assert_json(|user: &User| user.name == "Alice");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected: JSON of type")]
+    fn non_json_body_panics() {
+        client().get("/tagged").dispatch().assert_json(|_: &User| true);
+    }
+}
@@ -0,0 +1,84 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::State;
+use rocket::local::asynchronous::Client;
+use rocket::tokio::sync::Notify;
+
+#[get("/wait")]
+async fn wait(gate: State<'_, Notify>) -> &'static str {
+    gate.notified().await;
+    "opened"
+}
+
+#[post("/open")]
+fn open(gate: State<'_, Notify>) -> &'static str {
+    gate.notify();
+    "opening"
+}
+
+#[get("/seq/<n>")]
+fn seq(n: usize, log: State<'_, Mutex<Vec<usize>>>) -> String {
+    log.lock().unwrap().push(n);
+    n.to_string()
+}
+
+async fn client() -> Client {
+    let rocket = rocket::ignite()
+        .mount("/", routes![wait, open, seq])
+        .manage(Notify::new())
+        .manage(Mutex::new(Vec::<usize>::new()));
+
+    Client::new(rocket).await.unwrap()
+}
+
+#[rocket::async_test]
+async fn batched_requests_run_concurrently() {
+    let client = client().await;
+    let responses = client.batch()
+        .add(client.get("/wait"))
+        .add_after(Duration::from_millis(20), client.post("/open"))
+        .timeout(Duration::from_secs(5))
+        .dispatch()
+        .await;
+
+    let mut bodies = vec![];
+    for response in responses {
+        bodies.push(response.unwrap().into_string().await.unwrap());
+    }
+
+    assert_eq!(bodies, vec!["opened", "opening"]);
+}
+
+#[rocket::async_test]
+async fn batched_requests_start_in_order() {
+    let client = client().await;
+    let mut batch = client.batch();
+    for n in 0..5 {
+        batch = batch.add(client.get(format!("/seq/{}", n)));
+    }
+
+    let responses = batch.dispatch().await;
+    assert_eq!(responses.len(), 5);
+    let log = client.cargo().state::<Mutex<Vec<usize>>>().unwrap();
+    assert_eq!(*log.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+}
+
+#[rocket::async_test]
+async fn requests_time_out() {
+    let client = client().await;
+    let responses = client.batch()
+        .add(client.get("/wait"))
+        .add(client.get("/seq/7"))
+        .timeout(Duration::from_millis(50))
+        .dispatch()
+        .await;
+
+    assert!(responses[0].is_err());
+    assert!(responses[1].is_ok());
+
+    let result = client.get("/wait").dispatch_timeout(Duration::from_millis(50)).await;
+    assert!(result.is_err());
+}
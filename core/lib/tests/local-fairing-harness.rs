@@ -0,0 +1,119 @@
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::{Request, Data, Response, State};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::local::FairingHarness;
+use rocket::response::ResponseBody;
+use rocket::tokio::io::AsyncReadExt;
+
+struct Counter(AtomicUsize);
+
+/// Rejects requests without an API key, counting the rejections.
+struct ApiKey;
+
+#[rocket::async_trait]
+impl Fairing for ApiKey {
+    fn info(&self) -> Info {
+        Info { name: "API Key", kind: Kind::Request | Kind::Response }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &Data) {
+        if req.headers().get_one("X-Api-Key") != Some("secret") {
+            req.set_uri(rocket::http::uri::Origin::parse("/unauthorized").unwrap());
+            let counter = req.guard::<State<'_, Counter>>().await.unwrap();
+            counter.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if req.uri().path() == "/unauthorized" {
+            res.set_status(Status::Unauthorized);
+        }
+    }
+}
+
+/// Tags requests with whether their body has been fully received.
+struct BodyInspector;
+
+#[rocket::async_trait]
+impl Fairing for BodyInspector {
+    fn info(&self) -> Info {
+        Info { name: "Body Inspector", kind: Kind::Request }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, data: &Data) {
+        let complete = if data.peek_complete() { "yes" } else { "no" };
+        req.add_header(Header::new("X-Body-Complete", complete));
+    }
+}
+
+/// Upper-cases bodies, but doesn't declare `Kind::ResponseBody`.
+struct Shouter;
+
+#[rocket::async_trait]
+impl Fairing for Shouter {
+    fn info(&self) -> Info {
+        Info { name: "Shouter", kind: Kind::Response }
+    }
+
+    async fn on_response_body<'r>(
+        &self,
+        _: &'r Request<'_>,
+        _: &mut Response<'r>,
+        _: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        panic!("on_response_body must not be called")
+    }
+}
+
+#[rocket::async_test]
+async fn runs_request_and_response_callbacks() {
+    let harness = FairingHarness::new(ApiKey).manage(Counter(AtomicUsize::new(0)));
+
+    let mut request = harness.request(Method::Get, "/items");
+    request.add_header(Header::new("X-Api-Key", "secret"));
+    harness.on_request(&mut request, "").await;
+    assert_eq!(request.uri().path(), "/items");
+
+    let mut response = Response::new();
+    harness.on_response(&request, &mut response).await;
+    assert_eq!(response.status(), Status::Ok);
+
+    let mut request = harness.request(Method::Get, "/items");
+    harness.on_request(&mut request, "").await;
+    assert_eq!(request.uri().path(), "/unauthorized");
+
+    let mut response = Response::new();
+    harness.on_response(&request, &mut response).await;
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let request = harness.request(Method::Get, "/");
+    let counter = request.guard::<State<'_, Counter>>().await.unwrap();
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+}
+
+#[rocket::async_test]
+async fn only_runs_declared_callbacks() {
+    let harness = FairingHarness::new(Shouter);
+    let request = harness.request(Method::Get, "/");
+    let mut response = Response::build()
+        .sized_body(5, Cursor::new("hello"))
+        .finalize();
+
+    harness.on_response(&request, &mut response).await;
+
+    let mut body = String::new();
+    let mut reader = response.body_mut().unwrap().as_reader();
+    reader.read_to_string(&mut body).await.unwrap();
+    assert_eq!(body, "hello");
+}
+
+#[rocket::async_test]
+async fn passes_request_bodies_to_request_callbacks() {
+    let harness = FairingHarness::new(BodyInspector);
+    let mut request = harness.request(Method::Post, "/upload");
+    harness.on_request(&mut request, b"some data".to_vec()).await;
+    assert_eq!(request.headers().get_one("X-Body-Complete"), Some("yes"));
+}
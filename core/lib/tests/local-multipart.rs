@@ -0,0 +1,45 @@
+#[macro_use] extern crate rocket;
+
+use rocket::Data;
+use rocket::data::ToByteUnit;
+use rocket::http::ContentType;
+use rocket::local::Multipart;
+use rocket::local::blocking::Client;
+
+#[post("/upload", data = "<data>")]
+async fn upload(content_type: &ContentType, data: Data) -> String {
+    assert!(content_type.is_form_data());
+    let boundary = content_type.params()
+        .find(|&(key, _)| key == "boundary")
+        .map(|(_, value)| value.to_string())
+        .unwrap();
+
+    let body = data.open(1.mebibytes()).stream_to_string().await.unwrap();
+    body.replace(&boundary, "BOUNDARY")
+}
+
+#[test]
+fn multipart_bodies_are_encoded() {
+    let client = Client::new(rocket::ignite().mount("/", routes![upload])).unwrap();
+    let form = Multipart::new()
+        .text("title", "Report")
+        .file("report", "q1.csv", ContentType::CSV, "q,revenue\n1,100\n");
+
+    let response = client.post("/upload")
+        .header(ContentType::JSON)
+        .multipart(form)
+        .dispatch();
+
+    let expected = "--BOUNDARY\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\
+        \r\n\
+        Report\r\n\
+        --BOUNDARY\r\n\
+        Content-Disposition: form-data; name=\"report\"; filename=\"q1.csv\"\r\n\
+        Content-Type: text/csv; charset=utf-8\r\n\
+        \r\n\
+        q,revenue\n1,100\n\r\n\
+        --BOUNDARY--\r\n";
+
+    assert_eq!(response.into_string().unwrap(), expected);
+}
@@ -0,0 +1,76 @@
+#[macro_use] extern crate rocket;
+
+use rocket::{Request, State};
+use rocket::fairing::AdHoc;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Outcome};
+use rocket::local::blocking::Client;
+
+struct Db(&'static str);
+
+struct Count(usize);
+
+struct Admin(&'static str);
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = &'static str;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Admin, &'static str> {
+        if let Some(outcome) = req.stubbed_guard::<Admin, &'static str>() {
+            return outcome;
+        }
+
+        Outcome::Failure((Status::Unauthorized, "not logged in"))
+    }
+}
+
+#[get("/db")]
+fn db(db: State<'_, Db>, count: State<'_, Count>) -> String {
+    format!("{} {}", db.0, count.0)
+}
+
+#[get("/admin")]
+fn admin(admin: Admin) -> &'static str {
+    admin.0
+}
+
+fn rocket() -> rocket::Rocket {
+    rocket::ignite()
+        .mount("/", routes![db, admin])
+        .manage(Db("production"))
+        .attach(AdHoc::on_attach("Count", |rocket| async move {
+            Ok(rocket.manage(Count(1)))
+        }))
+}
+
+#[test]
+fn state_is_not_overridden_by_default() {
+    let client = Client::new(rocket()).unwrap();
+    assert_eq!(client.get("/db").dispatch().into_string().unwrap(), "production 1");
+    assert_eq!(client.get("/admin").dispatch().status(), Status::Unauthorized);
+}
+
+#[test]
+fn state_is_overridden() {
+    let client = Client::tracked_with(rocket(), |overrides| overrides
+        .state(Db("mock"))
+        .state(Count(42)))
+        .unwrap();
+
+    assert_eq!(client.get("/db").dispatch().into_string().unwrap(), "mock 42");
+}
+
+#[test]
+fn guards_are_stubbed() {
+    let client = Client::untracked_with(rocket(), |overrides| overrides
+        .guard(|req| match req.headers().get_one("X-Admin") {
+            Some("yes") => Outcome::Success(Admin("stubbed admin")),
+            _ => Outcome::Failure((Status::Forbidden, "stubbed failure")),
+        }))
+        .unwrap();
+
+    let response = client.get("/admin").header(rocket::http::Header::new("X-Admin", "yes"));
+    assert_eq!(response.dispatch().into_string().unwrap(), "stubbed admin");
+    assert_eq!(client.get("/admin").dispatch().status(), Status::Forbidden);
+}
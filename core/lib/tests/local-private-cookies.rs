@@ -0,0 +1,67 @@
+#[cfg(feature = "secrets")]
+mod private_cookies {
+    use rocket::{get, post, routes};
+    use rocket::config::{Config, Environment};
+    use rocket::http::{Cookie, CookieJar, Status};
+    use rocket::local::PrivateCookies;
+    use rocket::local::blocking::Client;
+
+    const KEY: &str = "8Xui8SN4mI+7egV/9dlfYYLGQJeEx4+DwmSQLwDVXJg=";
+
+    #[get("/me")]
+    fn me(jar: &CookieJar<'_>) -> Option<String> {
+        jar.get_private("user_id").map(|cookie| cookie.value().to_string())
+    }
+
+    #[post("/login")]
+    fn login(jar: &CookieJar<'_>) {
+        jar.add_private(Cookie::new("user_id", "7"));
+    }
+
+    fn client() -> Client {
+        let config = Config::build(Environment::Development)
+            .secret_key(KEY)
+            .finalize()
+            .unwrap();
+
+        Client::new(rocket::custom(config).mount("/", routes![me, login])).unwrap()
+    }
+
+    #[test]
+    fn sealed_cookies_are_accepted() {
+        let cookies = PrivateCookies::new(KEY).unwrap();
+        let sealed = cookies.seal(Cookie::new("user_id", "42"));
+
+        let client = client();
+        let response = client.get("/me").cookie(sealed.clone()).dispatch();
+        assert_eq!(response.into_string().unwrap(), "42");
+
+        let request = format!("GET /me HTTP/1.1\r\nCookie: {}\r\n\r\n", sealed);
+        let response = client.dispatch_raw(request);
+        assert!(response.ends_with(b"\r\n\r\n42"));
+    }
+
+    #[test]
+    fn cookies_sealed_with_another_key_are_rejected() {
+        let key = "fe4c5b09a9ac372156e44ce133bc940685ef5e0394d6e9274aadacc21e4f2643";
+        let sealed = PrivateCookies::new(key).unwrap().seal(Cookie::new("user_id", "42"));
+
+        let response = client().get("/me").cookie(sealed).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn server_cookies_can_be_opened() {
+        let client = client();
+        let response = client.post("/login").dispatch();
+        let crumb = response.cookies().get("user_id").unwrap();
+        let cookie = Cookie::new("user_id", crumb.value().to_string());
+
+        let cookies = PrivateCookies::from_config(client.rocket().config());
+        assert_eq!(cookies.open(&cookie).unwrap().value(), "7");
+
+        let tampered = Cookie::new("user_id", format!("{}x", cookie.value()));
+        assert!(cookies.open(&tampered).is_none());
+        assert!(PrivateCookies::new("bad key").is_err());
+    }
+}
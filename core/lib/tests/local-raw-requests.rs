@@ -0,0 +1,96 @@
+#[macro_use] extern crate rocket;
+
+use rocket::Request;
+use rocket::request::{self, FromRequest};
+use rocket::local::blocking::Client;
+
+struct Name(String);
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Name {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let name = req.headers().get_one("X-Name").unwrap_or_default();
+        request::Outcome::Success(Name(name.into()))
+    }
+}
+
+#[get("/hi")]
+fn hi(name: Name) -> String {
+    format!("hi {}", name.0)
+}
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String) -> String {
+    body
+}
+
+fn client() -> Client {
+    Client::new(rocket::ignite().mount("/", routes![hi, echo])).unwrap()
+}
+
+fn count(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack.windows(needle.len()).filter(|w| *w == needle).count()
+}
+
+#[test]
+fn dispatches_raw_requests() {
+    let client = client();
+    let request = "GET /hi HTTP/1.1\r\nHost: rocket.rs\r\nX-Name: Bob\r\n\r\n";
+    let response = client.dispatch_raw(request);
+    assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with(b"\r\n\r\nhi Bob"));
+}
+
+#[test]
+fn responds_to_pipelined_requests_in_order() {
+    let client = client();
+    let response = client.dispatch_raw("\
+        POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nfirst\
+        GET /hi HTTP/1.1\r\n\r\n\
+        POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nsecond\r\n0\r\n\r\n");
+
+    assert_eq!(count(&response, b"HTTP/1.1 200 OK\r\n"), 3);
+    let (first, hi, second) = (
+        count(&response, b"first"), count(&response, b"hi "), count(&response, b"second")
+    );
+
+    assert_eq!((first, hi, second), (1, 1, 1));
+    let position = |needle: &[u8]| response.windows(needle.len()).position(|w| w == needle);
+    assert!(position(b"first") < position(b"hi ") && position(b"hi ") < position(b"second"));
+}
+
+#[test]
+fn rejects_malformed_requests() {
+    let client = client();
+    let response = client.dispatch_raw("GET /hi\r\n\r\n");
+    assert!(response.starts_with(b"HTTP/1.1 400 Bad Request\r\n"));
+
+    let response = client.dispatch_raw("GET /hi HTTP/1.1\r\nBad Header\r\n\r\n");
+    assert!(response.starts_with(b"HTTP/1.1 400 Bad Request\r\n"));
+
+    // Conflicting lengths are rejected rather than guessed at.
+    let response = client.dispatch_raw("\
+        POST /echo HTTP/1.1\r\nContent-Length: 3\r\nContent-Length: 5\r\n\r\nfirst");
+
+    assert!(response.starts_with(b"HTTP/1.1 400 Bad Request\r\n"));
+}
+
+#[test]
+fn decodes_non_utf8_header_values_lossily() {
+    let client = client();
+    let response = client.dispatch_raw(&b"GET /hi HTTP/1.1\r\nX-Name: caf\xe9\r\n\r\n"[..]);
+    assert!(response.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("hi caf\u{FFFD}".as_bytes()));
+}
+
+#[test]
+fn async_dispatches_raw_requests() {
+    rocket::async_test(async {
+        let rocket = rocket::ignite().mount("/", routes![hi, echo]);
+        let client = rocket::local::asynchronous::Client::new(rocket).await.unwrap();
+        let response = client.dispatch_raw("GET /hi HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with(b"hi "));
+    })
+}
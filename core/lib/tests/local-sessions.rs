@@ -0,0 +1,47 @@
+#[macro_use] extern crate rocket;
+
+use rocket::http::{Cookie, CookieJar};
+use rocket::local::blocking::Client;
+
+#[post("/login/<name>")]
+fn login(jar: &CookieJar<'_>, name: String) {
+    jar.add(Cookie::new("user", name));
+}
+
+#[get("/whoami")]
+fn whoami(jar: &CookieJar<'_>) -> String {
+    jar.get("user").map(|c| c.value().to_string()).unwrap_or_else(|| "nobody".into())
+}
+
+fn client() -> Client {
+    Client::new(rocket::ignite().mount("/", routes![login, whoami])).unwrap()
+}
+
+#[test]
+fn sessions_have_separate_cookies() {
+    let client = client();
+    let alice = client.session("alice");
+    let bob = client.session("bob");
+
+    alice.post("/login/alice").dispatch();
+    assert_eq!(alice.get("/whoami").dispatch().into_string().unwrap(), "alice");
+    assert_eq!(bob.get("/whoami").dispatch().into_string().unwrap(), "nobody");
+    assert_eq!(client.get("/whoami").dispatch().into_string().unwrap(), "nobody");
+
+    bob.post("/login/bob").dispatch();
+    assert_eq!(bob.get("/whoami").dispatch().into_string().unwrap(), "bob");
+    assert_eq!(alice.get("/whoami").dispatch().into_string().unwrap(), "alice");
+
+    assert_eq!(alice.cookies().get("user").unwrap().value(), "alice");
+    assert!(client.cookies().get("user").is_none());
+}
+
+#[test]
+fn sessions_with_the_same_name_share_cookies() {
+    let client = client();
+    client.session("carol").post("/login/carol").dispatch();
+
+    let carol = client.session("carol");
+    assert_eq!(carol.name(), "carol");
+    assert_eq!(carol.get("/whoami").dispatch().into_string().unwrap(), "carol");
+}
@@ -0,0 +1,109 @@
+#[macro_use] extern crate rocket;
+
+use std::io::{Read, Write};
+
+use rocket::Request;
+use rocket::futures::stream::StreamExt;
+use rocket::http::{ContentType, Header, Status};
+use rocket::request::Upgrade;
+use rocket::response::content::Content;
+use rocket::tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+
+const EVENTS: &str = "retry: 1000\n\n\
+    event: join\ndata: alice\nid: 1\n\n\
+    : keep-alive\n\n\
+    data: hello\ndata: world\n\n\
+    data: interrupted";
+
+#[get("/events")]
+fn events() -> Content<&'static str> {
+    Content(ContentType::new("text", "event-stream"), EVENTS)
+}
+
+#[get("/shout")]
+fn shout(req: &Request<'_>) -> Option<Upgrade> {
+    let upgrade = req.upgrade("shout", |io| async move {
+        let (mut reader, mut writer) = io::split(io);
+        let mut buf = [0; 64];
+        while let Ok(n) = reader.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+
+            let _ = writer.write_all(&buf[..n].to_ascii_uppercase()).await;
+        }
+    });
+
+    Some(upgrade?.header(Header::new("X-Shout", "on")))
+}
+
+fn rocket() -> rocket::Rocket {
+    rocket::ignite().mount("/", routes![events, shout])
+}
+
+#[test]
+fn events_are_read_incrementally() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket()).unwrap();
+    let mut response = client.get("/events").dispatch();
+    let events: Vec<_> = response.events().map(|e| e.unwrap()).collect();
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].event(), Some("join"));
+    assert_eq!(events[0].data(), "alice");
+    assert_eq!(events[0].id(), Some("1"));
+    assert_eq!(events[0].retry(), Some(std::time::Duration::from_secs(1)));
+
+    assert_eq!(events[1].event(), None);
+    assert_eq!(events[1].data(), "hello\nworld");
+    assert_eq!(events[1].id(), Some("1"));
+}
+
+#[rocket::async_test]
+async fn async_events_are_read_incrementally() {
+    use rocket::local::asynchronous::Client;
+
+    let client = Client::new(rocket()).await.unwrap();
+    let mut response = client.get("/events").dispatch().await;
+    let data: Vec<_> = response.events()
+        .map(|event| event.unwrap().data().to_string())
+        .collect()
+        .await;
+
+    assert_eq!(data, vec!["alice", "hello\nworld"]);
+}
+
+#[test]
+fn local_connections_are_upgraded() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket()).unwrap();
+    let mut response = client.get("/shout")
+        .header(Header::new("Connection", "Upgrade"))
+        .header(Header::new("Upgrade", "shout"))
+        .dispatch();
+
+    assert_eq!(response.status(), Status::SwitchingProtocols);
+    assert_eq!(response.headers().get_one("X-Shout"), Some("on"));
+
+    let mut io = response.upgraded().unwrap();
+    for message in &["hello", "again"] {
+        io.write_all(message.as_bytes()).unwrap();
+        let mut buf = [0; 5];
+        io.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, message.to_uppercase().as_bytes());
+    }
+
+    assert!(response.upgraded().is_none());
+}
+
+#[test]
+fn requests_without_upgrade_are_not_upgraded() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket()).unwrap();
+    let mut response = client.get("/shout").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response.upgraded().is_none());
+}
@@ -0,0 +1,86 @@
+#[macro_use] extern crate rocket;
+
+use rocket::{Config, Logger};
+use rocket::config::{Environment, LoggingLevel};
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use rocket::request::{self, FromRequest, Request};
+
+struct Admin;
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match req.headers().get_one("X-Admin") {
+            Some("yes") => request::Outcome::Success(Admin),
+            Some(_) => request::Outcome::Failure((Status::Unauthorized, ())),
+            None => request::Outcome::Forward(()),
+        }
+    }
+}
+
+#[get("/level")]
+fn level(logger: Logger) -> String {
+    logger.level().to_string()
+}
+
+#[test]
+fn log_level_can_be_changed_at_runtime() {
+    let rocket = rocket::ignite()
+        .mount("/", routes![level])
+        .mount("/admin/log", Logger::routes());
+
+    let client = Client::new(rocket).unwrap();
+    let logger = client.cargo().logger();
+    assert!(logger.set_level(LoggingLevel::Normal));
+
+    let response = client.get("/admin/log").dispatch();
+    assert_eq!(response.into_string().unwrap(), "normal");
+
+    let response = client.put("/admin/log").body("debug\n").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), "debug");
+    assert_eq!(logger.level(), LoggingLevel::Debug);
+
+    let response = client.get("/level").dispatch();
+    assert_eq!(response.into_string().unwrap(), "debug");
+
+    let response = client.put("/admin/log").body("loud").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(logger.level(), LoggingLevel::Debug);
+
+    assert!(logger.set_level(LoggingLevel::Normal));
+    assert_eq!(logger.level(), LoggingLevel::Normal);
+}
+
+#[test]
+fn log_level_changes_are_restricted() {
+    let config = Config::build(Environment::Production).log_level(LoggingLevel::Critical);
+    let rocket = rocket::custom(config.unwrap())
+        .mount("/admin/log", Logger::routes())
+        .mount("/admin/guarded", Logger::guarded_routes::<Admin>());
+
+    // Only the statuses are checked: the level is global, shared by tests.
+    let client = Client::new(rocket).unwrap();
+    let response = client.put("/admin/log").body("debug").dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client.put("/admin/guarded").body("debug").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+
+    let response = client.put("/admin/guarded")
+        .header(Header::new("X-Admin", "no"))
+        .body("debug")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    let response = client.put("/admin/guarded")
+        .header(Header::new("X-Admin", "yes"))
+        .body("loud")
+        .dispatch();
+
+    assert_eq!(response.status(), Status::BadRequest);
+}
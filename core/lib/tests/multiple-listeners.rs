@@ -0,0 +1,75 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+
+use rocket::Shutdown;
+use rocket::config::{Config, Environment, ListenerConfig};
+use rocket::fairing::AdHoc;
+use rocket::local::blocking::Client;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+#[get("/")]
+fn index(listener: &ListenerConfig) -> String {
+    listener.name.clone()
+}
+
+#[get("/", rank = 2)]
+fn unknown() -> &'static str {
+    "none"
+}
+
+/// Launches a server with an additional `admin` listener, returning the ports
+/// of the default and `admin` listeners and a handle to shut the server down.
+async fn launch() -> (u16, u16, Shutdown) {
+    let mut config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    config.add_listener("admin", "127.0.0.1", 0).unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, unknown])
+        .attach(AdHoc::on_launch("Ports", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let config = cargo.config();
+            let _ = tx.send((config.port, config.listeners[0].port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+async fn get(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut buf = vec![];
+    stream.read_to_end(&mut buf).await.unwrap();
+    let response = String::from_utf8(buf).unwrap();
+    response.rsplit("\r\n\r\n").next().unwrap().to_string()
+}
+
+#[rocket::async_test]
+async fn each_listener_serves_the_application() {
+    let (default_port, admin_port, shutdown) = launch().await;
+    assert_ne!(default_port, 0);
+    assert_ne!(admin_port, 0);
+    assert_ne!(default_port, admin_port);
+
+    assert_eq!(get(default_port).await, "default");
+    assert_eq!(get(admin_port).await, "admin");
+
+    shutdown.shutdown();
+}
+
+#[test]
+fn local_requests_have_no_listener() {
+    let client = Client::new(rocket::ignite().mount("/", routes![index, unknown])).unwrap();
+    assert_eq!(client.get("/").dispatch().into_string().unwrap(), "none");
+}
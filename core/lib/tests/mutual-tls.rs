@@ -0,0 +1,98 @@
+#![cfg(all(feature = "tls", not(feature = "native-tls")))]
+
+#[macro_use] extern crate rocket;
+
+use std::sync::{Arc, Mutex};
+
+use rocket::Shutdown;
+use rocket::config::{Config, ConfigBuilder, Environment, MutualTls};
+use rocket::fairing::AdHoc;
+use rocket::request::PeerCertificates;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+use tokio_rustls::{rustls, webpki, TlsConnector};
+use tokio_rustls::rustls::internal::pemfile;
+
+const PRIVATE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private");
+
+fn private(file: &str) -> String {
+    format!("{}/{}", PRIVATE, file)
+}
+
+#[get("/")]
+fn index(certs: &PeerCertificates) -> String {
+    format!("{} certificates", certs.chain().len())
+}
+
+fn config(ca_certs: &str) -> ConfigBuilder {
+    let mtls = MutualTls::required(private(ca_certs)).crl(private("crl.pem"));
+    Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .tls(private("cert.pem"), private("key.pem"))
+        .mtls(mtls)
+}
+
+async fn launch() -> (u16, Shutdown) {
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config("ca_cert.pem").unwrap())
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+/// Requests `/` from the server at `port` with the client certificate in
+/// `{client}_cert.pem`, returning the response, if any.
+async fn get(port: u16, client: &str) -> std::io::Result<String> {
+    let certs = std::fs::read(private(&format!("{}_cert.pem", client))).unwrap();
+    let key = std::fs::read(private(&format!("{}_key.pem", client))).unwrap();
+    let certs = pemfile::certs(&mut &certs[..]).unwrap();
+    let key = pemfile::pkcs8_private_keys(&mut &key[..]).unwrap().remove(0);
+
+    let mut tls_config = rustls::ClientConfig::new();
+    let ca = std::fs::read(private("ca_cert.pem")).unwrap();
+    tls_config.root_store.add_pem_file(&mut &ca[..]).unwrap();
+    tls_config.set_single_client_cert(certs, key).unwrap();
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    let mut stream = TlsConnector::from(Arc::new(tls_config)).connect(name, stream).await?;
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+#[rocket::async_test]
+async fn revoked_client_certificates_are_refused() {
+    let (port, shutdown) = launch().await;
+
+    let response = get(port, "client").await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("1 certificates"));
+
+    // With TLS 1.3, the client may only learn that its certificate was
+    // refused once it reads, so any failure along the way will do.
+    let response = get(port, "revoked").await;
+    assert!(response.map_or(true, |r| r.is_empty()));
+
+    shutdown.shutdown();
+}
+
+#[test]
+fn crls_must_be_signed_by_a_configured_ca() {
+    assert!(config("ca_cert.pem").finalize().is_ok());
+
+    // `cert.pem` is the server's own certificate, not the CRL's issuer.
+    assert!(config("cert.pem").finalize().is_err());
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "native-tls")]
+
+#[macro_use] extern crate rocket;
+
+use std::sync::{Arc, Mutex};
+
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+use tokio_rustls::{rustls, webpki, TlsConnector};
+
+const PRIVATE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private");
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, TLS!"
+}
+
+#[rocket::async_test]
+async fn native_tls_serves_https() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .tls(format!("{}/cert.pem", PRIVATE), format!("{}/key_pkcs8.pem", PRIVATE))
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+
+    let mut tls_config = rustls::ClientConfig::new();
+    let ca = std::fs::read(format!("{}/ca_cert.pem", PRIVATE)).unwrap();
+    tls_config.root_store.add_pem_file(&mut &ca[..]).unwrap();
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let connector = TlsConnector::from(Arc::new(tls_config));
+    let mut stream = connector.connect(name, stream).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("Hello, TLS!"));
+
+    shutdown.shutdown();
+}
@@ -0,0 +1,64 @@
+#[macro_use] extern crate rocket;
+
+use rocket::config::Config;
+use rocket::local::blocking::Client;
+use rocket::http::{ContentType, Status};
+
+#[get("/hello/<name>")]
+fn hello(name: String) -> String {
+    format!("Hello, {}!", name)
+}
+
+#[post("/users", format = "json", data = "<body>")]
+fn create(body: String) -> String {
+    body
+}
+
+#[get("/users/<id>")]
+fn user(id: usize) -> String {
+    id.to_string()
+}
+
+fn client(config: Config) -> Client {
+    let rocket = rocket::custom(config).mount("/", routes![hello, create, user]);
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn near_misses_are_listed_in_development() {
+    let client = client(Config::development());
+
+    let response = client.post("/hello/Bob").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    let body = response.into_string().unwrap();
+    assert!(body.contains("Routes that nearly matched"));
+    assert!(body.contains("GET /hello/&lt;name&gt;: method is GET, not POST"));
+
+    let response = client.get("/helo/Bob").dispatch();
+    let body = response.into_string().unwrap();
+    assert!(body.contains("segment 1 is &#x27;hello&#x27;, not &#x27;helo&#x27;"));
+
+    let response = client.post("/users").header(ContentType::Plain).dispatch();
+    let body = response.into_string().unwrap();
+    assert!(body.contains("format is application/json, not text/plain"));
+
+    let response = client.get("/users/bob").dispatch();
+    let body = response.into_string().unwrap();
+    assert!(body.contains("matched but forwarded"));
+}
+
+#[test]
+fn no_near_misses_for_unrelated_paths() {
+    let client = client(Config::development());
+    let response = client.get("/a/b/c/d").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(!response.into_string().unwrap().contains("nearly matched"));
+}
+
+#[test]
+fn near_misses_are_not_listed_in_production() {
+    let client = client(Config::production());
+    let response = client.post("/hello/Bob").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(!response.into_string().unwrap().contains("nearly matched"));
+}
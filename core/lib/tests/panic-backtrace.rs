@@ -0,0 +1,35 @@
+#[macro_use] extern crate rocket;
+
+use std::backtrace::BacktraceStatus;
+
+use rocket::catcher::Panic;
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[get("/panic")]
+fn panics() -> &'static str {
+    panic!("handler exploded")
+}
+
+#[catch(500, error = "<panic>")]
+fn internal_error(panic: &Panic) -> String {
+    match panic.backtrace().status() {
+        BacktraceStatus::Captured => "captured".into(),
+        status => format!("{:?}", status),
+    }
+}
+
+// Kept in its own test binary: whether backtraces are enabled is read from the
+// environment once per process.
+#[test]
+fn panics_capture_a_backtrace() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    let rocket = rocket::ignite()
+        .mount("/", routes![panics])
+        .register(catchers![internal_error]);
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/panic").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.into_string().unwrap(), "captured");
+}
@@ -0,0 +1,44 @@
+#[macro_use] extern crate rocket;
+
+use rocket::catcher::Panic;
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[get("/panic")]
+fn panics() -> &'static str {
+    panic!("handler exploded")
+}
+
+#[get("/ok")]
+fn ok() -> &'static str {
+    "ok"
+}
+
+#[catch(500, error = "<panic>")]
+fn internal_error(panic: &Panic) -> String {
+    assert!(panic.location().unwrap().contains("panic-to-500.rs"));
+    format!("caught: {}", panic.message())
+}
+
+#[test]
+fn panics_are_caught_and_handled_by_catcher() {
+    let rocket = rocket::ignite()
+        .mount("/", routes![panics, ok])
+        .register(catchers![internal_error]);
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/panic").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.into_string().unwrap(), "caught: handler exploded");
+
+    // The client and server remain usable after a panic.
+    let response = client.get("/ok").dispatch();
+    assert_eq!(response.into_string().unwrap(), "ok");
+}
+
+#[test]
+fn panics_without_catcher_use_default_500() {
+    let client = Client::new(rocket::ignite().mount("/", routes![panics])).unwrap();
+    let response = client.get("/panic").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+}
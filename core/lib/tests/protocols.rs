@@ -0,0 +1,91 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+
+use rocket::Shutdown;
+use rocket::config::{Config, Environment, Protocol};
+use rocket::fairing::AdHoc;
+use rocket::local::blocking::Client;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+#[get("/")]
+fn index(protocol: Protocol) -> String {
+    protocol.to_string()
+}
+
+#[get("/", rank = 2)]
+fn unknown() -> &'static str {
+    "none"
+}
+
+/// Launches a server serving `protocols`, returning its port and a handle to
+/// shut it down.
+async fn launch(protocols: Vec<Protocol>) -> (u16, Shutdown) {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .protocols(protocols)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index, unknown])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+async fn get(port: u16, version: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("GET / {}\r\nHost: localhost\r\nConnection: close\r\n\r\n", version);
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut buf = vec![];
+    let _ = stream.read_to_end(&mut buf).await;
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+#[rocket::async_test]
+async fn reports_negotiated_protocol() {
+    let (port, shutdown) = launch(vec![Protocol::H2, Protocol::Http1]).await;
+    let response = get(port, "HTTP/1.1").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("http/1.1"));
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn reports_http1_0_requests() {
+    let (port, shutdown) = launch(vec![Protocol::Http1]).await;
+    let response = get(port, "HTTP/1.0").await;
+    assert!(response.contains(" 200 OK"));
+    assert!(response.ends_with("http/1.0"));
+    shutdown.shutdown();
+}
+
+#[test]
+fn serves_only_http1_by_default() {
+    let config = Config::build(Environment::Development).unwrap();
+    assert_eq!(config.protocols, [Protocol::Http1]);
+}
+
+#[rocket::async_test]
+async fn falls_back_to_http1() {
+    // `h2` is only served over TLS; this plaintext listener serves HTTP/1.1.
+    let (port, shutdown) = launch(vec![Protocol::H2]).await;
+    assert!(get(port, "HTTP/1.1").await.ends_with("http/1.1"));
+    shutdown.shutdown();
+}
+
+#[test]
+fn local_requests_have_no_protocol() {
+    let client = Client::new(rocket::ignite().mount("/", routes![index, unknown])).unwrap();
+    assert_eq!(client.get("/").dispatch().into_string().unwrap(), "none");
+}
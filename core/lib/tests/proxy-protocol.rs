@@ -0,0 +1,174 @@
+#[macro_use] extern crate rocket;
+
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::Shutdown;
+use rocket::config::{Config, ConfigBuilder, Environment, ServerConfig};
+use rocket::fairing::AdHoc;
+use rocket::http::ProxyHeader;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/")]
+fn index(remote: SocketAddr, header: &ProxyHeader) -> String {
+    let cipher = header.tls.as_ref().and_then(|tls| tls.cipher.as_deref());
+    format!("{} {:?}", remote, cipher)
+}
+
+/// Launches a server that requires PROXY protocol headers, returning its port
+/// and a handle to shut it down.
+async fn launch() -> (u16, Shutdown) {
+    launch_with(|config| config).await
+}
+
+/// Like [`launch()`], with the configuration modified by `f`.
+async fn launch_with<F>(f: F) -> (u16, Shutdown)
+    where F: FnOnce(ConfigBuilder) -> ConfigBuilder
+{
+    let builder = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .proxy_protocol(true);
+
+    let config = f(builder).unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    rx.await.unwrap()
+}
+
+/// Sends `header` followed by a request on a new connection, returning the
+/// response.
+async fn send(port: u16, header: &[u8]) -> String {
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(&[header, &request[..]].concat()).await.unwrap();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+#[rocket::async_test]
+async fn v1_header_sets_remote_address() {
+    let (port, shutdown) = launch().await;
+
+    let response = send(port, b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("192.0.2.1:56324 None"));
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn v2_header_relays_tls_details() {
+    let (port, shutdown) = launch().await;
+
+    let mut tls = vec![0x01, 0, 0, 0, 0, 0x23, 0, 6];
+    tls.extend_from_slice(b"AES256");
+
+    let mut payload = vec![192, 0, 2, 1, 198, 51, 100, 2, 0xDC, 0x04, 0x01, 0xBB];
+    payload.extend_from_slice(&[0x20, 0, tls.len() as u8]);
+    payload.extend_from_slice(&tls);
+
+    let mut header = b"\r\n\r\n\0\r\nQUIT\n\x21\x11".to_vec();
+    header.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    header.extend_from_slice(&payload);
+
+    let response = send(port, &header).await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("192.0.2.1:56324 Some(\"AES256\")"));
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn connections_without_header_are_closed() {
+    let (port, shutdown) = launch().await;
+
+    let response = send(port, b"").await;
+    assert!(response.is_empty());
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn headers_are_read_with_a_timeout() {
+    let server = ServerConfig { read_header_timeout: Some(1), ..ServerConfig::default() };
+    let (port, shutdown) = launch_with(|config| config.server(server)).await;
+
+    // A client that never sends its header is disconnected.
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    assert!(buf.is_empty());
+
+    shutdown.shutdown();
+}
+
+#[rocket::async_test]
+async fn pending_headers_count_against_max_connections() {
+    let server = ServerConfig { max_connections: Some(1), ..ServerConfig::default() };
+    let (port, shutdown) = launch_with(|config| config.server(server)).await;
+
+    // The first connection, whose header is pending, takes the only slot, so
+    // the second is closed without its header being read.
+    let pending = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    rocket::tokio::time::delay_for(Duration::from_millis(100)).await;
+    let response = send(port, b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\n").await;
+    assert!(response.is_empty());
+
+    drop(pending);
+    rocket::tokio::time::delay_for(Duration::from_millis(100)).await;
+    let response = send(port, b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+
+    shutdown.shutdown();
+}
+
+#[cfg(all(feature = "tls", not(feature = "native-tls")))]
+#[rocket::async_test]
+async fn header_precedes_tls_handshake() {
+    use std::sync::Arc;
+    use tokio_rustls::{rustls, webpki, TlsConnector};
+
+    let private = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/tls/private");
+    let certs = format!("{}/cert.pem", private);
+    let key = format!("{}/key.pem", private);
+    let (port, shutdown) = launch_with(|config| config.tls(certs, key)).await;
+
+    let mut tls_config = rustls::ClientConfig::new();
+    let ca = std::fs::read(format!("{}/ca_cert.pem", private)).unwrap();
+    tls_config.root_store.add_pem_file(&mut &ca[..]).unwrap();
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\n").await.unwrap();
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let connector = TlsConnector::from(Arc::new(tls_config));
+    let mut stream = connector.connect(name, stream).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut buf = vec![];
+    timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await.unwrap().unwrap();
+    let response = String::from_utf8_lossy(&buf);
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("192.0.2.1:56324 None"));
+
+    shutdown.shutdown();
+}
@@ -0,0 +1,121 @@
+#[macro_use] extern crate rocket;
+
+use std::fmt;
+
+use std::num::ParseIntError;
+
+use rocket::{Request, Data, Route};
+use rocket::catcher::TypedError;
+use rocket::request::{self, FromRequest};
+use rocket::handler::{Outcome, HandlerFuture};
+use rocket::fairing::AdHoc;
+use rocket::local::blocking::Client;
+use rocket::http::{Header, Method, Status};
+
+#[derive(Debug)]
+struct TokenError(&'static str);
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "token error: {}", self.0)
+    }
+}
+
+impl TypedError for TokenError { }
+
+struct Token;
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Token {
+    type Error = TokenError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match req.headers().get_one("token") {
+            Some("valid") => request::Outcome::Success(Token),
+            Some(_) => request::Outcome::Failure((Status::Unauthorized, TokenError("invalid"))),
+            None => request::Outcome::Failure((Status::Unauthorized, TokenError("missing"))),
+        }
+    }
+}
+
+#[get("/")]
+fn index(_token: Token) -> &'static str {
+    "welcome"
+}
+
+fn parse<'r>(req: &'r Request<'_>, _: Data) -> HandlerFuture<'r> {
+    let outcome = match req.uri().query().unwrap_or("").parse::<u8>() {
+        Ok(n) => Outcome::from(req, n.to_string()),
+        Err(e) => {
+            req.set_error(e);
+            Outcome::failure(Status::UnprocessableEntity)
+        }
+    };
+
+    Box::pin(async move { outcome })
+}
+
+/// The recorded error of `req`, if it has one of the types this test uses.
+fn error(req: &Request<'_>) -> Option<String> {
+    req.error::<TokenError>().map(|e| e.to_string())
+        .or_else(|| req.error::<ParseIntError>().map(|e| e.to_string()))
+}
+
+#[catch(default)]
+fn default(status: Status, req: &Request) -> String {
+    match error(req) {
+        Some(error) => format!("{}: {}", status.code, error),
+        None => format!("{}", status.code),
+    }
+}
+
+fn client() -> Client {
+    let rocket = rocket::ignite()
+        .mount("/", routes![index])
+        .mount("/parse", vec![Route::new(Method::Get, "/", parse)])
+        .register(catchers![default])
+        .attach(AdHoc::on_response("Error Header", |req, res| {
+            Box::pin(async move {
+                if let Some(error) = error(req) {
+                    res.set_header(Header::new("X-Error", error));
+                }
+            })
+        }));
+
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn guard_error_is_recorded() {
+    let client = client();
+    let response = client.get("/").header(Header::new("token", "bad")).dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    assert_eq!(response.headers().get_one("X-Error"), Some("token error: invalid"));
+    assert_eq!(response.into_string().unwrap(), "401: token error: invalid");
+
+    let response = client.get("/").dispatch();
+    assert_eq!(response.into_string().unwrap(), "401: token error: missing");
+}
+
+#[test]
+fn handler_error_is_recorded() {
+    let client = client();
+    let response = client.get("/parse?12").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.headers().get_one("X-Error").is_none());
+    assert_eq!(response.into_string().unwrap(), "12");
+
+    let response = client.get("/parse?nope").dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert!(response.headers().get_one("X-Error").is_some());
+    assert!(response.into_string().unwrap().starts_with("422: invalid digit"));
+}
+
+#[test]
+fn nothing_recorded_without_error() {
+    let client = client();
+    let response = client.get("/missing").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response.headers().get_one("X-Error").is_none());
+    assert_eq!(response.into_string().unwrap(), "404");
+}
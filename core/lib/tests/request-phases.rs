@@ -0,0 +1,74 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::{Arc, Mutex};
+
+use rocket::{Rocket, Route, Request, Data};
+use rocket::fairing::AdHoc;
+use rocket::handler::{self, Outcome};
+use rocket::http::Method;
+use rocket::local::blocking::Client;
+use rocket::request::Phase;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, world!"
+}
+
+#[get("/<n>", rank = 1)]
+fn number(n: usize) -> String {
+    n.to_string()
+}
+
+#[get("/<name>", rank = 2)]
+fn name(name: String) -> String {
+    name
+}
+
+fn manual<'r>(req: &'r Request<'_>, _: Data) -> handler::HandlerFuture<'r> {
+    Outcome::from(req, "manual").pin()
+}
+
+fn recorded(rocket: Rocket, uri: &str) -> Vec<Phase> {
+    let phases = Arc::new(Mutex::new(vec![]));
+    let recorder = phases.clone();
+    let rocket = rocket.attach(AdHoc::on_response("Record", move |req, _| {
+        let recorder = recorder.clone();
+        Box::pin(async move {
+            let recorded = req.phases().into_iter().map(|t| t.phase).collect();
+            *recorder.lock().unwrap() = recorded;
+        })
+    }));
+
+    let client = Client::new(rocket).unwrap();
+    client.get(uri).dispatch();
+    let recorded = phases.lock().unwrap().clone();
+    recorded
+}
+
+#[test]
+fn generated_handler_phases() {
+    let phases = recorded(rocket::ignite().mount("/", routes![index]), "/");
+    assert_eq!(phases, vec![Phase::Routing, Phase::Guards, Phase::Handler, Phase::Responder]);
+}
+
+#[test]
+fn forwarded_request_phases() {
+    let phases = recorded(rocket::ignite().mount("/", routes![number, name]), "/Bob");
+    assert_eq!(phases, vec![
+        Phase::Routing, Phase::Guards,
+        Phase::Routing, Phase::Guards, Phase::Handler, Phase::Responder,
+    ]);
+}
+
+#[test]
+fn manual_handler_phases() {
+    let route = Route::new(Method::Get, "/", manual);
+    let phases = recorded(rocket::ignite().mount("/", vec![route]), "/");
+    assert_eq!(phases, vec![Phase::Routing, Phase::Handler]);
+}
+
+#[test]
+fn unmatched_request_phases() {
+    let phases = recorded(rocket::ignite(), "/");
+    assert_eq!(phases, vec![Phase::Routing]);
+}
@@ -0,0 +1,152 @@
+#[macro_use] extern crate rocket;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rocket::data::ToByteUnit;
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::local::blocking::Client;
+use rocket::record::{Recorder, Recording};
+
+#[post("/echo", data = "<body>")]
+fn echo(body: String, content_type: &ContentType) -> String {
+    format!("{}: {}", content_type, body)
+}
+
+#[get("/hi")]
+fn hi() -> &'static str {
+    "hi"
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("rocket-record-{}-{}", name, std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Waits for the `n` recordings in `dir` written by the recorder's background
+/// thread and returns them.
+fn recordings(dir: &Path, n: usize) -> Vec<(PathBuf, Recording)> {
+    let start = Instant::now();
+    loop {
+        let recordings = Recording::load_dir(dir).unwrap_or_default();
+        if recordings.len() >= n || start.elapsed() > Duration::from_secs(5) {
+            return recordings;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn client(recorder: Recorder) -> Client {
+    let rocket = rocket::ignite().mount("/", routes![echo, hi]).attach(recorder);
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn records_and_replays_requests() {
+    let dir = dir("replay");
+    let client = client(Recorder::new(&dir));
+    let response = client.post("/echo")
+        .header(ContentType::Plain)
+        .header(Header::new("Authorization", "Bearer secret"))
+        .body("hello")
+        .dispatch();
+
+    assert_eq!(response.into_string().unwrap(), "text/plain; charset=utf-8: hello");
+    client.get("/hi?a=b").dispatch();
+
+    let recordings = recordings(&dir, 2);
+    assert_eq!(recordings.len(), 2);
+
+    let (_, posted) = &recordings[0];
+    assert_eq!(posted.method(), Method::Post);
+    assert_eq!(posted.uri(), "/echo");
+    assert_eq!(posted.body(), b"hello");
+    assert!(posted.headers().iter()
+        .any(|h| h.name().as_str() == "Authorization" && h.value() == "[redacted]"));
+
+    let (_, got) = &recordings[1];
+    assert_eq!(got.method(), Method::Get);
+    assert_eq!(got.uri(), "/hi?a=b");
+
+    let replayer = Client::new(rocket::ignite().mount("/", routes![echo, hi])).unwrap();
+    let response = replayer.replay(posted).dispatch();
+    assert_eq!(response.into_string().unwrap(), "text/plain; charset=utf-8: hello");
+
+    let response = replayer.replay(got).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn samples_and_truncates() {
+    let dir = dir("sample");
+    let client = client(Recorder::new(&dir).sample(2).body_limit(3.bytes()));
+    for _ in 0..4 {
+        client.post("/echo")
+            .header(ContentType::Plain)
+            .header(Header::new("Content-Length", "5"))
+            .body("hello")
+            .dispatch();
+    }
+
+    let recordings = recordings(&dir, 2);
+    assert_eq!(recordings.len(), 2);
+    assert!(recordings.iter().all(|(_, r)| r.body() == b"hel"));
+
+    // The recorded framing matches the truncated body.
+    let length = |r: &Recording| r.headers().iter()
+        .filter(|h| h.name() == "Content-Length")
+        .map(|h| h.value().to_string())
+        .collect::<Vec<_>>();
+
+    assert!(recordings.iter().all(|(_, r)| length(r) == ["3"]));
+
+    // The route still receives the whole body.
+    let response = client.post("/echo").header(ContentType::Plain).body("hello").dispatch();
+    assert_eq!(response.into_string().unwrap(), "text/plain; charset=utf-8: hello");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn redacts_bodies() {
+    let dir = dir("redact");
+    let recorder = Recorder::new(&dir).redact_body(|req, body| {
+        if req.content_type() == Some(&ContentType::Plain) {
+            *body = body.to_ascii_uppercase();
+        }
+    });
+
+    let client = client(recorder);
+    let response = client.post("/echo").header(ContentType::Plain).body("hello").dispatch();
+    assert_eq!(response.into_string().unwrap(), "text/plain; charset=utf-8: hello");
+
+    let recordings = recordings(&dir, 1);
+    assert_eq!(recordings.len(), 1);
+    assert_eq!(recordings[0].1.body(), b"HELLO");
+
+    #[cfg(unix)] {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&recordings[0].0).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parses_handwritten_recordings() {
+    let recording = Recording::parse(b"GET /hi HTTP/1.1\r\nAccept: */*\r\n\r\n").unwrap();
+    assert_eq!(recording.method(), Method::Get);
+    assert_eq!(recording.headers(), &[Header::new("Accept", "*/*")]);
+    assert!(recording.body().is_empty());
+
+    assert!(Recording::parse(b"GET /hi HTTP/1.1\r\n").is_err());
+    assert!(Recording::parse(b"FETCH /hi HTTP/1.1\r\n\r\n").is_err());
+    assert!(Recording::parse(b"GET /hi HTTP/1.1\r\nno colon\r\n\r\n").is_err());
+}
@@ -0,0 +1,122 @@
+#[macro_use] extern crate rocket;
+
+use std::io::{self, Cursor};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use rocket::{Request, Response};
+use rocket::fairing::{AdHoc, Fairing, Info, Kind};
+use rocket::response::{content, ResponseBody, Stream};
+use rocket::tokio::io::{AsyncRead, AsyncReadExt};
+use rocket::local::blocking::Client;
+use rocket::http::{ContentType, Status};
+
+struct Envelope;
+
+#[rocket::async_trait]
+impl Fairing for Envelope {
+    fn info(&self) -> Info {
+        Info { name: "Envelope", kind: Kind::ResponseBody }
+    }
+
+    async fn on_response_body<'r>(
+        &self,
+        _: &'r Request<'_>,
+        res: &mut Response<'r>,
+        body: ResponseBody<'r>
+    ) -> ResponseBody<'r> {
+        if res.content_type() != Some(ContentType::JSON) {
+            return body;
+        }
+
+        let data = body.into_string().await.unwrap();
+        let wrapped = format!(r#"{{"status":{},"data":{}}}"#, res.status().code, data);
+        res.set_status(Status::Ok);
+        ResponseBody::sized(Cursor::new(wrapped.clone()), wrapped.len())
+    }
+}
+
+#[get("/page")]
+fn page() -> content::Html<&'static str> {
+    content::Html("<html><body>Hi</body></html>")
+}
+
+#[get("/json")]
+fn json() -> content::Json<&'static str> {
+    content::Json("[1,2,3]")
+}
+
+#[get("/created")]
+fn created() -> rocket::response::status::Created<content::Json<&'static str>> {
+    rocket::response::status::Created::new("/json").body(content::Json("{}"))
+}
+
+/// A reader that always fails.
+struct Broken;
+
+impl AsyncRead for Broken {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        _: &mut [u8]
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "broken")))
+    }
+}
+
+#[get("/broken")]
+fn broken() -> Stream<impl AsyncRead> {
+    Stream::from(Cursor::new("partial").chain(Broken))
+}
+
+fn client() -> Client {
+    let rocket = rocket::ignite()
+        .mount("/", routes![page, json, created])
+        .attach(Envelope)
+        .attach(AdHoc::on_response_body("Injector", |_, res, body| {
+            if res.content_type() != Some(ContentType::HTML) {
+                return body;
+            }
+
+            let html = String::from_utf8_lossy(&body);
+            html.replace("</body>", "<script></script></body>").into()
+        }));
+
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn body_fairings_replace_bodies() {
+    let client = client();
+    let response = client.get("/page").dispatch();
+    assert_eq!(response.into_string().unwrap(), "<html><body>Hi<script></script></body></html>");
+
+    let response = client.get("/json").dispatch();
+    assert_eq!(response.into_string().unwrap(), r#"{"status":200,"data":[1,2,3]}"#);
+}
+
+#[test]
+fn body_fairings_can_change_status() {
+    let client = client();
+    let response = client.get("/created").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), r#"{"status":201,"data":{}}"#);
+}
+
+#[test]
+fn ad_hoc_body_fairings_propagate_read_errors() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let rocket = rocket::ignite()
+        .mount("/", routes![broken])
+        .attach(AdHoc::on_response_body("Counter", move |_, _, body| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            body
+        }));
+
+    let client = Client::new(rocket).unwrap();
+    assert!(client.get("/broken").dispatch().into_bytes().is_none());
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,217 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::Request;
+use rocket::cache::{Cache, CachedResponse, CacheStore, MemoryStore};
+use rocket::http::{Header, Status};
+use rocket::local::blocking::Client;
+use rocket::request::{self, FromRequest};
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static LANG: AtomicUsize = AtomicUsize::new(0);
+static UNCACHED: AtomicUsize = AtomicUsize::new(0);
+static SECRET: AtomicUsize = AtomicUsize::new(0);
+static PROFILE: AtomicUsize = AtomicUsize::new(0);
+static HOST: AtomicUsize = AtomicUsize::new(0);
+
+struct Admin;
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Admin {
+    type Error = ();
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        match req.headers().get_one("X-Admin") {
+            Some("yes") => request::Outcome::Success(Admin),
+            _ => request::Outcome::Failure((Status::Unauthorized, ())),
+        }
+    }
+}
+
+#[get("/count")]
+#[cache(ttl = "60s", tags = "count")]
+fn count() -> String {
+    (COUNT.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[get("/lang")]
+#[cache(ttl = "1m", vary = "Accept-Language")]
+fn lang() -> String {
+    (LANG.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[get("/uncached")]
+#[cache(ttl = "60s")]
+fn uncached() -> String {
+    (UNCACHED.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[get("/secret")]
+#[cache(ttl = "60s")]
+fn secret(_admin: Admin) -> String {
+    (SECRET.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[get("/profile")]
+#[cache(ttl = "60s")]
+fn profile() -> String {
+    (PROFILE.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[get("/host")]
+#[cache(ttl = "60s")]
+fn host() -> String {
+    (HOST.fetch_add(1, Ordering::SeqCst) + 1).to_string()
+}
+
+#[post("/invalidate")]
+async fn invalidate(cache: Cache) {
+    cache.invalidate_tag("count").await;
+}
+
+#[post("/invalidate/host")]
+async fn invalidate_host(cache: Cache) {
+    cache.invalidate("a.example.com/host").await;
+}
+
+fn client() -> Client {
+    let rocket = rocket::ignite()
+        .mount("/", routes![count, lang, secret, profile, host, invalidate, invalidate_host])
+        .attach(Cache::memory());
+
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn responses_are_cached_until_invalidated() {
+    let client = client();
+    let response = client.get("/count").dispatch();
+    assert!(response.headers().get_one("Age").is_none());
+    assert_eq!(response.into_string().unwrap(), "1");
+
+    let response = client.get("/count").dispatch();
+    assert_eq!(response.headers().get_one("Age"), Some("0"));
+    assert_eq!(response.into_string().unwrap(), "1");
+
+    let response = client.head("/count").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(COUNT.load(Ordering::SeqCst), 1);
+
+    let response = client.get("/count")
+        .header(Header::new("Cache-Control", "no-cache"))
+        .dispatch();
+
+    assert_eq!(response.into_string().unwrap(), "2");
+    assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "2");
+
+    client.post("/invalidate").dispatch();
+    assert_eq!(client.get("/count").dispatch().into_string().unwrap(), "3");
+}
+
+#[test]
+fn responses_vary_on_request_headers() {
+    let client = client();
+    let get = |lang: &str| {
+        let response = client.get("/lang")
+            .header(Header::new("Accept-Language", lang.to_string()))
+            .dispatch();
+
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept-Language"));
+        response.into_string().unwrap()
+    };
+
+    assert_eq!(get("en"), "1");
+    assert_eq!(get("fr"), "2");
+    assert_eq!(get("en"), "1");
+    assert_eq!(get("fr"), "2");
+}
+
+#[test]
+fn responses_are_not_cached_without_fairing() {
+    let client = Client::new(rocket::ignite().mount("/", routes![uncached])).unwrap();
+    assert_eq!(client.get("/uncached").dispatch().into_string().unwrap(), "1");
+    assert_eq!(client.get("/uncached").dispatch().into_string().unwrap(), "2");
+}
+
+#[test]
+fn cached_responses_are_guarded() {
+    let client = client();
+    let admin = |yes: bool| {
+        let value = if yes { "yes" } else { "no" };
+        client.get("/secret").header(Header::new("X-Admin", value)).dispatch()
+    };
+
+    assert_eq!(admin(true).into_string().unwrap(), "1");
+    assert_eq!(admin(true).into_string().unwrap(), "1");
+    assert_eq!(admin(false).status(), Status::Unauthorized);
+    assert_eq!(client.get("/secret").dispatch().status(), Status::Unauthorized);
+    assert_eq!(SECRET.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn requests_with_credentials_bypass_the_cache() {
+    let client = client();
+    let get = |header: Option<Header<'static>>| {
+        let mut request = client.get("/profile");
+        if let Some(header) = header {
+            request.add_header(header);
+        }
+
+        request.dispatch().into_string().unwrap()
+    };
+
+    assert_eq!(get(None), "1");
+    assert_eq!(get(Some(Header::new("Authorization", "Bearer alice"))), "2");
+    assert_eq!(get(Some(Header::new("Cookie", "session=bob"))), "3");
+    assert_eq!(get(None), "1");
+}
+
+#[test]
+fn responses_are_cached_per_host() {
+    let client = client();
+    let get = |host: &'static str| {
+        client.get("/host").header(Header::new("Host", host)).dispatch().into_string().unwrap()
+    };
+
+    assert_eq!(get("a.example.com"), "1");
+    assert_eq!(get("b.example.com"), "2");
+    assert_eq!(get("A.example.com"), "1");
+    assert_eq!(get("b.example.com"), "2");
+
+    client.post("/invalidate/host").dispatch();
+    assert_eq!(get("a.example.com"), "3");
+    assert_eq!(get("b.example.com"), "2");
+}
+
+fn response(body: &'static str) -> CachedResponse {
+    CachedResponse {
+        status: Status::Ok,
+        headers: vec![],
+        body: body.as_bytes().to_vec().into(),
+        vary: vec![],
+        tags: vec![],
+        stored: std::time::SystemTime::now(),
+        ttl: std::time::Duration::from_secs(60),
+    }
+}
+
+#[rocket::async_test]
+async fn memory_store_evicts_least_recently_used() {
+    let store = MemoryStore::with_capacity(2);
+    store.put("/a", response("a")).await;
+    store.put("/b", response("b")).await;
+    assert!(store.get("/a", &[]).await.is_some());
+
+    store.put("/c", response("c")).await;
+    assert_eq!(store.len(), 2);
+    assert!(store.get("/a", &[]).await.is_some());
+    assert!(store.get("/b", &[]).await.is_none());
+    assert!(store.get("/c", &[]).await.is_some());
+
+    let mut stale = response("stale");
+    stale.ttl = std::time::Duration::from_secs(0);
+    store.put("/d", stale).await;
+    assert!(store.get("/d", &[]).await.is_none());
+    assert_eq!(store.len(), 1);
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "compression")]
+
+#[macro_use] extern crate rocket;
+
+use std::io::Cursor;
+use std::pin::Pin;
+
+use rocket::config::{Config, Environment, CompressionConfig};
+use rocket::futures::executor::block_on;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::blocking::Client;
+use rocket::response::{Content, Response, Stream};
+use rocket::tokio::io::{AsyncRead, AsyncReadExt};
+
+use async_compression::tokio_02::bufread::{BrotliDecoder, GzipDecoder};
+
+const TEXT: &str = "Rocket compresses responses as negotiated with the client. ";
+
+#[get("/text")]
+fn text() -> String {
+    TEXT.repeat(100)
+}
+
+#[get("/small")]
+fn small() -> &'static str {
+    TEXT
+}
+
+#[get("/stream")]
+fn stream() -> Stream<Cursor<String>> {
+    Stream::from(Cursor::new(TEXT.repeat(100)))
+}
+
+#[get("/tagged")]
+fn tagged() -> Response<'static> {
+    let text = TEXT.repeat(100);
+    Response::build()
+        .raw_header("ETag", "\"v1\"")
+        .sized_body(text.len(), Cursor::new(text))
+        .finalize()
+}
+
+#[get("/image")]
+fn image() -> Content<Vec<u8>> {
+    Content(ContentType::PNG, vec![0; 4096])
+}
+
+fn client(enabled: bool) -> Client {
+    let compression = CompressionConfig { enabled, ..CompressionConfig::default() };
+    let config = Config::build(Environment::Development)
+        .compression(compression)
+        .unwrap();
+
+    let rocket = rocket::custom(config).mount("/", routes![text, small, stream, image, tagged]);
+    Client::new(rocket).unwrap()
+}
+
+/// Decodes `body`, compressed with `encoding`.
+fn decode(encoding: &str, body: &[u8]) -> String {
+    let mut decoder: Pin<Box<dyn AsyncRead + '_>> = match encoding {
+        "gzip" => Box::pin(GzipDecoder::new(body)),
+        "br" => Box::pin(BrotliDecoder::new(body)),
+        _ => panic!("unexpected encoding {}", encoding),
+    };
+
+    let mut decoded = String::new();
+    block_on(decoder.read_to_string(&mut decoded)).expect("valid compressed body");
+    decoded
+}
+
+#[test]
+fn bodies_are_compressed_as_negotiated() {
+    let client = client(true);
+    let brotli = if cfg!(feature = "brotli") { "br" } else { "gzip" };
+    for (accept, encoding) in &[("gzip", "gzip"), ("br, gzip", brotli)] {
+        for uri in &["/text", "/stream"] {
+            let response = client.get(*uri)
+                .header(Header::new("Accept-Encoding", *accept))
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.headers().get_one("Content-Encoding"), Some(*encoding));
+            assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+
+            let body = response.into_bytes().unwrap();
+            assert!(body.len() < TEXT.len() * 100);
+            assert_eq!(decode(encoding, &body), TEXT.repeat(100));
+        }
+    }
+}
+
+#[test]
+fn compressed_entity_tags_are_weak() {
+    let client = client(true);
+    let response = client.get("/tagged")
+        .header(Header::new("Accept-Encoding", "gzip"))
+        .dispatch();
+
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    assert_eq!(response.headers().get_one("ETag"), Some("W/\"v1\""));
+
+    let response = client.get("/tagged").dispatch();
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    assert_eq!(response.headers().get_one("ETag"), Some("\"v1\""));
+}
+
+#[test]
+fn bodies_are_sent_as_is_when_not_negotiated() {
+    let client = client(true);
+    let response = client.get("/text").dispatch();
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    assert_eq!(response.headers().get_one("Vary"), Some("Accept-Encoding"));
+    assert_eq!(response.into_string().unwrap(), TEXT.repeat(100));
+
+    let response = client.get("/text")
+        .header(Header::new("Accept-Encoding", "gzip;q=0"))
+        .dispatch();
+
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+}
+
+#[test]
+fn small_and_excluded_bodies_are_sent_as_is() {
+    let client = client(true);
+    for uri in &["/small", "/image"] {
+        let response = client.get(*uri)
+            .header(Header::new("Accept-Encoding", "gzip"))
+            .dispatch();
+
+        assert!(response.headers().get_one("Content-Encoding").is_none());
+        assert!(response.headers().get_one("Vary").is_none());
+    }
+}
+
+#[test]
+fn compression_is_disabled_by_default() {
+    let client = client(false);
+    let response = client.get("/text")
+        .header(Header::new("Accept-Encoding", "gzip"))
+        .dispatch();
+
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    assert_eq!(response.into_string().unwrap(), TEXT.repeat(100));
+}
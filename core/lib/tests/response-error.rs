@@ -0,0 +1,75 @@
+#[macro_use] extern crate rocket;
+
+use std::fmt;
+
+use rocket::catcher::TypedError;
+use rocket::response::ResponseError;
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[derive(Debug, PartialEq)]
+enum AppError {
+    Missing(&'static str),
+    Broken,
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Missing(what) => write!(f, "missing {}", what),
+            AppError::Broken => write!(f, "broken"),
+        }
+    }
+}
+
+impl TypedError for AppError { }
+
+impl ResponseError for AppError {
+    fn status(&self) -> Status {
+        match self {
+            AppError::Missing(_) => Status::NotFound,
+            AppError::Broken => Status::InternalServerError,
+        }
+    }
+}
+
+#[get("/<n>")]
+fn number(n: usize) -> Result<String, AppError> {
+    match n {
+        0 => Err(AppError::Missing("zero")),
+        1 => Err(AppError::Broken),
+        n => Ok(n.to_string()),
+    }
+}
+
+#[catch(default, error = "<e>")]
+fn app_error(status: Status, e: Option<&AppError>) -> String {
+    match e {
+        Some(e) => format!("{}: {}", status.code, e),
+        None => format!("{}: unknown", status.code),
+    }
+}
+
+#[test]
+fn response_errors_are_rendered_by_catchers() {
+    let rocket = rocket::ignite()
+        .mount("/", routes![number])
+        .register(catchers![app_error]);
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/7").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().unwrap(), "7");
+
+    let response = client.get("/0").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_string().unwrap(), "404: missing zero");
+
+    let response = client.get("/1").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.into_string().unwrap(), "500: broken");
+
+    let response = client.get("/a/b").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_string().unwrap(), "404: unknown");
+}
@@ -0,0 +1,72 @@
+#[macro_use] extern crate rocket;
+
+use std::time::Duration;
+
+use rocket::Load;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use rocket::tokio::sync::Barrier;
+use rocket::tokio::time::delay_for;
+
+#[get("/slow")]
+async fn slow() -> &'static str {
+    delay_for(Duration::from_millis(1500)).await;
+    "done"
+}
+
+#[get("/limited", max_concurrency = 1)]
+async fn limited(barrier: rocket::State<'_, Barrier>) -> &'static str {
+    barrier.wait().await;
+    delay_for(Duration::from_millis(1500)).await;
+    "done"
+}
+
+#[get("/load")]
+fn load(load: Load) -> String {
+    load.shed().to_string()
+}
+
+#[rocket::async_test]
+async fn requests_beyond_limit_are_rejected() {
+    let rocket = rocket::ignite()
+        .mount("/", routes![slow, limited, load])
+        .manage(Barrier::new(2));
+
+    let client = Client::new(rocket).await.unwrap();
+
+    // Occupy the only slot, then wait until the handler is running.
+    let first = client.get("/limited").dispatch();
+    let waiting = async {
+        let barrier = client.cargo().state::<Barrier>().unwrap();
+        barrier.wait().await;
+
+        // The queueing time elapses before the first request completes.
+        let response = client.get("/limited").dispatch().await;
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+
+        // Unlimited routes are unaffected.
+        let response = client.get("/slow").dispatch().await;
+        assert_eq!(response.into_string().await.unwrap(), "done");
+    };
+
+    let (first, _) = rocket::tokio::join!(first, waiting);
+    assert_eq!(first.into_string().await.unwrap(), "done");
+
+    let response = client.get("/load").dispatch().await;
+    assert_eq!(response.into_string().await.unwrap(), "1");
+}
+
+#[test]
+fn manual_concurrency_limits() {
+    use rocket::Route;
+    use rocket::http::Method;
+    use rocket::handler::dummy;
+
+    let route = Route::new(Method::Get, "/", dummy);
+    assert_eq!(route.concurrency_limit(), None);
+
+    let route = route.max_concurrency(3).unwrap().queue_timeout(Duration::from_millis(10));
+    assert_eq!(route.concurrency_limit(), Some(3));
+    assert!(route.max_concurrency(0).is_err());
+    assert_eq!(routes![limited][0].concurrency_limit(), Some(1));
+}
@@ -0,0 +1,48 @@
+#[macro_use] extern crate rocket;
+
+use rocket::Request;
+use rocket::data::{Limits, ToByteUnit};
+use rocket::config::{Config, Environment};
+use rocket::local::blocking::Client;
+
+fn limit(req: &Request<'_>, name: &str) -> String {
+    req.limits().get(name).map(|l| l.as_u64().to_string()).unwrap_or_else(|| "none".into())
+}
+
+#[post("/upload", limits(file = "2MiB", forms = "1kB"))]
+fn upload(req: &Request<'_>) -> String {
+    format!("{} {}", limit(req, "file"), limit(req, "forms"))
+}
+
+#[post("/other")]
+fn other(req: &Request<'_>) -> String {
+    format!("{} {}", limit(req, "file"), limit(req, "forms"))
+}
+
+#[test]
+fn route_limits_override_configured_limits() {
+    let config = Config::build(Environment::Development)
+        .limits(Limits::new().limit("file", 1.mebibytes()).limit("forms", 64.kibibytes()))
+        .unwrap();
+
+    let rocket = rocket::custom(config).mount("/", routes![upload, other]);
+    let client = Client::new(rocket).unwrap();
+
+    let response = client.post("/upload").dispatch();
+    assert_eq!(response.into_string().unwrap(), format!("{} 1000", 2 << 20));
+
+    let response = client.post("/other").dispatch();
+    assert_eq!(response.into_string().unwrap(), format!("{} {}", 1 << 20, 64 << 10));
+}
+
+#[test]
+fn manual_route_limits() {
+    use rocket::Route;
+    use rocket::http::Method;
+    use rocket::handler::dummy;
+
+    let route = Route::new(Method::Post, "/", dummy).limit("json", 5.mebibytes());
+    let limits = route.limits(&Limits::new());
+    assert_eq!(limits.get("json"), Some(5.mebibytes()));
+    assert_eq!(limits.get("forms"), Some(32.kibibytes()));
+}
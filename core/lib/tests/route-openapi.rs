@@ -0,0 +1,249 @@
+#[macro_use] extern crate rocket;
+
+use rocket::Route;
+use rocket::http::{MediaType, Status};
+use rocket::request::Form;
+use rocket::response::{content, status};
+use rocket::openapi::{Operation, Location, Schema, Property, SchemaGenerator};
+use rocket::openapi::{OpenApiFromRequest, Parameter};
+
+use serde::Serialize;
+
+#[derive(FromForm, JsonSchema)]
+struct Filter {
+    /// Only items with this tag.
+    tag: Option<String>,
+    limit: usize,
+}
+
+#[derive(JsonSchema)]
+enum Kind {
+    Book,
+    Record,
+}
+
+#[derive(JsonSchema)]
+struct Tree {
+    kind: Kind,
+    children: Vec<Tree>,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    order_id: u64,
+    #[serde(rename = "customer")]
+    customer_name: String,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    internal_note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shipped_at: Option<u64>,
+    state: OrderState,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum OrderState {
+    InTransit,
+    #[serde(rename = "done")]
+    Delivered,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    Lost,
+}
+
+struct ApiKey;
+
+impl OpenApiFromRequest for ApiKey {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Operation) {
+        let header = Parameter::new("X-Api-Key", Location::Header, Schema::string());
+        operation.parameters.push(header.required(true));
+    }
+}
+
+#[rocket::async_trait]
+impl<'a, 'r> rocket::request::FromRequest<'a, 'r> for ApiKey {
+    type Error = ();
+
+    async fn from_request(_: &'a rocket::Request<'r>) -> rocket::request::Outcome<Self, ()> {
+        rocket::request::Outcome::Success(ApiKey)
+    }
+}
+
+/// Looks up an item.
+///
+/// Items that don't exist are answered
+/// with `404 Not Found`.
+#[get("/items/<id>?<verbose>")]
+#[openapi(tags = "items, public")]
+fn item(id: u32, verbose: Option<bool>, _key: Option<ApiKey>) -> Option<content::Html<String>> {
+    let _ = (id, verbose);
+    None
+}
+
+#[get("/items?<filter..>")]
+#[openapi(operation_id = "listItems", deprecated)]
+fn items(filter: Form<Filter>, _key: ApiKey) -> &'static str {
+    let _ = filter;
+    "[]"
+}
+
+#[post("/items", data = "<form>")]
+#[openapi]
+fn add(form: Form<Filter>) -> status::Created<String> {
+    let _ = form;
+    status::Created::new("/items/1")
+}
+
+#[post("/raw", data = "<body>")]
+#[openapi]
+fn raw(body: String) -> impl rocket::response::Responder<'static, 'static> {
+    body
+}
+
+#[get("/undescribed")]
+fn undescribed() { }
+
+fn operation(routes: Vec<Route>, gen: &mut SchemaGenerator) -> Operation {
+    routes[0].operation(gen).expect("route is described")
+}
+
+#[test]
+fn doc_comments_and_options_are_recorded() {
+    let mut gen = SchemaGenerator::new();
+    let op = operation(routes![item], &mut gen);
+    assert_eq!(op.operation_id.as_deref(), Some("item"));
+    assert_eq!(op.summary.as_deref(), Some("Looks up an item."));
+    let description = "Items that don't exist are answered\nwith `404 Not Found`.";
+    assert_eq!(op.description.as_deref(), Some(description));
+    assert_eq!(op.tags, vec!["items", "public"]);
+    assert!(!op.deprecated);
+
+    let op = operation(routes![items], &mut gen);
+    assert_eq!(op.operation_id.as_deref(), Some("listItems"));
+    assert_eq!(op.summary, None);
+    assert!(op.deprecated);
+}
+
+#[test]
+fn parameters_are_described() {
+    let mut gen = SchemaGenerator::new();
+    let op = operation(routes![item], &mut gen);
+    let params = &op.parameters;
+    assert_eq!(params.len(), 3);
+
+    assert_eq!(params[0].name, "id");
+    assert_eq!(params[0].location, Location::Path);
+    assert!(params[0].required);
+    assert_eq!(params[0].schema, Schema::Integer { format: Some("int64") });
+
+    assert_eq!(params[1].name, "verbose");
+    assert_eq!(params[1].location, Location::Query);
+    assert!(!params[1].required);
+    assert_eq!(params[1].schema, Schema::Nullable(Box::new(Schema::Boolean)));
+
+    assert_eq!(params[2].name, "X-Api-Key");
+    assert_eq!(params[2].location, Location::Header);
+    assert!(!params[2].required);
+
+    let op = operation(routes![items], &mut gen);
+    let names: Vec<_> = op.parameters.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["tag", "limit", "X-Api-Key"]);
+    assert!(!op.parameters[0].required);
+    assert_eq!(op.parameters[0].description.as_deref(), Some("Only items with this tag."));
+    assert!(op.parameters[1].required && op.parameters[2].required);
+}
+
+#[test]
+fn bodies_and_responses_are_described() {
+    let mut gen = SchemaGenerator::new();
+    let op = operation(routes![item], &mut gen);
+    assert_eq!(op.request_body, None);
+    assert_eq!(op.responses.len(), 2);
+    assert_eq!(op.responses[0].status, Some(Status::Ok));
+    assert_eq!(op.responses[0].content_type, Some(MediaType::HTML));
+    assert_eq!(op.responses[0].schema, Some(Schema::string()));
+    assert_eq!(op.responses[1].status, Some(Status::NotFound));
+
+    let op = operation(routes![add], &mut gen);
+    let body = op.request_body.unwrap();
+    assert_eq!(body.content_type, Some(MediaType::Form));
+    assert_eq!(body.schema, Schema::Reference("Filter".into()));
+    assert_eq!(op.responses.len(), 1);
+    assert_eq!(op.responses[0].status, Some(Status::Created));
+    assert_eq!(op.responses[0].description, "Created");
+
+    let op = operation(routes![raw], &mut gen);
+    assert_eq!(op.request_body.unwrap().content_type, Some(MediaType::Plain));
+    assert_eq!(op.responses.len(), 1);
+    assert_eq!(op.responses[0].status, None);
+}
+
+#[test]
+fn named_schemas_are_components() {
+    let mut gen = SchemaGenerator::new();
+    assert_eq!(gen.subschema_for::<Tree>(), Schema::Reference("Tree".into()));
+    let byte = Schema::Integer { format: Some("int32") };
+    assert_eq!(gen.subschema_for::<Option<u8>>(), Schema::Nullable(Box::new(byte)));
+
+    let components = gen.into_components();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components["Kind"], Schema::Enum(vec!["Book".into(), "Record".into()]));
+    assert_eq!(components["Tree"], Schema::Object(vec![
+        Property {
+            name: "kind".into(),
+            schema: Schema::Reference("Kind".into()),
+            required: true,
+            description: None,
+        },
+        Property {
+            name: "children".into(),
+            schema: Schema::Array(Box::new(Schema::Reference("Tree".into()))),
+            required: true,
+            description: None,
+        },
+    ]));
+}
+
+#[test]
+fn serde_attributes_are_honored() {
+    let mut gen = SchemaGenerator::new();
+    gen.subschema_for::<Order>();
+    let components = gen.into_components();
+    let properties = match &components["Order"] {
+        Schema::Object(properties) => properties,
+        schema => panic!("expected an object schema, found {:?}", schema),
+    };
+
+    // The schema's property names are exactly those serde writes.
+    let order = Order {
+        order_id: 1,
+        customer_name: "Bob".into(),
+        internal_note: "VIP".into(),
+        shipped_at: Some(2),
+        state: OrderState::InTransit,
+    };
+
+    let json = serde_json::to_value(&order).unwrap();
+    let names: Vec<_> = properties.iter().map(|p| p.name.as_str()).collect();
+    let wire_names: Vec<_> = json.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+    assert_eq!(names, ["orderId", "customer", "shippedAt", "state"]);
+    let mut sorted_names = names.clone();
+    sorted_names.sort();
+    assert_eq!(sorted_names, wire_names);
+
+    let required: Vec<_> = properties.iter().map(|p| p.required).collect();
+    assert_eq!(required, [true, true, false, true]);
+
+    let variants = vec!["IN_TRANSIT".to_string(), "done".to_string()];
+    assert_eq!(components["OrderState"], Schema::Enum(variants));
+    assert_eq!(serde_json::to_value(OrderState::InTransit).unwrap(), "IN_TRANSIT");
+    assert_eq!(serde_json::to_value(OrderState::Delivered).unwrap(), "done");
+}
+
+#[test]
+fn undescribed_routes_have_no_operation() {
+    let route = &routes![undescribed][0];
+    assert!(route.operation(&mut SchemaGenerator::new()).is_none());
+}
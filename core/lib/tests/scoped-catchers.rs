@@ -0,0 +1,64 @@
+#[macro_use] extern crate rocket;
+
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[get("/fail")]
+fn fail() -> Status {
+    Status::BadRequest
+}
+
+#[get("/special")]
+fn special() -> Status {
+    Status::BadRequest
+}
+
+#[catch(404)]
+fn root_not_found() -> &'static str { "root 404" }
+
+#[catch(default)]
+fn root_default() -> &'static str { "root default" }
+
+#[catch(404)]
+fn api_not_found() -> &'static str { "api 404" }
+
+#[catch(default)]
+fn api_v1_default() -> &'static str { "api v1 default" }
+
+#[catch(400)]
+fn route_bad_request() -> &'static str { "route 400" }
+
+fn client() -> Client {
+    let special = routes![special].remove(0).register(catchers![route_bad_request]);
+    let rocket = rocket::ignite()
+        .mount("/", routes![fail])
+        .mount("/api", routes![fail])
+        .mount("/api", vec![special])
+        .register(catchers![root_not_found, root_default])
+        .register_at("/api", catchers![api_not_found])
+        .register_at("/api/v1", catchers![api_v1_default]);
+
+    Client::new(rocket).unwrap()
+}
+
+fn body_of(client: &Client, uri: &str) -> (Status, String) {
+    let response = client.get(uri.to_string()).dispatch();
+    (response.status(), response.into_string().unwrap())
+}
+
+#[test]
+fn closest_scope_wins() {
+    let client = client();
+    assert_eq!(body_of(&client, "/missing"), (Status::NotFound, "root 404".into()));
+    assert_eq!(body_of(&client, "/fail"), (Status::BadRequest, "root default".into()));
+    assert_eq!(body_of(&client, "/api/missing"), (Status::NotFound, "api 404".into()));
+    assert_eq!(body_of(&client, "/api/fail"), (Status::BadRequest, "root default".into()));
+    assert_eq!(body_of(&client, "/api/v1/missing"), (Status::NotFound, "api v1 default".into()));
+    assert_eq!(body_of(&client, "/apis/missing"), (Status::NotFound, "root 404".into()));
+}
+
+#[test]
+fn route_catchers_take_precedence() {
+    let client = client();
+    assert_eq!(body_of(&client, "/api/special"), (Status::BadRequest, "route 400".into()));
+}
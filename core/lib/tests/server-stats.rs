@@ -0,0 +1,69 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rocket::{ServerStats, State};
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/")]
+fn stats(stats: State<'_, ServerStats>) -> String {
+    format!("{} {} {} {} {}", stats.open_connections(), stats.active_requests(),
+        stats.accepted(), stats.refused(), stats.handshake_failures())
+}
+
+async fn send(port: u16, data: &[u8]) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(data).await.unwrap();
+
+    let mut buf = vec![];
+    let read = timeout(Duration::from_secs(5), stream.read_to_end(&mut buf)).await;
+    assert!(read.is_ok(), "connection was not closed");
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+#[rocket::async_test]
+async fn connections_are_counted() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .proxy_protocol(true)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![stats])
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+
+    // A connection without a PROXY header fails its handshake.
+    assert!(send(port, b"GET / HTTP/1.1\r\n\r\n").await.is_empty());
+
+    let request = b"PROXY TCP4 192.0.2.1 198.51.100.2 56324 443\r\n\
+        GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+
+    let response = send(port, request).await;
+    assert!(response.ends_with("1 1 1 0 1"), "{}", response);
+
+    shutdown.shutdown();
+}
+
+#[test]
+fn stats_are_managed() {
+    use rocket::local::blocking::Client;
+
+    let client = Client::new(rocket::ignite().mount("/", routes![stats])).unwrap();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.into_string().unwrap(), "0 0 0 0 0");
+}
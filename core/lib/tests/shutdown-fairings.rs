@@ -0,0 +1,40 @@
+use std::sync::{Arc, Mutex};
+
+use rocket::config::{Config, Environment};
+use rocket::fairing::{AdHoc, Kind};
+
+fn shutdown(log: &Arc<Mutex<Vec<&'static str>>>, name: &'static str) -> AdHoc {
+    let log = log.clone();
+    AdHoc::on_shutdown(name, move |_| {
+        Box::pin(async move {
+            log.lock().unwrap().push(name);
+        })
+    })
+}
+
+#[rocket::async_test]
+async fn shutdown_fairings_run_in_reverse_after_shutdown() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    let launch_log = log.clone();
+    let rocket = rocket::custom(config)
+        .attach(shutdown(&log, "first"))
+        .attach(shutdown(&log, "second"))
+        .attach(AdHoc::on_launch("Stop", move |cargo| {
+            launch_log.lock().unwrap().push("launch");
+            cargo.shutdown().shutdown();
+        }));
+
+    assert!(rocket.launch().await.is_ok());
+    assert_eq!(*log.lock().unwrap(), vec!["launch", "second", "first"]);
+}
+
+#[test]
+fn shutdown_kind() {
+    let fairing = AdHoc::on_shutdown("Shutdown", |_| Box::pin(async move { }));
+    assert!(rocket::fairing::Fairing::info(&fairing).kind.is_exactly(Kind::Shutdown));
+}
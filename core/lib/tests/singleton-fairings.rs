@@ -0,0 +1,60 @@
+#[macro_use] extern crate rocket;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rocket::{Rocket, Request, Response};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::local::blocking::Client;
+use rocket::http::Header;
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+struct Tagger {
+    kind: Kind,
+    attached: Arc<AtomicUsize>,
+}
+
+#[rocket::async_trait]
+impl Fairing for Tagger {
+    fn info(&self) -> Info {
+        Info { name: "Tagger", kind: self.kind }
+    }
+
+    async fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        self.attached.fetch_add(1, Ordering::SeqCst);
+        Ok(rocket)
+    }
+
+    async fn on_response<'r>(&self, _: &'r Request<'_>, res: &mut Response<'r>) {
+        res.adjoin_header(Header::new("X-Tag", "tagged"));
+    }
+}
+
+fn tags(kind: Kind) -> (usize, usize) {
+    let attached = Arc::new(AtomicUsize::new(0));
+    let tagger = || Tagger { kind, attached: attached.clone() };
+    let library = |rocket: Rocket| rocket.attach(tagger());
+
+    let rocket = library(rocket::ignite().mount("/", routes![index]))
+        .attach(tagger())
+        .attach(tagger());
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/").dispatch();
+    let tags = response.headers().get("X-Tag").count();
+    (attached.load(Ordering::SeqCst), tags)
+}
+
+#[test]
+fn fairings_are_attached_repeatedly_by_default() {
+    assert_eq!(tags(Kind::Attach | Kind::Response), (3, 3));
+}
+
+#[test]
+fn singleton_fairings_are_attached_once() {
+    assert_eq!(tags(Kind::Attach | Kind::Response | Kind::Singleton), (1, 1));
+}
@@ -0,0 +1,91 @@
+#![cfg(all(feature = "tls", not(feature = "native-tls")))]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use rocket::{Shutdown, TlsReloader};
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+
+fn private() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/tls/private")
+}
+
+/// Copies the example certificate and key to a temporary directory so that
+/// the test can replace them.
+fn copy_files() -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("rocket-tls-reload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let (certs, key) = (dir.join("cert.pem"), dir.join("key.pem"));
+    std::fs::copy(private().join("cert.pem"), &certs).unwrap();
+    std::fs::copy(private().join("key.pem"), &key).unwrap();
+    (certs, key)
+}
+
+/// Returns the length of the certificate chain a new connection to the
+/// server at `port` is served.
+async fn peer_chain_len(port: u16) -> usize {
+    use tokio_rustls::{rustls::{self, Session}, webpki, TlsConnector};
+
+    let mut config = rustls::ClientConfig::new();
+    let ca = std::fs::read(private().join("ca_cert.pem")).unwrap();
+    config.root_store.add_pem_file(&mut &ca[..]).unwrap();
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let stream = TlsConnector::from(Arc::new(config)).connect(name, stream).await.unwrap();
+    stream.get_ref().1.get_peer_certificates().unwrap().len()
+}
+
+#[rocket::async_test]
+async fn reloads_certificates_from_their_files() {
+    let (certs, key) = copy_files();
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .tls(certs.to_str().unwrap(), key.to_str().unwrap())
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel::<(TlsReloader, Shutdown, u16)>();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .attach(AdHoc::on_launch("Reloader", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.tls_reloader(), cargo.shutdown(), cargo.config().port));
+        }));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    let (tls, shutdown, port) = rx.await.unwrap();
+    assert_eq!(peer_chain_len(port).await, 1);
+
+    // New connections are served the reloaded chain, which now includes the
+    // CA certificate.
+    let ca = std::fs::read(private().join("ca_cert.pem")).unwrap();
+    let mut chain = std::fs::read(&certs).unwrap();
+    chain.extend_from_slice(&ca);
+    std::fs::write(&certs, chain).unwrap();
+    assert!(tls.reload().await.is_ok());
+    assert_eq!(peer_chain_len(port).await, 2);
+
+    // A malformed key is refused; the previous certificates remain in use.
+    std::fs::write(&key, "not a key").unwrap();
+    assert!(tls.reload().await.is_err());
+
+    std::fs::remove_file(&certs).unwrap();
+    assert!(tls.reload().await.is_err());
+    assert_eq!(peer_chain_len(port).await, 2);
+
+    shutdown.shutdown();
+    server.await.unwrap().unwrap();
+    std::fs::remove_dir_all(certs.parent().unwrap()).unwrap();
+}
+
+#[rocket::async_test]
+async fn reloading_before_launch_does_nothing() {
+    let mut rocket = rocket::ignite();
+    let tls = rocket.inspect().await.tls_reloader();
+    assert!(tls.reload().await.is_ok());
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "tower")]
+
+#[macro_use] extern crate rocket;
+
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use rocket::Request;
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::futures::future::BoxFuture;
+use rocket::tower::{RocketService, RemoteAddr, Layer, Service, HttpRequest, HttpResponse};
+use rocket::http::hyper::{self, HttpBody};
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::TcpStream;
+use rocket::tokio::sync::oneshot;
+use rocket::tokio::time::timeout;
+
+#[get("/")]
+fn index() -> &'static str {
+    "index"
+}
+
+#[get("/remote")]
+fn remote(req: &Request<'_>) -> String {
+    req.remote().map(|addr| addr.to_string()).unwrap_or_default()
+}
+
+async fn body_string(response: hyper::Response<hyper::Body>) -> String {
+    let mut body = response.into_body();
+    let mut bytes = vec![];
+    while let Some(chunk) = body.data().await {
+        bytes.extend_from_slice(&chunk.unwrap());
+    }
+
+    String::from_utf8(bytes).unwrap()
+}
+
+#[rocket::async_test]
+async fn rocket_service_dispatches_requests() {
+    let rocket = rocket::ignite().mount("/", routes![index, remote]);
+    let mut service = RocketService::new(rocket).await.unwrap();
+
+    let request = hyper::Request::get("/").body(hyper::Body::empty()).unwrap();
+    let response = service.call(request).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(body_string(response).await, "index");
+
+    let request = hyper::Request::get("/nope").body(hyper::Body::empty()).unwrap();
+    let response = service.call(request).await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[rocket::async_test]
+async fn rocket_service_reads_remote_addr() {
+    let rocket = rocket::ignite().mount("/", routes![index, remote]);
+    let mut service = RocketService::new(rocket).await.unwrap();
+
+    let mut request = hyper::Request::get("/remote").body(hyper::Body::empty()).unwrap();
+    request.extensions_mut().insert(RemoteAddr(([10, 0, 0, 1], 8000).into()));
+    let response = service.call(request).await.unwrap();
+    assert_eq!(body_string(response).await, "10.0.0.1:8000");
+}
+
+/// A layer counting the requests each instance of its service handles.
+struct Count;
+
+#[derive(Clone)]
+struct Counted<S> {
+    inner: S,
+    calls: usize,
+}
+
+impl<S> Layer<S> for Count {
+    type Service = Counted<S>;
+
+    fn layer(&self, inner: S) -> Counted<S> {
+        Counted { inner, calls: 0 }
+    }
+}
+
+impl<S> Service<HttpRequest> for Counted<S>
+    where S: Service<HttpRequest, Response = HttpResponse>, S::Future: Send + 'static
+{
+    type Response = HttpResponse;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<HttpResponse, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        self.calls += 1;
+        let calls = self.calls.to_string();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let mut response = response.await?;
+            response.headers_mut().insert("x-calls", calls.parse().unwrap());
+            Ok(response)
+        })
+    }
+}
+
+#[rocket::async_test]
+async fn layered_services_are_cloned_per_request() {
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(0)
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .layer(Count)
+        .attach(AdHoc::on_launch("Port", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send((cargo.config().port, cargo.shutdown()));
+        }));
+
+    rocket::tokio::spawn(rocket.launch());
+    let (port, shutdown) = rx.await.unwrap();
+
+    for _ in 0..3 {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+            .await
+            .expect("response")
+            .unwrap();
+
+        let response = response.to_lowercase();
+        assert!(response.starts_with("http/1.1 200"), "{}", response);
+        assert!(response.contains("x-calls: 1\r\n"), "{}", response);
+    }
+
+    shutdown.shutdown();
+}
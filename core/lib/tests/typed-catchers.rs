@@ -0,0 +1,154 @@
+#[macro_use] extern crate rocket;
+
+use rocket::{Catcher, Request};
+use rocket::catcher::{self, ErrorHandlerFuture, TypedError};
+use rocket::request::{self, FromRequest};
+use rocket::local::blocking::Client;
+use rocket::http::Status;
+
+#[derive(Debug, PartialEq)]
+struct KeyError(&'static str);
+
+impl TypedError for KeyError { }
+
+struct Key;
+
+#[rocket::async_trait]
+impl<'a, 'r> FromRequest<'a, 'r> for Key {
+    type Error = KeyError;
+
+    async fn from_request(req: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match req.headers().get_one("key") {
+            Some("secret") => request::Outcome::Success(Key),
+            Some(_) => request::Outcome::Failure((Status::Unauthorized, KeyError("bad key"))),
+            None => request::Outcome::Failure((Status::Unauthorized, KeyError("no key"))),
+        }
+    }
+}
+
+#[get("/")]
+fn index(_key: Key) -> &'static str {
+    "welcome"
+}
+
+#[get("/fail")]
+fn fail() -> Status {
+    Status::Unauthorized
+}
+
+#[get("/gone")]
+fn gone() -> Status {
+    Status::Gone
+}
+
+#[get("/teapot")]
+fn teapot() -> Status {
+    Status::ImATeapot
+}
+
+#[catch(401, error = "<e>")]
+fn unauthorized(req: &Request, e: &KeyError) -> String {
+    assert_eq!(req.error::<KeyError>(), Some(e));
+    format!("unauthorized: {}", e.0)
+}
+
+#[catch(default, error = "<e>")]
+fn default(status: Status, e: Option<&KeyError>) -> String {
+    format!("{}: {:?}", status.code, e)
+}
+
+#[catch(410)]
+fn gone_catcher() -> Result<&'static str, Status> {
+    Err(Status::ServiceUnavailable)
+}
+
+#[catch(418)]
+fn teapot_catcher() -> Status {
+    Status::ImATeapot
+}
+
+#[catch(503)]
+fn unavailable() -> &'static str {
+    "unavailable"
+}
+
+fn client() -> Client {
+    let rocket = rocket::ignite()
+        .mount("/", routes![index, fail, gone, teapot])
+        .register(catchers![unauthorized, default, gone_catcher, teapot_catcher, unavailable]);
+
+    Client::new(rocket).unwrap()
+}
+
+#[test]
+fn typed_catcher_receives_guard_error() {
+    let client = client();
+    let response = client.get("/").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    assert_eq!(response.into_string().unwrap(), "unauthorized: no key");
+
+    let response = client.get("/").header(rocket::http::Header::new("key", "nope")).dispatch();
+    assert_eq!(response.into_string().unwrap(), "unauthorized: bad key");
+
+    let response = client.get("/").header(rocket::http::Header::new("key", "secret")).dispatch();
+    assert_eq!(response.into_string().unwrap(), "welcome");
+}
+
+#[test]
+fn typed_catcher_declines_without_error() {
+    let client = client();
+    let response = client.get("/fail").dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    assert_eq!(response.into_string().unwrap(), "401: None");
+
+    let response = client.get("/missing").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_string().unwrap(), "404: None");
+}
+
+#[test]
+fn failing_catchers_do_not_decline() {
+    let client = client();
+
+    // A catcher failing with another status hands its error to the catchers
+    // for that status.
+    let response = client.get("/gone").dispatch();
+    assert_eq!(response.status(), Status::ServiceUnavailable);
+    assert_eq!(response.into_string().unwrap(), "unavailable");
+
+    // A catcher failing with its own status hasn't declined, so the default
+    // catcher isn't tried.
+    let response = client.get("/teapot").dispatch();
+    assert_eq!(response.status(), Status::InternalServerError);
+}
+
+fn api_only<'r>(status: Status, req: &'r Request<'_>) -> ErrorHandlerFuture<'r> {
+    Box::pin(async move {
+        if !req.uri().path().starts_with("/api") {
+            return catcher::decline(req, status);
+        }
+
+        Ok(rocket::Response::build().status(status).finalize())
+    })
+}
+
+#[catch(default)]
+fn fallback() -> &'static str {
+    "fallback"
+}
+
+#[test]
+fn declining_catchers_pass_to_the_next() {
+    let rocket = rocket::ignite()
+        .register(vec![Catcher::new(404, api_only)])
+        .register(catchers![fallback]);
+
+    let client = Client::new(rocket).unwrap();
+    let response = client.get("/api/missing").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response.into_string().is_none());
+
+    let response = client.get("/missing").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.into_string().unwrap(), "fallback");
+}
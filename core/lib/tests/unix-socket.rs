@@ -0,0 +1,106 @@
+#![cfg(unix)]
+
+#[macro_use] extern crate rocket;
+
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use rocket::config::{Config, Environment, UnixConfig};
+use rocket::error::{Error, LaunchErrorKind};
+use rocket::fairing::AdHoc;
+use rocket::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use rocket::tokio::net::UnixStream;
+use rocket::tokio::sync::oneshot;
+
+#[get("/")]
+fn index() -> &'static str {
+    "Hello, socket!"
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rocket-{}-{}.sock", name, std::process::id()))
+}
+
+#[rocket::async_test]
+async fn serves_and_cleans_up_unix_socket() {
+    let path = socket_path("serve");
+
+    // A stale socket from a previous run is replaced.
+    let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    drop(stale);
+    assert!(path.exists());
+
+    let config = Config::build(Environment::Development)
+        .address(format!("unix:{}", path.display()))
+        .unix(UnixConfig { mode: Some(0o600), cleanup: true })
+        .unwrap();
+
+    let (tx, rx) = oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let rocket = rocket::custom(config)
+        .mount("/", routes![index])
+        .attach(AdHoc::on_launch("Shutdown", move |cargo| {
+            let tx = tx.lock().unwrap().take().unwrap();
+            let _ = tx.send(cargo.shutdown());
+        }));
+
+    let server = rocket::tokio::spawn(rocket.launch());
+    let shutdown = rx.await.unwrap();
+
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert!(metadata.file_type().is_socket());
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+    let mut stream = UnixStream::connect(&path).await.unwrap();
+    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("Hello, socket!"));
+
+    shutdown.shutdown();
+    server.await.unwrap().unwrap();
+    assert!(!path.exists());
+}
+
+#[rocket::async_test]
+async fn refuses_to_replace_regular_files() {
+    let path = socket_path("file");
+    std::fs::write(&path, "not a socket").unwrap();
+
+    let config = Config::build(Environment::Development)
+        .address(format!("unix:{}", path.display()))
+        .unwrap();
+
+    match rocket::custom(config).launch().await {
+        Err(Error::Launch(e)) => assert!(matches!(e.kind(), LaunchErrorKind::Bind(_))),
+        _ => panic!("launched over a regular file"),
+    }
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[rocket::async_test]
+async fn refuses_to_replace_live_sockets() {
+    let path = socket_path("live");
+    let _ = std::fs::remove_file(&path);
+    let live = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+    let config = Config::build(Environment::Development)
+        .address(format!("unix:{}", path.display()))
+        .unix(UnixConfig { mode: Some(0o600), cleanup: true })
+        .unwrap();
+
+    match rocket::custom(config).launch().await {
+        Err(Error::Launch(e)) => assert!(matches!(e.kind(), LaunchErrorKind::Bind(_))),
+        _ => panic!("launched over a live socket"),
+    }
+
+    assert!(std::os::unix::net::UnixStream::connect(&path).is_ok());
+    drop(live);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -18,4 +18,29 @@ openssl x509 -req -sha256 -extfile <(printf "subjectAltName=${ALT}") -days 3650
     -CA ca_cert.pem -CAkey ca_key.pem -CAcreateserial \
     -in server.csr -out cert.pem
 
+# The `native-tls` backend requires a PKCS#8 private key.
+openssl pkcs8 -topk8 -nocrypt -in key.pem -out key_pkcs8.pem
+
 rm ca_cert.srl server.csr
+
+# Client certificates for mutual TLS, one of which is revoked by `crl.pem`.
+CRL_DIR=$(mktemp -d)
+touch "${CRL_DIR}/index.txt"
+printf "[ca]\ndefault_ca=crl\n[crl]\ndatabase=${CRL_DIR}/index.txt\ndefault_md=sha256\n" \
+    > "${CRL_DIR}/ca.cnf"
+
+for CLIENT in client revoked; do
+    openssl req -newkey rsa:2048 -nodes -sha256 -keyout ${CLIENT}_key.pem \
+        -subj "/C=US/ST=CA/O=Rocket/CN=${CLIENT}" -out ${CLIENT}.csr
+    openssl x509 -req -sha256 -extfile <(printf "extendedKeyUsage=clientAuth") -days 3650 \
+        -CA ca_cert.pem -CAkey ca_key.pem -CAcreateserial \
+        -in ${CLIENT}.csr -out ${CLIENT}_cert.pem
+    rm ${CLIENT}.csr
+done
+
+openssl ca -config "${CRL_DIR}/ca.cnf" -keyfile ca_key.pem -cert ca_cert.pem \
+    -revoke revoked_cert.pem
+openssl ca -config "${CRL_DIR}/ca.cnf" -keyfile ca_key.pem -cert ca_cert.pem \
+    -gencrl -crldays 3650 -out crl.pem
+
+rm -r ca_cert.srl "${CRL_DIR}"
@@ -81,7 +81,27 @@ if [ "$1" = "--contrib" ]; then
     tera_templates
     handlebars_templates
     serve
+    serve_zip
+    serve_s3
     helmet
+    cors
+    request_id
+    access_log
+    prometheus
+    opentelemetry
+    server_timing
+    maintenance
+    sessions
+    auth
+    oauth
+    ratelimit
+    scheduler
+    graphql
+    grpc_web
+    openapi
+    health
+    feature_flags
+    i18n
     diesel_postgres_pool
     diesel_sqlite_pool
     diesel_mysql_pool
@@ -89,6 +109,13 @@ if [ "$1" = "--contrib" ]; then
     mysql_pool
     sqlite_pool
     memcache_pool
+    sqlx_postgres_pool
+    sqlx_mysql_pool
+    sqlx_sqlite_pool
+    deadpool_postgres_pool
+    mongodb_pool
+    refinery_migrations
+    redis_pool
     brotli_compression
     gzip_compression
   )
@@ -108,6 +135,9 @@ elif [ "$1" = "--core" ]; then
   FEATURES=(
     secrets
     tls
+    native-tls
+    tls-acme
+    tower
   )
 
   pushd "${CORE_LIB_ROOT}" > /dev/null 2>&1